@@ -0,0 +1,17 @@
+-- ============================================================================
+-- Filters Data Type Migration
+-- Version: 006
+-- Description: Accept 'filters' as a sync data type
+-- ============================================================================
+
+ALTER TABLE sync_data DROP CONSTRAINT IF EXISTS sync_data_data_type_check;
+ALTER TABLE sync_data ADD CONSTRAINT sync_data_data_type_check
+    CHECK (data_type IN ('accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters'));
+
+ALTER TABLE sync_data_changes DROP CONSTRAINT IF EXISTS sync_data_changes_data_type_check;
+ALTER TABLE sync_data_changes ADD CONSTRAINT sync_data_changes_data_type_check
+    CHECK (data_type IN ('accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters'));
+
+ALTER TABLE deleted_records DROP CONSTRAINT IF EXISTS deleted_records_data_type_check;
+ALTER TABLE deleted_records ADD CONSTRAINT deleted_records_data_type_check
+    CHECK (data_type IN ('accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters'));
@@ -53,7 +53,7 @@ CREATE INDEX idx_devices_active ON devices(user_id, is_active) WHERE is_active =
 CREATE TABLE IF NOT EXISTS sync_data (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-    data_type VARCHAR(50) NOT NULL CHECK (data_type IN ('accounts', 'contacts', 'preferences', 'signatures')),
+    data_type VARCHAR(50) NOT NULL CHECK (data_type IN ('accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes')),
 
     -- Encrypted payload (E2E encrypted, server cannot decrypt)
     encrypted_blob BYTEA NOT NULL,
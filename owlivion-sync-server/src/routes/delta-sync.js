@@ -31,7 +31,7 @@ router.post('/:data_type/delta', deltaSyncUploadLimiter, deltaSyncUploadValidati
     const userId = req.user.userId;
 
     // Validate data_type
-    const validTypes = ['accounts', 'contacts', 'preferences', 'signatures'];
+    const validTypes = ['accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes'];
     if (!validTypes.includes(data_type)) {
       return res.status(400).json({
         success: false,
@@ -262,7 +262,7 @@ router.get('/:data_type/delta', deltaSyncDownloadLimiter, deltaSyncDownloadValid
     const deviceId = req.user.deviceId;
 
     // Validate data_type
-    const validTypes = ['accounts', 'contacts', 'preferences', 'signatures'];
+    const validTypes = ['accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes'];
     if (!validTypes.includes(data_type)) {
       return res.status(400).json({
         success: false,
@@ -397,7 +397,7 @@ router.get('/:data_type/deleted', deltaSyncDownloadLimiter, deltaSyncDownloadVal
     const userId = req.user.userId;
 
     // Validate data_type
-    const validTypes = ['accounts', 'contacts', 'preferences', 'signatures'];
+    const validTypes = ['accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes'];
     if (!validTypes.includes(data_type)) {
       return res.status(400).json({
         success: false,
@@ -217,7 +217,7 @@ router.get('/status', async (req, res, next) => {
 
     // 2. Return version and last_sync_at for each data type
     const syncStatus = {};
-    const allDataTypes = ['accounts', 'contacts', 'preferences', 'signatures'];
+    const allDataTypes = ['accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes'];
 
     // Initialize all data types with null
     allDataTypes.forEach((type) => {
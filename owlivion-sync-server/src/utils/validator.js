@@ -98,7 +98,7 @@ export const refreshTokenValidation = [
  */
 export const syncUploadValidation = [
   body('data_type')
-    .isIn(['accounts', 'contacts', 'preferences', 'signatures'])
+    .isIn(['accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes'])
     .withMessage('Invalid data type'),
 
   body('encrypted_blob')
@@ -138,7 +138,7 @@ export const syncUploadValidation = [
  */
 export const syncDownloadValidation = [
   query('data_type')
-    .isIn(['accounts', 'contacts', 'preferences', 'signatures'])
+    .isIn(['accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes'])
     .withMessage('Invalid data type'),
 
   validate,
@@ -160,7 +160,7 @@ export const deviceDeleteValidation = [
  */
 export const deltaSyncUploadValidation = [
   param('data_type')
-    .isIn(['accounts', 'contacts', 'preferences', 'signatures'])
+    .isIn(['accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes'])
     .withMessage('Invalid data type'),
 
   body('changes')
@@ -184,7 +184,7 @@ export const deltaSyncUploadValidation = [
  */
 export const deltaSyncDownloadValidation = [
   param('data_type')
-    .isIn(['accounts', 'contacts', 'preferences', 'signatures'])
+    .isIn(['accounts', 'contacts', 'preferences', 'signatures', 'templates', 'filters', 'notes'])
     .withMessage('Invalid data type'),
 
   query('since')
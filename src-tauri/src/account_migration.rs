@@ -0,0 +1,406 @@
+//! Account Migration
+//!
+//! Copies folders from one account to another (moving to a new provider):
+//! each message is fetched with its flags and arrival time and uploaded to the
+//! mapped target folder with APPEND, so it keeps its read state and original
+//! date. Messages whose Message-ID already exists in the target folder are
+//! skipped, which also makes running a migration again safe. Progress is
+//! stored per folder pair; an interrupted copy resumes after the last UID it
+//! processed.
+
+use crate::db::{Database, DbResult};
+use crate::mail::{AppendOutcome, AsyncImapClient, MailError, MailResult, RawMessage};
+use async_trait::async_trait;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Tauri event name for migration progress
+pub const MIGRATION_PROGRESS_EVENT: &str = "migration://progress";
+
+/// Message-IDs looked up per batch
+const BATCH_SIZE: usize = 50;
+
+/// Source folder and the target folder its messages are copied to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct FolderMapping {
+    pub source: String,
+    pub target: String,
+}
+
+/// Progress of one folder pair (payload of `migration://progress`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationState {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub source_account_id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub target_account_id: i64,
+    pub source_folder: String,
+    pub target_folder: String,
+    /// Messages in the source folder
+    pub total: u32,
+    /// Appended to the target folder
+    pub copied: u32,
+    /// Already in the target folder (same Message-ID)
+    pub skipped: u32,
+    /// Refused by the target server (over its APPENDLIMIT)
+    pub failed: u32,
+    /// Highest source UID processed; the copy resumes after it
+    pub last_uid: u32,
+    pub done: bool,
+}
+
+/// Account messages are copied from (implemented by the IMAP client)
+#[async_trait]
+pub trait MigrationSource: Send {
+    async fn list_uids(&mut self, folder: &str) -> MailResult<Vec<u32>>;
+    /// Message-ID of each UID that has one
+    async fn message_ids(&mut self, folder: &str, uids: &[u32]) -> MailResult<Vec<(u32, String)>>;
+    async fn fetch_message(&mut self, folder: &str, uid: u32) -> MailResult<RawMessage>;
+}
+
+/// Account messages are copied to (implemented by the IMAP client)
+#[async_trait]
+pub trait MigrationTarget: Send {
+    async fn contains(&mut self, folder: &str, message_id: &str) -> MailResult<bool>;
+    async fn append_message(&mut self, folder: &str, message: &RawMessage) -> MailResult<AppendOutcome>;
+}
+
+#[async_trait]
+impl MigrationSource for AsyncImapClient {
+    async fn list_uids(&mut self, folder: &str) -> MailResult<Vec<u32>> {
+        self.search_since(folder, None).await
+    }
+
+    async fn message_ids(&mut self, folder: &str, uids: &[u32]) -> MailResult<Vec<(u32, String)>> {
+        Ok(self
+            .fetch_emails_by_uids(folder, uids)
+            .await?
+            .into_iter()
+            .filter_map(|summary| Some((summary.uid, summary.message_id?)))
+            .collect())
+    }
+
+    async fn fetch_message(&mut self, folder: &str, uid: u32) -> MailResult<RawMessage> {
+        self.fetch_raw_message(folder, uid).await
+    }
+}
+
+#[async_trait]
+impl MigrationTarget for AsyncImapClient {
+    async fn contains(&mut self, folder: &str, message_id: &str) -> MailResult<bool> {
+        Ok(self.find_by_message_id(folder, message_id).await?.is_some())
+    }
+
+    async fn append_message(&mut self, folder: &str, message: &RawMessage) -> MailResult<AppendOutcome> {
+        AsyncImapClient::append_message(self, folder, message).await
+    }
+}
+
+/// Stored progress of all folder pairs between two accounts
+pub fn list_states(db: &Database, source_id: i64, target_id: i64) -> DbResult<Vec<MigrationState>> {
+    db.query(
+        "SELECT source_account_id, target_account_id, source_folder, target_folder,
+                total, copied, skipped, failed, last_uid, done
+         FROM account_migrations WHERE source_account_id = ?1 AND target_account_id = ?2
+         ORDER BY source_folder, target_folder",
+        params![source_id, target_id],
+        row_to_state,
+    )
+}
+
+fn load_state(db: &Database, source_id: i64, target_id: i64, mapping: &FolderMapping) -> DbResult<Option<MigrationState>> {
+    let mut states = db.query(
+        "SELECT source_account_id, target_account_id, source_folder, target_folder,
+                total, copied, skipped, failed, last_uid, done
+         FROM account_migrations
+         WHERE source_account_id = ?1 AND target_account_id = ?2 AND source_folder = ?3 AND target_folder = ?4",
+        params![source_id, target_id, mapping.source, mapping.target],
+        row_to_state,
+    )?;
+    Ok(states.pop())
+}
+
+fn row_to_state(row: &rusqlite::Row<'_>) -> rusqlite::Result<MigrationState> {
+    Ok(MigrationState {
+        source_account_id: row.get(0)?,
+        target_account_id: row.get(1)?,
+        source_folder: row.get(2)?,
+        target_folder: row.get(3)?,
+        total: row.get(4)?,
+        copied: row.get(5)?,
+        skipped: row.get(6)?,
+        failed: row.get(7)?,
+        last_uid: row.get(8)?,
+        done: row.get(9)?,
+    })
+}
+
+fn save_state(db: &Database, state: &MigrationState) -> DbResult<()> {
+    db.execute(
+        r#"
+        INSERT INTO account_migrations
+            (source_account_id, target_account_id, source_folder, target_folder,
+             total, copied, skipped, failed, last_uid, done, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))
+        ON CONFLICT(source_account_id, target_account_id, source_folder, target_folder) DO UPDATE SET
+            total = excluded.total,
+            copied = excluded.copied,
+            skipped = excluded.skipped,
+            failed = excluded.failed,
+            last_uid = excluded.last_uid,
+            done = excluded.done,
+            updated_at = excluded.updated_at
+        "#,
+        params![
+            state.source_account_id,
+            state.target_account_id,
+            state.source_folder,
+            state.target_folder,
+            state.total,
+            state.copied,
+            state.skipped,
+            state.failed,
+            state.last_uid,
+            state.done,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Copy one source folder to its target folder, oldest UIDs first
+/// Stops early (keeping progress) when `should_continue` turns false
+#[allow(clippy::too_many_arguments)]
+pub async fn run<S, T, C, P>(
+    db: &Database,
+    source: &mut S,
+    target: &mut T,
+    source_id: i64,
+    target_id: i64,
+    mapping: &FolderMapping,
+    should_continue: C,
+    on_progress: P,
+) -> Result<MigrationState, String>
+where
+    S: MigrationSource + ?Sized,
+    T: MigrationTarget + ?Sized,
+    C: Fn() -> bool,
+    P: Fn(&MigrationState),
+{
+    let mut uids = source
+        .list_uids(&mapping.source)
+        .await
+        .map_err(|e| format!("Failed to list messages: {}", e))?;
+    uids.sort_unstable();
+
+    let mut state = load_state(db, source_id, target_id, mapping).map_err(|e| e.to_string())?.unwrap_or(MigrationState {
+        source_account_id: source_id,
+        target_account_id: target_id,
+        source_folder: mapping.source.clone(),
+        target_folder: mapping.target.clone(),
+        total: 0,
+        copied: 0,
+        skipped: 0,
+        failed: 0,
+        last_uid: 0,
+        done: false,
+    });
+    let pending: Vec<u32> = uids.iter().copied().filter(|uid| *uid > state.last_uid).collect();
+    state.total = uids.len() as u32;
+    state.done = pending.is_empty();
+    save_state(db, &state).map_err(|e| e.to_string())?;
+    on_progress(&state);
+
+    log::info!(
+        "Migrating {} of account {} to {} of account {}: {} of {} messages left",
+        mapping.source, source_id, mapping.target, target_id, pending.len(), state.total
+    );
+
+    // Duplicates within the source folder, and copies the target has not indexed yet
+    let mut copied_ids: HashSet<String> = HashSet::new();
+    for batch in pending.chunks(BATCH_SIZE) {
+        if !should_continue() {
+            log::info!("Migration of {} suspended after UID {}", mapping.source, state.last_uid);
+            return Ok(state);
+        }
+
+        let message_ids = source
+            .message_ids(&mapping.source, batch)
+            .await
+            .map_err(|e| format!("Failed to read Message-IDs: {}", e))?;
+
+        for uid in batch {
+            let message_id = message_ids.iter().find(|(id_uid, _)| id_uid == uid).map(|(_, id)| id.as_str());
+            let duplicate = match message_id {
+                Some(id) if copied_ids.contains(id) => true,
+                Some(id) => target
+                    .contains(&mapping.target, id)
+                    .await
+                    .map_err(|e| format!("Failed to search the target folder: {}", e))?,
+                None => false,
+            };
+
+            if duplicate {
+                state.skipped += 1;
+            } else {
+                match source.fetch_message(&mapping.source, *uid).await {
+                    Ok(message) => {
+                        let outcome = target
+                            .append_message(&mapping.target, &message)
+                            .await
+                            .map_err(|e| format!("Failed to upload message: {}", e))?;
+                        match outcome {
+                            AppendOutcome::Appended => {
+                                state.copied += 1;
+                                copied_ids.extend(message_id.map(str::to_string));
+                            }
+                            AppendOutcome::TooLarge { size, limit } => {
+                                log::warn!("Migration: UID {} not copied ({} bytes, target accepts {})", uid, size, limit);
+                                state.failed += 1;
+                            }
+                        }
+                    }
+                    // Expunged since the folder was listed
+                    Err(MailError::NotFound(_)) => state.skipped += 1,
+                    Err(e) => return Err(format!("Failed to fetch message: {}", e)),
+                }
+            }
+
+            state.last_uid = *uid;
+            save_state(db, &state).map_err(|e| e.to_string())?;
+        }
+        on_progress(&state);
+    }
+
+    state.done = true;
+    save_state(db, &state).map_err(|e| e.to_string())?;
+    on_progress(&state);
+
+    Ok(state)
+}
+
+/// Account pairs with a migration in progress (prevents running the same one twice)
+#[derive(Default)]
+pub struct RunningMigrations {
+    running: Mutex<HashSet<(i64, i64)>>,
+}
+
+impl RunningMigrations {
+    /// Claim an account pair; false when a migration between them is already running
+    pub fn try_begin(&self, source_id: i64, target_id: i64) -> bool {
+        self.running
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert((source_id, target_id))
+    }
+
+    pub fn finish(&self, source_id: i64, target_id: i64) {
+        self.running
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&(source_id, target_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_new_account, NewAccount};
+    use std::cell::Cell;
+
+    /// Source folder: UID n has Message-ID <n@test>, except UID 3 which has none
+    /// and UID 4 which repeats <2@test>
+    struct FakeSource {
+        uids: Vec<u32>,
+    }
+
+    #[async_trait]
+    impl MigrationSource for FakeSource {
+        async fn list_uids(&mut self, _folder: &str) -> MailResult<Vec<u32>> {
+            Ok(self.uids.clone())
+        }
+
+        async fn message_ids(&mut self, _folder: &str, uids: &[u32]) -> MailResult<Vec<(u32, String)>> {
+            Ok(uids
+                .iter()
+                .filter(|uid| **uid != 3)
+                .map(|uid| (*uid, format!("<{}@test>", if *uid == 4 { 2 } else { *uid })))
+                .collect())
+        }
+
+        async fn fetch_message(&mut self, _folder: &str, uid: u32) -> MailResult<RawMessage> {
+            Ok(RawMessage {
+                raw: format!("Subject: {}\r\n\r\nBody", uid).into_bytes(),
+                flags: vec!["\\Seen".to_string()],
+                internal_date: None,
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeTarget {
+        existing: HashSet<String>,
+        appended: Vec<String>,
+    }
+
+    #[async_trait]
+    impl MigrationTarget for FakeTarget {
+        async fn contains(&mut self, _folder: &str, message_id: &str) -> MailResult<bool> {
+            Ok(self.existing.contains(message_id))
+        }
+
+        async fn append_message(&mut self, _folder: &str, message: &RawMessage) -> MailResult<AppendOutcome> {
+            assert_eq!(message.flags, ["\\Seen"]);
+            self.appended.push(String::from_utf8_lossy(&message.raw).lines().next().unwrap_or_default().to_string());
+            Ok(AppendOutcome::Appended)
+        }
+    }
+
+    fn account(db: &Database, email: &str) -> i64 {
+        db.add_account(&NewAccount {
+            is_default: false,
+            ..test_new_account(email)
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_copies_dedupes_and_resumes() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let (old, new) = (account(&db, "me@old.test"), account(&db, "me@new.test"));
+        let mapping = FolderMapping { source: "INBOX".to_string(), target: "Imported".to_string() };
+        let mut source = FakeSource { uids: (1..=60).collect() };
+        let mut target = FakeTarget { existing: ["<1@test>".to_string()].into_iter().collect(), ..Default::default() };
+
+        // Stop after the first batch
+        let budget = Cell::new(1u32);
+        let state = run(&db, &mut source, &mut target, old, new, &mapping, || {
+            let left = budget.get();
+            budget.set(left.saturating_sub(1));
+            left > 0
+        }, |_| {})
+        .await
+        .unwrap();
+        assert!(!state.done);
+        assert_eq!(state.last_uid, 50);
+        // <1@test> exists already, UID 4 repeats UID 2; UID 3 has no Message-ID and is copied
+        assert_eq!((state.copied, state.skipped), (48, 2));
+        assert_eq!(&target.appended[..2], ["Subject: 2", "Subject: 3"]);
+
+        let state = run(&db, &mut source, &mut target, old, new, &mapping, || true, |_| {}).await.unwrap();
+        assert!(state.done);
+        assert_eq!((state.total, state.copied, state.skipped, state.failed), (60, 58, 2, 0));
+        assert_eq!(target.appended.len(), 58);
+        assert_eq!(list_states(&db, old, new).unwrap(), vec![state]);
+
+        // New mail in the source is picked up by running again
+        source.uids.push(61);
+        let state = run(&db, &mut source, &mut target, old, new, &mapping, || true, |_| {}).await.unwrap();
+        assert_eq!((state.total, state.copied, state.last_uid), (61, 59, 61));
+    }
+}
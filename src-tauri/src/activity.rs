@@ -0,0 +1,258 @@
+//! Activity Log
+//!
+//! Local audit trail of destructive actions: deleted messages, filter deletes
+//! and moves, removed local folders and accounts. Actions that can be
+//! reverted carry an undo payload, which `undo` applies once. A delete is
+//! undone by clearing its deleted flag and cancelling the server delete if it
+//! is still queued; once the server has run it, the message can only be
+//! restored from the Trash folder there.
+
+use crate::db::{Database, DbError, DbResult};
+use crate::events::{EmailChange, EmailsChanged, StoreEvents};
+use crate::pending_ops;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Entries kept; older ones are pruned when new ones are recorded
+const MAX_ENTRIES: i64 = 1000;
+
+/// Kind of destructive action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityAction {
+    EmailDelete,
+    FilterDelete,
+    FilterMove,
+    LocalFolderDelete,
+    AccountRemove,
+}
+
+impl ActivityAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::EmailDelete => "email_delete",
+            Self::FilterDelete => "filter_delete",
+            Self::FilterMove => "filter_move",
+            Self::LocalFolderDelete => "local_folder_delete",
+            Self::AccountRemove => "account_remove",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [
+            Self::EmailDelete,
+            Self::FilterDelete,
+            Self::FilterMove,
+            Self::LocalFolderDelete,
+            Self::AccountRemove,
+        ]
+        .into_iter()
+        .find(|action| action.as_str() == value)
+    }
+}
+
+/// How to revert an action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum UndoPayload {
+    /// Clear the deleted flag of soft-deleted messages and cancel the server
+    /// delete when it is still queued
+    Restore {
+        folder: Option<String>,
+        uids: Vec<u32>,
+        email_ids: Vec<i64>,
+        pending_op_id: Option<String>,
+    },
+    /// Put a message back into the folder a filter moved it out of
+    MoveBack { email_id: i64, folder_id: i64 },
+}
+
+/// Logged action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEntry {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub account_id: Option<i64>,
+    pub action: ActivityAction,
+    pub summary: String,
+    /// Has an undo payload that was not applied yet
+    pub can_undo: bool,
+    pub undone_at: Option<String>,
+    pub created_at: String,
+}
+
+/// Log an action; failures are the caller's to log, never to fail the action
+pub fn record(
+    db: &Database,
+    account_id: Option<i64>,
+    action: ActivityAction,
+    summary: &str,
+    undo: Option<&UndoPayload>,
+) -> DbResult<i64> {
+    let undo = undo
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| DbError::Serialization(e.to_string()))?;
+    let id = db.execute_insert(
+        "INSERT INTO activity_log (account_id, action, summary, undo) VALUES (?1, ?2, ?3, ?4)",
+        params![account_id, action.as_str(), summary, undo],
+    )?;
+    db.execute("DELETE FROM activity_log WHERE id <= ?1", [id - MAX_ENTRIES])?;
+    Ok(id)
+}
+
+/// Most recent entries first
+pub fn list(db: &Database, limit: u32) -> DbResult<Vec<ActivityEntry>> {
+    db.query(
+        "SELECT id, account_id, action, summary, undo IS NOT NULL, undone_at, created_at
+         FROM activity_log ORDER BY id DESC LIMIT ?1",
+        [limit],
+        |row| {
+            let action: String = row.get(2)?;
+            let action = ActivityAction::parse(&action).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, format!("Unknown action {}", action).into())
+            })?;
+            let undone_at: Option<String> = row.get(5)?;
+            Ok(ActivityEntry {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                action,
+                summary: row.get(3)?,
+                can_undo: row.get::<_, bool>(4)? && undone_at.is_none(),
+                undone_at,
+                created_at: row.get(6)?,
+            })
+        },
+    )
+}
+
+/// Revert a logged action
+pub fn undo(db: &Database, events: &StoreEvents, id: i64) -> DbResult<()> {
+    let (account_id, undo, undone_at) = db
+        .query_row(
+            "SELECT account_id, undo, undone_at FROM activity_log WHERE id = ?1",
+            [id],
+            |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, Option<String>>(2)?)),
+        )
+        .map_err(|e| match e {
+            DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows) => DbError::NotFound(format!("Activity {}", id)),
+            other => other,
+        })?;
+
+    if undone_at.is_some() {
+        return Err(DbError::Constraint("This action was already undone".to_string()));
+    }
+    let undo: UndoPayload = match undo {
+        Some(json) => serde_json::from_str(&json).map_err(|e| DbError::Serialization(e.to_string()))?,
+        None => return Err(DbError::Constraint("This action cannot be undone".to_string())),
+    };
+
+    match undo {
+        UndoPayload::Restore { folder, uids, email_ids, pending_op_id } => {
+            if let Some(op_id) = pending_op_id {
+                if !pending_ops::cancel(db, &op_id)? {
+                    return Err(DbError::Constraint(
+                        "The server already deleted the message; restore it from Trash".to_string(),
+                    ));
+                }
+            }
+            for email_id in &email_ids {
+                db.update_email_flags(*email_id, None, None, Some(false))?;
+            }
+            events.notify(EmailsChanged {
+                account_id: account_id.unwrap_or_default(),
+                folder,
+                email_ids,
+                uids,
+                change: EmailChange::Restored,
+            });
+        }
+        UndoPayload::MoveBack { email_id, folder_id } => {
+            let email = db.get_email(email_id)?;
+            let folder = db.get_folder_by_id(folder_id)?;
+            db.execute("UPDATE emails SET folder_id = ?1 WHERE id = ?2", params![folder_id, email_id])?;
+            events.notify(EmailsChanged {
+                account_id: email.account_id,
+                folder: None,
+                email_ids: vec![email_id],
+                uids: vec![email.uid],
+                change: EmailChange::Moved { target: folder.remote_name },
+            });
+        }
+    }
+
+    db.execute(
+        "UPDATE activity_log SET undone_at = ?1 WHERE id = ?2",
+        params![chrono::Utc::now().to_rfc3339(), id],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewFolder};
+
+    fn setup() -> (Database, i64, i64) {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+        (db, account_id, folder_id)
+    }
+
+    #[test]
+    fn test_undo_soft_delete() {
+        let (db, account_id, folder_id) = setup();
+        let email_id = db.upsert_email(&test_email(account_id, folder_id, 1)).unwrap();
+        db.update_email_flags(email_id, None, None, Some(true)).unwrap();
+
+        let events = StoreEvents::new();
+        let restore = UndoPayload::Restore {
+            folder: Some("INBOX".to_string()),
+            uids: vec![1],
+            email_ids: vec![email_id],
+            pending_op_id: None,
+        };
+        let id = record(&db, Some(account_id), ActivityAction::FilterDelete, "Filter deleted", Some(&restore)).unwrap();
+        record(&db, Some(account_id), ActivityAction::AccountRemove, "Removed account", None).unwrap();
+
+        let entries = list(&db, 10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].action, ActivityAction::AccountRemove);
+        assert!(!entries[0].can_undo && entries[1].can_undo);
+        assert!(undo(&db, &events, entries[0].id).is_err());
+
+        undo(&db, &events, id).unwrap();
+        assert!(!db.get_email(email_id).unwrap().is_deleted);
+        assert!(!list(&db, 10).unwrap()[1].can_undo);
+        assert!(undo(&db, &events, id).is_err());
+    }
+
+    #[test]
+    fn test_undo_after_server_delete_fails() {
+        let (db, account_id, _) = setup();
+        let restore = UndoPayload::Restore {
+            folder: Some("INBOX".to_string()),
+            uids: vec![1],
+            email_ids: vec![],
+            pending_op_id: Some("gone".to_string()),
+        };
+        let id = record(&db, Some(account_id), ActivityAction::EmailDelete, "Deleted", Some(&restore)).unwrap();
+        assert!(undo(&db, &StoreEvents::new(), id).is_err());
+        assert!(list(&db, 10).unwrap()[0].can_undo);
+    }
+}
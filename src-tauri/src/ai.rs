@@ -0,0 +1,432 @@
+//! Smart Compose Autocomplete
+//!
+//! Inline sentence completion while composing. The text before the cursor is
+//! continued by the configured provider (Gemini, or any OpenAI-compatible
+//! endpoint such as a local Ollama) and streamed back chunk by chunk as
+//! `ai://completion` events.
+//!
+//! Privacy: the surrounding message (quoted thread, rest of the draft) is only
+//! sent to a remote provider when the account opted in. Providers on this
+//! machine always receive it. Requests are rate limited per account.
+//!
+//! The same provider also answers one-shot prompts (`generate`), e.g. for
+//! thread summaries.
+
+use crate::db::{Database, DbResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tauri event name for streamed completion chunks
+pub const COMPLETION_EVENT: &str = "ai://completion";
+
+/// Settings key holding the provider configuration
+pub(crate) const PROVIDER_SETTING: &str = "ai_completion_provider";
+
+const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+const DEFAULT_GEMINI_MODEL: &str = "gemini-2.0-flash";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Characters of context and of cursor text sent per request
+const MAX_CONTEXT_CHARS: usize = 4000;
+const MAX_CURSOR_CHARS: usize = 1000;
+
+/// Rate limits per account (completions fire while typing)
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_REQUESTS_PER_MINUTE: usize = 30;
+
+/// Completion provider kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "AIProviderKind"))]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    Gemini,
+    /// `/chat/completions` API (OpenAI, Ollama, LM Studio, ...)
+    OpenaiCompatible,
+}
+
+/// Provider configuration stored in settings table (API key encrypted)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderConfig {
+    pub kind: ProviderKind,
+    /// API base URL; defaults to Gemini's public endpoint
+    pub base_url: Option<String>,
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_encrypted: Option<String>,
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            kind: ProviderKind::Gemini,
+            base_url: None,
+            model: None,
+            api_key_encrypted: None,
+        }
+    }
+}
+
+/// Provider configuration as shown to the frontend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "AIProviderInfo"))]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub kind: ProviderKind,
+    pub base_url: String,
+    pub model: String,
+    pub has_api_key: bool,
+    /// False when the provider runs on this machine
+    pub is_remote: bool,
+}
+
+impl ProviderConfig {
+    pub fn base_url(&self) -> String {
+        self.base_url
+            .as_deref()
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| GEMINI_BASE_URL.to_string())
+    }
+
+    pub fn model(&self) -> String {
+        self.model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+            .unwrap_or(DEFAULT_GEMINI_MODEL)
+            .to_string()
+    }
+
+    /// Whether requests leave this machine
+    pub fn is_remote(&self) -> bool {
+        url::Url::parse(&self.base_url())
+            .ok()
+            .and_then(|url| url.host_str().map(|host| !crate::mail::config::is_loopback_host(host)))
+            .unwrap_or(true)
+    }
+
+    /// HTTPS only, except for providers on this machine
+    pub fn validate(&self) -> Result<(), String> {
+        let url = url::Url::parse(&self.base_url()).map_err(|e| format!("Invalid provider URL: {}", e))?;
+        match url.scheme() {
+            "https" => Ok(()),
+            "http" if !self.is_remote() => Ok(()),
+            _ => Err("Provider URL must use https (http is only allowed to localhost)".to_string()),
+        }
+    }
+
+    pub fn info(&self) -> ProviderInfo {
+        ProviderInfo {
+            kind: self.kind,
+            base_url: self.base_url(),
+            model: self.model(),
+            has_api_key: self.api_key_encrypted.is_some(),
+            is_remote: self.is_remote(),
+        }
+    }
+}
+
+pub fn load_provider(db: &Database) -> DbResult<ProviderConfig> {
+    Ok(db.get_setting(PROVIDER_SETTING)?.unwrap_or_default())
+}
+
+pub fn save_provider(db: &Database, config: &ProviderConfig) -> DbResult<()> {
+    db.set_setting(PROVIDER_SETTING, config)
+}
+
+fn context_setting(account_id: i64) -> String {
+    format!("ai_share_context_{}", account_id)
+}
+
+/// Whether the account allows message context to be sent to remote providers (default: no)
+pub fn context_sharing(db: &Database, account_id: i64) -> DbResult<bool> {
+    Ok(db.get_setting(&context_setting(account_id))?.unwrap_or(false))
+}
+
+pub fn set_context_sharing(db: &Database, account_id: i64, enabled: bool) -> DbResult<()> {
+    db.set_setting(&context_setting(account_id), &enabled)
+}
+
+/// Streamed completion chunk (payload of `ai://completion`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionChunk {
+    pub request_id: String,
+    pub text: String,
+    pub done: bool,
+}
+
+// ============================================================================
+// Rate limiting
+// ============================================================================
+
+/// Per-account request limiter
+#[derive(Default)]
+pub struct RateLimiter {
+    requests: Mutex<HashMap<i64, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Record a request, or refuse it when the account is over its limits
+    pub fn check(&self, account_id: i64, now: Instant) -> Result<(), String> {
+        let mut requests = self.requests.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let recent = requests.entry(account_id).or_default();
+
+        while recent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= Duration::from_secs(60))
+        {
+            recent.pop_front();
+        }
+        if recent.back().is_some_and(|t| now.duration_since(*t) < MIN_REQUEST_INTERVAL) {
+            return Err("Too many requests. Please wait a moment.".to_string());
+        }
+        if recent.len() >= MAX_REQUESTS_PER_MINUTE {
+            return Err("Rate limit exceeded. Please wait a minute.".to_string());
+        }
+
+        recent.push_back(now);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Completion
+// ============================================================================
+
+/// Last `max` characters of `text`
+fn tail(text: &str, max: usize) -> &str {
+    let count = text.chars().count();
+    match text.char_indices().nth(count.saturating_sub(max)) {
+        Some((index, _)) if count > max => &text[index..],
+        _ => text,
+    }
+}
+
+/// Prompt for the provider; `context` is omitted unless it may be shared
+pub fn build_prompt(context: Option<&str>, cursor_text: &str) -> String {
+    let mut prompt = String::from(
+        "You are an email autocomplete. Continue the user's text from exactly where it stops. \
+         Reply with the continuation only (at most one sentence), in the same language and tone, \
+         without repeating the existing text. Reply with nothing if no continuation fits.\n",
+    );
+    if let Some(context) = context.map(str::trim).filter(|context| !context.is_empty()) {
+        prompt.push_str("\nMessage context:\n");
+        prompt.push_str(tail(context, MAX_CONTEXT_CHARS));
+        prompt.push('\n');
+    }
+    prompt.push_str("\nText to continue:\n");
+    prompt.push_str(tail(cursor_text, MAX_CURSOR_CHARS));
+    prompt
+}
+
+/// Text of one streamed event (`data:` payload of the provider's SSE stream)
+fn chunk_text(kind: ProviderKind, data: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(data).ok()?;
+    let text = match kind {
+        ProviderKind::Gemini => value.pointer("/candidates/0/content/parts/0/text"),
+        ProviderKind::OpenaiCompatible => value.pointer("/choices/0/delta/content"),
+    };
+    text.and_then(|text| text.as_str()).map(str::to_string)
+}
+
+/// Request to the provider's generate endpoint (SSE stream when `stream`)
+fn provider_request(
+    config: &ProviderConfig,
+    prompt: &str,
+    max_tokens: u32,
+    stream: bool,
+) -> Result<reqwest::RequestBuilder, String> {
+    let api_key = config
+        .api_key_encrypted
+        .as_deref()
+        .map(crate::crypto::decrypt_password)
+        .transpose()?;
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let request = match config.kind {
+        ProviderKind::Gemini => {
+            let api_key = api_key.ok_or("Gemini API key is required. Please set it in Settings > AI.")?;
+            let method = if stream { "streamGenerateContent?alt=sse" } else { "generateContent" };
+            client
+                .post(format!("{}/models/{}:{}", config.base_url(), config.model(), method))
+                .header("x-goog-api-key", api_key)
+                .json(&serde_json::json!({
+                    "contents": [{ "parts": [{ "text": prompt }] }],
+                    "generationConfig": { "temperature": 0.3, "maxOutputTokens": max_tokens },
+                }))
+        }
+        ProviderKind::OpenaiCompatible => {
+            let request = client.post(format!("{}/chat/completions", config.base_url())).json(&serde_json::json!({
+                "model": config.model(),
+                "messages": [{ "role": "user", "content": prompt }],
+                "temperature": 0.3,
+                "max_tokens": max_tokens,
+                "stream": stream,
+            }));
+            match api_key {
+                Some(key) => request.bearer_auth(key),
+                None => request,
+            }
+        }
+    };
+    Ok(request)
+}
+
+/// Run `prompt` without streaming and return the generated text
+/// Callers decide what the prompt may contain (see `context_sharing`).
+pub async fn generate(config: &ProviderConfig, prompt: &str, max_tokens: u32) -> Result<String, String> {
+    config.validate()?;
+
+    let response = provider_request(config, prompt, max_tokens, false)?
+        .send()
+        .await
+        .map_err(|e| format!("AI request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("AI provider returned HTTP {}", response.status()));
+    }
+
+    let value: serde_json::Value = response.json().await.map_err(|e| format!("Invalid AI response: {}", e))?;
+    let text = match config.kind {
+        ProviderKind::Gemini => value.pointer("/candidates/0/content/parts/0/text"),
+        ProviderKind::OpenaiCompatible => value.pointer("/choices/0/message/content"),
+    };
+    text.and_then(|text| text.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| "AI provider returned no text".to_string())
+}
+
+/// Stream a completion, calling `on_chunk` for every piece of text
+/// Returns the full completion.
+pub async fn complete<F>(
+    config: &ProviderConfig,
+    share_context: bool,
+    context: &str,
+    cursor_text: &str,
+    mut on_chunk: F,
+) -> Result<String, String>
+where
+    F: FnMut(&str),
+{
+    config.validate()?;
+    if cursor_text.trim().is_empty() {
+        return Ok(String::new());
+    }
+
+    let context = (share_context || !config.is_remote()).then_some(context);
+    let prompt = build_prompt(context, cursor_text);
+    let request = provider_request(config, &prompt, 48, true)?;
+
+    let mut response = request.send().await.map_err(|e| format!("Completion request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Completion provider returned HTTP {}", response.status()));
+    }
+
+    let mut completion = String::new();
+    let mut pending = Vec::new();
+    while let Some(bytes) = response.chunk().await.map_err(|e| format!("Completion stream failed: {}", e))? {
+        pending.extend_from_slice(&bytes);
+        while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else { continue };
+            if let Some(text) = chunk_text(config.kind, data.trim()).filter(|text| !text.is_empty()) {
+                on_chunk(&text);
+                completion.push_str(&text);
+            }
+        }
+    }
+
+    Ok(completion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_and_prompt_privacy() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        assert!(limiter.check(1, start).is_ok());
+        assert!(limiter.check(1, start + Duration::from_millis(100)).is_err());
+        assert!(limiter.check(2, start + Duration::from_millis(100)).is_ok());
+
+        let mut now = start;
+        for _ in 1..MAX_REQUESTS_PER_MINUTE {
+            now += MIN_REQUEST_INTERVAL;
+            assert!(limiter.check(1, now).is_ok());
+        }
+        assert!(limiter.check(1, now + MIN_REQUEST_INTERVAL).is_err());
+        assert!(limiter.check(1, start + Duration::from_secs(75)).is_ok());
+
+        let prompt = build_prompt(None, "Thanks for the update, I will");
+        assert!(prompt.ends_with("Thanks for the update, I will"));
+        assert!(!prompt.contains("Message context"));
+        assert!(build_prompt(Some("Quoted thread"), "Hi").contains("Quoted thread"));
+        assert_eq!(tail("çok güzel", 5), "güzel");
+
+        let gemini = ProviderConfig::default();
+        assert!(gemini.is_remote() && gemini.validate().is_ok());
+        let local = ProviderConfig {
+            kind: ProviderKind::OpenaiCompatible,
+            base_url: Some("http://localhost:11434/v1".to_string()),
+            ..Default::default()
+        };
+        assert!(!local.is_remote() && local.validate().is_ok());
+        let plain_remote = ProviderConfig { base_url: Some("http://ai.example.com".to_string()), ..local.clone() };
+        assert!(plain_remote.validate().is_err());
+
+        let db = Database::in_memory().expect("Failed to create test DB");
+        assert!(!context_sharing(&db, 1).unwrap());
+        set_context_sharing(&db, 1, true).unwrap();
+        assert!(context_sharing(&db, 1).unwrap());
+        assert!(!context_sharing(&db, 2).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_complete_streams_chunks() {
+        let mut server = mockito::Server::new_async().await;
+        let sse = [
+            r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#,
+            r#"data: {"choices":[{"delta":{"content":" get back"}}]}"#,
+            r#"data: {"choices":[{"delta":{"content":" to you tomorrow."}}]}"#,
+            "data: [DONE]",
+        ]
+        .join("\n\n");
+        let endpoint = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("Quoted thread".to_string()))
+            .with_header("content-type", "text/event-stream")
+            .with_body(sse)
+            .create_async()
+            .await;
+
+        let config = ProviderConfig {
+            kind: ProviderKind::OpenaiCompatible,
+            base_url: Some(format!("{}/v1", server.url())),
+            model: Some("llama3".to_string()),
+            api_key_encrypted: None,
+        };
+
+        // Local provider: context is sent even without opting in
+        let mut chunks = Vec::new();
+        let completion = complete(&config, false, "Quoted thread", "Thanks, I will", |chunk| chunks.push(chunk.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(completion, " get back to you tomorrow.");
+        assert_eq!(chunks.len(), 2);
+        endpoint.assert_async().await;
+
+        assert_eq!(complete(&config, false, "", "   ", |_| {}).await.unwrap(), "");
+        let gemini = ProviderConfig::default();
+        assert!(complete(&gemini, false, "", "Hello", |_| {}).await.is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Plus-Address Aliases
+//!
+//! Subaddressing (RFC 5233): mail for `user+shop@example.com` is delivered
+//! to `user@example.com`. Handing every shop or newsletter its own alias
+//! shows who passed an address on. Aliases generated here keep the label
+//! they were handed out under. The alias a message came in through is read
+//! from its recipients, so aliases made up elsewhere are recognized too, and
+//! each alias is counted with how much of its mail ended up in Junk.
+
+use crate::db::{Database, DbError, DbResult};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Longest tag accepted after the '+'
+const MAX_TAG_LEN: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct PlusAlias {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    /// Part after the '+', lowercase
+    pub tag: String,
+    pub label: String,
+    pub address: String,
+    pub created_at: String,
+}
+
+/// Mail received through one alias
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AliasStats {
+    pub tag: String,
+    pub address: String,
+    /// Label given when the alias was generated (`None` if made up elsewhere)
+    pub label: Option<String>,
+    pub received: u32,
+    /// Messages in the Junk folder
+    pub junk: u32,
+    pub last_received: Option<String>,
+}
+
+/// Tag for a label: lowercase, spaces become '-', only letters, digits, '-', '_' and '.'
+pub fn tag_for(label: &str) -> Result<String, String> {
+    let tag = label.trim().to_lowercase().split_whitespace().collect::<Vec<_>>().join("-");
+    if tag.is_empty() || tag.len() > MAX_TAG_LEN {
+        return Err(format!("Alias label must be 1 to {} characters", MAX_TAG_LEN));
+    }
+    if let Some(c) = tag.chars().find(|c| !(c.is_ascii_alphanumeric() || "-_.".contains(*c))) {
+        return Err(format!("Alias labels cannot contain '{}'", c));
+    }
+    if tag.starts_with('.') || tag.ends_with('.') || tag.contains("..") {
+        return Err("Alias labels cannot start or end with '.' or contain '..'".to_string());
+    }
+    Ok(tag)
+}
+
+/// `user+tag@domain` for an account address (`None` if it is not an address)
+pub fn address_for(account_email: &str, tag: &str) -> Option<String> {
+    let (local, domain) = account_email.rsplit_once('@')?;
+    let user = local.split_once('+').map_or(local, |(user, _)| user);
+    Some(format!("{}+{}@{}", user, tag, domain))
+}
+
+/// Tag of an address if it is a plus-alias of the account
+pub fn tag_of(account_email: &str, address: &str) -> Option<String> {
+    let (own_local, own_domain) = account_email.rsplit_once('@')?;
+    let (local, domain) = address.trim().rsplit_once('@')?;
+    let (user, tag) = local.split_once('+')?;
+    let matches = domain.eq_ignore_ascii_case(own_domain) && user.eq_ignore_ascii_case(own_local);
+    (matches && !tag.is_empty()).then(|| tag.to_lowercase())
+}
+
+/// Addresses of a JSON recipient list (`["a@b"]`, `["Name <a@b>"]` or `[{"email"}]`)
+fn recipient_addresses(json: &str) -> Vec<String> {
+    serde_json::from_str::<Vec<serde_json::Value>>(json)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|value| {
+            let address = value.as_str().or_else(|| value.get("email")?.as_str())?;
+            let address = match address.rsplit_once('<') {
+                Some((_, bracketed)) => bracketed.trim_end_matches('>'),
+                None => address,
+            };
+            Some(address.trim().to_string())
+        })
+        .collect()
+}
+
+/// Alias a message was received through, from its To and Cc lists
+pub fn received_by(account_email: &str, to_json: &str, cc_json: &str) -> Option<String> {
+    recipient_addresses(to_json)
+        .into_iter()
+        .chain(recipient_addresses(cc_json))
+        .find_map(|address| tag_of(account_email, &address))
+}
+
+fn alias_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<PlusAlias> {
+    Ok(PlusAlias {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        tag: row.get(2)?,
+        label: row.get(3)?,
+        address: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+/// Aliases generated for an account, newest first
+pub fn list(db: &Database, account_id: i64) -> DbResult<Vec<PlusAlias>> {
+    db.query(
+        "SELECT id, account_id, tag, label, address, created_at FROM plus_aliases
+         WHERE account_id = ?1 ORDER BY created_at DESC, id DESC",
+        params![account_id],
+        alias_from_row,
+    )
+}
+
+/// Generate the alias for a label (the existing one if the label was used before)
+pub fn generate(db: &Database, account_id: i64, label: &str) -> DbResult<PlusAlias> {
+    let tag = tag_for(label).map_err(DbError::Constraint)?;
+    let account = db.get_account(account_id)?;
+    let address = address_for(&account.email, &tag)
+        .ok_or_else(|| DbError::Constraint(format!("Invalid account address: {}", account.email)))?;
+
+    db.execute(
+        "INSERT INTO plus_aliases (account_id, tag, label, address) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(account_id, tag) DO NOTHING",
+        params![account_id, tag, label.trim(), address],
+    )?;
+    db.query_row(
+        "SELECT id, account_id, tag, label, address, created_at FROM plus_aliases WHERE account_id = ?1 AND tag = ?2",
+        params![account_id, tag],
+        alias_from_row,
+    )
+}
+
+/// Forget a generated alias (its mail is still recognized)
+pub fn delete(db: &Database, account_id: i64, tag: &str) -> DbResult<bool> {
+    let affected = db.execute(
+        "DELETE FROM plus_aliases WHERE account_id = ?1 AND tag = ?2",
+        params![account_id, tag.to_lowercase()],
+    )?;
+    Ok(affected > 0)
+}
+
+/// Alias a cached message was received through
+pub fn alias_of_email(db: &Database, email_id: i64) -> DbResult<Option<String>> {
+    let email = db.get_email(email_id)?;
+    let account = db.get_account(email.account_id)?;
+    Ok(received_by(&account.email, &email.to_addresses, &email.cc_addresses))
+}
+
+/// Received and junk counts per alias, aliases with the most junk first
+/// Generated aliases that never received mail are included with zero counts.
+pub fn stats(db: &Database, account_id: i64) -> DbResult<Vec<AliasStats>> {
+    let account = db.get_account(account_id)?;
+    let Some((local, _)) = account.email.rsplit_once('@') else {
+        return Ok(Vec::new());
+    };
+
+    let mut by_tag: HashMap<String, AliasStats> = HashMap::new();
+    for alias in list(db, account_id)? {
+        by_tag.insert(
+            alias.tag.clone(),
+            AliasStats { tag: alias.tag, address: alias.address, label: Some(alias.label), received: 0, junk: 0, last_received: None },
+        );
+    }
+
+    // One row per message (copies in several folders share a Message-ID)
+    let rows: Vec<(String, String, String, bool)> = db.query(
+        "SELECT e.to_addresses, e.cc_addresses, MAX(e.date), MAX(f.folder_type = 'spam')
+         FROM emails e JOIN folders f ON f.id = e.folder_id
+         WHERE e.account_id = ?1 AND e.is_deleted = 0
+           AND (instr(lower(e.to_addresses), ?2) > 0 OR instr(lower(e.cc_addresses), ?2) > 0)
+         GROUP BY e.message_id",
+        params![account_id, format!("{}+", local.to_lowercase())],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )?;
+
+    for (to, cc, date, is_junk) in rows {
+        let Some(tag) = received_by(&account.email, &to, &cc) else {
+            continue;
+        };
+        let stats = by_tag.entry(tag.clone()).or_insert_with(|| AliasStats {
+            address: address_for(&account.email, &tag).unwrap_or_default(),
+            tag,
+            label: None,
+            received: 0,
+            junk: 0,
+            last_received: None,
+        });
+        stats.received += 1;
+        stats.junk += u32::from(is_junk);
+        if stats.last_received.as_ref().is_none_or(|last| *last < date) {
+            stats.last_received = Some(date);
+        }
+    }
+
+    let mut stats: Vec<AliasStats> = by_tag.into_values().collect();
+    stats.sort_by(|a, b| b.junk.cmp(&a.junk).then(b.received.cmp(&a.received)).then(a.tag.cmp(&b.tag)));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_and_recognition() {
+        assert_eq!(tag_for("  Shop  Online ").unwrap(), "shop-online");
+        assert!(tag_for("a@b").is_err());
+        assert!(tag_for("").is_err());
+        assert_eq!(address_for("ana@example.com", "shop").unwrap(), "ana+shop@example.com");
+
+        assert_eq!(tag_of("ana@example.com", "Ana+Shop@Example.com").as_deref(), Some("shop"));
+        assert_eq!(tag_of("ana@example.com", "ana@example.com"), None);
+        assert_eq!(tag_of("ana@example.com", "bob+shop@example.com"), None);
+        assert_eq!(tag_of("ana@example.com", "ana+shop@other.com"), None);
+
+        let to = r#"["Ana <ana+news@example.com>", {"name": "Bob", "email": "bob@example.com"}]"#;
+        assert_eq!(received_by("ana@example.com", to, "[]").as_deref(), Some("news"));
+        assert_eq!(received_by("ana@example.com", "[]", r#"[{"email": "ana+bank@example.com"}]"#).as_deref(), Some("bank"));
+        assert_eq!(received_by("ana@example.com", r#"["ana@example.com"]"#, "[]"), None);
+    }
+}
@@ -0,0 +1,256 @@
+//! Attachment Browser
+//!
+//! Lists every attachment known from an account's cached messages, with the
+//! message's sender, subject and date, for the "Attachments" view. Filters
+//! and sorting run in SQL on the indexed columns of `attachments`; results
+//! come in pages. Inline parts (embedded images) are left out unless asked for.
+
+use crate::db::{escape_like_pattern, Database, DbResult};
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+/// Attachments per page
+pub const PAGE_SIZE: u32 = 50;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, optional_fields))]
+#[serde(rename_all = "camelCase", default)]
+pub struct AttachmentFilters {
+    /// Part of the file name
+    pub name: Option<String>,
+    /// Part of the sender's address or name
+    pub sender: Option<String>,
+    /// MIME type, or a type prefix such as `image/`
+    pub content_type: Option<String>,
+    #[cfg_attr(test, ts(optional, type = "number"))]
+    pub min_size: Option<i64>,
+    #[cfg_attr(test, ts(optional, type = "number"))]
+    pub max_size: Option<i64>,
+    /// Messages dated at or after this (RFC 3339 or a date prefix like `2024-01-31`)
+    pub since: Option<String>,
+    /// Messages dated at or before this
+    pub until: Option<String>,
+    #[cfg_attr(test, ts(optional, type = "number"))]
+    pub folder_id: Option<i64>,
+    /// Only attachments already downloaded (or only those not yet)
+    pub downloaded: Option<bool>,
+    pub include_inline: bool,
+    pub sort: AttachmentSort,
+    /// Ascending instead of the default descending order
+    pub ascending: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub enum AttachmentSort {
+    #[default]
+    Date,
+    Size,
+    Name,
+    Sender,
+    Type,
+}
+
+impl AttachmentSort {
+    fn column(self) -> &'static str {
+        match self {
+            AttachmentSort::Date => "e.date",
+            AttachmentSort::Size => "a.size",
+            AttachmentSort::Name => "a.filename COLLATE NOCASE",
+            AttachmentSort::Sender => "COALESCE(NULLIF(e.from_name, ''), e.from_address) COLLATE NOCASE",
+            AttachmentSort::Type => "a.content_type",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentItem {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub email_id: i64,
+    pub filename: String,
+    pub content_type: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub size: i64,
+    pub is_inline: bool,
+    pub is_downloaded: bool,
+    pub from_address: String,
+    pub from_name: Option<String>,
+    pub subject: String,
+    pub date: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub folder_id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentPage {
+    pub items: Vec<AttachmentItem>,
+    /// Matching attachments on all pages
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+    pub has_more: bool,
+}
+
+/// One page (0-based) of an account's attachments
+pub fn list(db: &Database, account_id: i64, filters: &AttachmentFilters, page: u32) -> DbResult<AttachmentPage> {
+    let mut conditions = vec!["e.account_id = ?1".to_string(), "e.is_deleted = 0".to_string()];
+    if !filters.include_inline {
+        conditions.push("a.is_inline = 0".to_string());
+    }
+    let mut values: Vec<Value> = vec![account_id.into()];
+    let mut condition = |sql: &str, value: Value| {
+        values.push(value);
+        conditions.push(sql.replace('?', &format!("?{}", values.len())));
+    };
+    let like = |text: &str| Value::Text(format!("%{}%", escape_like_pattern(text.trim())));
+
+    if let Some(name) = filters.name.as_deref().filter(|name| !name.trim().is_empty()) {
+        condition("a.filename LIKE ? ESCAPE '\\'", like(name));
+    }
+    if let Some(sender) = filters.sender.as_deref().filter(|sender| !sender.trim().is_empty()) {
+        condition("(e.from_address LIKE ? ESCAPE '\\' OR e.from_name LIKE ? ESCAPE '\\')", like(sender));
+    }
+    if let Some(content_type) = filters.content_type.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        let content_type = content_type.to_ascii_lowercase();
+        if content_type.ends_with('/') {
+            condition("a.content_type LIKE ? ESCAPE '\\'", Value::Text(format!("{}%", escape_like_pattern(&content_type))));
+        } else {
+            condition("a.content_type = ?", Value::Text(content_type));
+        }
+    }
+    if let Some(min_size) = filters.min_size {
+        condition("a.size >= ?", min_size.into());
+    }
+    if let Some(max_size) = filters.max_size {
+        condition("a.size <= ?", max_size.into());
+    }
+    if let Some(since) = &filters.since {
+        condition("e.date >= ?", Value::Text(since.clone()));
+    }
+    if let Some(until) = &filters.until {
+        condition("e.date <= ?", Value::Text(until.clone()));
+    }
+    if let Some(folder_id) = filters.folder_id {
+        condition("e.folder_id = ?", folder_id.into());
+    }
+    if let Some(downloaded) = filters.downloaded {
+        condition("a.is_downloaded = ?", Value::Integer(downloaded.into()));
+    }
+
+    let from = format!("FROM attachments a JOIN emails e ON e.id = a.email_id WHERE {}", conditions.join(" AND "));
+    let total: i64 = db.query_row(
+        &format!("SELECT COUNT(*) {}", from),
+        rusqlite::params_from_iter(values.iter()),
+        |row| row.get(0),
+    )?;
+
+    let order = if filters.ascending { "ASC" } else { "DESC" };
+    let items = db.query(
+        &format!(
+            "SELECT a.id, a.email_id, a.filename, a.content_type, a.size, a.is_inline, a.is_downloaded,
+                    e.from_address, e.from_name, e.subject, e.date, e.folder_id
+             {} ORDER BY {} {}, a.id {} LIMIT {} OFFSET {}",
+            from,
+            filters.sort.column(),
+            order,
+            order,
+            PAGE_SIZE,
+            u64::from(page) * u64::from(PAGE_SIZE)
+        ),
+        rusqlite::params_from_iter(values.iter()),
+        |row| {
+            Ok(AttachmentItem {
+                id: row.get(0)?,
+                email_id: row.get(1)?,
+                filename: row.get(2)?,
+                content_type: row.get(3)?,
+                size: row.get(4)?,
+                is_inline: row.get(5)?,
+                is_downloaded: row.get(6)?,
+                from_address: row.get(7)?,
+                from_name: row.get(8)?,
+                subject: row.get(9)?,
+                date: row.get(10)?,
+                folder_id: row.get(11)?,
+            })
+        },
+    )?;
+
+    Ok(AttachmentPage {
+        has_more: (u64::from(page) + 1) * u64::from(PAGE_SIZE) < total.max(0) as u64,
+        items,
+        total,
+        page,
+        page_size: PAGE_SIZE,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewAttachment, NewEmail, NewFolder};
+
+    #[test]
+    fn test_list_filters_and_sorts() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+        let email_id = db
+            .upsert_email(&NewEmail {
+                from_address: "ana@test.com".to_string(),
+                from_name: Some("Ana".to_string()),
+                has_attachments: true,
+                has_inline_images: true,
+                ..test_email(account_id, folder_id, 1)
+            })
+            .unwrap();
+        for (filename, content_type, size, is_inline) in [
+            ("invoice_100%.pdf", "application/pdf", 2000, false),
+            ("photo.jpg", "image/jpeg", 5000, false),
+            ("logo.png", "image/png", 100, true),
+        ] {
+            db.insert_attachment(&NewAttachment {
+                email_id,
+                filename: filename.to_string(),
+                content_type: content_type.to_string(),
+                size,
+                content_id: None,
+                is_inline,
+                local_path: None,
+                is_downloaded: false,
+            })
+            .unwrap();
+        }
+
+        let all = list(&db, account_id, &AttachmentFilters { sort: AttachmentSort::Size, ..Default::default() }, 0).unwrap();
+        assert_eq!(all.total, 2);
+        assert_eq!(all.items[0].filename, "photo.jpg");
+        assert_eq!(all.items[0].from_name.as_deref(), Some("Ana"));
+        assert!(!all.has_more);
+
+        let images = AttachmentFilters { content_type: Some("image/".to_string()), include_inline: true, ..Default::default() };
+        assert_eq!(list(&db, account_id, &images, 0).unwrap().total, 2);
+        let named = AttachmentFilters { name: Some("100%".to_string()), sender: Some("ana".to_string()), ..Default::default() };
+        assert_eq!(list(&db, account_id, &named, 0).unwrap().items[0].filename, "invoice_100%.pdf");
+        let small = AttachmentFilters { max_size: Some(1000), ..Default::default() };
+        assert_eq!(list(&db, account_id, &small, 0).unwrap().total, 0);
+    }
+}
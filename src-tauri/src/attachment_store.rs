@@ -0,0 +1,444 @@
+//! Attachment Store
+//!
+//! Downloaded attachments are stored content-addressed under
+//! `<data dir>/attachments/<2 hex>/<sha256>`, so a file attached to many
+//! emails (a logo, the same PDF forwarded around) occupies disk once. Each
+//! `attachments` row points at its content through `content_hash` and
+//! `local_path`; triggers keep `attachment_blobs.refcount` in step with those
+//! rows, including rows removed together with their email. Unreferenced
+//! blobs are collected at startup.
+//!
+//! With encryption at rest enabled, blobs and draft attachment copies are
+//! written AES-256-GCM encrypted under a key of their own, kept wrapped by the
+//! installation key (`crypto::encrypt_file`) in the row's `wrapped_key`.
+//! Reads decrypt transparently; rows without a key are plain files.
+
+use crate::db::{Database, DbResult};
+use rusqlite::params;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Settings key for encrypting attachments at rest
+const ENCRYPTION_SETTING: &str = "attachment_encryption";
+
+/// Unreferenced blobs younger than this are kept (a download may be about to
+/// point a row at them)
+const GC_GRACE_MINUTES: i64 = 60;
+
+/// Content-addressed file store
+#[derive(Debug, Clone)]
+pub struct AttachmentStore {
+    root: PathBuf,
+    /// Serializes blob writes, so a blob file and its key come from the same writer
+    write_lock: Arc<Mutex<()>>,
+}
+
+pub fn content_hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+pub fn encryption_enabled(db: &Database) -> DbResult<bool> {
+    Ok(db.get_setting::<bool>(ENCRYPTION_SETTING)?.unwrap_or(false))
+}
+
+pub fn set_encryption_enabled(db: &Database, enabled: bool) -> DbResult<()> {
+    db.set_setting(ENCRYPTION_SETTING, &enabled)
+}
+
+/// Write `bytes` to `path` (through a temporary file), encrypted when
+/// `encrypt` is set; returns the wrapped key of an encrypted file
+pub fn write_file(path: &Path, bytes: &[u8], encrypt: bool) -> Result<Option<String>, String> {
+    let (contents, wrapped_key) = if encrypt {
+        let (ciphertext, wrapped_key) = crate::crypto::encrypt_file(bytes)?;
+        (std::borrow::Cow::Owned(ciphertext), Some(wrapped_key))
+    } else {
+        (std::borrow::Cow::Borrowed(bytes), None)
+    };
+
+    let dir = path.parent().ok_or("Invalid attachment path")?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create attachment directory: {}", e))?;
+    // Write then rename, so a crash never leaves a truncated file behind
+    let partial = dir.join(format!(".{}.partial", uuid::Uuid::new_v4()));
+    std::fs::write(&partial, &contents).map_err(|e| format!("Failed to write attachment: {}", e))?;
+    std::fs::rename(&partial, path).map_err(|e| {
+        let _ = std::fs::remove_file(&partial);
+        format!("Failed to store attachment: {}", e)
+    })?;
+    Ok(wrapped_key)
+}
+
+/// Read a file written by `write_file`
+pub fn read_file(path: &Path, wrapped_key: Option<&str>) -> Result<Vec<u8>, String> {
+    let contents = std::fs::read(path).map_err(|e| format!("Failed to read attachment: {}", e))?;
+    match wrapped_key {
+        Some(wrapped_key) => crate::crypto::decrypt_file(&contents, wrapped_key),
+        None => Ok(contents),
+    }
+}
+
+/// Attachment row for the `index`-th attachment of a message
+/// Rows are inserted in the order the message lists its attachments.
+pub fn find_attachment(
+    db: &Database,
+    account_id: i64,
+    folder: &str,
+    uid: u32,
+    index: usize,
+) -> DbResult<Option<crate::db::Attachment>> {
+    let ids = db.query(
+        "SELECT a.id FROM attachments a
+         JOIN emails e ON e.id = a.email_id
+         JOIN folders f ON f.id = e.folder_id
+         WHERE e.account_id = ?1 AND f.remote_name = ?2 AND e.uid = ?3
+         ORDER BY a.id
+         LIMIT 1 OFFSET ?4",
+        params![account_id, folder, uid, index as i64],
+        |row| row.get::<_, i64>(0),
+    )?;
+    match ids.first() {
+        Some(id) => Ok(Some(db.get_attachment(*id)?)),
+        None => Ok(None),
+    }
+}
+
+impl AttachmentStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Store next to the database file
+    pub fn for_database(db_path: &Path) -> Self {
+        Self::new(db_path.parent().unwrap_or_else(|| Path::new(".")).join("attachments"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path_for(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2]).join(hash)
+    }
+
+    /// Write content to the store unless it is already there
+    fn put(&self, db: &Database, bytes: &[u8]) -> Result<(String, PathBuf), String> {
+        let hash = content_hash(bytes);
+        let path = self.path_for(&hash);
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Touch an existing blob first so collection does not remove it underneath us
+        let existing = db
+            .execute(
+                "UPDATE attachment_blobs SET created_at = datetime('now') WHERE sha256 = ?1",
+                params![hash],
+            )
+            .map_err(|e| format!("Database error: {}", e))?;
+        if existing > 0 && path.exists() {
+            return Ok((hash, path));
+        }
+
+        // A new blob has no row yet, so collection cannot see it before the insert
+        let encrypt = encryption_enabled(db).map_err(|e| format!("Database error: {}", e))?;
+        let wrapped_key = write_file(&path, bytes, encrypt)?;
+        db.execute(
+            "INSERT INTO attachment_blobs (sha256, size, wrapped_key) VALUES (?1, ?2, ?3)
+             ON CONFLICT(sha256) DO UPDATE SET created_at = datetime('now'), wrapped_key = excluded.wrapped_key",
+            params![hash, bytes.len() as i64, wrapped_key],
+        )
+        .map_err(|e| format!("Database error: {}", e))?;
+        Ok((hash, path))
+    }
+
+    /// Content of a blob, decrypted when it is stored encrypted
+    fn read_blob(&self, db: &Database, hash: &str) -> Result<Vec<u8>, String> {
+        let wrapped_key = db
+            .query("SELECT wrapped_key FROM attachment_blobs WHERE sha256 = ?1", params![hash], |row| {
+                row.get::<_, Option<String>>(0)
+            })
+            .map_err(|e| format!("Database error: {}", e))?
+            .into_iter()
+            .next()
+            .flatten();
+        let path = self.path_for(hash);
+        match read_file(&path, wrapped_key.as_deref()) {
+            Ok(bytes) => Ok(bytes),
+            // Interrupted `encrypt_existing`: the key was recorded, the file not yet replaced
+            Err(e) if wrapped_key.is_some() => match std::fs::read(&path) {
+                Ok(contents) if content_hash(&contents) == hash => Ok(contents),
+                _ => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Store the content of attachment `attachment_id` and point the row at it
+    pub fn store(&self, db: &Database, attachment_id: i64, bytes: &[u8]) -> Result<PathBuf, String> {
+        let (hash, path) = self.put(db, bytes)?;
+        db.execute(
+            "UPDATE attachments SET content_hash = ?1, local_path = ?2, is_downloaded = 1 WHERE id = ?3",
+            params![hash, path.to_string_lossy(), attachment_id],
+        )
+        .map_err(|e| format!("Database error: {}", e))?;
+        Ok(path)
+    }
+
+    /// Stored content of an attachment, if it was downloaded before
+    pub fn load(&self, db: &Database, attachment: &crate::db::Attachment) -> Option<Vec<u8>> {
+        if !attachment.is_downloaded {
+            return None;
+        }
+        let hash: Option<String> = db
+            .query_row("SELECT content_hash FROM attachments WHERE id = ?1", params![attachment.id], |row| row.get(0))
+            .ok()?;
+        let result = match hash {
+            Some(hash) => self.read_blob(db, &hash),
+            None => read_file(Path::new(attachment.local_path.as_ref()?), None),
+        };
+        match result {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                log::warn!("Failed to load stored attachment {}: {}", attachment.id, e);
+                None
+            }
+        }
+    }
+
+    /// Encrypt blobs and draft attachment copies written in plain text
+    /// Returns the number of files encrypted.
+    pub fn encrypt_existing(&self, db: &Database) -> Result<usize, String> {
+        let db_error = |e: crate::db::DbError| format!("Database error: {}", e);
+        let mut encrypted = 0;
+
+        let hashes = db
+            .query("SELECT sha256 FROM attachment_blobs WHERE wrapped_key IS NULL", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .map_err(db_error)?;
+        for hash in hashes {
+            let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+            let path = self.path_for(&hash);
+            let Ok(bytes) = std::fs::read(&path) else { continue };
+            // Record the key before replacing the file; `read_blob` recognizes
+            // a plain file left behind by a crash in between by its hash
+            let (ciphertext, wrapped_key) = crate::crypto::encrypt_file(&bytes)?;
+            let partial = path.with_extension("encrypting");
+            std::fs::write(&partial, &ciphertext).map_err(|e| format!("Failed to write attachment: {}", e))?;
+            db.execute(
+                "UPDATE attachment_blobs SET wrapped_key = ?1 WHERE sha256 = ?2",
+                params![wrapped_key, hash],
+            )
+            .map_err(db_error)?;
+            std::fs::rename(&partial, &path).map_err(|e| format!("Failed to store attachment: {}", e))?;
+            encrypted += 1;
+        }
+
+        // Draft copies get a new name, so the row switches to the encrypted file in one update
+        let drafts = db
+            .query(
+                "SELECT id, local_path FROM draft_attachments WHERE wrapped_key IS NULL",
+                [],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )
+            .map_err(db_error)?;
+        for (id, old_path) in drafts {
+            let old_path = PathBuf::from(old_path);
+            let Ok(bytes) = std::fs::read(&old_path) else { continue };
+            let mut new_name = old_path.file_name().unwrap_or_default().to_owned();
+            new_name.push(".enc");
+            let new_path = old_path.with_file_name(new_name);
+            let wrapped_key = write_file(&new_path, &bytes, true)?;
+            db.execute(
+                "UPDATE draft_attachments SET local_path = ?1, wrapped_key = ?2 WHERE id = ?3",
+                params![new_path.to_string_lossy(), wrapped_key, id],
+            )
+            .map_err(db_error)?;
+            if let Err(e) = std::fs::remove_file(&old_path) {
+                log::warn!("Failed to remove plain draft attachment {:?}: {}", old_path, e);
+            }
+            encrypted += 1;
+        }
+        Ok(encrypted)
+    }
+
+    /// Move files downloaded before deduplication into the store
+    /// The old copy is removed once no other row still points at it.
+    pub fn import_legacy(&self, db: &Database) -> Result<usize, String> {
+        let rows = db
+            .query(
+                "SELECT id, local_path FROM attachments
+                 WHERE is_downloaded = 1 AND local_path IS NOT NULL AND content_hash IS NULL",
+                [],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let mut imported = 0;
+        for (id, old_path) in rows {
+            let Ok(bytes) = std::fs::read(&old_path) else {
+                db.execute(
+                    "UPDATE attachments SET is_downloaded = 0, local_path = NULL WHERE id = ?1",
+                    params![id],
+                )
+                .map_err(|e| format!("Database error: {}", e))?;
+                continue;
+            };
+            self.store(db, id, &bytes)?;
+            imported += 1;
+
+            let still_used: bool = db
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM attachments WHERE local_path = ?1",
+                    params![old_path],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Database error: {}", e))?;
+            if !still_used && !Path::new(&old_path).starts_with(&self.root) {
+                if let Err(e) = std::fs::remove_file(&old_path) {
+                    log::warn!("Failed to remove imported attachment {}: {}", old_path, e);
+                }
+            }
+        }
+        Ok(imported)
+    }
+
+    /// Delete blobs no attachment refers to any more
+    pub fn collect_garbage(&self, db: &Database) -> DbResult<usize> {
+        self.collect_older_than(db, GC_GRACE_MINUTES)
+    }
+
+    fn collect_older_than(&self, db: &Database, minutes: i64) -> DbResult<usize> {
+        let hashes = db.query(
+            "DELETE FROM attachment_blobs
+             WHERE refcount <= 0 AND created_at <= datetime('now', ?1)
+             RETURNING sha256",
+            params![format!("{} minutes", -minutes)],
+            |row| row.get::<_, String>(0),
+        )?;
+        for hash in &hashes {
+            let path = self.path_for(hash);
+            if let Err(e) = std::fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("Failed to remove attachment blob {:?}: {}", path, e);
+                }
+            }
+        }
+        Ok(hashes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    fn setup() -> (Database, AttachmentStore, PathBuf, Vec<i64>) {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = crate::sync_folder_to_db(&db, account_id, "INBOX").unwrap();
+
+        let mut attachment_ids = Vec::new();
+        for uid in 1..=3 {
+            let email_id = db
+                .execute_insert(
+                    "INSERT INTO emails (account_id, folder_id, message_id, uid, from_address, date)
+                     VALUES (?1, ?2, ?3, ?4, 'ana@example.com', '2025-01-01T10:00:00Z')",
+                    params![account_id, folder_id, format!("<{}@test.com>", uid), uid],
+                )
+                .unwrap();
+            let attachment_id = db
+                .execute_insert(
+                    "INSERT INTO attachments (email_id, filename) VALUES (?1, 'logo.png')",
+                    params![email_id],
+                )
+                .unwrap();
+            attachment_ids.push(attachment_id);
+        }
+
+        let dir = std::env::temp_dir().join(format!("owlivion-store-{}", uuid::Uuid::new_v4()));
+        let store = AttachmentStore::new(dir.join("attachments"));
+        (db, store, dir, attachment_ids)
+    }
+
+    fn refcount(db: &Database, hash: &str) -> Option<i64> {
+        db.query("SELECT refcount FROM attachment_blobs WHERE sha256 = ?1", params![hash], |row| row.get(0))
+            .unwrap()
+            .first()
+            .copied()
+    }
+
+    #[test]
+    fn test_identical_content_stored_once() {
+        let (db, store, dir, ids) = setup();
+        let logo = b"\x89PNG same logo".to_vec();
+        let hash = content_hash(&logo);
+
+        let first = store.store(&db, ids[0], &logo).unwrap();
+        let second = store.store(&db, ids[1], &logo).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(refcount(&db, &hash), Some(2));
+        assert_eq!(std::fs::read_dir(first.parent().unwrap()).unwrap().count(), 1);
+
+        let attachment = db.get_attachment(ids[1]).unwrap();
+        assert_eq!(store.load(&db, &attachment), Some(logo.clone()));
+
+        // Deleting an email releases its reference through the cascade
+        db.execute("DELETE FROM emails WHERE id = (SELECT email_id FROM attachments WHERE id = ?1)", params![ids[0]])
+            .unwrap();
+        assert_eq!(refcount(&db, &hash), Some(1));
+        assert_eq!(store.collect_older_than(&db, 0).unwrap(), 0);
+
+        db.execute("DELETE FROM attachments WHERE id = ?1", params![ids[1]]).unwrap();
+        assert_eq!(store.collect_garbage(&db).unwrap(), 0); // within the grace period
+        assert_eq!(store.collect_older_than(&db, -1).unwrap(), 1);
+        assert!(!first.exists());
+        assert_eq!(refcount(&db, &hash), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_import_legacy_files() {
+        let (db, store, dir, ids) = setup();
+        std::fs::create_dir_all(&dir).unwrap();
+        let legacy = dir.join("report.pdf");
+        std::fs::write(&legacy, b"%PDF report").unwrap();
+        for id in &ids[..2] {
+            db.update_attachment_path(*id, &legacy.to_string_lossy()).unwrap();
+        }
+        db.update_attachment_path(ids[2], &dir.join("gone.pdf").to_string_lossy()).unwrap();
+
+        assert_eq!(store.import_legacy(&db).unwrap(), 2);
+        assert!(!legacy.exists());
+        assert_eq!(refcount(&db, &content_hash(b"%PDF report")), Some(2));
+        assert_eq!(store.load(&db, &db.get_attachment(ids[0]).unwrap()), Some(b"%PDF report".to_vec()));
+        assert!(!db.get_attachment(ids[2]).unwrap().is_downloaded);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_encryption_at_rest() {
+        let (db, store, dir, ids) = setup();
+        let plain = b"%PDF plain before encryption".to_vec();
+        let plain_path = store.store(&db, ids[0], &plain).unwrap();
+        assert_eq!(std::fs::read(&plain_path).unwrap(), plain);
+
+        set_encryption_enabled(&db, true).unwrap();
+        let secret = b"%PDF salary figures".to_vec();
+        let secret_path = store.store(&db, ids[1], &secret).unwrap();
+        assert_ne!(std::fs::read(&secret_path).unwrap(), secret);
+        assert_eq!(store.load(&db, &db.get_attachment(ids[1]).unwrap()), Some(secret.clone()));
+
+        // Files written before encryption was enabled are converted
+        assert_eq!(store.encrypt_existing(&db).unwrap(), 1);
+        assert_ne!(std::fs::read(&plain_path).unwrap(), plain);
+        assert_eq!(store.load(&db, &db.get_attachment(ids[0]).unwrap()), Some(plain.clone()));
+
+        // Turning encryption off keeps encrypted files readable
+        set_encryption_enabled(&db, false).unwrap();
+        assert_eq!(store.load(&db, &db.get_attachment(ids[1]).unwrap()), Some(secret));
+        assert_eq!(store.encrypt_existing(&db).unwrap(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
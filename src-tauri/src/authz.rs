@@ -0,0 +1,243 @@
+//! Command Authorization
+//!
+//! Every command can be invoked by any script running in the webview, so a
+//! script injected through rendered content could call destructive commands
+//! directly. Commands that delete or replace data declare a [`Scope`] and
+//! require a confirmation token. The frontend asks for one with
+//! `confirm_request`; the backend shows a native dialog, which scripts in
+//! the webview cannot answer, and issues the token only when the user
+//! accepts. A token is bound to its scope and target, expires after
+//! `TOKEN_TTL` and can be used once. `Scope::command` names the command each
+//! scope gates, and a test checks that every one of them consumes its token.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long an issued token stays valid
+const TOKEN_TTL: Duration = Duration::from_secs(120);
+
+/// Action a confirmation token authorizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "ConfirmScope"))]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// `account_delete`; target is the account id
+    AccountDelete,
+    /// `filter_import`; target is the account id
+    FilterImport,
+    /// `rollback_sync`; target is `<data type>@<version>`
+    SyncRollback,
+    /// `crypto_rekey`; target is empty
+    CryptoRekey,
+    /// `cleanup_execute`; target is `<account id>@<action id>`
+    CleanupExecute,
+    /// `sender_block_apply`; target is the block id
+    SenderBlockApply,
+    /// `local_folder_delete`; target is the folder id
+    LocalFolderDelete,
+    /// `account_migrate`; target is `<source id>@<target id>`
+    AccountMigrate,
+    /// `mailmerge_start`; target is `<account id>@<template id>`
+    MailmergeStart,
+    /// `sync_rotate_master_password`; target is empty
+    SyncRotateMasterPassword,
+    /// `template_import`; target is the import file path
+    TemplateImport,
+    /// `diagnostics_export`; target is the bundle path
+    DiagnosticsExport,
+    /// `profile_switch`; target is the profile id
+    ProfileSwitch,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 13] = [
+        Scope::AccountDelete,
+        Scope::FilterImport,
+        Scope::SyncRollback,
+        Scope::CryptoRekey,
+        Scope::CleanupExecute,
+        Scope::SenderBlockApply,
+        Scope::LocalFolderDelete,
+        Scope::AccountMigrate,
+        Scope::MailmergeStart,
+        Scope::SyncRotateMasterPassword,
+        Scope::TemplateImport,
+        Scope::DiagnosticsExport,
+        Scope::ProfileSwitch,
+    ];
+
+    /// Command that requires a token of this scope
+    pub fn command(&self) -> &'static str {
+        match self {
+            Scope::AccountDelete => "account_delete",
+            Scope::FilterImport => "filter_import",
+            Scope::SyncRollback => "rollback_sync",
+            Scope::CryptoRekey => "crypto_rekey",
+            Scope::CleanupExecute => "cleanup_execute",
+            Scope::SenderBlockApply => "sender_block_apply",
+            Scope::LocalFolderDelete => "local_folder_delete",
+            Scope::AccountMigrate => "account_migrate",
+            Scope::MailmergeStart => "mailmerge_start",
+            Scope::SyncRotateMasterPassword => "sync_rotate_master_password",
+            Scope::TemplateImport => "template_import",
+            Scope::DiagnosticsExport => "diagnostics_export",
+            Scope::ProfileSwitch => "profile_switch",
+        }
+    }
+
+    /// Question shown in the confirmation dialog; `label` names the target
+    pub fn prompt(&self, label: &str) -> String {
+        match self {
+            Scope::AccountDelete => format!(
+                "{} hesabı ve bu cihazdaki tüm e-postaları silinecek. Devam edilsin mi?",
+                label
+            ),
+            Scope::FilterImport => format!("{} hesabına filtreler içe aktarılacak. Devam edilsin mi?", label),
+            Scope::SyncRollback => format!(
+                "Senkronize edilen veriler ({}) seçilen sürüme geri döndürülecek. Devam edilsin mi?",
+                label
+            ),
+            Scope::CryptoRekey => {
+                "Kayıtlı tüm parolalar ve anahtarlar yeni bir anahtarla yeniden şifrelenecek. Devam edilsin mi?".to_string()
+            }
+            Scope::CleanupExecute => format!("Temizlik önerisi uygulanacak ({}). Devam edilsin mi?", label),
+            Scope::SenderBlockApply => format!(
+                "{} göndericisinden gelen mevcut iletilere engelleme uygulanacak. Devam edilsin mi?",
+                label
+            ),
+            Scope::LocalFolderDelete => format!(
+                "{} yerel klasörü ve içindeki tüm iletiler silinecek. Devam edilsin mi?",
+                label
+            ),
+            Scope::AccountMigrate => format!("Klasörler {} kopyalanacak. Devam edilsin mi?", label),
+            Scope::MailmergeStart => format!("{} ile toplu gönderim başlatılacak. Devam edilsin mi?", label),
+            Scope::SyncRotateMasterPassword => "Senkronizasyon ana parolası değiştirilecek ve sunucudaki tüm veriler \
+                 yeniden şifrelenecek. Devam edilsin mi?"
+                .to_string(),
+            Scope::TemplateImport => format!("{} dosyasından şablonlar içe aktarılacak. Devam edilsin mi?", label),
+            Scope::DiagnosticsExport => format!(
+                "Tanılama paketi (günlükler ve hesap ayarları) {} konumuna yazılacak. Devam edilsin mi?",
+                label
+            ),
+            Scope::ProfileSwitch => format!(
+                "Uygulama {} profiline geçmek için yeniden başlatılacak. Devam edilsin mi?",
+                label
+            ),
+        }
+    }
+
+    /// Label of the dialog's accept button
+    pub fn accept_label(&self) -> &'static str {
+        match self {
+            Scope::AccountDelete => "Sil",
+            Scope::FilterImport => "İçe Aktar",
+            Scope::SyncRollback => "Geri Al",
+            Scope::CryptoRekey => "Yeniden Şifrele",
+            Scope::CleanupExecute => "Uygula",
+            Scope::SenderBlockApply => "Uygula",
+            Scope::LocalFolderDelete => "Sil",
+            Scope::AccountMigrate => "Kopyala",
+            Scope::MailmergeStart => "Gönder",
+            Scope::SyncRotateMasterPassword => "Değiştir",
+            Scope::TemplateImport => "İçe Aktar",
+            Scope::DiagnosticsExport => "Dışa Aktar",
+            Scope::ProfileSwitch => "Yeniden Başlat",
+        }
+    }
+}
+
+struct Grant {
+    scope: Scope,
+    target: String,
+    issued_at: Instant,
+}
+
+/// Confirmation tokens issued and not yet used
+#[derive(Default)]
+pub struct Confirmations {
+    grants: Mutex<HashMap<String, Grant>>,
+}
+
+impl Confirmations {
+    /// Issue a token after the user confirmed `scope` on `target`
+    pub fn issue(&self, scope: Scope, target: &str, now: Instant) -> String {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let mut grants = self.grants.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        grants.retain(|_, grant| now.duration_since(grant.issued_at) < TOKEN_TTL);
+        grants.insert(token.clone(), Grant { scope, target: target.to_string(), issued_at: now });
+        token
+    }
+
+    /// Use up a token; fails unless it was issued for this scope and target and has not expired
+    pub fn consume(&self, scope: Scope, target: &str, token: &str, now: Instant) -> Result<(), String> {
+        let grant = self
+            .grants
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(token);
+        match grant {
+            Some(grant)
+                if grant.scope == scope
+                    && grant.target == target
+                    && now.duration_since(grant.issued_at) < TOKEN_TTL =>
+            {
+                Ok(())
+            }
+            _ => Err("This action was not confirmed. Please confirm it and try again.".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_scoped_and_single_use() {
+        let confirmations = Confirmations::default();
+        let now = Instant::now();
+
+        let token = confirmations.issue(Scope::AccountDelete, "1", now);
+        assert!(confirmations.consume(Scope::AccountDelete, "1", &token, now).is_ok());
+        assert!(confirmations.consume(Scope::AccountDelete, "1", &token, now).is_err());
+
+        // A token for one target or scope does not authorize another, and is spent by the attempt
+        let token = confirmations.issue(Scope::AccountDelete, "1", now);
+        assert!(confirmations.consume(Scope::AccountDelete, "2", &token, now).is_err());
+        assert!(confirmations.consume(Scope::AccountDelete, "1", &token, now).is_err());
+        let token = confirmations.issue(Scope::FilterImport, "1", now);
+        assert!(confirmations.consume(Scope::AccountDelete, "1", &token, now).is_err());
+
+        let token = confirmations.issue(Scope::SyncRollback, "accounts@3", now);
+        assert!(confirmations
+            .consume(Scope::SyncRollback, "accounts@3", &token, now + TOKEN_TTL)
+            .is_err());
+        assert!(confirmations.consume(Scope::SyncRollback, "accounts@3", "forged", now).is_err());
+    }
+
+    /// Source of a command function in lib.rs, up to the next item
+    fn command_source<'a>(source: &'a str, command: &str) -> Option<&'a str> {
+        let start = source.find(&format!("async fn {}(", command))?;
+        let rest = &source[start..];
+        Some(&rest[..rest.find("\n}\n").unwrap_or(rest.len())])
+    }
+
+    #[test]
+    fn test_every_scope_is_checked_by_its_command() {
+        let source = include_str!("lib.rs");
+        for scope in Scope::ALL {
+            let body = command_source(source, scope.command())
+                .unwrap_or_else(|| panic!("command {} not found", scope.command()));
+            let compact: String = body.split_whitespace().collect();
+            assert!(compact.contains("confirm_token:String"), "{} takes no confirm_token", scope.command());
+            assert!(
+                compact.contains(&format!(".consume(authz::Scope::{:?},", scope)),
+                "{} does not check its {:?} token",
+                scope.command(),
+                scope
+            );
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Start on Login
+//!
+//! Registers the app to start when the user logs in: a Launch Agent on
+//! macOS, the `Run` registry key on Windows and an XDG autostart entry on
+//! Linux. Login launches carry `--autostart`, so they can be told apart from
+//! the user opening the app; with the `start_minimized` setting such a launch
+//! stays in the tray instead of showing the main window.
+
+use crate::db::Database;
+use crate::settings::{self, SettingsEvents};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_autostart::ManagerExt;
+
+/// Flag on the command line of login launches
+pub const AUTOSTART_FLAG: &str = "--autostart";
+
+/// Settings key of the "start minimized to tray" mode
+const MINIMIZED_SETTING: &str = "start_minimized";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AutostartStatus {
+    pub enabled: bool,
+    /// Login launches stay in the tray
+    pub minimized: bool,
+}
+
+/// Whether this process was started at login
+pub fn launched_at_login<I: IntoIterator<Item = String>>(args: I) -> bool {
+    args.into_iter().any(|arg| arg == AUTOSTART_FLAG)
+}
+
+/// Whether this launch should keep the main window hidden
+pub fn start_hidden<I: IntoIterator<Item = String>>(db: &Database, args: I) -> bool {
+    launched_at_login(args) && settings::get_bool(db, MINIMIZED_SETTING)
+}
+
+pub fn status<R: Runtime>(app: &AppHandle<R>, db: &Database) -> Result<AutostartStatus, String> {
+    Ok(AutostartStatus {
+        enabled: app
+            .autolaunch()
+            .is_enabled()
+            .map_err(|e| format!("Failed to read autostart entry: {}", e))?,
+        minimized: settings::get_bool(db, MINIMIZED_SETTING),
+    })
+}
+
+/// Start on login, hidden in the tray if `minimized`
+pub fn enable<R: Runtime>(
+    app: &AppHandle<R>,
+    db: &Database,
+    events: &SettingsEvents,
+    minimized: bool,
+) -> Result<AutostartStatus, String> {
+    let mut values = Map::new();
+    values.insert("startMinimized".to_string(), Value::Bool(minimized));
+    let changed = settings::set_many(db, &values).map_err(|e| format!("Failed to save autostart mode: {}", e))?;
+    events.notify(changed);
+    app.autolaunch()
+        .enable()
+        .map_err(|e| format!("Failed to create autostart entry: {}", e))?;
+    status(app, db)
+}
+
+/// Stop starting on login
+pub fn disable<R: Runtime>(app: &AppHandle<R>, db: &Database) -> Result<AutostartStatus, String> {
+    app.autolaunch()
+        .disable()
+        .map_err(|e| format!("Failed to remove autostart entry: {}", e))?;
+    status(app, db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_hidden() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let login = || ["owlivion-mail", AUTOSTART_FLAG].map(String::from);
+        let manual = || ["owlivion-mail"].map(String::from);
+
+        assert!(!start_hidden(&db, login()));
+        db.set_setting(MINIMIZED_SETTING, &true).unwrap();
+        assert!(start_hidden(&db, login()));
+        assert!(!start_hidden(&db, manual()));
+    }
+}
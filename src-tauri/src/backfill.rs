@@ -0,0 +1,356 @@
+//! Staged Historical Backfill
+//!
+//! A new account first shows its newest mail; older messages inside the
+//! account's `sync_days` window are then pulled in background batches, newest
+//! first. Progress is stored per folder so a restart resumes where it stopped,
+//! and the window can be extended later ("load older messages").
+
+use crate::db::{Database, DbResult};
+use crate::mail::{AsyncImapClient, EmailSummary, MailResult};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Tauri event name for backfill progress
+pub const BACKFILL_PROGRESS_EVENT: &str = "backfill://progress";
+
+/// Messages fetched per batch
+const BATCH_SIZE: usize = 100;
+
+/// Pause between batches so backfill never starves interactive use
+const BATCH_DELAY: Duration = Duration::from_millis(500);
+
+/// Backfill progress of one account folder (payload of `backfill://progress`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillState {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub folder: String,
+    /// Oldest date included; None means the full history
+    pub since: Option<NaiveDate>,
+    /// Messages in the window on the server
+    pub total: u32,
+    /// Of those, already cached locally
+    pub fetched: u32,
+    pub done: bool,
+}
+
+/// Server side of a backfill (implemented by the IMAP client)
+#[async_trait]
+pub trait BackfillSource: Send {
+    async fn search_since(&mut self, folder: &str, since: Option<NaiveDate>) -> MailResult<Vec<u32>>;
+    async fn fetch_by_uids(&mut self, folder: &str, uids: &[u32]) -> MailResult<Vec<EmailSummary>>;
+}
+
+#[async_trait]
+impl BackfillSource for AsyncImapClient {
+    async fn search_since(&mut self, folder: &str, since: Option<NaiveDate>) -> MailResult<Vec<u32>> {
+        AsyncImapClient::search_since(self, folder, since).await
+    }
+
+    async fn fetch_by_uids(&mut self, folder: &str, uids: &[u32]) -> MailResult<Vec<EmailSummary>> {
+        self.fetch_emails_by_uids(folder, uids).await
+    }
+}
+
+/// Start of the initial window for an account (`sync_days` <= 0 means everything)
+pub fn window_start(sync_days: i32, today: NaiveDate) -> Option<NaiveDate> {
+    if sync_days <= 0 {
+        return None;
+    }
+    today.checked_sub_days(chrono::Days::new(sync_days as u64))
+}
+
+/// Move the window start `days` further into the past
+pub fn extend_window(since: Option<NaiveDate>, days: u32) -> Option<NaiveDate> {
+    since.and_then(|date| date.checked_sub_days(chrono::Days::new(days as u64)))
+}
+
+/// UIDs still missing locally, newest first
+fn missing_uids(server_uids: &[u32], cached: &HashSet<u32>) -> Vec<u32> {
+    let mut missing: Vec<u32> = server_uids
+        .iter()
+        .copied()
+        .filter(|uid| !cached.contains(uid))
+        .collect();
+    missing.sort_unstable_by(|a, b| b.cmp(a));
+    missing
+}
+
+/// Stored progress for an account folder
+pub fn load_state(db: &Database, account_id: i64, folder: &str) -> DbResult<Option<BackfillState>> {
+    let mut states = db.query(
+        "SELECT account_id, folder, since, total, fetched, done FROM backfill_state WHERE account_id = ?1 AND folder = ?2",
+        params![account_id, folder],
+        row_to_state,
+    )?;
+    Ok(states.pop())
+}
+
+/// Stored progress for all folders of an account
+pub fn list_states(db: &Database, account_id: i64) -> DbResult<Vec<BackfillState>> {
+    db.query(
+        "SELECT account_id, folder, since, total, fetched, done FROM backfill_state WHERE account_id = ?1 ORDER BY folder",
+        [account_id],
+        row_to_state,
+    )
+}
+
+fn row_to_state(row: &rusqlite::Row<'_>) -> rusqlite::Result<BackfillState> {
+    let since: Option<String> = row.get(2)?;
+    Ok(BackfillState {
+        account_id: row.get(0)?,
+        folder: row.get(1)?,
+        since: since.and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()),
+        total: row.get(3)?,
+        fetched: row.get(4)?,
+        done: row.get(5)?,
+    })
+}
+
+fn save_state(db: &Database, state: &BackfillState) -> DbResult<()> {
+    db.execute(
+        r#"
+        INSERT INTO backfill_state (account_id, folder, since, total, fetched, done, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))
+        ON CONFLICT(account_id, folder) DO UPDATE SET
+            since = excluded.since,
+            total = excluded.total,
+            fetched = excluded.fetched,
+            done = excluded.done,
+            updated_at = excluded.updated_at
+        "#,
+        params![
+            state.account_id,
+            state.folder,
+            state.since.map(|d| d.format("%Y-%m-%d").to_string()),
+            state.total,
+            state.fetched,
+            state.done,
+        ],
+    )?;
+    Ok(())
+}
+
+fn cached_uids(db: &Database, folder_id: i64) -> DbResult<HashSet<u32>> {
+    let uids = db.query("SELECT uid FROM emails WHERE folder_id = ?1", [folder_id], |row| row.get(0))?;
+    Ok(uids.into_iter().collect())
+}
+
+/// Backfill a folder down to `since`, newest batches first
+/// Stops early (keeping progress) when `should_continue` turns false
+pub async fn run<S, C, P>(
+    db: &Database,
+    source: &mut S,
+    account_id: i64,
+    folder: &str,
+    since: Option<NaiveDate>,
+    should_continue: C,
+    on_progress: P,
+) -> Result<BackfillState, String>
+where
+    S: BackfillSource + ?Sized,
+    C: Fn() -> bool,
+    P: Fn(&BackfillState),
+{
+    let folder_id = crate::sync_folder_to_db(db, account_id, folder)?;
+
+    let server_uids = source
+        .search_since(folder, since)
+        .await
+        .map_err(|e| format!("Failed to list messages: {}", e))?;
+
+    let cached = cached_uids(db, folder_id).map_err(|e| e.to_string())?;
+    let missing = missing_uids(&server_uids, &cached);
+
+    let mut state = BackfillState {
+        account_id,
+        folder: folder.to_string(),
+        since,
+        total: server_uids.len() as u32,
+        fetched: (server_uids.len() - missing.len()) as u32,
+        done: missing.is_empty(),
+    };
+    save_state(db, &state).map_err(|e| e.to_string())?;
+    on_progress(&state);
+
+    log::info!(
+        "Backfill {} for account {}: {} of {} messages missing since {:?}",
+        folder, account_id, missing.len(), state.total, since
+    );
+
+    for batch in missing.chunks(BATCH_SIZE) {
+        if !should_continue() {
+            log::info!("Backfill {} for account {} suspended at {}/{}", folder, account_id, state.fetched, state.total);
+            return Ok(state);
+        }
+
+        let summaries = source
+            .fetch_by_uids(folder, batch)
+            .await
+            .map_err(|e| format!("Failed to fetch messages: {}", e))?;
+
+        let new_emails: Vec<_> = summaries
+            .iter()
+            .map(|summary| crate::summary_to_new_email(account_id, folder_id, summary))
+            .collect();
+        db.batch_upsert_emails(&new_emails).map_err(|e| e.to_string())?;
+        crate::mail::keywords::store_all(db, account_id, folder_id, &summaries);
+
+        // Count the whole batch: UIDs expunged meanwhile are not coming back
+        state.fetched = (state.fetched + batch.len() as u32).min(state.total);
+        save_state(db, &state).map_err(|e| e.to_string())?;
+        on_progress(&state);
+
+        tokio::time::sleep(BATCH_DELAY).await;
+    }
+
+    state.done = true;
+    save_state(db, &state).map_err(|e| e.to_string())?;
+    on_progress(&state);
+
+    Ok(state)
+}
+
+/// Folders with a backfill in progress (prevents running the same one twice)
+#[derive(Default)]
+pub struct RunningBackfills {
+    running: Mutex<HashSet<(i64, String)>>,
+}
+
+impl RunningBackfills {
+    /// Claim a folder; false when a backfill for it is already running
+    pub fn try_begin(&self, account_id: i64, folder: &str) -> bool {
+        self.running
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert((account_id, folder.to_string()))
+    }
+
+    pub fn finish(&self, account_id: i64, folder: &str) {
+        self.running
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&(account_id, folder.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+    use std::cell::Cell;
+
+    /// In-memory mailbox: UID n is dated n days before 2025-01-01
+    struct FakeMailbox {
+        uids: Vec<u32>,
+        fetches: usize,
+    }
+
+    fn date_of(uid: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap() - chrono::Days::new(u64::from(1000 - uid))
+    }
+
+    #[async_trait]
+    impl BackfillSource for FakeMailbox {
+        async fn search_since(&mut self, _folder: &str, since: Option<NaiveDate>) -> MailResult<Vec<u32>> {
+            Ok(self.uids.iter().copied().filter(|uid| since.is_none_or(|d| date_of(*uid) >= d)).collect())
+        }
+
+        async fn fetch_by_uids(&mut self, _folder: &str, uids: &[u32]) -> MailResult<Vec<EmailSummary>> {
+            self.fetches += 1;
+            Ok(uids
+                .iter()
+                .map(|uid| EmailSummary {
+                    uid: *uid,
+                    message_id: Some(format!("<{}@test>", uid)),
+                    from: "a@test.com".to_string(),
+                    from_name: None,
+                    subject: format!("Message {}", uid),
+                    preview: String::new(),
+                    date: date_of(*uid).to_string(),
+                    is_read: true,
+                    is_starred: false,
+                    has_attachments: false,
+                    size: 0,
+                    keywords: Vec::new(),
+                    account_id: None,
+                    account_email: None,
+                    account_name: None,
+                    account_color: None,
+                })
+                .collect())
+        }
+    }
+
+    fn setup_db() -> (Database, i64) {
+        let db = Database::in_memory().expect("Failed to create database");
+        let account_id = test_account(&db);
+        (db, account_id)
+    }
+
+    #[test]
+    fn test_window() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        assert_eq!(window_start(30, today), NaiveDate::from_ymd_opt(2025, 3, 1));
+        assert_eq!(window_start(0, today), None);
+        assert_eq!(extend_window(NaiveDate::from_ymd_opt(2025, 3, 1), 28), NaiveDate::from_ymd_opt(2025, 2, 1));
+        assert_eq!(extend_window(None, 30), None);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_newest_first_and_extend() {
+        let (db, account_id) = setup_db();
+        let mut mailbox = FakeMailbox { uids: (1..=1000).collect(), fetches: 0 };
+        let since = date_of(750);
+        let first_batch = Cell::new(None);
+
+        let state = run(&db, &mut mailbox, account_id, "INBOX", Some(since), || true, |s| {
+            if first_batch.get().is_none() && s.fetched > 0 {
+                first_batch.set(Some(s.fetched));
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(state.done);
+        assert_eq!((state.total, state.fetched), (251, 251));
+        assert_eq!(mailbox.fetches, 3);
+        assert_eq!(first_batch.get(), Some(100));
+        assert_eq!(load_state(&db, account_id, "INBOX").unwrap(), Some(state.clone()));
+
+        // "Load older messages" only fetches what is not cached yet
+        let older = extend_window(Some(since), 50);
+        let state = run(&db, &mut mailbox, account_id, "INBOX", older, || true, |_| {}).await.unwrap();
+        assert_eq!((state.total, state.fetched, state.done), (301, 301, true));
+        assert_eq!(mailbox.fetches, 4);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_suspends_and_resumes() {
+        let (db, account_id) = setup_db();
+        let mut mailbox = FakeMailbox { uids: (1..=250).collect(), fetches: 0 };
+        let budget = Cell::new(1u32);
+
+        let state = run(&db, &mut mailbox, account_id, "INBOX", None, || {
+            let left = budget.get();
+            budget.set(left.saturating_sub(1));
+            left > 0
+        }, |_| {})
+        .await
+        .unwrap();
+        assert!(!state.done);
+        assert_eq!(state.fetched, 100);
+        assert_eq!(list_states(&db, account_id).unwrap(), vec![state]);
+
+        let state = run(&db, &mut mailbox, account_id, "INBOX", None, || true, |_| {}).await.unwrap();
+        assert!(state.done);
+        assert_eq!(state.fetched, 250);
+    }
+}
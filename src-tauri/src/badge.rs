@@ -0,0 +1,244 @@
+//! Unread Badge
+//!
+//! Shows the number of unread Inbox messages on the taskbar or dock icon: the
+//! dock badge on macOS, the launcher count on Linux desktops that implement
+//! the Unity launcher API (via `owlivion-mail.desktop`), and an overlay icon
+//! drawn here on Windows. Store changes and synced mail request an update;
+//! requests are debounced so a large sync redraws the badge once. The
+//! `notification_badge` setting turns it off.
+
+use crate::db::{Database, DbResult};
+use crate::events::StoreEvents;
+use crate::settings::{self, SettingsEvents};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::{broadcast, Notify};
+
+/// Quiet time after the last request before the badge is redrawn
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Counts above this show as "99+"
+const MAX_SHOWN: i64 = 99;
+
+/// Side length of the Windows overlay icon
+const OVERLAY_SIZE: u32 = 32;
+
+/// 3x5 glyphs for the overlay icon, one row per byte (bit 2 = left column)
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const PLUS: [u8; 5] = [0b000, 0b010, 0b111, 0b010, 0b000];
+
+/// Unread messages in the Inbox folders of active accounts
+pub fn unread_count(db: &Database) -> DbResult<i64> {
+    db.query_row(
+        r#"
+        SELECT COUNT(*)
+        FROM emails e
+        JOIN folders f ON f.id = e.folder_id
+        JOIN accounts a ON a.id = e.account_id
+        WHERE f.folder_type = 'inbox' AND a.is_active = 1
+          AND e.is_read = 0 AND e.is_deleted = 0 AND e.is_spam = 0
+        "#,
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Badge text for a count ("99+" above `MAX_SHOWN`)
+pub fn label(count: i64) -> String {
+    if count > MAX_SHOWN {
+        format!("{}+", MAX_SHOWN)
+    } else {
+        count.to_string()
+    }
+}
+
+/// RGBA pixels of the overlay icon: white count on a red disc
+pub fn render_overlay(count: i64) -> Vec<u8> {
+    let size = OVERLAY_SIZE as i32;
+    let mut pixels = vec![0u8; (OVERLAY_SIZE * OVERLAY_SIZE * 4) as usize];
+    let mut set = |x: i32, y: i32, rgba: [u8; 4]| {
+        if (0..size).contains(&x) && (0..size).contains(&y) {
+            let i = ((y * size + x) * 4) as usize;
+            pixels[i..i + 4].copy_from_slice(&rgba);
+        }
+    };
+
+    let radius = size / 2;
+    for y in 0..size {
+        for x in 0..size {
+            let (dx, dy) = (x - radius, y - radius);
+            if dx * dx + dy * dy < radius * radius {
+                set(x, y, [220, 38, 38, 255]);
+            }
+        }
+    }
+
+    let glyphs: Vec<[u8; 5]> = label(count)
+        .chars()
+        .map(|c| c.to_digit(10).map_or(PLUS, |digit| DIGITS[digit as usize]))
+        .collect();
+    let scale = if glyphs.len() <= 2 { 3 } else { 2 };
+    let spacing = scale;
+    let width = glyphs.len() as i32 * (3 * scale + spacing) - spacing;
+    let (left, top) = ((size - width) / 2, (size - 5 * scale) / 2);
+
+    for (n, glyph) in glyphs.iter().enumerate() {
+        let glyph_left = left + n as i32 * (3 * scale + spacing);
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for py in 0..scale {
+                    for px in 0..scale {
+                        set(glyph_left + column * scale + px, top + row as i32 * scale + py, [255, 255, 255, 255]);
+                    }
+                }
+            }
+        }
+    }
+    pixels
+}
+
+/// Show a count on the main window's taskbar or dock icon (0 clears it)
+fn apply<R: Runtime>(app: &AppHandle<R>, count: i64) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    let result = window.set_overlay_icon(
+        (count > 0).then(|| tauri::image::Image::new_owned(render_overlay(count), OVERLAY_SIZE, OVERLAY_SIZE)),
+    );
+    #[cfg(not(target_os = "windows"))]
+    let result = window.set_badge_count((count > 0).then_some(count));
+
+    if let Err(e) = result {
+        log::warn!("Failed to update unread badge: {}", e);
+    }
+}
+
+/// Debounced badge updates in the background
+pub struct BadgeUpdater {
+    db: Arc<Database>,
+    wake: Arc<Notify>,
+    started: AtomicBool,
+}
+
+impl BadgeUpdater {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            wake: Arc::new(Notify::new()),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Redraw the badge once changes have settled
+    pub fn request(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Start the updater; store and setting changes request updates on their own
+    pub fn start<R: Runtime>(&self, app: AppHandle<R>, store_events: &StoreEvents, settings_events: &SettingsEvents) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let mut store_changes = store_events.subscribe();
+        let wake = self.wake.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) = store_changes.recv().await {
+                wake.notify_one();
+            }
+        });
+
+        let mut setting_changes = settings_events.subscribe();
+        let wake = self.wake.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match setting_changes.recv().await {
+                    Ok(changed) if !changed.changes.contains_key("notificationBadge") => {}
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => wake.notify_one(),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let db = self.db.clone();
+        let wake = self.wake.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut shown = None;
+            loop {
+                // Wait until requests stop arriving for a moment
+                loop {
+                    tokio::select! {
+                        _ = wake.notified() => {}
+                        _ = tokio::time::sleep(DEBOUNCE) => break,
+                    }
+                }
+
+                let count = if settings::get_bool(&db, "notification_badge") {
+                    match unread_count(&db) {
+                        Ok(count) => count,
+                        Err(e) => {
+                            log::warn!("Failed to count unread messages: {}", e);
+                            wake.notified().await;
+                            continue;
+                        }
+                    }
+                } else {
+                    0
+                };
+                if shown != Some(count) {
+                    apply(&app, count);
+                    shown = Some(count);
+                }
+                wake.notified().await;
+            }
+        });
+        self.request();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_and_overlay() {
+        assert_eq!(label(7), "7");
+        assert_eq!(label(99), "99");
+        assert_eq!(label(1500), "99+");
+
+        let pixels = render_overlay(8);
+        assert_eq!(pixels.len(), (OVERLAY_SIZE * OVERLAY_SIZE * 4) as usize);
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * OVERLAY_SIZE + x) * 4) as usize;
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+        };
+        // Transparent corner, red disc, white stroke through the middle of the "8"
+        assert_eq!(pixel(0, 0)[3], 0);
+        assert_eq!(pixel(16, 4), [220, 38, 38, 255]);
+        assert_eq!(pixel(16, 16), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_unread_count_empty() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        assert_eq!(unread_count(&db).unwrap(), 0);
+    }
+}
@@ -0,0 +1,11 @@
+//! Owlivion Mail headless CLI (built with `--features cli`)
+
+#[tokio::main]
+async fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    if let Err(e) = owlivion_mail_lib::cli::run(std::env::args().skip(1)).await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,286 @@
+//! Blocked Senders
+//!
+//! Blocking an address or a domain creates a filter that runs before every
+//! other filter and deletes the sender's mail or moves it to Junk. The
+//! blocklist keeps each pattern next to the filter it created, so unblocking
+//! removes both, while the filter itself can be inspected, edited or paused in
+//! the filter list like any other. A blocked domain covers its subdomains.
+
+use crate::db::{Database, DbError, DbResult};
+use crate::filters::{
+    ConditionField, ConditionOperator, FilterAction, FilterCondition, MatchLogic, NewEmailFilter,
+};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Priority of blocklist filters (lower runs first)
+pub const BLOCK_FILTER_PRIORITY: i32 = -1000;
+
+/// What happens to mail from a blocked sender
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum BlockAction {
+    Delete,
+    /// Move to Junk and mark as spam
+    Spam,
+}
+
+impl BlockAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            BlockAction::Delete => "delete",
+            BlockAction::Spam => "spam",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "delete" => BlockAction::Delete,
+            _ => BlockAction::Spam,
+        }
+    }
+}
+
+/// Entry of the blocklist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedSender {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    /// Lowercase address, or domain without '@'
+    pub pattern: String,
+    pub action: BlockAction,
+    /// Filter enforcing the block; `None` once the user deleted it
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub filter_id: Option<i64>,
+    pub created_at: String,
+}
+
+/// Lowercase address or domain from user input ("@example.com" is a domain)
+pub fn normalize(input: &str) -> Result<String, String> {
+    let pattern = input.trim().trim_start_matches('<').trim_end_matches('>').trim().to_lowercase();
+    let pattern = pattern.strip_prefix('@').unwrap_or(&pattern).to_string();
+
+    let valid_domain = |domain: &str| {
+        domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && domain.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+    };
+    let valid = match pattern.rsplit_once('@') {
+        Some((local, domain)) => !local.is_empty() && !local.contains(char::is_whitespace) && valid_domain(domain),
+        None => valid_domain(&pattern),
+    };
+    if !valid {
+        return Err(format!("Not an email address or domain: {}", input.trim()));
+    }
+    Ok(pattern)
+}
+
+fn is_address(pattern: &str) -> bool {
+    pattern.contains('@')
+}
+
+/// Filter that enforces a block
+fn filter_for(db: &Database, account_id: i64, pattern: &str, action: BlockAction) -> DbResult<NewEmailFilter> {
+    let condition = |operator, value: String| FilterCondition {
+        field: ConditionField::FromAddress,
+        operator,
+        value,
+    };
+    let conditions = if is_address(pattern) {
+        vec![condition(ConditionOperator::Equals, pattern.to_string())]
+    } else {
+        vec![
+            condition(ConditionOperator::EndsWith, format!("@{}", pattern)),
+            condition(ConditionOperator::EndsWith, format!(".{}", pattern)),
+        ]
+    };
+
+    let actions = match action {
+        BlockAction::Delete => vec![FilterAction::delete()],
+        BlockAction::Spam => {
+            let junk = db.query(
+                "SELECT id FROM folders WHERE account_id = ?1 AND folder_type = 'spam' AND is_local = 0
+                 ORDER BY id LIMIT 1",
+                [account_id],
+                |row| row.get::<_, i64>(0),
+            )?;
+            let mut actions: Vec<FilterAction> = junk.into_iter().map(FilterAction::move_to_folder).collect();
+            actions.push(FilterAction::mark_as_spam());
+            actions
+        }
+    };
+
+    Ok(NewEmailFilter {
+        account_id,
+        name: format!("Blocked: {}", pattern),
+        description: Some("Created by blocking this sender".to_string()),
+        is_enabled: true,
+        priority: BLOCK_FILTER_PRIORITY,
+        match_logic: MatchLogic::Any,
+        conditions,
+        actions,
+    })
+}
+
+fn blocked_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<BlockedSender> {
+    Ok(BlockedSender {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        pattern: row.get(2)?,
+        action: BlockAction::parse(&row.get::<_, String>(3)?),
+        filter_id: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+const BLOCKED_COLUMNS: &str = "id, account_id, pattern, action, filter_id, created_at";
+
+pub fn get(db: &Database, id: i64) -> DbResult<BlockedSender> {
+    db.query_row(
+        &format!("SELECT {} FROM blocked_senders WHERE id = ?1", BLOCKED_COLUMNS),
+        [id],
+        blocked_row,
+    )
+}
+
+pub fn list(db: &Database, account_id: i64) -> DbResult<Vec<BlockedSender>> {
+    db.query(
+        &format!("SELECT {} FROM blocked_senders WHERE account_id = ?1 ORDER BY pattern", BLOCKED_COLUMNS),
+        [account_id],
+        blocked_row,
+    )
+}
+
+/// Block a sender address or domain
+/// Blocking a pattern again replaces its filter with one for the new action.
+pub fn block(db: &Database, account_id: i64, input: &str, action: BlockAction) -> DbResult<BlockedSender> {
+    let pattern = normalize(input).map_err(DbError::Constraint)?;
+
+    let existing = db.query(
+        "SELECT filter_id FROM blocked_senders WHERE account_id = ?1 AND pattern = ?2",
+        params![account_id, pattern],
+        |row| row.get::<_, Option<i64>>(0),
+    )?;
+    if let Some(filter_id) = existing.into_iter().next().flatten() {
+        db.delete_filter(filter_id)?;
+    }
+
+    let filter_id = db.add_filter(&filter_for(db, account_id, &pattern, action)?)?;
+    db.execute(
+        "INSERT INTO blocked_senders (account_id, pattern, action, filter_id) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(account_id, pattern) DO UPDATE SET action = excluded.action, filter_id = excluded.filter_id",
+        params![account_id, pattern, action.as_str(), filter_id],
+    )?;
+    db.query_row(
+        &format!("SELECT {} FROM blocked_senders WHERE account_id = ?1 AND pattern = ?2", BLOCKED_COLUMNS),
+        params![account_id, pattern],
+        blocked_row,
+    )
+}
+
+/// Remove a blocklist entry and its filter
+pub fn unblock(db: &Database, id: i64) -> DbResult<()> {
+    let blocked = get(db, id)?;
+    if let Some(filter_id) = blocked.filter_id {
+        db.delete_filter(filter_id)?;
+    }
+    db.execute("DELETE FROM blocked_senders WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Existing mail of a blocked sender outside Junk and Trash
+pub fn matching_emails(db: &Database, blocked: &BlockedSender) -> DbResult<Vec<i64>> {
+    let sender_condition = if is_address(&blocked.pattern) {
+        "lower(trim(e.from_address)) = ?2"
+    } else {
+        "(lower(trim(e.from_address)) LIKE '%@' || ?2 OR lower(trim(e.from_address)) LIKE '%.' || ?2)"
+    };
+    db.query(
+        &format!(
+            "SELECT e.id FROM emails e
+             JOIN folders f ON f.id = e.folder_id
+             WHERE e.account_id = ?1 AND e.is_deleted = 0 AND f.folder_type NOT IN ('spam', 'trash')
+               AND {}
+             ORDER BY e.id",
+            sender_condition
+        ),
+        params![blocked.account_id, blocked.pattern],
+        |row| row.get(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewEmail, NewFolder};
+
+    fn add_email(db: &Database, account_id: i64, folder_id: i64, uid: u32, from: &str) -> i64 {
+        db.upsert_email(&NewEmail {
+            from_address: from.to_string(),
+            ..test_email(account_id, folder_id, uid)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" Spam@Example.COM ").unwrap(), "spam@example.com");
+        assert_eq!(normalize("<spam@example.com>").unwrap(), "spam@example.com");
+        assert_eq!(normalize("@Example.com").unwrap(), "example.com");
+        assert!(normalize("localhost").is_err());
+        assert!(normalize("a b@example.com").is_err());
+        assert!(normalize("@").is_err());
+    }
+
+    #[test]
+    fn test_block_creates_filter_and_finds_mail() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder = |name: &str, folder_type: &str| {
+            db.upsert_folder(&NewFolder {
+                account_id,
+                name: name.to_string(),
+                remote_name: name.to_string(),
+                folder_type: folder_type.to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap()
+        };
+        let inbox = folder("INBOX", "inbox");
+        let junk = folder("Junk", "spam");
+
+        let promo = add_email(&db, account_id, inbox, 1, "promo@shop.test");
+        let news = add_email(&db, account_id, inbox, 2, "news@mail.shop.test");
+        let ana = add_email(&db, account_id, inbox, 3, "ana@workshop.test");
+        add_email(&db, account_id, junk, 4, "old@shop.test");
+
+        let blocked = block(&db, account_id, "@Shop.test", BlockAction::Spam).unwrap();
+        assert_eq!(blocked.pattern, "shop.test");
+        assert_eq!(matching_emails(&db, &blocked).unwrap(), vec![promo, news]);
+
+        let filter = db.get_filter(blocked.filter_id.unwrap()).unwrap();
+        assert_eq!(filter.priority, BLOCK_FILTER_PRIORITY);
+        assert_eq!(filter.actions[0].folder_id, Some(junk));
+        let engine = crate::filters::FilterEngine::new(std::sync::Arc::new(db.clone()));
+        assert!(engine.test_filter(&filter, &db.get_email(news).unwrap()));
+        assert!(!engine.test_filter(&filter, &db.get_email(ana).unwrap()));
+
+        // Blocking again replaces the filter
+        let again = block(&db, account_id, "shop.test", BlockAction::Delete).unwrap();
+        assert_eq!(again.id, blocked.id);
+        assert!(db.get_filter(blocked.filter_id.unwrap()).is_err());
+        assert_eq!(list(&db, account_id).unwrap(), vec![again.clone()]);
+
+        unblock(&db, again.id).unwrap();
+        assert!(db.get_filter(again.filter_id.unwrap()).is_err());
+        assert!(list(&db, account_id).unwrap().is_empty());
+    }
+}
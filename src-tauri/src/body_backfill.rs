@@ -0,0 +1,292 @@
+//! Parallel Body Backfill
+//!
+//! After a folder's summaries are backfilled, the bodies of its cached
+//! messages are downloaded so they can be read and searched offline. An IMAP
+//! connection runs one command at a time, so the bodies are fetched by a
+//! bounded pool of workers, each on its own connection, all taking UIDs from
+//! one queue (newest first). Servers that refuse the extra connections simply
+//! get fewer workers. Bodies are fetched with BODY.PEEK[] and stay unread;
+//! messages that already have a body are skipped, so a stopped run resumes
+//! by starting again.
+
+use crate::db::{Database, DbResult};
+use crate::mail::{AsyncImapClient, MailResult};
+use async_trait::async_trait;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+
+/// Tauri event name for body backfill progress
+pub const BODY_PROGRESS_EVENT: &str = "backfill://bodies";
+
+/// Settings key holding `BodyBackfillSettings`
+const SETTINGS_KEY: &str = "body_backfill";
+
+/// Most connections a body backfill opens per folder
+const MAX_CONCURRENCY: usize = 8;
+
+/// Progress is reported after this many bodies
+const PROGRESS_EVERY: u32 = 25;
+
+/// Body backfill configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase", default)]
+pub struct BodyBackfillSettings {
+    pub enabled: bool,
+    /// Concurrent FETCHes (one IMAP connection each)
+    pub concurrency: usize,
+}
+
+impl Default for BodyBackfillSettings {
+    fn default() -> Self {
+        Self { enabled: true, concurrency: 4 }
+    }
+}
+
+impl BodyBackfillSettings {
+    /// Clamp values to what the pipeline supports
+    fn clamped(self) -> Self {
+        Self { concurrency: self.concurrency.clamp(1, MAX_CONCURRENCY), ..self }
+    }
+}
+
+pub fn load_settings(db: &Database) -> DbResult<BodyBackfillSettings> {
+    Ok(db.get_setting::<BodyBackfillSettings>(SETTINGS_KEY)?.unwrap_or_default().clamped())
+}
+
+/// Save settings; returns them as stored (clamped)
+pub fn save_settings(db: &Database, settings: BodyBackfillSettings) -> DbResult<BodyBackfillSettings> {
+    let settings = settings.clamped();
+    db.set_setting(SETTINGS_KEY, &settings)?;
+    Ok(settings)
+}
+
+/// Body backfill progress of one folder (payload of `backfill://bodies`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BodyBackfillProgress {
+    pub account_id: i64,
+    pub folder: String,
+    /// Cached messages without a body when the run started
+    pub total: u32,
+    pub fetched: u32,
+    pub failed: u32,
+    /// Connections that fetched bodies
+    pub connections: u32,
+    pub done: bool,
+}
+
+/// Server side of a body backfill (implemented by the IMAP client)
+#[async_trait]
+pub trait BodySource: Send {
+    /// Text and HTML body of a message, without marking it read
+    async fn fetch_body(&mut self, folder: &str, uid: u32) -> MailResult<(Option<String>, Option<String>)>;
+    async fn close(&mut self) {}
+}
+
+#[async_trait]
+impl BodySource for AsyncImapClient {
+    async fn fetch_body(&mut self, folder: &str, uid: u32) -> MailResult<(Option<String>, Option<String>)> {
+        let email = self.peek_email(folder, uid).await?;
+        Ok((email.body_text, email.body_html))
+    }
+
+    async fn close(&mut self) {
+        let _ = self.disconnect().await;
+    }
+}
+
+/// UIDs of cached messages without a body, newest first
+fn missing_bodies(db: &Database, folder_id: i64) -> DbResult<Vec<u32>> {
+    db.query(
+        "SELECT uid FROM emails WHERE folder_id = ?1 AND body_text IS NULL AND body_html IS NULL AND is_deleted = 0
+         ORDER BY uid DESC",
+        [folder_id],
+        |row| row.get(0),
+    )
+}
+
+fn store_body(db: &Database, folder_id: i64, uid: u32, text: Option<String>, html: Option<String>) -> DbResult<()> {
+    db.execute(
+        "UPDATE emails SET body_text = COALESCE(body_text, ?3), body_html = COALESCE(body_html, ?4)
+         WHERE folder_id = ?1 AND uid = ?2",
+        params![folder_id, uid, text, html],
+    )?;
+    Ok(())
+}
+
+/// Download the missing bodies of a folder over up to `concurrency` connections
+/// opened by `connect`. Stops early when `should_continue` turns false; fails
+/// only when no connection could be opened.
+#[allow(clippy::too_many_arguments)]
+pub async fn run<S, F, Fut, C, P>(
+    db: &Database,
+    account_id: i64,
+    folder: &str,
+    concurrency: usize,
+    connect: F,
+    should_continue: C,
+    on_progress: P,
+) -> Result<BodyBackfillProgress, String>
+where
+    S: BodySource,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<S, String>>,
+    C: Fn() -> bool,
+    P: Fn(&BodyBackfillProgress),
+{
+    let folder_id = crate::sync_folder_to_db(db, account_id, folder)?;
+    let missing = missing_bodies(db, folder_id).map_err(|e| e.to_string())?;
+
+    let progress = Mutex::new(BodyBackfillProgress {
+        account_id,
+        folder: folder.to_string(),
+        total: missing.len() as u32,
+        fetched: 0,
+        failed: 0,
+        connections: 0,
+        done: missing.is_empty(),
+    });
+    if missing.is_empty() {
+        let progress = progress.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+        on_progress(&progress);
+        return Ok(progress);
+    }
+
+    let workers = concurrency.clamp(1, MAX_CONCURRENCY).min(missing.len());
+    log::info!(
+        "Body backfill {} for account {}: {} bodies over {} connections",
+        folder, account_id, missing.len(), workers
+    );
+    let queue = Mutex::new(VecDeque::from(missing));
+    let last_error = Mutex::new(None::<String>);
+
+    let lock = |progress: &Mutex<BodyBackfillProgress>| {
+        progress.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    };
+    let runs = (0..workers).map(|_| {
+        let (connect, should_continue, on_progress) = (&connect, &should_continue, &on_progress);
+        let (queue, progress, last_error, lock) = (&queue, &progress, &last_error, &lock);
+        async move {
+            let mut source = match connect().await {
+                Ok(source) => source,
+                Err(e) => {
+                    // Likely over the server's connection limit; the others carry on
+                    log::debug!("Body backfill connection failed for account {}: {}", account_id, e);
+                    *last_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(e);
+                    return;
+                }
+            };
+            progress.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).connections += 1;
+
+            loop {
+                if !should_continue() {
+                    break;
+                }
+                let Some(uid) = queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).pop_front() else {
+                    break;
+                };
+                let stored = match source.fetch_body(folder, uid).await {
+                    Ok((text, html)) => store_body(db, folder_id, uid, text, html).map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+
+                let report = {
+                    let mut progress = progress.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    match stored {
+                        Ok(()) => progress.fetched += 1,
+                        Err(e) => {
+                            log::debug!("Body backfill of uid {} in {} failed: {}", uid, folder, e);
+                            progress.failed += 1;
+                        }
+                    }
+                    (progress.fetched + progress.failed) % PROGRESS_EVERY == 0
+                };
+                if report {
+                    on_progress(&lock(progress));
+                }
+            }
+            source.close().await;
+        }
+    });
+    futures::future::join_all(runs).await;
+
+    let mut progress = lock(&progress);
+    if progress.connections == 0 {
+        let error = last_error.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+        return Err(format!("Failed to connect: {}", error.unwrap_or_default()));
+    }
+    progress.done = queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).is_empty();
+    on_progress(&progress);
+    Ok(progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewEmail};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fetches take a moment, so workers overlap; UID 13 cannot be fetched
+    struct FakeSource<'a> {
+        in_flight: &'a AtomicUsize,
+        peak: &'a AtomicUsize,
+    }
+
+    #[async_trait]
+    impl BodySource for FakeSource<'_> {
+        async fn fetch_body(&mut self, _folder: &str, uid: u32) -> MailResult<(Option<String>, Option<String>)> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            if uid == 13 {
+                return Err(crate::mail::MailError::NotFound(format!("UID {}", uid)));
+            }
+            Ok((Some(format!("Body {}", uid)), None))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parallel_body_backfill() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = crate::sync_folder_to_db(&db, account_id, "INBOX").unwrap();
+        let emails: Vec<NewEmail> = (1..=40)
+            .map(|uid| NewEmail {
+                body_text: (uid == 1).then(|| "Already here".to_string()),
+                ..test_email(account_id, folder_id, uid)
+            })
+            .collect();
+        db.batch_upsert_emails(&emails).unwrap();
+
+        let (in_flight, peak, connects) = (AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0));
+        let connect = || {
+            // The server allows three connections
+            let refused = connects.fetch_add(1, Ordering::SeqCst) >= 3;
+            let source = FakeSource { in_flight: &in_flight, peak: &peak };
+            async move { if refused { Err("Too many connections".to_string()) } else { Ok(source) } }
+        };
+
+        let progress = run(&db, account_id, "INBOX", 4, connect, || true, |_| {}).await.unwrap();
+        assert!(progress.done);
+        assert_eq!((progress.total, progress.fetched, progress.failed), (39, 38, 1));
+        assert_eq!(progress.connections, 3);
+        assert!(peak.load(Ordering::SeqCst) > 1);
+
+        let body: Option<String> = db
+            .query_row("SELECT body_text FROM emails WHERE folder_id = ?1 AND uid = 40", [folder_id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(body.as_deref(), Some("Body 40"));
+        let body: Option<String> = db
+            .query_row("SELECT body_text FROM emails WHERE folder_id = ?1 AND uid = 1", [folder_id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(body.as_deref(), Some("Already here"));
+
+        // Only the failed message is left for the next run
+        assert_eq!(missing_bodies(&db, folder_id).unwrap(), vec![13]);
+    }
+}
@@ -0,0 +1,121 @@
+//! Bouncing Contacts
+//!
+//! When a delivery status notification is read (see `mail::bounce`), the
+//! contacts with a permanently failing address are marked with the time and
+//! the server's reason. The composer warns before writing to them again, and
+//! the marked contacts can be listed and removed from the address book.
+
+use crate::db::{Database, DbResult};
+use crate::mail::bounce::BouncedRecipient;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct BouncingContact {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    pub email: String,
+    pub name: Option<String>,
+    pub bounced_at: String,
+    pub bounce_reason: Option<String>,
+}
+
+/// Mark the account's (and global) contacts for the bounced addresses; returns the contacts marked
+pub fn record(db: &Database, account_id: i64, bounced: &[BouncedRecipient]) -> DbResult<usize> {
+    let mut marked = 0;
+    for recipient in bounced {
+        marked += db.execute(
+            "UPDATE contacts SET bounced_at = datetime('now'), bounce_reason = ?3, updated_at = datetime('now')
+             WHERE (account_id = ?1 OR account_id IS NULL) AND lower(email) = ?2 AND deleted = 0",
+            params![account_id, recipient.address, recipient.reason],
+        )?;
+    }
+    Ok(marked)
+}
+
+/// Contacts whose address bounced, most recent first
+pub fn list(db: &Database, account_id: i64) -> DbResult<Vec<BouncingContact>> {
+    db.query(
+        "SELECT id, email, name, bounced_at, bounce_reason FROM contacts
+         WHERE (account_id = ?1 OR account_id IS NULL) AND bounced_at IS NOT NULL AND deleted = 0
+         ORDER BY bounced_at DESC, id",
+        params![account_id],
+        |row| {
+            Ok(BouncingContact {
+                id: row.get(0)?,
+                email: row.get(1)?,
+                name: row.get(2)?,
+                bounced_at: row.get(3)?,
+                bounce_reason: row.get(4)?,
+            })
+        },
+    )
+}
+
+/// Lowercase bouncing addresses with their reason, for the pre-send check
+pub fn reasons(db: &Database, account_id: i64) -> DbResult<HashMap<String, String>> {
+    Ok(list(db, account_id)?
+        .into_iter()
+        .map(|contact| {
+            let reason = contact.bounce_reason.unwrap_or_else(|| "address does not exist".to_string());
+            (contact.email.to_lowercase(), reason)
+        })
+        .collect())
+}
+
+/// Remove bouncing contacts from the address book; contacts that did not bounce are kept
+pub fn remove(db: &Database, account_id: i64, contact_ids: &[i64]) -> DbResult<usize> {
+    let mut removed = 0;
+    for id in contact_ids {
+        removed += db.execute(
+            "UPDATE contacts SET deleted = 1, updated_at = datetime('now')
+             WHERE id = ?1 AND (account_id = ?2 OR account_id IS NULL) AND bounced_at IS NOT NULL",
+            params![id, account_id],
+        )?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    #[test]
+    fn test_mark_list_and_remove() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        for email in ["Bob@Example.org", "ana@example.org"] {
+            db.execute("INSERT INTO contacts (account_id, email) VALUES (?1, ?2)", params![account_id, email])
+                .unwrap();
+        }
+
+        let bounced = [
+            BouncedRecipient {
+                address: "bob@example.org".to_string(),
+                status: "5.1.1".to_string(),
+                reason: "550 User unknown".to_string(),
+            },
+            BouncedRecipient {
+                address: "nobody@example.org".to_string(),
+                status: "5.1.1".to_string(),
+                reason: "550 User unknown".to_string(),
+            },
+        ];
+        assert_eq!(record(&db, account_id, &bounced).unwrap(), 1);
+
+        let contacts = list(&db, account_id).unwrap();
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].email, "Bob@Example.org");
+        assert_eq!(reasons(&db, account_id).unwrap()["bob@example.org"], "550 User unknown");
+
+        let ana: i64 = db
+            .query_row("SELECT id FROM contacts WHERE email = 'ana@example.org'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remove(&db, account_id, &[contacts[0].id, ana]).unwrap(), 1);
+        assert!(list(&db, account_id).unwrap().is_empty());
+    }
+}
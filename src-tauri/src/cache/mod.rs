@@ -129,23 +129,29 @@ impl Default for EmailCache {
 
 /// Cache statistics
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 pub struct CacheStats {
     /// Number of cache hits
+    #[cfg_attr(test, ts(type = "number"))]
     pub hits: u64,
 
     /// Number of cache misses
+    #[cfg_attr(test, ts(type = "number"))]
     pub misses: u64,
 
     /// Total cache requests
+    #[cfg_attr(test, ts(type = "number"))]
     pub total_requests: u64,
 
     /// Cache hit rate (percentage)
     pub hit_rate: f64,
 
     /// Number of entries in cache
+    #[cfg_attr(test, ts(type = "number"))]
     pub entry_count: u64,
 
     /// Weighted size of cache
+    #[cfg_attr(test, ts(type = "number"))]
     pub weighted_size: u64,
 }
 
@@ -0,0 +1,133 @@
+//! Account Capabilities
+//!
+//! Per-account cache of what the servers support (see `mail::capabilities`).
+//! IMAP extensions are recorded whenever a connection logs in; SMTP
+//! extensions come from an EHLO probe, run when nothing is cached yet or the
+//! cached reply is older than `SMTP_PROBE_MAX_AGE_DAYS`. The send path checks
+//! the SMTP size limit before transmitting, so an oversized message fails
+//! at once instead of after uploading it.
+
+use crate::db::{Database, DbError, DbResult};
+use crate::mail::capabilities::{ImapCapabilities, SmtpCapabilities};
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Cached SMTP extensions older than this are probed again
+const SMTP_PROBE_MAX_AGE_DAYS: i64 = 7;
+
+/// Cached server extensions of an account
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AccountCapabilities {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    /// `None` until the account has connected once
+    pub imap: Option<ImapCapabilities>,
+    pub imap_probed_at: Option<String>,
+    /// `None` until the SMTP server has been probed
+    pub smtp: Option<SmtpCapabilities>,
+    pub smtp_probed_at: Option<String>,
+}
+
+impl AccountCapabilities {
+    /// Whether the SMTP extensions should be probed (again) at `now`
+    pub fn smtp_is_stale(&self, now: DateTime<Utc>) -> bool {
+        match self.smtp_probed_at.as_deref().and_then(|at| DateTime::parse_from_rfc3339(at).ok()) {
+            Some(at) if self.smtp.is_some() => now - at.with_timezone(&Utc) >= chrono::Duration::days(SMTP_PROBE_MAX_AGE_DAYS),
+            _ => true,
+        }
+    }
+}
+
+fn from_json<T: DeserializeOwned>(json: Option<String>) -> DbResult<Option<T>> {
+    json.map(|json| serde_json::from_str(&json).map_err(|e| DbError::Serialization(e.to_string())))
+        .transpose()
+}
+
+fn to_json<T: Serialize>(value: &T) -> DbResult<String> {
+    serde_json::to_string(value).map_err(|e| DbError::Serialization(e.to_string()))
+}
+
+pub fn get(db: &Database, account_id: i64) -> DbResult<AccountCapabilities> {
+    let rows = db.query(
+        "SELECT imap, imap_probed_at, smtp, smtp_probed_at FROM account_capabilities WHERE account_id = ?1",
+        params![account_id],
+        |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        },
+    )?;
+    let Some((imap, imap_probed_at, smtp, smtp_probed_at)) = rows.into_iter().next() else {
+        return Ok(AccountCapabilities { account_id, ..Default::default() });
+    };
+    Ok(AccountCapabilities {
+        account_id,
+        imap: from_json(imap)?,
+        imap_probed_at,
+        smtp: from_json(smtp)?,
+        smtp_probed_at,
+    })
+}
+
+/// Record the IMAP extensions seen at login (written only when they changed)
+pub fn store_imap(db: &Database, account_id: i64, imap: &ImapCapabilities) -> DbResult<()> {
+    db.execute(
+        "INSERT INTO account_capabilities (account_id, imap, imap_probed_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(account_id) DO UPDATE SET imap = excluded.imap, imap_probed_at = excluded.imap_probed_at
+         WHERE imap IS NOT excluded.imap",
+        params![account_id, to_json(imap)?, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Record the SMTP extensions from an EHLO probe
+pub fn store_smtp(db: &Database, account_id: i64, smtp: &SmtpCapabilities) -> DbResult<()> {
+    db.execute(
+        "INSERT INTO account_capabilities (account_id, smtp, smtp_probed_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(account_id) DO UPDATE SET smtp = excluded.smtp, smtp_probed_at = excluded.smtp_probed_at",
+        params![account_id, to_json(smtp)?, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    fn setup() -> (Database, i64) {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        (db, account_id)
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let (db, account_id) = setup();
+        let empty = get(&db, account_id).unwrap();
+        assert_eq!(empty, AccountCapabilities { account_id, ..Default::default() });
+        assert!(empty.smtp_is_stale(Utc::now()));
+
+        let imap = ImapCapabilities { move_: true, uidplus: true, ..Default::default() };
+        store_imap(&db, account_id, &imap).unwrap();
+        let smtp = SmtpCapabilities { size_limit: Some(10_000), pipelining: true, ..Default::default() };
+        store_smtp(&db, account_id, &smtp).unwrap();
+
+        let caps = get(&db, account_id).unwrap();
+        assert_eq!(caps.imap.as_ref(), Some(&imap));
+        assert_eq!(caps.smtp.as_ref(), Some(&smtp));
+        assert!(!caps.smtp_is_stale(Utc::now()));
+        assert!(caps.smtp_is_stale(Utc::now() + chrono::Duration::days(SMTP_PROBE_MAX_AGE_DAYS)));
+
+        // Unchanged extensions keep their original timestamp
+        db.execute("UPDATE account_capabilities SET imap_probed_at = 'then'", []).unwrap();
+        store_imap(&db, account_id, &imap).unwrap();
+        assert_eq!(get(&db, account_id).unwrap().imap_probed_at.as_deref(), Some("then"));
+    }
+}
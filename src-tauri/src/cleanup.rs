@@ -0,0 +1,259 @@
+//! Cleanup Suggestions
+//!
+//! Turns the mailbox statistics and the digest categories into a few
+//! concrete proposals ("You have 1,200 unread newsletters"), each with the
+//! messages it covers prepared as a batch. The batch is kept under an action
+//! id so the proposal can be carried out in one call without the frontend
+//! sending back the message list. Batches are replaced whenever suggestions
+//! are computed again. Starred messages and folders other than the Inbox
+//! and the user's own are never included.
+
+use crate::db::{Database, DbResult};
+use crate::digest::{categorize, DigestCategory};
+use crate::mailbox_stats::parse_date;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Messages above this size count as large
+const LARGE_MESSAGE_BYTES: i64 = 10 * 1024 * 1024;
+
+/// Promotions older than this are proposed for archiving
+const OLD_PROMOTION_DAYS: i64 = 30;
+
+/// Large messages older than this are proposed for deletion
+const OLD_LARGE_DAYS: i64 = 365;
+
+/// Fewer unread newsletters than this are not worth a suggestion
+const MIN_UNREAD_NEWSLETTERS: usize = 20;
+
+/// Fewer old promotions than this are not worth a suggestion
+const MIN_OLD_PROMOTIONS: usize = 20;
+
+fn setting(account_id: i64) -> String {
+    format!("cleanup_batches_{}", account_id)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub enum CleanupKind {
+    UnreadNewsletters,
+    OldPromotions,
+    LargeOldMessages,
+}
+
+/// What executing a suggestion does with its messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub enum CleanupAction {
+    /// Move to the account's Archive folder
+    Archive,
+    /// Move to Trash
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupSuggestion {
+    /// Pass to `cleanup_execute` to carry out the suggestion
+    pub action_id: String,
+    pub kind: CleanupKind,
+    pub action: CleanupAction,
+    pub title: String,
+    pub messages: usize,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total_size: i64,
+}
+
+/// Messages prepared for one suggestion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupBatch {
+    pub action_id: String,
+    pub action: CleanupAction,
+    pub email_ids: Vec<i64>,
+}
+
+struct Candidate {
+    id: i64,
+    from_address: String,
+    subject: String,
+    labels: String,
+    date: Option<DateTime<Utc>>,
+    size: i64,
+    is_read: bool,
+}
+
+/// "1200" as "1,200"
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn format_size(bytes: i64) -> String {
+    let mb = bytes as f64 / (1024.0 * 1024.0);
+    if mb >= 1024.0 {
+        format!("{:.1} GB", mb / 1024.0)
+    } else {
+        format!("{:.0} MB", mb)
+    }
+}
+
+/// Compute the suggestions for an account and store their batches
+pub fn suggestions(db: &Database, account_id: i64, now: DateTime<Utc>) -> DbResult<Vec<CleanupSuggestion>> {
+    let candidates = db.query(
+        "SELECT e.id, e.from_address, e.subject, e.labels, e.date, e.raw_size, e.is_read
+         FROM emails e JOIN folders f ON f.id = e.folder_id
+         WHERE e.account_id = ?1 AND e.is_deleted = 0 AND e.is_starred = 0
+           AND f.is_local = 0 AND f.folder_type IN ('inbox', 'custom')
+         ORDER BY e.id",
+        params![account_id],
+        |row| {
+            Ok(Candidate {
+                id: row.get(0)?,
+                from_address: row.get(1)?,
+                subject: row.get(2)?,
+                labels: row.get(3)?,
+                date: parse_date(&row.get::<_, String>(4)?),
+                size: row.get(5)?,
+                is_read: row.get(6)?,
+            })
+        },
+    )?;
+    let older_than = |email: &Candidate, days: i64| email.date.is_some_and(|date| (now - date).num_days() >= days);
+
+    let mut unread_newsletters = Vec::new();
+    let mut old_promotions = Vec::new();
+    let mut large_old = Vec::new();
+    for email in &candidates {
+        match categorize(&email.from_address, &email.subject, &email.labels) {
+            Some(DigestCategory::Newsletter) if !email.is_read => unread_newsletters.push(email),
+            Some(DigestCategory::Promotions) if older_than(email, OLD_PROMOTION_DAYS) => old_promotions.push(email),
+            _ => {}
+        }
+        if email.size > LARGE_MESSAGE_BYTES && older_than(email, OLD_LARGE_DAYS) {
+            large_old.push(email);
+        }
+    }
+
+    let mut batches = Vec::new();
+    let mut suggestions = Vec::new();
+    let mut suggest = |kind: CleanupKind, action: CleanupAction, emails: &[&Candidate], title: String| {
+        let action_id = uuid::Uuid::new_v4().to_string();
+        suggestions.push(CleanupSuggestion {
+            action_id: action_id.clone(),
+            kind,
+            action,
+            title,
+            messages: emails.len(),
+            total_size: emails.iter().map(|email| email.size).sum(),
+        });
+        batches.push(CleanupBatch { action_id, action, email_ids: emails.iter().map(|email| email.id).collect() });
+    };
+
+    if unread_newsletters.len() >= MIN_UNREAD_NEWSLETTERS {
+        let title = format!("You have {} unread newsletters — archive them", format_count(unread_newsletters.len()));
+        suggest(CleanupKind::UnreadNewsletters, CleanupAction::Archive, &unread_newsletters, title);
+    }
+    if old_promotions.len() >= MIN_OLD_PROMOTIONS {
+        let title = format!(
+            "Archive {} promotions older than {} days",
+            format_count(old_promotions.len()),
+            OLD_PROMOTION_DAYS
+        );
+        suggest(CleanupKind::OldPromotions, CleanupAction::Archive, &old_promotions, title);
+    }
+    if !large_old.is_empty() {
+        let size: i64 = large_old.iter().map(|email| email.size).sum();
+        let title = format!(
+            "Delete {} messages over {} older than a year to free {}",
+            format_count(large_old.len()),
+            format_size(LARGE_MESSAGE_BYTES),
+            format_size(size)
+        );
+        suggest(CleanupKind::LargeOldMessages, CleanupAction::Delete, &large_old, title);
+    }
+
+    db.set_setting(&setting(account_id), &batches)?;
+    Ok(suggestions)
+}
+
+/// A prepared batch, left in place (`None` if unknown or already executed)
+pub fn batch(db: &Database, account_id: i64, action_id: &str) -> DbResult<Option<CleanupBatch>> {
+    let batches: Vec<CleanupBatch> = db.get_setting(&setting(account_id))?.unwrap_or_default();
+    Ok(batches.into_iter().find(|batch| batch.action_id == action_id))
+}
+
+/// Remove and return a prepared batch (`None` if unknown or already executed)
+pub fn take_batch(db: &Database, account_id: i64, action_id: &str) -> DbResult<Option<CleanupBatch>> {
+    let mut batches: Vec<CleanupBatch> = db.get_setting(&setting(account_id))?.unwrap_or_default();
+    let Some(index) = batches.iter().position(|batch| batch.action_id == action_id) else {
+        return Ok(None);
+    };
+    let batch = batches.remove(index);
+    db.set_setting(&setting(account_id), &batches)?;
+    Ok(Some(batch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewEmail, NewFolder};
+
+    #[test]
+    fn test_suggestions_and_batches() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+        let add = |uid: u32, from: &str, date: &str, size: i32, is_starred: bool| {
+            db.upsert_email(&NewEmail {
+                from_address: from.to_string(),
+                date: date.to_string(),
+                is_starred,
+                raw_size: size,
+                ..test_email(account_id, folder_id, uid)
+            })
+            .unwrap()
+        };
+        for uid in 1..=25 {
+            add(uid, "newsletter@news.test", "2024-06-01T00:00:00Z", 20_000, false);
+        }
+        let large = add(100, "ana@test.com", "2022-01-01T00:00:00Z", 12 * 1024 * 1024, false);
+        add(101, "ana@test.com", "2022-01-01T00:00:00Z", 12 * 1024 * 1024, true);
+
+        let now = parse_date("2024-06-12T00:00:00Z").unwrap();
+        let found = suggestions(&db, account_id, now).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].kind, CleanupKind::UnreadNewsletters);
+        assert!(found[0].title.starts_with("You have 25 unread newsletters"));
+        assert_eq!(found[1].action, CleanupAction::Delete);
+
+        let peeked = batch(&db, account_id, &found[1].action_id).unwrap();
+        let batch = take_batch(&db, account_id, &found[1].action_id).unwrap().unwrap();
+        assert_eq!(peeked.as_ref(), Some(&batch));
+        assert_eq!(batch.email_ids, [large]);
+        assert_eq!(take_batch(&db, account_id, &found[1].action_id).unwrap(), None);
+        assert_eq!(format_count(1200), "1,200");
+        assert_eq!(format_count(999), "999");
+    }
+}
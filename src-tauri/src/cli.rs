@@ -0,0 +1,410 @@
+//! Headless CLI
+//!
+//! Scripting and debugging entry point behind the `cli` feature
+//! (`owlivion-mail-cli`). It opens the same database as the desktop app and
+//! reuses its IMAP/SMTP code paths, so cron jobs can sync or send mail and bugs
+//! can be reproduced without the Tauri UI.
+
+use crate::credentials::CredentialCache;
+use crate::db::{Account, Database};
+use crate::events::StoreEvents;
+use crate::mail::{self, AsyncImapClient, MailError, MailResult};
+use crate::pending_ops::{self, PendingOpKind, PendingOperation, QueuedSend, RemoteExecutor};
+use crate::profiles::Profiles;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: owlivion-mail-cli [--db PATH | --profile ID] <command> [options]
+
+Commands:
+  accounts                                  List configured accounts
+  sync    [--account A] [--folder F]        Send queued operations and fetch missing mail (default folder: INBOX)
+  list    [--account A] [--folder F] [--limit N]
+                                            List the newest messages on the server
+  send    [--account A] --to ADDR... [--cc ADDR...] [--bcc ADDR...] --subject S
+          [--body TEXT | --body-file PATH] [--html]
+                                            Send a message (body from stdin if not given)
+  export-mbox [--account A] --folder F --output PATH
+                                            Export a server or local folder as mbox
+
+Accounts are selected by address or id; the default account is used when omitted.
+Without --db, the database of the given profile (or the last one the app used) is opened.";
+
+/// Parsed command line: a command, its options and positional arguments
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub command: String,
+    options: HashMap<String, Vec<String>>,
+    flags: Vec<String>,
+}
+
+/// Options that take no value
+const FLAGS: [&str; 2] = ["html", "help"];
+
+impl Args {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if FLAGS.contains(&name) => parsed.flags.push(name.to_string()),
+                Some(name) => {
+                    let (name, value) = match name.split_once('=') {
+                        Some((name, value)) => (name.to_string(), value.to_string()),
+                        None => (name.to_string(), args.next().ok_or_else(|| format!("Missing value for --{}", name))?),
+                    };
+                    parsed.options.entry(name).or_default().push(value);
+                }
+                None if parsed.command.is_empty() => parsed.command = arg,
+                None => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).and_then(|values| values.last()).map(String::as_str)
+    }
+
+    /// All values of a repeatable option; comma-separated lists are split
+    pub fn all(&self, name: &str) -> Vec<String> {
+        self.options
+            .get(name)
+            .into_iter()
+            .flatten()
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn require(&self, name: &str) -> Result<&str, String> {
+        self.get(name).ok_or_else(|| format!("--{} is required", name))
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+}
+
+/// Run a CLI invocation (without the program name)
+pub async fn run<I: IntoIterator<Item = String>>(args: I) -> Result<(), String> {
+    let args = Args::parse(args)?;
+
+    if args.command.is_empty() || args.command == "help" || args.flag("help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let db_path = match args.get("db") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let profiles = Profiles::open_default()?;
+            profiles.database_path(&profiles.resolve(args.get("profile"))?)?
+        }
+    };
+    let db = Database::new(db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+    let credentials = CredentialCache::new();
+
+    match args.command.as_str() {
+        "accounts" => accounts(&db),
+        "sync" => sync(&db, &credentials, &args).await,
+        "list" => list(&db, &credentials, &args).await,
+        "send" => send(&db, &credentials, &args).await,
+        "export-mbox" => export_mbox(&db, &credentials, &args).await,
+        other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    }
+}
+
+/// Account by address or id, or the default account
+fn resolve_account(db: &Database, selector: Option<&str>) -> Result<Account, String> {
+    let accounts = db.get_accounts().map_err(|e| format!("Failed to load accounts: {}", e))?;
+
+    let account = match selector {
+        Some(selector) => accounts
+            .into_iter()
+            .find(|a| a.email.eq_ignore_ascii_case(selector) || a.id.to_string() == selector),
+        None => {
+            let default = accounts.iter().position(|a| a.is_default).unwrap_or(0);
+            accounts.into_iter().nth(default)
+        }
+    };
+
+    account.ok_or_else(|| match selector {
+        Some(selector) => format!("No account matches '{}'", selector),
+        None => "No accounts configured".to_string(),
+    })
+}
+
+fn accounts(db: &Database) -> Result<(), String> {
+    for account in db.get_accounts().map_err(|e| format!("Failed to load accounts: {}", e))? {
+        println!(
+            "{}\t{}\t{}{}",
+            account.id,
+            account.email,
+            account.display_name,
+            if account.is_default { "\t(default)" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+/// Executes queued operations over fresh connections (no shared sessions here)
+struct CliExecutor<'a> {
+    db: &'a Database,
+    credentials: &'a CredentialCache,
+}
+
+#[async_trait::async_trait]
+impl RemoteExecutor for CliExecutor<'_> {
+    async fn execute(&self, op: &PendingOperation) -> MailResult<()> {
+        let account_id: i64 = op.account_id.parse()
+            .map_err(|_| MailError::Config("Invalid account ID".to_string()))?;
+
+        if let PendingOpKind::Send { message } = &op.kind {
+            return crate::deliver_email(self.db, self.credentials, account_id, message, Some(&op.id)).await;
+        }
+
+        let mut client = crate::connect_fresh_imap(self.db, self.credentials, account_id).await.map_err(MailError::Connection)?;
+        let result = match &op.kind {
+            PendingOpKind::SetRead { value } => client.set_read(&op.folder, op.uid, *value).await,
+            PendingOpKind::SetStarred { value } => client.set_starred(&op.folder, op.uid, *value).await,
+            PendingOpKind::SetKeyword { keyword, value } => client.set_keyword(&op.folder, op.uid, keyword, *value).await,
+            PendingOpKind::Move { target } => client.move_email(&op.folder, op.uid, target).await.and_then(mail::MoveReport::into_result),
+            PendingOpKind::Delete { permanent } => client.delete_email(&op.folder, op.uid, *permanent).await,
+            PendingOpKind::Send { .. } => unreachable!("sends are handled above"),
+        };
+        let _ = client.disconnect().await;
+        result
+    }
+}
+
+async fn sync(db: &Database, credentials: &CredentialCache, args: &Args) -> Result<(), String> {
+    let queued = pending_ops::load_queue(db).map_err(|e| e.to_string())?.len();
+    if queued > 0 {
+        pending_ops::replay(db, &StoreEvents::new(), &CliExecutor { db, credentials })
+            .await
+            .map_err(|e| format!("Failed to replay queued operations: {}", e))?;
+        let left = pending_ops::load_queue(db).map_err(|e| e.to_string())?.len();
+        println!("Queued operations: {} sent, {} still pending", queued.saturating_sub(left), left);
+    }
+
+    let accounts = match args.get("account") {
+        Some(selector) => vec![resolve_account(db, Some(selector))?],
+        None => db.get_accounts().map_err(|e| format!("Failed to load accounts: {}", e))?,
+    };
+    let folder = args.get("folder").unwrap_or("INBOX");
+    let today = chrono::Local::now().date_naive();
+
+    let mut failed = 0;
+    for account in accounts.iter().filter(|a| a.is_active) {
+        let since = crate::backfill::window_start(account.sync_days, today);
+
+        let result = async {
+            let mut client = crate::connect_fresh_imap(db, credentials, account.id).await?;
+            let state = crate::backfill::run(db, &mut client, account.id, folder, since, || true, |_| {}).await;
+            let _ = client.disconnect().await;
+            state
+        }
+        .await;
+
+        match result {
+            Ok(state) => println!("{}\t{}\t{}/{} messages cached", account.email, folder, state.fetched, state.total),
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}\t{}\tfailed: {}", account.email, folder, e);
+            }
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} account(s) failed to sync", n)),
+    }
+}
+
+async fn list(db: &Database, credentials: &CredentialCache, args: &Args) -> Result<(), String> {
+    let account = resolve_account(db, args.get("account"))?;
+    let folder = args.get("folder").unwrap_or("INBOX");
+    let limit: u32 = match args.get("limit") {
+        Some(limit) => limit.parse().map_err(|_| format!("Invalid --limit: {}", limit))?,
+        None => 20,
+    };
+
+    let mut client = crate::connect_fresh_imap(db, credentials, account.id).await?;
+    let result = client.fetch_emails(folder, 0, limit).await;
+    let _ = client.disconnect().await;
+    let page = result.map_err(|e| format!("Failed to list {}: {}", folder, e))?;
+
+    for email in &page.emails {
+        let flags = format!(
+            "{}{}{}",
+            if email.is_read { '-' } else { 'N' },
+            if email.is_starred { '*' } else { '-' },
+            if email.has_attachments { '@' } else { '-' }
+        );
+        println!("{}\t{}\t{}\t{}\t{}", email.uid, flags, email.date, email.from, email.subject);
+    }
+    eprintln!("{} of {} messages in {}", page.emails.len(), page.total, folder);
+    Ok(())
+}
+
+async fn send(db: &Database, credentials: &CredentialCache, args: &Args) -> Result<(), String> {
+    let account = resolve_account(db, args.get("account"))?;
+
+    let (to, cc, bcc) = (args.all("to"), args.all("cc"), args.all("bcc"));
+    if to.is_empty() {
+        return Err("--to is required".to_string());
+    }
+    for address in to.iter().chain(&cc).chain(&bcc) {
+        crate::validate_email(address)?;
+    }
+
+    let body = match (args.get("body"), args.get("body-file")) {
+        (Some(body), _) => body.to_string(),
+        (None, Some(path)) => std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?,
+        (None, None) => std::io::read_to_string(std::io::stdin()).map_err(|e| format!("Failed to read stdin: {}", e))?,
+    };
+    let html = args.flag("html");
+
+    let mut message = QueuedSend {
+        to,
+        cc,
+        bcc,
+        subject: args.require("subject")?.to_string(),
+        text_body: (!html).then(|| body.clone()),
+        html_body: html.then_some(body),
+        attachment_paths: Vec::new(),
+        on_behalf_of: None,
+    };
+
+    crate::plugins::registry().before_send(&account, &mut message).await?;
+
+    crate::deliver_email(db, credentials, account.id, &message, None)
+        .await
+        .map_err(|e| format!("Failed to send: {}", e))?;
+    println!("Sent from {} to {}", account.email, message.to.join(", "));
+    Ok(())
+}
+
+async fn export_mbox(db: &Database, credentials: &CredentialCache, args: &Args) -> Result<(), String> {
+    let account = resolve_account(db, args.get("account"))?;
+    let folder = args.require("folder")?;
+    let output = args.require("output")?;
+
+    let local = db
+        .get_folders(account.id)
+        .map_err(|e| format!("Failed to load folders: {}", e))?
+        .into_iter()
+        .find(|f| f.is_local && (f.remote_name == folder || f.name == folder));
+
+    let sources = match local {
+        Some(local) => db
+            .get_local_folder_sources(local.id)
+            .map_err(|e| format!("Failed to read folder: {}", e))?,
+        None => {
+            let mut client = crate::connect_fresh_imap(db, credentials, account.id).await?;
+            let result = fetch_sources(&mut client, folder).await;
+            let _ = client.disconnect().await;
+            result.map_err(|e| format!("Failed to export {}: {}", folder, e))?
+        }
+    };
+
+    let mut mbox = Vec::new();
+    for (sender, date, raw) in &sources {
+        mail::mbox::append_message(&mut mbox, sender, date, raw);
+    }
+    std::fs::write(output, mbox).map_err(|e| format!("Failed to write {}: {}", output, e))?;
+
+    println!("Exported {} messages from {} to {}", sources.len(), folder, output);
+    Ok(())
+}
+
+/// (sender, date, raw source) of every message in a server folder, oldest first
+async fn fetch_sources(client: &mut AsyncImapClient, folder: &str) -> MailResult<Vec<(String, String, Vec<u8>)>> {
+    let mut uids = client.search_since(folder, None).await?;
+    uids.sort_unstable();
+
+    let mut sources = Vec::with_capacity(uids.len());
+    for uid in uids {
+        let raw = client.fetch_raw(folder, uid).await?;
+        let parsed = mail_parser::MessageParser::default().parse(&raw);
+        let sender = parsed
+            .as_ref()
+            .and_then(|m| m.from())
+            .and_then(|from| from.first())
+            .and_then(|addr| addr.address())
+            .unwrap_or_default()
+            .to_string();
+        let date = parsed
+            .as_ref()
+            .and_then(|m| m.date())
+            .map(|date| date.to_rfc3339())
+            .unwrap_or_default();
+        sources.push((sender, date, raw));
+    }
+    Ok(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&["send", "--to", "a@x.com,b@x.com", "--to=c@x.com", "--subject", "Hi", "--html"]).unwrap();
+        assert_eq!(args.command, "send");
+        assert_eq!(args.all("to"), vec!["a@x.com", "b@x.com", "c@x.com"]);
+        assert_eq!(args.get("subject"), Some("Hi"));
+        assert!(args.flag("html"));
+        assert!(args.all("cc").is_empty());
+        assert!(args.require("body").is_err());
+
+        assert!(parse(&["list", "--folder"]).is_err());
+        assert!(parse(&["list", "extra"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_mbox_from_server() {
+        use crate::test_util::{sample_message, MockImapServer};
+
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("cli.db");
+        let output = dir.path().join("inbox.mbox");
+
+        let server = MockImapServer::start("cli@mock.test", "secret").await.unwrap();
+        server.append("INBOX", &sample_message("a@mock.test", "cli@mock.test", "One", "From the start"), &[]);
+        server.append("INBOX", &sample_message("b@mock.test", "cli@mock.test", "Two", "Second"), &[]);
+
+        let db = Database::new(db_path.clone()).unwrap();
+        db.add_account(&crate::db::NewAccount {
+            imap_host: "127.0.0.1".to_string(),
+            imap_port: i32::from(server.port()),
+            imap_security: "NONE".to_string(),
+            smtp_host: "127.0.0.1".to_string(),
+            smtp_port: 25,
+            smtp_security: "NONE".to_string(),
+            password_encrypted: Some(crate::crypto::encrypt_password("secret").unwrap()),
+            sync_days: 0,
+            ..crate::db::test_new_account("cli@mock.test")
+        })
+        .unwrap();
+        drop(db);
+
+        let args = ["--db", db_path.to_str().unwrap(), "export-mbox", "--folder", "INBOX", "--output", output.to_str().unwrap()];
+        run(args.iter().map(|arg| arg.to_string())).await.unwrap();
+
+        let mbox = std::fs::read_to_string(&output).unwrap();
+        assert!(mbox.starts_with("From a@mock.test "));
+        assert!(mbox.contains("\nFrom b@mock.test "));
+        assert!(mbox.contains("\n>From the start\n"));
+    }
+}
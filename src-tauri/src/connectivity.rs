@@ -0,0 +1,210 @@
+//! Connectivity & Sync Control
+//!
+//! Tracks whether the OS reports the network as online, which accounts the
+//! user paused (metered connection, vacation) and which accounts are disabled.
+//! Background IMAP/SMTP work checks
+//! `may_sync` before touching the network, so nothing is attempted while offline
+//! and queued work resumes on its own once the network (or account) is back.
+//! On a metered network regular sync continues, but optional traffic (such as
+//! read-ahead prefetching) is skipped.
+
+use crate::db::{Database, DbResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Tauri event name emitted when the network state, a sync pause or a disabled account changes
+pub const CONNECTIVITY_CHANGED_EVENT: &str = "connectivity://changed";
+
+/// Payload of the `connectivity://changed` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityStatus {
+    pub online: bool,
+    pub metered: bool,
+    #[cfg_attr(test, ts(type = "Array<number>"))]
+    pub paused_accounts: Vec<i64>,
+    #[cfg_attr(test, ts(type = "Array<number>"))]
+    pub disabled_accounts: Vec<i64>,
+}
+
+/// Network state, per-account sync pauses and disabled accounts
+pub struct Connectivity {
+    online: AtomicBool,
+    metered: AtomicBool,
+    paused: Mutex<BTreeSet<i64>>,
+    disabled: Mutex<BTreeSet<i64>>,
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Connectivity {
+    /// Assume online until the OS says otherwise
+    pub fn new() -> Self {
+        Self {
+            online: AtomicBool::new(true),
+            metered: AtomicBool::new(false),
+            paused: Mutex::new(BTreeSet::new()),
+            disabled: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Load persisted sync pauses and disabled accounts
+    pub fn load(&self, db: &Database) -> DbResult<()> {
+        let ids = db.get_sync_paused_account_ids()?;
+        *self.paused.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = ids.into_iter().collect();
+        let ids = db.get_inactive_account_ids()?;
+        *self.disabled.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = ids.into_iter().collect();
+        Ok(())
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::SeqCst)
+    }
+
+    /// Record the OS network state; returns true when it changed
+    pub fn set_online(&self, online: bool) -> bool {
+        self.online.swap(online, Ordering::SeqCst) != online
+    }
+
+    pub fn is_metered(&self) -> bool {
+        self.metered.load(Ordering::SeqCst)
+    }
+
+    /// Record whether the network is metered; returns true when it changed
+    pub fn set_metered(&self, metered: bool) -> bool {
+        self.metered.swap(metered, Ordering::SeqCst) != metered
+    }
+
+    pub fn is_paused(&self, account_id: i64) -> bool {
+        self.paused
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(&account_id)
+    }
+
+    /// Pause or resume background sync for an account (persisted)
+    pub fn set_paused(&self, db: &Database, account_id: i64, paused: bool) -> DbResult<()> {
+        db.set_account_sync_paused(account_id, paused)?;
+
+        let mut guard = self.paused.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if paused {
+            guard.insert(account_id);
+        } else {
+            guard.remove(&account_id);
+        }
+        Ok(())
+    }
+
+    pub fn is_disabled(&self, account_id: i64) -> bool {
+        self.disabled
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(&account_id)
+    }
+
+    /// Disable or re-enable an account (persisted)
+    pub fn set_active(&self, db: &Database, account_id: i64, active: bool) -> DbResult<()> {
+        db.set_account_active(account_id, active)?;
+
+        let mut guard = self.disabled.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if active {
+            guard.remove(&account_id);
+        } else {
+            guard.insert(account_id);
+        }
+        Ok(())
+    }
+
+    /// Whether background IMAP/SMTP work may run for an account right now
+    pub fn may_sync(&self, account_id: i64) -> bool {
+        self.is_online() && !self.is_disabled(account_id) && !self.is_paused(account_id)
+    }
+
+    /// Reason background work is held back for an account (if it is)
+    pub fn blocked_reason(&self, account_id: i64) -> Option<&'static str> {
+        if !self.is_online() {
+            Some("Offline")
+        } else if self.is_disabled(account_id) {
+            Some("Account disabled")
+        } else if self.is_paused(account_id) {
+            Some("Sync paused")
+        } else {
+            None
+        }
+    }
+
+    pub fn status(&self) -> ConnectivityStatus {
+        ConnectivityStatus {
+            online: self.is_online(),
+            metered: self.is_metered(),
+            paused_accounts: self
+                .paused
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .iter()
+                .copied()
+                .collect(),
+            disabled_accounts: self
+                .disabled
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .iter()
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    #[test]
+    fn test_pause_and_offline_block_sync() {
+        let db = Database::in_memory().expect("Failed to create database");
+        let account_id = test_account(&db);
+
+        let connectivity = Connectivity::new();
+        assert!(connectivity.may_sync(account_id));
+
+        connectivity.set_paused(&db, account_id, true).unwrap();
+        assert_eq!(connectivity.blocked_reason(account_id), Some("Sync paused"));
+        assert!(connectivity.set_paused(&db, account_id + 1, true).is_err());
+
+        // Pauses survive a restart
+        let reloaded = Connectivity::new();
+        reloaded.load(&db).unwrap();
+        assert_eq!(reloaded.status().paused_accounts, vec![account_id]);
+
+        reloaded.set_paused(&db, account_id, false).unwrap();
+        assert!(reloaded.may_sync(account_id));
+
+        assert!(reloaded.set_online(false));
+        assert!(!reloaded.set_online(false));
+        assert_eq!(reloaded.blocked_reason(account_id), Some("Offline"));
+        assert!(reloaded.set_online(true));
+        assert!(reloaded.may_sync(account_id));
+
+        // Metered networks still sync
+        assert!(reloaded.set_metered(true));
+        assert!(reloaded.may_sync(account_id) && reloaded.status().metered);
+
+        // Disabled accounts stay blocked across restarts until enabled again
+        reloaded.set_active(&db, account_id, false).unwrap();
+        assert_eq!(reloaded.blocked_reason(account_id), Some("Account disabled"));
+        let restarted = Connectivity::new();
+        restarted.load(&db).unwrap();
+        assert_eq!(restarted.status().disabled_accounts, vec![account_id]);
+        assert!(!db.get_account(account_id).unwrap().is_active);
+        restarted.set_active(&db, account_id, true).unwrap();
+        assert!(restarted.may_sync(account_id));
+    }
+}
@@ -0,0 +1,389 @@
+//! Credential Cache
+//!
+//! Decrypted account passwords (or OAuth access tokens) kept in memory for a
+//! short time, so hot paths such as opening a message or downloading an
+//! attachment skip the database read and the AES-GCM decryption. Entries
+//! expire after `CREDENTIAL_TTL`, are dropped when an account's secret
+//! changes, and the whole cache is cleared when the app is locked or exits.
+//! Cached secrets are zeroized when they are dropped.
+//!
+//! Sign-in outcomes are tracked here too: after `MAX_AUTH_FAILURES`
+//! consecutive authentication failures an account needs attention, and its
+//! secret is no longer handed out (so background work stops hammering the
+//! server) until the user enters a new password.
+
+use crate::db::{Database, DbError, DbResult};
+use crate::mail::{MailError, MailResult};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// How long a decrypted secret stays cached
+pub const CREDENTIAL_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// How often expired entries are swept out
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Event emitted when an account needs a new password
+pub const NEEDS_ATTENTION_EVENT: &str = "account://needs-attention";
+
+/// Consecutive authentication failures before sign-ins stop
+pub const MAX_AUTH_FAILURES: i64 = 3;
+
+/// Sign-in failure state of an account
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "AccountAuthState"))]
+#[serde(rename_all = "camelCase")]
+pub struct AuthState {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub consecutive_failures: i64,
+    pub last_error: Option<String>,
+    pub last_failure_at: Option<String>,
+    /// Sign-ins stopped until a new password is entered
+    pub needs_attention: bool,
+}
+
+pub fn auth_state(db: &Database, account_id: i64) -> DbResult<AuthState> {
+    let rows = db.query(
+        "SELECT consecutive_failures, last_error, last_failure_at, needs_attention
+         FROM account_auth_state WHERE account_id = ?1",
+        params![account_id],
+        |row| {
+            Ok(AuthState {
+                account_id,
+                consecutive_failures: row.get(0)?,
+                last_error: row.get(1)?,
+                last_failure_at: row.get(2)?,
+                needs_attention: row.get(3)?,
+            })
+        },
+    )?;
+    Ok(rows.into_iter().next().unwrap_or(AuthState { account_id, ..Default::default() }))
+}
+
+#[derive(Debug)]
+pub enum CredentialError {
+    Database(DbError),
+    Missing,
+    Decryption(String),
+    /// Too many failed sign-ins; waiting for a new password
+    NeedsAttention,
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialError::Database(e) => write!(f, "Failed to get password: {}", e),
+            CredentialError::Missing => write!(f, "No password found for account"),
+            CredentialError::Decryption(e) => write!(f, "Password decryption failed: {}", e),
+            CredentialError::NeedsAttention => {
+                write!(f, "Sign-in failed repeatedly; enter a new password for this account")
+            }
+        }
+    }
+}
+
+struct Entry {
+    secret: Zeroizing<String>,
+    expires_at: Instant,
+}
+
+type Entries = Arc<Mutex<HashMap<i64, Entry>>>;
+
+type AttentionCallback = Box<dyn Fn(AuthState) + Send + Sync>;
+
+/// Decrypted secrets by account id
+pub struct CredentialCache {
+    entries: Entries,
+    ttl: Duration,
+    /// Accounts that need a new password
+    needs_attention: Mutex<BTreeSet<i64>>,
+    on_needs_attention: OnceLock<AttentionCallback>,
+    started: AtomicBool,
+}
+
+impl Default for CredentialCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CredentialCache {
+    pub fn new() -> Self {
+        Self::with_ttl(CREDENTIAL_TTL)
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+            needs_attention: Mutex::new(BTreeSet::new()),
+            on_needs_attention: OnceLock::new(),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Load the accounts that need attention
+    pub fn load(&self, db: &Database) -> DbResult<()> {
+        let ids = db.query(
+            "SELECT account_id FROM account_auth_state WHERE needs_attention = 1",
+            [],
+            |row| row.get::<_, i64>(0),
+        )?;
+        *self.needs_attention.lock().unwrap_or_else(|e| e.into_inner()) = ids.into_iter().collect();
+        Ok(())
+    }
+
+    pub fn needs_attention(&self, account_id: i64) -> bool {
+        self.needs_attention
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(&account_id)
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<i64, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Decrypted password of an account, from the cache or the database
+    pub fn password(&self, db: &Database, account_id: i64) -> Result<Zeroizing<String>, CredentialError> {
+        if self.needs_attention(account_id) {
+            return Err(CredentialError::NeedsAttention);
+        }
+        if let Some(secret) = self.get(account_id) {
+            return Ok(secret);
+        }
+
+        let encrypted = db
+            .get_account_password(account_id)
+            .map_err(CredentialError::Database)?
+            .ok_or(CredentialError::Missing)?;
+        let secret = Zeroizing::new(crate::crypto::decrypt_password(&encrypted).map_err(CredentialError::Decryption)?);
+        self.insert(account_id, &secret);
+        Ok(secret)
+    }
+
+    fn get(&self, account_id: i64) -> Option<Zeroizing<String>> {
+        let mut entries = self.entries();
+        match entries.get(&account_id) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.secret.clone()),
+            Some(_) => {
+                entries.remove(&account_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Cache a secret just written for an account (e.g. a refreshed token)
+    pub fn insert(&self, account_id: i64, secret: &str) {
+        self.entries().insert(
+            account_id,
+            Entry {
+                secret: Zeroizing::new(secret.to_string()),
+                expires_at: Instant::now() + self.ttl,
+            },
+        );
+    }
+
+    /// Forget an account's secret (changed or removed)
+    pub fn invalidate(&self, account_id: i64) {
+        self.entries().remove(&account_id);
+    }
+
+    /// Record the outcome of a sign-in (IMAP login or SMTP send)
+    /// Only authentication errors count as failures; a success resets the count.
+    pub fn record_login<T>(&self, db: &Database, account_id: i64, result: &MailResult<T>) {
+        let recorded = match result {
+            Ok(_) => db
+                .execute("DELETE FROM account_auth_state WHERE account_id = ?1", params![account_id])
+                .map(|_| ()),
+            Err(MailError::Authentication(error)) => self.record_failure(db, account_id, error),
+            Err(_) => Ok(()),
+        };
+        if let Err(e) = recorded {
+            log::warn!("Failed to record sign-in result for account {}: {}", account_id, e);
+        }
+    }
+
+    fn record_failure(&self, db: &Database, account_id: i64, error: &str) -> DbResult<()> {
+        // The stored secret was likely rejected; read it again next time
+        self.invalidate(account_id);
+
+        let state = db.query_row(
+            "INSERT INTO account_auth_state (account_id, consecutive_failures, last_error, last_failure_at, needs_attention)
+             VALUES (?1, 1, ?2, datetime('now'), ?3 <= 1)
+             ON CONFLICT(account_id) DO UPDATE SET
+                 consecutive_failures = consecutive_failures + 1,
+                 last_error = excluded.last_error,
+                 last_failure_at = excluded.last_failure_at,
+                 needs_attention = consecutive_failures + 1 >= ?3
+             RETURNING consecutive_failures, last_error, last_failure_at, needs_attention",
+            params![account_id, error, MAX_AUTH_FAILURES],
+            |row| {
+                Ok(AuthState {
+                    account_id,
+                    consecutive_failures: row.get(0)?,
+                    last_error: row.get(1)?,
+                    last_failure_at: row.get(2)?,
+                    needs_attention: row.get(3)?,
+                })
+            },
+        )?;
+        log::warn!(
+            "Authentication failed for account {} ({} in a row): {}",
+            account_id,
+            state.consecutive_failures,
+            error
+        );
+
+        if state.needs_attention {
+            let newly = self.needs_attention.lock().unwrap_or_else(|e| e.into_inner()).insert(account_id);
+            if newly {
+                log::warn!("Account {} needs a new password; sign-ins stopped", account_id);
+                if let Some(callback) = self.on_needs_attention.get() {
+                    callback(state);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Clear the failure state (new password entered) and allow sign-ins again
+    pub fn reset(&self, db: &Database, account_id: i64) -> DbResult<()> {
+        self.invalidate(account_id);
+        db.execute("DELETE FROM account_auth_state WHERE account_id = ?1", params![account_id])?;
+        self.needs_attention.lock().unwrap_or_else(|e| e.into_inner()).remove(&account_id);
+        Ok(())
+    }
+
+    /// Forget every secret
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn purge_expired(entries: &Entries) {
+        let now = Instant::now();
+        entries.lock().unwrap_or_else(|e| e.into_inner()).retain(|_, entry| entry.expires_at > now);
+    }
+
+    /// Start sweeping expired entries (only the first call has an effect)
+    /// `on_needs_attention` receives accounts whose sign-ins were stopped.
+    pub fn start<F>(&self, on_needs_attention: F)
+    where
+        F: Fn(AuthState) + Send + Sync + 'static,
+    {
+        if self.started.swap(true, Ordering::SeqCst) {
+            log::warn!("Credential cache sweeper already started");
+            return;
+        }
+        let _ = self.on_needs_attention.set(Box::new(on_needs_attention));
+        let entries = self.entries.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                Self::purge_expired(&entries);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_new_account, NewAccount};
+
+    fn setup() -> (Database, i64) {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = db
+            .add_account(&NewAccount {
+                password_encrypted: Some(crate::crypto::encrypt_password("secret").unwrap()),
+                ..test_new_account("me@test.com")
+            })
+            .unwrap();
+        (db, account_id)
+    }
+
+    #[test]
+    fn test_cached_until_invalidated() {
+        let (db, account_id) = setup();
+        let cache = CredentialCache::new();
+        assert_eq!(cache.password(&db, account_id).unwrap().as_str(), "secret");
+
+        // Served from memory: the stored value is not read again
+        db.execute("UPDATE accounts SET password_encrypted = NULL WHERE id = ?1", [account_id]).unwrap();
+        assert_eq!(cache.password(&db, account_id).unwrap().as_str(), "secret");
+
+        cache.invalidate(account_id);
+        assert!(matches!(cache.password(&db, account_id), Err(CredentialError::Missing)));
+
+        cache.insert(account_id, "refreshed-token");
+        assert_eq!(cache.password(&db, account_id).unwrap().as_str(), "refreshed-token");
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let (db, account_id) = setup();
+        let cache = CredentialCache::with_ttl(Duration::ZERO);
+        cache.password(&db, account_id).unwrap();
+        assert_eq!(cache.len(), 1);
+        CredentialCache::purge_expired(&cache.entries);
+        assert!(cache.is_empty());
+
+        cache.insert(account_id, "stale");
+        assert_eq!(cache.password(&db, account_id).unwrap().as_str(), "secret");
+    }
+
+    #[test]
+    fn test_repeated_auth_failures_need_attention() {
+        let (db, account_id) = setup();
+        let cache = CredentialCache::new();
+        let notified = Arc::new(Mutex::new(Vec::new()));
+        let sink = notified.clone();
+        cache.start(move |state| sink.lock().unwrap().push(state));
+
+        let rejected: MailResult<()> = Err(MailError::Authentication("Invalid credentials".to_string()));
+        let offline: MailResult<()> = Err(MailError::Connection("timed out".to_string()));
+        cache.record_login(&db, account_id, &rejected);
+        cache.record_login(&db, account_id, &offline);
+        cache.record_login(&db, account_id, &Ok(()));
+        assert_eq!(auth_state(&db, account_id).unwrap().consecutive_failures, 0);
+
+        for _ in 0..MAX_AUTH_FAILURES {
+            assert!(cache.password(&db, account_id).is_ok());
+            cache.record_login(&db, account_id, &rejected);
+        }
+        let state = auth_state(&db, account_id).unwrap();
+        assert!(state.needs_attention);
+        assert_eq!(state.last_error.as_deref(), Some("Invalid credentials"));
+        assert!(matches!(cache.password(&db, account_id), Err(CredentialError::NeedsAttention)));
+        assert_eq!(notified.lock().unwrap().len(), 1);
+
+        // Survives a restart until a new password is entered
+        let reloaded = CredentialCache::new();
+        reloaded.load(&db).unwrap();
+        assert!(reloaded.needs_attention(account_id));
+        reloaded.reset(&db, account_id).unwrap();
+        assert_eq!(reloaded.password(&db, account_id).unwrap().as_str(), "secret");
+        assert_eq!(auth_state(&db, account_id).unwrap(), AuthState { account_id, ..Default::default() });
+    }
+}
@@ -12,12 +12,19 @@ use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
 use ring::hkdf;
 use ring::rand::{SecureRandom, SystemRandom};
 use std::fs;
-use std::path::PathBuf;
-use zeroize::Zeroize;
+use std::path::{Path, PathBuf};
+use zeroize::{Zeroize, Zeroizing};
 
 const NONCE_LEN: usize = 12;
 const SALT_LEN: usize = 32;
 
+/// HKDF context of the password (secret) encryption key
+const PASSWORD_KEY_INFO: &[u8] = b"owlivion-mail-password-encryption-v3";
+/// HKDF context of the key wrapping per-file keys
+const FILE_KEY_WRAPPING_INFO: &[u8] = b"owlivion-mail-file-key-wrapping-v1";
+/// Plaintext of the key check value
+const KEY_CHECK_PLAINTEXT: &[u8] = b"owlivion-mail-key-check";
+
 /// Wrapper for sensitive data that zeroizes on drop
 #[allow(dead_code)]
 struct SecureString(String);
@@ -38,11 +45,7 @@ impl std::ops::Deref for SecureString {
 
 /// Get the salt file path in app data directory
 fn get_salt_file_path() -> Result<PathBuf, String> {
-    let app_dir = directories::ProjectDirs::from("com", "owlivion", "owlivion-mail")
-        .ok_or_else(|| "Failed to get app directories".to_string())?;
-    let data_dir = app_dir.data_dir();
-    fs::create_dir_all(data_dir).map_err(|e| format!("Failed to create data directory: {}", e))?;
-    Ok(data_dir.join(".encryption_salt"))
+    Ok(crate::portable::data_dir()?.join(".encryption_salt"))
 }
 
 /// Get or create installation-specific salt
@@ -67,7 +70,13 @@ fn get_or_create_salt() -> Result<[u8; SALT_LEN], String> {
     rng.fill(&mut salt)
         .map_err(|e| format!("Failed to generate salt: {:?}", e))?;
 
-    // Save salt to file with restricted permissions
+    write_salt_file(&salt_path, &salt)?;
+
+    Ok(salt)
+}
+
+/// Write a salt file with restricted permissions
+fn write_salt_file(path: &Path, salt: &[u8; SALT_LEN]) -> Result<(), String> {
     #[cfg(unix)]
     {
         use std::os::unix::fs::OpenOptionsExt;
@@ -76,10 +85,10 @@ fn get_or_create_salt() -> Result<[u8; SALT_LEN], String> {
             .create(true)
             .truncate(true)
             .mode(0o600)
-            .open(&salt_path)
+            .open(path)
             .map_err(|e| format!("Failed to create salt file: {}", e))?;
         use std::io::Write;
-        file.write_all(&salt)
+        file.write_all(salt)
             .map_err(|e| format!("Failed to write salt: {}", e))?;
     }
 
@@ -91,9 +100,9 @@ fn get_or_create_salt() -> Result<[u8; SALT_LEN], String> {
             .write(true)
             .create(true)
             .truncate(true)
-            .open(&salt_path)
+            .open(path)
             .map_err(|e| format!("Failed to create salt file: {}", e))?;
-        file.write_all(&salt)
+        file.write_all(salt)
             .map_err(|e| format!("Failed to write salt: {}", e))?;
 
         // SECURITY: Set hidden attribute on Windows to reduce visibility
@@ -104,23 +113,29 @@ fn get_or_create_salt() -> Result<[u8; SALT_LEN], String> {
             use std::process::Command;
             // Attempt to set hidden attribute using attrib command
             let _ = Command::new("attrib")
-                .args(["+H", &salt_path.to_string_lossy()])
+                .args(["+H", &path.to_string_lossy()])
                 .output();
         }
     }
 
     #[cfg(not(any(unix, windows)))]
     {
-        fs::write(&salt_path, &salt)
+        fs::write(path, salt)
             .map_err(|e| format!("Failed to write salt: {}", e))?;
     }
 
-    Ok(salt)
+    Ok(())
 }
 
 /// Get a unique machine identifier
 /// SECURITY: Combines multiple entropy sources for better key uniqueness
 fn get_machine_id() -> Result<String, String> {
+    // Portable data moves between machines, so its key rests on the salt stored
+    // with it; the data is only as safe as the drive it is on
+    if crate::portable::dir().is_some() {
+        return Ok("owlivion-portable".to_string());
+    }
+
     let mut id_parts: Vec<String> = Vec::new();
 
     // 1. Try to read machine-id (Linux)
@@ -172,11 +187,19 @@ fn get_machine_id() -> Result<String, String> {
 /// Derive encryption key using HKDF
 /// SECURITY: Uses machine ID + user + installation-specific salt for key material
 fn get_encryption_key() -> Result<[u8; 32], String> {
-    let salt = get_or_create_salt()?;
+    derive_key(PASSWORD_KEY_INFO)
+}
+
+/// Derive a key for one purpose (`info`) from the installation key material
+fn derive_key(info: &[u8]) -> Result<[u8; 32], String> {
+    derive_key_from(&get_or_create_salt()?, info)
+}
+
+fn derive_key_from(salt: &[u8; SALT_LEN], info: &[u8]) -> Result<[u8; 32], String> {
     let machine_id = get_machine_id()?;
 
     // Create HKDF salt from installation salt
-    let hkdf_salt = hkdf::Salt::new(hkdf::HKDF_SHA256, &salt);
+    let hkdf_salt = hkdf::Salt::new(hkdf::HKDF_SHA256, salt);
 
     // Input key material: machine ID (now includes user info)
     let ikm = machine_id.as_bytes();
@@ -185,7 +208,7 @@ fn get_encryption_key() -> Result<[u8; 32], String> {
     let prk = hkdf_salt.extract(ikm);
 
     // Expand with context info
-    let info: &[&[u8]] = &[b"owlivion-mail-password-encryption-v3"];
+    let info: &[&[u8]] = &[info];
     let okm = prk.expand(info, MyKeyType(32))
         .map_err(|_| "HKDF expansion failed".to_string())?;
 
@@ -285,6 +308,190 @@ pub fn decrypt_password(encrypted: &str) -> Result<String, String> {
     result
 }
 
+// ============================================================================
+// File encryption (per-file keys wrapped by the installation key)
+// ============================================================================
+
+/// Key of one encrypted file (zeroized on drop)
+pub type FileKey = Zeroizing<[u8; 32]>;
+
+/// AES-256-GCM with a random nonce; returns nonce || ciphertext || tag
+fn seal(key: &[u8; 32], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).map_err(|e| format!("Key error: {:?}", e))?);
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce_bytes)
+        .map_err(|e| format!("RNG error: {:?}", e))?;
+
+    let mut in_out = Vec::with_capacity(NONCE_LEN + plaintext.len() + AES_256_GCM.tag_len());
+    in_out.extend_from_slice(plaintext);
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce_bytes), Aad::empty(), &mut in_out)
+        .map_err(|e| format!("Encryption error: {:?}", e))?;
+    in_out.splice(0..0, nonce_bytes);
+    Ok(in_out)
+}
+
+/// Reverse of `seal`
+fn open(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < NONCE_LEN + AES_256_GCM.tag_len() {
+        return Err("Encrypted data too short".to_string());
+    }
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).map_err(|e| format!("Key error: {:?}", e))?);
+    let (nonce_bytes, ciphertext) = data.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce_bytes).map_err(|_| "Invalid nonce".to_string())?;
+
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::empty(), &mut in_out)
+        .map_err(|_| "Decryption failed - invalid key or corrupted data".to_string())?;
+    Ok(plaintext.to_vec())
+}
+
+/// Key that wraps file keys (separate HKDF context from password encryption)
+fn wrapping_key() -> Result<FileKey, String> {
+    Ok(Zeroizing::new(derive_key(FILE_KEY_WRAPPING_INFO)?))
+}
+
+/// Encrypt file contents with a fresh key
+/// Returns the ciphertext and the file key wrapped by the installation key (base64).
+pub fn encrypt_file(plaintext: &[u8]) -> Result<(Vec<u8>, String), String> {
+    let mut file_key: FileKey = Zeroizing::new([0u8; 32]);
+    SystemRandom::new()
+        .fill(&mut file_key[..])
+        .map_err(|e| format!("RNG error: {:?}", e))?;
+
+    let ciphertext = seal(&file_key, plaintext)?;
+    let wrapped = seal(&*wrapping_key()?, &file_key[..])?;
+    Ok((ciphertext, base64::engine::general_purpose::STANDARD.encode(wrapped)))
+}
+
+/// Decrypt file contents encrypted by `encrypt_file`
+pub fn decrypt_file(ciphertext: &[u8], wrapped_key: &str) -> Result<Vec<u8>, String> {
+    let wrapped = base64::engine::general_purpose::STANDARD
+        .decode(wrapped_key)
+        .map_err(|e| format!("Base64 decode error: {}", e))?;
+    let key_bytes = Zeroizing::new(open(&*wrapping_key()?, &wrapped)?);
+    let file_key: FileKey = Zeroizing::new(
+        key_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| "Invalid file key".to_string())?,
+    );
+    open(&file_key, ciphertext)
+}
+
+// ============================================================================
+// Key rotation
+// ============================================================================
+
+/// Salt of a new installation key while secrets are re-encrypted for it
+fn get_staged_salt_path() -> Result<PathBuf, String> {
+    Ok(get_salt_file_path()?.with_extension("new"))
+}
+
+/// Replacement installation key (a new salt with the same key derivation)
+///
+/// Rotation: `generate`, re-encrypt every secret with `reencrypt_secret` /
+/// `rewrap_file_key`, `stage` the salt, store the secrets together with
+/// `check_value`, then `activate`. A staged salt left by an interrupted
+/// rotation is settled by `resolve_staged_key`.
+pub struct NewKey {
+    salt: Zeroizing<[u8; SALT_LEN]>,
+}
+
+impl NewKey {
+    pub fn generate() -> Result<Self, String> {
+        let mut salt = Zeroizing::new([0u8; SALT_LEN]);
+        SystemRandom::new()
+            .fill(&mut salt[..])
+            .map_err(|e| format!("Failed to generate salt: {:?}", e))?;
+        Ok(Self { salt })
+    }
+
+    fn key(&self, info: &[u8]) -> Result<FileKey, String> {
+        Ok(Zeroizing::new(derive_key_from(&self.salt, info)?))
+    }
+
+    /// Re-encrypt a secret from `encrypt_password` under this key
+    pub fn reencrypt_secret(&self, encrypted: &str) -> Result<String, String> {
+        let plaintext = Zeroizing::new(decrypt_password(encrypted)?);
+        let sealed = seal(&*self.key(PASSWORD_KEY_INFO)?, plaintext.as_bytes())?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(sealed))
+    }
+
+    /// Decrypt a secret encrypted under this key
+    pub fn decrypt_secret(&self, encrypted: &str) -> Result<String, String> {
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(encrypted)
+            .map_err(|e| format!("Base64 decode error: {}", e))?;
+        let plaintext = open(&*self.key(PASSWORD_KEY_INFO)?, &data)?;
+        String::from_utf8(plaintext).map_err(|e| format!("UTF-8 decode error: {}", e))
+    }
+
+    /// Re-wrap a file key from `encrypt_file` under this key
+    /// The file itself keeps its key and needs no re-encryption.
+    pub fn rewrap_file_key(&self, wrapped_key: &str) -> Result<String, String> {
+        let wrapped = base64::engine::general_purpose::STANDARD
+            .decode(wrapped_key)
+            .map_err(|e| format!("Base64 decode error: {}", e))?;
+        let file_key = Zeroizing::new(open(&*wrapping_key()?, &wrapped)?);
+        let rewrapped = seal(&*self.key(FILE_KEY_WRAPPING_INFO)?, &file_key)?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(rewrapped))
+    }
+
+    /// Value that only this key decrypts, stored with the re-encrypted secrets
+    pub fn check_value(&self) -> Result<String, String> {
+        let sealed = seal(&*self.key(PASSWORD_KEY_INFO)?, KEY_CHECK_PLAINTEXT)?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(sealed))
+    }
+
+    /// Write the salt next to the current one, without using it yet
+    pub fn stage(&self) -> Result<(), String> {
+        write_salt_file(&get_staged_salt_path()?, &self.salt)
+    }
+
+    /// Replace the current salt with the staged one
+    pub fn activate(self) -> Result<(), String> {
+        fs::rename(get_staged_salt_path()?, get_salt_file_path()?)
+            .map_err(|e| format!("Failed to activate new key: {}", e))
+    }
+}
+
+/// Remove a staged salt (rotation abandoned before the secrets were stored)
+pub fn discard_staged_key() -> Result<(), String> {
+    match fs::remove_file(get_staged_salt_path()?) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove staged key: {}", e)),
+    }
+}
+
+/// Finish or roll back a rotation interrupted after `stage`
+/// `check_value` is the stored check value: when the staged key opens it, the
+/// re-encrypted secrets were committed and the staged salt is activated;
+/// otherwise it is discarded. Returns whether a staged salt was activated.
+pub fn resolve_staged_key(check_value: Option<&str>) -> Result<bool, String> {
+    let staged_path = get_staged_salt_path()?;
+    let Ok(salt_data) = fs::read(&staged_path) else {
+        return Ok(false);
+    };
+
+    let committed = match (<[u8; SALT_LEN]>::try_from(salt_data.as_slice()), check_value) {
+        (Ok(salt), Some(check_value)) => {
+            let staged = NewKey { salt: Zeroizing::new(salt) };
+            staged.decrypt_secret(check_value).is_ok_and(|v| v.as_bytes() == KEY_CHECK_PLAINTEXT)
+        }
+        _ => false,
+    };
+    if committed {
+        fs::rename(&staged_path, get_salt_file_path()?).map_err(|e| format!("Failed to activate new key: {}", e))?;
+    } else {
+        discard_staged_key()?;
+    }
+    Ok(committed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -335,6 +542,42 @@ mod tests {
         assert_eq!(decrypted, password);
     }
 
+    #[test]
+    fn test_file_encryption_roundtrip() {
+        let contents = b"%PDF-1.7 quarterly report".to_vec();
+        let (ciphertext, wrapped) = encrypt_file(&contents).expect("Encryption failed");
+        assert!(!ciphertext.windows(9).any(|w| w == b"quarterly"));
+        assert_eq!(decrypt_file(&ciphertext, &wrapped).expect("Decryption failed"), contents);
+
+        // Every file gets its own key
+        let (_, other_wrapped) = encrypt_file(&contents).expect("Encryption failed");
+        assert!(decrypt_file(&ciphertext, &other_wrapped).is_err());
+
+        let mut tampered = ciphertext.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt_file(&tampered, &wrapped).is_err());
+    }
+
+    #[test]
+    fn test_new_key_reencrypts_secrets() {
+        let new_key = NewKey::generate().expect("Key generation failed");
+
+        let encrypted = encrypt_password("hunter2").expect("Encryption failed");
+        let reencrypted = new_key.reencrypt_secret(&encrypted).expect("Re-encryption failed");
+        assert_eq!(new_key.decrypt_secret(&reencrypted).unwrap(), "hunter2");
+        assert!(decrypt_password(&reencrypted).is_err());
+
+        let (ciphertext, wrapped) = encrypt_file(b"attachment").expect("Encryption failed");
+        let rewrapped = new_key.rewrap_file_key(&wrapped).expect("Re-wrapping failed");
+        assert!(decrypt_file(&ciphertext, &rewrapped).is_err());
+        let rewrapped = base64::engine::general_purpose::STANDARD.decode(&rewrapped).unwrap();
+        let file_key = open(&new_key.key(FILE_KEY_WRAPPING_INFO).unwrap(), &rewrapped).unwrap();
+        assert_eq!(open(file_key.as_slice().try_into().unwrap(), &ciphertext).unwrap(), b"attachment");
+
+        let check = new_key.check_value().unwrap();
+        assert!(NewKey::generate().unwrap().decrypt_secret(&check).is_err());
+    }
+
     #[test]
     fn test_salt_persistence() {
         // First call creates salt
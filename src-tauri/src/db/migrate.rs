@@ -0,0 +1,590 @@
+//! Versioned Schema Migrations
+//!
+//! Every schema change is a numbered [`Migration`] in [`MIGRATIONS`]. Applied
+//! versions are recorded in the `schema_version` table; at startup the
+//! missing ones run in order, each in its own transaction together with its
+//! version row, so a failure leaves the database at the previous version.
+//! Destructive migrations (table rebuilds, dropped data) are preceded by a
+//! copy of the database file next to it.
+//!
+//! Databases created before versioning have no `schema_version` rows. For
+//! migrations 1-21 a probe recognises changes that are already in place
+//! (including those `schema.sql` creates on fresh installs), and those are
+//! recorded without running. New migrations use [`Applied::Recorded`] and add
+//! their SQL as the next numbered file in `migrations/`.
+
+use rusqlite::{params, Connection, TransactionBehavior};
+
+use super::DbResult;
+
+/// How to tell whether an unrecorded migration is already in place
+#[derive(Debug, Clone, Copy)]
+pub enum Applied {
+    /// Column exists in table
+    Column(&'static str, &'static str),
+    /// Table exists
+    Table(&'static str),
+    /// Index exists
+    Index(&'static str),
+    /// Setting row exists
+    Setting(&'static str),
+    /// Schema object `name` does not have `text` in its SQL
+    SqlLacks { name: &'static str, text: &'static str },
+    /// Only the `schema_version` row counts (migrations added after versioning)
+    Recorded,
+}
+
+/// One schema change
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub applied: Applied,
+    pub sql: &'static str,
+    /// Drops or rebuilds existing data: back up the database first
+    pub destructive: bool,
+}
+
+const fn migration(version: u32, name: &'static str, applied: Applied, sql: &'static str) -> Migration {
+    Migration { version, name, applied, sql, destructive: false }
+}
+
+/// All migrations, in version order
+/// From version 8 on, a migration's SQL file is numbered one below its
+/// version (version 10 runs `009_add_local_folders.sql`).
+pub const MIGRATIONS: &[Migration] = &[
+    migration(
+        1,
+        "Add signature column to accounts",
+        Applied::Column("accounts", "signature"),
+        "ALTER TABLE accounts ADD COLUMN signature TEXT DEFAULT '';",
+    ),
+    migration(
+        2,
+        "Add accept_invalid_certs column to accounts",
+        Applied::Column("accounts", "accept_invalid_certs"),
+        "ALTER TABLE accounts ADD COLUMN accept_invalid_certs INTEGER NOT NULL DEFAULT 0;",
+    ),
+    migration(
+        3,
+        "Add close_to_tray setting",
+        Applied::Setting("close_to_tray"),
+        "INSERT INTO settings (key, value) VALUES ('close_to_tray', 'true');",
+    ),
+    migration(
+        4,
+        "Delta sync: deleted column on accounts",
+        Applied::Column("accounts", "deleted"),
+        "ALTER TABLE accounts ADD COLUMN deleted INTEGER NOT NULL DEFAULT 0;
+         CREATE INDEX IF NOT EXISTS idx_accounts_deleted ON accounts(deleted) WHERE deleted = 0;",
+    ),
+    migration(
+        5,
+        "Delta sync: deleted column on contacts",
+        Applied::Column("contacts", "deleted"),
+        "ALTER TABLE contacts ADD COLUMN deleted INTEGER NOT NULL DEFAULT 0;
+         CREATE INDEX IF NOT EXISTS idx_contacts_deleted ON contacts(deleted) WHERE deleted = 0;",
+    ),
+    migration(
+        6,
+        "Delta sync: sync_metadata table",
+        Applied::Table("sync_metadata"),
+        r#"
+        CREATE TABLE sync_metadata (
+            data_type TEXT PRIMARY KEY CHECK (data_type IN ('accounts', 'contacts', 'preferences', 'signatures')),
+            last_sync_at TEXT,
+            last_sync_version INTEGER DEFAULT 0,
+            items_synced INTEGER DEFAULT 0,
+            items_changed INTEGER DEFAULT 0,
+            items_deleted INTEGER DEFAULT 0,
+            sync_status TEXT DEFAULT 'idle' CHECK (sync_status IN ('idle', 'syncing', 'error')),
+            error_message TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        INSERT INTO sync_metadata (data_type, last_sync_at) VALUES
+            ('accounts', NULL),
+            ('contacts', NULL),
+            ('preferences', NULL),
+            ('signatures', NULL);
+
+        CREATE TRIGGER sync_metadata_updated_at AFTER UPDATE ON sync_metadata
+        BEGIN
+            UPDATE sync_metadata SET updated_at = datetime('now') WHERE data_type = NEW.data_type;
+        END;
+        "#,
+    ),
+    migration(
+        7,
+        "Add priority_enabled column to accounts",
+        Applied::Column("accounts", "priority_enabled"),
+        "ALTER TABLE accounts ADD COLUMN priority_enabled INTEGER DEFAULT 1;
+         CREATE INDEX IF NOT EXISTS idx_accounts_priority ON accounts(priority_enabled);",
+    ),
+    migration(
+        8,
+        "Email templates",
+        Applied::Table("email_templates"),
+        include_str!("migrations/007_add_email_templates.sql"),
+    ),
+    migration(
+        9,
+        "Per-account priority fetch settings",
+        Applied::Column("accounts", "enable_priority_fetch"),
+        include_str!("migrations/008_add_account_priority_settings.sql"),
+    ),
+    migration(
+        10,
+        "Local-only folders",
+        Applied::Column("folders", "is_local"),
+        include_str!("migrations/009_add_local_folders.sql"),
+    ),
+    migration(
+        11,
+        "Persistent pending operations",
+        Applied::Table("pending_operations"),
+        include_str!("migrations/010_add_pending_operations.sql"),
+    ),
+    migration(
+        12,
+        "Per-account sync pause",
+        Applied::Column("accounts", "sync_paused"),
+        include_str!("migrations/011_add_account_sync_paused.sql"),
+    ),
+    migration(
+        13,
+        "Staged backfill progress",
+        Applied::Table("backfill_state"),
+        include_str!("migrations/012_add_backfill_state.sql"),
+    ),
+    migration(
+        14,
+        "Folder hierarchy",
+        Applied::Column("folders", "parent_id"),
+        include_str!("migrations/013_add_folder_hierarchy.sql"),
+    ),
+    Migration {
+        version: 15,
+        name: "Sync history accepts every sync data type",
+        applied: Applied::SqlLacks { name: "sync_history", text: "CHECK (data_type IN" },
+        sql: include_str!("migrations/014_relax_sync_history_types.sql"),
+        destructive: true,
+    },
+    migration(
+        16,
+        "RSS/Atom feed subscriptions",
+        Applied::Table("feeds"),
+        include_str!("migrations/015_add_feeds.sql"),
+    ),
+    migration(
+        17,
+        "VIP contacts",
+        Applied::Column("contacts", "is_vip"),
+        include_str!("migrations/016_add_contact_vip.sql"),
+    ),
+    migration(
+        18,
+        "AI thread summary cache",
+        Applied::Table("thread_summaries"),
+        include_str!("migrations/017_add_thread_summaries.sql"),
+    ),
+    migration(
+        19,
+        "Per-message AI metadata",
+        Applied::Table("email_ai_metadata"),
+        include_str!("migrations/018_add_email_ai_metadata.sql"),
+    ),
+    migration(
+        20,
+        "Keyset index for windowed folder listing",
+        Applied::Index("idx_emails_folder_window"),
+        include_str!("migrations/019_add_email_window_index.sql"),
+    ),
+    migration(
+        21,
+        "Template search triggers using the FTS5 'delete' command",
+        Applied::SqlLacks { name: "templates_fts_update", text: "UPDATE templates_fts" },
+        include_str!("migrations/020_fix_templates_fts_triggers.sql"),
+    ),
+    migration(
+        22,
+        "Content-addressed attachment storage",
+        Applied::Recorded,
+        include_str!("migrations/021_add_attachment_blobs.sql"),
+    ),
+    migration(
+        23,
+        "Attachment encryption at rest",
+        Applied::Recorded,
+        include_str!("migrations/022_add_attachment_encryption.sql"),
+    ),
+    migration(
+        24,
+        "Authentication failure tracking",
+        Applied::Recorded,
+        include_str!("migrations/023_add_account_auth_state.sql"),
+    ),
+    migration(
+        25,
+        "Server capability cache",
+        Applied::Recorded,
+        include_str!("migrations/024_add_account_capabilities.sql"),
+    ),
+    migration(
+        26,
+        "Message versions",
+        Applied::Recorded,
+        include_str!("migrations/025_add_message_versions.sql"),
+    ),
+    migration(
+        27,
+        "Activity log",
+        Applied::Recorded,
+        include_str!("migrations/026_add_activity_log.sql"),
+    ),
+    migration(
+        28,
+        "Focused inbox",
+        Applied::Recorded,
+        include_str!("migrations/027_add_focused_inbox.sql"),
+    ),
+    migration(
+        29,
+        "Domain policies",
+        Applied::Recorded,
+        include_str!("migrations/028_add_domain_policies.sql"),
+    ),
+    migration(
+        30,
+        "Markdown drafts",
+        Applied::Recorded,
+        include_str!("migrations/029_add_draft_markdown.sql"),
+    ),
+    migration(
+        31,
+        "Spam training",
+        Applied::Recorded,
+        include_str!("migrations/030_add_spam_training.sql"),
+    ),
+    migration(
+        32,
+        "Blocked senders",
+        Applied::Recorded,
+        include_str!("migrations/031_add_blocked_senders.sql"),
+    ),
+    migration(
+        33,
+        "Mail merge",
+        Applied::Recorded,
+        include_str!("migrations/032_add_mail_merges.sql"),
+    ),
+    migration(
+        34,
+        "Send quotas",
+        Applied::Recorded,
+        include_str!("migrations/033_add_send_quota.sql"),
+    ),
+    migration(
+        35,
+        "Folder watching",
+        Applied::Recorded,
+        include_str!("migrations/034_add_folder_watch.sql"),
+    ),
+    migration(
+        36,
+        "Operation journal",
+        Applied::Recorded,
+        include_str!("migrations/035_add_operation_journal.sql"),
+    ),
+    migration(
+        37,
+        "Email notes",
+        Applied::Recorded,
+        include_str!("migrations/036_add_email_notes.sql"),
+    ),
+    migration(
+        38,
+        "IMAP keywords",
+        Applied::Column("emails", "keywords"),
+        include_str!("migrations/037_add_email_keywords.sql"),
+    ),
+    migration(
+        39,
+        "Plus-address aliases",
+        Applied::Recorded,
+        include_str!("migrations/038_add_plus_aliases.sql"),
+    ),
+    migration(
+        40,
+        "Attachment browser indexes",
+        Applied::Recorded,
+        include_str!("migrations/039_add_attachment_indexes.sql"),
+    ),
+    migration(
+        41,
+        "Meeting invites",
+        Applied::Recorded,
+        include_str!("migrations/040_add_invites.sql"),
+    ),
+    migration(
+        42,
+        "Bouncing contacts",
+        Applied::Column("contacts", "bounced_at"),
+        include_str!("migrations/041_add_contact_bounces.sql"),
+    ),
+    migration(
+        43,
+        "Account migrations",
+        Applied::Table("account_migrations"),
+        include_str!("migrations/042_add_account_migrations.sql"),
+    ),
+    migration(
+        44,
+        "Email quarantine",
+        Applied::Table("email_quarantine"),
+        include_str!("migrations/043_add_email_quarantine.sql"),
+    ),
+    migration(
+        45,
+        "Account refresh intervals",
+        Applied::Recorded,
+        include_str!("migrations/044_add_account_refresh.sql"),
+    ),
+    migration(
+        46,
+        "Emails search update trigger on indexed columns only",
+        Applied::Recorded,
+        include_str!("migrations/045_fix_emails_fts_update_trigger.sql"),
+    ),
+    Migration {
+        version: 47,
+        name: "Quarantine keeps the cached rows",
+        applied: Applied::Recorded,
+        sql: include_str!("migrations/046_rekey_email_quarantine.sql"),
+        destructive: true,
+    },
+];
+
+/// Latest schema version this build knows
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Highest applied version (0 for an unversioned database)
+pub fn current_version(conn: &Connection) -> DbResult<u32> {
+    ensure_version_table(conn)?;
+    Ok(conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?)
+}
+
+fn ensure_version_table(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now')),
+            baselined INTEGER NOT NULL DEFAULT 0
+        );",
+    )?;
+    Ok(())
+}
+
+fn is_recorded(conn: &Connection, version: u32) -> DbResult<bool> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) > 0 FROM schema_version WHERE version = ?1",
+        params![version],
+        |row| row.get(0),
+    )?)
+}
+
+fn probe(conn: &Connection, applied: Applied) -> DbResult<bool> {
+    let found = match applied {
+        Applied::Column(table, column) => conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, column],
+            |row| row.get(0),
+        )?,
+        Applied::Table(name) => conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?,
+        Applied::Index(name) => conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'index' AND name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?,
+        Applied::Setting(key) => {
+            conn.query_row("SELECT COUNT(*) > 0 FROM settings WHERE key = ?1", params![key], |row| row.get(0))?
+        }
+        Applied::SqlLacks { name, text } => conn.query_row(
+            "SELECT COUNT(*) = 0 FROM sqlite_master WHERE name = ?1 AND instr(sql, ?2) > 0",
+            params![name, text],
+            |row| row.get(0),
+        )?,
+        Applied::Recorded => false,
+    };
+    Ok(found)
+}
+
+/// Copy the database file before a destructive migration
+/// In-memory databases have nothing to protect and are skipped.
+fn backup(conn: &Connection, version: u32) -> DbResult<Option<String>> {
+    let Some(path) = conn.path().filter(|path| !path.is_empty()) else {
+        return Ok(None);
+    };
+    let target = format!(
+        "{}.pre-v{}-{}.bak",
+        path,
+        version,
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    );
+    conn.execute("VACUUM INTO ?1", params![target])?;
+    Ok(Some(target))
+}
+
+/// Apply the missing migrations of `migrations` in version order
+/// Returns the versions that actually ran (baselined ones are not included).
+pub fn migrate(conn: &Connection, migrations: &[Migration]) -> DbResult<Vec<u32>> {
+    ensure_version_table(conn)?;
+
+    let current = current_version(conn)?;
+    if let Some(last) = migrations.last() {
+        if current > last.version {
+            log::warn!(
+                "Database schema version {} is newer than this build ({}); continuing",
+                current,
+                last.version
+            );
+        }
+    }
+
+    let mut ran = Vec::new();
+    for migration in migrations {
+        if is_recorded(conn, migration.version)? {
+            continue;
+        }
+
+        if probe(conn, migration.applied)? {
+            log::debug!("Recording migration {} ({}) as already applied", migration.version, migration.name);
+            conn.execute(
+                "INSERT INTO schema_version (version, name, baselined) VALUES (?1, ?2, 1)",
+                params![migration.version, migration.name],
+            )?;
+            continue;
+        }
+
+        if migration.destructive {
+            if let Some(path) = backup(conn, migration.version)? {
+                log::info!("Backed up database to {} before migration {}", path, migration.version);
+            }
+        }
+
+        log::info!("Running migration {}: {}", migration.version, migration.name);
+        let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        tx.execute_batch(migration.sql)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name) VALUES (?1, ?2)",
+            params![migration.version, migration.name],
+        )?;
+        tx.commit()?;
+        ran.push(migration.version);
+    }
+    Ok(ran)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL);").unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_versions_are_ordered() {
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version, "migration {} out of order", pair[1].version);
+        }
+        assert_eq!(latest_version(), MIGRATIONS.len() as u32);
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let conn = base();
+        let migrations = [
+            migration(1, "Add title", Applied::Recorded, "ALTER TABLE notes ADD COLUMN title TEXT;"),
+            migration(
+                2,
+                "Broken",
+                Applied::Recorded,
+                "ALTER TABLE notes ADD COLUMN tags TEXT; INSERT INTO missing_table VALUES (1);",
+            ),
+        ];
+
+        assert!(migrate(&conn, &migrations).is_err());
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        // The broken migration's first statement was rolled back with it
+        assert!(probe(&conn, Applied::Column("notes", "title")).unwrap());
+        assert!(!probe(&conn, Applied::Column("notes", "tags")).unwrap());
+
+        // Fixed in a later build: resumes at version 2, version 1 is not re-run
+        let fixed = [migrations[0], migration(2, "Add tags", Applied::Recorded, "ALTER TABLE notes ADD COLUMN tags TEXT;")];
+        assert_eq!(migrate(&conn, &fixed).unwrap(), vec![2]);
+        assert!(migrate(&conn, &fixed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unversioned_database_is_baselined() {
+        let conn = base();
+        conn.execute_batch("ALTER TABLE notes ADD COLUMN title TEXT;").unwrap();
+        let migrations = [
+            migration(1, "Add title", Applied::Column("notes", "title"), "ALTER TABLE notes ADD COLUMN title TEXT;"),
+            migration(2, "Add index", Applied::Index("idx_notes_title"), "CREATE INDEX idx_notes_title ON notes(title);"),
+        ];
+
+        assert_eq!(migrate(&conn, &migrations).unwrap(), vec![2]);
+        let baselined: Vec<u32> = conn
+            .prepare("SELECT version FROM schema_version WHERE baselined = 1")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(baselined, vec![1]);
+    }
+
+    #[test]
+    fn test_destructive_migration_backs_up_file() {
+        let dir = std::env::temp_dir().join(format!("owlivion-migrate-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let conn = Connection::open(dir.join("mail.db")).unwrap();
+        conn.execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL); INSERT INTO notes (body) VALUES ('keep');")
+            .unwrap();
+
+        let rebuild = Migration {
+            version: 1,
+            name: "Drop notes",
+            applied: Applied::Recorded,
+            sql: "DROP TABLE notes;",
+            destructive: true,
+        };
+        migrate(&conn, &[rebuild]).unwrap();
+
+        let backups: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".pre-v1-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        let copy = Connection::open(backups[0].path()).unwrap();
+        let body: String = copy.query_row("SELECT body FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(body, "keep");
+
+        drop(copy);
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,14 @@
+-- Migration 009: Local-only folders
+-- Folders stored only in SQLite (never synced to IMAP), e.g. a "Receipts" archive.
+-- Messages filed into them keep their full raw source for export.
+
+ALTER TABLE folders ADD COLUMN is_local INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_folders_local ON folders(account_id) WHERE is_local = 1;
+
+-- Raw RFC 822 source of messages stored in local folders
+CREATE TABLE IF NOT EXISTS local_message_sources (
+    email_id INTEGER PRIMARY KEY REFERENCES emails(id) ON DELETE CASCADE,
+    raw_source BLOB NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,19 @@
+-- Migration 010: Persistent pending operations
+-- Flag changes, moves, deletes and sends applied locally while the server was
+-- unreachable. Replayed in `seq` order once the account is back online.
+
+CREATE TABLE IF NOT EXISTS pending_operations (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    id TEXT NOT NULL UNIQUE,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    folder TEXT NOT NULL DEFAULT '',
+    uid INTEGER NOT NULL DEFAULT 0,
+    kind TEXT NOT NULL,                      -- JSON encoded operation
+    email_ids TEXT NOT NULL DEFAULT '[]',    -- JSON array of local email ids
+    previous_value INTEGER,                  -- flag value before the change (rollback)
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_operations_account ON pending_operations(account_id, seq);
@@ -0,0 +1,5 @@
+-- Migration 011: Per-account sync pause
+-- Lets users stop background IMAP/SMTP work for an account (metered connection,
+-- vacation) without removing it.
+
+ALTER TABLE accounts ADD COLUMN sync_paused INTEGER NOT NULL DEFAULT 0;
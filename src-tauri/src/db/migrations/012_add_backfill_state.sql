@@ -0,0 +1,14 @@
+-- Migration 012: Staged historical backfill
+-- Progress of background backfill per account folder, so a restart resumes
+-- where it stopped and the window can be extended later.
+
+CREATE TABLE IF NOT EXISTS backfill_state (
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    folder TEXT NOT NULL,
+    since TEXT,                            -- oldest date fetched (YYYY-MM-DD), NULL = full history
+    total INTEGER NOT NULL DEFAULT 0,      -- messages in the window on the server
+    fetched INTEGER NOT NULL DEFAULT 0,    -- of those, cached locally
+    done INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (account_id, folder)
+);
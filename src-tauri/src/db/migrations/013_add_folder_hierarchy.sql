@@ -0,0 +1,7 @@
+-- Migration 013: Folder hierarchy
+-- Links each IMAP folder to its parent (derived from the server's hierarchy
+-- delimiter) so nested folder trees can be rendered and renamed as a unit.
+
+ALTER TABLE folders ADD COLUMN parent_id INTEGER REFERENCES folders(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_folders_parent ON folders(parent_id);
@@ -0,0 +1,31 @@
+-- Migration 014: Open sync_history to new sync data types
+-- The data_type CHECK listed the original four categories only, so snapshots
+-- of newer types (templates, ...) were rejected. SQLite cannot alter a CHECK
+-- constraint, so the table is rebuilt; valid types are enforced by
+-- SyncDataType on the Rust side.
+
+CREATE TABLE sync_history_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    data_type TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    encrypted_snapshot BLOB NOT NULL,
+    snapshot_hash TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    operation TEXT NOT NULL CHECK (operation IN ('push', 'pull', 'merge')),
+    items_count INTEGER DEFAULT 0,
+    sync_status TEXT DEFAULT 'success' CHECK (sync_status IN ('success', 'failed', 'conflict')),
+    error_message TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(data_type, version)
+);
+
+INSERT INTO sync_history_new
+SELECT id, data_type, version, encrypted_snapshot, snapshot_hash, device_id,
+       operation, items_count, sync_status, error_message, created_at
+FROM sync_history;
+
+DROP TABLE sync_history;
+ALTER TABLE sync_history_new RENAME TO sync_history;
+
+CREATE INDEX IF NOT EXISTS idx_history_data_type ON sync_history(data_type, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_history_version ON sync_history(data_type, version);
@@ -0,0 +1,28 @@
+-- Migration 015: RSS/Atom feed subscriptions
+-- Feed entries are stored as messages in a local folder of the subscribing
+-- account; feed_items remembers every entry already seen so deleted items
+-- are not brought back by the next poll.
+
+CREATE TABLE IF NOT EXISTS feeds (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    folder_id INTEGER NOT NULL REFERENCES folders(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    interval_minutes INTEGER NOT NULL DEFAULT 60,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    etag TEXT,                             -- validators for conditional GET
+    last_modified TEXT,
+    last_polled_at TEXT,                   -- RFC 3339, NULL = never polled
+    last_error TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (account_id, url)
+);
+
+CREATE TABLE IF NOT EXISTS feed_items (
+    feed_id INTEGER NOT NULL REFERENCES feeds(id) ON DELETE CASCADE,
+    guid TEXT NOT NULL,
+    email_id INTEGER REFERENCES emails(id) ON DELETE SET NULL,
+    seen_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (feed_id, guid)
+);
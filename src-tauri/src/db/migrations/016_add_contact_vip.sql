@@ -0,0 +1,7 @@
+-- Migration 016: VIP senders
+-- Mail from VIP contacts always notifies (even in Do Not Disturb) and is
+-- listed in the cross-account "VIP" view.
+
+ALTER TABLE contacts ADD COLUMN is_vip INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_contacts_vip ON contacts(email) WHERE is_vip = 1;
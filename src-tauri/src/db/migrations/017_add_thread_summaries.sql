@@ -0,0 +1,13 @@
+-- Migration 017: AI thread summaries
+-- One cached summary per thread. `fingerprint` identifies the set of messages
+-- it was generated from; a new message in the thread changes it and the
+-- summary is regenerated on the next request.
+
+CREATE TABLE IF NOT EXISTS thread_summaries (
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    thread_id TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    summary_json TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (account_id, thread_id)
+);
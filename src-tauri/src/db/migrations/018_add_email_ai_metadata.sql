@@ -0,0 +1,15 @@
+-- Migration 018: Per-message AI/heuristic metadata
+-- Starts with reply-needed detection: the automatic classification, a manual
+-- override (NULL = automatic) and an optional reminder time.
+
+CREATE TABLE IF NOT EXISTS email_ai_metadata (
+    email_id INTEGER PRIMARY KEY REFERENCES emails(id) ON DELETE CASCADE,
+    needs_reply INTEGER NOT NULL DEFAULT 0,
+    needs_reply_reason TEXT,               -- 'question' | 'request'
+    needs_reply_override INTEGER,          -- set by the user, wins over needs_reply
+    reply_remind_at TEXT,                  -- RFC 3339, NULL = no reminder
+    classified_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_ai_metadata_remind
+    ON email_ai_metadata(reply_remind_at) WHERE reply_remind_at IS NOT NULL;
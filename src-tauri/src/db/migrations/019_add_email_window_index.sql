@@ -0,0 +1,7 @@
+-- Migration 019: Keyset index for windowed folder listing
+-- email_list_window pages through a folder by (date, uid) instead of OFFSET,
+-- so every window is an index range scan, however deep into a 100k-message
+-- folder the user has scrolled.
+
+CREATE INDEX IF NOT EXISTS idx_emails_folder_window
+    ON emails(folder_id, date DESC, uid DESC) WHERE is_deleted = 0;
@@ -0,0 +1,21 @@
+-- templates_fts is an external-content table: UPDATE/DELETE on it reads the
+-- terms to remove from email_templates, which AFTER triggers already changed,
+-- so edited templates left stale terms behind. Use the FTS5 'delete' command
+-- with the old values, like the emails_fts triggers, and rebuild once.
+
+DROP TRIGGER IF EXISTS templates_fts_update;
+DROP TRIGGER IF EXISTS templates_fts_delete;
+
+CREATE TRIGGER templates_fts_update AFTER UPDATE ON email_templates BEGIN
+    INSERT INTO templates_fts(templates_fts, rowid, name, description, subject_template, body_text_template)
+    VALUES ('delete', old.id, old.name, COALESCE(old.description, ''), old.subject_template, COALESCE(old.body_text_template, ''));
+    INSERT INTO templates_fts(rowid, name, description, subject_template, body_text_template)
+    VALUES (new.id, new.name, COALESCE(new.description, ''), new.subject_template, COALESCE(new.body_text_template, ''));
+END;
+
+CREATE TRIGGER templates_fts_delete AFTER DELETE ON email_templates BEGIN
+    INSERT INTO templates_fts(templates_fts, rowid, name, description, subject_template, body_text_template)
+    VALUES ('delete', old.id, old.name, COALESCE(old.description, ''), old.subject_template, COALESCE(old.body_text_template, ''));
+END;
+
+INSERT INTO templates_fts(templates_fts) VALUES('rebuild');
@@ -0,0 +1,33 @@
+-- Migration 021: Content-addressed attachment storage
+-- Downloaded attachments are stored once per SHA-256 digest; attachments
+-- rows point at their content through content_hash. The triggers keep
+-- refcount equal to the number of rows pointing at a blob (including rows
+-- removed by ON DELETE CASCADE), so unreferenced files can be collected.
+
+CREATE TABLE IF NOT EXISTS attachment_blobs (
+    sha256 TEXT PRIMARY KEY,
+    size INTEGER NOT NULL,
+    refcount INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+ALTER TABLE attachments ADD COLUMN content_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_attachments_content_hash ON attachments(content_hash) WHERE content_hash IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_attachment_blobs_unreferenced ON attachment_blobs(refcount) WHERE refcount <= 0;
+
+CREATE TRIGGER IF NOT EXISTS attachment_blobs_ref_insert AFTER INSERT ON attachments
+WHEN new.content_hash IS NOT NULL BEGIN
+    UPDATE attachment_blobs SET refcount = refcount + 1 WHERE sha256 = new.content_hash;
+END;
+
+CREATE TRIGGER IF NOT EXISTS attachment_blobs_ref_update AFTER UPDATE OF content_hash ON attachments
+WHEN old.content_hash IS NOT new.content_hash BEGIN
+    UPDATE attachment_blobs SET refcount = refcount - 1 WHERE sha256 = old.content_hash;
+    UPDATE attachment_blobs SET refcount = refcount + 1 WHERE sha256 = new.content_hash;
+END;
+
+CREATE TRIGGER IF NOT EXISTS attachment_blobs_ref_delete AFTER DELETE ON attachments
+WHEN old.content_hash IS NOT NULL BEGIN
+    UPDATE attachment_blobs SET refcount = refcount - 1 WHERE sha256 = old.content_hash;
+END;
@@ -0,0 +1,7 @@
+-- Migration 022: Attachment encryption at rest
+-- Files written while encryption is enabled are AES-256-GCM encrypted with a
+-- per-file key; wrapped_key holds that key wrapped by the installation key.
+-- NULL means the file is stored in plain text.
+
+ALTER TABLE attachment_blobs ADD COLUMN wrapped_key TEXT;
+ALTER TABLE draft_attachments ADD COLUMN wrapped_key TEXT;
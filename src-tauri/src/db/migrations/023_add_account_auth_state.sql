@@ -0,0 +1,11 @@
+-- Migration 023: Authentication failure tracking
+-- Consecutive sign-in failures per account. After too many the account needs
+-- attention: background sign-ins stop until the user enters a new password.
+
+CREATE TABLE IF NOT EXISTS account_auth_state (
+    account_id INTEGER PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    last_failure_at TEXT,
+    needs_attention INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,11 @@
+-- Migration 024: Server capability cache
+-- Extensions announced by each account's IMAP server (after login) and SMTP
+-- server (EHLO), stored as JSON so client code can branch on them.
+
+CREATE TABLE IF NOT EXISTS account_capabilities (
+    account_id INTEGER PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    imap TEXT,
+    imap_probed_at TEXT,
+    smtp TEXT,
+    smtp_probed_at TEXT
+);
@@ -0,0 +1,17 @@
+-- Migration 025: Message versions
+-- Each distinct content seen under one Message-ID (hash of subject and body),
+-- so a message re-delivered with altered content keeps both versions.
+
+CREATE TABLE IF NOT EXISTS message_versions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    message_id TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    subject TEXT NOT NULL DEFAULT '',
+    body_text TEXT,
+    body_html TEXT,
+    seen_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(account_id, message_id, content_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_versions_message ON message_versions(account_id, message_id);
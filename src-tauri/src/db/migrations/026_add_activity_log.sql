@@ -0,0 +1,13 @@
+-- Migration 026: Activity log
+-- Local audit trail of destructive actions. `undo` holds a JSON payload for
+-- actions that can be reverted; entries outlive the account they refer to.
+
+CREATE TABLE IF NOT EXISTS activity_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER,
+    action TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    undo TEXT,
+    undone_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,28 @@
+-- Migration 027: Focused inbox
+-- `email_focus` holds the Focused/Other split of inbox mail (`manual` = set by
+-- the user, never reclassified). Each user correction is kept in
+-- `focus_signals` and folded into per-account sender/domain weights.
+
+CREATE TABLE IF NOT EXISTS email_focus (
+    email_id INTEGER PRIMARY KEY REFERENCES emails(id) ON DELETE CASCADE,
+    focused INTEGER NOT NULL,
+    score REAL NOT NULL DEFAULT 0,
+    manual INTEGER NOT NULL DEFAULT 0,
+    classified_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS focus_signals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    email_id INTEGER,
+    sender TEXT NOT NULL,
+    focused INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS focus_weights (
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    feature TEXT NOT NULL,                 -- 'sender:<address>' | 'domain:<domain>'
+    weight REAL NOT NULL,
+    PRIMARY KEY (account_id, feature)
+);
@@ -0,0 +1,15 @@
+-- Migration 028: Per-domain security policies
+-- `pattern` is a sender address ('finance@bank.com') or a domain ('bank.com',
+-- which covers its subdomains). The most specific pattern wins.
+
+CREATE TABLE IF NOT EXISTS domain_policies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL UNIQUE,
+    require_dkim INTEGER NOT NULL DEFAULT 0,
+    require_tls INTEGER NOT NULL DEFAULT 0,
+    block_attachments INTEGER NOT NULL DEFAULT 0,
+    block_remote_content INTEGER NOT NULL DEFAULT 0,
+    warn_links INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
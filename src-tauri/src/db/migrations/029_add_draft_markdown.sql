@@ -0,0 +1,5 @@
+-- Migration 029: Markdown drafts
+-- Source of drafts written in Markdown; body_html and body_text hold the
+-- rendered parts.
+
+ALTER TABLE drafts ADD COLUMN body_markdown TEXT;
@@ -0,0 +1,29 @@
+-- Migration 030: Local spam training
+-- `spam_training` queues the user's verdict on messages moved into or out of
+-- Junk (`trained` = verdict the tokens are currently counted as). Token and
+-- sender counts are global across accounts.
+
+CREATE TABLE IF NOT EXISTS spam_training (
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    message_id TEXT NOT NULL,
+    verdict TEXT NOT NULL,                 -- 'spam' | 'ham'
+    trained TEXT,
+    sender TEXT NOT NULL DEFAULT '',
+    requested_at TEXT NOT NULL DEFAULT (datetime('now')),
+    trained_at TEXT,
+    PRIMARY KEY (account_id, message_id)
+);
+
+CREATE TABLE IF NOT EXISTS spam_tokens (
+    token TEXT PRIMARY KEY,
+    spam_count INTEGER NOT NULL DEFAULT 0,
+    ham_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS sender_reputation (
+    address TEXT PRIMARY KEY,
+    spam_count INTEGER NOT NULL DEFAULT 0,
+    ham_count INTEGER NOT NULL DEFAULT 0,
+    score REAL NOT NULL DEFAULT 0,        -- -1 (spam) to 1 (wanted)
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
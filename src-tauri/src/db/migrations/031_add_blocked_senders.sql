@@ -0,0 +1,13 @@
+-- Migration 031: Blocked senders
+-- Each entry owns the filter that enforces it; deleting that filter from the
+-- filter list leaves the entry without one until it is blocked again.
+
+CREATE TABLE IF NOT EXISTS blocked_senders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    pattern TEXT NOT NULL,                 -- lowercase address or domain
+    action TEXT NOT NULL DEFAULT 'spam',   -- 'delete' | 'spam'
+    filter_id INTEGER REFERENCES email_filters(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (account_id, pattern)
+);
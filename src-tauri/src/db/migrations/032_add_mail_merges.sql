@@ -0,0 +1,30 @@
+-- Migration 032: Mail merge
+-- A merge keeps a copy of its template; each CSV row becomes a recipient with
+-- its column values as JSON and its own delivery status.
+
+CREATE TABLE IF NOT EXISTS mail_merges (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    template_id INTEGER,
+    subject_template TEXT NOT NULL,
+    body_html_template TEXT NOT NULL,
+    body_text_template TEXT,
+    per_minute INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'paused',  -- 'running' | 'paused' | 'completed' | 'cancelled'
+    last_error TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS mail_merge_recipients (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    merge_id INTEGER NOT NULL REFERENCES mail_merges(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    email TEXT NOT NULL,
+    variables TEXT NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL DEFAULT 'pending', -- 'pending' | 'sent' | 'failed'
+    error TEXT,
+    sent_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_mail_merge_recipients_merge ON mail_merge_recipients(merge_id, status, position);
@@ -0,0 +1,18 @@
+-- Migration 033: Send quotas
+-- `send_log` holds the last day of deliveries per account (recipients per
+-- message); `send_quota_limits` replaces the provider's limits for an account.
+
+CREATE TABLE IF NOT EXISTS send_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    recipients INTEGER NOT NULL,
+    sent_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_send_log_account ON send_log(account_id, sent_at);
+
+CREATE TABLE IF NOT EXISTS send_quota_limits (
+    account_id INTEGER PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    per_minute INTEGER NOT NULL,
+    per_day INTEGER
+);
@@ -0,0 +1,9 @@
+-- Migration 034: Folder watching
+-- Folders watched for changes over NOTIFY or rotating IDLE, per account.
+-- Accounts without a row watch their Inbox.
+
+CREATE TABLE IF NOT EXISTS folder_watch (
+    account_id INTEGER PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    folders TEXT NOT NULL DEFAULT '["INBOX"]'
+);
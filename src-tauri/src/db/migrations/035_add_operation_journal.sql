@@ -0,0 +1,15 @@
+-- Migration 035: Operation journal
+-- Multi-step operations (sends, queued server changes, filter batch runs)
+-- record their intent before the first step and their progress after each
+-- one. A row left behind by a crash is completed or rolled back at startup.
+
+CREATE TABLE IF NOT EXISTS operation_journal (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,                      -- send | pending_operation | filter_batch
+    account_id INTEGER REFERENCES accounts(id) ON DELETE CASCADE,
+    payload TEXT NOT NULL DEFAULT 'null',    -- JSON encoded intent
+    step TEXT NOT NULL DEFAULT 'started',
+    progress TEXT,                           -- JSON encoded progress within the step
+    started_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
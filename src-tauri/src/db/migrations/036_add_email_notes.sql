@@ -0,0 +1,17 @@
+-- Migration 036: Email notes
+-- Private notes and color flags on messages. They are keyed by Message-ID,
+-- not the local email row, so they survive moves between folders and match
+-- the same message on other devices. Deleted notes stay as tombstones until
+-- the deletion has been synced.
+
+CREATE TABLE IF NOT EXISTS email_notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    message_id TEXT NOT NULL,
+    body TEXT NOT NULL DEFAULT '',
+    color TEXT,                              -- red | orange | yellow | green | blue | purple
+    deleted INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(account_id, message_id)
+);
@@ -0,0 +1,5 @@
+-- Migration 037: IMAP keywords
+-- Custom flags last reported by the server (JSON array), so tags removed by
+-- another client can be taken off the message's labels again.
+
+ALTER TABLE emails ADD COLUMN keywords TEXT NOT NULL DEFAULT '[]';
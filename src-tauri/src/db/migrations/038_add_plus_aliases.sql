@@ -0,0 +1,15 @@
+-- Migration 038: Plus-address aliases
+-- Subaddresses (user+shop@example.com) generated for an account, so the
+-- label given when handing one out is kept. Which alias received a message
+-- is read from its recipients; aliases seen but not generated here are
+-- recognized too.
+
+CREATE TABLE IF NOT EXISTS plus_aliases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,                       -- the part after '+', lowercase
+    label TEXT NOT NULL,
+    address TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(account_id, tag)
+);
@@ -0,0 +1,7 @@
+-- Migration 039: Attachment browser indexes
+-- The attachments view filters and sorts all attachments of an account by
+-- name, type and size.
+
+CREATE INDEX IF NOT EXISTS idx_attachments_filename ON attachments(filename COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS idx_attachments_content_type ON attachments(content_type);
+CREATE INDEX IF NOT EXISTS idx_attachments_size ON attachments(size);
@@ -0,0 +1,24 @@
+-- Migration 040: Meeting invites
+-- Invites sent from the compose flow (VEVENT UID per account) and the
+-- participation status each attendee last replied with.
+
+CREATE TABLE IF NOT EXISTS invites (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    uid TEXT NOT NULL,                       -- VEVENT UID
+    title TEXT NOT NULL,
+    start_at TEXT NOT NULL,                  -- RFC 3339, or YYYY-MM-DD when all day
+    end_at TEXT NOT NULL,
+    all_day INTEGER NOT NULL DEFAULT 0,
+    location TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(account_id, uid)
+);
+
+CREATE TABLE IF NOT EXISTS invite_attendees (
+    invite_id INTEGER NOT NULL REFERENCES invites(id) ON DELETE CASCADE,
+    email TEXT NOT NULL,                     -- lowercase
+    partstat TEXT NOT NULL DEFAULT 'NEEDS-ACTION',
+    responded_at TEXT,
+    PRIMARY KEY (invite_id, email)
+);
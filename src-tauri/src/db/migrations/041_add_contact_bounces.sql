@@ -0,0 +1,8 @@
+-- Migration 041: Bouncing contacts
+-- Set when a delivery status notification reports a permanent failure for
+-- the contact's address; the composer warns before mailing it again.
+
+ALTER TABLE contacts ADD COLUMN bounced_at TEXT;
+ALTER TABLE contacts ADD COLUMN bounce_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_contacts_bounced ON contacts(email) WHERE bounced_at IS NOT NULL;
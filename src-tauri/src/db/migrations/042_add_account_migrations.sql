@@ -0,0 +1,18 @@
+-- Migration 042: Account migrations
+-- Progress of copying folders from one account to another. Messages are
+-- copied in UID order, so `last_uid` is where an interrupted copy resumes.
+
+CREATE TABLE IF NOT EXISTS account_migrations (
+    source_account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    target_account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    source_folder TEXT NOT NULL,
+    target_folder TEXT NOT NULL,
+    total INTEGER NOT NULL DEFAULT 0,      -- messages in the source folder
+    copied INTEGER NOT NULL DEFAULT 0,     -- appended to the target folder
+    skipped INTEGER NOT NULL DEFAULT 0,    -- already in the target (same Message-ID)
+    failed INTEGER NOT NULL DEFAULT 0,     -- refused by the target (too large)
+    last_uid INTEGER NOT NULL DEFAULT 0,   -- highest source UID processed
+    done INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (source_account_id, target_account_id, source_folder, target_folder)
+);
@@ -0,0 +1,21 @@
+-- Migration 043: Email quarantine
+-- Cached messages of a folder whose UIDVALIDITY changed. Their UIDs no
+-- longer identify the same messages, so they are moved here before the
+-- folder is synced again; rows found again by Message-ID are removed once
+-- their local data has been carried over.
+
+CREATE TABLE IF NOT EXISTS email_quarantine (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    folder_id INTEGER NOT NULL REFERENCES folders(id) ON DELETE CASCADE,
+    uid_validity INTEGER NOT NULL,         -- UIDVALIDITY the uid belonged to
+    uid INTEGER NOT NULL,
+    message_id TEXT NOT NULL,
+    subject TEXT,
+    date TEXT,
+    body_text TEXT,
+    body_html TEXT,
+    quarantined_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_quarantine_folder ON email_quarantine(folder_id, message_id);
@@ -0,0 +1,8 @@
+-- Migration 044: Per-account refresh intervals
+-- How often the background poller refreshes an account. Accounts without a
+-- row use the global auto-sync interval.
+
+CREATE TABLE IF NOT EXISTS account_refresh (
+    account_id INTEGER PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    interval_minutes INTEGER NOT NULL CHECK (interval_minutes BETWEEN 1 AND 1440)
+);
@@ -0,0 +1,16 @@
+-- Migration 045: Emails search update trigger on indexed columns only
+-- emails_updated_at updates the row it fires for, which ran emails_au again
+-- inside the outer update. The outer emails_au then removed the old terms a
+-- second time and FTS5 reported the index as malformed. Fire only when an
+-- indexed column changes and rebuild once.
+
+DROP TRIGGER IF EXISTS emails_au;
+
+CREATE TRIGGER emails_au AFTER UPDATE OF subject, body_text, from_name, from_address ON emails BEGIN
+    INSERT INTO emails_fts(emails_fts, rowid, subject, body_text, from_name, from_address)
+    VALUES ('delete', OLD.id, OLD.subject, OLD.body_text, OLD.from_name, OLD.from_address);
+    INSERT INTO emails_fts(rowid, subject, body_text, from_name, from_address)
+    VALUES (NEW.id, NEW.subject, NEW.body_text, NEW.from_name, NEW.from_address);
+END;
+
+INSERT INTO emails_fts(emails_fts) VALUES('rebuild');
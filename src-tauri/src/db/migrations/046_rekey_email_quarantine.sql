@@ -0,0 +1,20 @@
+-- Migration 046: Quarantine keeps the cached rows
+-- Messages of a folder whose UIDVALIDITY changed now stay in `emails`, hidden
+-- under a placeholder UID, so their labels, keywords, focus and reply state
+-- and downloaded data survive. The quarantine row remembers the old UID until
+-- the message is found again by Message-ID and takes its new UID. The old
+-- table held copies of deleted rows and is dropped with them.
+
+DROP TABLE IF EXISTS email_quarantine;
+
+CREATE TABLE email_quarantine (
+    email_id INTEGER PRIMARY KEY REFERENCES emails(id) ON DELETE CASCADE,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    folder_id INTEGER NOT NULL REFERENCES folders(id) ON DELETE CASCADE,
+    uid_validity INTEGER NOT NULL,         -- UIDVALIDITY the uid belonged to
+    uid INTEGER NOT NULL,                  -- UID before the reset
+    message_id TEXT NOT NULL,
+    quarantined_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_quarantine_folder ON email_quarantine(folder_id, message_id);
@@ -3,6 +3,9 @@
 //! Provides SQLite database operations for email storage, accounts, and settings.
 //! SECURITY HARDENED: Input validation, LIKE escaping, pagination limits
 
+pub mod migrate;
+pub mod recovery;
+
 use rusqlite::{Connection, params};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -15,10 +18,39 @@ use r2d2_sqlite::SqliteConnectionManager;
 
 // SECURITY: Maximum pagination limits
 const MAX_PAGE_SIZE: i32 = 100;
+/// Maximum messages per window of `get_emails_window` (virtual scrolling)
+const MAX_WINDOW_SIZE: i32 = 500;
 const MAX_SEARCH_LIMIT: i32 = 200;
 
+/// remote_name prefix for local-only folders (keeps them apart from IMAP mailbox names)
+pub const LOCAL_FOLDER_PREFIX: &str = "owlivion-local/";
+
+/// Maximum length of a local folder name
+const MAX_LOCAL_FOLDER_NAME: usize = 255;
+
+fn local_remote_name(name: &str) -> String {
+    format!("{}{}", LOCAL_FOLDER_PREFIX, name)
+}
+
+/// SECURITY: Validate local folder names (non-empty, bounded, no control characters)
+fn validate_local_folder_name(name: &str) -> DbResult<&str> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_LOCAL_FOLDER_NAME || name.chars().any(|c| c.is_control()) {
+        return Err(DbError::Constraint("Invalid local folder name".to_string()));
+    }
+    Ok(name)
+}
+
+/// Parse a SQLite `datetime('now')` value (UTC) or an RFC 3339 timestamp
+pub fn parse_db_timestamp(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .map(|naive| naive.and_utc())
+        .ok()
+        .or_else(|| chrono::DateTime::parse_from_rfc3339(value).ok().map(|d| d.with_timezone(&chrono::Utc)))
+}
+
 /// SECURITY: Escape LIKE wildcards to prevent pattern injection
-fn escape_like_pattern(query: &str) -> String {
+pub(crate) fn escape_like_pattern(query: &str) -> String {
     query
         .replace('\\', "\\\\")
         .replace('%', "\\%")
@@ -126,6 +158,34 @@ pub struct Database {
     pool: Arc<Pool<SqliteConnectionManager>>,
 }
 
+/// Whether any messages are quarantined after a UIDVALIDITY change
+fn has_quarantine(conn: &Connection) -> DbResult<bool> {
+    Ok(conn.query_row("SELECT EXISTS (SELECT 1 FROM email_quarantine)", [], |row| row.get(0))?)
+}
+
+/// Give a quarantined copy of `email` (same folder and Message-ID) the UID
+/// it was listed under, so the upsert that follows updates that row
+/// Placeholder Message-IDs (`uid-N`) never match.
+fn claim_quarantined(conn: &Connection, email: &NewEmail) -> DbResult<()> {
+    if email.message_id.starts_with("uid-") {
+        return Ok(());
+    }
+    let claimed: Vec<i64> = conn
+        .prepare(
+            "SELECT q.email_id FROM email_quarantine q
+             WHERE q.account_id = ?1 AND q.folder_id = ?2 AND q.message_id = ?3
+               AND NOT EXISTS (SELECT 1 FROM emails e WHERE e.account_id = ?1 AND e.folder_id = ?2 AND e.uid = ?4)
+             ORDER BY q.email_id DESC LIMIT 1",
+        )?
+        .query_map(params![email.account_id, email.folder_id, email.message_id, email.uid], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    if let Some(email_id) = claimed.first() {
+        conn.execute("UPDATE emails SET uid = ?2 WHERE id = ?1", params![email_id, email.uid])?;
+        conn.execute("DELETE FROM email_quarantine WHERE email_id = ?1", params![email_id])?;
+    }
+    Ok(())
+}
+
 impl Database {
     /// Create a new database connection pool
     /// Uses r2d2 for connection pooling (10-20x faster than mutex locking)
@@ -210,164 +270,20 @@ impl Database {
     // MIGRATIONS
     // =========================================================================
 
-    /// Run migrations for existing databases
+    /// Bring the schema up to the latest version (see `migrate`)
     fn run_migrations(conn: &Connection) -> DbResult<()> {
-        // Migration 1: Add signature column to accounts table if not exists
-        let has_signature: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM pragma_table_info('accounts') WHERE name = 'signature'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_signature {
-            log::info!("Running migration: Adding signature column to accounts");
-            conn.execute("ALTER TABLE accounts ADD COLUMN signature TEXT DEFAULT ''", [])?;
-        }
-
-        // Migration 2: Add accept_invalid_certs column to accounts table if not exists
-        let has_accept_invalid_certs: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM pragma_table_info('accounts') WHERE name = 'accept_invalid_certs'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_accept_invalid_certs {
-            log::info!("Running migration: Adding accept_invalid_certs column to accounts");
-            conn.execute("ALTER TABLE accounts ADD COLUMN accept_invalid_certs INTEGER NOT NULL DEFAULT 0", [])?;
-        }
-
-        // Migration 3: Add close_to_tray setting if not exists
-        let has_close_to_tray: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM settings WHERE key = 'close_to_tray'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_close_to_tray {
-            log::info!("Running migration: Adding close_to_tray setting");
-            conn.execute("INSERT INTO settings (key, value) VALUES ('close_to_tray', 'true')", [])?;
-        }
-
-        // Migration 4: Delta Sync - Add deleted column to accounts table
-        let has_accounts_deleted: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM pragma_table_info('accounts') WHERE name = 'deleted'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_accounts_deleted {
-            log::info!("Running migration: Adding deleted column to accounts (delta sync)");
-            conn.execute("ALTER TABLE accounts ADD COLUMN deleted INTEGER NOT NULL DEFAULT 0", [])?;
-            conn.execute("CREATE INDEX IF NOT EXISTS idx_accounts_deleted ON accounts(deleted) WHERE deleted = 0", [])?;
-        }
-
-        // Migration 5: Delta Sync - Add deleted column to contacts table
-        let has_contacts_deleted: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM pragma_table_info('contacts') WHERE name = 'deleted'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_contacts_deleted {
-            log::info!("Running migration: Adding deleted column to contacts (delta sync)");
-            conn.execute("ALTER TABLE contacts ADD COLUMN deleted INTEGER NOT NULL DEFAULT 0", [])?;
-            conn.execute("CREATE INDEX IF NOT EXISTS idx_contacts_deleted ON contacts(deleted) WHERE deleted = 0", [])?;
-        }
-
-        // Migration 6: Delta Sync - Create sync_metadata table
-        let has_sync_metadata: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='sync_metadata'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_sync_metadata {
-            log::info!("Running migration: Creating sync_metadata table (delta sync)");
-            conn.execute_batch(r#"
-                CREATE TABLE sync_metadata (
-                    data_type TEXT PRIMARY KEY CHECK (data_type IN ('accounts', 'contacts', 'preferences', 'signatures')),
-                    last_sync_at TEXT,
-                    last_sync_version INTEGER DEFAULT 0,
-                    items_synced INTEGER DEFAULT 0,
-                    items_changed INTEGER DEFAULT 0,
-                    items_deleted INTEGER DEFAULT 0,
-                    sync_status TEXT DEFAULT 'idle' CHECK (sync_status IN ('idle', 'syncing', 'error')),
-                    error_message TEXT,
-                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-                );
-
-                INSERT INTO sync_metadata (data_type, last_sync_at) VALUES
-                    ('accounts', NULL),
-                    ('contacts', NULL),
-                    ('preferences', NULL),
-                    ('signatures', NULL);
-
-                CREATE TRIGGER sync_metadata_updated_at AFTER UPDATE ON sync_metadata
-                BEGIN
-                    UPDATE sync_metadata SET updated_at = datetime('now') WHERE data_type = NEW.data_type;
-                END;
-            "#)?;
-        }
-
-        // Migration 7: Add priority_enabled column to accounts table
-        let has_priority_enabled: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM pragma_table_info('accounts') WHERE name = 'priority_enabled'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_priority_enabled {
-            log::info!("Running migration: Adding priority_enabled column to accounts");
-            conn.execute("ALTER TABLE accounts ADD COLUMN priority_enabled INTEGER DEFAULT 1", [])?;
-            conn.execute("CREATE INDEX IF NOT EXISTS idx_accounts_priority ON accounts(priority_enabled)", [])?;
-        }
-
-        // Migration 8: Email Templates - Create email_templates table
-        let has_templates: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='email_templates'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_templates {
-            log::info!("Running migration: Creating email_templates table");
-            conn.execute_batch(include_str!("migrations/007_add_email_templates.sql"))?;
-        }
-
-        // Migration 9: Add enable_priority_fetch column to accounts table
-        let has_enable_priority_fetch: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM pragma_table_info('accounts') WHERE name = 'enable_priority_fetch'",
-                [],
-                |row| row.get(0),
-            )
-            .unwrap_or(false);
-
-        if !has_enable_priority_fetch {
-            log::info!("Running migration: Adding enable_priority_fetch column to accounts");
-            conn.execute_batch(include_str!("migrations/008_add_account_priority_settings.sql"))?;
+        let ran = migrate::migrate(conn, migrate::MIGRATIONS)?;
+        if !ran.is_empty() {
+            log::info!("Database schema migrated to version {}", migrate::latest_version());
         }
-
         Ok(())
     }
 
+    /// Applied schema version
+    pub fn schema_version(&self) -> DbResult<u32> {
+        migrate::current_version(&*self.get_conn()?)
+    }
+
     // =========================================================================
     // ACCOUNTS
     // =========================================================================
@@ -761,6 +677,58 @@ impl Database {
         Ok(())
     }
 
+    /// Pause or resume background syncing for an account
+    pub fn set_account_sync_paused(&self, account_id: i64, paused: bool) -> DbResult<()> {
+        let conn = self.get_conn()?;
+
+        let affected = conn.execute(
+            "UPDATE accounts SET sync_paused = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![paused as i32, account_id],
+        )?;
+
+        if affected == 0 {
+            return Err(DbError::NotFound(format!("Account {}", account_id)));
+        }
+        Ok(())
+    }
+
+    /// Enable or disable an account; its local data is kept either way
+    pub fn set_account_active(&self, account_id: i64, active: bool) -> DbResult<()> {
+        let conn = self.get_conn()?;
+
+        let affected = conn.execute(
+            "UPDATE accounts SET is_active = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![active as i32, account_id],
+        )?;
+
+        if affected == 0 {
+            return Err(DbError::NotFound(format!("Account {}", account_id)));
+        }
+        Ok(())
+    }
+
+    /// Ids of disabled accounts
+    pub fn get_inactive_account_ids(&self) -> DbResult<Vec<i64>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("SELECT id FROM accounts WHERE is_active = 0 ORDER BY id")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Ids of accounts whose background sync is paused
+    pub fn get_sync_paused_account_ids(&self) -> DbResult<Vec<i64>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare("SELECT id FROM accounts WHERE sync_paused = 1 ORDER BY id")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+
+        Ok(ids)
+    }
+
     /// Get account metadata (display_name and email) for badge generation
     pub fn get_account_metadata(&self, account_id: i64) -> DbResult<(String, String)> {
         let conn = self.get_conn()?;
@@ -790,7 +758,8 @@ impl Database {
                 name = excluded.name,
                 folder_type = excluded.folder_type,
                 is_subscribed = excluded.is_subscribed,
-                is_selectable = excluded.is_selectable
+                is_selectable = excluded.is_selectable,
+                delimiter = excluded.delimiter
             "#,
             params![
                 folder.account_id,
@@ -820,7 +789,7 @@ impl Database {
         let mut stmt = conn.prepare(
             r#"
             SELECT id, account_id, name, remote_name, folder_type,
-                   unread_count, total_count, is_subscribed, is_selectable, delimiter
+                   unread_count, total_count, is_subscribed, is_selectable, delimiter, is_local, parent_id
             FROM folders
             WHERE account_id = ?1
             ORDER BY
@@ -851,6 +820,8 @@ impl Database {
                     is_subscribed: row.get(7)?,
                     is_selectable: row.get(8)?,
                     delimiter: row.get(9)?,
+                    is_local: row.get(10)?,
+                    parent_id: row.get(11)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -858,6 +829,106 @@ impl Database {
         Ok(folders)
     }
 
+    /// Remote name of the account's folder with a special role (trash, sent, ...)
+    pub fn get_special_folder(&self, account_id: i64, folder_type: &str) -> DbResult<Option<String>> {
+        let conn = self.get_conn()?;
+        let result = conn.query_row(
+            r#"
+            SELECT remote_name FROM folders
+            WHERE account_id = ?1 AND folder_type = ?2 AND is_local = 0 AND is_selectable = 1
+            ORDER BY id ASC
+            LIMIT 1
+            "#,
+            params![account_id, folder_type],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(remote_name) => Ok(Some(remote_name)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    /// Link the account's IMAP folders to their parents using each folder's delimiter
+    pub fn link_folder_parents(&self, account_id: i64) -> DbResult<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        let folders: Vec<(i64, String, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT id, remote_name, COALESCE(delimiter, '/') FROM folders WHERE account_id = ?1 AND is_local = 0",
+            )?;
+            let rows = stmt.query_map([account_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let ids: std::collections::HashMap<&str, i64> =
+            folders.iter().map(|(id, remote_name, _)| (remote_name.as_str(), *id)).collect();
+
+        for (id, remote_name, delimiter) in &folders {
+            let parent_id = crate::mail::folder_tree::parent_path(remote_name, delimiter)
+                .and_then(|parent| ids.get(parent).copied());
+            tx.execute(
+                "UPDATE folders SET parent_id = ?1 WHERE id = ?2 AND parent_id IS NOT ?1",
+                params![parent_id, id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Rename an IMAP folder and every folder below it
+    ///
+    /// Cached emails stay attached (they reference folder ids); queued
+    /// operations and backfill progress keyed by remote name follow the rename.
+    /// Returns the number of folders renamed.
+    pub fn rename_folder_subtree(&self, account_id: i64, old_path: &str, new_path: &str) -> DbResult<usize> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        let folders: Vec<(i64, String, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT id, remote_name, COALESCE(delimiter, '/') FROM folders WHERE account_id = ?1 AND is_local = 0",
+            )?;
+            let rows = stmt.query_map([account_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut renamed = 0;
+        for (id, remote_name, delimiter) in &folders {
+            let Some(rebased) = crate::mail::folder_tree::rebase_path(remote_name, old_path, new_path, delimiter) else {
+                continue;
+            };
+            let name = crate::mail::folder_tree::leaf_name(&rebased, delimiter).to_string();
+
+            tx.execute(
+                "UPDATE folders SET remote_name = ?1, name = ?2, updated_at = datetime('now') WHERE id = ?3",
+                params![rebased, name, id],
+            )?;
+            tx.execute(
+                "UPDATE pending_operations SET folder = ?1 WHERE account_id = ?2 AND folder = ?3",
+                params![rebased, account_id, remote_name],
+            )?;
+            tx.execute(
+                "UPDATE backfill_state SET folder = ?1 WHERE account_id = ?2 AND folder = ?3",
+                params![rebased, account_id, remote_name],
+            )?;
+            renamed += 1;
+        }
+
+        if renamed == 0 {
+            return Err(DbError::NotFound(format!("Folder {}", old_path)));
+        }
+
+        tx.commit()?;
+
+        // A move changes the parent of the renamed folder itself
+        self.link_folder_parents(account_id)?;
+        Ok(renamed)
+    }
+
     /// Update folder counts
     pub fn update_folder_counts(&self, folder_id: i64, unread: i32, total: i32) -> DbResult<()> {
         // SECURITY: Handle mutex poisoning gracefully
@@ -869,14 +940,257 @@ impl Database {
         Ok(())
     }
 
+    // =========================================================================
+    // LOCAL FOLDERS
+    // =========================================================================
+
+    /// Create a local-only folder (stored in SQLite, never synced to IMAP)
+    pub fn create_local_folder(&self, account_id: i64, name: &str) -> DbResult<i64> {
+        let name = validate_local_folder_name(name)?;
+        let conn = self.get_conn()?;
+
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM folders WHERE account_id = ?1 AND remote_name = ?2",
+            params![account_id, local_remote_name(name)],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Err(DbError::Constraint(format!("Local folder '{}' already exists", name)));
+        }
+
+        conn.execute(
+            r#"
+            INSERT INTO folders (account_id, name, remote_name, folder_type, is_subscribed, is_selectable, is_local)
+            VALUES (?1, ?2, ?3, 'custom', 1, 1, 1)
+            "#,
+            params![account_id, name, local_remote_name(name)],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Id of the local folder `name`, created when missing
+    pub fn get_or_create_local_folder(&self, account_id: i64, name: &str) -> DbResult<i64> {
+        let name = validate_local_folder_name(name)?;
+        match self.get_local_folders(account_id)?.into_iter().find(|f| f.name == name) {
+            Some(folder) => Ok(folder.id),
+            None => self.create_local_folder(account_id, name),
+        }
+    }
+
+    /// Get local-only folders for account
+    pub fn get_local_folders(&self, account_id: i64) -> DbResult<Vec<Folder>> {
+        Ok(self
+            .get_folders(account_id)?
+            .into_iter()
+            .filter(|f| f.is_local)
+            .collect())
+    }
+
+    /// Rename a local-only folder
+    pub fn rename_local_folder(&self, folder_id: i64, name: &str) -> DbResult<()> {
+        let name = validate_local_folder_name(name)?;
+        let conn = self.get_conn()?;
+
+        let updated = conn
+            .execute(
+                "UPDATE folders SET name = ?1, remote_name = ?2, updated_at = datetime('now') WHERE id = ?3 AND is_local = 1",
+                params![name, local_remote_name(name), folder_id],
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::SqliteFailure(err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation => {
+                    DbError::Constraint(format!("Local folder '{}' already exists", name))
+                }
+                other => DbError::Sqlite(other),
+            })?;
+
+        if updated == 0 {
+            return Err(DbError::NotFound(format!("Local folder {}", folder_id)));
+        }
+        Ok(())
+    }
+
+    /// Delete a local-only folder and all messages stored in it
+    pub fn delete_local_folder(&self, folder_id: i64) -> DbResult<()> {
+        let conn = self.get_conn()?;
+        let deleted = conn.execute(
+            "DELETE FROM folders WHERE id = ?1 AND is_local = 1",
+            [folder_id],
+        )?;
+
+        if deleted == 0 {
+            return Err(DbError::NotFound(format!("Local folder {}", folder_id)));
+        }
+        Ok(())
+    }
+
+    /// Store a message in a local folder together with its raw source
+    /// Assigns the next free UID in the folder and refreshes folder counts
+    pub fn insert_local_email(&self, email: &NewEmail, raw_source: &[u8]) -> DbResult<i64> {
+        let folder = self.get_folder_by_id(email.folder_id)?;
+        if !folder.is_local {
+            return Err(DbError::Constraint("Target folder is not a local folder".to_string()));
+        }
+
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        let next_uid: u32 = tx.query_row(
+            "SELECT COALESCE(MAX(uid), 0) + 1 FROM emails WHERE folder_id = ?1",
+            [email.folder_id],
+            |row| row.get(0),
+        )?;
+
+        tx.execute(
+            r#"
+            INSERT INTO emails (
+                account_id, folder_id, message_id, uid,
+                from_address, from_name, to_addresses, cc_addresses, bcc_addresses, reply_to,
+                subject, preview, body_text, body_html, date,
+                is_read, is_starred, is_deleted, is_spam, is_draft, is_answered, is_forwarded,
+                has_attachments, has_inline_images,
+                thread_id, in_reply_to, references_header, raw_headers, raw_size, priority, labels
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31
+            )
+            "#,
+            params![
+                folder.account_id,
+                email.folder_id,
+                email.message_id,
+                next_uid,
+                email.from_address,
+                email.from_name,
+                email.to_addresses,
+                email.cc_addresses,
+                email.bcc_addresses,
+                email.reply_to,
+                email.subject,
+                email.preview,
+                email.body_text,
+                email.body_html,
+                email.date,
+                email.is_read,
+                email.is_starred,
+                false,
+                email.is_spam,
+                email.is_draft,
+                email.is_answered,
+                email.is_forwarded,
+                email.has_attachments,
+                email.has_inline_images,
+                email.thread_id,
+                email.in_reply_to,
+                email.references_header,
+                email.raw_headers,
+                email.raw_size,
+                email.priority,
+                email.labels,
+            ],
+        )?;
+        let email_id = tx.last_insert_rowid();
+
+        tx.execute(
+            "INSERT INTO local_message_sources (email_id, raw_source) VALUES (?1, ?2)",
+            params![email_id, raw_source],
+        )?;
+
+        tx.commit()?;
+        self.refresh_folder_counts(email.folder_id)?;
+
+        Ok(email_id)
+    }
+
+    /// Move a message that is already stored locally into another local folder
+    pub fn move_local_email(&self, email_id: i64, target_folder_id: i64) -> DbResult<()> {
+        let target = self.get_folder_by_id(target_folder_id)?;
+        if !target.is_local {
+            return Err(DbError::Constraint("Target folder is not a local folder".to_string()));
+        }
+
+        let source_folder_id: i64 = self.query_row(
+            "SELECT e.folder_id FROM emails e JOIN local_message_sources s ON s.email_id = e.id WHERE e.id = ?1",
+            [email_id],
+            |row| row.get(0),
+        )?;
+
+        let conn = self.get_conn()?;
+        conn.execute(
+            r#"
+            UPDATE emails SET
+                account_id = ?1,
+                folder_id = ?2,
+                uid = (SELECT COALESCE(MAX(uid), 0) + 1 FROM emails WHERE folder_id = ?2)
+            WHERE id = ?3
+            "#,
+            params![target.account_id, target_folder_id, email_id],
+        )?;
+        drop(conn);
+
+        self.refresh_folder_counts(source_folder_id)?;
+        self.refresh_folder_counts(target_folder_id)?;
+        Ok(())
+    }
+
+    /// Get raw RFC 822 source of a locally stored message
+    pub fn get_local_email_source(&self, email_id: i64) -> DbResult<Vec<u8>> {
+        self.query_row(
+            "SELECT raw_source FROM local_message_sources WHERE email_id = ?1",
+            [email_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| match e {
+            DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows) => {
+                DbError::NotFound(format!("Local source for email {}", email_id))
+            }
+            other => other,
+        })
+    }
+
+    /// Get (sender, date, raw source) of all messages in a local folder, oldest first
+    pub fn get_local_folder_sources(&self, folder_id: i64) -> DbResult<Vec<(String, String, Vec<u8>)>> {
+        self.query(
+            r#"
+            SELECT e.from_address, e.date, s.raw_source
+            FROM emails e
+            JOIN local_message_sources s ON s.email_id = e.id
+            WHERE e.folder_id = ?1 AND e.is_deleted = 0
+            ORDER BY e.date ASC
+            "#,
+            [folder_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+    }
+
+    /// Recalculate unread/total counts of a folder from stored messages
+    pub fn refresh_folder_counts(&self, folder_id: i64) -> DbResult<()> {
+        let conn = self.get_conn()?;
+        conn.execute(
+            r#"
+            UPDATE folders SET
+                unread_count = (SELECT COUNT(*) FROM emails WHERE folder_id = ?1 AND is_deleted = 0 AND is_read = 0),
+                total_count = (SELECT COUNT(*) FROM emails WHERE folder_id = ?1 AND is_deleted = 0)
+            WHERE id = ?1
+            "#,
+            [folder_id],
+        )?;
+        Ok(())
+    }
+
     // =========================================================================
     // EMAILS
     // =========================================================================
 
     /// Insert or update email
+    /// A quarantined copy of the message (see `uid_validity`) is updated
+    /// instead of adding a row.
     pub fn upsert_email(&self, email: &NewEmail) -> DbResult<i64> {
         // SECURITY: Handle mutex poisoning gracefully
         let conn = self.get_conn()?;
+        if has_quarantine(&conn)? {
+            claim_quarantined(&conn, email)?;
+        }
 
         conn.execute(
             r#"
@@ -899,7 +1213,8 @@ impl Database {
                 is_answered = excluded.is_answered,
                 is_forwarded = excluded.is_forwarded,
                 body_text = COALESCE(excluded.body_text, body_text),
-                body_html = COALESCE(excluded.body_html, body_html)
+                body_html = COALESCE(excluded.body_html, body_html),
+                raw_size = CASE WHEN excluded.raw_size > 0 THEN excluded.raw_size ELSE raw_size END
             "#,
             params![
                 email.account_id,
@@ -950,6 +1265,7 @@ impl Database {
         let tx = conn.transaction()?;
 
         let mut email_ids = Vec::with_capacity(emails.len());
+        let quarantine = has_quarantine(&tx)?;
 
         // Prepare statement once for all emails
         let mut stmt = tx.prepare(r#"
@@ -972,10 +1288,14 @@ impl Database {
                 is_answered = excluded.is_answered,
                 is_forwarded = excluded.is_forwarded,
                 body_text = COALESCE(excluded.body_text, body_text),
-                body_html = COALESCE(excluded.body_html, body_html)
+                body_html = COALESCE(excluded.body_html, body_html),
+                raw_size = CASE WHEN excluded.raw_size > 0 THEN excluded.raw_size ELSE raw_size END
         "#)?;
 
         for email in emails {
+            if quarantine {
+                claim_quarantined(&tx, email)?;
+            }
             stmt.execute(params![
                 email.account_id,
                 email.folder_id,
@@ -1072,6 +1392,84 @@ impl Database {
         Ok(emails)
     }
 
+    /// Get the window of a folder's messages that follows `before` (newest first)
+    /// Keyset pagination on (date, uid): cost does not grow with scroll depth.
+    /// The total is only counted for the first window.
+    pub fn get_emails_window(
+        &self,
+        folder_id: i64,
+        before: Option<&EmailCursor>,
+        count: i32,
+    ) -> DbResult<EmailWindow> {
+        let safe_count = count.clamp(1, MAX_WINDOW_SIZE);
+        let conn = self.get_conn()?;
+
+        let columns = "id, message_id, uid, from_address, from_name, subject, preview, date, \
+                       is_read, is_starred, has_attachments, has_inline_images";
+        let map_row = |row: &rusqlite::Row<'_>| {
+            Ok(EmailSummary {
+                id: row.get(0)?,
+                message_id: row.get(1)?,
+                uid: row.get(2)?,
+                from_address: row.get(3)?,
+                from_name: row.get(4)?,
+                subject: row.get(5)?,
+                preview: row.get(6)?,
+                date: row.get(7)?,
+                is_read: row.get(8)?,
+                is_starred: row.get(9)?,
+                has_attachments: row.get(10)?,
+                has_inline_images: row.get(11)?,
+            })
+        };
+
+        // One extra row tells whether another window follows
+        let mut emails = match before {
+            Some(cursor) => conn
+                .prepare(&format!(
+                    "SELECT {} FROM emails
+                     WHERE folder_id = ?1 AND is_deleted = 0 AND (date, uid) < (?2, ?3)
+                     ORDER BY date DESC, uid DESC
+                     LIMIT ?4",
+                    columns
+                ))?
+                .query_map(params![folder_id, cursor.date, cursor.uid, safe_count + 1], map_row)?
+                .collect::<Result<Vec<_>, _>>()?,
+            None => conn
+                .prepare(&format!(
+                    "SELECT {} FROM emails
+                     WHERE folder_id = ?1 AND is_deleted = 0
+                     ORDER BY date DESC, uid DESC
+                     LIMIT ?2",
+                    columns
+                ))?
+                .query_map(params![folder_id, safe_count + 1], map_row)?
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        let has_more = emails.len() > safe_count as usize;
+        emails.truncate(safe_count as usize);
+
+        let total = match before {
+            Some(_) => None,
+            None => Some(conn.query_row(
+                "SELECT COUNT(*) FROM emails WHERE folder_id = ?1 AND is_deleted = 0",
+                [folder_id],
+                |row| row.get(0),
+            )?),
+        };
+
+        Ok(EmailWindow {
+            next_cursor: emails
+                .last()
+                .filter(|_| has_more)
+                .map(|last| EmailCursor { date: last.date.clone(), uid: last.uid }),
+            emails,
+            has_more,
+            total,
+        })
+    }
+
     /// Get full email by ID
     pub fn get_email(&self, id: i64) -> DbResult<Email> {
         // SECURITY: Handle mutex poisoning gracefully
@@ -1150,6 +1548,40 @@ impl Database {
         Ok(())
     }
 
+    /// Find cached email ids by IMAP UIDs in a folder (identified by remote name)
+    pub fn find_email_ids_by_uids(&self, account_id: i64, folder: &str, uids: &[u32]) -> DbResult<Vec<i64>> {
+        if uids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let uid_placeholders = vec!["?"; uids.len()].join(",");
+        let query = format!(
+            r#"
+            SELECT e.id FROM emails e
+            JOIN folders f ON f.id = e.folder_id
+            WHERE e.account_id = ? AND f.remote_name = ? AND e.uid IN ({})
+            "#,
+            uid_placeholders
+        );
+        let mut params: Vec<&dyn rusqlite::ToSql> = vec![&account_id, &folder];
+        for uid in uids {
+            params.push(uid);
+        }
+
+        self.query(&query, &params[..], |row| row.get(0))
+    }
+
+    /// Remove cached emails (attachments and FTS entries follow via cascade/triggers)
+    pub fn delete_emails(&self, ids: &[i64]) -> DbResult<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        for id in ids {
+            tx.execute("DELETE FROM emails WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Search emails using FTS
     /// SECURITY: Validates account_id, sanitizes FTS5 query, and enforces search limits
     pub fn search_emails(&self, account_id: i64, query: &str, limit: i32) -> DbResult<Vec<EmailSummary>> {
@@ -1180,15 +1612,21 @@ impl Database {
                    e.subject, e.preview, e.date,
                    e.is_read, e.is_starred, e.has_attachments, e.has_inline_images
             FROM emails e
-            JOIN emails_fts fts ON fts.rowid = e.id
-            WHERE e.account_id = ?1 AND emails_fts MATCH ?2
+            WHERE e.account_id = ?1
+              AND (e.id IN (SELECT rowid FROM emails_fts WHERE emails_fts MATCH ?2)
+                   OR EXISTS (SELECT 1 FROM email_notes n
+                              WHERE n.account_id = e.account_id AND n.message_id = e.message_id
+                                AND n.deleted = 0 AND n.body LIKE ?4 ESCAPE '\'))
             ORDER BY e.date DESC
             LIMIT ?3
             "#,
         )?;
 
+        // Private notes on a message match too
+        let note_pattern = format!("%{}%", escape_like_pattern(query));
+
         let emails = stmt
-            .query_map(params![account_id, sanitized_query, safe_limit], |row| {
+            .query_map(params![account_id, sanitized_query, safe_limit, note_pattern], |row| {
                 Ok(EmailSummary {
                     id: row.get(0)?,
                     message_id: row.get(1)?,
@@ -1232,22 +1670,25 @@ impl Database {
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(account_id)];
         let mut param_index = 2;
 
-        // FTS5 query (if provided)
-        let use_fts = if let Some(ref query) = filters.query {
+        // FTS5 query (if provided), also matching private notes on a message
+        if let Some(ref query) = filters.query {
             if !query.is_empty() && query.len() <= 500 {
                 let sanitized = sanitize_fts5_query(query);
                 if !sanitized.is_empty() {
+                    where_clauses.push(format!(
+                        "(e.id IN (SELECT rowid FROM emails_fts WHERE emails_fts MATCH ?{}) \
+                         OR EXISTS (SELECT 1 FROM email_notes n \
+                                    WHERE n.account_id = e.account_id AND n.message_id = e.message_id \
+                                      AND n.deleted = 0 AND n.body LIKE ?{} ESCAPE '\\'))",
+                        param_index,
+                        param_index + 1
+                    ));
                     params.push(Box::new(sanitized));
-                    true
-                } else {
-                    false
+                    params.push(Box::new(format!("%{}%", escape_like_pattern(query))));
+                    param_index += 2;
                 }
-            } else {
-                false
             }
-        } else {
-            false
-        };
+        }
 
         // Date range filter
         if let Some(ref date_range) = filters.date_range {
@@ -1321,32 +1762,11 @@ impl Database {
             FROM emails e
         "#;
 
-        let fts_join = if use_fts {
-            "JOIN emails_fts fts ON fts.rowid = e.id"
-        } else {
-            ""
-        };
-
-        let fts_where = if use_fts {
-            "emails_fts MATCH ?2"
-        } else {
-            ""
-        };
-
-        let mut all_where_clauses = where_clauses.clone();
-        if use_fts {
-            all_where_clauses.push(fts_where.to_string());
-        }
-
-        let where_clause = if !all_where_clauses.is_empty() {
-            format!("WHERE {}", all_where_clauses.join(" AND "))
-        } else {
-            String::new()
-        };
+        let where_clause = format!("WHERE {}", where_clauses.join(" AND "));
 
         let query = format!(
-            "{} {} {} ORDER BY e.date DESC LIMIT {} OFFSET {}",
-            base_select, fts_join, where_clause, safe_limit, safe_offset
+            "{} {} ORDER BY e.date DESC LIMIT {} OFFSET {}",
+            base_select, where_clause, safe_limit, safe_offset
         );
 
         // Execute query
@@ -1354,39 +1774,139 @@ impl Database {
 
         let conn = self.get_conn()?;
 
-        let mut stmt = conn.prepare(&query)?;
+        let mut stmt = conn.prepare(&query)?;
+
+        // Convert params to references for query_map
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let emails = stmt
+            .query_map(&param_refs[..], |row| {
+                Ok(EmailSummary {
+                    id: row.get(0)?,
+                    message_id: row.get(1)?,
+                    uid: row.get(2)?,
+                    from_address: row.get(3)?,
+                    from_name: row.get(4)?,
+                    subject: row.get(5)?,
+                    preview: row.get(6)?,
+                    date: row.get(7)?,
+                    is_read: row.get(8)?,
+                    is_starred: row.get(9)?,
+                    has_attachments: row.get(10)?,
+                    has_inline_images: row.get(11)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let search_time = start_time.elapsed().as_millis() as i64;
+        let total_count = emails.len() as i64;
+        let has_more = emails.len() as i32 == safe_limit;
+
+        Ok(SearchResult {
+            emails,
+            total_count,
+            has_more,
+            search_time,
+        })
+    }
+
+    // =========================================================================
+    // VIRTUAL FOLDERS
+    // =========================================================================
+
+    /// Get a page of a cross-account virtual folder (Starred / Unread / VIP / Needs reply) from the local cache
+    /// Messages present in several folders of one account (e.g. Gmail labels) are listed once
+    /// SECURITY: Enforces pagination limits to prevent DoS
+    pub fn get_virtual_folder_emails(
+        &self,
+        kind: VirtualFolderKind,
+        limit: i32,
+        offset: i32,
+    ) -> DbResult<VirtualFolderPage> {
+        let safe_limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let safe_offset = offset.max(0);
+
+        let condition = match kind {
+            VirtualFolderKind::Starred => "e.is_starred = 1",
+            VirtualFolderKind::Unread => "e.is_read = 0",
+            VirtualFolderKind::Vip => {
+                "lower(e.from_address) IN (SELECT lower(email) FROM contacts WHERE is_vip = 1 AND deleted = 0) \
+                 AND f.folder_type NOT IN ('sent', 'drafts')"
+            }
+            VirtualFolderKind::NeedsReply => {
+                "e.is_answered = 0 AND e.id IN (SELECT email_id FROM email_ai_metadata \
+                 WHERE COALESCE(needs_reply_override, needs_reply) = 1)"
+            }
+        };
+
+        // Skip disabled/deleted accounts and folders that would only add noise or duplicates
+        let base = format!(
+            r#"
+            FROM emails e
+            JOIN accounts a ON a.id = e.account_id
+            JOIN folders f ON f.id = e.folder_id
+            WHERE {} AND e.is_deleted = 0 AND e.is_spam = 0
+              AND a.is_active = 1 AND a.deleted = 0
+              AND f.folder_type NOT IN ('trash', 'spam', 'starred')
+            "#,
+            condition
+        );
+
+        let conn = self.get_conn()?;
+
+        let total_count: i64 = conn.query_row(
+            &format!("SELECT COUNT(DISTINCT e.account_id || ':' || e.message_id) {}", base),
+            [],
+            |row| row.get(0),
+        )?;
 
-        // Convert params to references for query_map
-        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        // MAX(e.id) makes SQLite take the bare columns from the newest copy of each message
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT MAX(e.id), e.message_id, e.uid, e.from_address, e.from_name, e.subject, e.preview, e.date,
+                   e.is_read, e.is_starred, e.has_attachments, e.has_inline_images,
+                   e.account_id, a.email, e.folder_id, f.name, f.remote_name
+            {}
+            GROUP BY e.account_id, e.message_id
+            ORDER BY e.date DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
+            base
+        ))?;
 
         let emails = stmt
-            .query_map(&param_refs[..], |row| {
-                Ok(EmailSummary {
-                    id: row.get(0)?,
-                    message_id: row.get(1)?,
-                    uid: row.get(2)?,
-                    from_address: row.get(3)?,
-                    from_name: row.get(4)?,
-                    subject: row.get(5)?,
-                    preview: row.get(6)?,
-                    date: row.get(7)?,
-                    is_read: row.get(8)?,
-                    is_starred: row.get(9)?,
-                    has_attachments: row.get(10)?,
-                    has_inline_images: row.get(11)?,
+            .query_map(params![safe_limit, safe_offset], |row| {
+                Ok(VirtualFolderEmail {
+                    email: EmailSummary {
+                        id: row.get(0)?,
+                        message_id: row.get(1)?,
+                        uid: row.get(2)?,
+                        from_address: row.get(3)?,
+                        from_name: row.get(4)?,
+                        subject: row.get(5)?,
+                        preview: row.get(6)?,
+                        date: row.get(7)?,
+                        is_read: row.get(8)?,
+                        is_starred: row.get(9)?,
+                        has_attachments: row.get(10)?,
+                        has_inline_images: row.get(11)?,
+                    },
+                    account_id: row.get(12)?,
+                    account_email: row.get(13)?,
+                    folder_id: row.get(14)?,
+                    folder_name: row.get(15)?,
+                    folder_remote_name: row.get(16)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
-        let search_time = start_time.elapsed().as_millis() as i64;
-        let total_count = emails.len() as i64;
-        let has_more = emails.len() as i32 == safe_limit;
+        let has_more = (safe_offset as i64 + emails.len() as i64) < total_count;
 
-        Ok(SearchResult {
+        Ok(VirtualFolderPage {
+            kind,
             emails,
             total_count,
             has_more,
-            search_time,
         })
     }
 
@@ -1547,7 +2067,7 @@ impl Database {
         let mut stmt = conn.prepare(
             r#"
             SELECT id, account_id, email, name, avatar_url, company, phone, notes,
-                   is_favorite, email_count, last_emailed_at
+                   is_favorite, email_count, last_emailed_at, is_vip
             FROM contacts
             ORDER BY email_count DESC, email ASC
             "#,
@@ -1566,6 +2086,7 @@ impl Database {
                 is_favorite: row.get(8)?,
                 email_count: row.get(9)?,
                 last_emailed_at: row.get(10)?,
+                is_vip: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -1599,7 +2120,7 @@ impl Database {
         let mut stmt = conn.prepare(
             r#"
             SELECT id, account_id, email, name, avatar_url, company, phone, notes,
-                   is_favorite, email_count, last_emailed_at
+                   is_favorite, email_count, last_emailed_at, is_vip
             FROM contacts
             WHERE account_id = ?1
               AND (email LIKE ?2 ESCAPE '\' OR name LIKE ?2 ESCAPE '\')
@@ -1621,6 +2142,140 @@ impl Database {
                 is_favorite: row.get(8)?,
                 email_count: row.get(9)?,
                 last_emailed_at: row.get(10)?,
+                is_vip: row.get(11)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(contacts)
+    }
+
+    /// Lowercase addresses the account has corresponded with: contacts,
+    /// recipients of sent mail and senders of received mail
+    pub fn get_correspondent_addresses(&self, account_id: i64) -> DbResult<std::collections::HashSet<String>> {
+        let conn = self.get_conn()?;
+
+        // Address columns hold either plain strings or {"email", "name"} objects
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT lower(email) FROM contacts
+            WHERE account_id = ?1 OR account_id IS NULL
+            UNION
+            SELECT lower(CASE j.type WHEN 'object' THEN json_extract(j.value, '$.email') ELSE j.value END)
+            FROM emails e
+            JOIN folders f ON f.id = e.folder_id
+            JOIN json_each(CASE WHEN json_valid(e.to_addresses) THEN e.to_addresses ELSE '[]' END) j
+            WHERE e.account_id = ?1 AND f.folder_type = 'sent'
+            UNION
+            SELECT lower(CASE j.type WHEN 'object' THEN json_extract(j.value, '$.email') ELSE j.value END)
+            FROM emails e
+            JOIN folders f ON f.id = e.folder_id
+            JOIN json_each(CASE WHEN json_valid(e.cc_addresses) THEN e.cc_addresses ELSE '[]' END) j
+            WHERE e.account_id = ?1 AND f.folder_type = 'sent'
+            UNION
+            SELECT lower(from_address) FROM emails
+            WHERE account_id = ?1 AND is_spam = 0
+            "#,
+        )?;
+
+        let addresses = stmt
+            .query_map([account_id], |row| row.get::<_, Option<String>>(0))?
+            .filter_map(|address| address.transpose())
+            .collect::<Result<_, _>>()?;
+
+        Ok(addresses)
+    }
+
+    /// Recent mail received from an address, across accounts
+    /// `replied_to` is the date of our sent message it answers, if any.
+    pub fn get_sender_activity(&self, address: &str, limit: i32) -> DbResult<Vec<SenderActivity>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT r.date,
+                   (SELECT s.date FROM emails s
+                    JOIN folders sf ON sf.id = s.folder_id
+                    WHERE s.account_id = r.account_id AND s.message_id = r.in_reply_to
+                      AND sf.folder_type = 'sent'
+                    LIMIT 1)
+            FROM emails r
+            JOIN folders f ON f.id = r.folder_id
+            WHERE lower(r.from_address) = lower(?1) AND r.is_spam = 0
+              AND f.folder_type NOT IN ('sent', 'drafts', 'trash', 'spam')
+            GROUP BY r.account_id, r.message_id
+            ORDER BY r.date DESC
+            LIMIT ?2
+            "#,
+        )?;
+
+        let activity = stmt
+            .query_map(params![address.trim(), limit.clamp(1, 1000)], |row| {
+                Ok(SenderActivity {
+                    date: row.get(0)?,
+                    replied_to: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(activity)
+    }
+
+    /// Mark or unmark an address as VIP for an account (creates the contact if needed)
+    pub fn set_contact_vip(&self, account_id: i64, email: &str, vip: bool) -> DbResult<()> {
+        let email = email.trim();
+        if email.is_empty() || !email.contains('@') {
+            return Err(DbError::Constraint("Invalid email address".to_string()));
+        }
+
+        let conn = self.get_conn()?;
+        let updated = conn.execute(
+            r#"
+            UPDATE contacts
+            SET is_vip = ?3, deleted = CASE WHEN ?3 THEN 0 ELSE deleted END, updated_at = datetime('now')
+            WHERE account_id = ?1 AND lower(email) = lower(?2)
+            "#,
+            params![account_id, email, vip],
+        )?;
+
+        if updated == 0 && vip {
+            conn.execute(
+                "INSERT INTO contacts (account_id, email, is_vip) VALUES (?1, ?2, 1)",
+                params![account_id, email],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// VIP contacts of all accounts
+    pub fn get_vip_contacts(&self) -> DbResult<Vec<Contact>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, account_id, email, name, avatar_url, company, phone, notes,
+                   is_favorite, email_count, last_emailed_at, is_vip
+            FROM contacts
+            WHERE is_vip = 1 AND deleted = 0
+            ORDER BY COALESCE(name, email) ASC
+            "#,
+        )?;
+
+        let contacts = stmt.query_map([], |row| {
+            Ok(Contact {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                email: row.get(2)?,
+                name: row.get(3)?,
+                avatar_url: row.get(4)?,
+                company: row.get(5)?,
+                phone: row.get(6)?,
+                notes: row.get(7)?,
+                is_favorite: row.get(8)?,
+                email_count: row.get(9)?,
+                last_emailed_at: row.get(10)?,
+                is_vip: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -1628,6 +2283,17 @@ impl Database {
         Ok(contacts)
     }
 
+    /// Lowercase addresses of VIP contacts (any account)
+    pub fn get_vip_addresses(&self) -> DbResult<std::collections::HashSet<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt =
+            conn.prepare("SELECT DISTINCT lower(email) FROM contacts WHERE is_vip = 1 AND deleted = 0")?;
+        let addresses = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(addresses)
+    }
+
     // =========================================================================
     // EMAIL TEMPLATES
     // =========================================================================
@@ -1707,6 +2373,47 @@ impl Database {
         Ok(templates)
     }
 
+    /// Get every template regardless of account (for sync)
+    pub fn get_all_templates(&self) -> DbResult<Vec<EmailTemplate>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, account_id, name, description, category, subject_template,
+                   body_html_template, body_text_template, tags, is_enabled,
+                   is_favorite, usage_count, last_used_at, created_at, updated_at
+            FROM email_templates
+            ORDER BY id ASC
+            "#,
+        )?;
+
+        let templates = stmt.query_map([], |row| {
+            let tags_json: String = row.get(8)?;
+            let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+
+            Ok(EmailTemplate {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                name: row.get(2)?,
+                description: row.get(3)?,
+                category: row.get(4)?,
+                subject_template: row.get(5)?,
+                body_html_template: row.get(6)?,
+                body_text_template: row.get(7)?,
+                tags,
+                is_enabled: row.get(9)?,
+                is_favorite: row.get(10)?,
+                usage_count: row.get(11)?,
+                last_used_at: row.get(12)?,
+                created_at: row.get(13)?,
+                updated_at: row.get(14)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(templates)
+    }
+
     /// Get a single template by ID
     pub fn get_template(&self, id: i64) -> DbResult<EmailTemplate> {
         // SECURITY: Handle mutex poisoning gracefully
@@ -2207,7 +2914,7 @@ impl Database {
         let conn = self.get_conn()?;
 
         let folder = conn.query_row(
-            "SELECT id, account_id, name, remote_name, folder_type, unread_count, total_count, is_subscribed, is_selectable, delimiter FROM folders WHERE id = ?1",
+            "SELECT id, account_id, name, remote_name, folder_type, unread_count, total_count, is_subscribed, is_selectable, delimiter, is_local, parent_id FROM folders WHERE id = ?1",
             [id],
             |row| {
                 Ok(Folder {
@@ -2221,6 +2928,8 @@ impl Database {
                     is_subscribed: row.get(7)?,
                     is_selectable: row.get(8)?,
                     delimiter: row.get(9)?,
+                    is_local: row.get(10)?,
+                    parent_id: row.get(11)?,
                 })
             },
         )?;
@@ -2457,8 +3166,10 @@ pub struct NewAccount {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct Account {
+    #[cfg_attr(test, ts(type = "number"))]
     pub id: i64,
     pub email: String,
     pub display_name: String,
@@ -2472,6 +3183,7 @@ pub struct Account {
     pub smtp_username: Option<String>,
     pub oauth_provider: Option<String>,
     pub oauth_refresh_token: Option<String>,
+    #[cfg_attr(test, ts(type = "number | null"))]
     pub oauth_expires_at: Option<i64>,
     pub is_active: bool,
     pub is_default: bool,
@@ -2501,8 +3213,11 @@ pub struct NewFolder {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "LocalFolder"))]
 pub struct Folder {
+    #[cfg_attr(test, ts(type = "number"))]
     pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
     pub account_id: i64,
     pub name: String,
     pub remote_name: String,
@@ -2512,6 +3227,13 @@ pub struct Folder {
     pub is_subscribed: bool,
     pub is_selectable: bool,
     pub delimiter: String,
+    /// Local-only folder stored in SQLite, never synced to IMAP
+    #[serde(default)]
+    pub is_local: bool,
+    /// Parent folder in the server hierarchy (None for top-level folders)
+    #[serde(default)]
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub parent_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2550,7 +3272,10 @@ pub struct NewEmail {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "LocalEmailSummary"))]
+#[serde(rename_all = "camelCase")]
 pub struct EmailSummary {
+    #[cfg_attr(test, ts(type = "number"))]
     pub id: i64,
     pub message_id: String,
     pub uid: u32,
@@ -2565,19 +3290,47 @@ pub struct EmailSummary {
     pub has_inline_images: bool,
 }
 
+/// Position in a folder listing: the last message of the previous window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct EmailCursor {
+    pub date: String,
+    pub uid: u32,
+}
+
+/// Window of a folder listing (see `get_emails_window`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct EmailWindow {
+    pub emails: Vec<EmailSummary>,
+    /// Cursor of the next window, None at the end of the folder
+    pub next_cursor: Option<EmailCursor>,
+    pub has_more: bool,
+    /// Messages in the folder (first window only)
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub total: Option<i64>,
+}
+
 // Advanced search types
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, optional_fields))]
+#[serde(rename_all = "camelCase")]
 pub struct DateRange {
     pub start_date: Option<String>,
     pub end_date: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, optional_fields))]
+#[serde(rename_all = "camelCase")]
 pub struct SearchFilters {
     pub query: Option<String>,
     pub date_range: Option<DateRange>,
     pub from_email: Option<String>,
     pub from_domain: Option<String>,
+    #[cfg_attr(test, ts(optional, type = "number"))]
     pub folder_id: Option<i64>,
     pub has_attachments: Option<bool>,
     pub is_read: Option<bool>,
@@ -2586,17 +3339,66 @@ pub struct SearchFilters {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
 pub struct SearchResult {
     pub emails: Vec<EmailSummary>,
+    #[cfg_attr(test, ts(type = "number"))]
     pub total_count: i64,
     pub has_more: bool,
+    #[cfg_attr(test, ts(type = "number"))]
     pub search_time: i64, // milliseconds
 }
 
+/// Cross-account virtual folders built from the local cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "lowercase")]
+pub enum VirtualFolderKind {
+    Starred,
+    Unread,
+    /// Mail from VIP contacts
+    Vip,
+    /// Received mail awaiting our reply (see `reply_needed`)
+    #[serde(rename = "needs_reply")]
+    NeedsReply,
+}
+
+/// Email in a virtual folder, with the account/folder it lives in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualFolderEmail {
+    #[serde(flatten)]
+    pub email: EmailSummary,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub account_email: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub folder_id: i64,
+    pub folder_name: String,
+    pub folder_remote_name: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualFolderPage {
+    pub kind: VirtualFolderKind,
+    pub emails: Vec<VirtualFolderEmail>,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total_count: i64,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "LocalEmail"))]
 pub struct Email {
+    #[cfg_attr(test, ts(type = "number"))]
     pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
     pub account_id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
     pub folder_id: i64,
     pub message_id: String,
     pub uid: u32,
@@ -2685,9 +3487,21 @@ pub struct NewContact {
     pub is_favorite: bool,
 }
 
+/// Message received from a correspondent (see `get_sender_activity`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SenderActivity {
+    pub date: String,
+    /// Date of our sent message this one replies to
+    pub replied_to: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
 pub struct Contact {
+    #[cfg_attr(test, ts(type = "number"))]
     pub id: i64,
+    #[cfg_attr(test, ts(type = "number | null"))]
     pub account_id: Option<i64>,
     pub email: String,
     pub name: Option<String>,
@@ -2698,15 +3512,22 @@ pub struct Contact {
     pub is_favorite: bool,
     pub email_count: i32,
     pub last_emailed_at: Option<String>,
+    /// Always notifies, listed in the VIP view
+    #[serde(default)]
+    pub is_vip: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct Attachment {
+    #[cfg_attr(test, ts(type = "number"))]
     pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
     pub email_id: i64,
     pub filename: String,
     pub content_type: String,
+    #[cfg_attr(test, ts(type = "number"))]
     pub size: i64,
     pub content_id: Option<String>,
     pub is_inline: bool,
@@ -2716,9 +3537,12 @@ pub struct Attachment {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct EmailTemplate {
+    #[cfg_attr(test, ts(type = "number"))]
     pub id: i64,
+    #[cfg_attr(test, ts(type = "number | null"))]
     pub account_id: Option<i64>,
     pub name: String,
     pub description: Option<String>,
@@ -2729,6 +3553,7 @@ pub struct EmailTemplate {
     pub tags: Vec<String>,
     pub is_enabled: bool,
     pub is_favorite: bool,
+    #[cfg_attr(test, ts(type = "number"))]
     pub usage_count: i64,
     pub last_used_at: Option<String>,
     pub created_at: String,
@@ -2736,8 +3561,10 @@ pub struct EmailTemplate {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct NewEmailTemplate {
+    #[cfg_attr(test, ts(type = "number | null"))]
     pub account_id: Option<i64>,
     pub name: String,
     pub description: Option<String>,
@@ -2954,7 +3781,7 @@ impl Database {
 
         let query = r#"
             SELECT id, account_id, email, name, avatar_url, company, phone, notes,
-                   is_favorite, email_count, last_emailed_at
+                   is_favorite, email_count, last_emailed_at, is_vip
             FROM contacts
             WHERE deleted = 0
         "#;
@@ -2981,6 +3808,7 @@ impl Database {
                 is_favorite: row.get(8)?,
                 email_count: row.get(9)?,
                 last_emailed_at: row.get(10)?,
+                is_vip: row.get(11)?,
             })
         };
 
@@ -3044,6 +3872,77 @@ impl Database {
 
 }
 
+/// Account settings for tests (a default password account on test hosts)
+#[cfg(test)]
+pub(crate) fn test_new_account(email: &str) -> NewAccount {
+    NewAccount {
+        email: email.to_string(),
+        display_name: "Me".to_string(),
+        imap_host: "imap.test.com".to_string(),
+        imap_port: 993,
+        imap_security: "SSL".to_string(),
+        imap_username: None,
+        smtp_host: "smtp.test.com".to_string(),
+        smtp_port: 587,
+        smtp_security: "STARTTLS".to_string(),
+        smtp_username: None,
+        password_encrypted: Some("password".to_string()),
+        oauth_provider: None,
+        oauth_access_token: None,
+        oauth_refresh_token: None,
+        oauth_expires_at: None,
+        is_default: true,
+        signature: String::new(),
+        sync_days: 30,
+        accept_invalid_certs: false,
+    }
+}
+
+/// Add the test account me@test.com and return its id
+#[cfg(test)]
+pub(crate) fn test_account(db: &Database) -> i64 {
+    db.add_account(&test_new_account("me@test.com"))
+        .expect("Failed to add test account")
+}
+
+/// Unread plain message for tests; override fields with struct update syntax
+#[cfg(test)]
+pub(crate) fn test_email(account_id: i64, folder_id: i64, uid: u32) -> NewEmail {
+    NewEmail {
+        account_id,
+        folder_id,
+        message_id: format!("<{}@test.com>", uid),
+        uid,
+        from_address: "sender@example.com".to_string(),
+        from_name: None,
+        to_addresses: "[]".to_string(),
+        cc_addresses: "[]".to_string(),
+        bcc_addresses: "[]".to_string(),
+        reply_to: None,
+        subject: format!("Message {}", uid),
+        preview: String::new(),
+        body_text: None,
+        body_html: None,
+        date: "2024-01-01T00:00:00Z".to_string(),
+        is_read: false,
+        is_starred: false,
+        is_deleted: false,
+        is_spam: false,
+        is_draft: false,
+        is_answered: false,
+        is_forwarded: false,
+        has_attachments: false,
+        has_inline_images: false,
+        thread_id: None,
+        in_reply_to: None,
+        references_header: None,
+        raw_headers: None,
+        raw_size: 0,
+        priority: 3,
+        labels: "[]".to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3166,6 +4065,7 @@ mod tests {
                 action: FilterActionType::MarkAsRead,
                 folder_id: None,
                 label: None,
+                webhook: None,
             }],
         };
 
@@ -3527,6 +4427,316 @@ mod tests {
         assert!(speedup >= 3.0, "Batch insert should be at least 3x faster, got {:.2}x", speedup);
     }
 
+    #[test]
+    fn test_local_folders() {
+        let db = Database::in_memory().expect("Failed to create database");
+
+        let account_id = test_account(&db);
+
+        let receipts = db.create_local_folder(account_id, " Receipts ").expect("Failed to create folder");
+        let archive = db.create_local_folder(account_id, "Archive 2024").expect("Failed to create folder");
+        assert!(db.create_local_folder(account_id, "Receipts").is_err());
+        assert!(db.create_local_folder(account_id, "   ").is_err());
+
+        let folders = db.get_local_folders(account_id).expect("Failed to list folders");
+        assert_eq!(folders.len(), 2);
+        assert!(folders.iter().all(|f| f.is_local && f.remote_name.starts_with(LOCAL_FOLDER_PREFIX)));
+
+        let raw = b"From: shop@example.com\r\nSubject: Your receipt\r\n\r\nInvoice total 42 TL\r\n";
+        let email = NewEmail {
+            from_address: "shop@example.com".to_string(),
+            subject: "Your receipt".to_string(),
+            preview: "Invoice total 42 TL".to_string(),
+            body_text: Some("Invoice total 42 TL".to_string()),
+            raw_size: raw.len() as i32,
+            ..test_email(account_id, receipts, 0)
+        };
+
+        let first = db.insert_local_email(&email, raw).expect("Failed to store email");
+        let second = db.insert_local_email(&email, raw).expect("Failed to store email");
+        assert_eq!(db.get_email(first).unwrap().uid, 1);
+        assert_eq!(db.get_email(second).unwrap().uid, 2);
+        assert_eq!(db.get_folder_by_id(receipts).unwrap().unread_count, 2);
+        assert_eq!(db.get_local_email_source(first).unwrap(), raw.to_vec());
+
+        // Local messages are part of regular search
+        let results = db.search_emails(account_id, "invoice", 10).expect("Search failed");
+        assert_eq!(results.len(), 2);
+
+        db.move_local_email(second, archive).expect("Failed to move email");
+        assert_eq!(db.get_folder_by_id(receipts).unwrap().total_count, 1);
+        assert_eq!(db.get_local_folder_sources(archive).unwrap().len(), 1);
+
+        db.rename_local_folder(archive, "Receipts").expect_err("Duplicate name must fail");
+        db.rename_local_folder(archive, "Old receipts").expect("Failed to rename");
+
+        db.delete_local_folder(receipts).expect("Failed to delete folder");
+        assert!(db.get_local_email_source(first).is_err());
+        assert_eq!(db.get_local_folders(account_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_folder_hierarchy_rename_keeps_children() {
+        let db = Database::in_memory().expect("Failed to create database");
+
+        let account_id = test_account(&db);
+
+        for path in ["Clients", "Clients.ACME", "Clients.ACME.2024", "Clients.ACMEOld", "Archive"] {
+            db.upsert_folder(&NewFolder {
+                account_id,
+                name: path.rsplit('.').next().unwrap().to_string(),
+                remote_name: path.to_string(),
+                folder_type: "custom".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: ".".to_string(),
+            })
+            .expect("Failed to add folder");
+        }
+        db.link_folder_parents(account_id).expect("Failed to link parents");
+
+        let by_path = |db: &Database| -> std::collections::HashMap<String, Folder> {
+            db.get_folders(account_id)
+                .unwrap()
+                .into_iter()
+                .map(|f| (f.remote_name.clone(), f))
+                .collect()
+        };
+
+        let folders = by_path(&db);
+        assert_eq!(folders["Clients"].parent_id, None);
+        assert_eq!(folders["Clients.ACME"].parent_id, Some(folders["Clients"].id));
+        assert_eq!(folders["Clients.ACME.2024"].parent_id, Some(folders["Clients.ACME"].id));
+
+        // Move "Clients.ACME" below "Archive" as "Archive.Acme"
+        let renamed = db
+            .rename_folder_subtree(account_id, "Clients.ACME", "Archive.Acme")
+            .expect("Failed to rename subtree");
+        assert_eq!(renamed, 2);
+
+        let folders = by_path(&db);
+        assert!(folders.contains_key("Clients.ACMEOld"));
+        assert_eq!(folders["Archive.Acme"].name, "Acme");
+        assert_eq!(folders["Archive.Acme"].parent_id, Some(folders["Archive"].id));
+        assert_eq!(folders["Archive.Acme.2024"].parent_id, Some(folders["Archive.Acme"].id));
+
+        assert!(db.rename_folder_subtree(account_id, "Missing", "Other").is_err());
+    }
+
+    #[test]
+    fn test_virtual_folders_aggregate_across_accounts() {
+        let db = Database::in_memory().expect("Failed to create database");
+
+        let mut folder_ids = Vec::new();
+        for (i, email) in ["one@test.com", "two@test.com"].iter().enumerate() {
+            let account = NewAccount {
+                display_name: format!("Account {}", i),
+                is_default: i == 0,
+                ..test_new_account(email)
+            };
+            let account_id = db.add_account(&account).expect("Failed to add account");
+
+            for (remote_name, folder_type) in [("INBOX", "inbox"), ("[Gmail]/All Mail", "archive"), ("Trash", "trash")] {
+                let folder_id = db
+                    .upsert_folder(&NewFolder {
+                        account_id,
+                        name: remote_name.to_string(),
+                        remote_name: remote_name.to_string(),
+                        folder_type: folder_type.to_string(),
+                        is_subscribed: true,
+                        is_selectable: true,
+                        delimiter: "/".to_string(),
+                    })
+                    .expect("Failed to create folder");
+                folder_ids.push((account_id, folder_id, folder_type));
+            }
+        }
+
+        // (message_id, is_read, is_starred) stored in every folder of every account
+        let messages = [("a@x", false, true), ("b@x", false, false), ("c@x", true, true)];
+        let emails: Vec<NewEmail> = folder_ids
+            .iter()
+            .flat_map(|(account_id, folder_id, _)| {
+                messages.iter().enumerate().map(move |(uid, (message_id, is_read, is_starred))| NewEmail {
+                    message_id: message_id.to_string(),
+                    subject: format!("Message {}", message_id),
+                    date: format!("2024-01-0{}T00:00:00Z", uid + 1),
+                    is_read: *is_read,
+                    is_starred: *is_starred,
+                    ..test_email(*account_id, *folder_id, uid as u32 + 1)
+                })
+            })
+            .collect();
+        db.batch_upsert_emails(&emails).expect("Failed to insert emails");
+
+        // Two starred messages per account, deduplicated across INBOX/All Mail, trash excluded
+        let starred = db.get_virtual_folder_emails(VirtualFolderKind::Starred, 50, 0).unwrap();
+        assert_eq!(starred.total_count, 4);
+        assert_eq!(starred.emails.len(), 4);
+        assert!(starred.emails.iter().all(|e| e.email.is_starred && e.folder_remote_name != "Trash"));
+        assert_eq!(starred.emails[0].email.message_id, "c@x");
+
+        let unread = db.get_virtual_folder_emails(VirtualFolderKind::Unread, 3, 0).unwrap();
+        assert_eq!(unread.total_count, 4);
+        assert_eq!(unread.emails.len(), 3);
+        assert!(unread.has_more);
+
+        let last_page = db.get_virtual_folder_emails(VirtualFolderKind::Unread, 3, 3).unwrap();
+        assert_eq!(last_page.emails.len(), 1);
+        assert!(!last_page.has_more);
+    }
+
+    #[test]
+    fn test_correspondent_addresses() {
+        let db = Database::in_memory().expect("Failed to create database");
+        let account_id = test_account(&db);
+
+        let folder = |name: &str, folder_type: &str| {
+            db.upsert_folder(&NewFolder {
+                account_id,
+                name: name.to_string(),
+                remote_name: name.to_string(),
+                folder_type: folder_type.to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .expect("Failed to create folder")
+        };
+        let inbox = folder("INBOX", "inbox");
+        let sent = folder("Sent", "sent");
+
+        let email = |folder_id: i64, uid: u32, from: &str, to: &str| NewEmail {
+            from_address: from.to_string(),
+            to_addresses: to.to_string(),
+            subject: "Hello".to_string(),
+            is_read: true,
+            ..test_email(account_id, folder_id, uid)
+        };
+        db.batch_upsert_emails(&[
+            email(inbox, 1, "Ana@Owlivion.com", "[]"),
+            email(sent, 1, "me@test.com", r#"[{"email":"bob@example.org","name":"Bob"}]"#),
+            email(sent, 2, "me@test.com", r#"["carol@example.net"]"#),
+        ])
+        .expect("Failed to store emails");
+
+        let addresses = db.get_correspondent_addresses(account_id).unwrap();
+        for address in ["ana@owlivion.com", "bob@example.org", "carol@example.net"] {
+            assert!(addresses.contains(address), "missing {}", address);
+        }
+    }
+
+    #[test]
+    fn test_vip_contacts_and_view() {
+        let db = Database::in_memory().expect("Failed to create database");
+        let account_id = test_account(&db);
+        let inbox = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .expect("Failed to create folder");
+
+        let email = |uid: u32, from: &str| NewEmail {
+            from_address: from.to_string(),
+            subject: "Hello".to_string(),
+            date: format!("2025-01-0{}T10:00:00Z", uid),
+            is_read: true,
+            ..test_email(account_id, inbox, uid)
+        };
+        db.batch_upsert_emails(&[email(1, "Boss@Example.com"), email(2, "alice@example.com")])
+            .expect("Failed to store emails");
+
+        assert!(db.set_contact_vip(account_id, "not-an-address", true).is_err());
+        db.set_contact_vip(account_id, "boss@example.com", true).unwrap();
+
+        let vips = db.get_vip_contacts().unwrap();
+        assert_eq!(vips.len(), 1);
+        assert!(vips[0].is_vip);
+        assert!(db.get_vip_addresses().unwrap().contains("boss@example.com"));
+
+        let page = db.get_virtual_folder_emails(VirtualFolderKind::Vip, 50, 0).unwrap();
+        assert_eq!(page.total_count, 1);
+        assert_eq!(page.emails[0].email.from_address, "Boss@Example.com");
+
+        db.set_contact_vip(account_id, "BOSS@example.com", false).unwrap();
+        assert!(db.get_vip_addresses().unwrap().is_empty());
+        assert_eq!(db.get_virtual_folder_emails(VirtualFolderKind::Vip, 50, 0).unwrap().total_count, 0);
+    }
+
+    #[test]
+    fn test_email_window_keyset_pagination() {
+        let db = Database::in_memory().expect("Failed to create database");
+        let account_id = test_account(&db);
+        let inbox = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .expect("Failed to create folder");
+
+        // Seven messages, several sharing a date; UID 4 is deleted
+        let emails: Vec<NewEmail> = (1..=8u32)
+            .map(|uid| NewEmail {
+                date: format!("2025-01-0{}T10:00:00Z", uid.div_ceil(2)),
+                is_read: true,
+                is_deleted: uid == 4,
+                ..test_email(account_id, inbox, uid)
+            })
+            .collect();
+        db.batch_upsert_emails(&emails).expect("Failed to store emails");
+
+        let mut uids = Vec::new();
+        let first = db.get_emails_window(inbox, None, 3).unwrap();
+        assert_eq!(first.total, Some(7));
+        uids.extend(first.emails.iter().map(|e| e.uid));
+
+        let mut cursor = first.next_cursor;
+        while let Some(before) = cursor {
+            let window = db.get_emails_window(inbox, Some(&before), 3).unwrap();
+            assert_eq!(window.total, None);
+            uids.extend(window.emails.iter().map(|e| e.uid));
+            cursor = window.next_cursor;
+            assert_eq!(cursor.is_some(), window.has_more);
+        }
+        assert_eq!(uids, vec![8, 7, 6, 5, 3, 2, 1]);
+
+        // Windows are index range scans, not sorts
+        let conn = db.get_conn().unwrap();
+        let plan: Vec<String> = conn
+            .prepare(
+                "EXPLAIN QUERY PLAN SELECT id FROM emails WHERE folder_id = 1 AND is_deleted = 0 \
+                 AND (date, uid) < ('2025-01-03', 5) ORDER BY date DESC, uid DESC LIMIT 4",
+            )
+            .unwrap()
+            .query_map([], |row| row.get::<_, String>(3))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(plan.iter().any(|step| step.contains("idx_emails_folder_window")), "{:?}", plan);
+        assert!(!plan.iter().any(|step| step.contains("TEMP B-TREE")), "{:?}", plan);
+    }
+
+    #[test]
+    fn test_fresh_database_at_latest_schema_version() {
+        let db = Database::in_memory().expect("Failed to create database");
+        assert_eq!(db.schema_version().unwrap(), migrate::latest_version());
+
+        // Re-running is a no-op
+        let conn = db.get_conn().unwrap();
+        assert!(migrate::migrate(&conn, migrate::MIGRATIONS).unwrap().is_empty());
+    }
+
     #[test]
     fn test_wal_mode_enabled() {
         let db = Database::in_memory().expect("Failed to create database");
@@ -0,0 +1,289 @@
+//! Startup Integrity Check and Recovery
+//!
+//! Before the connection pool opens the database, `PRAGMA integrity_check`
+//! runs on it. A corrupt database is not used: its readable rows are copied,
+//! table by table, into a freshly created database (row by row from both ends
+//! of a table when a bulk copy hits a damaged page), the corrupt file is kept
+//! next to it as `<name>.corrupt-<timestamp>`, and the recovered copy takes
+//! its place. When nothing can be read the app starts with an empty database;
+//! mail is fetched again from the servers, and the backup stays on disk.
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, ErrorCode, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::{Database, DbError, DbResult};
+
+/// Event emitted at startup when the database had to be recovered
+pub const RECOVERY_EVENT: &str = "db://recovered";
+
+/// Outcome of the startup check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "DbRecoveryReport"))]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase", tag = "status")]
+pub enum RecoveryReport {
+    /// Integrity check passed (or there was no database yet)
+    Healthy,
+    /// Readable rows were copied into a new database
+    Recovered {
+        backup_path: String,
+        rows_recovered: usize,
+        /// Tables where rows were lost
+        incomplete_tables: Vec<String>,
+    },
+    /// Nothing could be read; started from an empty database
+    Reset { backup_path: String, error: String },
+}
+
+fn is_corruption(error: &rusqlite::Error) -> bool {
+    matches!(
+        error,
+        rusqlite::Error::SqliteFailure(e, _) if matches!(e.code, ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+    )
+}
+
+/// Problems reported by `PRAGMA integrity_check` (empty = healthy)
+pub fn integrity_problems(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check(20)")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let problems: Vec<String> = rows.collect::<rusqlite::Result<_>>()?;
+    Ok(problems.into_iter().filter(|line| line != "ok").collect())
+}
+
+/// Check the database at `path` and recover it when corrupt
+/// Errors other than corruption (locked, unreadable file) are left for
+/// `Database::new` to report.
+pub fn check_and_recover(path: &Path) -> DbResult<RecoveryReport> {
+    if !path.exists() {
+        return Ok(RecoveryReport::Healthy);
+    }
+
+    let problems = match Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .and_then(|conn| integrity_problems(&conn))
+    {
+        Ok(problems) if problems.is_empty() => return Ok(RecoveryReport::Healthy),
+        Ok(problems) => problems.join("; "),
+        Err(e) if is_corruption(&e) => e.to_string(),
+        Err(e) => {
+            log::warn!("Skipping database integrity check: {}", e);
+            return Ok(RecoveryReport::Healthy);
+        }
+    };
+    log::error!("Database {:?} is corrupt: {}", path, problems);
+
+    let recovering = sibling(path, ".recovering");
+    remove_with_journals(&recovering);
+    let copied = recover_into(path, &recovering);
+
+    let backup = sibling(path, &format!(".corrupt-{}", chrono::Utc::now().format("%Y%m%d%H%M%S")));
+    move_with_journals(path, &backup)?;
+    let backup_path = backup.to_string_lossy().into_owned();
+
+    match copied {
+        Ok((rows_recovered, incomplete_tables)) => {
+            std::fs::rename(&recovering, path).map_err(io_error)?;
+            log::warn!(
+                "Recovered {} rows into a new database; corrupt copy kept at {}",
+                rows_recovered,
+                backup_path
+            );
+            Ok(RecoveryReport::Recovered { backup_path, rows_recovered, incomplete_tables })
+        }
+        Err(e) => {
+            remove_with_journals(&recovering);
+            log::error!("Database recovery failed ({}); starting empty, corrupt copy kept at {}", e, backup_path);
+            Ok(RecoveryReport::Reset { backup_path, error: e.to_string() })
+        }
+    }
+}
+
+/// Copy every readable row of `source` into a new database at `target`
+/// Returns the rows copied and the tables that could not be read completely.
+pub fn recover_into(source: &Path, target: &Path) -> DbResult<(usize, Vec<String>)> {
+    let conn = Connection::open(target)?;
+    conn.execute_batch(include_str!("schema.sql"))?;
+    Database::run_migrations(&conn)?;
+
+    // Rows arrive in table order, not dependency order
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+    conn.execute("ATTACH DATABASE ?1 AS old", [source.to_string_lossy()])?;
+
+    let mut rows = 0;
+    let mut incomplete = Vec::new();
+    for table in copyable_tables(&conn)? {
+        let columns = common_columns(&conn, &table)?;
+        if columns.is_empty() {
+            continue;
+        }
+        let (copied, complete) = copy_table(&conn, &table, &columns)?;
+        rows += copied;
+        if !complete {
+            incomplete.push(table);
+        }
+    }
+
+    conn.execute_batch("DETACH DATABASE old;")?;
+    if !integrity_problems(&conn)?.is_empty() {
+        return Err(DbError::Constraint("Recovered database failed its integrity check".to_string()));
+    }
+    Ok((rows, incomplete))
+}
+
+/// Regular tables of the new schema, without FTS tables and their shadow
+/// tables (the insert triggers index the copied rows again)
+fn copyable_tables(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.name FROM main.sqlite_master t
+         WHERE t.type = 'table' AND t.name NOT LIKE 'sqlite_%' AND t.sql NOT LIKE 'CREATE VIRTUAL TABLE%'
+           AND NOT EXISTS (
+               SELECT 1 FROM main.sqlite_master v
+               WHERE v.sql LIKE 'CREATE VIRTUAL TABLE%' AND t.name LIKE v.name || '\\_%' ESCAPE '\\'
+           )
+           AND EXISTS (SELECT 1 FROM old.sqlite_master o WHERE o.type = 'table' AND o.name = t.name)
+         ORDER BY t.rootpage",
+    )?;
+    let tables = stmt.query_map([], |row| row.get(0))?;
+    tables.collect()
+}
+
+/// Columns present in both the new and the old table
+fn common_columns(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT n.name FROM pragma_table_info(?1, 'main') n
+         WHERE EXISTS (SELECT 1 FROM pragma_table_info(?1, 'old') o WHERE o.name = n.name)
+         ORDER BY n.cid",
+    )?;
+    let columns = stmt.query_map([table], |row| row.get(0))?;
+    columns.collect()
+}
+
+/// Copy a table; on a damaged page, fall back to reading row by row from
+/// both ends so only the rows on unreadable pages are lost
+fn copy_table(conn: &Connection, table: &str, columns: &[String]) -> DbResult<(usize, bool)> {
+    let list = columns.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", ");
+    let insert = format!(
+        "INSERT OR REPLACE INTO main.\"{table}\" ({list}) VALUES ({})",
+        vec!["?"; columns.len()].join(", ")
+    );
+
+    match conn.execute(&format!("INSERT OR REPLACE INTO main.\"{table}\" ({list}) SELECT {list} FROM old.\"{table}\""), []) {
+        Ok(copied) => return Ok((copied, true)),
+        Err(e) if is_corruption(&e) => log::warn!("Table {} is damaged, copying row by row: {}", table, e),
+        Err(e) => return Err(e.into()),
+    }
+
+    // Rows already copied are replaced, so the second pass may overlap the first
+    let mut complete = true;
+    for order in ["ASC", "DESC"] {
+        let mut select = conn.prepare(&format!("SELECT {list} FROM old.\"{table}\" ORDER BY rowid {order}"))?;
+        let mut rows = select.query([])?;
+        loop {
+            match rows.next() {
+                Ok(Some(row)) => {
+                    let values = (0..columns.len()).map(|i| row.get::<_, Value>(i)).collect::<rusqlite::Result<Vec<_>>>()?;
+                    conn.execute(&insert, params_from_iter(values))?;
+                }
+                Ok(None) => break,
+                Err(e) if is_corruption(&e) => {
+                    complete = false;
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if complete {
+            break;
+        }
+    }
+    let copied = conn.query_row(&format!("SELECT COUNT(*) FROM main.\"{table}\""), [], |row| row.get(0))?;
+    Ok((copied, complete))
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn io_error(e: std::io::Error) -> DbError {
+    DbError::Constraint(format!("File error during recovery: {}", e))
+}
+
+/// Move a database file together with its WAL and shared-memory files
+fn move_with_journals(from: &Path, to: &Path) -> DbResult<()> {
+    std::fs::rename(from, to).map_err(io_error)?;
+    for suffix in ["-wal", "-shm"] {
+        let journal = sibling(from, suffix);
+        if journal.exists() {
+            std::fs::rename(&journal, sibling(to, suffix)).map_err(io_error)?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_with_journals(path: &Path) {
+    for suffix in ["", "-wal", "-shm", "-journal"] {
+        let _ = std::fs::remove_file(sibling(path, suffix));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::params;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("owlivion-recovery-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_recover_copies_rows_and_reindexes() {
+        let dir = temp_dir();
+        let source = dir.join("owlivion.db");
+        {
+            let db = Database::new(source.clone()).unwrap();
+            db.set_setting("theme", &"dark").unwrap();
+            db.execute(
+                "INSERT INTO email_templates (account_id, name, subject_template, body_html_template) VALUES (NULL, 'Thanks', 'Thank you', '<p>Thanks</p>')",
+                params![],
+            )
+            .unwrap();
+        }
+
+        let target = dir.join("recovered.db");
+        let (rows, incomplete) = recover_into(&source, &target).unwrap();
+        assert!(rows >= 2);
+        assert!(incomplete.is_empty());
+
+        let db = Database::new(target).unwrap();
+        assert_eq!(db.get_setting::<String>("theme").unwrap().as_deref(), Some("dark"));
+        let hits: i64 = db
+            .query_row("SELECT COUNT(*) FROM templates_fts WHERE templates_fts MATCH 'thank'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(hits, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unreadable_database_is_replaced_and_kept() {
+        let dir = temp_dir();
+        let path = dir.join("owlivion.db");
+        std::fs::write(&path, vec![0x5a; 8192]).unwrap();
+
+        let report = check_and_recover(&path).unwrap();
+        let RecoveryReport::Reset { backup_path, .. } = report else {
+            panic!("expected reset, got {:?}", report);
+        };
+        assert!(Path::new(&backup_path).exists());
+        assert!(!path.exists());
+        assert_eq!(check_and_recover(&dir.join("missing.db")).unwrap(), RecoveryReport::Healthy);
+
+        // A fresh database is created in its place and passes the check
+        drop(Database::new(path.clone()).unwrap());
+        assert_eq!(check_and_recover(&path).unwrap(), RecoveryReport::Healthy);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -202,7 +202,7 @@ CREATE TRIGGER IF NOT EXISTS emails_ad AFTER DELETE ON emails BEGIN
     VALUES ('delete', OLD.id, OLD.subject, OLD.body_text, OLD.from_name, OLD.from_address);
 END;
 
-CREATE TRIGGER IF NOT EXISTS emails_au AFTER UPDATE ON emails BEGIN
+CREATE TRIGGER IF NOT EXISTS emails_au AFTER UPDATE OF subject, body_text, from_name, from_address ON emails BEGIN
     INSERT INTO emails_fts(emails_fts, rowid, subject, body_text, from_name, from_address)
     VALUES ('delete', OLD.id, OLD.subject, OLD.body_text, OLD.from_name, OLD.from_address);
     INSERT INTO emails_fts(rowid, subject, body_text, from_name, from_address)
@@ -567,7 +567,7 @@ CREATE TABLE IF NOT EXISTS sync_history (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
 
     -- Sync metadata
-    data_type TEXT NOT NULL CHECK (data_type IN ('accounts', 'contacts', 'preferences', 'signatures')),
+    data_type TEXT NOT NULL,               -- SyncDataType::as_str()
     version INTEGER NOT NULL,              -- Server version number
 
     -- Snapshot data (encrypted)
@@ -0,0 +1,227 @@
+//! Deep Links
+//!
+//! Links that open the app at a specific place: `mailto:` links become a
+//! prefilled draft in the composer, `owlivion://email/<id>` opens a message
+//! and `owlivion://folder/<account id>/<folder>` a folder. On macOS links
+//! arrive as events while the app runs; on Windows and Linux the link is the
+//! command line of a new launch. While the app runs, that launch hands its
+//! command line to the running app and exits before opening the database
+//! (single-instance plugin); profile links are then ignored, since switching
+//! profiles restarts the app. Links are queued until the frontend takes
+//! them, so one arriving before the window has loaded is not lost; the
+//! `deep-link://open` event tells a loaded frontend to take them.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Event announcing queued links
+pub const OPEN_EVENT: &str = "deep-link://open";
+
+/// Payload of `deep-link://open` (sent as `null`; links are taken by command)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LinksPending;
+
+/// Command line flag of the desktop entry's "Compose" action
+const COMPOSE_FLAG: &str = "--compose";
+
+/// Longest link accepted (mailto bodies can be large, but not unbounded)
+const MAX_LINK_LEN: usize = 64 * 1024;
+
+/// Draft from a `mailto:` link
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct MailtoDraft {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub bcc: Vec<String>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+}
+
+/// Where a link leads
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DeepLink {
+    Compose { draft: MailtoDraft },
+    #[serde(rename_all = "camelCase")]
+    Email {
+        #[cfg_attr(test, ts(type = "number"))]
+        email_id: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Folder {
+        #[cfg_attr(test, ts(type = "number"))]
+        account_id: i64,
+        folder: String,
+    },
+}
+
+fn decode(value: &str) -> Result<String, String> {
+    urlencoding::decode(value)
+        .map(|decoded| decoded.into_owned())
+        .map_err(|e| format!("Invalid link encoding: {}", e))
+}
+
+fn add_recipients(list: &mut Vec<String>, value: &str) -> Result<(), String> {
+    for address in decode(value)?.split(',') {
+        let address = address.trim();
+        if address.is_empty() {
+            continue;
+        }
+        // Line breaks in a recipient would end up as extra headers
+        if !address.contains('@') || address.contains(['\r', '\n']) {
+            return Err(format!("Invalid recipient in link: {}", address));
+        }
+        list.push(address.to_string());
+    }
+    Ok(())
+}
+
+/// Draft from a `mailto:` link (RFC 6068); unknown fields such as attachments are ignored
+fn parse_mailto(rest: &str) -> Result<MailtoDraft, String> {
+    let (recipients, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut draft = MailtoDraft::default();
+    add_recipients(&mut draft.to, recipients)?;
+
+    for field in query.split('&').filter(|field| !field.is_empty()) {
+        let (name, value) = field.split_once('=').unwrap_or((field, ""));
+        match name.to_ascii_lowercase().as_str() {
+            "to" => add_recipients(&mut draft.to, value)?,
+            "cc" => add_recipients(&mut draft.cc, value)?,
+            "bcc" => add_recipients(&mut draft.bcc, value)?,
+            "subject" => {
+                let subject = decode(value)?.replace(['\r', '\n'], " ");
+                draft.subject = Some(subject.trim().to_string()).filter(|s| !s.is_empty());
+            }
+            "body" => draft.body = Some(decode(value)?.replace("\r\n", "\n")),
+            _ => {}
+        }
+    }
+    Ok(draft)
+}
+
+fn parse_owlivion(rest: &str) -> Result<DeepLink, String> {
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let mut segments = rest.split('/').filter(|segment| !segment.is_empty());
+    let id = |segment: Option<&str>| {
+        segment
+            .and_then(|s| s.parse::<i64>().ok())
+            .ok_or_else(|| format!("Invalid link: owlivion://{}", rest))
+    };
+
+    match segments.next() {
+        Some("email") => Ok(DeepLink::Email { email_id: id(segments.next())? }),
+        Some("folder") => {
+            let account_id = id(segments.next())?;
+            let folder = segments.map(decode).collect::<Result<Vec<_>, _>>()?.join("/");
+            if folder.is_empty() {
+                return Err("Link names no folder".to_string());
+            }
+            Ok(DeepLink::Folder { account_id, folder })
+        }
+        _ => Err(format!("Unsupported link: owlivion://{}", rest)),
+    }
+}
+
+/// Parse a `mailto:` or `owlivion://` link
+pub fn parse(link: &str) -> Result<DeepLink, String> {
+    let link = link.trim();
+    if link.len() > MAX_LINK_LEN {
+        return Err("Link is too long".to_string());
+    }
+    let (scheme, rest) = link.split_once(':').ok_or_else(|| format!("Not a link: {}", link))?;
+    match scheme.to_ascii_lowercase().as_str() {
+        "mailto" => Ok(DeepLink::Compose { draft: parse_mailto(rest)? }),
+        "owlivion" => parse_owlivion(rest.strip_prefix("//").unwrap_or(rest)),
+        _ => Err(format!("Unsupported link scheme: {}", scheme)),
+    }
+}
+
+/// Links on a launch command line, including the desktop entry's `--compose`
+/// Profile links are left to the profile selection at launch.
+pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Vec<DeepLink> {
+    args.into_iter()
+        .filter_map(|arg| {
+            if arg == COMPOSE_FLAG {
+                return Some(DeepLink::Compose { draft: MailtoDraft::default() });
+            }
+            let lower = arg.to_ascii_lowercase();
+            if (!lower.starts_with("mailto:") && !lower.starts_with("owlivion://"))
+                || lower.starts_with("owlivion://profile/")
+            {
+                return None;
+            }
+            parse(&arg).map_err(|e| log::warn!("Ignoring link: {}", e)).ok()
+        })
+        .collect()
+}
+
+/// Opened links, until the frontend takes them
+#[derive(Default)]
+pub struct PendingLinks {
+    links: Mutex<Vec<DeepLink>>,
+}
+
+impl PendingLinks {
+    pub fn push(&self, links: impl IntoIterator<Item = DeepLink>) {
+        if let Ok(mut pending) = self.links.lock() {
+            pending.extend(links);
+        }
+    }
+
+    pub fn take(&self) -> Vec<DeepLink> {
+        self.links.lock().map(|mut pending| std::mem::take(&mut *pending)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mailto() {
+        let link = parse(
+            "mailto:alice@example.com,bob+news@example.com?cc=carol@example.com&Subject=Hello%20there%0D%0AX-Evil:%201&body=Line%201%0D%0ALine%202&attach=/etc/passwd",
+        )
+        .unwrap();
+        assert_eq!(
+            link,
+            DeepLink::Compose {
+                draft: MailtoDraft {
+                    to: vec!["alice@example.com".to_string(), "bob+news@example.com".to_string()],
+                    cc: vec!["carol@example.com".to_string()],
+                    bcc: vec![],
+                    subject: Some("Hello there  X-Evil: 1".to_string()),
+                    body: Some("Line 1\nLine 2".to_string()),
+                }
+            }
+        );
+
+        assert_eq!(parse("MAILTO:?to=dave@example.com").unwrap(), DeepLink::Compose {
+            draft: MailtoDraft { to: vec!["dave@example.com".to_string()], ..Default::default() }
+        });
+        assert!(parse("mailto:not-an-address").is_err());
+        assert!(parse("mailto:?bcc=eve@example.com%0D%0ABcc:%20x@y.test").is_err());
+    }
+
+    #[test]
+    fn test_parse_owlivion_and_args() {
+        assert_eq!(parse("owlivion://email/42").unwrap(), DeepLink::Email { email_id: 42 });
+        assert_eq!(parse("owlivion://folder/3/INBOX/Work%20Stuff/").unwrap(), DeepLink::Folder {
+            account_id: 3,
+            folder: "INBOX/Work Stuff".to_string()
+        });
+        assert!(parse("owlivion://email/abc").is_err());
+        assert!(parse("owlivion://folder/3").is_err());
+        assert!(parse("https://example.com").is_err());
+
+        let args = ["app", "--profile", "work", "owlivion://profile/work", "mailto:a@b.test", "--compose", "owlivion://email/x"]
+            .map(String::from);
+        assert_eq!(from_args(args), [
+            DeepLink::Compose { draft: MailtoDraft { to: vec!["a@b.test".to_string()], ..Default::default() } },
+            DeepLink::Compose { draft: MailtoDraft::default() },
+        ]);
+    }
+}
@@ -0,0 +1,192 @@
+//! Default Mail Client
+//!
+//! Whether `mailto:` links open in this app, and making them do so. Each
+//! platform keeps the choice in its own place: the URL association of the
+//! current user on Windows, LaunchServices on macOS and `xdg-settings` on
+//! Linux desktops. Windows does not let applications take over a protocol
+//! the user has assigned to another one, so there the system's Default Apps
+//! page is opened for the user to finish the change.
+
+use tauri::{AppHandle, Runtime};
+
+/// Desktop entry installed by the Linux packages
+#[cfg(any(target_os = "linux", test))]
+const DESKTOP_ENTRY: &str = "owlivion-mail.desktop";
+
+/// Run a system tool and return its output
+#[cfg(any(windows, target_os = "linux"))]
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Data of a value in `reg query` output (`    Name    REG_SZ    data`)
+#[cfg(any(windows, test))]
+fn reg_value(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (_, data) = line.split_once("REG_SZ")?;
+        Some(data.trim().to_string()).filter(|data| !data.is_empty())
+    })
+}
+
+/// Whether a desktop entry is this app's (the package's own, or the handler
+/// entry written when registering from an AppImage)
+#[cfg(any(target_os = "linux", test))]
+fn is_own_desktop_entry(entry: &str) -> bool {
+    entry.trim() == DESKTOP_ENTRY || entry.trim().starts_with("owlivion-mail-handler")
+}
+
+#[cfg(target_os = "macos")]
+mod launch_services {
+    use core_foundation::base::TCFType;
+    use core_foundation::string::{CFString, CFStringRef};
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn LSCopyDefaultHandlerForURLScheme(scheme: CFStringRef) -> CFStringRef;
+        fn LSSetDefaultHandlerForURLScheme(scheme: CFStringRef, handler: CFStringRef) -> i32;
+    }
+
+    /// Bundle identifier of the app handling a URL scheme
+    pub fn default_handler(scheme: &str) -> Option<String> {
+        let scheme = CFString::new(scheme);
+        let handler = unsafe { LSCopyDefaultHandlerForURLScheme(scheme.as_concrete_TypeRef()) };
+        if handler.is_null() {
+            return None;
+        }
+        // Returned under the create rule, so the wrapper releases it
+        Some(unsafe { CFString::wrap_under_create_rule(handler) }.to_string())
+    }
+
+    pub fn set_default_handler(scheme: &str, bundle_id: &str) -> Result<(), String> {
+        let scheme = CFString::new(scheme);
+        let bundle_id = CFString::new(bundle_id);
+        let status =
+            unsafe { LSSetDefaultHandlerForURLScheme(scheme.as_concrete_TypeRef(), bundle_id.as_concrete_TypeRef()) };
+        if status != 0 {
+            return Err(format!("LaunchServices refused the change (error {})", status));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `mailto:` links open in this app
+pub fn is_default<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    #[cfg(windows)]
+    {
+        let _ = app;
+        let exe = std::env::current_exe().map_err(|e| format!("Failed to locate the app: {}", e))?;
+        let exe = exe.display().to_string().to_lowercase();
+
+        // The user's choice wins over the class registered for the protocol
+        let prog_id = run(
+            "reg",
+            &[
+                "query",
+                r"HKCU\Software\Microsoft\Windows\Shell\Associations\UrlAssociations\mailto\UserChoice",
+                "/v",
+                "ProgId",
+            ],
+        )
+        .ok()
+        .and_then(|output| reg_value(&output))
+        .unwrap_or_else(|| "mailto".to_string());
+
+        let command = run("reg", &["query", &format!(r"HKCR\{}\shell\open\command", prog_id), "/ve"])
+            .ok()
+            .and_then(|output| reg_value(&output))
+            .unwrap_or_default();
+        Ok(command.to_lowercase().contains(&exe))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use tauri::Manager;
+        let bundle_id = &app.config().identifier;
+        Ok(launch_services::default_handler("mailto").is_some_and(|handler| handler.eq_ignore_ascii_case(bundle_id)))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = app;
+        let entry = run("xdg-settings", &["get", "default-url-scheme-handler", "mailto"])?;
+        Ok(is_own_desktop_entry(&entry))
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    {
+        let _ = app;
+        Err("Choosing the default mail client is not supported on this system".to_string())
+    }
+}
+
+/// Make this app open `mailto:` links
+/// Returns whether it is the default now; false means the user has to
+/// confirm in the system settings, which have been opened.
+pub fn set_default<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
+    #[cfg(windows)]
+    {
+        use tauri_plugin_deep_link::DeepLinkExt;
+        app.deep_link()
+            .register("mailto")
+            .map_err(|e| format!("Failed to register mailto links: {}", e))?;
+        if is_default(app)? {
+            return Ok(true);
+        }
+        open::that("ms-settings:defaultapps").map_err(|e| format!("Failed to open Default Apps: {}", e))?;
+        Ok(false)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use tauri::Manager;
+        launch_services::set_default_handler("mailto", &app.config().identifier)?;
+        is_default(app)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Without the package's desktop entry (AppImage, development builds)
+        // a handler entry is written for this executable
+        if let Err(e) = run("xdg-settings", &["set", "default-url-scheme-handler", "mailto", DESKTOP_ENTRY]) {
+            use tauri_plugin_deep_link::DeepLinkExt;
+            log::info!("Registering a mailto handler entry ({})", e);
+            app.deep_link()
+                .register("mailto")
+                .map_err(|e| format!("Failed to register mailto links: {}", e))?;
+        }
+        is_default(app)
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    {
+        let _ = app;
+        Err("Choosing the default mail client is not supported on this system".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_output_parsing() {
+        let output = "\r\nHKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\Shell\\Associations\\UrlAssociations\\mailto\\UserChoice\r\n    ProgId    REG_SZ    Outlook.URL.mailto.15\r\n\r\n";
+        assert_eq!(reg_value(output), Some("Outlook.URL.mailto.15".to_string()));
+        assert_eq!(reg_value("ERROR: The system was unable to find the specified registry key or value."), None);
+
+        assert!(is_own_desktop_entry("owlivion-mail.desktop\n"));
+        assert!(is_own_desktop_entry("owlivion-mail-handler.desktop"));
+        assert!(!is_own_desktop_entry("thunderbird.desktop"));
+    }
+}
@@ -0,0 +1,55 @@
+//! Sending on Behalf
+//!
+//! A delegate with access to someone else's mailbox (see `mail::namespace`)
+//! can send as that mailbox: From carries the owner's address and Sender the
+//! account's own, which clients show as "on behalf of". Whether the server
+//! accepts this is up to its administrator, so the addresses an account may
+//! send for are configured per account and any other From is refused
+//! before connecting.
+
+use crate::db::{Database, DbResult};
+
+fn setting(account_id: i64) -> String {
+    format!("send_on_behalf_{}", account_id)
+}
+
+/// Addresses the account may send on behalf of (lowercase)
+pub fn allowed(db: &Database, account_id: i64) -> DbResult<Vec<String>> {
+    Ok(db.get_setting(&setting(account_id))?.unwrap_or_default())
+}
+
+/// Replace the addresses the account may send on behalf of
+/// Returns them normalized (trimmed, lowercase, without duplicates).
+pub fn set_allowed(db: &Database, account_id: i64, addresses: &[String]) -> DbResult<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for address in addresses {
+        let address = address.trim().to_lowercase();
+        if !address.is_empty() && !normalized.contains(&address) {
+            normalized.push(address);
+        }
+    }
+    db.set_setting(&setting(account_id), &normalized)?;
+    Ok(normalized)
+}
+
+/// Whether the account may use `address` as From
+pub fn is_allowed(db: &Database, account_id: i64, address: &str) -> DbResult<bool> {
+    let address = address.trim().to_lowercase();
+    Ok(allowed(db, account_id)?.contains(&address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_addresses() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        assert!(!is_allowed(&db, 1, "boss@example.com").unwrap());
+
+        let saved = set_allowed(&db, 1, &[" Boss@Example.com ".to_string(), "boss@example.com".to_string(), String::new()]).unwrap();
+        assert_eq!(saved, ["boss@example.com"]);
+        assert!(is_allowed(&db, 1, "BOSS@example.com").unwrap());
+        assert!(!is_allowed(&db, 2, "boss@example.com").unwrap());
+    }
+}
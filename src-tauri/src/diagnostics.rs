@@ -0,0 +1,262 @@
+//! Diagnostics Bundle
+//!
+//! Support asks for one zip file instead of screenshots of log output. The
+//! bundle holds recent log lines (kept in memory by the logger installed with
+//! `init_logging`), the schema version, account settings without any
+//! credentials, the cached server capabilities and the latest stored errors.
+//! Passwords, tokens and API keys in log lines and error messages are always
+//! masked; email addresses only when the user asks for it, since they often
+//! matter for finding a problem.
+
+use crate::capabilities;
+use crate::db::{Database, DbResult};
+use regex_lite::Regex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{Cursor, Write};
+use std::sync::Mutex;
+
+/// Log lines kept for the bundle
+const MAX_LOG_LINES: usize = 2000;
+
+/// Stored errors included in the bundle
+const MAX_ERRORS: u32 = 200;
+
+lazy_static::lazy_static! {
+    /// Most recent log lines, oldest first
+    static ref RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(MAX_LOG_LINES));
+
+    /// `password=...`, `token: ...`, `Bearer ...` and the like
+    static ref SECRET_PATTERN: Regex = Regex::new(
+        r#"(?i)\b((?:password|passwd|access_token|refresh_token|token|secret|api_key|apikey|authorization)["']?\s*[:=]\s*["']?|bearer\s+)(?:bearer\s+|basic\s+)?[^\s"',;&]+"#
+    ).expect("valid secret pattern");
+
+    /// Secrets in URL query strings (the Gemini API takes its key as `?key=`)
+    static ref QUERY_SECRET_PATTERN: Regex = Regex::new(r#"([?&](?:key|code|access_token)=)[^&\s"']+"#)
+        .expect("valid query pattern");
+
+    static ref ADDRESS_PATTERN: Regex = Regex::new(r"[A-Za-z0-9._%+-]+@([A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,})")
+        .expect("valid address pattern");
+}
+
+/// Logger that writes like `env_logger` and remembers recent lines
+struct RecentLogs {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for RecentLogs {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.inner.matches(record) {
+            let line = format!(
+                "{} {:<5} {}: {}",
+                chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            if let Ok(mut lines) = RECENT_LOGS.lock() {
+                if lines.len() == MAX_LOG_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install the logger (`RUST_LOG` filter, `info` by default)
+pub fn init_logging() {
+    let logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let max_level = logger.filter();
+    if log::set_boxed_logger(Box::new(RecentLogs { inner: logger })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Mask secrets, and addresses if asked to (keeping their domain)
+pub fn redact(text: &str, redact_addresses: bool) -> String {
+    let text = SECRET_PATTERN.replace_all(text, "$1[REDACTED]");
+    let text = QUERY_SECRET_PATTERN.replace_all(&text, "$1[REDACTED]");
+    if redact_addresses {
+        ADDRESS_PATTERN.replace_all(&text, "***@$1").into_owned()
+    } else {
+        text.into_owned()
+    }
+}
+
+/// Overview file of the bundle
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    app_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    created_at: String,
+    schema_version: u32,
+    addresses_redacted: bool,
+}
+
+/// Account settings without credentials
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountInfo {
+    id: i64,
+    email: String,
+    imap_host: String,
+    imap_port: i32,
+    imap_security: String,
+    smtp_host: String,
+    smtp_port: i32,
+    smtp_security: String,
+    oauth_provider: Option<String>,
+    is_active: bool,
+    sync_days: i32,
+    accept_invalid_certs: bool,
+}
+
+/// Error stored by a background task
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredError {
+    source: String,
+    account_id: Option<i64>,
+    message: String,
+    at: Option<String>,
+}
+
+fn recent_errors(db: &Database) -> DbResult<Vec<StoredError>> {
+    db.query(
+        r#"
+        SELECT 'pending_operation', account_id, last_error, created_at FROM pending_operations WHERE last_error IS NOT NULL
+        UNION ALL
+        SELECT 'sign_in', account_id, last_error, last_failure_at FROM account_auth_state WHERE last_error IS NOT NULL
+        UNION ALL
+        SELECT 'folder_sync', account_id, sync_error, updated_at FROM sync_state WHERE sync_error IS NOT NULL
+        UNION ALL
+        SELECT 'feed', account_id, last_error, last_polled_at FROM feeds WHERE last_error IS NOT NULL
+        UNION ALL
+        SELECT 'mail_merge', account_id, last_error, updated_at FROM mail_merges WHERE last_error IS NOT NULL
+        ORDER BY 4 DESC
+        LIMIT ?1
+        "#,
+        [MAX_ERRORS],
+        |row| {
+            Ok(StoredError {
+                source: row.get(0)?,
+                account_id: row.get(1)?,
+                message: row.get(2)?,
+                at: row.get(3)?,
+            })
+        },
+    )
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize diagnostics: {}", e))
+}
+
+/// Build the diagnostics zip
+pub fn bundle(db: &Database, redact_addresses: bool) -> Result<Vec<u8>, String> {
+    let db_error = |e: crate::db::DbError| format!("Failed to read diagnostics: {}", e);
+
+    let manifest = Manifest {
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        schema_version: db.schema_version().map_err(db_error)?,
+        addresses_redacted: redact_addresses,
+    };
+
+    let accounts = db.get_accounts().map_err(db_error)?;
+    let mut capabilities = Vec::with_capacity(accounts.len());
+    for account in &accounts {
+        capabilities.push(capabilities::get(db, account.id).map_err(db_error)?);
+    }
+    let accounts: Vec<AccountInfo> = accounts
+        .into_iter()
+        .map(|account| AccountInfo {
+            id: account.id,
+            email: account.email,
+            imap_host: account.imap_host,
+            imap_port: account.imap_port,
+            imap_security: account.imap_security,
+            smtp_host: account.smtp_host,
+            smtp_port: account.smtp_port,
+            smtp_security: account.smtp_security,
+            oauth_provider: account.oauth_provider,
+            is_active: account.is_active,
+            sync_days: account.sync_days,
+            accept_invalid_certs: account.accept_invalid_certs,
+        })
+        .collect();
+
+    let logs = RECENT_LOGS
+        .lock()
+        .map(|lines| lines.iter().map(String::as_str).collect::<Vec<_>>().join("\n"))
+        .unwrap_or_default();
+
+    let files = [
+        ("manifest.json", to_json(&manifest)?),
+        ("logs.txt", logs),
+        ("accounts.json", to_json(&accounts)?),
+        ("capabilities.json", to_json(&capabilities)?),
+        ("errors.json", to_json(&recent_errors(db).map_err(db_error)?)?),
+    ];
+
+    let zip_error = |e: zip::result::ZipError| format!("Failed to write diagnostics bundle: {}", e);
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in files {
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(redact(&content, redact_addresses).as_bytes())
+            .map_err(|e| format!("Failed to write diagnostics bundle: {}", e))?;
+    }
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_redact() {
+        let line = "LOGIN failed for bob@example.com: password=hunter2 token: abc123 Authorization: Bearer xyz";
+        let redacted = redact(line, false);
+        assert!(!redacted.contains("hunter2") && !redacted.contains("abc123") && !redacted.contains("xyz"));
+        assert!(redacted.contains("bob@example.com"));
+        assert!(redacted.contains("password=[REDACTED]"));
+
+        assert_eq!(
+            redact("GET https://api.test/v1?key=AIza123&alt=json for bob@mail.example.com", true),
+            "GET https://api.test/v1?key=[REDACTED]&alt=json for ***@mail.example.com"
+        );
+    }
+
+    #[test]
+    fn test_bundle_contents() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let data = bundle(&db, true).unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+        let mut names: Vec<_> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(names, ["accounts.json", "capabilities.json", "errors.json", "logs.txt", "manifest.json"]);
+
+        let mut manifest = String::new();
+        archive.by_name("manifest.json").unwrap().read_to_string(&mut manifest).unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(manifest["schemaVersion"], crate::db::migrate::latest_version());
+        assert_eq!(manifest["addressesRedacted"], true);
+    }
+}
@@ -0,0 +1,576 @@
+//! Newsletter Digest
+//!
+//! Messages recognised as newsletters (or promotions, social updates and
+//! automated notifications, as configured) do not trigger desktop
+//! notifications. Instead, once a day or week, each account gets a single
+//! generated message in its local "Digests" folder listing everything from
+//! those categories that arrived since the previous digest.
+//!
+//! Categorisation is heuristic (sender address, sender domain, subject); a
+//! label with the category name, e.g. set by a filter, always wins.
+
+use crate::db::{Database, DbResult};
+use crate::mail::compose::escape_html;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use lettre::message::{Mailbox, MessageBuilder};
+use lettre::{Address, Message};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tauri event name emitted after scheduled digests were generated
+pub const DIGEST_CREATED_EVENT: &str = "digest://created";
+
+/// Local folder receiving the digests
+pub const DIGEST_FOLDER: &str = "Digests";
+
+/// Settings key holding the digest configuration
+const CONFIG_SETTING: &str = "digest_config";
+
+/// How often the scheduler checks whether a digest is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Messages listed in one digest (newest first)
+const MAX_DIGEST_ITEMS: usize = 500;
+
+/// Categories that can be rolled up into the digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum DigestCategory {
+    Newsletter,
+    Promotions,
+    Social,
+    Notifications,
+}
+
+impl DigestCategory {
+    pub const ALL: [DigestCategory; 4] = [
+        DigestCategory::Newsletter,
+        DigestCategory::Promotions,
+        DigestCategory::Social,
+        DigestCategory::Notifications,
+    ];
+
+    /// Label name that puts a message into this category
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestCategory::Newsletter => "newsletter",
+            DigestCategory::Promotions => "promotions",
+            DigestCategory::Social => "social",
+            DigestCategory::Notifications => "notifications",
+        }
+    }
+
+    /// Section heading in the digest
+    pub fn title(&self) -> &'static str {
+        match self {
+            DigestCategory::Newsletter => "Newsletters",
+            DigestCategory::Promotions => "Promotions",
+            DigestCategory::Social => "Social",
+            DigestCategory::Notifications => "Notifications",
+        }
+    }
+}
+
+/// How often digests are generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum DigestSchedule {
+    Daily,
+    Weekly,
+}
+
+impl DigestSchedule {
+    fn period(&self) -> ChronoDuration {
+        match self {
+            DigestSchedule::Daily => ChronoDuration::days(1),
+            DigestSchedule::Weekly => ChronoDuration::weeks(1),
+        }
+    }
+}
+
+/// Digest configuration stored in settings table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct DigestConfig {
+    pub enabled: bool,
+    pub schedule: DigestSchedule,
+    /// Local hour the digest is generated (0-23)
+    pub hour: u32,
+    /// Day of the weekly digest (0 = Monday)
+    pub weekday: u32,
+    pub categories: Vec<DigestCategory>,
+    /// Last generation (RFC 3339)
+    pub last_run: Option<String>,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: DigestSchedule::Daily,
+            hour: 8,
+            weekday: 0,
+            categories: vec![DigestCategory::Newsletter],
+            last_run: None,
+        }
+    }
+}
+
+impl DigestConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hour > 23 {
+            return Err(format!("Digest hour must be 0-23, got {}", self.hour));
+        }
+        if self.weekday > 6 {
+            return Err(format!("Digest weekday must be 0-6, got {}", self.weekday));
+        }
+        if self.categories.is_empty() {
+            return Err("Select at least one digest category".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether a message of `category` is held back for the digest
+    pub fn includes(&self, category: Option<DigestCategory>) -> bool {
+        self.enabled && category.is_some_and(|category| self.categories.contains(&category))
+    }
+
+    /// Most recent scheduled time at or before `now` (local wall clock)
+    pub fn last_scheduled(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let at = NaiveTime::from_hms_opt(self.hour.min(23), 0, 0).unwrap_or_default();
+        let mut scheduled = now.date().and_time(at);
+        if scheduled > now {
+            scheduled -= ChronoDuration::days(1);
+        }
+        if self.schedule == DigestSchedule::Weekly {
+            let days_back = (scheduled.weekday().num_days_from_monday() + 7 - self.weekday.min(6)) % 7;
+            scheduled -= ChronoDuration::days(i64::from(days_back));
+        }
+        scheduled
+    }
+
+    /// Whether a digest is due at `now`
+    pub fn is_due(&self, now: DateTime<Local>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let scheduled = self.last_scheduled(now.naive_local());
+        let Some(scheduled) = Local.from_local_datetime(&scheduled).earliest() else {
+            return false;
+        };
+        match self.last_run.as_deref().and_then(crate::db::parse_db_timestamp) {
+            Some(last_run) => last_run < scheduled,
+            None => true,
+        }
+    }
+}
+
+pub fn load_config(db: &Database) -> DbResult<DigestConfig> {
+    Ok(db.get_setting(CONFIG_SETTING)?.unwrap_or_default())
+}
+
+pub fn save_config(db: &Database, config: &DigestConfig) -> DbResult<()> {
+    db.set_setting(CONFIG_SETTING, config)
+}
+
+// ============================================================================
+// Categorisation
+// ============================================================================
+
+/// Sender domains of newsletter platforms
+const NEWSLETTER_DOMAINS: &[&str] = &[
+    "substack.com",
+    "beehiiv.com",
+    "mailchimpapp.com",
+    "mcsv.net",
+    "list-manage.com",
+    "campaign-archive.com",
+    "mailerlite.com",
+    "convertkit-mail.com",
+    "ghost.io",
+    "buttondown.email",
+    "revue.email",
+];
+
+const SOCIAL_DOMAINS: &[&str] = &[
+    "facebookmail.com",
+    "linkedin.com",
+    "twitter.com",
+    "x.com",
+    "instagram.com",
+    "pinterest.com",
+    "reddit.com",
+    "redditmail.com",
+    "tiktok.com",
+    "youtube.com",
+    "mastodon.social",
+];
+
+const NEWSLETTER_SENDERS: &[&str] = &["newsletter", "news", "digest", "weekly", "bulletin", "bulten", "editor"];
+const PROMOTION_SENDERS: &[&str] = &["marketing", "promo", "promotions", "offers", "deals", "sales", "shop", "store"];
+const NOTIFICATION_SENDERS: &[&str] = &["noreply", "no-reply", "donotreply", "do-not-reply", "notifications", "notification", "alerts", "alert", "notify"];
+
+const NEWSLETTER_SUBJECTS: &[&str] = &["newsletter", "bülten", "weekly digest", "this week in", "issue #"];
+const PROMOTION_SUBJECTS: &[&str] = &["% off", "sale", "discount", "indirim", "kampanya", "fırsat", "coupon", "kupon", "free shipping"];
+
+fn domain_matches(domain: &str, known: &[&str]) -> bool {
+    known
+        .iter()
+        .any(|candidate| domain == *candidate || domain.ends_with(&format!(".{}", candidate)))
+}
+
+/// Category of a message, if it belongs to one
+pub fn categorize(from_address: &str, subject: &str, labels_json: &str) -> Option<DigestCategory> {
+    let labels: Vec<String> = serde_json::from_str(labels_json).unwrap_or_default();
+    let labelled = DigestCategory::ALL.into_iter().find(|category| {
+        labels
+            .iter()
+            .any(|label| label.eq_ignore_ascii_case(category.as_str()) || label.eq_ignore_ascii_case(category.title()))
+    });
+    if labelled.is_some() {
+        return labelled;
+    }
+
+    let address = from_address.trim().to_lowercase();
+    let (local, domain) = address.rsplit_once('@').unwrap_or((address.as_str(), ""));
+    let local_word = |words: &[&str]| local.split(['.', '_', '+']).any(|part| words.contains(&part));
+    let subject = subject.to_lowercase();
+    let subject_has = |words: &[&str]| words.iter().any(|word| subject.contains(word));
+
+    if domain_matches(domain, NEWSLETTER_DOMAINS) || local_word(NEWSLETTER_SENDERS) || subject_has(NEWSLETTER_SUBJECTS) {
+        Some(DigestCategory::Newsletter)
+    } else if domain_matches(domain, SOCIAL_DOMAINS) {
+        Some(DigestCategory::Social)
+    } else if local_word(PROMOTION_SENDERS) || subject_has(PROMOTION_SUBJECTS) {
+        Some(DigestCategory::Promotions)
+    } else if local_word(NOTIFICATION_SENDERS) {
+        Some(DigestCategory::Notifications)
+    } else {
+        None
+    }
+}
+
+/// Whether a desktop notification for this message should be suppressed
+pub fn suppresses_notification(config: &DigestConfig, from_address: &str, subject: &str, labels_json: &str) -> bool {
+    config.includes(categorize(from_address, subject, labels_json))
+}
+
+// ============================================================================
+// Generation
+// ============================================================================
+
+/// Message listed in a digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestItem {
+    pub email_id: i64,
+    pub category: DigestCategory,
+    pub from: String,
+    pub from_name: Option<String>,
+    pub subject: String,
+    pub preview: String,
+    pub date: String,
+}
+
+/// Digest generated for an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct DigestResult {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    /// Id of the digest message (None when nothing arrived in the period)
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub email_id: Option<i64>,
+    pub items: usize,
+}
+
+/// Server messages of the configured categories received after `since` (UTC)
+pub fn collect_items(
+    db: &Database,
+    account_id: i64,
+    config: &DigestConfig,
+    since: DateTime<Utc>,
+) -> DbResult<Vec<DigestItem>> {
+    let rows = db.query(
+        "SELECT e.id, e.from_address, e.from_name, e.subject, e.preview, e.date, e.labels
+         FROM emails e JOIN folders f ON f.id = e.folder_id
+         WHERE e.account_id = ?1 AND f.is_local = 0 AND e.is_deleted = 0 AND e.is_spam = 0
+           AND e.is_draft = 0 AND e.received_at > ?2
+         ORDER BY e.received_at DESC, e.id DESC",
+        params![account_id, since.format("%Y-%m-%d %H:%M:%S").to_string()],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        },
+    )?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(email_id, from, from_name, subject, preview, date, labels)| {
+            let category = categorize(&from, &subject, &labels).filter(|c| config.categories.contains(c))?;
+            Some(DigestItem { email_id, category, from, from_name, subject, preview, date })
+        })
+        .take(MAX_DIGEST_ITEMS)
+        .collect())
+}
+
+/// RFC 822 digest message
+fn digest_message(recipient: &str, config: &DigestConfig, items: &[DigestItem], now: DateTime<Utc>) -> Result<Vec<u8>, String> {
+    let period = match config.schedule {
+        DigestSchedule::Daily => "Daily",
+        DigestSchedule::Weekly => "Weekly",
+    };
+    let subject = format!(
+        "{} digest: {} message{} ({})",
+        period,
+        items.len(),
+        if items.len() == 1 { "" } else { "s" },
+        now.with_timezone(&Local).format("%d %b %Y"),
+    );
+
+    let mut html = format!("<h2>{}</h2>\n", escape_html(&subject));
+    let mut text = format!("{}\n", subject);
+    for category in DigestCategory::ALL {
+        let section: Vec<&DigestItem> = items.iter().filter(|item| item.category == category).collect();
+        if section.is_empty() {
+            continue;
+        }
+
+        html.push_str(&format!("<h3>{} ({})</h3>\n<ul>\n", category.title(), section.len()));
+        text.push_str(&format!("\n{} ({})\n", category.title(), section.len()));
+        for item in section {
+            let sender = item.from_name.as_deref().filter(|name| !name.is_empty()).unwrap_or(&item.from);
+            html.push_str(&format!(
+                "<li><b>{}</b> &mdash; {}<br><small>{}</small></li>\n",
+                escape_html(sender),
+                escape_html(&item.subject),
+                escape_html(&item.preview),
+            ));
+            text.push_str(&format!("- {}: {}\n", sender, item.subject));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    let sender = Address::new("digest", "owlivion.local").map_err(|e| e.to_string())?;
+    let builder: MessageBuilder = Message::builder()
+        .from(Mailbox::new(Some("Owlivion Digest".to_string()), sender))
+        .to(recipient
+            .parse()
+            .map_err(|e| format!("Invalid account address: {}", e))?)
+        .subject(subject)
+        .message_id(Some(format!("<digest-{}@owlivion.local>", uuid::Uuid::new_v4())))
+        .date(now.into());
+
+    let message = crate::mail::compose::build_message(builder, Some(text), Some(html), Vec::new())
+        .map_err(|e| e.to_string())?;
+    Ok(message.formatted())
+}
+
+/// Build and store the digest of one account covering messages since `since`
+pub fn generate(
+    db: &Database,
+    account_id: i64,
+    config: &DigestConfig,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<DigestResult, String> {
+    let items = collect_items(db, account_id, config, since).map_err(|e| format!("Failed to collect digest: {}", e))?;
+    if items.is_empty() {
+        return Ok(DigestResult { account_id, email_id: None, items: 0 });
+    }
+
+    let account = db.get_account(account_id).map_err(|e| format!("Failed to get account: {}", e))?;
+    let folder_id = db
+        .get_or_create_local_folder(account_id, DIGEST_FOLDER)
+        .map_err(|e| format!("Failed to create digest folder: {}", e))?;
+
+    let raw = digest_message(&account.email, config, &items, now)?;
+    let new_email = crate::new_email_from_raw(account_id, folder_id, &raw)?;
+    let email_id = db
+        .insert_local_email(&new_email, &raw)
+        .map_err(|e| format!("Failed to store digest: {}", e))?;
+
+    log::info!("Generated digest for account {} with {} message(s)", account_id, items.len());
+    Ok(DigestResult { account_id, email_id: Some(email_id), items: items.len() })
+}
+
+/// Generate digests for every active account and remember the run
+/// Without a previous run the digest covers one schedule period.
+pub fn run_all(db: &Database, config: &mut DigestConfig, now: DateTime<Utc>) -> Result<Vec<DigestResult>, String> {
+    let since = config
+        .last_run
+        .as_deref()
+        .and_then(crate::db::parse_db_timestamp)
+        .unwrap_or(now - config.schedule.period());
+
+    let mut results = Vec::new();
+    for account in db.get_all_accounts().map_err(|e| format!("Failed to list accounts: {}", e))? {
+        match generate(db, account.id, config, since, now) {
+            Ok(result) => results.push(result),
+            Err(e) => log::warn!("Failed to generate digest for account {}: {}", account.id, e),
+        }
+    }
+
+    config.last_run = Some(now.to_rfc3339());
+    save_config(db, config).map_err(|e| format!("Failed to save digest config: {}", e))?;
+    Ok(results)
+}
+
+/// Background generator for scheduled digests
+pub struct DigestScheduler {
+    db: Arc<Database>,
+    started: AtomicBool,
+}
+
+impl DigestScheduler {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Start the scheduler (only the first call has an effect)
+    /// `on_created` receives the digests that contain messages.
+    pub fn start<F>(&self, on_created: F)
+    where
+        F: Fn(Vec<DigestResult>) + Send + Sync + 'static,
+    {
+        if self.started.swap(true, Ordering::SeqCst) {
+            log::warn!("Digest scheduler already started");
+            return;
+        }
+        let db = self.db.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let mut config = match load_config(&db) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        log::error!("Failed to load digest config: {}", e);
+                        continue;
+                    }
+                };
+                if !config.is_due(Local::now()) {
+                    continue;
+                }
+
+                match run_all(&db, &mut config, Utc::now()) {
+                    Ok(results) => {
+                        let created: Vec<DigestResult> =
+                            results.into_iter().filter(|result| result.email_id.is_some()).collect();
+                        if !created.is_empty() {
+                            on_created(created);
+                        }
+                    }
+                    Err(e) => log::error!("Failed to generate digests: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_categorize_and_schedule() {
+        assert_eq!(categorize("writer@example.substack.com", "Issue 12", "[]"), Some(DigestCategory::Newsletter));
+        assert_eq!(categorize("newsletter@shop.test", "Hello", "[]"), Some(DigestCategory::Newsletter));
+        assert_eq!(categorize("messages-noreply@linkedin.com", "New message", "[]"), Some(DigestCategory::Social));
+        assert_eq!(categorize("team@shop.test", "Summer sale: 30% off", "[]"), Some(DigestCategory::Promotions));
+        assert_eq!(categorize("no-reply@bank.test", "Statement ready", "[]"), Some(DigestCategory::Notifications));
+        assert_eq!(categorize("alice@example.com", "Lunch tomorrow?", "[]"), None);
+        assert_eq!(categorize("alice@example.com", "Lunch", r#"["Newsletter"]"#), Some(DigestCategory::Newsletter));
+
+        let mut config = DigestConfig { enabled: true, ..Default::default() };
+        assert!(suppresses_notification(&config, "news@paper.test", "Morning briefing", "[]"));
+        assert!(!suppresses_notification(&config, "no-reply@bank.test", "Statement", "[]"));
+        config.categories = Vec::new();
+        assert!(config.validate().is_err());
+
+        // Wednesday 2025-01-15 07:30 local
+        let now = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap().and_hms_opt(7, 30, 0).unwrap();
+        let daily = DigestConfig { hour: 8, ..Default::default() };
+        assert_eq!(daily.last_scheduled(now), NaiveDate::from_ymd_opt(2025, 1, 14).unwrap().and_hms_opt(8, 0, 0).unwrap());
+
+        let weekly = DigestConfig { schedule: DigestSchedule::Weekly, hour: 6, weekday: 4, ..Default::default() };
+        assert_eq!(weekly.last_scheduled(now), NaiveDate::from_ymd_opt(2025, 1, 10).unwrap().and_hms_opt(6, 0, 0).unwrap());
+
+        let mut due = DigestConfig { enabled: true, ..Default::default() };
+        assert!(due.is_due(Local::now()));
+        due.last_run = Some(Utc::now().to_rfc3339());
+        assert!(!due.is_due(Local::now()));
+    }
+
+    #[test]
+    fn test_generate_rolls_up_newsletters() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+
+        let folder_id = crate::sync_folder_to_db(&db, account_id, "INBOX").unwrap();
+        let summary = |uid: u32, from: &str, subject: &str| crate::mail::EmailSummary {
+            uid,
+            message_id: Some(format!("<{}@test.com>", uid)),
+            from: from.to_string(),
+            from_name: None,
+            subject: subject.to_string(),
+            preview: "Preview".to_string(),
+            date: "2025-01-01T10:00:00Z".to_string(),
+            is_read: false,
+            is_starred: false,
+            has_attachments: false,
+            size: 0,
+            keywords: Vec::new(),
+            account_id: None,
+            account_email: None,
+            account_name: None,
+            account_color: None,
+        };
+        let ids = db
+            .batch_upsert_emails(&[
+                crate::summary_to_new_email(account_id, folder_id, &summary(1, "news@paper.test", "Morning <briefing>")),
+                crate::summary_to_new_email(account_id, folder_id, &summary(2, "bob@test.com", "Project update")),
+                crate::summary_to_new_email(account_id, folder_id, &summary(3, "team@shop.test", "Big sale")),
+            ])
+            .unwrap();
+
+        let mut config = DigestConfig { enabled: true, ..Default::default() };
+        let since = Utc::now() - ChronoDuration::days(1);
+        let items = collect_items(&db, account_id, &config, since).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].email_id, ids[0]);
+
+        let results = run_all(&db, &mut config, Utc::now()).unwrap();
+        assert_eq!(results[0].items, 1);
+        let digest = db.get_email(results[0].email_id.unwrap()).unwrap();
+        assert!(digest.subject.starts_with("Daily digest: 1 message"));
+        assert!(digest.body_html.unwrap_or_default().contains("Morning &lt;briefing&gt;"));
+        assert!(db.get_folder_by_id(digest.folder_id).unwrap().is_local);
+
+        // The run is remembered; nothing new arrived since
+        assert!(load_config(&db).unwrap().last_run.is_some());
+        let again = run_all(&db, &mut config, Utc::now() + ChronoDuration::seconds(1)).unwrap();
+        assert_eq!(again[0].email_id, None);
+    }
+}
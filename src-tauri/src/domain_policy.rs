@@ -0,0 +1,280 @@
+//! Domain Policies
+//!
+//! User-defined security rules for mail from a sender address or domain,
+//! e.g. "mail from finance@bank.com must pass DKIM and arrive over TLS".
+//! A message that breaks its policy is untrusted: its attachments and remote
+//! content are blocked and its links are flagged. A policy can also block
+//! attachments and remote content outright, or ask for a warning before
+//! links to domains other than the sender's are opened.
+
+use crate::db::{Database, DbError, DbResult};
+use crate::mail::DeliveryAuth;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Stored policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct DomainPolicy {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    /// Sender address or domain (covers subdomains)
+    pub pattern: String,
+    pub require_dkim: bool,
+    pub require_tls: bool,
+    pub block_attachments: bool,
+    pub block_remote_content: bool,
+    pub warn_links: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Policy as entered by the user
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase", default)]
+pub struct NewDomainPolicy {
+    pub pattern: String,
+    pub require_dkim: bool,
+    pub require_tls: bool,
+    pub block_attachments: bool,
+    pub block_remote_content: bool,
+    pub warn_links: bool,
+}
+
+/// Requirement a message did not meet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyViolation {
+    DkimNotVerified,
+    NotDeliveredOverTls,
+}
+
+/// Policy applied to one message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyVerdict {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub policy_id: i64,
+    pub pattern: String,
+    pub violations: Vec<PolicyViolation>,
+    pub block_attachments: bool,
+    pub block_remote_content: bool,
+    pub warn_links: bool,
+}
+
+/// Whether a link needs a warning before it is opened
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCheck {
+    pub host: Option<String>,
+    pub warn: bool,
+}
+
+/// Lowercase address or domain; None when it is neither
+fn normalize_pattern(pattern: &str) -> Option<String> {
+    let pattern = pattern.trim().trim_start_matches("*.").trim_start_matches('@').to_lowercase();
+    let domain = match pattern.split_once('@') {
+        Some((local, domain)) if !local.is_empty() => domain,
+        Some(_) => return None,
+        None => pattern.as_str(),
+    };
+    let valid = domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !domain.contains('@')
+        && !pattern.chars().any(char::is_whitespace);
+    valid.then_some(pattern)
+}
+
+/// Patterns that cover `address`, most specific first
+fn candidates(address: &str) -> Vec<String> {
+    let address = address.trim().to_lowercase();
+    let Some((_, domain)) = address.rsplit_once('@') else {
+        return Vec::new();
+    };
+    let mut candidates = vec![address.clone()];
+    let mut domain = domain;
+    while domain.contains('.') {
+        candidates.push(domain.to_string());
+        domain = domain.split_once('.').map(|(_, parent)| parent).unwrap_or_default();
+    }
+    candidates
+}
+
+fn domain_of(address: &str) -> Option<String> {
+    address.trim().to_lowercase().rsplit_once('@').map(|(_, domain)| domain.to_string())
+}
+
+/// Same host or a subdomain of `domain`
+fn host_within(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+const SELECT: &str = "SELECT id, pattern, require_dkim, require_tls, block_attachments, block_remote_content, warn_links,
+            created_at, updated_at
+     FROM domain_policies";
+
+fn from_row(row: &rusqlite::Row) -> rusqlite::Result<DomainPolicy> {
+    Ok(DomainPolicy {
+        id: row.get(0)?,
+        pattern: row.get(1)?,
+        require_dkim: row.get(2)?,
+        require_tls: row.get(3)?,
+        block_attachments: row.get(4)?,
+        block_remote_content: row.get(5)?,
+        warn_links: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+pub fn list(db: &Database) -> DbResult<Vec<DomainPolicy>> {
+    db.query(&format!("{} ORDER BY pattern", SELECT), [], from_row)
+}
+
+/// Create a policy, or replace the rules of the one with the same pattern
+pub fn save(db: &Database, policy: &NewDomainPolicy) -> DbResult<DomainPolicy> {
+    let pattern = normalize_pattern(&policy.pattern)
+        .ok_or_else(|| DbError::Constraint(format!("'{}' is not an address or domain", policy.pattern.trim())))?;
+    db.execute(
+        "INSERT INTO domain_policies (pattern, require_dkim, require_tls, block_attachments, block_remote_content, warn_links)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(pattern) DO UPDATE SET
+             require_dkim = excluded.require_dkim,
+             require_tls = excluded.require_tls,
+             block_attachments = excluded.block_attachments,
+             block_remote_content = excluded.block_remote_content,
+             warn_links = excluded.warn_links,
+             updated_at = datetime('now')",
+        params![
+            pattern,
+            policy.require_dkim,
+            policy.require_tls,
+            policy.block_attachments,
+            policy.block_remote_content,
+            policy.warn_links
+        ],
+    )?;
+    db.query_row(&format!("{} WHERE pattern = ?1", SELECT), [&pattern], from_row)
+}
+
+/// Returns false if there was no such policy
+pub fn delete(db: &Database, id: i64) -> DbResult<bool> {
+    Ok(db.execute("DELETE FROM domain_policies WHERE id = ?1", [id])? > 0)
+}
+
+/// Most specific policy covering a sender address
+pub fn for_sender(db: &Database, address: &str) -> DbResult<Option<DomainPolicy>> {
+    let candidates = candidates(address);
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+    let placeholders = vec!["?"; candidates.len()].join(", ");
+    let policies = db.query(
+        &format!("{} WHERE pattern IN ({})", SELECT, placeholders),
+        rusqlite::params_from_iter(&candidates),
+        from_row,
+    )?;
+    Ok(candidates
+        .iter()
+        .find_map(|candidate| policies.iter().find(|policy| &policy.pattern == candidate))
+        .cloned())
+}
+
+/// Apply a policy to a received message
+pub fn evaluate(policy: &DomainPolicy, delivery: &DeliveryAuth) -> PolicyVerdict {
+    let mut violations = Vec::new();
+    if policy.require_dkim && delivery.dkim != Some(true) {
+        violations.push(PolicyViolation::DkimNotVerified);
+    }
+    if policy.require_tls && delivery.tls != Some(true) {
+        violations.push(PolicyViolation::NotDeliveredOverTls);
+    }
+    let untrusted = !violations.is_empty();
+    PolicyVerdict {
+        policy_id: policy.id,
+        pattern: policy.pattern.clone(),
+        violations,
+        block_attachments: policy.block_attachments || untrusted,
+        block_remote_content: policy.block_remote_content || untrusted,
+        warn_links: policy.warn_links || untrusted,
+    }
+}
+
+/// Verdict for a message from `sender`, if a policy covers it
+pub fn verdict(db: &Database, sender: &str, delivery: &DeliveryAuth) -> DbResult<Option<PolicyVerdict>> {
+    Ok(for_sender(db, sender)?.map(|policy| evaluate(&policy, delivery)))
+}
+
+/// Whether opening `url` from a message of `sender` needs a warning
+/// Links to the sender's own domain, or to a domain with a policy of its
+/// own, are known; anything else is warned about when the policy asks for it.
+pub fn check_link(db: &Database, sender: &str, url: &str) -> DbResult<LinkCheck> {
+    let host = url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.trim_end_matches('.').to_lowercase()));
+    let warn_links = for_sender(db, sender)?.is_some_and(|policy| policy.warn_links);
+    let Some(host) = host else {
+        // Not a web link with a host; nothing to compare against
+        return Ok(LinkCheck { host: None, warn: warn_links });
+    };
+    if !warn_links {
+        return Ok(LinkCheck { host: Some(host), warn: false });
+    }
+
+    let known = domain_of(sender).is_some_and(|domain| host_within(&host, &domain))
+        || list(db)?.iter().any(|policy| !policy.pattern.contains('@') && host_within(&host, &policy.pattern));
+    Ok(LinkCheck { host: Some(host), warn: !known })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_specific_policy_wins() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        save(&db, &NewDomainPolicy { pattern: "Bank.test".to_string(), warn_links: true, ..Default::default() }).unwrap();
+        let finance = save(
+            &db,
+            &NewDomainPolicy { pattern: "finance@bank.test".to_string(), require_dkim: true, ..Default::default() },
+        )
+        .unwrap();
+        assert!(save(&db, &NewDomainPolicy { pattern: "not a domain".to_string(), ..Default::default() }).is_err());
+        assert_eq!(list(&db).unwrap().len(), 2);
+
+        assert_eq!(for_sender(&db, "Finance@Bank.test").unwrap().unwrap().id, finance.id);
+        assert_eq!(for_sender(&db, "news@mail.bank.test").unwrap().unwrap().pattern, "bank.test");
+        assert!(for_sender(&db, "someone@other.test").unwrap().is_none());
+
+        // Missing or failed DKIM makes the message untrusted
+        let verdict = evaluate(&finance, &DeliveryAuth { dkim: None, tls: Some(true) });
+        assert_eq!(verdict.violations, vec![PolicyViolation::DkimNotVerified]);
+        assert!(verdict.block_attachments && verdict.block_remote_content);
+        let verdict = evaluate(&finance, &DeliveryAuth { dkim: Some(true), tls: None });
+        assert!(verdict.violations.is_empty() && !verdict.block_attachments);
+
+        assert!(delete(&db, finance.id).unwrap());
+        assert!(!delete(&db, finance.id).unwrap());
+    }
+
+    #[test]
+    fn test_link_warnings() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        save(&db, &NewDomainPolicy { pattern: "bank.test".to_string(), warn_links: true, ..Default::default() }).unwrap();
+        save(&db, &NewDomainPolicy { pattern: "payments.test".to_string(), ..Default::default() }).unwrap();
+
+        let sender = "alerts@bank.test";
+        assert!(!check_link(&db, sender, "https://login.bank.test/reset").unwrap().warn);
+        assert!(!check_link(&db, sender, "https://payments.test/pay").unwrap().warn);
+        let phish = check_link(&db, sender, "https://bank.test.evil.example/login").unwrap();
+        assert_eq!((phish.host.as_deref(), phish.warn), (Some("bank.test.evil.example"), true));
+        // Senders without a link policy are not warned about
+        assert!(!check_link(&db, "friend@other.test", "https://evil.example").unwrap().warn);
+    }
+}
@@ -0,0 +1,316 @@
+//! Event Extraction
+//!
+//! Finds dates and times in what the sender wrote ("Can we meet Friday at
+//! 3pm?", "The workshop is on March 14, 10:00-12:30") and proposes events the
+//! user can confirm before they go to a calendar or an ICS file. Extraction is
+//! rule based and runs locally: at most one event per sentence, a date must be
+//! present (a time alone is too ambiguous), and relative dates are resolved
+//! against the message's own date in the sender's timezone.
+
+use crate::db::{Database, DbResult};
+use crate::mail::precheck::own_text;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use regex_lite::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Length of a timed event without an end time
+const DEFAULT_DURATION_MINUTES: i64 = 60;
+
+/// Longest excerpt kept as the event's source text
+const MAX_SOURCE_CHARS: usize = 200;
+
+const MONTH: &str =
+    "(january|february|march|april|may|june|july|august|september|october|november|december|jan|feb|mar|apr|jun|jul|aug|sept|sep|oct|nov|dec)";
+
+const WEEKDAY: &str = "(monday|tuesday|wednesday|thursday|friday|saturday|sunday)";
+
+/// Event proposed from a message, for the user to confirm
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct ProposedEvent {
+    pub title: String,
+    /// RFC 3339 in the sender's offset, or `YYYY-MM-DD` for all-day events
+    pub start: String,
+    /// Exclusive end: RFC 3339, or the day after the last day for all-day events
+    pub end: String,
+    pub all_day: bool,
+    /// Sentence the event was found in
+    pub source: String,
+}
+
+/// Compiled patterns (built once per extraction)
+struct Patterns {
+    iso: Regex,
+    numeric: Regex,
+    month_day: Regex,
+    day_month: Regex,
+    relative: Regex,
+    weekday: Regex,
+    range_12h: Regex,
+    range_24h: Regex,
+    time_12h: Regex,
+    time_24h: Regex,
+}
+
+impl Patterns {
+    fn new() -> Self {
+        let re = |pattern: &str| Regex::new(&format!("(?i){}", pattern)).expect("valid event pattern");
+        Self {
+            iso: re(r"\b(\d{4})-(\d{2})-(\d{2})\b"),
+            numeric: re(r"\b(\d{1,2})([./])(\d{1,2})[./](\d{4}|\d{2})\b"),
+            month_day: re(&format!(r"\b{}\.?\s+(\d{{1,2}})(?:st|nd|rd|th)?\b(?:,?\s+(\d{{4}}))?", MONTH)),
+            day_month: re(&format!(r"\b(\d{{1,2}})(?:st|nd|rd|th)?\s+(?:of\s+)?{}\b\.?(?:,?\s+(\d{{4}}))?", MONTH)),
+            relative: re(r"\b(today|tonight|tomorrow)\b"),
+            weekday: re(&format!(r"\b(?:(next|this|on)\s+)?{}\b", WEEKDAY)),
+            range_12h: re(
+                r"\b(\d{1,2})(?:[:.](\d{2}))?\s*(am|pm)?\s*(?:-|–|to|until|till)\s*(\d{1,2})(?:[:.](\d{2}))?\s*(am|pm)\b",
+            ),
+            range_24h: re(r"\b(\d{1,2}):(\d{2})\s*(?:-|–|to|until|till)\s*(\d{1,2}):(\d{2})\b"),
+            time_12h: re(r"\b(\d{1,2})(?:[:.](\d{2}))?\s*(am|pm)\b"),
+            time_24h: re(r"\b(\d{1,2}):(\d{2})\b"),
+        }
+    }
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let prefix: String = name.to_lowercase().chars().take(3).collect();
+    ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"]
+        .iter()
+        .position(|month| *month == prefix)
+        .map(|index| index as u32 + 1)
+}
+
+fn weekday_of(name: &str) -> Option<Weekday> {
+    name.to_lowercase().parse().ok()
+}
+
+/// Date with a year when given, otherwise the next occurrence on or after `today`
+fn date_in_year(today: NaiveDate, year: Option<i32>, month: u32, day: u32) -> Option<NaiveDate> {
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(if year < 100 { 2000 + year } else { year }, month, day),
+        None => {
+            let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+            if date < today {
+                NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+            } else {
+                Some(date)
+            }
+        }
+    }
+}
+
+/// First date mentioned in a sentence, with the byte range of its mention
+fn find_date(patterns: &Patterns, sentence: &str, today: NaiveDate) -> Option<(NaiveDate, std::ops::Range<usize>)> {
+    let number = |caps: &regex_lite::Captures<'_>, i: usize| caps.get(i).and_then(|m| m.as_str().parse::<i64>().ok());
+    let mut found: Vec<(NaiveDate, std::ops::Range<usize>)> = Vec::new();
+
+    for caps in patterns.iso.captures_iter(sentence) {
+        if let (Some(y), Some(m), Some(d)) = (number(&caps, 1), number(&caps, 2), number(&caps, 3)) {
+            if let Some(date) = NaiveDate::from_ymd_opt(y as i32, m as u32, d as u32) {
+                found.push((date, caps.get(0)?.range()));
+            }
+        }
+    }
+    for caps in patterns.numeric.captures_iter(sentence) {
+        let (Some(a), Some(b), Some(y)) = (number(&caps, 1), number(&caps, 3), number(&caps, 4)) else {
+            continue;
+        };
+        // Dots are day-first; slashes are month-first unless that cannot be a month
+        let day_first = &caps[2] == "." || a > 12;
+        let (month, day) = if day_first { (b, a) } else { (a, b) };
+        if let Some(date) = date_in_year(today, Some(y as i32), month as u32, day as u32) {
+            found.push((date, caps.get(0)?.range()));
+        }
+    }
+    for (pattern, month_index, day_index) in [(&patterns.month_day, 1, 2), (&patterns.day_month, 2, 1)] {
+        for caps in pattern.captures_iter(sentence) {
+            let (Some(month), Some(day)) = (month_number(&caps[month_index]), number(&caps, day_index)) else {
+                continue;
+            };
+            if let Some(date) = date_in_year(today, number(&caps, 3).map(|y| y as i32), month, day as u32) {
+                found.push((date, caps.get(0)?.range()));
+            }
+        }
+    }
+    if let Some(caps) = patterns.relative.captures(sentence) {
+        let offset = if caps[1].eq_ignore_ascii_case("tomorrow") { 1 } else { 0 };
+        found.push((today + Duration::days(offset), caps.get(0)?.range()));
+    }
+    if let Some(caps) = patterns.weekday.captures(sentence) {
+        if let Some(weekday) = weekday_of(&caps[2]) {
+            let this = caps.get(1).is_some_and(|m| m.as_str().eq_ignore_ascii_case("this"));
+            let mut days = (weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64).rem_euclid(7);
+            if days == 0 && !this {
+                days = 7;
+            }
+            found.push((today + Duration::days(days), caps.get(0)?.range()));
+        }
+    }
+
+    found.into_iter().min_by_key(|(_, range)| range.start)
+}
+
+fn time_of(hour: i64, minute: i64, meridiem: Option<&str>) -> Option<NaiveTime> {
+    let hour = match meridiem.map(str::to_lowercase).as_deref() {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(u32::try_from(hour).ok()?, u32::try_from(minute).ok()?, 0)
+}
+
+/// Start and optional end time mentioned in a sentence, outside the date's mention
+fn find_times(patterns: &Patterns, sentence: &str, date_range: &std::ops::Range<usize>) -> Option<(NaiveTime, Option<NaiveTime>)> {
+    let outside = |range: std::ops::Range<usize>| range.end <= date_range.start || range.start >= date_range.end;
+    let number = |caps: &regex_lite::Captures<'_>, i: usize| caps.get(i).map_or(Some(0), |m| m.as_str().parse::<i64>().ok());
+
+    for caps in patterns.range_12h.captures_iter(sentence) {
+        if !outside(caps.get(0)?.range()) {
+            continue;
+        }
+        let end_meridiem = caps.get(6).map(|m| m.as_str());
+        let start_meridiem = caps.get(3).map(|m| m.as_str()).or(end_meridiem);
+        if let (Some(start), Some(end)) = (
+            time_of(number(&caps, 1)?, number(&caps, 2)?, start_meridiem),
+            time_of(number(&caps, 4)?, number(&caps, 5)?, end_meridiem),
+        ) {
+            return Some((start, Some(end)));
+        }
+    }
+    for caps in patterns.range_24h.captures_iter(sentence) {
+        if !outside(caps.get(0)?.range()) {
+            continue;
+        }
+        if let (Some(start), Some(end)) = (
+            time_of(number(&caps, 1)?, number(&caps, 2)?, None),
+            time_of(number(&caps, 3)?, number(&caps, 4)?, None),
+        ) {
+            return Some((start, Some(end)));
+        }
+    }
+    for caps in patterns.time_12h.captures_iter(sentence) {
+        if outside(caps.get(0)?.range()) {
+            if let Some(start) = time_of(number(&caps, 1)?, number(&caps, 2)?, caps.get(3).map(|m| m.as_str())) {
+                return Some((start, None));
+            }
+        }
+    }
+    for caps in patterns.time_24h.captures_iter(sentence) {
+        if outside(caps.get(0)?.range()) {
+            if let Some(start) = time_of(number(&caps, 1)?, number(&caps, 2)?, None) {
+                return Some((start, None));
+            }
+        }
+    }
+    None
+}
+
+/// Subject without reply and forward prefixes
+fn title_of(subject: &str) -> String {
+    let mut title = subject.trim();
+    loop {
+        let lower = title.to_lowercase();
+        match ["re:", "fw:", "fwd:", "aw:", "ynt:", "ilt:"].iter().find(|prefix| lower.starts_with(*prefix)) {
+            Some(prefix) => title = title[prefix.len()..].trim_start(),
+            None => break,
+        }
+    }
+    if title.is_empty() {
+        "Event".to_string()
+    } else {
+        title.to_string()
+    }
+}
+
+/// Events proposed from a text, resolving relative dates against `sent`
+pub fn extract(subject: &str, text: &str, sent: DateTime<FixedOffset>) -> Vec<ProposedEvent> {
+    let patterns = Patterns::new();
+    let today = sent.date_naive();
+    let title = title_of(subject);
+    let mut events: Vec<ProposedEvent> = Vec::new();
+
+    let sentences = text.lines().flat_map(|line| line.split_inclusive(['!', '?']).flat_map(|part| part.split_inclusive(". ")));
+    for sentence in sentences.map(str::trim).filter(|sentence| !sentence.is_empty()) {
+        let Some((date, date_range)) = find_date(&patterns, sentence, today) else {
+            continue;
+        };
+
+        let event = match find_times(&patterns, sentence, &date_range) {
+            Some((start_time, end_time)) => {
+                let Some(start) = sent.timezone().from_local_datetime(&date.and_time(start_time)).single() else {
+                    continue;
+                };
+                let end = match end_time {
+                    Some(end_time) if end_time > start_time => date.and_time(end_time),
+                    _ => date.and_time(start_time) + Duration::minutes(DEFAULT_DURATION_MINUTES),
+                };
+                let Some(end) = sent.timezone().from_local_datetime(&end).single() else {
+                    continue;
+                };
+                ProposedEvent {
+                    title: title.clone(),
+                    start: start.to_rfc3339(),
+                    end: end.to_rfc3339(),
+                    all_day: false,
+                    source: String::new(),
+                }
+            }
+            None => ProposedEvent {
+                title: title.clone(),
+                start: date.to_string(),
+                end: (date + Duration::days(1)).to_string(),
+                all_day: true,
+                source: String::new(),
+            },
+        };
+        if events.iter().any(|existing| existing.start == event.start) {
+            continue;
+        }
+        events.push(ProposedEvent { source: sentence.chars().take(MAX_SOURCE_CHARS).collect(), ..event });
+    }
+    events
+}
+
+/// Events proposed from what the sender of a stored message wrote
+pub fn for_email(db: &Database, email_id: i64) -> DbResult<Vec<ProposedEvent>> {
+    let email = db.get_email(email_id)?;
+    let sent = DateTime::parse_from_rfc3339(&email.date)
+        .or_else(|_| DateTime::parse_from_rfc2822(&email.date))
+        .unwrap_or_else(|_| Utc::now().fixed_offset());
+    let text = own_text(email.body_text.as_deref(), email.body_html.as_deref());
+    let text = if text.trim().is_empty() { email.preview.clone() } else { text };
+    Ok(extract(&email.subject, &text, sent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_dates_and_times() {
+        // Wednesday
+        let sent = DateTime::parse_from_rfc3339("2024-03-06T09:00:00+03:00").unwrap();
+        let text = "Hi Ana,\n\
+                    Can we meet Friday at 3pm? Otherwise next Monday 10:00-11:30 works.\n\
+                    The conference runs on March 14th, 2024.\n\
+                    Deadline is 15.04.2024 at 17:00. Thanks for the 2 files.";
+        let events = extract("Re: Planning", text, sent);
+
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0].title, "Planning");
+        assert_eq!(events[0].start, "2024-03-08T15:00:00+03:00");
+        assert_eq!(events[0].end, "2024-03-08T16:00:00+03:00");
+        assert_eq!(events[1].start, "2024-03-11T10:00:00+03:00");
+        assert_eq!(events[1].end, "2024-03-11T11:30:00+03:00");
+        assert!(events[2].all_day);
+        assert_eq!((events[2].start.as_str(), events[2].end.as_str()), ("2024-03-14", "2024-03-15"));
+        assert_eq!(events[3].start, "2024-04-15T17:00:00+03:00");
+
+        assert!(extract("Lunch", "See you at 1pm", sent).is_empty());
+        let tomorrow = extract("Call", "Tomorrow 9am-10am?", sent);
+        assert_eq!(tomorrow[0].start, "2024-03-07T09:00:00+03:00");
+        assert_eq!(tomorrow[0].end, "2024-03-07T10:00:00+03:00");
+    }
+}
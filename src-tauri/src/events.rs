@@ -0,0 +1,286 @@
+//! Store Change Events
+//!
+//! Every mutation of cached emails (read, star, move, delete, label) is applied
+//! to SQLite and announced on an in-process broadcast bus. The bus is forwarded
+//! to the frontend as a `store://emails-changed` Tauri event so all open views
+//! refresh from the same source of truth instead of drifting apart.
+//!
+//! This module is also the catalog of every Tauri event the backend sends:
+//! each payload type is bound to its event name through [`AppEvent`], and all
+//! emitters go through [`emit`], so a payload can only be sent under its own
+//! name and the frontend contract is listed in one place.
+
+use crate::db::{Database, DbResult};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::sync::broadcast;
+
+/// Tauri event name for email store changes
+pub const EMAILS_CHANGED_EVENT: &str = "store://emails-changed";
+
+/// Tauri event name for optimistic operations that failed and were rolled back
+pub const PENDING_OP_FAILED_EVENT: &str = "store://pending-op-failed";
+
+/// Buffered events before slow subscribers start lagging
+const EVENT_BUS_CAPACITY: usize = 256;
+
+/// A payload sent to the frontend under a fixed event name
+pub trait AppEvent: Serialize {
+    const NAME: &'static str;
+}
+
+/// Bind payload types to their event names and list the names
+macro_rules! app_events {
+    ($($payload:ty => $name:expr),* $(,)?) => {
+        $(impl AppEvent for $payload {
+            const NAME: &'static str = $name;
+        })*
+
+        /// Names of all events sent to the frontend
+        pub const EVENT_NAMES: &[&str] = &[$($name),*];
+    };
+}
+
+app_events! {
+    EmailsChanged => EMAILS_CHANGED_EVENT,
+    PendingOpFailed => PENDING_OP_FAILED_EVENT,
+    crate::settings::SettingsChanged => crate::settings::SETTINGS_CHANGED_EVENT,
+    crate::connectivity::ConnectivityStatus => crate::connectivity::CONNECTIVITY_CHANGED_EVENT,
+    crate::backfill::BackfillState => crate::backfill::BACKFILL_PROGRESS_EVENT,
+    crate::body_backfill::BodyBackfillProgress => crate::body_backfill::BODY_PROGRESS_EVENT,
+    crate::uid_validity::ResyncReport => crate::uid_validity::RESYNC_REPORT_EVENT,
+    crate::folder_watch::FolderChanged => crate::folder_watch::FOLDER_CHANGED_EVENT,
+    crate::refresh_schedule::RefreshDue => crate::refresh_schedule::REFRESH_DUE_EVENT,
+    crate::account_migration::MigrationState => crate::account_migration::MIGRATION_PROGRESS_EVENT,
+    crate::mailmerge::MergeProgress => crate::mailmerge::MAILMERGE_PROGRESS_EVENT,
+    crate::ai::CompletionChunk => crate::ai::COMPLETION_EVENT,
+    Vec<crate::feeds::FeedPollResult> => crate::feeds::FEEDS_UPDATED_EVENT,
+    Vec<crate::digest::DigestResult> => crate::digest::DIGEST_CREATED_EVENT,
+    Vec<crate::reply_needed::ReplyReminder> => crate::reply_needed::REMINDER_EVENT,
+    Vec<crate::search_index::IndexStatus> => crate::search_index::REPAIRED_EVENT,
+    crate::credentials::AuthState => crate::credentials::NEEDS_ATTENTION_EVENT,
+    crate::db::recovery::RecoveryReport => crate::db::recovery::RECOVERY_EVENT,
+    crate::deep_links::LinksPending => crate::deep_links::OPEN_EVENT,
+    crate::hotkeys::ComposeRequested => crate::hotkeys::COMPOSE_EVENT,
+    crate::startup::StartupProgress => crate::startup::PROGRESS_EVENT,
+    crate::startup::StartupInbox => crate::startup::INBOX_EVENT,
+}
+
+/// Send an event to the frontend; failures are logged, never fatal
+pub fn emit<R: Runtime, E: AppEvent>(emitter: &impl Emitter<R>, payload: &E) {
+    if let Err(e) = emitter.emit(E::NAME, payload) {
+        log::warn!("Failed to emit {}: {}", E::NAME, e);
+    }
+}
+
+/// Kind of change applied to a set of emails
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum EmailChange {
+    Read { value: bool },
+    Starred { value: bool },
+    /// Moved out of the folder; `target` is the destination folder name
+    Moved { target: String },
+    Deleted { permanent: bool },
+    Labels { labels: Vec<String> },
+    /// An optimistic move/delete was rolled back; the emails are visible again
+    Restored,
+    /// Re-delivered with altered content; `versions` distinct contents are kept
+    ContentChanged { versions: usize },
+}
+
+/// Payload of the `store://emails-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailsChanged {
+    pub account_id: i64,
+    /// Source folder (remote name) when known
+    pub folder: Option<String>,
+    /// Local database ids of affected emails
+    pub email_ids: Vec<i64>,
+    /// IMAP UIDs of affected emails (in `folder`)
+    pub uids: Vec<u32>,
+    pub change: EmailChange,
+}
+
+/// Payload of the `store://pending-op-failed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingOpFailed {
+    pub op_id: String,
+    pub account_id: i64,
+    pub email_ids: Vec<i64>,
+    pub error: String,
+}
+
+/// Event carried on the store bus
+#[derive(Debug, Clone)]
+pub enum StoreEvent {
+    EmailsChanged(EmailsChanged),
+    PendingOpFailed(PendingOpFailed),
+}
+
+/// Broadcast bus for store changes (cheap to clone)
+#[derive(Clone)]
+pub struct StoreEvents {
+    sender: broadcast::Sender<StoreEvent>,
+}
+
+impl Default for StoreEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StoreEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    /// Subscribe to store changes
+    pub fn subscribe(&self) -> broadcast::Receiver<StoreEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish a change (no-op when nothing was affected)
+    pub fn notify(&self, event: EmailsChanged) {
+        if event.email_ids.is_empty() && event.uids.is_empty() {
+            return;
+        }
+        // Sending only fails when there are no subscribers, which is fine
+        let _ = self.sender.send(StoreEvent::EmailsChanged(event));
+    }
+
+    /// Publish the failure of an optimistic operation
+    pub fn notify_failure(&self, event: PendingOpFailed) {
+        let _ = self.sender.send(StoreEvent::PendingOpFailed(event));
+    }
+}
+
+/// Forward store events to the frontend as Tauri events
+pub fn forward_to_frontend<R: Runtime>(app: AppHandle<R>, events: &StoreEvents) {
+    let mut receiver = events.subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(StoreEvent::EmailsChanged(payload)) => emit(&app, &payload),
+                Ok(StoreEvent::PendingOpFailed(payload)) => emit(&app, &payload),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Store event forwarder lagged, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Apply a change to the cached emails identified by (account, folder, uids)
+/// Returns the ids of affected emails (captured before rows are removed)
+pub fn apply_email_change(
+    db: &Database,
+    account_id: i64,
+    folder: &str,
+    uids: &[u32],
+    change: &EmailChange,
+) -> DbResult<Vec<i64>> {
+    let email_ids = db.find_email_ids_by_uids(account_id, folder, uids)?;
+
+    match change {
+        EmailChange::Read { value } => {
+            for id in &email_ids {
+                db.update_email_flags(*id, Some(*value), None, None)?;
+            }
+        }
+        EmailChange::Starred { value } => {
+            for id in &email_ids {
+                db.update_email_flags(*id, None, Some(*value), None)?;
+            }
+        }
+        // The server assigns a new UID in the target folder; the next sync of
+        // that folder brings the message back, so the stale source row goes away
+        EmailChange::Moved { .. } | EmailChange::Deleted { .. } => {
+            db.delete_emails(&email_ids)?;
+        }
+        EmailChange::Restored => {
+            for id in &email_ids {
+                db.update_email_flags(*id, None, None, Some(false))?;
+            }
+        }
+        // The versions live in `message_versions`; the cached row is unchanged
+        EmailChange::ContentChanged { .. } => {}
+        EmailChange::Labels { labels } => {
+            let labels_json = serde_json::to_string(labels)
+                .map_err(|e| crate::db::DbError::Serialization(e.to_string()))?;
+            for id in &email_ids {
+                db.execute(
+                    "UPDATE emails SET labels = ?1 WHERE id = ?2",
+                    rusqlite::params![labels_json, id],
+                )?;
+            }
+        }
+    }
+
+    Ok(email_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_payload_shape() {
+        let event = EmailsChanged {
+            account_id: 1,
+            folder: Some("INBOX".to_string()),
+            email_ids: vec![10, 11],
+            uids: vec![100, 101],
+            change: EmailChange::Moved { target: "Archive".to_string() },
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["accountId"], 1);
+        assert_eq!(json["emailIds"], serde_json::json!([10, 11]));
+        assert_eq!(json["change"]["type"], "moved");
+        assert_eq!(json["change"]["target"], "Archive");
+    }
+
+    #[test]
+    fn test_event_names_are_unique() {
+        let mut names = EVENT_NAMES.to_vec();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), EVENT_NAMES.len());
+        assert!(EVENT_NAMES.contains(&<EmailsChanged as AppEvent>::NAME));
+        // Signals without data still send `null`, as the listeners expect
+        assert_eq!(serde_json::to_value(crate::hotkeys::ComposeRequested).unwrap(), serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_notify_skips_empty_changes() {
+        let events = StoreEvents::new();
+        let mut receiver = events.subscribe();
+
+        events.notify(EmailsChanged {
+            account_id: 1,
+            folder: None,
+            email_ids: vec![],
+            uids: vec![],
+            change: EmailChange::Read { value: true },
+        });
+        events.notify(EmailsChanged {
+            account_id: 1,
+            folder: None,
+            email_ids: vec![5],
+            uids: vec![],
+            change: EmailChange::Starred { value: true },
+        });
+
+        match receiver.recv().await.unwrap() {
+            StoreEvent::EmailsChanged(received) => assert_eq!(received.email_ids, vec![5]),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        assert!(receiver.try_recv().is_err());
+    }
+}
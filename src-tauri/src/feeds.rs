@@ -0,0 +1,822 @@
+//! RSS/Atom Feed Subscriptions
+//!
+//! Feeds are polled in the background and every new entry is stored as a
+//! message in a local folder of the subscribing account (default "Feeds"), so
+//! feed items show up next to mail and go through the same filters, search and
+//! mbox export. Polls use conditional GET (ETag / Last-Modified), and every
+//! entry seen is remembered so items the user deleted are not fetched again.
+
+use crate::db::{Database, DbResult};
+use crate::filters::FilterEngine;
+use crate::mail::compose::escape_html;
+use crate::plugins;
+use chrono::{DateTime, FixedOffset, Utc};
+use lettre::message::{Mailbox, MessageBuilder};
+use lettre::{Address, Message};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Tauri event name emitted after a background poll stored new items or failed
+pub const FEEDS_UPDATED_EVENT: &str = "feeds://updated";
+
+/// Local folder used when no folder name is given
+pub const DEFAULT_FOLDER: &str = "Feeds";
+
+/// Poll interval bounds (minutes)
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 60;
+const MIN_INTERVAL_MINUTES: u32 = 5;
+const MAX_INTERVAL_MINUTES: u32 = 1440;
+
+/// How often the poller looks for feeds that are due
+const POLL_TICK: Duration = Duration::from_secs(60);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Larger documents are rejected rather than parsed
+const MAX_FEED_BYTES: usize = 5 * 1024 * 1024;
+
+/// Entries stored per poll (the newest ones, in document order)
+const MAX_ENTRIES_PER_POLL: usize = 100;
+
+/// Feed subscription
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct Feed {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    /// Local folder receiving the entries
+    #[cfg_attr(test, ts(type = "number"))]
+    pub folder_id: i64,
+    pub url: String,
+    /// Feed title (taken from the document on the first successful poll)
+    pub title: String,
+    pub interval_minutes: u32,
+    pub enabled: bool,
+    pub last_polled_at: Option<String>,
+    pub last_error: Option<String>,
+    #[serde(skip)]
+    pub etag: Option<String>,
+    #[serde(skip)]
+    pub last_modified: Option<String>,
+}
+
+impl Feed {
+    /// Whether the feed should be polled at `now`
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let last = self
+            .last_polled_at
+            .as_deref()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok());
+        match last {
+            Some(last) => now >= last + chrono::Duration::minutes(i64::from(self.interval_minutes)),
+            None => true,
+        }
+    }
+}
+
+/// Outcome of polling one feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct FeedPollResult {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub feed_id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    /// Ids of the messages created for new entries
+    #[cfg_attr(test, ts(type = "Array<number>"))]
+    pub new_email_ids: Vec<i64>,
+    pub error: Option<String>,
+}
+
+/// Parsed RSS or Atom document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedDocument {
+    pub title: String,
+    pub link: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+/// RSS item / Atom entry
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedEntry {
+    /// RSS guid or Atom id; falls back to the link, then the title
+    pub guid: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub author: Option<String>,
+    pub published: Option<DateTime<FixedOffset>>,
+    /// Short description (HTML)
+    pub summary: Option<String>,
+    /// Full content (HTML), when the feed provides it
+    pub content: Option<String>,
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse an RSS 2.0, RSS 1.0 (RDF) or Atom document
+pub fn parse_feed(xml: &str) -> Result<FeedDocument, String> {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    let mut reader = Reader::from_str(xml);
+    reader.check_end_names(false);
+
+    let mut document = FeedDocument::default();
+    let mut entry: Option<FeedEntry> = None;
+    let mut is_feed = false;
+    // Open elements with the text length at their start
+    let mut open: Vec<(String, usize)> = Vec::new();
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = local_name(e.local_name().as_ref());
+                if open.is_empty() {
+                    is_feed = matches!(name.as_str(), "rss" | "rdf" | "feed");
+                }
+                match name.as_str() {
+                    "item" | "entry" => entry = Some(FeedEntry::default()),
+                    "link" => apply_link(&e, entry.as_mut(), &mut document),
+                    _ => {}
+                }
+                open.push((name, text.len()));
+            }
+            Ok(Event::Empty(e)) if local_name(e.local_name().as_ref()) == "link" => {
+                apply_link(&e, entry.as_mut(), &mut document);
+            }
+            Ok(Event::Text(e)) => {
+                let value = e
+                    .unescape()
+                    .map(|value| value.into_owned())
+                    .unwrap_or_else(|_| String::from_utf8_lossy(&e).into_owned());
+                text.push_str(&value);
+            }
+            Ok(Event::CData(e)) => text.push_str(&String::from_utf8_lossy(&e.into_inner())),
+            Ok(Event::End(_)) => {
+                let Some((name, start)) = open.pop() else { continue };
+                let parent = open.last().map(|(parent, _)| parent.as_str()).unwrap_or_default();
+                let value = text[start.min(text.len())..].trim().to_string();
+
+                // Unknown elements leave their text to the parent (e.g. XHTML content)
+                let handled = match entry.as_mut() {
+                    Some(current) if matches!(name.as_str(), "item" | "entry") => {
+                        let mut finished = std::mem::take(current);
+                        entry = None;
+                        if finished.guid.is_empty() {
+                            finished.guid = finished.link.clone().unwrap_or_else(|| finished.title.clone());
+                        }
+                        if !finished.guid.is_empty() {
+                            document.entries.push(finished);
+                        }
+                        true
+                    }
+                    Some(current) if parent == "author" => {
+                        if name == "name" && !value.is_empty() {
+                            current.author = Some(value);
+                        }
+                        true
+                    }
+                    Some(current) if matches!(parent, "item" | "entry") => {
+                        apply_entry_field(current, &name, value);
+                        true
+                    }
+                    Some(_) => false,
+                    None if matches!(parent, "channel" | "feed") => {
+                        match name.as_str() {
+                            "title" => document.title = value,
+                            "link" if !value.is_empty() && document.link.is_none() => document.link = Some(value),
+                            _ => {}
+                        }
+                        true
+                    }
+                    None => true,
+                };
+                if handled {
+                    text.truncate(start.min(text.len()));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid feed XML at {}: {}", reader.buffer_position(), e)),
+            _ => {}
+        }
+    }
+
+    if !is_feed {
+        return Err("Not an RSS or Atom feed".to_string());
+    }
+    Ok(document)
+}
+
+fn local_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).to_ascii_lowercase()
+}
+
+/// Atom `<link href rel>`; only alternate links point at the article
+fn apply_link(element: &quick_xml::events::BytesStart, entry: Option<&mut FeedEntry>, document: &mut FeedDocument) {
+    let mut href = None;
+    let mut rel = None;
+    for attr in element.attributes().flatten() {
+        let value = attr.unescape_value().map(|v| v.into_owned()).unwrap_or_default();
+        match attr.key.local_name().as_ref() {
+            b"href" => href = Some(value),
+            b"rel" => rel = Some(value),
+            _ => {}
+        }
+    }
+
+    let Some(href) = href.filter(|href| !href.trim().is_empty()) else { return };
+    if rel.as_deref().is_some_and(|rel| rel != "alternate") {
+        return;
+    }
+    match entry {
+        Some(entry) => {
+            entry.link.get_or_insert(href);
+        }
+        None => {
+            document.link.get_or_insert(href);
+        }
+    }
+}
+
+fn apply_entry_field(entry: &mut FeedEntry, name: &str, value: String) {
+    if value.is_empty() {
+        return;
+    }
+    match name {
+        "title" => entry.title = value,
+        "link" => {
+            entry.link.get_or_insert(value);
+        }
+        "guid" | "id" => entry.guid = value,
+        "pubdate" | "published" | "date" => entry.published = parse_date(&value).or(entry.published),
+        "updated" => entry.published = entry.published.or_else(|| parse_date(&value)),
+        "description" | "summary" => entry.summary = Some(value),
+        "encoded" | "content" => entry.content = Some(value),
+        "author" | "creator" => entry.author = Some(value),
+        _ => {}
+    }
+}
+
+/// RFC 2822 (RSS) or RFC 3339 (Atom, Dublin Core) date
+fn parse_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+}
+
+// ============================================================================
+// Subscriptions
+// ============================================================================
+
+const FEED_COLUMNS: &str = "id, account_id, folder_id, url, title, interval_minutes, enabled, \
+                            last_polled_at, last_error, etag, last_modified";
+
+fn row_to_feed(row: &rusqlite::Row<'_>) -> rusqlite::Result<Feed> {
+    Ok(Feed {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        folder_id: row.get(2)?,
+        url: row.get(3)?,
+        title: row.get(4)?,
+        interval_minutes: row.get(5)?,
+        enabled: row.get(6)?,
+        last_polled_at: row.get(7)?,
+        last_error: row.get(8)?,
+        etag: row.get(9)?,
+        last_modified: row.get(10)?,
+    })
+}
+
+/// Feed URLs must be http(s)
+pub fn validate_url(url: &str) -> Result<url::Url, String> {
+    let parsed = url::Url::parse(url.trim()).map_err(|e| format!("Invalid feed URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err("Feed URL must be an http or https address".to_string());
+    }
+    Ok(parsed)
+}
+
+fn clamp_interval(minutes: u32) -> u32 {
+    minutes.clamp(MIN_INTERVAL_MINUTES, MAX_INTERVAL_MINUTES)
+}
+
+/// Subscribe an account to a feed
+/// Entries go to the local folder `folder_name` (created when missing).
+pub fn add_feed(
+    db: &Database,
+    account_id: i64,
+    url: &str,
+    folder_name: Option<&str>,
+    interval_minutes: Option<u32>,
+) -> Result<Feed, String> {
+    let url = validate_url(url)?;
+    let folder_name = folder_name.map(str::trim).filter(|name| !name.is_empty()).unwrap_or(DEFAULT_FOLDER);
+    let folder_id = db
+        .get_or_create_local_folder(account_id, folder_name)
+        .map_err(|e| format!("Failed to create feed folder: {}", e))?;
+
+    let id = db
+        .execute_insert(
+            "INSERT INTO feeds (account_id, folder_id, url, title, interval_minutes) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                account_id,
+                folder_id,
+                url.as_str(),
+                url.host_str().unwrap_or_default(),
+                clamp_interval(interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES)),
+            ],
+        )
+        .map_err(|e| match e {
+            crate::db::DbError::Sqlite(rusqlite::Error::SqliteFailure(err, _))
+                if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                "Already subscribed to this feed".to_string()
+            }
+            e => format!("Failed to add feed: {}", e),
+        })?;
+
+    get_feed(db, id).map_err(|e| format!("Failed to load feed: {}", e))
+}
+
+pub fn get_feed(db: &Database, id: i64) -> DbResult<Feed> {
+    db.query_row(&format!("SELECT {} FROM feeds WHERE id = ?1", FEED_COLUMNS), [id], row_to_feed)
+}
+
+/// Feeds of an account, or of every account
+pub fn list_feeds(db: &Database, account_id: Option<i64>) -> DbResult<Vec<Feed>> {
+    match account_id {
+        Some(account_id) => db.query(
+            &format!("SELECT {} FROM feeds WHERE account_id = ?1 ORDER BY title", FEED_COLUMNS),
+            [account_id],
+            row_to_feed,
+        ),
+        None => db.query(&format!("SELECT {} FROM feeds ORDER BY account_id, title", FEED_COLUMNS), [], row_to_feed),
+    }
+}
+
+/// Change the poll interval or pause a feed
+pub fn update_feed(db: &Database, id: i64, interval_minutes: u32, enabled: bool) -> DbResult<Feed> {
+    db.execute(
+        "UPDATE feeds SET interval_minutes = ?1, enabled = ?2 WHERE id = ?3",
+        params![clamp_interval(interval_minutes), enabled, id],
+    )?;
+    get_feed(db, id)
+}
+
+/// Unsubscribe; items already stored stay in their folder
+pub fn remove_feed(db: &Database, id: i64) -> DbResult<()> {
+    db.execute("DELETE FROM feeds WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+// ============================================================================
+// Polling
+// ============================================================================
+
+enum Fetched {
+    NotModified,
+    Document {
+        document: FeedDocument,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+/// Download a feed, honouring the stored validators
+async fn fetch(feed: &Feed) -> Result<Fetched, String> {
+    validate_url(&feed.url)?;
+
+    let mut request = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?
+        .get(&feed.url)
+        .header("User-Agent", concat!("Owlivion-Mail/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/rss+xml, application/atom+xml, application/xml;q=0.9, */*;q=0.8");
+    if let Some(etag) = &feed.etag {
+        request = request.header("If-None-Match", etag);
+    }
+    if let Some(last_modified) = &feed.last_modified {
+        request = request.header("If-Modified-Since", last_modified);
+    }
+
+    let response = request.send().await.map_err(|e| format!("Feed request failed: {}", e))?;
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+    if !response.status().is_success() {
+        return Err(format!("Feed returned HTTP {}", response.status()));
+    }
+    if response.content_length().is_some_and(|length| length as usize > MAX_FEED_BYTES) {
+        return Err("Feed is too large".to_string());
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let etag = header("etag");
+    let last_modified = header("last-modified");
+
+    let body = response.bytes().await.map_err(|e| format!("Failed to read feed: {}", e))?;
+    if body.len() > MAX_FEED_BYTES {
+        return Err("Feed is too large".to_string());
+    }
+
+    Ok(Fetched::Document {
+        document: parse_feed(&String::from_utf8_lossy(&body))?,
+        etag,
+        last_modified,
+    })
+}
+
+/// Plain text from an HTML fragment (for the text part and preview)
+fn html_to_text(html: &str) -> String {
+    let mut result = String::new();
+    let mut inside_tag = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => {
+                inside_tag = true;
+                result.push(' ');
+            }
+            '>' => inside_tag = false,
+            _ if !inside_tag => result.push(c),
+            _ => {}
+        }
+    }
+
+    result
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// RFC 822 message for a feed entry
+fn entry_message(feed: &Feed, recipient: &str, entry: &FeedEntry) -> Result<Vec<u8>, String> {
+    let host = url::Url::parse(&feed.url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+    let sender = Address::new("feed", &host)
+        .or_else(|_| Address::new("feed", "localhost"))
+        .map_err(|e| e.to_string())?;
+    let sender_name = match &entry.author {
+        Some(author) => format!("{} ({})", feed.title, author),
+        None => feed.title.clone(),
+    };
+
+    let digest = ring::digest::digest(&ring::digest::SHA256, format!("{}\n{}", feed.url, entry.guid).as_bytes());
+    let message_id = format!("<{}@feeds.owlivion>", &hex::encode(digest.as_ref())[..32]);
+
+    let title = if entry.title.is_empty() { "(untitled)" } else { entry.title.as_str() };
+    let body = entry.content.as_deref().or(entry.summary.as_deref()).unwrap_or_default();
+
+    let mut html = match &entry.link {
+        Some(link) => format!("<h2><a href=\"{}\">{}</a></h2>\n", escape_html(link), escape_html(title)),
+        None => format!("<h2>{}</h2>\n", escape_html(title)),
+    };
+    html.push_str(body);
+    let mut text = html_to_text(body);
+    if let Some(link) = &entry.link {
+        html.push_str(&format!("\n<p><a href=\"{}\">{}</a></p>", escape_html(link), escape_html(link)));
+        text.push_str(&format!("\n\n{}", link));
+    }
+
+    let published = entry.published.map(SystemTime::from).unwrap_or_else(SystemTime::now);
+    let builder: MessageBuilder = Message::builder()
+        .from(Mailbox::new(Some(sender_name), sender))
+        .to(recipient
+            .parse()
+            .map_err(|e| format!("Invalid account address: {}", e))?)
+        .subject(title)
+        .message_id(Some(message_id))
+        .date(published);
+
+    let message = crate::mail::compose::build_message(builder, Some(text), Some(html), Vec::new())
+        .map_err(|e| e.to_string())?;
+    Ok(message.formatted())
+}
+
+/// Store entries not seen before as messages in the feed's folder
+/// Returns the ids of the new messages.
+pub fn store_entries(db: &Database, feed: &Feed, recipient: &str, entries: &[FeedEntry]) -> Result<Vec<i64>, String> {
+    let mut new_ids = Vec::new();
+
+    for entry in entries.iter().take(MAX_ENTRIES_PER_POLL) {
+        // Claim the guid first so concurrent polls never store an entry twice
+        let claimed = db
+            .execute(
+                "INSERT OR IGNORE INTO feed_items (feed_id, guid) VALUES (?1, ?2)",
+                params![feed.id, entry.guid],
+            )
+            .map_err(|e| format!("Failed to record feed item: {}", e))?;
+        if claimed == 0 {
+            continue;
+        }
+
+        let stored = entry_message(feed, recipient, entry).and_then(|raw| {
+            let new_email = crate::new_email_from_raw(feed.account_id, feed.folder_id, &raw)?;
+            db.insert_local_email(&new_email, &raw).map_err(|e| e.to_string())
+        });
+        match stored {
+            Ok(email_id) => {
+                db.execute(
+                    "UPDATE feed_items SET email_id = ?1 WHERE feed_id = ?2 AND guid = ?3",
+                    params![email_id, feed.id, entry.guid],
+                )
+                .map_err(|e| format!("Failed to record feed item: {}", e))?;
+                new_ids.push(email_id);
+            }
+            Err(e) => {
+                // Release the guid so the entry is retried on the next poll
+                let _ = db.execute(
+                    "DELETE FROM feed_items WHERE feed_id = ?1 AND guid = ?2",
+                    params![feed.id, entry.guid],
+                );
+                return Err(format!("Failed to store feed item: {}", e));
+            }
+        }
+    }
+
+    Ok(new_ids)
+}
+
+/// Poll one feed, store new entries and run plugins and filters on them
+pub async fn poll_feed(db: &Arc<Database>, engine: &FilterEngine, feed: &Feed) -> FeedPollResult {
+    let mut result = FeedPollResult {
+        feed_id: feed.id,
+        account_id: feed.account_id,
+        new_email_ids: Vec::new(),
+        error: None,
+    };
+
+    let outcome = match fetch(feed).await {
+        Ok(Fetched::NotModified) => Ok(None),
+        Ok(Fetched::Document { document, etag, last_modified }) => {
+            let mut current = feed.clone();
+            if !document.title.is_empty() {
+                current.title = document.title.clone();
+            }
+            db.get_account(feed.account_id)
+                .map_err(|e| format!("Failed to get account: {}", e))
+                .and_then(|account| store_entries(db, &current, &account.email, &document.entries))
+                .map(|ids| Some((current.title, etag, last_modified, ids)))
+        }
+        Err(e) => Err(e),
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let saved = match outcome {
+        Ok(None) => db.execute(
+            "UPDATE feeds SET last_polled_at = ?1, last_error = NULL WHERE id = ?2",
+            params![now, feed.id],
+        ),
+        Ok(Some((title, etag, last_modified, ids))) => {
+            result.new_email_ids = ids;
+            db.execute(
+                "UPDATE feeds SET title = ?1, etag = ?2, last_modified = ?3, last_polled_at = ?4, last_error = NULL
+                 WHERE id = ?5",
+                params![title, etag, last_modified, now, feed.id],
+            )
+        }
+        Err(e) => {
+            log::warn!("Feed {} ({}) poll failed: {}", feed.id, feed.url, e);
+            let saved = db.execute(
+                "UPDATE feeds SET last_polled_at = ?1, last_error = ?2 WHERE id = ?3",
+                params![now, e, feed.id],
+            );
+            result.error = Some(e);
+            saved
+        }
+    };
+    if let Err(e) = saved {
+        log::error!("Failed to save poll state of feed {}: {}", feed.id, e);
+    }
+
+    let plugins = plugins::registry();
+    for email_id in &result.new_email_ids {
+        let Ok(email) = db.get_email(*email_id) else { continue };
+        plugins.email_received(&email).await;
+
+        match engine.apply_filters(&email).await {
+            Ok(actions) if !actions.is_empty() => {
+                if let Err(e) = engine.execute_actions(*email_id, actions).await {
+                    log::warn!("Failed to execute filter actions on feed item {}: {}", email_id, e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to apply filters to feed item {}: {}", email_id, e),
+        }
+    }
+
+    if !result.new_email_ids.is_empty() {
+        log::info!("Feed {} stored {} new item(s)", feed.id, result.new_email_ids.len());
+    }
+    result
+}
+
+/// Poll every enabled feed that is due
+pub async fn poll_due(db: &Arc<Database>, engine: &FilterEngine, now: DateTime<Utc>) -> DbResult<Vec<FeedPollResult>> {
+    let mut results = Vec::new();
+    for feed in list_feeds(db, None)?.into_iter().filter(|feed| feed.is_due(now)) {
+        results.push(poll_feed(db, engine, &feed).await);
+    }
+    Ok(results)
+}
+
+/// Background poller for all feeds
+pub struct FeedPoller {
+    db: Arc<Database>,
+    started: AtomicBool,
+}
+
+impl FeedPoller {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Start the poller (only the first call has an effect)
+    /// `on_update` receives the results of polls that stored items or failed.
+    pub fn start<F>(&self, engine: FilterEngine, on_update: F)
+    where
+        F: Fn(Vec<FeedPollResult>) + Send + Sync + 'static,
+    {
+        if self.started.swap(true, Ordering::SeqCst) {
+            log::warn!("Feed poller already started");
+            return;
+        }
+        let db = self.db.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_TICK);
+            loop {
+                interval.tick().await;
+                match poll_due(&db, &engine, Utc::now()).await {
+                    Ok(results) => {
+                        let changed: Vec<FeedPollResult> = results
+                            .into_iter()
+                            .filter(|result| !result.new_email_ids.is_empty() || result.error.is_some())
+                            .collect();
+                        if !changed.is_empty() {
+                            on_update(changed);
+                        }
+                    }
+                    Err(e) => log::error!("Failed to load feeds: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:atom="http://www.w3.org/2005/Atom">
+  <channel>
+    <title>Owlivion Blog</title>
+    <link>https://blog.owlivion.test/</link>
+    <atom:link href="https://blog.owlivion.test/rss" rel="self"/>
+    <item>
+      <title>Release 1.2 &amp; more</title>
+      <link>https://blog.owlivion.test/1-2</link>
+      <guid isPermaLink="false">post-12</guid>
+      <pubDate>Wed, 01 Jan 2025 10:00:00 +0000</pubDate>
+      <description><![CDATA[<p>Short <b>summary</b></p>]]></description>
+      <content:encoded><![CDATA[<p>Full article text</p>]]></content:encoded>
+    </item>
+    <item>
+      <title>No guid</title>
+      <link>https://blog.owlivion.test/no-guid</link>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom Example</title>
+  <link href="https://atom.test/" rel="alternate"/>
+  <entry>
+    <title type="html">Hello &lt;em&gt;world&lt;/em&gt;</title>
+    <link href="https://atom.test/comments" rel="replies"/>
+    <link href="https://atom.test/hello"/>
+    <id>urn:uuid:1225c695</id>
+    <updated>2025-02-03T04:05:06Z</updated>
+    <author><name>Jane</name><email>jane@atom.test</email></author>
+    <content type="xhtml"><div xmlns="http://www.w3.org/1999/xhtml">Hi <b>there</b></div></content>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn test_parse_rss_and_atom() {
+        let rss = parse_feed(RSS).unwrap();
+        assert_eq!(rss.title, "Owlivion Blog");
+        assert_eq!(rss.link.as_deref(), Some("https://blog.owlivion.test/"));
+        assert_eq!(rss.entries.len(), 2);
+        let first = &rss.entries[0];
+        assert_eq!(first.title, "Release 1.2 & more");
+        assert_eq!(first.guid, "post-12");
+        assert_eq!(first.content.as_deref(), Some("<p>Full article text</p>"));
+        assert_eq!(first.summary.as_deref(), Some("<p>Short <b>summary</b></p>"));
+        assert_eq!(first.published.unwrap().to_rfc3339(), "2025-01-01T10:00:00+00:00");
+        assert_eq!(rss.entries[1].guid, "https://blog.owlivion.test/no-guid");
+
+        let atom = parse_feed(ATOM).unwrap();
+        assert_eq!(atom.title, "Atom Example");
+        let entry = &atom.entries[0];
+        assert_eq!(entry.title, "Hello <em>world</em>");
+        assert_eq!(entry.link.as_deref(), Some("https://atom.test/hello"));
+        assert_eq!(entry.guid, "urn:uuid:1225c695");
+        assert_eq!(entry.author.as_deref(), Some("Jane"));
+        assert_eq!(entry.content.as_deref(), Some("Hi there"));
+        assert!(entry.published.is_some());
+
+        assert!(parse_feed("<html><body>Not a feed</body></html>").is_err());
+        assert!(validate_url("ftp://example.com/feed").is_err());
+    }
+
+    fn setup_db() -> (Arc<Database>, i64) {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        (Arc::new(db), account_id)
+    }
+
+    #[tokio::test]
+    async fn test_poll_stores_new_entries_once() {
+        let (db, account_id) = setup_db();
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/rss")
+            .with_header("content-type", "application/rss+xml")
+            .with_header("etag", "\"v1\"")
+            .with_body(RSS)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let feed = add_feed(&db, account_id, &format!("{}/rss", server.url()), None, Some(1)).unwrap();
+        assert_eq!(feed.interval_minutes, MIN_INTERVAL_MINUTES);
+        assert!(add_feed(&db, account_id, &feed.url, None, None).is_err());
+
+        let engine = FilterEngine::new(db.clone());
+        let result = poll_feed(&db, &engine, &feed).await;
+        assert_eq!(result.error, None);
+        assert_eq!(result.new_email_ids.len(), 2);
+
+        let feed = get_feed(&db, feed.id).unwrap();
+        assert_eq!(feed.title, "Owlivion Blog");
+        assert_eq!(feed.etag.as_deref(), Some("\"v1\""));
+        assert!(!feed.is_due(Utc::now()));
+
+        let email = db.get_email(result.new_email_ids[0]).unwrap();
+        assert_eq!(email.subject, "Release 1.2 & more");
+        assert_eq!(email.from_name.as_deref(), Some("Owlivion Blog"));
+        assert!(email.preview.contains("Full article text"));
+        assert!(db.get_folder_by_id(email.folder_id).unwrap().is_local);
+
+        // A deleted item is not brought back by the next poll
+        db.execute("DELETE FROM emails WHERE id = ?1", [email.id]).unwrap();
+        let again = poll_feed(&db, &engine, &feed).await;
+        assert!(again.new_email_ids.is_empty());
+        first.assert_async().await;
+
+        // Errors are recorded on the feed
+        let missing = add_feed(&db, account_id, &format!("{}/missing", server.url()), Some("News"), None).unwrap();
+        let failed = poll_feed(&db, &engine, &missing).await;
+        assert!(failed.error.is_some());
+        assert!(get_feed(&db, missing.id).unwrap().last_error.is_some());
+        assert_eq!(list_feeds(&db, Some(account_id)).unwrap().len(), 2);
+
+        remove_feed(&db, missing.id).unwrap();
+        assert_eq!(list_feeds(&db, None).unwrap().len(), 1);
+    }
+}
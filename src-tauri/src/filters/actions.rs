@@ -1,19 +1,27 @@
 //! Filter actions to perform on matched emails
 
+use super::webhook::WebhookTarget;
 use serde::{Deserialize, Serialize};
 
 /// Action to perform when filter matches
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 pub struct FilterAction {
     pub action: FilterActionType,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional, type = "number"))]
     pub folder_id: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
     pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub webhook: Option<WebhookTarget>,
 }
 
 /// Types of actions that can be performed
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "snake_case")]
 pub enum FilterActionType {
     /// Move email to a specific folder
@@ -30,6 +38,8 @@ pub enum FilterActionType {
     Delete,
     /// Archive email
     Archive,
+    /// POST the email to a webhook URL
+    Webhook,
 }
 
 impl FilterAction {
@@ -39,6 +49,7 @@ impl FilterAction {
             action: FilterActionType::MoveToFolder,
             folder_id: Some(folder_id),
             label: None,
+            webhook: None,
         }
     }
 
@@ -48,6 +59,7 @@ impl FilterAction {
             action: FilterActionType::AddLabel,
             folder_id: None,
             label: Some(label.into()),
+            webhook: None,
         }
     }
 
@@ -57,6 +69,7 @@ impl FilterAction {
             action: FilterActionType::MarkAsRead,
             folder_id: None,
             label: None,
+            webhook: None,
         }
     }
 
@@ -66,6 +79,7 @@ impl FilterAction {
             action: FilterActionType::MarkAsStarred,
             folder_id: None,
             label: None,
+            webhook: None,
         }
     }
 
@@ -75,6 +89,7 @@ impl FilterAction {
             action: FilterActionType::MarkAsSpam,
             folder_id: None,
             label: None,
+            webhook: None,
         }
     }
 
@@ -84,6 +99,7 @@ impl FilterAction {
             action: FilterActionType::Delete,
             folder_id: None,
             label: None,
+            webhook: None,
         }
     }
 
@@ -93,6 +109,17 @@ impl FilterAction {
             action: FilterActionType::Archive,
             folder_id: None,
             label: None,
+            webhook: None,
+        }
+    }
+
+    /// Create a webhook action
+    pub fn webhook(url: impl Into<String>, include_body: bool) -> Self {
+        Self {
+            action: FilterActionType::Webhook,
+            folder_id: None,
+            label: None,
+            webhook: Some(WebhookTarget::new(url, include_body)),
         }
     }
 }
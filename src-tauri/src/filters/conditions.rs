@@ -4,7 +4,8 @@ use crate::db::Email;
 use serde::{Deserialize, Serialize};
 
 /// Filter condition to match against emails
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 pub struct FilterCondition {
     pub field: ConditionField,
     pub operator: ConditionOperator,
@@ -13,9 +14,13 @@ pub struct FilterCondition {
 
 /// Email fields that can be filtered
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "snake_case")]
 pub enum ConditionField {
+    /// Sender address and display name
     From,
+    /// Sender address only
+    FromAddress,
     To,
     Subject,
     Body,
@@ -24,6 +29,7 @@ pub enum ConditionField {
 
 /// Comparison operators for conditions
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "snake_case")]
 pub enum ConditionOperator {
     Contains,
@@ -57,6 +63,7 @@ impl FilterCondition {
                 format!("{} {}", email.from_address, email.from_name.as_deref().unwrap_or(""))
                     .to_lowercase()
             }
+            ConditionField::FromAddress => email.from_address.trim().to_lowercase(),
             ConditionField::To => email.to_addresses.to_lowercase(),
             ConditionField::Subject => email.subject.to_lowercase(),
             ConditionField::Body => {
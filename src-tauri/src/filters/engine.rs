@@ -1,18 +1,57 @@
 //! Filter engine - applies filters to emails
 
+use super::webhook::{self, WebhookFilter, WebhookPayload, WebhookTarget};
 use super::{EmailFilter, FilterAction, FilterActionType, MatchLogic};
+use crate::activity::{self, ActivityAction, UndoPayload};
 use crate::db::{Database, DbResult, Email};
+use crate::events::{EmailChange, EmailsChanged, StoreEvents};
+use crate::plugins::{self, PluginRegistry};
 use std::sync::Arc;
 
 /// Filter engine that applies rules to emails
 pub struct FilterEngine {
     db: Arc<Database>,
+    events: Option<StoreEvents>,
+    plugins: Arc<PluginRegistry>,
 }
 
 impl FilterEngine {
     /// Create a new filter engine
     pub fn new(db: Arc<Database>) -> Self {
-        Self { db }
+        Self { db, events: None, plugins: plugins::registry() }
+    }
+
+    /// Publish store change events for executed actions
+    pub fn with_events(mut self, events: StoreEvents) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Consult these plugins instead of the process-wide registry
+    pub fn with_plugins(mut self, plugins: Arc<PluginRegistry>) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Record a destructive action in the activity log (failures are only logged)
+    fn log_activity(&self, email: &Email, action: ActivityAction, summary: &str, undo: UndoPayload) {
+        if let Err(e) = activity::record(&self.db, Some(email.account_id), action, summary, Some(&undo)) {
+            log::warn!("Failed to record filter activity for email {}: {}", email.id, e);
+        }
+    }
+
+    /// Announce a change to a single email on the store event bus
+    fn notify(&self, email_id: i64, change: EmailChange) {
+        let Some(events) = &self.events else { return };
+        if let Ok(email) = self.db.get_email(email_id) {
+            events.notify(EmailsChanged {
+                account_id: email.account_id,
+                folder: None,
+                email_ids: vec![email.id],
+                uids: vec![email.uid],
+                change,
+            });
+        }
     }
 
     /// Apply all enabled filters to an email
@@ -25,8 +64,13 @@ impl FilterEngine {
 
         for filter in filters {
             if self.test_filter(&filter, email) {
-                // Filter matched! Collect actions
-                actions_to_perform.extend(filter.actions.clone());
+                // Filter matched! Collect actions (webhooks report which filter fired)
+                actions_to_perform.extend(filter.actions.iter().cloned().map(|mut action| {
+                    if let Some(target) = &mut action.webhook {
+                        target.filter = Some(WebhookFilter { id: filter.id, name: filter.name.clone() });
+                    }
+                    action
+                }));
 
                 // Update filter statistics
                 self.update_filter_stats(filter.id).await?;
@@ -68,39 +112,86 @@ impl FilterEngine {
         email_id: i64,
         actions: Vec<FilterAction>,
     ) -> DbResult<()> {
+        let email = self.db.get_email(email_id)?;
+
         for action in actions {
+            if !self.plugins.allow_filter_action(&email, &action).await {
+                continue;
+            }
+
             match action.action {
                 FilterActionType::MoveToFolder => {
                     if let Some(folder_id) = action.folder_id {
                         self.move_email_to_folder(email_id, folder_id).await?;
+                        if let Ok(folder) = self.db.get_folder_by_id(folder_id) {
+                            let summary = format!("Filter moved \"{}\" to {}", email.subject, folder.name);
+                            let undo = UndoPayload::MoveBack { email_id, folder_id: email.folder_id };
+                            self.log_activity(&email, ActivityAction::FilterMove, &summary, undo);
+                            self.notify(email_id, EmailChange::Moved { target: folder.remote_name });
+                        }
                     }
                 }
                 FilterActionType::AddLabel => {
                     if let Some(label) = action.label {
                         self.add_email_label(email_id, &label).await?;
+                        let labels = self.db.get_email(email_id)
+                            .map(|email| serde_json::from_str(&email.labels).unwrap_or_default())
+                            .unwrap_or_default();
+                        self.notify(email_id, EmailChange::Labels { labels });
                     }
                 }
                 FilterActionType::MarkAsRead => {
                     self.db.update_email_flags(email_id, Some(true), None, None)?;
+                    self.notify(email_id, EmailChange::Read { value: true });
                 }
                 FilterActionType::MarkAsStarred => {
                     self.db.update_email_flags(email_id, None, Some(true), None)?;
+                    self.notify(email_id, EmailChange::Starred { value: true });
                 }
                 FilterActionType::MarkAsSpam => {
                     self.mark_email_as_spam(email_id).await?;
                 }
                 FilterActionType::Delete => {
                     self.db.update_email_flags(email_id, None, None, Some(true))?;
+                    let summary = format!("Filter deleted \"{}\"", email.subject);
+                    let undo = UndoPayload::Restore {
+                        folder: None,
+                        uids: vec![email.uid],
+                        email_ids: vec![email_id],
+                        pending_op_id: None,
+                    };
+                    self.log_activity(&email, ActivityAction::FilterDelete, &summary, undo);
+                    self.notify(email_id, EmailChange::Deleted { permanent: false });
                 }
                 FilterActionType::Archive => {
                     self.archive_email(email_id).await?;
                 }
+                FilterActionType::Webhook => {
+                    if let Some(target) = action.webhook {
+                        self.send_webhook(&email, target)?;
+                    }
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Post the email to a webhook in the background (failures are only logged)
+    fn send_webhook(&self, email: &Email, target: WebhookTarget) -> DbResult<()> {
+        let secret = webhook::signing_secret(&self.db)?;
+        let filter = target.filter.clone().unwrap_or(WebhookFilter { id: 0, name: String::new() });
+        let payload = WebhookPayload::new(filter, email, target.include_body);
+
+        tokio::spawn(async move {
+            match webhook::deliver(&target, &payload, &secret).await {
+                Ok(()) => log::info!("Webhook for filter '{}' delivered", payload.filter.name),
+                Err(e) => log::warn!("Webhook for filter '{}' failed: {}", payload.filter.name, e),
+            }
+        });
+        Ok(())
+    }
+
     /// Get all enabled filters for an account
     async fn get_enabled_filters(&self, account_id: i64) -> DbResult<Vec<EmailFilter>> {
         let sql = r#"
@@ -206,6 +297,9 @@ impl FilterEngine {
 
         if let Some(folder_id) = archive_folder_id {
             self.move_email_to_folder(email_id, folder_id).await?;
+            if let Ok(folder) = self.db.get_folder_by_id(folder_id) {
+                self.notify(email_id, EmailChange::Moved { target: folder.remote_name });
+            }
         } else {
             log::warn!("No archive folder found for account {}", email.account_id);
         }
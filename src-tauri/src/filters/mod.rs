@@ -5,17 +5,23 @@
 pub mod actions;
 pub mod conditions;
 pub mod engine;
+pub mod portable;
+pub mod webhook;
 
 pub use actions::{FilterAction, FilterActionType};
 pub use conditions::{FilterCondition, ConditionField, ConditionOperator};
 pub use engine::FilterEngine;
+pub use webhook::WebhookTarget;
 
 use serde::{Deserialize, Serialize};
 
 /// Email filter rule stored in database
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 pub struct EmailFilter {
+    #[cfg_attr(test, ts(type = "number"))]
     pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
     pub account_id: i64,
     pub name: String,
     pub description: Option<String>,
@@ -32,7 +38,9 @@ pub struct EmailFilter {
 
 /// New filter for insertion
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 pub struct NewEmailFilter {
+    #[cfg_attr(test, ts(type = "number"))]
     pub account_id: i64,
     pub name: String,
     pub description: Option<String>,
@@ -45,6 +53,7 @@ pub struct NewEmailFilter {
 
 /// Match logic for multiple conditions
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "lowercase")]
 pub enum MatchLogic {
     All, // AND - all conditions must match
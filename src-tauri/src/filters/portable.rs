@@ -0,0 +1,241 @@
+//! Portable filters for Owlivion sync
+//!
+//! Filters reference their target folders by local id, which means nothing on
+//! another machine. In sync form they carry the folder's remote path instead
+//! and are matched by (account address, filter name).
+
+use super::{EmailFilter, FilterAction, FilterActionType, FilterCondition, MatchLogic, NewEmailFilter, WebhookTarget};
+use crate::db::{parse_db_timestamp, Database, DbResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Filter action with its folder given by remote path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionItem {
+    pub action: FilterActionType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookTarget>,
+}
+
+/// Filter without local ids or match statistics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterItem {
+    /// Owning account's address
+    pub account_email: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub is_enabled: bool,
+    pub priority: i32,
+    pub match_logic: MatchLogic,
+    pub conditions: Vec<FilterCondition>,
+    pub actions: Vec<ActionItem>,
+
+    /// Last local modification (for conflict resolution)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl FilterItem {
+    /// Sync form of a filter; None if it points at a folder that no longer exists
+    pub fn from_filter(filter: &EmailFilter, account_email: &str, folder_paths: &HashMap<i64, String>) -> Option<Self> {
+        let actions = filter
+            .actions
+            .iter()
+            .map(|action| {
+                let folder_path = match action.folder_id {
+                    Some(id) => Some(folder_paths.get(&id)?.clone()),
+                    None => None,
+                };
+                Some(ActionItem {
+                    action: action.action.clone(),
+                    folder_path,
+                    label: action.label.clone(),
+                    webhook: action.webhook.clone(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            account_email: account_email.to_string(),
+            name: filter.name.clone(),
+            description: filter.description.clone(),
+            is_enabled: filter.is_enabled,
+            priority: filter.priority,
+            match_logic: filter.match_logic,
+            conditions: filter.conditions.clone(),
+            actions,
+            updated_at: parse_db_timestamp(&filter.updated_at),
+        })
+    }
+
+    /// Local form of the filter; None if a target folder is missing on this machine
+    pub fn to_new_filter(&self, account_id: i64, folder_ids: &HashMap<String, i64>) -> Option<NewEmailFilter> {
+        let actions = self
+            .actions
+            .iter()
+            .map(|action| {
+                let folder_id = match &action.folder_path {
+                    Some(path) => Some(*folder_ids.get(path)?),
+                    None => None,
+                };
+                Some(FilterAction {
+                    action: action.action.clone(),
+                    folder_id,
+                    label: action.label.clone(),
+                    webhook: action.webhook.clone(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(NewEmailFilter {
+            account_id,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            is_enabled: self.is_enabled,
+            priority: self.priority,
+            match_logic: self.match_logic,
+            conditions: self.conditions.clone(),
+            actions,
+        })
+    }
+
+    /// Same rule (ignores timestamps)
+    pub fn same_content(&self, other: &FilterItem) -> bool {
+        Self { updated_at: None, ..self.clone() } == Self { updated_at: None, ..other.clone() }
+    }
+}
+
+/// All local filters in sync form
+pub fn collect_sync_items(db: &Database) -> DbResult<Vec<FilterItem>> {
+    let mut items = Vec::new();
+
+    for account in db.get_accounts()? {
+        let folder_paths: HashMap<i64, String> = db
+            .get_folders(account.id)?
+            .into_iter()
+            .map(|folder| (folder.id, folder.remote_name))
+            .collect();
+
+        for filter in db.get_filters(account.id)? {
+            match FilterItem::from_filter(&filter, &account.email, &folder_paths) {
+                Some(item) => items.push(item),
+                None => log::warn!("Filter '{}' targets a deleted folder, not syncing it", filter.name),
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+/// Store synced filters, matching accounts by address and folders by path
+/// Returns the number of filters added or updated.
+pub fn apply_sync_items(db: &Database, items: &[FilterItem]) -> DbResult<usize> {
+    let mut by_account: HashMap<&str, Vec<&FilterItem>> = HashMap::new();
+    for item in items {
+        by_account.entry(item.account_email.as_str()).or_default().push(item);
+    }
+
+    let mut changed = 0;
+
+    for (email, items) in by_account {
+        let Some(account) = db.get_account_by_email(email)? else {
+            log::warn!("Account not found locally, skipping {} filters for {}", items.len(), email);
+            continue;
+        };
+
+        let folders = db.get_folders(account.id)?;
+        let folder_ids: HashMap<String, i64> = folders.iter().map(|f| (f.remote_name.clone(), f.id)).collect();
+        let folder_paths: HashMap<i64, String> = folders.into_iter().map(|f| (f.id, f.remote_name)).collect();
+        let existing = db.get_filters(account.id)?;
+
+        for item in items {
+            let Some(new_filter) = item.to_new_filter(account.id, &folder_ids) else {
+                log::warn!("Target folder of filter '{}' not found for {}, skipping", item.name, email);
+                continue;
+            };
+
+            match existing.iter().find(|f| f.name == item.name) {
+                Some(current) => {
+                    let unchanged = FilterItem::from_filter(current, email, &folder_paths)
+                        .is_some_and(|local| local.same_content(item));
+                    if !unchanged {
+                        db.update_filter(current.id, &new_filter)?;
+                        changed += 1;
+                    }
+                }
+                None => {
+                    db.add_filter(&new_filter)?;
+                    changed += 1;
+                }
+            }
+        }
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, NewFolder};
+    use crate::filters::{ConditionField, ConditionOperator};
+
+    #[test]
+    fn test_sync_items_use_folder_paths() {
+        let db = Database::in_memory().expect("Failed to create database");
+        let account_id = test_account(&db);
+        let folder_id = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "Invoices".to_string(),
+                remote_name: "Finance/Invoices".to_string(),
+                folder_type: "custom".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+
+        db.add_filter(&NewEmailFilter {
+            account_id,
+            name: "Invoices".to_string(),
+            description: None,
+            is_enabled: true,
+            priority: 1,
+            match_logic: MatchLogic::All,
+            conditions: vec![FilterCondition {
+                field: ConditionField::Subject,
+                operator: ConditionOperator::Contains,
+                value: "invoice".to_string(),
+            }],
+            actions: vec![FilterAction::move_to_folder(folder_id), FilterAction::mark_as_read()],
+        })
+        .unwrap();
+
+        let items = collect_sync_items(&db).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].actions[0].folder_path.as_deref(), Some("Finance/Invoices"));
+
+        // Identical rule is left alone; an edited one is updated in place
+        assert_eq!(apply_sync_items(&db, &items).unwrap(), 0);
+        let mut edited = items[0].clone();
+        edited.priority = 5;
+        assert_eq!(apply_sync_items(&db, &[edited]).unwrap(), 1);
+        assert_eq!(db.get_filters(account_id).unwrap()[0].priority, 5);
+
+        // Rules pointing at folders this machine does not have are skipped
+        let mut unknown = items[0].clone();
+        unknown.name = "Elsewhere".to_string();
+        unknown.actions[0].folder_path = Some("Missing".to_string());
+        assert_eq!(apply_sync_items(&db, &[unknown]).unwrap(), 0);
+        assert_eq!(db.get_filters(account_id).unwrap().len(), 1);
+    }
+}
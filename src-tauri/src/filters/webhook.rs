@@ -0,0 +1,259 @@
+//! Webhook filter action
+//!
+//! When a filter with a webhook action matches, the email is POSTed as JSON
+//! to the configured URL (Slack workflows, Zapier, self-hosted scripts). Each
+//! request is signed with HMAC-SHA256 over `"{timestamp}.{body}"` using a
+//! per-installation secret, sent as `X-Owlivion-Signature: sha256=<hex>`
+//! together with `X-Owlivion-Timestamp`, so receivers can reject forgeries
+//! and replays.
+
+use crate::db::{Database, DbResult, Email};
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Settings key holding the signing secret (hex)
+const SECRET_SETTING: &str = "webhook_signing_secret";
+
+/// Give up on slow receivers so filter processing is never held up for long
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Event name sent in `X-Owlivion-Event` and the payload
+pub const FILTER_MATCHED_EVENT: &str = "filter.matched";
+
+/// Webhook destination of a filter action
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookTarget {
+    pub url: String,
+    /// Include the message body (text and HTML) in the payload
+    #[serde(default)]
+    pub include_body: bool,
+    /// Filter the action belongs to (filled in by the engine on a match)
+    #[serde(skip)]
+    pub filter: Option<WebhookFilter>,
+}
+
+impl WebhookTarget {
+    pub fn new(url: impl Into<String>, include_body: bool) -> Self {
+        Self {
+            url: url.into(),
+            include_body,
+            filter: None,
+        }
+    }
+
+    /// HTTPS only; plain HTTP is allowed to this machine for local scripts
+    pub fn validate(&self) -> Result<(), String> {
+        let url = url::Url::parse(&self.url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+        let host = url.host_str().ok_or("Webhook URL has no host")?;
+
+        match url.scheme() {
+            "https" => Ok(()),
+            "http" if crate::mail::config::is_loopback_host(host) => Ok(()),
+            _ => Err("Webhook URL must use https (http is only allowed to localhost)".to_string()),
+        }
+    }
+}
+
+/// Filter that triggered the webhook
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookFilter {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Email as sent to webhooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEmail {
+    pub id: i64,
+    pub account_id: i64,
+    pub uid: u32,
+    pub message_id: String,
+    pub from: String,
+    pub from_name: Option<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: String,
+    pub date: String,
+    pub preview: String,
+    pub has_attachments: bool,
+    pub labels: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_html: Option<String>,
+}
+
+/// JSON body of a webhook request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookPayload {
+    pub event: String,
+    pub filter: WebhookFilter,
+    pub email: WebhookEmail,
+    pub sent_at: String,
+}
+
+impl WebhookPayload {
+    pub fn new(filter: WebhookFilter, email: &Email, include_body: bool) -> Self {
+        let addresses = |json: &str| serde_json::from_str::<Vec<String>>(json).unwrap_or_default();
+
+        Self {
+            event: FILTER_MATCHED_EVENT.to_string(),
+            filter,
+            email: WebhookEmail {
+                id: email.id,
+                account_id: email.account_id,
+                uid: email.uid,
+                message_id: email.message_id.clone(),
+                from: email.from_address.clone(),
+                from_name: email.from_name.clone(),
+                to: addresses(&email.to_addresses),
+                cc: addresses(&email.cc_addresses),
+                subject: email.subject.clone(),
+                date: email.date.clone(),
+                preview: email.preview.clone(),
+                has_attachments: email.has_attachments,
+                labels: addresses(&email.labels),
+                body_text: email.body_text.clone().filter(|_| include_body),
+                body_html: email.body_html.clone().filter(|_| include_body),
+            },
+            sent_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Signing secret, generated on first use
+pub fn signing_secret(db: &Database) -> DbResult<String> {
+    match db.get_setting::<String>(SECRET_SETTING)? {
+        Some(secret) => Ok(secret),
+        None => rotate_secret(db),
+    }
+}
+
+/// Replace the signing secret (receivers must be updated)
+pub fn rotate_secret(db: &Database) -> DbResult<String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| crate::db::DbError::Constraint("Failed to generate webhook secret".to_string()))?;
+
+    let secret = hex::encode(bytes);
+    db.set_setting(SECRET_SETTING, &secret)?;
+    Ok(secret)
+}
+
+/// Hex HMAC-SHA256 of `"{timestamp}.{body}"`
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let mut context = hmac::Context::with_key(&key);
+    context.update(timestamp.to_string().as_bytes());
+    context.update(b".");
+    context.update(body);
+    hex::encode(context.sign().as_ref())
+}
+
+/// POST a signed payload; non-2xx responses are errors
+pub async fn deliver(target: &WebhookTarget, payload: &WebhookPayload, secret: &str) -> Result<(), String> {
+    target.validate()?;
+
+    let body = serde_json::to_vec(payload).map_err(|e| format!("Failed to encode payload: {}", e))?;
+    let timestamp = chrono::Utc::now().timestamp();
+
+    let response = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?
+        .post(&target.url)
+        .header("Content-Type", "application/json")
+        .header("User-Agent", concat!("Owlivion-Mail/", env!("CARGO_PKG_VERSION")))
+        .header("X-Owlivion-Event", &payload.event)
+        .header("X-Owlivion-Timestamp", timestamp.to_string())
+        .header("X-Owlivion-Signature", format!("sha256={}", sign(secret, timestamp, &body)))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Webhook returned HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_validation_and_signature() {
+        let target = |url: &str| WebhookTarget::new(url, false);
+        assert!(target("https://hooks.slack.com/services/x").validate().is_ok());
+        assert!(target("http://127.0.0.1:8080/hook").validate().is_ok());
+        assert!(target("http://example.com/hook").validate().is_err());
+        assert!(target("ftp://example.com").validate().is_err());
+        assert!(target("not a url").validate().is_err());
+
+        // Signature is stable for the same input and changes with the timestamp
+        let signature = sign("secret", 1_700_000_000, b"{}");
+        assert_eq!(signature.len(), 64);
+        assert_eq!(signature, sign("secret", 1_700_000_000, b"{}"));
+        assert_ne!(signature, sign("secret", 1_700_000_001, b"{}"));
+
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let secret = signing_secret(&db).unwrap();
+        assert_eq!(secret.len(), 64);
+        assert_eq!(signing_secret(&db).unwrap(), secret);
+        assert_ne!(rotate_secret(&db).unwrap(), secret);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_posts_signed_json() {
+        let mut server = mockito::Server::new_async().await;
+        let hook = server
+            .mock("POST", "/hook")
+            .match_header("x-owlivion-event", FILTER_MATCHED_EVENT)
+            .match_header("x-owlivion-signature", mockito::Matcher::Regex("^sha256=[0-9a-f]{64}$".to_string()))
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"event":"filter.matched","filter":{"name":"Invoices"}}"#.to_string(),
+            ))
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let payload = WebhookPayload {
+            event: FILTER_MATCHED_EVENT.to_string(),
+            filter: WebhookFilter { id: 1, name: "Invoices".to_string() },
+            email: WebhookEmail {
+                id: 1,
+                account_id: 1,
+                uid: 10,
+                message_id: "<1@test>".to_string(),
+                from: "billing@test.com".to_string(),
+                from_name: None,
+                to: vec!["me@test.com".to_string()],
+                cc: Vec::new(),
+                subject: "Invoice".to_string(),
+                date: "2025-01-01T10:00:00Z".to_string(),
+                preview: String::new(),
+                has_attachments: false,
+                labels: Vec::new(),
+                body_text: None,
+                body_html: None,
+            },
+            sent_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let target = WebhookTarget::new(format!("{}/hook", server.url()), false);
+
+        deliver(&target, &payload, "secret").await.unwrap();
+        hook.assert_async().await;
+
+        let failing = WebhookTarget::new(format!("{}/missing", server.url()), false);
+        assert!(deliver(&failing, &payload, "secret").await.is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Focused Inbox
+//!
+//! Inbox mail is split into Focused and Other. Each message is scored from a
+//! small per-account model: a weight for its sender and one for the sender's
+//! domain, on top of a prior that sends newsletters, social notifications
+//! and mailing lists to Other. Whenever the user moves a message between the
+//! two, the correction is recorded in `focus_signals`, the sender and domain
+//! weights move towards it, and the automatically classified mail from that
+//! domain is scored again. Messages the user placed are never reclassified.
+
+use crate::db::{Database, DbResult};
+use crate::digest;
+use crate::reply_needed;
+use rusqlite::params;
+
+/// Weight change per correction
+const SENDER_STEP: f64 = 1.0;
+const DOMAIN_STEP: f64 = 0.5;
+
+/// Weights are kept within ±MAX_WEIGHT so old habits can be unlearned
+const MAX_WEIGHT: f64 = 5.0;
+
+/// Prior of mail from a person, and of bulk or automated mail
+const PERSON_PRIOR: f64 = 0.5;
+const BULK_PRIOR: f64 = -1.0;
+
+/// Features of a message the model has weights for
+struct Features {
+    sender: String,
+    domain: String,
+}
+
+impl Features {
+    fn of(from_address: &str) -> Self {
+        let sender = from_address.trim().to_lowercase();
+        let domain = sender.rsplit_once('@').map(|(_, domain)| domain).unwrap_or_default().to_string();
+        Self {
+            sender: format!("sender:{}", sender),
+            domain: format!("domain:{}", domain),
+        }
+    }
+}
+
+fn weight(db: &Database, account_id: i64, feature: &str) -> DbResult<f64> {
+    let weights = db.query(
+        "SELECT weight FROM focus_weights WHERE account_id = ?1 AND feature = ?2",
+        params![account_id, feature],
+        |row| row.get::<_, f64>(0),
+    )?;
+    Ok(weights.first().copied().unwrap_or(0.0))
+}
+
+fn adjust(db: &Database, account_id: i64, feature: &str, delta: f64) -> DbResult<()> {
+    let weight = (weight(db, account_id, feature)? + delta).clamp(-MAX_WEIGHT, MAX_WEIGHT);
+    db.execute(
+        "INSERT INTO focus_weights (account_id, feature, weight) VALUES (?1, ?2, ?3)
+         ON CONFLICT(account_id, feature) DO UPDATE SET weight = excluded.weight",
+        params![account_id, feature, weight],
+    )?;
+    Ok(())
+}
+
+/// Score of a message; Focused when above zero
+fn score(
+    db: &Database,
+    account_id: i64,
+    from_address: &str,
+    subject: &str,
+    labels: &str,
+    raw_headers: Option<&str>,
+) -> DbResult<f64> {
+    let features = Features::of(from_address);
+    let bulk = digest::categorize(from_address, subject, labels).is_some() || reply_needed::is_automated(raw_headers);
+    let prior = if bulk { BULK_PRIOR } else { PERSON_PRIOR };
+    Ok(prior + weight(db, account_id, &features.sender)? + weight(db, account_id, &features.domain)?)
+}
+
+/// Classify inbox messages among `email_ids` that have no focus state yet
+/// Returns the number of messages classified.
+pub fn classify(db: &Database, email_ids: &[i64]) -> DbResult<usize> {
+    let mut classified = 0;
+    for email_id in email_ids {
+        let rows = db.query(
+            "SELECT e.account_id, e.from_address, e.subject, e.labels, e.raw_headers
+             FROM emails e
+             JOIN folders f ON f.id = e.folder_id
+             LEFT JOIN email_focus m ON m.email_id = e.id
+             WHERE e.id = ?1 AND m.email_id IS NULL AND f.folder_type = 'inbox'",
+            [email_id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            },
+        )?;
+        let Some((account_id, from, subject, labels, raw_headers)) = rows.into_iter().next() else {
+            continue;
+        };
+
+        let score = score(db, account_id, &from, &subject, &labels, raw_headers.as_deref())?;
+        db.execute(
+            "INSERT OR IGNORE INTO email_focus (email_id, focused, score) VALUES (?1, ?2, ?3)",
+            params![email_id, score > 0.0, score],
+        )?;
+        classified += 1;
+    }
+    Ok(classified)
+}
+
+/// Place a message in Focused or Other and learn from it
+/// Returns the number of other messages that moved as a result.
+pub fn set_focus(db: &Database, email_id: i64, focused: bool) -> DbResult<usize> {
+    let email = db.get_email(email_id)?;
+    let features = Features::of(&email.from_address);
+
+    db.execute(
+        "INSERT INTO focus_signals (account_id, email_id, sender, focused) VALUES (?1, ?2, ?3, ?4)",
+        params![email.account_id, email_id, email.from_address.trim().to_lowercase(), focused],
+    )?;
+    let direction = if focused { 1.0 } else { -1.0 };
+    adjust(db, email.account_id, &features.sender, direction * SENDER_STEP)?;
+    adjust(db, email.account_id, &features.domain, direction * DOMAIN_STEP)?;
+
+    db.execute(
+        "INSERT INTO email_focus (email_id, focused, manual) VALUES (?1, ?2, 1)
+         ON CONFLICT(email_id) DO UPDATE SET focused = excluded.focused, manual = 1,
+             classified_at = datetime('now')",
+        params![email_id, focused],
+    )?;
+
+    // Mail from the same domain is scored again with the new weights
+    let domain = features.domain.trim_start_matches("domain:").to_string();
+    let related = db.query(
+        "SELECT e.id, e.from_address, e.subject, e.labels, e.raw_headers, m.focused
+         FROM email_focus m
+         JOIN emails e ON e.id = m.email_id
+         WHERE m.manual = 0 AND e.account_id = ?1 AND lower(e.from_address) LIKE ?2 ESCAPE '\\'",
+        params![email.account_id, format!("%@{}", escape_like(&domain))],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, bool>(5)?,
+            ))
+        },
+    )?;
+
+    let mut moved = 0;
+    for (id, from, subject, labels, raw_headers, was_focused) in related {
+        let score = score(db, email.account_id, &from, &subject, &labels, raw_headers.as_deref())?;
+        db.execute(
+            "UPDATE email_focus SET focused = ?1, score = ?2, classified_at = datetime('now') WHERE email_id = ?3",
+            params![score > 0.0, score, id],
+        )?;
+        if (score > 0.0) != was_focused {
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// UIDs of a folder's messages that are in Other
+pub fn other_uids(db: &Database, account_id: i64, folder: &str) -> DbResult<Vec<u32>> {
+    db.query(
+        "SELECT e.uid
+         FROM email_focus m
+         JOIN emails e ON e.id = m.email_id
+         JOIN folders f ON f.id = e.folder_id
+         WHERE e.account_id = ?1 AND f.remote_name = ?2 AND m.focused = 0 AND e.is_deleted = 0
+         ORDER BY e.uid DESC",
+        params![account_id, folder],
+        |row| row.get(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewEmail, NewFolder};
+
+    fn add_email(db: &Database, account_id: i64, folder_id: i64, uid: u32, from: &str, subject: &str) -> i64 {
+        db.upsert_email(&NewEmail {
+            from_address: from.to_string(),
+            subject: subject.to_string(),
+            ..test_email(account_id, folder_id, uid)
+        })
+        .unwrap()
+    }
+
+    fn is_focused(db: &Database, email_id: i64) -> bool {
+        db.query_row("SELECT focused FROM email_focus WHERE email_id = ?1", [email_id], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_corrections_train_sender_and_domain() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let inbox = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+
+        let person = add_email(&db, account_id, inbox, 1, "ana@partner.test", "Budget");
+        let newsletter = add_email(&db, account_id, inbox, 2, "newsletter@shop.test", "Weekly deals");
+        let colleague = add_email(&db, account_id, inbox, 3, "Bob@Partner.test", "Lunch");
+        assert_eq!(classify(&db, &[person, newsletter, colleague]).unwrap(), 3);
+        // Already classified
+        assert_eq!(classify(&db, &[person]).unwrap(), 0);
+        assert!(is_focused(&db, person) && is_focused(&db, colleague));
+        assert!(!is_focused(&db, newsletter));
+
+        // Moving one partner.test sender to Other also moves the other one
+        assert_eq!(set_focus(&db, person, false).unwrap(), 1);
+        assert!(!is_focused(&db, person) && !is_focused(&db, colleague));
+        assert_eq!(other_uids(&db, account_id, "INBOX").unwrap(), vec![3, 2, 1]);
+
+        // The sender weight outweighs the bulk prior for new mail
+        set_focus(&db, newsletter, true).unwrap();
+        set_focus(&db, newsletter, true).unwrap();
+        let next = add_email(&db, account_id, inbox, 4, "newsletter@shop.test", "More deals");
+        classify(&db, &[next]).unwrap();
+        assert!(is_focused(&db, next));
+
+        let signals: i64 = db.query_row("SELECT COUNT(*) FROM focus_signals", [], |row| row.get(0)).unwrap();
+        assert_eq!(signals, 3);
+    }
+}
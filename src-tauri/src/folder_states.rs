@@ -0,0 +1,139 @@
+//! Cached Folder States
+//!
+//! `email_list` tags each page with a token built from the folder's state
+//! (UIDVALIDITY, UIDNEXT, message count and HIGHESTMODSEQ) as read when the
+//! page was fetched. The state is kept here so a later list that sends the
+//! token back can answer `not_modified` without asking the server again.
+//!
+//! An entry is dropped when the folder watcher reports a change, when the app
+//! changes messages of the account itself, when the account disconnects and
+//! after `MAX_AGE`, so folders nobody watches are still re-read. A state read
+//! while the folder was being invalidated is not stored.
+
+use crate::events::{StoreEvent, StoreEvents};
+use crate::mail::FolderState;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// How long a state is trusted without a change notification
+pub const MAX_AGE: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Entry {
+    /// Bumped by every invalidation
+    generation: u64,
+    state: Option<(FolderState, Instant)>,
+}
+
+type Entries = HashMap<(String, String), Entry>;
+
+/// Last known state of each listed folder
+#[derive(Default)]
+pub struct FolderStates {
+    entries: Arc<Mutex<Entries>>,
+}
+
+fn lock(entries: &Mutex<Entries>) -> std::sync::MutexGuard<'_, Entries> {
+    entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn invalidate_in(entries: &mut Entries, account_id: &str, folder: Option<&str>) {
+    for ((account, name), entry) in entries.iter_mut() {
+        if account == account_id && folder.is_none_or(|folder| folder == name) {
+            entry.generation += 1;
+            entry.state = None;
+        }
+    }
+}
+
+impl FolderStates {
+    /// Cached state of a folder, unless it expired
+    pub fn get(&self, account_id: &str, folder: &str, now: Instant) -> Option<FolderState> {
+        let entries = lock(&self.entries);
+        let entry = entries.get(&(account_id.to_string(), folder.to_string()))?;
+        entry
+            .state
+            .filter(|(_, recorded)| now.saturating_duration_since(*recorded) < MAX_AGE)
+            .map(|(state, _)| state)
+    }
+
+    /// Generation to pass to `record` for a state about to be read
+    pub fn begin(&self, account_id: &str, folder: &str) -> u64 {
+        let mut entries = lock(&self.entries);
+        entries.entry((account_id.to_string(), folder.to_string())).or_default().generation
+    }
+
+    /// Store a state read since `begin` returned `generation`
+    /// Ignored when the folder was invalidated in between.
+    pub fn record(&self, account_id: &str, folder: &str, generation: u64, state: FolderState, now: Instant) {
+        let mut entries = lock(&self.entries);
+        let entry = entries.entry((account_id.to_string(), folder.to_string())).or_default();
+        if entry.generation == generation {
+            entry.state = Some((state, now));
+        }
+    }
+
+    /// Forget the state of one folder, or of all folders of the account
+    pub fn invalidate(&self, account_id: &str, folder: Option<&str>) {
+        invalidate_in(&mut lock(&self.entries), account_id, folder);
+    }
+
+    /// Invalidate folders whenever the app changes their messages
+    pub fn start(&self, store_events: &StoreEvents) {
+        let entries = self.entries.clone();
+        let mut receiver = store_events.subscribe();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(StoreEvent::EmailsChanged(change)) => {
+                        // A move also changes the target folder, so drop the account
+                        invalidate_in(&mut lock(&entries), &change.account_id.to_string(), None);
+                    }
+                    Ok(StoreEvent::PendingOpFailed(_)) => {}
+                    Err(broadcast::error::RecvError::Lagged(_)) => lock(&entries).clear(),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(messages: u32) -> FolderState {
+        FolderState { uid_validity: Some(1), uid_next: Some(messages + 1), messages, highest_modseq: 7 }
+    }
+
+    #[test]
+    fn test_cached_state_until_invalidated_or_expired() {
+        let states = FolderStates::default();
+        let now = Instant::now();
+        assert_eq!(states.get("1", "INBOX", now), None);
+
+        let generation = states.begin("1", "INBOX");
+        states.record("1", "INBOX", generation, state(3), now);
+        assert_eq!(states.get("1", "INBOX", now), Some(state(3)));
+        assert_eq!(states.get("1", "INBOX", now + MAX_AGE), None);
+
+        states.invalidate("1", Some("INBOX"));
+        assert_eq!(states.get("1", "INBOX", now), None);
+
+        // Invalidated while the state was being read: not stored
+        let generation = states.begin("1", "INBOX");
+        let other = states.begin("1", "Archive");
+        states.invalidate("1", None);
+        states.record("1", "INBOX", generation, state(4), now);
+        states.record("1", "Archive", other, state(1), now);
+        assert_eq!(states.get("1", "INBOX", now), None);
+        assert_eq!(states.get("1", "Archive", now), None);
+
+        let generation = states.begin("2", "INBOX");
+        states.record("2", "INBOX", generation, state(5), now);
+        states.invalidate("1", None);
+        assert_eq!(states.get("2", "INBOX", now), Some(state(5)));
+    }
+}
@@ -0,0 +1,425 @@
+//! Folder Watching
+//!
+//! Each connected account keeps one extra connection waiting for changes in
+//! its watched folders (the Inbox unless configured otherwise), so new mail
+//! shows up without polling. Servers with NOTIFY (RFC 5465) report changes in
+//! all watched folders on that one connection; elsewhere the watcher rotates
+//! IDLE over the folders, a slice of time each, and compares message counts
+//! when it comes back to a folder. Changes are reported per folder and the
+//! frontend syncs the folder in question.
+
+use crate::db::{Database, DbResult};
+use crate::mail::capabilities::ImapCapabilities;
+use crate::mail::{AsyncImapClient, MailResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Tauri event name for a change in a watched folder
+pub const FOLDER_CHANGED_EVENT: &str = "folder_watch://changed";
+
+/// Most folders one account may watch
+pub const MAX_WATCHED_FOLDERS: usize = 20;
+
+/// NOOP interval while waiting for NOTIFY events (servers drop idle connections after 30 minutes)
+const NOTIFY_KEEPALIVE: Duration = Duration::from_secs(10 * 60);
+
+/// IDLE time per folder when rotating over several
+const IDLE_SLICE: Duration = Duration::from_secs(60);
+
+/// IDLE time for a single folder (RFC 2177: re-issue at least every 29 minutes)
+const IDLE_SINGLE: Duration = Duration::from_secs(25 * 60);
+
+/// Wait before reconnecting a watcher whose connection failed
+pub const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Watched folders of an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "FolderWatchConfig"))]
+#[serde(rename_all = "camelCase")]
+pub struct WatchConfig {
+    pub enabled: bool,
+    /// Remote folder names
+    pub folders: Vec<String>,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            folders: vec!["INBOX".to_string()],
+        }
+    }
+}
+
+impl WatchConfig {
+    /// Trim and de-duplicate the folder list
+    pub fn normalize(mut self) -> Result<Self, String> {
+        let mut folders: Vec<String> = Vec::new();
+        for folder in self.folders.iter().map(|f| f.trim()).filter(|f| !f.is_empty()) {
+            if folder.chars().any(|c| c.is_control()) {
+                return Err(format!("Invalid folder name: {}", folder));
+            }
+            if !folders.iter().any(|f| f == folder) {
+                folders.push(folder.to_string());
+            }
+        }
+        if folders.len() > MAX_WATCHED_FOLDERS {
+            return Err(format!("At most {} folders can be watched", MAX_WATCHED_FOLDERS));
+        }
+        self.folders = folders;
+        Ok(self)
+    }
+}
+
+/// How an account's folders are watched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "FolderWatchMode"))]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMode {
+    /// All folders on one connection via NOTIFY
+    Notify,
+    /// IDLE on each folder in turn
+    RotatingIdle,
+    /// Disabled, nothing to watch, or the server supports neither
+    Off,
+}
+
+/// Pick the watch mode for a server
+pub fn mode(config: &WatchConfig, capabilities: Option<&ImapCapabilities>) -> WatchMode {
+    if !config.enabled || config.folders.is_empty() {
+        return WatchMode::Off;
+    }
+    match capabilities {
+        Some(caps) if caps.notify => WatchMode::Notify,
+        Some(caps) if caps.idle => WatchMode::RotatingIdle,
+        _ => WatchMode::Off,
+    }
+}
+
+fn quote(folder: &str) -> String {
+    format!("\"{}\"", folder.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// NOTIFY SET command for new, expunged and flag-changed messages in `folders`
+pub fn notify_command(folders: &[String]) -> String {
+    let mailboxes: Vec<String> = folders.iter().map(|f| quote(f)).collect();
+    format!(
+        "NOTIFY SET (mailboxes ({}) (MessageNew MessageExpunge FlagChange))",
+        mailboxes.join(" ")
+    )
+}
+
+/// Stored configuration (the default when none was saved)
+pub fn get_config(db: &Database, account_id: i64) -> DbResult<WatchConfig> {
+    let stored = db
+        .get_conn()?
+        .query_row(
+            "SELECT enabled, folders FROM folder_watch WHERE account_id = ?1",
+            [account_id],
+            |row| Ok((row.get::<_, bool>(0)?, row.get::<_, String>(1)?)),
+        )
+        .optional()?;
+    Ok(match stored {
+        Some((enabled, folders)) => WatchConfig {
+            enabled,
+            folders: serde_json::from_str(&folders).unwrap_or_default(),
+        },
+        None => WatchConfig::default(),
+    })
+}
+
+pub fn set_config(db: &Database, account_id: i64, config: &WatchConfig) -> DbResult<()> {
+    let folders = serde_json::to_string(&config.folders).unwrap_or_else(|_| "[]".to_string());
+    db.execute(
+        "INSERT INTO folder_watch (account_id, enabled, folders) VALUES (?1, ?2, ?3)
+         ON CONFLICT(account_id) DO UPDATE SET enabled = excluded.enabled, folders = excluded.folders",
+        params![account_id, config.enabled, folders],
+    )?;
+    Ok(())
+}
+
+/// Message count and next UID of a folder, compared between IDLE visits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FolderSnapshot {
+    pub exists: u32,
+    pub uid_next: Option<u32>,
+}
+
+/// Server side of a watcher (implemented by the IMAP client)
+#[async_trait]
+pub trait WatchSource: Send {
+    async fn notify_set(&mut self, folders: &[String]) -> MailResult<()>;
+    /// Folders reported changed within `timeout`; empty when nothing happened
+    async fn wait_notify(&mut self, timeout: Duration) -> MailResult<Vec<String>>;
+    async fn noop(&mut self) -> MailResult<()>;
+    /// Open a folder read-only
+    async fn examine(&mut self, folder: &str) -> MailResult<FolderSnapshot>;
+    /// IDLE in the open folder; true when the server reported a change
+    async fn idle(&mut self, timeout: Duration) -> MailResult<bool>;
+}
+
+#[async_trait]
+impl WatchSource for AsyncImapClient {
+    async fn notify_set(&mut self, folders: &[String]) -> MailResult<()> {
+        AsyncImapClient::notify_set(self, &notify_command(folders)).await
+    }
+
+    async fn wait_notify(&mut self, timeout: Duration) -> MailResult<Vec<String>> {
+        AsyncImapClient::wait_notify(self, timeout).await
+    }
+
+    async fn noop(&mut self) -> MailResult<()> {
+        AsyncImapClient::noop(self).await
+    }
+
+    async fn examine(&mut self, folder: &str) -> MailResult<FolderSnapshot> {
+        let (exists, uid_next) = self.examine_folder(folder).await?;
+        Ok(FolderSnapshot { exists, uid_next })
+    }
+
+    async fn idle(&mut self, timeout: Duration) -> MailResult<bool> {
+        self.idle_wait(timeout).await
+    }
+}
+
+/// Watch with NOTIFY until the connection fails
+pub async fn watch_notify<S, F>(source: &mut S, folders: &[String], on_change: F) -> MailResult<()>
+where
+    S: WatchSource + ?Sized,
+    F: Fn(&str),
+{
+    source.notify_set(folders).await?;
+    loop {
+        let changed = source.wait_notify(NOTIFY_KEEPALIVE).await?;
+        if changed.is_empty() {
+            source.noop().await?;
+        }
+        for folder in changed.iter().filter(|changed| folders.contains(*changed)) {
+            on_change(folder);
+        }
+    }
+}
+
+/// Rotate IDLE over the folders until the connection fails
+pub async fn watch_rotating<S, F>(source: &mut S, folders: &[String], on_change: F) -> MailResult<()>
+where
+    S: WatchSource + ?Sized,
+    F: Fn(&str),
+{
+    let slice = if folders.len() > 1 { IDLE_SLICE } else { IDLE_SINGLE };
+    let mut snapshots: HashMap<&str, FolderSnapshot> = HashMap::new();
+    loop {
+        for folder in folders {
+            let snapshot = source.examine(folder).await?;
+            // Changed while another folder had the connection
+            if snapshots.insert(folder, snapshot).is_some_and(|previous| previous != snapshot) {
+                on_change(folder);
+            }
+            if source.idle(slice).await? {
+                on_change(folder);
+                let snapshot = source.examine(folder).await?;
+                snapshots.insert(folder, snapshot);
+            }
+        }
+    }
+}
+
+/// Watcher state of an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "FolderWatchStatus"))]
+#[serde(rename_all = "camelCase")]
+pub struct WatchStatus {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub mode: WatchMode,
+    pub folders: Vec<String>,
+    pub connected: bool,
+    pub last_change_at: Option<DateTime<Utc>>,
+    /// Why the last connection ended
+    pub last_error: Option<String>,
+}
+
+impl WatchStatus {
+    pub fn new(account_id: i64, folders: &[String]) -> Self {
+        Self {
+            account_id,
+            mode: WatchMode::Off,
+            folders: folders.to_vec(),
+            connected: false,
+            last_change_at: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Change a watcher's shared status
+pub fn update_status(status: &Mutex<WatchStatus>, change: impl FnOnce(&mut WatchStatus)) {
+    change(&mut status.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+}
+
+/// Payload of `folder_watch://changed`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderChanged {
+    pub account_id: i64,
+    pub folder: String,
+}
+
+struct Watcher {
+    task: tauri::async_runtime::JoinHandle<()>,
+    status: Arc<Mutex<WatchStatus>>,
+}
+
+/// Running watchers, one per account
+#[derive(Default)]
+pub struct FolderWatchers {
+    watchers: Mutex<HashMap<i64, Watcher>>,
+}
+
+impl FolderWatchers {
+    /// Register a watcher task, stopping the account's previous one
+    pub fn start(&self, account_id: i64, task: tauri::async_runtime::JoinHandle<()>, status: Arc<Mutex<WatchStatus>>) {
+        let previous = self
+            .watchers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(account_id, Watcher { task, status });
+        if let Some(previous) = previous {
+            previous.task.abort();
+        }
+    }
+
+    pub fn stop(&self, account_id: i64) {
+        let removed = self
+            .watchers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&account_id);
+        if let Some(watcher) = removed {
+            watcher.task.abort();
+        }
+    }
+
+    pub fn status(&self, account_id: i64) -> Option<WatchStatus> {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&account_id)
+            .map(|watcher| watcher.status.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::MailError;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    /// Scripted server; fails with `Connection` once the script runs out
+    #[derive(Default)]
+    struct FakeServer {
+        notify_events: VecDeque<Vec<String>>,
+        snapshots: VecDeque<FolderSnapshot>,
+        idle_results: VecDeque<bool>,
+        commands: Vec<String>,
+    }
+
+    fn closed() -> MailError {
+        MailError::Connection("closed".to_string())
+    }
+
+    #[async_trait]
+    impl WatchSource for FakeServer {
+        async fn notify_set(&mut self, folders: &[String]) -> MailResult<()> {
+            self.commands.push(notify_command(folders));
+            Ok(())
+        }
+
+        async fn wait_notify(&mut self, _timeout: Duration) -> MailResult<Vec<String>> {
+            self.notify_events.pop_front().ok_or_else(closed)
+        }
+
+        async fn noop(&mut self) -> MailResult<()> {
+            self.commands.push("NOOP".to_string());
+            Ok(())
+        }
+
+        async fn examine(&mut self, folder: &str) -> MailResult<FolderSnapshot> {
+            self.commands.push(format!("EXAMINE {}", folder));
+            self.snapshots.pop_front().ok_or_else(closed)
+        }
+
+        async fn idle(&mut self, _timeout: Duration) -> MailResult<bool> {
+            self.idle_results.pop_front().ok_or_else(closed)
+        }
+    }
+
+    fn folders(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_config_and_mode() {
+        let config = WatchConfig {
+            enabled: true,
+            folders: folders(&[" INBOX ", "Work", "INBOX", ""]),
+        }
+        .normalize()
+        .unwrap();
+        assert_eq!(config.folders, folders(&["INBOX", "Work"]));
+        assert!(WatchConfig { enabled: true, folders: vec!["a\r\nb".to_string()] }.normalize().is_err());
+
+        let mut caps = ImapCapabilities { idle: true, ..Default::default() };
+        assert_eq!(mode(&config, Some(&caps)), WatchMode::RotatingIdle);
+        caps.notify = true;
+        assert_eq!(mode(&config, Some(&caps)), WatchMode::Notify);
+        assert_eq!(mode(&WatchConfig { enabled: false, ..config.clone() }, Some(&caps)), WatchMode::Off);
+        assert_eq!(mode(&config, None), WatchMode::Off);
+
+        assert_eq!(
+            notify_command(&folders(&["INBOX", "Clients \"A\""])),
+            "NOTIFY SET (mailboxes (\"INBOX\" \"Clients \\\"A\\\"\") (MessageNew MessageExpunge FlagChange))"
+        );
+
+        let db = Database::in_memory().expect("Failed to create test DB");
+        assert_eq!(get_config(&db, 1).unwrap(), WatchConfig::default());
+    }
+
+    #[tokio::test]
+    async fn test_notify_reports_watched_folders() {
+        let mut server = FakeServer {
+            notify_events: VecDeque::from(vec![folders(&["Work"]), vec![], folders(&["INBOX", "Other"])]),
+            ..Default::default()
+        };
+        let changed = RefCell::new(Vec::new());
+        let watched = folders(&["INBOX", "Work"]);
+        let result = watch_notify(&mut server, &watched, |f| changed.borrow_mut().push(f.to_string())).await;
+
+        assert!(result.is_err());
+        assert_eq!(changed.into_inner(), folders(&["Work", "INBOX"]));
+        assert_eq!(server.commands[1], "NOOP");
+    }
+
+    #[tokio::test]
+    async fn test_rotating_idle_detects_changes() {
+        let snapshot = |exists| FolderSnapshot { exists, uid_next: Some(exists + 1) };
+        let mut server = FakeServer {
+            // INBOX, Work, Work after IDLE data, INBOX unchanged, Work grown meanwhile
+            snapshots: VecDeque::from(vec![snapshot(5), snapshot(2), snapshot(3), snapshot(5), snapshot(4)]),
+            idle_results: VecDeque::from(vec![false, true, false]),
+            ..Default::default()
+        };
+        let changed = RefCell::new(Vec::new());
+        let watched = folders(&["INBOX", "Work"]);
+        let result = watch_rotating(&mut server, &watched, |f| changed.borrow_mut().push(f.to_string())).await;
+
+        assert!(result.is_err());
+        assert_eq!(changed.into_inner(), folders(&["Work", "Work"]));
+    }
+}
@@ -0,0 +1,189 @@
+//! Global Hotkeys
+//!
+//! System-wide shortcuts that work while the window is hidden in the tray:
+//! one opens the composer, one shows or hides the main window. The bindings
+//! are stored in the settings table and registered again whenever they
+//! change. A binding used twice is rejected when saving; one another program
+//! already holds is reported as a conflict and left unregistered, while the
+//! other binding still works.
+
+use crate::db::{Database, DbResult};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Settings key holding the hotkey configuration
+const CONFIG_SETTING: &str = "global_hotkeys";
+
+/// Frontend event opening the composer (shared with the tray menu)
+pub const COMPOSE_EVENT: &str = "tray:new-email";
+
+/// Payload of the compose event (sent as `null`)
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ComposeRequested;
+
+/// Action bound to a hotkey
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    Compose,
+    ToggleWindow,
+}
+
+/// Hotkey configuration stored in settings table
+/// Bindings use the `CommandOrControl+Shift+M` form; None leaves an action unbound.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyConfig {
+    pub enabled: bool,
+    pub compose: Option<String>,
+    pub toggle_window: Option<String>,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            compose: Some("CommandOrControl+Shift+M".to_string()),
+            toggle_window: Some("CommandOrControl+Shift+O".to_string()),
+        }
+    }
+}
+
+impl HotkeyConfig {
+    /// Bound actions with their parsed shortcuts
+    fn bindings(&self) -> Result<Vec<(HotkeyAction, Shortcut)>, String> {
+        let mut bindings: Vec<(HotkeyAction, Shortcut)> = Vec::new();
+        for (action, binding) in [
+            (HotkeyAction::Compose, &self.compose),
+            (HotkeyAction::ToggleWindow, &self.toggle_window),
+        ] {
+            let Some(binding) = binding.as_deref().map(str::trim).filter(|b| !b.is_empty()) else {
+                continue;
+            };
+            let shortcut = Shortcut::from_str(binding).map_err(|e| format!("Invalid shortcut {}: {}", binding, e))?;
+            if bindings.iter().any(|(_, other)| *other == shortcut) {
+                return Err(format!("{} is assigned to more than one action", binding));
+            }
+            bindings.push((action, shortcut));
+        }
+        Ok(bindings)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.bindings().map(|_| ())
+    }
+}
+
+/// Binding that could not be registered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyConflict {
+    pub action: HotkeyAction,
+    pub shortcut: String,
+    pub error: String,
+}
+
+/// Outcome of registering the hotkeys
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyStatus {
+    pub registered: Vec<HotkeyAction>,
+    pub conflicts: Vec<HotkeyConflict>,
+}
+
+pub fn load_config(db: &Database) -> DbResult<HotkeyConfig> {
+    Ok(db.get_setting(CONFIG_SETTING)?.unwrap_or_default())
+}
+
+pub fn save_config(db: &Database, config: &HotkeyConfig) -> DbResult<()> {
+    db.set_setting(CONFIG_SETTING, config)
+}
+
+fn run<R: Runtime>(app: &AppHandle<R>, action: HotkeyAction) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    let visible = window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+    match action {
+        HotkeyAction::ToggleWindow if visible && window.is_focused().unwrap_or(false) => {
+            let _ = window.hide();
+        }
+        _ => {
+            let _ = window.show();
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+    }
+    if action == HotkeyAction::Compose {
+        crate::events::emit(&window, &ComposeRequested);
+    }
+}
+
+/// Replace the registered hotkeys with the configured ones
+pub fn apply<R: Runtime>(app: &AppHandle<R>, config: &HotkeyConfig) -> Result<HotkeyStatus, String> {
+    let global_shortcut = app.global_shortcut();
+    global_shortcut
+        .unregister_all()
+        .map_err(|e| format!("Failed to unregister hotkeys: {}", e))?;
+
+    let mut status = HotkeyStatus::default();
+    if !config.enabled {
+        return Ok(status);
+    }
+
+    for (action, shortcut) in config.bindings()? {
+        let registered = global_shortcut.on_shortcut(shortcut, move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                run(app, action);
+            }
+        });
+        match registered {
+            Ok(()) => status.registered.push(action),
+            Err(e) => {
+                log::warn!("Hotkey {} for {:?} is unavailable: {}", shortcut, action, e);
+                status.conflicts.push(HotkeyConflict {
+                    action,
+                    shortcut: shortcut.to_string(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings() {
+        let config = HotkeyConfig::default();
+        let bindings = config.bindings().unwrap();
+        assert_eq!(bindings.iter().map(|(action, _)| *action).collect::<Vec<_>>(), [
+            HotkeyAction::Compose,
+            HotkeyAction::ToggleWindow
+        ]);
+
+        let unbound = HotkeyConfig { toggle_window: Some(" ".to_string()), ..config.clone() };
+        assert_eq!(unbound.bindings().unwrap().len(), 1);
+
+        let invalid = HotkeyConfig { compose: Some("Ctrl+Shift+Nope".to_string()), ..config.clone() };
+        assert!(invalid.validate().is_err());
+
+        // The same keys written differently still collide
+        let duplicate = HotkeyConfig {
+            compose: Some("Ctrl+Shift+M".to_string()),
+            toggle_window: Some("shift+control+m".to_string()),
+            ..config
+        };
+        assert!(duplicate.validate().is_err());
+    }
+}
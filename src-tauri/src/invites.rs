@@ -0,0 +1,152 @@
+//! Meeting Invites
+//!
+//! Invites sent with `email_send` are recorded with their attendees, so the
+//! replies that come back (iTIP `METHOD:REPLY` parts, see `mail::calendar`)
+//! can be tallied per invite: who accepted, declined or has not answered yet.
+//! A reply only updates attendees the invite was sent to; anything else in it
+//! is ignored.
+
+use crate::db::{Database, DbResult};
+use crate::mail::calendar::{CalendarPart, NewInvite};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct InviteAttendee {
+    pub email: String,
+    pub partstat: String,
+    pub responded_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct Invite {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub uid: String,
+    pub title: String,
+    pub start: String,
+    pub end: String,
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub created_at: String,
+    pub attendees: Vec<InviteAttendee>,
+}
+
+/// Globally unique VEVENT UID in the account's domain
+pub fn new_uid(account_email: &str) -> String {
+    let domain = account_email.rsplit_once('@').map_or("owlivion.local", |(_, domain)| domain);
+    format!("{}@{}", uuid::Uuid::new_v4(), domain)
+}
+
+/// Remember an invite that was sent, every attendee still to answer
+pub fn record_sent(db: &Database, account_id: i64, uid: &str, invite: &NewInvite, attendees: &[String]) -> DbResult<i64> {
+    let invite_id = db.execute_insert(
+        "INSERT INTO invites (account_id, uid, title, start_at, end_at, all_day, location)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![account_id, uid, invite.title.trim(), invite.start, invite.end, invite.all_day, invite.location],
+    )?;
+    for attendee in attendees {
+        db.execute(
+            "INSERT OR IGNORE INTO invite_attendees (invite_id, email) VALUES (?1, ?2)",
+            params![invite_id, attendee.trim().to_lowercase()],
+        )?;
+    }
+    Ok(invite_id)
+}
+
+/// Apply a REPLY to the invite it answers; returns the attendees updated
+/// Only the sender's own answer counts, so nobody can reply for someone else.
+pub fn record_reply(db: &Database, account_id: i64, sender: &str, part: &CalendarPart) -> DbResult<usize> {
+    if part.method != "REPLY" {
+        return Ok(0);
+    }
+    let mut updated = 0;
+    for attendee in part.attendees.iter().filter(|attendee| attendee.email.eq_ignore_ascii_case(sender.trim())) {
+        updated += db.execute(
+            "UPDATE invite_attendees SET partstat = ?1, responded_at = datetime('now')
+             WHERE email = ?2 AND partstat != ?1
+               AND invite_id = (SELECT id FROM invites WHERE account_id = ?3 AND uid = ?4)",
+            params![attendee.partstat, attendee.email, account_id, part.uid],
+        )?;
+    }
+    Ok(updated)
+}
+
+/// Invites sent from an account with their attendees' answers, newest first
+pub fn list(db: &Database, account_id: i64) -> DbResult<Vec<Invite>> {
+    let mut invites = db.query(
+        "SELECT id, account_id, uid, title, start_at, end_at, all_day, location, created_at
+         FROM invites WHERE account_id = ?1 ORDER BY created_at DESC, id DESC",
+        params![account_id],
+        |row| {
+            Ok(Invite {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                uid: row.get(2)?,
+                title: row.get(3)?,
+                start: row.get(4)?,
+                end: row.get(5)?,
+                all_day: row.get(6)?,
+                location: row.get(7)?,
+                created_at: row.get(8)?,
+                attendees: Vec::new(),
+            })
+        },
+    )?;
+    for invite in &mut invites {
+        invite.attendees = db.query(
+            "SELECT email, partstat, responded_at FROM invite_attendees WHERE invite_id = ?1 ORDER BY email",
+            params![invite.id],
+            |row| Ok(InviteAttendee { email: row.get(0)?, partstat: row.get(1)?, responded_at: row.get(2)? }),
+        )?;
+    }
+    Ok(invites)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+    use crate::mail::calendar::AttendeeStatus;
+
+    #[test]
+    fn test_replies_update_attendees() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let invite = NewInvite {
+            title: "Planning".to_string(),
+            start: "2024-03-08T15:00:00+03:00".to_string(),
+            end: "2024-03-08T16:00:00+03:00".to_string(),
+            all_day: false,
+            location: None,
+            description: None,
+        };
+        let uid = new_uid("me@test.com");
+        assert!(uid.ends_with("@test.com"));
+        record_sent(&db, account_id, &uid, &invite, &["Ana@Test.com".to_string(), "bob@test.com".to_string()]).unwrap();
+
+        let reply = |uid: &str, email: &str| CalendarPart {
+            method: "REPLY".to_string(),
+            uid: uid.to_string(),
+            summary: None,
+            attendees: vec![AttendeeStatus { email: email.to_string(), partstat: "ACCEPTED".to_string() }],
+        };
+        assert_eq!(record_reply(&db, account_id, "Ana@test.com", &reply(&uid, "ana@test.com")).unwrap(), 1);
+        // Same answer again, someone not invited, answering for someone else, another invite
+        assert_eq!(record_reply(&db, account_id, "ana@test.com", &reply(&uid, "ana@test.com")).unwrap(), 0);
+        assert_eq!(record_reply(&db, account_id, "eve@test.com", &reply(&uid, "eve@test.com")).unwrap(), 0);
+        assert_eq!(record_reply(&db, account_id, "eve@test.com", &reply(&uid, "bob@test.com")).unwrap(), 0);
+        assert_eq!(record_reply(&db, account_id, "bob@test.com", &reply("other-uid", "bob@test.com")).unwrap(), 0);
+
+        let invites = list(&db, account_id).unwrap();
+        assert_eq!(invites[0].attendees[0].partstat, "ACCEPTED");
+        assert!(invites[0].attendees[0].responded_at.is_some());
+        assert_eq!(invites[0].attendees[1].partstat, "NEEDS-ACTION");
+    }
+}
@@ -0,0 +1,287 @@
+//! Operation Journal
+//!
+//! Write-ahead journal for operations that take several steps: a direct send
+//! (SMTP, then the Sent copy), a queued server change (run on the server, then
+//! local bookkeeping) and a filter batch run (many messages, one at a time).
+//! Each records its intent before the first step and its progress after each
+//! one, and removes its entry when done. An entry still present at startup
+//! belongs to an operation interrupted by a crash; `recover` completes it or
+//! rolls it back:
+//!
+//! - A send that was not confirmed by the server is queued again. A message
+//!   accepted just before the crash may thus go out twice, but is never lost.
+//! - A send the server accepted only lacks its Sent copy, which is logged.
+//! - A queued change the server already ran gets its local bookkeeping done
+//!   and leaves the queue instead of running twice (no second COPY of a
+//!   moved message). One that did not run stays queued and is retried.
+//! - A filter batch is resumed after the last message it finished, once the
+//!   app is running (see `interrupted_filter_batches`).
+
+use crate::db::{Database, DbError, DbResult};
+use crate::pending_ops::{self, PendingOperation, PendingOps, QueuedSend};
+use rusqlite::params;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Kind of journaled operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalKind {
+    /// Message sent directly from the composer; payload is the `QueuedSend`
+    Send,
+    /// Entry of the pending operation queue (flag change, move, delete or
+    /// queued send); shares the operation's id
+    PendingOperation,
+    /// Filter run over existing messages; payload is a `FilterBatch`
+    FilterBatch,
+}
+
+impl JournalKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Send => "send",
+            Self::PendingOperation => "pending_operation",
+            Self::FilterBatch => "filter_batch",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [Self::Send, Self::PendingOperation, Self::FilterBatch]
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+    }
+}
+
+/// How far an operation got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalStep {
+    /// Nothing confirmed by the server yet
+    Started,
+    /// The server accepted the operation; local follow-up steps remain
+    RemoteDone,
+}
+
+impl JournalStep {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Started => "started",
+            Self::RemoteDone => "remote_done",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        [Self::Started, Self::RemoteDone].into_iter().find(|step| step.as_str() == value)
+    }
+}
+
+/// Intent of a filter batch run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterBatch {
+    pub account_id: i64,
+    /// Single filter to apply; all enabled filters if None
+    pub filter_id: Option<i64>,
+    /// Folder to process; the whole account if None
+    pub folder_id: Option<i64>,
+}
+
+/// Journaled operation that has not finished
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub id: String,
+    pub kind: JournalKind,
+    pub account_id: Option<i64>,
+    pub step: JournalStep,
+    payload: String,
+    progress: Option<String>,
+    pub started_at: String,
+}
+
+impl JournalEntry {
+    /// Decode the recorded intent
+    pub fn payload<T: DeserializeOwned>(&self) -> DbResult<T> {
+        serde_json::from_str(&self.payload).map_err(|e| DbError::Serialization(e.to_string()))
+    }
+
+    /// Decode the recorded progress (None before the first record)
+    pub fn progress<T: DeserializeOwned>(&self) -> DbResult<Option<T>> {
+        self.progress
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|e| DbError::Serialization(e.to_string()))
+    }
+}
+
+/// Record the intent of an operation before its first step
+/// A retried operation reusing its id starts over.
+pub fn begin<T: Serialize>(
+    db: &Database,
+    id: &str,
+    kind: JournalKind,
+    account_id: Option<i64>,
+    payload: &T,
+) -> DbResult<()> {
+    let payload = serde_json::to_string(payload).map_err(|e| DbError::Serialization(e.to_string()))?;
+    db.execute(
+        "INSERT OR REPLACE INTO operation_journal (id, kind, account_id, payload, step) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, kind.as_str(), account_id, payload, JournalStep::Started.as_str()],
+    )?;
+    Ok(())
+}
+
+/// Record that an operation reached a step
+pub fn advance(db: &Database, id: &str, step: JournalStep) -> DbResult<()> {
+    db.execute(
+        "UPDATE operation_journal SET step = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![step.as_str(), id],
+    )?;
+    Ok(())
+}
+
+/// Record progress within the current step
+pub fn record_progress<T: Serialize>(db: &Database, id: &str, progress: &T) -> DbResult<()> {
+    let progress = serde_json::to_string(progress).map_err(|e| DbError::Serialization(e.to_string()))?;
+    db.execute(
+        "UPDATE operation_journal SET progress = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![progress, id],
+    )?;
+    Ok(())
+}
+
+/// Remove the entry of a finished (or abandoned) operation
+pub fn finish(db: &Database, id: &str) -> DbResult<()> {
+    db.execute("DELETE FROM operation_journal WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Unfinished operations, oldest first
+pub fn interrupted(db: &Database) -> DbResult<Vec<JournalEntry>> {
+    db.query(
+        "SELECT id, kind, account_id, step, payload, progress, started_at
+         FROM operation_journal ORDER BY started_at, rowid",
+        [],
+        |row| {
+            let kind: String = row.get(1)?;
+            let kind = JournalKind::parse(&kind).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, format!("Unknown kind {}", kind).into())
+            })?;
+            let step: String = row.get(3)?;
+            let step = JournalStep::parse(&step).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, format!("Unknown step {}", step).into())
+            })?;
+            Ok(JournalEntry {
+                id: row.get(0)?,
+                kind,
+                account_id: row.get(2)?,
+                step,
+                payload: row.get(4)?,
+                progress: row.get(5)?,
+                started_at: row.get(6)?,
+            })
+        },
+    )
+}
+
+/// Complete or roll back sends and queued changes interrupted by a crash
+/// Filter batches are left for `interrupted_filter_batches`. Returns the
+/// number of recovered entries.
+pub fn recover(db: &Database, pending: &PendingOps) -> DbResult<usize> {
+    let mut recovered = 0;
+    for entry in interrupted(db)? {
+        match (entry.kind, entry.step) {
+            (JournalKind::Send, JournalStep::Started) => {
+                let account_id = entry
+                    .account_id
+                    .ok_or_else(|| DbError::Constraint(format!("Journaled send {} has no account", entry.id)))?;
+                let message: QueuedSend = entry.payload()?;
+                log::warn!("Send {} was interrupted, queueing the message again", entry.id);
+                pending.enqueue(&PendingOperation::send(&account_id.to_string(), message))?;
+            }
+            (JournalKind::Send, JournalStep::RemoteDone) => {
+                log::warn!("Send {} was interrupted after delivery; its Sent copy may be missing", entry.id);
+            }
+            // Still queued (retried by the queue) or already settled
+            (JournalKind::PendingOperation, JournalStep::Started) => {}
+            (JournalKind::PendingOperation, JournalStep::RemoteDone) => {
+                if pending_ops::complete(db, &entry.id)? {
+                    log::info!("Completed pending operation {} the server ran before a crash", entry.id);
+                }
+            }
+            (JournalKind::FilterBatch, _) => continue,
+        }
+        finish(db, &entry.id)?;
+        recovered += 1;
+    }
+    Ok(recovered)
+}
+
+/// Filter batch runs interrupted by a crash, with the id of the last message
+/// each finished
+pub fn interrupted_filter_batches(db: &Database) -> DbResult<Vec<(String, FilterBatch, Option<i64>)>> {
+    interrupted(db)?
+        .into_iter()
+        .filter(|entry| entry.kind == JournalKind::FilterBatch)
+        .map(|entry| Ok((entry.id.clone(), entry.payload()?, entry.progress()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+    use std::sync::Arc;
+
+    fn message(subject: &str) -> QueuedSend {
+        QueuedSend {
+            to: vec!["bob@test.com".to_string()],
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            subject: subject.to_string(),
+            text_body: Some("Hi".to_string()),
+            html_body: None,
+            attachment_paths: Vec::new(),
+            on_behalf_of: None,
+        }
+    }
+
+    #[test]
+    fn test_recover_interrupted_operations() {
+        let db = Arc::new(Database::in_memory().expect("Failed to create test DB"));
+        let account_id = test_account(&db);
+        let pending = PendingOps::new(db.clone());
+
+        // Send cut off before the server confirmed it: queued again
+        begin(&db, "send-1", JournalKind::Send, Some(account_id), &message("Lost")).unwrap();
+        // Send the server accepted: not sent twice
+        begin(&db, "send-2", JournalKind::Send, Some(account_id), &message("Delivered")).unwrap();
+        advance(&db, "send-2", JournalStep::RemoteDone).unwrap();
+
+        // Queued send the server accepted before the queue entry was removed
+        let queued = PendingOperation::send(&account_id.to_string(), message("Queued"));
+        pending.enqueue(&queued).unwrap();
+        begin(&db, &queued.id, JournalKind::PendingOperation, Some(account_id), &queued.kind).unwrap();
+        advance(&db, &queued.id, JournalStep::RemoteDone).unwrap();
+
+        let batch = FilterBatch { account_id, filter_id: Some(7), folder_id: None };
+        begin(&db, "batch-1", JournalKind::FilterBatch, Some(account_id), &batch).unwrap();
+        record_progress(&db, "batch-1", &42i64).unwrap();
+        // A finished operation leaves nothing behind
+        begin(&db, "send-3", JournalKind::Send, Some(account_id), &message("Done")).unwrap();
+        finish(&db, "send-3").unwrap();
+
+        assert_eq!(interrupted(&db).unwrap().len(), 4);
+        assert_eq!(recover(&db, &pending).unwrap(), 3);
+
+        let queue = pending_ops::load_queue(&db).unwrap();
+        assert_eq!(queue.len(), 1);
+        assert!(matches!(&queue[0].kind, pending_ops::PendingOpKind::Send { message } if message.subject == "Lost"));
+
+        assert_eq!(
+            interrupted_filter_batches(&db).unwrap(),
+            vec![("batch-1".to_string(), batch, Some(42))]
+        );
+        assert_eq!(recover(&db, &pending).unwrap(), 0);
+    }
+}
@@ -2,14 +2,80 @@
 //!
 //! A modern, AI-powered email client built with Tauri and React.
 
+pub mod account_migration;
+pub mod activity;
+pub mod ai;
+pub mod aliases;
+pub mod attachment_browser;
+pub mod attachment_store;
+pub mod authz;
+pub mod autostart;
+pub mod backfill;
+pub mod badge;
+pub mod blocklist;
+pub mod body_backfill;
+pub mod bounces;
 pub mod cache;
+pub mod capabilities;
+pub mod cleanup;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod connectivity;
+pub mod credentials;
 pub mod crypto;
 pub mod db;
+pub mod default_mailer;
+pub mod delegation;
+pub mod deep_links;
+pub mod diagnostics;
+pub mod digest;
+pub mod domain_policy;
+pub mod event_extract;
+pub mod events;
+pub mod feeds;
 pub mod filters;
+pub mod folder_states;
+pub mod folder_watch;
+pub mod focus;
+pub mod hotkeys;
+pub mod invites;
+pub mod journal;
+pub mod links;
+pub mod list_snapshot;
 pub mod mail;
+pub mod mailbox_stats;
+pub mod mailmerge;
+pub mod maintenance;
+pub mod message_versions;
+pub mod metrics;
+pub mod notes;
+pub mod notifications;
 pub mod oauth;
+pub mod onboarding;
+pub mod pending_ops;
+pub mod plugins;
+pub mod portable;
+pub mod prefetch;
+pub mod profiles;
+pub mod refresh_schedule;
+pub mod rekey;
+pub mod reply_needed;
+pub mod search_index;
+pub mod send_quota;
+pub mod sent_copy;
+pub mod settings;
+pub mod spam;
+pub mod startup;
 pub mod sync;
+pub mod temp_upload;
+pub mod templates;
+pub mod thread_export;
+pub mod thread_summary;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod tray;
+pub mod uid_validity;
+pub mod undo;
 
 use db::{Database, EmailSummary, EmailTemplate, NewAccount as DbNewAccount, NewEmailTemplate};
 use mail::{fetch_autoconfig, fetch_autoconfig_debug, AsyncImapClient, AutoConfig, AutoConfigDebug, ImapClient, ImapConfig, SecurityType};
@@ -119,13 +185,78 @@ pub struct AppState {
     sync_manager: Arc<StdMutex<Option<sync::SyncManager>>>,
     background_scheduler: Arc<sync::BackgroundScheduler>,
     email_cache: cache::EmailCache,
+    store_events: events::StoreEvents,
+    settings_events: settings::SettingsEvents,
+    pending_ops: pending_ops::PendingOps,
+    connectivity: connectivity::Connectivity,
+    backfills: backfill::RunningBackfills,
+    migrations: account_migration::RunningMigrations,
+    mail_merges: mailmerge::RunningMerges,
+    folder_watchers: folder_watch::FolderWatchers,
+    feeds: feeds::FeedPoller,
+    digests: digest::DigestScheduler,
+    replies: reply_needed::ReplyTracker,
+    search_index: search_index::SearchIndexMaintainer,
+    maintenance: maintenance::MaintenanceScheduler,
+    spam_trainer: spam::SpamTrainer,
+    badge: badge::BadgeUpdater,
+    refresh_scheduler: refresh_schedule::RefreshScheduler,
+    /// Registration result of the global hotkeys
+    hotkeys: StdMutex<hotkeys::HotkeyStatus>,
+    /// Opened links the frontend has not handled yet
+    deep_links: deep_links::PendingLinks,
+    /// Result of the startup integrity check
+    startup_recovery: db::recovery::RecoveryReport,
+    /// Profile whose data this instance uses
+    profile: String,
+    ai_limiter: ai::RateLimiter,
+    attachment_store: attachment_store::AttachmentStore,
+    /// Decrypted account passwords for hot paths
+    credentials: Arc<credentials::CredentialCache>,
+    /// Bodies read ahead when a folder is opened
+    prefetch: Arc<prefetch::PrefetchCache>,
+    undo: undo::UndoRegistry,
+    /// Confirmation tokens for destructive commands
+    confirmations: authz::Confirmations,
+    /// Recent `email_list_changes` pages to diff refreshes against
+    list_snapshots: list_snapshot::ListSnapshots,
+    folder_states: folder_states::FolderStates,
+    /// Attachments being uploaded from the composer in chunks
+    temp_uploads: temp_upload::TempUploads,
+    startup: startup::StartupSync,
 }
 
 impl AppState {
-    pub fn new(db: Database) -> Self {
+    pub fn new(db: Database, attachment_store: attachment_store::AttachmentStore) -> Self {
         let db_arc = Arc::new(db);
         let sync_manager = Arc::new(StdMutex::new(Some(sync::SyncManager::new(db_arc.clone()))));
         let background_scheduler = Arc::new(sync::BackgroundScheduler::new(db_arc.clone()));
+        let pending_ops = pending_ops::PendingOps::new(db_arc.clone());
+        let connectivity = connectivity::Connectivity::new();
+        if let Err(e) = connectivity.load(&db_arc) {
+            log::warn!("Failed to load paused accounts: {}", e);
+        }
+        let credentials = Arc::new(credentials::CredentialCache::new());
+        if let Err(e) = credentials.load(&db_arc) {
+            log::warn!("Failed to load account sign-in failures: {}", e);
+        }
+        if let Err(e) = mailmerge::recover_interrupted(&db_arc) {
+            log::warn!("Failed to pause interrupted mail merges: {}", e);
+        }
+        match journal::recover(&db_arc, &pending_ops) {
+            Ok(0) => {}
+            Ok(recovered) => log::info!("Recovered {} interrupted operations", recovered),
+            Err(e) => log::warn!("Failed to recover interrupted operations: {}", e),
+        }
+
+        let feeds = feeds::FeedPoller::new(db_arc.clone());
+        let digests = digest::DigestScheduler::new(db_arc.clone());
+        let replies = reply_needed::ReplyTracker::new(db_arc.clone());
+        let search_index = search_index::SearchIndexMaintainer::new(db_arc.clone());
+        let maintenance = maintenance::MaintenanceScheduler::new(db_arc.clone());
+        let spam_trainer = spam::SpamTrainer::new(db_arc.clone());
+        let badge = badge::BadgeUpdater::new(db_arc.clone());
+        let refresh_scheduler = refresh_schedule::RefreshScheduler::new(db_arc.clone());
 
         Self {
             db: db_arc,
@@ -134,6 +265,36 @@ impl AppState {
             sync_manager,
             background_scheduler,
             email_cache: cache::EmailCache::new(),
+            store_events: events::StoreEvents::new(),
+            settings_events: settings::SettingsEvents::new(),
+            pending_ops,
+            connectivity,
+            backfills: backfill::RunningBackfills::default(),
+            migrations: account_migration::RunningMigrations::default(),
+            mail_merges: mailmerge::RunningMerges::default(),
+            folder_watchers: folder_watch::FolderWatchers::default(),
+            feeds,
+            digests,
+            replies,
+            search_index,
+            maintenance,
+            spam_trainer,
+            badge,
+            refresh_scheduler,
+            hotkeys: StdMutex::new(hotkeys::HotkeyStatus::default()),
+            deep_links: deep_links::PendingLinks::default(),
+            startup_recovery: db::recovery::RecoveryReport::Healthy,
+            profile: profiles::DEFAULT_PROFILE.to_string(),
+            ai_limiter: ai::RateLimiter::default(),
+            attachment_store,
+            credentials,
+            prefetch: Arc::new(prefetch::PrefetchCache::new()),
+            undo: undo::UndoRegistry::new(),
+            confirmations: authz::Confirmations::default(),
+            temp_uploads: temp_upload::TempUploads::new(portable::temp_dir().join("owlivion-mail-attachments")),
+            list_snapshots: list_snapshot::ListSnapshots::default(),
+            folder_states: folder_states::FolderStates::default(),
+            startup: startup::StartupSync::default(),
         }
     }
 
@@ -373,6 +534,43 @@ fn get_current_folder_safe(
 // Database Sync Helpers
 // ============================================================================
 
+/// Convert a fetched envelope summary into a row for the local cache
+fn summary_to_new_email(account_id: i64, folder_id: i64, email_summary: &mail::EmailSummary) -> db::NewEmail {
+    db::NewEmail {
+        account_id,
+        folder_id,
+        message_id: email_summary.message_id.clone().unwrap_or_else(|| format!("uid-{}", email_summary.uid)),
+        uid: email_summary.uid,
+        from_address: email_summary.from.clone(),
+        from_name: email_summary.from_name.clone(),
+        to_addresses: "[]".to_string(),
+        cc_addresses: "[]".to_string(),
+        bcc_addresses: "[]".to_string(),
+        reply_to: None,
+        subject: email_summary.subject.clone(),
+        preview: email_summary.preview.clone(),
+        body_text: None,
+        body_html: None,
+        date: email_summary.date.clone(),
+        is_read: email_summary.is_read,
+        is_starred: email_summary.is_starred,
+        is_deleted: false,
+        is_spam: false,
+        is_draft: false,
+        is_answered: false,
+        is_forwarded: false,
+        has_attachments: email_summary.has_attachments,
+        has_inline_images: false,
+        thread_id: None,
+        in_reply_to: None,
+        references_header: None,
+        raw_headers: None,
+        raw_size: email_summary.size as i32,
+        priority: 3,
+        labels: "[]".to_string(),
+    }
+}
+
 /// Sync folder information to database
 /// Creates or updates folder record and returns folder_id
 fn sync_folder_to_db(
@@ -492,7 +690,7 @@ fn sync_email_to_db(
         in_reply_to: None,
         references_header: None,
         raw_headers: None,
-        raw_size: 0,
+        raw_size: email_summary.size as i32,
         priority: 3,
         labels: "[]".to_string(),
     };
@@ -505,6 +703,99 @@ fn sync_email_to_db(
     Ok((email_id, true)) // New email inserted
 }
 
+/// Apply a server-confirmed change to the local cache and notify all open views
+fn record_email_change(
+    state: &AppState,
+    account_id: &str,
+    folder: &str,
+    uids: Vec<u32>,
+    change: events::EmailChange,
+) {
+    let Ok(account_id_num) = account_id.parse::<i64>() else {
+        log::warn!("Not recording {:?}: invalid account ID '{}'", change, account_id);
+        return;
+    };
+
+    let email_ids = events::apply_email_change(&state.db, account_id_num, folder, &uids, &change)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to update local cache for {:?}: {}", change, e);
+            Vec::new()
+        });
+
+    state.store_events.notify(events::EmailsChanged {
+        account_id: account_id_num,
+        folder: Some(folder.to_string()),
+        email_ids,
+        uids,
+        change,
+    });
+}
+
+/// Apply an email mutation optimistically and queue the IMAP command
+fn queue_email_operation(
+    state: &AppState,
+    account_id: &str,
+    folder: &str,
+    uid: u32,
+    kind: pending_ops::PendingOpKind,
+) -> Result<pending_ops::PendingOperation, String> {
+    let op = pending_ops::apply_local(&state.db, &state.store_events, account_id, folder, uid, kind)
+        .map_err(|e| format!("Failed to update email: {}", e))?;
+    state.pending_ops.enqueue(&op)
+        .map_err(|e| format!("Failed to queue operation: {}", e))?;
+    Ok(op)
+}
+
+/// Record a destructive action in the activity log (failures are only logged)
+fn log_activity(
+    state: &AppState,
+    account_id: Option<i64>,
+    action: activity::ActivityAction,
+    summary: &str,
+    undo: Option<activity::UndoPayload>,
+) {
+    if let Err(e) = activity::record(&state.db, account_id, action, summary, undo.as_ref()) {
+        log::warn!("Failed to record activity: {}", e);
+    }
+}
+
+/// Executes queued email operations (IMAP via the shared sessions, sends via SMTP)
+struct MailOpExecutor {
+    app: tauri::AppHandle,
+}
+
+#[async_trait::async_trait]
+impl pending_ops::RemoteExecutor for MailOpExecutor {
+    async fn execute(&self, op: &pending_ops::PendingOperation) -> mail::MailResult<()> {
+        let state = self.app.state::<AppState>();
+        let account_id: i64 = op.account_id.parse()
+            .map_err(|_| mail::MailError::Config("Invalid account ID".to_string()))?;
+
+        // Offline, paused or waiting for a new password: keep the operation queued
+        if !state.connectivity.may_sync(account_id) || state.credentials.needs_attention(account_id) {
+            return Err(mail::MailError::NotConnected);
+        }
+
+        if let pending_ops::PendingOpKind::Send { message } = &op.kind {
+            return deliver_email(&state.db, &state.credentials, account_id, message, Some(&op.id)).await;
+        }
+
+        let mut async_clients = state.async_imap_clients.lock().await;
+        let client = async_clients
+            .get_mut(&op.account_id)
+            .ok_or(mail::MailError::NotConnected)?;
+
+        match &op.kind {
+            pending_ops::PendingOpKind::SetRead { value } => client.set_read(&op.folder, op.uid, *value).await,
+            pending_ops::PendingOpKind::SetStarred { value } => client.set_starred(&op.folder, op.uid, *value).await,
+            pending_ops::PendingOpKind::SetKeyword { keyword, value } => client.set_keyword(&op.folder, op.uid, keyword, *value).await,
+            pending_ops::PendingOpKind::Move { target } => client.move_email(&op.folder, op.uid, target).await.and_then(mail::MoveReport::into_result),
+            pending_ops::PendingOpKind::Delete { permanent } => client.delete_email(&op.folder, op.uid, *permanent).await,
+            pending_ops::PendingOpKind::Send { .. } => unreachable!("sends are handled above"),
+        }
+    }
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -783,6 +1074,7 @@ async fn account_add(
     let account_id = state.db.add_account(&new_account)
         .map_err(|e| format!("Database error: {}", e))?;
 
+    state.refresh_scheduler.request();
     log::info!("Account added with ID: {}", account_id);
     Ok(account_id.to_string())
 }
@@ -836,6 +1128,9 @@ async fn account_update(
 
     state.db.update_account(id, &updated_account)
         .map_err(|e| format!("Database error: {}", e))?;
+    // New password: forget the old one and allow sign-ins again
+    state.credentials.reset(&state.db, id)
+        .map_err(|e| format!("Database error: {}", e))?;
 
     log::info!("Account updated: {}", id);
     Ok(())
@@ -921,25 +1216,29 @@ async fn account_list(state: State<'_, AppState>) -> Result<Vec<db::Account>, St
 /// Connect to an account (used when app starts or reconnecting)
 /// SECURITY: Validates stored configuration before connecting
 #[tauri::command]
-async fn account_connect(state: State<'_, AppState>, account_id: String) -> Result<(), String> {
+async fn account_connect(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    account_id: String,
+) -> Result<(), String> {
     log::info!("Connecting to account: {}", account_id);
     let id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
 
     let account = state.db.get_account(id)
         .map_err(|_| "Database error".to_string())?;
+    if !account.is_active {
+        return Err("Account is disabled. Enable it to connect.".to_string());
+    }
 
     // SECURITY: Validate stored host and port before connecting
     validate_host(&account.imap_host)?;
     validate_port(account.imap_port as u16)?;
     validate_security_type(&account.imap_security)?;
 
-    let encrypted_password = state.db.get_account_password(id)
-        .map_err(|_| "Database error".to_string())?
-        .ok_or_else(|| "No password stored".to_string())?;
-
-    // Decrypt password (or access token for OAuth)
-    let mut password = crypto::decrypt_password(&encrypted_password)
-        .map_err(|_| "Password decryption failed".to_string())?;
+    // Decrypted password (or access token for OAuth)
+    let mut password = state.credentials.password(&state.db, id)
+        .map_err(|e| e.to_string())?
+        .to_string();
 
     // Check if OAuth token needs refresh
     if account.oauth_provider.is_some() {
@@ -977,6 +1276,7 @@ async fn account_connect(state: State<'_, AppState>, account_id: String) -> Resu
 
                             state.db.update_oauth_access_token(id, &encrypted_new_token)
                                 .map_err(|e| format!("Database error: {}", e))?;
+                            state.credentials.insert(id, &result.access_token);
 
                             // Update expiry time (1 hour from now)
                             let new_expires_at = chrono::Utc::now().timestamp() + 3600;
@@ -1018,19 +1318,148 @@ async fn account_connect(state: State<'_, AppState>, account_id: String) -> Resu
 
     // Create async IMAP client only (sync client has parser issues)
     let mut async_client = AsyncImapClient::new(config);
-    async_client.connect().await.map_err(|e| sanitize_error_message(&e.to_string()))?;
+    let connected = async_client.connect().await;
+    state.credentials.record_login(&state.db, id, &connected);
+    connected.map_err(|e| sanitize_error_message(&e.to_string()))?;
+    async_client.set_trash_folder(state.db.get_special_folder(id, "trash").ok().flatten());
+    record_imap_capabilities(&state.db, id, &async_client);
 
     // Store async client
     let mut async_clients = state.async_imap_clients.lock().await;
     async_clients.insert(account_id.clone(), async_client);
+    drop(async_clients);
+
+    // Replay operations queued while the account was offline
+    state.pending_ops.wake();
+
+    // First connect imports history; later connects resume unfinished backfills
+    resume_backfills(&app_handle, &state, id, account.sync_days);
+    spawn_folder_watch(&app_handle, &state, id);
 
     log::info!("Account connected successfully");
     Ok(())
 }
 
-/// Delete an account
+/// Sign-in failure state of an account (needs a new password?)
+#[tauri::command]
+async fn account_get_auth_state(state: State<'_, AppState>, account_id: i64) -> Result<credentials::AuthState, String> {
+    credentials::auth_state(&state.db, account_id)
+        .map_err(|e| format!("Failed to load sign-in state: {}", e))
+}
+
+/// Server extensions of an account (IDLE, MOVE, SMTP size limit, ...)
+/// Probes the servers when nothing is cached yet, the SMTP reply is stale, or `refresh` is set.
+#[tauri::command]
+async fn account_get_capabilities(
+    state: State<'_, AppState>,
+    account_id: i64,
+    refresh: Option<bool>,
+) -> Result<capabilities::AccountCapabilities, String> {
+    let refresh = refresh.unwrap_or(false);
+    let cached = capabilities::get(&state.db, account_id)
+        .map_err(|e| format!("Failed to load capabilities: {}", e))?;
+
+    if refresh || cached.imap.is_none() {
+        let mut client = connect_fresh_imap(&state.db, &state.credentials, account_id).await?;
+        let _ = client.disconnect().await;
+    }
+
+    if refresh || cached.smtp_is_stale(chrono::Utc::now()) {
+        let account = state.db.get_account(account_id)
+            .map_err(|e| format!("Failed to get account: {}", e))?;
+        let smtp = mail::capabilities::probe_smtp(
+            &account.smtp_host,
+            account.smtp_port as u16,
+            parse_security(&account.smtp_security),
+            account.accept_invalid_certs,
+        )
+        .await
+        .map_err(|e| format!("Failed to probe SMTP server: {}", e))?;
+        capabilities::store_smtp(&state.db, account_id, &smtp)
+            .map_err(|e| format!("Failed to save capabilities: {}", e))?;
+    }
+
+    capabilities::get(&state.db, account_id)
+        .map_err(|e| format!("Failed to load capabilities: {}", e))
+}
+
+/// Ask the user to confirm a destructive action in a native dialog
+/// Returns the token the action's command requires, or None when the user declined.
+#[tauri::command]
+async fn confirm_request(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    scope: authz::Scope,
+    target: String,
+) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let id = |value: &str| value.parse::<i64>().map_err(|_| "Invalid confirmation target".to_string());
+    let account_email = |value: &str| -> Result<String, String> {
+        state.db.get_account(id(value)?).map(|account| account.email).map_err(|_| "Account not found".to_string())
+    };
+    let pair = || target.split_once('@').ok_or_else(|| "Invalid confirmation target".to_string());
+    let label = match scope {
+        authz::Scope::AccountDelete | authz::Scope::FilterImport => account_email(&target)?,
+        authz::Scope::SyncRollback => match target.split_once('@') {
+            Some((data_type, version)) => format!("{}, sürüm {}", data_type, version),
+            None => target.clone(),
+        },
+        authz::Scope::CryptoRekey | authz::Scope::SyncRotateMasterPassword => String::new(),
+        authz::Scope::CleanupExecute => {
+            let (account_id, action_id) = pair()?;
+            let batch = cleanup::batch(&state.db, id(account_id)?, action_id)
+                .map_err(|e| format!("Failed to load cleanup batch: {}", e))?
+                .ok_or("This suggestion is out of date. Refresh the suggestions and try again.")?;
+            format!("{}, {} ileti", account_email(account_id)?, batch.email_ids.len())
+        }
+        authz::Scope::SenderBlockApply => blocklist::get(&state.db, id(&target)?)
+            .map(|blocked| blocked.pattern)
+            .map_err(|e| format!("Failed to get blocked sender: {}", e))?,
+        authz::Scope::LocalFolderDelete => state.db.get_folder_by_id(id(&target)?)
+            .map(|folder| folder.name)
+            .map_err(|e| format!("Failed to get local folder: {}", e))?,
+        authz::Scope::AccountMigrate => {
+            let (source_id, target_id) = pair()?;
+            format!("{} hesabından {} hesabına", account_email(source_id)?, account_email(target_id)?)
+        }
+        authz::Scope::MailmergeStart => {
+            let (account_id, template_id) = pair()?;
+            let template = state.db.get_template(id(template_id)?)
+                .map_err(|e| format!("Failed to get template: {}", e))?;
+            format!("{} şablonu ({})", template.name, account_email(account_id)?)
+        }
+        authz::Scope::TemplateImport | authz::Scope::DiagnosticsExport => target.clone(),
+        authz::Scope::ProfileSwitch => profiles::Profiles::open_default()?
+            .list()?
+            .into_iter()
+            .find(|profile| profile.id == target)
+            .map(|profile| profile.name)
+            .ok_or("Profile not found")?,
+    };
+
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    app_handle
+        .dialog()
+        .message(scope.prompt(&label))
+        .title("Owlivion Mail")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(scope.accept_label().to_string(), "İptal".to_string()))
+        .show(move |accepted| {
+            let _ = sender.send(accepted);
+        });
+    if !receiver.await.unwrap_or(false) {
+        return Ok(None);
+    }
+    Ok(Some(state.confirmations.issue(scope, &target, Instant::now())))
+}
+
+/// Delete an account (requires an `account_delete` confirmation token)
 #[tauri::command]
-async fn account_delete(state: State<'_, AppState>, account_id: String) -> Result<(), String> {
+async fn account_delete(state: State<'_, AppState>, account_id: String, confirm_token: String) -> Result<(), String> {
+    state
+        .confirmations
+        .consume(authz::Scope::AccountDelete, &account_id, &confirm_token, Instant::now())?;
     log::info!("Deleting account: {}", account_id);
     let id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
 
@@ -1038,21 +1467,52 @@ async fn account_delete(state: State<'_, AppState>, account_id: String) -> Resul
     let mut async_clients = state.async_imap_clients.lock().await;
     async_clients.remove(&account_id);
     drop(async_clients);
+    state.folder_watchers.stop(id);
+    state.folder_states.invalidate(&account_id, None);
+
+    let email = state.db.get_account(id).map(|account| account.email).unwrap_or_default();
 
     // Delete from database
     state.db.delete_account(id)
         .map_err(|e| format!("Database error: {}", e))?;
+    state.credentials.invalidate(id);
+    state.prefetch.clear_account(id);
+    state.badge.request();
+    state.refresh_scheduler.request();
+    log_activity(&state, Some(id), activity::ActivityAction::AccountRemove, &format!("Removed account {}", email), None);
 
     log::info!("Account {} deleted successfully", account_id);
     Ok(())
 }
 
+/// Addresses an account may send on behalf of (delegated or shared mailboxes)
+#[tauri::command]
+async fn account_get_send_on_behalf(state: State<'_, AppState>, account_id: i64) -> Result<Vec<String>, String> {
+    delegation::allowed(&state.db, account_id)
+        .map_err(|e| format!("Failed to load delegated addresses: {}", e))
+}
+
+/// Set the addresses an account may send on behalf of
+/// The server must grant the account this right; it is not checked here.
+#[tauri::command]
+async fn account_set_send_on_behalf(
+    state: State<'_, AppState>,
+    account_id: i64,
+    addresses: Vec<String>,
+) -> Result<Vec<String>, String> {
+    for address in &addresses {
+        validate_email(address.trim())?;
+    }
+    delegation::set_allowed(&state.db, account_id, &addresses)
+        .map_err(|e| format!("Failed to save delegated addresses: {}", e))
+}
+
 /// Get folders for an account
 #[tauri::command]
 async fn folder_list(
     state: State<'_, AppState>,
     account_id: String,
-) -> Result<Vec<mail::Folder>, String> {
+) -> Result<Vec<mail::folder_tree::FolderNode>, String> {
     log::info!("Listing folders for account: {}", account_id);
 
     let mut async_clients = state.async_imap_clients.lock().await;
@@ -1062,21 +1522,175 @@ async fn folder_list(
         .ok_or_else(|| "Account not connected".to_string())?;
 
     let folders = client.list_folders().await.map_err(|e| e.to_string())?;
+    drop(async_clients);
+
+    // Remember the special-use mapping so sync, filters and delete use the
+    // server's Sent/Trash/... even when their names are localized
+    store_listed_folders(&state, &account_id, &folders);
 
     log::info!("Found {} folders for account {}", folders.len(), account_id);
-    Ok(folders)
+    Ok(mail::folder_tree::build_tree(folders))
+}
+
+/// Get the shared and other users' folders of an account, one tree per namespace
+#[tauri::command]
+async fn folder_list_shared(
+    state: State<'_, AppState>,
+    account_id: String,
+) -> Result<Vec<mail::namespace::SharedTree>, String> {
+    let mut async_clients = state.async_imap_clients.lock().await;
+
+    let client = async_clients
+        .get_mut(&account_id)
+        .ok_or_else(|| "Account not connected".to_string())?;
+
+    let (namespaces, folders) = client.list_shared_folders().await
+        .map_err(|e| format!("Failed to list shared folders: {}", e))?;
+    drop(async_clients);
+
+    // Cached like the account's own folders so they can be opened and synced
+    store_listed_folders(&state, &account_id, &folders);
+
+    Ok(mail::namespace::shared_trees(&namespaces, &folders))
+}
+
+/// Cache folders as listed by the server
+fn store_listed_folders(state: &AppState, account_id: &str, folders: &[mail::Folder]) {
+    if let Ok(id) = account_id.parse::<i64>() {
+        for folder in folders {
+            let result = state.db.upsert_folder(&db::NewFolder {
+                account_id: id,
+                name: folder.name.clone(),
+                remote_name: folder.path.clone(),
+                folder_type: folder.folder_type.as_db_str().to_string(),
+                is_subscribed: folder.is_subscribed,
+                is_selectable: folder.is_selectable,
+                delimiter: folder.delimiter.clone(),
+            });
+            if let Err(e) = result {
+                log::warn!("Failed to store folder '{}': {}", folder.path, e);
+            }
+        }
+        if let Err(e) = state.db.link_folder_parents(id) {
+            log::warn!("Failed to link folder hierarchy: {}", e);
+        }
+    }
+}
+
+/// Cached IMAP folder by remote path
+fn find_remote_folder(state: &AppState, account_id: &str, path: &str) -> Result<db::Folder, String> {
+    let id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+
+    state
+        .db
+        .get_folders(id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .into_iter()
+        .find(|f| !f.is_local && f.remote_name == path)
+        .ok_or_else(|| "Folder not found, refresh the folder list".to_string())
+}
+
+/// Rename or move an IMAP folder together with its children
+/// Returns the folder's new path
+async fn relocate_folder(
+    state: &AppState,
+    account_id: &str,
+    folder: &db::Folder,
+    new_path: &str,
+) -> Result<String, String> {
+    let path = folder.remote_name.as_str();
+
+    if path.eq_ignore_ascii_case("INBOX") {
+        return Err("The inbox cannot be renamed or moved".to_string());
+    }
+    if path == new_path {
+        return Ok(new_path.to_string());
+    }
+    if mail::folder_tree::is_within(new_path, path, &folder.delimiter) {
+        return Err("A folder cannot be moved into itself".to_string());
+    }
+
+    {
+        let mut async_clients = state.async_imap_clients.lock().await;
+        let client = async_clients
+            .get_mut(account_id)
+            .ok_or_else(|| "Account not connected".to_string())?;
+        client
+            .rename_folder(path, new_path)
+            .await
+            .map_err(|e| format!("Failed to rename folder: {}", e))?;
+    }
+
+    let renamed = state
+        .db
+        .rename_folder_subtree(folder.account_id, path, new_path)
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    // Keep the open folder selected under its new path
+    {
+        let mut current = state.current_folder.lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(open) = current.get_mut(account_id) {
+            if let Some(rebased) = mail::folder_tree::rebase_path(open, path, new_path, &folder.delimiter) {
+                *open = rebased;
+            }
+        }
+    }
+
+    log::info!("Renamed folder '{}' to '{}' ({} folders updated)", path, new_path, renamed);
+    Ok(new_path.to_string())
+}
+
+/// Rename a folder in place (children keep their place below it)
+#[tauri::command]
+async fn folder_rename(
+    state: State<'_, AppState>,
+    account_id: String,
+    path: String,
+    new_name: String,
+) -> Result<String, String> {
+    let folder = find_remote_folder(&state, &account_id, &path)?;
+    let new_name = new_name.trim();
+
+    if new_name.is_empty() || new_name.contains(folder.delimiter.as_str()) {
+        return Err("Invalid folder name".to_string());
+    }
+
+    let parent = mail::folder_tree::parent_path(&path, &folder.delimiter);
+    let new_path = mail::folder_tree::join_path(parent, new_name, &folder.delimiter);
+    relocate_folder(&state, &account_id, &folder, &new_path).await
+}
+
+/// Move a folder (and its children) under another folder, or to the top level
+#[tauri::command]
+async fn folder_move(
+    state: State<'_, AppState>,
+    account_id: String,
+    path: String,
+    new_parent: Option<String>,
+) -> Result<String, String> {
+    let folder = find_remote_folder(&state, &account_id, &path)?;
+
+    let name = mail::folder_tree::leaf_name(&path, &folder.delimiter);
+    let new_path = mail::folder_tree::join_path(new_parent.as_deref(), name, &folder.delimiter);
+    relocate_folder(&state, &account_id, &folder, &new_path).await
 }
 
 /// Fetch emails with pagination
+/// Passing back the `state_token` of the last result returns `not_modified`
+/// without fetching while the folder's cached state (see `folder_states`)
+/// still matches it.
 /// SECURITY: Enforces pagination limits to prevent DoS
 #[tauri::command]
 async fn email_list(
     state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
     account_id: String,
     folder: Option<String>,
     page: u32,
     page_size: u32,
-) -> Result<mail::FetchResult, String> {
+    state_token: Option<String>,
+) -> Result<mail::EmailListPage, String> {
     // SECURITY: Enforce pagination limits
     let safe_page_size = page_size.min(MAX_PAGE_SIZE).max(1);
 
@@ -1103,6 +1717,24 @@ async fn email_list(
 
     let client = async_clients.get_mut(&account_id).unwrap();
 
+    // Skip the fetch when the folder is in the state the caller's page was read in
+    if let Some(folder_state) = state.folder_states.get(&account_id, &folder_path, Instant::now()) {
+        let token = folder_state.token(&account_id, &folder_path, page, safe_page_size);
+        if state_token.as_ref() == Some(&token) {
+            log::info!("email_list: {} page {} not modified", folder_path, page);
+            return Ok(mail::EmailListPage {
+                result: mail::FetchResult {
+                    emails: Vec::new(),
+                    total: folder_state.messages,
+                    has_more: page.saturating_add(1).saturating_mul(safe_page_size) < folder_state.messages,
+                },
+                state_token: Some(token),
+                not_modified: true,
+            });
+        }
+    }
+    let generation = state.folder_states.begin(&account_id, &folder_path);
+
     log::info!("Calling fetch_emails for folder='{}', page={}, size={}", folder_path, page, safe_page_size);
     let result = client
         .fetch_emails(&folder_path, page, safe_page_size)
@@ -1112,6 +1744,20 @@ async fn email_list(
             format!("Failed to fetch emails: {}", e)
         })?;
 
+    // One STATUS gives both the new token and the UIDVALIDITY check
+    let folder_state = client.folder_state(&folder_path).await.unwrap_or_else(|e| {
+        log::warn!("Failed to read state of {}: {}", folder_path, e);
+        None
+    });
+    let uid_validity = match folder_state {
+        Some(folder_state) => folder_state.uid_validity,
+        None => current_uid_validity(client, &folder_path).await,
+    };
+    let current_token = folder_state.map(|folder_state| {
+        state.folder_states.record(&account_id, &folder_path, generation, folder_state, Instant::now());
+        folder_state.token(&account_id, &folder_path, page, safe_page_size)
+    });
+
     // Release IMAP lock before DB operations
     drop(async_clients);
 
@@ -1125,62 +1771,35 @@ async fn email_list(
             e
         })
         .unwrap_or(1); // Fallback to ID 1 if folder sync fails
+    reset_on_uid_validity_change(&state, &app_handle, account_id_num, folder_id, &folder_path, uid_validity).await;
 
     // OPTIMIZATION: Batch sync emails to database (10-50x faster)
     let mut new_email_ids = Vec::new();
 
     if !result.emails.is_empty() {
         // Convert EmailSummary to NewEmail batch
-        let new_emails: Vec<db::NewEmail> = result.emails.iter().map(|email_summary| {
-            db::NewEmail {
-                account_id: account_id_num,
-                folder_id,
-                message_id: email_summary.message_id.clone().unwrap_or_else(|| format!("uid-{}", email_summary.uid)),
-                uid: email_summary.uid,
-                from_address: email_summary.from.clone(),
-                from_name: email_summary.from_name.clone(),
-                to_addresses: "[]".to_string(),
-                cc_addresses: "[]".to_string(),
-                bcc_addresses: "[]".to_string(),
-                reply_to: None,
-                subject: email_summary.subject.clone(),
-                preview: email_summary.preview.clone(),
-                body_text: None,
-                body_html: None,
-                date: email_summary.date.clone(),
-                is_read: email_summary.is_read,
-                is_starred: email_summary.is_starred,
-                is_deleted: false,
-                is_spam: false,
-                is_draft: false,
-                is_answered: false,
-                is_forwarded: false,
-                has_attachments: email_summary.has_attachments,
-                has_inline_images: false,
-                thread_id: None,
-                in_reply_to: None,
-                references_header: None,
-                raw_headers: None,
-                raw_size: 0,
-                priority: 3,
-                labels: "[]".to_string(),
-            }
-        }).collect();
+        let new_emails: Vec<db::NewEmail> = result.emails.iter()
+            .map(|email_summary| summary_to_new_email(account_id_num, folder_id, email_summary))
+            .collect();
 
         // Batch upsert (transaction-based, very fast)
         match state.db.batch_upsert_emails(&new_emails) {
             Ok(email_ids) => {
+                mail::keywords::store_all(&state.db, account_id_num, folder_id, &result.emails);
                 new_email_ids = email_ids;
                 log::info!("✓ Batch synced {} emails to DB (folder_id={})", new_emails.len(), folder_id);
+                state.badge.request();
             }
             Err(e) => log::warn!("Failed to batch sync emails: {}", e),
         }
     }
 
+    classify_focus(&state.db, &new_email_ids);
+
     // Apply filters to new emails automatically
     if !new_email_ids.is_empty() {
         use filters::FilterEngine;
-        let engine = FilterEngine::new(state.db.clone());
+        let engine = FilterEngine::new(state.db.clone()).with_events(state.store_events.clone());
         let mut filters_applied = 0;
 
         for email_id in new_email_ids {
@@ -1206,6 +1825,11 @@ async fn email_list(
         }
     }
 
+    // Opening a folder reads its newest unread messages ahead
+    if page == 0 {
+        start_prefetch(&state, account_id_num, &folder_path, &result.emails);
+    }
+
     // Add account metadata to all emails (for unified inbox compatibility)
     let mut result_with_account_id = result;
     for email in &mut result_with_account_id.emails {
@@ -1213,110 +1837,347 @@ async fn email_list(
     }
 
     log::info!("✓ email_list SUCCESS: returning {} emails (total={}) with account_id={}", result_with_account_id.emails.len(), result_with_account_id.total, account_id);
-    Ok(result_with_account_id)
+    Ok(mail::EmailListPage {
+        result: result_with_account_id,
+        state_token: current_token,
+        not_modified: false,
+    })
 }
 
-/// Sync emails with automatic filter application
-/// Fetches emails, saves to database, and applies filters
+/// Listing the list view shows, sent back by `email_list_changes`
+#[derive(Debug, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct ListSince {
+    #[cfg_attr(test, ts(type = "number"))]
+    snapshot_id: u64,
+    /// `state_token` returned with the snapshot
+    state_token: Option<String>,
+}
+
+/// Fetch a list page and return only what changed since the `since` snapshot
+/// Without a known snapshot the whole page comes back (`full`). With the
+/// snapshot's `state_token` an unchanged folder is not fetched again.
 #[tauri::command]
-async fn email_sync_with_filters(
+async fn email_list_changes(
     state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
     account_id: String,
     folder: Option<String>,
     page: u32,
     page_size: u32,
-) -> Result<EmailSyncResult, String> {
-    // SECURITY: Enforce pagination limits
+    since: Option<ListSince>,
+) -> Result<list_snapshot::EmailListChanges, String> {
+    let folder_path = folder.unwrap_or_else(|| "INBOX".to_string());
     let safe_page_size = page_size.min(MAX_PAGE_SIZE).max(1);
+    let since_snapshot = since.as_ref().map(|since| since.snapshot_id);
+    // The token only helps while the snapshot it belongs to is still kept
+    let base = since_snapshot
+        .and_then(|id| state.list_snapshots.emails(id, &account_id, &folder_path, page, safe_page_size));
+    let state_token = since.and_then(|since| since.state_token);
+    let mut listed = email_list(
+        state.clone(),
+        app_handle,
+        account_id.clone(),
+        Some(folder_path.clone()),
+        page,
+        safe_page_size,
+        state_token.filter(|_| base.is_some()),
+    )
+    .await?;
+    if listed.not_modified {
+        listed.result.emails = base.unwrap_or_default();
+    }
 
-    log::info!("Syncing emails with filters: account {} folder {:?}", account_id, folder);
-    let folder_path = folder.unwrap_or_else(|| "INBOX".to_string());
+    let mut changes = state.list_snapshots.record(
+        &account_id,
+        &folder_path,
+        page,
+        safe_page_size,
+        listed.result.emails,
+        listed.result.total,
+        listed.result.has_more,
+        since_snapshot,
+    );
+    changes.state_token = listed.state_token;
+    Ok(changes)
+}
 
-    // Parse account_id
-    let account_id_num: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+/// Startup sync steps over the shared IMAP sessions
+struct AppStartup {
+    app: tauri::AppHandle,
+}
 
-    // Sync folder to database (create if not exists)
-    let folder_id = sync_folder_to_db(&state.db, account_id_num, &folder_path)?;
+#[async_trait::async_trait]
+impl startup::StartupSteps for AppStartup {
+    async fn connect(&self, account_id: i64) -> Result<(), String> {
+        account_connect(self.app.state(), self.app.clone(), account_id.to_string()).await
+    }
 
-    // Fetch emails
-    let mut async_clients = state.async_imap_clients.lock().await;
-    let client = async_clients
-        .get_mut(&account_id)
-        .ok_or("Account not connected")?;
+    async fn inbox_page(&self, account_id: i64, page: u32, page_size: u32) -> Result<mail::FetchResult, String> {
+        let list = email_list(
+            self.app.state(),
+            self.app.clone(),
+            account_id.to_string(),
+            Some("INBOX".to_string()),
+            page,
+            page_size,
+            None,
+        )
+        .await?;
+        Ok(list.result)
+    }
 
-    let result = client
-        .fetch_emails(&folder_path, page, safe_page_size)
-        .await
-        .map_err(|e| format!("Failed to fetch emails: {}", e))?;
+    fn progress(&self, progress: startup::StartupProgress) {
+        events::emit(&self.app, &progress);
+    }
 
-    drop(async_clients); // Release lock
+    fn inbox(&self, inbox: startup::StartupInbox) {
+        events::emit(&self.app, &inbox);
+    }
+}
 
-    // OPTIMIZATION: Batch sync emails to database
-    let mut new_email_ids = Vec::new();
-    let mut filters_applied_count = 0;
-    let mut new_emails_count = 0;
+/// Connect the accounts at startup: the default one first with its Inbox
+/// streamed, then the others a few at a time
+/// Returns the plan right away; steps arrive as `startup://progress` and
+/// `startup://inbox` events. None when the startup sync already ran.
+#[tauri::command]
+async fn startup_sync(state: State<'_, AppState>, app_handle: tauri::AppHandle) -> Result<Option<startup::StartupPlan>, String> {
+    let accounts = state.db.get_all_accounts()
+        .map_err(|e| format!("Failed to get accounts: {}", e))?;
+    if !state.startup.begin() {
+        return Ok(None);
+    }
+    let plan = startup::plan(accounts.iter().map(|account| (account.id, account.is_default)));
 
-    if !result.emails.is_empty() {
-        // Check existing UIDs to identify new emails
-        let uids: Vec<u32> = result.emails.iter().map(|e| e.uid).collect();
-        let uid_placeholders = vec!["?"; uids.len()].join(",");
-        let existing_query = format!(
-            "SELECT uid FROM emails WHERE account_id = ? AND folder_id = ? AND uid IN ({})",
-            uid_placeholders
-        );
+    let task_plan = plan.clone();
+    tauri::async_runtime::spawn(async move {
+        startup::run(&task_plan, &AppStartup { app: app_handle }).await;
+    });
+    Ok(Some(plan))
+}
 
-        let existing_uids: std::collections::HashSet<u32> = {
-            let conn = state.db.get_conn().map_err(|e| format!("DB error: {}", e))?;
-            let mut stmt = conn.prepare(&existing_query).map_err(|e| format!("Query error: {}", e))?;
-            let mut params: Vec<&dyn rusqlite::ToSql> = vec![&account_id_num, &folder_id];
-            for uid in &uids {
-                params.push(uid);
-            }
+/// UIDVALIDITY of a folder, or None when the server does not report it
+async fn current_uid_validity(client: &mut mail::AsyncImapClient, folder: &str) -> Option<u32> {
+    client.uid_validity(folder).await.unwrap_or_else(|e| {
+        log::warn!("Failed to read UIDVALIDITY of {}: {}", folder, e);
+        None
+    })
+}
+
+/// Quarantine the folder's cache and start a re-sync when its UIDVALIDITY changed
+/// Must run before the fetched page is stored, so new UIDs never land on stale rows.
+async fn reset_on_uid_validity_change(
+    state: &AppState,
+    app_handle: &tauri::AppHandle,
+    account_id: i64,
+    folder_id: i64,
+    folder: &str,
+    uid_validity: Option<u32>,
+) {
+    let Some(current) = uid_validity else {
+        return;
+    };
+    let previous = match uid_validity::check(&state.db, account_id, folder_id, current) {
+        Ok(Some(previous)) => previous,
+        Ok(None) => return,
+        Err(e) => {
+            log::warn!("Failed to check UIDVALIDITY of {}: {}", folder, e);
+            return;
+        }
+    };
+
+    log::warn!(
+        "UIDVALIDITY of {} for account {} changed ({} -> {}); re-syncing the folder",
+        folder, account_id, previous, current
+    );
+    let quarantined = match uid_validity::quarantine(&state.db, account_id, folder_id, previous) {
+        Ok(quarantined) => quarantined,
+        Err(e) => {
+            log::warn!("Failed to quarantine cached messages of {}: {}", folder, e);
+            return;
+        }
+    };
+    state.email_cache.clear().await;
+    state.badge.request();
+
+    let report = uid_validity::ResyncReport {
+        account_id,
+        folder: folder.to_string(),
+        previous_uid_validity: previous,
+        uid_validity: current,
+        quarantined,
+        refreshed: 0,
+        unmatched: quarantined,
+    };
+    spawn_uid_validity_resync(app_handle.clone(), folder_id, report);
+}
+
+/// Fetch the whole sync window of a reset folder again, then report how many
+/// quarantined messages came back
+fn spawn_uid_validity_resync(app_handle: tauri::AppHandle, folder_id: i64, mut report: uid_validity::ResyncReport) {
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        let (account_id, folder) = (report.account_id, report.folder.clone());
+        if state.backfills.try_begin(account_id, &folder) {
+            let result = match state.db.get_account(account_id) {
+                Ok(account) => match connect_fresh_imap(&state.db, &state.credentials, account_id).await {
+                    Ok(mut client) => {
+                        let since = backfill::window_start(account.sync_days, chrono::Utc::now().date_naive());
+                        let result = backfill::run(
+                            &state.db,
+                            &mut client,
+                            account_id,
+                            &folder,
+                            since,
+                            || state.connectivity.may_sync(account_id),
+                            |_| {},
+                        )
+                        .await;
+                        let _ = client.disconnect().await;
+                        result.map(|_| ())
+                    }
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(format!("Failed to get account: {}", e)),
+            };
+            if let Err(e) = result {
+                log::warn!("Re-sync of {} for account {} failed: {}", folder, account_id, e);
+            }
+            state.backfills.finish(account_id, &folder);
+        }
+
+        match uid_validity::unmatched(&state.db, account_id, folder_id, report.previous_uid_validity) {
+            Ok(unmatched) => {
+                report.refreshed = report.quarantined.saturating_sub(unmatched);
+                report.unmatched = unmatched;
+            }
+            Err(e) => log::warn!("Failed to count quarantined messages of {}: {}", folder, e),
+        }
+        log::info!(
+            "Re-synced {} for account {}: {} of {} cached messages refreshed",
+            folder, account_id, report.refreshed, report.quarantined
+        );
+        events::emit(&app_handle, &report);
+        state.badge.request();
+    });
+}
+
+/// Sort newly synced inbox mail into Focused and Other
+fn classify_focus(db: &Database, email_ids: &[i64]) {
+    if let Err(e) = focus::classify(db, email_ids) {
+        log::warn!("Failed to classify focus of new mail: {}", e);
+    }
+}
+
+/// Fetch the newest unread bodies of a folder in the background
+/// Skipped while the account cannot sync or the network is metered.
+fn start_prefetch(state: &AppState, account_id: i64, folder: &str, emails: &[mail::EmailSummary]) {
+    if !state.connectivity.may_sync(account_id) || state.connectivity.is_metered() {
+        return;
+    }
+    let settings = match prefetch::load_settings(&state.db) {
+        Ok(settings) if settings.enabled => settings,
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("Failed to load prefetch settings: {}", e);
+            return;
+        }
+    };
+    let uids = state.prefetch.candidates(account_id, folder, emails, settings.max_messages);
+    if uids.is_empty() {
+        return;
+    }
+
+    let db = state.db.clone();
+    let credentials = state.credentials.clone();
+    let cache = state.prefetch.clone();
+    let folder = folder.to_string();
+    tauri::async_runtime::spawn(async move {
+        let cached = cache
+            .prefetch(&settings, account_id, &folder, uids, || connect_fresh_imap(&db, &credentials, account_id))
+            .await;
+        log::debug!("Prefetched {} message(s) in {} for account {}", cached, folder, account_id);
+    });
+}
+
+/// Sync emails with automatic filter application
+/// Fetches emails, saves to database, and applies filters
+#[tauri::command]
+async fn email_sync_with_filters(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    account_id: String,
+    folder: Option<String>,
+    page: u32,
+    page_size: u32,
+) -> Result<EmailSyncResult, String> {
+    // SECURITY: Enforce pagination limits
+    let safe_page_size = page_size.min(MAX_PAGE_SIZE).max(1);
+
+    log::info!("Syncing emails with filters: account {} folder {:?}", account_id, folder);
+    let folder_path = folder.unwrap_or_else(|| "INBOX".to_string());
+
+    // Parse account_id
+    let account_id_num: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+
+    if let Some(reason) = state.connectivity.blocked_reason(account_id_num) {
+        return Err(format!("{}: skipping sync for account {}", reason, account_id));
+    }
+
+    // Sync folder to database (create if not exists)
+    let folder_id = sync_folder_to_db(&state.db, account_id_num, &folder_path)?;
+
+    // Fetch emails
+    let mut async_clients = state.async_imap_clients.lock().await;
+    let client = async_clients
+        .get_mut(&account_id)
+        .ok_or("Account not connected")?;
+
+    let result = client
+        .fetch_emails(&folder_path, page, safe_page_size)
+        .await
+        .map_err(|e| format!("Failed to fetch emails: {}", e))?;
+    let uid_validity = current_uid_validity(client, &folder_path).await;
+
+    drop(async_clients); // Release lock
+    reset_on_uid_validity_change(&state, &app_handle, account_id_num, folder_id, &folder_path, uid_validity).await;
+
+    // OPTIMIZATION: Batch sync emails to database
+    let mut new_email_ids = Vec::new();
+    let mut filters_applied_count = 0;
+    let mut new_emails_count = 0;
+
+    if !result.emails.is_empty() {
+        // Check existing UIDs to identify new emails
+        let uids: Vec<u32> = result.emails.iter().map(|e| e.uid).collect();
+        let uid_placeholders = vec!["?"; uids.len()].join(",");
+        let existing_query = format!(
+            "SELECT uid FROM emails WHERE account_id = ? AND folder_id = ? AND uid IN ({})",
+            uid_placeholders
+        );
+
+        let existing_uids: std::collections::HashSet<u32> = {
+            let conn = state.db.get_conn().map_err(|e| format!("DB error: {}", e))?;
+            let mut stmt = conn.prepare(&existing_query).map_err(|e| format!("Query error: {}", e))?;
+            let mut params: Vec<&dyn rusqlite::ToSql> = vec![&account_id_num, &folder_id];
+            for uid in &uids {
+                params.push(uid);
+            }
             let rows = stmt.query_map(&params[..], |row| row.get::<_, u32>(0))
                 .map_err(|e| format!("Query failed: {}", e))?;
             rows.filter_map(|r| r.ok()).collect()
         };
 
         // Convert EmailSummary to NewEmail batch
-        let new_emails: Vec<db::NewEmail> = result.emails.iter().map(|email_summary| {
-            db::NewEmail {
-                account_id: account_id_num,
-                folder_id,
-                message_id: email_summary.message_id.clone().unwrap_or_else(|| format!("uid-{}", email_summary.uid)),
-                uid: email_summary.uid,
-                from_address: email_summary.from.clone(),
-                from_name: email_summary.from_name.clone(),
-                to_addresses: "[]".to_string(),
-                cc_addresses: "[]".to_string(),
-                bcc_addresses: "[]".to_string(),
-                reply_to: None,
-                subject: email_summary.subject.clone(),
-                preview: email_summary.preview.clone(),
-                body_text: None,
-                body_html: None,
-                date: email_summary.date.clone(),
-                is_read: email_summary.is_read,
-                is_starred: email_summary.is_starred,
-                is_deleted: false,
-                is_spam: false,
-                is_draft: false,
-                is_answered: false,
-                is_forwarded: false,
-                has_attachments: email_summary.has_attachments,
-                has_inline_images: false,
-                thread_id: None,
-                in_reply_to: None,
-                references_header: None,
-                raw_headers: None,
-                raw_size: 0,
-                priority: 3,
-                labels: "[]".to_string(),
-            }
-        }).collect();
+        let new_emails: Vec<db::NewEmail> = result.emails.iter()
+            .map(|email_summary| summary_to_new_email(account_id_num, folder_id, email_summary))
+            .collect();
 
         // Batch upsert
         let email_ids = state.db.batch_upsert_emails(&new_emails)
             .map_err(|e| format!("Failed to batch sync: {}", e))?;
+        mail::keywords::store_all(&state.db, account_id_num, folder_id, &result.emails);
 
         // Identify new email IDs (UIDs that didn't exist before)
         for (i, email_summary) in result.emails.iter().enumerate() {
@@ -1327,14 +2188,21 @@ async fn email_sync_with_filters(
 
         new_emails_count = new_email_ids.len();
         log::info!("Batch synced {} emails ({} new) to DB", new_emails.len(), new_emails_count);
+        state.badge.request();
+
+        classify_focus(&state.db, &new_email_ids);
 
         // Apply filters to new emails only
         if !new_email_ids.is_empty() {
             use filters::FilterEngine;
-            let engine = FilterEngine::new(state.db.clone());
+            let engine = FilterEngine::new(state.db.clone()).with_events(state.store_events.clone());
+
+            let plugins = plugins::registry();
 
             for email_id in new_email_ids {
                 if let Ok(email) = state.db.get_email(email_id) {
+                    plugins.email_received(&email).await;
+
                     if let Ok(actions) = engine.apply_filters(&email).await {
                         if !actions.is_empty() {
                             filters_applied_count += 1;
@@ -1467,12 +2335,30 @@ async fn email_list_all_accounts(
         let account_display_name = account.display_name.clone();
         let folder_path_clone = folder_path.clone();
         let db_clone = db.clone();
+        let credentials = state.credentials.clone();
         let enable_priority = account.enable_priority_fetch;
+        let blocked_reason = state.connectivity.blocked_reason(account_id);
 
         let handle = tokio::spawn(async move {
             let start_time = Instant::now();
             let account_id_str = account_id.to_string();
 
+            // Offline or paused accounts are reported without touching the network
+            if let Some(reason) = blocked_reason {
+                return mail::AccountFetchTaskResult {
+                    emails: vec![],
+                    status: mail::AccountFetchStatus {
+                        account_id: account_id_str,
+                        account_email: account_email.clone(),
+                        account_name: Some(account_display_name.clone()),
+                        email_count: 0,
+                        success: false,
+                        error: Some(reason.to_string()),
+                        fetch_time_ms: 0,
+                    },
+                };
+            }
+
             log::info!("[Account {}] Starting fetch (priority={})", account_email, enable_priority);
 
             // Get account metadata for badge
@@ -1486,42 +2372,9 @@ async fn email_list_all_accounts(
 
             let account_color = generate_account_color(&email);
 
-            // Get encrypted password
-            let encrypted_password = match db_clone.get_account_password(account_id) {
-                Ok(Some(pwd)) => pwd,
-                Ok(None) => {
-                    return mail::AccountFetchTaskResult {
-                        emails: vec![],
-                        status: mail::AccountFetchStatus {
-                            account_id: account_id_str,
-                            account_email: account_email.clone(),
-                            account_name: Some(display_name),
-                            email_count: 0,
-                            success: false,
-                            error: Some("No password found".to_string()),
-                            fetch_time_ms: start_time.elapsed().as_millis() as u64,
-                        },
-                    };
-                }
-                Err(e) => {
-                    return mail::AccountFetchTaskResult {
-                        emails: vec![],
-                        status: mail::AccountFetchStatus {
-                            account_id: account_id_str,
-                            account_email: account_email.clone(),
-                            account_name: Some(display_name),
-                            email_count: 0,
-                            success: false,
-                            error: Some(format!("Failed to get password: {}", e)),
-                            fetch_time_ms: start_time.elapsed().as_millis() as u64,
-                        },
-                    };
-                }
-            };
-
-            // Decrypt password
-            let password = match crypto::decrypt_password(&encrypted_password) {
-                Ok(pwd) => pwd,
+            // Decrypted password (cached across fetches)
+            let password = match credentials.password(&db_clone, account_id) {
+                Ok(pwd) => pwd.to_string(),
                 Err(e) => {
                     return mail::AccountFetchTaskResult {
                         emails: vec![],
@@ -1531,7 +2384,7 @@ async fn email_list_all_accounts(
                             account_name: Some(display_name),
                             email_count: 0,
                             success: false,
-                            error: Some(format!("Password decryption failed: {}", e)),
+                            error: Some(e.to_string()),
                             fetch_time_ms: start_time.elapsed().as_millis() as u64,
                         },
                     };
@@ -1558,7 +2411,9 @@ async fn email_list_all_accounts(
 
             let mut client = AsyncImapClient::new(imap_config);
 
-            if let Err(e) = client.connect().await {
+            let connected = client.connect().await;
+            credentials.record_login(&db_clone, account_id, &connected);
+            if let Err(e) = connected {
                 return mail::AccountFetchTaskResult {
                     emails: vec![],
                     status: mail::AccountFetchStatus {
@@ -1698,14 +2553,9 @@ async fn email_list_all_accounts(
 async fn connect_account_internal(state: &State<'_, AppState>, account: &db::Account) -> Result<(), String> {
     let account_id = account.id.to_string();
 
-    // Get password
-    let encrypted_password = state.db.get_account_password(account.id)
-        .map_err(|e| format!("Failed to get password: {}", e))?
-        .ok_or_else(|| "No password found for account".to_string())?;
-
-    // Decrypt password
-    let password = crypto::decrypt_password(&encrypted_password)
-        .map_err(|e| format!("Password decryption failed: {}", e))?;
+    let password = state.credentials.password(&state.db, account.id)
+        .map_err(|e| e.to_string())?
+        .to_string();
 
     // Parse security type
     let security = match account.imap_security.to_uppercase().as_str() {
@@ -1727,7 +2577,11 @@ async fn connect_account_internal(state: &State<'_, AppState>, account: &db::Acc
 
     // Create and connect client
     let mut client = mail::AsyncImapClient::new(config);
-    client.connect().await.map_err(|e| format!("{}", e))?;
+    let connected = client.connect().await;
+    state.credentials.record_login(&state.db, account.id, &connected);
+    connected.map_err(|e| format!("{}", e))?;
+    client.set_trash_folder(state.db.get_special_folder(account.id, "trash").ok().flatten());
+    record_imap_capabilities(&state.db, account.id, &client);
 
     // Store client
     let mut async_clients = state.async_imap_clients.lock().await;
@@ -1753,52 +2607,63 @@ async fn email_get(
         get_current_folder_safe(&state.current_folder, &account_id)
     });
 
-    // Get account details from database for fresh connection
     let account_id_num: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
-    let account = state.db.get_account(account_id_num)
-        .map_err(|e| format!("Failed to get account: {}", e))?;
-    let encrypted_password = state.db.get_account_password(account_id_num)
-        .map_err(|e| format!("Failed to get password: {}", e))?
-        .ok_or_else(|| "No password found for account".to_string())?;
-
-    // Decrypt password
-    let password = crypto::decrypt_password(&encrypted_password)
-        .map_err(|e| format!("Password decryption failed: {}", e))?;
 
-    // Parse security type
-    let security = match account.imap_security.to_uppercase().as_str() {
-        "SSL" => mail::SecurityType::SSL,
-        "STARTTLS" => mail::SecurityType::STARTTLS,
-        _ => mail::SecurityType::SSL,
+    // Read ahead when the folder was opened
+    let mut email = match state.prefetch.get(account_id_num, &folder_path, uid) {
+        Some(email) => {
+            log::info!("email_get: uid={} served from prefetch cache", uid);
+            email
+        }
+        None => fetch_email_fresh(&state, account_id_num, &folder_path, uid).await?,
     };
 
-    // Create ImapConfig for fresh connection
-    let config = mail::ImapConfig {
-        host: account.imap_host.clone(),
-        port: account.imap_port as u16,
-        security,
-        username: account.email.clone(),
-        password,
-        accept_invalid_certs: account.accept_invalid_certs,
-        oauth_provider: account.oauth_provider.clone(),
-    };
+    match domain_policy::verdict(&state.db, &email.from, &email.delivery) {
+        Ok(verdict) => email.policy = verdict,
+        Err(e) => log::warn!("Failed to check domain policy: {}", e),
+    }
 
-    // Create a fresh connection for this request to avoid session conflicts
-    log::info!("email_get: creating fresh IMAP connection for uid={}", uid);
-    let mut fresh_client = mail::AsyncImapClient::new(config);
-    fresh_client.connect().await.map_err(|e| format!("Failed to connect: {}", e))?;
+    // A message re-delivered with altered content keeps both versions
+    if let Some(message_id) = email.message_id.as_deref() {
+        let recorded = message_versions::record(
+            &state.db,
+            account_id_num,
+            message_id,
+            &email.subject,
+            email.body_text.as_deref(),
+            email.body_html.as_deref(),
+        );
+        match recorded {
+            Ok(Some(versions)) => {
+                log::info!("email_get: uid={} was re-delivered with new content ({} versions)", uid, versions);
+                state.store_events.notify(events::EmailsChanged {
+                    account_id: account_id_num,
+                    folder: Some(folder_path.clone()),
+                    email_ids: state.db.find_email_ids_by_uids(account_id_num, &folder_path, &[uid]).unwrap_or_default(),
+                    uids: vec![uid],
+                    change: events::EmailChange::ContentChanged { versions },
+                });
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to record message version: {}", e),
+        }
+    }
 
-    // Fetch with timeout (15 seconds)
-    let fetch_result = tokio::time::timeout(
-        std::time::Duration::from_secs(15),
-        fresh_client.fetch_email(&folder_path, uid)
-    ).await;
+    // An attendee answering one of our invites
+    if let Some(part) = email.calendar.as_ref().filter(|part| part.method == "REPLY") {
+        if let Err(e) = invites::record_reply(&state.db, account_id_num, &email.from, part) {
+            log::warn!("Failed to record invite reply: {}", e);
+        }
+    }
 
-    let email = match fetch_result {
-        Ok(Ok(email)) => email,
-        Ok(Err(e)) => return Err(format!("Fetch error: {}", e)),
-        Err(_) => return Err("Fetch timeout - server did not respond in time".to_string()),
-    };
+    // A delivery failure report marks the contacts it names as bouncing
+    if !email.bounced.is_empty() {
+        match bounces::record(&state.db, account_id_num, &email.bounced) {
+            Ok(0) => {}
+            Ok(marked) => log::info!("email_get: uid={} marked {} contacts as bouncing", uid, marked),
+            Err(e) => log::warn!("Failed to record bounce: {}", e),
+        }
+    }
 
     // Save attachments to database if email exists in DB and has attachments
     if !email.attachments.is_empty() {
@@ -1852,6 +2717,81 @@ async fn email_get(
     Ok(email)
 }
 
+/// Fetch an email over a fresh connection (the server marks it read)
+async fn fetch_email_fresh(
+    state: &AppState,
+    account_id_num: i64,
+    folder_path: &str,
+    uid: u32,
+) -> Result<mail::ParsedEmail, String> {
+    // Get account details from database for fresh connection
+    let account = state.db.get_account(account_id_num)
+        .map_err(|e| format!("Failed to get account: {}", e))?;
+    let password = state.credentials.password(&state.db, account_id_num)
+        .map_err(|e| e.to_string())?
+        .to_string();
+
+    // Parse security type
+    let security = match account.imap_security.to_uppercase().as_str() {
+        "SSL" => mail::SecurityType::SSL,
+        "STARTTLS" => mail::SecurityType::STARTTLS,
+        _ => mail::SecurityType::SSL,
+    };
+
+    // Create ImapConfig for fresh connection
+    let config = mail::ImapConfig {
+        host: account.imap_host.clone(),
+        port: account.imap_port as u16,
+        security,
+        username: account.email.clone(),
+        password,
+        accept_invalid_certs: account.accept_invalid_certs,
+        oauth_provider: account.oauth_provider.clone(),
+    };
+
+    // Create a fresh connection for this request to avoid session conflicts
+    log::info!("email_get: creating fresh IMAP connection for uid={}", uid);
+    let mut fresh_client = mail::AsyncImapClient::new(config);
+    let connected = fresh_client.connect().await;
+    state.credentials.record_login(&state.db, account_id_num, &connected);
+    connected.map_err(|e| format!("Failed to connect: {}", e))?;
+
+    // Fetch with timeout (15 seconds)
+    let fetch_result = tokio::time::timeout(
+        std::time::Duration::from_secs(15),
+        fresh_client.fetch_email(folder_path, uid)
+    ).await;
+
+    match fetch_result {
+        Ok(Ok(email)) => Ok(email),
+        Ok(Err(e)) => Err(format!("Fetch error: {}", e)),
+        Err(_) => Err("Fetch timeout - server did not respond in time".to_string()),
+    }
+}
+
+/// All contents seen for a Message-ID, oldest first
+#[tauri::command]
+async fn email_versions(
+    state: State<'_, AppState>,
+    account_id: i64,
+    message_id: String,
+) -> Result<Vec<message_versions::MessageVersion>, String> {
+    message_versions::versions(&state.db, account_id, &message_id)
+        .map_err(|e| format!("Failed to load message versions: {}", e))
+}
+
+/// Refuse attachments of senders whose domain policy blocks them
+fn ensure_attachments_allowed(db: &Database, sender: &str) -> Result<(), String> {
+    let policy = domain_policy::for_sender(db, sender)
+        .map_err(|e| format!("Failed to check domain policy: {}", e))?;
+    match policy {
+        Some(policy) if policy.block_attachments => {
+            Err(format!("Attachments from {} are blocked by the domain policy for {}", sender, policy.pattern))
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Download attachment from email
 #[tauri::command]
 async fn email_download_attachment(
@@ -1866,18 +2806,40 @@ async fn email_download_attachment(
     let account_id_num: i64 = account_id.parse()
         .map_err(|_| "Invalid account ID".to_string())?;
 
+    let sender: Option<String> = state.db.query(
+        "SELECT e.from_address FROM emails e JOIN folders f ON f.id = e.folder_id
+         WHERE e.account_id = ?1 AND f.remote_name = ?2 AND e.uid = ?3",
+        rusqlite::params![account_id_num, folder, uid],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to get email: {}", e))?
+    .into_iter()
+    .next();
+    if let Some(sender) = sender {
+        ensure_attachments_allowed(&state.db, &sender)?;
+    }
+
+    // Served from the attachment store when downloaded before
+    let stored = attachment_store::find_attachment(&state.db, account_id_num, &folder, uid, attachment_index)
+        .map_err(|e| format!("Failed to get attachment: {}", e))?;
+    if let Some(stored) = &stored {
+        if let Some(bytes) = state.attachment_store.load(&state.db, stored) {
+            return Ok(mail::AttachmentData {
+                filename: stored.filename.clone(),
+                content_type: stored.content_type.clone(),
+                size: bytes.len() as u32,
+                data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &bytes),
+            });
+        }
+    }
+
     // Get account details
     let account = state.db.get_account(account_id_num)
         .map_err(|e| format!("Failed to get account: {}", e))?;
 
-    // Get encrypted password
-    let encrypted_password = state.db.get_account_password(account_id_num)
-        .map_err(|e| format!("Failed to get password: {}", e))?
-        .ok_or_else(|| "No password found for account".to_string())?;
-
-    // Decrypt password
-    let password = crypto::decrypt_password(&encrypted_password)
-        .map_err(|e| format!("Password decryption failed: {}", e))?;
+    let password = state.credentials.password(&state.db, account_id_num)
+        .map_err(|e| e.to_string())?
+        .to_string();
 
     // Parse security type
     let security = match account.imap_security.to_uppercase().as_str() {
@@ -1900,7 +2862,9 @@ async fn email_download_attachment(
     // Create a fresh connection for this request
     log::info!("email_download_attachment: creating fresh IMAP connection");
     let mut fresh_client = mail::AsyncImapClient::new(config);
-    fresh_client.connect().await.map_err(|e| format!("Failed to connect: {}", e))?;
+    let connected = fresh_client.connect().await;
+    state.credentials.record_login(&state.db, account_id_num, &connected);
+    connected.map_err(|e| format!("Failed to connect: {}", e))?;
 
     // Fetch attachment with timeout (30 seconds - larger files may take longer)
     let fetch_result = tokio::time::timeout(
@@ -1915,6 +2879,17 @@ async fn email_download_attachment(
     };
 
     log::info!("✓ email_download_attachment: downloaded {} ({} bytes)", attachment.filename, attachment.size);
+
+    if let Some(stored) = &stored {
+        match base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &attachment.data) {
+            Ok(bytes) => {
+                if let Err(e) = state.attachment_store.store(&state.db, stored.id, &bytes) {
+                    log::warn!("Failed to store attachment: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to decode attachment for storage: {}", e),
+        }
+    }
     Ok(attachment)
 }
 
@@ -1980,29 +2955,155 @@ async fn email_search_advanced(
     Ok(result)
 }
 
-/// Mark email as read/unread
+/// Page size for cross-account virtual folders
+const VIRTUAL_FOLDER_PAGE_SIZE: u32 = 50;
+
+/// List a cross-account virtual folder ("starred", "unread", "vip" or "needs_reply") from the local database
+/// No IMAP traffic: uses flags cached by the last sync
 #[tauri::command]
-async fn email_mark_read(
+async fn virtual_folder_list(
     state: State<'_, AppState>,
-    account_id: String,
-    uid: u32,
-    read: bool,
-    folder: Option<String>,
-) -> Result<(), String> {
-    // SECURITY: Use safe folder lookup that handles mutex poisoning
-    let folder_path = folder.unwrap_or_else(|| {
-        get_current_folder_safe(&state.current_folder, &account_id)
-    });
+    kind: db::VirtualFolderKind,
+    page: u32,
+) -> Result<db::VirtualFolderPage, String> {
+    if kind == db::VirtualFolderKind::NeedsReply {
+        reply_needed::classify_pending(&state.db)
+            .map_err(|e| format!("Failed to classify new mail: {}", e))?;
+    }
 
-    let mut async_clients = state.async_imap_clients.lock().await;
-    let client = async_clients
-        .get_mut(&account_id)
-        .ok_or_else(|| "Account not connected".to_string())?;
+    state.db.get_virtual_folder_emails(
+        kind,
+        VIRTUAL_FOLDER_PAGE_SIZE as i32,
+        (page * VIRTUAL_FOLDER_PAGE_SIZE) as i32,
+    )
+    .map_err(|e| format!("Failed to load virtual folder: {}", e))
+}
 
-    client
-        .set_read(&folder_path, uid, read)
-        .await
-        .map_err(|e| e.to_string())
+/// Override whether a message needs a reply (None = automatic detection)
+#[tauri::command]
+async fn reply_needed_set(
+    state: State<'_, AppState>,
+    email_id: i64,
+    needs_reply: Option<bool>,
+) -> Result<(), String> {
+    reply_needed::set_override(&state.db, email_id, needs_reply)
+        .map_err(|e| format!("Failed to update reply status: {}", e))
+}
+
+/// Remind about an unanswered message at `remind_at` (RFC 3339; None clears it)
+/// Due reminders are emitted as `reply://reminder` events.
+#[tauri::command]
+async fn reply_needed_remind(
+    state: State<'_, AppState>,
+    email_id: i64,
+    remind_at: Option<String>,
+) -> Result<(), String> {
+    let remind_at = remind_at
+        .map(|at| {
+            chrono::DateTime::parse_from_rfc3339(&at)
+                .map(|at| at.with_timezone(&chrono::Utc))
+                .map_err(|e| format!("Invalid reminder time: {}", e))
+        })
+        .transpose()?;
+
+    reply_needed::set_reminder(&state.db, email_id, remind_at)
+        .map_err(|e| format!("Failed to save reminder: {}", e))
+}
+
+/// Events proposed from the dates and times a message mentions, for the user to confirm
+#[tauri::command]
+async fn email_extract_events(
+    state: State<'_, AppState>,
+    email_id: i64,
+) -> Result<Vec<event_extract::ProposedEvent>, String> {
+    event_extract::for_email(&state.db, email_id)
+        .map_err(|e| format!("Failed to extract events: {}", e))
+}
+
+/// Meeting invites sent from an account with each attendee's answer
+#[tauri::command]
+async fn invite_list(state: State<'_, AppState>, account_id: i64) -> Result<Vec<invites::Invite>, String> {
+    invites::list(&state.db, account_id)
+        .map_err(|e| format!("Failed to load invites: {}", e))
+}
+
+/// All domain policies
+#[tauri::command]
+async fn domain_policy_list(state: State<'_, AppState>) -> Result<Vec<domain_policy::DomainPolicy>, String> {
+    domain_policy::list(&state.db)
+        .map_err(|e| format!("Failed to load domain policies: {}", e))
+}
+
+/// Create a domain policy, or replace the rules of an existing pattern
+#[tauri::command]
+async fn domain_policy_save(
+    state: State<'_, AppState>,
+    policy: domain_policy::NewDomainPolicy,
+) -> Result<domain_policy::DomainPolicy, String> {
+    domain_policy::save(&state.db, &policy)
+        .map_err(|e| format!("Failed to save domain policy: {}", e))
+}
+
+#[tauri::command]
+async fn domain_policy_delete(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    match domain_policy::delete(&state.db, id) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("Domain policy {} not found", id)),
+        Err(e) => Err(format!("Failed to delete domain policy: {}", e)),
+    }
+}
+
+/// Final destination of a link (wrappers decoded, shorteners resolved
+/// without loading the target) with reputation hints
+#[tauri::command]
+async fn link_preview(url: String) -> Result<links::LinkPreview, String> {
+    links::preview(&url).await
+}
+
+/// Whether opening a link from a sender's message needs an interstitial warning
+#[tauri::command]
+async fn domain_policy_check_link(
+    state: State<'_, AppState>,
+    sender: String,
+    url: String,
+) -> Result<domain_policy::LinkCheck, String> {
+    domain_policy::check_link(&state.db, &sender, &url)
+        .map_err(|e| format!("Failed to check link: {}", e))
+}
+
+/// Move a message between Focused and Other; the choice trains the classifier
+/// Returns how many other messages moved along with it.
+#[tauri::command]
+async fn email_set_focus(state: State<'_, AppState>, email_id: i64, focused: bool) -> Result<usize, String> {
+    focus::set_focus(&state.db, email_id, focused)
+        .map_err(|e| format!("Failed to update focus: {}", e))
+}
+
+/// UIDs of a folder's messages sorted into Other
+#[tauri::command]
+async fn email_focus_other(state: State<'_, AppState>, account_id: i64, folder: Option<String>) -> Result<Vec<u32>, String> {
+    let folder = folder.unwrap_or_else(|| "INBOX".to_string());
+    focus::other_uids(&state.db, account_id, &folder)
+        .map_err(|e| format!("Failed to load focused inbox: {}", e))
+}
+
+/// Mark email as read/unread
+#[tauri::command]
+async fn email_mark_read(
+    state: State<'_, AppState>,
+    account_id: String,
+    uid: u32,
+    read: bool,
+    folder: Option<String>,
+) -> Result<undo::UndoHandle, String> {
+    // SECURITY: Use safe folder lookup that handles mutex poisoning
+    let folder_path = folder.unwrap_or_else(|| {
+        get_current_folder_safe(&state.current_folder, &account_id)
+    });
+
+    // Applied locally right away; the IMAP command runs in the background
+    let op = queue_email_operation(&state, &account_id, &folder_path, uid, pending_ops::PendingOpKind::SetRead { value: read })?;
+    Ok(state.undo.issue(&state.db, op))
 }
 
 /// Mark email as starred/unstarred
@@ -2013,21 +3114,63 @@ async fn email_mark_starred(
     uid: u32,
     starred: bool,
     folder: Option<String>,
-) -> Result<(), String> {
+) -> Result<undo::UndoHandle, String> {
     // SECURITY: Use safe folder lookup that handles mutex poisoning
     let folder_path = folder.unwrap_or_else(|| {
         get_current_folder_safe(&state.current_folder, &account_id)
     });
 
+    // Applied locally right away; the IMAP command runs in the background
+    let op = queue_email_operation(&state, &account_id, &folder_path, uid, pending_ops::PendingOpKind::SetStarred { value: starred })?;
+    Ok(state.undo.issue(&state.db, op))
+}
+
+/// Add or remove a shared tag (an IMAP keyword) on an email
+#[tauri::command]
+async fn email_set_tag(
+    state: State<'_, AppState>,
+    account_id: String,
+    uid: u32,
+    tag: String,
+    value: bool,
+    folder: Option<String>,
+) -> Result<undo::UndoHandle, String> {
+    let folder_path = folder.unwrap_or_else(|| {
+        get_current_folder_safe(&state.current_folder, &account_id)
+    });
+    let account_id_num: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+
+    // Reuse the keyword the message already carries for this tag, e.g. `$label1` for "Important"
+    let existing = match state.db.find_email_ids_by_uids(account_id_num, &folder_path, &[uid])
+        .map_err(|e| format!("Database error: {}", e))?
+        .first()
+    {
+        Some(id) => mail::keywords::get(&state.db, *id).map_err(|e| format!("Database error: {}", e))?,
+        None => Vec::new(),
+    };
+    let keyword = match existing.into_iter().find(|keyword| mail::keywords::label_for(keyword) == tag.trim()) {
+        Some(keyword) => keyword,
+        None => mail::keywords::keyword_for(&tag)?,
+    };
+
+    let op = queue_email_operation(&state, &account_id, &folder_path, uid, pending_ops::PendingOpKind::SetKeyword { keyword, value })?;
+    Ok(state.undo.issue(&state.db, op))
+}
+
+/// Tags defined in a folder and whether new ones can be added
+#[tauri::command]
+async fn imap_folder_tags(
+    state: State<'_, AppState>,
+    account_id: String,
+    folder: String,
+) -> Result<mail::keywords::FolderKeywords, String> {
     let mut async_clients = state.async_imap_clients.lock().await;
     let client = async_clients
         .get_mut(&account_id)
         .ok_or_else(|| "Account not connected".to_string())?;
 
-    client
-        .set_starred(&folder_path, uid, starred)
-        .await
-        .map_err(|e| e.to_string())
+    client.folder_keywords(&folder).await
+        .map_err(|e| format!("Failed to load folder tags: {}", e))
 }
 
 /// Move email to a folder
@@ -2038,21 +3181,20 @@ async fn email_move(
     uid: u32,
     target_folder: String,
     folder: Option<String>,
-) -> Result<(), String> {
+) -> Result<undo::UndoHandle, String> {
     // SECURITY: Use safe folder lookup that handles mutex poisoning
     let folder_path = folder.unwrap_or_else(|| {
         get_current_folder_safe(&state.current_folder, &account_id)
     });
 
-    let mut async_clients = state.async_imap_clients.lock().await;
-    let client = async_clients
-        .get_mut(&account_id)
-        .ok_or_else(|| "Account not connected".to_string())?;
-
-    client
-        .move_email(&folder_path, uid, &target_folder)
-        .await
-        .map_err(|e| e.to_string())
+    // Applied locally right away; the IMAP command runs in the background
+    let op = queue_email_operation(&state, &account_id, &folder_path, uid, pending_ops::PendingOpKind::Move { target: target_folder.clone() })?;
+    if let Ok(account_id_num) = account_id.parse::<i64>() {
+        if let Err(e) = spam::record_move(&state.db, account_id_num, &op.email_ids, &folder_path, &target_folder) {
+            log::warn!("Failed to queue spam training: {}", e);
+        }
+    }
+    Ok(state.undo.issue(&state.db, op))
 }
 
 /// Delete email
@@ -2063,122 +3205,654 @@ async fn email_delete(
     uid: u32,
     permanent: bool,
     folder: Option<String>,
-) -> Result<(), String> {
+) -> Result<undo::UndoHandle, String> {
     // SECURITY: Use safe folder lookup that handles mutex poisoning
     let folder_path = folder.unwrap_or_else(|| {
         get_current_folder_safe(&state.current_folder, &account_id)
     });
 
-    let mut async_clients = state.async_imap_clients.lock().await;
-    let client = async_clients
-        .get_mut(&account_id)
-        .ok_or_else(|| "Account not connected".to_string())?;
+    // Applied locally right away; the IMAP command runs in the background
+    let op = queue_email_operation(&state, &account_id, &folder_path, uid, pending_ops::PendingOpKind::Delete { permanent })?;
+    let handle = state.undo.issue(&state.db, op.clone());
 
-    client
-        .delete_email(&folder_path, uid, permanent)
-        .await
-        .map_err(|e| e.to_string())
+    let subject = op.email_ids.first()
+        .and_then(|id| state.db.get_email(*id).ok())
+        .map(|email| email.subject)
+        .unwrap_or_default();
+    let summary = if permanent {
+        format!("Permanently deleted \"{}\" from {}", subject, folder_path)
+    } else {
+        format!("Deleted \"{}\" from {}", subject, folder_path)
+    };
+    let undo = activity::UndoPayload::Restore {
+        folder: Some(folder_path),
+        uids: vec![uid],
+        email_ids: op.email_ids,
+        pending_op_id: Some(op.id),
+    };
+    log_activity(&state, account_id.parse().ok(), activity::ActivityAction::EmailDelete, &summary, Some(undo));
+    Ok(handle)
+}
+
+/// Reverse a mailbox action by the token it returned
+/// Actions the server already ran are reversed with a compensating command.
+#[tauri::command]
+async fn action_undo(state: State<'_, AppState>, token: String) -> Result<(), String> {
+    match state.undo.undo(&state.db, &state.store_events, &token)? {
+        None => {}
+        Some(undo::Compensation::Flag { account_id, folder, uid, kind }) => {
+            queue_email_operation(&state, &account_id, &folder, uid, kind)?;
+        }
+        Some(undo::Compensation::MoveBack { account_id, folder, message_id, back_to }) => {
+            let found = {
+                let mut async_clients = state.async_imap_clients.lock().await;
+                let client = async_clients
+                    .get_mut(&account_id)
+                    .ok_or("Account not connected. Please try reconnecting the account.")?;
+                client.find_by_message_id(&folder, &message_id).await
+                    .map_err(|e| format!("Failed to find message: {}", e))?
+            };
+            let uid = found.ok_or_else(|| format!("The message is no longer in {}", folder))?;
+            queue_email_operation(&state, &account_id, &folder, uid, pending_ops::PendingOpKind::Move { target: back_to })?;
+        }
+    }
+    Ok(())
+}
+
+/// Seconds an undo token stays valid
+#[tauri::command]
+async fn undo_window_get(state: State<'_, AppState>) -> Result<u64, String> {
+    undo::window_secs(&state.db)
+        .map_err(|e| format!("Failed to load undo window: {}", e))
+}
+
+/// Save the undo window; returns it as stored (1 to 300 seconds)
+#[tauri::command]
+async fn undo_window_set(state: State<'_, AppState>, seconds: u64) -> Result<u64, String> {
+    undo::set_window_secs(&state.db, seconds)
+        .map_err(|e| format!("Failed to save undo window: {}", e))
+}
+
+/// Recent destructive actions, newest first
+#[tauri::command]
+async fn activity_list(
+    state: State<'_, AppState>,
+    limit: Option<u32>,
+) -> Result<Vec<activity::ActivityEntry>, String> {
+    activity::list(&state.db, limit.unwrap_or(100).min(1000))
+        .map_err(|e| format!("Failed to load activity: {}", e))
+}
+
+/// Revert a logged action (soft deletes and filter moves)
+#[tauri::command]
+async fn activity_undo(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    activity::undo(&state.db, &state.store_events, id)
+        .map_err(|e| format!("Failed to undo: {}", e))
+}
+
+/// List operations applied locally but not yet confirmed by the server
+#[tauri::command]
+async fn pending_ops_list(
+    state: State<'_, AppState>,
+    account_id: Option<String>,
+) -> Result<Vec<pending_ops::PendingOperation>, String> {
+    let ops = state.pending_ops.list()
+        .map_err(|e| format!("Failed to load pending operations: {}", e))?;
+
+    Ok(match account_id {
+        Some(account_id) => ops.into_iter().filter(|op| op.account_id == account_id).collect(),
+        None => ops,
+    })
+}
+
+/// Names of the registered mail processing plugins
+#[tauri::command]
+fn plugin_list() -> Vec<String> {
+    plugins::registry().names()
 }
 
 /// Attachment file path for sending
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 pub struct AttachmentPath {
     pub path: String,
     pub filename: String,
     pub content_type: String,
+    /// Content-ID for inline images referenced as cid: from the HTML body
+    #[serde(default)]
+    pub content_id: Option<String>,
+    /// Type the caller declared, when detection found the file to be something else
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub declared_content_type: Option<String>,
+}
+
+/// Effective content type of an attachment, and the declared one if it was wrong
+fn attachment_content_type(info: mail::mime_detect::MimeInfo, filename: &str) -> (String, Option<String>) {
+    if !info.mismatch() {
+        return (info.content_type, None);
+    }
+    log::warn!(
+        "Attachment {} declared as '{}' but detected as '{}'",
+        filename, info.declared, info.content_type
+    );
+    (info.content_type, Some(info.declared))
 }
 
-/// Send an email
-/// SECURITY: Validates all recipients and enforces limits
+/// Collect on-disk sizes of attachments queued for sending
+async fn collect_attachment_sizes(
+    paths: &[AttachmentPath],
+) -> Result<Vec<mail::attachment_guard::AttachmentSize>, String> {
+    let mut sizes = Vec::with_capacity(paths.len());
+    for att_path in paths {
+        let metadata = tokio::fs::metadata(&att_path.path)
+            .await
+            .map_err(|e| format!("Failed to read attachment {}: {}", att_path.filename, e))?;
+        sizes.push(mail::attachment_guard::AttachmentSize {
+            filename: att_path.filename.clone(),
+            size: metadata.len(),
+        });
+    }
+    Ok(sizes)
+}
+
+/// Load the configured large-attachment strategy (if any) from settings
+fn load_large_attachment_strategy(
+    db: &Database,
+) -> Option<Box<dyn mail::attachment_guard::LargeAttachmentStrategy>> {
+    let config: mail::attachment_guard::WebDavConfig = db
+        .get_setting(mail::attachment_guard::WEBDAV_SETTING)
+        .ok()
+        .flatten()?;
+    let password = match crypto::decrypt_password(&config.password_encrypted) {
+        Ok(password) => password,
+        Err(e) => {
+            log::warn!("Cannot decrypt the WebDAV password: {}", e);
+            return None;
+        }
+    };
+
+    match mail::attachment_guard::WebDavStrategy::new(config, password) {
+        Ok(strategy) => Some(Box::new(strategy)),
+        Err(e) => {
+            log::warn!("Invalid large attachment strategy config: {}", e);
+            None
+        }
+    }
+}
+
+/// Check attachment sizes against the account's provider limit before sending
 #[tauri::command]
-async fn email_send(
+async fn attachment_check_size(
     state: State<'_, AppState>,
     account_id: String,
-    to: Vec<String>,
-    cc: Vec<String>,
-    bcc: Vec<String>,
-    subject: String,
-    text_body: Option<String>,
-    html_body: Option<String>,
-    attachment_paths: Option<Vec<AttachmentPath>>,
-) -> Result<(), String> {
-    // SECURITY: Validate account ID
+    attachment_paths: Vec<AttachmentPath>,
+) -> Result<mail::attachment_guard::AttachmentSizeReport, String> {
     let id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
-    if id <= 0 {
-        return Err("Invalid account ID".to_string());
-    }
+    let account = state.db.get_account(id)
+        .map_err(|e| format!("Database error: {}", e))?;
 
-    // SECURITY: Validate recipient counts
-    let total_recipients = to.len() + cc.len() + bcc.len();
-    if total_recipients == 0 {
-        return Err("At least one recipient is required".to_string());
-    }
-    if total_recipients > MAX_RECIPIENTS {
-        return Err(format!("Too many recipients (max {})", MAX_RECIPIENTS));
+    let sizes = collect_attachment_sizes(&attachment_paths).await?;
+    let limit = mail::attachment_guard::provider_limit(&account.smtp_host, &account.email);
+    let has_strategy = load_large_attachment_strategy(&state.db).is_some();
+
+    Ok(mail::attachment_guard::check_attachment_sizes(&limit, &sizes, has_strategy))
+}
+
+/// Configure WebDAV uploads for large attachments
+/// `password`: None keeps the stored password.
+#[tauri::command]
+async fn attachment_webdav_set(
+    state: State<'_, AppState>,
+    base_url: String,
+    username: String,
+    password: Option<String>,
+    public_base_url: Option<String>,
+) -> Result<(), String> {
+    let password_encrypted = match password {
+        Some(password) => crypto::encrypt_password(&password)?,
+        None => state
+            .db
+            .get_setting::<mail::attachment_guard::WebDavConfig>(mail::attachment_guard::WEBDAV_SETTING)
+            .map_err(|e| format!("Database error: {}", e))?
+            .map(|config| config.password_encrypted)
+            .ok_or_else(|| "WebDAV password is required".to_string())?,
+    };
+    let config = mail::attachment_guard::WebDavConfig { base_url, username, password_encrypted, public_base_url };
+    if !config.base_url.starts_with("https://") {
+        return Err("WebDAV URL must use HTTPS".to_string());
     }
 
-    // SECURITY: Validate all email addresses
-    for email in to.iter().chain(cc.iter()).chain(bcc.iter()) {
-        validate_email(email)?;
+    state
+        .db
+        .set_setting(mail::attachment_guard::WEBDAV_SETTING, &config)
+        .map_err(|e| format!("Failed to save WebDAV settings: {}", e))
+}
+
+/// Upload a large attachment via the configured strategy and return a shareable link
+#[tauri::command]
+async fn attachment_share_link(
+    state: State<'_, AppState>,
+    attachment: AttachmentPath,
+) -> Result<String, String> {
+    // SECURITY: Validate filename
+    if attachment.filename.contains("..") || attachment.filename.contains('/') || attachment.filename.contains('\\') {
+        return Err("Invalid filename".to_string());
     }
 
-    // SECURITY: Validate subject length
-    if subject.len() > 998 {
-        return Err("Subject too long (max 998 characters)".to_string());
+    let strategy = load_large_attachment_strategy(&state.db)
+        .ok_or_else(|| "No large attachment upload provider configured".to_string())?;
+
+    let data = tokio::fs::read(&attachment.path)
+        .await
+        .map_err(|e| format!("Failed to read attachment {}: {}", attachment.filename, e))?;
+
+    log::info!("Uploading large attachment '{}' via {}", attachment.filename, strategy.name());
+
+    strategy
+        .upload(&attachment.filename, data)
+        .await
+        .map_err(|e| sanitize_error_message(&e.to_string()))
+}
+
+/// Whether the replied-to thread is marked confidential, and its participants
+fn reply_thread_context(db: &Database, account_id: i64, folder: &str, uid: u32) -> db::DbResult<(bool, Vec<String>)> {
+    let Some(id) = db.find_email_ids_by_uids(account_id, folder, &[uid])?.into_iter().next() else {
+        return Ok((false, Vec::new()));
+    };
+    let email = db.get_email(id)?;
+    let references = format!(
+        "{} {}",
+        email.in_reply_to.as_deref().unwrap_or_default(),
+        email.references_header.as_deref().unwrap_or_default()
+    );
+
+    let messages = db.query(
+        r#"
+        SELECT subject, labels, from_address, to_addresses, cc_addresses FROM emails
+        WHERE account_id = ?1
+          AND (id = ?2 OR (thread_id IS NOT NULL AND thread_id = ?3) OR instr(?4, message_id) > 0)
+        "#,
+        rusqlite::params![account_id, id, email.thread_id, references.trim()],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        },
+    )?;
+
+    // Address lists hold plain strings or {"email", "name"} objects
+    let addresses = |json: &str| -> Vec<String> {
+        serde_json::from_str::<Vec<serde_json::Value>>(json)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|value| value.as_str().or_else(|| value.get("email").and_then(|email| email.as_str())))
+            .map(str::to_lowercase)
+            .collect()
+    };
+
+    let mut confidential = false;
+    let mut participants = Vec::new();
+    for (subject, labels, from, to, cc) in messages {
+        let labels: Vec<String> = serde_json::from_str(&labels).unwrap_or_default();
+        confidential |= mail::precheck::is_confidential(&subject, &labels);
+        participants.push(from.to_lowercase());
+        participants.extend(addresses(&to));
+        participants.extend(addresses(&cc));
     }
 
-    // SECURITY: Check for header injection in subject
-    if subject.contains('\r') || subject.contains('\n') {
-        return Err("Invalid characters in subject".to_string());
+    Ok((confidential, participants))
+}
+
+/// Pre-send analysis: forgotten attachments, empty subject, missing or invalid recipients,
+/// likely domain typos, bouncing contacts, first-time recipients on confidential threads, reply-all to long
+/// lists and outside recipients on internal threads
+/// Returns warnings for the composer to confirm; call before `email_send`.
+#[tauri::command]
+fn email_precheck_send(
+    state: State<'_, AppState>,
+    account_id: String,
+    draft: mail::precheck::OutgoingDraft,
+) -> Result<Vec<mail::precheck::SendWarning>, String> {
+    use mail::precheck::{SendWarning, SendWarningKind};
+
+    let id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+    let recipients: Vec<&str> = draft.to.iter()
+        .chain(draft.cc.iter())
+        .chain(draft.bcc.iter())
+        .map(String::as_str)
+        .collect();
+    let mut warnings = mail::precheck::check(
+        recipients.len(),
+        &draft.subject,
+        draft.text_body.as_deref(),
+        draft.html_body.as_deref(),
+        draft.attachment_count,
+    );
+
+    let mut valid_recipients = Vec::new();
+    for email in &recipients {
+        match validate_email(email.trim()) {
+            Ok(()) => valid_recipients.push(*email),
+            Err(e) => warnings.push(SendWarning::new(SendWarningKind::InvalidRecipient, e, Some(email.to_string()))),
+        }
+    }
+    if recipients.len() > MAX_RECIPIENTS {
+        warnings.push(SendWarning::new(
+            SendWarningKind::InvalidRecipient,
+            format!("Too many recipients (max {})", MAX_RECIPIENTS),
+            None,
+        ));
     }
 
     let account = state.db.get_account(id)
         .map_err(|e| format!("Database error: {}", e))?;
+    let mut context = mail::precheck::RecipientContext {
+        known_addresses: state.db.get_correspondent_addresses(id)
+            .map_err(|e| format!("Database error: {}", e))?,
+        internal_domains: account.email.rsplit_once('@').map(|(_, domain)| domain.to_lowercase()).into_iter().collect(),
+        bouncing: bounces::reasons(&state.db, id)
+            .map_err(|e| format!("Database error: {}", e))?,
+        ..Default::default()
+    };
+    if let (Some(folder), Some(uid)) = (draft.reply_folder.as_deref(), draft.reply_uid) {
+        let (confidential, participants) = reply_thread_context(&state.db, id, folder, uid)
+            .map_err(|e| format!("Database error: {}", e))?;
+        context.confidential_thread = confidential;
+        context.thread_participants = participants.clone();
+        context.known_addresses.extend(participants);
+    }
+    warnings.extend(mail::precheck::check_recipients(&valid_recipients, &context));
 
-    let encrypted_password = state.db.get_account_password(id)
+    let settings = state.db.get_setting::<mail::precheck::PrecheckSettings>(mail::precheck::SETTINGS_KEY)
         .map_err(|e| format!("Database error: {}", e))?
-        .ok_or_else(|| "No password stored".to_string())?;
+        .unwrap_or_default();
+    warnings.extend(mail::precheck::check_audience(&valid_recipients, draft.reply_all, &settings, &context));
 
-    // Decrypt password (or access token for OAuth)
-    let password = crypto::decrypt_password(&encrypted_password)
-        .map_err(|e| format!("Password decryption failed: {}", e))?;
+    Ok(warnings)
+}
 
-    log::info!("Sending email from {} to {:?}", account.email, to);
+/// Limits of the pre-send checks
+#[tauri::command]
+async fn precheck_settings_get(state: State<'_, AppState>) -> Result<mail::precheck::PrecheckSettings, String> {
+    state.db.get_setting::<mail::precheck::PrecheckSettings>(mail::precheck::SETTINGS_KEY)
+        .map(Option::unwrap_or_default)
+        .map_err(|e| format!("Failed to load precheck settings: {}", e))
+}
 
-    // Check if this is an OAuth account
-    if account.oauth_provider.is_some() {
-        log::info!("Using OAuth2 SMTP for account: {}", account.email);
+/// Save the limits of the pre-send checks
+#[tauri::command]
+async fn precheck_settings_set(
+    state: State<'_, AppState>,
+    settings: mail::precheck::PrecheckSettings,
+) -> Result<(), String> {
+    state.db.set_setting(mail::precheck::SETTINGS_KEY, &settings)
+        .map_err(|e| format!("Failed to save precheck settings: {}", e))
+}
 
-        // Determine email body format
-        let (body_str, is_html) = if let Some(html) = html_body {
-            (html, true)
-        } else {
+/// Check SPF/DKIM/DMARC of the account's sending domain against its SMTP host
+/// Mail failing these checks is likely to land in spam; mailbox providers are skipped.
+#[tauri::command]
+async fn account_check_sender_auth(
+    state: State<'_, AppState>,
+    account_id: String,
+) -> Result<mail::sender_auth::SenderAuthReport, String> {
+    let id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+    let account = state.db.get_account(id)
+        .map_err(|e| format!("Account not found: {}", e))?;
+
+    let report = mail::sender_auth::check(&account.email, &account.smtp_host).await?;
+    log::info!(
+        "Sender auth check for {}: spf_covers_smtp={}, dkim_selectors={}, warnings={}",
+        report.domain, report.spf_covers_smtp_host, report.dkim_selectors.len(), report.warnings.len()
+    );
+    Ok(report)
+}
+
+/// Optional parts of a message sent with `email_send`
+#[derive(Debug, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct SendOptions {
+    text_body: Option<String>,
+    html_body: Option<String>,
+    /// Rendered to both bodies when set
+    markdown_body: Option<String>,
+    attachment_paths: Option<Vec<AttachmentPath>>,
+    /// Delegated mailbox to send as (see `delegation`)
+    on_behalf_of: Option<String>,
+    invite: Option<mail::calendar::NewInvite>,
+}
+
+/// Send an email
+/// SECURITY: Validates all recipients and enforces limits
+#[tauri::command]
+async fn email_send(
+    state: State<'_, AppState>,
+    account_id: String,
+    to: Vec<String>,
+    cc: Vec<String>,
+    bcc: Vec<String>,
+    subject: String,
+    options: SendOptions,
+) -> Result<(), String> {
+    let SendOptions { text_body, html_body, markdown_body, attachment_paths, on_behalf_of, invite } = options;
+
+    // SECURITY: Validate account ID
+    let id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+    if id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    // SECURITY: Validate recipient counts
+    let total_recipients = to.len() + cc.len() + bcc.len();
+    if total_recipients == 0 {
+        return Err("At least one recipient is required".to_string());
+    }
+    if total_recipients > MAX_RECIPIENTS {
+        return Err(format!("Too many recipients (max {})", MAX_RECIPIENTS));
+    }
+
+    // SECURITY: Validate all email addresses
+    for email in to.iter().chain(cc.iter()).chain(bcc.iter()) {
+        validate_email(email)?;
+    }
+
+    // SECURITY: Validate subject length
+    if subject.len() > 998 {
+        return Err("Subject too long (max 998 characters)".to_string());
+    }
+
+    // SECURITY: Check for header injection in subject
+    if subject.contains('\r') || subject.contains('\n') {
+        return Err("Invalid characters in subject".to_string());
+    }
+
+    let account = state.db.get_account(id)
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    // Sending as a delegated mailbox only for addresses configured on the account
+    let on_behalf_of = on_behalf_of
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty() && !address.eq_ignore_ascii_case(&account.email));
+    if let Some(address) = &on_behalf_of {
+        validate_email(address)?;
+        if !delegation::is_allowed(&state.db, id, address).map_err(|e| format!("Database error: {}", e))? {
+            return Err(format!("{} is not allowed to send on behalf of {}", account.email, address));
+        }
+    }
+
+    // Reject messages the provider would bounce before reading any attachment data
+    if let Some(paths) = &attachment_paths {
+        if !paths.is_empty() {
+            let sizes = collect_attachment_sizes(paths).await?;
+            let limit = mail::attachment_guard::provider_limit(&account.smtp_host, &account.email);
+            let has_strategy = load_large_attachment_strategy(&state.db).is_some();
+            let report = mail::attachment_guard::check_attachment_sizes(&limit, &sizes, has_strategy);
+
+            if report.exceeds_limit {
+                let mut message = format!(
+                    "Attachments too large for {} (encoded {:.1}MB, max {:.1}MB)",
+                    report.provider,
+                    report.total_encoded_size as f64 / (1024.0 * 1024.0),
+                    report.max_message_size as f64 / (1024.0 * 1024.0),
+                );
+                if report.suggest_link {
+                    message.push_str(". Share large files as a link instead");
+                }
+                return Err(message);
+            }
+        }
+    }
+
+    // A Markdown body is sent as its rendered HTML and text parts
+    let (text_body, html_body) = match markdown_body {
+        Some(_) if html_body.is_some() => {
+            return Err("Provide either an HTML or a Markdown body, not both".to_string());
+        }
+        Some(markdown) => {
+            let rendered = mail::markdown::render(&markdown);
+            (text_body.or(Some(rendered.text)), Some(rendered.html))
+        }
+        None => (text_body, html_body),
+    };
+
+    // Recipients whose client shows text only get a readable alternative
+    let text_body = text_body.filter(|text| !text.trim().is_empty()).or_else(|| html_body.as_deref().map(mail::html_text::html_to_text));
+
+    // A meeting invite travels as a text/calendar REQUEST part; To and Cc are the attendees
+    let mut attachment_paths = attachment_paths.unwrap_or_default();
+    let invite = match invite {
+        Some(invite) => {
+            let organizer = on_behalf_of.as_deref().unwrap_or(&account.email);
+            let uid = invites::new_uid(organizer);
+            let attendees: Vec<String> = to.iter().chain(cc.iter()).cloned().collect();
+            let organizer_name = Some(account.display_name.as_str()).filter(|name| !name.trim().is_empty());
+            let ics = mail::calendar::request(&invite, &uid, organizer, organizer_name, &attendees, chrono::Utc::now())?;
+            attachment_paths.push(
+                write_temp_attachment(
+                    mail::calendar::INVITE_FILENAME.to_string(),
+                    mail::calendar::REQUEST_CONTENT_TYPE.to_string(),
+                    ics.into_bytes(),
+                )
+                .await?,
+            );
+            Some((uid, invite, attendees))
+        }
+        None => None,
+    };
+
+    let mut message = pending_ops::QueuedSend {
+        to,
+        cc,
+        bcc,
+        subject,
+        text_body,
+        html_body,
+        attachment_paths,
+        on_behalf_of,
+    };
+    plugins::registry().before_send(&account, &mut message).await?;
+
+    // Known to be offline: queue without waiting for a connection timeout
+    let result = if state.connectivity.is_online() {
+        // Journaled so a crash mid-send queues the message again at startup
+        let journal_id = uuid::Uuid::new_v4().to_string();
+        journal::begin(&state.db, &journal_id, journal::JournalKind::Send, Some(id), &message)
+            .map_err(|e| format!("Failed to journal message: {}", e))?;
+        let result = deliver_email(&state.db, &state.credentials, id, &message, Some(&journal_id)).await;
+        if let Err(e) = journal::finish(&state.db, &journal_id) {
+            log::warn!("Failed to finish journal entry {}: {}", journal_id, e);
+        }
+        result
+    } else {
+        Err(mail::MailError::NotConnected)
+    };
+
+    match result {
+        Ok(()) => {}
+        Err(e) if pending_ops::should_wait(&e) => {
+            // Keep the message and deliver it once the connection or quota is back
+            log::warn!("Cannot send now, queueing message: {}", e);
+            state.pending_ops.enqueue(&pending_ops::PendingOperation::send(&account_id, message))
+                .map_err(|e| format!("Failed to queue message: {}", e))?;
+        }
+        Err(e) => return Err(sanitize_error_message(&e.to_string())),
+    }
+
+    // Track the invite so attendee replies can be matched to it
+    if let Some((uid, invite, attendees)) = invite {
+        if let Err(e) = invites::record_sent(&state.db, id, &uid, &invite, &attendees) {
+            log::warn!("Failed to record invite {}: {}", uid, e);
+        }
+    }
+    Ok(())
+}
+
+/// Deliver a validated message via SMTP (OAuth2 or password)
+/// Network failures are reported as `MailError::Connection` so callers can queue the message.
+/// A journaled send is advanced once the server accepted the message.
+async fn deliver_email(
+    db: &Database,
+    credentials: &credentials::CredentialCache,
+    id: i64,
+    message: &pending_ops::QueuedSend,
+    journal_id: Option<&str>,
+) -> mail::MailResult<()> {
+    let account = db.get_account(id)
+        .map_err(|e| mail::MailError::Config(format!("Database error: {}", e)))?;
+
+    // Password (or access token for OAuth)
+    let password = credentials.password(db, id)
+        .map_err(|e| match e {
+            credentials::CredentialError::Database(e) => mail::MailError::Config(format!("Database error: {}", e)),
+            e => mail::MailError::Authentication(e.to_string()),
+        })?
+        .to_string();
+
+    let recipients = (message.to.len() + message.cc.len() + message.bcc.len()) as u32;
+    wait_for_quota(db, &account, recipients).await?;
+
+    let pending_ops::QueuedSend { to, cc, bcc, subject, text_body, html_body, attachment_paths, on_behalf_of } = message.clone();
+
+    log::info!("Sending email from {} to {:?}", account.email, to);
+
+    // Check if this is an OAuth account
+    if account.oauth_provider.is_some() {
+        log::info!("Using OAuth2 SMTP for account: {}", account.email);
+
+        // Determine email body format
+        let (body_str, is_html) = if let Some(html) = html_body {
+            (html, true)
+        } else {
             (text_body.unwrap_or_default(), false)
         };
 
         // Load attachments
         let mut attachments_data = Vec::new();
-        if let Some(paths) = &attachment_paths {
-            for att_path in paths {
-                let data = tokio::fs::read(&att_path.path)
-                    .await
-                    .map_err(|e| format!("Failed to read attachment {}: {}", att_path.filename, e))?;
-
-                attachments_data.push(mail::smtp_oauth::AttachmentData {
-                    filename: att_path.filename.clone(),
-                    content_type: att_path.content_type.clone(),
-                    data,
-                });
-            }
+        for att_path in &attachment_paths {
+            let data = read_attachment(att_path).await?;
+            let content_type = mail::mime_detect::detect(&att_path.filename, &att_path.content_type, &data).content_type;
+
+            attachments_data.push(mail::smtp_oauth::AttachmentData {
+                filename: att_path.filename.clone(),
+                content_type,
+                data,
+                content_id: att_path.content_id.clone(),
+            });
         }
 
         // Use OAuth2 SMTP implementation
-        return mail::smtp_oauth::send_email_oauth(
+        let sent = mail::smtp_oauth::send_email_oauth(
             &account.smtp_host,
             account.smtp_port as u16,
             &account.email,
             &password, // This is the access token
-            &account.email,
+            on_behalf_of.as_deref().unwrap_or(&account.email),
             &to,
             &cc,
             &bcc,
@@ -2190,1953 +3864,4650 @@ async fn email_send(
         .await
         .map_err(|e| {
             log::error!("OAuth SMTP send failed: {}", e);
-            e.to_string()
+            e
         });
+        credentials.record_login(db, id, &sent);
+        if sent.is_ok() {
+            record_send(db, id, recipients);
+            record_delivered(db, journal_id);
+        }
+        return sent;
     }
 
     // Build and send email using lettre
     use lettre::{
-        message::{header::ContentType, Mailbox, MultiPart, SinglePart},
+        message::Mailbox,
         transport::smtp::authentication::Credentials,
-        AsyncSmtpTransport, AsyncTransport, Message,
+        AsyncTransport, Message,
     };
 
-    let from: Mailbox = account
+    let account_mailbox: Mailbox = account
         .email
         .parse()
-        .map_err(|e: lettre::address::AddressError| e.to_string())?;
-
-    let mut email_builder = Message::builder()
-        .from(from)
-        .subject(&subject);
+        .map_err(|e: lettre::address::AddressError| mail::MailError::Config(e.to_string()))?;
+
+    // On behalf of: the delegated mailbox in From, the account in Sender (and the envelope)
+    let mut email_builder = match &on_behalf_of {
+        Some(address) => {
+            let from: Mailbox = address
+                .parse()
+                .map_err(|e: lettre::address::AddressError| mail::MailError::Config(e.to_string()))?;
+            Message::builder().from(from).sender(account_mailbox)
+        }
+        None => Message::builder().from(account_mailbox),
+    }
+    .subject(&subject);
 
     // Add recipients
     for recipient in &to {
         let mailbox: Mailbox = recipient
             .parse()
-            .map_err(|e: lettre::address::AddressError| e.to_string())?;
+            .map_err(|e: lettre::address::AddressError| mail::MailError::Config(e.to_string()))?;
         email_builder = email_builder.to(mailbox);
     }
 
     for recipient in &cc {
         let mailbox: Mailbox = recipient
             .parse()
-            .map_err(|e: lettre::address::AddressError| e.to_string())?;
+            .map_err(|e: lettre::address::AddressError| mail::MailError::Config(e.to_string()))?;
         email_builder = email_builder.cc(mailbox);
     }
 
     for recipient in &bcc {
         let mailbox: Mailbox = recipient
             .parse()
-            .map_err(|e: lettre::address::AddressError| e.to_string())?;
+            .map_err(|e: lettre::address::AddressError| mail::MailError::Config(e.to_string()))?;
         email_builder = email_builder.bcc(mailbox);
     }
 
-    // Build body with or without attachments
-    let email = if let Some(paths) = &attachment_paths {
-        if !paths.is_empty() {
-            // Build multipart mixed body with attachments
-            let mut final_multipart = if let (Some(text), Some(html)) = (&text_body, &html_body) {
-                // Alternative text/html
-                MultiPart::mixed().multipart(
-                    MultiPart::alternative()
-                        .singlepart(
-                            SinglePart::builder()
-                                .header(ContentType::TEXT_PLAIN)
-                                .body(text.clone()),
-                        )
-                        .singlepart(
-                            SinglePart::builder()
-                                .header(ContentType::TEXT_HTML)
-                                .body(html.clone()),
-                        ),
-                )
-            } else if let Some(html) = &html_body {
-                MultiPart::mixed().singlepart(
-                    SinglePart::builder()
-                        .header(ContentType::TEXT_HTML)
-                        .body(html.clone()),
-                )
-            } else {
-                MultiPart::mixed().singlepart(
-                    SinglePart::builder()
-                        .header(ContentType::TEXT_PLAIN)
-                        .body(text_body.clone().unwrap_or_default()),
-                )
-            };
+    // Load attachments (parts with a content_id are embedded as inline images)
+    let mut parts = Vec::new();
+    for att_path in &attachment_paths {
+        let data = read_attachment(att_path).await?;
+        // Sent with the type of the actual content, whatever was declared
+        let content_type = mail::mime_detect::detect(&att_path.filename, &att_path.content_type, &data).content_type;
+
+        parts.push(mail::compose::OutgoingPart {
+            filename: att_path.filename.clone(),
+            content_type,
+            data,
+            content_id: att_path.content_id.clone(),
+        });
+    }
 
-            // Add all attachments
-            for att_path in paths {
-                let data = tokio::fs::read(&att_path.path)
-                    .await
-                    .map_err(|e| format!("Failed to read attachment {}: {}", att_path.filename, e))?;
+    let email = mail::compose::build_message(email_builder, text_body, html_body, parts)
+        .map_err(|e| mail::MailError::Smtp(e.to_string()))?;
+
+    // A message over the announced SIZE limit would be refused after the upload
+    if let Some(limit) = capabilities::get(db, id).ok().and_then(|caps| caps.smtp?.size_limit) {
+        let size = email.formatted().len() as u64;
+        if size > limit {
+            return Err(mail::MailError::Config(format!(
+                "Message is {} bytes but the server accepts at most {} bytes",
+                size, limit
+            )));
+        }
+    }
 
-                let content_type: ContentType = att_path.content_type
-                    .parse()
-                    .unwrap_or_else(|_| ContentType::parse("application/octet-stream").unwrap());
+    // Formatted before sending, since the transport consumes the message
+    let sent_copy = sent_copy::upload_enabled(db).unwrap_or(false).then(|| email.formatted());
 
-                final_multipart = final_multipart.singlepart(
-                    lettre::message::Attachment::new(att_path.filename.clone())
-                        .body(data, content_type),
-                );
-            }
+    let creds = Credentials::new(account.smtp_username.clone().unwrap_or(account.email.clone()), password);
 
-            email_builder
-                .multipart(final_multipart)
-                .map_err(|e| e.to_string())?
-        } else {
-            // No attachments, build simple body
-            if let (Some(text), Some(html)) = (&text_body, &html_body) {
-                email_builder
-                    .multipart(
-                        MultiPart::alternative()
-                            .singlepart(
-                                SinglePart::builder()
-                                    .header(ContentType::TEXT_PLAIN)
-                                    .body(text.clone()),
-                            )
-                            .singlepart(
-                                SinglePart::builder()
-                                    .header(ContentType::TEXT_HTML)
-                                    .body(html.clone()),
-                            ),
-                    )
-                    .map_err(|e| e.to_string())?
-            } else if let Some(html) = html_body {
-                email_builder
-                    .header(ContentType::TEXT_HTML)
-                    .body(html)
-                    .map_err(|e| e.to_string())?
-            } else {
-                email_builder
-                    .header(ContentType::TEXT_PLAIN)
-                    .body(text_body.unwrap_or_default())
-                    .map_err(|e| e.to_string())?
-            }
+    let mailer = smtp_transport(&account, creds)?;
+
+    let sent = mailer.send(email).await.map_err(smtp_error);
+    credentials.record_login(db, id, &sent);
+    sent?;
+    record_send(db, id, recipients);
+    record_delivered(db, journal_id);
+
+    log::info!("Email sent successfully");
+
+    // The message is out; a failed Sent copy must not fail the send
+    if let Some(raw) = sent_copy {
+        if let Err(e) = save_sent_copy(db, credentials, id, &subject, &raw).await {
+            log::warn!("Failed to save sent copy: {}", e);
         }
+    }
+    Ok(())
+}
+
+/// Redirect a stored message unchanged to new recipients (Resent-* headers)
+#[tauri::command]
+async fn email_redirect(
+    state: State<'_, AppState>,
+    email_id: i64,
+    to: Vec<String>,
+) -> Result<(), String> {
+    if to.is_empty() {
+        return Err("At least one recipient is required".to_string());
+    }
+    if to.len() > MAX_RECIPIENTS {
+        return Err(format!("Too many recipients (max {})", MAX_RECIPIENTS));
+    }
+    for address in &to {
+        validate_email(address)?;
+    }
+
+    let email = state.db.get_email(email_id)
+        .map_err(|e| format!("Failed to get email: {}", e))?;
+    let folder = state.db.get_folder_by_id(email.folder_id)
+        .map_err(|e| format!("Failed to get folder: {}", e))?;
+
+    let raw = if folder.is_local {
+        state.db.get_local_email_source(email_id)
+            .map_err(|e| format!("Failed to read message source: {}", e))?
     } else {
-        // No attachments, build simple body
-        if let (Some(text), Some(html)) = (&text_body, &html_body) {
-            email_builder
-                .multipart(
-                    MultiPart::alternative()
-                        .singlepart(
-                            SinglePart::builder()
-                                .header(ContentType::TEXT_PLAIN)
-                                .body(text.clone()),
-                        )
-                        .singlepart(
-                            SinglePart::builder()
-                                .header(ContentType::TEXT_HTML)
-                                .body(html.clone()),
-                        ),
-                )
-                .map_err(|e| e.to_string())?
-        } else if let Some(html) = html_body {
-            email_builder
-                .header(ContentType::TEXT_HTML)
-                .body(html)
-                .map_err(|e| e.to_string())?
-        } else {
-            email_builder
-                .header(ContentType::TEXT_PLAIN)
-                .body(text_body.unwrap_or_default())
-                .map_err(|e| e.to_string())?
-        }
+        let mut client = connect_fresh_imap(&state.db, &state.credentials, email.account_id).await?;
+        let raw = client.fetch_raw(&folder.remote_name, email.uid).await;
+        let _ = client.disconnect().await;
+        raw.map_err(|e| format!("Failed to fetch message: {}", e))?
     };
 
-    let creds = Credentials::new(account.smtp_username.clone().unwrap_or(account.email.clone()), password);
+    redirect_raw(&state.db, &state.credentials, email.account_id, &to, &raw)
+        .await
+        .map_err(|e| sanitize_error_message(&e.to_string()))?;
+
+    log::info!("✓ Redirected email {} to {} recipient(s)", email_id, to.len());
+    Ok(())
+}
+
+/// Send a raw message with a resent block; the envelope sender is the redirecting account
+async fn redirect_raw(
+    db: &Database,
+    credentials: &credentials::CredentialCache,
+    id: i64,
+    to: &[String],
+    raw: &[u8],
+) -> mail::MailResult<()> {
+    let account = db.get_account(id)
+        .map_err(|e| mail::MailError::Config(format!("Database error: {}", e)))?;
+    let password = credentials.password(db, id)
+        .map_err(|e| match e {
+            credentials::CredentialError::Database(e) => mail::MailError::Config(format!("Database error: {}", e)),
+            e => mail::MailError::Authentication(e.to_string()),
+        })?
+        .to_string();
+
+    wait_for_quota(db, &account, to.len() as u32).await?;
+    let message = mail::redirect::resend(raw, &mail::redirect::ResentFields::now(&account.email, to));
+
+    let sent = if account.oauth_provider.is_some() {
+        mail::smtp_oauth::send_raw_oauth(
+            &account.smtp_host,
+            account.smtp_port as u16,
+            &account.email,
+            &password,
+            &account.email,
+            to,
+            &message,
+        )
+        .await
+    } else {
+        use lettre::{address::Envelope, transport::smtp::authentication::Credentials, Address, AsyncTransport};
+
+        let parse = |address: &str| {
+            address
+                .parse::<Address>()
+                .map_err(|e| mail::MailError::Config(e.to_string()))
+        };
+        let recipients = to.iter().map(|address| parse(address)).collect::<mail::MailResult<Vec<_>>>()?;
+        let envelope = Envelope::new(Some(parse(&account.email)?), recipients)
+            .map_err(|e| mail::MailError::Config(e.to_string()))?;
+
+        let creds = Credentials::new(account.smtp_username.clone().unwrap_or(account.email.clone()), password);
+        smtp_transport(&account, creds)?
+            .send_raw(&envelope, &message)
+            .await
+            .map(|_| ())
+            .map_err(smtp_error)
+    };
+    credentials.record_login(db, id, &sent);
+    if sent.is_ok() {
+        record_send(db, id, to.len() as u32);
+    }
+    sent
+}
+
+/// Hold a send until the account's sending quota admits it
+/// Waits out a full minute window; a used-up day fails with `QuotaExceeded`.
+async fn wait_for_quota(db: &Database, account: &db::Account, recipients: u32) -> mail::MailResult<()> {
+    let db_error = |e: db::DbError| mail::MailError::Config(format!("Database error: {}", e));
+    let (_, limits, _) = send_quota::limits(db, account.id, &account.smtp_host, &account.email).map_err(db_error)?;
+    loop {
+        match send_quota::admit(db, account.id, &limits, recipients, chrono::Utc::now()).map_err(db_error)? {
+            send_quota::Admission::Now => return Ok(()),
+            send_quota::Admission::Wait(wait) => {
+                log::info!("Send rate limit of {} reached, waiting {:?}", account.email, wait);
+                tokio::time::sleep(wait).await;
+            }
+            send_quota::Admission::DailyLimit(until) => {
+                return Err(mail::MailError::QuotaExceeded(format!(
+                    "{} recipients per day, sending again from {}",
+                    limits.per_day.unwrap_or_default(),
+                    until.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                )));
+            }
+            send_quota::Admission::TooManyRecipients(per_day) => {
+                return Err(mail::MailError::Config(format!(
+                    "More recipients than the daily sending limit of {}",
+                    per_day
+                )));
+            }
+        }
+    }
+}
+
+/// Count a delivered message against the account's sending quota
+fn record_send(db: &Database, account_id: i64, recipients: u32) {
+    if let Err(e) = send_quota::record(db, account_id, recipients, chrono::Utc::now()) {
+        log::warn!("Failed to record send for quota: {}", e);
+    }
+}
+
+/// Advance a journaled send past delivery (failures are only logged)
+fn record_delivered(db: &Database, journal_id: Option<&str>) {
+    if let Some(journal_id) = journal_id {
+        if let Err(e) = journal::advance(db, journal_id, journal::JournalStep::RemoteDone) {
+            log::warn!("Failed to journal delivery of {}: {}", journal_id, e);
+        }
+    }
+}
 
-    let security = parse_security(&account.smtp_security);
+/// SMTP transport for a password-authenticated account
+fn smtp_transport(
+    account: &db::Account,
+    creds: lettre::transport::smtp::authentication::Credentials,
+) -> mail::MailResult<lettre::AsyncSmtpTransport<lettre::Tokio1Executor>> {
+    use lettre::AsyncSmtpTransport;
 
-    let mailer = match security {
+    let mailer = match parse_security(&account.smtp_security) {
         SecurityType::SSL => {
             AsyncSmtpTransport::<lettre::Tokio1Executor>::relay(&account.smtp_host)
-                .map_err(|e| e.to_string())?
+                .map_err(|e| mail::MailError::Config(e.to_string()))?
                 .credentials(creds)
                 .port(account.smtp_port as u16)
                 .build()
         }
         SecurityType::STARTTLS => {
             AsyncSmtpTransport::<lettre::Tokio1Executor>::starttls_relay(&account.smtp_host)
-                .map_err(|e| e.to_string())?
+                .map_err(|e| mail::MailError::Config(e.to_string()))?
+                .credentials(creds)
+                .port(account.smtp_port as u16)
+                .build()
+        }
+        // SECURITY: Plain SMTP only to this machine (local bridges, test servers)
+        SecurityType::NONE if mail::config::is_loopback_host(&account.smtp_host) => {
+            AsyncSmtpTransport::<lettre::Tokio1Executor>::builder_dangerous(&account.smtp_host)
                 .credentials(creds)
                 .port(account.smtp_port as u16)
                 .build()
         }
         SecurityType::NONE => {
-            return Err("Insecure SMTP not supported".to_string());
+            return Err(mail::MailError::Config("Insecure SMTP is only supported to localhost".to_string()));
         }
     };
+    Ok(mailer)
+}
+
+/// Classify an SMTP failure
+/// Server replies mean we reached it; anything else is a network problem
+fn smtp_error(e: lettre::transport::smtp::Error) -> mail::MailError {
+    if e.status().is_some_and(|code| code.to_string() == "535") {
+        mail::MailError::Authentication(e.to_string())
+    } else if e.is_response() || e.is_client() {
+        mail::MailError::Smtp(e.to_string())
+    } else {
+        mail::MailError::Connection(e.to_string())
+    }
+}
+
+/// Upload a sent message to the account's Sent folder
+async fn save_sent_copy(
+    db: &Database,
+    credentials: &credentials::CredentialCache,
+    account_id: i64,
+    subject: &str,
+    raw: &[u8],
+) -> Result<(), String> {
+    let Some(folder) = db.get_special_folder(account_id, "sent").map_err(|e| format!("Database error: {}", e))? else {
+        log::info!("No Sent folder known for account {}, not saving a copy", account_id);
+        return Ok(());
+    };
 
-    mailer.send(email).await.map_err(|e| e.to_string())?;
+    let mut client = connect_fresh_imap(db, credentials, account_id).await?;
+    let uploaded = sent_copy::upload(db, &mut client, account_id, &folder, subject, raw).await;
+    let _ = client.disconnect().await;
+    uploaded
+        .map(|_| ())
+        .map_err(|e| format!("Failed to upload sent copy: {}", e))
+}
 
-    log::info!("Email sent successfully");
-    Ok(())
+/// Read an attachment queued for sending
+/// A missing file is a configuration problem, not a connectivity one, so it is never retried
+async fn read_attachment(att_path: &AttachmentPath) -> mail::MailResult<Vec<u8>> {
+    tokio::fs::read(&att_path.path)
+        .await
+        .map_err(|e| mail::MailError::Config(format!("Failed to read attachment {}: {}", att_path.filename, e)))
 }
 
 // ============================================================================
-// Attachment Commands
+// Local Folder Commands
 // ============================================================================
 
-/// Write temporary file from byte array (for frontend File objects)
-#[tauri::command]
-async fn write_temp_attachment(
-    filename: String,
-    content_type: String,
-    data: Vec<u8>,
-) -> Result<AttachmentPath, String> {
-    // SECURITY: Validate filename
-    if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
-        return Err("Invalid filename".to_string());
-    }
+/// Open a fresh IMAP connection for an account (avoids conflicts with the shared session)
+async fn connect_fresh_imap(
+    db: &Database,
+    credentials: &credentials::CredentialCache,
+    account_id: i64,
+) -> Result<AsyncImapClient, String> {
+    let account = db.get_account(account_id)
+        .map_err(|e| format!("Failed to get account: {}", e))?;
+    let password = credentials.password(db, account_id)
+        .map_err(|e| e.to_string())?
+        .to_string();
 
-    // SECURITY: Limit file size (50MB)
-    const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
-    if data.len() > MAX_FILE_SIZE {
-        return Err("File too large (max 50MB)".to_string());
-    }
+    let config = ImapConfig {
+        host: account.imap_host.clone(),
+        port: account.imap_port as u16,
+        username: account.imap_username.clone().unwrap_or(account.email.clone()),
+        password,
+        security: parse_security(&account.imap_security),
+        accept_invalid_certs: account.accept_invalid_certs,
+        oauth_provider: account.oauth_provider.clone(),
+    };
 
-    // Create temp directory for attachments
-    let temp_dir = std::env::temp_dir().join("owlivion-mail-attachments");
-    tokio::fs::create_dir_all(&temp_dir)
-        .await
-        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let mut client = AsyncImapClient::new(config);
+    let connected = client.connect().await;
+    credentials.record_login(db, account_id, &connected);
+    connected.map_err(|e| format!("Failed to connect: {}", e))?;
+    record_imap_capabilities(db, account_id, &client);
+    Ok(client)
+}
 
-    // Generate unique filename
-    let unique_name = format!("{}_{}", uuid::Uuid::new_v4(), filename);
-    let temp_path = temp_dir.join(&unique_name);
+/// Cache the IMAP extensions a freshly connected client saw at login
+fn record_imap_capabilities(db: &Database, account_id: i64, client: &AsyncImapClient) {
+    if let Some(caps) = client.capabilities() {
+        if let Err(e) = capabilities::store_imap(db, account_id, caps) {
+            log::warn!("Failed to cache IMAP capabilities for account {}: {}", account_id, e);
+        }
+    }
+}
 
-    // Write file to temp location
-    tokio::fs::write(&temp_path, data)
-        .await
-        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+/// Build a database email record from a raw RFC 822 message
+fn new_email_from_raw(account_id: i64, folder_id: i64, raw: &[u8]) -> Result<db::NewEmail, String> {
+    use mail_parser::MimeHeaders;
 
-    Ok(AttachmentPath {
-        path: temp_path.to_string_lossy().to_string(),
-        filename,
-        content_type,
+    let parsed = mail_parser::MessageParser::default()
+        .parse(raw)
+        .ok_or_else(|| "Failed to parse message".to_string())?;
+
+    let addresses_json = |addresses: Option<&mail_parser::Address>| -> String {
+        let list: Vec<serde_json::Value> = addresses
+            .map(|a| {
+                a.iter()
+                    .map(|addr| serde_json::json!({
+                        "email": addr.address().unwrap_or_default(),
+                        "name": addr.name().unwrap_or_default(),
+                    }))
+                    .collect()
+            })
+            .unwrap_or_default();
+        serde_json::Value::Array(list).to_string()
+    };
+
+    let from = parsed.from().and_then(|a| a.first());
+    let body_text = mail::charset::body_text(&parsed, 0);
+    let body_html = mail::charset::body_html(&parsed, 0);
+    let preview: String = body_text
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(200)
+        .collect();
+
+    Ok(db::NewEmail {
+        account_id,
+        folder_id,
+        message_id: parsed
+            .message_id()
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| format!("local-{}", uuid::Uuid::new_v4())),
+        uid: 0, // Assigned when stored
+        from_address: from.and_then(|a| a.address()).unwrap_or("unknown").to_string(),
+        from_name: from.and_then(|a| a.name()).map(|n| n.to_string()),
+        to_addresses: addresses_json(parsed.to()),
+        cc_addresses: addresses_json(parsed.cc()),
+        bcc_addresses: addresses_json(parsed.bcc()),
+        reply_to: parsed.reply_to().and_then(|a| a.first()).and_then(|a| a.address()).map(|a| a.to_string()),
+        subject: parsed.subject().unwrap_or_default().to_string(),
+        preview,
+        body_text,
+        body_html,
+        date: parsed
+            .date()
+            .map(|d| d.to_rfc3339())
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+        is_read: true,
+        is_starred: false,
+        is_deleted: false,
+        is_spam: false,
+        is_draft: false,
+        is_answered: false,
+        is_forwarded: false,
+        has_attachments: parsed.attachment_count() > 0,
+        has_inline_images: parsed.attachments().any(|a| a.content_id().is_some()),
+        thread_id: None,
+        in_reply_to: parsed.in_reply_to().as_text().map(|s| s.to_string()),
+        references_header: parsed
+            .references()
+            .as_text_list()
+            .map(|refs| serde_json::to_string(&refs).unwrap_or_default()),
+        raw_headers: None,
+        raw_size: raw.len() as i32,
+        priority: 3,
+        labels: "[]".to_string(),
     })
 }
 
-/// Upload attachment and return temporary path
+/// Create a local-only folder (stored in the database, never synced to IMAP)
 #[tauri::command]
-async fn attachment_upload(
-    file_path: String,
-    filename: String,
-    content_type: String,
-) -> Result<AttachmentPath, String> {
-    // SECURITY: Validate filename
-    if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
-        return Err("Invalid filename".to_string());
-    }
+async fn local_folder_create(
+    state: State<'_, AppState>,
+    account_id: String,
+    name: String,
+) -> Result<db::Folder, String> {
+    let account_id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+    let folder_id = state.db.create_local_folder(account_id, &name)
+        .map_err(|e| format!("Failed to create local folder: {}", e))?;
 
-    // Read file
-    let data = tokio::fs::read(&file_path)
-        .await
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+    log::info!("✓ Created local folder '{}' (id={}) for account {}", name.trim(), folder_id, account_id);
+    state.db.get_folder_by_id(folder_id)
+        .map_err(|e| format!("Failed to get folder: {}", e))
+}
 
-    // SECURITY: Limit file size (50MB)
-    const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
-    if data.len() > MAX_FILE_SIZE {
-        return Err("File too large (max 50MB)".to_string());
-    }
+/// List local-only folders of an account
+#[tauri::command]
+async fn local_folder_list(
+    state: State<'_, AppState>,
+    account_id: String,
+) -> Result<Vec<db::Folder>, String> {
+    let account_id: i64 = account_id.parse().map_err(|_| "Invalid account ID")?;
+    state.db.get_local_folders(account_id)
+        .map_err(|e| format!("Failed to list local folders: {}", e))
+}
 
-    // Create temp directory for attachments
-    let temp_dir = std::env::temp_dir().join("owlivion-mail-attachments");
-    tokio::fs::create_dir_all(&temp_dir)
-        .await
-        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+/// Rename a local-only folder
+#[tauri::command]
+async fn local_folder_rename(
+    state: State<'_, AppState>,
+    folder_id: i64,
+    name: String,
+) -> Result<(), String> {
+    state.db.rename_local_folder(folder_id, &name)
+        .map_err(|e| format!("Failed to rename local folder: {}", e))
+}
 
-    // Generate unique filename
-    let unique_name = format!("{}_{}", uuid::Uuid::new_v4(), filename);
-    let temp_path = temp_dir.join(&unique_name);
+/// Delete a local-only folder and all messages stored in it
+/// Requires a `local_folder_delete` confirmation token for the folder id.
+#[tauri::command]
+async fn local_folder_delete(
+    state: State<'_, AppState>,
+    folder_id: i64,
+    confirm_token: String,
+) -> Result<(), String> {
+    state
+        .confirmations
+        .consume(authz::Scope::LocalFolderDelete, &folder_id.to_string(), &confirm_token, Instant::now())?;
+    let folder = state.db.get_folder_by_id(folder_id).ok();
+    state.db.delete_local_folder(folder_id)
+        .map_err(|e| format!("Failed to delete local folder: {}", e))?;
+    log_activity(
+        &state,
+        folder.as_ref().map(|folder| folder.account_id),
+        activity::ActivityAction::LocalFolderDelete,
+        &format!("Deleted local folder {}", folder.as_ref().map_or("", |folder| folder.name.as_str())),
+        None,
+    );
 
-    // Copy file to temp location
-    tokio::fs::write(&temp_path, data)
-        .await
-        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+    log::info!("✓ Deleted local folder {}", folder_id);
+    Ok(())
+}
 
-    Ok(AttachmentPath {
-        path: temp_path.to_string_lossy().to_string(),
-        filename,
-        content_type,
-    })
+/// List messages stored in a local folder
+/// SECURITY: Enforces pagination limits to prevent DoS
+#[tauri::command]
+async fn local_folder_emails(
+    state: State<'_, AppState>,
+    folder_id: i64,
+    page: u32,
+    page_size: u32,
+) -> Result<Vec<EmailSummary>, String> {
+    let folder = state.db.get_folder_by_id(folder_id)
+        .map_err(|e| format!("Failed to get folder: {}", e))?;
+    if !folder.is_local {
+        return Err("Not a local folder".to_string());
+    }
+
+    let safe_page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+    state.db.get_emails(
+        folder.account_id,
+        folder_id,
+        safe_page_size as i32,
+        (page * safe_page_size) as i32,
+    )
+    .map_err(|e| format!("Failed to list emails: {}", e))
 }
 
-/// Get attachments for an email
+/// List a window of a folder's messages for virtual scrolling (newest first)
+/// Pass the date and UID of the last message already shown to get the next
+/// window; the cost is the same at any depth, unlike page/offset listing.
 #[tauri::command]
-async fn get_email_attachments(
+async fn email_list_window(
     state: State<'_, AppState>,
-    email_id: i64,
-) -> Result<Vec<db::Attachment>, String> {
-    state.db.get_attachments_for_email(email_id)
-        .map_err(|e| format!("Failed to get attachments: {}", e))
+    folder_id: i64,
+    before_date: Option<String>,
+    before_uid: Option<u32>,
+    count: u32,
+) -> Result<db::EmailWindow, String> {
+    let before = match (before_date, before_uid) {
+        (Some(date), Some(uid)) => Some(db::EmailCursor { date, uid }),
+        (None, None) => None,
+        _ => return Err("Both before_date and before_uid are required".to_string()),
+    };
+
+    state.db.get_emails_window(folder_id, before.as_ref(), count.min(i32::MAX as u32) as i32)
+        .map_err(|e| format!("Failed to list emails: {}", e))
 }
 
-/// Download attachment to user-selected location
+/// Compare the search indexes with the stored mail and templates
 #[tauri::command]
-async fn attachment_download(
+async fn search_index_check(
     state: State<'_, AppState>,
-    account_id: i64,
-    email_id: i64,
-    attachment_id: i64,
-    save_path: String,
-) -> Result<(), String> {
-    // Get attachment info
-    let attachment = state.db.get_attachment(attachment_id)
-        .map_err(|e| format!("Failed to get attachment: {}", e))?;
+    account_id: Option<i64>,
+) -> Result<Vec<search_index::IndexStatus>, String> {
+    search_index::SearchIndex::ALL
+        .into_iter()
+        .map(|index| search_index::check(&state.db, index, account_id))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to check search index: {}", e))
+}
 
-    // Verify attachment belongs to email
-    if attachment.email_id != email_id {
-        return Err("Attachment does not belong to this email".to_string());
-    }
+/// Rebuild the search indexes, e.g. when results look stale after a crash
+#[tauri::command]
+async fn search_index_rebuild(
+    state: State<'_, AppState>,
+    account_id: Option<i64>,
+) -> Result<Vec<search_index::IndexStatus>, String> {
+    search_index::rebuild_all(&state.db, account_id)
+        .map_err(|e| format!("Failed to rebuild search index: {}", e))
+}
 
-    // Check if already downloaded locally
-    if attachment.is_downloaded {
-        if let Some(local_path) = &attachment.local_path {
-            if tokio::fs::metadata(local_path).await.is_ok() {
-                // Copy to save location
-                tokio::fs::copy(local_path, &save_path)
-                    .await
-                    .map_err(|e| format!("Failed to copy file: {}", e))?;
-                return Ok(());
-            }
-        }
-    }
+/// Outcome of the startup database integrity check
+/// The recovery event fires before the UI listens, so it can ask here.
+#[tauri::command]
+async fn db_recovery_report(state: State<'_, AppState>) -> Result<db::recovery::RecoveryReport, String> {
+    Ok(state.startup_recovery.clone())
+}
 
-    // Need to download from IMAP server
-    // Get account info
-    let account = state.db.get_account(account_id)
-        .map_err(|e| format!("Failed to get account: {}", e))?;
+/// Run database maintenance now (ANALYZE, vacuum, WAL checkpoint)
+#[tauri::command]
+async fn db_maintenance_run(state: State<'_, AppState>) -> Result<maintenance::MaintenanceReport, String> {
+    maintenance::run(&state.db)
+        .map_err(|e| format!("Failed to run database maintenance: {}", e))
+}
+
+/// Report of the last database maintenance run
+#[tauri::command]
+async fn db_maintenance_last(state: State<'_, AppState>) -> Result<Option<maintenance::MaintenanceReport>, String> {
+    maintenance::last_report(&state.db)
+        .map_err(|e| format!("Failed to load maintenance report: {}", e))
+}
+
+/// Schedule of the spam training sweep
+#[tauri::command]
+async fn spam_training_get_config(state: State<'_, AppState>) -> Result<spam::TrainingConfig, String> {
+    spam::load_config(&state.db)
+        .map_err(|e| format!("Failed to load spam training config: {}", e))
+}
+
+/// Turn the spam training sweep on or off and set its interval
+#[tauri::command]
+async fn spam_training_set_config(
+    state: State<'_, AppState>,
+    config: spam::TrainingConfig,
+) -> Result<spam::TrainingConfig, String> {
+    config.validate()?;
+    spam::save_config(&state.db, &config)
+        .map_err(|e| format!("Failed to save spam training config: {}", e))?;
+    Ok(config)
+}
 
-    let encrypted_password = state.db.get_account_password(account_id)
-        .map_err(|e| format!("Failed to get password: {}", e))?
-        .ok_or_else(|| "No password stored".to_string())?;
+/// Train on messages moved into or out of Junk now
+#[tauri::command]
+async fn spam_training_run(state: State<'_, AppState>) -> Result<spam::SweepReport, String> {
+    spam::run(&state.db)
+        .map_err(|e| format!("Failed to train spam classifier: {}", e))
+}
+
+/// Report of the last spam training sweep
+#[tauri::command]
+async fn spam_training_last(state: State<'_, AppState>) -> Result<Option<spam::SweepReport>, String> {
+    spam::last_report(&state.db)
+        .map_err(|e| format!("Failed to load spam training report: {}", e))
+}
 
-    let password = crypto::decrypt_password(&encrypted_password)
-        .map_err(|e| format!("Password decryption failed: {}", e))?;
+/// Spam probability of a message and its sender's reputation
+#[tauri::command]
+async fn spam_score(state: State<'_, AppState>, email_id: i64) -> Result<spam::SpamScore, String> {
+    spam::score(&state.db, email_id)
+        .map_err(|e| format!("Failed to score message: {}", e))
+}
 
-    // Get email info to find folder
+/// Why a message is considered spam: deciding tokens, sender reputation and the
+/// verdicts of server-side filters from its headers
+/// The headers are read from the server; offline, the report covers the local classifier only.
+#[tauri::command]
+async fn email_spam_report(state: State<'_, AppState>, email_id: i64) -> Result<spam::SpamReport, String> {
     let email = state.db.get_email(email_id)
         .map_err(|e| format!("Failed to get email: {}", e))?;
-
     let folder = state.db.get_folder_by_id(email.folder_id)
         .map_err(|e| format!("Failed to get folder: {}", e))?;
 
-    // Connect to IMAP and fetch email with attachments
-    let config = ImapConfig {
-        host: account.imap_host.clone(),
-        port: account.imap_port as u16,
-        username: account.imap_username.clone().unwrap_or(account.email.clone()),
-        password: password.clone(),
-        security: parse_security(&account.imap_security),
-        accept_invalid_certs: account.accept_invalid_certs,
-        oauth_provider: account.oauth_provider.clone(),
+    let raw = if folder.is_local {
+        state.db.get_local_email_source(email_id).map_err(|e| e.to_string())
+    } else {
+        match connect_fresh_imap(&state.db, &state.credentials, email.account_id).await {
+            Ok(mut client) => {
+                let raw = client.fetch_raw(&folder.remote_name, email.uid).await;
+                let _ = client.disconnect().await;
+                raw.map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e),
+        }
+    };
+    let headers = match raw {
+        Ok(raw) => mail::headers::parse(&raw),
+        Err(e) => {
+            log::warn!("email_spam_report: headers of email {} unavailable: {}", email_id, e);
+            Vec::new()
+        }
     };
 
-    let mut imap_client = AsyncImapClient::new(config);
-    imap_client.connect().await
-        .map_err(|e| format!("Failed to connect to IMAP: {}", e))?;
-
-    let parsed_email = imap_client.fetch_email(&folder.remote_name, email.uid).await
-        .map_err(|e| format!("Failed to fetch email: {}", e))?;
-
-    // Find attachment in parsed email
-    let att_info = parsed_email.attachments.iter()
-        .find(|a| a.filename == attachment.filename)
-        .ok_or_else(|| "Attachment not found in email".to_string())?;
-
-    // For now, we can't download individual attachments - would need to parse BODYSTRUCTURE
-    // This is a limitation - we'll note it for future improvement
-    return Err("Attachment download from server not yet implemented. Please re-fetch the email.".to_string());
+    spam::report(&state.db, email_id, &headers)
+        .map_err(|e| format!("Failed to explain spam score: {}", e))
 }
 
-// ============================================================================
-// Sync Commands
-// ============================================================================
+/// Run a block's filter actions on the sender's existing mail
+async fn apply_block(state: &AppState, blocked: &blocklist::BlockedSender) -> Result<usize, String> {
+    let Some(filter_id) = blocked.filter_id else {
+        return Err("The filter of this block was deleted; block the sender again".to_string());
+    };
+    let filter = state
+        .db
+        .get_filter(filter_id)
+        .map_err(|e| format!("Failed to get filter: {}", e))?;
+    let email_ids = blocklist::matching_emails(&state.db, blocked)
+        .map_err(|e| format!("Failed to find messages: {}", e))?;
 
-use sync::SyncConfig;
-use std::sync::Mutex as StdMutex;
+    let engine = filters::FilterEngine::new(state.db.clone()).with_events(state.store_events.clone());
+    for &email_id in &email_ids {
+        engine
+            .execute_actions(email_id, filter.actions.clone())
+            .await
+            .map_err(|e| format!("Failed to execute actions: {}", e))?;
+    }
+    Ok(email_ids.len())
+}
 
-/// Register new Owlivion Account
+/// Block a sender address or domain, optionally acting on their existing mail
 #[tauri::command]
-async fn sync_register(
+async fn sender_block(
     state: State<'_, AppState>,
-    email: String,
-    password: String,
-    master_password: String,
-) -> Result<(), String> {
-    let manager = state.get_sync_manager()?;
-    manager.register(email, password, master_password).await
-        .map_err(|e| format!("Registration failed: {}", e))
+    account_id: i64,
+    address_or_domain: String,
+    action: blocklist::BlockAction,
+    apply_to_existing: bool,
+) -> Result<SenderBlockResult, String> {
+    let blocked = blocklist::block(&state.db, account_id, &address_or_domain, action)
+        .map_err(|e| format!("Failed to block sender: {}", e))?;
+    let applied = if apply_to_existing {
+        apply_block(&state, &blocked).await?
+    } else {
+        0
+    };
+    Ok(SenderBlockResult { blocked, applied })
 }
 
-/// Login to Owlivion Account
+/// Blocked senders of an account
 #[tauri::command]
-async fn sync_login(
+async fn sender_block_list(
     state: State<'_, AppState>,
-    email: String,
-    password: String,
-) -> Result<(), String> {
-    let manager = state.get_sync_manager()?;
-    manager.login(email, password).await
-        .map_err(|e| format!("Login failed: {}", e))
+    account_id: i64,
+) -> Result<Vec<blocklist::BlockedSender>, String> {
+    blocklist::list(&state.db, account_id)
+        .map_err(|e| format!("Failed to list blocked senders: {}", e))
 }
 
-/// Logout from Owlivion Account
+/// Unblock a sender and delete the filter created for them
 #[tauri::command]
-async fn sync_logout(state: State<'_, AppState>) -> Result<(), String> {
-    let manager = state.get_sync_manager()?;
-    manager.logout().await
-        .map_err(|e| format!("Logout failed: {}", e))
+async fn sender_unblock(state: State<'_, AppState>, block_id: i64) -> Result<(), String> {
+    blocklist::unblock(&state.db, block_id)
+        .map_err(|e| format!("Failed to unblock sender: {}", e))
 }
 
-/// Start manual sync (bidirectional with conflict detection)
+/// Apply a block to the sender's existing mail; returns the number of messages
+/// Requires a `sender_block_apply` confirmation token for the block id.
 #[tauri::command]
-async fn sync_start(state: State<'_, AppState>, master_password: String) -> Result<SyncResultDto, String> {
-    let manager = state.get_sync_manager()?;
-    let result = manager.sync_all(&master_password).await
-        .map_err(|e| format!("Sync failed: {}", e))?;
+async fn sender_block_apply(state: State<'_, AppState>, block_id: i64, confirm_token: String) -> Result<usize, String> {
+    state
+        .confirmations
+        .consume(authz::Scope::SenderBlockApply, &block_id.to_string(), &confirm_token, Instant::now())?;
+    let blocked = blocklist::get(&state.db, block_id)
+        .map_err(|e| format!("Failed to get blocked sender: {}", e))?;
+    apply_block(&state, &blocked).await
+}
 
-    Ok(SyncResultDto {
-        accounts_synced: result.accounts_synced,
-        contacts_synced: result.contacts_synced,
-        preferences_synced: result.preferences_synced,
-        signatures_synced: result.signatures_synced,
-        errors: result.errors,
-        conflicts: result.conflicts.map(|conflicts| {
-            conflicts.into_iter().map(|c| ConflictInfoDto {
-                data_type: c.data_type,
-                local_version: c.local_version,
-                server_version: c.server_version,
-                local_updated_at: c.local_updated_at.map(|t| t.to_rfc3339()),
-                server_updated_at: c.server_updated_at.map(|t| t.to_rfc3339()),
-                strategy: format!("{:?}", c.strategy),
-                conflict_details: c.conflict_details,
-                local_data: c.local_data,
-                server_data: c.server_data,
-            }).collect()
-        }),
-    })
+/// Sending quota of an account: limits in effect and recipients sent recently
+#[tauri::command]
+async fn send_quota_status(state: State<'_, AppState>, account_id: i64) -> Result<send_quota::QuotaStatus, String> {
+    let account = state.db.get_account(account_id)
+        .map_err(|e| format!("Failed to get account: {}", e))?;
+    send_quota::status(&state.db, account_id, &account.smtp_host, &account.email, chrono::Utc::now())
+        .map_err(|e| format!("Failed to get sending quota: {}", e))
 }
 
-/// Resolve a sync conflict manually
+/// Set an account's own sending limits; `None` returns to the provider's
 #[tauri::command]
-async fn sync_resolve_conflict(
+async fn send_quota_set_limits(
     state: State<'_, AppState>,
-    data_type: String,
-    strategy: String,
-    master_password: String,
+    account_id: i64,
+    limits: Option<send_quota::QuotaLimits>,
 ) -> Result<(), String> {
-    let manager = state.get_sync_manager()?;
-
-    // Parse data type
-    let data_type_enum = match data_type.as_str() {
-        "accounts" => crate::sync::SyncDataType::Accounts,
-        "contacts" => crate::sync::SyncDataType::Contacts,
-        "preferences" => crate::sync::SyncDataType::Preferences,
-        "signatures" => crate::sync::SyncDataType::Signatures,
-        _ => return Err("Invalid data type".to_string()),
-    };
+    if let Some(limits) = &limits {
+        limits.validate()?;
+    }
+    send_quota::set_custom_limits(&state.db, account_id, limits)
+        .map_err(|e| format!("Failed to save sending limits: {}", e))
+}
 
-    // Parse strategy
-    let strategy_enum = match strategy.as_str() {
-        "use_local" => crate::sync::ConflictStrategy::UseLocal,
-        "use_server" => crate::sync::ConflictStrategy::UseServer,
-        "merge" => crate::sync::ConflictStrategy::Merge,
-        "manual" => crate::sync::ConflictStrategy::Manual,
-        _ => return Err("Invalid strategy".to_string()),
-    };
+/// Drop every cached decrypted credential (e.g. when the app is locked)
+#[tauri::command]
+async fn credentials_lock(state: State<'_, AppState>) -> Result<(), String> {
+    state.credentials.clear();
+    Ok(())
+}
 
-    // Resolve the conflict using the manager
-    manager.resolve_conflict(data_type_enum, strategy_enum, &master_password)
+/// Re-encrypt all stored secrets under a newly generated installation key
+/// Requires a `crypto_rekey` confirmation token (empty target).
+#[tauri::command]
+async fn crypto_rekey(state: State<'_, AppState>, confirm_token: String) -> Result<rekey::RekeyReport, String> {
+    state
+        .confirmations
+        .consume(authz::Scope::CryptoRekey, "", &confirm_token, Instant::now())?;
+    let db = state.db.clone();
+    tauri::async_runtime::spawn_blocking(move || rekey::rekey(&db))
         .await
-        .map_err(|e| format!("Conflict resolution failed: {}", e))
+        .map_err(|e| format!("Key rotation task failed: {}", e))?
 }
 
-/// Get sync configuration
+/// Get a message stored in a local folder
 #[tauri::command]
-async fn sync_get_config(state: State<'_, AppState>) -> Result<SyncConfigDto, String> {
-    let manager = state.get_sync_manager()?;
-    let config = manager.get_config().await;
-
-    Ok(SyncConfigDto {
-        enabled: config.enabled,
-        user_id: config.user_id,
-        device_id: config.device_id,
-        device_name: config.device_name,
-        platform: config.platform.as_str().to_string(),
-        last_sync_at: config.last_sync_at.map(|t| t.to_rfc3339()),
-        sync_accounts: config.sync_accounts,
-        sync_contacts: config.sync_contacts,
-        sync_preferences: config.sync_preferences,
-        sync_signatures: config.sync_signatures,
-    })
+async fn local_email_get(
+    state: State<'_, AppState>,
+    email_id: i64,
+) -> Result<db::Email, String> {
+    let email = state.db.get_email(email_id)
+        .map_err(|e| format!("Failed to get email: {}", e))?;
+    let folder = state.db.get_folder_by_id(email.folder_id)
+        .map_err(|e| format!("Failed to get folder: {}", e))?;
+    if !folder.is_local {
+        return Err("Email is not stored in a local folder".to_string());
+    }
+    Ok(email)
 }
 
-/// Update sync configuration
+/// Copy or move a server message into a local folder, storing its full raw source
+/// Returns the id of the stored local message
 #[tauri::command]
-async fn sync_update_config(state: State<'_, AppState>, config: SyncConfigDto) -> Result<(), String> {
-    let manager = state.get_sync_manager()?;
+async fn email_copy_to_local_folder(
+    state: State<'_, AppState>,
+    account_id: String,
+    folder: String,
+    uid: u32,
+    local_folder_id: i64,
+    remove_original: bool,
+) -> Result<i64, String> {
+    let account_id_num: i64 = account_id.parse()
+        .map_err(|_| "Invalid account ID".to_string())?;
 
-    use sync::Platform;
+    let mut client = connect_fresh_imap(&state.db, &state.credentials, account_id_num).await?;
+    let raw = client.fetch_raw(&folder, uid).await
+        .map_err(|e| format!("Failed to fetch message: {}", e))?;
 
-    let platform = match config.platform.as_str() {
-        "windows" => Platform::Windows,
-        "macos" => Platform::MacOS,
-        "linux" => Platform::Linux,
-        _ => return Err("Invalid platform".to_string()),
-    };
+    let new_email = new_email_from_raw(account_id_num, local_folder_id, &raw)?;
+    let email_id = state.db.insert_local_email(&new_email, &raw)
+        .map_err(|e| format!("Failed to store message locally: {}", e))?;
 
-    let sync_config = SyncConfig {
-        enabled: config.enabled,
-        user_id: config.user_id,
-        device_id: config.device_id,
-        device_name: config.device_name,
-        platform,
-        last_sync_at: config.last_sync_at.and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok().map(|dt| dt.with_timezone(&chrono::Utc))),
-        sync_interval_minutes: 30, // TODO: Add to DTO
-        sync_on_startup: true, // TODO: Add to DTO
-        sync_accounts: config.sync_accounts,
-        sync_contacts: config.sync_contacts,
-        sync_preferences: config.sync_preferences,
-        sync_signatures: config.sync_signatures,
-        master_key_salt: None, // Managed internally
-    };
+    // Only remove from the server once the local copy is safely stored
+    if remove_original {
+        client.delete_email(&folder, uid, true).await
+            .map_err(|e| format!("Stored locally but failed to remove original: {}", e))?;
 
-    manager.update_config(sync_config).await;
-    Ok(())
-}
+        let target = state.db.get_folder_by_id(local_folder_id)
+            .map(|f| f.remote_name)
+            .unwrap_or_default();
+        record_email_change(&state, &account_id, &folder, vec![uid], events::EmailChange::Moved { target });
+    }
 
-/// Get sync status for all data types
-#[tauri::command]
-async fn sync_get_status(state: State<'_, AppState>) -> Result<Vec<SyncStatusDto>, String> {
-    let manager = state.get_sync_manager()?;
-    let statuses = manager.get_status().await
-        .map_err(|e| format!("Failed to get status: {}", e))?;
+    let _ = client.disconnect().await;
 
-    Ok(statuses.into_iter().map(|s| SyncStatusDto {
-        data_type: s.data_type,
-        version: s.version,
-        last_sync_at: s.last_sync_at.map(|t| t.to_rfc3339()),
-        status: s.status.as_str().to_string(),
-    }).collect())
+    log::info!(
+        "✓ {} uid={} from '{}' into local folder {} (email_id={})",
+        if remove_original { "Moved" } else { "Copied" },
+        uid, folder, local_folder_id, email_id
+    );
+    Ok(email_id)
 }
 
-/// List all devices for this account
+/// Copy or move a locally stored message into another local folder
 #[tauri::command]
-async fn sync_list_devices(state: State<'_, AppState>) -> Result<Vec<DeviceInfoDto>, String> {
-    let manager = state.get_sync_manager()?;
-    let devices = manager.list_devices().await
-        .map_err(|e| format!("Failed to list devices: {}", e))?;
+async fn local_email_transfer(
+    state: State<'_, AppState>,
+    email_id: i64,
+    target_folder_id: i64,
+    keep_original: bool,
+) -> Result<i64, String> {
+    if !keep_original {
+        let source = state.db.get_email(email_id)
+            .map_err(|e| format!("Failed to get email: {}", e))?;
+        state.db.move_local_email(email_id, target_folder_id)
+            .map_err(|e| format!("Failed to move message: {}", e))?;
+
+        let target = state.db.get_folder_by_id(target_folder_id)
+            .map_err(|e| format!("Failed to get folder: {}", e))?;
+        state.store_events.notify(events::EmailsChanged {
+            account_id: source.account_id,
+            folder: state.db.get_folder_by_id(source.folder_id).ok().map(|f| f.remote_name),
+            email_ids: vec![email_id],
+            uids: vec![source.uid],
+            change: events::EmailChange::Moved { target: target.remote_name },
+        });
+        return Ok(email_id);
+    }
 
-    Ok(devices.into_iter().map(|d| DeviceInfoDto {
-        device_id: d.device_id,
-        device_name: d.device_name,
-        platform: d.platform,
-        last_seen_at: d.last_seen_at,
-        created_at: d.created_at,
-    }).collect())
+    let raw = state.db.get_local_email_source(email_id)
+        .map_err(|e| format!("Failed to read message source: {}", e))?;
+    let source = state.db.get_email(email_id)
+        .map_err(|e| format!("Failed to get email: {}", e))?;
+
+    let mut new_email = new_email_from_raw(source.account_id, target_folder_id, &raw)?;
+    new_email.is_read = source.is_read;
+    new_email.is_starred = source.is_starred;
+
+    state.db.insert_local_email(&new_email, &raw)
+        .map_err(|e| format!("Failed to copy message: {}", e))
 }
 
-/// Revoke device access
+/// Export a local folder as an mbox file
+/// Returns the number of exported messages
 #[tauri::command]
-async fn sync_revoke_device(state: State<'_, AppState>, device_id: String) -> Result<(), String> {
-    let manager = state.get_sync_manager()?;
-    manager.revoke_device(&device_id).await
-        .map_err(|e| format!("Failed to revoke device: {}", e))
+async fn local_folder_export(
+    state: State<'_, AppState>,
+    folder_id: i64,
+    save_path: String,
+) -> Result<usize, String> {
+    let folder = state.db.get_folder_by_id(folder_id)
+        .map_err(|e| format!("Failed to get folder: {}", e))?;
+    if !folder.is_local {
+        return Err("Not a local folder".to_string());
+    }
+
+    let sources = state.db.get_local_folder_sources(folder_id)
+        .map_err(|e| format!("Failed to read folder: {}", e))?;
+
+    let mut mbox = Vec::new();
+    for (sender, date, raw) in &sources {
+        mail::mbox::append_message(&mut mbox, sender, date, raw);
+    }
+
+    tokio::fs::write(&save_path, mbox)
+        .await
+        .map_err(|e| format!("Failed to write export file: {}", e))?;
+
+    log::info!("✓ Exported {} messages from local folder '{}'", sources.len(), folder.name);
+    Ok(sources.len())
 }
 
-/// Get queue statistics
+// ============================================================================
+// Feed Commands
+// ============================================================================
+
+/// Subscribe an account to an RSS/Atom feed and fetch it right away
+/// Entries are stored in the local folder `folder_name` (default "Feeds").
 #[tauri::command]
-fn sync_get_queue_stats(state: State<'_, AppState>) -> Result<QueueStatsDto, String> {
-    let manager = state.get_sync_manager()?;
-    let stats = manager.get_queue_stats()
-        .map_err(|e| format!("Failed to get queue stats: {}", e))?;
+async fn feed_add(
+    state: State<'_, AppState>,
+    account_id: i64,
+    url: String,
+    folder_name: Option<String>,
+    interval_minutes: Option<u32>,
+) -> Result<feeds::FeedPollResult, String> {
+    let feed = feeds::add_feed(&state.db, account_id, &url, folder_name.as_deref(), interval_minutes)?;
+    log::info!("✓ Subscribed account {} to feed {} (id={})", account_id, feed.url, feed.id);
 
-    Ok(QueueStatsDto {
-        pending_count: stats.pending_count,
-        in_progress_count: stats.in_progress_count,
-        failed_count: stats.failed_count,
-        completed_count: stats.completed_count,
-        total_count: stats.total_count,
-    })
+    let engine = filters::FilterEngine::new(state.db.clone()).with_events(state.store_events.clone());
+    Ok(feeds::poll_feed(&state.db, &engine, &feed).await)
 }
 
-/// Process pending queue items (retry failed syncs)
+/// List feed subscriptions of an account (all accounts when omitted)
 #[tauri::command]
-async fn sync_process_queue(
+async fn feed_list(
     state: State<'_, AppState>,
-    master_password: String,
-) -> Result<ProcessQueueResultDto, String> {
-    let manager = state.get_sync_manager()?;
-    let result = manager.process_queue(&master_password).await
-        .map_err(|e| format!("Failed to process queue: {}", e))?;
-
-    Ok(ProcessQueueResultDto {
-        processed: result.processed,
-        succeeded: result.succeeded,
-        failed: result.failed,
-    })
+    account_id: Option<i64>,
+) -> Result<Vec<feeds::Feed>, String> {
+    feeds::list_feeds(&state.db, account_id)
+        .map_err(|e| format!("Failed to list feeds: {}", e))
 }
 
-/// Retry all failed queue items
+/// Change a feed's poll interval or pause it
 #[tauri::command]
-fn sync_retry_failed(state: State<'_, AppState>) -> Result<i32, String> {
-    let manager = state.get_sync_manager()?;
-    manager.retry_failed_syncs()
-        .map_err(|e| format!("Failed to retry failed syncs: {}", e))
+async fn feed_update(
+    state: State<'_, AppState>,
+    feed_id: i64,
+    interval_minutes: u32,
+    enabled: bool,
+) -> Result<feeds::Feed, String> {
+    feeds::update_feed(&state.db, feed_id, interval_minutes, enabled)
+        .map_err(|e| format!("Failed to update feed: {}", e))
 }
 
-/// Clear completed queue items older than N days
+/// Unsubscribe from a feed; stored items stay in their folder
 #[tauri::command]
-fn sync_clear_completed_queue(state: State<'_, AppState>, older_than_days: i32) -> Result<i32, String> {
-    let manager = state.get_sync_manager()?;
-    manager.clear_completed_queue(older_than_days)
-        .map_err(|e| format!("Failed to clear completed queue: {}", e))
+async fn feed_remove(
+    state: State<'_, AppState>,
+    feed_id: i64,
+) -> Result<(), String> {
+    feeds::remove_feed(&state.db, feed_id)
+        .map_err(|e| format!("Failed to remove feed: {}", e))?;
+
+    log::info!("✓ Removed feed {}", feed_id);
+    Ok(())
 }
 
-/// Clear permanently failed queue items
+/// Poll a feed now, regardless of its interval
 #[tauri::command]
-fn sync_clear_failed_queue(state: State<'_, AppState>) -> Result<i32, String> {
-    let manager = state.get_sync_manager()?;
-    manager.clear_failed_queue()
-        .map_err(|e| format!("Failed to clear failed queue: {}", e))
+async fn feed_refresh(
+    state: State<'_, AppState>,
+    feed_id: i64,
+) -> Result<feeds::FeedPollResult, String> {
+    let feed = feeds::get_feed(&state.db, feed_id)
+        .map_err(|e| format!("Failed to get feed: {}", e))?;
+
+    let engine = filters::FilterEngine::new(state.db.clone()).with_events(state.store_events.clone());
+    Ok(feeds::poll_feed(&state.db, &engine, &feed).await)
 }
 
 // ============================================================================
-// Sync History & Rollback Commands
+// Digest Commands
 // ============================================================================
 
+/// Get the newsletter digest configuration
 #[tauri::command]
-async fn get_sync_history(
-    data_type: String,
-    limit: i32,
+async fn digest_get_config(state: State<'_, AppState>) -> Result<digest::DigestConfig, String> {
+    digest::load_config(&state.db)
+        .map_err(|e| format!("Failed to load digest config: {}", e))
+}
+
+/// Update schedule and categories of the newsletter digest
+#[tauri::command]
+async fn digest_set_config(
     state: State<'_, AppState>,
-) -> Result<Vec<SyncSnapshotDto>, String> {
-    let manager = state.get_sync_manager()?;
-    let data_type_enum = parse_sync_data_type(&data_type)?;
+    config: digest::DigestConfig,
+) -> Result<digest::DigestConfig, String> {
+    config.validate()?;
 
-    let snapshots = manager.get_sync_history(data_type_enum, limit)
-        .map_err(|e| format!("Failed to get history: {}", e))?;
+    let current = digest::load_config(&state.db)
+        .map_err(|e| format!("Failed to load digest config: {}", e))?;
+    let config = digest::DigestConfig { last_run: current.last_run, ..config };
+    digest::save_config(&state.db, &config)
+        .map_err(|e| format!("Failed to save digest config: {}", e))?;
 
-    Ok(snapshots.into_iter().map(|s| SyncSnapshotDto {
-        id: s.id.unwrap_or(0),
-        data_type: s.data_type,
-        version: s.version,
-        snapshot_hash: s.snapshot_hash,
-        device_id: s.device_id,
-        operation: format!("{:?}", s.operation).to_lowercase(),
-        items_count: s.items_count,
-        sync_status: format!("{:?}", s.sync_status).to_lowercase(),
-        error_message: s.error_message,
-        created_at: s.created_at.to_rfc3339(),
-    }).collect())
+    log::info!("Digest config updated: enabled={}, schedule={:?}", config.enabled, config.schedule);
+    Ok(config)
 }
 
+/// Generate digests for all accounts now, covering everything since the last digest
 #[tauri::command]
-async fn rollback_sync(
-    data_type: String,
-    version: i64,
-    master_password: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let manager = state.get_sync_manager()?;
-    let data_type_enum = parse_sync_data_type(&data_type)?;
-
-    manager.rollback_to_version(data_type_enum, version, &master_password).await
-        .map_err(|e| format!("Rollback failed: {}", e))
+async fn digest_run_now(state: State<'_, AppState>) -> Result<Vec<digest::DigestResult>, String> {
+    let mut config = digest::load_config(&state.db)
+        .map_err(|e| format!("Failed to load digest config: {}", e))?;
+    digest::run_all(&state.db, &mut config, chrono::Utc::now())
+}
+
+/// New-mail notification candidate
+#[derive(Debug, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct NotificationCandidate {
+    from: String,
+    subject: String,
 }
 
+/// Which of these new messages should raise a desktop notification
+/// Messages held back for the digest return false.
 #[tauri::command]
-async fn enforce_sync_retention(
-    retention_days: i64,
+async fn digest_filter_notifications(
     state: State<'_, AppState>,
-) -> Result<usize, String> {
-    let manager = state.get_sync_manager()?;
-    manager.enforce_history_retention(retention_days)
-        .map_err(|e| format!("Failed to enforce retention: {}", e))
+    emails: Vec<NotificationCandidate>,
+) -> Result<Vec<bool>, String> {
+    let config = digest::load_config(&state.db)
+        .map_err(|e| format!("Failed to load digest config: {}", e))?;
+
+    Ok(emails
+        .iter()
+        .map(|email| !digest::suppresses_notification(&config, &email.from, &email.subject, "[]"))
+        .collect())
 }
 
 // ============================================================================
-// Session Management Commands
+// VIP & Notification Routing Commands
 // ============================================================================
 
-/// Get active sessions
+/// Mark or unmark a sender as VIP for an account
+/// VIP mail always notifies, even in Do Not Disturb.
 #[tauri::command]
-async fn sync_get_sessions(
+async fn contact_set_vip(
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+    account_id: i64,
+    email: String,
+    vip: bool,
+) -> Result<(), String> {
+    state.db.set_contact_vip(account_id, &email, vip)
+        .map_err(|e| format!("Failed to update VIP contact: {}", e))?;
+
+    log::info!("Contact VIP flag set to {} for account {}", vip, account_id);
+    Ok(())
 }
 
-/// Revoke a specific session
+/// VIP contacts of all accounts
 #[tauri::command]
-async fn sync_revoke_session(
-    device_id: String,
-    state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+async fn contact_list_vip(state: State<'_, AppState>) -> Result<Vec<db::Contact>, String> {
+    state.db.get_vip_contacts()
+        .map_err(|e| format!("Failed to load VIP contacts: {}", e))
 }
 
-/// Revoke all sessions except current
+/// Contacts whose address bounced, with when and why
 #[tauri::command]
-async fn sync_revoke_all_sessions(
+async fn contact_list_bouncing(state: State<'_, AppState>, account_id: i64) -> Result<Vec<bounces::BouncingContact>, String> {
+    bounces::list(&state.db, account_id)
+        .map_err(|e| format!("Failed to load bouncing contacts: {}", e))
+}
+
+/// Remove dead addresses from the address book; returns how many were removed
+#[tauri::command]
+async fn contact_remove_bouncing(
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+    account_id: i64,
+    contact_ids: Vec<i64>,
+) -> Result<usize, String> {
+    bounces::remove(&state.db, account_id, &contact_ids)
+        .map_err(|e| format!("Failed to remove bouncing contacts: {}", e))
 }
 
-// ============================================================================
-// Audit Log Commands
-// ============================================================================
+#[tauri::command]
+async fn notification_get_dnd(state: State<'_, AppState>) -> Result<notifications::DndConfig, String> {
+    notifications::load_dnd(&state.db).map_err(|e| format!("Failed to load DND config: {}", e))
+}
 
-/// Get audit logs with filters
+/// Update Do Not Disturb (always on, or daily quiet hours)
 #[tauri::command]
-async fn sync_get_audit_logs(
-    filters: Option<serde_json::Value>,
+async fn notification_set_dnd(
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+    config: notifications::DndConfig,
+) -> Result<notifications::DndConfig, String> {
+    config.validate()?;
+    notifications::save_dnd(&state.db, &config)
+        .map_err(|e| format!("Failed to save DND config: {}", e))?;
+
+    log::info!("DND config updated: enabled={}, quiet_hours={:?}", config.enabled, config.quiet_hours);
+    Ok(config)
 }
 
-/// Get audit statistics
+/// How each of these new messages should be announced
+/// VIP senders are always `priority`; DND and the digest make the rest `silent`.
 #[tauri::command]
-async fn sync_get_audit_stats(
+async fn notification_route(
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+    emails: Vec<NotificationCandidate>,
+) -> Result<Vec<notifications::NotificationRoute>, String> {
+    let router = notifications::Router::load(&state.db)
+        .map_err(|e| format!("Failed to load notification settings: {}", e))?;
+    let now = chrono::Local::now().time();
+
+    Ok(emails
+        .iter()
+        .map(|email| router.route(&email.from, &email.subject, now))
+        .collect())
 }
 
-/// Export audit logs to CSV
 #[tauri::command]
-async fn sync_export_audit_logs(
-    start_date: Option<String>,
-    end_date: Option<String>,
+async fn notification_get_sounds(state: State<'_, AppState>) -> Result<notifications::sound::SoundConfig, String> {
+    notifications::sound::load_config(&state.db).map_err(|e| format!("Failed to load notification sounds: {}", e))
+}
+
+/// Update the volume and the per-account and per-folder sounds
+#[tauri::command]
+async fn notification_set_sounds(
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+    config: notifications::sound::SoundConfig,
+) -> Result<notifications::sound::SoundConfig, String> {
+    config.validate()?;
+    notifications::sound::save_config(&state.db, &config)
+        .map_err(|e| format!("Failed to save notification sounds: {}", e))?;
+
+    log::info!("Notification sounds updated: volume={}, overrides={}", config.volume, config.overrides.len());
+    Ok(config)
+}
+
+/// Play the sound for a new message in an account's folder
+/// Nothing plays when the message is routed silently (DND, digest) or sounds
+/// are off; returns whether a sound played.
+#[tauri::command]
+async fn notification_play_sound(
+    state: State<'_, AppState>,
+    account_id: i64,
+    folder: Option<String>,
+    email: NotificationCandidate,
+) -> Result<bool, String> {
+    let router = notifications::Router::load(&state.db)
+        .map_err(|e| format!("Failed to load notification settings: {}", e))?;
+    let route = router.route(&email.from, &email.subject, chrono::Local::now().time());
+
+    let selected = notifications::sound::select(&state.db, account_id, folder.as_deref(), route)
+        .map_err(|e| format!("Failed to load notification sounds: {}", e))?;
+    Ok(match selected {
+        Some((sound, volume)) => {
+            notifications::sound::play(sound, volume);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Play a sound once, e.g. to try it in the settings (ignores DND)
+#[tauri::command]
+async fn notification_preview_sound(
+    state: State<'_, AppState>,
+    sound: notifications::sound::Sound,
+    volume: Option<u8>,
+) -> Result<(), String> {
+    sound.validate()?;
+    let volume = match volume {
+        Some(volume) => volume,
+        None => notifications::sound::load_config(&state.db)
+            .map_err(|e| format!("Failed to load notification sounds: {}", e))?
+            .volume,
+    };
+    notifications::sound::play(sound, volume);
+    Ok(())
 }
 
 // ============================================================================
-// Two-Factor Authentication Commands
+// Global Hotkey Commands
 // ============================================================================
 
-/// Get 2FA status for current user
+/// Get the global hotkey bindings
 #[tauri::command]
-async fn sync_get_2fa_status(
-    state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+async fn hotkeys_get_config(state: State<'_, AppState>) -> Result<hotkeys::HotkeyConfig, String> {
+    hotkeys::load_config(&state.db).map_err(|e| format!("Failed to load hotkeys: {}", e))
 }
 
-/// Setup 2FA (generate secret and QR code)
+/// Save the global hotkey bindings and register them again
+/// Shortcuts held by another application are returned as conflicts.
 #[tauri::command]
-async fn sync_setup_2fa(
+async fn hotkeys_set_config(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+    config: hotkeys::HotkeyConfig,
+) -> Result<hotkeys::HotkeyStatus, String> {
+    config.validate()?;
+    hotkeys::save_config(&state.db, &config).map_err(|e| format!("Failed to save hotkeys: {}", e))?;
+
+    let status = hotkeys::apply(&app, &config)?;
+    *state.hotkeys.lock().map_err(|e| format!("Lock error: {}", e))? = status.clone();
+
+    log::info!(
+        "Global hotkeys updated: enabled={}, registered={}, conflicts={}",
+        config.enabled,
+        status.registered.len(),
+        status.conflicts.len()
+    );
+    Ok(status)
 }
 
-/// Enable 2FA (confirm with token and get backup codes)
+/// Which hotkeys are registered and which are held by another application
 #[tauri::command]
-async fn sync_enable_2fa(
-    token: String,
-    state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+async fn hotkeys_status(state: State<'_, AppState>) -> Result<hotkeys::HotkeyStatus, String> {
+    state
+        .hotkeys
+        .lock()
+        .map(|status| status.clone())
+        .map_err(|e| format!("Lock error: {}", e))
 }
 
-/// Disable 2FA (requires password and 2FA token)
+// ============================================================================
+// Deep Link Commands
+// ============================================================================
+
+/// Parse a `mailto:` or `owlivion://` link (e.g. one clicked in a message)
 #[tauri::command]
-async fn sync_disable_2fa(
-    password: String,
-    token: String,
-    state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+async fn deep_link_parse(link: String) -> Result<deep_links::DeepLink, String> {
+    deep_links::parse(&link)
 }
 
-/// Verify 2FA during login
+/// Links opened from outside the app since the last call
+/// Call on startup and on each `deep-link://open` event.
 #[tauri::command]
-async fn sync_verify_2fa(
-    email: String,
-    token: String,
-    remember_device: bool,
-    state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    // TODO: Implement SyncManager API integration
-    // This is a stub implementation - SyncManager API needs to be updated
-    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+async fn deep_link_take_pending(state: State<'_, AppState>) -> Result<Vec<deep_links::DeepLink>, String> {
+    Ok(state.deep_links.take())
+}
+
+/// Whether `mailto:` links open in this app
+#[tauri::command]
+async fn system_is_default_mailer(app: tauri::AppHandle) -> Result<bool, String> {
+    default_mailer::is_default(&app)
+}
+
+/// Make this app the default mail client
+/// Returns false when the user still has to confirm in the system settings
+/// (Windows), which are opened for them.
+#[tauri::command]
+async fn system_set_default_mailer(app: tauri::AppHandle) -> Result<bool, String> {
+    let is_default = default_mailer::set_default(&app)?;
+    log::info!("Default mail client registration: {}", if is_default { "done" } else { "awaiting user" });
+    Ok(is_default)
 }
 
 // ============================================================================
-// Background Scheduler Commands
+// Autostart Commands
 // ============================================================================
 
-/// Start background scheduler
+/// Start the app when the user logs in
+/// With `minimized`, login launches stay in the tray.
 #[tauri::command]
-async fn scheduler_start(state: State<'_, AppState>) -> Result<(), String> {
-    state.background_scheduler
-        .start(state.sync_manager.clone())
-        .await
-        .map_err(|e| format!("Failed to start scheduler: {}", e))
+async fn autostart_enable(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    minimized: bool,
+) -> Result<autostart::AutostartStatus, String> {
+    let status = autostart::enable(&app, &state.db, &state.settings_events, minimized)?;
+    log::info!("✓ Autostart enabled (minimized: {})", minimized);
+    Ok(status)
 }
 
-/// Stop background scheduler
+/// Stop starting the app on login
 #[tauri::command]
-async fn scheduler_stop(state: State<'_, AppState>) -> Result<(), String> {
-    state.background_scheduler
-        .stop()
-        .await
-        .map_err(|e| format!("Failed to stop scheduler: {}", e))
+async fn autostart_disable(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<autostart::AutostartStatus, String> {
+    let status = autostart::disable(&app, &state.db)?;
+    log::info!("Autostart disabled");
+    Ok(status)
 }
 
-/// Get scheduler status
+/// Whether the app starts on login, and whether minimized
 #[tauri::command]
-async fn scheduler_get_status(state: State<'_, AppState>) -> Result<SchedulerStatusDto, String> {
-    let config = state.background_scheduler.get_config().await;
-    let running = state.background_scheduler.is_running();
+async fn autostart_status(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<autostart::AutostartStatus, String> {
+    autostart::status(&app, &state.db)
+}
 
-    // Calculate next_run timestamp
-    let next_run = if let Some(ref last_run_str) = config.last_run {
-        if let Ok(last_run) = chrono::DateTime::parse_from_rfc3339(last_run_str) {
-            let next = last_run + chrono::Duration::minutes(config.interval_minutes as i64);
-            Some(next.to_rfc3339())
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+// ============================================================================
+// Email Notes Commands
+// ============================================================================
 
-    Ok(SchedulerStatusDto {
-        enabled: config.enabled,
-        running,
-        interval_minutes: config.interval_minutes,
-        last_run: config.last_run,
-        next_run,
-    })
+/// Account and Message-ID a note of this email is stored under
+fn note_key(db: &Database, email_id: i64) -> Result<(i64, String), String> {
+    let email = db.get_email(email_id).map_err(|e| format!("Failed to get email: {}", e))?;
+    Ok((email.account_id, email.message_id))
 }
 
-/// Update scheduler configuration
+/// Private note on an email, if any
 #[tauri::command]
-async fn scheduler_update_config(
+async fn note_get(state: State<'_, AppState>, email_id: i64) -> Result<Option<notes::EmailNote>, String> {
+    let (account_id, message_id) = note_key(&state.db, email_id)?;
+    notes::get(&state.db, account_id, &message_id).map_err(|e| format!("Failed to get note: {}", e))
+}
+
+/// Save the note and color flag of an email; empty with no color removes it
+#[tauri::command]
+async fn note_set(
     state: State<'_, AppState>,
-    enabled: bool,
-    interval_minutes: u64,
-) -> Result<(), String> {
-    state.background_scheduler
-        .update_config(enabled, interval_minutes, state.sync_manager.clone())
-        .await
-        .map_err(|e| format!("Failed to update scheduler config: {}", e))
+    email_id: i64,
+    body: String,
+    color: Option<String>,
+) -> Result<Option<notes::EmailNote>, String> {
+    let (account_id, message_id) = note_key(&state.db, email_id)?;
+    notes::set(&state.db, account_id, &message_id, &body, color.as_deref())
+        .map_err(|e| format!("Failed to save note: {}", e))
+}
+
+/// Remove the note of an email
+#[tauri::command]
+async fn note_delete(state: State<'_, AppState>, email_id: i64) -> Result<bool, String> {
+    let (account_id, message_id) = note_key(&state.db, email_id)?;
+    notes::delete(&state.db, account_id, &message_id).map_err(|e| format!("Failed to delete note: {}", e))
+}
+
+/// Notes of an account (or of all accounts), most recently edited first
+#[tauri::command]
+async fn note_list(state: State<'_, AppState>, account_id: Option<i64>) -> Result<Vec<notes::EmailNote>, String> {
+    notes::list(&state.db, account_id).map_err(|e| format!("Failed to list notes: {}", e))
 }
 
 // ============================================================================
-// Draft Commands
+// Plus-Address Alias Commands
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
-struct DraftEmailData {
-    id: Option<i64>,
-    account_id: i64,
-    to_addresses: String,
-    cc_addresses: String,
-    bcc_addresses: String,
-    subject: String,
-    body_text: String,
-    body_html: String,
-    reply_to_email_id: Option<i64>,
-    forward_email_id: Option<i64>,
-    compose_type: String,
+/// Generate a plus-address (user+label@domain) for handing out
+#[tauri::command]
+async fn alias_generate(state: State<'_, AppState>, account_id: i64, label: String) -> Result<aliases::PlusAlias, String> {
+    aliases::generate(&state.db, account_id, &label).map_err(|e| format!("Failed to generate alias: {}", e))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct DraftAttachmentData {
-    filename: String,
-    content_type: String,
-    size: i64,
-    local_path: String,
+/// Plus-addresses generated for an account, newest first
+#[tauri::command]
+async fn alias_list(state: State<'_, AppState>, account_id: i64) -> Result<Vec<aliases::PlusAlias>, String> {
+    aliases::list(&state.db, account_id).map_err(|e| format!("Failed to list aliases: {}", e))
 }
 
-/// Draft list item (lightweight, for listing)
-#[derive(Debug, Serialize, Deserialize)]
-struct DraftListItem {
-    id: i64,
-    account_id: i64,
-    subject: String,
-    to_addresses: String,
-    created_at: String,
-    updated_at: String,
+/// Forget a generated plus-address
+#[tauri::command]
+async fn alias_delete(state: State<'_, AppState>, account_id: i64, tag: String) -> Result<bool, String> {
+    aliases::delete(&state.db, account_id, &tag).map_err(|e| format!("Failed to delete alias: {}", e))
 }
 
-/// Draft detail (full data, for editing)
-#[derive(Debug, Serialize, Deserialize)]
-struct DraftDetail {
-    id: i64,
-    account_id: i64,
-    to_addresses: String,
-    cc_addresses: String,
-    bcc_addresses: String,
-    subject: String,
-    body_text: String,
-    body_html: String,
-    reply_to_email_id: Option<i64>,
-    forward_email_id: Option<i64>,
-    compose_type: String,
-    created_at: String,
-    updated_at: String,
-    attachments: Vec<DraftAttachmentData>,
+/// Tag of the plus-address an email was received through, if any
+#[tauri::command]
+async fn alias_of_email(state: State<'_, AppState>, email_id: i64) -> Result<Option<String>, String> {
+    aliases::alias_of_email(&state.db, email_id).map_err(|e| format!("Failed to read email: {}", e))
 }
 
-/// Sanitize filename to prevent path traversal attacks
-fn sanitize_filename(filename: &str) -> String {
-    filename
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '.' || *c == '_' || *c == '-')
-        .collect()
+/// Mail received and junk per plus-address of an account
+#[tauri::command]
+async fn alias_stats(state: State<'_, AppState>, account_id: i64) -> Result<Vec<aliases::AliasStats>, String> {
+    aliases::stats(&state.db, account_id).map_err(|e| format!("Failed to load alias statistics: {}", e))
 }
 
-/// Save or update a draft email
+// ============================================================================
+// Send Time Suggestion Commands
+// ============================================================================
+
+/// Suggest when to send to these recipients, from their past activity
+/// `sendAt` is meant for the scheduled send; None means no usable pattern.
 #[tauri::command]
-async fn draft_save(
+async fn suggest_send_time(
     state: State<'_, AppState>,
-    draft: DraftEmailData,
-    attachments: Vec<DraftAttachmentData>,
-    app_handle: tauri::AppHandle,
-) -> Result<i64, String> {
-    // Validate account
-    let account_id = draft.account_id;
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
+    recipients: Vec<String>,
+) -> Result<metrics::SendTimeSuggestion, String> {
+    if recipients.len() > MAX_RECIPIENTS {
+        return Err(format!("Too many recipients (max {})", MAX_RECIPIENTS));
     }
 
-    // Validate JSON fields
-    serde_json::from_str::<Vec<serde_json::Value>>(&draft.to_addresses)
-        .map_err(|_| "Invalid to_addresses JSON")?;
-    serde_json::from_str::<Vec<serde_json::Value>>(&draft.cc_addresses)
-        .map_err(|_| "Invalid cc_addresses JSON")?;
-    serde_json::from_str::<Vec<serde_json::Value>>(&draft.bcc_addresses)
-        .map_err(|_| "Invalid bcc_addresses JSON")?;
+    metrics::suggest_send_time(&state.db, &recipients, chrono::Local::now())
+        .map_err(|e| format!("Failed to analyse recipients: {}", e))
+}
 
-    // Insert or update draft
-    let draft_id = if let Some(existing_id) = draft.id {
-        // UPDATE existing
-        state.db.execute(
-            "UPDATE drafts SET
-                to_addresses = ?2, cc_addresses = ?3, bcc_addresses = ?4,
-                subject = ?5, body_text = ?6, body_html = ?7,
-                reply_to_email_id = ?8, forward_email_id = ?9,
-                compose_type = ?10, updated_at = datetime('now')
-             WHERE id = ?1 AND account_id = ?11",
-            rusqlite::params![
-                existing_id,
-                draft.to_addresses,
-                draft.cc_addresses,
-                draft.bcc_addresses,
-                draft.subject,
-                draft.body_text,
-                draft.body_html,
-                draft.reply_to_email_id,
-                draft.forward_email_id,
-                draft.compose_type,
-                account_id,
-            ],
-        )
-        .map_err(|e| format!("Failed to update draft: {}", e))?;
+// ============================================================================
+// Smart Compose Commands
+// ============================================================================
 
-        // Delete old attachments
-        state.db.execute(
-            "DELETE FROM draft_attachments WHERE draft_id = ?1",
-            rusqlite::params![existing_id],
-        )
-        .map_err(|e| format!("Failed to delete old attachments: {}", e))?;
+/// Complete the sentence before the cursor
+/// Chunks are streamed as `ai://completion` events tagged with `request_id`;
+/// the full completion is returned. `context` is only sent to remote
+/// providers when the account allows it.
+#[tauri::command]
+async fn ai_complete(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    account_id: i64,
+    context: String,
+    cursor_text: String,
+    request_id: String,
+) -> Result<String, String> {
+    state.ai_limiter.check(account_id, Instant::now())?;
 
-        existing_id
-    } else {
-        // INSERT new
-        state.db.execute_insert(
-            "INSERT INTO drafts (
-                account_id, to_addresses, cc_addresses, bcc_addresses,
-                subject, body_text, body_html, reply_to_email_id,
-                forward_email_id, compose_type
-             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            rusqlite::params![
-                account_id,
-                draft.to_addresses,
-                draft.cc_addresses,
-                draft.bcc_addresses,
-                draft.subject,
-                draft.body_text,
-                draft.body_html,
-                draft.reply_to_email_id,
-                draft.forward_email_id,
-                draft.compose_type,
-            ],
-        )
-        .map_err(|e| format!("Failed to insert draft: {}", e))?
-    };
+    let config = ai::load_provider(&state.db)
+        .map_err(|e| format!("Failed to load AI provider: {}", e))?;
+    let share_context = ai::context_sharing(&state.db, account_id)
+        .map_err(|e| format!("Failed to load privacy setting: {}", e))?;
 
-    // Copy attachments to persistent cache
-    if !attachments.is_empty() {
-        let cache_dir = app_handle
-            .path()
-            .app_cache_dir()
-            .map_err(|e| format!("Failed to get cache directory: {}", e))?;
+    let emit = |text: &str, done: bool| {
+        let chunk = ai::CompletionChunk { request_id: request_id.clone(), text: text.to_string(), done };
+        events::emit(&app, &chunk);
+    };
 
-        let drafts_dir = cache_dir.join("drafts").join(draft_id.to_string());
-        tokio::fs::create_dir_all(&drafts_dir)
-            .await
-            .map_err(|e| format!("Failed to create drafts directory: {}", e))?;
+    let result = ai::complete(&config, share_context, &context, &cursor_text, |chunk| emit(chunk, false)).await;
+    emit("", true);
+    result
+}
 
-        for (idx, att) in attachments.iter().enumerate() {
-            let dest_filename = format!("{}_{}", idx, sanitize_filename(&att.filename));
-            let dest_path = drafts_dir.join(&dest_filename);
+/// Get the completion provider (without the API key)
+#[tauri::command]
+async fn ai_get_provider(state: State<'_, AppState>) -> Result<ai::ProviderInfo, String> {
+    ai::load_provider(&state.db)
+        .map(|config| config.info())
+        .map_err(|e| format!("Failed to load AI provider: {}", e))
+}
 
-            tokio::fs::copy(&att.local_path, &dest_path)
-                .await
-                .map_err(|e| format!("Failed to copy attachment: {}", e))?;
+/// Configure the completion provider
+/// `api_key`: None keeps the stored key, an empty string removes it.
+#[tauri::command]
+async fn ai_set_provider(
+    state: State<'_, AppState>,
+    kind: ai::ProviderKind,
+    base_url: Option<String>,
+    model: Option<String>,
+    api_key: Option<String>,
+) -> Result<ai::ProviderInfo, String> {
+    let current = ai::load_provider(&state.db)
+        .map_err(|e| format!("Failed to load AI provider: {}", e))?;
+
+    let api_key_encrypted = match api_key.as_deref().map(str::trim) {
+        None => current.api_key_encrypted,
+        Some("") => None,
+        Some(key) => Some(crypto::encrypt_password(key)?),
+    };
+    let config = ai::ProviderConfig { kind, base_url, model, api_key_encrypted };
+    config.validate()?;
 
-            state.db.execute(
-                "INSERT INTO draft_attachments (draft_id, filename, content_type, size, local_path)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
-                rusqlite::params![
-                    draft_id,
-                    att.filename,
-                    att.content_type,
-                    att.size,
-                    dest_path.to_string_lossy().to_string(),
-                ],
-            )
-            .map_err(|e| format!("Failed to insert attachment: {}", e))?;
-        }
-    }
+    ai::save_provider(&state.db, &config)
+        .map_err(|e| format!("Failed to save AI provider: {}", e))?;
+    log::info!("AI completion provider set to {:?} ({})", config.kind, config.base_url());
+    Ok(config.info())
+}
 
-    Ok(draft_id)
+/// Whether message context may be sent to remote AI providers for an account
+#[tauri::command]
+async fn ai_get_context_sharing(state: State<'_, AppState>, account_id: i64) -> Result<bool, String> {
+    ai::context_sharing(&state.db, account_id)
+        .map_err(|e| format!("Failed to load privacy setting: {}", e))
 }
 
-/// Delete a draft email
+/// Allow or forbid sending message context to remote AI providers for an account
 #[tauri::command]
-async fn draft_delete(state: State<'_, AppState>, draft_id: i64) -> Result<(), String> {
-    state.db.execute("DELETE FROM drafts WHERE id = ?1", rusqlite::params![draft_id])
-        .map_err(|e| format!("Failed to delete draft: {}", e))?;
-    Ok(())
+async fn ai_set_context_sharing(
+    state: State<'_, AppState>,
+    account_id: i64,
+    enabled: bool,
+) -> Result<(), String> {
+    ai::set_context_sharing(&state.db, account_id, enabled)
+        .map_err(|e| format!("Failed to save privacy setting: {}", e))
 }
 
-/// List drafts for an account
+/// Summarize a thread: summary, decisions and action items
+/// Cached until a new message joins the thread. Remote providers only receive
+/// the thread when the account allows sharing mail content.
 #[tauri::command]
-async fn draft_list(state: State<'_, AppState>, account_id: i64) -> Result<Vec<DraftListItem>, String> {
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
+async fn thread_summarize(
+    state: State<'_, AppState>,
+    account_id: i64,
+    thread_id: String,
+) -> Result<thread_summary::ThreadSummary, String> {
+    if thread_id.trim().is_empty() {
+        return Err("Thread ID is required".to_string());
     }
+    state.ai_limiter.check(account_id, Instant::now())?;
 
-    let result = state.db.query(
-        "SELECT id, account_id, subject, to_addresses, created_at, updated_at
-         FROM drafts
-         WHERE account_id = ?1
-         ORDER BY updated_at DESC",
-        rusqlite::params![account_id],
-        |row| {
-            Ok(DraftListItem {
-                id: row.get(0)?,
-                account_id: row.get(1)?,
-                subject: row.get(2)?,
-                to_addresses: row.get(3)?,
-                created_at: row.get(4)?,
-                updated_at: row.get(5)?,
-            })
-        },
-    )
-    .map_err(|e| format!("Failed to list drafts: {}", e))?;
+    let config = ai::load_provider(&state.db)
+        .map_err(|e| format!("Failed to load AI provider: {}", e))?;
+    let share_context = ai::context_sharing(&state.db, account_id)
+        .map_err(|e| format!("Failed to load privacy setting: {}", e))?;
 
-    Ok(result)
+    thread_summary::summarize(&state.db, &config, share_context, account_id, thread_id.trim()).await
 }
 
-/// Get a single draft with full details
+/// Write a whole conversation to a self-contained, print-friendly HTML file
+/// Bodies are sanitized; stored inline images are embedded, remote ones dropped.
 #[tauri::command]
-async fn draft_get(state: State<'_, AppState>, draft_id: i64) -> Result<DraftDetail, String> {
-    if draft_id <= 0 {
-        return Err("Invalid draft ID".to_string());
+async fn thread_export_html(
+    state: State<'_, AppState>,
+    account_id: i64,
+    thread_id: String,
+    path: String,
+) -> Result<(), String> {
+    if thread_id.trim().is_empty() {
+        return Err("Thread ID is required".to_string());
     }
 
-    // Get draft data
-    let drafts = state.db.query(
-        "SELECT id, account_id, to_addresses, cc_addresses, bcc_addresses,
-                subject, body_text, body_html, reply_to_email_id, forward_email_id,
-                compose_type, created_at, updated_at
-         FROM drafts
-         WHERE id = ?1",
-        rusqlite::params![draft_id],
-        |row| {
-            Ok((
-                row.get::<_, i64>(0)?,
-                row.get::<_, i64>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, String>(4)?,
-                row.get::<_, String>(5)?,
-                row.get::<_, String>(6)?,
-                row.get::<_, String>(7)?,
-                row.get::<_, Option<i64>>(8)?,
-                row.get::<_, Option<i64>>(9)?,
-                row.get::<_, String>(10)?,
-                row.get::<_, String>(11)?,
-                row.get::<_, String>(12)?,
-            ))
-        },
-    )
-    .map_err(|e| format!("Failed to get draft: {}", e))?;
-
-    if drafts.is_empty() {
-        return Err("Draft not found".to_string());
-    }
+    let db = state.db.clone();
+    let store = state.attachment_store.clone();
+    let html = tauri::async_runtime::spawn_blocking(move || {
+        thread_export::render(&db, &store, account_id, thread_id.trim())
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))??;
 
-    let (id, account_id, to_addresses, cc_addresses, bcc_addresses,
-         subject, body_text, body_html, reply_to_email_id, forward_email_id,
-         compose_type, created_at, updated_at) = drafts[0].clone();
+    tokio::fs::write(&path, &html)
+        .await
+        .map_err(|e| format!("Failed to write conversation: {}", e))?;
+    log::info!("✓ Exported conversation to {} ({} bytes)", path, html.len());
+    Ok(())
+}
 
-    // Get attachments
-    let attachments = state.db.query(
-        "SELECT filename, content_type, size, local_path
-         FROM draft_attachments
-         WHERE draft_id = ?1",
-        rusqlite::params![draft_id],
-        |row| {
-            Ok(DraftAttachmentData {
-                filename: row.get(0)?,
-                content_type: row.get(1)?,
-                size: row.get(2)?,
-                local_path: row.get(3)?,
-            })
-        },
-    )
-    .map_err(|e| format!("Failed to get attachments: {}", e))?;
+/// A whole conversation as Markdown for note-taking tools, quotes trimmed
+/// With `attachments_dir`, downloaded attachments are saved there and linked.
+#[tauri::command]
+async fn thread_export_markdown(
+    state: State<'_, AppState>,
+    account_id: i64,
+    thread_id: String,
+    attachments_dir: Option<String>,
+) -> Result<String, String> {
+    if thread_id.trim().is_empty() {
+        return Err("Thread ID is required".to_string());
+    }
 
-    Ok(DraftDetail {
-        id,
-        account_id,
-        to_addresses,
-        cc_addresses,
-        bcc_addresses,
-        subject,
-        body_text,
-        body_html,
-        reply_to_email_id,
-        forward_email_id,
-        compose_type,
-        created_at,
-        updated_at,
-        attachments,
+    let db = state.db.clone();
+    let store = state.attachment_store.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let attachments_dir = attachments_dir.filter(|dir| !dir.trim().is_empty()).map(std::path::PathBuf::from);
+        thread_export::render_markdown(&db, &store, account_id, thread_id.trim(), attachments_dir.as_deref())
     })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
 }
 
 // ============================================================================
-// EMAIL FILTERS COMMANDS
+// Attachment Commands
 // ============================================================================
 
-use db::{EmailFilter as DbEmailFilter, NewEmailFilter as DbNewEmailFilter};
-use filters::{FilterAction, FilterCondition, MatchLogic};
-
-/// Add a new email filter
+/// Whether attachments are encrypted at rest
 #[tauri::command]
-async fn filter_add(
-    state: State<'_, AppState>,
-    filter: DbNewEmailFilter,
-) -> Result<i64, String> {
-    // Validate account_id
-    if filter.account_id <= 0 {
-        return Err("Invalid account ID".to_string());
-    }
-
-    // Validate filter name
-    if filter.name.trim().is_empty() {
-        return Err("Filter name cannot be empty".to_string());
-    }
-
-    // Validate conditions
-    if filter.conditions.is_empty() {
-        return Err("Filter must have at least one condition".to_string());
-    }
+async fn attachment_encryption_get(state: State<'_, AppState>) -> Result<bool, String> {
+    attachment_store::encryption_enabled(&state.db)
+        .map_err(|e| format!("Failed to load encryption setting: {}", e))
+}
 
-    // Validate actions
-    if filter.actions.is_empty() {
-        return Err("Filter must have at least one action".to_string());
+/// Turn attachment encryption at rest on or off
+/// Enabling encrypts the files already stored and returns how many; files
+/// stay encrypted (and readable) after it is turned off again.
+#[tauri::command]
+async fn attachment_encryption_set(state: State<'_, AppState>, enabled: bool) -> Result<usize, String> {
+    attachment_store::set_encryption_enabled(&state.db, enabled)
+        .map_err(|e| format!("Failed to save encryption setting: {}", e))?;
+    if !enabled {
+        return Ok(0);
     }
 
-    let filter_id = state
-        .db
-        .add_filter(&filter)
-        .map_err(|e| format!("Failed to add filter: {}", e))?;
-
-    log::info!("Created filter '{}' with ID {}", filter.name, filter_id);
-
-    Ok(filter_id)
+    let db = state.db.clone();
+    let store = state.attachment_store.clone();
+    tauri::async_runtime::spawn_blocking(move || store.encrypt_existing(&db))
+        .await
+        .map_err(|e| format!("Encryption task failed: {}", e))?
+        .map_err(|e| format!("Failed to encrypt attachments: {}", e))
 }
 
-/// Get all filters for an account
+/// Page through an account's attachments for the attachments view
 #[tauri::command]
-async fn filter_list(
+async fn attachments_list(
     state: State<'_, AppState>,
     account_id: i64,
-) -> Result<Vec<DbEmailFilter>, String> {
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
-    }
-
-    let filters = state
-        .db
-        .get_filters(account_id)
-        .map_err(|e| format!("Failed to list filters: {}", e))?;
-
-    Ok(filters)
+    filters: Option<attachment_browser::AttachmentFilters>,
+    page: Option<u32>,
+) -> Result<attachment_browser::AttachmentPage, String> {
+    attachment_browser::list(&state.db, account_id, &filters.unwrap_or_default(), page.unwrap_or(0))
+        .map_err(|e| format!("Failed to list attachments: {}", e))
 }
 
-/// Get a single filter by ID
+/// Largest messages, biggest senders, counts per year and unread aging
 #[tauri::command]
-async fn filter_get(state: State<'_, AppState>, filter_id: i64) -> Result<DbEmailFilter, String> {
-    if filter_id <= 0 {
-        return Err("Invalid filter ID".to_string());
-    }
-
-    let filter = state
-        .db
-        .get_filter(filter_id)
-        .map_err(|e| format!("Failed to get filter: {}", e))?;
+async fn mailbox_stats(state: State<'_, AppState>, account_id: i64) -> Result<mailbox_stats::MailboxStats, String> {
+    mailbox_stats::compute(&state.db, account_id, chrono::Utc::now())
+        .map_err(|e| format!("Failed to compute mailbox statistics: {}", e))
+}
 
-    Ok(filter)
+/// Cleanup proposals for an account, each with a prepared batch action id
+#[tauri::command]
+async fn cleanup_suggestions(state: State<'_, AppState>, account_id: i64) -> Result<Vec<cleanup::CleanupSuggestion>, String> {
+    cleanup::suggestions(&state.db, account_id, chrono::Utc::now())
+        .map_err(|e| format!("Failed to compute cleanup suggestions: {}", e))
 }
 
-/// Update an existing filter
+/// Carry out a cleanup suggestion; returns the number of messages queued
+/// Requires a `cleanup_execute` confirmation token for `<account id>@<action id>`.
 #[tauri::command]
-async fn filter_update(
+async fn cleanup_execute(
     state: State<'_, AppState>,
-    filter_id: i64,
-    filter: DbNewEmailFilter,
-) -> Result<(), String> {
-    if filter_id <= 0 {
-        return Err("Invalid filter ID".to_string());
-    }
-
-    // Validate same as filter_add
-    if filter.name.trim().is_empty() {
-        return Err("Filter name cannot be empty".to_string());
-    }
+    account_id: i64,
+    action_id: String,
+    confirm_token: String,
+) -> Result<usize, String> {
+    state
+        .confirmations
+        .consume(authz::Scope::CleanupExecute, &format!("{}@{}", account_id, action_id), &confirm_token, Instant::now())?;
+    let batch = cleanup::take_batch(&state.db, account_id, &action_id)
+        .map_err(|e| format!("Failed to load cleanup batch: {}", e))?
+        .ok_or("This suggestion is out of date. Refresh the suggestions and try again.")?;
+
+    let kind = match batch.action {
+        cleanup::CleanupAction::Archive => {
+            let archive = state
+                .db
+                .get_folders(account_id)
+                .map_err(|e| format!("Failed to get folders: {}", e))?
+                .into_iter()
+                .find(|folder| folder.folder_type == "archive" && !folder.is_local)
+                .ok_or("This account has no Archive folder")?;
+            pending_ops::PendingOpKind::Move { target: archive.remote_name }
+        }
+        cleanup::CleanupAction::Delete => pending_ops::PendingOpKind::Delete { permanent: false },
+    };
 
-    if filter.conditions.is_empty() {
-        return Err("Filter must have at least one condition".to_string());
+    let account = account_id.to_string();
+    let mut queued = 0;
+    for email_id in batch.email_ids {
+        // Messages moved or deleted since the suggestion was made are skipped
+        let Ok(email) = state.db.get_email(email_id) else {
+            continue;
+        };
+        if email.is_deleted {
+            continue;
+        }
+        let folder = state
+            .db
+            .get_folder_by_id(email.folder_id)
+            .map_err(|e| format!("Failed to get folder: {}", e))?;
+        if matches!(&kind, pending_ops::PendingOpKind::Move { target } if *target == folder.remote_name) {
+            continue;
+        }
+        queue_email_operation(&state, &account, &folder.remote_name, email.uid, kind.clone())?;
+        queued += 1;
     }
 
-    if filter.actions.is_empty() {
-        return Err("Filter must have at least one action".to_string());
+    if batch.action == cleanup::CleanupAction::Delete && queued > 0 {
+        let summary = format!("Cleanup moved {} large old messages to Trash", queued);
+        log_activity(&state, Some(account_id), activity::ActivityAction::EmailDelete, &summary, None);
     }
+    Ok(queued)
+}
 
-    state
-        .db
-        .update_filter(filter_id, &filter)
-        .map_err(|e| format!("Failed to update filter: {}", e))?;
+// ============================================================================
+// Diagnostics Commands
+// ============================================================================
 
-    log::info!("Updated filter ID {}", filter_id);
+/// Write a diagnostics zip for support (logs, schema version, account settings
+/// without credentials, server capabilities, recent errors)
+/// Secrets are always masked; addresses too when `redact_addresses` is set.
+/// Requires a `diagnostics_export` confirmation token for the path.
+#[tauri::command]
+async fn diagnostics_export(
+    state: State<'_, AppState>,
+    path: String,
+    redact_addresses: Option<bool>,
+    confirm_token: String,
+) -> Result<(), String> {
+    state
+        .confirmations
+        .consume(authz::Scope::DiagnosticsExport, &path, &confirm_token, Instant::now())?;
+    let bundle = diagnostics::bundle(&state.db, redact_addresses.unwrap_or(false))?;
+    tokio::fs::write(&path, bundle)
+        .await
+        .map_err(|e| format!("Failed to write diagnostics bundle: {}", e))?;
 
+    log::info!("✓ Exported diagnostics bundle to {}", path);
     Ok(())
 }
 
-/// Delete a filter
-#[tauri::command]
-async fn filter_delete(state: State<'_, AppState>, filter_id: i64) -> Result<(), String> {
-    if filter_id <= 0 {
-        return Err("Invalid filter ID".to_string());
-    }
+// ============================================================================
+// Profile Commands
+// ============================================================================
 
-    state
-        .db
-        .delete_filter(filter_id)
-        .map_err(|e| format!("Failed to delete filter: {}", e))?;
+/// Portable data directory, when running in portable mode
+#[tauri::command]
+async fn portable_dir_get() -> Result<Option<String>, String> {
+    Ok(portable::dir().map(|dir| dir.display().to_string()))
+}
 
-    log::info!("Deleted filter ID {}", filter_id);
+/// Profiles, and the one this instance runs in
+#[tauri::command]
+async fn profile_list(state: State<'_, AppState>) -> Result<profiles::ProfileList, String> {
+    Ok(profiles::ProfileList {
+        current: state.profile.clone(),
+        profiles: profiles::Profiles::open_default()?.list()?,
+    })
+}
 
-    Ok(())
+/// Create an empty profile (it opens with the first-run setup)
+#[tauri::command]
+async fn profile_create(name: String) -> Result<profiles::Profile, String> {
+    profiles::Profiles::open_default()?.create(&name)
 }
 
-/// Toggle filter enabled state
+/// Make a profile the one opened at launch and restart into it
+/// The whole app state belongs to one profile, so it is rebuilt by a new process.
+/// Requires a `profile_switch` confirmation token for the profile id.
 #[tauri::command]
-async fn filter_toggle(state: State<'_, AppState>, filter_id: i64) -> Result<(), String> {
-    if filter_id <= 0 {
-        return Err("Invalid filter ID".to_string());
+async fn profile_switch(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    profile_id: String,
+    confirm_token: String,
+) -> Result<(), String> {
+    state
+        .confirmations
+        .consume(authz::Scope::ProfileSwitch, &profile_id, &confirm_token, Instant::now())?;
+    let profiles = profiles::Profiles::open_default()?;
+    profiles.set_active(&profile_id)?;
+    if profile_id == state.profile {
+        return Ok(());
     }
 
-    state
-        .db
-        .toggle_filter(filter_id)
-        .map_err(|e| format!("Failed to toggle filter: {}", e))?;
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to locate the app: {}", e))?;
+    // The new process would otherwise hand its launch to this one and exit
+    tauri_plugin_single_instance::destroy(&app_handle);
+    std::process::Command::new(exe)
+        .arg("--profile")
+        .arg(&profile_id)
+        .spawn()
+        .map_err(|e| format!("Failed to start profile {}: {}", profile_id, e))?;
+    log::info!("Switching from profile {} to {}", state.profile, profile_id);
+    app_handle.exit(0);
+    Ok(())
+}
 
-    log::info!("Toggled filter ID {}", filter_id);
+// ============================================================================
+// Onboarding Commands
+// ============================================================================
 
-    Ok(())
+/// Progress of the guided first-run setup, with tips once it is finished
+#[tauri::command]
+async fn onboarding_get_state(state: State<'_, AppState>) -> Result<onboarding::OnboardingState, String> {
+    onboarding::state(&state.db)
+        .map_err(|e| format!("Failed to load onboarding state: {}", e))
 }
 
-/// Test if a filter would match a specific email
+/// Mark a setup step completed (or skipped); returns the new state
 #[tauri::command]
-async fn filter_test(
+async fn onboarding_complete_step(
     state: State<'_, AppState>,
-    filter_id: i64,
-    email_id: i64,
-) -> Result<bool, String> {
-    if filter_id <= 0 || email_id <= 0 {
-        return Err("Invalid filter or email ID".to_string());
-    }
-
-    // Get filter
-    let filter = state
-        .db
-        .get_filter(filter_id)
-        .map_err(|e| format!("Failed to get filter: {}", e))?;
+    step: onboarding::OnboardingStep,
+    skipped: Option<bool>,
+) -> Result<onboarding::OnboardingState, String> {
+    onboarding::complete_step(&state.db, step, skipped.unwrap_or(false))
+        .map_err(|e| format!("Failed to update onboarding: {}", e))
+}
 
-    // Get email
-    let email = state
-        .db
-        .get_email(email_id)
-        .map_err(|e| format!("Failed to get email: {}", e))?;
+/// Start the guided setup over
+#[tauri::command]
+async fn onboarding_reset(state: State<'_, AppState>) -> Result<onboarding::OnboardingState, String> {
+    onboarding::reset(&state.db)
+        .and_then(|_| onboarding::state(&state.db))
+        .map_err(|e| format!("Failed to reset onboarding: {}", e))
+}
 
-    // Create filter engine and test
-    use filters::FilterEngine;
-    let engine = FilterEngine::new(state.db.clone());
-    let matches = engine.test_filter(&filter, &email);
+// ============================================================================
+// Settings Commands
+// ============================================================================
 
-    Ok(matches)
+/// All registered settings by camelCase name, with defaults filled in
+#[tauri::command]
+async fn settings_get_all(state: State<'_, AppState>) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    settings::get_all(&state.db)
+        .map_err(|e| format!("Failed to load settings: {}", e))
 }
 
-/// Apply filters to existing emails in batch
+/// Change several settings at once; nothing is saved if any value is invalid
+/// Returns the values that changed, which are also sent as `settings://changed`.
 #[tauri::command]
-async fn filter_apply_batch(
+async fn settings_set_many(
     state: State<'_, AppState>,
-    account_id: i64,
-    filter_id: Option<i64>,
-    folder_id: Option<i64>,
-) -> Result<FilterBatchResult, String> {
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
-    }
-
-    log::info!(
-        "Batch applying filters: account_id={}, filter_id={:?}, folder_id={:?}",
-        account_id,
-        filter_id,
-        folder_id
-    );
-
-    // Get emails to process
-    let email_select = r#"
-        SELECT id, account_id, folder_id, message_id, uid,
-               from_address, from_name, to_addresses, cc_addresses, bcc_addresses, reply_to,
-               subject, preview, body_text, body_html, date,
-               is_read, is_starred, is_deleted, is_spam, is_draft, is_answered, is_forwarded,
-               has_attachments, has_inline_images, thread_id, in_reply_to, references_header,
-               priority, labels
-    "#;
+    values: serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let changed = settings::set_many(&state.db, &values)
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+    state.settings_events.notify(changed.clone());
+    Ok(changed)
+}
 
-    let emails = if let Some(fid) = folder_id {
-        // Filter by folder
-        state
-            .db
-            .query(
-                &format!("{} FROM emails WHERE account_id = ?1 AND folder_id = ?2 AND is_deleted = 0", email_select),
-                rusqlite::params![account_id, fid],
-                |row| db::Email::from_row(row),
-            )
-            .map_err(|e| format!("Failed to get emails: {}", e))?
-    } else {
-        // All emails for account
-        state
-            .db
-            .query(
-                &format!("{} FROM emails WHERE account_id = ?1 AND is_deleted = 0", email_select),
-                [account_id],
-                |row| db::Email::from_row(row),
-            )
-            .map_err(|e| format!("Failed to get emails: {}", e))?
-    };
+// ============================================================================
+// Prefetch Commands
+// ============================================================================
 
-    log::info!("Processing {} emails", emails.len());
+/// Read-ahead settings
+#[tauri::command]
+async fn prefetch_settings_get(state: State<'_, AppState>) -> Result<prefetch::PrefetchSettings, String> {
+    prefetch::load_settings(&state.db)
+        .map_err(|e| format!("Failed to load prefetch settings: {}", e))
+}
 
-    // Create filter engine
-    use filters::FilterEngine;
-    let engine = FilterEngine::new(state.db.clone());
+/// Save read-ahead settings; returns them as stored (clamped to supported values)
+#[tauri::command]
+async fn prefetch_settings_set(
+    state: State<'_, AppState>,
+    settings: prefetch::PrefetchSettings,
+) -> Result<prefetch::PrefetchSettings, String> {
+    prefetch::save_settings(&state.db, settings)
+        .map_err(|e| format!("Failed to save prefetch settings: {}", e))
+}
 
-    let mut emails_processed = 0;
-    let mut filters_matched = 0;
-    let mut actions_executed = 0;
+// ============================================================================
+// Sent Copy Commands
+// ============================================================================
 
-    for email in emails {
-        emails_processed += 1;
+/// Whether sent messages are uploaded to the Sent folder
+#[tauri::command]
+async fn sent_copy_upload_get(state: State<'_, AppState>) -> Result<bool, String> {
+    sent_copy::upload_enabled(&state.db)
+        .map_err(|e| format!("Failed to load sent copy setting: {}", e))
+}
 
-        // Get actions to perform
-        let actions = if let Some(fid) = filter_id {
-            // Apply specific filter only
-            let filter = state
-                .db
-                .get_filter(fid)
-                .map_err(|e| format!("Failed to get filter: {}", e))?;
+/// Turn uploading sent messages to the Sent folder on or off
+#[tauri::command]
+async fn sent_copy_upload_set(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    sent_copy::set_upload_enabled(&state.db, enabled)
+        .map_err(|e| format!("Failed to save sent copy setting: {}", e))
+}
 
-            if engine.test_filter(&filter, &email) {
-                filters_matched += 1;
-                // Update filter stats
-                state
-                    .db
-                    .execute(
-                        "UPDATE email_filters SET matched_count = matched_count + 1, last_matched_at = datetime('now') WHERE id = ?1",
-                        [fid],
-                    )
-                    .map_err(|e| format!("Failed to update filter stats: {}", e))?;
+/// Sent copies skipped because they exceeded the server's APPENDLIMIT
+#[tauri::command]
+async fn sent_copy_warnings(
+    state: State<'_, AppState>,
+    account_id: Option<i64>,
+) -> Result<Vec<sent_copy::SentCopyWarning>, String> {
+    sent_copy::warnings(&state.db, account_id)
+        .map_err(|e| format!("Failed to load sent copy warnings: {}", e))
+}
 
-                filter.actions
-            } else {
-                vec![]
-            }
-        } else {
-            // Apply all filters
-            let filter_actions = engine
-                .apply_filters(&email)
-                .await
-                .map_err(|e| format!("Failed to apply filters: {}", e))?;
+/// Write temporary file from byte array (for frontend File objects)
+#[tauri::command]
+async fn write_temp_attachment(
+    filename: String,
+    content_type: String,
+    data: Vec<u8>,
+) -> Result<AttachmentPath, String> {
+    // SECURITY: Validate filename
+    if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
+        return Err("Invalid filename".to_string());
+    }
 
-            if !filter_actions.is_empty() {
-                filters_matched += 1;
-            }
+    // SECURITY: Limit file size (50MB)
+    const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
+    if data.len() > MAX_FILE_SIZE {
+        return Err("File too large (max 50MB)".to_string());
+    }
 
-            filter_actions
-        };
+    // Create temp directory for attachments
+    let temp_dir = portable::temp_dir().join("owlivion-mail-attachments");
+    tokio::fs::create_dir_all(&temp_dir)
+        .await
+        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
 
-        // Execute actions
-        if !actions.is_empty() {
-            actions_executed += actions.len();
-            engine
-                .execute_actions(email.id, actions)
-                .await
-                .map_err(|e| format!("Failed to execute actions: {}", e))?;
-        }
-    }
+    // Generate unique filename
+    let unique_name = format!("{}_{}", uuid::Uuid::new_v4(), filename);
+    let temp_path = temp_dir.join(&unique_name);
+    let (content_type, declared_content_type) =
+        attachment_content_type(mail::mime_detect::detect(&filename, &content_type, &data), &filename);
 
-    log::info!(
-        "Batch complete: processed={}, matched={}, actions={}",
-        emails_processed,
-        filters_matched,
-        actions_executed
-    );
+    // Write file to temp location
+    tokio::fs::write(&temp_path, data)
+        .await
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
 
-    Ok(FilterBatchResult {
-        emails_processed,
-        filters_matched,
-        actions_executed,
+    Ok(AttachmentPath {
+        path: temp_path.to_string_lossy().to_string(),
+        filename,
+        content_type,
+        content_id: None,
+        declared_content_type,
     })
 }
 
-/// Export filters as JSON
+/// Start a chunked upload of a composer attachment; returns the upload handle
 #[tauri::command]
-async fn filter_export(
+async fn temp_attachment_begin(
     state: State<'_, AppState>,
-    account_id: i64,
+    filename: String,
+    content_type: String,
+    size: u64,
 ) -> Result<String, String> {
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
-    }
-
-    let filters = state
-        .db
-        .get_filters(account_id)
-        .map_err(|e| format!("Failed to get filters: {}", e))?;
+    state.temp_uploads.begin(&filename, &content_type, size).await
+}
 
-    // Convert to JSON
+/// Append a chunk to an upload
+/// The chunk is the raw request body; the `Upload-Handle` header names the upload.
+#[tauri::command]
+async fn temp_attachment_append(state: State<'_, AppState>, request: tauri::ipc::Request<'_>) -> Result<u64, String> {
+    let handle = request
+        .headers()
+        .get("upload-handle")
+        .and_then(|value| value.to_str().ok())
+        .ok_or("Missing upload handle")?;
+    let tauri::ipc::InvokeBody::Raw(chunk) = request.body() else {
+        return Err("Expected a binary chunk".to_string());
+    };
+    state.temp_uploads.append(handle, chunk).await
+}
+
+/// Complete an upload and return the temp file to attach
+#[tauri::command]
+async fn temp_attachment_finish(state: State<'_, AppState>, handle: String) -> Result<AttachmentPath, String> {
+    let upload = state.temp_uploads.finish(&handle).await?;
+    let info = mail::mime_detect::detect_file(&upload.path, &upload.filename, &upload.content_type)
+        .await
+        .map_err(|e| format!("Failed to read uploaded file: {}", e))?;
+    let (content_type, declared_content_type) = attachment_content_type(info, &upload.filename);
+    Ok(AttachmentPath {
+        path: upload.path.to_string_lossy().to_string(),
+        filename: upload.filename,
+        content_type,
+        content_id: None,
+        declared_content_type,
+    })
+}
+
+/// Cancel an upload and delete its temp file
+#[tauri::command]
+async fn temp_attachment_abort(state: State<'_, AppState>, handle: String) -> Result<(), String> {
+    state.temp_uploads.abort(&handle).await;
+    Ok(())
+}
+
+/// Upload attachment and return temporary path
+#[tauri::command]
+async fn attachment_upload(
+    file_path: String,
+    filename: String,
+    content_type: String,
+) -> Result<AttachmentPath, String> {
+    // SECURITY: Validate filename
+    if filename.contains("..") || filename.contains('/') || filename.contains('\\') {
+        return Err("Invalid filename".to_string());
+    }
+
+    // Read file
+    let data = tokio::fs::read(&file_path)
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    // SECURITY: Limit file size (50MB)
+    const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
+    if data.len() > MAX_FILE_SIZE {
+        return Err("File too large (max 50MB)".to_string());
+    }
+
+    // Create temp directory for attachments
+    let temp_dir = portable::temp_dir().join("owlivion-mail-attachments");
+    tokio::fs::create_dir_all(&temp_dir)
+        .await
+        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+
+    // Generate unique filename
+    let unique_name = format!("{}_{}", uuid::Uuid::new_v4(), filename);
+    let temp_path = temp_dir.join(&unique_name);
+
+    let (content_type, declared_content_type) =
+        attachment_content_type(mail::mime_detect::detect(&filename, &content_type, &data), &filename);
+
+    // Copy file to temp location
+    tokio::fs::write(&temp_path, data)
+        .await
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+
+    Ok(AttachmentPath {
+        path: temp_path.to_string_lossy().to_string(),
+        filename,
+        content_type,
+        content_id: None,
+        declared_content_type,
+    })
+}
+
+/// Quoted HTML with inline images re-embedded for a reply/forward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct QuotedInlineHtml {
+    pub html: String,
+    pub inline_attachments: Vec<AttachmentPath>,
+}
+
+/// Prepare quoted HTML that references inline images of the original message
+/// Downloads referenced cid: parts to temp files and rewrites them to fresh Content-IDs
+#[tauri::command]
+async fn email_quote_inline_images(
+    state: State<'_, AppState>,
+    account_id: String,
+    folder: String,
+    uid: u32,
+    html: String,
+) -> Result<QuotedInlineHtml, String> {
+    let cid_refs = mail::compose::extract_cid_references(&html);
+    if cid_refs.is_empty() {
+        return Ok(QuotedInlineHtml { html, inline_attachments: Vec::new() });
+    }
+
+    let account_id_num: i64 = account_id.parse()
+        .map_err(|_| "Invalid account ID".to_string())?;
+    let account = state.db.get_account(account_id_num)
+        .map_err(|e| format!("Failed to get account: {}", e))?;
+    let password = state.credentials.password(&state.db, account_id_num)
+        .map_err(|e| e.to_string())?
+        .to_string();
+
+    let config = ImapConfig {
+        host: account.imap_host.clone(),
+        port: account.imap_port as u16,
+        username: account.imap_username.clone().unwrap_or(account.email.clone()),
+        password,
+        security: parse_security(&account.imap_security),
+        accept_invalid_certs: account.accept_invalid_certs,
+        oauth_provider: account.oauth_provider.clone(),
+    };
+
+    let mut client = AsyncImapClient::new(config);
+    let connected = client.connect().await;
+    state.credentials.record_login(&state.db, account_id_num, &connected);
+    connected.map_err(|e| format!("Failed to connect: {}", e))?;
+
+    let original = client.fetch_email(&folder, uid).await
+        .map_err(|e| format!("Fetch error: {}", e))?;
+
+    let temp_dir = portable::temp_dir().join("owlivion-mail-attachments");
+    tokio::fs::create_dir_all(&temp_dir)
+        .await
+        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+
+    let mut mapping = HashMap::new();
+    let mut inline_attachments = Vec::new();
+
+    for cid in &cid_refs {
+        let Some(att) = original.attachments.iter().find(|a| {
+            a.content_id.as_deref().map(mail::compose::normalize_content_id).as_deref() == Some(cid.as_str())
+        }) else {
+            log::warn!("Quoted HTML references unknown inline part cid:{}", cid);
+            continue;
+        };
+
+        let data = client.fetch_attachment(&folder, uid, att.index).await
+            .map_err(|e| format!("Failed to fetch inline image: {}", e))?;
+        let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &data.data)
+            .map_err(|e| format!("Invalid inline image data: {}", e))?;
+
+        let filename = sanitize_filename(&att.filename);
+        let (content_type, declared_content_type) =
+            attachment_content_type(mail::mime_detect::detect(&filename, &att.content_type, &bytes), &filename);
+        let temp_path = temp_dir.join(format!("{}_{}", uuid::Uuid::new_v4(), filename));
+        tokio::fs::write(&temp_path, bytes)
+            .await
+            .map_err(|e| format!("Failed to write temp file: {}", e))?;
+
+        let new_cid = mail::compose::generate_content_id(&account.email);
+        mapping.insert(cid.clone(), new_cid.clone());
+        inline_attachments.push(AttachmentPath {
+            path: temp_path.to_string_lossy().to_string(),
+            filename,
+            content_type,
+            content_id: Some(new_cid),
+            declared_content_type,
+        });
+    }
+
+    let _ = client.disconnect().await;
+
+    Ok(QuotedInlineHtml {
+        html: mail::compose::rewrite_cid_references(&html, &mapping),
+        inline_attachments,
+    })
+}
+
+/// Get attachments for an email
+#[tauri::command]
+async fn get_email_attachments(
+    state: State<'_, AppState>,
+    email_id: i64,
+) -> Result<Vec<db::Attachment>, String> {
+    state.db.get_attachments_for_email(email_id)
+        .map_err(|e| format!("Failed to get attachments: {}", e))
+}
+
+/// Download attachment to user-selected location
+#[tauri::command]
+async fn attachment_download(
+    state: State<'_, AppState>,
+    account_id: i64,
+    email_id: i64,
+    attachment_id: i64,
+    save_path: String,
+) -> Result<(), String> {
+    // Get attachment info
+    let attachment = state.db.get_attachment(attachment_id)
+        .map_err(|e| format!("Failed to get attachment: {}", e))?;
+
+    // Verify attachment belongs to email
+    if attachment.email_id != email_id {
+        return Err("Attachment does not belong to this email".to_string());
+    }
+
+    let email = state.db.get_email(email_id)
+        .map_err(|e| format!("Failed to get email: {}", e))?;
+    ensure_attachments_allowed(&state.db, &email.from_address)?;
+
+    // Already in the attachment store
+    if let Some(bytes) = state.attachment_store.load(&state.db, &attachment) {
+        tokio::fs::write(&save_path, bytes)
+            .await
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        return Ok(());
+    }
+
+    // Fetch from the server: rows are stored in the order the message lists its attachments
+    let folder = state.db.get_folder_by_id(email.folder_id)
+        .map_err(|e| format!("Failed to get folder: {}", e))?;
+    let index: i64 = state.db.query_row(
+        "SELECT COUNT(*) FROM attachments WHERE email_id = ?1 AND id < ?2",
+        rusqlite::params![email_id, attachment_id],
+        |row| row.get(0),
+    ).map_err(|e| format!("Failed to get attachment: {}", e))?;
+
+    let mut imap_client = connect_fresh_imap(&state.db, &state.credentials, account_id).await?;
+    let data = imap_client.fetch_attachment(&folder.remote_name, email.uid, index as usize).await
+        .map_err(|e| format!("Failed to fetch attachment: {}", e))?;
+    let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &data.data)
+        .map_err(|e| format!("Invalid attachment data: {}", e))?;
+
+    if let Err(e) = state.attachment_store.store(&state.db, attachment_id, &bytes) {
+        log::warn!("Failed to store attachment: {}", e);
+    }
+    tokio::fs::write(&save_path, bytes)
+        .await
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(())
+}
+
+// ============================================================================
+// Sync Commands
+// ============================================================================
+
+use sync::SyncConfig;
+use std::sync::Mutex as StdMutex;
+
+/// Register new Owlivion Account
+#[tauri::command]
+async fn sync_register(
+    state: State<'_, AppState>,
+    email: String,
+    password: String,
+    master_password: String,
+) -> Result<(), String> {
+    let manager = state.get_sync_manager()?;
+    manager.register(email, password, master_password).await
+        .map_err(|e| format!("Registration failed: {}", e))
+}
+
+/// Login to Owlivion Account
+#[tauri::command]
+async fn sync_login(
+    state: State<'_, AppState>,
+    email: String,
+    password: String,
+) -> Result<(), String> {
+    let manager = state.get_sync_manager()?;
+    manager.login(email, password).await
+        .map_err(|e| format!("Login failed: {}", e))
+}
+
+/// Logout from Owlivion Account
+#[tauri::command]
+async fn sync_logout(state: State<'_, AppState>) -> Result<(), String> {
+    let manager = state.get_sync_manager()?;
+    manager.logout().await
+        .map_err(|e| format!("Logout failed: {}", e))
+}
+
+/// Start manual sync (bidirectional with conflict detection)
+#[tauri::command]
+async fn sync_start(state: State<'_, AppState>, master_password: String) -> Result<SyncResultDto, String> {
+    let manager = state.get_sync_manager()?;
+    let result = manager.sync_all(&master_password).await
+        .map_err(|e| format!("Sync failed: {}", e))?;
+
+    Ok(sync_result_dto(result))
+}
+
+/// Sync again only the data types whose last sync failed transiently
+#[tauri::command]
+async fn sync_retry_deferred(state: State<'_, AppState>, master_password: String) -> Result<SyncResultDto, String> {
+    let manager = state.get_sync_manager()?;
+    let result = manager.retry_deferred(&master_password).await
+        .map_err(|e| format!("Sync failed: {}", e))?;
+
+    Ok(sync_result_dto(result))
+}
+
+fn sync_result_dto(result: sync::SyncResult) -> SyncResultDto {
+    SyncResultDto {
+        accounts_synced: result.accounts_synced,
+        contacts_synced: result.contacts_synced,
+        preferences_synced: result.preferences_synced,
+        signatures_synced: result.signatures_synced,
+        templates_synced: result.templates_synced,
+        filters_synced: result.filters_synced,
+        notes_synced: result.notes_synced,
+        errors: result.errors,
+        types: result.types,
+        offline: result.offline,
+        conflicts: result.conflicts.map(|conflicts| {
+            conflicts.into_iter().map(|c| ConflictInfoDto {
+                data_type: c.data_type,
+                local_version: c.local_version,
+                server_version: c.server_version,
+                local_updated_at: c.local_updated_at.map(|t| t.to_rfc3339()),
+                server_updated_at: c.server_updated_at.map(|t| t.to_rfc3339()),
+                strategy: format!("{:?}", c.strategy),
+                conflict_details: c.conflict_details,
+                local_data: c.local_data,
+                server_data: c.server_data,
+            }).collect()
+        }),
+    }
+}
+
+/// Resolve a sync conflict manually
+#[tauri::command]
+async fn sync_resolve_conflict(
+    state: State<'_, AppState>,
+    data_type: String,
+    strategy: String,
+    master_password: String,
+) -> Result<(), String> {
+    let manager = state.get_sync_manager()?;
+
+    // Parse data type
+    let data_type_enum = match data_type.as_str() {
+        "accounts" => crate::sync::SyncDataType::Accounts,
+        "contacts" => crate::sync::SyncDataType::Contacts,
+        "preferences" => crate::sync::SyncDataType::Preferences,
+        "signatures" => crate::sync::SyncDataType::Signatures,
+        "templates" => crate::sync::SyncDataType::Templates,
+        "filters" => crate::sync::SyncDataType::Filters,
+        "notes" => crate::sync::SyncDataType::Notes,
+        _ => return Err("Invalid data type".to_string()),
+    };
+
+    // Parse strategy
+    let strategy_enum = match strategy.as_str() {
+        "use_local" => crate::sync::ConflictStrategy::UseLocal,
+        "use_server" => crate::sync::ConflictStrategy::UseServer,
+        "merge" => crate::sync::ConflictStrategy::Merge,
+        "manual" => crate::sync::ConflictStrategy::Manual,
+        _ => return Err("Invalid strategy".to_string()),
+    };
+
+    // Resolve the conflict using the manager
+    manager.resolve_conflict(data_type_enum, strategy_enum, &master_password)
+        .await
+        .map_err(|e| format!("Conflict resolution failed: {}", e))
+}
+
+/// Get sync configuration
+#[tauri::command]
+async fn sync_get_config(state: State<'_, AppState>) -> Result<SyncConfigDto, String> {
+    let manager = state.get_sync_manager()?;
+    let config = manager.get_config().await;
+
+    Ok(SyncConfigDto {
+        enabled: config.enabled,
+        user_id: config.user_id,
+        device_id: config.device_id,
+        device_name: config.device_name,
+        platform: config.platform.as_str().to_string(),
+        last_sync_at: config.last_sync_at.map(|t| t.to_rfc3339()),
+        sync_accounts: config.sync_accounts,
+        sync_contacts: config.sync_contacts,
+        sync_preferences: config.sync_preferences,
+        sync_signatures: config.sync_signatures,
+        sync_templates: config.sync_templates,
+        sync_filters: config.sync_filters,
+        sync_notes: config.sync_notes,
+    })
+}
+
+/// Update sync configuration
+#[tauri::command]
+async fn sync_update_config(state: State<'_, AppState>, config: SyncConfigDto) -> Result<(), String> {
+    let manager = state.get_sync_manager()?;
+
+    use sync::Platform;
+
+    let platform = match config.platform.as_str() {
+        "windows" => Platform::Windows,
+        "macos" => Platform::MacOS,
+        "linux" => Platform::Linux,
+        _ => return Err("Invalid platform".to_string()),
+    };
+
+    let sync_config = SyncConfig {
+        enabled: config.enabled,
+        user_id: config.user_id,
+        device_id: config.device_id,
+        device_name: config.device_name,
+        platform,
+        last_sync_at: config.last_sync_at.and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok().map(|dt| dt.with_timezone(&chrono::Utc))),
+        sync_interval_minutes: 30, // TODO: Add to DTO
+        sync_on_startup: true, // TODO: Add to DTO
+        sync_accounts: config.sync_accounts,
+        sync_contacts: config.sync_contacts,
+        sync_preferences: config.sync_preferences,
+        sync_signatures: config.sync_signatures,
+        sync_templates: config.sync_templates,
+        sync_filters: config.sync_filters,
+        sync_notes: config.sync_notes,
+        master_key_salt: None, // Managed internally
+    };
+
+    manager.update_config(sync_config).await;
+    Ok(())
+}
+
+/// Get sync status for all data types
+#[tauri::command]
+async fn sync_get_status(state: State<'_, AppState>) -> Result<Vec<SyncStatusDto>, String> {
+    let manager = state.get_sync_manager()?;
+    let statuses = manager.get_status().await
+        .map_err(|e| format!("Failed to get status: {}", e))?;
+
+    Ok(statuses.into_iter().map(|s| SyncStatusDto {
+        data_type: s.data_type,
+        version: s.version,
+        last_sync_at: s.last_sync_at.map(|t| t.to_rfc3339()),
+        status: s.status.as_str().to_string(),
+    }).collect())
+}
+
+/// List all devices for this account
+#[tauri::command]
+async fn sync_list_devices(state: State<'_, AppState>) -> Result<Vec<DeviceInfoDto>, String> {
+    let manager = state.get_sync_manager()?;
+    let devices = manager.list_devices().await
+        .map_err(|e| format!("Failed to list devices: {}", e))?;
+
+    Ok(devices.into_iter().map(|d| DeviceInfoDto {
+        device_id: d.device_id,
+        device_name: d.device_name,
+        platform: d.platform,
+        last_seen_at: d.last_seen_at,
+        created_at: d.created_at,
+    }).collect())
+}
+
+/// Revoke device access
+#[tauri::command]
+async fn sync_revoke_device(state: State<'_, AppState>, device_id: String) -> Result<(), String> {
+    let manager = state.get_sync_manager()?;
+    manager.revoke_device(&device_id).await
+        .map_err(|e| format!("Failed to revoke device: {}", e))
+}
+
+/// Get queue statistics
+#[tauri::command]
+fn sync_get_queue_stats(state: State<'_, AppState>) -> Result<QueueStatsDto, String> {
+    let manager = state.get_sync_manager()?;
+    let stats = manager.get_queue_stats()
+        .map_err(|e| format!("Failed to get queue stats: {}", e))?;
+
+    Ok(QueueStatsDto {
+        pending_count: stats.pending_count,
+        in_progress_count: stats.in_progress_count,
+        failed_count: stats.failed_count,
+        completed_count: stats.completed_count,
+        total_count: stats.total_count,
+    })
+}
+
+/// Process pending queue items (retry failed syncs)
+#[tauri::command]
+async fn sync_process_queue(
+    state: State<'_, AppState>,
+    master_password: String,
+) -> Result<ProcessQueueResultDto, String> {
+    let manager = state.get_sync_manager()?;
+    let result = manager.process_queue(&master_password).await
+        .map_err(|e| format!("Failed to process queue: {}", e))?;
+
+    Ok(ProcessQueueResultDto {
+        processed: result.processed,
+        succeeded: result.succeeded,
+        failed: result.failed,
+    })
+}
+
+/// Retry all failed queue items
+#[tauri::command]
+fn sync_retry_failed(state: State<'_, AppState>) -> Result<i32, String> {
+    let manager = state.get_sync_manager()?;
+    manager.retry_failed_syncs()
+        .map_err(|e| format!("Failed to retry failed syncs: {}", e))
+}
+
+/// Clear completed queue items older than N days
+#[tauri::command]
+fn sync_clear_completed_queue(state: State<'_, AppState>, older_than_days: i32) -> Result<i32, String> {
+    let manager = state.get_sync_manager()?;
+    manager.clear_completed_queue(older_than_days)
+        .map_err(|e| format!("Failed to clear completed queue: {}", e))
+}
+
+/// Clear permanently failed queue items
+#[tauri::command]
+fn sync_clear_failed_queue(state: State<'_, AppState>) -> Result<i32, String> {
+    let manager = state.get_sync_manager()?;
+    manager.clear_failed_queue()
+        .map_err(|e| format!("Failed to clear failed queue: {}", e))
+}
+
+// ============================================================================
+// Sync History & Rollback Commands
+// ============================================================================
+
+#[tauri::command]
+async fn get_sync_history(
+    data_type: String,
+    limit: i32,
+    state: State<'_, AppState>,
+) -> Result<Vec<SyncSnapshotDto>, String> {
+    let manager = state.get_sync_manager()?;
+    let data_type_enum = parse_sync_data_type(&data_type)?;
+
+    let snapshots = manager.get_sync_history(data_type_enum, limit)
+        .map_err(|e| format!("Failed to get history: {}", e))?;
+
+    Ok(snapshots.into_iter().map(|s| SyncSnapshotDto {
+        id: s.id.unwrap_or(0),
+        data_type: s.data_type,
+        version: s.version,
+        snapshot_hash: s.snapshot_hash,
+        device_id: s.device_id,
+        operation: format!("{:?}", s.operation).to_lowercase(),
+        items_count: s.items_count,
+        sync_status: format!("{:?}", s.sync_status).to_lowercase(),
+        error_message: s.error_message,
+        created_at: s.created_at.to_rfc3339(),
+    }).collect())
+}
+
+/// Restore synced data to an earlier version
+/// Requires a `sync_rollback` confirmation token for `<data type>@<version>`.
+#[tauri::command]
+async fn rollback_sync(
+    data_type: String,
+    version: i64,
+    master_password: String,
+    confirm_token: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.confirmations.consume(
+        authz::Scope::SyncRollback,
+        &format!("{}@{}", data_type, version),
+        &confirm_token,
+        Instant::now(),
+    )?;
+    let manager = state.get_sync_manager()?;
+    let data_type_enum = parse_sync_data_type(&data_type)?;
+
+    manager.rollback_to_version(data_type_enum, version, &master_password).await
+        .map_err(|e| format!("Rollback failed: {}", e))
+}
+
+#[tauri::command]
+async fn enforce_sync_retention(
+    retention_days: i64,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let manager = state.get_sync_manager()?;
+    manager.enforce_history_retention(retention_days)
+        .map_err(|e| format!("Failed to enforce retention: {}", e))
+}
+
+// ============================================================================
+// Session Management Commands
+// ============================================================================
+
+/// Get active sessions
+#[tauri::command]
+async fn sync_get_sessions(
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+/// Revoke a specific session
+#[tauri::command]
+async fn sync_revoke_session(
+    device_id: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+/// Revoke all sessions except current
+#[tauri::command]
+async fn sync_revoke_all_sessions(
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+// ============================================================================
+// Audit Log Commands
+// ============================================================================
+
+/// Get audit logs with filters
+#[tauri::command]
+async fn sync_get_audit_logs(
+    filters: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+/// Get audit statistics
+#[tauri::command]
+async fn sync_get_audit_stats(
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+/// Export audit logs to CSV
+#[tauri::command]
+async fn sync_export_audit_logs(
+    start_date: Option<String>,
+    end_date: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+// ============================================================================
+// Two-Factor Authentication Commands
+// ============================================================================
+
+/// Get 2FA status for current user
+#[tauri::command]
+async fn sync_get_2fa_status(
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+/// Setup 2FA (generate secret and QR code)
+#[tauri::command]
+async fn sync_setup_2fa(
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+/// Enable 2FA (confirm with token and get backup codes)
+#[tauri::command]
+async fn sync_enable_2fa(
+    token: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+/// Disable 2FA (requires password and 2FA token)
+#[tauri::command]
+async fn sync_disable_2fa(
+    password: String,
+    token: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+/// Verify 2FA during login
+#[tauri::command]
+async fn sync_verify_2fa(
+    email: String,
+    token: String,
+    remember_device: bool,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    // TODO: Implement SyncManager API integration
+    // This is a stub implementation - SyncManager API needs to be updated
+    Err("Feature not yet implemented - SyncManager integration pending".to_string())
+}
+
+// ============================================================================
+// Background Scheduler Commands
+// ============================================================================
+
+/// Start background scheduler
+#[tauri::command]
+async fn scheduler_start(state: State<'_, AppState>) -> Result<(), String> {
+    state.background_scheduler
+        .start(state.sync_manager.clone())
+        .await
+        .map_err(|e| format!("Failed to start scheduler: {}", e))
+}
+
+/// Stop background scheduler
+#[tauri::command]
+async fn scheduler_stop(state: State<'_, AppState>) -> Result<(), String> {
+    state.background_scheduler
+        .stop()
+        .await
+        .map_err(|e| format!("Failed to stop scheduler: {}", e))
+}
+
+/// Get scheduler status
+#[tauri::command]
+async fn scheduler_get_status(state: State<'_, AppState>) -> Result<SchedulerStatusDto, String> {
+    let config = state.background_scheduler.get_config().await;
+    let running = state.background_scheduler.is_running();
+
+    // Calculate next_run timestamp
+    let next_run = if let Some(ref last_run_str) = config.last_run {
+        if let Ok(last_run) = chrono::DateTime::parse_from_rfc3339(last_run_str) {
+            let next = last_run + chrono::Duration::minutes(config.interval_minutes as i64);
+            Some(next.to_rfc3339())
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    Ok(SchedulerStatusDto {
+        enabled: config.enabled,
+        running,
+        interval_minutes: config.interval_minutes,
+        last_run: config.last_run,
+        next_run,
+    })
+}
+
+/// Update scheduler configuration
+#[tauri::command]
+async fn scheduler_update_config(
+    state: State<'_, AppState>,
+    enabled: bool,
+    interval_minutes: u64,
+) -> Result<(), String> {
+    state.background_scheduler
+        .update_config(enabled, interval_minutes, state.sync_manager.clone())
+        .await
+        .map_err(|e| format!("Failed to update scheduler config: {}", e))
+}
+
+// ============================================================================
+// Draft Commands
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct DraftEmailData {
+    #[cfg_attr(test, ts(type = "number | null"))]
+    id: Option<i64>,
+    #[cfg_attr(test, ts(type = "number"))]
+    account_id: i64,
+    to_addresses: String,
+    cc_addresses: String,
+    bcc_addresses: String,
+    subject: String,
+    body_text: String,
+    body_html: String,
+    /// Markdown source; body_html and body_text are rendered from it
+    #[serde(default)]
+    body_markdown: Option<String>,
+    #[cfg_attr(test, ts(type = "number | null"))]
+    reply_to_email_id: Option<i64>,
+    #[cfg_attr(test, ts(type = "number | null"))]
+    forward_email_id: Option<i64>,
+    compose_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct DraftAttachmentData {
+    filename: String,
+    content_type: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    size: i64,
+    local_path: String,
+}
+
+/// Draft list item (lightweight, for listing)
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct DraftListItem {
+    #[cfg_attr(test, ts(type = "number"))]
+    id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    account_id: i64,
+    subject: String,
+    to_addresses: String,
+    created_at: String,
+    updated_at: String,
+}
+
+/// Draft detail (full data, for editing)
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct DraftDetail {
+    #[cfg_attr(test, ts(type = "number"))]
+    id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    account_id: i64,
+    to_addresses: String,
+    cc_addresses: String,
+    bcc_addresses: String,
+    subject: String,
+    body_text: String,
+    body_html: String,
+    body_markdown: Option<String>,
+    #[cfg_attr(test, ts(type = "number | null"))]
+    reply_to_email_id: Option<i64>,
+    #[cfg_attr(test, ts(type = "number | null"))]
+    forward_email_id: Option<i64>,
+    compose_type: String,
+    created_at: String,
+    updated_at: String,
+    attachments: Vec<DraftAttachmentData>,
+}
+
+/// Sanitize filename to prevent path traversal attacks
+fn sanitize_filename(filename: &str) -> String {
+    filename
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '.' || *c == '_' || *c == '-')
+        .collect()
+}
+
+/// Save or update a draft email
+#[tauri::command]
+async fn draft_save(
+    state: State<'_, AppState>,
+    mut draft: DraftEmailData,
+    attachments: Vec<DraftAttachmentData>,
+    app_handle: tauri::AppHandle,
+) -> Result<i64, String> {
+    // Validate account
+    let account_id = draft.account_id;
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    // Validate JSON fields
+    serde_json::from_str::<Vec<serde_json::Value>>(&draft.to_addresses)
+        .map_err(|_| "Invalid to_addresses JSON")?;
+    serde_json::from_str::<Vec<serde_json::Value>>(&draft.cc_addresses)
+        .map_err(|_| "Invalid cc_addresses JSON")?;
+    serde_json::from_str::<Vec<serde_json::Value>>(&draft.bcc_addresses)
+        .map_err(|_| "Invalid bcc_addresses JSON")?;
+
+    if let Some(markdown) = &draft.body_markdown {
+        let rendered = mail::markdown::render(markdown);
+        draft.body_html = rendered.html;
+        draft.body_text = rendered.text;
+    }
+
+    // Insert or update draft
+    let draft_id = if let Some(existing_id) = draft.id {
+        // UPDATE existing
+        state.db.execute(
+            "UPDATE drafts SET
+                to_addresses = ?2, cc_addresses = ?3, bcc_addresses = ?4,
+                subject = ?5, body_text = ?6, body_html = ?7,
+                reply_to_email_id = ?8, forward_email_id = ?9,
+                compose_type = ?10, body_markdown = ?12, updated_at = datetime('now')
+             WHERE id = ?1 AND account_id = ?11",
+            rusqlite::params![
+                existing_id,
+                draft.to_addresses,
+                draft.cc_addresses,
+                draft.bcc_addresses,
+                draft.subject,
+                draft.body_text,
+                draft.body_html,
+                draft.reply_to_email_id,
+                draft.forward_email_id,
+                draft.compose_type,
+                account_id,
+                draft.body_markdown,
+            ],
+        )
+        .map_err(|e| format!("Failed to update draft: {}", e))?;
+
+        // Delete old attachments
+        state.db.execute(
+            "DELETE FROM draft_attachments WHERE draft_id = ?1",
+            rusqlite::params![existing_id],
+        )
+        .map_err(|e| format!("Failed to delete old attachments: {}", e))?;
+
+        existing_id
+    } else {
+        // INSERT new
+        state.db.execute_insert(
+            "INSERT INTO drafts (
+                account_id, to_addresses, cc_addresses, bcc_addresses,
+                subject, body_text, body_html, reply_to_email_id,
+                forward_email_id, compose_type, body_markdown
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            rusqlite::params![
+                account_id,
+                draft.to_addresses,
+                draft.cc_addresses,
+                draft.bcc_addresses,
+                draft.subject,
+                draft.body_text,
+                draft.body_html,
+                draft.reply_to_email_id,
+                draft.forward_email_id,
+                draft.compose_type,
+                draft.body_markdown,
+            ],
+        )
+        .map_err(|e| format!("Failed to insert draft: {}", e))?
+    };
+
+    // Copy attachments to persistent cache (encrypted when encryption at rest is on)
+    if !attachments.is_empty() {
+        let encrypt = attachment_store::encryption_enabled(&state.db)
+            .map_err(|e| format!("Failed to load encryption setting: {}", e))?;
+        let cache_dir = match portable::cache_dir() {
+            Some(dir) => dir,
+            None => app_handle
+                .path()
+                .app_cache_dir()
+                .map_err(|e| format!("Failed to get cache directory: {}", e))?,
+        };
+
+        let drafts_dir = profiles::cache_dir(&cache_dir, &state.profile).join("drafts").join(draft_id.to_string());
+        tokio::fs::create_dir_all(&drafts_dir)
+            .await
+            .map_err(|e| format!("Failed to create drafts directory: {}", e))?;
+
+        for (idx, att) in attachments.iter().enumerate() {
+            let dest_filename = format!("{}_{}", idx, sanitize_filename(&att.filename));
+            let dest_path = drafts_dir.join(&dest_filename);
+
+            let bytes = tokio::fs::read(&att.local_path)
+                .await
+                .map_err(|e| format!("Failed to copy attachment: {}", e))?;
+            let wrapped_key = attachment_store::write_file(&dest_path, &bytes, encrypt)
+                .map_err(|e| format!("Failed to copy attachment: {}", e))?;
+
+            state.db.execute(
+                "INSERT INTO draft_attachments (draft_id, filename, content_type, size, local_path, wrapped_key)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    draft_id,
+                    att.filename,
+                    att.content_type,
+                    att.size,
+                    dest_path.to_string_lossy().to_string(),
+                    wrapped_key,
+                ],
+            )
+            .map_err(|e| format!("Failed to insert attachment: {}", e))?;
+        }
+    }
+
+    Ok(draft_id)
+}
+
+/// Delete a draft email
+#[tauri::command]
+async fn draft_delete(state: State<'_, AppState>, draft_id: i64) -> Result<(), String> {
+    state.db.execute("DELETE FROM drafts WHERE id = ?1", rusqlite::params![draft_id])
+        .map_err(|e| format!("Failed to delete draft: {}", e))?;
+    Ok(())
+}
+
+/// List drafts for an account
+#[tauri::command]
+async fn draft_list(state: State<'_, AppState>, account_id: i64) -> Result<Vec<DraftListItem>, String> {
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    let result = state.db.query(
+        "SELECT id, account_id, subject, to_addresses, created_at, updated_at
+         FROM drafts
+         WHERE account_id = ?1
+         ORDER BY updated_at DESC",
+        rusqlite::params![account_id],
+        |row| {
+            Ok(DraftListItem {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                subject: row.get(2)?,
+                to_addresses: row.get(3)?,
+                created_at: row.get(4)?,
+                updated_at: row.get(5)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Failed to list drafts: {}", e))?;
+
+    Ok(result)
+}
+
+/// Get a single draft with full details
+#[tauri::command]
+async fn draft_get(state: State<'_, AppState>, draft_id: i64) -> Result<DraftDetail, String> {
+    if draft_id <= 0 {
+        return Err("Invalid draft ID".to_string());
+    }
+
+    // Get draft data
+    let drafts = state.db.query(
+        "SELECT id, account_id, to_addresses, cc_addresses, bcc_addresses,
+                subject, body_text, body_html, reply_to_email_id, forward_email_id,
+                compose_type, created_at, updated_at, body_markdown
+         FROM drafts
+         WHERE id = ?1",
+        rusqlite::params![draft_id],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+                row.get::<_, Option<i64>>(8)?,
+                row.get::<_, Option<i64>>(9)?,
+                row.get::<_, String>(10)?,
+                row.get::<_, String>(11)?,
+                row.get::<_, String>(12)?,
+                row.get::<_, Option<String>>(13)?,
+            ))
+        },
+    )
+    .map_err(|e| format!("Failed to get draft: {}", e))?;
+
+    if drafts.is_empty() {
+        return Err("Draft not found".to_string());
+    }
+
+    let (id, account_id, to_addresses, cc_addresses, bcc_addresses,
+         subject, body_text, body_html, reply_to_email_id, forward_email_id,
+         compose_type, created_at, updated_at, body_markdown) = drafts[0].clone();
+
+    // Get attachments
+    let rows = state.db.query(
+        "SELECT filename, content_type, size, local_path, wrapped_key
+         FROM draft_attachments
+         WHERE draft_id = ?1",
+        rusqlite::params![draft_id],
+        |row| {
+            Ok((
+                DraftAttachmentData {
+                    filename: row.get(0)?,
+                    content_type: row.get(1)?,
+                    size: row.get(2)?,
+                    local_path: row.get(3)?,
+                },
+                row.get::<_, Option<String>>(4)?,
+            ))
+        },
+    )
+    .map_err(|e| format!("Failed to get attachments: {}", e))?;
+
+    // Encrypted copies are handed to the composer as decrypted temp files
+    let mut attachments = Vec::with_capacity(rows.len());
+    for (mut att, wrapped_key) in rows {
+        if let Some(wrapped_key) = wrapped_key {
+            let bytes = attachment_store::read_file(std::path::Path::new(&att.local_path), Some(&wrapped_key))
+                .map_err(|e| format!("Failed to decrypt attachment {}: {}", att.filename, e))?;
+            let temp = write_temp_attachment(sanitize_filename(&att.filename), att.content_type.clone(), bytes).await?;
+            att.local_path = temp.path;
+        }
+        attachments.push(att);
+    }
+
+    Ok(DraftDetail {
+        id,
+        account_id,
+        to_addresses,
+        cc_addresses,
+        bcc_addresses,
+        subject,
+        body_text,
+        body_html,
+        body_markdown,
+        reply_to_email_id,
+        forward_email_id,
+        compose_type,
+        created_at,
+        updated_at,
+        attachments,
+    })
+}
+
+// ============================================================================
+// EMAIL FILTERS COMMANDS
+// ============================================================================
+
+use db::{EmailFilter as DbEmailFilter, NewEmailFilter as DbNewEmailFilter};
+use filters::{FilterAction, FilterCondition, MatchLogic};
+
+/// Check action parameters that the database does not enforce
+fn validate_filter_actions(actions: &[FilterAction]) -> Result<(), String> {
+    for action in actions {
+        if action.action == filters::FilterActionType::Webhook {
+            action.webhook.as_ref()
+                .ok_or("Webhook action requires a URL")?
+                .validate()?;
+        }
+    }
+    Ok(())
+}
+
+/// Add a new email filter
+#[tauri::command]
+async fn filter_add(
+    state: State<'_, AppState>,
+    filter: DbNewEmailFilter,
+) -> Result<i64, String> {
+    // Validate account_id
+    if filter.account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    // Validate filter name
+    if filter.name.trim().is_empty() {
+        return Err("Filter name cannot be empty".to_string());
+    }
+
+    // Validate conditions
+    if filter.conditions.is_empty() {
+        return Err("Filter must have at least one condition".to_string());
+    }
+
+    // Validate actions
+    if filter.actions.is_empty() {
+        return Err("Filter must have at least one action".to_string());
+    }
+    validate_filter_actions(&filter.actions)?;
+
+    let filter_id = state
+        .db
+        .add_filter(&filter)
+        .map_err(|e| format!("Failed to add filter: {}", e))?;
+
+    log::info!("Created filter '{}' with ID {}", filter.name, filter_id);
+
+    Ok(filter_id)
+}
+
+/// Get all filters for an account
+#[tauri::command]
+async fn filter_list(
+    state: State<'_, AppState>,
+    account_id: i64,
+) -> Result<Vec<DbEmailFilter>, String> {
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    let filters = state
+        .db
+        .get_filters(account_id)
+        .map_err(|e| format!("Failed to list filters: {}", e))?;
+
+    Ok(filters)
+}
+
+/// Get a single filter by ID
+#[tauri::command]
+async fn filter_get(state: State<'_, AppState>, filter_id: i64) -> Result<DbEmailFilter, String> {
+    if filter_id <= 0 {
+        return Err("Invalid filter ID".to_string());
+    }
+
+    let filter = state
+        .db
+        .get_filter(filter_id)
+        .map_err(|e| format!("Failed to get filter: {}", e))?;
+
+    Ok(filter)
+}
+
+/// Update an existing filter
+#[tauri::command]
+async fn filter_update(
+    state: State<'_, AppState>,
+    filter_id: i64,
+    filter: DbNewEmailFilter,
+) -> Result<(), String> {
+    if filter_id <= 0 {
+        return Err("Invalid filter ID".to_string());
+    }
+
+    // Validate same as filter_add
+    if filter.name.trim().is_empty() {
+        return Err("Filter name cannot be empty".to_string());
+    }
+
+    if filter.conditions.is_empty() {
+        return Err("Filter must have at least one condition".to_string());
+    }
+
+    if filter.actions.is_empty() {
+        return Err("Filter must have at least one action".to_string());
+    }
+    validate_filter_actions(&filter.actions)?;
+
+    state
+        .db
+        .update_filter(filter_id, &filter)
+        .map_err(|e| format!("Failed to update filter: {}", e))?;
+
+    log::info!("Updated filter ID {}", filter_id);
+
+    Ok(())
+}
+
+/// Delete a filter
+#[tauri::command]
+async fn filter_delete(state: State<'_, AppState>, filter_id: i64) -> Result<(), String> {
+    if filter_id <= 0 {
+        return Err("Invalid filter ID".to_string());
+    }
+
+    state
+        .db
+        .delete_filter(filter_id)
+        .map_err(|e| format!("Failed to delete filter: {}", e))?;
+
+    log::info!("Deleted filter ID {}", filter_id);
+
+    Ok(())
+}
+
+/// Toggle filter enabled state
+#[tauri::command]
+async fn filter_toggle(state: State<'_, AppState>, filter_id: i64) -> Result<(), String> {
+    if filter_id <= 0 {
+        return Err("Invalid filter ID".to_string());
+    }
+
+    state
+        .db
+        .toggle_filter(filter_id)
+        .map_err(|e| format!("Failed to toggle filter: {}", e))?;
+
+    log::info!("Toggled filter ID {}", filter_id);
+
+    Ok(())
+}
+
+/// Test if a filter would match a specific email
+#[tauri::command]
+async fn filter_test(
+    state: State<'_, AppState>,
+    filter_id: i64,
+    email_id: i64,
+) -> Result<bool, String> {
+    if filter_id <= 0 || email_id <= 0 {
+        return Err("Invalid filter or email ID".to_string());
+    }
+
+    // Get filter
+    let filter = state
+        .db
+        .get_filter(filter_id)
+        .map_err(|e| format!("Failed to get filter: {}", e))?;
+
+    // Get email
+    let email = state
+        .db
+        .get_email(email_id)
+        .map_err(|e| format!("Failed to get email: {}", e))?;
+
+    // Create filter engine and test
+    use filters::FilterEngine;
+    let engine = FilterEngine::new(state.db.clone()).with_events(state.store_events.clone());
+    let matches = engine.test_filter(&filter, &email);
+
+    Ok(matches)
+}
+
+/// Apply filters to existing emails in batch
+#[tauri::command]
+async fn filter_apply_batch(
+    state: State<'_, AppState>,
+    account_id: i64,
+    filter_id: Option<i64>,
+    folder_id: Option<i64>,
+) -> Result<FilterBatchResult, String> {
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    log::info!(
+        "Batch applying filters: account_id={}, filter_id={:?}, folder_id={:?}",
+        account_id,
+        filter_id,
+        folder_id
+    );
+
+    // Journaled so a run cut short by a crash resumes at the next startup
+    let batch = journal::FilterBatch { account_id, filter_id, folder_id };
+    let journal_id = uuid::Uuid::new_v4().to_string();
+    journal::begin(&state.db, &journal_id, journal::JournalKind::FilterBatch, Some(account_id), &batch)
+        .map_err(|e| format!("Failed to journal filter batch: {}", e))?;
+
+    let result = run_filter_batch(&state, &journal_id, &batch, None).await;
+    if let Err(e) = journal::finish(&state.db, &journal_id) {
+        log::warn!("Failed to finish journal entry {}: {}", journal_id, e);
+    }
+    result
+}
+
+/// Apply filters to the emails of a batch after `after_email_id`, in id order
+/// Progress is journaled after every email whose actions ran.
+async fn run_filter_batch(
+    state: &AppState,
+    journal_id: &str,
+    batch: &journal::FilterBatch,
+    after_email_id: Option<i64>,
+) -> Result<FilterBatchResult, String> {
+    // Get emails to process
+    let email_select = r#"
+        SELECT id, account_id, folder_id, message_id, uid,
+               from_address, from_name, to_addresses, cc_addresses, bcc_addresses, reply_to,
+               subject, preview, body_text, body_html, date,
+               is_read, is_starred, is_deleted, is_spam, is_draft, is_answered, is_forwarded,
+               has_attachments, has_inline_images, thread_id, in_reply_to, references_header,
+               priority, labels
+    "#;
+
+    // Without a folder, all emails of the account
+    let emails = state
+        .db
+        .query(
+            &format!(
+                "{} FROM emails WHERE account_id = ?1 AND (?2 IS NULL OR folder_id = ?2) AND id > ?3 AND is_deleted = 0 ORDER BY id",
+                email_select
+            ),
+            rusqlite::params![batch.account_id, batch.folder_id, after_email_id.unwrap_or(0)],
+            |row| db::Email::from_row(row),
+        )
+        .map_err(|e| format!("Failed to get emails: {}", e))?;
+
+    log::info!("Processing {} emails", emails.len());
+
+    // Create filter engine
+    use filters::FilterEngine;
+    let engine = FilterEngine::new(state.db.clone()).with_events(state.store_events.clone());
+
+    let mut emails_processed = 0;
+    let mut filters_matched = 0;
+    let mut actions_executed = 0;
+
+    for email in emails {
+        emails_processed += 1;
+
+        // Get actions to perform
+        let actions = if let Some(fid) = batch.filter_id {
+            // Apply specific filter only
+            let filter = state
+                .db
+                .get_filter(fid)
+                .map_err(|e| format!("Failed to get filter: {}", e))?;
+
+            if engine.test_filter(&filter, &email) {
+                filters_matched += 1;
+                // Update filter stats
+                state
+                    .db
+                    .execute(
+                        "UPDATE email_filters SET matched_count = matched_count + 1, last_matched_at = datetime('now') WHERE id = ?1",
+                        [fid],
+                    )
+                    .map_err(|e| format!("Failed to update filter stats: {}", e))?;
+
+                filter.actions
+            } else {
+                vec![]
+            }
+        } else {
+            // Apply all filters
+            let filter_actions = engine
+                .apply_filters(&email)
+                .await
+                .map_err(|e| format!("Failed to apply filters: {}", e))?;
+
+            if !filter_actions.is_empty() {
+                filters_matched += 1;
+            }
+
+            filter_actions
+        };
+
+        // Execute actions
+        if !actions.is_empty() {
+            actions_executed += actions.len();
+            engine
+                .execute_actions(email.id, actions)
+                .await
+                .map_err(|e| format!("Failed to execute actions: {}", e))?;
+            journal::record_progress(&state.db, journal_id, &email.id)
+                .map_err(|e| format!("Failed to journal filter batch: {}", e))?;
+        }
+    }
+
+    log::info!(
+        "Batch complete: processed={}, matched={}, actions={}",
+        emails_processed,
+        filters_matched,
+        actions_executed
+    );
+
+    Ok(FilterBatchResult {
+        emails_processed,
+        filters_matched,
+        actions_executed,
+    })
+}
+
+/// Finish filter batch runs a crash interrupted, after the last email each completed
+fn resume_filter_batches(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        let batches = match journal::interrupted_filter_batches(&state.db) {
+            Ok(batches) => batches,
+            Err(e) => {
+                log::warn!("Failed to load interrupted filter batches: {}", e);
+                return;
+            }
+        };
+
+        for (journal_id, batch, after_email_id) in batches {
+            log::info!("Resuming interrupted filter batch {} after email {:?}", journal_id, after_email_id);
+            if let Err(e) = run_filter_batch(&state, &journal_id, &batch, after_email_id).await {
+                log::warn!("Failed to resume filter batch {}: {}", journal_id, e);
+            }
+            if let Err(e) = journal::finish(&state.db, &journal_id) {
+                log::warn!("Failed to finish journal entry {}: {}", journal_id, e);
+            }
+        }
+    });
+}
+
+/// Export filters as JSON
+#[tauri::command]
+async fn filter_export(
+    state: State<'_, AppState>,
+    account_id: i64,
+) -> Result<String, String> {
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    let filters = state
+        .db
+        .get_filters(account_id)
+        .map_err(|e| format!("Failed to get filters: {}", e))?;
+
+    // Convert to JSON
     serde_json::to_string_pretty(&filters)
         .map_err(|e| format!("Failed to serialize filters: {}", e))
 }
 
-/// Import filters from JSON
+/// Import filters from JSON (requires a `filter_import` confirmation token)
+#[tauri::command]
+async fn filter_import(
+    state: State<'_, AppState>,
+    account_id: i64,
+    json_data: String,
+    confirm_token: String,
+) -> Result<usize, String> {
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+    state
+        .confirmations
+        .consume(authz::Scope::FilterImport, &account_id.to_string(), &confirm_token, Instant::now())?;
+
+    // Parse JSON
+    let filters: Vec<DbEmailFilter> = serde_json::from_str(&json_data)
+        .map_err(|e| format!("Invalid JSON format: {}", e))?;
+
+    log::info!("Importing {} filters for account {}", filters.len(), account_id);
+
+    let mut imported_count = 0;
+
+    for filter in filters {
+        // Create new filter (without ID) for the target account
+        let new_filter = DbNewEmailFilter {
+            account_id,
+            name: filter.name,
+            description: filter.description,
+            is_enabled: filter.is_enabled,
+            priority: filter.priority,
+            match_logic: filter.match_logic,
+            conditions: filter.conditions,
+            actions: filter.actions,
+        };
+
+        // Check if filter with same name already exists
+        let existing = state
+            .db
+            .get_filters(account_id)
+            .ok()
+            .and_then(|filters| {
+                filters.iter().find(|f| f.name == new_filter.name).cloned()
+            });
+
+        if existing.is_some() {
+            log::warn!("Skipping filter '{}' - already exists", new_filter.name);
+            continue;
+        }
+
+        if let Err(e) = validate_filter_actions(&new_filter.actions) {
+            log::warn!("Skipping filter '{}' - {}", new_filter.name, e);
+            continue;
+        }
+
+        // Add filter
+        state
+            .db
+            .add_filter(&new_filter)
+            .map_err(|e| format!("Failed to import filter '{}': {}", new_filter.name, e))?;
+
+        imported_count += 1;
+    }
+
+    log::info!("Successfully imported {} filters", imported_count);
+    Ok(imported_count)
+}
+
+/// Secret used to sign webhook requests (generated on first use)
+#[tauri::command]
+fn webhook_get_secret(state: State<'_, AppState>) -> Result<String, String> {
+    filters::webhook::signing_secret(&state.db)
+        .map_err(|e| format!("Failed to load webhook secret: {}", e))
+}
+
+/// Generate a new webhook signing secret
+#[tauri::command]
+fn webhook_rotate_secret(state: State<'_, AppState>) -> Result<String, String> {
+    filters::webhook::rotate_secret(&state.db)
+        .map_err(|e| format!("Failed to rotate webhook secret: {}", e))
+}
+
+/// Send a sample payload to a webhook URL
+#[tauri::command]
+async fn webhook_test(state: State<'_, AppState>, url: String, include_body: bool) -> Result<(), String> {
+    use filters::webhook::{self, WebhookEmail, WebhookFilter, WebhookPayload, FILTER_MATCHED_EVENT};
+
+    let target = filters::WebhookTarget::new(url, include_body);
+    target.validate()?;
+
+    let payload = WebhookPayload {
+        event: FILTER_MATCHED_EVENT.to_string(),
+        filter: WebhookFilter { id: 0, name: "Webhook test".to_string() },
+        email: WebhookEmail {
+            id: 0,
+            account_id: 0,
+            uid: 0,
+            message_id: "<test@owlivion.local>".to_string(),
+            from: "sender@example.com".to_string(),
+            from_name: Some("Example Sender".to_string()),
+            to: vec!["you@example.com".to_string()],
+            cc: Vec::new(),
+            subject: "Owlivion webhook test".to_string(),
+            date: chrono::Utc::now().to_rfc3339(),
+            preview: "If you can read this, the webhook works.".to_string(),
+            has_attachments: false,
+            labels: Vec::new(),
+            body_text: include_body.then(|| "If you can read this, the webhook works.".to_string()),
+            body_html: None,
+        },
+        sent_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let secret = webhook::signing_secret(&state.db)
+        .map_err(|e| format!("Failed to load webhook secret: {}", e))?;
+    webhook::deliver(&target, &payload, &secret).await
+}
+
+// ============================================================================
+// EMAIL TEMPLATES
+// ============================================================================
+
+/// Add a new email template
+#[tauri::command]
+async fn template_add(
+    state: State<'_, AppState>,
+    template: NewEmailTemplate,
+) -> Result<i64, String> {
+    // Validation
+    templates::validate(&template)?;
+
+    log::info!("Adding template: {}", template.name);
+
+    state
+        .db
+        .add_template(&template)
+        .map_err(|e| format!("Failed to add template: {}", e))
+}
+
+/// Get all templates for an account
+#[tauri::command]
+async fn template_list(
+    state: State<'_, AppState>,
+    account_id: i64,
+) -> Result<Vec<EmailTemplate>, String> {
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    state
+        .db
+        .get_templates(account_id)
+        .map_err(|e| format!("Failed to get templates: {}", e))
+}
+
+/// Get a single template by ID
+#[tauri::command]
+async fn template_get(
+    state: State<'_, AppState>,
+    template_id: i64,
+) -> Result<EmailTemplate, String> {
+    if template_id <= 0 {
+        return Err("Invalid template ID".to_string());
+    }
+
+    state
+        .db
+        .get_template(template_id)
+        .map_err(|e| format!("Failed to get template: {}", e))
+}
+
+/// Update an existing template
+#[tauri::command]
+async fn template_update(
+    state: State<'_, AppState>,
+    template_id: i64,
+    template: NewEmailTemplate,
+) -> Result<(), String> {
+    if template_id <= 0 {
+        return Err("Invalid template ID".to_string());
+    }
+
+    // Validation
+    templates::validate(&template)?;
+
+    log::info!("Updating template ID: {}", template_id);
+
+    state
+        .db
+        .update_template(template_id, &template)
+        .map_err(|e| format!("Failed to update template: {}", e))
+}
+
+/// Delete a template
+#[tauri::command]
+async fn template_delete(
+    state: State<'_, AppState>,
+    template_id: i64,
+) -> Result<(), String> {
+    if template_id <= 0 {
+        return Err("Invalid template ID".to_string());
+    }
+
+    log::info!("Deleting template ID: {}", template_id);
+
+    state
+        .db
+        .delete_template(template_id)
+        .map_err(|e| format!("Failed to delete template: {}", e))
+}
+
+/// Toggle template enabled status
+#[tauri::command]
+async fn template_toggle(
+    state: State<'_, AppState>,
+    template_id: i64,
+) -> Result<(), String> {
+    if template_id <= 0 {
+        return Err("Invalid template ID".to_string());
+    }
+
+    state
+        .db
+        .toggle_template(template_id)
+        .map_err(|e| format!("Failed to toggle template: {}", e))
+}
+
+/// Toggle template favorite status
+#[tauri::command]
+async fn template_toggle_favorite(
+    state: State<'_, AppState>,
+    template_id: i64,
+) -> Result<(), String> {
+    if template_id <= 0 {
+        return Err("Invalid template ID".to_string());
+    }
+
+    state
+        .db
+        .toggle_template_favorite(template_id)
+        .map_err(|e| format!("Failed to toggle template favorite: {}", e))
+}
+
+/// Increment template usage count
+#[tauri::command]
+async fn template_increment_usage(
+    state: State<'_, AppState>,
+    template_id: i64,
+) -> Result<(), String> {
+    if template_id <= 0 {
+        return Err("Invalid template ID".to_string());
+    }
+
+    state
+        .db
+        .increment_template_usage(template_id)
+        .map_err(|e| format!("Failed to increment template usage: {}", e))
+}
+
+/// Search templates using FTS5
+#[tauri::command]
+async fn template_search(
+    state: State<'_, AppState>,
+    account_id: i64,
+    query: String,
+    limit: i32,
+) -> Result<Vec<EmailTemplate>, String> {
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+    if query.trim().is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    if query.len() > 500 {
+        return Err("Search query too long".to_string());
+    }
+
+    state
+        .db
+        .search_templates(account_id, &query, limit)
+        .map_err(|e| format!("Failed to search templates: {}", e))
+}
+
+/// Get templates by category
+#[tauri::command]
+async fn template_get_by_category(
+    state: State<'_, AppState>,
+    account_id: i64,
+    category: String,
+) -> Result<Vec<EmailTemplate>, String> {
+    if account_id <= 0 {
+        return Err("Invalid account ID".to_string());
+    }
+
+    // Validate category
+    let valid_categories = vec![
+        "business", "personal", "customer_support",
+        "sales", "marketing", "internal", "custom"
+    ];
+    if !valid_categories.contains(&category.as_str()) {
+        return Err("Invalid category".to_string());
+    }
+
+    state
+        .db
+        .get_templates_by_category(account_id, &category)
+        .map_err(|e| format!("Failed to get templates by category: {}", e))
+}
+
+/// Get favorite templates
 #[tauri::command]
-async fn filter_import(
+async fn template_get_favorites(
     state: State<'_, AppState>,
     account_id: i64,
-    json_data: String,
-) -> Result<usize, String> {
+) -> Result<Vec<EmailTemplate>, String> {
     if account_id <= 0 {
         return Err("Invalid account ID".to_string());
     }
 
-    // Parse JSON
-    let filters: Vec<DbEmailFilter> = serde_json::from_str(&json_data)
-        .map_err(|e| format!("Invalid JSON format: {}", e))?;
+    state
+        .db
+        .get_favorite_templates(account_id)
+        .map_err(|e| format!("Failed to get favorite templates: {}", e))
+}
 
-    log::info!("Importing {} filters for account {}", filters.len(), account_id);
+/// Get available template categories
+#[tauri::command]
+async fn template_get_categories() -> Result<Vec<String>, String> {
+    Ok(templates::CATEGORIES.iter().map(|c| c.to_string()).collect())
+}
 
-    let mut imported_count = 0;
+/// Export templates to a JSON file that can be imported on another machine
+#[tauri::command]
+async fn template_export(
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+    path: String,
+) -> Result<usize, String> {
+    if ids.is_empty() {
+        return Err("No templates selected".to_string());
+    }
 
-    for filter in filters {
-        // Create new filter (without ID) for the target account
-        let new_filter = DbNewEmailFilter {
-            account_id,
-            name: filter.name,
-            description: filter.description,
-            is_enabled: filter.is_enabled,
-            priority: filter.priority,
-            match_logic: filter.match_logic,
-            conditions: filter.conditions,
-            actions: filter.actions,
-        };
+    let file = templates::export_templates(&state.db, &ids)
+        .map_err(|e| format!("Failed to export templates: {}", e))?;
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize templates: {}", e))?;
 
-        // Check if filter with same name already exists
-        let existing = state
-            .db
-            .get_filters(account_id)
-            .ok()
-            .and_then(|filters| {
-                filters.iter().find(|f| f.name == new_filter.name).cloned()
-            });
+    tokio::fs::write(&path, json)
+        .await
+        .map_err(|e| format!("Failed to write export file: {}", e))?;
 
-        if existing.is_some() {
-            log::warn!("Skipping filter '{}' - already exists", new_filter.name);
-            continue;
-        }
+    log::info!("Exported {} templates", file.templates.len());
+    Ok(file.templates.len())
+}
 
-        // Add filter
-        state
-            .db
-            .add_filter(&new_filter)
-            .map_err(|e| format!("Failed to import filter '{}': {}", new_filter.name, e))?;
+/// Import templates from an export file
+/// `account_id` None imports them as global templates; `on_conflict` decides
+/// what happens to templates whose name already exists (default: skip).
+/// Requires a `template_import` confirmation token for the path.
+#[tauri::command]
+async fn template_import(
+    state: State<'_, AppState>,
+    path: String,
+    account_id: Option<i64>,
+    on_conflict: Option<templates::ConflictPolicy>,
+    confirm_token: String,
+) -> Result<templates::ImportSummary, String> {
+    state
+        .confirmations
+        .consume(authz::Scope::TemplateImport, &path, &confirm_token, Instant::now())?;
+    if account_id.is_some_and(|id| id <= 0) {
+        return Err("Invalid account ID".to_string());
+    }
+
+    let json = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read import file: {}", e))?;
+    let file = templates::TemplateExportFile::parse(&json)?;
+
+    let summary = templates::import_templates(
+        &state.db,
+        account_id,
+        &file.templates,
+        on_conflict.unwrap_or_default(),
+    )
+    .map_err(|e| format!("Failed to import templates: {}", e))?;
+
+    log::info!("Imported templates: {:?}", summary);
+    Ok(summary)
+}
+
+/// Change the sync master password and re-encrypt all server data
+/// Call again with the same passwords to resume an interrupted rotation.
+/// Requires a `sync_rotate_master_password` confirmation token (empty target).
+#[tauri::command]
+async fn sync_rotate_master_password(
+    state: State<'_, AppState>,
+    old_master_password: String,
+    new_master_password: String,
+    confirm_token: String,
+) -> Result<sync::KeyRotationResult, String> {
+    state
+        .confirmations
+        .consume(authz::Scope::SyncRotateMasterPassword, "", &confirm_token, Instant::now())?;
+    let manager = state.get_sync_manager()?;
+    manager.rotate_master_password(&old_master_password, &new_master_password).await
+        .map_err(|e| format!("Failed to change master password: {}", e))
+}
+
+// Helper function to parse data type string
+fn parse_sync_data_type(data_type: &str) -> Result<sync::SyncDataType, String> {
+    match data_type {
+        "accounts" => Ok(sync::SyncDataType::Accounts),
+        "contacts" => Ok(sync::SyncDataType::Contacts),
+        "preferences" => Ok(sync::SyncDataType::Preferences),
+        "signatures" => Ok(sync::SyncDataType::Signatures),
+        "templates" => Ok(sync::SyncDataType::Templates),
+        "filters" => Ok(sync::SyncDataType::Filters),
+        "notes" => Ok(sync::SyncDataType::Notes),
+        _ => Err(format!("Invalid data type: {}", data_type)),
+    }
+}
+
+// DTO Types for Tauri Commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct FilterBatchResult {
+    emails_processed: usize,
+    filters_matched: usize,
+    actions_executed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct SenderBlockResult {
+    blocked: blocklist::BlockedSender,
+    /// Existing messages the block was applied to
+    applied: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+struct EmailSyncResult {
+    fetch_result: mail::FetchResult,
+    new_emails_count: usize,
+    filters_applied_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+struct SyncConfigDto {
+    enabled: bool,
+    user_id: Option<String>,
+    device_id: String,
+    device_name: String,
+    platform: String,
+    last_sync_at: Option<String>,
+    sync_accounts: bool,
+    sync_contacts: bool,
+    sync_preferences: bool,
+    sync_signatures: bool,
+    #[serde(default)]
+    sync_templates: bool,
+    #[serde(default)]
+    sync_filters: bool,
+    #[serde(default)]
+    sync_notes: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+struct SyncStatusDto {
+    data_type: String,
+    version: i32,
+    last_sync_at: Option<String>,
+    status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+struct DeviceInfoDto {
+    device_id: String,
+    device_name: String,
+    platform: String,
+    last_seen_at: String,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+struct SyncResultDto {
+    accounts_synced: bool,
+    contacts_synced: bool,
+    preferences_synced: bool,
+    signatures_synced: bool,
+    templates_synced: bool,
+    filters_synced: bool,
+    notes_synced: bool,
+    errors: Vec<String>,
+    types: Vec<sync::TypeSyncResult>,
+    offline: bool,
+    conflicts: Option<Vec<ConflictInfoDto>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+struct ConflictInfoDto {
+    data_type: String,
+    local_version: i32,
+    server_version: i32,
+    local_updated_at: Option<String>,
+    server_updated_at: Option<String>,
+    strategy: String,
+    conflict_details: String,
+    local_data: serde_json::Value,
+    server_data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+struct QueueStatsDto {
+    pending_count: i32,
+    in_progress_count: i32,
+    failed_count: i32,
+    completed_count: i32,
+    total_count: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+struct ProcessQueueResultDto {
+    processed: i32,
+    succeeded: i32,
+    failed: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "SyncSnapshot"))]
+#[serde(rename_all = "camelCase")]
+struct SyncSnapshotDto {
+    #[cfg_attr(test, ts(type = "number"))]
+    id: i64,
+    data_type: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    version: i64,
+    snapshot_hash: String,
+    device_id: String,
+    operation: String,
+    items_count: i32,
+    sync_status: String,
+    error_message: Option<String>,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+struct SchedulerStatusDto {
+    enabled: bool,
+    running: bool,
+    #[cfg_attr(test, ts(type = "number"))]
+    interval_minutes: u64,
+    last_run: Option<String>,
+    next_run: Option<String>,
+}
+
+// ============================================================================
+// OAuth2 Authentication Commands
+// ============================================================================
+
+// ============================================================================
+// Account Priority Settings Commands
+// ============================================================================
+
+/// Get priority fetching setting for an account
+#[tauri::command]
+async fn account_get_priority_fetch(
+    state: State<'_, AppState>,
+    account_id: i64,
+) -> Result<bool, String> {
+    state.db.get_account_priority_setting(account_id)
+        .map_err(|e| format!("Failed to get priority setting: {}", e))
+}
+
+/// Set priority fetching setting for an account
+#[tauri::command]
+async fn account_set_priority_fetch(
+    state: State<'_, AppState>,
+    account_id: i64,
+    enabled: bool,
+) -> Result<(), String> {
+    state.db.set_account_priority_setting(account_id, enabled)
+        .map_err(|e| format!("Failed to set priority setting: {}", e))
+}
+
+// ============================================================================
+// Backfill Commands
+// ============================================================================
+
+/// Start a background backfill of a folder; false when one is already running
+fn spawn_backfill(app_handle: tauri::AppHandle, account_id: i64, folder: String, since: Option<chrono::NaiveDate>) -> bool {
+    if !app_handle.state::<AppState>().backfills.try_begin(account_id, &folder) {
+        return false;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        let result = match connect_fresh_imap(&state.db, &state.credentials, account_id).await {
+            Ok(mut client) => {
+                let result = backfill::run(
+                    &state.db,
+                    &mut client,
+                    account_id,
+                    &folder,
+                    since,
+                    || state.connectivity.may_sync(account_id),
+                    |progress| {
+                        events::emit(&app_handle, progress);
+                    },
+                )
+                .await;
+                let _ = client.disconnect().await;
+                result
+            }
+            Err(e) => Err(e),
+        };
 
-        imported_count += 1;
-    }
+        match result {
+            Ok(_) => backfill_bodies(&app_handle, &state, account_id, &folder).await,
+            Err(e) => log::warn!("Backfill of {} for account {} failed: {}", folder, account_id, e),
+        }
+        state.backfills.finish(account_id, &folder);
+        state.badge.request();
+    });
 
-    log::info!("Successfully imported {} filters", imported_count);
-    Ok(imported_count)
+    true
 }
 
-// ============================================================================
-// EMAIL TEMPLATES
-// ============================================================================
+/// Download the bodies of a backfilled folder over parallel connections
+/// Skipped when disabled or while the network is metered.
+async fn backfill_bodies(app_handle: &tauri::AppHandle, state: &AppState, account_id: i64, folder: &str) {
+    let settings = match body_backfill::load_settings(&state.db) {
+        Ok(settings) if settings.enabled => settings,
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("Failed to load body backfill settings: {}", e);
+            return;
+        }
+    };
+    if state.connectivity.is_metered() {
+        return;
+    }
 
-/// Add a new email template
-#[tauri::command]
-async fn template_add(
-    state: State<'_, AppState>,
-    template: NewEmailTemplate,
-) -> Result<i64, String> {
-    // Validation
-    if template.name.trim().is_empty() {
-        return Err("Template name cannot be empty".to_string());
+    let result = body_backfill::run(
+        &state.db,
+        account_id,
+        folder,
+        settings.concurrency,
+        || connect_fresh_imap(&state.db, &state.credentials, account_id),
+        || state.connectivity.may_sync(account_id),
+        |progress| {
+            events::emit(app_handle, progress);
+        },
+    )
+    .await;
+    if let Err(e) = result {
+        log::warn!("Body backfill of {} for account {} failed: {}", folder, account_id, e);
     }
-    if template.name.len() > 200 {
-        return Err("Template name cannot exceed 200 characters".to_string());
+}
+
+/// Start the initial INBOX import or resume unfinished backfills of an account
+fn resume_backfills(app_handle: &tauri::AppHandle, state: &AppState, account_id: i64, sync_days: i32) {
+    if !state.connectivity.may_sync(account_id) {
+        return;
     }
-    if template.subject_template.len() > 1000 {
-        return Err("Subject template cannot exceed 1000 characters".to_string());
+
+    let states = match backfill::list_states(&state.db, account_id) {
+        Ok(states) => states,
+        Err(e) => {
+            log::warn!("Failed to load backfill state: {}", e);
+            return;
+        }
+    };
+
+    if states.is_empty() {
+        let since = backfill::window_start(sync_days, chrono::Utc::now().date_naive());
+        spawn_backfill(app_handle.clone(), account_id, "INBOX".to_string(), since);
+        return;
     }
-    if template.body_html_template.len() > 50_000 {
-        return Err("Body template cannot exceed 50KB".to_string());
+
+    for pending in states.into_iter().filter(|s| !s.done) {
+        spawn_backfill(app_handle.clone(), account_id, pending.folder, pending.since);
     }
+}
 
-    // Validate category
-    let valid_categories = vec![
-        "business", "personal", "customer_support",
-        "sales", "marketing", "internal", "custom"
-    ];
-    if !valid_categories.contains(&template.category.as_str()) {
-        return Err("Invalid category".to_string());
+/// Start (or restart) watching an account's folders for changes
+/// The watcher reconnects after failures until stopped. OAuth accounts are not
+/// watched, as their sessions are not kept open.
+fn spawn_folder_watch(app_handle: &tauri::AppHandle, state: &AppState, account_id: i64) {
+    let config = match folder_watch::get_config(&state.db, account_id) {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Failed to load watched folders: {}", e);
+            return;
+        }
+    };
+    let oauth = state.db.get_account(account_id).map_or(true, |account| account.oauth_provider.is_some());
+    if !config.enabled || config.folders.is_empty() || oauth {
+        state.folder_watchers.stop(account_id);
+        return;
     }
 
-    log::info!("Adding template: {}", template.name);
+    let status = Arc::new(Mutex::new(folder_watch::WatchStatus::new(account_id, &config.folders)));
+    let task_status = status.clone();
+    let app_handle = app_handle.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        use folder_watch::WatchMode;
+        loop {
+            let state = app_handle.state::<AppState>();
+            if state.db.get_account(account_id).is_err() {
+                return;
+            }
+            if !state.connectivity.may_sync(account_id) {
+                tokio::time::sleep(folder_watch::RETRY_DELAY).await;
+                continue;
+            }
 
-    state
-        .db
-        .add_template(&template)
-        .map_err(|e| format!("Failed to add template: {}", e))
+            let result = match connect_fresh_imap(&state.db, &state.credentials, account_id).await {
+                Ok(mut client) => {
+                    let mode = folder_watch::mode(&config, client.capabilities());
+                    folder_watch::update_status(&task_status, |status| {
+                        status.mode = mode;
+                        status.connected = mode != WatchMode::Off;
+                        status.last_error = None;
+                    });
+                    let on_change = |folder: &str| {
+                        folder_watch::update_status(&task_status, |status| status.last_change_at = Some(chrono::Utc::now()));
+                        state.folder_states.invalidate(&account_id.to_string(), Some(folder));
+                        let changed = folder_watch::FolderChanged { account_id, folder: folder.to_string() };
+                        events::emit(&app_handle, &changed);
+                    };
+                    let result = match mode {
+                        WatchMode::Notify => folder_watch::watch_notify(&mut client, &config.folders, on_change).await,
+                        WatchMode::RotatingIdle => {
+                            folder_watch::watch_rotating(&mut client, &config.folders, on_change).await
+                        }
+                        WatchMode::Off => {
+                            log::info!("Server of account {} supports neither NOTIFY nor IDLE", account_id);
+                            let _ = client.disconnect().await;
+                            return;
+                        }
+                    };
+                    let _ = client.disconnect().await;
+                    result.map_err(|e| e.to_string())
+                }
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                log::warn!("Folder watch for account {} interrupted: {}", account_id, e);
+                folder_watch::update_status(&task_status, |status| {
+                    status.connected = false;
+                    status.last_error = Some(e);
+                });
+            }
+            tokio::time::sleep(folder_watch::RETRY_DELAY).await;
+        }
+    });
+    state.folder_watchers.start(account_id, task, status);
 }
 
-/// Get all templates for an account
+/// Watched folders of an account
 #[tauri::command]
-async fn template_list(
+async fn folder_watch_get_config(state: State<'_, AppState>, account_id: i64) -> Result<folder_watch::WatchConfig, String> {
+    folder_watch::get_config(&state.db, account_id)
+        .map_err(|e| format!("Failed to load watched folders: {}", e))
+}
+
+/// Change the watched folders; a connected account's watcher restarts with them
+#[tauri::command]
+async fn folder_watch_set_config(
     state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
     account_id: i64,
-) -> Result<Vec<EmailTemplate>, String> {
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
+    config: folder_watch::WatchConfig,
+) -> Result<folder_watch::WatchConfig, String> {
+    let config = config.normalize()?;
+    folder_watch::set_config(&state.db, account_id, &config)
+        .map_err(|e| format!("Failed to save watched folders: {}", e))?;
+
+    let connected = state.async_imap_clients.lock().await.contains_key(&account_id.to_string());
+    if connected {
+        spawn_folder_watch(&app_handle, &state, account_id);
     }
-
-    state
-        .db
-        .get_templates(account_id)
-        .map_err(|e| format!("Failed to get templates: {}", e))
+    Ok(config)
 }
 
-/// Get a single template by ID
+/// Watcher state of an account (None while not watching)
 #[tauri::command]
-async fn template_get(
-    state: State<'_, AppState>,
-    template_id: i64,
-) -> Result<EmailTemplate, String> {
-    if template_id <= 0 {
-        return Err("Invalid template ID".to_string());
-    }
+async fn folder_watch_status(state: State<'_, AppState>, account_id: i64) -> Result<Option<folder_watch::WatchStatus>, String> {
+    Ok(state.folder_watchers.status(account_id))
+}
 
-    state
-        .db
-        .get_template(template_id)
-        .map_err(|e| format!("Failed to get template: {}", e))
+/// Accounts with their own refresh interval
+#[tauri::command]
+async fn refresh_intervals_get(state: State<'_, AppState>) -> Result<Vec<refresh_schedule::AccountRefresh>, String> {
+    refresh_schedule::list(&state.db).map_err(|e| format!("Failed to load refresh intervals: {}", e))
 }
 
-/// Update an existing template
+/// Set an account's refresh interval in minutes (None follows the auto-sync interval)
 #[tauri::command]
-async fn template_update(
+async fn refresh_interval_set(
     state: State<'_, AppState>,
-    template_id: i64,
-    template: NewEmailTemplate,
+    account_id: i64,
+    interval_minutes: Option<u32>,
 ) -> Result<(), String> {
-    if template_id <= 0 {
-        return Err("Invalid template ID".to_string());
-    }
-
-    // Validation
-    if template.name.trim().is_empty() {
-        return Err("Template name cannot be empty".to_string());
-    }
-    if template.name.len() > 200 {
-        return Err("Template name cannot exceed 200 characters".to_string());
-    }
-    if template.subject_template.len() > 1000 {
-        return Err("Subject template cannot exceed 1000 characters".to_string());
-    }
-    if template.body_html_template.len() > 50_000 {
-        return Err("Body template cannot exceed 50KB".to_string());
-    }
-
-    // Validate category
-    let valid_categories = vec![
-        "business", "personal", "customer_support",
-        "sales", "marketing", "internal", "custom"
-    ];
-    if !valid_categories.contains(&template.category.as_str()) {
-        return Err("Invalid category".to_string());
-    }
+    refresh_schedule::set_interval(&state.db, account_id, interval_minutes)
+        .map_err(|e| format!("Failed to save refresh interval: {}", e))?;
+    state.refresh_scheduler.request();
+    Ok(())
+}
 
-    log::info!("Updating template ID: {}", template_id);
+/// Backfill a folder within the account's sync window
+#[tauri::command]
+async fn backfill_start(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    account_id: i64,
+    folder: Option<String>,
+) -> Result<bool, String> {
+    let account = state.db.get_account(account_id)
+        .map_err(|e| format!("Failed to get account: {}", e))?;
+    let folder = folder.unwrap_or_else(|| "INBOX".to_string());
+    let since = backfill::window_start(account.sync_days, chrono::Utc::now().date_naive());
 
-    state
-        .db
-        .update_template(template_id, &template)
-        .map_err(|e| format!("Failed to update template: {}", e))
+    Ok(spawn_backfill(app_handle, account_id, folder, since))
 }
 
-/// Delete a template
+/// Extend the backfill window of a folder by `days` ("load older messages")
 #[tauri::command]
-async fn template_delete(
+async fn backfill_load_older(
     state: State<'_, AppState>,
-    template_id: i64,
-) -> Result<(), String> {
-    if template_id <= 0 {
-        return Err("Invalid template ID".to_string());
+    app_handle: tauri::AppHandle,
+    account_id: i64,
+    folder: Option<String>,
+    days: u32,
+) -> Result<bool, String> {
+    const MAX_EXTEND_DAYS: u32 = 3650;
+    if days == 0 || days > MAX_EXTEND_DAYS {
+        return Err(format!("Days must be 1-{}", MAX_EXTEND_DAYS));
     }
 
-    log::info!("Deleting template ID: {}", template_id);
+    let folder = folder.unwrap_or_else(|| "INBOX".to_string());
+    let current = match backfill::load_state(&state.db, account_id, &folder)
+        .map_err(|e| format!("Failed to load backfill state: {}", e))?
+    {
+        Some(existing) => existing.since,
+        None => {
+            let account = state.db.get_account(account_id)
+                .map_err(|e| format!("Failed to get account: {}", e))?;
+            backfill::window_start(account.sync_days, chrono::Utc::now().date_naive())
+        }
+    };
 
-    state
-        .db
-        .delete_template(template_id)
-        .map_err(|e| format!("Failed to delete template: {}", e))
+    // Already covering the full history: nothing older to load
+    let Some(since) = backfill::extend_window(current, days) else {
+        return Ok(false);
+    };
+
+    Ok(spawn_backfill(app_handle, account_id, folder, Some(since)))
 }
 
-/// Toggle template enabled status
+/// Get the body backfill settings
 #[tauri::command]
-async fn template_toggle(
-    state: State<'_, AppState>,
-    template_id: i64,
-) -> Result<(), String> {
-    if template_id <= 0 {
-        return Err("Invalid template ID".to_string());
-    }
-
-    state
-        .db
-        .toggle_template(template_id)
-        .map_err(|e| format!("Failed to toggle template: {}", e))
+async fn body_backfill_settings_get(state: State<'_, AppState>) -> Result<body_backfill::BodyBackfillSettings, String> {
+    body_backfill::load_settings(&state.db)
+        .map_err(|e| format!("Failed to load body backfill settings: {}", e))
 }
 
-/// Toggle template favorite status
+/// Save the body backfill settings; returns them as stored
 #[tauri::command]
-async fn template_toggle_favorite(
+async fn body_backfill_settings_set(
     state: State<'_, AppState>,
-    template_id: i64,
-) -> Result<(), String> {
-    if template_id <= 0 {
-        return Err("Invalid template ID".to_string());
-    }
-
-    state
-        .db
-        .toggle_template_favorite(template_id)
-        .map_err(|e| format!("Failed to toggle template favorite: {}", e))
+    settings: body_backfill::BodyBackfillSettings,
+) -> Result<body_backfill::BodyBackfillSettings, String> {
+    body_backfill::save_settings(&state.db, settings)
+        .map_err(|e| format!("Failed to save body backfill settings: {}", e))
 }
 
-/// Increment template usage count
+/// Backfill progress for all folders of an account
 #[tauri::command]
-async fn template_increment_usage(
+async fn backfill_status(
     state: State<'_, AppState>,
-    template_id: i64,
-) -> Result<(), String> {
-    if template_id <= 0 {
-        return Err("Invalid template ID".to_string());
-    }
-
-    state
-        .db
-        .increment_template_usage(template_id)
-        .map_err(|e| format!("Failed to increment template usage: {}", e))
+    account_id: i64,
+) -> Result<Vec<backfill::BackfillState>, String> {
+    backfill::list_states(&state.db, account_id)
+        .map_err(|e| format!("Failed to load backfill state: {}", e))
 }
 
-/// Search templates using FTS5
+// ============================================================================
+// Account Migration Commands
+// ============================================================================
+
+/// Copy folders from one account to another in the background; false when a
+/// migration between the two accounts is already running
+/// Requires an `account_migrate` confirmation token for `<source id>@<target id>`.
 #[tauri::command]
-async fn template_search(
+async fn account_migrate(
     state: State<'_, AppState>,
-    account_id: i64,
-    query: String,
-    limit: i32,
-) -> Result<Vec<EmailTemplate>, String> {
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
+    app_handle: tauri::AppHandle,
+    source_id: i64,
+    target_id: i64,
+    folder_mapping: Vec<account_migration::FolderMapping>,
+    confirm_token: String,
+) -> Result<bool, String> {
+    state
+        .confirmations
+        .consume(authz::Scope::AccountMigrate, &format!("{}@{}", source_id, target_id), &confirm_token, Instant::now())?;
+    if source_id == target_id {
+        return Err("Source and target account must differ".to_string());
     }
-    if query.trim().is_empty() {
-        return Err("Search query cannot be empty".to_string());
+    if folder_mapping.is_empty() {
+        return Err("No folders selected".to_string());
     }
-    if query.len() > 500 {
-        return Err("Search query too long".to_string());
+    for account_id in [source_id, target_id] {
+        state.db.get_account(account_id)
+            .map_err(|e| format!("Failed to get account: {}", e))?;
+    }
+    if !state.migrations.try_begin(source_id, target_id) {
+        return Ok(false);
     }
 
-    state
-        .db
-        .search_templates(account_id, &query, limit)
-        .map_err(|e| format!("Failed to search templates: {}", e))
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        let result = async {
+            let mut source = connect_fresh_imap(&state.db, &state.credentials, source_id).await?;
+            let mut target = match connect_fresh_imap(&state.db, &state.credentials, target_id).await {
+                Ok(target) => target,
+                Err(e) => {
+                    let _ = source.disconnect().await;
+                    return Err(e);
+                }
+            };
+
+            let mut result = Ok(());
+            for mapping in &folder_mapping {
+                result = account_migration::run(
+                    &state.db,
+                    &mut source,
+                    &mut target,
+                    source_id,
+                    target_id,
+                    mapping,
+                    || state.connectivity.may_sync(source_id) && state.connectivity.may_sync(target_id),
+                    |progress| {
+                        events::emit(&app_handle, progress);
+                    },
+                )
+                .await
+                .map(|_| ());
+                if result.is_err() {
+                    break;
+                }
+            }
+            let _ = source.disconnect().await;
+            let _ = target.disconnect().await;
+            result
+        }
+        .await;
+
+        if let Err(e) = result {
+            log::warn!("Migration from account {} to {} failed: {}", source_id, target_id, e);
+        }
+        state.migrations.finish(source_id, target_id);
+    });
+
+    Ok(true)
 }
 
-/// Get templates by category
+/// Migration progress for each folder pair between two accounts
 #[tauri::command]
-async fn template_get_by_category(
+async fn account_migrate_status(
     state: State<'_, AppState>,
-    account_id: i64,
-    category: String,
-) -> Result<Vec<EmailTemplate>, String> {
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
-    }
+    source_id: i64,
+    target_id: i64,
+) -> Result<Vec<account_migration::MigrationState>, String> {
+    account_migration::list_states(&state.db, source_id, target_id)
+        .map_err(|e| format!("Failed to load migration state: {}", e))
+}
 
-    // Validate category
-    let valid_categories = vec![
-        "business", "personal", "customer_support",
-        "sales", "marketing", "internal", "custom"
-    ];
-    if !valid_categories.contains(&category.as_str()) {
-        return Err("Invalid category".to_string());
+// ============================================================================
+// Mail Merge Commands
+// ============================================================================
+
+/// Sends merge messages through the account's SMTP connection
+struct AppMergeSender(tauri::AppHandle);
+
+#[async_trait::async_trait]
+impl mailmerge::MergeSender for AppMergeSender {
+    async fn send(&self, account_id: i64, message: &pending_ops::QueuedSend) -> mail::MailResult<()> {
+        let state = self.0.state::<AppState>();
+        if !state.connectivity.is_online() {
+            return Err(mail::MailError::NotConnected);
+        }
+        deliver_email(&state.db, &state.credentials, account_id, message, None).await
     }
+}
 
-    state
-        .db
-        .get_templates_by_category(account_id, &category)
-        .map_err(|e| format!("Failed to get templates by category: {}", e))
+/// Send the pending recipients of a merge in the background; false when it is already running
+fn spawn_mailmerge(app_handle: tauri::AppHandle, merge_id: i64) -> bool {
+    let Some(cancelled) = app_handle.state::<AppState>().mail_merges.try_begin(merge_id) else {
+        return false;
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        let delay = state
+            .db
+            .query_row("SELECT per_minute FROM mail_merges WHERE id = ?1", [merge_id], |row| row.get(0))
+            .map(mailmerge::send_delay)
+            .unwrap_or_else(|_| mailmerge::send_delay(mailmerge::DEFAULT_PER_MINUTE));
+        let sender = AppMergeSender(app_handle.clone());
+        let result = mailmerge::run(&state.db, &sender, merge_id, &cancelled, delay, |progress| {
+            events::emit(&app_handle, progress);
+        })
+        .await;
+
+        if let Err(e) = result {
+            log::warn!("Mail merge {} failed: {}", merge_id, e);
+        }
+        state.mail_merges.finish(merge_id);
+    });
+
+    true
 }
 
-/// Get favorite templates
+/// Create a mail merge from a template and CSV text and start sending
+/// Returns the merge id. Requires a `mailmerge_start` confirmation token for
+/// `<account id>@<template id>`.
 #[tauri::command]
-async fn template_get_favorites(
+async fn mailmerge_start(
     state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
     account_id: i64,
-) -> Result<Vec<EmailTemplate>, String> {
-    if account_id <= 0 {
-        return Err("Invalid account ID".to_string());
+    template_id: i64,
+    csv: String,
+    per_minute: Option<u32>,
+    confirm_token: String,
+) -> Result<i64, String> {
+    state
+        .confirmations
+        .consume(authz::Scope::MailmergeStart, &format!("{}@{}", account_id, template_id), &confirm_token, Instant::now())?;
+    let template = state.db.get_template(template_id)
+        .map_err(|e| format!("Failed to get template: {}", e))?;
+    let merge_id = mailmerge::create(
+        &state.db,
+        account_id,
+        &template,
+        &csv,
+        per_minute.unwrap_or(mailmerge::DEFAULT_PER_MINUTE),
+    )
+    .map_err(|e| format!("Failed to create mail merge: {}", e))?;
+    if let Err(e) = state.db.increment_template_usage(template_id) {
+        log::warn!("Failed to update template usage: {}", e);
     }
 
-    state
-        .db
-        .get_favorite_templates(account_id)
-        .map_err(|e| format!("Failed to get favorite templates: {}", e))
+    spawn_mailmerge(app_handle, merge_id);
+    Ok(merge_id)
 }
 
-/// Get available template categories
+/// Progress of a mail merge with the status of every recipient
 #[tauri::command]
-async fn template_get_categories() -> Result<Vec<String>, String> {
-    Ok(vec![
-        "business".to_string(),
-        "personal".to_string(),
-        "customer_support".to_string(),
-        "sales".to_string(),
-        "marketing".to_string(),
-        "internal".to_string(),
-        "custom".to_string(),
-    ])
+async fn mailmerge_status(state: State<'_, AppState>, merge_id: i64) -> Result<mailmerge::MergeStatus, String> {
+    mailmerge::status(&state.db, merge_id)
+        .map_err(|e| format!("Failed to get mail merge: {}", e))
 }
 
-// Helper function to parse data type string
-fn parse_sync_data_type(data_type: &str) -> Result<sync::SyncDataType, String> {
-    match data_type {
-        "accounts" => Ok(sync::SyncDataType::Accounts),
-        "contacts" => Ok(sync::SyncDataType::Contacts),
-        "preferences" => Ok(sync::SyncDataType::Preferences),
-        "signatures" => Ok(sync::SyncDataType::Signatures),
-        _ => Err(format!("Invalid data type: {}", data_type)),
+/// Stop a mail merge; recipients not yet sent stay pending
+#[tauri::command]
+async fn mailmerge_cancel(state: State<'_, AppState>, merge_id: i64) -> Result<(), String> {
+    // A running merge records the cancellation before its next message
+    if state.mail_merges.cancel(merge_id) {
+        return Ok(());
     }
+    mailmerge::cancel(&state.db, merge_id)
+        .map_err(|e| format!("Failed to cancel mail merge: {}", e))
 }
 
-// DTO Types for Tauri Commands
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct FilterBatchResult {
-    emails_processed: usize,
-    filters_matched: usize,
-    actions_executed: usize,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct EmailSyncResult {
-    fetch_result: mail::FetchResult,
-    new_emails_count: usize,
-    filters_applied_count: usize,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SyncConfigDto {
-    enabled: bool,
-    user_id: Option<String>,
-    device_id: String,
-    device_name: String,
-    platform: String,
-    last_sync_at: Option<String>,
-    sync_accounts: bool,
-    sync_contacts: bool,
-    sync_preferences: bool,
-    sync_signatures: bool,
+/// Resume a paused or cancelled merge with its pending recipients; false when it is already running
+#[tauri::command]
+async fn mailmerge_resume(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    merge_id: i64,
+) -> Result<bool, String> {
+    let progress = mailmerge::progress(&state.db, merge_id)
+        .map_err(|e| format!("Failed to get mail merge: {}", e))?;
+    if progress.pending == 0 {
+        return Err("The mail merge has no recipients left to send".to_string());
+    }
+    Ok(spawn_mailmerge(app_handle, merge_id))
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SyncStatusDto {
-    data_type: String,
-    version: i32,
-    last_sync_at: Option<String>,
-    status: String,
-}
+// ============================================================================
+// Sync Control Commands
+// ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct DeviceInfoDto {
-    device_id: String,
-    device_name: String,
-    platform: String,
-    last_seen_at: String,
-    created_at: String,
+/// Emit the current connectivity status to the frontend
+fn emit_connectivity_changed(app_handle: &tauri::AppHandle, state: &AppState) {
+    events::emit(app_handle, &state.connectivity.status());
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SyncResultDto {
-    accounts_synced: bool,
-    contacts_synced: bool,
-    preferences_synced: bool,
-    signatures_synced: bool,
-    errors: Vec<String>,
-    conflicts: Option<Vec<ConflictInfoDto>>,
+/// Get network state and paused accounts
+#[tauri::command]
+async fn connectivity_status(state: State<'_, AppState>) -> Result<connectivity::ConnectivityStatus, String> {
+    Ok(state.connectivity.status())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ConflictInfoDto {
-    data_type: String,
-    local_version: i32,
-    server_version: i32,
-    local_updated_at: Option<String>,
-    server_updated_at: Option<String>,
-    strategy: String,
-    conflict_details: String,
-    local_data: serde_json::Value,
-    server_data: serde_json::Value,
-}
+/// Report the OS network state (called from the frontend online/offline listeners)
+/// Going offline suspends background IMAP/SMTP work; coming back resumes it
+#[tauri::command]
+async fn network_set_online(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    online: bool,
+) -> Result<(), String> {
+    if let Ok(manager) = state.get_sync_manager() {
+        manager.set_online(online);
+    }
+    if !state.connectivity.set_online(online) {
+        return Ok(());
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct QueueStatsDto {
-    pending_count: i32,
-    in_progress_count: i32,
-    failed_count: i32,
-    completed_count: i32,
-    total_count: i32,
+    log::info!("Network is now {}", if online { "online" } else { "offline" });
+    if online {
+        state.pending_ops.wake();
+    }
+    emit_connectivity_changed(&app_handle, &state);
+    Ok(())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ProcessQueueResultDto {
-    processed: i32,
-    succeeded: i32,
-    failed: i32,
+/// Report whether the network is metered (skips optional traffic such as prefetching)
+#[tauri::command]
+async fn network_set_metered(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    metered: bool,
+) -> Result<(), String> {
+    if state.connectivity.set_metered(metered) {
+        log::info!("Network is now {}", if metered { "metered" } else { "unmetered" });
+        emit_connectivity_changed(&app_handle, &state);
+    }
+    Ok(())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SyncSnapshotDto {
-    id: i64,
-    data_type: String,
-    version: i64,
-    snapshot_hash: String,
-    device_id: String,
-    operation: String,
-    items_count: i32,
-    sync_status: String,
-    error_message: Option<String>,
-    created_at: String,
-}
+/// Pause background syncing for an account
+#[tauri::command]
+async fn account_sync_pause(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    account_id: i64,
+) -> Result<(), String> {
+    state.connectivity.set_paused(&state.db, account_id, true)
+        .map_err(|e| format!("Failed to pause sync: {}", e))?;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SchedulerStatusDto {
-    enabled: bool,
-    running: bool,
-    interval_minutes: u64,
-    last_run: Option<String>,
-    next_run: Option<String>,
+    log::info!("Sync paused for account {}", account_id);
+    emit_connectivity_changed(&app_handle, &state);
+    Ok(())
 }
 
-// ============================================================================
-// OAuth2 Authentication Commands
-// ============================================================================
-
-// ============================================================================
-// Account Priority Settings Commands
-// ============================================================================
-
-/// Get priority fetching setting for an account
+/// Resume background syncing for an account
 #[tauri::command]
-async fn account_get_priority_fetch(
+async fn account_sync_resume(
     state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
     account_id: i64,
-) -> Result<bool, String> {
-    state.db.get_account_priority_setting(account_id)
-        .map_err(|e| format!("Failed to get priority setting: {}", e))
+) -> Result<(), String> {
+    state.connectivity.set_paused(&state.db, account_id, false)
+        .map_err(|e| format!("Failed to resume sync: {}", e))?;
+
+    log::info!("Sync resumed for account {}", account_id);
+    state.pending_ops.wake();
+    emit_connectivity_changed(&app_handle, &state);
+    Ok(())
 }
 
-/// Set priority fetching setting for an account
+/// Disable or re-enable an account without deleting it
+/// A disabled account is disconnected and left out of the unified inbox and
+/// all background work; its local mail stays for when it is enabled again.
 #[tauri::command]
-async fn account_set_priority_fetch(
+async fn account_set_active(
     state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
     account_id: i64,
-    enabled: bool,
+    active: bool,
 ) -> Result<(), String> {
-    state.db.set_account_priority_setting(account_id, enabled)
-        .map_err(|e| format!("Failed to set priority setting: {}", e))
+    state.connectivity.set_active(&state.db, account_id, active)
+        .map_err(|e| format!("Failed to update account: {}", e))?;
+
+    if active {
+        log::info!("Account {} enabled", account_id);
+        state.pending_ops.wake();
+    } else {
+        state.folder_watchers.stop(account_id);
+        state.folder_states.invalidate(&account_id.to_string(), None);
+        let client = state.async_imap_clients.lock().await.remove(&account_id.to_string());
+        if let Some(mut client) = client {
+            let _ = client.disconnect().await;
+        }
+        log::info!("Account {} disabled", account_id);
+    }
+    state.badge.request();
+    state.refresh_scheduler.request();
+    emit_connectivity_changed(&app_handle, &state);
+    Ok(())
 }
 
 // ============================================================================
@@ -4258,6 +8629,7 @@ async fn complete_oauth_flow(provider: &str) -> Result<OAuthCompleteResult, Stri
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 struct OAuthCompleteResult {
     email: String,
     display_name: Option<String>,
@@ -4287,6 +8659,12 @@ async fn cache_clear(state: State<'_, AppState>) -> Result<(), String> {
     Ok(())
 }
 
+/// Attachment download totals per fetch method (BINARY vs whole message)
+#[tauri::command]
+async fn metrics_fetch_benchmarks() -> Result<Vec<metrics::FetchBenchmark>, String> {
+    Ok(metrics::fetch_benchmarks())
+}
+
 /// Background sync all emails for a folder (progressive loading)
 /// Fetches all emails in chunks without blocking the UI
 #[tauri::command]
@@ -4307,55 +8685,91 @@ async fn email_sync_all_background(
 // Application Entry Point
 // ============================================================================
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    // Load .env file for OAuth credentials
-    dotenvy::dotenv().ok();
-
-    // Initialize logger
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
-
-    // SECURITY: Graceful error handling instead of panics at startup
-    // Get app directories with proper error handling
-    let app_dir = match directories::ProjectDirs::from("com", "owlivion", "owlivion-mail") {
-        Some(dirs) => dirs,
-        None => {
-            log::error!("Failed to get app directories - cannot determine data location");
-            eprintln!("FATAL: Failed to get app directories. Please ensure HOME environment variable is set.");
-            std::process::exit(1);
+/// Profile to start into (launch flag or link, else the last one switched to) and its database
+fn launch_database_path() -> Result<(String, std::path::PathBuf), String> {
+    let profiles = profiles::Profiles::open_default()?;
+    let requested = profiles::launch_profile(std::env::args().skip(1));
+    let profile = match profiles.resolve(requested.as_deref()) {
+        Ok(profile) => profile,
+        Err(e) => {
+            log::warn!("{}; opening the last used profile", e);
+            profiles.active()?
         }
     };
+    let db_path = profiles.database_path(&profile)?;
+    Ok((profile, db_path))
+}
 
-    let data_dir = app_dir.data_dir();
+/// Open the launch profile's database, recovering it first if it is corrupt
+fn open_app_state() -> Result<AppState, String> {
+    let (profile, db_path) = launch_database_path()?;
+    log::info!("Profile {}, database path: {:?}", profile, db_path);
+
+    // Recover a corrupt database before the pool opens it
+    let startup_recovery =
+        db::recovery::check_and_recover(&db_path).map_err(|e| format!("Database recovery failed: {}", e))?;
+
+    let attachment_store = attachment_store::AttachmentStore::for_database(&db_path);
+
+    let db = Database::new(db_path).map_err(|e| format!("Database initialization failed: {}", e))?;
+    log::info!("Database initialized successfully");
 
-    // Create data directory with proper error handling
-    if let Err(e) = std::fs::create_dir_all(data_dir) {
-        log::error!("Failed to create data directory: {}", e);
-        eprintln!("FATAL: Failed to create data directory at {:?}: {}", data_dir, e);
-        std::process::exit(1);
+    // Settle a key rotation cut short before secrets are decrypted
+    if let Err(e) = rekey::resolve_interrupted(&db) {
+        log::error!("Failed to resolve interrupted key rotation: {}", e);
     }
 
-    let db_path = data_dir.join("owlivion.db");
-    log::info!("Database path: {:?}", db_path);
+    let mut app_state = AppState::new(db, attachment_store);
+    app_state.startup_recovery = startup_recovery;
+    app_state.profile = profile;
+    Ok(app_state)
+}
 
-    // Initialize database with proper error handling
-    let db = match Database::new(db_path) {
-        Ok(db) => db,
-        Err(e) => {
-            log::error!("Failed to initialize database: {}", e);
-            eprintln!("FATAL: Database initialization failed: {}", e);
-            std::process::exit(1);
-        }
+/// Show the main window and hand opened links to the frontend
+fn open_links(app: &tauri::AppHandle, links: Vec<deep_links::DeepLink>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    // A launch handed off while this app is still starting has no state to queue into yet
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
     };
-    log::info!("Database initialized successfully");
+    if links.is_empty() {
+        return;
+    }
+    state.deep_links.push(links);
+    events::emit(app, &deep_links::LinksPending);
+}
 
-    let app_state = AppState::new(db);
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // Load .env file for OAuth credentials
+    dotenvy::dotenv().ok();
+
+    // Initialize logger (recent lines are kept for the diagnostics bundle)
+    diagnostics::init_logging();
+
+    // Keep the WebView's storage with the portable data as well
+    #[cfg(windows)]
+    if let Some(dir) = portable::dir() {
+        std::env::set_var("WEBVIEW2_USER_DATA_FOLDER", dir.join("webview"));
+    }
 
     // Run Tauri application with proper error handling
-    if let Err(e) = tauri::Builder::default()
-        .manage(app_state)
+    let app = match tauri::Builder::default()
+        // A second launch hands its command line to the running app and exits
+        // here, before it opens the database
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            open_links(app, deep_links::from_args(args));
+        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_autostart::Builder::new().arg(autostart::AUTOSTART_FLAG).build())
+        .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
             greet,
             autoconfig_detect,
@@ -4368,25 +8782,190 @@ pub fn run() {
             account_update_signature,
             account_get_priority_fetch,
             account_set_priority_fetch,
+            connectivity_status,
+            network_set_online,
+            network_set_metered,
+            account_sync_pause,
+            account_sync_resume,
+            account_set_active,
+            backfill_start,
+            backfill_load_older,
+            backfill_status,
+            body_backfill_settings_get,
+            body_backfill_settings_set,
+            account_migrate,
+            account_migrate_status,
+            mailmerge_start,
+            mailmerge_status,
+            mailmerge_cancel,
+            mailmerge_resume,
             fetch_url_content,
             account_list,
             account_connect,
+            account_get_auth_state,
+            account_get_capabilities,
             account_delete,
+            confirm_request,
+            account_get_send_on_behalf,
+            account_set_send_on_behalf,
             folder_list,
+            folder_list_shared,
+            folder_rename,
+            folder_move,
             email_list,
+            email_list_changes,
+            startup_sync,
             email_list_all_accounts,
             email_sync_with_filters,
             email_get,
+            email_versions,
             email_download_attachment,
             email_search,
             email_search_advanced,
+            virtual_folder_list,
+            reply_needed_set,
+            reply_needed_remind,
+            email_extract_events,
+            invite_list,
+            email_set_focus,
+            email_focus_other,
+            domain_policy_list,
+            domain_policy_save,
+            domain_policy_delete,
+            domain_policy_check_link,
+            link_preview,
             email_mark_read,
             email_mark_starred,
+            email_set_tag,
+            imap_folder_tags,
             email_move,
             email_delete,
+            pending_ops_list,
+            activity_list,
+            activity_undo,
+            action_undo,
+            undo_window_get,
+            undo_window_set,
+            plugin_list,
+            email_precheck_send,
+            precheck_settings_get,
+            precheck_settings_set,
+            account_check_sender_auth,
             email_send,
+            attachment_encryption_get,
+            attachment_encryption_set,
+            attachments_list,
+            mailbox_stats,
+            cleanup_suggestions,
+            cleanup_execute,
+            diagnostics_export,
+            portable_dir_get,
+            profile_list,
+            profile_create,
+            profile_switch,
+            onboarding_get_state,
+            onboarding_complete_step,
+            onboarding_reset,
+            settings_get_all,
+            settings_set_many,
+            prefetch_settings_get,
+            prefetch_settings_set,
+            sent_copy_upload_get,
+            sent_copy_upload_set,
+            sent_copy_warnings,
             write_temp_attachment,
+            temp_attachment_begin,
+            temp_attachment_append,
+            temp_attachment_finish,
+            temp_attachment_abort,
             attachment_upload,
+            attachment_check_size,
+            attachment_webdav_set,
+            attachment_share_link,
+            email_quote_inline_images,
+            local_folder_create,
+            local_folder_list,
+            local_folder_rename,
+            local_folder_delete,
+            local_folder_emails,
+            email_list_window,
+            search_index_check,
+            search_index_rebuild,
+            db_recovery_report,
+            db_maintenance_run,
+            db_maintenance_last,
+            spam_training_get_config,
+            spam_training_set_config,
+            spam_training_run,
+            spam_training_last,
+            spam_score,
+            email_spam_report,
+            sender_block,
+            sender_block_list,
+            sender_unblock,
+            sender_block_apply,
+            email_redirect,
+            send_quota_status,
+            send_quota_set_limits,
+            folder_watch_get_config,
+            folder_watch_set_config,
+            folder_watch_status,
+            refresh_intervals_get,
+            refresh_interval_set,
+            crypto_rekey,
+            credentials_lock,
+            local_email_get,
+            email_copy_to_local_folder,
+            local_email_transfer,
+            local_folder_export,
+            feed_add,
+            feed_list,
+            feed_update,
+            feed_remove,
+            feed_refresh,
+            digest_get_config,
+            digest_set_config,
+            digest_run_now,
+            digest_filter_notifications,
+            contact_set_vip,
+            contact_list_vip,
+            contact_list_bouncing,
+            contact_remove_bouncing,
+            notification_get_dnd,
+            notification_set_dnd,
+            notification_route,
+            notification_get_sounds,
+            notification_set_sounds,
+            notification_play_sound,
+            notification_preview_sound,
+            hotkeys_get_config,
+            hotkeys_set_config,
+            hotkeys_status,
+            deep_link_parse,
+            deep_link_take_pending,
+            system_is_default_mailer,
+            system_set_default_mailer,
+            autostart_enable,
+            autostart_disable,
+            autostart_status,
+            note_get,
+            note_set,
+            note_delete,
+            note_list,
+            alias_generate,
+            alias_list,
+            alias_delete,
+            alias_of_email,
+            alias_stats,
+            suggest_send_time,
+            ai_complete,
+            ai_get_provider,
+            ai_set_provider,
+            ai_get_context_sharing,
+            ai_set_context_sharing,
+            thread_summarize,
+            thread_export_html,
+            thread_export_markdown,
             get_email_attachments,
             attachment_download,
             oauth_start_gmail,
@@ -4394,9 +8973,11 @@ pub fn run() {
             sync_login,
             sync_logout,
             sync_start,
+            sync_retry_deferred,
             sync_resolve_conflict,
             sync_get_config,
             sync_update_config,
+            sync_rotate_master_password,
             sync_get_status,
             sync_list_devices,
             sync_revoke_device,
@@ -4426,6 +9007,9 @@ pub fn run() {
             filter_apply_batch,
             filter_export,
             filter_import,
+            webhook_get_secret,
+            webhook_rotate_secret,
+            webhook_test,
             template_add,
             template_list,
             template_get,
@@ -4438,6 +9022,8 @@ pub fn run() {
             template_get_by_category,
             template_get_favorites,
             template_get_categories,
+            template_export,
+            template_import,
             sync_get_sessions,
             sync_revoke_session,
             sync_revoke_all_sessions,
@@ -4451,9 +9037,151 @@ pub fn run() {
             sync_verify_2fa,
             cache_get_stats,
             cache_clear,
+            metrics_fetch_benchmarks,
             email_sync_all_background,
         ])
         .setup(|app| {
+            // SECURITY: Graceful error handling instead of panics at startup
+            let app_state = match open_app_state() {
+                Ok(app_state) => app_state,
+                Err(e) => {
+                    log::error!("{}", e);
+                    eprintln!("FATAL: {}", e);
+                    // Let the next launch start instead of handing off to this one
+                    tauri_plugin_single_instance::destroy(app);
+                    std::process::exit(1);
+                }
+            };
+            app.manage(app_state);
+
+            // Forward store change events to the frontend
+            events::forward_to_frontend(app.handle().clone(), &app.state::<AppState>().store_events);
+            settings::forward_to_frontend(app.handle().clone(), &app.state::<AppState>().settings_events);
+
+            // The window starts hidden; login launches in "start minimized" mode keep it in the tray
+            let state = app.state::<AppState>();
+            if autostart::start_hidden(&state.db, std::env::args()) {
+                log::info!("Started at login, staying in the tray");
+            } else if let Some(window) = app.get_webview_window("main") {
+                if let Err(e) = window.show() {
+                    log::warn!("Failed to show main window: {}", e);
+                }
+            }
+
+            // Run queued IMAP operations in the background
+            state.pending_ops.start(
+                state.store_events.clone(),
+                Arc::new(MailOpExecutor { app: app.handle().clone() }),
+            );
+
+            // Finish filter batch runs interrupted by a crash
+            resume_filter_batches(app.handle().clone());
+
+            // Poll RSS/Atom subscriptions into their local folders
+            let feed_events = app.handle().clone();
+            state.feeds.start(
+                filters::FilterEngine::new(state.db.clone()).with_events(state.store_events.clone()),
+                move |results| {
+                    events::emit(&feed_events, &results);
+                },
+            );
+
+            // Roll newsletters up into scheduled digests
+            let digest_events = app.handle().clone();
+            state.digests.start(move |results| {
+                events::emit(&digest_events, &results);
+            });
+
+            // Classify mail awaiting a reply and announce due reminders
+            let reply_events = app.handle().clone();
+            state.replies.start(move |reminders| {
+                events::emit(&reply_events, &reminders);
+            });
+
+            // Tell the UI the database was rebuilt from a corrupt file
+            if state.startup_recovery != db::recovery::RecoveryReport::Healthy {
+                events::emit(app.handle(), &state.startup_recovery);
+            }
+
+            // Keep the search indexes consistent and compact
+            let index_events = app.handle().clone();
+            state.search_index.start(move |repaired| {
+                events::emit(&index_events, &repaired);
+            });
+
+            // Checkpoint, analyze and vacuum the database while idle
+            state.maintenance.start(&state.store_events);
+
+            // Forget cached folder states when the app changes messages
+            state.folder_states.start(&state.store_events);
+
+            // Train the spam classifier on messages moved into or out of Junk
+            state.spam_trainer.start();
+
+            // Unread count on the taskbar or dock icon
+            state.badge.start(app.handle().clone(), &state.store_events, &state.settings_events);
+            state.refresh_scheduler.start(app.handle().clone(), &state.settings_events);
+
+            // mailto: and owlivion:// links from the launch and, on macOS, while running
+            // (on Windows and Linux later links come from the single-instance handoff)
+            state.deep_links.push(deep_links::from_args(std::env::args()));
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                #[cfg(any(windows, target_os = "linux"))]
+                if let Err(e) = app.deep_link().register("owlivion") {
+                    log::warn!("Failed to register owlivion:// links: {}", e);
+                }
+
+                #[cfg(target_os = "macos")]
+                {
+                    let link_handle = app.handle().clone();
+                    app.deep_link().on_open_url(move |event| {
+                        let links: Vec<_> = event
+                            .urls()
+                            .iter()
+                            .filter_map(|url| deep_links::parse(url.as_str()).map_err(|e| log::warn!("Ignoring link: {}", e)).ok())
+                            .collect();
+                        if !links.is_empty() {
+                            open_links(&link_handle, links);
+                        }
+                    });
+                }
+            }
+
+            // Global compose and show/hide shortcuts
+            match hotkeys::load_config(&state.db)
+                .map_err(|e| e.to_string())
+                .and_then(|config| hotkeys::apply(app.handle(), &config))
+            {
+                Ok(status) => {
+                    if let Ok(mut current) = state.hotkeys.lock() {
+                        *current = status;
+                    }
+                }
+                Err(e) => log::warn!("Failed to register global hotkeys: {}", e),
+            }
+
+            // Expire cached credentials, report accounts that need a new password
+            let attention_handle = app.handle().clone();
+            state.credentials.start(move |auth_state| {
+                events::emit(&attention_handle, &auth_state);
+            });
+
+            // Move pre-deduplication downloads into the attachment store, drop unused blobs
+            let store_db = state.db.clone();
+            let store = state.attachment_store.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                match store.import_legacy(&store_db) {
+                    Ok(0) => {}
+                    Ok(imported) => log::info!("Moved {} downloaded attachments into the attachment store", imported),
+                    Err(e) => log::error!("Failed to import downloaded attachments: {}", e),
+                }
+                if let Err(e) = store.collect_garbage(&store_db) {
+                    log::error!("Failed to collect unused attachments: {}", e);
+                }
+            });
+
             // Setup system tray
             if let Err(e) = tray::setup_tray(&app.handle()) {
                 log::error!("Failed to setup system tray: {}", e);
@@ -4468,11 +9196,7 @@ pub fn run() {
                     if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                         // Check if close_to_tray is enabled
                         if let Some(state) = app_handle.try_state::<AppState>() {
-                            // Get setting from database (JSON boolean)
-                            let should_minimize: bool = state.db.get_setting("close_to_tray")
-                                .ok()
-                                .flatten()
-                                .unwrap_or(true); // Default: minimize to tray
+                            let should_minimize = settings::get_bool(&state.db, "close_to_tray");
 
                             if should_minimize {
                                 // Hide window instead of closing
@@ -4515,10 +9239,20 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
+        .build(tauri::generate_context!())
     {
-        log::error!("Tauri application error: {}", e);
-        eprintln!("FATAL: Tauri application error: {}", e);
-        std::process::exit(1);
-    }
+        Ok(app) => app,
+        Err(e) => {
+            log::error!("Tauri application error: {}", e);
+            eprintln!("FATAL: Tauri application error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    app.run(|app, event| {
+        if let tauri::RunEvent::Exit = event {
+            // SECURITY: Drop decrypted credentials before the process ends
+            app.state::<AppState>().credentials.clear();
+        }
+    });
 }
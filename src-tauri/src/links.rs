@@ -0,0 +1,313 @@
+//! Link Analysis
+//!
+//! Mail links are often wrapped by a security gateway (Outlook SafeLinks,
+//! Proofpoint), a redirector (Google, Facebook, Slack) or a click tracker.
+//! Where the destination is encoded in the link itself it is decoded locally,
+//! without contacting anyone. URL shorteners and click trackers that keep the
+//! destination on their server are resolved from the backend with HEAD
+//! requests that read the `Location` header but never load the target.
+//! Tracking parameters are stripped from the result, and the final address
+//! gets reputation hints (raw IP host, punycode, plain HTTP, ...).
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+/// Wrappers decoded or redirects resolved per link
+const MAX_HOPS: usize = 5;
+
+/// Timeout of each HEAD request to a shortener
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Hosts that keep the destination on their server (resolved with HEAD)
+const SHORTENERS: &[&str] = &[
+    "bit.ly",
+    "bitly.com",
+    "t.co",
+    "tinyurl.com",
+    "goo.gl",
+    "ow.ly",
+    "buff.ly",
+    "is.gd",
+    "lnkd.in",
+    "rebrand.ly",
+    "cutt.ly",
+    "t.ly",
+    "shorturl.at",
+];
+
+/// Click trackers of mailing services (resolved with HEAD)
+const CLICK_TRACKERS: &[&str] = &["list-manage.com", "ct.sendgrid.net", "mailchi.mp", "hubspotlinks.com", "mandrillapp.com"];
+
+/// Query parameters that only identify the recipient or campaign
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid", "_hsenc", "_hsmi", "mkt_tok", "igshid", "yclid",
+];
+
+/// What stands out about a link's destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum LinkHint {
+    /// Plain HTTP
+    Insecure,
+    /// Host is an IP address rather than a name
+    IpAddress,
+    /// Internationalised host (possible look-alike)
+    Punycode,
+    /// `user@host` form that hides the real host
+    Credentials,
+    /// Port other than the scheme's default
+    UnusualPort,
+    /// Passed through a URL shortener
+    Shortened,
+    /// Passed through a click tracker
+    Tracked,
+    /// Destination could not be determined (shortener unreachable, too many hops)
+    Unresolved,
+    /// Not a web link
+    NotWeb,
+}
+
+/// Destination of a link
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct LinkPreview {
+    pub original: String,
+    pub final_url: String,
+    pub domain: Option<String>,
+    /// Addresses passed through, in order, excluding the original
+    pub hops: Vec<String>,
+    pub hints: Vec<LinkHint>,
+}
+
+fn host_matches(host: &str, domains: &[&str]) -> bool {
+    domains
+        .iter()
+        .any(|domain| host == *domain || host.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+fn query_value(url: &Url, names: &[&str]) -> Option<String> {
+    url.query_pairs().find(|(key, _)| names.contains(&key.as_ref())).map(|(_, value)| value.into_owned())
+}
+
+fn web_url(value: &str) -> Option<Url> {
+    Url::parse(value).ok().filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Destination encoded in a wrapper link, if `url` is one
+fn decode_wrapper(url: &Url) -> Option<Url> {
+    let host = url.host_str()?.to_lowercase();
+    let path = url.path();
+
+    let inner = if host.ends_with("safelinks.protection.outlook.com") {
+        query_value(url, &["url"])
+    } else if host == "urldefense.proofpoint.com" && path.starts_with("/v2/url") {
+        // v2 encodes '%' as '-' and '/' as '_'
+        query_value(url, &["u"]).and_then(|value| {
+            let encoded = value.replace('-', "%").replace('_', "/");
+            urlencoding::decode(&encoded).ok().map(|decoded| decoded.into_owned())
+        })
+    } else if host == "urldefense.com" && path.starts_with("/v3/__") {
+        let rest = url.as_str().split_once("/v3/__")?.1;
+        rest.split_once("__;").map(|(inner, _)| inner.to_string())
+    } else if (host == "www.google.com" || host == "google.com") && path == "/url" {
+        query_value(url, &["q", "url"])
+    } else if host_matches(&host, &["l.facebook.com", "lm.facebook.com", "l.instagram.com"]) && path.starts_with("/l") {
+        query_value(url, &["u"])
+    } else if host == "slack-redir.net" {
+        query_value(url, &["url"])
+    } else if host == "www.youtube.com" && path == "/redirect" {
+        query_value(url, &["q"])
+    } else if host_matches(&host, CLICK_TRACKERS) || host_matches(&host, SHORTENERS) {
+        None
+    } else {
+        // Generic open redirect carrying the full destination
+        query_value(url, &["redirect", "redirect_url", "redirect_uri", "target", "dest", "destination"])
+            .filter(|value| web_url(value).is_some() && (host.contains("track") || host.contains("click")))
+    };
+
+    inner.as_deref().and_then(web_url)
+}
+
+/// Drop recipient and campaign tracking parameters
+pub fn strip_tracking(url: &Url) -> Url {
+    let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    let kept: Vec<&(String, String)> = pairs
+        .iter()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_str()))
+        .collect();
+    if kept.len() == pairs.len() {
+        return url.clone();
+    }
+
+    let mut stripped = url.clone();
+    if kept.is_empty() {
+        stripped.set_query(None);
+    } else {
+        stripped.query_pairs_mut().clear().extend_pairs(kept.iter().map(|(key, value)| (key, value)));
+    }
+    stripped
+}
+
+/// Decode wrapper links without any network access
+/// Returns the addresses passed through after the original, the last one
+/// being the destination as far as it can be decoded.
+pub fn unwrap_local(url: &Url) -> Vec<Url> {
+    let mut hops = Vec::new();
+    let mut current = url.clone();
+    while hops.len() < MAX_HOPS {
+        let Some(inner) = decode_wrapper(&current) else { break };
+        hops.push(inner.clone());
+        current = inner;
+    }
+    hops
+}
+
+fn hints(url: &Url) -> Vec<LinkHint> {
+    let mut hints = Vec::new();
+    if !matches!(url.scheme(), "http" | "https") {
+        hints.push(LinkHint::NotWeb);
+        return hints;
+    }
+    if url.scheme() == "http" {
+        hints.push(LinkHint::Insecure);
+    }
+    match url.host() {
+        Some(url::Host::Ipv4(_)) | Some(url::Host::Ipv6(_)) => hints.push(LinkHint::IpAddress),
+        Some(url::Host::Domain(domain)) if domain.split('.').any(|label| label.starts_with("xn--")) => {
+            hints.push(LinkHint::Punycode)
+        }
+        _ => {}
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        hints.push(LinkHint::Credentials);
+    }
+    if url.port().is_some() {
+        hints.push(LinkHint::UnusualPort);
+    }
+    hints
+}
+
+/// `Location` of a redirect response, without following it
+async fn redirect_target(client: &reqwest::Client, url: &Url) -> Option<Url> {
+    let response = client.head(url.as_str()).send().await.ok()?;
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let location = response.headers().get(reqwest::header::LOCATION)?.to_str().ok()?;
+    url.join(location).ok().filter(|target| matches!(target.scheme(), "http" | "https"))
+}
+
+/// Final destination of a link and what stands out about it
+pub async fn preview(url: &str) -> Result<LinkPreview, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Ok(LinkPreview {
+            original: url.to_string(),
+            final_url: parsed.to_string(),
+            domain: None,
+            hops: Vec::new(),
+            hints: hints(&parsed),
+        });
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut hops: Vec<Url> = Vec::new();
+    let mut extra = Vec::new();
+    let mut current = parsed.clone();
+    loop {
+        let decoded = unwrap_local(&current);
+        if let Some(last) = decoded.last() {
+            current = last.clone();
+            hops.extend(decoded);
+        }
+
+        let host = current.host_str().unwrap_or_default().to_lowercase();
+        let shortened = host_matches(&host, SHORTENERS);
+        let tracked = host_matches(&host, CLICK_TRACKERS);
+        if !shortened && !tracked {
+            break;
+        }
+        let hint = if shortened { LinkHint::Shortened } else { LinkHint::Tracked };
+        if !extra.contains(&hint) {
+            extra.push(hint);
+        }
+        if hops.len() >= MAX_HOPS * 2 {
+            extra.push(LinkHint::Unresolved);
+            break;
+        }
+        match redirect_target(&client, &current).await {
+            Some(target) => {
+                hops.push(target.clone());
+                current = target;
+            }
+            None => {
+                extra.push(LinkHint::Unresolved);
+                break;
+            }
+        }
+    }
+
+    let final_url = strip_tracking(&current);
+    let mut all_hints = hints(&final_url);
+    all_hints.extend(extra);
+    Ok(LinkPreview {
+        original: url.to_string(),
+        domain: final_url.host_str().map(|host| host.to_lowercase()),
+        final_url: final_url.to_string(),
+        hops: hops.iter().map(Url::to_string).collect(),
+        hints: all_hints,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unwrap(url: &str) -> String {
+        let url = Url::parse(url).unwrap();
+        let hops = unwrap_local(&url);
+        strip_tracking(hops.last().unwrap_or(&url)).to_string()
+    }
+
+    #[test]
+    fn test_unwrap_wrappers() {
+        assert_eq!(
+            unwrap("https://eur01.safelinks.protection.outlook.com/?url=https%3A%2F%2Fexample.com%2Finvoice%3Fid%3D7&data=x&reserved=0"),
+            "https://example.com/invoice?id=7"
+        );
+        assert_eq!(
+            unwrap("https://urldefense.proofpoint.com/v2/url?u=https-3A__example.com_path_page&d=DwMF&c=x"),
+            "https://example.com/path/page"
+        );
+        assert_eq!(unwrap("https://urldefense.com/v3/__https://example.com/a__;!!abc$"), "https://example.com/a");
+        assert_eq!(
+            unwrap("https://www.google.com/url?q=https://example.com/&sa=D"),
+            "https://example.com/"
+        );
+        // Nested wrappers and tracking parameters
+        assert_eq!(
+            unwrap("https://l.facebook.com/l.php?u=https%3A%2F%2Fwww.google.com%2Furl%3Fq%3Dhttps%253A%252F%252Fshop.test%252F%253Futm_source%253Dmail%2526item%253D3"),
+            "https://shop.test/?item=3"
+        );
+        // Not decodable locally
+        assert_eq!(unwrap("https://bit.ly/abc"), "https://bit.ly/abc");
+    }
+
+    #[test]
+    fn test_hints() {
+        let hint = |url: &str| hints(&Url::parse(url).unwrap());
+        assert!(hint("https://example.com/").is_empty());
+        assert_eq!(hint("http://192.0.2.1:8080/login"), vec![LinkHint::Insecure, LinkHint::IpAddress, LinkHint::UnusualPort]);
+        assert_eq!(hint("https://xn--80ak6aa92e.com/"), vec![LinkHint::Punycode]);
+        assert_eq!(hint("https://bank.test@evil.example/"), vec![LinkHint::Credentials]);
+        assert_eq!(hint("mailto:a@b.test"), vec![LinkHint::NotWeb]);
+    }
+}
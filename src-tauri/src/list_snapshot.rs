@@ -0,0 +1,193 @@
+//! Email List Snapshots
+//!
+//! Each listing returned through `email_list_changes` is kept as a numbered
+//! snapshot. When the list view refreshes it sends the id of the snapshot it
+//! shows, and only the summaries added, changed or removed since then cross
+//! IPC, plus the UID order of the page. An unknown or expired snapshot id (or
+//! one for another folder or page) gets the full page. Snapshots live in
+//! memory only and the oldest are dropped beyond `MAX_SNAPSHOTS`.
+
+use crate::mail::EmailSummary;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Snapshots kept at once (a few per open list view)
+const MAX_SNAPSHOTS: usize = 64;
+
+/// Listing a snapshot belongs to: account, folder, page and page size
+type ListKey = (String, String, u32, u32);
+
+/// A page of the list relative to an earlier snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct EmailListChanges {
+    /// Pass this as `since_snapshot` on the next refresh
+    #[cfg_attr(test, ts(type = "number"))]
+    pub snapshot_id: u64,
+    /// True when `added` holds the whole page (no usable base snapshot)
+    pub full: bool,
+    pub total: u32,
+    pub has_more: bool,
+    /// UIDs of the page in display order
+    pub order: Vec<u32>,
+    pub added: Vec<EmailSummary>,
+    /// Summaries of messages whose flags, subject or other fields changed
+    pub changed: Vec<EmailSummary>,
+    pub removed: Vec<u32>,
+    /// Pass this as `state_token` on the next refresh (see `email_list`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub state_token: Option<String>,
+}
+
+struct Snapshot {
+    key: ListKey,
+    emails: Vec<EmailSummary>,
+}
+
+#[derive(Default)]
+struct SnapshotState {
+    next_id: u64,
+    snapshots: HashMap<u64, Snapshot>,
+    /// Ids oldest first
+    order: VecDeque<u64>,
+}
+
+/// Recently returned list pages
+#[derive(Default)]
+pub struct ListSnapshots {
+    state: Mutex<SnapshotState>,
+}
+
+/// Summaries added and changed in `new`, and UIDs of `old` no longer in it
+fn diff(old: &[EmailSummary], new: &[EmailSummary]) -> (Vec<EmailSummary>, Vec<EmailSummary>, Vec<u32>) {
+    let previous: HashMap<u32, &EmailSummary> = old.iter().map(|email| (email.uid, email)).collect();
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for email in new {
+        match previous.get(&email.uid) {
+            None => added.push(email.clone()),
+            Some(before) if *before != email => changed.push(email.clone()),
+            Some(_) => {}
+        }
+    }
+    let removed = old
+        .iter()
+        .map(|email| email.uid)
+        .filter(|uid| !new.iter().any(|email| email.uid == *uid))
+        .collect();
+    (added, changed, removed)
+}
+
+impl ListSnapshots {
+    /// Store a page as a new snapshot and describe it relative to `since`
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &self,
+        account_id: &str,
+        folder: &str,
+        page: u32,
+        page_size: u32,
+        emails: Vec<EmailSummary>,
+        total: u32,
+        has_more: bool,
+        since: Option<u64>,
+    ) -> EmailListChanges {
+        let key: ListKey = (account_id.to_string(), folder.to_string(), page, page_size);
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let base = since
+            .and_then(|id| state.snapshots.get(&id))
+            .filter(|snapshot| snapshot.key == key);
+        let (full, (added, changed, removed)) = match base {
+            Some(snapshot) => (false, diff(&snapshot.emails, &emails)),
+            None => (true, (emails.clone(), Vec::new(), Vec::new())),
+        };
+
+        state.next_id += 1;
+        let snapshot_id = state.next_id;
+        state.snapshots.insert(snapshot_id, Snapshot { key, emails: emails.clone() });
+        state.order.push_back(snapshot_id);
+        while state.order.len() > MAX_SNAPSHOTS {
+            if let Some(oldest) = state.order.pop_front() {
+                state.snapshots.remove(&oldest);
+            }
+        }
+
+        EmailListChanges {
+            snapshot_id,
+            full,
+            total,
+            has_more,
+            order: emails.iter().map(|email| email.uid).collect(),
+            added,
+            changed,
+            removed,
+            state_token: None,
+        }
+    }
+
+    /// Messages of snapshot `id`, if it is still kept and of this listing
+    pub fn emails(&self, id: u64, account_id: &str, folder: &str, page: u32, page_size: u32) -> Option<Vec<EmailSummary>> {
+        let key: ListKey = (account_id.to_string(), folder.to_string(), page, page_size);
+        let state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.snapshots.get(&id).filter(|snapshot| snapshot.key == key).map(|snapshot| snapshot.emails.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(uid: u32, is_read: bool) -> EmailSummary {
+        EmailSummary {
+            uid,
+            message_id: Some(format!("<{}@test>", uid)),
+            from: "a@test.com".to_string(),
+            from_name: None,
+            subject: format!("Message {}", uid),
+            preview: String::new(),
+            date: "2024-03-08".to_string(),
+            is_read,
+            is_starred: false,
+            has_attachments: false,
+            size: 0,
+            keywords: Vec::new(),
+            account_id: None,
+            account_email: None,
+            account_name: None,
+            account_color: None,
+        }
+    }
+
+    #[test]
+    fn test_changes_since_snapshot() {
+        let snapshots = ListSnapshots::default();
+        let first = snapshots.record("1", "INBOX", 0, 3, vec![summary(3, false), summary(2, false), summary(1, true)], 3, false, None);
+        assert!(first.full);
+        assert_eq!(first.added.len(), 3);
+
+        // UID 4 arrived, 2 was read, 1 dropped off the page
+        let page = vec![summary(4, false), summary(3, false), summary(2, true)];
+        let second = snapshots.record("1", "INBOX", 0, 3, page, 4, true, Some(first.snapshot_id));
+        assert!(!second.full);
+        assert_eq!(second.order, vec![4, 3, 2]);
+        assert_eq!(second.added.iter().map(|e| e.uid).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(second.changed.iter().map(|e| e.uid).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(second.removed, vec![1]);
+        assert_eq!(snapshots.emails(second.snapshot_id, "1", "INBOX", 0, 3).map(|emails| emails.len()), Some(3));
+        assert_eq!(snapshots.emails(second.snapshot_id, "1", "INBOX", 1, 3), None);
+
+        // A snapshot of another folder is no base
+        let other = snapshots.record("1", "Archive", 0, 3, vec![summary(9, true)], 1, false, Some(second.snapshot_id));
+        assert!(other.full);
+
+        // Expired snapshots fall back to the full page
+        for _ in 0..MAX_SNAPSHOTS {
+            snapshots.record("1", "INBOX", 0, 3, Vec::new(), 0, false, None);
+        }
+        assert!(snapshots.record("1", "INBOX", 0, 3, Vec::new(), 0, false, Some(second.snapshot_id)).full);
+    }
+}
@@ -3,11 +3,16 @@
 //! Uses async-imap crate which has better parser compatibility.
 
 use crate::mail::{
-    config::{ImapConfig, SecurityType},
-    EmailSummary, FetchResult, Folder, FolderType, MailError, MailResult, ParsedEmail, EmailAttachment, AttachmentData,
+    capabilities::ImapCapabilities,
+    binary, bounce, calendar, headers, keywords,
+    namespace::{NamespaceKind, Namespaces},
+    config::{is_loopback_host, ImapConfig, SecurityType},
+    special_use, EmailSummary, FetchResult, Folder, FolderState, FolderType, MailError, MailResult, ParsedEmail, EmailAttachment, AttachmentData,
+    AppendOutcome, DeliveryAuth, MessageStructure, MoveMethod, MoveOutcome, MoveReport, MoveStep, RawMessage, quotes::BodyStructure,
 };
+use crate::metrics::{self, FetchMethod};
 use async_imap::{Authenticator, Session};
-use futures::{pin_mut, StreamExt};
+use futures::StreamExt;
 use tokio_util::compat::TokioAsyncReadCompatExt;
 use mail_parser::MimeHeaders;
 
@@ -109,63 +114,311 @@ fn sanitize_folder_name(folder: &str) -> String {
         .replace('\0', "")
 }
 
-/// Decode MIME encoded header (RFC 2047)
+/// Flag as written in IMAP (`\Seen`, `\*`, `$label1`)
+fn async_flag_name(flag: &async_imap::types::Flag<'_>) -> String {
+    match flag {
+        async_imap::types::Flag::MayCreate => "\\*".to_string(),
+        async_imap::types::Flag::Custom(name) => name.to_string(),
+        system => format!("\\{:?}", system),
+    }
+}
+
+/// Flag as written in IMAP, for the OAuth (sync) session
+fn sync_flag_name(flag: &imap::types::Flag<'_>) -> String {
+    match flag {
+        imap::types::Flag::MayCreate => "\\*".to_string(),
+        imap::types::Flag::Custom(name) => name.to_string(),
+        system => format!("\\{:?}", system),
+    }
+}
+
+/// Flags worth carrying over to a copy: not `\Recent` (server-managed),
+/// `\Deleted` (about to be expunged) or `\*`
+fn copyable_flags(flags: impl Iterator<Item = String>) -> Vec<String> {
+    flags.filter(|flag| !matches!(flag.as_str(), "\\Recent" | "\\Deleted" | "\\*")).collect()
+}
+
+/// Decode MIME encoded header (RFC 2047), honouring the declared charset
 fn decode_mime_header(input: &str) -> String {
-    if !input.contains("=?") {
-        return input.to_string();
+    super::charset::decode_encoded_words(input)
+}
+
+/// Expunge one message flagged \Deleted in the selected mailbox
+/// UIDPLUS allows UID EXPUNGE; without it a plain EXPUNGE also removes other
+/// messages flagged \Deleted there. Sent as a raw command because the
+/// `uid_expunge`/`expunge` streams end at the tagged reply without checking it.
+async fn expunge_uid(session: &mut Session<SessionStream>, caps: &ImapCapabilities, uid_str: &str) -> MailResult<()> {
+    let command = if caps.uidplus { format!("UID EXPUNGE {}", uid_str) } else { "EXPUNGE".to_string() };
+    session
+        .run_command_and_check_ok(command)
+        .await
+        .map_err(|e| MailError::Imap(e.to_string()))
+}
+
+/// `expunge_uid` for the synchronous OAuth session
+fn sync_expunge_uid(
+    session: &mut imap::Session<native_tls::TlsStream<std::net::TcpStream>>,
+    caps: &ImapCapabilities,
+    uid_str: &str,
+) -> imap::error::Result<()> {
+    if caps.uidplus {
+        session.uid_expunge(uid_str)?;
+    } else {
+        session.expunge()?;
     }
+    Ok(())
+}
 
-    let mut result = input.to_string();
-
-    // Handle UTF-8 Base64 encoded strings =?charset?B?text?=
-    if let Ok(re_b64) = regex_lite::Regex::new(r"=\?([^?]+)\?[Bb]\?([^?]+)\?=") {
-        result = re_b64.replace_all(&result, |caps: &regex_lite::Captures| {
-            let encoded = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
-                .ok()
-                .and_then(|bytes| String::from_utf8(bytes).ok())
-                .unwrap_or_else(|| encoded.to_string())
-        }).to_string();
+/// Failure type of the synchronous OAuth session operations
+type SyncError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Log in with a password over a sync connection and send NAMESPACE
+fn read_namespaces(config: &ImapConfig) -> MailResult<Namespaces> {
+    fn query<T: std::io::Read + std::io::Write>(client: imap::Client<T>, config: &ImapConfig) -> MailResult<Namespaces> {
+        let mut session = client
+            .login(&config.username, &config.password)
+            .map_err(|(e, _client)| MailError::Authentication(e.to_string()))?;
+        let response = session
+            .run_command_and_read_response("NAMESPACE")
+            .map_err(|e| MailError::Imap(e.to_string()));
+        let _ = session.logout();
+        Ok(Namespaces::parse_response(&String::from_utf8_lossy(&response?)).unwrap_or_default())
     }
 
-    // Handle quoted-printable =?charset?Q?text?=
-    if let Ok(re_qp) = regex_lite::Regex::new(r"=\?([^?]+)\?[Qq]\?([^?]+)\?=") {
-        result = re_qp.replace_all(&result, |caps: &regex_lite::Captures| {
-            let encoded = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            decode_quoted_printable(encoded)
-        }).to_string();
+    let address = (config.host.as_str(), config.port);
+    let tls = || {
+        native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(config.accept_invalid_certs)
+            .build()
+            .map_err(|e| MailError::Connection(format!("TLS error: {}", e)))
+    };
+    match config.security {
+        SecurityType::SSL => query(
+            imap::connect(address, &config.host, &tls()?).map_err(|e| MailError::Connection(e.to_string()))?,
+            config,
+        ),
+        SecurityType::STARTTLS => query(
+            imap::connect_starttls(address, &config.host, &tls()?).map_err(|e| MailError::Connection(e.to_string()))?,
+            config,
+        ),
+        SecurityType::NONE => {
+            if !is_loopback_host(&config.host) {
+                return Err(MailError::Connection("Unencrypted connections are only allowed to localhost".to_string()));
+            }
+            let stream = std::net::TcpStream::connect(address).map_err(|e| MailError::Connection(e.to_string()))?;
+            let mut client = imap::Client::new(stream);
+            client.read_greeting().map_err(|e| MailError::Connection(e.to_string()))?;
+            query(client, config)
+        }
     }
+}
 
-    // Replace underscores with spaces (common in MIME headers)
-    result.replace("_", " ")
+/// Set or clear flags on a UID set in the selected mailbox
+/// Like `expunge_uid`, sent raw so a NO from the server is an error.
+async fn store_flags(session: &mut Session<SessionStream>, uid_set: &str, query: &str) -> MailResult<()> {
+    session
+        .run_command_and_check_ok(format!("UID STORE {} {}", uid_set, query))
+        .await
+        .map_err(|e| MailError::Imap(e.to_string()))
 }
 
-/// Decode quoted-printable string
-fn decode_quoted_printable(input: &str) -> String {
-    let mut result = Vec::new();
-    let mut chars = input.chars().peekable();
+/// Message-ID of a message in the selected mailbox
+async fn message_id_of(session: &mut Session<SessionStream>, uid_str: &str) -> MailResult<Option<String>> {
+    let mut messages_stream = session
+        .uid_fetch(uid_str, "(UID ENVELOPE)")
+        .await
+        .map_err(|e| MailError::Imap(e.to_string()))?;
+
+    let mut message_id = None;
+    while let Some(result) = messages_stream.next().await {
+        let message = result.map_err(|e| MailError::Imap(e.to_string()))?;
+        if message_id.is_none() {
+            message_id = message
+                .envelope()
+                .and_then(|envelope| envelope.message_id.as_ref())
+                .map(|id| String::from_utf8_lossy(id).to_string());
+        }
+    }
+    Ok(message_id)
+}
 
-    while let Some(c) = chars.next() {
-        if c == '=' {
-            let hex: String = chars.by_ref().take(2).collect();
-            if let Ok(byte) = u8::from_str_radix(&hex, 16) {
-                result.push(byte);
-            }
-        } else if c == '_' {
-            result.push(b' ');
-        } else {
-            result.push(c as u8);
+/// UID SEARCH for the copy of a message: its Message-ID among the UIDs the
+/// target assigned since `uid_next` was read. `None` when the copy cannot be
+/// told apart from other messages.
+fn copy_search_query(uid_next: Option<u32>, message_id: Option<&str>) -> Option<(u32, String)> {
+    let uid_next = uid_next?;
+    let message_id = message_id.filter(|id| !id.is_empty() && !id.contains(['"', '\\', '\r', '\n']))?;
+    Some((uid_next, format!("UID {}:* HEADER Message-ID \"{}\"", uid_next, message_id)))
+}
+
+/// COPY + \Deleted + EXPUNGE as one logical move, for servers without MOVE
+/// The source mailbox must be selected. Without UIDPLUS the original is left
+/// flagged \Deleted instead of expunged (`MoveOutcome::LeftFlagged`). When
+/// marking or expunging the original fails, the move is rolled back: the
+/// original loses its \Deleted flag and the copy is removed from the target
+/// (expunged with UIDPLUS, otherwise left flagged \Deleted). A failing COPY
+/// changes nothing and is returned as an error.
+async fn copy_delete_move(
+    session: &mut Session<SessionStream>,
+    caps: &ImapCapabilities,
+    uid_str: &str,
+    target: &str,
+) -> MailResult<MoveReport> {
+    let uid_next = session
+        .status(target, "(UIDNEXT)")
+        .await
+        .map_err(|e| MailError::Imap(e.to_string()))?
+        .uid_next;
+    let message_id = message_id_of(session, uid_str).await?;
+
+    session
+        .uid_copy(uid_str, target)
+        .await
+        .map_err(|e| MailError::Imap(e.to_string()))?;
+
+    let failed = match store_flags(session, uid_str, "+FLAGS (\\Deleted)").await {
+        Err(e) => Some((MoveStep::MarkDeleted, e)),
+        Ok(()) if !caps.uidplus => return Ok(left_flagged(target)),
+        Ok(()) => expunge_uid(session, caps, uid_str).await.err().map(|e| (MoveStep::Expunge, e)),
+    };
+    let Some((failed_step, error)) = failed else {
+        return Ok(MoveReport::moved(MoveMethod::CopyDelete));
+    };
+    log::warn!("Move to {} failed at {:?} ({}), rolling back", target, failed_step, error);
+
+    let outcome = match undo_copy(session, caps, uid_str, target, copy_search_query(uid_next, message_id.as_deref())).await {
+        Ok(()) => MoveOutcome::RolledBack { failed_step, error: error.to_string() },
+        Err(rollback_error) => {
+            log::error!("Rolling back move to {} failed: {}", target, rollback_error);
+            MoveOutcome::Incomplete { failed_step, error: error.to_string(), rollback_error: rollback_error.to_string() }
         }
+    };
+    Ok(MoveReport { method: MoveMethod::CopyDelete, outcome })
+}
+
+/// Report of a copy-based move on a server without UIDPLUS
+fn left_flagged(target: &str) -> MoveReport {
+    log::warn!(
+        "Server lacks UIDPLUS: moved to {} but left the original flagged \\Deleted rather than expunging the whole folder",
+        target
+    );
+    MoveReport { method: MoveMethod::CopyDelete, outcome: MoveOutcome::LeftFlagged }
+}
+
+/// Undo the COPY of `copy_delete_move` (leaves the target selected)
+async fn undo_copy(
+    session: &mut Session<SessionStream>,
+    caps: &ImapCapabilities,
+    uid_str: &str,
+    target: &str,
+    copy_query: Option<(u32, String)>,
+) -> MailResult<()> {
+    store_flags(session, uid_str, "-FLAGS (\\Deleted)").await?;
+
+    let (uid_next, query) =
+        copy_query.ok_or_else(|| MailError::Imap(format!("The copy in {} could not be identified", target)))?;
+    session
+        .select(target)
+        .await
+        .map_err(|e| MailError::Imap(e.to_string()))?;
+    // `n:*` also matches the highest UID when it is below n
+    let copies: Vec<String> = session
+        .uid_search(&query)
+        .await
+        .map_err(|e| MailError::Imap(e.to_string()))?
+        .into_iter()
+        .filter(|uid| *uid >= uid_next)
+        .map(|uid| uid.to_string())
+        .collect();
+    if copies.is_empty() {
+        return Ok(());
     }
 
-    String::from_utf8(result).unwrap_or_else(|_| input.to_string())
+    let set = copies.join(",");
+    store_flags(session, &set, "+FLAGS (\\Deleted)").await?;
+    if caps.uidplus {
+        expunge_uid(session, caps, &set).await?;
+    }
+    Ok(())
 }
 
-type TlsStream = async_native_tls::TlsStream<tokio_util::compat::Compat<tokio::net::TcpStream>>;
+/// `copy_delete_move` for the synchronous OAuth session
+fn sync_copy_delete_move(
+    session: &mut imap::Session<native_tls::TlsStream<std::net::TcpStream>>,
+    caps: &ImapCapabilities,
+    uid_str: &str,
+    target: &str,
+) -> Result<MoveReport, SyncError> {
+    let uid_next = session.status(target, "(UIDNEXT)")?.uid_next;
+    let message_id = session
+        .uid_fetch(uid_str, "(UID ENVELOPE)")?
+        .iter()
+        .next()
+        .and_then(|message| message.envelope().and_then(|envelope| envelope.message_id.as_ref()))
+        .map(|id| String::from_utf8_lossy(id).to_string());
+
+    session.uid_copy(uid_str, target)?;
+
+    let failed = match session.uid_store(uid_str, "+FLAGS (\\Deleted)") {
+        Err(e) => Some((MoveStep::MarkDeleted, e)),
+        Ok(_) if !caps.uidplus => return Ok(left_flagged(target)),
+        Ok(_) => sync_expunge_uid(session, caps, uid_str).err().map(|e| (MoveStep::Expunge, e)),
+    };
+    let Some((failed_step, error)) = failed else {
+        return Ok(MoveReport::moved(MoveMethod::CopyDelete));
+    };
+    log::warn!("Move to {} failed at {:?} ({}), rolling back", target, failed_step, error);
+
+    let outcome = match sync_undo_copy(session, caps, uid_str, target, copy_search_query(uid_next, message_id.as_deref())) {
+        Ok(()) => MoveOutcome::RolledBack { failed_step, error: error.to_string() },
+        Err(rollback_error) => {
+            log::error!("Rolling back move to {} failed: {}", target, rollback_error);
+            MoveOutcome::Incomplete { failed_step, error: error.to_string(), rollback_error: rollback_error.to_string() }
+        }
+    };
+    Ok(MoveReport { method: MoveMethod::CopyDelete, outcome })
+}
+
+/// `undo_copy` for the synchronous OAuth session
+fn sync_undo_copy(
+    session: &mut imap::Session<native_tls::TlsStream<std::net::TcpStream>>,
+    caps: &ImapCapabilities,
+    uid_str: &str,
+    target: &str,
+    copy_query: Option<(u32, String)>,
+) -> Result<(), SyncError> {
+    session.uid_store(uid_str, "-FLAGS (\\Deleted)")?;
+
+    let (uid_next, query) = copy_query.ok_or_else(|| format!("The copy in {} could not be identified", target))?;
+    session.select(target)?;
+    let copies: Vec<String> = session
+        .uid_search(&query)?
+        .into_iter()
+        .filter(|uid| *uid >= uid_next)
+        .map(|uid| uid.to_string())
+        .collect();
+    if copies.is_empty() {
+        return Ok(());
+    }
+
+    let set = copies.join(",");
+    session.uid_store(&set, "+FLAGS (\\Deleted)")?;
+    if caps.uidplus {
+        sync_expunge_uid(session, caps, &set)?;
+    }
+    Ok(())
+}
+
+/// Transport under an async session: TLS, or plain TCP to a loopback server
+trait ImapStream: futures::io::AsyncRead + futures::io::AsyncWrite + Unpin + Send + Sync + std::fmt::Debug {}
+
+impl<T> ImapStream for T where T: futures::io::AsyncRead + futures::io::AsyncWrite + Unpin + Send + Sync + std::fmt::Debug {}
+
+type SessionStream = Box<dyn ImapStream>;
 
 /// Session type enum - supports both async and sync sessions
 enum ImapSession {
-    Async(Session<TlsStream>),
+    Async(Session<SessionStream>),
     OAuth(()),  // OAuth uses fresh connections for each operation
 }
 
@@ -173,6 +426,12 @@ enum ImapSession {
 pub struct AsyncImapClient {
     session: Option<ImapSession>,
     config: ImapConfig,
+    /// Trash mailbox announced by SPECIAL-USE (or known from the folder cache)
+    trash_folder: Option<String>,
+    /// Extensions announced after login (`None` until connected)
+    capabilities: Option<ImapCapabilities>,
+    /// NAMESPACE reply, once queried
+    namespaces: Option<Namespaces>,
 }
 
 impl AsyncImapClient {
@@ -181,11 +440,35 @@ impl AsyncImapClient {
         Self {
             session: None,
             config,
+            trash_folder: None,
+            capabilities: None,
+            namespaces: None,
         }
     }
 
+    /// Extensions the server announced after login
+    pub fn capabilities(&self) -> Option<&ImapCapabilities> {
+        self.capabilities.as_ref()
+    }
+
+    /// Use a Trash mailbox known from the folder cache until the next LIST
+    pub fn set_trash_folder(&mut self, folder: Option<String>) {
+        self.trash_folder = folder;
+    }
+
+    /// Trash candidates: the special-use mailbox first, then common names
+    fn trash_candidates(&self) -> Vec<String> {
+        let mut candidates: Vec<String> = self.trash_folder.iter().cloned().collect();
+        for name in ["Trash", "[Gmail]/Trash", "Deleted Items", "Deleted"] {
+            if !candidates.iter().any(|c| c == name) {
+                candidates.push(name.to_string());
+            }
+        }
+        candidates
+    }
+
     /// Helper: Get async session or return error for OAuth (not implemented yet)
-    fn get_async_session(&mut self) -> MailResult<&mut Session<TlsStream>> {
+    fn get_async_session(&mut self) -> MailResult<&mut Session<SessionStream>> {
         let session = self.session.as_mut().ok_or(MailError::NotConnected)?;
 
         match session {
@@ -275,7 +558,7 @@ impl AsyncImapClient {
                     .await
                     .map_err(|e| MailError::Connection(e.to_string()))?;
 
-                let client = async_imap::Client::new(tls_stream);
+                let client = async_imap::Client::new(Box::new(tls_stream) as SessionStream);
 
                 // Use OAuth2 XOAUTH2 authentication for OAuth accounts
                 if self.config.oauth_provider.is_some() {
@@ -288,7 +571,7 @@ impl AsyncImapClient {
                     let access_token = self.config.password.clone();
                     let accept_invalid_certs = self.config.accept_invalid_certs;
 
-                    tokio::task::spawn_blocking(move || {
+                    self.capabilities = tokio::task::spawn_blocking(move || {
                         log::info!("OAuth2: Connecting to {}:993...", host);
 
                         // Create TLS connector
@@ -327,10 +610,19 @@ impl AsyncImapClient {
 
                         log::info!("✓ INBOX selected successfully");
 
+                        // Raw response: the typed capability list does not expose APPENDLIMIT=n
+                        let capabilities = match session.run_command_and_read_response("CAPABILITY") {
+                            Ok(response) => Some(ImapCapabilities::parse_response(&String::from_utf8_lossy(&response))),
+                            Err(e) => {
+                                log::warn!("OAuth2: CAPABILITY failed: {}", e);
+                                None
+                            }
+                        };
+
                         // Logout from sync session
                         let _ = session.logout();
 
-                        Ok::<(), MailError>(())
+                        Ok::<_, MailError>(capabilities)
                     })
                     .await
                     .map_err(|e| MailError::Connection(format!("Spawn blocking error: {}", e)))??;
@@ -363,7 +655,7 @@ impl AsyncImapClient {
                     .await
                     .map_err(|e| MailError::Connection(e.to_string()))?;
 
-                let client = async_imap::Client::new(tls_stream);
+                let client = async_imap::Client::new(Box::new(tls_stream) as SessionStream);
 
                 // Use OAuth2 XOAUTH2 authentication for OAuth accounts
                 if self.config.oauth_provider.is_some() {
@@ -376,7 +668,7 @@ impl AsyncImapClient {
                     let access_token = self.config.password.clone();
                     let accept_invalid_certs = self.config.accept_invalid_certs;
 
-                    tokio::task::spawn_blocking(move || {
+                    self.capabilities = tokio::task::spawn_blocking(move || {
                         log::info!("OAuth2: Connecting to {}:993...", host);
 
                         // Create TLS connector
@@ -415,10 +707,19 @@ impl AsyncImapClient {
 
                         log::info!("✓ INBOX selected successfully");
 
+                        // Raw response: the typed capability list does not expose APPENDLIMIT=n
+                        let capabilities = match session.run_command_and_read_response("CAPABILITY") {
+                            Ok(response) => Some(ImapCapabilities::parse_response(&String::from_utf8_lossy(&response))),
+                            Err(e) => {
+                                log::warn!("OAuth2: CAPABILITY failed: {}", e);
+                                None
+                            }
+                        };
+
                         // Logout from sync session
                         let _ = session.logout();
 
-                        Ok::<(), MailError>(())
+                        Ok::<_, MailError>(capabilities)
                     })
                     .await
                     .map_err(|e| MailError::Connection(format!("Spawn blocking error: {}", e)))??;
@@ -438,30 +739,39 @@ impl AsyncImapClient {
                 }
             }
             SecurityType::NONE => {
-                // SECURITY WARNING: Unencrypted connection
-                log::warn!("⚠️  CRITICAL SECURITY WARNING: Connecting without encryption!");
-                log::warn!("⚠️  Credentials will be sent in PLAIN TEXT over the network!");
+                // SECURITY: Plain connections only to this machine (local bridges, test servers)
+                if !is_loopback_host(&self.config.host) {
+                    return Err(MailError::Connection(
+                        "Unencrypted connections are only allowed to localhost. Please use SSL/TLS or STARTTLS. If your server has a self-signed certificate, the app now accepts those automatically.".to_string(),
+                    ));
+                }
+                log::warn!("⚠️  Connecting to {} without encryption", self.config.host);
 
-                // Try to connect without TLS (plain TCP)
-                // Note: Most modern email servers don't support this
                 let stream = tokio::net::TcpStream::connect(&address)
                     .await
-                    .map_err(|e| MailError::Connection(format!("Plain connection failed: {}. Most email servers require SSL/TLS encryption. Try using SSL (port 993) or STARTTLS (port 143) instead.", e)))?;
+                    .map_err(|e| MailError::Connection(format!("Plain connection failed: {}", e)))?;
 
-                let compat_stream = stream.compat();
-                let client = async_imap::Client::new(compat_stream);
+                let client = async_imap::Client::new(Box::new(stream.compat()) as SessionStream);
 
-                // Try to login without encryption
                 let session = client
                     .login(&self.config.username, &self.config.password)
                     .await
-                    .map_err(|e| MailError::Authentication(format!("Authentication failed on unencrypted connection: {}. Server may not support plain text login.", e.0)))?;
+                    .map_err(|e| MailError::Authentication(e.0.to_string()))?;
+
+                self.session = Some(ImapSession::Async(session));
+            }
+        }
 
-                // Store session (this won't compile as-is, need to handle different types)
-                // For now, return error with suggestion
-                return Err(MailError::Connection(
-                    "Unencrypted connections are not fully supported yet. Please use SSL/TLS or STARTTLS. If your server has a self-signed certificate, the app now accepts those automatically.".to_string(),
-                ));
+        if let Some(ImapSession::Async(session)) = self.session.as_mut() {
+            match session.capabilities().await {
+                Ok(caps) => {
+                    let names = caps.iter().filter_map(|cap| match cap {
+                        async_imap::types::Capability::Atom(atom) => Some(&**atom),
+                        _ => None,
+                    });
+                    self.capabilities = Some(ImapCapabilities::parse(names));
+                }
+                Err(e) => log::warn!("CAPABILITY failed for {}: {}", self.config.host, e),
             }
         }
 
@@ -487,14 +797,158 @@ impl AsyncImapClient {
         Ok(())
     }
 
+    /// Keep the connection alive
+    pub async fn noop(&mut self) -> MailResult<()> {
+        let session = self.get_async_session()?;
+        session.noop().await.map_err(|e| MailError::Imap(e.to_string()))
+    }
+
+    /// Open a folder read-only; returns its message count and UIDNEXT
+    pub async fn examine_folder(&mut self, folder: &str) -> MailResult<(u32, Option<u32>)> {
+        let safe_folder = sanitize_folder_name(folder);
+        let session = self.get_async_session()?;
+        let mailbox = session
+            .examine(&safe_folder)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+        Ok((mailbox.exists, mailbox.uid_next))
+    }
+
+    /// IDLE in the open folder for up to `timeout`
+    /// Returns true when the server reported a change. The connection is
+    /// dropped if IDLE fails half-way.
+    pub async fn idle_wait(&mut self, timeout: std::time::Duration) -> MailResult<bool> {
+        use async_imap::extensions::idle::IdleResponse;
+
+        self.get_async_session()?;
+        let Some(ImapSession::Async(session)) = self.session.take() else {
+            return Err(MailError::NotConnected);
+        };
+
+        let mut handle = session.idle();
+        handle.init().await.map_err(|e| MailError::Imap(e.to_string()))?;
+        let (wait, _stop) = handle.wait_with_timeout(timeout);
+        let response = wait.await.map_err(|e| MailError::Connection(e.to_string()))?;
+        let session = handle.done().await.map_err(|e| MailError::Imap(e.to_string()))?;
+        self.session = Some(ImapSession::Async(session));
+
+        Ok(matches!(response, IdleResponse::NewData(_)))
+    }
+
+    /// Send `NOTIFY SET ...` (RFC 5465); events are then read with `wait_notify`
+    pub async fn notify_set(&mut self, command: &str) -> MailResult<()> {
+        let session = self.get_async_session()?;
+        session
+            .run_command_and_check_ok(command)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))
+    }
+
+    /// Mailboxes reported changed by NOTIFY within `timeout` (empty when none)
+    /// Events arriving together are collected into one result.
+    pub async fn wait_notify(&mut self, timeout: std::time::Duration) -> MailResult<Vec<String>> {
+        use async_imap::imap_proto::{MailboxDatum, Response, Status};
+
+        let session = self.get_async_session()?;
+        let mut changed: Vec<String> = Vec::new();
+        let mut wait = timeout;
+        loop {
+            let response = match tokio::time::timeout(wait, session.read_response()).await {
+                Err(_) => return Ok(changed),
+                Ok(Ok(Some(response))) => response,
+                Ok(Ok(None)) => return Err(MailError::Connection("Server closed the connection".to_string())),
+                Ok(Err(e)) => return Err(MailError::Connection(e.to_string())),
+            };
+            match response.parsed() {
+                Response::MailboxData(MailboxDatum::Status { mailbox, .. })
+                    if !changed.iter().any(|m| m == mailbox.as_ref()) =>
+                {
+                    changed.push(mailbox.to_string());
+                }
+                Response::Data { status: Status::Bye, information, .. } => {
+                    return Err(MailError::Connection(
+                        information.as_deref().unwrap_or("Server closed the connection").to_string(),
+                    ));
+                }
+                _ => {}
+            }
+            if !changed.is_empty() {
+                wait = std::time::Duration::from_millis(500);
+            }
+        }
+    }
+
     /// List folders
     pub async fn list_folders(&mut self) -> MailResult<Vec<Folder>> {
+        let namespaces = self.namespaces().await.unwrap_or_else(|e| {
+            log::warn!("NAMESPACE failed for {}: {}", self.config.host, e);
+            Namespaces::default()
+        });
+
+        // Shared and other users' folders are listed separately
+        let mut folders = self.list_matching("*").await?;
+        folders.retain(|folder| namespaces.kind_of(&folder.path) == NamespaceKind::Personal);
+
+        special_use::classify_folders(&mut folders);
+        self.trash_folder = special_use::find_folder(&folders, &FolderType::Trash);
+        Ok(folders)
+    }
+
+    /// Folders in the server's shared and other users' namespaces
+    /// They are never classified as the account's own special folders.
+    pub async fn list_shared_folders(&mut self) -> MailResult<(Namespaces, Vec<Folder>)> {
+        let namespaces = self.namespaces().await?;
+        let prefixes: Vec<String> = namespaces.foreign().map(|(_, namespace)| namespace.prefix.clone()).collect();
+
+        let mut folders: Vec<Folder> = Vec::new();
+        for prefix in prefixes {
+            for mut folder in self.list_matching(&format!("{}*", prefix)).await? {
+                if folders.iter().all(|known| known.path != folder.path) {
+                    folder.folder_type = FolderType::Custom;
+                    folders.push(folder);
+                }
+            }
+        }
+        Ok((namespaces, folders))
+    }
+
+    /// Personal, other users' and shared namespaces (RFC 2342), queried once
+    /// per client. async-imap cannot parse a NAMESPACE response, so for
+    /// password logins it is read over a short-lived sync connection.
+    pub async fn namespaces(&mut self) -> MailResult<Namespaces> {
+        if let Some(namespaces) = &self.namespaces {
+            return Ok(namespaces.clone());
+        }
+        if !self.capabilities.as_ref().is_some_and(|caps| caps.namespace) {
+            return Ok(Namespaces::default());
+        }
+
+        let namespaces = if let Some(ImapSession::OAuth(_)) = &self.session {
+            self.with_oauth_session(|session| {
+                let response = session.run_command_and_read_response("NAMESPACE")?;
+                Ok(Namespaces::parse_response(&String::from_utf8_lossy(&response)).unwrap_or_default())
+            }).await?
+        } else {
+            self.get_async_session()?;
+            let config = self.config.clone();
+            tokio::task::spawn_blocking(move || read_namespaces(&config))
+                .await
+                .map_err(|e| MailError::Connection(format!("Spawn blocking error: {}", e)))??
+        };
+
+        self.namespaces = Some(namespaces.clone());
+        Ok(namespaces)
+    }
+
+    /// LIST mailboxes matching a pattern (unclassified)
+    async fn list_matching(&mut self, pattern: &str) -> MailResult<Vec<Folder>> {
         // Check if OAuth session
         if let Some(ImapSession::OAuth(_)) = &self.session {
             log::info!("OAuth list_folders: using sync session");
 
+            let pattern = pattern.to_string();
             return self.with_oauth_session(move |session| {
-                let mailboxes = session.list(Some(""), Some("*"))?;
+                let mailboxes = session.list(Some(""), Some(&pattern))?;
 
                 let mut folders = Vec::new();
                 for mb in mailboxes.iter() {
@@ -502,6 +956,10 @@ impl AsyncImapClient {
                     let delimiter = mb.delimiter()
                         .map(|d| d.to_string())
                         .unwrap_or("/".to_string());
+                    let attributes: Vec<String> = mb.attributes()
+                        .iter()
+                        .map(special_use::imap_attribute)
+                        .collect();
 
                     folders.push(Folder {
                         name: name.split(&delimiter).last().unwrap_or(&name).to_string(),
@@ -509,9 +967,10 @@ impl AsyncImapClient {
                         folder_type: FolderType::from_name(&name),
                         delimiter,
                         is_subscribed: true,
-                        is_selectable: true,
+                        is_selectable: !special_use::is_noselect(&attributes),
                         unread_count: 0,
                         total_count: 0,
+                        attributes,
                     });
                 }
 
@@ -524,7 +983,7 @@ impl AsyncImapClient {
         let session = self.get_async_session()?;
 
         let mut mailboxes_stream = session
-            .list(Some(""), Some("*"))
+            .list(Some(""), Some(pattern))
             .await
             .map_err(|e| MailError::Imap(e.to_string()))?;
 
@@ -535,6 +994,10 @@ impl AsyncImapClient {
             let delimiter = mb.delimiter()
                 .map(|d: &str| d.to_string())
                 .unwrap_or("/".to_string());
+            let attributes: Vec<String> = mb.attributes()
+                .iter()
+                .map(special_use::async_imap_attribute)
+                .collect();
 
             folders.push(Folder {
                 name: name.split(&delimiter).last().unwrap_or(&name).to_string(),
@@ -542,11 +1005,13 @@ impl AsyncImapClient {
                 folder_type: FolderType::from_name(&name),
                 delimiter,
                 is_subscribed: true,
-                is_selectable: true,
+                is_selectable: !special_use::is_noselect(&attributes),
                 unread_count: 0,
                 total_count: 0,
+                attributes,
             });
         }
+        drop(mailboxes_stream);
 
         Ok(folders)
     }
@@ -630,7 +1095,7 @@ impl AsyncImapClient {
                 log::info!("OAuth: Fetching range: {}", range);
 
                 // Fetch emails
-                let messages = session.fetch(&range, "(UID FLAGS ENVELOPE)")?;
+                let messages = session.fetch(&range, "(UID FLAGS RFC822.SIZE ENVELOPE)")?;
 
                 // Collect messages
                 let mut emails: Vec<EmailSummary> = Vec::new();
@@ -641,6 +1106,10 @@ impl AsyncImapClient {
 
                     let is_read = flags.iter().any(|f| matches!(f, imap::types::Flag::Seen));
                     let is_starred = flags.iter().any(|f| matches!(f, imap::types::Flag::Flagged));
+                    let keywords = keywords::from_flags(flags.iter().filter_map(|f| match f {
+                        imap::types::Flag::Custom(keyword) => Some(keyword.as_ref()),
+                        _ => None,
+                    }));
 
                     if let Some(envelope) = message.envelope() {
                         let from = envelope
@@ -699,6 +1168,8 @@ impl AsyncImapClient {
                             is_read,
                             is_starred,
                             has_attachments: false,
+                            size: message.size.unwrap_or(0),
+                            keywords,
                             account_id: None, // Will be set by fetch_emails_with_account_metadata
                             account_email: None,
                             account_name: None,
@@ -763,7 +1234,7 @@ impl AsyncImapClient {
 
         // Fetch emails - returns a Stream
         let mut messages_stream = session
-            .fetch(&range, "(UID FLAGS ENVELOPE)")
+            .fetch(&range, "(UID FLAGS RFC822.SIZE ENVELOPE)")
             .await
             .map_err(|e| MailError::Imap(e.to_string()))?;
 
@@ -781,6 +1252,10 @@ impl AsyncImapClient {
             let flags_vec: Vec<_> = flags.collect();
             let is_read = flags_vec.iter().any(|f| matches!(f, async_imap::types::Flag::Seen));
             let is_starred = flags_vec.iter().any(|f| matches!(f, async_imap::types::Flag::Flagged));
+            let keywords = keywords::from_flags(flags_vec.iter().filter_map(|f| match f {
+                async_imap::types::Flag::Custom(keyword) => Some(keyword.as_ref()),
+                _ => None,
+            }));
 
             if let Some(envelope) = message.envelope() {
                 let from = envelope
@@ -839,6 +1314,8 @@ impl AsyncImapClient {
                     is_read,
                     is_starred,
                     has_attachments: false,
+                    size: message.size.unwrap_or(0),
+                    keywords,
                     account_id: None,
                     account_email: None,
                     account_name: None,
@@ -905,9 +1382,19 @@ impl AsyncImapClient {
         ).await
     }
 
-    /// Fetch a single email with full content
-    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    /// Fetch a single email with full content (the server marks it read)
     pub async fn fetch_email(&mut self, folder: &str, uid: u32) -> MailResult<ParsedEmail> {
+        self.fetch_parsed(folder, uid, "(UID FLAGS ENVELOPE RFC822)").await
+    }
+
+    /// Fetch a single email with full content without marking it read
+    pub async fn peek_email(&mut self, folder: &str, uid: u32) -> MailResult<ParsedEmail> {
+        self.fetch_parsed(folder, uid, "(UID FLAGS ENVELOPE BODY.PEEK[])").await
+    }
+
+    /// Fetch and parse one message with the given FETCH items
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    async fn fetch_parsed(&mut self, folder: &str, uid: u32, items: &'static str) -> MailResult<ParsedEmail> {
         // SECURITY: Sanitize folder name
         let safe_folder = sanitize_folder_name(folder);
 
@@ -927,7 +1414,7 @@ impl AsyncImapClient {
                 // Fetch the email with body
                 let uid_str = uid.to_string();
                 log::info!("OAuth fetch_email: fetching UID {}...", uid);
-                let messages = session.uid_fetch(&uid_str, "(UID FLAGS ENVELOPE RFC822)")?;
+                let messages = session.uid_fetch(&uid_str, items)?;
                 log::info!("OAuth fetch_email: got {} messages", messages.len());
 
                 if let Some(message) = messages.iter().next() {
@@ -1003,6 +1490,12 @@ impl AsyncImapClient {
                     log::info!("OAuth fetch_email: parsing body...");
                     let body = message.body();
                     log::info!("OAuth fetch_email: body present={}", body.is_some());
+                    let delivery = body.map(DeliveryAuth::from_raw).unwrap_or_default();
+                    let structure = body.and_then(MessageStructure::from_raw);
+                    let size = body.map_or(0, |raw| raw.len() as u32);
+                    let headers = body.map(headers::parse).unwrap_or_default();
+                    let calendar = body.and_then(calendar::from_raw);
+                    let bounced = body.map(bounce::from_raw).unwrap_or_default();
                     let (body_text, body_html, attachments) = if let Some(body_bytes) = body {
                         log::info!("OAuth fetch_email: body size={} bytes", body_bytes.len());
                         parse_email_body(body_bytes)
@@ -1014,6 +1507,7 @@ impl AsyncImapClient {
                     log::debug!("OAuth Email fetched: uid={}, body_text_len={:?}, body_html_len={:?}, attachments_count={}",
                         uid, body_text.as_ref().map(|s: &String| s.len()), body_html.as_ref().map(|s: &String| s.len()), attachments.len());
 
+                    let quotes = BodyStructure::analyze(body_text.as_deref(), body_html.as_deref());
                     return Ok(ParsedEmail {
                         uid,
                         message_id,
@@ -1028,6 +1522,14 @@ impl AsyncImapClient {
                         is_read,
                         is_starred,
                         attachments,
+                        delivery,
+                        quotes,
+                        structure,
+                        policy: None,
+                        size,
+                        headers,
+                        calendar,
+                        bounced,
                     });
                 }
 
@@ -1056,7 +1558,7 @@ impl AsyncImapClient {
         let uid_str = uid.to_string();
         log::info!("fetch_email: fetching UID {}...", uid);
         let mut messages_stream = session
-            .uid_fetch(&uid_str, "(UID FLAGS ENVELOPE RFC822)")
+            .uid_fetch(&uid_str, items)
             .await
             .map_err(|e| {
                 log::error!("fetch_email: uid_fetch failed: {}", e);
@@ -1145,6 +1647,12 @@ impl AsyncImapClient {
             log::info!("fetch_email: parsing body...");
             let body = message.body();
             log::info!("fetch_email: body present={}", body.is_some());
+            let delivery = body.map(DeliveryAuth::from_raw).unwrap_or_default();
+            let structure = body.and_then(MessageStructure::from_raw);
+            let size = body.map_or(0, |raw| raw.len() as u32);
+            let headers = body.map(headers::parse).unwrap_or_default();
+            let calendar = body.and_then(calendar::from_raw);
+            let bounced = body.map(bounce::from_raw).unwrap_or_default();
             let (body_text, body_html, attachments) = if let Some(body_bytes) = body {
                 log::info!("fetch_email: body size={} bytes", body_bytes.len());
                 parse_email_body(body_bytes)
@@ -1157,6 +1665,7 @@ impl AsyncImapClient {
             log::debug!("Email fetched: uid={}, body_text_len={:?}, body_html_len={:?}, attachments_count={}",
                 uid, body_text.as_ref().map(|s: &String| s.len()), body_html.as_ref().map(|s: &String| s.len()), attachments.len());
 
+            let quotes = BodyStructure::analyze(body_text.as_deref(), body_html.as_deref());
             return Ok(ParsedEmail {
                 uid,
                 message_id,
@@ -1171,6 +1680,14 @@ impl AsyncImapClient {
                 is_read,
                 is_starred,
                 attachments,
+                delivery,
+                quotes,
+                structure,
+                policy: None,
+                size,
+                headers,
+                calendar,
+                bounced,
             });
         }
 
@@ -1288,6 +1805,109 @@ impl AsyncImapClient {
         Ok(uids_set.into_iter().collect())
     }
 
+    /// Search for emails received on or after `since` (all emails when None)
+    /// Used for staged historical backfill
+    pub async fn search_since(&mut self, folder: &str, since: Option<chrono::NaiveDate>) -> MailResult<Vec<u32>> {
+        let Some(since) = since else {
+            return self.search_all(folder).await;
+        };
+
+        let safe_folder = sanitize_folder_name(folder);
+        // IMAP date format (RFC 3501): 1-Jan-2024
+        let query = format!("SINCE {}", since.format("%-d-%b-%Y"));
+
+        // OAuth session check (use sync imap)
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            let folder_clone = safe_folder.clone();
+            return self.with_oauth_session(move |session| {
+                session.select(&folder_clone)?;
+                let uids = session.uid_search(&query)?;
+                Ok(uids.into_iter().collect())
+            }).await;
+        }
+
+        // Regular async session
+        let session = self.get_async_session()?;
+        session.select(&safe_folder).await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        let uids_set = session.uid_search(&query).await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        Ok(uids_set.into_iter().collect())
+    }
+
+    /// Current UIDVALIDITY of `folder` (None when the server does not report it)
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    pub async fn uid_validity(&mut self, folder: &str) -> MailResult<Option<u32>> {
+        let safe_folder = sanitize_folder_name(folder);
+
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            return self.with_oauth_session(move |session| {
+                Ok(session.status(&safe_folder, "(UIDVALIDITY)")?.uid_validity)
+            }).await;
+        }
+
+        let session = self.get_async_session()?;
+        let mailbox = session.status(&safe_folder, "(UIDVALIDITY)").await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        Ok(mailbox.uid_validity)
+    }
+
+    /// Counters of `folder` used to tell whether a listed page is still current
+    /// None without CONDSTORE, where flag changes would go unnoticed, and for
+    /// OAuth sessions, whose STATUS reply does not carry HIGHESTMODSEQ.
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    pub async fn folder_state(&mut self, folder: &str) -> MailResult<Option<FolderState>> {
+        if !self.capabilities().is_some_and(|caps| caps.condstore) {
+            return Ok(None);
+        }
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            return Ok(None);
+        }
+
+        let safe_folder = sanitize_folder_name(folder);
+        let session = self.get_async_session()?;
+        let mailbox = session.status(&safe_folder, "(MESSAGES UIDNEXT UIDVALIDITY HIGHESTMODSEQ)").await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        Ok(mailbox.highest_modseq.map(|highest_modseq| FolderState {
+            uid_validity: mailbox.uid_validity,
+            uid_next: mailbox.uid_next,
+            messages: mailbox.exists,
+            highest_modseq,
+        }))
+    }
+
+    /// UID of the newest message in `folder` with the given Message-ID
+    /// `None` when there is none or the Message-ID cannot be searched safely.
+    /// SECURITY: Folder name sanitized; Message-IDs with quotes or line breaks are refused
+    pub async fn find_by_message_id(&mut self, folder: &str, message_id: &str) -> MailResult<Option<u32>> {
+        if message_id.is_empty() || message_id.contains(['"', '\\', '\r', '\n']) {
+            return Ok(None);
+        }
+        let safe_folder = sanitize_folder_name(folder);
+        let query = format!("HEADER Message-ID \"{}\"", message_id);
+
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            return self.with_oauth_session(move |session| {
+                session.select(&safe_folder)?;
+                let uids = session.uid_search(&query)?;
+                Ok(uids.into_iter().max())
+            }).await;
+        }
+
+        let session = self.get_async_session()?;
+        session.select(&safe_folder).await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        let uids_set = session.uid_search(&query).await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        Ok(uids_set.into_iter().max())
+    }
+
     /// Fetch emails with priority (unread first)
     /// Returns error if SEARCH commands fail, fallback to sequence-based fetch
     pub async fn fetch_emails_with_priority(
@@ -1354,8 +1974,8 @@ impl AsyncImapClient {
     }
 
     /// Fetch specific emails by UID list
-    /// Helper for priority fetching
-    async fn fetch_emails_by_uids(
+    /// Used by priority fetching and historical backfill
+    pub async fn fetch_emails_by_uids(
         &mut self,
         folder: &str,
         uids: &[u32],
@@ -1382,7 +2002,7 @@ impl AsyncImapClient {
             return self.with_oauth_session(move |session| {
                 session.select(&folder_clone)?;
 
-                let messages = session.uid_fetch(&uid_list_clone, "(UID FLAGS ENVELOPE)")?;
+                let messages = session.uid_fetch(&uid_list_clone, "(UID FLAGS RFC822.SIZE ENVELOPE)")?;
 
                 let mut emails: Vec<EmailSummary> = Vec::new();
 
@@ -1392,6 +2012,10 @@ impl AsyncImapClient {
 
                     let is_read = flags.iter().any(|f| matches!(f, imap::types::Flag::Seen));
                     let is_starred = flags.iter().any(|f| matches!(f, imap::types::Flag::Flagged));
+                    let keywords = keywords::from_flags(flags.iter().filter_map(|f| match f {
+                        imap::types::Flag::Custom(keyword) => Some(keyword.as_ref()),
+                        _ => None,
+                    }));
 
                     if let Some(envelope) = message.envelope() {
                         let from = envelope
@@ -1450,6 +2074,8 @@ impl AsyncImapClient {
                             is_read,
                             is_starred,
                             has_attachments: false,
+                            size: message.size.unwrap_or(0),
+                            keywords,
                             account_id: None, // Will be set by fetch_emails_with_account_metadata
                             account_email: None,
                             account_name: None,
@@ -1468,7 +2094,7 @@ impl AsyncImapClient {
             .map_err(|e| MailError::Imap(e.to_string()))?;
 
         let mut messages_stream = session
-            .uid_fetch(&uid_list, "(UID FLAGS ENVELOPE)")
+            .uid_fetch(&uid_list, "(UID FLAGS RFC822.SIZE ENVELOPE)")
             .await
             .map_err(|e| MailError::Imap(e.to_string()))?;
 
@@ -1483,6 +2109,10 @@ impl AsyncImapClient {
             let flags_vec: Vec<_> = flags.collect();
             let is_read = flags_vec.iter().any(|f| matches!(f, async_imap::types::Flag::Seen));
             let is_starred = flags_vec.iter().any(|f| matches!(f, async_imap::types::Flag::Flagged));
+            let keywords = keywords::from_flags(flags_vec.iter().filter_map(|f| match f {
+                async_imap::types::Flag::Custom(keyword) => Some(keyword.as_ref()),
+                _ => None,
+            }));
 
             if let Some(envelope) = message.envelope() {
                 let from = envelope
@@ -1541,6 +2171,8 @@ impl AsyncImapClient {
                     is_read,
                     is_starred,
                     has_attachments: false,
+                    size: message.size.unwrap_or(0),
+                    keywords,
                     account_id: None,
                     account_email: None,
                     account_name: None,
@@ -1640,12 +2272,84 @@ impl AsyncImapClient {
         Ok(())
     }
 
+    /// Tags a folder offers, and whether new ones can be created
+    /// SELECT is used because EXAMINE reports no PERMANENTFLAGS.
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    pub async fn folder_keywords(&mut self, folder: &str) -> MailResult<keywords::FolderKeywords> {
+        let safe_folder = sanitize_folder_name(folder);
+
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            return self.with_oauth_session(move |session| {
+                let mailbox = session.select(&safe_folder)?;
+                let permanent: Vec<String> = mailbox.permanent_flags.iter().map(|f| f.to_string()).collect();
+                let flags: Vec<String> = mailbox.flags.iter().map(|f| f.to_string()).collect();
+                Ok(keywords::FolderKeywords::new(&permanent, &flags))
+            }).await;
+        }
+
+        let session = self.get_async_session()?;
+        let mailbox = session
+            .select(&safe_folder)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+        let permanent: Vec<String> = mailbox.permanent_flags.iter().map(async_flag_name).collect();
+        let flags: Vec<String> = mailbox.flags.iter().map(async_flag_name).collect();
+        Ok(keywords::FolderKeywords::new(&permanent, &flags))
+    }
+
+    /// Add or remove a keyword (custom flag) on an email
+    /// Adding is refused when the folder's PERMANENTFLAGS do not allow the keyword.
+    /// SECURITY: Folder name sanitized; the keyword must be a plain IMAP atom
+    pub async fn set_keyword(&mut self, folder: &str, uid: u32, keyword: &str, value: bool) -> MailResult<()> {
+        keywords::validate(keyword).map_err(MailError::Config)?;
+        let safe_folder = sanitize_folder_name(folder);
+        let flag_cmd = format!("{}FLAGS ({})", if value { '+' } else { '-' }, keyword);
+        let refused = || MailError::Config(format!("{} does not allow the tag '{}'", folder, keyword));
+
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            log::info!("OAuth set_keyword: using sync session");
+
+            let keyword = keyword.to_string();
+            let stored = self.with_oauth_session(move |session| {
+                let mailbox = session.select(&safe_folder)?;
+                let permanent: Vec<String> = mailbox.permanent_flags.iter().map(|f| f.to_string()).collect();
+                if value && !keywords::allowed(&permanent, &keyword) {
+                    return Ok(false);
+                }
+                session.uid_store(uid.to_string(), &flag_cmd)?;
+                Ok(true)
+            }).await?;
+            return if stored { Ok(()) } else { Err(refused()) };
+        }
+
+        let session = self.get_async_session()?;
+        let mailbox = session
+            .select(&safe_folder)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+        let permanent: Vec<String> = mailbox.permanent_flags.iter().map(async_flag_name).collect();
+        if value && !keywords::allowed(&permanent, keyword) {
+            return Err(refused());
+        }
+
+        let mut stream = session
+            .uid_store(uid.to_string(), &flag_cmd)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+        while let Some(_) = stream.next().await {}
+
+        Ok(())
+    }
+
     /// Move email to another folder
+    /// Uses UID MOVE when the server announces MOVE, otherwise COPY + \Deleted + EXPUNGE,
+    /// rolled back if it fails after the copy (see `copy_delete_move`).
     /// SECURITY: Folder names sanitized to prevent IMAP injection
-    pub async fn move_email(&mut self, folder: &str, uid: u32, target_folder: &str) -> MailResult<()> {
+    pub async fn move_email(&mut self, folder: &str, uid: u32, target_folder: &str) -> MailResult<MoveReport> {
         // SECURITY: Sanitize folder names
         let safe_folder = sanitize_folder_name(folder);
         let safe_target = sanitize_folder_name(target_folder);
+        let caps = self.capabilities.clone().unwrap_or_default();
 
         // Check if OAuth session
         if let Some(ImapSession::OAuth(_)) = &self.session {
@@ -1658,16 +2362,12 @@ impl AsyncImapClient {
 
                 let uid_str = uid.to_string();
 
-                // Copy to target folder
-                session.uid_copy(&uid_str, &safe_target_clone)?;
-
-                // Mark original as deleted
-                session.uid_store(&uid_str, "+FLAGS (\\Deleted)")?;
-
-                // Expunge deleted messages
-                session.expunge()?;
+                if caps.move_ {
+                    session.uid_mv(&uid_str, &safe_target_clone)?;
+                    return Ok(MoveReport::moved(MoveMethod::Move));
+                }
 
-                Ok(())
+                sync_copy_delete_move(session, &caps, &uid_str, &safe_target_clone)
             }).await;
         }
 
@@ -1681,32 +2381,15 @@ impl AsyncImapClient {
 
         let uid_str = uid.to_string();
 
-        // Copy to target folder
-        session
-            .uid_copy(&uid_str, &safe_target)
-            .await
-            .map_err(|e| MailError::Imap(e.to_string()))?;
-
-        // Mark original as deleted and consume the stream
-        {
-            let mut stream = session
-                .uid_store(&uid_str, "+FLAGS (\\Deleted)")
-                .await
-                .map_err(|e| MailError::Imap(e.to_string()))?;
-            while let Some(_) = stream.next().await {}
-        } // stream is dropped here
-
-        // Expunge deleted messages and consume the stream
-        {
-            let expunge_stream = session
-                .expunge()
+        if caps.move_ {
+            session
+                .uid_mv(&uid_str, &safe_target)
                 .await
                 .map_err(|e| MailError::Imap(e.to_string()))?;
-            pin_mut!(expunge_stream);
-            while let Some(_) = expunge_stream.next().await {}
+            return Ok(MoveReport::moved(MoveMethod::Move));
         }
 
-        Ok(())
+        copy_delete_move(session, &caps, &uid_str, &safe_target).await
     }
 
     /// Delete email
@@ -1714,12 +2397,14 @@ impl AsyncImapClient {
     pub async fn delete_email(&mut self, folder: &str, uid: u32, permanent: bool) -> MailResult<()> {
         // SECURITY: Sanitize folder name
         let safe_folder = sanitize_folder_name(folder);
+        let caps = self.capabilities.clone().unwrap_or_default();
 
         // Check if OAuth session
         if let Some(ImapSession::OAuth(_)) = &self.session {
             log::info!("OAuth delete_email: using sync session");
 
             let safe_folder_clone = safe_folder.clone();
+            let trash_folders = self.trash_candidates();
             return self.with_oauth_session(move |session| {
                 session.select(&safe_folder_clone)?;
 
@@ -1730,20 +2415,19 @@ impl AsyncImapClient {
                     session.uid_store(&uid_str, "+FLAGS (\\Deleted)")?;
 
                     // Expunge
-                    session.expunge()?;
+                    sync_expunge_uid(session, &caps, &uid_str)?;
                 } else {
-                    // Move to Trash folder - try common trash folder names
-                    let trash_folders = ["Trash", "[Gmail]/Trash", "Deleted Items", "Deleted"];
+                    // Move to Trash folder - special-use mailbox first, then common names
                     let mut moved = false;
 
                     for trash in &trash_folders {
-                        if session.uid_copy(&uid_str, trash).is_ok() {
-                            // Mark as deleted
-                            session.uid_store(&uid_str, "+FLAGS (\\Deleted)")?;
-
-                            // Expunge
-                            session.expunge()?;
-
+                        if caps.move_ {
+                            if session.uid_mv(&uid_str, trash).is_ok() {
+                                moved = true;
+                                break;
+                            }
+                        } else if let Ok(report) = sync_copy_delete_move(session, &caps, &uid_str, trash) {
+                            report.into_result()?;
                             moved = true;
                             break;
                         }
@@ -1760,6 +2444,7 @@ impl AsyncImapClient {
         }
 
         // Regular async session flow
+        let trash_folders = self.trash_candidates();
         let session = self.get_async_session()?;
 
         session
@@ -1770,50 +2455,20 @@ impl AsyncImapClient {
         let uid_str = uid.to_string();
 
         if permanent {
-            // Mark as deleted and consume the stream
-            {
-                let mut stream = session
-                    .uid_store(&uid_str, "+FLAGS (\\Deleted)")
-                    .await
-                    .map_err(|e| MailError::Imap(e.to_string()))?;
-                while let Some(_) = stream.next().await {}
-            } // stream is dropped here
-
-            // Expunge and consume the stream
-            {
-                let expunge_stream = session
-                    .expunge()
-                    .await
-                    .map_err(|e| MailError::Imap(e.to_string()))?;
-                pin_mut!(expunge_stream);
-                while let Some(_) = expunge_stream.next().await {}
-            }
+            store_flags(session, &uid_str, "+FLAGS (\\Deleted)").await?;
+            expunge_uid(session, &caps, &uid_str).await?;
         } else {
-            // Move to Trash folder - try common trash folder names
-            let trash_folders = ["Trash", "[Gmail]/Trash", "Deleted Items", "Deleted"];
+            // Move to Trash folder - special-use mailbox first, then common names
             let mut moved = false;
 
             for trash in &trash_folders {
-                if session.uid_copy(&uid_str, trash).await.is_ok() {
-                    // Mark as deleted and consume the stream
-                    {
-                        let mut stream = session
-                            .uid_store(&uid_str, "+FLAGS (\\Deleted)")
-                            .await
-                            .map_err(|e| MailError::Imap(e.to_string()))?;
-                        while let Some(_) = stream.next().await {}
-                    } // stream is dropped here
-
-                    // Expunge and consume the stream
-                    {
-                        let expunge_stream = session
-                            .expunge()
-                            .await
-                            .map_err(|e| MailError::Imap(e.to_string()))?;
-                        pin_mut!(expunge_stream);
-                        while let Some(_) = expunge_stream.next().await {}
+                if caps.move_ {
+                    if session.uid_mv(&uid_str, trash).await.is_ok() {
+                        moved = true;
+                        break;
                     }
-
+                } else if let Ok(report) = copy_delete_move(session, &caps, &uid_str, trash).await {
+                    report.into_result()?;
                     moved = true;
                     break;
                 }
@@ -1821,20 +2476,276 @@ impl AsyncImapClient {
 
             if !moved {
                 // If no trash folder found, just mark as deleted
-                let mut stream = session
-                    .uid_store(&uid_str, "+FLAGS (\\Deleted)")
-                    .await
-                    .map_err(|e| MailError::Imap(e.to_string()))?;
-                while let Some(_) = stream.next().await {}
+                store_flags(session, &uid_str, "+FLAGS (\\Deleted)").await?;
             }
         }
 
         Ok(())
     }
 
-    /// Fetch a specific attachment from an email
+    /// Upload a message to a folder with the given flags (e.g. `\Seen`)
+    /// A message over the server's APPENDLIMIT is not sent at all; the caller
+    /// decides what skipping it means.
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    pub async fn append(&mut self, folder: &str, raw: &[u8], flags: &str) -> MailResult<AppendOutcome> {
+        self.append_dated(folder, raw, flags, None).await
+    }
+
+    /// Upload a copy of a message keeping its flags and arrival time
+    pub async fn append_message(&mut self, folder: &str, message: &RawMessage) -> MailResult<AppendOutcome> {
+        self.append_dated(folder, &message.raw, &message.flags.join(" "), message.internal_date).await
+    }
+
+    /// APPEND with an optional INTERNALDATE
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    async fn append_dated(
+        &mut self,
+        folder: &str,
+        raw: &[u8],
+        flags: &str,
+        date: Option<chrono::DateTime<chrono::FixedOffset>>,
+    ) -> MailResult<AppendOutcome> {
+        let safe_folder = sanitize_folder_name(folder);
+
+        if let Some(limit) = self.capabilities.as_ref().and_then(|caps| caps.append_limit) {
+            if raw.len() as u64 > limit {
+                return Ok(AppendOutcome::TooLarge { size: raw.len() as u64, limit });
+            }
+        }
+
+        let flag_list = format!("({})", flags);
+
+        // Check if OAuth session
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            log::info!("OAuth append: using sync session");
+
+            let raw = raw.to_vec();
+            let flags: Vec<imap::types::Flag<'static>> = flags
+                .split_whitespace()
+                .map(|flag| imap::types::Flag::from(flag.to_string()))
+                .collect();
+            return self.with_oauth_session(move |session| {
+                session.append_with_flags_and_date(&safe_folder, &raw, &flags, date)?;
+                Ok(AppendOutcome::Appended)
+            }).await;
+        }
+
+        let session = self.get_async_session()?;
+
+        // date-time = DQUOTE date-day-fixed "-" date-month "-" date-year SP time SP zone DQUOTE
+        let internal_date = date.map(|date| date.format("\"%d-%b-%Y %H:%M:%S %z\"").to_string());
+        session
+            .append(&safe_folder, Some(flag_list.as_str()), internal_date.as_deref(), raw)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        Ok(AppendOutcome::Appended)
+    }
+
+    /// Rename (or move) a folder; the server carries its children along
+    /// SECURITY: Folder names sanitized to prevent IMAP injection
+    pub async fn rename_folder(&mut self, from: &str, to: &str) -> MailResult<()> {
+        let safe_from = sanitize_folder_name(from);
+        let safe_to = sanitize_folder_name(to);
+
+        if safe_from != from || safe_to != to {
+            return Err(MailError::Imap("Folder name contains unsupported characters".to_string()));
+        }
+
+        // Check if OAuth session
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            log::info!("OAuth rename_folder: using sync session");
+
+            return self.with_oauth_session(move |session| {
+                session.rename(&safe_from, &safe_to)?;
+                Ok(())
+            }).await;
+        }
+
+        // Regular async session flow
+        let session = self.get_async_session()?;
+
+        session
+            .rename(&safe_from, &safe_to)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Fetch the full raw RFC 822 source of an email without marking it as read
     /// SECURITY: Folder name sanitized to prevent IMAP injection
+    pub async fn fetch_raw(&mut self, folder: &str, uid: u32) -> MailResult<Vec<u8>> {
+        let safe_folder = sanitize_folder_name(folder);
+
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            let safe_folder_clone = safe_folder.clone();
+            return self.with_oauth_session(move |session| {
+                session.select(&safe_folder_clone)?;
+
+                let messages = session.uid_fetch(uid.to_string(), "(UID BODY.PEEK[])")?;
+                if let Some(body) = messages.iter().next().and_then(|m| m.body()) {
+                    return Ok(body.to_vec());
+                }
+
+                Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Email {} not found", uid)
+                )) as Box<dyn std::error::Error + Send + Sync>)
+            }).await;
+        }
+
+        let session = self.get_async_session()?;
+
+        session
+            .select(&safe_folder)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        let mut messages_stream = session
+            .uid_fetch(uid.to_string(), "(UID BODY.PEEK[])")
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        let mut raw = None;
+        while let Some(result) = messages_stream.next().await {
+            let message = result.map_err(|e| MailError::Imap(e.to_string()))?;
+            if raw.is_none() {
+                raw = message.body().map(|b| b.to_vec());
+            }
+        }
+
+        raw.ok_or_else(|| MailError::NotFound(format!("Email {} not found", uid)))
+    }
+
+    /// Fetch the raw source of an email with its flags and INTERNALDATE, without marking it as read
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    pub async fn fetch_raw_message(&mut self, folder: &str, uid: u32) -> MailResult<RawMessage> {
+        let safe_folder = sanitize_folder_name(folder);
+        let query = "(UID FLAGS INTERNALDATE BODY.PEEK[])";
+
+        if let Some(ImapSession::OAuth(_)) = &self.session {
+            return self.with_oauth_session(move |session| {
+                session.select(&safe_folder)?;
+
+                let messages = session.uid_fetch(uid.to_string(), query)?;
+                if let Some(message) = messages.iter().find(|m| m.body().is_some()) {
+                    return Ok(RawMessage {
+                        raw: message.body().unwrap_or_default().to_vec(),
+                        flags: copyable_flags(message.flags().iter().map(sync_flag_name)),
+                        internal_date: message.internal_date(),
+                    });
+                }
+
+                Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Email {} not found", uid)
+                )) as Box<dyn std::error::Error + Send + Sync>)
+            }).await;
+        }
+
+        let session = self.get_async_session()?;
+
+        session
+            .select(&safe_folder)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        let mut messages_stream = session
+            .uid_fetch(uid.to_string(), query)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+
+        let mut found = None;
+        while let Some(result) = messages_stream.next().await {
+            let message = result.map_err(|e| MailError::Imap(e.to_string()))?;
+            if found.is_none() {
+                if let Some(body) = message.body() {
+                    found = Some(RawMessage {
+                        raw: body.to_vec(),
+                        flags: copyable_flags(message.flags().map(|flag| async_flag_name(&flag))),
+                        internal_date: message.internal_date(),
+                    });
+                }
+            }
+        }
+
+        found.ok_or_else(|| MailError::NotFound(format!("Email {} not found", uid)))
+    }
+
+    /// Fetch a specific attachment from an email
+    /// Large attachments are fetched over BINARY when the server supports it
+    /// (see `binary`); download times are recorded in `metrics`.
     pub async fn fetch_attachment(&mut self, folder: &str, uid: u32, attachment_index: usize) -> MailResult<AttachmentData> {
+        let started = std::time::Instant::now();
+        match self.fetch_attachment_binary(folder, uid, attachment_index).await {
+            Ok(Some(attachment)) => {
+                metrics::record_fetch(FetchMethod::Binary, attachment.size as usize, started.elapsed());
+                return Ok(attachment);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("BINARY fetch of UID {} failed, fetching the whole message: {}", uid, e),
+        }
+
+        let started = std::time::Instant::now();
+        let attachment = self.fetch_attachment_full(folder, uid, attachment_index).await?;
+        metrics::record_fetch(FetchMethod::FullMessage, attachment.size as usize, started.elapsed());
+        Ok(attachment)
+    }
+
+    /// A large transfer-encoded attachment fetched decoded with BINARY
+    /// `None` when the server lacks BINARY, the account uses OAuth, or
+    /// BODYSTRUCTURE does not show a large encoded part at that index.
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    async fn fetch_attachment_binary(&mut self, folder: &str, uid: u32, attachment_index: usize) -> MailResult<Option<AttachmentData>> {
+        let Some(caps) = self.capabilities.clone().filter(|caps| caps.binary) else {
+            return Ok(None);
+        };
+        if self.config.oauth_provider.is_some() || !matches!(self.session, Some(ImapSession::Async(_))) {
+            return Ok(None);
+        }
+        let safe_folder = sanitize_folder_name(folder);
+
+        let session = self.get_async_session()?;
+        session
+            .select(&safe_folder)
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+        let mut messages_stream = session
+            .uid_fetch(uid.to_string(), "(UID BODYSTRUCTURE)")
+            .await
+            .map_err(|e| MailError::Imap(e.to_string()))?;
+        let mut part = None;
+        while let Some(result) = messages_stream.next().await {
+            let message = result.map_err(|e| MailError::Imap(e.to_string()))?;
+            if let Some(structure) = message.bodystructure() {
+                part = binary::attachment_parts(structure).and_then(|parts| parts.into_iter().nth(attachment_index));
+            }
+        }
+        drop(messages_stream);
+
+        let Some(part) = part.filter(|part| part.encoded && part.octets >= binary::MIN_BINARY_OCTETS) else {
+            return Ok(None);
+        };
+
+        log::info!("fetch_attachment: UID {} part {} ({} bytes) over BINARY", uid, part.section, part.octets);
+        let config = self.config.clone();
+        let section = part.section.clone();
+        let data = tokio::task::spawn_blocking(move || binary::fetch_binary(&config, &caps, &safe_folder, uid, &section))
+            .await
+            .map_err(|e| MailError::Connection(format!("Spawn blocking error: {}", e)))??;
+
+        Ok(Some(AttachmentData {
+            filename: part.filename.unwrap_or_else(|| format!("attachment_{}", attachment_index)),
+            content_type: part.content_type,
+            size: data.len() as u32,
+            data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data),
+        }))
+    }
+
+    /// Fetch the whole message and take the attachment from it
+    /// SECURITY: Folder name sanitized to prevent IMAP injection
+    async fn fetch_attachment_full(&mut self, folder: &str, uid: u32, attachment_index: usize) -> MailResult<AttachmentData> {
         // SECURITY: Sanitize folder name
         let safe_folder = sanitize_folder_name(folder);
 
@@ -1955,8 +2866,9 @@ impl AsyncImapClient {
 fn parse_email_body(body: &[u8]) -> (Option<String>, Option<String>, Vec<EmailAttachment>) {
     // Try to parse with mail_parser
     if let Some(parsed) = mail_parser::MessageParser::default().parse(body) {
-        let body_text = parsed.body_text(0).map(|s| s.to_string());
-        let body_html = parsed.body_html(0).map(|s| s.to_string());
+        // Normalize legacy/mislabeled charsets to UTF-8
+        let body_text = super::charset::body_text(&parsed, 0);
+        let body_html = super::charset::body_html(&parsed, 0);
 
         // Extract attachments with full metadata
         let attachments: Vec<EmailAttachment> = parsed.attachments()
@@ -2001,7 +2913,7 @@ fn parse_email_body(body: &[u8]) -> (Option<String>, Option<String>, Vec<EmailAt
         return (body_text, body_html, attachments);
     }
 
-    // Fallback: treat as plain text
-    let text = String::from_utf8_lossy(body).to_string();
+    // Fallback: treat as plain text of unknown charset
+    let text = super::charset::decode_text(body, None).text;
     (Some(text), None, vec![])
 }
@@ -0,0 +1,276 @@
+//! Attachment Size Guard
+//!
+//! Pre-send validation of attachment sizes against known provider limits,
+//! plus a pluggable strategy for sharing oversized files as links
+//! (WebDAV / Nextcloud) instead of embedding them in the message.
+
+use crate::mail::{MailError, MailResult};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Fallback limit when the provider is unknown (most servers accept 25MB)
+pub const DEFAULT_MAX_MESSAGE_SIZE: u64 = 25 * 1024 * 1024;
+
+/// Approximate size of headers and body parts added around attachments
+const MESSAGE_OVERHEAD_BYTES: u64 = 16 * 1024;
+
+/// Known per-provider maximum message sizes (after MIME encoding)
+/// Matched against the SMTP host or the account email domain
+const PROVIDER_LIMITS: &[(&str, &str, u64)] = &[
+    ("gmail", "gmail.com", 25 * 1024 * 1024),
+    ("gmail", "googlemail.com", 25 * 1024 * 1024),
+    ("outlook", "outlook.com", 20 * 1024 * 1024),
+    ("outlook", "hotmail.com", 20 * 1024 * 1024),
+    ("outlook", "live.com", 20 * 1024 * 1024),
+    ("office365", "office365.com", 35 * 1024 * 1024),
+    ("yahoo", "yahoo.com", 25 * 1024 * 1024),
+    ("icloud", "icloud.com", 20 * 1024 * 1024),
+    ("icloud", "me.com", 20 * 1024 * 1024),
+    ("yandex", "yandex.com", 30 * 1024 * 1024),
+    ("yandex", "yandex.com.tr", 30 * 1024 * 1024),
+    ("zoho", "zoho.com", 20 * 1024 * 1024),
+    ("gmx", "gmx.com", 20 * 1024 * 1024),
+    ("protonmail", "proton.me", 25 * 1024 * 1024),
+];
+
+/// Resolved provider limit for an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderLimit {
+    pub provider: String,
+    pub max_message_size: u64,
+}
+
+/// Single attachment entry to validate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentSize {
+    pub filename: String,
+    pub size: u64,
+}
+
+/// Result of a pre-send size check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentSizeReport {
+    pub provider: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub max_message_size: u64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total_raw_size: u64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total_encoded_size: u64,
+    pub exceeds_limit: bool,
+    /// Attachments that would exceed the limit on their own
+    pub oversized: Vec<String>,
+    /// True when a large-attachment strategy is configured and should be offered
+    pub suggest_link: bool,
+}
+
+/// Look up the known limit for an account by SMTP host, then email domain
+pub fn provider_limit(smtp_host: &str, email: &str) -> ProviderLimit {
+    let host = smtp_host.to_lowercase();
+    let domain = email
+        .rsplit('@')
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+
+    for (provider, provider_domain, limit) in PROVIDER_LIMITS {
+        if domain == *provider_domain || host == *provider_domain || host.ends_with(&format!(".{provider_domain}")) {
+            return ProviderLimit {
+                provider: provider.to_string(),
+                max_message_size: *limit,
+            };
+        }
+    }
+
+    ProviderLimit {
+        provider: "default".to_string(),
+        max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+    }
+}
+
+/// Size of data after base64 transfer encoding with 76-char lines + CRLF (RFC 2045)
+pub fn encoded_size(raw_size: u64) -> u64 {
+    let base64_len = raw_size.div_ceil(3) * 4;
+    let line_breaks = base64_len.div_ceil(76) * 2;
+    base64_len + line_breaks
+}
+
+/// Validate attachments against the provider limit
+/// Totals the encoded sizes plus a fixed message overhead
+pub fn check_attachment_sizes(
+    limit: &ProviderLimit,
+    attachments: &[AttachmentSize],
+    has_link_strategy: bool,
+) -> AttachmentSizeReport {
+    let total_raw_size: u64 = attachments.iter().map(|a| a.size).sum();
+    let total_encoded_size: u64 = attachments.iter().map(|a| encoded_size(a.size)).sum::<u64>()
+        + MESSAGE_OVERHEAD_BYTES;
+
+    let oversized = attachments
+        .iter()
+        .filter(|a| encoded_size(a.size) + MESSAGE_OVERHEAD_BYTES > limit.max_message_size)
+        .map(|a| a.filename.clone())
+        .collect();
+
+    let exceeds_limit = total_encoded_size > limit.max_message_size;
+
+    AttachmentSizeReport {
+        provider: limit.provider.clone(),
+        max_message_size: limit.max_message_size,
+        total_raw_size,
+        total_encoded_size,
+        exceeds_limit,
+        oversized,
+        suggest_link: exceeds_limit && has_link_strategy,
+    }
+}
+
+// ============================================================================
+// Large Attachment Strategies
+// ============================================================================
+
+/// Pluggable strategy for delivering attachments that are too large to send
+/// Implementations upload the file somewhere and return a shareable URL
+#[async_trait]
+pub trait LargeAttachmentStrategy: Send + Sync {
+    /// Strategy identifier (e.g. "webdav")
+    fn name(&self) -> &'static str;
+
+    /// Upload file contents and return a link that can be inserted in the body
+    async fn upload(&self, filename: &str, data: Vec<u8>) -> MailResult<String>;
+}
+
+/// Settings key of the WebDAV upload configuration
+pub(crate) const WEBDAV_SETTING: &str = "large_attachment_webdav";
+
+/// WebDAV / Nextcloud upload settings (stored in the [`WEBDAV_SETTING`] setting)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebDavConfig {
+    /// Collection URL, e.g. https://cloud.example.com/remote.php/dav/files/user/Mail
+    pub base_url: String,
+    pub username: String,
+    /// Encrypted with `crypto::encrypt_password`
+    pub password_encrypted: String,
+    /// Optional public URL prefix used for the returned link
+    pub public_base_url: Option<String>,
+}
+
+/// WebDAV upload strategy (works with Nextcloud/ownCloud)
+pub struct WebDavStrategy {
+    config: WebDavConfig,
+    password: String,
+    client: reqwest::Client,
+}
+
+impl WebDavStrategy {
+    /// `password` is the decrypted `config.password_encrypted`
+    pub fn new(config: WebDavConfig, password: String) -> MailResult<Self> {
+        // SECURITY: Never upload attachments over plain HTTP
+        if !config.base_url.starts_with("https://") {
+            return Err(MailError::Config("WebDAV URL must use HTTPS".to_string()));
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .map_err(|e| MailError::Config(format!("HTTP client error: {}", e)))?;
+
+        Ok(Self { config, password, client })
+    }
+}
+
+#[async_trait]
+impl LargeAttachmentStrategy for WebDavStrategy {
+    fn name(&self) -> &'static str {
+        "webdav"
+    }
+
+    async fn upload(&self, filename: &str, data: Vec<u8>) -> MailResult<String> {
+        // Prefix with a random id so concurrent uploads never collide
+        let remote_name = format!(
+            "{}_{}",
+            uuid::Uuid::new_v4().simple(),
+            urlencoding::encode(filename)
+        );
+        let upload_url = format!("{}/{}", self.config.base_url.trim_end_matches('/'), remote_name);
+
+        let response = self
+            .client
+            .put(&upload_url)
+            .basic_auth(&self.config.username, Some(&self.password))
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| MailError::Connection(format!("WebDAV upload failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(MailError::Connection(format!(
+                "WebDAV upload failed with status {}",
+                response.status()
+            )));
+        }
+
+        let link = match &self.config.public_base_url {
+            Some(public) => format!("{}/{}", public.trim_end_matches('/'), remote_name),
+            None => upload_url,
+        };
+
+        log::info!("✓ Uploaded large attachment '{}' via WebDAV", filename);
+        Ok(link)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoded_size_accounts_for_base64_and_line_breaks() {
+        assert_eq!(encoded_size(0), 0);
+        assert_eq!(encoded_size(3), 4 + 2);
+        // 57 raw bytes -> exactly one 76-char line
+        assert_eq!(encoded_size(57), 76 + 2);
+        assert!(encoded_size(10 * 1024 * 1024) > 13 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_provider_limit_lookup() {
+        assert_eq!(provider_limit("smtp.gmail.com", "user@gmail.com").provider, "gmail");
+        assert_eq!(
+            provider_limit("smtp-mail.outlook.com", "user@hotmail.com").max_message_size,
+            20 * 1024 * 1024
+        );
+        assert_eq!(provider_limit("mail.example.org", "user@example.org").provider, "default");
+        // Provider names inside unrelated hosts do not match
+        assert_eq!(provider_limit("smtp.notgmail.net", "user@notgmail.net").provider, "default");
+        assert_eq!(provider_limit("mail.yahoo-partner.example", "user@example.org").provider, "default");
+    }
+
+    #[test]
+    fn test_check_attachment_sizes_flags_oversized() {
+        let limit = provider_limit("smtp.gmail.com", "user@gmail.com");
+        let attachments = vec![
+            AttachmentSize { filename: "small.pdf".to_string(), size: 1024 * 1024 },
+            AttachmentSize { filename: "video.mp4".to_string(), size: 49 * 1024 * 1024 },
+        ];
+
+        let report = check_attachment_sizes(&limit, &attachments, true);
+        assert!(report.exceeds_limit);
+        assert!(report.suggest_link);
+        assert_eq!(report.oversized, vec!["video.mp4".to_string()]);
+
+        // A 19MB raw file encodes to ~26MB and no longer fits into Gmail's 25MB
+        let report = check_attachment_sizes(
+            &limit,
+            &[AttachmentSize { filename: "a.zip".to_string(), size: 19 * 1024 * 1024 }],
+            false,
+        );
+        assert!(report.exceeds_limit);
+        assert!(!report.suggest_link);
+    }
+}
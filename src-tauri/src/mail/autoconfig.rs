@@ -17,6 +17,7 @@ use std::time::Duration;
 
 /// Auto-detected email configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct AutoConfig {
     pub provider: Option<String>,
@@ -28,11 +29,13 @@ pub struct AutoConfig {
     pub smtp_port: u16,
     pub smtp_security: SecurityType,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
     pub detection_method: Option<String>,
 }
 
 /// Detailed auto-detection debug info
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct AutoConfigDebug {
     pub email: String,
@@ -50,6 +53,7 @@ pub struct AutoConfigDebug {
     pub guessing_tried: bool,
     pub guessing_result: Option<String>,
     pub final_config: Option<AutoConfig>,
+    #[cfg_attr(test, ts(type = "number"))]
     pub total_duration_ms: u128,
 }
 
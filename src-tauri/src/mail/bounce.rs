@@ -0,0 +1,122 @@
+//! Bounce Parsing
+//!
+//! Reads delivery status notifications (RFC 3464 `multipart/report` with a
+//! `message/delivery-status` part) for the recipients whose delivery failed
+//! permanently. Delayed deliveries and temporary (4.x.x) failures are left
+//! out; those addresses may still work.
+
+use crate::mail::headers;
+use mail_parser::{MessageParser, MimeHeaders};
+use serde::{Deserialize, Serialize};
+
+/// Recipient a DSN reports as permanently undeliverable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct BouncedRecipient {
+    /// Lowercase address
+    pub address: String,
+    /// Enhanced status code (`5.1.1`)
+    pub status: String,
+    /// Diagnostic from the remote server, or the status code when there is none
+    pub reason: String,
+}
+
+/// Value of a `type; value` field (`rfc822; bob@example.org`, `smtp; 550 ...`)
+fn typed_value(value: &str) -> &str {
+    value.split_once(';').map_or(value, |(_, value)| value).trim()
+}
+
+/// Permanently failed recipients listed in a `message/delivery-status` body
+pub fn parse_delivery_status(text: &str) -> Vec<BouncedRecipient> {
+    let text = text.replace("\r\n", "\n");
+    // The first block describes the reporting MTA, each further one a recipient
+    text.split("\n\n")
+        .skip(1)
+        .filter_map(|block| {
+            let fields = headers::parse(block.trim_start_matches('\n').as_bytes());
+            let field = |name: &str| {
+                fields.iter().find(|field| field.name.eq_ignore_ascii_case(name)).map(|field| field.value.as_str())
+            };
+            let status = field("Status").unwrap_or_default().trim();
+            let failed = field("Action").is_some_and(|action| action.trim().eq_ignore_ascii_case("failed"));
+            if !failed || !status.starts_with('5') {
+                return None;
+            }
+            let address = typed_value(field("Final-Recipient").or(field("Original-Recipient"))?)
+                .trim_matches(['<', '>'])
+                .to_lowercase();
+            if !address.contains('@') {
+                return None;
+            }
+            let reason = field("Diagnostic-Code")
+                .map(typed_value)
+                .filter(|reason| !reason.is_empty())
+                .unwrap_or(status);
+            Some(BouncedRecipient { address, status: status.to_string(), reason: reason.to_string() })
+        })
+        .collect()
+}
+
+/// Permanently failed recipients of a raw DSN message; empty for any other message
+pub fn from_raw(raw: &[u8]) -> Vec<BouncedRecipient> {
+    let Some(message) = MessageParser::default().parse(raw) else {
+        return Vec::new();
+    };
+    message
+        .parts
+        .iter()
+        .filter(|part| {
+            part.content_type().is_some_and(|content_type| {
+                content_type.ctype().eq_ignore_ascii_case("message")
+                    && content_type.subtype().is_some_and(|subtype| {
+                        subtype.eq_ignore_ascii_case("delivery-status")
+                            || subtype.eq_ignore_ascii_case("global-delivery-status")
+                    })
+            })
+        })
+        .flat_map(|part| parse_delivery_status(&String::from_utf8_lossy(part.contents())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delivery_status_notification() {
+        let raw = "From: Mail Delivery System <MAILER-DAEMON@mx.test.com>\r\n\
+            To: me@test.com\r\n\
+            Subject: Undelivered Mail Returned to Sender\r\n\
+            Content-Type: multipart/report; report-type=delivery-status; boundary=\"b\"\r\n\r\n\
+            --b\r\n\
+            Content-Type: text/plain\r\n\r\n\
+            I'm sorry to have to inform you that your message could not be delivered.\r\n\
+            --b\r\n\
+            Content-Type: message/delivery-status\r\n\r\n\
+            Reporting-MTA: dns; mx.test.com\r\n\
+            Arrival-Date: Fri, 8 Mar 2024 12:00:00 +0000\r\n\r\n\
+            Final-Recipient: rfc822; Bob@Example.org\r\n\
+            Action: failed\r\n\
+            Status: 5.1.1\r\n\
+            Diagnostic-Code: smtp; 550 5.1.1 <bob@example.org>: Recipient address\r\n \
+            rejected: User unknown\r\n\r\n\
+            Final-Recipient: rfc822; ana@example.org\r\n\
+            Action: delayed\r\n\
+            Status: 4.4.1\r\n\r\n\
+            Final-Recipient: rfc822; <eve@example.org>\r\n\
+            Action: failed\r\n\
+            Status: 5.2.2\r\n\
+            --b--\r\n";
+
+        let bounced = from_raw(raw.as_bytes());
+        assert_eq!(bounced.len(), 2);
+        assert_eq!(bounced[0].address, "bob@example.org");
+        assert_eq!(bounced[0].status, "5.1.1");
+        assert_eq!(bounced[0].reason, "550 5.1.1 <bob@example.org>: Recipient address rejected: User unknown");
+        assert_eq!(bounced[1].address, "eve@example.org");
+        assert_eq!(bounced[1].reason, "5.2.2");
+
+        assert!(from_raw(b"From: ana@example.org\r\nSubject: Hi\r\n\r\nAction: failed\r\n").is_empty());
+    }
+}
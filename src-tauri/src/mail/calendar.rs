@@ -0,0 +1,319 @@
+//! Calendar Invites
+//!
+//! iCalendar (RFC 5545) meeting requests as sent by the compose flow, and the
+//! iTIP (RFC 5546) parts found in received mail. An invite is a VEVENT with
+//! the account as ORGANIZER and every recipient as an ATTENDEE asked to
+//! respond; it travels as a `text/calendar; method=REQUEST` part, which mail
+//! and calendar clients offer to accept or decline. Their answers come back
+//! as `METHOD:REPLY` parts carrying the attendee's PARTSTAT.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use mail_parser::{MessageParser, MimeHeaders};
+use serde::{Deserialize, Serialize};
+
+/// Content type of the invite part
+pub const REQUEST_CONTENT_TYPE: &str = "text/calendar; method=REQUEST; charset=UTF-8";
+
+/// File name of the invite part
+pub const INVITE_FILENAME: &str = "invite.ics";
+
+const PRODID: &str = "-//Owlivion//Owlivion Mail//EN";
+
+/// Meeting to invite the recipients to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct NewInvite {
+    pub title: String,
+    /// RFC 3339, or `YYYY-MM-DD` for all-day events
+    pub start: String,
+    /// Exclusive end in the same form as `start`
+    pub end: String,
+    #[serde(default)]
+    pub all_day: bool,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Calendar part of a received message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarPart {
+    /// iTIP method in uppercase (`REQUEST`, `REPLY`, `CANCEL`, ...)
+    pub method: String,
+    pub uid: String,
+    pub summary: Option<String>,
+    /// Attendees with their participation status (a REPLY lists the responder)
+    pub attendees: Vec<AttendeeStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AttendeeStatus {
+    /// Lowercase address
+    pub email: String,
+    /// `ACCEPTED`, `DECLINED`, `TENTATIVE`, `NEEDS-ACTION` or `DELEGATED`
+    pub partstat: String,
+}
+
+/// Escape a TEXT value (RFC 5545 section 3.3.11)
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+/// Parameter value, quoted when it contains separators
+fn param_value(value: &str) -> String {
+    let value = value.replace(['"', '\r', '\n'], "");
+    if value.contains([';', ':', ',']) {
+        format!("\"{}\"", value)
+    } else {
+        value
+    }
+}
+
+/// Content line folded at 75 octets (continuations start with a space)
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// DTSTART/DTEND property in UTC, or as a DATE for all-day events
+fn time_property(name: &str, value: &str, all_day: bool) -> Result<String, String> {
+    if all_day {
+        let date = NaiveDate::parse_from_str(value.get(..10).unwrap_or(value), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date: {}", value))?;
+        return Ok(format!("{};VALUE=DATE:{}", name, date.format("%Y%m%d")));
+    }
+    let time = DateTime::parse_from_rfc3339(value).map_err(|_| format!("Invalid time: {}", value))?;
+    Ok(format!("{}:{}", name, time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")))
+}
+
+/// VCALENDAR with METHOD:REQUEST inviting `attendees` to the meeting
+pub fn request(
+    invite: &NewInvite,
+    uid: &str,
+    organizer: &str,
+    organizer_name: Option<&str>,
+    attendees: &[String],
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    if invite.title.trim().is_empty() {
+        return Err("The invite needs a title".to_string());
+    }
+    let start = time_property("DTSTART", &invite.start, invite.all_day)?;
+    let end = time_property("DTEND", &invite.end, invite.all_day)?;
+    // Both values are UTC timestamps or dates of the same form, which order as text
+    let value = |property: &str| property.split_once(':').map(|(_, value)| value.to_string());
+    if value(&end) <= value(&start) {
+        return Err("The invite must end after it starts".to_string());
+    }
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        format!("PRODID:{}", PRODID),
+        "VERSION:2.0".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:REQUEST".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")),
+        start,
+        end,
+        format!("SUMMARY:{}", escape_text(invite.title.trim())),
+    ];
+    if let Some(location) = invite.location.as_deref().filter(|location| !location.trim().is_empty()) {
+        lines.push(format!("LOCATION:{}", escape_text(location.trim())));
+    }
+    if let Some(description) = invite.description.as_deref().filter(|description| !description.trim().is_empty()) {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description.trim())));
+    }
+    lines.push(match organizer_name.filter(|name| !name.trim().is_empty()) {
+        Some(name) => format!("ORGANIZER;CN={}:mailto:{}", param_value(name.trim()), organizer),
+        None => format!("ORGANIZER:mailto:{}", organizer),
+    });
+    for attendee in attendees {
+        lines.push(format!(
+            "ATTENDEE;CUTYPE=INDIVIDUAL;ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION;RSVP=TRUE:mailto:{}",
+            attendee
+        ));
+    }
+    lines.extend(["SEQUENCE:0", "STATUS:CONFIRMED", "TRANSP:OPAQUE", "END:VEVENT", "END:VCALENDAR"].map(str::to_string));
+
+    let mut ics = String::new();
+    for line in &lines {
+        fold(line, &mut ics);
+    }
+    Ok(ics)
+}
+
+/// Content lines with folding undone
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Unescape a TEXT value
+fn unescape_text(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+struct ContentLine<'a> {
+    /// Uppercase property name
+    name: String,
+    /// Parameters with uppercase names
+    params: Vec<(String, String)>,
+    value: &'a str,
+}
+
+fn split_line(line: &str) -> Option<ContentLine<'_>> {
+    // The value starts at the first ':' outside a quoted parameter value
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut segments = head.split(';');
+    let name = segments.next()?.trim().to_ascii_uppercase();
+    let params = segments
+        .filter_map(|segment| {
+            let (key, value) = segment.split_once('=')?;
+            Some((key.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        })
+        .collect();
+    Some(ContentLine { name, params, value })
+}
+
+/// Method, UID, summary and attendees of an iCalendar object (first VEVENT)
+pub fn parse(ics: &str) -> Option<CalendarPart> {
+    let mut method = None;
+    let mut uid = None;
+    let mut summary = None;
+    let mut attendees = Vec::new();
+    let mut in_event = false;
+
+    for line in unfold(ics) {
+        let Some(ContentLine { name, params, value }) = split_line(&line) else {
+            continue;
+        };
+        match (name.as_str(), in_event) {
+            ("METHOD", false) => method = Some(value.trim().to_ascii_uppercase()),
+            ("BEGIN", false) if value.eq_ignore_ascii_case("VEVENT") => in_event = true,
+            ("END", true) if value.eq_ignore_ascii_case("VEVENT") => break,
+            ("UID", true) => uid = Some(value.trim().to_string()),
+            ("SUMMARY", true) => summary = Some(unescape_text(value)),
+            ("ATTENDEE", true) => {
+                let email = value.trim();
+                let email = email.get(..7).filter(|scheme| scheme.eq_ignore_ascii_case("mailto:")).map_or(email, |_| &email[7..]);
+                let partstat = params
+                    .iter()
+                    .find(|(key, _)| key == "PARTSTAT")
+                    .map_or("NEEDS-ACTION".to_string(), |(_, value)| value.to_ascii_uppercase());
+                attendees.push(AttendeeStatus { email: email.to_lowercase(), partstat });
+            }
+            _ => {}
+        }
+    }
+
+    Some(CalendarPart { method: method?, uid: uid?, summary, attendees })
+}
+
+/// First calendar part of a raw message
+pub fn from_raw(raw: &[u8]) -> Option<CalendarPart> {
+    let message = MessageParser::default().parse(raw)?;
+    message.parts.iter().find_map(|part| {
+        let content_type = part.content_type()?;
+        let subtype = content_type.subtype().unwrap_or_default();
+        let is_calendar = (content_type.ctype().eq_ignore_ascii_case("text") && subtype.eq_ignore_ascii_case("calendar"))
+            || (content_type.ctype().eq_ignore_ascii_case("application") && subtype.eq_ignore_ascii_case("ics"));
+        if !is_calendar {
+            return None;
+        }
+        parse(&String::from_utf8_lossy(part.contents()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_and_reply() {
+        let invite = NewInvite {
+            title: "Budget review; Q3".to_string(),
+            start: "2024-03-08T15:00:00+03:00".to_string(),
+            end: "2024-03-08T16:00:00+03:00".to_string(),
+            all_day: false,
+            location: Some("Room 4, 2nd floor".to_string()),
+            description: None,
+        };
+        let now = DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let attendees = ["ana@example.com".to_string(), "bob@example.com".to_string()];
+        let ics = request(&invite, "abc@owlivion", "me@example.com", Some("Me, Myself"), &attendees, now).unwrap();
+
+        assert!(ics.contains("METHOD:REQUEST\r\n"));
+        assert!(ics.contains("DTSTART:20240308T120000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Budget review\\; Q3\r\n"));
+        assert!(ics.contains("LOCATION:Room 4\\, 2nd floor\r\n"));
+        assert!(ics.contains("ORGANIZER;CN=\"Me, Myself\":mailto:me@example.com\r\n"));
+        assert!(ics.lines().all(|line| line.len() <= 75));
+
+        let parsed = parse(&ics).unwrap();
+        assert_eq!(parsed.method, "REQUEST");
+        assert_eq!(parsed.uid, "abc@owlivion");
+        assert_eq!(parsed.summary.as_deref(), Some("Budget review; Q3"));
+        assert_eq!(parsed.attendees.len(), 2);
+        assert_eq!(parsed.attendees[1].partstat, "NEEDS-ACTION");
+
+        let backwards = NewInvite { end: invite.start.clone(), ..invite.clone() };
+        assert!(request(&backwards, "x", "me@example.com", None, &attendees, now).is_err());
+
+        let raw = b"From: ana@example.com\r\nSubject: Accepted: Budget review\r\n\
+            Content-Type: text/calendar; method=REPLY; charset=UTF-8\r\n\r\n\
+            BEGIN:VCALENDAR\r\nMETHOD:REPLY\r\nBEGIN:VEVENT\r\nUID:abc@owlivion\r\n\
+            ATTENDEE;PARTSTAT=ACCEPTED;CN=\"Ana: Sales\":MAILTO:Ana@Example.com\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let reply = from_raw(raw).unwrap();
+        assert_eq!(reply.method, "REPLY");
+        assert_eq!(reply.attendees, [AttendeeStatus { email: "ana@example.com".to_string(), partstat: "ACCEPTED".to_string() }]);
+    }
+}
@@ -0,0 +1,261 @@
+//! Server Capabilities
+//!
+//! What an account's IMAP and SMTP servers announce: IMAP CAPABILITY after
+//! login and the SMTP EHLO reply. Client code branches on these (UID MOVE
+//! instead of COPY + EXPUNGE, UID EXPUNGE of a single message, a size check
+//! before sending) rather than assuming an extension and failing at runtime.
+
+use crate::mail::{config::is_loopback_host, MailError, MailResult, SecurityType};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// Time allowed for each step of an SMTP probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Extensions of an IMAP server that change how the client works
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct ImapCapabilities {
+    /// IDLE (RFC 2177): push notifications for a selected mailbox
+    pub idle: bool,
+    /// MOVE (RFC 6851): atomic UID MOVE
+    #[serde(rename = "move")]
+    pub move_: bool,
+    /// CONDSTORE (RFC 7162): mod-sequences for flag changes
+    pub condstore: bool,
+    /// UIDPLUS (RFC 4315): UID EXPUNGE and APPENDUID/COPYUID
+    pub uidplus: bool,
+    /// SPECIAL-USE (RFC 6154): mailbox roles in LIST
+    pub special_use: bool,
+    /// NOTIFY (RFC 5465): change events for several mailboxes on one connection
+    #[serde(default)]
+    pub notify: bool,
+    /// NAMESPACE (RFC 2342): personal, other users' and shared folder prefixes
+    #[serde(default)]
+    pub namespace: bool,
+    /// Largest message APPEND accepts (APPENDLIMIT=n, RFC 7889); `None` if
+    /// not announced or only announced per mailbox
+    #[serde(default)]
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub append_limit: Option<u64>,
+    /// BINARY (RFC 3516): FETCH BINARY[] returns parts already decoded
+    #[serde(default)]
+    pub binary: bool,
+    /// LITERAL+ (RFC 7888): literals of any size may be sent without waiting
+    /// for a continuation request
+    #[serde(default)]
+    pub literal_plus: bool,
+    /// LITERAL- (RFC 7888): the same for literals up to 4096 bytes
+    #[serde(default)]
+    pub literal_minus: bool,
+}
+
+impl ImapCapabilities {
+    /// Parse a list of capability names (case-insensitive)
+    pub fn parse<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let names: Vec<String> = names.into_iter().map(|name| name.to_ascii_uppercase()).collect();
+        let has = |wanted: &str| names.iter().any(|name| name == wanted);
+        Self {
+            idle: has("IDLE"),
+            move_: has("MOVE"),
+            condstore: has("CONDSTORE"),
+            uidplus: has("UIDPLUS"),
+            special_use: has("SPECIAL-USE"),
+            notify: has("NOTIFY"),
+            namespace: has("NAMESPACE"),
+            append_limit: names
+                .iter()
+                .find_map(|name| name.strip_prefix("APPENDLIMIT="))
+                .and_then(|limit| limit.parse().ok()),
+            binary: has("BINARY"),
+            literal_plus: has("LITERAL+"),
+            literal_minus: has("LITERAL-"),
+        }
+    }
+
+    /// Whether a literal of `len` bytes may be sent without a continuation round trip
+    pub fn non_sync_literal(&self, len: usize) -> bool {
+        self.literal_plus || (self.literal_minus && len <= 4096)
+    }
+
+    /// Parse the untagged `* CAPABILITY` line of a raw server response
+    pub fn parse_response(response: &str) -> Self {
+        let names = response
+            .lines()
+            .find_map(|line| line.strip_prefix("* CAPABILITY "))
+            .unwrap_or_default();
+        Self::parse(names.split_whitespace())
+    }
+}
+
+/// Extensions of an SMTP server from its EHLO reply
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpCapabilities {
+    /// Largest message the server accepts (SIZE, RFC 1870); `None` if not
+    /// announced or announced without a limit
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub size_limit: Option<u64>,
+    /// PIPELINING (RFC 2920)
+    pub pipelining: bool,
+    /// 8BITMIME (RFC 6152)
+    pub eight_bit_mime: bool,
+    /// SMTPUTF8 (RFC 6531)
+    pub smtp_utf8: bool,
+    /// AUTH mechanisms offered (after STARTTLS when the server uses it)
+    pub auth_mechanisms: Vec<String>,
+}
+
+impl SmtpCapabilities {
+    /// Parse a (multi-line) `250` EHLO reply; the first line is the greeting
+    pub fn parse_ehlo(reply: &str) -> Self {
+        let mut caps = Self::default();
+        for line in reply.lines().skip(1) {
+            let Some(keyword_line) = line.get(4..) else {
+                continue;
+            };
+            let mut words = keyword_line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            match keyword.to_ascii_uppercase().as_str() {
+                "SIZE" => caps.size_limit = words.next().and_then(|size| size.parse().ok()).filter(|size| *size > 0),
+                "PIPELINING" => caps.pipelining = true,
+                "8BITMIME" => caps.eight_bit_mime = true,
+                "SMTPUTF8" => caps.smtp_utf8 = true,
+                "AUTH" => caps.auth_mechanisms = words.map(|word| word.to_ascii_uppercase()).collect(),
+                _ => {}
+            }
+        }
+        caps
+    }
+}
+
+/// Read one SMTP reply (all continuation lines) and check its code
+fn read_reply(reader: &mut impl BufRead, expected: &str) -> MailResult<String> {
+    let mut reply = String::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|e| MailError::Connection(e.to_string()))? == 0 {
+            return Err(MailError::Connection("SMTP server closed the connection".to_string()));
+        }
+        let done = line.as_bytes().get(3) != Some(&b'-');
+        reply.push_str(line.trim_end());
+        reply.push('\n');
+        if done {
+            break;
+        }
+    }
+    if !reply.starts_with(expected) {
+        return Err(MailError::Smtp(format!("Unexpected reply: {}", reply.trim_end())));
+    }
+    Ok(reply)
+}
+
+/// Send EHLO and parse the reply, then QUIT
+fn ehlo<S: Read + Write>(stream: S, greeted: bool) -> MailResult<SmtpCapabilities> {
+    let mut reader = BufReader::new(stream);
+    if !greeted {
+        read_reply(&mut reader, "220")?;
+    }
+    reader.get_mut().write_all(b"EHLO localhost\r\n")?;
+    let caps = SmtpCapabilities::parse_ehlo(&read_reply(&mut reader, "250")?);
+    let _ = reader.get_mut().write_all(b"QUIT\r\n");
+    Ok(caps)
+}
+
+/// Connect to an SMTP server and read its EHLO extensions (no login)
+/// With STARTTLS the extensions are read again after the upgrade, since
+/// servers often announce AUTH and SIZE only over TLS.
+pub async fn probe_smtp(host: &str, port: u16, security: SecurityType, accept_invalid_certs: bool) -> MailResult<SmtpCapabilities> {
+    if matches!(security, SecurityType::NONE) && !is_loopback_host(host) {
+        return Err(MailError::Config("Insecure SMTP is only supported to localhost".to_string()));
+    }
+    let host = host.to_string();
+
+    tokio::task::spawn_blocking(move || {
+        let stream = TcpStream::connect((host.as_str(), port)).map_err(|e| MailError::Connection(e.to_string()))?;
+        stream.set_read_timeout(Some(PROBE_TIMEOUT))?;
+        stream.set_write_timeout(Some(PROBE_TIMEOUT))?;
+
+        let tls = || {
+            native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(accept_invalid_certs)
+                .build()
+                .map_err(|e| MailError::Smtp(format!("TLS error: {}", e)))
+        };
+
+        match security {
+            SecurityType::NONE => ehlo(stream, false),
+            SecurityType::SSL => {
+                let stream = tls()?
+                    .connect(&host, stream)
+                    .map_err(|e| MailError::Connection(format!("TLS handshake failed: {}", e)))?;
+                ehlo(stream, false)
+            }
+            SecurityType::STARTTLS => {
+                let mut reader = BufReader::new(stream);
+                read_reply(&mut reader, "220")?;
+                reader.get_mut().write_all(b"EHLO localhost\r\n")?;
+                read_reply(&mut reader, "250")?;
+                reader.get_mut().write_all(b"STARTTLS\r\n")?;
+                read_reply(&mut reader, "220")?;
+                let stream = tls()?
+                    .connect(&host, reader.into_inner())
+                    .map_err(|e| MailError::Connection(format!("TLS handshake failed: {}", e)))?;
+                ehlo(stream, true)
+            }
+        }
+    })
+    .await
+    .map_err(|e| MailError::Connection(format!("Spawn blocking error: {}", e)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_imap_capabilities() {
+        let caps = ImapCapabilities::parse(["IMAP4rev1", "UIDPLUS", "move", "SPECIAL-USE", "AUTH=PLAIN"]);
+        assert!(caps.move_ && caps.uidplus && caps.special_use);
+        assert!(!caps.idle && !caps.condstore);
+        assert_eq!(caps.append_limit, None);
+        assert_eq!(serde_json::to_value(&caps).unwrap()["move"], true);
+
+        let caps = ImapCapabilities::parse_response("* CAPABILITY IMAP4rev1 IDLE NAMESPACE APPENDLIMIT=1000\r\nA1 OK done\r\n");
+        assert!(caps.idle && caps.namespace);
+        assert_eq!(caps.append_limit, Some(1000));
+        assert!(!caps.binary && !caps.non_sync_literal(1));
+
+        let caps = ImapCapabilities::parse(["IMAP4rev1", "BINARY", "LITERAL-"]);
+        assert!(caps.binary);
+        assert!(caps.non_sync_literal(4096) && !caps.non_sync_literal(4097));
+    }
+
+    #[test]
+    fn test_parse_ehlo() {
+        let caps = SmtpCapabilities::parse_ehlo(
+            "250-smtp.example.com Hello\n250-SIZE 35882577\n250-PIPELINING\n250-AUTH LOGIN plain XOAUTH2\n250 8BITMIME\n",
+        );
+        assert_eq!(caps.size_limit, Some(35882577));
+        assert!(caps.pipelining && caps.eight_bit_mime && !caps.smtp_utf8);
+        assert_eq!(caps.auth_mechanisms, vec!["LOGIN", "PLAIN", "XOAUTH2"]);
+
+        // SIZE without a number, or SIZE 0, means no fixed limit
+        let caps = SmtpCapabilities::parse_ehlo("250-localhost\n250 SIZE\n");
+        assert_eq!(caps.size_limit, None);
+    }
+
+    #[tokio::test]
+    async fn test_probe_mock_server() {
+        let server = crate::test_util::mock_smtp::MockSmtpServer::start("alice@mock.test", "secret").await.unwrap();
+        let caps = probe_smtp(&server.host(), server.port(), SecurityType::NONE, false).await.unwrap();
+        assert!(caps.eight_bit_mime);
+        assert_eq!(caps.auth_mechanisms, vec!["PLAIN", "LOGIN"]);
+    }
+}
@@ -0,0 +1,470 @@
+//! Charset-Robust Decoding
+//!
+//! Normalizes incoming text from legacy charsets (ISO-8859-9, windows-1254,
+//! KOI8-R, ...) and malformed transfer encodings into UTF-8, reporting how many
+//! characters had to be replaced so callers can surface encoding problems.
+
+use encoding_rs::{Encoding, KOI8_R, UTF_8, WINDOWS_1252, WINDOWS_1254};
+use mail_parser::{MimeHeaders, PartType};
+use serde::{Deserialize, Serialize};
+
+/// Unicode replacement character produced for undecodable input
+const REPLACEMENT_CHAR: char = '\u{FFFD}';
+
+/// Byte sequences typical of UTF-8 text that was decoded as Latin-1/windows-125x
+/// (e.g. "Ä±" for "ı", "ÅŸ" for "ş", "Ã¼" for "ü")
+const MOJIBAKE_MARKERS: &[&str] = &["Ã", "Ä", "Å", "Â"];
+
+/// Content-Transfer-Encoding of a MIME part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferEncoding {
+    None,
+    QuotedPrintable,
+    Base64,
+}
+
+/// Result of decoding a byte sequence into UTF-8
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedText {
+    pub text: String,
+    /// Charset label declared by the message (if any)
+    pub declared_charset: Option<String>,
+    /// Charset actually used for decoding (WHATWG canonical name)
+    pub charset: String,
+    /// True when the charset was guessed rather than taken from the declaration
+    pub charset_guessed: bool,
+    /// Number of characters replaced with U+FFFD
+    pub replacement_count: usize,
+    /// Number of malformed quoted-printable / base64 sequences encountered
+    pub malformed_sequences: usize,
+    /// True when double-encoded (mojibake) UTF-8 was repaired
+    pub repaired_mojibake: bool,
+}
+
+impl DecodedText {
+    /// True when decoding needed any lossy or heuristic step
+    pub fn has_problems(&self) -> bool {
+        self.replacement_count > 0 || self.malformed_sequences > 0 || self.charset_guessed || self.repaired_mojibake
+    }
+}
+
+/// Resolve a charset label, tolerating common malformed variants
+/// ("utf8", "UTF_8", "iso8859-9", quoted labels, "x-" prefixes)
+pub fn resolve_charset(label: &str) -> Option<&'static Encoding> {
+    let cleaned = label.trim().trim_matches('"').trim_matches('\'').to_ascii_lowercase();
+    if cleaned.is_empty() {
+        return None;
+    }
+
+    if let Some(encoding) = Encoding::for_label(cleaned.as_bytes()) {
+        return Some(encoding);
+    }
+
+    // Normalize separators: "utf_8" / "iso8859_9" / "iso88599"
+    let normalized = cleaned.replace('_', "-");
+    if let Some(encoding) = Encoding::for_label(normalized.as_bytes()) {
+        return Some(encoding);
+    }
+    if let Some(rest) = normalized.strip_prefix("iso8859-").or_else(|| normalized.strip_prefix("iso8859")) {
+        if let Some(encoding) = Encoding::for_label(format!("iso-8859-{}", rest.trim_start_matches('-')).as_bytes()) {
+            return Some(encoding);
+        }
+    }
+    if let Some(rest) = normalized.strip_prefix("x-") {
+        return Encoding::for_label(rest.as_bytes());
+    }
+
+    None
+}
+
+/// Guess the charset of non-UTF-8 bytes without a usable declaration
+/// Cyrillic-heavy text (most letters are 8-bit) is treated as KOI8-R,
+/// anything else as windows-1254 (Turkish superset of ISO-8859-9)
+fn guess_legacy_charset(bytes: &[u8]) -> &'static Encoding {
+    let high = bytes.iter().filter(|b| **b >= 0x80).count();
+    let ascii_letters = bytes.iter().filter(|b| b.is_ascii_alphabetic()).count();
+
+    if high > 0 && high > ascii_letters {
+        KOI8_R
+    } else {
+        WINDOWS_1254
+    }
+}
+
+/// Decode bytes to UTF-8 using the declared charset, falling back to heuristics
+pub fn decode_text(bytes: &[u8], declared_charset: Option<&str>) -> DecodedText {
+    let declared = declared_charset.and_then(resolve_charset);
+
+    let (encoding, charset_guessed) = match declared {
+        // A UTF-8 declaration on invalid UTF-8 is a common mislabel of legacy mail
+        Some(enc) if enc == UTF_8 && std::str::from_utf8(bytes).is_err() => {
+            (guess_legacy_charset(bytes), true)
+        }
+        Some(enc) => (enc, false),
+        None if std::str::from_utf8(bytes).is_ok() => (UTF_8, declared_charset.is_some()),
+        None => (guess_legacy_charset(bytes), true),
+    };
+
+    let (decoded, _, _) = encoding.decode(bytes);
+    let mut text = decoded.into_owned();
+    let replacement_count = text.chars().filter(|c| *c == REPLACEMENT_CHAR).count();
+
+    let repaired_mojibake = match repair_mojibake(&text) {
+        Some(repaired) => {
+            text = repaired;
+            true
+        }
+        None => false,
+    };
+
+    DecodedText {
+        text,
+        declared_charset: declared_charset.map(|s| s.to_string()),
+        charset: encoding.name().to_string(),
+        charset_guessed,
+        replacement_count,
+        malformed_sequences: 0,
+        repaired_mojibake,
+    }
+}
+
+/// Decode a transfer-encoded MIME body and then its charset
+pub fn decode_body(raw: &[u8], transfer_encoding: TransferEncoding, declared_charset: Option<&str>) -> DecodedText {
+    let (bytes, malformed) = match transfer_encoding {
+        TransferEncoding::None => (raw.to_vec(), 0),
+        TransferEncoding::QuotedPrintable => decode_quoted_printable_bytes(raw, false),
+        TransferEncoding::Base64 => decode_base64_lenient(raw),
+    };
+
+    let mut decoded = decode_text(&bytes, declared_charset);
+    decoded.malformed_sequences = malformed;
+    decoded
+}
+
+/// Repair UTF-8 text that was wrongly decoded as windows-1252/Latin-1 ("TÃ¼rkÃ§e" -> "Türkçe")
+/// Returns None when the text doesn't look double-encoded or the repair would lose data
+pub fn repair_mojibake(text: &str) -> Option<String> {
+    if !MOJIBAKE_MARKERS.iter().any(|m| text.contains(m)) {
+        return None;
+    }
+
+    let (bytes, _, had_unmappable) = WINDOWS_1252.encode(text);
+    if had_unmappable {
+        return None;
+    }
+
+    let repaired = std::str::from_utf8(&bytes).ok()?;
+    let before = MOJIBAKE_MARKERS.iter().map(|m| text.matches(m).count()).sum::<usize>();
+    let after = MOJIBAKE_MARKERS.iter().map(|m| repaired.matches(m).count()).sum::<usize>();
+
+    if after < before && repaired.chars().count() < text.chars().count() {
+        Some(repaired.to_string())
+    } else {
+        None
+    }
+}
+
+/// Decode quoted-printable bytes (RFC 2045), returning (bytes, malformed_sequence_count)
+/// Malformed escapes are kept literally instead of being dropped.
+/// `header_mode` enables RFC 2047 "Q" semantics (underscore means space).
+pub fn decode_quoted_printable_bytes(input: &[u8], header_mode: bool) -> (Vec<u8>, usize) {
+    let mut result = Vec::with_capacity(input.len());
+    let mut malformed = 0;
+    let mut i = 0;
+
+    while i < input.len() {
+        let byte = input[i];
+        match byte {
+            b'=' => {
+                // Soft line break: "=\r\n" or "=\n" (possibly with trailing whitespace)
+                let mut j = i + 1;
+                while j < input.len() && (input[j] == b' ' || input[j] == b'\t') {
+                    j += 1;
+                }
+                if j < input.len() && (input[j] == b'\r' || input[j] == b'\n') {
+                    if input[j] == b'\r' && j + 1 < input.len() && input[j + 1] == b'\n' {
+                        j += 1;
+                    }
+                    i = j + 1;
+                    continue;
+                }
+                if j == input.len() {
+                    // Trailing "=" at end of input
+                    i = j;
+                    continue;
+                }
+
+                let hex = input.get(i + 1..i + 3);
+                match hex.and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(decoded) => {
+                        result.push(decoded);
+                        i += 3;
+                    }
+                    None => {
+                        malformed += 1;
+                        result.push(b'=');
+                        i += 1;
+                    }
+                }
+            }
+            b'_' if header_mode => {
+                result.push(b' ');
+                i += 1;
+            }
+            _ => {
+                result.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    (result, malformed)
+}
+
+/// Decode base64 while ignoring whitespace and garbage characters
+/// Returns (bytes, malformed_count) where malformed counts skipped characters
+pub fn decode_base64_lenient(input: &[u8]) -> (Vec<u8>, usize) {
+    let mut malformed = 0;
+    let cleaned: Vec<u8> = input
+        .iter()
+        .copied()
+        .filter(|b| {
+            if b.is_ascii_alphanumeric() || *b == b'+' || *b == b'/' {
+                true
+            } else {
+                if !b.is_ascii_whitespace() && *b != b'=' {
+                    malformed += 1;
+                }
+                false
+            }
+        })
+        .collect();
+
+    // Drop a dangling sextet that can't form a byte
+    let usable = cleaned.len() - (cleaned.len() % 4 == 1) as usize;
+    let decoded = base64::Engine::decode(
+        &base64::engine::general_purpose::STANDARD_NO_PAD,
+        &cleaned[..usable],
+    )
+    .unwrap_or_else(|_| {
+        malformed += 1;
+        Vec::new()
+    });
+
+    (decoded, malformed)
+}
+
+/// Decode RFC 2047 encoded-words in a header value, honouring their charset
+pub fn decode_encoded_words(input: &str) -> String {
+    if !input.contains("=?") {
+        return input.to_string();
+    }
+
+    let re = match regex_lite::Regex::new(r"=\?([^?]+)\?([BbQq])\?([^?]*)\?=") {
+        Ok(re) => re,
+        Err(_) => return input.to_string(),
+    };
+
+    let mut result = String::with_capacity(input.len());
+    let mut last_end = 0;
+    let mut previous_was_word = false;
+
+    for caps in re.captures_iter(input) {
+        let whole = caps.get(0).unwrap();
+        let between = &input[last_end..whole.start()];
+
+        // Whitespace between adjacent encoded-words is not displayed (RFC 2047 §6.2)
+        if !(previous_was_word && between.trim().is_empty()) {
+            result.push_str(between);
+        }
+
+        // Charset may carry an RFC 2231 language suffix: "utf-8*tr"
+        let charset = caps[1].split('*').next().unwrap_or("utf-8");
+        let payload = caps[3].as_bytes();
+        let bytes = if caps[2].eq_ignore_ascii_case("b") {
+            decode_base64_lenient(payload).0
+        } else {
+            decode_quoted_printable_bytes(payload, true).0
+        };
+
+        result.push_str(&decode_text(&bytes, Some(charset)).text);
+        last_end = whole.end();
+        previous_was_word = true;
+    }
+
+    result.push_str(&input[last_end..]);
+    result
+}
+
+// ============================================================================
+// Parsed Message Integration
+// ============================================================================
+
+/// Re-decode a parsed text/HTML part from its raw bytes when mail-parser's
+/// output looks broken (undeclared charset, mislabeled charset, double encoding)
+/// Returns None when the parsed text is already fine
+fn repair_part(message: &mail_parser::Message, part_id: usize) -> Option<DecodedText> {
+    let part = message.parts.get(part_id)?;
+    let current = match &part.body {
+        PartType::Text(text) | PartType::Html(text) => text.as_ref(),
+        _ => return None,
+    };
+
+    let current_replacements = current.chars().filter(|c| *c == REPLACEMENT_CHAR).count();
+    if !part.is_encoding_problem && current_replacements == 0 {
+        return repair_mojibake(current).map(|text| DecodedText {
+            text,
+            declared_charset: part.content_type().and_then(|ct| ct.attribute("charset")).map(|s| s.to_string()),
+            charset: UTF_8.name().to_string(),
+            charset_guessed: false,
+            replacement_count: 0,
+            malformed_sequences: 0,
+            repaired_mojibake: true,
+        });
+    }
+
+    let raw = message.raw_message().get(part.offset_body..part.offset_end)?;
+    let transfer_encoding = match part.encoding {
+        mail_parser::Encoding::None => TransferEncoding::None,
+        mail_parser::Encoding::QuotedPrintable => TransferEncoding::QuotedPrintable,
+        mail_parser::Encoding::Base64 => TransferEncoding::Base64,
+    };
+    let charset = part.content_type().and_then(|ct| ct.attribute("charset"));
+    let decoded = decode_body(raw, transfer_encoding, charset);
+
+    // Keep mail-parser's result unless re-decoding is actually better
+    if decoded.replacement_count < current_replacements || (part.is_encoding_problem && decoded.replacement_count == 0) {
+        Some(decoded)
+    } else {
+        None
+    }
+}
+
+fn log_repair(kind: &str, decoded: &DecodedText) {
+    log::warn!(
+        "Normalized {} body: declared={:?} used={} guessed={} replacements={} malformed={} mojibake={}",
+        kind,
+        decoded.declared_charset,
+        decoded.charset,
+        decoded.charset_guessed,
+        decoded.replacement_count,
+        decoded.malformed_sequences,
+        decoded.repaired_mojibake
+    );
+}
+
+/// Plain text body at `pos`, normalized to UTF-8
+pub fn body_text(message: &mail_parser::Message, pos: usize) -> Option<String> {
+    if let Some(&part_id) = message.text_body.get(pos) {
+        let is_text = matches!(message.parts.get(part_id).map(|p| &p.body), Some(PartType::Text(_)));
+        if is_text {
+            if let Some(decoded) = repair_part(message, part_id) {
+                log_repair("text", &decoded);
+                return Some(decoded.text);
+            }
+        }
+    }
+    message.body_text(pos).map(|s| s.to_string())
+}
+
+/// HTML body at `pos`, normalized to UTF-8
+pub fn body_html(message: &mail_parser::Message, pos: usize) -> Option<String> {
+    if let Some(&part_id) = message.html_body.get(pos) {
+        let is_html = matches!(message.parts.get(part_id).map(|p| &p.body), Some(PartType::Html(_)));
+        if is_html {
+            if let Some(decoded) = repair_part(message, part_id) {
+                log_repair("html", &decoded);
+                return Some(decoded.text);
+            }
+        }
+    }
+    message.body_html(pos).map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (description, raw bytes, declared charset, expected UTF-8 text)
+    const CORPUS: &[(&str, &[u8], Option<&str>, &str)] = &[
+        ("utf-8 turkish", "Günaydın, şimdi çığ öğle".as_bytes(), Some("utf-8"), "Günaydın, şimdi çığ öğle"),
+        ("iso-8859-9 turkish", b"G\xfcnayd\xfdn \xfe\xe7\xf0", Some("ISO-8859-9"), "Günaydın şçğ"),
+        ("windows-1254 turkish", b"\xdcr\xfcn \xd0\xfcvenli\xf0i", Some("windows-1254"), "Ürün Ğüvenliği"),
+        ("koi8-r russian", b"\xf0\xd2\xc9\xd7\xc5\xd4", Some("koi8-r"), "Привет"),
+        ("malformed label iso8859_9", b"\xfe", Some("iso8859_9"), "ş"),
+        ("malformed label utf8", "çay".as_bytes(), Some("UTF8"), "çay"),
+        ("quoted label", b"\xfd", Some("\"windows-1254\""), "ı"),
+        ("undeclared turkish", b"Te\xfeekk\xfcrler", None, "Teşekkürler"),
+        ("utf-8 mislabel on 1254 bytes", b"\xc7ok g\xfczel", Some("utf-8"), "Çok güzel"),
+        ("undeclared russian", b"\xf0\xd2\xc9\xd7\xc5\xd4 \xcd\xc9\xd2", None, "Привет мир"),
+        ("unknown charset ascii", b"plain text", Some("x-unknown-charset"), "plain text"),
+    ];
+
+    #[test]
+    fn test_decode_corpus() {
+        for (name, bytes, charset, expected) in CORPUS {
+            let decoded = decode_text(bytes, *charset);
+            assert_eq!(decoded.text, *expected, "corpus entry '{}' failed", name);
+            assert_eq!(decoded.replacement_count, 0, "corpus entry '{}' had replacements", name);
+        }
+    }
+
+    #[test]
+    fn test_guessed_charset_is_reported() {
+        let decoded = decode_text(b"Te\xfeekk\xfcrler", None);
+        assert!(decoded.charset_guessed);
+        assert_eq!(decoded.charset, "windows-1254");
+        assert!(decoded.has_problems());
+
+        let clean = decode_text("Merhaba".as_bytes(), Some("utf-8"));
+        assert!(!clean.has_problems());
+    }
+
+    #[test]
+    fn test_mojibake_repair() {
+        assert_eq!(repair_mojibake("TÃ¼rkÃ§e karakterler: Ä±ÅŸÄŸ").as_deref(), Some("Türkçe karakterler: ışğ"));
+        assert_eq!(repair_mojibake("Türkçe"), None);
+        // Legitimate Latin text must not be mangled
+        assert_eq!(repair_mojibake("Ã la carte"), None);
+    }
+
+    #[test]
+    fn test_quoted_printable_soft_breaks_and_malformed() {
+        let (bytes, malformed) = decode_quoted_printable_bytes(b"G=FCnayd=FDn=\r\n d=FCnya =ZZ", false);
+        assert_eq!(malformed, 1);
+        let decoded = decode_text(&bytes, Some("iso-8859-9"));
+        assert_eq!(decoded.text, "Günaydın dünya =ZZ");
+
+        let body = decode_body(b"=C3=A7ay=\nl=C4=B1", TransferEncoding::QuotedPrintable, Some("utf-8"));
+        assert_eq!(body.text, "çaylı");
+        assert_eq!(body.malformed_sequences, 0);
+    }
+
+    #[test]
+    fn test_base64_lenient() {
+        let body = decode_body(b"w6dh\r\neQ==\r\n", TransferEncoding::Base64, Some("utf-8"));
+        assert_eq!(body.text, "çay");
+
+        let (_, malformed) = decode_base64_lenient(b"w6dh*eQ==");
+        assert_eq!(malformed, 1);
+    }
+
+    #[test]
+    fn test_parsed_message_without_charset_is_normalized() {
+        let mut raw = b"From: a@example.com\r\nSubject: test\r\nContent-Type: text/plain\r\nContent-Transfer-Encoding: 8bit\r\n\r\n".to_vec();
+        raw.extend_from_slice(b"Te\xfeekk\xfcrler, g\xf6r\xfc\xfcr\xfcz");
+        let message = mail_parser::MessageParser::default().parse(&raw).unwrap();
+
+        assert_eq!(body_text(&message, 0).as_deref(), Some("Teşekkürler, görüürüz"));
+    }
+
+    #[test]
+    fn test_decode_encoded_words_with_charsets() {
+        assert_eq!(decode_encoded_words("=?ISO-8859-9?Q?G=FCnayd=FDn_d=FCnya?="), "Günaydın dünya");
+        assert_eq!(decode_encoded_words("=?windows-1254?B?VGX+ZWtr/HJsZXI=?="), "Teşekkürler");
+        assert_eq!(decode_encoded_words("=?UTF-8?B?w6dheQ==?= =?UTF-8?Q?_zaman=C4=B1?="), "çay zamanı");
+        assert_eq!(decode_encoded_words("Re: =?koi8-r?B?8NLJ18XU?= there"), "Re: Привет there");
+        assert_eq!(decode_encoded_words("No encoding here"), "No encoding here");
+    }
+}
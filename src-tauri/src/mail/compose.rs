@@ -0,0 +1,227 @@
+//! Outgoing Message Composition
+//!
+//! Builds MIME bodies for outgoing mail:
+//! mixed[ alternative[ text, related[ html, inline images ] ], attachments ]
+//! and handles cid: references for inline images.
+
+use crate::mail::{MailError, MailResult};
+use lettre::message::{header::ContentType, Attachment, MessageBuilder, MultiPart, SinglePart};
+use lettre::Message;
+use std::collections::HashMap;
+
+/// File part of an outgoing message
+#[derive(Debug, Clone)]
+pub struct OutgoingPart {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+    /// Content-ID for inline images referenced as `cid:` from the HTML body
+    pub content_id: Option<String>,
+}
+
+/// Strip angle brackets and whitespace from a Content-ID value
+pub fn normalize_content_id(content_id: &str) -> String {
+    content_id
+        .trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .to_string()
+}
+
+/// Generate a globally unique Content-ID for a new inline part
+pub fn generate_content_id(sender_email: &str) -> String {
+    let domain = sender_email.rsplit('@').next().unwrap_or("owlivion.local");
+    format!("{}@{}", uuid::Uuid::new_v4().simple(), domain)
+}
+
+/// Escape text for use in HTML content and attribute values
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Extract all `cid:` references from HTML (deduplicated, in order of appearance)
+pub fn extract_cid_references(html: &str) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    let lower = html.to_ascii_lowercase();
+    let mut search_from = 0;
+
+    while let Some(pos) = lower[search_from..].find("cid:") {
+        let start = search_from + pos + 4;
+        let end = html[start..]
+            .find(|c: char| c == '"' || c == '\'' || c == ')' || c == '>' || c.is_whitespace())
+            .map(|i| start + i)
+            .unwrap_or(html.len());
+
+        let cid = normalize_content_id(&html[start..end]);
+        if !cid.is_empty() && !result.contains(&cid) {
+            result.push(cid);
+        }
+        search_from = end;
+    }
+
+    result
+}
+
+/// Rewrite `cid:` references in HTML using an old -> new Content-ID mapping
+/// Used when quoting HTML that embeds inline images from another message
+pub fn rewrite_cid_references(html: &str, mapping: &HashMap<String, String>) -> String {
+    let mut result = html.to_string();
+    for (old_cid, new_cid) in mapping {
+        for prefix in ["cid:", "CID:", "Cid:"] {
+            result = result.replace(&format!("{}{}", prefix, old_cid), &format!("cid:{}", new_cid));
+        }
+    }
+    result
+}
+
+fn parse_content_type(content_type: &str) -> ContentType {
+    content_type
+        .parse()
+        .unwrap_or_else(|_| ContentType::parse("application/octet-stream").unwrap())
+}
+
+/// Build the final message body from text/HTML bodies and file parts
+/// Parts with a Content-ID are embedded as multipart/related when an HTML body exists,
+/// everything else is attached as a regular attachment
+pub fn build_message(
+    builder: MessageBuilder,
+    text_body: Option<String>,
+    html_body: Option<String>,
+    parts: Vec<OutgoingPart>,
+) -> MailResult<Message> {
+    let mut inline_parts = Vec::new();
+    let mut attachment_parts = Vec::new();
+
+    for part in parts {
+        let content_type = parse_content_type(&part.content_type);
+        match part.content_id {
+            Some(cid) if html_body.is_some() => {
+                inline_parts.push(
+                    Attachment::new_inline(normalize_content_id(&cid)).body(part.data, content_type),
+                );
+            }
+            _ => {
+                attachment_parts.push(Attachment::new(part.filename).body(part.data, content_type));
+            }
+        }
+    }
+
+    let html_single = html_body.map(|html| {
+        SinglePart::builder()
+            .header(ContentType::TEXT_HTML)
+            .body(html)
+    });
+
+    // HTML with inline images becomes multipart/related
+    let html_related = match html_single {
+        Some(html) if !inline_parts.is_empty() => {
+            let mut related = MultiPart::related().singlepart(html);
+            for inline in inline_parts {
+                related = related.singlepart(inline);
+            }
+            Some(Ok(related))
+        }
+        Some(html) => Some(Err(html)),
+        None => None,
+    };
+
+    let text_single = text_body.map(|text| {
+        SinglePart::builder()
+            .header(ContentType::TEXT_PLAIN)
+            .body(text)
+    });
+
+    // Ok(multipart) or Err(singlepart) for the readable body
+    let body: Result<MultiPart, SinglePart> = match (text_single, html_related) {
+        (Some(text), Some(html)) => {
+            let alternative = MultiPart::alternative().singlepart(text);
+            Ok(match html {
+                Ok(related) => alternative.multipart(related),
+                Err(single) => alternative.singlepart(single),
+            })
+        }
+        (None, Some(html)) => html,
+        (Some(text), None) => Err(text),
+        (None, None) => Err(SinglePart::builder()
+            .header(ContentType::TEXT_PLAIN)
+            .body(String::new())),
+    };
+
+    let message = if attachment_parts.is_empty() {
+        match body {
+            Ok(multi) => builder.multipart(multi),
+            Err(single) => builder.singlepart(single),
+        }
+    } else {
+        let mut mixed = match body {
+            Ok(multi) => MultiPart::mixed().multipart(multi),
+            Err(single) => MultiPart::mixed().singlepart(single),
+        };
+        for attachment in attachment_parts {
+            mixed = mixed.singlepart(attachment);
+        }
+        builder.multipart(mixed)
+    };
+
+    message.map_err(|e| MailError::Smtp(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_and_rewrite_cid_references() {
+        let html = r#"<p>Hi</p><img src="cid:logo@example.com"><img src='CID:<chart.png>'>
+            <div style="background: url(cid:logo@example.com)"></div>"#;
+
+        let cids = extract_cid_references(html);
+        assert_eq!(cids, vec!["logo@example.com".to_string(), "chart.png".to_string()]);
+
+        let mut mapping = HashMap::new();
+        mapping.insert("logo@example.com".to_string(), "new1@owlivion.com".to_string());
+        let rewritten = rewrite_cid_references(html, &mapping);
+        assert!(!rewritten.contains("cid:logo@example.com"));
+        assert_eq!(rewritten.matches("cid:new1@owlivion.com").count(), 2);
+    }
+
+    #[test]
+    fn test_build_message_with_inline_image_uses_related() {
+        let builder = Message::builder()
+            .from("sender@example.com".parse().unwrap())
+            .to("rcpt@example.com".parse().unwrap())
+            .subject("Inline");
+
+        let message = build_message(
+            builder,
+            Some("plain".to_string()),
+            Some(r#"<img src="cid:img1@example.com">"#.to_string()),
+            vec![
+                OutgoingPart {
+                    filename: "img.png".to_string(),
+                    content_type: "image/png".to_string(),
+                    data: vec![0x89, 0x50, 0x4e, 0x47],
+                    content_id: Some("<img1@example.com>".to_string()),
+                },
+                OutgoingPart {
+                    filename: "doc.pdf".to_string(),
+                    content_type: "application/pdf".to_string(),
+                    data: b"%PDF".to_vec(),
+                    content_id: None,
+                },
+            ],
+        )
+        .unwrap();
+
+        let raw = String::from_utf8_lossy(&message.formatted()).to_string();
+        assert!(raw.contains("multipart/mixed"));
+        assert!(raw.contains("multipart/alternative"));
+        assert!(raw.contains("multipart/related"));
+        assert!(raw.contains("Content-ID: <img1@example.com>"));
+        assert!(raw.contains("filename=\"doc.pdf\""));
+    }
+}
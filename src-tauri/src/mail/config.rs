@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 /// Security type for email connections
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "UPPERCASE")]
 pub enum SecurityType {
     #[default]
@@ -30,6 +31,13 @@ impl SecurityType {
     }
 }
 
+/// Whether a host is this machine (the only place plain connections are allowed)
+pub fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
 /// IMAP server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImapConfig {
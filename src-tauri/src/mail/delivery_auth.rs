@@ -0,0 +1,75 @@
+//! Delivery Authentication
+//!
+//! What the receiving server recorded about how a message reached it: the
+//! DKIM result from `Authentication-Results` and whether the last hop used
+//! TLS, from the topmost `Received` header. `None` means the headers do not
+//! say, which a policy requiring the check treats as a failure.
+
+use serde::{Deserialize, Serialize};
+
+/// Transport and signature checks of a received message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryAuth {
+    /// DKIM signature verified by the receiving server
+    pub dkim: Option<bool>,
+    /// Last hop delivered over TLS
+    pub tls: Option<bool>,
+}
+
+/// Markers a server writes into `Received` for TLS connections (lowercase)
+const TLS_MARKERS: &[&str] = &["esmtps", "esmtpsa", "utf8smtps", "lmtps", "using tls", "(version=tls", "tls1"];
+
+/// Header fields of a raw message with lowercase names
+fn header_fields(raw: &[u8]) -> Vec<(String, String)> {
+    super::headers::parse(raw).into_iter().map(|field| (field.name.to_lowercase(), field.value)).collect()
+}
+
+impl DeliveryAuth {
+    /// Read the checks from the headers of a raw message
+    pub fn from_raw(raw: &[u8]) -> Self {
+        let fields = header_fields(raw);
+
+        let mut dkim = None;
+        for (_, value) in fields.iter().filter(|(name, _)| name == "authentication-results") {
+            let value = value.to_lowercase();
+            if value.contains("dkim=pass") {
+                dkim = Some(true);
+                break;
+            }
+            if value.contains("dkim=") {
+                dkim = Some(false);
+            }
+        }
+
+        let tls = fields.iter().find(|(name, _)| name == "received").map(|(_, value)| {
+            let value = value.to_lowercase();
+            TLS_MARKERS.iter().any(|marker| value.contains(marker))
+        });
+
+        Self { dkim, tls }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_raw() {
+        let raw = b"Received: from mail.bank.test (mail.bank.test [192.0.2.1])\r\n\
+            \tby mx.example.com with ESMTPS id abc\r\n\
+            Received: from internal by mail.bank.test with ESMTP\r\n\
+            Authentication-Results: mx.example.com;\r\n\
+            \tdkim=pass header.d=bank.test; spf=pass\r\n\
+            Subject: Statement\r\n\r\nBody mentioning dkim=fail\r\n";
+        assert_eq!(DeliveryAuth::from_raw(raw), DeliveryAuth { dkim: Some(true), tls: Some(true) });
+
+        let raw = b"Received: from relay by mx.example.com with SMTP\r\n\
+            Authentication-Results: mx.example.com; dkim=fail reason=\"bad signature\"\r\n\r\n";
+        assert_eq!(DeliveryAuth::from_raw(raw), DeliveryAuth { dkim: Some(false), tls: Some(false) });
+
+        assert_eq!(DeliveryAuth::from_raw(b"Subject: Hi\r\n\r\n"), DeliveryAuth::default());
+    }
+}
@@ -0,0 +1,184 @@
+//! Folder Hierarchy
+//!
+//! IMAP lists mailboxes flat; nesting is only implied by the hierarchy
+//! delimiter in each path ("Clients/ACME/2024"). These helpers rebuild the
+//! tree, filling in ancestors the server did not list (\NonExistent or
+//! unsubscribed parents) so deep folders never end up detached at the root.
+
+use super::{Folder, FolderType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Folder with its nested children, as returned by `folder_list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+pub struct FolderNode {
+    #[serde(flatten)]
+    pub folder: Folder,
+    pub children: Vec<FolderNode>,
+}
+
+/// Path of the parent folder, if any
+pub fn parent_path<'a>(path: &'a str, delimiter: &str) -> Option<&'a str> {
+    if delimiter.is_empty() {
+        return None;
+    }
+    path.rsplit_once(delimiter)
+        .map(|(parent, _)| parent)
+        .filter(|parent| !parent.is_empty())
+}
+
+/// Last segment of a folder path
+pub fn leaf_name<'a>(path: &'a str, delimiter: &str) -> &'a str {
+    if delimiter.is_empty() {
+        return path;
+    }
+    path.rsplit(delimiter).next().unwrap_or(path)
+}
+
+/// Join a parent path and a child name
+pub fn join_path(parent: Option<&str>, name: &str, delimiter: &str) -> String {
+    match parent {
+        Some(parent) if !parent.is_empty() => format!("{}{}{}", parent, delimiter, name),
+        _ => name.to_string(),
+    }
+}
+
+/// Whether `path` is `ancestor` itself or lies below it
+pub fn is_within(path: &str, ancestor: &str, delimiter: &str) -> bool {
+    path == ancestor
+        || (!delimiter.is_empty()
+            && path.len() > ancestor.len() + delimiter.len()
+            && path.starts_with(ancestor)
+            && path[ancestor.len()..].starts_with(delimiter))
+}
+
+/// New path of `path` after its ancestor `old_root` was renamed to `new_root`
+pub fn rebase_path(path: &str, old_root: &str, new_root: &str, delimiter: &str) -> Option<String> {
+    if !is_within(path, old_root, delimiter) {
+        return None;
+    }
+    Some(format!("{}{}", new_root, &path[old_root.len()..]))
+}
+
+/// Build the folder tree, keeping the server's order among siblings
+pub fn build_tree(mut folders: Vec<Folder>) -> Vec<FolderNode> {
+    let mut known: HashMap<String, usize> = folders
+        .iter()
+        .enumerate()
+        .map(|(index, folder)| (folder.path.clone(), index))
+        .collect();
+
+    // Add placeholders for ancestors the server did not list
+    let mut index = 0;
+    while index < folders.len() {
+        let delimiter = folders[index].delimiter.clone();
+        let missing = parent_path(&folders[index].path, &delimiter)
+            .filter(|parent| !known.contains_key(*parent))
+            .map(str::to_string);
+
+        if let Some(parent) = missing {
+            known.insert(parent.clone(), folders.len());
+            folders.push(Folder {
+                name: leaf_name(&parent, &delimiter).to_string(),
+                path: parent,
+                folder_type: FolderType::Custom,
+                delimiter,
+                is_subscribed: false,
+                is_selectable: false,
+                unread_count: 0,
+                total_count: 0,
+                attributes: vec!["\\NonExistent".to_string()],
+            });
+        }
+        index += 1;
+    }
+
+    let mut children: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    for (index, folder) in folders.iter().enumerate() {
+        let parent = parent_path(&folder.path, &folder.delimiter).and_then(|p| known.get(p).copied());
+        children.entry(parent).or_default().push(index);
+    }
+
+    let mut slots: Vec<Option<Folder>> = folders.into_iter().map(Some).collect();
+    attach(None, &children, &mut slots)
+}
+
+fn attach(
+    parent: Option<usize>,
+    children: &HashMap<Option<usize>, Vec<usize>>,
+    slots: &mut [Option<Folder>],
+) -> Vec<FolderNode> {
+    let Some(indices) = children.get(&parent) else {
+        return Vec::new();
+    };
+
+    indices
+        .iter()
+        .filter_map(|&index| {
+            let folder = slots[index].take()?;
+            Some(FolderNode {
+                folder,
+                children: attach(Some(index), children, slots),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(path: &str, delimiter: &str) -> Folder {
+        Folder {
+            name: leaf_name(path, delimiter).to_string(),
+            path: path.to_string(),
+            folder_type: FolderType::from_name(path),
+            delimiter: delimiter.to_string(),
+            is_subscribed: true,
+            is_selectable: true,
+            unread_count: 0,
+            total_count: 0,
+            attributes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_build_tree_nests_and_fills_missing_parents() {
+        let tree = build_tree(vec![
+            folder("INBOX", "."),
+            folder("INBOX.Clients", "."),
+            folder("INBOX.Clients.ACME", "."),
+            folder("Projects.2024.Q1", "."),
+        ]);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].folder.path, "INBOX");
+        assert_eq!(tree[0].children[0].folder.path, "INBOX.Clients");
+        assert_eq!(tree[0].children[0].children[0].folder.name, "ACME");
+
+        // "Projects" and "Projects.2024" were never listed
+        let projects = &tree[1];
+        assert_eq!(projects.folder.path, "Projects");
+        assert!(!projects.folder.is_selectable);
+        assert_eq!(projects.children[0].folder.path, "Projects.2024");
+        assert_eq!(projects.children[0].children[0].folder.path, "Projects.2024.Q1");
+
+        let json = serde_json::to_value(&tree[0]).unwrap();
+        assert_eq!(json["path"], "INBOX");
+        assert_eq!(json["children"][0]["name"], "Clients");
+    }
+
+    #[test]
+    fn test_path_helpers() {
+        assert_eq!(parent_path("A/B/C", "/"), Some("A/B"));
+        assert_eq!(parent_path("A", "/"), None);
+        assert_eq!(join_path(Some("A/B"), "D", "/"), "A/B/D");
+        assert_eq!(join_path(None, "D", "/"), "D");
+
+        assert!(is_within("A/B/C", "A/B", "/"));
+        assert!(!is_within("A/Bee", "A/B", "/"));
+        assert_eq!(rebase_path("A/B/C", "A/B", "X", "/"), Some("X/C".to_string()));
+        assert_eq!(rebase_path("A/Bee", "A/B", "X", "/"), None);
+    }
+}
@@ -0,0 +1,49 @@
+//! Message Headers
+//!
+//! The header block of a raw message as name/value pairs, for the "Show
+//! headers" view and for checks that read individual fields. Folded lines are
+//! joined; values are kept as sent (encoded words are not decoded).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct HeaderField {
+    pub name: String,
+    pub value: String,
+}
+
+/// Unfolded header fields of a raw message, in order
+pub fn parse(raw: &[u8]) -> Vec<HeaderField> {
+    let text = String::from_utf8_lossy(raw);
+    let mut fields: Vec<HeaderField> = Vec::new();
+    for line in text.lines() {
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with([' ', '\t']) {
+            if let Some(field) = fields.last_mut() {
+                field.value.push(' ');
+                field.value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            fields.push(HeaderField { name: name.trim().to_string(), value: value.trim().to_string() });
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unfolds_and_stops_at_body() {
+        let raw = b"Subject: Quarterly\r\n report\r\nX-Spam-Score: 1.2\r\n\r\nBody: not a header\r\n";
+        let fields = parse(raw);
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0], HeaderField { name: "Subject".to_string(), value: "Quarterly report".to_string() });
+        assert_eq!(fields[1].name, "X-Spam-Score");
+    }
+}
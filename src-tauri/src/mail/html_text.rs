@@ -0,0 +1,434 @@
+//! HTML to Plain Text
+//!
+//! Generates the text/plain alternative of an HTML message. Block elements
+//! become line breaks and blank lines, lists keep their bullets and numbers
+//! (indented when nested), blockquotes are quoted with `>`, and links are
+//! numbered with their targets listed as footnotes at the end. Scripts,
+//! styles and the document head are dropped.
+
+/// Elements whose content is not text
+const SKIPPED: &[&str] = &["head", "style", "script", "title", "template"];
+
+/// Elements that start and end on their own line
+const BLOCKS: &[&str] = &[
+    "div", "section", "article", "header", "footer", "nav", "aside", "main", "table", "tr", "dl", "dt", "dd", "center",
+    "address", "figure", "figcaption", "form",
+];
+
+/// Elements separated from their surroundings by a blank line
+const PARAGRAPHS: &[&str] = &["p", "h1", "h2", "h3", "h4", "h5", "h6", "pre", "ul", "ol", "blockquote"];
+
+/// Builds the text line by line
+#[derive(Default)]
+struct Writer {
+    lines: Vec<String>,
+    current: String,
+    /// Whitespace seen since the last word
+    space: bool,
+    /// Blank line wanted before the next text, quoted to this depth
+    blank: Option<usize>,
+    quote_depth: usize,
+    /// Open lists: None = bulleted, Some(n) = numbered, next number n
+    lists: Vec<Option<u32>>,
+    /// Bullet or number of a list item that has no text yet
+    marker: Option<String>,
+    pre: usize,
+    skip: usize,
+    /// Open link: target and text seen so far
+    link: Option<(String, String)>,
+    footnotes: Vec<String>,
+}
+
+impl Writer {
+    fn quote_prefix(&self) -> String {
+        ">".repeat(self.quote_depth)
+    }
+
+    /// Start a line's content: quote marks, list indent, bullet
+    fn start_line(&mut self) {
+        if let Some(depth) = self.blank.take() {
+            if self.lines.last().is_some_and(|line| !line.trim_start_matches('>').is_empty()) {
+                self.lines.push(">".repeat(depth));
+            }
+        }
+
+        let quote = self.quote_prefix();
+        if !quote.is_empty() {
+            self.current.push_str(&quote);
+            self.current.push(' ');
+        }
+        let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+        match self.marker.take() {
+            Some(marker) => {
+                self.current.push_str(&indent);
+                self.current.push_str(&marker);
+            }
+            None if !self.lists.is_empty() => {
+                self.current.push_str(&indent);
+                self.current.push_str("  ");
+            }
+            None => {}
+        }
+    }
+
+    fn line_started(&self) -> bool {
+        !self.current.is_empty()
+    }
+
+    fn push_char(&mut self, c: char) {
+        if !self.line_started() {
+            self.start_line();
+        }
+        self.current.push(c);
+        if let Some((_, text)) = &mut self.link {
+            text.push(c);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip > 0 {
+            return;
+        }
+        for c in text.chars() {
+            if self.pre > 0 {
+                if c == '\n' {
+                    self.end_line(true);
+                } else if c != '\r' {
+                    self.push_char(c);
+                }
+            } else if c.is_whitespace() {
+                self.space = true;
+            } else {
+                if self.space && self.line_started() && !self.current.ends_with(' ') && self.marker.is_none() {
+                    self.push_char(' ');
+                }
+                self.space = false;
+                self.push_char(c);
+            }
+        }
+    }
+
+    /// Finish the current line (`force` keeps empty lines, inside `<pre>`)
+    fn end_line(&mut self, force: bool) {
+        if self.line_started() {
+            let line = std::mem::take(&mut self.current);
+            self.lines.push(line.trim_end().to_string());
+        } else if force {
+            self.start_line();
+            let line = std::mem::take(&mut self.current);
+            self.lines.push(line.trim_end().to_string());
+        }
+        self.space = false;
+    }
+
+    fn paragraph(&mut self) {
+        self.end_line(false);
+        if !self.lines.is_empty() {
+            self.blank = Some(self.blank.map_or(self.quote_depth, |depth| depth.min(self.quote_depth)));
+        }
+    }
+
+    fn open(&mut self, name: &str, tag: &str) {
+        if SKIPPED.contains(&name) {
+            self.skip += 1;
+            return;
+        }
+        match name {
+            "br" => self.end_line(true),
+            "hr" => {
+                self.paragraph();
+                self.text("----");
+                self.paragraph();
+            }
+            "li" => {
+                self.end_line(false);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.marker = Some(marker);
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.paragraph();
+                } else {
+                    self.end_line(false);
+                }
+                let start = attr(tag, "start").and_then(|start| start.parse().ok()).unwrap_or(1);
+                self.lists.push((name == "ol").then_some(start));
+            }
+            "blockquote" => {
+                self.paragraph();
+                self.quote_depth += 1;
+            }
+            "pre" => {
+                self.paragraph();
+                self.pre += 1;
+            }
+            // Cells of a row are separated by a space
+            "td" | "th" if self.line_started() => self.space = true,
+            "a" => {
+                if let Some(href) = attr(tag, "href") {
+                    self.link = Some((href, String::new()));
+                }
+            }
+            "img" => {
+                if let Some(alt) = attr(tag, "alt").filter(|alt| !alt.trim().is_empty()) {
+                    self.text(&format!(" [{}] ", alt.trim()));
+                }
+            }
+            _ if PARAGRAPHS.contains(&name) => self.paragraph(),
+            _ if BLOCKS.contains(&name) => self.end_line(false),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if SKIPPED.contains(&name) {
+            self.skip = self.skip.saturating_sub(1);
+            return;
+        }
+        match name {
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.marker = None;
+                if self.lists.is_empty() {
+                    self.paragraph();
+                } else {
+                    self.end_line(false);
+                }
+            }
+            "li" => self.end_line(false),
+            "blockquote" => {
+                self.end_line(false);
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.paragraph();
+            }
+            "pre" => {
+                self.pre = self.pre.saturating_sub(1);
+                self.paragraph();
+            }
+            "a" => {
+                if let Some((href, text)) = self.link.take() {
+                    if let Some(footnote) = footnote(&href, &text) {
+                        self.footnotes.push(footnote);
+                        let number = format!("[{}]", self.footnotes.len());
+                        self.text(&number);
+                    }
+                }
+            }
+            _ if PARAGRAPHS.contains(&name) => self.paragraph(),
+            _ if BLOCKS.contains(&name) => self.end_line(false),
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.end_line(false);
+        while self.lines.last().is_some_and(|line| line.is_empty()) {
+            self.lines.pop();
+        }
+        let mut text = self.lines.join("\n").replace('\u{a0}', " ");
+        if !self.footnotes.is_empty() {
+            text.push_str("\n\n");
+            let notes: Vec<String> =
+                self.footnotes.iter().enumerate().map(|(n, href)| format!("[{}] {}", n + 1, href)).collect();
+            text.push_str(&notes.join("\n"));
+        }
+        text
+    }
+}
+
+/// Link target worth a footnote: not an in-page anchor or script, and not
+/// already spelled out in the link text
+fn footnote(href: &str, text: &str) -> Option<String> {
+    let href = href.trim();
+    let lower = href.to_lowercase();
+    if href.is_empty() || href.starts_with('#') || lower.starts_with("javascript:") || lower.starts_with("cid:") {
+        return None;
+    }
+    let shown = text.trim().trim_end_matches('/').to_lowercase();
+    let target = lower.trim_start_matches("mailto:").trim_start_matches("tel:").trim_end_matches('/');
+    let bare = target.trim_start_matches("https://").trim_start_matches("http://");
+    (shown != target && shown != bare).then(|| href.to_string())
+}
+
+/// Value of an attribute in the source of a start tag
+fn attr(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(name) {
+        let start = from + pos;
+        from = start + name.len();
+        let before_ok = lower[..start].ends_with(|c: char| c.is_whitespace());
+        let rest = lower[from..].trim_start();
+        if !before_ok || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = tag.len() - rest.len() + 1;
+        let value = tag[value_start..].trim_start();
+        let parsed = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value.split(|c: char| c.is_whitespace() || c == '>' || c == '/').next().unwrap_or_default(),
+        };
+        return Some(decode_entities(parsed));
+    }
+    None
+}
+
+/// Decode character references
+pub fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('&') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let decoded = rest[1..].find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..=end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                "ndash" => Some('–'),
+                "mdash" => Some('—'),
+                "hellip" => Some('…'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                "bull" => Some('•'),
+                "copy" => Some('©'),
+                "reg" => Some('®'),
+                "euro" => Some('€'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 2))
+        });
+        match decoded {
+            Some((c, len)) => {
+                result.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Plain-text rendering of an HTML body
+pub fn html_to_text(html: &str) -> String {
+    let mut writer = Writer::default();
+    let mut rest = html;
+
+    while let Some(pos) = rest.find('<') {
+        writer.text(&decode_entities(&rest[..pos]));
+        rest = &rest[pos..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map(|end| &comment[end + 3..]).unwrap_or("");
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            writer.text(&decode_entities(rest));
+            rest = "";
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let (closing, body) = match tag.strip_prefix('/') {
+            Some(body) => (true, body),
+            None => (false, tag),
+        };
+        let name: String = body
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            // `<!DOCTYPE>`, `<?xml?>` or a stray '<'
+            if !tag.starts_with(['!', '?']) {
+                writer.text(&format!("<{}>", tag));
+            }
+            continue;
+        }
+
+        if closing {
+            writer.close(&name);
+        } else {
+            writer.open(&name, tag);
+            if tag.ends_with('/') && name != "br" && name != "hr" && name != "img" {
+                writer.close(&name);
+            }
+        }
+    }
+    writer.text(&decode_entities(rest));
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structure() {
+        let html = r#"<html><head><style>p { color: red }</style></head><body>
+            <h1>Weekly&nbsp;update</h1>
+            <p>Hello <b>team</b>,<br>numbers are   in.</p>
+            <ul><li>Sales up</li><li>Costs <i>down</i>
+              <ol start="3"><li>Rent</li><li>Travel</li></ol></li></ul>
+            <p>See <a href="https://example.com/report">the report</a> or
+               <a href="https://example.com">example.com</a> &amp; <a href="mailto:bob@example.com">bob@example.com</a>.</p>
+            </body></html>"#;
+        assert_eq!(
+            html_to_text(html),
+            "Weekly update\n\
+             \n\
+             Hello team,\n\
+             numbers are in.\n\
+             \n\
+             - Sales up\n\
+             - Costs down\n\
+             \x20 3. Rent\n\
+             \x20 4. Travel\n\
+             \n\
+             See the report[1] or example.com & bob@example.com.\n\
+             \n\
+             [1] https://example.com/report"
+        );
+    }
+
+    #[test]
+    fn test_quotes_and_pre() {
+        let html = "<p>Sounds good.</p><blockquote><p>Can we meet?</p><blockquote>Monday works</blockquote></blockquote>\
+                    <pre>line 1\n  line 2</pre><!-- hidden --><p>&#169; &#x263A;</p>";
+        assert_eq!(
+            html_to_text(html),
+            "Sounds good.\n\
+             \n\
+             > Can we meet?\n\
+             >\n\
+             >> Monday works\n\
+             \n\
+             line 1\n\
+             \x20 line 2\n\
+             \n\
+             © ☺"
+        );
+    }
+}
@@ -4,8 +4,8 @@
 
 use crate::mail::{
     config::{ImapConfig, SecurityType},
-    EmailAttachment, EmailSummary, FetchResult, Folder, FolderType, MailError, MailResult,
-    ParsedEmail,
+    bounce, calendar, headers, special_use, EmailAttachment, EmailSummary, FetchResult, Folder, FolderType, MailError,
+    MailResult, MessageStructure, ParsedEmail, DeliveryAuth, quotes::BodyStructure,
 };
 use imap::Session;
 use mail_parser::MimeHeaders;
@@ -122,6 +122,11 @@ impl ImapClient {
             .map(|mb| {
                 let name = mb.name().to_string();
                 let delimiter = mb.delimiter().map(|d| d.to_string()).unwrap_or("/".to_string());
+                let attributes: Vec<String> = mb
+                    .attributes()
+                    .iter()
+                    .map(special_use::imap_attribute)
+                    .collect();
 
                 Folder {
                     name: name.split(&delimiter).last().unwrap_or(&name).to_string(),
@@ -129,15 +134,16 @@ impl ImapClient {
                     folder_type: FolderType::from_name(&name),
                     delimiter,
                     is_subscribed: true,
-                    is_selectable: !mb.attributes().iter().any(|a| {
-                        matches!(a, imap::types::NameAttribute::NoSelect)
-                    }),
+                    is_selectable: !special_use::is_noselect(&attributes),
                     unread_count: 0,
                     total_count: 0,
+                    attributes,
                 }
             })
             .collect();
 
+        special_use::classify_folders(&mut folders);
+
         // Get message counts for each selectable folder
         for folder in &mut folders {
             if folder.is_selectable {
@@ -270,6 +276,10 @@ impl ImapClient {
 
             let is_read = flags.iter().any(|f| matches!(f, imap::types::Flag::Seen));
             let is_starred = flags.iter().any(|f| matches!(f, imap::types::Flag::Flagged));
+            let keywords = super::keywords::from_flags(flags.iter().filter_map(|f| match f {
+                imap::types::Flag::Custom(keyword) => Some(keyword.as_ref()),
+                _ => None,
+            }));
 
             if let Some(envelope) = message.envelope() {
                 let from = envelope
@@ -322,6 +332,8 @@ impl ImapClient {
                     is_read,
                     is_starred,
                     has_attachments: false, // Would need BODYSTRUCTURE to detect
+                    size: message.size.unwrap_or(0),
+                    keywords,
                     account_id: None, // Not used in sync imap client
                     account_email: None,
                     account_name: None,
@@ -447,6 +459,10 @@ impl ImapClient {
         // Parse body using mail-parser
         let body = message.body().unwrap_or(&[]);
         let (body_text, body_html, attachments) = parse_email_body(body);
+        let delivery = DeliveryAuth::from_raw(body);
+        let structure = MessageStructure::from_raw(body);
+        let headers = headers::parse(body);
+        let quotes = BodyStructure::analyze(body_text.as_deref(), body_html.as_deref());
 
         Ok(ParsedEmail {
             uid,
@@ -462,6 +478,14 @@ impl ImapClient {
             is_read,
             is_starred,
             attachments,
+            delivery,
+            quotes,
+            structure,
+            policy: None,
+            size: body.len() as u32,
+            headers,
+            calendar: calendar::from_raw(body),
+            bounced: bounce::from_raw(body),
         })
     }
 
@@ -614,56 +638,9 @@ impl ImapClient {
     }
 }
 
-/// Decode MIME encoded header (RFC 2047)
+/// Decode MIME encoded header (RFC 2047), honouring the declared charset
 fn decode_mime_header(input: &str) -> String {
-    // Simple decoder for =?charset?encoding?text?= format
-    if !input.contains("=?") {
-        return input.to_string();
-    }
-
-    let mut result = input.to_string();
-
-    // Handle UTF-8 Base64 encoded strings
-    if let Ok(re_b64) = regex_lite::Regex::new(r"=\?([^?]+)\?[Bb]\?([^?]+)\?=") {
-        result = re_b64.replace_all(&result, |caps: &regex_lite::Captures| {
-            let encoded = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
-                .ok()
-                .and_then(|bytes| String::from_utf8(bytes).ok())
-                .unwrap_or_else(|| encoded.to_string())
-        }).to_string();
-    }
-
-    // Handle quoted-printable
-    if let Ok(re_qp) = regex_lite::Regex::new(r"=\?([^?]+)\?[Qq]\?([^?]+)\?=") {
-        result = re_qp.replace_all(&result, |caps: &regex_lite::Captures| {
-            let encoded = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-            decode_quoted_printable(encoded)
-        }).to_string();
-    }
-
-    result.replace("_", " ")
-}
-
-/// Decode quoted-printable string
-fn decode_quoted_printable(input: &str) -> String {
-    let mut result = String::new();
-    let mut chars = input.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        if c == '=' {
-            let hex: String = chars.by_ref().take(2).collect();
-            if let Ok(byte) = u8::from_str_radix(&hex, 16) {
-                result.push(byte as char);
-            }
-        } else if c == '_' {
-            result.push(' ');
-        } else {
-            result.push(c);
-        }
-    }
-
-    result
+    super::charset::decode_encoded_words(input)
 }
 
 /// Sanitize string for IMAP commands to prevent injection attacks
@@ -704,10 +681,10 @@ fn parse_email_body(body: &[u8]) -> (Option<String>, Option<String>, Vec<EmailAt
 
     if let Some(message) = mail_parser::MessageParser::default().parse(body) {
         // Get text body
-        body_text = message.body_text(0).map(|s| s.to_string());
+        body_text = super::charset::body_text(&message, 0);
 
         // Get HTML body
-        body_html = message.body_html(0).map(|s| s.to_string());
+        body_html = super::charset::body_html(&message, 0);
 
         // Get attachments
         for (index, attachment) in message.attachments().enumerate() {
@@ -0,0 +1,250 @@
+//! IMAP Keywords
+//!
+//! Custom flags (RFC 3501 keywords) shared through the server: tags another
+//! client or a teammate sets on a message in a shared mailbox. Keywords seen
+//! on fetch are kept in `emails.keywords` and shown as labels; a keyword
+//! removed on the server takes its label away again, while labels added
+//! locally (e.g. by filters) stay. Thunderbird's `$label1`..`$label5` get
+//! their usual names. Keywords that carry client state rather than a tag,
+//! like `$Forwarded` or `$Junk`, are not shown.
+
+use crate::db::{Database, DbError, DbResult};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Keywords clients use for message state, not as tags
+const STATE_KEYWORDS: &[&str] = &[
+    "$Forwarded",
+    "$MDNSent",
+    "$Junk",
+    "$NotJunk",
+    "Junk",
+    "NonJunk",
+    "NotJunk",
+    "$Phishing",
+    "$SubmitPending",
+    "$Submitted",
+    "$HasAttachment",
+    "$HasNoAttachment",
+];
+
+/// Thunderbird's predefined tags
+const THUNDERBIRD_LABELS: [(&str, &str); 5] = [
+    ("$label1", "Important"),
+    ("$label2", "Work"),
+    ("$label3", "Personal"),
+    ("$label4", "To Do"),
+    ("$label5", "Later"),
+];
+
+/// Longest keyword accepted when tagging
+const MAX_KEYWORD_LEN: usize = 64;
+
+/// Tag keywords among the custom flags of a message (system flags and
+/// client state keywords left out)
+pub fn from_flags<'a>(flags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for flag in flags {
+        let flag = flag.trim();
+        if flag.is_empty()
+            || flag.starts_with('\\')
+            || STATE_KEYWORDS.iter().any(|state| state.eq_ignore_ascii_case(flag))
+            || keywords.iter().any(|seen| seen.eq_ignore_ascii_case(flag))
+        {
+            continue;
+        }
+        keywords.push(flag.to_string());
+    }
+    keywords
+}
+
+/// Label shown for a keyword
+pub fn label_for(keyword: &str) -> String {
+    THUNDERBIRD_LABELS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(keyword))
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| keyword.to_string())
+}
+
+/// Keyword stored on the server for a label
+/// Spaces become underscores; other characters an IMAP atom cannot hold are refused.
+pub fn keyword_for(label: &str) -> Result<String, String> {
+    let label = label.trim();
+    if let Some((keyword, _)) = THUNDERBIRD_LABELS.iter().find(|(_, name)| name.eq_ignore_ascii_case(label)) {
+        return Ok(keyword.to_string());
+    }
+
+    let keyword = label.replace(' ', "_");
+    validate(&keyword)?;
+    Ok(keyword)
+}
+
+/// Check that a keyword can be sent as a tag (an IMAP atom, not a system
+/// flag or a client state keyword)
+pub fn validate(keyword: &str) -> Result<(), String> {
+    if keyword.is_empty() || keyword.len() > MAX_KEYWORD_LEN {
+        return Err(format!("Tag must be 1 to {} characters", MAX_KEYWORD_LEN));
+    }
+    if keyword.starts_with('\\') || STATE_KEYWORDS.iter().any(|state| state.eq_ignore_ascii_case(keyword)) {
+        return Err(format!("'{}' is reserved and cannot be used as a tag", keyword));
+    }
+    // atom-specials of RFC 3501, plus ']' which is not allowed in flags
+    if let Some(c) = keyword
+        .chars()
+        .find(|c| !c.is_ascii() || c.is_ascii_control() || c.is_ascii_whitespace() || "(){%*\"\\]".contains(*c))
+    {
+        return Err(format!("Tags cannot contain '{}'", c.escape_default()));
+    }
+    Ok(())
+}
+
+/// Whether the folder keeps this keyword, given its PERMANENTFLAGS
+/// (`\*` allows new keywords; no PERMANENTFLAGS at all means no restriction)
+pub fn allowed(permanent_flags: &[String], keyword: &str) -> bool {
+    permanent_flags.is_empty()
+        || permanent_flags
+            .iter()
+            .any(|flag| flag == "\\*" || flag.eq_ignore_ascii_case(keyword))
+}
+
+/// Tags a folder offers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct FolderKeywords {
+    /// New keywords can be created (PERMANENTFLAGS has `\*` or is absent)
+    pub allows_new: bool,
+    /// Keywords already defined in the folder
+    pub keywords: Vec<String>,
+    /// Their labels, in the same order
+    pub labels: Vec<String>,
+}
+
+impl FolderKeywords {
+    /// From the folder's PERMANENTFLAGS and FLAGS as reported on SELECT
+    pub fn new(permanent_flags: &[String], flags: &[String]) -> Self {
+        let keywords = from_flags(flags.iter().chain(permanent_flags).map(String::as_str));
+        Self {
+            allows_new: permanent_flags.is_empty() || permanent_flags.iter().any(|flag| flag == "\\*"),
+            labels: keywords.iter().map(|keyword| label_for(keyword)).collect(),
+            keywords,
+        }
+    }
+}
+
+/// Labels after the server's keywords changed from `old` to `new`
+fn merge_labels(labels: &[String], old: &[String], new: &[String]) -> Vec<String> {
+    let contains = |list: &[String], keyword: &str| list.iter().any(|k| k.eq_ignore_ascii_case(keyword));
+    let removed: Vec<String> = old.iter().filter(|k| !contains(new, k)).map(|k| label_for(k)).collect();
+
+    let mut merged: Vec<String> = labels.iter().filter(|label| !removed.contains(label)).cloned().collect();
+    for keyword in new {
+        let label = label_for(keyword);
+        if !merged.contains(&label) {
+            merged.push(label);
+        }
+    }
+    merged
+}
+
+fn parse_list(json: &str) -> Vec<String> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+fn to_json(list: &[String]) -> DbResult<String> {
+    serde_json::to_string(list).map_err(|e| DbError::Serialization(e.to_string()))
+}
+
+/// Store the keywords the server reported for a cached email
+/// Returns the new labels if they changed.
+pub fn store(db: &Database, email_id: i64, keywords: &[String]) -> DbResult<Option<Vec<String>>> {
+    let (labels, stored): (String, String) = db.query_row(
+        "SELECT labels, keywords FROM emails WHERE id = ?1",
+        params![email_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let (labels, stored) = (parse_list(&labels), parse_list(&stored));
+    if stored == keywords {
+        return Ok(None);
+    }
+
+    let merged = merge_labels(&labels, &stored, keywords);
+    db.execute(
+        "UPDATE emails SET labels = ?1, keywords = ?2 WHERE id = ?3",
+        params![to_json(&merged)?, to_json(keywords)?, email_id],
+    )?;
+    Ok((merged != labels).then_some(merged))
+}
+
+/// Store the keywords of messages fetched from a folder and cached
+pub fn store_all(db: &Database, account_id: i64, folder_id: i64, summaries: &[super::EmailSummary]) {
+    for summary in summaries {
+        let result = db
+            .query_row(
+                "SELECT id FROM emails WHERE account_id = ?1 AND folder_id = ?2 AND uid = ?3",
+                params![account_id, folder_id, summary.uid],
+                |row| row.get::<_, i64>(0),
+            )
+            .and_then(|email_id| store(db, email_id, &summary.keywords));
+        if let Err(e) = result {
+            log::warn!("Failed to store keywords of UID {}: {}", summary.uid, e);
+        }
+    }
+}
+
+/// Keywords of a cached email as last seen on the server
+pub fn get(db: &Database, email_id: i64) -> DbResult<Vec<String>> {
+    let stored: String = db.query_row("SELECT keywords FROM emails WHERE id = ?1", params![email_id], |row| row.get(0))?;
+    Ok(parse_list(&stored))
+}
+
+/// Whether a cached email carries a keyword
+pub fn has_keyword(db: &Database, email_id: i64, keyword: &str) -> DbResult<bool> {
+    Ok(get(db, email_id)?.iter().any(|k| k.eq_ignore_ascii_case(keyword)))
+}
+
+/// Add or remove a keyword on a cached email ahead of the server
+/// Returns the email's labels afterwards.
+pub fn set_local(db: &Database, email_id: i64, keyword: &str, value: bool) -> DbResult<Vec<String>> {
+    let mut keywords: Vec<String> = get(db, email_id)?.into_iter().filter(|k| !k.eq_ignore_ascii_case(keyword)).collect();
+    if value {
+        keywords.push(keyword.to_string());
+    }
+    store(db, email_id, &keywords)?;
+
+    let labels: String = db.query_row("SELECT labels FROM emails WHERE id = ?1", params![email_id], |row| row.get(0))?;
+    Ok(parse_list(&labels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords_and_labels() {
+        let keywords = from_flags(["\\Seen", "$label1", "Project_X", "$Forwarded", "project_x", "\\Recent"]);
+        assert_eq!(keywords, ["$label1", "Project_X"]);
+        assert_eq!(label_for("$Label1"), "Important");
+        assert_eq!(label_for("Project_X"), "Project_X");
+
+        assert_eq!(keyword_for("To Do").unwrap(), "$label4");
+        assert_eq!(keyword_for("Client review").unwrap(), "Client_review");
+        assert!(keyword_for("bad(tag)").is_err());
+        assert!(keyword_for("\\Seen").is_err());
+        assert!(keyword_for("$Junk").is_err());
+
+        assert!(allowed(&[], "Anything"));
+        assert!(allowed(&["\\Seen".to_string(), "\\*".to_string()], "New_tag"));
+        assert!(allowed(&["\\Seen".to_string(), "Project_X".to_string()], "project_x"));
+        assert!(!allowed(&["\\Seen".to_string(), "\\Flagged".to_string()], "New_tag"));
+        let folder = FolderKeywords::new(&["\\Seen".to_string(), "$label2".to_string()], &["\\Seen".to_string(), "Billing".to_string()]);
+        assert!(!folder.allows_new);
+        assert_eq!(folder.labels, ["Billing", "Work"]);
+
+        // Local labels survive, labels of keywords removed on the server go
+        let labels = vec!["Newsletter".to_string(), "Important".to_string()];
+        let merged = merge_labels(&labels, &["$label1".to_string()], &["Team".to_string()]);
+        assert_eq!(merged, ["Newsletter", "Team"]);
+    }
+}
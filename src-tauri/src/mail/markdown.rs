@@ -0,0 +1,88 @@
+//! Markdown Composition
+//!
+//! Renders a Markdown body (CommonMark with tables, strikethrough and task
+//! lists) to the HTML part of an outgoing message, and derives the plain-text
+//! part from that HTML so both render the same way. Raw HTML in the source
+//! is shown as text rather than passed through, and links or images with a
+//! scheme other than http(s), mailto, tel or cid are neutralised.
+
+use crate::mail::html_text::html_to_text;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+
+/// Bodies rendered from Markdown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedBody {
+    pub html: String,
+    pub text: String,
+}
+
+/// Link targets kept as written
+fn is_safe_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    match lower.split_once(':') {
+        // No scheme, or a colon after the first path segment: relative
+        None => true,
+        Some((scheme, _)) if scheme.contains(['/', '?', '#']) => true,
+        Some((scheme, _)) => matches!(scheme, "http" | "https" | "mailto" | "tel" | "cid"),
+    }
+}
+
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    if is_safe_url(&url) {
+        url
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+/// HTML for a Markdown body
+pub fn to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+            Event::Start(Tag::Link { link_type, dest_url: safe_url(dest_url), title, id })
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+            Event::Start(Tag::Image { link_type, dest_url: safe_url(dest_url), title, id })
+        }
+        event => event,
+    });
+
+    let mut output = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut output, events);
+    output
+}
+
+/// HTML and plain-text parts for a Markdown body
+pub fn render(markdown: &str) -> RenderedBody {
+    let html = to_html(markdown);
+    let text = html_to_text(&html);
+    RenderedBody { html, text }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let rendered = render("# Plan\n\nShip **on Friday**, see [notes](https://example.com/notes).\n\n- one\n- two\n\n> quoted\n");
+        assert!(rendered.html.contains("<h1>Plan</h1>"));
+        assert!(rendered.html.contains("<strong>on Friday</strong>"));
+        assert!(rendered.html.contains("<a href=\"https://example.com/notes\">notes</a>"));
+        assert_eq!(
+            rendered.text,
+            "Plan\n\nShip on Friday, see notes[1].\n\n- one\n- two\n\n> quoted\n\n[1] https://example.com/notes"
+        );
+    }
+
+    #[test]
+    fn test_sanitized() {
+        let html = to_html("<script>alert(1)</script>\n\n[x](javascript:alert(1)) ![y](data:image/png;base64,AA) <b>hi</b>");
+        assert!(!html.contains("<script>") && html.contains("&lt;script&gt;"));
+        assert!(!html.contains("javascript:") && !html.contains("data:"));
+        assert!(html.contains("&lt;b&gt;hi&lt;/b&gt;"));
+        assert!(is_safe_url("mailto:a@b.test") && is_safe_url("/path?x=a:b") && is_safe_url("cid:logo"));
+    }
+}
@@ -0,0 +1,60 @@
+//! mbox Export
+//!
+//! Writes raw RFC 822 messages in mboxrd format (RFC 4155), the format
+//! understood by Thunderbird, mutt and most mail archivers.
+
+use chrono::{DateTime, Utc};
+
+/// Append one message to an mboxrd buffer
+/// `sender` and `date` are used for the "From " separator line
+pub fn append_message(out: &mut Vec<u8>, sender: &str, date: &str, raw: &[u8]) {
+    let sender = if sender.trim().is_empty() || sender.contains(char::is_whitespace) {
+        "MAILER-DAEMON"
+    } else {
+        sender
+    };
+
+    let date = DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+
+    out.extend_from_slice(format!("From {} {}\n", sender, date.format("%a %b %e %H:%M:%S %Y")).as_bytes());
+
+    for line in raw.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        // mboxrd: quote "From " lines (and already quoted ">From " lines) with one more '>'
+        let unquoted = line.iter().position(|b| *b != b'>').map(|i| &line[i..]).unwrap_or(&[]);
+        if unquoted.starts_with(b"From ") {
+            out.push(b'>');
+        }
+
+        out.extend_from_slice(line);
+        out.push(b'\n');
+    }
+
+    // Messages are separated by an empty line
+    if !out.ends_with(b"\n\n") {
+        out.push(b'\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_message_quotes_from_lines() {
+        let raw = b"From: a@example.com\r\nSubject: hi\r\n\r\nFrom here on\r\n>From quoted\r\nbye\r\n";
+        let mut out = Vec::new();
+        append_message(&mut out, "a@example.com", "Tue, 1 Jul 2025 10:00:00 +0000", raw);
+
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("From a@example.com Tue Jul  1 10:00:00 2025\n"));
+        assert!(text.contains("\nFrom: a@example.com\n"));
+        assert!(text.contains("\n>From here on\n"));
+        assert!(text.contains("\n>>From quoted\n"));
+        assert!(text.ends_with("bye\n\n"));
+    }
+}
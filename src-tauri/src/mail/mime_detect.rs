@@ -0,0 +1,239 @@
+//! MIME Type Detection
+//!
+//! Content types of attachments come from the frontend (the browser's guess
+//! from the file name) and are not trusted. The type used for a file is taken
+//! from its leading bytes when they carry a known signature, else from the
+//! file extension, and only then from the declared type. Office documents and
+//! other ZIP- or OLE-based formats share a signature, so for those the
+//! extension picks the specific type.
+
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Leading bytes examined for a signature
+pub const SNIFF_LEN: usize = 512;
+
+/// Type used when nothing better is known
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+const ZIP: &str = "application/zip";
+const OLE: &str = "application/x-ole-storage";
+
+/// Declared and detected type of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MimeInfo {
+    /// Type given by the caller
+    pub declared: String,
+    /// Type found from the content or the extension
+    pub detected: Option<String>,
+    /// Type to use: `detected`, else a valid `declared`, else octet-stream
+    pub content_type: String,
+}
+
+impl MimeInfo {
+    /// The declared type disagrees with what the file is
+    pub fn mismatch(&self) -> bool {
+        !essence(&self.declared).eq_ignore_ascii_case(essence(&self.content_type))
+    }
+}
+
+/// `type/subtype` without parameters
+fn essence(content_type: &str) -> &str {
+    content_type.split(';').next().unwrap_or("").trim()
+}
+
+/// Type from a file signature
+pub fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"%PDF-", "application/pdf"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"II*\x00", "image/tiff"),
+        (b"MM\x00*", "image/tiff"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"PK\x03\x04", ZIP),
+        (b"PK\x05\x06", ZIP),
+        (b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1", OLE),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+        (b"Rar!\x1a\x07", "application/vnd.rar"),
+        (b"ID3", "audio/mpeg"),
+        (b"OggS", "audio/ogg"),
+        (b"{\\rtf", "application/rtf"),
+        (b"MZ", "application/x-msdownload"),
+        (b"\x7fELF", "application/x-executable"),
+        (b"BEGIN:VCALENDAR", "text/calendar"),
+        (b"BEGIN:VCARD", "text/vcard"),
+    ];
+
+    if bytes.len() >= 12 && bytes.starts_with(b"RIFF") {
+        return match &bytes[8..12] {
+            b"WEBP" => Some("image/webp"),
+            b"WAVE" => Some("audio/wav"),
+            b"AVI " => Some("video/x-msvideo"),
+            _ => None,
+        };
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return match &bytes[8..12] {
+            b"qt  " => Some("video/quicktime"),
+            b"M4A " => Some("audio/mp4"),
+            b"heic" | b"heix" => Some("image/heic"),
+            _ => Some("video/mp4"),
+        };
+    }
+    if let Some((_, content_type)) = SIGNATURES.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return Some(content_type);
+    }
+
+    let text = String::from_utf8_lossy(&bytes[..bytes.len().min(64)]).trim_start().to_ascii_lowercase();
+    if text.starts_with("<!doctype html") || text.starts_with("<html") {
+        return Some("text/html");
+    }
+    if text.starts_with("<svg") {
+        return Some("image/svg+xml");
+    }
+    None
+}
+
+/// Type from a file name's extension
+pub fn from_extension(filename: &str) -> Option<&'static str> {
+    let extension = filename.rsplit_once('.')?.1.to_ascii_lowercase();
+    let content_type = match extension.as_str() {
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "ico" => "image/x-icon",
+        "heic" => "image/heic",
+        "svg" => "image/svg+xml",
+        "zip" => ZIP,
+        "gz" => "application/gzip",
+        "7z" => "application/x-7z-compressed",
+        "rar" => "application/vnd.rar",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "odp" => "application/vnd.oasis.opendocument.presentation",
+        "epub" => "application/epub+zip",
+        "doc" => "application/msword",
+        "xls" => "application/vnd.ms-excel",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "msg" => "application/vnd.ms-outlook",
+        "rtf" => "application/rtf",
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "htm" | "html" => "text/html",
+        "md" => "text/markdown",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "ics" => "text/calendar",
+        "vcf" => "text/vcard",
+        "eml" => "message/rfc822",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "avi" => "video/x-msvideo",
+        "exe" | "dll" => "application/x-msdownload",
+        _ => return None,
+    };
+    Some(content_type)
+}
+
+/// The declared type, when it is a well-formed `type/subtype`
+fn valid_declared(declared: &str) -> Option<&str> {
+    let (kind, subtype) = essence(declared).split_once('/')?;
+    let token = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c));
+    (token(kind) && token(subtype)).then_some(declared.trim())
+}
+
+/// Detect the type of a file from its name and leading bytes
+pub fn detect(filename: &str, declared: &str, head: &[u8]) -> MimeInfo {
+    let by_extension = from_extension(filename);
+    let detected = match sniff(head) {
+        // Container formats: the extension names the document type inside
+        Some(container @ (ZIP | OLE)) => Some(
+            by_extension
+                .filter(|ext| *ext == ZIP || is_container_type(ext, container))
+                .unwrap_or(if container == ZIP { ZIP } else { OCTET_STREAM }),
+        ),
+        Some(content_type) => Some(content_type),
+        None => by_extension,
+    };
+
+    let content_type = detected
+        .map(str::to_string)
+        .or_else(|| valid_declared(declared).map(str::to_string))
+        .unwrap_or_else(|| OCTET_STREAM.to_string());
+    MimeInfo {
+        declared: declared.to_string(),
+        detected: detected.map(str::to_string),
+        content_type,
+    }
+}
+
+/// Document types stored in a ZIP or OLE container
+fn is_container_type(content_type: &str, container: &str) -> bool {
+    if container == ZIP {
+        content_type.starts_with("application/vnd.openxmlformats-")
+            || content_type.starts_with("application/vnd.oasis.opendocument.")
+            || content_type == "application/epub+zip"
+    } else {
+        matches!(
+            content_type,
+            "application/msword" | "application/vnd.ms-excel" | "application/vnd.ms-powerpoint" | "application/vnd.ms-outlook"
+        )
+    }
+}
+
+/// Detect the type of a file on disk
+pub async fn detect_file(path: &Path, filename: &str, declared: &str) -> std::io::Result<MimeInfo> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    (&mut file).take(SNIFF_LEN as u64).read_to_end(&mut head).await?;
+    Ok(detect(filename, declared, &head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        // Content wins over a wrong declaration
+        let png = detect("photo.jpg", "image/jpeg", b"\x89PNG\r\n\x1a\n....");
+        assert_eq!(png.content_type, "image/png");
+        assert!(png.mismatch());
+
+        let html = detect("invoice.pdf", "application/pdf", b"  <!DOCTYPE html><html>");
+        assert_eq!(html.content_type, "text/html");
+
+        // ZIP and OLE containers take the document type from the extension
+        let docx = detect("report.docx", "application/octet-stream", b"PK\x03\x04rest");
+        assert_eq!(docx.content_type, "application/vnd.openxmlformats-officedocument.wordprocessingml.document");
+        assert_eq!(detect("report.pdf", "", b"PK\x03\x04rest").content_type, "application/zip");
+        let xls = detect("sheet.xls", "", b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1");
+        assert_eq!(xls.content_type, "application/vnd.ms-excel");
+        assert_eq!(detect("sheet.bin", "", b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1").content_type, OCTET_STREAM);
+
+        // Without a signature the extension decides, then a valid declared type
+        let text = detect("notes.TXT", "text/plain; charset=utf-8", b"hello");
+        assert_eq!(text.content_type, "text/plain");
+        assert!(!text.mismatch());
+        assert_eq!(detect("data.custom", "application/x-custom", b"hello").content_type, "application/x-custom");
+        assert_eq!(detect("data.custom", "not a type", b"hello").content_type, OCTET_STREAM);
+
+        assert_eq!(sniff(b"RIFF\x00\x00\x00\x00WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff(b"\x00\x00\x00\x18ftypmp42"), Some("video/mp4"));
+    }
+}
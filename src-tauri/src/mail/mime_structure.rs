@@ -0,0 +1,260 @@
+//! MIME Structure
+//!
+//! How a message was interpreted: its MIME tree with the content type,
+//! charset and transfer encoding of every part, and the malformed input the
+//! parser worked around on the way (a multipart without boundary or closing
+//! delimiter, header lines that are not fields, unknown charsets or transfer
+//! encodings, undecodable content). Lets the message view explain why a
+//! message renders oddly instead of silently showing its best guess.
+
+use crate::mail::charset::resolve_charset;
+use mail_parser::{Message, MessageParser, MessagePart, MimeHeaders, PartType};
+use serde::{Deserialize, Serialize};
+
+/// Nesting of message/rfc822 parts followed
+const MAX_DEPTH: usize = 10;
+
+/// Transfer encodings defined by RFC 2045 (lowercase)
+const KNOWN_ENCODINGS: &[&str] = &["7bit", "8bit", "binary", "quoted-printable", "base64"];
+
+/// One part of the MIME tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct MimePart {
+    /// IMAP section number ("" for the message itself, "1.2", ...)
+    pub path: String,
+    pub content_type: String,
+    pub charset: Option<String>,
+    /// Content-Transfer-Encoding as declared
+    pub encoding: Option<String>,
+    /// "inline" or "attachment"
+    pub disposition: Option<String>,
+    pub filename: Option<String>,
+    pub content_id: Option<String>,
+    /// Bytes of the part body as transmitted
+    pub size: usize,
+    pub parts: Vec<MimePart>,
+}
+
+/// Kind of malformed input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "ParseWarningKind"))]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// Multipart without a boundary, or whose boundary never occurs
+    MissingBoundary,
+    /// Multipart without the closing `--boundary--` delimiter
+    UnclosedMultipart,
+    /// Header line that is not a `Name: value` field
+    InvalidHeader,
+    UnknownCharset,
+    UnknownEncoding,
+    /// Content that could not be decoded with its transfer encoding
+    EncodingProblem,
+    /// Nested messages beyond the depth followed
+    TooDeep,
+}
+
+/// Malformed input the parser worked around
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct ParseWarning {
+    pub kind: WarningKind,
+    /// Path of the part it concerns
+    pub part: String,
+    pub detail: String,
+}
+
+/// MIME tree of a message and the problems found while parsing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct MessageStructure {
+    pub root: MimePart,
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl MessageStructure {
+    /// Parse a raw message; `None` when it is not a message at all
+    pub fn from_raw(raw: &[u8]) -> Option<Self> {
+        let message = MessageParser::default().parse(raw)?;
+        let mut warnings = Vec::new();
+        let root = walk(&message, 0, String::new(), 0, &mut warnings);
+        Some(Self { root, warnings })
+    }
+}
+
+fn child_path(parent: &str, index: usize) -> String {
+    if parent.is_empty() {
+        (index + 1).to_string()
+    } else {
+        format!("{}.{}", parent, index + 1)
+    }
+}
+
+/// Header lines of a part that are neither fields nor continuations
+fn invalid_header_lines(header: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(header)
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty() && !line.starts_with([' ', '\t']))
+        .filter(|line| match line.split_once(':') {
+            Some((name, _)) => name.is_empty() || !name.bytes().all(|b| b.is_ascii_graphic()),
+            None => true,
+        })
+        .map(|line| line.chars().take(80).collect())
+        .collect()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty() && haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+fn warn(warnings: &mut Vec<ParseWarning>, kind: WarningKind, part: &str, detail: impl Into<String>) {
+    warnings.push(ParseWarning { kind, part: part.to_string(), detail: detail.into() });
+}
+
+fn check_part(message: &Message, part: &MessagePart, path: &str, warnings: &mut Vec<ParseWarning>) {
+    let raw = message.raw_message();
+    let header = raw.get(part.offset_header..part.offset_body).unwrap_or_default();
+    for line in invalid_header_lines(header) {
+        warn(warnings, WarningKind::InvalidHeader, path, line);
+    }
+
+    let content_type = part.content_type();
+    // The parser reads a multipart without usable boundary as a single part
+    if content_type.is_some_and(|ct| ct.ctype().eq_ignore_ascii_case("multipart")) {
+        match content_type.and_then(|ct| ct.attribute("boundary")) {
+            None => warn(warnings, WarningKind::MissingBoundary, path, "no boundary parameter"),
+            Some(boundary) => {
+                let body = raw.get(part.offset_body..part.offset_end).unwrap_or_default();
+                let delimiter = format!("--{}", boundary);
+                if !contains(body, delimiter.as_bytes()) {
+                    warn(warnings, WarningKind::MissingBoundary, path, format!("boundary \"{}\" not found", boundary));
+                } else if !contains(body, format!("{}--", delimiter).as_bytes()) {
+                    warn(warnings, WarningKind::UnclosedMultipart, path, format!("no closing \"{}--\"", delimiter));
+                }
+            }
+        }
+    }
+
+    if let Some(charset) = content_type.and_then(|ct| ct.attribute("charset")) {
+        if resolve_charset(charset).is_none() {
+            warn(warnings, WarningKind::UnknownCharset, path, charset);
+        }
+    }
+    if let Some(encoding) = part.content_transfer_encoding() {
+        if !KNOWN_ENCODINGS.contains(&encoding.trim().to_ascii_lowercase().as_str()) {
+            warn(warnings, WarningKind::UnknownEncoding, path, encoding);
+        }
+    }
+    if part.is_encoding_problem {
+        warn(warnings, WarningKind::EncodingProblem, path, "content could not be fully decoded");
+    }
+}
+
+fn walk(message: &Message, id: usize, path: String, depth: usize, warnings: &mut Vec<ParseWarning>) -> MimePart {
+    let Some(part) = message.part(id) else {
+        return MimePart {
+            path,
+            content_type: "application/octet-stream".to_string(),
+            charset: None,
+            encoding: None,
+            disposition: None,
+            filename: None,
+            content_id: None,
+            size: 0,
+            parts: Vec::new(),
+        };
+    };
+    check_part(message, part, &path, warnings);
+
+    let content_type = part
+        .content_type()
+        .map(|ct| format!("{}/{}", ct.ctype(), ct.subtype().unwrap_or("*")).to_ascii_lowercase())
+        .unwrap_or_else(|| match part.body {
+            PartType::Multipart(_) => "multipart/mixed".to_string(),
+            PartType::Html(_) => "text/html".to_string(),
+            PartType::Text(_) => "text/plain".to_string(),
+            _ => "application/octet-stream".to_string(),
+        });
+
+    let parts = match &part.body {
+        PartType::Multipart(children) => children
+            .iter()
+            .enumerate()
+            .map(|(index, &child)| walk(message, child, child_path(&path, index), depth, warnings))
+            .collect(),
+        PartType::Message(nested) if depth + 1 < MAX_DEPTH => {
+            // Sections inside an attached message continue from its own root
+            let nested_root = walk(nested, 0, path.clone(), depth + 1, warnings);
+            vec![nested_root]
+        }
+        PartType::Message(_) => {
+            warn(warnings, WarningKind::TooDeep, &path, "nested message not examined");
+            Vec::new()
+        }
+        _ => Vec::new(),
+    };
+
+    MimePart {
+        content_type,
+        charset: part.content_type().and_then(|ct| ct.attribute("charset")).map(str::to_string),
+        encoding: part.content_transfer_encoding().map(str::to_string),
+        disposition: part.content_disposition().map(|cd| cd.ctype().to_ascii_lowercase()),
+        filename: part.attachment_name().map(str::to_string),
+        content_id: part.content_id().map(str::to_string),
+        size: part.offset_end.saturating_sub(part.offset_body),
+        parts,
+        path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structure() {
+        let raw = b"From: a@example.com\r\nSubject: Report\r\nContent-Type: multipart/mixed; boundary=\"XX\"\r\n\r\n\
+--XX\r\nContent-Type: multipart/alternative; boundary=\"YY\"\r\n\r\n\
+--YY\r\nContent-Type: text/plain; charset=utf-8\r\n\r\nHello\r\n\
+--YY\r\nContent-Type: text/html; charset=utf-8\r\nContent-Transfer-Encoding: quoted-printable\r\n\r\n<p>Hello</p>\r\n\
+--YY--\r\n\
+--XX\r\nContent-Type: application/pdf; name=\"r.pdf\"\r\nContent-Disposition: attachment; filename=\"r.pdf\"\r\n\
+Content-Transfer-Encoding: base64\r\n\r\nJVBERi0=\r\n\
+--XX--\r\n";
+        let structure = MessageStructure::from_raw(raw).unwrap();
+        assert!(structure.warnings.is_empty(), "{:?}", structure.warnings);
+
+        let root = &structure.root;
+        assert_eq!((root.path.as_str(), root.content_type.as_str()), ("", "multipart/mixed"));
+        let alternative = &root.parts[0];
+        assert_eq!(alternative.content_type, "multipart/alternative");
+        assert_eq!(alternative.parts[1].path, "1.2");
+        assert_eq!(alternative.parts[1].encoding.as_deref(), Some("quoted-printable"));
+        assert_eq!(alternative.parts[0].charset.as_deref(), Some("utf-8"));
+        let pdf = &root.parts[1];
+        assert_eq!(pdf.path, "2");
+        assert_eq!(pdf.filename.as_deref(), Some("r.pdf"));
+        assert_eq!(pdf.disposition.as_deref(), Some("attachment"));
+    }
+
+    #[test]
+    fn test_warnings() {
+        let raw = b"From: a@example.com\r\nthis is not a header\r\nContent-Type: multipart/mixed; boundary=\"XX\"\r\n\r\n\
+--XX\r\nContent-Type: text/plain; charset=x-klingon\r\nContent-Transfer-Encoding: uuencode\r\n\r\nHello\r\n";
+        let structure = MessageStructure::from_raw(raw).unwrap();
+        let kinds: Vec<WarningKind> = structure.warnings.iter().map(|warning| warning.kind).collect();
+        assert!(kinds.contains(&WarningKind::InvalidHeader));
+        assert!(kinds.contains(&WarningKind::UnclosedMultipart));
+        assert!(kinds.contains(&WarningKind::UnknownCharset));
+        assert!(kinds.contains(&WarningKind::UnknownEncoding));
+
+        let raw = b"Content-Type: multipart/mixed\r\n\r\nbody\r\n";
+        let structure = MessageStructure::from_raw(raw).unwrap();
+        assert_eq!(structure.warnings[0].kind, WarningKind::MissingBoundary);
+    }
+}
@@ -2,11 +2,33 @@
 //!
 //! Email functionality including IMAP, SMTP, and auto-configuration.
 
+pub mod attachment_guard;
 pub mod autoconfig;
 pub mod async_imap;
+pub mod binary;
+pub mod bounce;
+pub mod calendar;
+pub mod capabilities;
+pub mod charset;
+pub mod compose;
 pub mod config;
+pub mod delivery_auth;
+pub mod folder_tree;
+pub mod headers;
+pub mod html_text;
 pub mod imap;
+pub mod keywords;
+pub mod markdown;
+pub mod mbox;
+pub mod mime_detect;
+pub mod mime_structure;
+pub mod namespace;
+pub mod precheck;
+pub mod quotes;
+pub mod redirect;
+pub mod sender_auth;
 pub mod smtp_oauth;
+pub mod special_use;
 
 use serde::{Deserialize, Serialize};
 
@@ -14,7 +36,9 @@ use serde::{Deserialize, Serialize};
 pub use autoconfig::{fetch_autoconfig, fetch_autoconfig_debug, AutoConfig, AutoConfigDebug};
 pub use async_imap::AsyncImapClient;
 pub use config::{AccountConfig, ImapConfig, SecurityType, SmtpConfig};
+pub use delivery_auth::DeliveryAuth;
 pub use imap::ImapClient;
+pub use mime_structure::MessageStructure;
 
 /// Result type alias for mail operations
 pub type MailResult<T> = Result<T, MailError>;
@@ -43,12 +67,16 @@ pub enum MailError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Sending quota reached: {0}")]
+    QuotaExceeded(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
 
 /// Email folder representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "ImapFolder"))]
 pub struct Folder {
     pub name: String,
     pub path: String,
@@ -58,10 +86,14 @@ pub struct Folder {
     pub is_selectable: bool,
     pub unread_count: u32,
     pub total_count: u32,
+    /// LIST attributes as sent by the server (e.g. `\Sent`, `\Noselect`)
+    #[serde(default)]
+    pub attributes: Vec<String>,
 }
 
 /// Folder types
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 pub enum FolderType {
     Inbox,
     Sent,
@@ -100,6 +132,103 @@ impl FolderType {
             FolderType::Custom
         }
     }
+
+    /// Value stored in the `folders.folder_type` column
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            FolderType::Inbox => "inbox",
+            FolderType::Sent => "sent",
+            FolderType::Drafts => "drafts",
+            FolderType::Trash => "trash",
+            FolderType::Junk => "spam",
+            FolderType::Archive => "archive",
+            FolderType::Starred => "starred",
+            FolderType::Custom => "custom",
+        }
+    }
+}
+
+/// How a message was moved between folders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveMethod {
+    /// UID MOVE (the server announces MOVE)
+    Move,
+    /// COPY, then \Deleted and EXPUNGE of the original
+    CopyDelete,
+}
+
+/// Step of a COPY-based move that can fail after the copy exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveStep {
+    MarkDeleted,
+    Expunge,
+}
+
+/// Result of a move
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase", tag = "status")]
+pub enum MoveOutcome {
+    Moved,
+    /// Copied, and the original flagged \Deleted but not expunged: without
+    /// UIDPLUS an EXPUNGE would also remove every other message flagged
+    /// \Deleted in the folder
+    LeftFlagged,
+    /// A step failed and the earlier steps were undone: the message is still
+    /// (only) in its original folder
+    RolledBack { failed_step: MoveStep, error: String },
+    /// A step failed and undoing the earlier steps failed too; the message
+    /// may now exist in both folders
+    Incomplete { failed_step: MoveStep, error: String, rollback_error: String },
+}
+
+/// How a move was carried out and how it ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveReport {
+    pub method: MoveMethod,
+    pub outcome: MoveOutcome,
+}
+
+impl MoveReport {
+    pub fn moved(method: MoveMethod) -> Self {
+        Self { method, outcome: MoveOutcome::Moved }
+    }
+
+    /// `Ok` only when the message was moved (or is only awaiting an expunge)
+    pub fn into_result(self) -> MailResult<()> {
+        match self.outcome {
+            MoveOutcome::Moved | MoveOutcome::LeftFlagged => Ok(()),
+            MoveOutcome::RolledBack { failed_step, error } => Err(MailError::Imap(format!(
+                "Move failed at {:?} and was rolled back: {}",
+                failed_step, error
+            ))),
+            MoveOutcome::Incomplete { failed_step, error, rollback_error } => Err(MailError::Imap(format!(
+                "Move failed at {:?} ({}) and could not be rolled back: {}",
+                failed_step, error, rollback_error
+            ))),
+        }
+    }
+}
+
+/// Result of uploading a message with APPEND
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase", tag = "status")]
+pub enum AppendOutcome {
+    Appended,
+    /// Not uploaded: larger than the server's APPENDLIMIT
+    TooLarge { size: u64, limit: u64 },
+}
+
+/// Raw message with what APPEND needs to recreate it in another mailbox
+#[derive(Debug, Clone, Default)]
+pub struct RawMessage {
+    pub raw: Vec<u8>,
+    /// Flags as written in IMAP (`\Seen`, `$label1`)
+    pub flags: Vec<String>,
+    /// When the server received the message (INTERNALDATE)
+    pub internal_date: Option<chrono::DateTime<chrono::FixedOffset>>,
 }
 
 /// Search criteria
@@ -125,7 +254,8 @@ impl SearchCriteria {
 }
 
 /// Email summary for list view
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct EmailSummary {
     pub uid: u32,
@@ -138,18 +268,29 @@ pub struct EmailSummary {
     pub is_read: bool,
     pub is_starred: bool,
     pub has_attachments: bool,
+    /// Message size in bytes (RFC822.SIZE), 0 if unknown
+    #[serde(default)]
+    pub size: u32,
+    /// Tag keywords (custom IMAP flags) on the message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
     pub account_id: Option<String>,  // Account ID for unified inbox
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
     pub account_email: Option<String>,  // Account email for display
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
     pub account_name: Option<String>,  // Account name/label
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
     pub account_color: Option<String>,  // Account color badge (hex)
 }
 
 /// Fetch result with pagination
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct FetchResult {
     pub emails: Vec<EmailSummary>,
@@ -157,8 +298,51 @@ pub struct FetchResult {
     pub has_more: bool,
 }
 
+/// Folder counters that change whenever its message list or flags change
+/// Read with one STATUS; HIGHESTMODSEQ needs CONDSTORE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FolderState {
+    pub uid_validity: Option<u32>,
+    pub uid_next: Option<u32>,
+    pub messages: u32,
+    pub highest_modseq: u64,
+}
+
+impl FolderState {
+    /// Opaque token for one page of this folder in this state
+    pub fn token(&self, account_id: &str, folder: &str, page: u32, page_size: u32) -> String {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (account_id, folder, page, page_size).hash(&mut hasher);
+        format!(
+            "{}.{}.{}.{}.{:x}",
+            self.uid_validity.unwrap_or(0),
+            self.uid_next.unwrap_or(0),
+            self.messages,
+            self.highest_modseq,
+            hasher.finish()
+        )
+    }
+}
+
+/// `email_list` page tagged with the folder state it was read in
+/// `not_modified` means the state still matches the token the caller sent:
+/// `emails` is empty and the caller keeps the page it has.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct EmailListPage {
+    #[serde(flatten)]
+    pub result: FetchResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub state_token: Option<String>,
+    pub not_modified: bool,
+}
+
 /// Multi-account fetch result with per-account status
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct MultiAccountFetchResult {
     pub emails: Vec<EmailSummary>,
@@ -169,6 +353,7 @@ pub struct MultiAccountFetchResult {
 
 /// Per-account fetch status
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct AccountFetchStatus {
     pub account_id: String,
@@ -177,6 +362,7 @@ pub struct AccountFetchStatus {
     pub email_count: u32,
     pub success: bool,
     pub error: Option<String>,
+    #[cfg_attr(test, ts(type = "number"))]
     pub fetch_time_ms: u64,
 }
 
@@ -189,6 +375,7 @@ pub struct AccountFetchTaskResult {
 
 /// Parsed email
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct ParsedEmail {
     pub uid: u32,
@@ -204,10 +391,38 @@ pub struct ParsedEmail {
     pub is_read: bool,
     pub is_starred: bool,
     pub attachments: Vec<EmailAttachment>,
+    /// DKIM and TLS checks recorded by the receiving server
+    #[serde(default)]
+    pub delivery: DeliveryAuth,
+    /// Quoted text and signature markers, for collapsing them
+    #[serde(default)]
+    pub quotes: quotes::BodyStructure,
+    /// MIME tree and parse warnings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub structure: Option<MessageStructure>,
+    /// Domain policy of the sender, when one applies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub policy: Option<crate::domain_policy::PolicyVerdict>,
+    /// Size of the raw message in bytes
+    #[serde(default)]
+    pub size: u32,
+    /// Header fields as sent, for showing the full headers
+    #[serde(default)]
+    pub headers: Vec<headers::HeaderField>,
+    /// iCalendar invite or reply carried by the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub calendar: Option<calendar::CalendarPart>,
+    /// Recipients a delivery status notification reports as undeliverable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bounced: Vec<bounce::BouncedRecipient>,
 }
 
 /// Email attachment metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct EmailAttachment {
     pub filename: String,
@@ -220,6 +435,7 @@ pub struct EmailAttachment {
 
 /// Email attachment with data
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "camelCase")]
 pub struct AttachmentData {
     pub filename: String,
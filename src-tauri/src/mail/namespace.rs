@@ -0,0 +1,243 @@
+//! IMAP Namespaces
+//!
+//! NAMESPACE (RFC 2342) tells which parts of the folder hierarchy are the
+//! user's own, which are other users' mailboxes delegated to them and which
+//! are shared team folders ("Other Users/anna/INBOX", "#shared/support").
+//! Folders in the latter two are shown as separate trees rather than mixed
+//! into the account's own folders, and are never taken for its Sent or Trash.
+
+use super::folder_tree::{build_tree, FolderNode};
+use super::Folder;
+use serde::{Deserialize, Serialize};
+
+/// One namespace: a path prefix and its hierarchy delimiter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Namespace {
+    pub prefix: String,
+    /// `None` for a flat namespace
+    pub delimiter: Option<String>,
+}
+
+impl Namespace {
+    /// Whether a folder path lies in this namespace (or is its root)
+    fn contains(&self, path: &str) -> bool {
+        if self.prefix.is_empty() {
+            return false;
+        }
+        let root = self.delimiter.as_deref().and_then(|delimiter| self.prefix.strip_suffix(delimiter));
+        path.starts_with(&self.prefix) || root == Some(path)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub enum NamespaceKind {
+    Personal,
+    OtherUsers,
+    Shared,
+}
+
+/// Namespaces announced by the server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Namespaces {
+    pub personal: Vec<Namespace>,
+    pub other_users: Vec<Namespace>,
+    pub shared: Vec<Namespace>,
+}
+
+impl Namespaces {
+    /// Parse the untagged `* NAMESPACE` line of a raw server response
+    pub fn parse_response(response: &str) -> Option<Self> {
+        let line = response.lines().find_map(|line| {
+            let (name, rest) = line.strip_prefix("* ")?.split_once(' ')?;
+            name.eq_ignore_ascii_case("NAMESPACE").then_some(rest)
+        })?;
+
+        let mut tokens = tokenize(line)?.into_iter();
+        let mut groups = Vec::new();
+        for _ in 0..3 {
+            groups.push(parse_group(&mut tokens)?);
+        }
+        let shared = groups.pop()?;
+        let other_users = groups.pop()?;
+        let personal = groups.pop()?;
+        Some(Self { personal, other_users, shared })
+    }
+
+    /// Namespaces holding mailboxes that are not the user's own
+    pub fn foreign(&self) -> impl Iterator<Item = (NamespaceKind, &Namespace)> {
+        self.other_users
+            .iter()
+            .map(|namespace| (NamespaceKind::OtherUsers, namespace))
+            .chain(self.shared.iter().map(|namespace| (NamespaceKind::Shared, namespace)))
+            .filter(|(_, namespace)| !namespace.prefix.is_empty())
+    }
+
+    /// Namespace a folder belongs to (personal unless under another prefix)
+    pub fn kind_of(&self, path: &str) -> NamespaceKind {
+        self.foreign()
+            .find(|(_, namespace)| namespace.contains(path))
+            .map(|(kind, _)| kind)
+            .unwrap_or(NamespaceKind::Personal)
+    }
+}
+
+/// Folders of one shared or other users' namespace, as returned by `folder_list_shared`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "SharedFolderTree"))]
+#[serde(rename_all = "camelCase")]
+pub struct SharedTree {
+    pub kind: NamespaceKind,
+    pub prefix: String,
+    pub delimiter: Option<String>,
+    pub folders: Vec<FolderNode>,
+}
+
+/// One tree per foreign namespace that has folders
+pub fn shared_trees(namespaces: &Namespaces, folders: &[Folder]) -> Vec<SharedTree> {
+    namespaces
+        .foreign()
+        .filter_map(|(kind, namespace)| {
+            let folders: Vec<Folder> = folders
+                .iter()
+                .filter(|folder| namespaces.kind_of(&folder.path) == kind && namespace.contains(&folder.path))
+                .cloned()
+                .collect();
+            (!folders.is_empty()).then(|| SharedTree {
+                kind,
+                prefix: namespace.prefix.clone(),
+                delimiter: namespace.delimiter.clone(),
+                folders: build_tree(folders),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Nil,
+    String(String),
+}
+
+/// Split a NAMESPACE line into parentheses, NIL and strings (quoted or atoms)
+fn tokenize(line: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next()? {
+                        '\\' => value.push(chars.next()?),
+                        '"' => break,
+                        c => value.push(c),
+                    }
+                }
+                tokens.push(Token::String(value));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut atom = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == '(' || next == ')' {
+                        break;
+                    }
+                    atom.push(next);
+                    chars.next();
+                }
+                tokens.push(if atom.eq_ignore_ascii_case("NIL") { Token::Nil } else { Token::String(atom) });
+            }
+        }
+    }
+    Some(tokens)
+}
+
+/// `NIL` or `(("prefix" "delimiter" ext...) ...)`; extension data is skipped
+fn parse_group(tokens: &mut impl Iterator<Item = Token>) -> Option<Vec<Namespace>> {
+    match tokens.next()? {
+        Token::Nil => return Some(Vec::new()),
+        Token::Open => {}
+        _ => return None,
+    }
+
+    let mut namespaces = Vec::new();
+    loop {
+        match tokens.next()? {
+            Token::Close => return Some(namespaces),
+            Token::Open => {}
+            _ => return None,
+        }
+        let Token::String(prefix) = tokens.next()? else {
+            return None;
+        };
+        let delimiter = match tokens.next()? {
+            Token::String(delimiter) => Some(delimiter),
+            Token::Nil => None,
+            _ => return None,
+        };
+        namespaces.push(Namespace { prefix, delimiter });
+
+        let mut depth = 1;
+        while depth > 0 {
+            match tokens.next()? {
+                Token::Open => depth += 1,
+                Token::Close => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::FolderType;
+
+    #[test]
+    fn test_parse_and_split_namespaces() {
+        let response = "* NAMESPACE ((\"\" \"/\")) ((\"Other Users/\" \"/\")) ((\"#shared/\" \"/\" \"X-PARAM\" (\"a\" \"b\"))(\"#public.\" NIL))\r\n\
+                        A1 OK Namespace completed\r\n";
+        let namespaces = Namespaces::parse_response(response).unwrap();
+        assert_eq!(namespaces.personal, [Namespace { prefix: String::new(), delimiter: Some("/".to_string()) }]);
+        assert_eq!(namespaces.other_users[0].prefix, "Other Users/");
+        assert_eq!(namespaces.shared.len(), 2);
+        assert_eq!(namespaces.shared[1].delimiter, None);
+
+        let none = Namespaces::parse_response("* NAMESPACE ((\"INBOX.\" \".\")) NIL NIL").unwrap();
+        assert!(none.other_users.is_empty() && none.shared.is_empty());
+        assert_eq!(Namespaces::parse_response("* CAPABILITY IMAP4rev1"), None);
+
+        assert_eq!(namespaces.kind_of("INBOX"), NamespaceKind::Personal);
+        assert_eq!(namespaces.kind_of("Other Users/anna/INBOX"), NamespaceKind::OtherUsers);
+        assert_eq!(namespaces.kind_of("#shared"), NamespaceKind::Shared);
+        assert_eq!(namespaces.kind_of("#sharedstuff"), NamespaceKind::Personal);
+
+        let folder = |path: &str| Folder {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            folder_type: FolderType::Custom,
+            delimiter: "/".to_string(),
+            is_subscribed: true,
+            is_selectable: true,
+            unread_count: 0,
+            total_count: 0,
+            attributes: Vec::new(),
+        };
+        let trees = shared_trees(
+            &namespaces,
+            &[folder("INBOX"), folder("Other Users/anna/INBOX"), folder("#shared/support"), folder("#shared/sales")],
+        );
+        assert_eq!(trees.len(), 2);
+        assert_eq!(trees[0].kind, NamespaceKind::OtherUsers);
+        assert_eq!(trees[0].folders[0].folder.path, "Other Users");
+        assert_eq!(trees[1].folders[0].children.len(), 2);
+    }
+}
@@ -0,0 +1,516 @@
+//! Pre-Send Checks
+//!
+//! Warnings the composer can show before a message goes out: a body that
+//! mentions an attachment when none is attached, an empty subject, no
+//! recipients, a likely typo in a recipient domain, an address that bounced,
+//! a first-time recipient on a confidential thread, a reply-all to a long recipient list, or outside
+//! recipients added to an internal thread. Unlike the validation in
+//! `email_send`, none of these block sending; the user decides.
+
+use crate::mail::quotes;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Word beginnings that imply an attachment (lowercase)
+/// Matched at the start of a word, so "attach" covers "attached" and "attachment".
+const ATTACHMENT_PHRASES: &[&str] = &[
+    // English
+    "attach",
+    "enclosed",
+    // Turkish
+    "ekte",
+    "ekli",
+    "ek olarak",
+    "eklenmiş",
+    "iliştir",
+    // German
+    "anbei",
+    "im anhang",
+    "angehängt",
+    // French
+    "pièce jointe",
+    "pièces jointes",
+    "ci-joint",
+    // Spanish
+    "adjunt",
+    // Italian
+    "in allegato",
+    "allegat",
+    // Dutch
+    "bijgevoegd",
+    "in de bijlage",
+];
+
+/// Widely used mailbox providers, checked for near-miss domains (gamil.com)
+pub(crate) const POPULAR_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "msn.com",
+    "yahoo.com",
+    "icloud.com",
+    "me.com",
+    "aol.com",
+    "yandex.com",
+    "proton.me",
+    "protonmail.com",
+    "gmx.com",
+    "gmx.de",
+    "zoho.com",
+    "mail.com",
+];
+
+/// Subject words and labels that mark a thread as confidential (lowercase)
+const CONFIDENTIAL_MARKERS: &[&str] = &[
+    "confidential",
+    "gizli",
+    "vertraulich",
+    "confidentiel",
+    "confidencial",
+    "riservato",
+];
+
+/// Setting holding the `PrecheckSettings`
+pub const SETTINGS_KEY: &str = "precheck_settings";
+
+/// Configurable limits of the pre-send checks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrecheckSettings {
+    /// Reply-all to more recipients than this asks for confirmation (0 turns the check off)
+    pub reply_all_threshold: usize,
+}
+
+impl Default for PrecheckSettings {
+    fn default() -> Self {
+        Self { reply_all_threshold: 10 }
+    }
+}
+
+/// Kind of pre-send warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum SendWarningKind {
+    MissingAttachment,
+    EmptySubject,
+    NoRecipients,
+    InvalidRecipient,
+    /// Recipient domain is one edit away from a known domain
+    DomainTypo,
+    /// Address of a contact whose mail bounced
+    BouncingRecipient,
+    /// Address never corresponded with, on a confidential thread
+    NewRecipientOnConfidentialThread,
+    /// Reply-all reaching more recipients than the configured threshold
+    LargeReplyAll,
+    /// Recipients outside the organization on a thread that was internal so far
+    ExternalRecipients,
+}
+
+/// Warning returned by `email_precheck_send`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SendWarning {
+    pub kind: SendWarningKind,
+    pub message: String,
+    /// Matched phrase, offending address or suggested correction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub detail: Option<String>,
+    /// Number of recipients the warning is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub count: Option<usize>,
+}
+
+impl SendWarning {
+    pub fn new(kind: SendWarningKind, message: impl Into<String>, detail: Option<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            detail,
+            count: None,
+        }
+    }
+
+    fn with_count(self, count: usize) -> Self {
+        Self { count: Some(count), ..self }
+    }
+}
+
+/// Text the user wrote, without quoted replies and signature
+pub(crate) fn own_text(text_body: Option<&str>, html_body: Option<&str>) -> String {
+    match text_body.filter(|text| !text.trim().is_empty()) {
+        Some(text) => quotes::own_text(text),
+        None => html_body
+            .map(|html| html_own_text(&html[..quotes::html_quote_start(html).unwrap_or(html.len())]))
+            .unwrap_or_default(),
+    }
+}
+
+/// Plain text of an HTML body, dropping `<blockquote>` content
+fn html_own_text(html: &str) -> String {
+    let mut result = String::new();
+    let mut quote_depth = 0usize;
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        if quote_depth == 0 {
+            result.push_str(&rest[..start]);
+        }
+        let Some(end) = rest[start..].find('>') else { break };
+        let tag = rest[start + 1..start + end].trim().to_lowercase();
+        if tag.starts_with("blockquote") {
+            quote_depth += 1;
+        } else if tag.starts_with("/blockquote") {
+            quote_depth = quote_depth.saturating_sub(1);
+        }
+        result.push(' ');
+        rest = &rest[start + end + 1..];
+    }
+    if quote_depth == 0 {
+        result.push_str(rest);
+    }
+
+    result
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// First phrase implying an attachment, as written in the text
+pub fn find_attachment_mention(text: &str) -> Option<String> {
+    let lower = text.to_lowercase();
+
+    ATTACHMENT_PHRASES.iter().find_map(|phrase| {
+        lower.match_indices(phrase).find_map(|(index, _)| {
+            let word_start = lower[..index].chars().next_back().is_none_or(|c| !c.is_alphanumeric());
+            if !word_start {
+                return None;
+            }
+            // Report the whole word ("attachment", not "attach")
+            let end = lower[index + phrase.len()..]
+                .find(|c: char| !c.is_alphanumeric())
+                .map_or(lower.len(), |offset| index + phrase.len() + offset);
+            Some(lower[index..end].to_string())
+        })
+    })
+}
+
+/// Warnings for a message about to be sent
+/// Recipient addresses are validated by the caller.
+pub fn check(
+    recipient_count: usize,
+    subject: &str,
+    text_body: Option<&str>,
+    html_body: Option<&str>,
+    attachment_count: usize,
+) -> Vec<SendWarning> {
+    let mut warnings = Vec::new();
+
+    if recipient_count == 0 {
+        warnings.push(SendWarning::new(SendWarningKind::NoRecipients, "No recipients", None));
+    }
+    if subject.trim().is_empty() {
+        warnings.push(SendWarning::new(SendWarningKind::EmptySubject, "The subject is empty", None));
+    }
+    if attachment_count == 0 {
+        if let Some(phrase) = find_attachment_mention(&own_text(text_body, html_body)) {
+            warnings.push(SendWarning::new(
+                SendWarningKind::MissingAttachment,
+                format!("The message mentions an attachment (\"{}\") but nothing is attached", phrase),
+                Some(phrase),
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// Draft as passed to `email_precheck_send`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct OutgoingDraft {
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    #[serde(default)]
+    pub bcc: Vec<String>,
+    #[serde(default)]
+    pub subject: String,
+    pub text_body: Option<String>,
+    pub html_body: Option<String>,
+    #[serde(default)]
+    pub attachment_count: usize,
+    /// Folder and UID of the message being replied to or forwarded
+    pub reply_folder: Option<String>,
+    pub reply_uid: Option<u32>,
+    /// The draft was started with Reply All
+    #[serde(default)]
+    pub reply_all: bool,
+}
+
+/// Correspondence history the recipients are compared against
+#[derive(Debug, Clone, Default)]
+pub struct RecipientContext {
+    /// Lowercase addresses the account has exchanged mail with
+    pub known_addresses: HashSet<String>,
+    /// The draft replies to or forwards a thread marked confidential
+    pub confidential_thread: bool,
+    /// Lowercase domains of the account's own organization
+    pub internal_domains: HashSet<String>,
+    /// Lowercase addresses on the replied-to thread
+    pub thread_participants: Vec<String>,
+    /// Lowercase addresses of bouncing contacts with the server's reason
+    pub bouncing: HashMap<String, String>,
+}
+
+/// Whether a subject or labels mark a thread as confidential
+pub fn is_confidential(subject: &str, labels: &[String]) -> bool {
+    let subject = subject.to_lowercase();
+    CONFIDENTIAL_MARKERS.iter().any(|marker| {
+        subject.contains(marker) || labels.iter().any(|label| label.trim().to_lowercase() == *marker)
+    })
+}
+
+/// Edit distance counting adjacent transpositions as one edit (optimal string alignment)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Known domain the given one was probably meant to be
+/// Domains that are known themselves, or too short to judge, are left alone.
+pub fn suggest_domain<'a>(domain: &str, known_domains: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let domain = domain.trim().to_lowercase();
+    if domain.chars().count() < 6 {
+        return None;
+    }
+
+    let candidates: Vec<&str> = POPULAR_DOMAINS.iter().copied().chain(known_domains).collect();
+    if candidates.contains(&domain.as_str()) {
+        return None;
+    }
+    candidates
+        .into_iter()
+        .find(|candidate| edit_distance(&domain, candidate) == 1)
+        .map(str::to_string)
+}
+
+/// Per-recipient warnings: domain typos, bouncing addresses and new recipients on confidential threads
+pub fn check_recipients(recipients: &[&str], context: &RecipientContext) -> Vec<SendWarning> {
+    let known_domains: HashSet<&str> = context
+        .known_addresses
+        .iter()
+        .filter_map(|address| address.rsplit_once('@').map(|(_, domain)| domain))
+        .collect();
+    let mut warnings = Vec::new();
+
+    for recipient in recipients {
+        let address = recipient.trim().to_lowercase();
+        let Some((local, domain)) = address.rsplit_once('@') else { continue };
+        let is_known = context.known_addresses.contains(&address);
+
+        if !is_known {
+            if let Some(suggestion) = suggest_domain(domain, known_domains.iter().copied()) {
+                let corrected = format!("{}@{}", local, suggestion);
+                warnings.push(SendWarning::new(
+                    SendWarningKind::DomainTypo,
+                    format!("Did you mean {} instead of {}?", corrected, address),
+                    Some(corrected),
+                ));
+            }
+        }
+        if let Some(reason) = context.bouncing.get(&address) {
+            warnings.push(SendWarning::new(
+                SendWarningKind::BouncingRecipient,
+                format!("Mail to {} bounced before: {}", address, reason),
+                Some(address.clone()),
+            ));
+        }
+        if context.confidential_thread && !is_known {
+            warnings.push(SendWarning::new(
+                SendWarningKind::NewRecipientOnConfidentialThread,
+                format!("{} has never been contacted before and this thread is confidential", address),
+                Some(address),
+            ));
+        }
+    }
+
+    warnings
+}
+
+fn domain_of(address: &str) -> Option<&str> {
+    address.rsplit_once('@').map(|(_, domain)| domain)
+}
+
+/// Warnings about who a reply reaches: a reply-all to a long list, and
+/// outside recipients on a thread only the organization took part in so far
+pub fn check_audience(
+    recipients: &[&str],
+    reply_all: bool,
+    settings: &PrecheckSettings,
+    context: &RecipientContext,
+) -> Vec<SendWarning> {
+    let recipients: Vec<String> = recipients.iter().map(|recipient| recipient.trim().to_lowercase()).collect();
+    let mut warnings = Vec::new();
+
+    if reply_all && settings.reply_all_threshold > 0 && recipients.len() > settings.reply_all_threshold {
+        let unknown = recipients.iter().filter(|address| !context.known_addresses.contains(*address)).count();
+        let mut message = format!("Replying to all {} recipients", recipients.len());
+        if unknown > 0 {
+            message.push_str(&format!(", {} of them not in your address book", unknown));
+        }
+        warnings.push(SendWarning::new(SendWarningKind::LargeReplyAll, message, None).with_count(recipients.len()));
+    }
+
+    let is_internal = |address: &str| domain_of(address).is_some_and(|domain| context.internal_domains.contains(domain));
+    let internal_thread = !context.thread_participants.is_empty()
+        && context.thread_participants.iter().all(|address| is_internal(address));
+    if internal_thread {
+        let external: Vec<&str> =
+            recipients.iter().map(String::as_str).filter(|address| !is_internal(address)).collect();
+        if !external.is_empty() {
+            let message = match external.len() {
+                1 => format!("{} is outside your organization and this thread was internal", external[0]),
+                count => format!("{} recipients are outside your organization and this thread was internal", count),
+            };
+            warnings.push(
+                SendWarning::new(SendWarningKind::ExternalRecipients, message, Some(external.join(", ")))
+                    .with_count(external.len()),
+            );
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_mentions() {
+        assert_eq!(find_attachment_mention("Please see the attached report.").as_deref(), Some("attached"));
+        assert_eq!(find_attachment_mention("Raporu ekte bulabilirsiniz.").as_deref(), Some("ekte"));
+        assert_eq!(find_attachment_mention("Anbei die Rechnung").as_deref(), Some("anbei"));
+        assert_eq!(find_attachment_mention("Voir la pièce jointe").as_deref(), Some("pièce jointe"));
+        assert_eq!(find_attachment_mention("Te envío el archivo adjunto").as_deref(), Some("adjunto"));
+
+        // Only at word starts ("şeklinde" is not "ekli")
+        assert_eq!(find_attachment_mention("Bu şeklinde olmalı"), None);
+        assert_eq!(find_attachment_mention("Thanks, see you tomorrow"), None);
+    }
+
+    #[test]
+    fn test_check_ignores_quoted_text() {
+        let reply = "Sounds good, thanks!\n\nOn Mon, Jan 6, 2025, Ana wrote:\n> I attached the draft.";
+        assert!(check(1, "Re: Draft", Some(reply), None, 0).is_empty());
+        assert!(check(1, "Re: Draft", Some("> see attachment\nOk"), None, 0).is_empty());
+
+        let html = "<p>Looks fine</p><blockquote><p>Attached is the plan</p></blockquote>";
+        assert!(check(1, "Plan", None, Some(html), 0).is_empty());
+
+        let warnings = check(0, "  ", None, Some("<p>I've <b>attached</b> the plan</p>"), 0);
+        let kinds: Vec<_> = warnings.iter().map(|w| w.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![SendWarningKind::NoRecipients, SendWarningKind::EmptySubject, SendWarningKind::MissingAttachment]
+        );
+        assert_eq!(warnings[2].detail.as_deref(), Some("attached"));
+
+        // Nothing to warn about once a file is attached
+        assert!(check(1, "Plan", Some("Attached is the plan"), None, 1).is_empty());
+    }
+
+    #[test]
+    fn test_recipient_typos_and_confidential_threads() {
+        assert_eq!(edit_distance("gamil.com", "gmail.com"), 1);
+        assert_eq!(suggest_domain("gamil.com", []).as_deref(), Some("gmail.com"));
+        assert_eq!(suggest_domain("hotmial.com", []).as_deref(), Some("hotmail.com"));
+        assert_eq!(suggest_domain("gmail.com", []), None);
+        assert_eq!(suggest_domain("owlivion.co", ["owlivion.com"]).as_deref(), Some("owlivion.com"));
+        assert_eq!(suggest_domain("example.org", ["owlivion.com"]), None);
+
+        let mut context = RecipientContext {
+            known_addresses: ["ana@owlivion.com".to_string()].into_iter().collect(),
+            ..Default::default()
+        };
+        let warnings = check_recipients(&["Ana@owlivion.com", "john@gamil.com", "bob@owlivion.cm"], &context);
+        let details: Vec<_> = warnings.iter().filter_map(|w| w.detail.as_deref()).collect();
+        assert_eq!(details, vec!["john@gmail.com", "bob@owlivion.com"]);
+
+        context.confidential_thread = true;
+        let warnings = check_recipients(&["ana@owlivion.com", "eve@example.org"], &context);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, SendWarningKind::NewRecipientOnConfidentialThread);
+
+        context.confidential_thread = false;
+        context.bouncing.insert("ana@owlivion.com".to_string(), "550 User unknown".to_string());
+        let warnings = check_recipients(&["Ana@owlivion.com"], &context);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, SendWarningKind::BouncingRecipient);
+        assert_eq!(warnings[0].message, "Mail to ana@owlivion.com bounced before: 550 User unknown");
+
+        assert!(is_confidential("CONFIDENTIAL: Q3 numbers", &[]));
+        assert!(is_confidential("Q3 numbers", &["Gizli".to_string()]));
+        assert!(!is_confidential("Q3 numbers", &["Work".to_string()]));
+    }
+
+    #[test]
+    fn test_reply_all_and_external_recipients() {
+        let settings = PrecheckSettings { reply_all_threshold: 3 };
+        let mut context = RecipientContext {
+            known_addresses: ["ana@owlivion.com".to_string(), "bob@owlivion.com".to_string()].into_iter().collect(),
+            internal_domains: ["owlivion.com".to_string()].into_iter().collect(),
+            thread_participants: vec!["me@owlivion.com".to_string(), "ana@owlivion.com".to_string()],
+            ..Default::default()
+        };
+
+        let team = ["ana@owlivion.com", "bob@owlivion.com", "cem@owlivion.com", "dan@owlivion.com"];
+        let warnings = check_audience(&team, true, &settings, &context);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, SendWarningKind::LargeReplyAll);
+        assert_eq!(warnings[0].count, Some(4));
+        assert_eq!(warnings[0].message, "Replying to all 4 recipients, 2 of them not in your address book");
+
+        // Same list without Reply All, or with the check turned off
+        assert!(check_audience(&team, false, &settings, &context).is_empty());
+        assert!(check_audience(&team, true, &PrecheckSettings { reply_all_threshold: 0 }, &context).is_empty());
+
+        let warnings = check_audience(&["ana@owlivion.com", "Eve@Example.org"], false, &settings, &context);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, SendWarningKind::ExternalRecipients);
+        assert_eq!(warnings[0].detail.as_deref(), Some("eve@example.org"));
+        assert_eq!(warnings[0].count, Some(1));
+
+        // A thread that already included outsiders
+        context.thread_participants.push("eve@example.org".to_string());
+        assert!(check_audience(&["eve@example.org"], false, &settings, &context).is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+//! Quoted Text and Signatures
+//!
+//! Splits a message body into what the sender wrote, the quoted earlier
+//! messages and the signature, so the UI can collapse the noise and AI
+//! features can leave it out. In plain text, quotes are `>` lines and
+//! everything after a reply header ("On ... wrote:" in the common client
+//! languages, Outlook's "-----Original Message-----" or its From:/Sent:
+//! block); a signature starts at the `-- ` separator or a "Sent from my ..."
+//! line. In HTML, the quoted part starts at the first quote container the
+//! common clients insert.
+
+use serde::{Deserialize, Serialize};
+
+/// Role of a run of lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "BodySectionKind"))]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    /// Written by the sender
+    Body,
+    /// Reply header, quoted or forwarded text
+    Quote,
+    Signature,
+}
+
+/// Lines `start..end` (0-based) of the plain-text body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct BodySection {
+    pub kind: SectionKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Structural markers of a message body
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct BodyStructure {
+    /// Sections of the plain-text body, in order
+    pub text_sections: Vec<BodySection>,
+    /// Byte offset in the HTML body where the quoted part starts
+    pub html_quote_start: Option<usize>,
+}
+
+impl BodyStructure {
+    pub fn analyze(body_text: Option<&str>, body_html: Option<&str>) -> Self {
+        Self {
+            text_sections: body_text.map(sections).unwrap_or_default(),
+            html_quote_start: body_html.and_then(html_quote_start),
+        }
+    }
+}
+
+/// Endings of single-line reply headers, by client language (lowercase)
+const REPLY_HEADER_ENDINGS: &[&str] = &[
+    "wrote:",           // en
+    "schrieb:",         // de
+    "a écrit :",        // fr
+    "a écrit:",         // fr
+    "escribió:",        // es
+    "ha scritto:",      // it
+    "schreef:",         // nl
+    "escreveu:",        // pt
+    "yazdı:",           // tr
+    "pisze:",           // pl
+    "napsal:",          // cs
+    "skrev:",           // da, no, sv
+    "kirjoitti:",       // fi
+    "написал:",         // ru
+    "написала:",        // ru
+    "написал(а):",      // ru
+    "έγραψε:",          // el
+];
+
+/// Reply headers with the sender after the verb: "Am ... schrieb Name <addr>:"
+const REPLY_HEADER_VERBS: &[(&str, &str)] = &[("am ", " schrieb "), ("op ", " schreef "), ("den ", " skrev ")];
+
+/// How reply headers start in the languages that begin with a preposition
+const REPLY_HEADER_STARTS: &[&str] = &["on ", "am ", "le ", "el ", "il ", "op ", "em ", "w dniu ", "den ", "dne "];
+
+/// Lines starting a forwarded or quoted original (lowercase)
+const ORIGINAL_MESSAGE_MARKERS: &[&str] = &[
+    "-----original message-----",
+    "----- original message -----",
+    "-------- original message --------",
+    "---------- forwarded message ---------",
+    "-------- forwarded message --------",
+    "begin forwarded message:",
+    "-----ursprüngliche nachricht-----",
+    "-----message d'origine-----",
+    "-----mensaje original-----",
+    "-----messaggio originale-----",
+    "-----oorspronkelijk bericht-----",
+    "-----orijinal ileti-----",
+];
+
+/// Outlook's header block: a From: line followed by these (lowercase)
+const OUTLOOK_HEADER_FIELDS: &[&str] = &["sent:", "date:", "gesendet:", "envoyé :", "enviado el:", "gönderildi:", "to:"];
+
+/// Mobile and webmail signatures without a separator (lowercase)
+const SIGNATURE_LINES: &[&str] = &[
+    "sent from my ",
+    "get outlook for ",
+    "sent from mail for windows",
+    "sent from yahoo mail",
+    "von meinem iphone gesendet",
+    "envoyé de mon iphone",
+    "enviado desde mi iphone",
+    "iphone'umdan gönderildi",
+];
+
+/// HTML containers holding the quoted part (lowercase)
+const HTML_QUOTE_MARKERS: &[&str] = &[
+    "<div class=\"gmail_quote",
+    "<blockquote",
+    "<div id=\"divrplyfwdmsg\"",
+    "<div id=\"appendonsend\"",
+    "<div class=\"moz-cite-prefix\"",
+    "<div class=\"yahoo_quoted\"",
+    "<hr id=\"stopspelling\"",
+];
+
+fn is_quoted(line: &str) -> bool {
+    line.trim_start().starts_with('>')
+}
+
+/// Whether a trimmed, lowercase line is an "On ... wrote:" header
+fn is_reply_header(lower: &str) -> bool {
+    REPLY_HEADER_ENDINGS.iter().any(|ending| lower.ends_with(ending))
+        || (lower.ends_with(':')
+            && REPLY_HEADER_VERBS.iter().any(|(start, verb)| lower.starts_with(start) && lower.contains(verb)))
+}
+
+/// Reply header on `line`, possibly wrapped onto the next line
+/// Returns the number of lines it takes.
+fn reply_header_lines(line: &str, next: Option<&str>) -> Option<usize> {
+    let lower = line.trim().to_lowercase();
+    if lower.is_empty() || is_quoted(line) {
+        return None;
+    }
+    if ORIGINAL_MESSAGE_MARKERS.iter().any(|marker| lower.starts_with(marker)) || is_reply_header(&lower) {
+        return Some(1);
+    }
+    // Clients wrap long "On ..., Name <address> wrote:" lines
+    if lower.len() > 200 || !REPLY_HEADER_STARTS.iter().any(|start| lower.starts_with(start)) {
+        return None;
+    }
+    let next = next?.trim().to_lowercase();
+    (!next.is_empty() && next.len() < 100 && is_reply_header(&format!("{} {}", lower, next))).then_some(2)
+}
+
+/// Outlook's "From: / Sent: / To: / Subject:" block
+fn is_outlook_header(lines: &[&str], index: usize) -> bool {
+    let lower = |i: usize| lines.get(i).map(|line| line.trim().to_lowercase()).unwrap_or_default();
+    let first = lower(index);
+    (first.starts_with("from:") || first.starts_with("von:") || first.starts_with("de :") || first.starts_with("kimden:"))
+        && (index + 1..index + 3).any(|i| OUTLOOK_HEADER_FIELDS.iter().any(|field| lower(i).starts_with(field)))
+}
+
+fn is_signature_start(line: &str) -> bool {
+    let lower = line.trim_end().to_lowercase();
+    lower == "-- " || lower == "--" || SIGNATURE_LINES.iter().any(|start| lower.trim_start().starts_with(start))
+}
+
+/// Sections of a plain-text body
+pub fn sections(text: &str) -> Vec<BodySection> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut kinds = vec![SectionKind::Body; lines.len()];
+
+    let mut index = 0;
+    let mut in_signature = false;
+    while index < lines.len() {
+        let line = lines[index];
+        if let Some(count) = reply_header_lines(line, lines.get(index + 1).copied()) {
+            let after = index + count;
+            let next_content = (after..lines.len()).find(|&i| !lines[i].trim().is_empty());
+            if next_content.is_some_and(|i| is_quoted(lines[i])) {
+                // Header of an interleaved or bottom quote: only the header itself
+                for kind in &mut kinds[index..after] {
+                    *kind = SectionKind::Quote;
+                }
+                index = after;
+                in_signature = false;
+                continue;
+            }
+            // Top-posted reply: the rest is the earlier conversation
+            for kind in &mut kinds[index..] {
+                *kind = SectionKind::Quote;
+            }
+            break;
+        }
+        if is_outlook_header(&lines, index) {
+            for kind in &mut kinds[index..] {
+                *kind = SectionKind::Quote;
+            }
+            break;
+        }
+
+        if is_quoted(line) {
+            kinds[index] = SectionKind::Quote;
+            in_signature = false;
+        } else if in_signature || is_signature_start(line) {
+            kinds[index] = SectionKind::Signature;
+            in_signature = true;
+        }
+        index += 1;
+    }
+
+    // Blank lines between two quoted lines belong to the quote
+    for i in 1..lines.len().saturating_sub(1) {
+        if lines[i].trim().is_empty() && kinds[i] == SectionKind::Body {
+            let before = kinds[i - 1];
+            let after = (i + 1..lines.len()).find(|&j| !lines[j].trim().is_empty()).map(|j| kinds[j]);
+            if before == SectionKind::Quote && after == Some(SectionKind::Quote) {
+                kinds[i] = SectionKind::Quote;
+            }
+        }
+    }
+
+    let mut sections: Vec<BodySection> = Vec::new();
+    for (line, kind) in kinds.into_iter().enumerate() {
+        match sections.last_mut() {
+            Some(section) if section.kind == kind => section.end = line + 1,
+            _ => sections.push(BodySection { kind, start: line, end: line + 1 }),
+        }
+    }
+    sections
+}
+
+/// What the sender wrote: the body without quotes and signature
+pub fn own_text(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    sections(text)
+        .iter()
+        .filter(|section| section.kind == SectionKind::Body)
+        .flat_map(|section| lines[section.start..section.end].iter().copied())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Byte offset where the quoted part of an HTML body starts
+pub fn html_quote_start(html: &str) -> Option<usize> {
+    let lower = html.to_ascii_lowercase();
+    HTML_QUOTE_MARKERS.iter().filter_map(|marker| lower.find(marker)).min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<(SectionKind, usize, usize)> {
+        sections(text).into_iter().map(|section| (section.kind, section.start, section.end)).collect()
+    }
+
+    #[test]
+    fn test_top_posted_reply() {
+        let text = "Sounds good.\n\n--\nAna Lima\nACME\n\nAm Mo., 6. Jan. 2025 um 10:00 Uhr schrieb Bob <bob@x.test>:\n> Is Friday ok?\n";
+        assert_eq!(
+            kinds(text),
+            vec![(SectionKind::Body, 0, 2), (SectionKind::Signature, 2, 6), (SectionKind::Quote, 6, 8)]
+        );
+        assert_eq!(own_text(text), "Sounds good.");
+
+        // Wrapped Gmail header followed by unquoted text, and Outlook blocks
+        let wrapped = "Yes.\nOn Mon, Jan 6, 2025 at 10:00 AM Bob Smith <\nbob@example.com> wrote:\nIs Friday ok?";
+        assert_eq!(kinds(wrapped), vec![(SectionKind::Body, 0, 1), (SectionKind::Quote, 1, 4)]);
+        let outlook = "Done.\n\nFrom: Bob\nSent: Monday\nTo: Ana\nSubject: Plan\n\nPlease check.";
+        assert_eq!(own_text(outlook), "Done.");
+        assert_eq!(own_text("Merhaba\n\n6 Oca 2025 Pzt, 10:00 tarihinde Bob şunu yazdı:\n> Selam"), "Merhaba");
+    }
+
+    #[test]
+    fn test_interleaved_reply() {
+        let text = "Hi,\n\nOn Mon, Bob wrote:\n> Is Friday ok?\n>\n> And the budget?\n\nFriday works.\n> Room?\nB2.\n\nSent from my iPhone";
+        assert_eq!(
+            kinds(text),
+            vec![
+                (SectionKind::Body, 0, 2),
+                (SectionKind::Quote, 2, 6),
+                (SectionKind::Body, 6, 8),
+                (SectionKind::Quote, 8, 9),
+                (SectionKind::Body, 9, 11),
+                (SectionKind::Signature, 11, 12),
+            ]
+        );
+        assert_eq!(own_text(text), "Hi,\n\n\nFriday works.\nB2.");
+    }
+
+    #[test]
+    fn test_html_quote_start() {
+        let html = "<div>Thanks!</div><div class=\"gmail_quote\"><div>On Mon, Bob wrote:</div><blockquote>Hi</blockquote></div>";
+        assert_eq!(html_quote_start(html), html.find("<div class"));
+        assert_eq!(html_quote_start("<p>No quote</p>"), None);
+    }
+}
@@ -0,0 +1,90 @@
+//! Redirect (Resend)
+//!
+//! Redirecting passes a received message on unchanged, so the final recipient
+//! sees and replies to the original sender. A resent block (RFC 5322 section
+//! 3.6.6) is prepended naming who redirected the message, to whom and when;
+//! the original From, Subject, Message-ID and body are left as they are.
+
+/// Fields of the resent block
+#[derive(Debug, Clone)]
+pub struct ResentFields {
+    /// Redirecting account address
+    pub from: String,
+    pub to: Vec<String>,
+    /// RFC 5322 date
+    pub date: String,
+    /// Message-ID of the redirection, without angle brackets
+    pub message_id: String,
+}
+
+impl ResentFields {
+    /// Fields for a redirection sent now
+    pub fn now(from: &str, to: &[String]) -> Self {
+        let domain = from.rsplit_once('@').map_or("localhost", |(_, domain)| domain);
+        Self {
+            from: from.to_string(),
+            to: to.to_vec(),
+            date: chrono::Local::now().to_rfc2822(),
+            message_id: format!("{}@{}", uuid::Uuid::new_v4(), domain),
+        }
+    }
+}
+
+/// The raw message with a resent block prepended and CRLF line endings
+pub fn resend(raw: &[u8], fields: &ResentFields) -> Vec<u8> {
+    let block = format!(
+        "Resent-From: {}\r\nResent-To: {}\r\nResent-Date: {}\r\nResent-Message-ID: <{}>\r\n",
+        fields.from,
+        fields.to.join(", "),
+        fields.date,
+        fields.message_id
+    );
+
+    let mut message = Vec::with_capacity(block.len() + raw.len() + raw.len() / 40);
+    message.extend_from_slice(block.as_bytes());
+    // SMTP requires CRLF; messages from local folders may use bare LF
+    let mut previous = 0u8;
+    for &byte in raw {
+        if byte == b'\n' && previous != b'\r' {
+            message.push(b'\r');
+        }
+        message.push(byte);
+        previous = byte;
+    }
+    if !message.ends_with(b"\r\n") {
+        message.extend_from_slice(b"\r\n");
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resend_prepends_block() {
+        let fields = ResentFields {
+            from: "desk@example.com".to_string(),
+            to: vec!["agent@example.com".to_string(), "lead@example.com".to_string()],
+            date: "Mon, 6 Jan 2025 10:00:00 +0000".to_string(),
+            message_id: "abc@example.com".to_string(),
+        };
+        let raw = b"From: Customer <c@shop.test>\nSubject: Help\n\nMy order.\n";
+        let message = String::from_utf8(resend(raw, &fields)).unwrap();
+        assert_eq!(
+            message,
+            "Resent-From: desk@example.com\r\n\
+             Resent-To: agent@example.com, lead@example.com\r\n\
+             Resent-Date: Mon, 6 Jan 2025 10:00:00 +0000\r\n\
+             Resent-Message-ID: <abc@example.com>\r\n\
+             From: Customer <c@shop.test>\r\nSubject: Help\r\n\r\nMy order.\r\n"
+        );
+
+        // CRLF input passes unchanged
+        let raw = b"From: c@shop.test\r\n\r\nHi";
+        assert!(resend(raw, &fields).ends_with(b"From: c@shop.test\r\n\r\nHi\r\n"));
+
+        let now = ResentFields::now("desk@example.com", &fields.to);
+        assert!(now.message_id.ends_with("@example.com"));
+    }
+}
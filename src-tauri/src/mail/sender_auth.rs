@@ -0,0 +1,452 @@
+//! Sender Authentication Check
+//!
+//! Diagnostics for accounts sending from a custom domain: does the domain's
+//! SPF record authorise the configured SMTP host, and are DKIM keys published
+//! under the usual selectors? Receivers put mail failing these checks in spam,
+//! so the result is a list of warnings rather than a hard error. Mailbox
+//! providers (gmail.com, outlook.com, ...) manage this themselves and are
+//! skipped.
+
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::TokioAsyncResolver;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+/// DNS lookups allowed while expanding SPF includes (RFC 7208 limit)
+const MAX_SPF_LOOKUPS: usize = 10;
+
+/// DKIM selectors published by common providers and mail servers
+const DKIM_SELECTORS: &[&str] = &[
+    "default",
+    "google",
+    "selector1",
+    "selector2",
+    "k1",
+    "k2",
+    "s1",
+    "s2",
+    "dkim",
+    "mail",
+    "smtp",
+    "zoho",
+    "zmail",
+    "protonmail",
+    "protonmail2",
+    "fm1",
+    "mxvault",
+    "everlytickey1",
+    "mandrill",
+    "sendgrid",
+];
+
+/// One SPF term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpfMechanism {
+    Include(String),
+    /// A/AAAA of the domain (None = the record's own domain)
+    A(Option<String>),
+    /// MX hosts of the domain (None = the record's own domain)
+    Mx(Option<String>),
+    Ip(IpNet),
+    /// `all` with its qualifier (`-`, `~`, `?`, `+`)
+    All(char),
+}
+
+/// Parsed `v=spf1` record
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpfRecord {
+    pub mechanisms: Vec<SpfMechanism>,
+    pub redirect: Option<String>,
+}
+
+impl SpfRecord {
+    /// Parse a TXT record; None when it is not SPF
+    /// Fail (`-`) mechanisms other than `all` are ignored: they never authorise a host.
+    pub fn parse(txt: &str) -> Option<Self> {
+        let mut terms = txt.split_whitespace();
+        if !terms.next()?.eq_ignore_ascii_case("v=spf1") {
+            return None;
+        }
+
+        let mut record = SpfRecord::default();
+        for term in terms {
+            let lower = term.to_lowercase();
+            if let Some(target) = lower.strip_prefix("redirect=") {
+                record.redirect = Some(target.to_string());
+                continue;
+            }
+
+            let (qualifier, body) = match lower.chars().next() {
+                Some(q @ ('+' | '-' | '~' | '?')) => (q, &lower[1..]),
+                _ => ('+', lower.as_str()),
+            };
+            let (name, value) = match body.split_once([':', '/']) {
+                Some((name, _)) => (name, body[name.len()..].trim_start_matches(':')),
+                None => (body, ""),
+            };
+            let domain = (!value.is_empty() && !value.starts_with('/'))
+                .then(|| value.split('/').next().unwrap_or(value).to_string());
+
+            let mechanism = match name {
+                "all" => SpfMechanism::All(qualifier),
+                _ if qualifier != '+' => continue,
+                "include" => match domain {
+                    Some(domain) => SpfMechanism::Include(domain),
+                    None => continue,
+                },
+                "a" => SpfMechanism::A(domain),
+                "mx" => SpfMechanism::Mx(domain),
+                "ip4" | "ip6" => match IpNet::parse(value) {
+                    Some(net) => SpfMechanism::Ip(net),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            record.mechanisms.push(mechanism);
+        }
+        Some(record)
+    }
+
+    /// Qualifier of the terminating `all`, if any
+    pub fn all_qualifier(&self) -> Option<char> {
+        self.mechanisms.iter().find_map(|m| match m {
+            SpfMechanism::All(q) => Some(*q),
+            _ => None,
+        })
+    }
+}
+
+/// IPv4/IPv6 network from an `ip4:`/`ip6:` mechanism
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNet {
+    pub addr: IpAddr,
+    pub prefix: u8,
+}
+
+impl IpNet {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (value, None),
+        };
+        let addr: IpAddr = addr.parse().ok()?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse().ok().filter(|p| *p <= max)?,
+            None => max,
+        };
+        Some(Self { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix)).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix)).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Kind of sender authentication warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum SenderAuthWarningKind {
+    NoSpf,
+    MultipleSpf,
+    /// SPF does not cover the SMTP host
+    SpfExcludesSmtpHost,
+    /// SPF ends in `+all`/`?all` and authorises anyone
+    SpfPermissive,
+    /// Too many includes to evaluate
+    SpfTooManyLookups,
+    NoDkim,
+    NoDmarc,
+    DnsError,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SenderAuthWarning {
+    pub kind: SenderAuthWarningKind,
+    pub message: String,
+}
+
+/// Result of `account_check_sender_auth`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SenderAuthReport {
+    pub domain: String,
+    pub smtp_host: String,
+    /// False for mailbox providers, which are not checked
+    pub custom_domain: bool,
+    pub spf_record: Option<String>,
+    /// SPF authorises the SMTP host
+    pub spf_covers_smtp_host: bool,
+    /// Selectors with a published DKIM key
+    pub dkim_selectors: Vec<String>,
+    pub dmarc_record: Option<String>,
+    pub warnings: Vec<SenderAuthWarning>,
+    /// Mail from this account will likely be filtered as spam
+    pub likely_spam: bool,
+}
+
+impl SenderAuthReport {
+    fn warn(&mut self, kind: SenderAuthWarningKind, message: impl Into<String>) {
+        self.warnings.push(SenderAuthWarning { kind, message: message.into() });
+    }
+}
+
+/// Registrable part of a host name, e.g. `smtp.eu.mailgun.org` -> `mailgun.org`
+fn base_domain(host: &str) -> String {
+    let parts: Vec<&str> = host.trim_end_matches('.').split('.').collect();
+    parts[parts.len().saturating_sub(2)..].join(".").to_lowercase()
+}
+
+async fn txt_records(resolver: &TokioAsyncResolver, name: &str) -> Result<Vec<String>, String> {
+    match resolver.txt_lookup(name).await {
+        Ok(lookup) => Ok(lookup
+            .iter()
+            .map(|txt| {
+                txt.txt_data()
+                    .iter()
+                    .map(|part| String::from_utf8_lossy(part))
+                    .collect::<String>()
+            })
+            .collect()),
+        Err(e) if matches!(e.kind(), hickory_resolver::error::ResolveErrorKind::NoRecordsFound { .. }) => {
+            Ok(Vec::new())
+        }
+        Err(e) => Err(format!("TXT lookup for {} failed: {}", name, e)),
+    }
+}
+
+async fn host_ips(resolver: &TokioAsyncResolver, host: &str) -> Vec<IpAddr> {
+    match resolver.lookup_ip(host).await {
+        Ok(lookup) => lookup.iter().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Expanded SPF policy of a domain
+#[derive(Debug, Default)]
+struct SpfPolicy {
+    networks: Vec<IpNet>,
+    /// Hosts whose addresses are authorised (a/mx mechanisms)
+    hosts: Vec<String>,
+    /// Domains visited through include/redirect
+    domains: HashSet<String>,
+    too_many_lookups: bool,
+}
+
+/// Follow includes and redirects breadth-first, within the lookup limit
+async fn expand_spf(resolver: &TokioAsyncResolver, domain: &str, record: &SpfRecord) -> SpfPolicy {
+    let mut policy = SpfPolicy::default();
+    let mut queue = vec![(domain.to_string(), record.clone())];
+    let mut lookups = 0usize;
+
+    while let Some((current, record)) = queue.pop() {
+        policy.domains.insert(current.clone());
+        let mut targets = Vec::new();
+        for mechanism in &record.mechanisms {
+            match mechanism {
+                SpfMechanism::Include(target) => targets.push(target.clone()),
+                SpfMechanism::Ip(net) => policy.networks.push(*net),
+                SpfMechanism::A(host) => policy.hosts.push(host.clone().unwrap_or_else(|| current.clone())),
+                SpfMechanism::Mx(host) => {
+                    let host = host.clone().unwrap_or_else(|| current.clone());
+                    lookups += 1;
+                    if let Ok(mx) = resolver.mx_lookup(host.as_str()).await {
+                        policy.hosts.extend(mx.iter().map(|mx| mx.exchange().to_string().trim_end_matches('.').to_string()));
+                    }
+                }
+                SpfMechanism::All(_) => {}
+            }
+        }
+        targets.extend(record.redirect.clone());
+
+        for target in targets {
+            if policy.domains.contains(&target) {
+                continue;
+            }
+            lookups += 1;
+            if lookups > MAX_SPF_LOOKUPS {
+                policy.too_many_lookups = true;
+                return policy;
+            }
+            let Ok(records) = txt_records(resolver, &target).await else { continue };
+            if let Some(record) = records.iter().find_map(|txt| SpfRecord::parse(txt)) {
+                queue.push((target, record));
+            }
+        }
+    }
+    policy
+}
+
+/// Check SPF, DKIM and DMARC of the domain in `from_address` against `smtp_host`
+pub async fn check(from_address: &str, smtp_host: &str) -> Result<SenderAuthReport, String> {
+    let domain = from_address
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.trim().to_lowercase())
+        .filter(|domain| domain.contains('.'))
+        .ok_or_else(|| format!("Invalid sender address: {}", from_address))?;
+    let smtp_host = smtp_host.trim().trim_end_matches('.').to_lowercase();
+
+    let mut report = SenderAuthReport {
+        custom_domain: !crate::mail::precheck::POPULAR_DOMAINS.contains(&domain.as_str()),
+        domain: domain.clone(),
+        smtp_host: smtp_host.clone(),
+        ..Default::default()
+    };
+    if !report.custom_domain {
+        return Ok(report);
+    }
+
+    let resolver = TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default());
+
+    // SPF
+    match txt_records(&resolver, &domain).await {
+        Ok(records) => {
+            let spf: Vec<&String> = records.iter().filter(|txt| SpfRecord::parse(txt).is_some()).collect();
+            if spf.len() > 1 {
+                report.warn(
+                    SenderAuthWarningKind::MultipleSpf,
+                    format!("{} publishes {} SPF records; receivers treat this as an error", domain, spf.len()),
+                );
+            }
+            match spf.first().and_then(|txt| SpfRecord::parse(txt).map(|record| (txt.to_string(), record))) {
+                Some((txt, record)) => {
+                    report.spf_record = Some(txt);
+                    if matches!(record.all_qualifier(), Some('+' | '?')) {
+                        report.warn(
+                            SenderAuthWarningKind::SpfPermissive,
+                            "SPF record allows any server to send for this domain",
+                        );
+                    }
+
+                    let policy = expand_spf(&resolver, &domain, &record).await;
+                    if policy.too_many_lookups {
+                        report.warn(
+                            SenderAuthWarningKind::SpfTooManyLookups,
+                            format!("SPF record needs more than {} DNS lookups; receivers may reject it", MAX_SPF_LOOKUPS),
+                        );
+                    }
+
+                    // Included provider domain matches the SMTP host (include:spf.mailgun.org for
+                    // smtp.mailgun.org), or the host's address is listed directly or via a/mx
+                    let provider = base_domain(&smtp_host);
+                    let mut covered = policy
+                        .domains
+                        .iter()
+                        .any(|included| *included != domain && base_domain(included) == provider);
+                    if !covered {
+                        let smtp_ips = host_ips(&resolver, &smtp_host).await;
+                        covered = smtp_ips.iter().any(|ip| policy.networks.iter().any(|net| net.contains(*ip)));
+                        for host in &policy.hosts {
+                            if covered {
+                                break;
+                            }
+                            let ips = host_ips(&resolver, host).await;
+                            covered = smtp_ips.iter().any(|ip| ips.contains(ip));
+                        }
+                    }
+                    report.spf_covers_smtp_host = covered;
+                    if !covered {
+                        report.warn(
+                            SenderAuthWarningKind::SpfExcludesSmtpHost,
+                            format!("SPF record of {} does not include {}", domain, smtp_host),
+                        );
+                    }
+                }
+                None => report.warn(SenderAuthWarningKind::NoSpf, format!("{} has no SPF record", domain)),
+            }
+        }
+        Err(e) => report.warn(SenderAuthWarningKind::DnsError, e),
+    }
+
+    // DKIM: a key under any common selector
+    for selector in DKIM_SELECTORS {
+        let name = format!("{}._domainkey.{}", selector, domain);
+        if let Ok(records) = txt_records(&resolver, &name).await {
+            if records.iter().any(|txt| txt.contains("p=")) {
+                report.dkim_selectors.push(selector.to_string());
+            }
+        }
+    }
+    if report.dkim_selectors.is_empty() {
+        report.warn(
+            SenderAuthWarningKind::NoDkim,
+            format!("No DKIM key found for {} under common selectors", domain),
+        );
+    }
+
+    // DMARC
+    if let Ok(records) = txt_records(&resolver, &format!("_dmarc.{}", domain)).await {
+        report.dmarc_record = records.into_iter().find(|txt| txt.to_lowercase().starts_with("v=dmarc1"));
+    }
+    if report.dmarc_record.is_none() {
+        report.warn(SenderAuthWarningKind::NoDmarc, format!("{} has no DMARC policy", domain));
+    }
+
+    report.likely_spam = !report.spf_covers_smtp_host && report.dkim_selectors.is_empty();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spf() {
+        let record = SpfRecord::parse("v=spf1 ip4:192.0.2.0/24 a mx:mail.example.com include:_spf.google.com -ip4:10.0.0.1 ~all").unwrap();
+        assert_eq!(
+            record.mechanisms,
+            vec![
+                SpfMechanism::Ip(IpNet::parse("192.0.2.0/24").unwrap()),
+                SpfMechanism::A(None),
+                SpfMechanism::Mx(Some("mail.example.com".to_string())),
+                SpfMechanism::Include("_spf.google.com".to_string()),
+                SpfMechanism::All('~'),
+            ]
+        );
+        assert_eq!(record.all_qualifier(), Some('~'));
+
+        let redirect = SpfRecord::parse("v=spf1 redirect=_spf.example.net").unwrap();
+        assert_eq!(redirect.redirect.as_deref(), Some("_spf.example.net"));
+        assert_eq!(SpfRecord::parse("google-site-verification=abc"), None);
+        assert_eq!(SpfRecord::parse("v=spf1 a/24 ?all").unwrap().mechanisms, vec![SpfMechanism::A(None), SpfMechanism::All('?')]);
+    }
+
+    #[test]
+    fn test_ip_networks() {
+        let net = IpNet::parse("192.0.2.0/24").unwrap();
+        assert!(net.contains("192.0.2.55".parse().unwrap()));
+        assert!(!net.contains("192.0.3.1".parse().unwrap()));
+        assert!(!net.contains("2001:db8::1".parse().unwrap()));
+
+        let host = IpNet::parse("198.51.100.7").unwrap();
+        assert!(host.contains("198.51.100.7".parse().unwrap()));
+        assert!(!host.contains("198.51.100.8".parse().unwrap()));
+
+        let v6 = IpNet::parse("2001:db8::/32").unwrap();
+        assert!(v6.contains("2001:db8:ffff::1".parse().unwrap()));
+        assert!(IpNet::parse("0.0.0.0/0").unwrap().contains("203.0.113.9".parse().unwrap()));
+        assert_eq!(IpNet::parse("192.0.2.0/33"), None);
+    }
+
+    #[test]
+    fn test_base_domain() {
+        assert_eq!(base_domain("smtp.gmail.com"), "gmail.com");
+        assert_eq!(base_domain("_spf.google.com"), "google.com");
+        assert_eq!(base_domain("localhost"), "localhost");
+    }
+}
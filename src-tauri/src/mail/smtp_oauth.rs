@@ -12,6 +12,8 @@ pub struct AttachmentData {
     pub filename: String,
     pub content_type: String,
     pub data: Vec<u8>,
+    /// Content-ID for inline images (embedded as multipart/related when body is HTML)
+    pub content_id: Option<String>,
 }
 
 /// Send email using SMTP with OAuth2 XOAUTH2 authentication
@@ -42,90 +44,16 @@ pub async fn send_email_oauth(
 
     // Run SMTP operations in blocking thread
     tokio::task::spawn_blocking(move || {
-        log::info!("SMTP OAuth: Connecting to {}:{}...", smtp_host, smtp_port);
-
-        // Connect to SMTP server with TLS
-        let tls_connector = native_tls::TlsConnector::builder()
-            .build()
-            .map_err(|e| {
-                log::error!("TLS builder error: {}", e);
-                MailError::Smtp(format!("TLS error: {}", e))
-            })?;
-
-        let stream = TcpStream::connect((smtp_host.as_str(), smtp_port))
-            .map_err(|e| {
-                log::error!("TCP connection failed to {}:{} - {}", smtp_host, smtp_port, e);
-                MailError::Smtp(format!("Connection failed: {}", e))
-            })?;
-
-        log::info!("TCP connected, starting TLS handshake...");
-
-        let mut tls_stream = tls_connector
-            .connect(&smtp_host, stream)
-            .map_err(|e| {
-                log::error!("TLS handshake failed: {}", e);
-                MailError::Smtp(format!("TLS handshake failed: {}", e))
-            })?;
-
-        // Read SMTP banner
-        let mut response = read_response(&mut tls_stream)?;
-        if !response.starts_with("220") {
-            return Err(MailError::Smtp(format!("Invalid SMTP banner: {}", response)));
-        }
-
-        // Send EHLO
-        send_command(&mut tls_stream, &format!("EHLO {}\r\n", smtp_host))?;
-        response = read_response(&mut tls_stream)?;
-        if !response.starts_with("250") {
-            return Err(MailError::Smtp(format!("EHLO failed: {}", response)));
-        }
-
-        // Send AUTH XOAUTH2
-        let auth_string = format!("user={}\x01auth=Bearer {}\x01\x01", email, access_token);
-        let auth_base64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, auth_string.as_bytes());
-        send_command(&mut tls_stream, &format!("AUTH XOAUTH2 {}\r\n", auth_base64))?;
-        response = read_response(&mut tls_stream)?;
-
-        if !response.starts_with("235") {
-            // If we get 334, we need to send an empty response
-            if response.starts_with("334") {
-                send_command(&mut tls_stream, "\r\n")?;
-                response = read_response(&mut tls_stream)?;
-            }
-
-            if !response.starts_with("235") {
-                return Err(MailError::Smtp(format!("OAuth2 authentication failed: {}. Try removing and re-adding the account.", response)));
-            }
-        }
-
-        log::info!("✓ SMTP OAuth2 authentication successful");
-
-        // Send MAIL FROM
-        send_command(&mut tls_stream, &format!("MAIL FROM:<{}>\r\n", from))?;
-        response = read_response(&mut tls_stream)?;
-        if !response.starts_with("250") {
-            return Err(MailError::Smtp(format!("MAIL FROM failed: {}", response)));
-        }
-
-        // Send RCPT TO for all recipients
-        for recipient in to.iter().chain(cc.iter()).chain(bcc.iter()) {
-            send_command(&mut tls_stream, &format!("RCPT TO:<{}>\r\n", recipient))?;
-            response = read_response(&mut tls_stream)?;
-            if !response.starts_with("250") {
-                return Err(MailError::Smtp(format!("RCPT TO failed for {}: {}", recipient, response)));
-            }
-        }
-
-        // Send DATA
-        send_command(&mut tls_stream, "DATA\r\n")?;
-        response = read_response(&mut tls_stream)?;
-        if !response.starts_with("354") {
-            return Err(MailError::Smtp(format!("DATA failed: {}", response)));
-        }
+        let mut tls_stream = connect(&smtp_host, smtp_port, &email, &access_token)?;
+        // The account is the envelope sender, also when sending on behalf of another mailbox
+        start_data(&mut tls_stream, &email, to.iter().chain(cc.iter()).chain(bcc.iter()))?;
 
         // Build email message
         let mut email_data = String::new();
         email_data.push_str(&format!("From: {}\r\n", from));
+        if !from.eq_ignore_ascii_case(&email) {
+            email_data.push_str(&format!("Sender: {}\r\n", email));
+        }
 
         if !to.is_empty() {
             email_data.push_str(&format!("To: {}\r\n", to.join(", ")));
@@ -138,43 +66,28 @@ pub async fn send_email_oauth(
         email_data.push_str(&format!("Subject: {}\r\n", subject));
         email_data.push_str("MIME-Version: 1.0\r\n");
 
-        // Use multipart if there are attachments
-        if attachments.is_empty() {
-            // Simple message without attachments
-            if is_html {
-                email_data.push_str("Content-Type: text/html; charset=utf-8\r\n");
-            } else {
-                email_data.push_str("Content-Type: text/plain; charset=utf-8\r\n");
-            }
-            email_data.push_str("\r\n");
-            email_data.push_str(&body);
+        // Inline images only make sense for HTML bodies
+        let (inline, regular): (Vec<&AttachmentData>, Vec<&AttachmentData>) = attachments
+            .iter()
+            .partition(|a| is_html && a.content_id.is_some());
+
+        if regular.is_empty() {
+            // Readable body (optionally multipart/related) is the top-level part
+            write_body_part(&mut email_data, &body, is_html, &inline);
         } else {
             // Multipart message with attachments
-            let boundary = format!("----=_Part_{}_{}",
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                uuid::Uuid::new_v4().to_string().replace('-', "")
-            );
+            let boundary = new_boundary();
 
             email_data.push_str(&format!("Content-Type: multipart/mixed; boundary=\"{}\"\r\n", boundary));
             email_data.push_str("\r\n");
             email_data.push_str(&format!("--{}\r\n", boundary));
 
             // Body part
-            if is_html {
-                email_data.push_str("Content-Type: text/html; charset=utf-8\r\n");
-            } else {
-                email_data.push_str("Content-Type: text/plain; charset=utf-8\r\n");
-            }
-            email_data.push_str("Content-Transfer-Encoding: 8bit\r\n");
-            email_data.push_str("\r\n");
-            email_data.push_str(&body);
+            write_body_part(&mut email_data, &body, is_html, &inline);
             email_data.push_str("\r\n");
 
             // Attachment parts
-            for attachment in &attachments {
+            for attachment in regular {
                 email_data.push_str(&format!("--{}\r\n", boundary));
                 email_data.push_str(&format!("Content-Type: {}; name=\"{}\"\r\n",
                     attachment.content_type, attachment.filename));
@@ -182,18 +95,7 @@ pub async fn send_email_oauth(
                 email_data.push_str(&format!("Content-Disposition: attachment; filename=\"{}\"\r\n",
                     attachment.filename));
                 email_data.push_str("\r\n");
-
-                // Encode attachment data as base64
-                let base64_data = base64::Engine::encode(
-                    &base64::engine::general_purpose::STANDARD,
-                    &attachment.data
-                );
-
-                // Split base64 into 76-character lines (RFC 2045)
-                for chunk in base64_data.as_bytes().chunks(76) {
-                    email_data.push_str(&String::from_utf8_lossy(chunk));
-                    email_data.push_str("\r\n");
-                }
+                push_base64_lines(&mut email_data, &attachment.data);
             }
 
             email_data.push_str(&format!("--{}--\r\n", boundary));
@@ -203,7 +105,7 @@ pub async fn send_email_oauth(
 
         // Send email data
         send_command(&mut tls_stream, &email_data)?;
-        response = read_response(&mut tls_stream)?;
+        let response = read_response(&mut tls_stream)?;
         if !response.starts_with("250") {
             return Err(MailError::Smtp(format!("Send failed: {}", response)));
         }
@@ -224,6 +126,233 @@ pub async fn send_email_oauth(
     Ok(())
 }
 
+/// Send an already formatted message (CRLF line endings) with OAuth2 XOAUTH2 authentication
+pub async fn send_raw_oauth(
+    smtp_host: &str,
+    smtp_port: u16,
+    email: &str,
+    access_token: &str,
+    from: &str,
+    recipients: &[String],
+    raw: &[u8],
+) -> Result<(), MailError> {
+    let smtp_host = smtp_host.to_string();
+    let email = email.to_string();
+    let access_token = access_token.to_string();
+    let from = from.to_string();
+    let recipients = recipients.to_vec();
+    let data = dot_stuff(raw);
+
+    tokio::task::spawn_blocking(move || {
+        let mut tls_stream = connect(&smtp_host, smtp_port, &email, &access_token)?;
+        start_data(&mut tls_stream, &from, recipients.iter())?;
+
+        tls_stream
+            .write_all(&data)
+            .and_then(|_| tls_stream.write_all(b".\r\n"))
+            .and_then(|_| tls_stream.flush())
+            .map_err(|e| MailError::Smtp(format!("Write error: {}", e)))?;
+        let response = read_response(&mut tls_stream)?;
+        if !response.starts_with("250") {
+            return Err(MailError::Smtp(format!("Send failed: {}", response)));
+        }
+
+        send_command(&mut tls_stream, "QUIT\r\n")?;
+        let _ = read_response(&mut tls_stream);
+
+        log::info!("✓ Raw message sent via OAuth2 SMTP");
+        Ok(())
+    })
+    .await
+    .map_err(|e| MailError::Smtp(format!("Spawn blocking error: {}", e)))?
+}
+
+/// Double leading dots so no line ends the DATA phase early (RFC 5321 section 4.5.2)
+fn dot_stuff(raw: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(raw.len() + 64);
+    let mut line_start = true;
+    for &byte in raw {
+        if line_start && byte == b'.' {
+            data.push(b'.');
+        }
+        data.push(byte);
+        line_start = byte == b'\n';
+    }
+    if !data.ends_with(b"\r\n") {
+        data.extend_from_slice(b"\r\n");
+    }
+    data
+}
+
+/// Connect over TLS and authenticate with XOAUTH2
+fn connect(
+    smtp_host: &str,
+    smtp_port: u16,
+    email: &str,
+    access_token: &str,
+) -> Result<native_tls::TlsStream<TcpStream>, MailError> {
+    log::info!("SMTP OAuth: Connecting to {}:{}...", smtp_host, smtp_port);
+
+    // Connect to SMTP server with TLS
+    let tls_connector = native_tls::TlsConnector::builder()
+        .build()
+        .map_err(|e| {
+            log::error!("TLS builder error: {}", e);
+            MailError::Smtp(format!("TLS error: {}", e))
+        })?;
+
+    let stream = TcpStream::connect((smtp_host, smtp_port))
+        .map_err(|e| {
+            log::error!("TCP connection failed to {}:{} - {}", smtp_host, smtp_port, e);
+            MailError::Connection(e.to_string())
+        })?;
+
+    log::info!("TCP connected, starting TLS handshake...");
+
+    let mut tls_stream = tls_connector
+        .connect(smtp_host, stream)
+        .map_err(|e| {
+            log::error!("TLS handshake failed: {}", e);
+            MailError::Smtp(format!("TLS handshake failed: {}", e))
+        })?;
+
+    // Read SMTP banner
+    let mut response = read_response(&mut tls_stream)?;
+    if !response.starts_with("220") {
+        return Err(MailError::Smtp(format!("Invalid SMTP banner: {}", response)));
+    }
+
+    // Send EHLO
+    send_command(&mut tls_stream, &format!("EHLO {}\r\n", smtp_host))?;
+    response = read_response(&mut tls_stream)?;
+    if !response.starts_with("250") {
+        return Err(MailError::Smtp(format!("EHLO failed: {}", response)));
+    }
+
+    // Send AUTH XOAUTH2
+    let auth_string = format!("user={}\x01auth=Bearer {}\x01\x01", email, access_token);
+    let auth_base64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, auth_string.as_bytes());
+    send_command(&mut tls_stream, &format!("AUTH XOAUTH2 {}\r\n", auth_base64))?;
+    response = read_response(&mut tls_stream)?;
+
+    if !response.starts_with("235") {
+        // If we get 334, we need to send an empty response
+        if response.starts_with("334") {
+            send_command(&mut tls_stream, "\r\n")?;
+            response = read_response(&mut tls_stream)?;
+        }
+
+        if !response.starts_with("235") {
+            return Err(MailError::Smtp(format!("OAuth2 authentication failed: {}. Try removing and re-adding the account.", response)));
+        }
+    }
+
+    log::info!("✓ SMTP OAuth2 authentication successful");
+    Ok(tls_stream)
+}
+
+/// Announce the envelope and start the DATA phase
+fn start_data<'a>(
+    tls_stream: &mut native_tls::TlsStream<TcpStream>,
+    from: &str,
+    recipients: impl Iterator<Item = &'a String>,
+) -> Result<(), MailError> {
+    // Send MAIL FROM
+    send_command(tls_stream, &format!("MAIL FROM:<{}>\r\n", from))?;
+    let mut response = read_response(tls_stream)?;
+    if !response.starts_with("250") {
+        return Err(MailError::Smtp(format!("MAIL FROM failed: {}", response)));
+    }
+
+    // Send RCPT TO for all recipients
+    for recipient in recipients {
+        send_command(tls_stream, &format!("RCPT TO:<{}>\r\n", recipient))?;
+        response = read_response(tls_stream)?;
+        if !response.starts_with("250") {
+            return Err(MailError::Smtp(format!("RCPT TO failed for {}: {}", recipient, response)));
+        }
+    }
+
+    // Send DATA
+    send_command(tls_stream, "DATA\r\n")?;
+    response = read_response(tls_stream)?;
+    if !response.starts_with("354") {
+        return Err(MailError::Smtp(format!("DATA failed: {}", response)));
+    }
+    Ok(())
+}
+
+/// Generate a unique MIME boundary
+fn new_boundary() -> String {
+    format!("----=_Part_{}_{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        uuid::Uuid::new_v4().to_string().replace('-', "")
+    )
+}
+
+/// Encode data as base64 split into 76-character lines (RFC 2045)
+fn push_base64_lines(email_data: &mut String, data: &[u8]) {
+    let base64_data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data);
+    for chunk in base64_data.as_bytes().chunks(76) {
+        email_data.push_str(&String::from_utf8_lossy(chunk));
+        email_data.push_str("\r\n");
+    }
+}
+
+/// Write the readable body part (headers + content)
+/// HTML with inline images is wrapped in multipart/related with Content-ID parts
+fn write_body_part(email_data: &mut String, body: &str, is_html: bool, inline: &[&AttachmentData]) {
+    let body_content_type = if is_html {
+        "Content-Type: text/html; charset=utf-8\r\n"
+    } else {
+        "Content-Type: text/plain; charset=utf-8\r\n"
+    };
+
+    if inline.is_empty() {
+        email_data.push_str(body_content_type);
+        email_data.push_str("Content-Transfer-Encoding: 8bit\r\n");
+        email_data.push_str("\r\n");
+        email_data.push_str(body);
+        return;
+    }
+
+    let boundary = new_boundary();
+    email_data.push_str(&format!(
+        "Content-Type: multipart/related; type=\"text/html\"; boundary=\"{}\"\r\n",
+        boundary
+    ));
+    email_data.push_str("\r\n");
+    email_data.push_str(&format!("--{}\r\n", boundary));
+    email_data.push_str(body_content_type);
+    email_data.push_str("Content-Transfer-Encoding: 8bit\r\n");
+    email_data.push_str("\r\n");
+    email_data.push_str(body);
+    email_data.push_str("\r\n");
+
+    for attachment in inline {
+        let content_id = attachment
+            .content_id
+            .as_deref()
+            .map(crate::mail::compose::normalize_content_id)
+            .unwrap_or_default();
+
+        email_data.push_str(&format!("--{}\r\n", boundary));
+        email_data.push_str(&format!("Content-Type: {}; name=\"{}\"\r\n",
+            attachment.content_type, attachment.filename));
+        email_data.push_str("Content-Transfer-Encoding: base64\r\n");
+        email_data.push_str(&format!("Content-ID: <{}>\r\n", content_id));
+        email_data.push_str(&format!("Content-Disposition: inline; filename=\"{}\"\r\n",
+            attachment.filename));
+        email_data.push_str("\r\n");
+        push_base64_lines(email_data, &attachment.data);
+    }
+
+    email_data.push_str(&format!("--{}--\r\n", boundary));
+}
+
 /// Send SMTP command
 fn send_command(stream: &mut native_tls::TlsStream<TcpStream>, command: &str) -> Result<(), MailError> {
     stream
@@ -246,3 +375,13 @@ fn read_response(stream: &mut native_tls::TlsStream<TcpStream>) -> Result<String
     log::debug!("SMTP Response: {}", response.trim());
     Ok(response)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_stuff() {
+        assert_eq!(dot_stuff(b".hidden\r\nok\r\n.\r\nend"), b"..hidden\r\nok\r\n..\r\nend\r\n".to_vec());
+    }
+}
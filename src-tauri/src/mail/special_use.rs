@@ -0,0 +1,170 @@
+//! Special-Use Folder Detection
+//!
+//! Classifies mailboxes by the SPECIAL-USE (RFC 6154) and Gmail XLIST flags
+//! reported in LIST responses, so localized folders such as "Gönderilmiş Öğeler"
+//! are recognised as Sent. Folder names are only a fallback for servers that
+//! advertise no flags.
+
+use super::{Folder, FolderType};
+
+/// Normalize an attribute from the `imap` crate (OAuth path) to its wire form
+pub fn imap_attribute(attribute: &imap::types::NameAttribute) -> String {
+    use imap::types::NameAttribute;
+
+    match attribute {
+        NameAttribute::NoInferiors => "\\Noinferiors".to_string(),
+        NameAttribute::NoSelect => "\\Noselect".to_string(),
+        NameAttribute::Marked => "\\Marked".to_string(),
+        NameAttribute::Unmarked => "\\Unmarked".to_string(),
+        NameAttribute::Custom(value) => value.to_string(),
+    }
+}
+
+/// Normalize an attribute from `async-imap` to its wire form
+pub fn async_imap_attribute(attribute: &::async_imap::types::NameAttribute) -> String {
+    use ::async_imap::types::NameAttribute;
+
+    match attribute {
+        NameAttribute::NoInferiors => "\\Noinferiors".to_string(),
+        NameAttribute::NoSelect => "\\Noselect".to_string(),
+        NameAttribute::Marked => "\\Marked".to_string(),
+        NameAttribute::Unmarked => "\\Unmarked".to_string(),
+        NameAttribute::All => "\\All".to_string(),
+        NameAttribute::Archive => "\\Archive".to_string(),
+        NameAttribute::Drafts => "\\Drafts".to_string(),
+        NameAttribute::Flagged => "\\Flagged".to_string(),
+        NameAttribute::Junk => "\\Junk".to_string(),
+        NameAttribute::Sent => "\\Sent".to_string(),
+        NameAttribute::Trash => "\\Trash".to_string(),
+        NameAttribute::Extension(value) => value.to_string(),
+        // The enum is non-exhaustive; keep whatever newer releases add
+        other => format!("{:?}", other),
+    }
+}
+
+/// Whether the attributes mark a mailbox as not selectable
+pub fn is_noselect(attributes: &[String]) -> bool {
+    attributes
+        .iter()
+        .any(|a| a.eq_ignore_ascii_case("\\Noselect") || a.eq_ignore_ascii_case("\\NonExistent"))
+}
+
+/// Folder type announced by SPECIAL-USE or XLIST attributes
+pub fn special_use_type(attributes: &[String]) -> Option<FolderType> {
+    attributes.iter().find_map(|attribute| {
+        match attribute.to_ascii_lowercase().as_str() {
+            "\\inbox" => Some(FolderType::Inbox),
+            "\\sent" => Some(FolderType::Sent),
+            "\\drafts" => Some(FolderType::Drafts),
+            "\\trash" => Some(FolderType::Trash),
+            "\\junk" | "\\spam" => Some(FolderType::Junk),
+            // Gmail's "All Mail" is the closest thing it has to an archive
+            "\\archive" | "\\all" | "\\allmail" => Some(FolderType::Archive),
+            "\\flagged" | "\\starred" => Some(FolderType::Starred),
+            _ => None,
+        }
+    })
+}
+
+/// Assign folder types, preferring server flags over names
+///
+/// A name-based guess never overrides a role the server already assigned to
+/// another mailbox (e.g. a user folder called "Sent 2019" next to a `\Sent`).
+pub fn classify_folders(folders: &mut [Folder]) {
+    let mut flagged: Vec<FolderType> = Vec::new();
+
+    for folder in folders.iter_mut() {
+        if folder.path.eq_ignore_ascii_case("INBOX") {
+            folder.folder_type = FolderType::Inbox;
+            flagged.push(FolderType::Inbox);
+        } else if let Some(folder_type) = special_use_type(&folder.attributes) {
+            flagged.push(folder_type.clone());
+            folder.folder_type = folder_type;
+        }
+    }
+
+    for folder in folders.iter_mut() {
+        if folder.path.eq_ignore_ascii_case("INBOX") || special_use_type(&folder.attributes).is_some() {
+            continue;
+        }
+
+        let guess = FolderType::from_name(&folder.path);
+        folder.folder_type = if flagged.contains(&guess) {
+            FolderType::Custom
+        } else {
+            guess
+        };
+    }
+}
+
+/// Remote path of the first folder with the given type
+pub fn find_folder(folders: &[Folder], folder_type: &FolderType) -> Option<String> {
+    folders
+        .iter()
+        .find(|f| &f.folder_type == folder_type && f.is_selectable)
+        .map(|f| f.path.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(path: &str, attributes: &[&str]) -> Folder {
+        Folder {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            folder_type: FolderType::Custom,
+            delimiter: "/".to_string(),
+            is_subscribed: true,
+            is_selectable: true,
+            unread_count: 0,
+            total_count: 0,
+            attributes: attributes.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_localized_folders_use_special_use_flags() {
+        let mut folders = vec![
+            folder("INBOX", &["\\HasNoChildren"]),
+            folder("Gönderilmiş Öğeler", &["\\HasNoChildren", "\\Sent"]),
+            folder("Taslaklar", &["\\Drafts"]),
+            folder("Çöp Kutusu", &["\\Trash"]),
+            folder("Önemsiz", &["\\Junk"]),
+            folder("[Gmail]/Tüm Postalar", &["\\All"]),
+            folder("Sent 2019", &[]),
+            folder("Projects", &[]),
+        ];
+
+        classify_folders(&mut folders);
+
+        let types: Vec<FolderType> = folders.iter().map(|f| f.folder_type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                FolderType::Inbox,
+                FolderType::Sent,
+                FolderType::Drafts,
+                FolderType::Trash,
+                FolderType::Junk,
+                FolderType::Archive,
+                FolderType::Custom,
+                FolderType::Custom,
+            ]
+        );
+        assert_eq!(find_folder(&folders, &FolderType::Trash), Some("Çöp Kutusu".to_string()));
+    }
+
+    #[test]
+    fn test_name_fallback_without_flags() {
+        let mut folders = vec![folder("INBOX", &[]), folder("Sent Items", &[]), folder("Deleted Items", &[])];
+
+        classify_folders(&mut folders);
+
+        assert_eq!(folders[1].folder_type, FolderType::Sent);
+        assert_eq!(folders[2].folder_type, FolderType::Trash);
+        assert_eq!(special_use_type(&["\\XLIST-Spam".to_string()]), None);
+        assert_eq!(special_use_type(&["\\Spam".to_string()]), Some(FolderType::Junk));
+        assert!(is_noselect(&["\\NoSelect".to_string()]));
+    }
+}
@@ -0,0 +1,226 @@
+//! Mailbox Statistics
+//!
+//! Where an account's space goes, for cleaning up a mailbox that is close to
+//! its quota: the largest messages, the senders whose mail takes up the most
+//! room, how many messages each year holds and how long unread mail has been
+//! sitting. Sizes are the RFC822.SIZE reported at sync; messages synced before
+//! sizes were recorded count as 0 bytes until they are fetched again.
+
+use crate::db::{Database, DbResult};
+use chrono::{DateTime, Datelike, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Entries in the largest-message and sender lists
+const TOP_LIMIT: u32 = 20;
+
+/// Upper bounds (in days) of the unread aging buckets; the last one is open-ended
+const AGING_BUCKETS: &[(&str, Option<i64>)] = &[
+    ("week", Some(7)),
+    ("month", Some(30)),
+    ("quarter", Some(90)),
+    ("year", Some(365)),
+    ("older", None),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct LargeMessage {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub email_id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub folder_id: i64,
+    pub subject: String,
+    pub from_address: String,
+    pub date: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub size: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SenderVolume {
+    pub from_address: String,
+    pub from_name: Option<String>,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub messages: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total_size: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct YearCount {
+    pub year: i32,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub messages: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total_size: i64,
+}
+
+/// Unread messages received within a span of days
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AgingBucket {
+    /// `week`, `month`, `quarter`, `year` or `older`
+    pub label: String,
+    /// Upper bound in days (`None` for the last bucket)
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub max_days: Option<i64>,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub messages: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct MailboxStats {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total_messages: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub total_size: i64,
+    pub largest_messages: Vec<LargeMessage>,
+    pub top_senders: Vec<SenderVolume>,
+    /// Oldest year first; messages with an unreadable date are left out
+    pub per_year: Vec<YearCount>,
+    pub unread_aging: Vec<AgingBucket>,
+}
+
+/// Dates are stored as RFC 3339, or as the envelope's RFC 2822 text for older rows
+pub(crate) fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .or_else(|_| DateTime::parse_from_rfc2822(date))
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Statistics for an account's cached messages, with aging measured from `now`
+pub fn compute(db: &Database, account_id: i64, now: DateTime<Utc>) -> DbResult<MailboxStats> {
+    let (total_messages, total_size) = db.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(raw_size), 0) FROM emails WHERE account_id = ?1 AND is_deleted = 0",
+        params![account_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let largest_messages = db.query(
+        "SELECT id, folder_id, subject, from_address, date, raw_size FROM emails
+         WHERE account_id = ?1 AND is_deleted = 0 AND raw_size > 0
+         ORDER BY raw_size DESC, id LIMIT ?2",
+        params![account_id, TOP_LIMIT],
+        |row| {
+            Ok(LargeMessage {
+                email_id: row.get(0)?,
+                folder_id: row.get(1)?,
+                subject: row.get(2)?,
+                from_address: row.get(3)?,
+                date: row.get(4)?,
+                size: row.get(5)?,
+            })
+        },
+    )?;
+
+    let top_senders = db.query(
+        "SELECT lower(from_address), MAX(from_name), COUNT(*), SUM(raw_size) FROM emails
+         WHERE account_id = ?1 AND is_deleted = 0
+         GROUP BY lower(from_address)
+         ORDER BY SUM(raw_size) DESC, COUNT(*) DESC LIMIT ?2",
+        params![account_id, TOP_LIMIT],
+        |row| {
+            Ok(SenderVolume {
+                from_address: row.get(0)?,
+                from_name: row.get(1)?,
+                messages: row.get(2)?,
+                total_size: row.get(3)?,
+            })
+        },
+    )?;
+
+    let rows: Vec<(String, i64, bool)> = db.query(
+        "SELECT date, raw_size, is_read FROM emails WHERE account_id = ?1 AND is_deleted = 0",
+        params![account_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+
+    let mut years: BTreeMap<i32, YearCount> = BTreeMap::new();
+    let mut unread_aging: Vec<AgingBucket> = AGING_BUCKETS
+        .iter()
+        .map(|(label, max_days)| AgingBucket { label: label.to_string(), max_days: *max_days, messages: 0 })
+        .collect();
+    for (date, size, is_read) in rows {
+        let Some(date) = parse_date(&date) else {
+            continue;
+        };
+        let year = years.entry(date.year()).or_insert(YearCount { year: date.year(), messages: 0, total_size: 0 });
+        year.messages += 1;
+        year.total_size += size;
+
+        if !is_read {
+            let age = (now - date).num_days().max(0);
+            if let Some(bucket) = unread_aging.iter_mut().find(|bucket| bucket.max_days.is_none_or(|max| age < max)) {
+                bucket.messages += 1;
+            }
+        }
+    }
+
+    Ok(MailboxStats {
+        total_messages,
+        total_size,
+        largest_messages,
+        top_senders,
+        per_year: years.into_values().collect(),
+        unread_aging,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewEmail, NewFolder};
+
+    #[test]
+    fn test_compute() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+        for (uid, from, date, size, is_read) in [
+            (1, "news@shop.test", "2024-06-10T08:00:00Z", 900_000, false),
+            (2, "News@shop.test", "Mon, 3 Jun 2024 08:00:00 +0000", 100_000, true),
+            (3, "ana@test.com", "2023-01-01T00:00:00Z", 5_000_000, false),
+        ] {
+            db.upsert_email(&NewEmail {
+                from_address: from.to_string(),
+                date: date.to_string(),
+                is_read,
+                raw_size: size,
+                ..test_email(account_id, folder_id, uid)
+            })
+            .unwrap();
+        }
+
+        let now = parse_date("2024-06-12T00:00:00Z").unwrap();
+        let stats = compute(&db, account_id, now).unwrap();
+        assert_eq!(stats.total_messages, 3);
+        assert_eq!(stats.total_size, 6_000_000);
+        assert_eq!(stats.largest_messages[0].from_address, "ana@test.com");
+        assert_eq!(stats.top_senders[1].from_address, "news@shop.test");
+        assert_eq!(stats.top_senders[1].messages, 2);
+        assert_eq!(stats.per_year.iter().map(|year| (year.year, year.messages)).collect::<Vec<_>>(), [(2023, 1), (2024, 2)]);
+        assert_eq!(stats.unread_aging[0].messages, 1);
+        assert_eq!(stats.unread_aging[4].messages, 1);
+    }
+}
@@ -0,0 +1,600 @@
+//! Mail Merge
+//!
+//! Sends one personalized message per row of a CSV file. Every `{{column}}`
+//! in the template's subject and bodies is replaced by that row's value
+//! (HTML-escaped in the HTML body). The template is copied into the merge
+//! when it is created, so editing it later does not change a running merge.
+//!
+//! Messages go out one at a time with a pause between them to stay below the
+//! provider's sending limits. Each recipient's status is stored, so a merge
+//! stopped by a lost connection, a rejected login, a used-up daily sending
+//! quota or an app restart resumes with the recipients not yet sent. A
+//! recipient the server refuses is marked failed and the merge continues.
+
+use crate::db::{Database, DbError, DbResult, EmailTemplate};
+use crate::mail::{html_text, MailError, MailResult};
+use crate::pending_ops::{self, QueuedSend};
+use async_trait::async_trait;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Tauri event name for merge progress
+pub const MAILMERGE_PROGRESS_EVENT: &str = "mailmerge://progress";
+
+/// Sending rate when none is given
+pub const DEFAULT_PER_MINUTE: u32 = 20;
+
+/// Highest sending rate accepted
+pub const MAX_PER_MINUTE: u32 = 120;
+
+/// Rows accepted from one CSV file
+pub const MAX_RECIPIENTS: usize = 5000;
+
+/// Header names recognized as the recipient address column (lowercase)
+const EMAIL_COLUMNS: &[&str] = &["email", "e-mail", "email_address", "recipient_email", "e-posta", "eposta"];
+
+/// State of a merge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "MailMergeState"))]
+#[serde(rename_all = "snake_case")]
+pub enum MergeState {
+    Running,
+    /// Stopped by a connection or login failure, or by quitting the app
+    Paused,
+    Completed,
+    Cancelled,
+}
+
+impl MergeState {
+    fn as_str(self) -> &'static str {
+        match self {
+            MergeState::Running => "running",
+            MergeState::Paused => "paused",
+            MergeState::Completed => "completed",
+            MergeState::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "running" => MergeState::Running,
+            "completed" => MergeState::Completed,
+            "cancelled" => MergeState::Cancelled,
+            _ => MergeState::Paused,
+        }
+    }
+}
+
+/// Counts of a merge (payload of `mailmerge://progress`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "MailMergeProgress"))]
+#[serde(rename_all = "camelCase")]
+pub struct MergeProgress {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub merge_id: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub state: MergeState,
+    pub total: u32,
+    pub sent: u32,
+    pub failed: u32,
+    pub pending: u32,
+    /// Why the merge was paused
+    pub last_error: Option<String>,
+}
+
+/// Delivery of one recipient
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "MailMergeRecipient"))]
+#[serde(rename_all = "camelCase")]
+pub struct MergeRecipient {
+    pub email: String,
+    /// "pending", "sent" or "failed"
+    pub status: String,
+    pub error: Option<String>,
+    pub sent_at: Option<String>,
+}
+
+/// A merge with the status of every recipient
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "MailMergeStatus"))]
+#[serde(rename_all = "camelCase")]
+pub struct MergeStatus {
+    #[serde(flatten)]
+    pub progress: MergeProgress,
+    pub per_minute: u32,
+    pub created_at: String,
+    pub recipients: Vec<MergeRecipient>,
+}
+
+/// One CSV row: recipient address and the row's values by column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecipientRow {
+    pub email: String,
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Sends the rendered messages (SMTP in the app, a fake in tests)
+#[async_trait]
+pub trait MergeSender: Send + Sync {
+    async fn send(&self, account_id: i64, message: &QueuedSend) -> MailResult<()>;
+}
+
+/// Parse CSV text (RFC 4180 quoting; ',', ';' or tab separated)
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let text = text.trim_start_matches('\u{feff}');
+    let header = text.lines().next().unwrap_or_default();
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|&d| header.matches(d).count())
+        .unwrap_or(',');
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            c if c == delimiter && !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+fn is_valid_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !address.contains(|c: char| c.is_whitespace() || c.is_control() || c == '<' || c == '>')
+                && address.len() <= 254
+        }
+        None => false,
+    }
+}
+
+/// Recipients of a CSV file; variables are named by the lowercase header
+/// Rows repeating an earlier address are skipped.
+pub fn recipients_from_csv(csv: &str) -> Result<(Vec<String>, Vec<RecipientRow>), String> {
+    let mut rows = parse_csv(csv).into_iter();
+    let columns: Vec<String> = rows
+        .next()
+        .ok_or("The CSV file is empty")?
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    let email_column = columns
+        .iter()
+        .position(|name| EMAIL_COLUMNS.contains(&name.as_str()))
+        .ok_or("The CSV file needs an \"email\" column")?;
+
+    let mut seen = HashSet::new();
+    let mut recipients = Vec::new();
+    for (index, row) in rows.enumerate() {
+        let email = row.get(email_column).map(|value| value.trim()).unwrap_or_default();
+        if !is_valid_address(email) {
+            // Row 1 is the header
+            return Err(format!("Row {}: invalid email address \"{}\"", index + 2, email));
+        }
+        if !seen.insert(email.to_lowercase()) {
+            continue;
+        }
+        let variables = columns
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !name.is_empty())
+            .map(|(i, name)| (name.clone(), row.get(i).map(|value| value.trim().to_string()).unwrap_or_default()))
+            .collect();
+        recipients.push(RecipientRow { email: email.to_string(), variables });
+    }
+
+    if recipients.is_empty() {
+        return Err("The CSV file has no recipients".to_string());
+    }
+    if recipients.len() > MAX_RECIPIENTS {
+        return Err(format!("Too many recipients (max {})", MAX_RECIPIENTS));
+    }
+    Ok((columns, recipients))
+}
+
+/// Variable names used in a template (lowercase, in order of appearance)
+pub fn template_variables(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + end].trim().to_lowercase();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+        rest = &rest[start + 2 + end + 2..];
+    }
+    names
+}
+
+/// Replace `{{name}}` with the row's values; unknown names are left as they are
+pub fn render(template: &str, variables: &BTreeMap<String, String>, escape: fn(&str) -> String) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + end].trim().to_lowercase();
+        match variables.get(&name) {
+            Some(value) => output.push_str(&escape(value)),
+            None => output.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &rest[start + 2 + end + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Subjects are a single header line
+fn subject_value(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+/// Create a merge from a template and a CSV file
+pub fn create(db: &Database, account_id: i64, template: &EmailTemplate, csv: &str, per_minute: u32) -> DbResult<i64> {
+    if per_minute == 0 || per_minute > MAX_PER_MINUTE {
+        return Err(DbError::Constraint(format!("Sending rate must be 1-{} per minute", MAX_PER_MINUTE)));
+    }
+    let (columns, recipients) = recipients_from_csv(csv).map_err(DbError::Constraint)?;
+
+    let texts = [
+        template.subject_template.as_str(),
+        template.body_html_template.as_str(),
+        template.body_text_template.as_deref().unwrap_or_default(),
+    ];
+    let mut missing: Vec<String> = Vec::new();
+    for name in texts.iter().flat_map(|text| template_variables(text)) {
+        if !columns.contains(&name) && !missing.contains(&name) {
+            missing.push(name);
+        }
+    }
+    if !missing.is_empty() {
+        return Err(DbError::Constraint(format!("No CSV column for: {}", missing.join(", "))));
+    }
+
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO mail_merges (account_id, template_id, subject_template, body_html_template, body_text_template, per_minute)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            account_id,
+            template.id,
+            template.subject_template,
+            template.body_html_template,
+            template.body_text_template,
+            per_minute
+        ],
+    )?;
+    let merge_id = tx.last_insert_rowid();
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO mail_merge_recipients (merge_id, position, email, variables) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (position, recipient) in recipients.iter().enumerate() {
+            let variables = serde_json::to_string(&recipient.variables).unwrap_or_else(|_| "{}".to_string());
+            insert.execute(params![merge_id, position as i64, recipient.email, variables])?;
+        }
+    }
+    tx.commit()?;
+    Ok(merge_id)
+}
+
+pub fn progress(db: &Database, merge_id: i64) -> DbResult<MergeProgress> {
+    db.query_row(
+        "SELECT m.account_id, m.status, m.last_error,
+                COUNT(r.id),
+                COALESCE(SUM(r.status = 'sent'), 0),
+                COALESCE(SUM(r.status = 'failed'), 0),
+                COALESCE(SUM(r.status = 'pending'), 0)
+         FROM mail_merges m LEFT JOIN mail_merge_recipients r ON r.merge_id = m.id
+         WHERE m.id = ?1
+         GROUP BY m.id",
+        [merge_id],
+        |row| {
+            Ok(MergeProgress {
+                merge_id,
+                account_id: row.get(0)?,
+                state: MergeState::parse(&row.get::<_, String>(1)?),
+                last_error: row.get(2)?,
+                total: row.get(3)?,
+                sent: row.get(4)?,
+                failed: row.get(5)?,
+                pending: row.get(6)?,
+            })
+        },
+    )
+}
+
+pub fn status(db: &Database, merge_id: i64) -> DbResult<MergeStatus> {
+    let progress = progress(db, merge_id)?;
+    let (per_minute, created_at) = db.query_row(
+        "SELECT per_minute, created_at FROM mail_merges WHERE id = ?1",
+        [merge_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let recipients = db.query(
+        "SELECT email, status, error, sent_at FROM mail_merge_recipients WHERE merge_id = ?1 ORDER BY position",
+        [merge_id],
+        |row| {
+            Ok(MergeRecipient {
+                email: row.get(0)?,
+                status: row.get(1)?,
+                error: row.get(2)?,
+                sent_at: row.get(3)?,
+            })
+        },
+    )?;
+    Ok(MergeStatus { progress, per_minute, created_at, recipients })
+}
+
+fn set_state(db: &Database, merge_id: i64, state: MergeState, error: Option<&str>) -> DbResult<()> {
+    db.execute(
+        "UPDATE mail_merges SET status = ?2, last_error = ?3, updated_at = datetime('now') WHERE id = ?1",
+        params![merge_id, state.as_str(), error],
+    )?;
+    Ok(())
+}
+
+/// Cancel a merge that is not running (a running one is stopped via `RunningMerges`)
+pub fn cancel(db: &Database, merge_id: i64) -> DbResult<()> {
+    set_state(db, merge_id, MergeState::Cancelled, None)
+}
+
+/// Pause merges left running by a previous session so they can be resumed
+pub fn recover_interrupted(db: &Database) -> DbResult<usize> {
+    db.execute(
+        "UPDATE mail_merges SET status = 'paused', last_error = 'Interrupted', updated_at = datetime('now')
+         WHERE status = 'running'",
+        [],
+    )
+}
+
+/// Pause between two messages
+pub fn send_delay(per_minute: u32) -> Duration {
+    Duration::from_millis(60_000 / u64::from(per_minute.max(1)))
+}
+
+/// Whether a failure stops the merge rather than just its recipient
+fn stops_merge(error: &MailError) -> bool {
+    pending_ops::should_wait(error) || matches!(error, MailError::Authentication(_))
+}
+
+/// Send to the pending recipients of a merge
+/// Stops before the next message once `cancelled` is set.
+pub async fn run(
+    db: &Database,
+    sender: &dyn MergeSender,
+    merge_id: i64,
+    cancelled: &AtomicBool,
+    delay: Duration,
+    on_progress: impl Fn(&MergeProgress),
+) -> DbResult<MergeProgress> {
+    let (account_id, subject_template, html_template, text_template): (i64, String, String, Option<String>) = db
+        .query_row(
+            "SELECT account_id, subject_template, body_html_template, body_text_template FROM mail_merges WHERE id = ?1",
+            [merge_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
+    set_state(db, merge_id, MergeState::Running, None)?;
+
+    let pending = db.query(
+        "SELECT id, email, variables FROM mail_merge_recipients
+         WHERE merge_id = ?1 AND status = 'pending' ORDER BY position",
+        [merge_id],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+    )?;
+
+    for (index, (recipient_id, email, variables)) in pending.iter().enumerate() {
+        if cancelled.load(Ordering::SeqCst) {
+            set_state(db, merge_id, MergeState::Cancelled, None)?;
+            return progress(db, merge_id);
+        }
+        if index > 0 {
+            tokio::time::sleep(delay).await;
+        }
+
+        let variables: BTreeMap<String, String> = serde_json::from_str(variables).unwrap_or_default();
+        let html_body = render(&html_template, &variables, crate::mail::compose::escape_html);
+        let text_body = match &text_template {
+            Some(text) if !text.trim().is_empty() => render(text, &variables, str::to_string),
+            _ => html_text::html_to_text(&html_body),
+        };
+        let message = QueuedSend {
+            to: vec![email.clone()],
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            subject: subject_value(&render(&subject_template, &variables, str::to_string)),
+            text_body: Some(text_body),
+            html_body: Some(html_body),
+            attachment_paths: Vec::new(),
+            on_behalf_of: None,
+        };
+
+        match sender.send(account_id, &message).await {
+            Ok(()) => {
+                db.execute(
+                    "UPDATE mail_merge_recipients SET status = 'sent', error = NULL, sent_at = datetime('now') WHERE id = ?1",
+                    [recipient_id],
+                )?;
+            }
+            Err(e) if stops_merge(&e) => {
+                log::warn!("Mail merge {} paused: {}", merge_id, e);
+                set_state(db, merge_id, MergeState::Paused, Some(&e.to_string()))?;
+                let paused = progress(db, merge_id)?;
+                on_progress(&paused);
+                return Ok(paused);
+            }
+            Err(e) => {
+                db.execute(
+                    "UPDATE mail_merge_recipients SET status = 'failed', error = ?2 WHERE id = ?1",
+                    params![recipient_id, e.to_string()],
+                )?;
+            }
+        }
+        on_progress(&progress(db, merge_id)?);
+    }
+
+    set_state(db, merge_id, MergeState::Completed, None)?;
+    let done = progress(db, merge_id)?;
+    on_progress(&done);
+    Ok(done)
+}
+
+/// Merges being sent, with their cancel flags
+#[derive(Default)]
+pub struct RunningMerges {
+    running: Mutex<HashMap<i64, Arc<AtomicBool>>>,
+}
+
+impl RunningMerges {
+    /// Claim a merge; `None` when it is already being sent
+    pub fn try_begin(&self, merge_id: i64) -> Option<Arc<AtomicBool>> {
+        let mut running = self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if running.contains_key(&merge_id) {
+            return None;
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        running.insert(merge_id, flag.clone());
+        Some(flag)
+    }
+
+    /// Ask a running merge to stop; false when it is not running
+    pub fn cancel(&self, merge_id: i64) -> bool {
+        let running = self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        running.get(&merge_id).map(|flag| flag.store(true, Ordering::SeqCst)).is_some()
+    }
+
+    pub fn finish(&self, merge_id: i64) {
+        self.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(&merge_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    struct FakeSender {
+        sent: Mutex<Vec<QueuedSend>>,
+        /// Address the server refuses, and the number of sends before going offline
+        refused: &'static str,
+        offline_after: Option<usize>,
+    }
+
+    #[async_trait]
+    impl MergeSender for FakeSender {
+        async fn send(&self, _account_id: i64, message: &QueuedSend) -> MailResult<()> {
+            let mut sent = self.sent.lock().unwrap();
+            if self.offline_after.is_some_and(|limit| sent.len() >= limit) {
+                return Err(MailError::Connection("offline".to_string()));
+            }
+            if message.to[0] == self.refused {
+                return Err(MailError::Smtp("550 no such user".to_string()));
+            }
+            sent.push(message.clone());
+            Ok(())
+        }
+    }
+
+    fn template(subject: &str, html: &str) -> EmailTemplate {
+        EmailTemplate {
+            id: 0,
+            account_id: None,
+            name: "Invite".to_string(),
+            description: None,
+            category: "marketing".to_string(),
+            subject_template: subject.to_string(),
+            body_html_template: html.to_string(),
+            body_text_template: None,
+            tags: Vec::new(),
+            is_enabled: true,
+            is_favorite: false,
+            usage_count: 0,
+            last_used_at: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_csv_and_render() {
+        let csv = "\u{feff}Email;Name;Note\r\nana@x.test;\"Lima; Ana\";\"says \"\"hi\"\"\"\r\n\r\nANA@x.test;Dup;\nbo@x.test;Bo;\"two\nlines\"\n";
+        let (columns, rows) = recipients_from_csv(csv).unwrap();
+        assert_eq!(columns, vec!["email", "name", "note"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].variables["name"], "Lima; Ana");
+        assert_eq!(rows[0].variables["note"], "says \"hi\"");
+        assert_eq!(rows[1].variables["note"], "two\nlines");
+
+        assert_eq!(template_variables("Hi {{ Name }}, {{name}} {{note}}"), vec!["name", "note"]);
+        let html = render("<p>Hi {{ name }} {{unknown}}</p>", &rows[0].variables, crate::mail::compose::escape_html);
+        assert_eq!(html, "<p>Hi Lima; Ana {{unknown}}</p>");
+        let note = render("{{note}}", &rows[0].variables, crate::mail::compose::escape_html);
+        assert_eq!(note, "says &quot;hi&quot;");
+
+        assert!(recipients_from_csv("name\nAna\n").unwrap_err().contains("email"));
+        assert!(recipients_from_csv("email\nnot-an-address\n").unwrap_err().starts_with("Row 2"));
+    }
+
+    #[tokio::test]
+    async fn test_run_resumes_after_failure() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+
+        let csv = "email,name\na@x.test,Ana\nb@x.test,Bo\nc@x.test,Cem\nd@x.test,Dee\n";
+        assert!(create(&db, account_id, &template("Hi {{first}}", ""), csv, 20).is_err());
+        let merge_id = create(&db, account_id, &template("Hi {{name}}", "<p>Dear {{name}}</p>"), csv, 20).unwrap();
+
+        // Offline after two messages; b@x.test is refused by the server
+        let sender = FakeSender { sent: Mutex::new(Vec::new()), refused: "b@x.test", offline_after: Some(2) };
+        let cancelled = AtomicBool::new(false);
+        let paused = run(&db, &sender, merge_id, &cancelled, Duration::ZERO, |_| {}).await.unwrap();
+        assert_eq!(paused.state, MergeState::Paused);
+        assert_eq!((paused.sent, paused.failed, paused.pending), (2, 1, 1));
+
+        let sender = FakeSender { sent: Mutex::new(Vec::new()), refused: "", offline_after: None };
+        let done = run(&db, &sender, merge_id, &cancelled, Duration::ZERO, |_| {}).await.unwrap();
+        assert_eq!(done.state, MergeState::Completed);
+        assert_eq!((done.sent, done.failed, done.pending), (3, 1, 0));
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].subject, "Hi Dee");
+        assert!(sent[0].text_body.as_deref().unwrap().contains("Dear Dee"));
+
+        let status = status(&db, merge_id).unwrap();
+        assert_eq!(status.recipients[1].status, "failed");
+        assert!(status.recipients[1].error.as_deref().unwrap().contains("550"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Database Maintenance
+//!
+//! Keeps the SQLite file small and the query planner informed: ANALYZE,
+//! incremental vacuum of free pages and a truncating WAL checkpoint. The
+//! scheduler runs them once a day, only while the app is idle (no store
+//! changes for a while), so they never compete with the user; the
+//! `db_maintenance_run` command runs them on demand. Each run records the
+//! database and WAL size before and after. Scheduled runs also drop messages
+//! left in UIDVALIDITY quarantine for too long (see `uid_validity`).
+
+use crate::db::{Database, DbResult};
+use crate::events::StoreEvents;
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Settings key holding the last maintenance report
+const LAST_REPORT_SETTING: &str = "db_maintenance_last";
+
+/// How often the scheduler looks for an idle moment
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Minimum time between scheduled runs (hours)
+const RUN_EVERY_HOURS: i64 = 24;
+
+/// No store changes for this long counts as idle (seconds)
+const IDLE_AFTER_SECS: i64 = 10 * 60;
+
+/// Free pages reclaimed per incremental vacuum
+const VACUUM_PAGES: i64 = 2000;
+
+/// Share of free pages that justifies switching a database to incremental
+/// auto-vacuum (a one-time full VACUUM)
+const FREELIST_VACUUM_RATIO: f64 = 0.25;
+
+/// Outcome of one maintenance run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "DbMaintenanceReport"))]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceReport {
+    pub ran_at: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub duration_ms: u64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub db_bytes_before: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub db_bytes_after: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub wal_bytes_before: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub wal_bytes_after: i64,
+    /// Free pages returned to the file system
+    #[cfg_attr(test, ts(type = "number"))]
+    pub pages_freed: i64,
+    /// Switched to incremental auto-vacuum with a full VACUUM
+    pub full_vacuum: bool,
+    /// Readers kept the checkpoint from copying the whole WAL
+    pub checkpoint_busy: bool,
+}
+
+fn pragma_i64(conn: &Connection, pragma: &str) -> rusqlite::Result<i64> {
+    conn.query_row(&format!("PRAGMA {}", pragma), [], |row| row.get(0))
+}
+
+/// Size of the WAL file next to the database (0 for in-memory databases)
+fn wal_bytes(conn: &Connection) -> i64 {
+    conn.path()
+        .filter(|path| !path.is_empty())
+        .and_then(|path| std::fs::metadata(format!("{}-wal", path)).ok())
+        .map_or(0, |meta| meta.len() as i64)
+}
+
+fn db_bytes(conn: &Connection) -> rusqlite::Result<i64> {
+    Ok(pragma_i64(conn, "page_count")? * pragma_i64(conn, "page_size")?)
+}
+
+/// Run ANALYZE, vacuum and a WAL checkpoint
+pub fn run(db: &Database) -> DbResult<MaintenanceReport> {
+    let started = Instant::now();
+    let conn = db.get_conn()?;
+
+    let db_bytes_before = db_bytes(&conn)?;
+    let wal_bytes_before = wal_bytes(&conn);
+    let free_before = pragma_i64(&conn, "freelist_count")?;
+
+    conn.execute_batch("ANALYZE;")?;
+
+    // auto_vacuum: 0 = none, 1 = full, 2 = incremental
+    let mut full_vacuum = false;
+    if pragma_i64(&conn, "auto_vacuum")? == 2 {
+        conn.execute_batch(&format!("PRAGMA incremental_vacuum({});", VACUUM_PAGES))?;
+    } else if free_before as f64 > pragma_i64(&conn, "page_count")? as f64 * FREELIST_VACUUM_RATIO {
+        log::info!("Switching database to incremental auto-vacuum ({} free pages)", free_before);
+        conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+        full_vacuum = true;
+    }
+
+    let (busy, _, _): (i64, i64, i64) =
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+    let report = MaintenanceReport {
+        ran_at: Utc::now().to_rfc3339(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        db_bytes_before,
+        db_bytes_after: db_bytes(&conn)?,
+        wal_bytes_before,
+        wal_bytes_after: wal_bytes(&conn),
+        pages_freed: (free_before - pragma_i64(&conn, "freelist_count")?).max(0),
+        full_vacuum,
+        checkpoint_busy: busy != 0,
+    };
+    drop(conn);
+
+    log::info!(
+        "Database maintenance: {} -> {} bytes, WAL {} -> {} bytes, {} pages freed in {} ms",
+        report.db_bytes_before,
+        report.db_bytes_after,
+        report.wal_bytes_before,
+        report.wal_bytes_after,
+        report.pages_freed,
+        report.duration_ms
+    );
+    db.set_setting(LAST_REPORT_SETTING, &report)?;
+    Ok(report)
+}
+
+pub fn last_report(db: &Database) -> DbResult<Option<MaintenanceReport>> {
+    db.get_setting(LAST_REPORT_SETTING)
+}
+
+/// Whether a scheduled run is due at `now`
+pub fn is_due(last: Option<&MaintenanceReport>, last_activity: i64, now: DateTime<Utc>) -> bool {
+    let idle = now.timestamp() - last_activity >= IDLE_AFTER_SECS;
+    let stale = match last.and_then(|report| DateTime::parse_from_rfc3339(&report.ran_at).ok()) {
+        Some(ran_at) => now - ran_at.with_timezone(&Utc) >= chrono::Duration::hours(RUN_EVERY_HOURS),
+        None => true,
+    };
+    idle && stale
+}
+
+/// Runs maintenance once a day while the app is idle
+pub struct MaintenanceScheduler {
+    db: Arc<Database>,
+    started: AtomicBool,
+    /// Unix time of the last store change
+    last_activity: Arc<AtomicI64>,
+}
+
+impl MaintenanceScheduler {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            started: AtomicBool::new(false),
+            last_activity: Arc::new(AtomicI64::new(Utc::now().timestamp())),
+        }
+    }
+
+    /// Start the scheduler (only the first call has an effect)
+    /// Store changes on `events` count as activity and postpone maintenance.
+    pub fn start(&self, events: &StoreEvents) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            log::warn!("Maintenance scheduler already started");
+            return;
+        }
+
+        let mut receiver = events.subscribe();
+        let last_activity = self.last_activity.clone();
+        tauri::async_runtime::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            while let Ok(_) | Err(RecvError::Lagged(_)) = receiver.recv().await {
+                last_activity.store(Utc::now().timestamp(), Ordering::Relaxed);
+            }
+        });
+
+        let db = self.db.clone();
+        let last_activity = self.last_activity.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let last = match last_report(&db) {
+                    Ok(last) => last,
+                    Err(e) => {
+                        log::error!("Failed to load maintenance report: {}", e);
+                        continue;
+                    }
+                };
+                if !is_due(last.as_ref(), last_activity.load(Ordering::Relaxed), Utc::now()) {
+                    continue;
+                }
+                match crate::uid_validity::purge_expired(&db, Utc::now()) {
+                    Ok(0) => {}
+                    Ok(purged) => log::info!("Dropped {} quarantined messages that never came back", purged),
+                    Err(e) => log::error!("Failed to purge quarantined messages: {}", e),
+                }
+                if let Err(e) = run(&db) {
+                    log::error!("Database maintenance failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_records_report() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let report = run(&db).unwrap();
+        assert!(report.db_bytes_before > 0);
+        assert_eq!(report.wal_bytes_after, 0);
+        assert_eq!(last_report(&db).unwrap(), Some(report));
+    }
+
+    #[test]
+    fn test_due_only_when_idle_and_stale() {
+        let now = Utc::now();
+        let report = |hours_ago: i64| MaintenanceReport {
+            ran_at: (now - chrono::Duration::hours(hours_ago)).to_rfc3339(),
+            duration_ms: 0,
+            db_bytes_before: 0,
+            db_bytes_after: 0,
+            wal_bytes_before: 0,
+            wal_bytes_after: 0,
+            pages_freed: 0,
+            full_vacuum: false,
+            checkpoint_busy: false,
+        };
+        let idle_since = now.timestamp() - IDLE_AFTER_SECS;
+
+        assert!(is_due(None, idle_since, now));
+        assert!(is_due(Some(&report(25)), idle_since, now));
+        assert!(!is_due(Some(&report(2)), idle_since, now));
+        // Busy user: wait
+        assert!(!is_due(Some(&report(25)), now.timestamp() - 60, now));
+    }
+}
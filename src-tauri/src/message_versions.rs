@@ -0,0 +1,125 @@
+//! Message Versions
+//!
+//! Some servers re-deliver a message under the same Message-ID with altered
+//! content (Exchange recall-and-replace, Gmail resending an edited message).
+//! Every body the client opens is hashed, and each distinct content of a
+//! Message-ID is kept in `message_versions`. When a second version shows up
+//! the change is announced on the store bus so the user can compare them.
+
+use crate::db::{Database, DbResult};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// One content seen under a Message-ID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct MessageVersion {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub id: i64,
+    pub message_id: String,
+    pub content_hash: String,
+    pub subject: String,
+    pub body_text: Option<String>,
+    pub body_html: Option<String>,
+    pub seen_at: String,
+}
+
+/// Line endings and trailing whitespace differ between deliveries without
+/// the content changing
+fn normalize(text: &str) -> String {
+    text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+/// Hash of the parts a resend can alter (subject and bodies)
+pub fn content_hash(subject: &str, body_text: Option<&str>, body_html: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    for part in [Some(subject), body_text, body_html] {
+        hasher.update(normalize(part.unwrap_or_default()).as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Record the content of an opened message
+/// Returns the number of versions when this content is new and not the
+/// first one seen (the message was changed), otherwise `None`.
+pub fn record(
+    db: &Database,
+    account_id: i64,
+    message_id: &str,
+    subject: &str,
+    body_text: Option<&str>,
+    body_html: Option<&str>,
+) -> DbResult<Option<usize>> {
+    if message_id.trim().is_empty() || (body_text.is_none() && body_html.is_none()) {
+        return Ok(None);
+    }
+
+    let hash = content_hash(subject, body_text, body_html);
+    let inserted = db.execute(
+        "INSERT OR IGNORE INTO message_versions (account_id, message_id, content_hash, subject, body_text, body_html)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![account_id, message_id, hash, subject, body_text, body_html],
+    )?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+
+    let versions: i64 = db.query_row(
+        "SELECT COUNT(*) FROM message_versions WHERE account_id = ?1 AND message_id = ?2",
+        params![account_id, message_id],
+        |row| row.get(0),
+    )?;
+    Ok((versions > 1).then_some(versions as usize))
+}
+
+/// All versions of a message, oldest first
+pub fn versions(db: &Database, account_id: i64, message_id: &str) -> DbResult<Vec<MessageVersion>> {
+    db.query(
+        "SELECT id, message_id, content_hash, subject, body_text, body_html, seen_at
+         FROM message_versions WHERE account_id = ?1 AND message_id = ?2 ORDER BY id",
+        params![account_id, message_id],
+        |row| {
+            Ok(MessageVersion {
+                id: row.get(0)?,
+                message_id: row.get(1)?,
+                content_hash: row.get(2)?,
+                subject: row.get(3)?,
+                body_text: row.get(4)?,
+                body_html: row.get(5)?,
+                seen_at: row.get(6)?,
+            })
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    #[test]
+    fn test_changed_content_is_a_new_version() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+
+        let id = "<offer@test.com>";
+        assert_eq!(record(&db, account_id, id, "Offer", Some("Price: 100\r\n"), None).unwrap(), None);
+        // Same content, different line endings: not a change
+        assert_eq!(record(&db, account_id, id, "Offer", Some("Price: 100\n"), None).unwrap(), None);
+        assert_eq!(record(&db, account_id, id, "Offer", Some("Price: 120\n"), None).unwrap(), Some(2));
+        // Seeing either version again does not report it again
+        assert_eq!(record(&db, account_id, id, "Offer", Some("Price: 100"), None).unwrap(), None);
+
+        let stored = versions(&db, account_id, id).unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].body_text.as_deref(), Some("Price: 100\r\n"));
+        assert_eq!(stored[1].body_text.as_deref(), Some("Price: 120\n"));
+
+        // Without a Message-ID or a body there is nothing to compare
+        assert_eq!(record(&db, account_id, "", "Offer", Some("x"), None).unwrap(), None);
+        assert_eq!(record(&db, account_id, id, "Offer", None, None).unwrap(), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! Correspondence Metrics
+//!
+//! Local analysis of when correspondents read and answer mail, used to
+//! suggest a send time: the hour of the week a recipient is most active in,
+//! weighting their replies to our messages above mail they start themselves.
+//! Everything is computed from the local cache; nothing leaves the machine.
+//!
+//! Also keeps running totals of attachment downloads per fetch method, so
+//! BINARY fetches can be compared against fetching the whole message.
+
+use crate::db::{parse_db_timestamp, Database, DbResult};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Hours in a week (slot = weekday from Monday * 24 + hour)
+const WEEK_SLOTS: usize = 7 * 24;
+
+/// Messages per recipient that are analysed (newest first)
+const MAX_ACTIVITY: i32 = 500;
+
+/// Fewer messages than this are not enough to suggest anything
+const MIN_SAMPLES: usize = 3;
+
+/// A reply to our own mail says more about when the recipient reads it
+const REPLY_WEIGHT: f64 = 2.0;
+
+/// Weight of the neighbouring hours when scoring a slot
+const NEIGHBOUR_WEIGHT: f64 = 0.5;
+
+/// Send-time analysis for one recipient
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct RecipientSendTime {
+    pub address: String,
+    /// Messages received from this address that were analysed
+    pub samples: usize,
+    pub replies: usize,
+    /// Median time between our message and their reply
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub median_reply_minutes: Option<i64>,
+    /// Most active hour of the week (local time), when there is enough data
+    pub best_weekday: Option<u32>,
+    pub best_hour: Option<u32>,
+}
+
+/// Result of `suggest_send_time`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SendTimeSuggestion {
+    /// Suggested send time (RFC 3339) for the scheduled send; None = no pattern, send now
+    pub send_at: Option<String>,
+    /// True when now is already the best time
+    pub send_now: bool,
+    pub recipients: Vec<RecipientSendTime>,
+}
+
+/// Activity histogram over the hours of the week
+#[derive(Debug, Clone)]
+pub struct WeekHistogram {
+    slots: [f64; WEEK_SLOTS],
+    samples: usize,
+}
+
+impl Default for WeekHistogram {
+    fn default() -> Self {
+        Self {
+            slots: [0.0; WEEK_SLOTS],
+            samples: 0,
+        }
+    }
+}
+
+fn slot_of(at: NaiveDateTime) -> usize {
+    at.weekday().num_days_from_monday() as usize * 24 + at.hour() as usize
+}
+
+impl WeekHistogram {
+    pub fn add(&mut self, at: NaiveDateTime, weight: f64) {
+        self.slots[slot_of(at)] += weight;
+        self.samples += 1;
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Add another histogram scaled to the same total, so every recipient counts equally
+    pub fn merge_normalized(&mut self, other: &WeekHistogram) {
+        let total: f64 = other.slots.iter().sum();
+        if total <= 0.0 {
+            return;
+        }
+        for (slot, value) in self.slots.iter_mut().zip(other.slots.iter()) {
+            *slot += value / total;
+        }
+        self.samples += other.samples;
+    }
+
+    /// Slot score including half of each neighbouring hour
+    fn score(&self, slot: usize) -> f64 {
+        let prev = self.slots[(slot + WEEK_SLOTS - 1) % WEEK_SLOTS];
+        let next = self.slots[(slot + 1) % WEEK_SLOTS];
+        self.slots[slot] + NEIGHBOUR_WEIGHT * (prev + next)
+    }
+
+    /// Most active (weekday, hour)
+    pub fn peak(&self) -> Option<(u32, u32)> {
+        if self.samples < MIN_SAMPLES {
+            return None;
+        }
+        let best = (0..WEEK_SLOTS).max_by(|a, b| self.score(*a).total_cmp(&self.score(*b)).then(b.cmp(a)))?;
+        Some(((best / 24) as u32, (best % 24) as u32))
+    }
+
+    /// Earliest time within the next week falling into the best-scoring hour
+    /// Returns `now` itself when the current hour scores as well as any later one.
+    pub fn next_best(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.samples < MIN_SAMPLES {
+            return None;
+        }
+        let hour_start = now.date().and_hms_opt(now.hour(), 0, 0)?;
+
+        let mut best: Option<(f64, NaiveDateTime)> = None;
+        for i in 0..WEEK_SLOTS as i64 {
+            let candidate = if i == 0 { now } else { hour_start + Duration::hours(i) };
+            let score = self.score(slot_of(candidate));
+            match best {
+                Some((best_score, _)) if score <= best_score => {}
+                _ => best = Some((score, candidate)),
+            }
+        }
+        best.filter(|(score, _)| *score > 0.0).map(|(_, at)| at)
+    }
+}
+
+/// Histogram and reply delays of one correspondent
+pub fn analyse(address: &str, activity: &[crate::db::SenderActivity]) -> (WeekHistogram, RecipientSendTime) {
+    let mut histogram = WeekHistogram::default();
+    let mut delays = Vec::new();
+
+    for item in activity {
+        let Some(at) = parse_db_timestamp(&item.date) else { continue };
+        let at = at.with_timezone(&Local).naive_local();
+        match item.replied_to.as_deref().and_then(parse_db_timestamp) {
+            Some(sent) => {
+                histogram.add(at, REPLY_WEIGHT);
+                let delay = at - sent.with_timezone(&Local).naive_local();
+                if delay >= Duration::zero() {
+                    delays.push(delay.num_minutes());
+                }
+            }
+            None => histogram.add(at, 1.0),
+        }
+    }
+
+    delays.sort_unstable();
+    let peak = histogram.peak();
+    let summary = RecipientSendTime {
+        address: address.to_string(),
+        samples: histogram.samples(),
+        replies: delays.len(),
+        median_reply_minutes: delays.get(delays.len() / 2).copied(),
+        best_weekday: peak.map(|(weekday, _)| weekday),
+        best_hour: peak.map(|(_, hour)| hour),
+    };
+    (histogram, summary)
+}
+
+/// Suggest when to send a message to `recipients`
+pub fn suggest_send_time(db: &Database, recipients: &[String], now: DateTime<Local>) -> DbResult<SendTimeSuggestion> {
+    let mut combined = WeekHistogram::default();
+    let mut summaries = Vec::new();
+
+    for address in recipients {
+        let address = address.trim().to_lowercase();
+        if address.is_empty() || summaries.iter().any(|s: &RecipientSendTime| s.address == address) {
+            continue;
+        }
+        let activity = db.get_sender_activity(&address, MAX_ACTIVITY)?;
+        let (histogram, summary) = analyse(&address, &activity);
+        if histogram.samples() >= MIN_SAMPLES {
+            combined.merge_normalized(&histogram);
+        }
+        summaries.push(summary);
+    }
+
+    let now_local = now.naive_local();
+    let best = combined.next_best(now_local);
+    Ok(SendTimeSuggestion {
+        send_now: best == Some(now_local),
+        send_at: best
+            .and_then(|at| Local.from_local_datetime(&at).earliest())
+            .map(|at| at.to_rfc3339()),
+        recipients: summaries,
+    })
+}
+
+/// How an attachment was downloaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub enum FetchMethod {
+    /// `BINARY.PEEK[section]`: just the part, decoded by the server
+    Binary,
+    /// The whole message, decoded locally
+    FullMessage,
+}
+
+/// Attachment download totals of one fetch method since start
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct FetchBenchmark {
+    pub method: FetchMethod,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub fetches: u64,
+    /// Attachment bytes delivered (decoded)
+    #[cfg_attr(test, ts(type = "number"))]
+    pub bytes: u64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub millis: u64,
+    pub bytes_per_second: Option<f64>,
+}
+
+lazy_static::lazy_static! {
+    /// (fetches, bytes, millis) per method
+    static ref FETCH_TOTALS: Mutex<HashMap<FetchMethod, (u64, u64, u64)>> = Mutex::new(HashMap::new());
+}
+
+/// Count one attachment download of `bytes` that took `elapsed`
+pub fn record_fetch(method: FetchMethod, bytes: usize, elapsed: std::time::Duration) {
+    let mut totals = FETCH_TOTALS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entry = totals.entry(method).or_default();
+    entry.0 += 1;
+    entry.1 += bytes as u64;
+    entry.2 += elapsed.as_millis() as u64;
+}
+
+/// Download totals per fetch method, BINARY first
+pub fn fetch_benchmarks() -> Vec<FetchBenchmark> {
+    let totals = FETCH_TOTALS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    [FetchMethod::Binary, FetchMethod::FullMessage]
+        .into_iter()
+        .filter_map(|method| {
+            let (fetches, bytes, millis) = *totals.get(&method)?;
+            Some(FetchBenchmark {
+                method,
+                fetches,
+                bytes,
+                millis,
+                bytes_per_second: (millis > 0).then(|| bytes as f64 * 1000.0 / millis as f64),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SenderActivity;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        // 2025-01-06 is a Monday
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap().and_hms_opt(hour, 15, 0).unwrap()
+    }
+
+    #[test]
+    fn test_next_best_slot() {
+        let mut histogram = WeekHistogram::default();
+        // Tuesdays around 9:00, one stray Friday evening
+        histogram.add(at(7, 9), 1.0);
+        histogram.add(at(14, 9), 2.0);
+        histogram.add(at(14, 10), 1.0);
+        histogram.add(at(10, 20), 1.0);
+
+        assert_eq!(histogram.peak(), Some((1, 9)));
+
+        // Monday noon -> Tuesday 09:00
+        let monday = at(6, 12);
+        assert_eq!(histogram.next_best(monday), Some(NaiveDate::from_ymd_opt(2025, 1, 7).unwrap().and_hms_opt(9, 0, 0).unwrap()));
+
+        // Already Tuesday 09:15 -> now
+        let tuesday = at(7, 9);
+        assert_eq!(histogram.next_best(tuesday), Some(tuesday));
+
+        // Too little data
+        let mut sparse = WeekHistogram::default();
+        sparse.add(at(7, 9), 1.0);
+        assert_eq!(sparse.next_best(monday), None);
+        assert_eq!(sparse.peak(), None);
+    }
+
+    #[test]
+    fn test_analyse_reply_delays() {
+        let activity = vec![
+            SenderActivity { date: "2025-01-07T09:30:00Z".to_string(), replied_to: Some("2025-01-07T09:00:00Z".to_string()) },
+            SenderActivity { date: "2025-01-08T10:00:00Z".to_string(), replied_to: Some("2025-01-07T22:00:00Z".to_string()) },
+            SenderActivity { date: "2025-01-09T11:00:00Z".to_string(), replied_to: None },
+            SenderActivity { date: "not a date".to_string(), replied_to: None },
+        ];
+        let (histogram, summary) = analyse("bob@example.com", &activity);
+        assert_eq!(histogram.samples(), 3);
+        assert_eq!(summary.samples, 3);
+        assert_eq!(summary.replies, 2);
+        assert_eq!(summary.median_reply_minutes, Some(720));
+        assert!(summary.best_hour.is_some());
+    }
+
+    #[test]
+    fn test_fetch_benchmarks() {
+        record_fetch(FetchMethod::FullMessage, 4000, std::time::Duration::from_millis(8));
+        record_fetch(FetchMethod::Binary, 3000, std::time::Duration::from_millis(2));
+        record_fetch(FetchMethod::Binary, 1000, std::time::Duration::from_millis(2));
+
+        let benchmarks = fetch_benchmarks();
+        assert_eq!(benchmarks.len(), 2);
+        assert_eq!(benchmarks[0].method, FetchMethod::Binary);
+        assert_eq!((benchmarks[0].fetches, benchmarks[0].bytes), (2, 4000));
+        assert_eq!(benchmarks[0].bytes_per_second, Some(1_000_000.0));
+        assert_eq!(benchmarks[1].bytes_per_second, Some(500_000.0));
+    }
+}
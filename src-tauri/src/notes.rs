@@ -0,0 +1,267 @@
+//! Email Notes
+//!
+//! Private notes and color flags attached to messages. They live only in the
+//! local database (and, if the user opts in, in the encrypted sync data) and
+//! are never written to the mail server. Notes are keyed by the message's
+//! Message-ID within its account, so a note follows the message across
+//! folders and matches the same message on another device. Note text is
+//! included in message search.
+
+use crate::db::{parse_db_timestamp, Database, DbError, DbResult};
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Flag colors offered in the UI
+pub const COLORS: [&str; 6] = ["red", "orange", "yellow", "green", "blue", "purple"];
+
+/// Longest note accepted
+const MAX_NOTE_LEN: usize = 16 * 1024;
+
+const DB_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct EmailNote {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub message_id: String,
+    /// A local copy of the message, if any
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub email_id: Option<i64>,
+    pub body: String,
+    pub color: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+const NOTE_COLUMNS: &str = "n.account_id, n.message_id,
+    (SELECT MAX(e.id) FROM emails e WHERE e.account_id = n.account_id AND e.message_id = n.message_id AND e.is_deleted = 0),
+    n.body, n.color, n.created_at, n.updated_at";
+
+fn note_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<EmailNote> {
+    Ok(EmailNote {
+        account_id: row.get(0)?,
+        message_id: row.get(1)?,
+        email_id: row.get(2)?,
+        body: row.get(3)?,
+        color: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+pub fn get(db: &Database, account_id: i64, message_id: &str) -> DbResult<Option<EmailNote>> {
+    let note = db.query_row(
+        &format!(
+            "SELECT {} FROM email_notes n WHERE n.account_id = ?1 AND n.message_id = ?2 AND n.deleted = 0",
+            NOTE_COLUMNS
+        ),
+        params![account_id, message_id],
+        note_from_row,
+    );
+    match note {
+        Ok(note) => Ok(Some(note)),
+        Err(DbError::Sqlite(rusqlite::Error::QueryReturnedNoRows)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Notes of an account (or of all accounts), most recently edited first
+pub fn list(db: &Database, account_id: Option<i64>) -> DbResult<Vec<EmailNote>> {
+    db.query(
+        &format!(
+            "SELECT {} FROM email_notes n
+             WHERE n.deleted = 0 AND (?1 IS NULL OR n.account_id = ?1)
+             ORDER BY n.updated_at DESC",
+            NOTE_COLUMNS
+        ),
+        params![account_id],
+        note_from_row,
+    )
+}
+
+/// Save the note of a message; an empty note without a color removes it
+pub fn set(
+    db: &Database,
+    account_id: i64,
+    message_id: &str,
+    body: &str,
+    color: Option<&str>,
+) -> DbResult<Option<EmailNote>> {
+    let body = body.trim();
+    if body.len() > MAX_NOTE_LEN {
+        return Err(DbError::Constraint(format!("Note is longer than {} bytes", MAX_NOTE_LEN)));
+    }
+    let color = color.map(str::trim).filter(|color| !color.is_empty());
+    if let Some(color) = color {
+        if !COLORS.contains(&color) {
+            return Err(DbError::Constraint(format!("Unknown note color: {}", color)));
+        }
+    }
+    if body.is_empty() && color.is_none() {
+        delete(db, account_id, message_id)?;
+        return Ok(None);
+    }
+
+    db.execute(
+        "INSERT INTO email_notes (account_id, message_id, body, color)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(account_id, message_id) DO UPDATE SET
+            body = excluded.body,
+            color = excluded.color,
+            deleted = 0,
+            updated_at = datetime('now')",
+        params![account_id, message_id, body, color],
+    )?;
+    get(db, account_id, message_id)
+}
+
+/// Remove the note of a message (kept as a tombstone for sync)
+pub fn delete(db: &Database, account_id: i64, message_id: &str) -> DbResult<bool> {
+    let affected = db.execute(
+        "UPDATE email_notes SET body = '', color = NULL, deleted = 1, updated_at = datetime('now')
+         WHERE account_id = ?1 AND message_id = ?2 AND deleted = 0",
+        params![account_id, message_id],
+    )?;
+    Ok(affected > 0)
+}
+
+// ============================================================================
+// Sync
+// ============================================================================
+
+/// Note in sync form, matched by (account address, Message-ID)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteItem {
+    /// Owning account's address
+    pub account_email: String,
+    pub message_id: String,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Deleted notes are synced so the deletion reaches other devices
+    #[serde(default)]
+    pub deleted: bool,
+
+    /// Last local modification (for conflict resolution)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// All local notes, including deletions, in sync form
+pub fn collect_sync_items(db: &Database) -> DbResult<Vec<NoteItem>> {
+    db.query(
+        "SELECT a.email, n.message_id, n.body, n.color, n.deleted, n.updated_at
+         FROM email_notes n JOIN accounts a ON a.id = n.account_id
+         ORDER BY a.email, n.message_id",
+        [],
+        |row| {
+            let updated_at: String = row.get(5)?;
+            Ok(NoteItem {
+                account_email: row.get(0)?,
+                message_id: row.get(1)?,
+                body: row.get(2)?,
+                color: row.get(3)?,
+                deleted: row.get(4)?,
+                updated_at: parse_db_timestamp(&updated_at),
+            })
+        },
+    )
+}
+
+/// Store synced notes, matching accounts by address
+/// Returns the number of notes added, updated or deleted.
+pub fn apply_sync_items(db: &Database, items: &[NoteItem]) -> DbResult<usize> {
+    let mut by_account: HashMap<&str, Vec<&NoteItem>> = HashMap::new();
+    for item in items {
+        by_account.entry(item.account_email.as_str()).or_default().push(item);
+    }
+
+    let mut changed = 0;
+
+    for (email, items) in by_account {
+        let Some(account) = db.get_account_by_email(email)? else {
+            log::warn!("Account not found locally, skipping {} notes for {}", items.len(), email);
+            continue;
+        };
+
+        let local: HashMap<String, (String, Option<String>, bool)> = db
+            .query(
+                "SELECT message_id, body, color, deleted FROM email_notes WHERE account_id = ?1",
+                params![account.id],
+                |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?))),
+            )?
+            .into_iter()
+            .collect();
+
+        for item in items {
+            if item.color.as_deref().is_some_and(|color| !COLORS.contains(&color)) {
+                log::warn!("Synced note for {} has an unknown color, skipping", item.message_id);
+                continue;
+            }
+            let content = (item.body.clone(), item.color.clone(), item.deleted);
+            if local.get(&item.message_id) == Some(&content) {
+                continue;
+            }
+
+            let updated_at = item.updated_at.unwrap_or_else(Utc::now).format(DB_TIMESTAMP_FORMAT).to_string();
+            db.execute(
+                "INSERT INTO email_notes (account_id, message_id, body, color, deleted, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(account_id, message_id) DO UPDATE SET
+                    body = excluded.body,
+                    color = excluded.color,
+                    deleted = excluded.deleted,
+                    updated_at = excluded.updated_at",
+                params![account.id, item.message_id, item.body, item.color, item.deleted, updated_at],
+            )?;
+            changed += 1;
+        }
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_new_account;
+
+    #[test]
+    fn test_notes_and_sync_items() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = db.add_account(&test_new_account("notes@test.com")).unwrap();
+
+        let note = set(&db, account_id, "<1@test.com>", "  Call back on Monday ", Some("red")).unwrap().unwrap();
+        assert_eq!(note.body, "Call back on Monday");
+        assert_eq!(note.email_id, None);
+        assert!(set(&db, account_id, "<1@test.com>", "x", Some("pink")).is_err());
+        set(&db, account_id, "<2@test.com>", "", Some("blue")).unwrap();
+        assert_eq!(list(&db, Some(account_id)).unwrap().len(), 2);
+
+        // Clearing a note leaves a tombstone for sync
+        assert!(set(&db, account_id, "<2@test.com>", "", None).unwrap().is_none());
+        assert_eq!(get(&db, account_id, "<2@test.com>").unwrap(), None);
+        let items = collect_sync_items(&db).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().any(|item| item.message_id == "<2@test.com>" && item.deleted));
+
+        // Unchanged notes are not rewritten; others keep the remote timestamp
+        assert_eq!(apply_sync_items(&db, &items).unwrap(), 0);
+        let remote = NoteItem {
+            account_email: "notes@test.com".to_string(),
+            message_id: "<2@test.com>".to_string(),
+            body: "Restored".to_string(),
+            color: Some("green".to_string()),
+            deleted: false,
+            updated_at: parse_db_timestamp("2026-01-02 03:04:05"),
+        };
+        assert_eq!(apply_sync_items(&db, &[remote]).unwrap(), 1);
+        let note = get(&db, account_id, "<2@test.com>").unwrap().unwrap();
+        assert_eq!((note.body.as_str(), note.updated_at.as_str()), ("Restored", "2026-01-02 03:04:05"));
+    }
+}
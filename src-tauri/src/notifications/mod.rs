@@ -0,0 +1,147 @@
+//! Notification Routing
+//!
+//! Decides how a newly arrived message is announced. Do Not Disturb silences
+//! regular notifications, either permanently or during a daily quiet-hours
+//! window; newsletters held back for the digest stay silent. Mail from VIP
+//! contacts always notifies, DND and digest notwithstanding, and is flagged as
+//! priority so the UI can make it stand out. `sound` plays the matching sound.
+
+pub mod sound;
+
+use crate::db::{Database, DbResult};
+use crate::digest::{self, DigestConfig};
+use chrono::{NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Settings key holding the Do Not Disturb configuration
+const DND_SETTING: &str = "notification_dnd";
+
+/// Do Not Disturb configuration stored in settings table
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct DndConfig {
+    /// DND active all day
+    pub enabled: bool,
+    /// Daily quiet hours, local time (0-23); the window may wrap past midnight
+    pub quiet_hours: Option<(u32, u32)>,
+}
+
+impl DndConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some((start, end)) = self.quiet_hours {
+            if start > 23 || end > 23 {
+                return Err(format!("Quiet hours must be 0-23, got {}-{}", start, end));
+            }
+            if start == end {
+                return Err("Quiet hours start and end must differ".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether regular notifications are silenced at local time `now`
+    pub fn is_active(&self, now: NaiveTime) -> bool {
+        if self.enabled {
+            return true;
+        }
+        match self.quiet_hours {
+            Some((start, end)) if start < end => (start..end).contains(&now.hour()),
+            Some((start, end)) => now.hour() >= start || now.hour() < end,
+            None => false,
+        }
+    }
+}
+
+pub fn load_dnd(db: &Database) -> DbResult<DndConfig> {
+    Ok(db.get_setting(DND_SETTING)?.unwrap_or_default())
+}
+
+pub fn save_dnd(db: &Database, config: &DndConfig) -> DbResult<()> {
+    db.set_setting(DND_SETTING, config)
+}
+
+/// How a new message is announced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationRoute {
+    /// No notification (DND or held back for the digest)
+    Silent,
+    Normal,
+    /// VIP sender: always shown
+    Priority,
+}
+
+/// Everything needed to route a batch of new messages
+pub struct Router {
+    pub dnd: DndConfig,
+    pub digest: DigestConfig,
+    /// Lowercase VIP addresses
+    pub vips: HashSet<String>,
+}
+
+impl Router {
+    pub fn load(db: &Database) -> DbResult<Self> {
+        Ok(Self {
+            dnd: load_dnd(db)?,
+            digest: digest::load_config(db)?,
+            vips: db.get_vip_addresses()?,
+        })
+    }
+
+    pub fn route(&self, from_address: &str, subject: &str, now: NaiveTime) -> NotificationRoute {
+        if self.vips.contains(&from_address.trim().to_lowercase()) {
+            NotificationRoute::Priority
+        } else if self.dnd.is_active(now)
+            || digest::suppresses_notification(&self.digest, from_address, subject, "[]")
+        {
+            NotificationRoute::Silent
+        } else {
+            NotificationRoute::Normal
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let overnight = DndConfig { enabled: false, quiet_hours: Some((22, 7)) };
+        assert!(overnight.is_active(at(23)));
+        assert!(overnight.is_active(at(3)));
+        assert!(!overnight.is_active(at(7)));
+        assert!(!overnight.is_active(at(12)));
+
+        let lunch = DndConfig { enabled: false, quiet_hours: Some((12, 13)) };
+        assert!(lunch.is_active(at(12)));
+        assert!(!lunch.is_active(at(13)));
+
+        assert!(DndConfig { enabled: true, quiet_hours: None }.is_active(at(12)));
+        assert!(DndConfig { enabled: false, quiet_hours: Some((5, 5)) }.validate().is_err());
+        assert!(DndConfig { enabled: false, quiet_hours: Some((5, 24)) }.validate().is_err());
+    }
+
+    #[test]
+    fn test_vip_bypasses_dnd_and_digest() {
+        let router = Router {
+            dnd: DndConfig { enabled: true, quiet_hours: None },
+            digest: DigestConfig { enabled: true, ..Default::default() },
+            vips: ["boss@example.com".to_string(), "news@paper.test".to_string()].into_iter().collect(),
+        };
+        assert_eq!(router.route("Boss@Example.com", "Call me", at(9)), NotificationRoute::Priority);
+        assert_eq!(router.route("news@paper.test", "Morning briefing", at(9)), NotificationRoute::Priority);
+        assert_eq!(router.route("alice@example.com", "Lunch?", at(9)), NotificationRoute::Silent);
+
+        let router = Router { dnd: DndConfig::default(), ..router };
+        assert_eq!(router.route("alice@example.com", "Lunch?", at(9)), NotificationRoute::Normal);
+        assert_eq!(router.route("newsletter@shop.test", "Hello", at(9)), NotificationRoute::Silent);
+    }
+}
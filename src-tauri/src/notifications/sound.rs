@@ -0,0 +1,260 @@
+//! Notification Sounds
+//!
+//! New mail is announced with one of the built-in sounds (the same names the
+//! settings offer) or an audio file picked by the user, per account or per
+//! folder of an account. The backend plays it, so a sound is heard while the
+//! window is hidden in the tray. Sounds follow the notification route: a
+//! silent route (DND, digest) plays nothing, VIP mail always plays.
+
+use super::NotificationRoute;
+use crate::db::{Database, DbResult};
+use crate::settings;
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// Settings key holding the sound configuration
+const SOUND_SETTING: &str = "notification_sounds";
+
+/// Built-in sounds, as offered by the `notification_sound_type` setting
+pub const BUILT_IN_SOUNDS: &[&str] = &[
+    "gentle", "pop", "chime", "ding", "subtle", "system", "owlivion", "whisper", "call", "moonlight",
+];
+
+/// File types a custom sound may have
+const SOUND_FILE_EXTENSIONS: &[&str] = &["wav", "mp3", "ogg", "flac"];
+
+/// Recorded owl call used by the `call` sound
+const OWL_CALL: &[u8] = include_bytes!("../../../public/sounds/owlivion-signature.ogg");
+
+/// Default volume in percent
+fn default_volume() -> u8 {
+    70
+}
+
+/// Sound to play
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "NotificationSound"))]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum Sound {
+    BuiltIn(String),
+    /// Audio file (WAV, MP3, Ogg Vorbis or FLAC)
+    File(String),
+    /// No sound
+    Silent,
+}
+
+impl Sound {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Sound::BuiltIn(name) if !BUILT_IN_SOUNDS.contains(&name.as_str()) => {
+                Err(format!("Unknown sound: {}", name))
+            }
+            Sound::File(path) => {
+                let path = Path::new(path);
+                let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+                if !SOUND_FILE_EXTENSIONS.contains(&extension.as_str()) {
+                    return Err(format!("Sound files must be one of: {}", SOUND_FILE_EXTENSIONS.join(", ")));
+                }
+                if !path.is_file() {
+                    return Err(format!("Sound file not found: {}", path.display()));
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Sound for an account, or one folder of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "NotificationSoundOverride"))]
+#[serde(rename_all = "camelCase")]
+pub struct SoundOverride {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    /// Remote folder name; the whole account if None
+    pub folder: Option<String>,
+    pub sound: Sound,
+}
+
+/// Sound configuration stored in settings table
+/// Whether sounds play at all and the default sound are the
+/// `notification_sound` and `notification_sound_type` settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "NotificationSoundConfig"))]
+#[serde(rename_all = "camelCase")]
+pub struct SoundConfig {
+    /// Volume in percent (0-100)
+    #[serde(default = "default_volume")]
+    pub volume: u8,
+    #[serde(default)]
+    pub overrides: Vec<SoundOverride>,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self { volume: default_volume(), overrides: Vec::new() }
+    }
+}
+
+impl SoundConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.volume > 100 {
+            return Err(format!("Volume must be 0-100, got {}", self.volume));
+        }
+        for (i, entry) in self.overrides.iter().enumerate() {
+            entry.sound.validate()?;
+            if self.overrides[..i]
+                .iter()
+                .any(|other| other.account_id == entry.account_id && other.folder == entry.folder)
+            {
+                return Err("Each account and folder can have only one sound".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Sound for new mail in a folder: the folder's own, else the account's, else `default`
+    pub fn sound_for(&self, account_id: i64, folder: Option<&str>, default: Sound) -> Sound {
+        let for_account = |folder: Option<&str>| {
+            self.overrides
+                .iter()
+                .find(|entry| entry.account_id == account_id && entry.folder.as_deref() == folder)
+                .map(|entry| entry.sound.clone())
+        };
+        folder
+            .and_then(|folder| for_account(Some(folder)))
+            .or_else(|| for_account(None))
+            .unwrap_or(default)
+    }
+}
+
+pub fn load_config(db: &Database) -> DbResult<SoundConfig> {
+    Ok(db.get_setting(SOUND_SETTING)?.unwrap_or_default())
+}
+
+pub fn save_config(db: &Database, config: &SoundConfig) -> DbResult<()> {
+    db.set_setting(SOUND_SETTING, config)
+}
+
+/// Sound announcing new mail, None when nothing should play
+pub fn select(
+    db: &Database,
+    account_id: i64,
+    folder: Option<&str>,
+    route: NotificationRoute,
+) -> DbResult<Option<(Sound, u8)>> {
+    if route == NotificationRoute::Silent || !settings::get_bool(db, "notification_sound") {
+        return Ok(None);
+    }
+    let default = settings::find("notification_sound_type")
+        .map(|def| settings::value(db, def))
+        .transpose()?
+        .and_then(|value| value.as_str().map(|name| Sound::BuiltIn(name.to_string())))
+        .unwrap_or(Sound::Silent);
+
+    let config = load_config(db)?;
+    match config.sound_for(account_id, folder, default) {
+        Sound::Silent => Ok(None),
+        sound => Ok(Some((sound, config.volume))),
+    }
+}
+
+/// Synthesized tones of a built-in sound: (frequency Hz, length ms, gain)
+fn tones(name: &str) -> &'static [(f32, u64, f32)] {
+    match name {
+        "gentle" => &[(523.25, 50, 0.6), (659.25, 450, 0.6)],
+        "pop" => &[(800.0, 60, 1.0), (400.0, 90, 1.0)],
+        "chime" => &[(523.25, 150, 0.5), (659.25, 150, 0.5), (783.99, 300, 0.5)],
+        "ding" => &[(1200.0, 800, 0.8)],
+        "subtle" => &[(440.0, 300, 0.3)],
+        "owlivion" => &[(392.0, 250, 0.5), (523.25, 250, 0.5), (392.0, 500, 0.5)],
+        "whisper" => &[(220.0, 150, 0.4), (330.0, 200, 0.5), (220.0, 250, 0.4)],
+        "moonlight" => &[(1046.5, 200, 0.4), (1318.51, 200, 0.4), (1567.98, 400, 0.4)],
+        // "system" and anything unknown
+        _ => &[(800.0, 200, 0.5)],
+    }
+}
+
+fn play_blocking(sound: &Sound, volume: u8) -> Result<(), String> {
+    // The stream must stay alive until the sound has finished
+    let (_stream, handle) = rodio::OutputStream::try_default().map_err(|e| format!("No audio output: {}", e))?;
+    let sink = rodio::Sink::try_new(&handle).map_err(|e| format!("Failed to open audio output: {}", e))?;
+    sink.set_volume(f32::from(volume) / 100.0);
+
+    match sound {
+        Sound::Silent => return Ok(()),
+        Sound::BuiltIn(name) if name == "call" => {
+            let decoder = rodio::Decoder::new(std::io::Cursor::new(OWL_CALL))
+                .map_err(|e| format!("Failed to decode sound: {}", e))?;
+            sink.append(decoder);
+        }
+        Sound::BuiltIn(name) => {
+            for &(frequency, millis, gain) in tones(name) {
+                let mut tone = rodio::source::SineWave::new(frequency).take_duration(Duration::from_millis(millis));
+                tone.set_filter_fadeout();
+                sink.append(tone.amplify(gain));
+            }
+        }
+        Sound::File(path) => {
+            let file = std::fs::File::open(path).map_err(|e| format!("Failed to open sound file: {}", e))?;
+            let decoder = rodio::Decoder::new(std::io::BufReader::new(file))
+                .map_err(|e| format!("Failed to decode sound: {}", e))?;
+            sink.append(decoder);
+        }
+    }
+    sink.sleep_until_end();
+    Ok(())
+}
+
+/// Play a sound in the background (failures are only logged)
+pub fn play(sound: Sound, volume: u8) {
+    std::thread::spawn(move || {
+        if let Err(e) = play_blocking(&sound, volume.min(100)) {
+            log::warn!("Failed to play notification sound: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn built_in(name: &str) -> Sound {
+        Sound::BuiltIn(name.to_string())
+    }
+
+    #[test]
+    fn test_sound_selection() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let config = SoundConfig {
+            volume: 40,
+            overrides: vec![
+                SoundOverride { account_id: 1, folder: None, sound: built_in("pop") },
+                SoundOverride { account_id: 1, folder: Some("Alerts".to_string()), sound: built_in("ding") },
+                SoundOverride { account_id: 2, folder: Some("Spam".to_string()), sound: Sound::Silent },
+            ],
+        };
+        config.validate().unwrap();
+        save_config(&db, &config).unwrap();
+
+        assert_eq!(select(&db, 1, Some("Alerts"), NotificationRoute::Normal).unwrap(), Some((built_in("ding"), 40)));
+        assert_eq!(select(&db, 1, Some("INBOX"), NotificationRoute::Normal).unwrap(), Some((built_in("pop"), 40)));
+        // Default sound from the settings registry
+        assert_eq!(select(&db, 2, Some("INBOX"), NotificationRoute::Priority).unwrap(), Some((built_in("call"), 40)));
+        assert_eq!(select(&db, 2, Some("Spam"), NotificationRoute::Normal).unwrap(), None);
+        // DND or digest
+        assert_eq!(select(&db, 1, None, NotificationRoute::Silent).unwrap(), None);
+
+        db.set_setting("notification_sound", &false).unwrap();
+        assert_eq!(select(&db, 1, None, NotificationRoute::Priority).unwrap(), None);
+
+        assert!(built_in("trumpet").validate().is_err());
+        assert!(Sound::File("/no/such/sound.wav".to_string()).validate().is_err());
+        assert!(Sound::File("/etc/hostname".to_string()).validate().is_err());
+        let duplicate = SoundConfig { overrides: vec![config.overrides[0].clone(), config.overrides[0].clone()], ..config };
+        assert!(duplicate.validate().is_err());
+    }
+}
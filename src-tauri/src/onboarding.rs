@@ -0,0 +1,200 @@
+//! First-Run Onboarding
+//!
+//! The guided setup walks a new user through adding an account, choosing sync
+//! settings and granting notification permission. Progress is kept in the
+//! settings table so the guide resumes at the first open step after a
+//! restart; a step the data already proves done (an account exists) counts as
+//! completed on its own. Once setup is through, tips point at features the
+//! data shows are still unused.
+
+use crate::blocklist::BLOCK_FILTER_PRIORITY;
+use crate::db::{Database, DbError, DbResult};
+use serde::{Deserialize, Serialize};
+
+/// Settings key holding onboarding progress
+const PROGRESS_SETTING: &str = "onboarding_progress";
+
+/// Steps of the guided setup, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    AccountAdded,
+    SyncConfigured,
+    NotificationsGranted,
+}
+
+impl OnboardingStep {
+    pub const ALL: [OnboardingStep; 3] = [
+        OnboardingStep::AccountAdded,
+        OnboardingStep::SyncConfigured,
+        OnboardingStep::NotificationsGranted,
+    ];
+}
+
+/// Hint about a feature the user has not tried yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingTip {
+    /// An account has no signature
+    AddSignature,
+    /// No filters yet (blocklist entries do not count)
+    CreateFilter,
+    /// No templates yet
+    SaveTemplate,
+}
+
+/// Stored progress
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress {
+    completed: Vec<OnboardingStep>,
+    skipped: Vec<OnboardingStep>,
+    finished_at: Option<String>,
+}
+
+impl Progress {
+    fn is_done(&self, step: OnboardingStep) -> bool {
+        self.completed.contains(&step) || self.skipped.contains(&step)
+    }
+}
+
+/// Onboarding state for the frontend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingState {
+    pub completed: Vec<OnboardingStep>,
+    pub skipped: Vec<OnboardingStep>,
+    /// First step neither completed nor skipped; None once setup is finished
+    pub current_step: Option<OnboardingStep>,
+    pub finished_at: Option<String>,
+    pub tips: Vec<OnboardingTip>,
+}
+
+fn load_progress(db: &Database) -> DbResult<Progress> {
+    Ok(db.get_setting(PROGRESS_SETTING)?.unwrap_or_default())
+}
+
+fn save_progress(db: &Database, progress: &Progress) -> DbResult<()> {
+    db.set_setting(PROGRESS_SETTING, progress)
+}
+
+/// Count an existing account as the completed first step
+fn detect_account(db: &Database, progress: &mut Progress) -> DbResult<()> {
+    // Accounts added before onboarding existed (or outside the guide)
+    if !progress.is_done(OnboardingStep::AccountAdded) && !db.get_accounts()?.is_empty() {
+        progress.completed.push(OnboardingStep::AccountAdded);
+    }
+    Ok(())
+}
+
+fn tips(db: &Database) -> DbResult<Vec<OnboardingTip>> {
+    let accounts = db.get_accounts()?;
+    if accounts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut tips = Vec::new();
+    if accounts.iter().any(|account| account.signature.trim().is_empty()) {
+        tips.push(OnboardingTip::AddSignature);
+    }
+    let filters: i64 = db.query_row(
+        "SELECT COUNT(*) FROM email_filters WHERE priority <> ?1",
+        [BLOCK_FILTER_PRIORITY],
+        |row| row.get(0),
+    )?;
+    if filters == 0 {
+        tips.push(OnboardingTip::CreateFilter);
+    }
+    let templates: i64 = db.query_row("SELECT COUNT(*) FROM email_templates", [], |row| row.get(0))?;
+    if templates == 0 {
+        tips.push(OnboardingTip::SaveTemplate);
+    }
+    Ok(tips)
+}
+
+/// Current onboarding state
+pub fn state(db: &Database) -> DbResult<OnboardingState> {
+    let mut progress = load_progress(db)?;
+    detect_account(db, &mut progress)?;
+
+    let current_step = OnboardingStep::ALL.into_iter().find(|step| !progress.is_done(*step));
+    let tips = if current_step.is_none() { tips(db)? } else { Vec::new() };
+    Ok(OnboardingState {
+        completed: progress.completed,
+        skipped: progress.skipped,
+        current_step,
+        finished_at: progress.finished_at,
+        tips,
+    })
+}
+
+/// Mark a step completed, or skipped
+/// Adding an account cannot be skipped and only completes once one exists.
+pub fn complete_step(db: &Database, step: OnboardingStep, skipped: bool) -> DbResult<OnboardingState> {
+    if step == OnboardingStep::AccountAdded {
+        if skipped {
+            return Err(DbError::Constraint("Adding an account cannot be skipped".to_string()));
+        }
+        if db.get_accounts()?.is_empty() {
+            return Err(DbError::Constraint("No account has been added yet".to_string()));
+        }
+    }
+
+    let mut progress = load_progress(db)?;
+    progress.completed.retain(|s| *s != step);
+    progress.skipped.retain(|s| *s != step);
+    if skipped {
+        progress.skipped.push(step);
+    } else {
+        progress.completed.push(step);
+    }
+    detect_account(db, &mut progress)?;
+    if progress.finished_at.is_none() && OnboardingStep::ALL.into_iter().all(|s| progress.is_done(s)) {
+        progress.finished_at = Some(chrono::Utc::now().to_rfc3339());
+    }
+    save_progress(db, &progress)?;
+    state(db)
+}
+
+/// Start the guided setup over
+pub fn reset(db: &Database) -> DbResult<()> {
+    save_progress(db, &Progress::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    #[test]
+    fn test_onboarding_progress() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+
+        let initial = state(&db).unwrap();
+        assert_eq!(initial.current_step, Some(OnboardingStep::AccountAdded));
+        assert!(complete_step(&db, OnboardingStep::AccountAdded, false).is_err());
+        assert!(complete_step(&db, OnboardingStep::AccountAdded, true).is_err());
+
+        // Later steps can be done first; the current step stays the first open one
+        let after_skip = complete_step(&db, OnboardingStep::NotificationsGranted, true).unwrap();
+        assert_eq!(after_skip.skipped, vec![OnboardingStep::NotificationsGranted]);
+        assert_eq!(after_skip.current_step, Some(OnboardingStep::AccountAdded));
+
+        test_account(&db);
+        assert_eq!(state(&db).unwrap().current_step, Some(OnboardingStep::SyncConfigured));
+
+        let finished = complete_step(&db, OnboardingStep::SyncConfigured, false).unwrap();
+        assert_eq!(finished.current_step, None);
+        assert!(finished.finished_at.is_some());
+        assert_eq!(
+            finished.tips,
+            vec![OnboardingTip::AddSignature, OnboardingTip::CreateFilter, OnboardingTip::SaveTemplate]
+        );
+
+        reset(&db).unwrap();
+        assert_eq!(state(&db).unwrap().current_step, Some(OnboardingStep::SyncConfigured));
+    }
+}
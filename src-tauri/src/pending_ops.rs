@@ -0,0 +1,648 @@
+//! Pending Operations
+//!
+//! Local-first email mutations: read/star/move/delete are applied to SQLite
+//! immediately and queued in the `pending_operations` table together with
+//! outgoing mail that could not be delivered. A background worker replays the
+//! queue in order; while an account is offline its operations simply wait.
+//!
+//! Conflicts are resolved in favour of the server: a flag change on a message
+//! expunged remotely drops the local copy, a move/delete of such a message is
+//! treated as done. Operations the server keeps rejecting are rolled back and
+//! reported with a `store://pending-op-failed` event.
+//!
+//! Each replay is journaled (see `journal`), so an operation the server ran
+//! just before a crash is completed at startup instead of running twice.
+
+use crate::db::{Database, DbError, DbResult};
+use crate::events::{EmailChange, EmailsChanged, PendingOpFailed, StoreEvents};
+use crate::journal::{self, JournalKind, JournalStep};
+use crate::mail::{keywords, MailError, MailResult};
+use crate::AttachmentPath;
+use async_trait::async_trait;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Failed attempts (while online) before an operation is rolled back
+const MAX_ATTEMPTS: u32 = 4;
+
+/// How often the queue is retried without an explicit wake-up
+const REPLAY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Outgoing message waiting for delivery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedSend {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub bcc: Vec<String>,
+    pub subject: String,
+    pub text_body: Option<String>,
+    pub html_body: Option<String>,
+    #[serde(default)]
+    pub attachment_paths: Vec<AttachmentPath>,
+    /// Mailbox the account sends for (From), with the account as Sender
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(test, ts(optional))]
+    pub on_behalf_of: Option<String>,
+}
+
+/// Server-side command behind a pending operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PendingOpKind {
+    SetRead { value: bool },
+    SetStarred { value: bool },
+    /// Add (`value`) or remove an IMAP keyword
+    SetKeyword { keyword: String, value: bool },
+    Move { target: String },
+    Delete { permanent: bool },
+    Send { message: QueuedSend },
+}
+
+impl PendingOpKind {
+    /// Store change announced when the operation is applied locally
+    fn change(&self) -> Option<EmailChange> {
+        match self {
+            PendingOpKind::SetRead { value } => Some(EmailChange::Read { value: *value }),
+            PendingOpKind::SetStarred { value } => Some(EmailChange::Starred { value: *value }),
+            // Announced with the resulting labels by `apply_local`
+            PendingOpKind::SetKeyword { .. } => None,
+            PendingOpKind::Move { target } => Some(EmailChange::Moved { target: target.clone() }),
+            PendingOpKind::Delete { permanent } => Some(EmailChange::Deleted { permanent: *permanent }),
+            PendingOpKind::Send { .. } => None,
+        }
+    }
+}
+
+/// Mutation applied locally and waiting for the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct PendingOperation {
+    pub id: String,
+    pub account_id: String,
+    pub folder: String,
+    pub uid: u32,
+    pub kind: PendingOpKind,
+    /// Local rows touched by the optimistic change
+    #[cfg_attr(test, ts(type = "Array<number>"))]
+    pub email_ids: Vec<i64>,
+    /// Flag value before the change (rollback of read/star)
+    pub previous_value: Option<bool>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+impl PendingOperation {
+    /// Queue entry for a message that could not be delivered
+    pub fn send(account_id: &str, message: QueuedSend) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            account_id: account_id.to_string(),
+            folder: String::new(),
+            uid: 0,
+            kind: PendingOpKind::Send { message },
+            email_ids: Vec::new(),
+            previous_value: None,
+            attempts: 0,
+            last_error: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Executes pending operations on the server
+#[async_trait]
+pub trait RemoteExecutor: Send + Sync {
+    async fn execute(&self, op: &PendingOperation) -> MailResult<()>;
+}
+
+/// Persistent operation queue processed in order by a background worker
+pub struct PendingOps {
+    db: Arc<Database>,
+    wake: Arc<Notify>,
+    started: AtomicBool,
+}
+
+impl PendingOps {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            wake: Arc::new(Notify::new()),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Persist an operation that was already applied locally and wake the worker
+    pub fn enqueue(&self, op: &PendingOperation) -> DbResult<()> {
+        save_operation(&self.db, op)?;
+        self.wake();
+        Ok(())
+    }
+
+    /// Retry the queue now (e.g. after an account reconnected)
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Operations not yet confirmed by the server, oldest first
+    pub fn list(&self) -> DbResult<Vec<PendingOperation>> {
+        load_queue(&self.db)
+    }
+
+    /// Start the background worker (only the first call has an effect)
+    pub fn start(&self, events: StoreEvents, executor: Arc<dyn RemoteExecutor>) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            log::warn!("Pending operation worker already started");
+            return;
+        }
+        let db = self.db.clone();
+        let wake = self.wake.clone();
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                if let Err(e) = replay(&db, &events, executor.as_ref()).await {
+                    log::error!("Failed to replay pending operations: {}", e);
+                }
+                // Timing out just means another pass for accounts still offline
+                let _ = tokio::time::timeout(REPLAY_INTERVAL, wake.notified()).await;
+            }
+        });
+    }
+}
+
+/// Finish an operation the server already ran: local bookkeeping, then
+/// removal from the queue; false if it is no longer queued
+pub fn complete(db: &Database, id: &str) -> DbResult<bool> {
+    let Some(op) = load_queue(db)?.into_iter().find(|op| op.id == id) else {
+        return Ok(false);
+    };
+    finalize(db, &op)?;
+    remove_operation(db, &op.id)?;
+    Ok(true)
+}
+
+/// Apply an operation to the local cache and announce it
+/// Moves and deletes only hide the rows so they can be restored on failure
+pub fn apply_local(
+    db: &Database,
+    events: &StoreEvents,
+    account_id: &str,
+    folder: &str,
+    uid: u32,
+    kind: PendingOpKind,
+) -> DbResult<PendingOperation> {
+    let account_id_num = parse_account_id(account_id)?;
+    let email_ids = db.find_email_ids_by_uids(account_id_num, folder, &[uid])?;
+
+    let previous_value = match (&kind, email_ids.first()) {
+        (PendingOpKind::SetRead { .. }, Some(id)) => Some(db.get_email(*id)?.is_read),
+        (PendingOpKind::SetStarred { .. }, Some(id)) => Some(db.get_email(*id)?.is_starred),
+        (PendingOpKind::SetKeyword { keyword, .. }, Some(id)) => Some(keywords::has_keyword(db, *id, keyword)?),
+        _ => None,
+    };
+
+    let mut labels = None;
+    for id in &email_ids {
+        match &kind {
+            PendingOpKind::SetRead { value } => db.update_email_flags(*id, Some(*value), None, None)?,
+            PendingOpKind::SetStarred { value } => db.update_email_flags(*id, None, Some(*value), None)?,
+            PendingOpKind::SetKeyword { keyword, value } => {
+                labels = Some(keywords::set_local(db, *id, keyword, *value)?);
+            }
+            PendingOpKind::Move { .. } | PendingOpKind::Delete { .. } => {
+                db.update_email_flags(*id, None, None, Some(true))?
+            }
+            PendingOpKind::Send { .. } => {}
+        }
+    }
+
+    if let Some(change) = kind.change().or(labels.map(|labels| EmailChange::Labels { labels })) {
+        events.notify(EmailsChanged {
+            account_id: account_id_num,
+            folder: Some(folder.to_string()),
+            email_ids: email_ids.clone(),
+            uids: vec![uid],
+            change,
+        });
+    }
+
+    Ok(PendingOperation {
+        id: uuid::Uuid::new_v4().to_string(),
+        account_id: account_id.to_string(),
+        folder: folder.to_string(),
+        uid,
+        kind,
+        email_ids,
+        previous_value,
+        attempts: 0,
+        last_error: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Errors caused by missing connectivity rather than by the operation itself
+pub fn is_offline_error(error: &MailError) -> bool {
+    matches!(error, MailError::NotConnected | MailError::Connection(_) | MailError::Io(_))
+}
+
+/// Errors that clear up by waiting: no connection, or the sending quota is used up
+pub fn should_wait(error: &MailError) -> bool {
+    is_offline_error(error) || matches!(error, MailError::QuotaExceeded(_))
+}
+
+/// Execute all queued operations in order
+/// An account that is offline (or just failed) holds back its later operations,
+/// so e.g. "mark read" never overtakes the move queued before it.
+pub async fn replay(db: &Database, events: &StoreEvents, executor: &dyn RemoteExecutor) -> DbResult<()> {
+    let mut held_back: HashSet<String> = HashSet::new();
+
+    for mut op in load_queue(db)? {
+        if held_back.contains(&op.account_id) {
+            continue;
+        }
+
+        journal::begin(db, &op.id, JournalKind::PendingOperation, parse_account_id(&op.account_id).ok(), &op.kind)?;
+        let error = match executor.execute(&op).await {
+            Ok(()) => {
+                journal::advance(db, &op.id, JournalStep::RemoteDone)?;
+                finalize(db, &op)?;
+                remove_operation(db, &op.id)?;
+                journal::finish(db, &op.id)?;
+                continue;
+            }
+            Err(e) => e,
+        };
+        // The operation is still queued; what happens to it next is decided below
+        journal::finish(db, &op.id)?;
+
+        if should_wait(&error) {
+            // Not the operation's fault; wait for the connection or quota without counting it
+            log::debug!("Account {} cannot send now, keeping pending operation {}: {}", op.account_id, op.id, error);
+            op.last_error = Some(error.to_string());
+            save_attempt(db, &op)?;
+            held_back.insert(op.account_id.clone());
+        } else if matches!(error, MailError::NotFound(_)) && !matches!(op.kind, PendingOpKind::Send { .. }) {
+            resolve_conflict(db, events, &op, error.to_string())?;
+            remove_operation(db, &op.id)?;
+        } else {
+            op.attempts += 1;
+            op.last_error = Some(error.to_string());
+
+            if op.attempts >= MAX_ATTEMPTS || matches!(error, MailError::Config(_)) {
+                rollback(db, events, &op, error.to_string());
+                remove_operation(db, &op.id)?;
+            } else {
+                log::warn!(
+                    "Pending operation {} failed (attempt {}/{}): {}",
+                    op.id, op.attempts, MAX_ATTEMPTS, error
+                );
+                save_attempt(db, &op)?;
+                held_back.insert(op.account_id.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_account_id(account_id: &str) -> DbResult<i64> {
+    account_id
+        .parse()
+        .map_err(|_| DbError::Constraint("Invalid account ID".to_string()))
+}
+
+fn save_operation(db: &Database, op: &PendingOperation) -> DbResult<()> {
+    let kind = serde_json::to_string(&op.kind).map_err(|e| DbError::Serialization(e.to_string()))?;
+    let email_ids = serde_json::to_string(&op.email_ids).map_err(|e| DbError::Serialization(e.to_string()))?;
+
+    db.execute(
+        r#"
+        INSERT INTO pending_operations
+            (id, account_id, folder, uid, kind, email_ids, previous_value, attempts, last_error, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        "#,
+        params![
+            op.id,
+            parse_account_id(&op.account_id)?,
+            op.folder,
+            op.uid,
+            kind,
+            email_ids,
+            op.previous_value,
+            op.attempts,
+            op.last_error,
+            op.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+fn save_attempt(db: &Database, op: &PendingOperation) -> DbResult<()> {
+    db.execute(
+        "UPDATE pending_operations SET attempts = ?1, last_error = ?2 WHERE id = ?3",
+        params![op.attempts, op.last_error, op.id],
+    )?;
+    Ok(())
+}
+
+fn remove_operation(db: &Database, id: &str) -> DbResult<()> {
+    db.execute("DELETE FROM pending_operations WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Drop a queued operation before it runs; false if it is no longer queued
+/// (already executed, rolled back or never queued)
+pub fn cancel(db: &Database, id: &str) -> DbResult<bool> {
+    Ok(db.execute("DELETE FROM pending_operations WHERE id = ?1", [id])? > 0)
+}
+
+/// Operations not yet confirmed by the server, oldest first
+pub fn load_queue(db: &Database) -> DbResult<Vec<PendingOperation>> {
+    let rows = db.query(
+        r#"
+        SELECT id, account_id, folder, uid, kind, email_ids, previous_value, attempts, last_error, created_at
+        FROM pending_operations
+        ORDER BY seq
+        "#,
+        [],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, Option<bool>>(6)?,
+                row.get::<_, u32>(7)?,
+                row.get::<_, Option<String>>(8)?,
+                row.get::<_, String>(9)?,
+            ))
+        },
+    )?;
+
+    rows.into_iter()
+        .map(|(id, account_id, folder, uid, kind, email_ids, previous_value, attempts, last_error, created_at)| {
+            Ok(PendingOperation {
+                id,
+                account_id: account_id.to_string(),
+                folder,
+                uid,
+                kind: serde_json::from_str(&kind).map_err(|e| DbError::Serialization(e.to_string()))?,
+                email_ids: serde_json::from_str(&email_ids).map_err(|e| DbError::Serialization(e.to_string()))?,
+                previous_value,
+                attempts,
+                last_error,
+                created_at,
+            })
+        })
+        .collect()
+}
+
+/// Server confirmed the operation: drop rows that were only hidden
+fn finalize(db: &Database, op: &PendingOperation) -> DbResult<()> {
+    match op.kind {
+        PendingOpKind::Move { .. } | PendingOpKind::Delete { .. } => db.delete_emails(&op.email_ids),
+        _ => Ok(()),
+    }
+}
+
+/// The message no longer exists on the server
+fn resolve_conflict(db: &Database, events: &StoreEvents, op: &PendingOperation, error: String) -> DbResult<()> {
+    // A move or delete of an expunged message already has the intended outcome
+    if matches!(op.kind, PendingOpKind::Move { .. } | PendingOpKind::Delete { .. }) {
+        return finalize(db, op);
+    }
+
+    log::warn!("Pending operation {} targets an expunged message, dropping local copy", op.id);
+    db.delete_emails(&op.email_ids)?;
+
+    let account_id = op.account_id.parse().unwrap_or_default();
+    events.notify(EmailsChanged {
+        account_id,
+        folder: Some(op.folder.clone()),
+        email_ids: op.email_ids.clone(),
+        uids: vec![op.uid],
+        change: EmailChange::Deleted { permanent: true },
+    });
+    events.notify_failure(PendingOpFailed {
+        op_id: op.id.clone(),
+        account_id,
+        email_ids: op.email_ids.clone(),
+        error,
+    });
+    Ok(())
+}
+
+/// Server rejected the operation: restore the previous local state and report
+fn rollback(db: &Database, events: &StoreEvents, op: &PendingOperation, error: String) {
+    log::error!("Pending operation {} failed, rolling back: {}", op.id, error);
+    revert_local(db, events, op);
+
+    events.notify_failure(PendingOpFailed {
+        op_id: op.id.clone(),
+        account_id: op.account_id.parse().unwrap_or_default(),
+        email_ids: op.email_ids.clone(),
+        error,
+    });
+}
+
+/// Restore the local state an operation replaced and announce it
+/// (a rejected operation, or one undone before it ran)
+pub fn revert_local(db: &Database, events: &StoreEvents, op: &PendingOperation) {
+    let change = match &op.kind {
+        PendingOpKind::SetRead { value } => Some(EmailChange::Read { value: op.previous_value.unwrap_or(!value) }),
+        PendingOpKind::SetStarred { value } => {
+            Some(EmailChange::Starred { value: op.previous_value.unwrap_or(!value) })
+        }
+        PendingOpKind::SetKeyword { keyword, value } => {
+            let value = op.previous_value.unwrap_or(!value);
+            let mut labels = Vec::new();
+            for id in &op.email_ids {
+                match keywords::set_local(db, *id, keyword, value) {
+                    Ok(restored) => labels = restored,
+                    Err(e) => log::warn!("Failed to roll back email {}: {}", id, e),
+                }
+            }
+            Some(EmailChange::Labels { labels })
+        }
+        PendingOpKind::Move { .. } | PendingOpKind::Delete { .. } => Some(EmailChange::Restored),
+        PendingOpKind::Send { .. } => None,
+    };
+    let Some(change) = change else {
+        return;
+    };
+
+    for id in &op.email_ids {
+        let result = match change {
+            EmailChange::Read { value } => db.update_email_flags(*id, Some(value), None, None),
+            EmailChange::Starred { value } => db.update_email_flags(*id, None, Some(value), None),
+            // Restored above
+            EmailChange::Labels { .. } => Ok(()),
+            _ => db.update_email_flags(*id, None, None, Some(false)),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to roll back email {}: {}", id, e);
+        }
+    }
+
+    events.notify(EmailsChanged {
+        account_id: op.account_id.parse().unwrap_or_default(),
+        folder: Some(op.folder.clone()),
+        email_ids: op.email_ids.clone(),
+        uids: vec![op.uid],
+        change,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewFolder};
+    use crate::events::StoreEvent;
+    use std::sync::Mutex;
+
+    /// Records executed operations; fails every call with the configured error
+    struct MockExecutor {
+        online: AtomicBool,
+        error: Option<fn() -> MailError>,
+        executed: Mutex<Vec<PendingOpKind>>,
+    }
+
+    impl MockExecutor {
+        fn new(online: bool, error: Option<fn() -> MailError>) -> Self {
+            Self {
+                online: AtomicBool::new(online),
+                error,
+                executed: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl RemoteExecutor for MockExecutor {
+        async fn execute(&self, op: &PendingOperation) -> MailResult<()> {
+            if !self.online.load(Ordering::SeqCst) {
+                return Err(MailError::NotConnected);
+            }
+            if let Some(error) = self.error {
+                return Err(error());
+            }
+            self.executed.lock().unwrap().push(op.kind.clone());
+            Ok(())
+        }
+    }
+
+    fn setup_db() -> (Arc<Database>, String, i64) {
+        let db = Database::in_memory().expect("Failed to create database");
+        let account_id = test_account(&db);
+        let folder_id = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+        let email_id = db.upsert_email(&test_email(account_id, folder_id, 42)).unwrap();
+        (Arc::new(db), account_id.to_string(), email_id)
+    }
+
+    #[tokio::test]
+    async fn test_offline_queue_replays_in_order() {
+        let (db, account_id, email_id) = setup_db();
+        let events = StoreEvents::new();
+        let ops = PendingOps::new(db.clone());
+        let executor = MockExecutor::new(false, None);
+
+        let read = apply_local(&db, &events, &account_id, "INBOX", 42, PendingOpKind::SetRead { value: true }).unwrap();
+        ops.enqueue(&read).unwrap();
+        let kind = PendingOpKind::Move { target: "Archive".to_string() };
+        let moved = apply_local(&db, &events, &account_id, "INBOX", 42, kind.clone()).unwrap();
+        ops.enqueue(&moved).unwrap();
+        let message = QueuedSend {
+            to: vec!["b@test.com".to_string()],
+            cc: vec![],
+            bcc: vec![],
+            subject: "Offline".to_string(),
+            text_body: Some("Sent later".to_string()),
+            html_body: None,
+            attachment_paths: vec![],
+            on_behalf_of: None,
+        };
+        ops.enqueue(&PendingOperation::send(&account_id, message.clone())).unwrap();
+
+        // Offline: nothing runs, nothing is rolled back
+        replay(&db, &events, &executor).await.unwrap();
+        let queued = ops.list().unwrap();
+        assert_eq!(queued.len(), 3);
+        assert!(queued.iter().all(|op| op.attempts == 0));
+        assert!(queued[0].last_error.is_some());
+        assert!(db.get_email(email_id).unwrap().is_deleted);
+
+        executor.online.store(true, Ordering::SeqCst);
+        replay(&db, &events, &executor).await.unwrap();
+
+        assert_eq!(
+            *executor.executed.lock().unwrap(),
+            vec![PendingOpKind::SetRead { value: true }, kind, PendingOpKind::Send { message }]
+        );
+        assert!(ops.list().unwrap().is_empty());
+        assert!(db.get_email(email_id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_flag_change_on_expunged_message_drops_local_copy() {
+        let (db, account_id, email_id) = setup_db();
+        let events = StoreEvents::new();
+        let mut receiver = events.subscribe();
+        let ops = PendingOps::new(db.clone());
+        let executor = MockExecutor::new(true, Some(|| MailError::NotFound("UID 42".to_string())));
+
+        let op = apply_local(&db, &events, &account_id, "INBOX", 42, PendingOpKind::SetStarred { value: true }).unwrap();
+        ops.enqueue(&op).unwrap();
+        replay(&db, &events, &executor).await.unwrap();
+
+        assert!(db.get_email(email_id).is_err());
+        assert!(ops.list().unwrap().is_empty());
+
+        let mut saw_failure = false;
+        while let Ok(event) = receiver.try_recv() {
+            saw_failure |= matches!(event, StoreEvent::PendingOpFailed(ref f) if f.op_id == op.id);
+        }
+        assert!(saw_failure);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_operation_is_rolled_back() {
+        let (db, account_id, email_id) = setup_db();
+        let events = StoreEvents::new();
+        let ops = PendingOps::new(db.clone());
+        let executor = MockExecutor::new(true, Some(|| MailError::Imap("NO [CANNOT]".to_string())));
+
+        let op = apply_local(&db, &events, &account_id, "INBOX", 42, PendingOpKind::SetRead { value: true }).unwrap();
+        ops.enqueue(&op).unwrap();
+        assert!(db.get_email(email_id).unwrap().is_read);
+
+        for attempt in 1..MAX_ATTEMPTS {
+            replay(&db, &events, &executor).await.unwrap();
+            assert_eq!(ops.list().unwrap()[0].attempts, attempt);
+        }
+        replay(&db, &events, &executor).await.unwrap();
+
+        assert!(ops.list().unwrap().is_empty());
+        assert!(!db.get_email(email_id).unwrap().is_read);
+    }
+}
@@ -0,0 +1,258 @@
+//! Mail Processing Plugins
+//!
+//! Extension points for third-party processing (CRM loggers, custom spam
+//! engines, outgoing mail policies) without patching the command layer.
+//! Plugins are compiled in and registered once at startup, before `run()`:
+//!
+//! ```ignore
+//! fn main() {
+//!     owlivion_mail_lib::plugins::register(std::sync::Arc::new(MyCrmLogger::default()));
+//!     owlivion_mail_lib::run()
+//! }
+//! ```
+//!
+//! Every hook has a no-op default, so a plugin only implements what it needs.
+//! Hooks run in registration order. A failing plugin is logged and skipped;
+//! only an explicit rejection in `on_before_send` stops a message.
+
+use crate::db::{Account, Email};
+use crate::filters::FilterAction;
+use crate::pending_ops::QueuedSend;
+use async_trait::async_trait;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+/// Plugin hook errors
+#[derive(Error, Debug)]
+pub enum PluginError {
+    /// Stop the operation (only honoured by `on_before_send`)
+    #[error("{0}")]
+    Rejected(String),
+
+    /// The plugin itself failed; processing continues without it
+    #[error("{0}")]
+    Failed(String),
+}
+
+pub type PluginResult<T> = Result<T, PluginError>;
+
+/// Mail processing hooks
+#[async_trait]
+pub trait MailPlugin: Send + Sync {
+    /// Unique, human-readable name (used in logs and `plugin_list`)
+    fn name(&self) -> &str;
+
+    /// A new message was stored locally (before filters run)
+    async fn on_email_received(&self, _email: &Email) -> PluginResult<()> {
+        Ok(())
+    }
+
+    /// An outgoing message passed validation and is about to be sent or queued
+    /// The message may be modified; `PluginError::Rejected` cancels the send.
+    async fn on_before_send(&self, _account: &Account, _message: &mut QueuedSend) -> PluginResult<()> {
+        Ok(())
+    }
+
+    /// A matched filter action is about to run; return false to skip it
+    async fn on_filter_action(&self, _email: &Email, _action: &FilterAction) -> PluginResult<bool> {
+        Ok(true)
+    }
+}
+
+/// Registered plugins, run in registration order
+#[derive(Default)]
+pub struct PluginRegistry {
+    plugins: RwLock<Vec<Arc<dyn MailPlugin>>>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a plugin; a plugin with the same name is replaced
+    pub fn register(&self, plugin: Arc<dyn MailPlugin>) {
+        let mut plugins = self.plugins.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        plugins.retain(|existing| existing.name() != plugin.name());
+        log::info!("Registered mail plugin '{}'", plugin.name());
+        plugins.push(plugin);
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.snapshot().iter().map(|plugin| plugin.name().to_string()).collect()
+    }
+
+    /// Copy of the list so no lock is held across hook calls
+    fn snapshot(&self) -> Vec<Arc<dyn MailPlugin>> {
+        self.plugins.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Run `on_email_received` on every plugin
+    pub async fn email_received(&self, email: &Email) {
+        for plugin in self.snapshot() {
+            if let Err(e) = plugin.on_email_received(email).await {
+                log::warn!("Plugin '{}' failed on received email {}: {}", plugin.name(), email.id, e);
+            }
+        }
+    }
+
+    /// Run `on_before_send` on every plugin
+    /// Returns the rejection reason if a plugin cancelled the send.
+    pub async fn before_send(&self, account: &Account, message: &mut QueuedSend) -> Result<(), String> {
+        for plugin in self.snapshot() {
+            match plugin.on_before_send(account, message).await {
+                Ok(()) => {}
+                Err(PluginError::Rejected(reason)) => {
+                    log::info!("Plugin '{}' rejected message '{}': {}", plugin.name(), message.subject, reason);
+                    return Err(format!("Rejected by {}: {}", plugin.name(), reason));
+                }
+                Err(e) => log::warn!("Plugin '{}' failed before send: {}", plugin.name(), e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether every plugin allows a filter action (failures count as allowed)
+    pub async fn allow_filter_action(&self, email: &Email, action: &FilterAction) -> bool {
+        for plugin in self.snapshot() {
+            match plugin.on_filter_action(email, action).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    log::info!("Plugin '{}' skipped {:?} on email {}", plugin.name(), action.action, email.id);
+                    return false;
+                }
+                Err(e) => log::warn!("Plugin '{}' failed on filter action: {}", plugin.name(), e),
+            }
+        }
+        true
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Process-wide registry used by the app and the CLI
+    static ref REGISTRY: Arc<PluginRegistry> = Arc::new(PluginRegistry::new());
+}
+
+/// The process-wide plugin registry
+pub fn registry() -> Arc<PluginRegistry> {
+    REGISTRY.clone()
+}
+
+/// Register a plugin with the process-wide registry
+pub fn register(plugin: Arc<dyn MailPlugin>) {
+    REGISTRY.register(plugin);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, Database};
+    use crate::filters::FilterActionType;
+    use std::sync::Mutex;
+
+    /// Logs received subjects, tags outgoing mail and vetoes deletes
+    #[derive(Default)]
+    struct Recorder {
+        received: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl MailPlugin for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        async fn on_email_received(&self, email: &Email) -> PluginResult<()> {
+            self.received.lock().unwrap().push(email.subject.clone());
+            Ok(())
+        }
+
+        async fn on_before_send(&self, _account: &Account, message: &mut QueuedSend) -> PluginResult<()> {
+            if message.to.iter().any(|to| to.ends_with("@blocked.test")) {
+                return Err(PluginError::Rejected("blocked domain".to_string()));
+            }
+            message.subject = format!("[crm] {}", message.subject);
+            Ok(())
+        }
+
+        async fn on_filter_action(&self, _email: &Email, action: &FilterAction) -> PluginResult<bool> {
+            Ok(action.action != FilterActionType::Delete)
+        }
+    }
+
+    /// Fails every hook
+    struct Broken;
+
+    #[async_trait]
+    impl MailPlugin for Broken {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        async fn on_before_send(&self, _account: &Account, _message: &mut QueuedSend) -> PluginResult<()> {
+            Err(PluginError::Failed("backend down".to_string()))
+        }
+
+        async fn on_filter_action(&self, _email: &Email, _action: &FilterAction) -> PluginResult<bool> {
+            Err(PluginError::Failed("backend down".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_run_in_order_and_failures_are_contained() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let account = db.get_account(account_id).unwrap();
+
+        let registry = PluginRegistry::new();
+        let recorder = Arc::new(Recorder::default());
+        registry.register(Arc::new(Broken));
+        registry.register(recorder.clone());
+        registry.register(Arc::new(Broken));
+        assert_eq!(registry.names(), vec!["recorder", "broken"]);
+
+        let mut message = QueuedSend {
+            to: vec!["bob@test.com".to_string()],
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            subject: "Hello".to_string(),
+            text_body: Some("Hi".to_string()),
+            html_body: None,
+            attachment_paths: Vec::new(),
+            on_behalf_of: None,
+        };
+        registry.before_send(&account, &mut message).await.unwrap();
+        assert_eq!(message.subject, "[crm] Hello");
+
+        message.to.push("eve@blocked.test".to_string());
+        let error = registry.before_send(&account, &mut message).await.unwrap_err();
+        assert!(error.contains("recorder") && error.contains("blocked domain"));
+
+        let folder_id = crate::sync_folder_to_db(&db, account_id, "INBOX").unwrap();
+        let summary = crate::mail::EmailSummary {
+            uid: 7,
+            message_id: Some("<7@test.com>".to_string()),
+            from: "alice@test.com".to_string(),
+            from_name: None,
+            subject: "Invoice".to_string(),
+            preview: String::new(),
+            date: "2025-01-01T10:00:00Z".to_string(),
+            is_read: false,
+            is_starred: false,
+            has_attachments: false,
+            size: 0,
+            keywords: Vec::new(),
+            account_id: None,
+            account_email: None,
+            account_name: None,
+            account_color: None,
+        };
+        let ids = db.batch_upsert_emails(&[crate::summary_to_new_email(account_id, folder_id, &summary)]).unwrap();
+        let email = db.get_email(ids[0]).unwrap();
+        registry.email_received(&email).await;
+        assert_eq!(*recorder.received.lock().unwrap(), vec!["Invoice"]);
+
+        assert!(registry.allow_filter_action(&email, &FilterAction::mark_as_read()).await);
+        assert!(!registry.allow_filter_action(&email, &FilterAction::delete()).await);
+    }
+}
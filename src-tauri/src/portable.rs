@@ -0,0 +1,100 @@
+//! Portable Mode
+//!
+//! To run from a USB stick, all data can be kept beside the executable
+//! instead of in the user's platform directories. Portable mode is on when
+//! `OWLIVION_PORTABLE_DIR` names a directory, or when a `portable.txt` marker
+//! sits next to the binary; the marker may hold a path to use instead of the
+//! default `data` directory. Relative paths start at the binary's directory.
+//! Databases and profiles, the encryption salt, caches and temporary
+//! attachment files then all live below that directory.
+//!
+//! Stored passwords are then encrypted with a key derived from the salt in the
+//! portable directory alone (not the machine), so they keep working on any
+//! computer and are only as safe as the drive itself.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable naming the portable data directory
+pub const PORTABLE_ENV: &str = "OWLIVION_PORTABLE_DIR";
+
+/// Marker file next to the executable
+pub const MARKER_FILE: &str = "portable.txt";
+
+/// Data directory beside the executable when the marker names none
+const DEFAULT_DIR: &str = "data";
+
+/// Portable data directory for an executable directory and the environment value
+pub fn detect(exe_dir: &Path, env_dir: Option<&str>) -> Option<PathBuf> {
+    if let Some(dir) = env_dir.map(str::trim).filter(|dir| !dir.is_empty()) {
+        return Some(exe_dir.join(dir));
+    }
+
+    let marker = std::fs::read_to_string(exe_dir.join(MARKER_FILE)).ok()?;
+    let dir = marker.lines().next().map(str::trim).filter(|dir| !dir.is_empty()).unwrap_or(DEFAULT_DIR);
+    Some(exe_dir.join(dir))
+}
+
+/// Portable data directory of this process (None outside portable mode)
+pub fn dir() -> Option<&'static Path> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let exe = std::env::current_exe().ok()?;
+        let dir = detect(exe.parent()?, std::env::var(PORTABLE_ENV).ok().as_deref());
+        if let Some(dir) = &dir {
+            log::info!("Portable mode, data directory: {:?}", dir);
+        }
+        dir
+    })
+    .as_deref()
+}
+
+/// Directory for databases, profiles and the encryption salt (created if missing)
+pub fn data_dir() -> Result<PathBuf, String> {
+    let data_dir = match dir() {
+        Some(dir) => dir.to_path_buf(),
+        None => directories::ProjectDirs::from("com", "owlivion", "owlivion-mail")
+            .ok_or("Failed to get app directories. Please ensure HOME environment variable is set.")?
+            .data_dir()
+            .to_path_buf(),
+    };
+    std::fs::create_dir_all(&data_dir)
+        .map_err(|e| format!("Failed to create data directory at {:?}: {}", data_dir, e))?;
+    Ok(data_dir)
+}
+
+/// Cache directory in portable mode (otherwise the platform's app cache directory applies)
+pub fn cache_dir() -> Option<PathBuf> {
+    dir().map(|dir| dir.join("cache"))
+}
+
+/// Directory for temporary files
+pub fn temp_dir() -> PathBuf {
+    match dir() {
+        Some(dir) => dir.join("tmp"),
+        None => std::env::temp_dir(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let exe_dir = std::env::temp_dir().join(format!("owlivion-portable-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&exe_dir).unwrap();
+
+        assert_eq!(detect(&exe_dir, None), None);
+        assert_eq!(detect(&exe_dir, Some("  ")), None);
+        assert_eq!(detect(&exe_dir, Some("/media/usb/mail")), Some(PathBuf::from("/media/usb/mail")));
+
+        std::fs::write(exe_dir.join(MARKER_FILE), "").unwrap();
+        assert_eq!(detect(&exe_dir, None), Some(exe_dir.join("data")));
+        std::fs::write(exe_dir.join(MARKER_FILE), "mail-data\n# anything else is ignored\n").unwrap();
+        assert_eq!(detect(&exe_dir, None), Some(exe_dir.join("mail-data")));
+        assert_eq!(detect(&exe_dir, Some("other")), Some(exe_dir.join("other")));
+
+        let _ = std::fs::remove_dir_all(&exe_dir);
+    }
+}
@@ -0,0 +1,340 @@
+//! Read-Ahead Prefetch
+//!
+//! When a folder is opened, the full bodies of its newest unread messages are
+//! fetched in the background and kept in memory, so opening one of them needs
+//! no round trip to the server. Bodies are fetched with BODY.PEEK[] and stay
+//! unread. How many messages are fetched, how many bytes are kept and how many
+//! connections run at once are settings; nothing is prefetched while the
+//! account cannot sync or the network is metered.
+
+use crate::db::{Database, DbResult};
+use crate::mail::{AsyncImapClient, EmailSummary, ParsedEmail};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Settings key holding `PrefetchSettings`
+const SETTINGS_KEY: &str = "prefetch";
+
+/// Upper bounds for user-supplied settings
+const MAX_MESSAGES: usize = 50;
+const MAX_CONCURRENCY: usize = 4;
+
+/// Read-ahead configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrefetchSettings {
+    pub enabled: bool,
+    /// Unread messages fetched when a folder is opened
+    pub max_messages: usize,
+    /// Total size of the bodies kept in memory
+    pub budget_bytes: usize,
+    /// IMAP connections used at once
+    pub concurrency: usize,
+}
+
+impl Default for PrefetchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_messages: 10,
+            budget_bytes: 8 * 1024 * 1024,
+            concurrency: 2,
+        }
+    }
+}
+
+impl PrefetchSettings {
+    /// Clamp values to what the prefetcher supports
+    fn clamped(self) -> Self {
+        Self {
+            max_messages: self.max_messages.min(MAX_MESSAGES),
+            concurrency: self.concurrency.clamp(1, MAX_CONCURRENCY),
+            ..self
+        }
+    }
+}
+
+pub fn load_settings(db: &Database) -> DbResult<PrefetchSettings> {
+    Ok(db.get_setting::<PrefetchSettings>(SETTINGS_KEY)?.unwrap_or_default().clamped())
+}
+
+/// Save settings; returns them as stored (clamped)
+pub fn save_settings(db: &Database, settings: PrefetchSettings) -> DbResult<PrefetchSettings> {
+    let settings = settings.clamped();
+    db.set_setting(SETTINGS_KEY, &settings)?;
+    Ok(settings)
+}
+
+/// Size a prefetched body counts against the budget
+fn body_size(email: &ParsedEmail) -> usize {
+    email.body_text.as_ref().map_or(0, String::len) + email.body_html.as_ref().map_or(0, String::len)
+}
+
+type Key = (i64, String, u32);
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<Key, ParsedEmail>,
+    /// Insertion order, oldest first
+    order: VecDeque<Key>,
+    bytes: usize,
+    /// Folders with a prefetch run in progress
+    running: HashSet<(i64, String)>,
+}
+
+impl CacheState {
+    fn remove(&mut self, key: &Key) -> Option<ParsedEmail> {
+        let email = self.entries.remove(key)?;
+        self.order.retain(|k| k != key);
+        self.bytes -= body_size(&email);
+        Some(email)
+    }
+}
+
+/// Memory usage of the prefetch cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefetchStats {
+    pub messages: usize,
+    pub bytes: usize,
+}
+
+/// Prefetched message bodies, evicted oldest first beyond the byte budget
+#[derive(Default)]
+pub struct PrefetchCache {
+    state: Mutex<CacheState>,
+}
+
+impl PrefetchCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn get(&self, account_id: i64, folder: &str, uid: u32) -> Option<ParsedEmail> {
+        self.lock().entries.get(&(account_id, folder.to_string(), uid)).cloned()
+    }
+
+    /// Keep a body; older ones are dropped until the cache fits `budget_bytes`
+    /// A body larger than the whole budget is not kept.
+    pub fn insert(&self, account_id: i64, folder: &str, email: ParsedEmail, budget_bytes: usize) -> bool {
+        let size = body_size(&email);
+        if size > budget_bytes {
+            return false;
+        }
+
+        let mut state = self.lock();
+        let key = (account_id, folder.to_string(), email.uid);
+        state.remove(&key);
+        while state.bytes + size > budget_bytes {
+            let Some(oldest) = state.order.front().cloned() else {
+                break;
+            };
+            state.remove(&oldest);
+        }
+        state.bytes += size;
+        state.order.push_back(key.clone());
+        state.entries.insert(key, email);
+        true
+    }
+
+    /// Forget everything cached for an account
+    pub fn clear_account(&self, account_id: i64) {
+        let mut state = self.lock();
+        let keys: Vec<Key> = state.order.iter().filter(|key| key.0 == account_id).cloned().collect();
+        for key in keys {
+            state.remove(&key);
+        }
+    }
+
+    pub fn stats(&self) -> PrefetchStats {
+        let state = self.lock();
+        PrefetchStats { messages: state.entries.len(), bytes: state.bytes }
+    }
+
+    /// UIDs worth prefetching from a folder listing: unread and not cached
+    /// yet, newest first
+    pub fn candidates(&self, account_id: i64, folder: &str, emails: &[EmailSummary], max_messages: usize) -> Vec<u32> {
+        let state = self.lock();
+        let mut uids: Vec<u32> = emails
+            .iter()
+            .filter(|email| !email.is_read)
+            .map(|email| email.uid)
+            .filter(|uid| !state.entries.contains_key(&(account_id, folder.to_string(), *uid)))
+            .collect();
+        uids.sort_unstable_by(|a, b| b.cmp(a));
+        uids.truncate(max_messages);
+        uids
+    }
+
+    /// Mark a folder as being prefetched; false if a run is already going
+    fn start_run(&self, account_id: i64, folder: &str) -> bool {
+        self.lock().running.insert((account_id, folder.to_string()))
+    }
+
+    fn finish_run(&self, account_id: i64, folder: &str) {
+        self.lock().running.remove(&(account_id, folder.to_string()));
+    }
+
+    /// Fetch `uids` over up to `settings.concurrency` connections opened by
+    /// `connect` and keep their bodies; returns how many were cached
+    /// Stops early once the fetched bodies fill the byte budget.
+    pub async fn prefetch<F, Fut>(
+        &self,
+        settings: &PrefetchSettings,
+        account_id: i64,
+        folder: &str,
+        uids: Vec<u32>,
+        connect: F,
+    ) -> usize
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<AsyncImapClient, String>>,
+    {
+        if uids.is_empty() || !self.start_run(account_id, folder) {
+            return 0;
+        }
+
+        let workers = settings.concurrency.clamp(1, MAX_CONCURRENCY).min(uids.len());
+        let fetched_bytes = AtomicUsize::new(0);
+        let cached = AtomicUsize::new(0);
+
+        let runs = (0..workers).map(|worker| {
+            // Round-robin, so the newest messages are fetched first
+            let share: Vec<u32> = uids.iter().copied().skip(worker).step_by(workers).collect();
+            let (connect, fetched_bytes, cached) = (&connect, &fetched_bytes, &cached);
+            async move {
+                let mut client = match connect().await {
+                    Ok(client) => client,
+                    Err(e) => {
+                        log::warn!("Prefetch connection failed for account {}: {}", account_id, e);
+                        return;
+                    }
+                };
+                for uid in share {
+                    if fetched_bytes.load(Ordering::SeqCst) >= settings.budget_bytes {
+                        break;
+                    }
+                    match client.peek_email(folder, uid).await {
+                        Ok(email) => {
+                            fetched_bytes.fetch_add(body_size(&email), Ordering::SeqCst);
+                            if self.insert(account_id, folder, email, settings.budget_bytes) {
+                                cached.fetch_add(1, Ordering::SeqCst);
+                            }
+                        }
+                        Err(e) => log::debug!("Prefetch of uid {} in {} failed: {}", uid, folder, e),
+                    }
+                }
+                let _ = client.disconnect().await;
+            }
+        });
+        futures::future::join_all(runs).await;
+
+        self.finish_run(account_id, folder);
+        cached.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{sample_message, MockImapServer};
+
+    fn email(uid: u32, body: &str) -> ParsedEmail {
+        ParsedEmail {
+            uid,
+            message_id: None,
+            from: "a@test.com".to_string(),
+            from_name: None,
+            to: vec![],
+            cc: vec![],
+            subject: String::new(),
+            date: String::new(),
+            body_text: Some(body.to_string()),
+            body_html: None,
+            is_read: false,
+            is_starred: false,
+            attachments: vec![],
+            delivery: Default::default(),
+            quotes: Default::default(),
+            structure: None,
+            policy: None,
+            size: 0,
+            headers: vec![],
+            calendar: None,
+            bounced: vec![],
+        }
+    }
+
+    #[test]
+    fn test_budget_evicts_oldest() {
+        let cache = PrefetchCache::new();
+        assert!(cache.insert(1, "INBOX", email(1, "aaaa"), 10));
+        assert!(cache.insert(1, "INBOX", email(2, "bbbb"), 10));
+        assert!(cache.insert(1, "INBOX", email(3, "cccc"), 10));
+        assert!(cache.get(1, "INBOX", 1).is_none());
+        assert!(cache.get(1, "INBOX", 3).is_some());
+        assert_eq!(cache.stats(), PrefetchStats { messages: 2, bytes: 8 });
+
+        // Too large for the budget on its own
+        assert!(!cache.insert(1, "INBOX", email(4, "x".repeat(11).as_str()), 10));
+
+        cache.clear_account(1);
+        assert_eq!(cache.stats(), PrefetchStats { messages: 0, bytes: 0 });
+    }
+
+    #[test]
+    fn test_settings_are_clamped() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        assert_eq!(load_settings(&db).unwrap(), PrefetchSettings::default());
+
+        let saved = save_settings(&db, PrefetchSettings { max_messages: 500, concurrency: 0, ..Default::default() }).unwrap();
+        assert_eq!((saved.max_messages, saved.concurrency), (MAX_MESSAGES, 1));
+        assert_eq!(load_settings(&db).unwrap(), saved);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_keeps_messages_unread() {
+        let server = MockImapServer::start("alice@mock.test", "secret").await.unwrap();
+        for n in 1..=3 {
+            server.append(
+                "INBOX",
+                &sample_message("bob@mock.test", "alice@mock.test", &format!("Note {}", n), "Hello"),
+                &[],
+            );
+        }
+        server.append("INBOX", &sample_message("bob@mock.test", "alice@mock.test", "Old", "Read"), &["\\Seen"]);
+
+        let mut client = AsyncImapClient::new(server.config());
+        client.connect().await.unwrap();
+        let listing = client.fetch_emails("INBOX", 0, 50).await.unwrap();
+        let _ = client.disconnect().await;
+
+        let cache = PrefetchCache::new();
+        let uids = cache.candidates(1, "INBOX", &listing.emails, 2);
+        assert_eq!(uids, vec![3, 2]);
+
+        let settings = PrefetchSettings::default();
+        let config = server.config();
+        let cached = cache
+            .prefetch(&settings, 1, "INBOX", uids, || {
+                let mut client = AsyncImapClient::new(config.clone());
+                async move {
+                    client.connect().await.map_err(|e| e.to_string())?;
+                    Ok(client)
+                }
+            })
+            .await;
+        assert_eq!(cached, 2);
+        assert!(cache.get(1, "INBOX", 3).unwrap().body_text.unwrap().contains("Hello"));
+        assert_eq!(cache.candidates(1, "INBOX", &listing.emails, 10), vec![1]);
+        assert!(server.messages("INBOX").iter().take(3).all(|message| !message.has_flag("\\Seen")));
+    }
+}
@@ -0,0 +1,257 @@
+//! User Profiles
+//!
+//! A profile (e.g. "Work" and "Personal") is a separate set of data: its own
+//! database, attachment store and draft cache. The default profile keeps the
+//! data directory itself, so existing installs need no migration; other
+//! profiles live in `profiles/<id>/`. The list of profiles and the one opened
+//! at launch are kept in `profiles.json` in the data directory.
+//!
+//! Everything in the app state (database pool, schedulers, IMAP sessions)
+//! belongs to one profile, so switching restarts the app into the chosen
+//! profile. `--profile <id>` or an `owlivion://profile/<id>` link on the
+//! command line starts straight into a profile.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Profile using the data directory itself
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profile list and launch profile, in the data directory
+const REGISTRY_FILE: &str = "profiles.json";
+
+/// Link form of the launch flag
+const PROFILE_URL_PREFIX: &str = "owlivion://profile/";
+
+/// Longest profile name
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// Directory name under `profiles/` (`default` for the data directory)
+    pub id: String,
+    pub name: String,
+    pub created_at: Option<String>,
+}
+
+/// Profiles and the one the running app uses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    pub current: String,
+    pub profiles: Vec<Profile>,
+}
+
+/// Contents of `profiles.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Registry {
+    /// Profile opened at launch
+    active: Option<String>,
+    /// Profiles besides the default one
+    profiles: Vec<Profile>,
+}
+
+/// Profiles under one data directory
+#[derive(Debug, Clone)]
+pub struct Profiles {
+    root: PathBuf,
+}
+
+impl Profiles {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Profiles in the platform data directory (or the portable one)
+    pub fn open_default() -> Result<Self, String> {
+        Ok(Self::new(crate::portable::data_dir()?))
+    }
+
+    fn load(&self) -> Result<Registry, String> {
+        match std::fs::read_to_string(self.root.join(REGISTRY_FILE)) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid {}: {}", REGISTRY_FILE, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Registry::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", REGISTRY_FILE, e)),
+        }
+    }
+
+    fn save(&self, registry: &Registry) -> Result<(), String> {
+        std::fs::create_dir_all(&self.root)
+            .map_err(|e| format!("Failed to create data directory at {:?}: {}", self.root, e))?;
+        let json = serde_json::to_string_pretty(registry).map_err(|e| e.to_string())?;
+        std::fs::write(self.root.join(REGISTRY_FILE), json)
+            .map_err(|e| format!("Failed to write {}: {}", REGISTRY_FILE, e))
+    }
+
+    /// All profiles, the default one first
+    pub fn list(&self) -> Result<Vec<Profile>, String> {
+        let mut profiles = vec![Profile {
+            id: DEFAULT_PROFILE.to_string(),
+            name: "Default".to_string(),
+            created_at: None,
+        }];
+        profiles.extend(self.load()?.profiles);
+        Ok(profiles)
+    }
+
+    pub fn exists(&self, id: &str) -> Result<bool, String> {
+        Ok(self.list()?.iter().any(|profile| profile.id == id))
+    }
+
+    /// Profile opened at launch when none is requested
+    pub fn active(&self) -> Result<String, String> {
+        let active = self.load()?.active.unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        // A hand-edited or stale entry falls back to the default profile
+        Ok(if self.exists(&active)? { active } else { DEFAULT_PROFILE.to_string() })
+    }
+
+    pub fn set_active(&self, id: &str) -> Result<(), String> {
+        if !self.exists(id)? {
+            return Err(format!("Unknown profile: {}", id));
+        }
+        let mut registry = self.load()?;
+        registry.active = Some(id.to_string());
+        self.save(&registry)
+    }
+
+    /// Add a profile; its id is derived from the name
+    pub fn create(&self, name: &str) -> Result<Profile, String> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(format!("Profile name must be 1-{} characters", MAX_NAME_LEN));
+        }
+
+        if self.list()?.iter().any(|profile| profile.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("A profile named {} already exists", name));
+        }
+        let mut registry = self.load()?;
+        let base = slug(name);
+        let taken = |id: &str| id == DEFAULT_PROFILE || registry.profiles.iter().any(|profile| profile.id == id);
+        let id = (1..)
+            .map(|n| if n == 1 { base.clone() } else { format!("{}-{}", base, n) })
+            .find(|id| !taken(id))
+            .unwrap_or(base);
+
+        let profile = Profile {
+            id,
+            name: name.to_string(),
+            created_at: Some(chrono::Utc::now().to_rfc3339()),
+        };
+        std::fs::create_dir_all(self.dir(&profile.id))
+            .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+        registry.profiles.push(profile.clone());
+        self.save(&registry)?;
+        Ok(profile)
+    }
+
+    /// Data directory of a profile
+    pub fn dir(&self, id: &str) -> PathBuf {
+        if id == DEFAULT_PROFILE {
+            self.root.clone()
+        } else {
+            self.root.join("profiles").join(id)
+        }
+    }
+
+    /// Database of a profile (creating its directory)
+    pub fn database_path(&self, id: &str) -> Result<PathBuf, String> {
+        let dir = self.dir(id);
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data directory at {:?}: {}", dir, e))?;
+        Ok(dir.join("owlivion.db"))
+    }
+
+    /// Profile to open: the requested one if given, else the active one
+    pub fn resolve(&self, requested: Option<&str>) -> Result<String, String> {
+        match requested {
+            Some(id) if self.exists(id)? => Ok(id.to_string()),
+            Some(id) => Err(format!("Unknown profile: {}", id)),
+            None => self.active(),
+        }
+    }
+}
+
+/// Cache directory of a profile below the app cache directory
+pub fn cache_dir(app_cache_dir: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE {
+        app_cache_dir.to_path_buf()
+    } else {
+        app_cache_dir.join("profiles").join(id)
+    }
+}
+
+/// Lowercase ASCII id from a profile name
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "profile".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Profile requested on the command line (`--profile <id>`, `--profile=<id>`
+/// or an `owlivion://profile/<id>` link)
+pub fn launch_profile<I: IntoIterator<Item = String>>(args: I) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(id) = arg.strip_prefix("--profile=") {
+            return Some(id.to_string());
+        }
+        if let Some(id) = arg.strip_prefix(PROFILE_URL_PREFIX) {
+            return Some(id.trim_end_matches('/').to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        let root = std::env::temp_dir().join(format!("owlivion-profiles-{}", uuid::Uuid::new_v4()));
+        let profiles = Profiles::new(&root);
+
+        assert_eq!(profiles.active().unwrap(), DEFAULT_PROFILE);
+        let work = profiles.create(" Work ").unwrap();
+        assert_eq!((work.id.as_str(), work.name.as_str()), ("work", "Work"));
+        assert!(profiles.create("work").is_err());
+        assert_eq!(profiles.create("Work!").unwrap().id, "work-2");
+        assert!(profiles.create("default").is_err());
+        assert_eq!(profiles.create("Default Work").unwrap().id, "default-work");
+        assert_eq!(profiles.list().unwrap().len(), 4);
+
+        assert_eq!(profiles.database_path("work").unwrap(), root.join("profiles/work/owlivion.db"));
+        assert_eq!(profiles.database_path(DEFAULT_PROFILE).unwrap(), root.join("owlivion.db"));
+
+        profiles.set_active("work").unwrap();
+        assert!(profiles.set_active("missing").is_err());
+        assert_eq!(profiles.resolve(None).unwrap(), "work");
+        assert_eq!(profiles.resolve(Some(DEFAULT_PROFILE)).unwrap(), DEFAULT_PROFILE);
+        assert!(profiles.resolve(Some("missing")).is_err());
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(launch_profile(args(&["app", "--profile", "work"])), Some("work".to_string()));
+        assert_eq!(launch_profile(args(&["app", "--profile=work"])), Some("work".to_string()));
+        assert_eq!(launch_profile(args(&["app", "owlivion://profile/work/"])), Some("work".to_string()));
+        assert_eq!(launch_profile(args(&["app"])), None);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,251 @@
+//! Refresh Scheduling
+//!
+//! The background poller refreshes each account on its own timer. An account
+//! may have its own interval (say a work account every 2 minutes and an old
+//! hobby account hourly); accounts without one follow the global auto-sync
+//! interval. [`RefreshScheduler`] keeps one timer per enabled account, so
+//! changing one account's interval leaves the others running, and announces
+//! each due refresh as `refresh://due` for the frontend to carry out.
+
+use crate::db::{Database, DbError, DbResult};
+use crate::settings::{self, SettingsEvents};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use tokio::sync::{broadcast, Notify};
+
+/// Tauri event name for an account whose refresh timer fired
+pub const REFRESH_DUE_EVENT: &str = "refresh://due";
+
+/// Shortest refresh interval in minutes
+pub const MIN_INTERVAL_MINUTES: u32 = 1;
+
+/// Longest refresh interval in minutes (one day)
+pub const MAX_INTERVAL_MINUTES: u32 = 24 * 60;
+
+/// Refresh interval of an account that has its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRefresh {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub interval_minutes: u32,
+}
+
+/// Payload of `refresh://due`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshDue {
+    pub account_id: i64,
+}
+
+/// Accounts with their own interval
+pub fn list(db: &Database) -> DbResult<Vec<AccountRefresh>> {
+    db.query(
+        "SELECT account_id, interval_minutes FROM account_refresh ORDER BY account_id",
+        [],
+        |row| {
+            Ok(AccountRefresh {
+                account_id: row.get(0)?,
+                interval_minutes: row.get(1)?,
+            })
+        },
+    )
+}
+
+/// Give an account its own interval, or with None return it to the global one
+pub fn set_interval(db: &Database, account_id: i64, interval_minutes: Option<u32>) -> DbResult<()> {
+    match interval_minutes {
+        Some(minutes) => {
+            if !(MIN_INTERVAL_MINUTES..=MAX_INTERVAL_MINUTES).contains(&minutes) {
+                return Err(DbError::Constraint(format!(
+                    "Refresh interval must be {}-{} minutes",
+                    MIN_INTERVAL_MINUTES, MAX_INTERVAL_MINUTES
+                )));
+            }
+            db.execute(
+                "INSERT INTO account_refresh (account_id, interval_minutes) VALUES (?1, ?2)
+                 ON CONFLICT(account_id) DO UPDATE SET interval_minutes = excluded.interval_minutes",
+                params![account_id, minutes],
+            )?;
+        }
+        None => {
+            db.execute("DELETE FROM account_refresh WHERE account_id = ?1", [account_id])?;
+        }
+    }
+    Ok(())
+}
+
+/// Interval of every account refreshed in the background
+/// Empty while auto-sync is off; disabled accounts are left out.
+pub fn schedule(db: &Database) -> DbResult<Vec<AccountRefresh>> {
+    if !settings::get_bool(db, "auto_sync_enabled") {
+        return Ok(Vec::new());
+    }
+    let global = settings::get_integer(db, "auto_sync_interval") as u32;
+    db.query(
+        "SELECT a.id, r.interval_minutes FROM accounts a
+         LEFT JOIN account_refresh r ON r.account_id = a.id
+         WHERE a.is_active = 1 ORDER BY a.id",
+        [],
+        |row| {
+            Ok(AccountRefresh {
+                account_id: row.get(0)?,
+                interval_minutes: row.get::<_, Option<u32>>(1)?.unwrap_or(global),
+            })
+        },
+    )
+}
+
+/// Per-account refresh timers in the background
+pub struct RefreshScheduler {
+    db: Arc<Database>,
+    wake: Arc<Notify>,
+    started: AtomicBool,
+}
+
+impl RefreshScheduler {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            wake: Arc::new(Notify::new()),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Re-read the schedule after an interval or the set of accounts changed
+    pub fn request(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Start the scheduler; auto-sync setting changes re-read the schedule on their own
+    pub fn start<R: Runtime>(&self, app: AppHandle<R>, settings_events: &SettingsEvents) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let mut setting_changes = settings_events.subscribe();
+        let wake = self.wake.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match setting_changes.recv().await {
+                    Ok(changed)
+                        if !changed.changes.contains_key("autoSyncEnabled")
+                            && !changed.changes.contains_key("autoSyncInterval") => {}
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => wake.notify_one(),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let db = self.db.clone();
+        let wake = self.wake.clone();
+        tauri::async_runtime::spawn(async move {
+            // Running timers by account, with their interval in minutes
+            let mut timers: HashMap<i64, (u32, tauri::async_runtime::JoinHandle<()>)> = HashMap::new();
+            loop {
+                match schedule(&db) {
+                    Ok(wanted) => {
+                        // Only timers whose account or interval changed are restarted
+                        timers.retain(|account_id, (minutes, task)| {
+                            let keep = wanted.iter().any(|r| r.account_id == *account_id && r.interval_minutes == *minutes);
+                            if !keep {
+                                task.abort();
+                            }
+                            keep
+                        });
+                        for refresh in wanted {
+                            if timers.contains_key(&refresh.account_id) {
+                                continue;
+                            }
+                            let task = tauri::async_runtime::spawn(run_timer(app.clone(), refresh));
+                            timers.insert(refresh.account_id, (refresh.interval_minutes, task));
+                        }
+                    }
+                    Err(e) => log::warn!("Failed to load the refresh schedule: {}", e),
+                }
+                wake.notified().await;
+            }
+        });
+    }
+}
+
+/// Announce a refresh of the account every interval (the first one interval from now)
+async fn run_timer<R: Runtime>(app: AppHandle<R>, refresh: AccountRefresh) {
+    let period = Duration::from_secs(u64::from(refresh.interval_minutes) * 60);
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    // After sleep, refresh once rather than catching up on every missed tick
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        crate::events::emit(&app, &RefreshDue { account_id: refresh.account_id });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_new_account, NewAccount};
+    use serde_json::json;
+
+    fn add_account(db: &Database, email: &str) -> i64 {
+        db.add_account(&NewAccount {
+            is_default: false,
+            ..test_new_account(email)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_intervals_per_account() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let work = add_account(&db, "work@test.com");
+        let hobby = add_account(&db, "hobby@test.com");
+        assert!(list(&db).unwrap().is_empty());
+
+        set_interval(&db, work, Some(2)).unwrap();
+        set_interval(&db, hobby, Some(60)).unwrap();
+        set_interval(&db, work, Some(3)).unwrap();
+        assert_eq!(
+            list(&db).unwrap(),
+            vec![
+                AccountRefresh { account_id: work, interval_minutes: 3 },
+                AccountRefresh { account_id: hobby, interval_minutes: 60 },
+            ]
+        );
+
+        assert!(set_interval(&db, work, Some(0)).is_err());
+        assert!(set_interval(&db, work, Some(MAX_INTERVAL_MINUTES + 1)).is_err());
+
+        // Clearing returns the account to the global interval
+        set_interval(&db, work, None).unwrap();
+        assert_eq!(list(&db).unwrap(), vec![AccountRefresh { account_id: hobby, interval_minutes: 60 }]);
+    }
+
+    #[test]
+    fn test_schedule_follows_auto_sync_and_active_accounts() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let work = add_account(&db, "work@test.com");
+        let hobby = add_account(&db, "hobby@test.com");
+        let old = add_account(&db, "old@test.com");
+        set_interval(&db, hobby, Some(60)).unwrap();
+
+        settings::set_many(&db, json!({ "autoSyncEnabled": false }).as_object().unwrap()).unwrap();
+        assert!(schedule(&db).unwrap().is_empty());
+
+        settings::set_many(&db, json!({ "autoSyncEnabled": true, "autoSyncInterval": 10 }).as_object().unwrap()).unwrap();
+        db.set_account_active(old, false).unwrap();
+        assert_eq!(
+            schedule(&db).unwrap(),
+            vec![
+                AccountRefresh { account_id: work, interval_minutes: 10 },
+                AccountRefresh { account_id: hobby, interval_minutes: 60 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Key Rotation
+//!
+//! `crypto_rekey` moves every stored secret to a new installation key, e.g.
+//! after the salt file may have leaked: account passwords and OAuth tokens, the
+//! AI provider API key, the WebDAV upload password and the wrapped keys of
+//! encrypted attachments. All of
+//! it is read and re-encrypted inside one transaction, so a secret the current
+//! key cannot open aborts the rotation with nothing changed. The new salt is
+//! staged before the transaction commits (together with a check value only
+//! the new key opens) and replaces the current salt afterwards; at startup
+//! `resolve_interrupted` finishes or rolls back a rotation cut short between
+//! those steps.
+
+use crate::crypto::{self, NewKey};
+use crate::db::Database;
+use rusqlite::{params, OptionalExtension, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+
+/// Settings key of the check value stored by the latest rotation
+const KEY_CHECK_SETTING: &str = "crypto_key_check";
+
+/// What a rotation re-encrypted
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct RekeyReport {
+    /// Account passwords and OAuth tokens
+    pub account_secrets: usize,
+    /// AI provider API key and WebDAV upload password
+    pub other_secrets: usize,
+    /// Wrapped keys of encrypted attachment files
+    pub file_keys: usize,
+}
+
+/// Rotate the installation key
+pub fn rekey(db: &Database) -> Result<RekeyReport, String> {
+    let key = NewKey::generate()?;
+    key.stage()?;
+    let report = match rekey_with(db, &key) {
+        Ok(report) => report,
+        Err(e) => {
+            crypto::discard_staged_key()?;
+            return Err(e);
+        }
+    };
+    key.activate()
+        .map_err(|e| format!("{} (the rotation completes at the next start)", e))?;
+    log::info!(
+        "Rotated encryption key: {} account secrets, {} other secrets, {} file keys",
+        report.account_secrets,
+        report.other_secrets,
+        report.file_keys
+    );
+    Ok(report)
+}
+
+/// Finish or roll back a rotation interrupted before its salt was activated
+pub fn resolve_interrupted(db: &Database) -> Result<(), String> {
+    let check_value: Option<String> = db
+        .get_setting(KEY_CHECK_SETTING)
+        .map_err(|e| format!("Failed to load key check value: {}", e))?;
+    if crypto::resolve_staged_key(check_value.as_deref())? {
+        log::warn!("Completed an interrupted encryption key rotation");
+    }
+    Ok(())
+}
+
+/// OAuth token columns of older rows hold the token itself; only ciphertext
+/// is re-encrypted
+fn reencrypt_token(key: &NewKey, token: Option<String>, count: &mut usize) -> Option<String> {
+    token.map(|token| match key.reencrypt_secret(&token) {
+        Ok(reencrypted) => {
+            *count += 1;
+            reencrypted
+        }
+        Err(_) => token,
+    })
+}
+
+/// Re-encrypt every stored secret for `key` and store its check value
+fn rekey_with(db: &Database, key: &NewKey) -> Result<RekeyReport, String> {
+    let db_error = |e: rusqlite::Error| format!("Database error: {}", e);
+    let mut conn = db.get_conn().map_err(|e| format!("Database error: {}", e))?;
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate).map_err(db_error)?;
+    let mut report = RekeyReport::default();
+
+    let accounts = {
+        let mut stmt = tx
+            .prepare("SELECT id, email, password_encrypted, oauth_access_token, oauth_refresh_token FROM accounts")
+            .map_err(db_error)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })
+            .map_err(db_error)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(db_error)?
+    };
+    for (id, email, password, access_token, refresh_token) in accounts {
+        let password = password
+            .map(|password| key.reencrypt_secret(&password))
+            .transpose()
+            .map_err(|e| format!("Cannot decrypt the password of {}: {}", email, e))?;
+        report.account_secrets += usize::from(password.is_some());
+        let access_token = reencrypt_token(key, access_token, &mut report.account_secrets);
+        let refresh_token = reencrypt_token(key, refresh_token, &mut report.account_secrets);
+        tx.execute(
+            "UPDATE accounts SET password_encrypted = ?1, oauth_access_token = ?2, oauth_refresh_token = ?3 WHERE id = ?4",
+            params![password, access_token, refresh_token, id],
+        )
+        .map_err(db_error)?;
+    }
+
+    let provider: Option<String> = tx
+        .query_row("SELECT value FROM settings WHERE key = ?1", [crate::ai::PROVIDER_SETTING], |row| row.get(0))
+        .optional()
+        .map_err(db_error)?;
+    if let Some(json) = provider {
+        let mut config: crate::ai::ProviderConfig =
+            serde_json::from_str(&json).map_err(|e| format!("Invalid AI provider setting: {}", e))?;
+        if let Some(api_key) = &config.api_key_encrypted {
+            config.api_key_encrypted = Some(
+                key.reencrypt_secret(api_key)
+                    .map_err(|e| format!("Cannot decrypt the AI provider API key: {}", e))?,
+            );
+            let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+            tx.execute("UPDATE settings SET value = ?1 WHERE key = ?2", params![json, crate::ai::PROVIDER_SETTING])
+                .map_err(db_error)?;
+            report.other_secrets += 1;
+        }
+    }
+
+    let webdav: Option<String> = tx
+        .query_row(
+            "SELECT value FROM settings WHERE key = ?1",
+            [crate::mail::attachment_guard::WEBDAV_SETTING],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(db_error)?;
+    if let Some(json) = webdav {
+        let mut config: crate::mail::attachment_guard::WebDavConfig =
+            serde_json::from_str(&json).map_err(|e| format!("Invalid WebDAV setting: {}", e))?;
+        config.password_encrypted = key
+            .reencrypt_secret(&config.password_encrypted)
+            .map_err(|e| format!("Cannot decrypt the WebDAV password: {}", e))?;
+        let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+        tx.execute(
+            "UPDATE settings SET value = ?1 WHERE key = ?2",
+            params![json, crate::mail::attachment_guard::WEBDAV_SETTING],
+        )
+        .map_err(db_error)?;
+        report.other_secrets += 1;
+    }
+
+    for (table, id_column) in [("attachment_blobs", "sha256"), ("draft_attachments", "id")] {
+        let wrapped_keys = {
+            let mut stmt = tx
+                .prepare(&format!("SELECT {id_column}, wrapped_key FROM {table} WHERE wrapped_key IS NOT NULL"))
+                .map_err(db_error)?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, rusqlite::types::Value>(0)?, row.get::<_, String>(1)?)))
+                .map_err(db_error)?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(db_error)?
+        };
+        for (id, wrapped_key) in wrapped_keys {
+            let rewrapped = key
+                .rewrap_file_key(&wrapped_key)
+                .map_err(|e| format!("Cannot unwrap an attachment key: {}", e))?;
+            tx.execute(
+                &format!("UPDATE {table} SET wrapped_key = ?1 WHERE {id_column} = ?2"),
+                params![rewrapped, id],
+            )
+            .map_err(db_error)?;
+            report.file_keys += 1;
+        }
+    }
+
+    let check_value = serde_json::to_string(&key.check_value()?).map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+        params![KEY_CHECK_SETTING, check_value],
+    )
+    .map_err(db_error)?;
+
+    tx.commit().map_err(db_error)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_new_account, NewAccount};
+
+    fn setup() -> (Database, i64) {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = db
+            .add_account(&NewAccount {
+                password_encrypted: Some(crypto::encrypt_password("secret").unwrap()),
+                oauth_refresh_token: Some("plain-refresh-token".to_string()),
+                ..test_new_account("me@test.com")
+            })
+            .unwrap();
+        (db, account_id)
+    }
+
+    #[test]
+    fn test_rekey_reencrypts_all_secrets() {
+        let (db, account_id) = setup();
+        let config = crate::ai::ProviderConfig {
+            api_key_encrypted: Some(crypto::encrypt_password("sk-test").unwrap()),
+            ..Default::default()
+        };
+        crate::ai::save_provider(&db, &config).unwrap();
+        let webdav = crate::mail::attachment_guard::WebDavConfig {
+            base_url: "https://cloud.test.com/dav".to_string(),
+            username: "me".to_string(),
+            password_encrypted: crypto::encrypt_password("dav-secret").unwrap(),
+            public_base_url: None,
+        };
+        db.set_setting(crate::mail::attachment_guard::WEBDAV_SETTING, &webdav).unwrap();
+        let (_, wrapped) = crypto::encrypt_file(b"attachment").unwrap();
+        db.execute(
+            "INSERT INTO attachment_blobs (sha256, size, wrapped_key) VALUES ('abc', 10, ?1)",
+            params![wrapped],
+        )
+        .unwrap();
+
+        let key = NewKey::generate().unwrap();
+        let report = rekey_with(&db, &key).unwrap();
+        assert_eq!(report, RekeyReport { account_secrets: 1, other_secrets: 2, file_keys: 1 });
+
+        let password = db.get_account_password(account_id).unwrap().unwrap();
+        assert_eq!(key.decrypt_secret(&password).unwrap(), "secret");
+        assert!(crypto::decrypt_password(&password).is_err());
+        let api_key = crate::ai::load_provider(&db).unwrap().api_key_encrypted.unwrap();
+        assert_eq!(key.decrypt_secret(&api_key).unwrap(), "sk-test");
+        let webdav: crate::mail::attachment_guard::WebDavConfig =
+            db.get_setting(crate::mail::attachment_guard::WEBDAV_SETTING).unwrap().unwrap();
+        assert_eq!(key.decrypt_secret(&webdav.password_encrypted).unwrap(), "dav-secret");
+        // Plain-text tokens are left alone
+        assert_eq!(db.get_account(account_id).unwrap().oauth_refresh_token.as_deref(), Some("plain-refresh-token"));
+        let check: String = db.get_setting(KEY_CHECK_SETTING).unwrap().unwrap();
+        assert!(key.decrypt_secret(&check).is_ok());
+    }
+
+    #[test]
+    fn test_undecryptable_secret_aborts_without_changes() {
+        let (db, account_id) = setup();
+        let before = db.get_account_password(account_id).unwrap();
+        let second = db
+            .execute_insert(
+                "INSERT INTO accounts (email, display_name, imap_host, smtp_host, password_encrypted)
+                 VALUES ('other@test.com', 'Other', 'imap.test.com', 'smtp.test.com', 'bm90LWVuY3J5cHRlZA==')",
+                [],
+            )
+            .unwrap();
+
+        let error = rekey_with(&db, &NewKey::generate().unwrap()).unwrap_err();
+        assert!(error.contains("other@test.com"), "{}", error);
+        assert_eq!(db.get_account_password(account_id).unwrap(), before);
+        assert!(db.get_account_password(second).unwrap().is_some());
+        assert_eq!(db.get_setting::<String>(KEY_CHECK_SETTING).unwrap(), None);
+    }
+}
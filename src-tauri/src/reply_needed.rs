@@ -0,0 +1,352 @@
+//! Reply-Needed Detection
+//!
+//! Incoming mail that asks us something is classified as "needs reply": the
+//! message is addressed to us directly (in To, small recipient list) and the
+//! sender's own text contains a question or a request. Newsletters, automated
+//! senders and mailing lists never need a reply. The classification is
+//! stored in `email_ai_metadata`; the user can override it, and set a
+//! reminder that is announced as a `reply://reminder` event when due.
+//!
+//! Messages we answered (`\Answered`) drop out of the "needs reply" view.
+
+use crate::db::{Database, DbResult};
+use crate::digest;
+use crate::mail::precheck::own_text;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Tauri event name emitted when reply reminders are due
+pub const REMINDER_EVENT: &str = "reply://reminder";
+
+/// How often new mail is classified and reminders are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Only mail received in this many days is classified
+const CLASSIFY_DAYS: i64 = 30;
+
+/// Messages classified per pass
+const CLASSIFY_BATCH: i64 = 500;
+
+/// More recipients than this is a broadcast, not a direct request
+const MAX_DIRECT_RECIPIENTS: usize = 5;
+
+/// Phrases asking the reader to act or answer (lowercase)
+const REQUEST_PHRASES: &[&str] = &[
+    // English
+    "let me know",
+    "please confirm",
+    "please reply",
+    "please advise",
+    "please send",
+    "please review",
+    "could you",
+    "can you",
+    "would you",
+    "your thoughts",
+    "get back to me",
+    "awaiting your",
+    "looking forward to your reply",
+    // Turkish
+    "lütfen",
+    "bilgi verir misiniz",
+    "dönüş yapar",
+    "dönüşünüzü",
+    "rica ederim",
+    "misiniz",
+    "mısınız",
+    // German
+    "bitte um rückmeldung",
+    "könnten sie",
+    "kannst du",
+];
+
+/// Why a message needs a reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyReason {
+    Question,
+    Request,
+}
+
+impl ReplyReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplyReason::Question => "question",
+            ReplyReason::Request => "request",
+        }
+    }
+}
+
+/// Message as seen by the classifier
+#[derive(Debug, Clone, Default)]
+pub struct Candidate {
+    pub from_address: String,
+    pub to_addresses: String,
+    pub cc_addresses: String,
+    pub subject: String,
+    pub body_text: Option<String>,
+    pub body_html: Option<String>,
+    pub labels: String,
+    pub raw_headers: Option<String>,
+}
+
+/// Addresses of a JSON address list (plain strings or {"email", "name"} objects)
+fn addresses(json: &str) -> Vec<String> {
+    serde_json::from_str::<Vec<serde_json::Value>>(json)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|value| value.as_str().or_else(|| value.get("email").and_then(|email| email.as_str())))
+        .map(|address| address.trim().to_lowercase())
+        .collect()
+}
+
+/// Mailing list or auto-generated message
+pub(crate) fn is_automated(raw_headers: Option<&str>) -> bool {
+    let Some(headers) = raw_headers else { return false };
+    let headers = headers.to_lowercase();
+    headers.contains("list-id")
+        || headers.contains("list-unsubscribe")
+        || headers.contains("auto-submitted\":\"auto")
+        || headers.contains("auto-submitted: auto")
+        || headers.contains("precedence\":\"bulk")
+        || headers.contains("precedence: bulk")
+}
+
+/// Whether a message needs a reply from one of `own_addresses` (lowercase)
+pub fn classify(email: &Candidate, own_addresses: &HashSet<String>) -> Option<ReplyReason> {
+    let from = email.from_address.trim().to_lowercase();
+    if own_addresses.contains(&from)
+        || digest::categorize(&from, &email.subject, &email.labels).is_some()
+        || is_automated(email.raw_headers.as_deref())
+    {
+        return None;
+    }
+
+    let to = addresses(&email.to_addresses);
+    let recipients = to.len() + addresses(&email.cc_addresses).len();
+    if !to.iter().any(|address| own_addresses.contains(address)) || recipients > MAX_DIRECT_RECIPIENTS {
+        return None;
+    }
+
+    let text = format!(
+        "{}\n{}",
+        email.subject,
+        own_text(email.body_text.as_deref(), email.body_html.as_deref())
+    )
+    .to_lowercase();
+    if text.contains(['?', '？', '؟']) {
+        Some(ReplyReason::Question)
+    } else if REQUEST_PHRASES.iter().any(|phrase| text.contains(phrase)) {
+        Some(ReplyReason::Request)
+    } else {
+        None
+    }
+}
+
+/// Classify recent inbox mail that has no metadata yet
+/// Returns the number of messages classified.
+pub fn classify_pending(db: &Database) -> DbResult<usize> {
+    let own_addresses: HashSet<String> =
+        db.query("SELECT lower(email) FROM accounts", [], |row| row.get(0))?.into_iter().collect();
+
+    let candidates = db.query(
+        "SELECT e.id, e.from_address, e.to_addresses, e.cc_addresses, e.subject, e.body_text, e.body_html,
+                e.labels, e.raw_headers
+         FROM emails e
+         JOIN folders f ON f.id = e.folder_id
+         LEFT JOIN email_ai_metadata m ON m.email_id = e.id
+         WHERE m.email_id IS NULL AND f.folder_type = 'inbox'
+           AND e.is_deleted = 0 AND e.is_spam = 0 AND e.is_draft = 0
+           AND e.received_at > datetime('now', ?1)
+         ORDER BY e.id DESC
+         LIMIT ?2",
+        params![format!("-{} days", CLASSIFY_DAYS), CLASSIFY_BATCH],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                Candidate {
+                    from_address: row.get(1)?,
+                    to_addresses: row.get(2)?,
+                    cc_addresses: row.get(3)?,
+                    subject: row.get(4)?,
+                    body_text: row.get(5)?,
+                    body_html: row.get(6)?,
+                    labels: row.get(7)?,
+                    raw_headers: row.get(8)?,
+                },
+            ))
+        },
+    )?;
+
+    for (email_id, candidate) in &candidates {
+        let reason = classify(candidate, &own_addresses);
+        db.execute(
+            "INSERT OR IGNORE INTO email_ai_metadata (email_id, needs_reply, needs_reply_reason) VALUES (?1, ?2, ?3)",
+            params![email_id, reason.is_some(), reason.map(|reason| reason.as_str())],
+        )?;
+    }
+    Ok(candidates.len())
+}
+
+/// Override the classification of a message (None = back to automatic)
+pub fn set_override(db: &Database, email_id: i64, needs_reply: Option<bool>) -> DbResult<()> {
+    db.execute(
+        "INSERT INTO email_ai_metadata (email_id, needs_reply_override) VALUES (?1, ?2)
+         ON CONFLICT(email_id) DO UPDATE SET needs_reply_override = excluded.needs_reply_override",
+        params![email_id, needs_reply],
+    )?;
+    Ok(())
+}
+
+/// Remind about a message at `remind_at` (None clears the reminder)
+pub fn set_reminder(db: &Database, email_id: i64, remind_at: Option<DateTime<Utc>>) -> DbResult<()> {
+    db.execute(
+        "INSERT INTO email_ai_metadata (email_id, reply_remind_at) VALUES (?1, ?2)
+         ON CONFLICT(email_id) DO UPDATE SET reply_remind_at = excluded.reply_remind_at",
+        params![email_id, remind_at.map(|at| at.to_rfc3339())],
+    )?;
+    Ok(())
+}
+
+/// Due reminder (payload item of `reply://reminder`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyReminder {
+    pub email_id: i64,
+    pub account_id: i64,
+    pub from: String,
+    pub subject: String,
+}
+
+/// Reminders due at `now` for messages still unanswered; each fires once
+pub fn take_due_reminders(db: &Database, now: DateTime<Utc>) -> DbResult<Vec<ReplyReminder>> {
+    let due = db.query(
+        "SELECT m.email_id, m.reply_remind_at, e.account_id, e.from_address, e.subject, e.is_answered
+         FROM email_ai_metadata m
+         JOIN emails e ON e.id = m.email_id
+         WHERE m.reply_remind_at IS NOT NULL",
+        [],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                ReplyReminder {
+                    email_id: row.get(0)?,
+                    account_id: row.get(2)?,
+                    from: row.get(3)?,
+                    subject: row.get(4)?,
+                },
+                row.get::<_, bool>(5)?,
+            ))
+        },
+    )?;
+
+    let mut reminders = Vec::new();
+    for (email_id, remind_at, reminder, answered) in due {
+        if crate::db::parse_db_timestamp(&remind_at).is_some_and(|at| at > now) {
+            continue;
+        }
+        db.execute("UPDATE email_ai_metadata SET reply_remind_at = NULL WHERE email_id = ?1", [email_id])?;
+        if !answered {
+            reminders.push(reminder);
+        }
+    }
+    Ok(reminders)
+}
+
+/// Background classification and reminder loop
+pub struct ReplyTracker {
+    db: Arc<Database>,
+    started: AtomicBool,
+}
+
+impl ReplyTracker {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Start the tracker (only the first call has an effect)
+    /// `on_reminders` receives reminders that became due.
+    pub fn start<F>(&self, on_reminders: F)
+    where
+        F: Fn(Vec<ReplyReminder>) + Send + Sync + 'static,
+    {
+        if self.started.swap(true, Ordering::SeqCst) {
+            log::warn!("Reply tracker already started");
+            return;
+        }
+        let db = self.db.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = classify_pending(&db) {
+                    log::error!("Failed to classify new mail: {}", e);
+                }
+                match take_due_reminders(&db, Utc::now()) {
+                    Ok(reminders) if !reminders.is_empty() => on_reminders(reminders),
+                    Ok(_) => {}
+                    Err(e) => log::error!("Failed to check reply reminders: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn own() -> HashSet<String> {
+        ["me@example.com".to_string()].into_iter().collect()
+    }
+
+    fn email(to: &str, subject: &str, body: &str) -> Candidate {
+        Candidate {
+            from_address: "ana@partner.test".to_string(),
+            to_addresses: to.to_string(),
+            cc_addresses: "[]".to_string(),
+            subject: subject.to_string(),
+            body_text: Some(body.to_string()),
+            labels: "[]".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let direct = r#"[{"email":"Me@Example.com","name":"Me"}]"#;
+        assert_eq!(classify(&email(direct, "Budget", "Is Friday ok for you?"), &own()), Some(ReplyReason::Question));
+        assert_eq!(classify(&email(direct, "Budget", "Please confirm the numbers."), &own()), Some(ReplyReason::Request));
+        assert_eq!(classify(&email(direct, "Budget", "FYI, numbers attached."), &own()), None);
+
+        // Question only in the quoted part
+        let quoted = "Sounds good.\n\nOn Mon, Jan 6, Me wrote:\n> Is Friday ok?";
+        assert_eq!(classify(&email(direct, "Re: Budget", quoted), &own()), None);
+
+        // CC only, or a broadcast
+        let mut cc = email(r#"["boss@partner.test"]"#, "Budget", "Any questions?");
+        cc.cc_addresses = r#"["me@example.com"]"#.to_string();
+        assert_eq!(classify(&cc, &own()), None);
+        let many = r#"["me@example.com","a@x.test","b@x.test","c@x.test","d@x.test","e@x.test"]"#;
+        assert_eq!(classify(&email(many, "All hands", "Questions?"), &own()), None);
+
+        // Automated senders and lists
+        let mut newsletter = email(direct, "Weekly", "What's new?");
+        newsletter.from_address = "newsletter@shop.test".to_string();
+        assert_eq!(classify(&newsletter, &own()), None);
+        let mut list = email(direct, "Thread", "Can anyone help?");
+        list.raw_headers = Some(r#"{"List-Id":"<dev.lists.test>"}"#.to_string());
+        assert_eq!(classify(&list, &own()), None);
+    }
+}
@@ -0,0 +1,308 @@
+//! Search Index Maintenance
+//!
+//! `emails_fts` and `templates_fts` are external-content FTS5 tables kept in
+//! sync by triggers. A crash mid-write, rows written while a trigger was
+//! missing or an UPDATE that bypassed them leave the index out of step with
+//! the content, and search silently returns stale or missing hits.
+//!
+//! The maintainer checks both indexes at startup and daily (row counts plus
+//! FTS5's own `integrity-check` against the content table), rebuilds an index
+//! that drifted, and merges/optimizes index segments so queries stay fast.
+
+use crate::db::{Database, DbResult};
+use rusqlite::{params, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Event emitted when an index had to be rebuilt
+pub const REPAIRED_EVENT: &str = "search-index://repaired";
+
+/// Time between incremental merges
+const MERGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Every Nth merge tick (and the first) also checks and optimizes
+const FULL_CHECK_EVERY: u64 = 4;
+
+/// Pages of work per incremental merge
+const MERGE_PAGES: i64 = 500;
+
+/// FTS index and its content table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum SearchIndex {
+    Emails,
+    Templates,
+}
+
+impl SearchIndex {
+    pub const ALL: [SearchIndex; 2] = [SearchIndex::Emails, SearchIndex::Templates];
+
+    fn table(self) -> &'static str {
+        match self {
+            SearchIndex::Emails => "emails_fts",
+            SearchIndex::Templates => "templates_fts",
+        }
+    }
+
+    fn content(self) -> &'static str {
+        match self {
+            SearchIndex::Emails => "emails",
+            SearchIndex::Templates => "email_templates",
+        }
+    }
+}
+
+/// State of one index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "SearchIndexStatus"))]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStatus {
+    pub index: SearchIndex,
+    /// Content rows (of the account, when the check is scoped to one)
+    #[cfg_attr(test, ts(type = "number"))]
+    pub rows: i64,
+    /// Content rows with no index entry
+    #[cfg_attr(test, ts(type = "number"))]
+    pub missing: i64,
+    /// Index entries whose row no longer exists
+    #[cfg_attr(test, ts(type = "number"))]
+    pub orphaned: i64,
+    /// Indexed terms match the content (FTS5 integrity-check)
+    pub terms_match: bool,
+    /// Rebuilt by this run
+    pub rebuilt: bool,
+}
+
+impl IndexStatus {
+    pub fn is_consistent(&self) -> bool {
+        self.missing == 0 && self.orphaned == 0 && self.terms_match
+    }
+}
+
+/// Compare an index with its content table
+/// `account_id` limits the row counts to one account; orphaned entries have
+/// no row left to tell their account, so they are always counted globally.
+pub fn check(db: &Database, index: SearchIndex, account_id: Option<i64>) -> DbResult<IndexStatus> {
+    let (table, content) = (index.table(), index.content());
+
+    let (rows, missing): (i64, i64) = db.query_row(
+        &format!(
+            "SELECT COUNT(*), COALESCE(SUM(d.id IS NULL), 0)
+             FROM {content} c LEFT JOIN {table}_docsize d ON d.id = c.id
+             WHERE ?1 IS NULL OR c.account_id = ?1"
+        ),
+        params![account_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let orphaned: i64 = db.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM {table}_docsize d
+             WHERE NOT EXISTS (SELECT 1 FROM {content} c WHERE c.id = d.id)"
+        ),
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(IndexStatus {
+        index,
+        rows,
+        missing,
+        orphaned,
+        terms_match: terms_match(db, index)?,
+        rebuilt: false,
+    })
+}
+
+/// FTS5 integrity-check; rank 1 also compares the index with the content table
+fn terms_match(db: &Database, index: SearchIndex) -> DbResult<bool> {
+    let table = index.table();
+    match db.execute(&format!("INSERT INTO {table}({table}, rank) VALUES('integrity-check', 1)"), []) {
+        Ok(_) => Ok(true),
+        Err(crate::db::DbError::Sqlite(rusqlite::Error::SqliteFailure(e, _))) if e.code == ErrorCode::DatabaseCorrupt => {
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Rebuild an index from its content table
+pub fn rebuild(db: &Database, index: SearchIndex) -> DbResult<()> {
+    let table = index.table();
+    db.execute(&format!("INSERT INTO {table}({table}) VALUES('rebuild')"), [])?;
+    Ok(())
+}
+
+/// Merge index segments incrementally (cheap, bounded work)
+pub fn merge(db: &Database, index: SearchIndex) -> DbResult<()> {
+    let table = index.table();
+    db.execute(&format!("INSERT INTO {table}({table}, rank) VALUES('merge', ?1)"), params![MERGE_PAGES])?;
+    Ok(())
+}
+
+/// Merge all segments into one
+pub fn optimize(db: &Database, index: SearchIndex) -> DbResult<()> {
+    let table = index.table();
+    db.execute(&format!("INSERT INTO {table}({table}) VALUES('optimize')"), [])?;
+    Ok(())
+}
+
+/// Check an index and rebuild it when it drifted from its content
+pub fn repair(db: &Database, index: SearchIndex, account_id: Option<i64>) -> DbResult<IndexStatus> {
+    let status = check(db, index, account_id)?;
+    if status.is_consistent() {
+        return Ok(status);
+    }
+
+    log::warn!(
+        "Search index {} drifted ({} missing, {} orphaned, terms match: {}), rebuilding",
+        index.table(),
+        status.missing,
+        status.orphaned,
+        status.terms_match
+    );
+    rebuild(db, index)?;
+    Ok(IndexStatus { rebuilt: true, ..check(db, index, account_id)? })
+}
+
+/// Rebuild the search indexes, reporting the state of `account_id`'s rows
+/// FTS5 rebuilds an external-content index from the whole content table, so
+/// the rebuild itself covers every account.
+pub fn rebuild_all(db: &Database, account_id: Option<i64>) -> DbResult<Vec<IndexStatus>> {
+    SearchIndex::ALL
+        .into_iter()
+        .map(|index| {
+            rebuild(db, index)?;
+            optimize(db, index)?;
+            Ok(IndexStatus { rebuilt: true, ..check(db, index, account_id)? })
+        })
+        .collect()
+}
+
+/// Periodic index check, repair and segment merging
+pub struct SearchIndexMaintainer {
+    db: Arc<Database>,
+    started: AtomicBool,
+}
+
+impl SearchIndexMaintainer {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Start the maintainer (only the first call has an effect)
+    /// `on_repaired` receives the indexes that had to be rebuilt.
+    pub fn start<F>(&self, on_repaired: F)
+    where
+        F: Fn(Vec<IndexStatus>) + Send + Sync + 'static,
+    {
+        if self.started.swap(true, Ordering::SeqCst) {
+            log::warn!("Search index maintainer already started");
+            return;
+        }
+        let db = self.db.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(MERGE_INTERVAL);
+            let mut ticks: u64 = 0;
+            loop {
+                interval.tick().await;
+                let full = ticks.is_multiple_of(FULL_CHECK_EVERY);
+                ticks += 1;
+
+                let mut repaired = Vec::new();
+                for index in SearchIndex::ALL {
+                    let result = if full {
+                        repair(&db, index, None).and_then(|status| {
+                            optimize(&db, index)?;
+                            if status.rebuilt {
+                                repaired.push(status);
+                            }
+                            Ok(())
+                        })
+                    } else {
+                        merge(&db, index)
+                    };
+                    if let Err(e) = result {
+                        log::error!("Search index maintenance failed for {}: {}", index.table(), e);
+                    }
+                }
+                if !repaired.is_empty() {
+                    on_repaired(repaired);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    fn setup() -> (Database, i64) {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = crate::sync_folder_to_db(&db, account_id, "INBOX").unwrap();
+        for (uid, subject) in [(1, "Quarterly invoice"), (2, "Lunch plans")] {
+            db.execute(
+                "INSERT INTO emails (account_id, folder_id, message_id, uid, from_address, subject, body_text, date)
+                 VALUES (?1, ?2, ?3, ?4, 'ana@example.com', ?5, 'Body', '2025-01-01T10:00:00Z')",
+                params![account_id, folder_id, format!("<{}@test.com>", uid), uid, subject],
+            )
+            .unwrap();
+        }
+        (db, account_id)
+    }
+
+    fn hits(db: &Database, term: &str) -> i64 {
+        db.query_row("SELECT COUNT(*) FROM emails_fts WHERE emails_fts MATCH ?1", params![term], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_consistent_index() {
+        let (db, account_id) = setup();
+        let status = check(&db, SearchIndex::Emails, Some(account_id)).unwrap();
+        assert_eq!((status.rows, status.missing, status.orphaned), (2, 0, 0));
+        assert!(status.is_consistent());
+        assert!(check(&db, SearchIndex::Templates, None).unwrap().is_consistent());
+        assert_eq!(check(&db, SearchIndex::Emails, Some(account_id + 1)).unwrap().rows, 0);
+    }
+
+    #[test]
+    fn test_repair_after_writes_outside_triggers() {
+        let (db, account_id) = setup();
+        db.execute_batch(
+            "DROP TRIGGER emails_au; DROP TRIGGER emails_ai;
+             UPDATE emails SET subject = 'Renamed report' WHERE uid = 1;",
+        )
+        .unwrap();
+        db.execute(
+            "INSERT INTO emails (account_id, folder_id, message_id, uid, from_address, subject, date)
+             SELECT account_id, folder_id, '<3@test.com>', 3, 'bob@example.com', 'Unindexed', date FROM emails WHERE uid = 2",
+            [],
+        )
+        .unwrap();
+
+        let status = check(&db, SearchIndex::Emails, Some(account_id)).unwrap();
+        assert_eq!(status.missing, 1);
+        assert!(!status.terms_match);
+        assert_eq!(hits(&db, "renamed"), 0);
+
+        let repaired = repair(&db, SearchIndex::Emails, Some(account_id)).unwrap();
+        assert!(repaired.rebuilt && repaired.is_consistent());
+        assert_eq!(hits(&db, "renamed"), 1);
+        assert_eq!(hits(&db, "unindexed"), 1);
+        assert_eq!(hits(&db, "quarterly"), 0);
+
+        // Already consistent: nothing to do
+        assert!(!repair(&db, SearchIndex::Emails, None).unwrap().rebuilt);
+        merge(&db, SearchIndex::Emails).unwrap();
+    }
+}
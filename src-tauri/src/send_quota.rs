@@ -0,0 +1,279 @@
+//! Send Quotas
+//!
+//! Providers block accounts that send too much: Gmail allows about 500
+//! recipients per rolling day, Outlook.com 300, and most throttle bursts.
+//! Every delivered message is logged with its recipient count, and a send is
+//! admitted only while the account stays below its provider's per-minute and
+//! per-day limits (or limits set for the account). A full minute window makes
+//! the sender wait; a used-up day makes it fail with `QuotaExceeded`, which
+//! queues the message (or pauses a mail merge) until the quota frees up.
+
+use crate::db::{Database, DbResult};
+use crate::mail::attachment_guard;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Recipients per minute when the provider is unknown
+const DEFAULT_PER_MINUTE: u32 = 30;
+
+/// Known provider limits: (provider, recipients per minute, recipients per day)
+/// Provider names as resolved by `attachment_guard::provider_limit`.
+const PROVIDER_QUOTAS: &[(&str, u32, u32)] = &[
+    ("gmail", 20, 500),
+    ("outlook", 30, 300),
+    ("office365", 30, 10_000),
+    ("yahoo", 20, 500),
+    ("icloud", 20, 1000),
+    ("yandex", 20, 500),
+    ("zoho", 20, 500),
+    ("gmx", 20, 500),
+    ("protonmail", 20, 1000),
+];
+
+/// Stored format of `send_log.sent_at` (SQLite's datetime())
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Sending limits of an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "SendQuotaLimits"))]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaLimits {
+    pub per_minute: u32,
+    /// None when the provider has no known daily limit
+    pub per_day: Option<u32>,
+}
+
+impl QuotaLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.per_minute == 0 || self.per_minute > 1000 {
+            return Err("Per-minute limit must be 1-1000".to_string());
+        }
+        if self.per_day.is_some_and(|per_day| per_day == 0) {
+            return Err("Daily limit must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Limits for an account's provider, matched by SMTP host and address
+pub fn provider_limits(smtp_host: &str, email: &str) -> (String, QuotaLimits) {
+    let provider = attachment_guard::provider_limit(smtp_host, email).provider;
+    let limits = PROVIDER_QUOTAS
+        .iter()
+        .find(|(name, _, _)| *name == provider)
+        .map_or(QuotaLimits { per_minute: DEFAULT_PER_MINUTE, per_day: None }, |&(_, per_minute, per_day)| {
+            QuotaLimits { per_minute, per_day: Some(per_day) }
+        });
+    (provider, limits)
+}
+
+/// Limits set for an account, replacing the provider's
+pub fn custom_limits(db: &Database, account_id: i64) -> DbResult<Option<QuotaLimits>> {
+    Ok(db
+        .get_conn()?
+        .query_row(
+            "SELECT per_minute, per_day FROM send_quota_limits WHERE account_id = ?1",
+            [account_id],
+            |row| Ok(QuotaLimits { per_minute: row.get(0)?, per_day: row.get(1)? }),
+        )
+        .optional()?)
+}
+
+/// Set (or with `None` remove) an account's own limits
+pub fn set_custom_limits(db: &Database, account_id: i64, limits: Option<QuotaLimits>) -> DbResult<()> {
+    match limits {
+        Some(limits) => db.execute(
+            "INSERT INTO send_quota_limits (account_id, per_minute, per_day) VALUES (?1, ?2, ?3)
+             ON CONFLICT(account_id) DO UPDATE SET per_minute = excluded.per_minute, per_day = excluded.per_day",
+            params![account_id, limits.per_minute, limits.per_day],
+        )?,
+        None => db.execute("DELETE FROM send_quota_limits WHERE account_id = ?1", [account_id])?,
+    };
+    Ok(())
+}
+
+/// Whether a send may go out now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    Now,
+    /// The minute window is full; try again after this long
+    Wait(std::time::Duration),
+    /// The daily quota is used up until this time
+    DailyLimit(DateTime<Utc>),
+    /// More recipients than the daily quota allows in one message
+    TooManyRecipients(u32),
+}
+
+/// Sending quota of an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "SendQuotaStatus"))]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaStatus {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub provider: String,
+    pub limits: QuotaLimits,
+    /// Whether the limits were set for the account
+    pub custom: bool,
+    pub sent_last_minute: u32,
+    /// Recipients in the last 24 hours
+    pub sent_last_day: u32,
+    pub remaining_today: Option<u32>,
+    /// When the oldest send of the day window drops out (while any were sent)
+    pub next_release_at: Option<DateTime<Utc>>,
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Sends of the last 24 hours before `now`, oldest first
+fn recent_sends(db: &Database, account_id: i64, now: DateTime<Utc>) -> DbResult<Vec<(DateTime<Utc>, u32)>> {
+    let rows = db.query(
+        "SELECT sent_at, recipients FROM send_log WHERE account_id = ?1 AND sent_at > ?2 ORDER BY sent_at",
+        params![account_id, format_time(now - Duration::days(1))],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)),
+    )?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(sent_at, recipients)| {
+            NaiveDateTime::parse_from_str(&sent_at, TIMESTAMP_FORMAT)
+                .ok()
+                .map(|time| (time.and_utc(), recipients))
+        })
+        .collect())
+}
+
+/// Limits in effect for an account
+pub fn limits(db: &Database, account_id: i64, smtp_host: &str, email: &str) -> DbResult<(String, QuotaLimits, bool)> {
+    let (provider, provider_limits) = provider_limits(smtp_host, email);
+    Ok(match custom_limits(db, account_id)? {
+        Some(custom) => (provider, custom, true),
+        None => (provider, provider_limits, false),
+    })
+}
+
+/// Decide whether a message to `recipients` recipients may go out at `now`
+pub fn admit(
+    db: &Database,
+    account_id: i64,
+    limits: &QuotaLimits,
+    recipients: u32,
+    now: DateTime<Utc>,
+) -> DbResult<Admission> {
+    let sends = recent_sends(db, account_id, now)?;
+
+    if let Some(per_day) = limits.per_day {
+        if recipients > per_day {
+            return Ok(Admission::TooManyRecipients(per_day));
+        }
+        let used: u32 = sends.iter().map(|(_, count)| count).sum();
+        if used + recipients > per_day {
+            // Wait until enough of the oldest sends leave the 24-hour window
+            let mut freed = 0;
+            for (sent_at, count) in &sends {
+                freed += count;
+                if used - freed + recipients <= per_day {
+                    return Ok(Admission::DailyLimit(*sent_at + Duration::days(1)));
+                }
+            }
+        }
+    }
+
+    let minute_ago = now - Duration::minutes(1);
+    let in_minute: Vec<&(DateTime<Utc>, u32)> = sends.iter().filter(|(sent_at, _)| *sent_at > minute_ago).collect();
+    let used: u32 = in_minute.iter().map(|(_, count)| count).sum();
+    // A message larger than the minute limit goes out alone
+    if used > 0 && used + recipients > limits.per_minute {
+        let mut freed = 0;
+        for (sent_at, count) in &in_minute {
+            freed += count;
+            if used - freed + recipients <= limits.per_minute || freed == used {
+                let wait = (*sent_at + Duration::minutes(1) - now).to_std().unwrap_or_default();
+                return Ok(Admission::Wait(wait + std::time::Duration::from_secs(1)));
+            }
+        }
+    }
+    Ok(Admission::Now)
+}
+
+/// Log a delivered message and drop entries older than the day window
+pub fn record(db: &Database, account_id: i64, recipients: u32, now: DateTime<Utc>) -> DbResult<()> {
+    db.execute(
+        "INSERT INTO send_log (account_id, recipients, sent_at) VALUES (?1, ?2, ?3)",
+        params![account_id, recipients, format_time(now)],
+    )?;
+    db.execute(
+        "DELETE FROM send_log WHERE sent_at <= ?1",
+        [format_time(now - Duration::days(1))],
+    )?;
+    Ok(())
+}
+
+pub fn status(db: &Database, account_id: i64, smtp_host: &str, email: &str, now: DateTime<Utc>) -> DbResult<QuotaStatus> {
+    let (provider, limits, custom) = limits(db, account_id, smtp_host, email)?;
+    let sends = recent_sends(db, account_id, now)?;
+    let sent_last_day: u32 = sends.iter().map(|(_, count)| count).sum();
+    let minute_ago = now - Duration::minutes(1);
+    let sent_last_minute = sends.iter().filter(|(sent_at, _)| *sent_at > minute_ago).map(|(_, count)| count).sum();
+
+    Ok(QuotaStatus {
+        account_id,
+        provider,
+        limits,
+        custom,
+        sent_last_minute,
+        sent_last_day,
+        remaining_today: limits.per_day.map(|per_day| per_day.saturating_sub(sent_last_day)),
+        next_release_at: sends.first().map(|(sent_at, _)| *sent_at + Duration::days(1)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_new_account, NewAccount};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_admission() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = db
+            .add_account(&NewAccount {
+                imap_host: "imap.gmail.com".to_string(),
+                smtp_host: "smtp.gmail.com".to_string(),
+                smtp_port: 465,
+                smtp_security: "SSL".to_string(),
+                ..test_new_account("me@gmail.com")
+            })
+            .unwrap();
+
+        let (provider, gmail) = provider_limits("smtp.gmail.com", "me@gmail.com");
+        assert_eq!((provider.as_str(), gmail.per_day), ("gmail", Some(500)));
+        assert_eq!(provider_limits("mail.example.org", "me@example.org").1.per_day, None);
+
+        let limits = QuotaLimits { per_minute: 10, per_day: Some(20) };
+        let start = Utc.with_ymd_and_hms(2025, 1, 6, 10, 0, 0).unwrap();
+        assert_eq!(admit(&db, account_id, &limits, 25, start).unwrap(), Admission::TooManyRecipients(20));
+
+        record(&db, account_id, 8, start).unwrap();
+        let now = start + Duration::seconds(20);
+        assert_eq!(admit(&db, account_id, &limits, 2, now).unwrap(), Admission::Now);
+        assert_eq!(admit(&db, account_id, &limits, 3, now).unwrap(), Admission::Wait(std::time::Duration::from_secs(41)));
+
+        record(&db, account_id, 10, start + Duration::hours(1)).unwrap();
+        let later = start + Duration::hours(2);
+        assert_eq!(
+            admit(&db, account_id, &limits, 5, later).unwrap(),
+            Admission::DailyLimit(start + Duration::days(1))
+        );
+
+        let status = status(&db, account_id, "smtp.gmail.com", "me@gmail.com", later).unwrap();
+        assert_eq!((status.sent_last_day, status.custom), (18, false));
+        set_custom_limits(&db, account_id, Some(limits)).unwrap();
+        assert_eq!(custom_limits(&db, account_id).unwrap(), Some(limits));
+        set_custom_limits(&db, account_id, None).unwrap();
+        assert_eq!(custom_limits(&db, account_id).unwrap(), None);
+    }
+}
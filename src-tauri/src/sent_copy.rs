@@ -0,0 +1,128 @@
+//! Sent Copies
+//!
+//! After a message goes out over SMTP, a copy can be uploaded (IMAP APPEND)
+//! to the account's Sent folder so other clients see it too. This is off by
+//! default: many providers (Gmail, Outlook.com) file submitted mail
+//! themselves and a second copy would be a duplicate. OAuth accounts are
+//! never uploaded for the same reason.
+//!
+//! A copy larger than the server's APPENDLIMIT is skipped rather than failing
+//! the send; the skip is recorded as a warning (`sent_copy_warnings`).
+
+use crate::db::{Database, DbResult};
+use crate::mail::{AppendOutcome, AsyncImapClient, MailResult};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Settings key: upload sent copies (bool)
+const UPLOAD_SETTING: &str = "sent_copy_upload";
+
+/// Settings key holding the most recent skipped uploads
+const WARNINGS_SETTING: &str = "sent_copy_warnings";
+
+/// Skipped uploads kept
+const MAX_WARNINGS: usize = 50;
+
+/// Sent copy that was not uploaded because the server would refuse it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SentCopyWarning {
+    #[cfg_attr(test, ts(type = "number"))]
+    pub account_id: i64,
+    pub folder: String,
+    pub subject: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub size: u64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub limit: u64,
+    pub recorded_at: String,
+}
+
+pub fn upload_enabled(db: &Database) -> DbResult<bool> {
+    Ok(db.get_setting(UPLOAD_SETTING)?.unwrap_or(false))
+}
+
+pub fn set_upload_enabled(db: &Database, enabled: bool) -> DbResult<()> {
+    db.set_setting(UPLOAD_SETTING, &enabled)
+}
+
+/// Skipped uploads, newest first (optionally of one account)
+pub fn warnings(db: &Database, account_id: Option<i64>) -> DbResult<Vec<SentCopyWarning>> {
+    let warnings: Vec<SentCopyWarning> = db.get_setting(WARNINGS_SETTING)?.unwrap_or_default();
+    Ok(warnings
+        .into_iter()
+        .filter(|warning| account_id.is_none_or(|id| warning.account_id == id))
+        .collect())
+}
+
+fn record_warning(db: &Database, warning: SentCopyWarning) -> DbResult<()> {
+    let mut warnings = warnings(db, None)?;
+    warnings.insert(0, warning);
+    warnings.truncate(MAX_WARNINGS);
+    db.set_setting(WARNINGS_SETTING, &warnings)
+}
+
+/// Upload a sent message to `folder`, marked as read
+/// A copy over the APPENDLIMIT is skipped and recorded as a warning.
+pub async fn upload(
+    db: &Database,
+    client: &mut AsyncImapClient,
+    account_id: i64,
+    folder: &str,
+    subject: &str,
+    raw: &[u8],
+) -> MailResult<AppendOutcome> {
+    let outcome = client.append(folder, raw, "\\Seen").await?;
+
+    if let AppendOutcome::TooLarge { size, limit } = outcome {
+        log::warn!(
+            "Sent copy for account {} not saved: {} bytes exceeds the server's APPENDLIMIT of {} bytes",
+            account_id,
+            size,
+            limit
+        );
+        let warning = SentCopyWarning {
+            account_id,
+            folder: folder.to_string(),
+            subject: subject.to_string(),
+            size,
+            limit,
+            recorded_at: Utc::now().to_rfc3339(),
+        };
+        if let Err(e) = record_warning(db, warning) {
+            log::warn!("Failed to record sent copy warning: {}", e);
+        }
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{sample_message, MockImapServer};
+
+    #[tokio::test]
+    async fn test_copy_over_append_limit_is_skipped_and_recorded() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        assert!(!upload_enabled(&db).unwrap());
+
+        let server = MockImapServer::start("alice@mock.test", "secret").await.unwrap();
+        server.set_capabilities("IMAP4rev1 UIDPLUS APPENDLIMIT=64");
+        server.add_mailbox("Sent", &["\\Sent"]);
+        let mut client = AsyncImapClient::new(server.config());
+        client.connect().await.unwrap();
+        assert_eq!(client.capabilities().and_then(|caps| caps.append_limit), Some(64));
+
+        let raw = sample_message("alice@mock.test", "bob@mock.test", "Report", "Numbers");
+        let outcome = upload(&db, &mut client, 7, "Sent", "Report", &raw).await.unwrap();
+        assert_eq!(outcome, AppendOutcome::TooLarge { size: raw.len() as u64, limit: 64 });
+        assert!(!server.commands().iter().any(|command| command.starts_with("APPEND")));
+        assert!(server.messages("Sent").is_empty());
+
+        let recorded = warnings(&db, Some(7)).unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!((recorded[0].subject.as_str(), recorded[0].limit), ("Report", 64));
+        assert!(warnings(&db, Some(8)).unwrap().is_empty());
+    }
+}
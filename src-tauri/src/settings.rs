@@ -0,0 +1,320 @@
+//! Settings Registry
+//!
+//! Preferences are stored as JSON values in the `settings` table. The registry
+//! declares every known key with its type and default, rejects invalid writes,
+//! and announces changes on a broadcast bus that is forwarded to the frontend
+//! as `settings://changed`, so open views and background modules pick up new
+//! values without a restart. The frontend addresses settings by camelCase
+//! name (`closeToTray`), matching its `Settings` type; the table keeps
+//! snake_case keys (`close_to_tray`).
+//!
+//! Module-specific settings with structured values (digest, prefetch, DND, ...)
+//! keep their own typed accessors and are not part of the registry.
+
+use crate::db::{Database, DbError, DbResult};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Runtime};
+use tokio::sync::broadcast;
+
+/// Tauri event name for changed settings
+pub const SETTINGS_CHANGED_EVENT: &str = "settings://changed";
+
+/// Buffered events before slow subscribers start lagging
+const EVENT_BUS_CAPACITY: usize = 64;
+
+/// Type and default of a setting
+#[derive(Debug, Clone, Copy)]
+pub enum SettingType {
+    Bool(bool),
+    Integer { default: i64, min: i64, max: i64 },
+    Choice { default: &'static str, options: &'static [&'static str] },
+    /// Free text; null by default
+    Text,
+    /// JSON object; empty by default
+    Object,
+}
+
+/// A known setting
+#[derive(Debug, Clone, Copy)]
+pub struct SettingDef {
+    /// Key in the `settings` table
+    pub key: &'static str,
+    pub setting_type: SettingType,
+}
+
+const fn setting(key: &'static str, setting_type: SettingType) -> SettingDef {
+    SettingDef { key, setting_type }
+}
+
+/// All registered settings (defaults match `DEFAULT_SETTINGS` in the frontend)
+pub const REGISTRY: &[SettingDef] = &[
+    // Appearance
+    setting("theme", SettingType::Choice { default: "dark", options: &["dark", "light", "system"] }),
+    setting("language", SettingType::Choice { default: "tr", options: &["tr", "en"] }),
+    setting("compact_list_view", SettingType::Bool(false)),
+    setting("show_avatars", SettingType::Bool(true)),
+    setting("conversation_view", SettingType::Bool(true)),
+    // Notifications
+    setting("notifications_enabled", SettingType::Bool(true)),
+    setting("notification_sound", SettingType::Bool(true)),
+    setting(
+        "notification_sound_type",
+        SettingType::Choice { default: "call", options: crate::notifications::sound::BUILT_IN_SOUNDS },
+    ),
+    setting("notification_badge", SettingType::Bool(true)),
+    // Behavior
+    setting("auto_mark_read", SettingType::Bool(true)),
+    setting("auto_mark_read_delay", SettingType::Integer { default: 3, min: 0, max: 60 }),
+    setting("confirm_delete", SettingType::Bool(true)),
+    setting("confirm_send", SettingType::Bool(false)),
+    setting("signature_position", SettingType::Choice { default: "bottom", options: &["top", "bottom"] }),
+    setting("reply_position", SettingType::Choice { default: "top", options: &["top", "bottom"] }),
+    setting("close_to_tray", SettingType::Bool(true)),
+    setting("start_minimized", SettingType::Bool(false)),
+    // AI
+    setting("gemini_api_key", SettingType::Text),
+    setting("ai_auto_summarize", SettingType::Bool(false)),
+    setting(
+        "ai_reply_tone",
+        SettingType::Choice { default: "professional", options: &["professional", "friendly", "formal", "casual"] },
+    ),
+    setting("auto_phishing_detection", SettingType::Bool(true)),
+    // Shortcuts
+    setting("keyboard_shortcuts_enabled", SettingType::Bool(true)),
+    // Auto-sync
+    setting("auto_sync_enabled", SettingType::Bool(false)),
+    setting("auto_sync_interval", SettingType::Integer { default: 5, min: 1, max: 60 }),
+    // Multi-account
+    setting("account_priority_settings", SettingType::Object),
+    setting(
+        "unified_inbox_sort_by",
+        SettingType::Choice { default: "priority", options: &["date", "account", "unread", "priority"] },
+    ),
+];
+
+impl SettingDef {
+    /// camelCase name used by the frontend
+    pub fn name(&self) -> String {
+        let mut name = String::with_capacity(self.key.len());
+        let mut upper = false;
+        for c in self.key.chars() {
+            if c == '_' {
+                upper = true;
+            } else if upper {
+                name.push(c.to_ascii_uppercase());
+                upper = false;
+            } else {
+                name.push(c);
+            }
+        }
+        name
+    }
+
+    pub fn default_value(&self) -> Value {
+        match self.setting_type {
+            SettingType::Bool(default) => Value::Bool(default),
+            SettingType::Integer { default, .. } => Value::from(default),
+            SettingType::Choice { default, .. } => Value::from(default),
+            SettingType::Text => Value::Null,
+            SettingType::Object => Value::Object(Map::new()),
+        }
+    }
+
+    /// Check a new value; empty text is stored as null
+    pub fn validate(&self, value: &Value) -> Result<Value, String> {
+        let invalid = |expected: &str| Err(format!("{} must be {}", self.name(), expected));
+        match (self.setting_type, value) {
+            (SettingType::Bool(_), Value::Bool(_)) => Ok(value.clone()),
+            (SettingType::Bool(_), _) => invalid("true or false"),
+            (SettingType::Integer { min, max, .. }, _) => match value.as_i64() {
+                Some(number) if (min..=max).contains(&number) => Ok(Value::from(number)),
+                _ => invalid(&format!("a whole number from {} to {}", min, max)),
+            },
+            (SettingType::Choice { options, .. }, _) => match value.as_str() {
+                Some(choice) if options.contains(&choice) => Ok(value.clone()),
+                _ => invalid(&format!("one of: {}", options.join(", "))),
+            },
+            (SettingType::Text, Value::Null) => Ok(Value::Null),
+            (SettingType::Text, Value::String(text)) if text.trim().is_empty() => Ok(Value::Null),
+            (SettingType::Text, Value::String(_)) => Ok(value.clone()),
+            (SettingType::Text, _) => invalid("text"),
+            (SettingType::Object, Value::Object(_)) => Ok(value.clone()),
+            (SettingType::Object, _) => invalid("an object"),
+        }
+    }
+}
+
+/// Look up a setting by camelCase name or storage key
+pub fn find(name: &str) -> Option<&'static SettingDef> {
+    REGISTRY.iter().find(|def| def.key == name || def.name() == name)
+}
+
+fn stored(db: &Database, key: &str) -> DbResult<Option<Value>> {
+    let json: Option<String> = db
+        .get_conn()?
+        .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()?;
+    Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Current value of a setting; missing or invalid stored values read as the default
+pub fn value(db: &Database, def: &SettingDef) -> DbResult<Value> {
+    Ok(stored(db, def.key)?
+        .and_then(|value| def.validate(&value).ok())
+        .unwrap_or_else(|| def.default_value()))
+}
+
+/// Current value of a boolean setting (its default if unknown to the registry)
+pub fn get_bool(db: &Database, key: &str) -> bool {
+    let Some(def) = find(key) else {
+        return false;
+    };
+    value(db, def)
+        .ok()
+        .and_then(|value| value.as_bool())
+        .unwrap_or_else(|| def.default_value().as_bool().unwrap_or(false))
+}
+
+/// Current value of an integer setting (0 if unknown to the registry)
+pub fn get_integer(db: &Database, key: &str) -> i64 {
+    let Some(def) = find(key) else {
+        return 0;
+    };
+    value(db, def)
+        .ok()
+        .and_then(|value| value.as_i64())
+        .unwrap_or_else(|| def.default_value().as_i64().unwrap_or(0))
+}
+
+/// All registered settings by camelCase name
+pub fn get_all(db: &Database) -> DbResult<Map<String, Value>> {
+    let mut values = Map::new();
+    for def in REGISTRY {
+        values.insert(def.name(), value(db, def)?);
+    }
+    Ok(values)
+}
+
+/// Validate and store several settings at once (all or none)
+/// Returns the values that actually changed, by camelCase name.
+pub fn set_many(db: &Database, values: &Map<String, Value>) -> DbResult<Map<String, Value>> {
+    let mut updates = Vec::with_capacity(values.len());
+    for (name, value) in values {
+        let def = find(name).ok_or_else(|| DbError::Constraint(format!("Unknown setting: {}", name)))?;
+        let value = def.validate(value).map_err(DbError::Constraint)?;
+        updates.push((def, value));
+    }
+
+    let mut changed = Map::new();
+    for (def, value) in &updates {
+        if self::value(db, def)? != *value {
+            changed.insert(def.name(), value.clone());
+        }
+    }
+
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    for (def, value) in &updates {
+        tx.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![def.key, value.to_string()],
+        )?;
+    }
+    tx.commit()?;
+    Ok(changed)
+}
+
+/// Payload of the `settings://changed` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsChanged {
+    /// New values by camelCase name
+    pub changes: Map<String, Value>,
+}
+
+/// Broadcast bus for setting changes (cheap to clone)
+#[derive(Clone)]
+pub struct SettingsEvents {
+    sender: broadcast::Sender<SettingsChanged>,
+}
+
+impl Default for SettingsEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SettingsEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    /// Subscribe to setting changes
+    pub fn subscribe(&self) -> broadcast::Receiver<SettingsChanged> {
+        self.sender.subscribe()
+    }
+
+    /// Publish changed values (no-op when nothing changed)
+    pub fn notify(&self, changes: Map<String, Value>) {
+        if changes.is_empty() {
+            return;
+        }
+        // Sending only fails when there are no subscribers, which is fine
+        let _ = self.sender.send(SettingsChanged { changes });
+    }
+}
+
+/// Forward setting changes to the frontend as Tauri events
+pub fn forward_to_frontend<R: Runtime>(app: AppHandle<R>, events: &SettingsEvents) {
+    let mut receiver = events.subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(changed) => crate::events::emit(&app, &changed),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Settings event forwarder lagged, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_registry_defaults_and_validation() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+
+        let all = get_all(&db).unwrap();
+        assert_eq!(all["closeToTray"], json!(true));
+        assert_eq!(all["autoMarkReadDelay"], json!(3));
+        assert_eq!(all["geminiApiKey"], Value::Null);
+        assert_eq!(find("closeToTray").unwrap().key, "close_to_tray");
+        assert_eq!(find("close_to_tray").unwrap().name(), "closeToTray");
+
+        // Invalid or unknown entries reject the whole batch
+        let values = json!({ "theme": "light", "autoSyncInterval": 0 });
+        assert!(set_many(&db, values.as_object().unwrap()).is_err());
+        let values = json!({ "theme": "light", "noSuchSetting": true });
+        assert!(set_many(&db, values.as_object().unwrap()).is_err());
+        assert_eq!(get_all(&db).unwrap()["theme"], json!("dark"));
+
+        let values = json!({ "theme": "light", "closeToTray": true, "geminiApiKey": " " });
+        let changed = set_many(&db, values.as_object().unwrap()).unwrap();
+        assert_eq!(Value::Object(changed), json!({ "theme": "light" }));
+        assert_eq!(get_all(&db).unwrap()["theme"], json!("light"));
+
+        // Values written by older builds that no longer validate read as the default
+        db.set_setting("close_to_tray", &"yes").unwrap();
+        assert!(get_bool(&db, "close_to_tray"));
+        db.set_setting("close_to_tray", &false).unwrap();
+        assert!(!get_bool(&db, "closeToTray"));
+    }
+}
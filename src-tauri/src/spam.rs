@@ -0,0 +1,866 @@
+//! Local Spam Classifier
+//!
+//! A token-based (naive Bayes) classifier trained from what the user does
+//! with their mail: moving a message into the Junk folder marks it as spam,
+//! moving one out of Junk marks it as wanted. The move only queues the
+//! verdict in `spam_training`; a periodic sweep trains on the message once it
+//! has arrived in its new folder, so training sees the content the server
+//! stored. Each trained message also updates its sender's reputation. When
+//! the user changes their mind about a message, its tokens are untrained
+//! before they are counted the other way.
+//!
+//! A spam report explains a classification: the tokens that decided the local
+//! score, the sender's reputation, and the verdicts that server-side filters
+//! (SpamAssassin, Rspamd, Microsoft) left in the message headers.
+
+use crate::db::{Database, DbResult};
+use crate::mail::headers::HeaderField;
+use crate::mail::html_text::html_to_text;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings key of the sweep configuration
+const CONFIG_SETTING: &str = "spam_training";
+
+/// Settings key holding the last sweep report
+const LAST_REPORT_SETTING: &str = "spam_training_last";
+
+/// How often the scheduler checks whether a sweep is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Queued verdicts whose message never arrived are dropped after this long
+const PENDING_EXPIRY_DAYS: i64 = 7;
+
+/// Trained messages of each kind needed before the classifier scores mail
+const MIN_TRAINED: i64 = 5;
+
+/// Distinct tokens counted per message
+const MAX_TOKENS: usize = 1000;
+
+/// Tokens furthest from neutral that decide a score
+const INTERESTING_TOKENS: usize = 15;
+
+/// Weight of the neutral assumption for rarely seen tokens
+const NEUTRAL_STRENGTH: f64 = 1.0;
+
+/// What the user decided about a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Spam,
+    /// Wanted mail
+    Ham,
+}
+
+impl Verdict {
+    fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Spam => "spam",
+            Verdict::Ham => "ham",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "spam" => Some(Verdict::Spam),
+            "ham" => Some(Verdict::Ham),
+            _ => None,
+        }
+    }
+}
+
+/// When the training sweep runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "SpamTrainingConfig"))]
+#[serde(rename_all = "camelCase")]
+pub struct TrainingConfig {
+    pub enabled: bool,
+    /// Minutes between sweeps (5 to 1440)
+    pub interval_minutes: u32,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: 30,
+        }
+    }
+}
+
+impl TrainingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(5..=1440).contains(&self.interval_minutes) {
+            return Err(format!("Training interval must be 5-1440 minutes, got {}", self.interval_minutes));
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of one training sweep
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "SpamSweepReport"))]
+#[serde(rename_all = "camelCase")]
+pub struct SweepReport {
+    pub ran_at: String,
+    /// Messages the user moved to Junk
+    pub spam_trained: usize,
+    /// Messages the user rescued from Junk
+    pub ham_trained: usize,
+    /// Queued verdicts dropped because the message never arrived
+    pub expired: usize,
+}
+
+/// How a sender's mail was judged so far
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SenderReputation {
+    pub address: String,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub spam_count: i64,
+    #[cfg_attr(test, ts(type = "number"))]
+    pub ham_count: i64,
+    /// -1 (only spam) to 1 (only wanted), closer to 0 with few messages
+    pub score: f64,
+}
+
+/// Spam score of a message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SpamScore {
+    /// Probability of spam; `None` until enough messages were trained
+    pub probability: Option<f64>,
+    pub sender: Option<SenderReputation>,
+}
+
+/// Contribution of one token to the local score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct TokenSignal {
+    /// Word, `subject:` word or `from:` domain
+    pub token: String,
+    /// Spam probability of messages containing the token
+    pub probability: f64,
+    /// Log-odds the token adds to the score; positive leans towards spam
+    pub weight: f64,
+}
+
+/// Rule a server-side filter matched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SpamRule {
+    pub name: String,
+    pub score: Option<f64>,
+    pub description: Option<String>,
+}
+
+/// What a server-side filter decided, read from its headers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "ServerSpamVerdict"))]
+#[serde(rename_all = "camelCase")]
+pub struct ServerVerdict {
+    /// `SpamAssassin`, `Rspamd` or `Microsoft`
+    pub filter: String,
+    pub is_spam: Option<bool>,
+    pub score: Option<f64>,
+    /// Score from which the filter treats mail as spam
+    pub threshold: Option<f64>,
+    /// Matched rules, highest score first
+    pub rules: Vec<SpamRule>,
+}
+
+/// Why a message is (or is not) considered spam
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct SpamReport {
+    /// The message is in Junk or flagged as spam
+    pub in_junk: bool,
+    /// Local classifier probability; `None` until enough messages were trained
+    pub probability: Option<f64>,
+    /// Tokens that decided the local score, most decisive first
+    pub tokens: Vec<TokenSignal>,
+    pub sender: Option<SenderReputation>,
+    pub server: Vec<ServerVerdict>,
+}
+
+pub fn load_config(db: &Database) -> DbResult<TrainingConfig> {
+    Ok(db.get_setting(CONFIG_SETTING)?.unwrap_or_default())
+}
+
+pub fn save_config(db: &Database, config: &TrainingConfig) -> DbResult<()> {
+    db.set_setting(CONFIG_SETTING, config)
+}
+
+pub fn last_report(db: &Database) -> DbResult<Option<SweepReport>> {
+    db.get_setting(LAST_REPORT_SETTING)
+}
+
+// ============================================================================
+// Training
+// ============================================================================
+
+fn folder_type(db: &Database, account_id: i64, folder: &str) -> DbResult<Option<String>> {
+    let types = db.query(
+        "SELECT folder_type FROM folders WHERE account_id = ?1 AND remote_name = ?2 AND is_local = 0",
+        params![account_id, folder],
+        |row| row.get::<_, String>(0),
+    )?;
+    Ok(types.into_iter().next())
+}
+
+/// Queue a verdict for messages the user moved into or out of Junk
+/// Returns the number of messages queued; moves not involving Junk queue none.
+pub fn record_move(db: &Database, account_id: i64, email_ids: &[i64], from: &str, to: &str) -> DbResult<usize> {
+    let from_spam = folder_type(db, account_id, from)?.as_deref() == Some("spam");
+    let to_spam = folder_type(db, account_id, to)?.as_deref() == Some("spam");
+    let verdict = match (from_spam, to_spam) {
+        (false, true) => Verdict::Spam,
+        (true, false) => Verdict::Ham,
+        _ => return Ok(0),
+    };
+
+    let mut queued = 0;
+    for email_id in email_ids {
+        let email = db.get_email(*email_id)?;
+        if email.message_id.is_empty() {
+            continue;
+        }
+        db.execute(
+            "INSERT INTO spam_training (account_id, message_id, verdict, sender) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(account_id, message_id) DO UPDATE SET
+                verdict = excluded.verdict, sender = excluded.sender, requested_at = datetime('now')",
+            params![account_id, email.message_id, verdict.as_str(), email.from_address.trim().to_lowercase()],
+        )?;
+        queued += 1;
+    }
+    Ok(queued)
+}
+
+/// Distinct lowercase words of a message, subject words and sender domain marked
+fn tokenize(from: &str, subject: &str, body: &str) -> BTreeSet<String> {
+    let mut tokens = BTreeSet::new();
+    let from = from.trim().to_lowercase();
+    if let Some((_, domain)) = from.rsplit_once('@') {
+        tokens.insert(format!("from:{}", domain.trim_end_matches('>')));
+    }
+
+    let words = |text: &str| {
+        text.split(|c: char| !(c.is_alphanumeric() || c == '$' || c == '€' || c == '%'))
+            .filter(|word| (3..=24).contains(&word.chars().count()) && !word.chars().all(|c| c.is_ascii_digit()))
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    for word in words(subject) {
+        tokens.insert(format!("subject:{}", word));
+    }
+    for word in words(body) {
+        if tokens.len() >= MAX_TOKENS {
+            break;
+        }
+        tokens.insert(word);
+    }
+    tokens
+}
+
+/// Add `delta` to the token and sender counts of one verdict
+fn count(db: &Database, tokens: &BTreeSet<String>, sender: &str, verdict: Verdict, delta: i64) -> DbResult<()> {
+    let (spam, ham) = match verdict {
+        Verdict::Spam => (delta, 0),
+        Verdict::Ham => (0, delta),
+    };
+
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    {
+        let mut statement = tx.prepare(
+            "INSERT INTO spam_tokens (token, spam_count, ham_count) VALUES (?1, MAX(?2, 0), MAX(?3, 0))
+             ON CONFLICT(token) DO UPDATE SET
+                spam_count = MAX(spam_count + ?2, 0), ham_count = MAX(ham_count + ?3, 0)",
+        )?;
+        for token in tokens {
+            statement.execute(params![token, spam, ham])?;
+        }
+    }
+    if !sender.is_empty() {
+        tx.execute(
+            "INSERT INTO sender_reputation (address, spam_count, ham_count) VALUES (?1, MAX(?2, 0), MAX(?3, 0))
+             ON CONFLICT(address) DO UPDATE SET
+                spam_count = MAX(spam_count + ?2, 0), ham_count = MAX(ham_count + ?3, 0),
+                updated_at = datetime('now')",
+            params![sender, spam, ham],
+        )?;
+        tx.execute(
+            "UPDATE sender_reputation
+             SET score = CAST(ham_count - spam_count AS REAL) / (ham_count + spam_count + 1)
+             WHERE address = ?1",
+            [sender],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Text the classifier reads from a stored message
+struct Content {
+    from: String,
+    subject: String,
+    body: String,
+}
+
+impl Content {
+    fn tokens(&self) -> BTreeSet<String> {
+        tokenize(&self.from, &self.subject, &self.body)
+    }
+}
+
+fn content_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Content> {
+    let body_text: Option<String> = row.get(2)?;
+    let body_html: Option<String> = row.get(3)?;
+    let body = body_text
+        .filter(|text| !text.trim().is_empty())
+        .or_else(|| body_html.map(|html| html_to_text(&html)))
+        .map_or_else(|| row.get(4), Ok)?;
+    Ok(Content {
+        from: row.get(0)?,
+        subject: row.get(1)?,
+        body,
+    })
+}
+
+/// Train on queued verdicts whose message has arrived in its new folder
+pub fn sweep(db: &Database) -> DbResult<SweepReport> {
+    let pending = db.query(
+        "SELECT account_id, message_id, verdict, trained FROM spam_training
+         WHERE trained IS NULL OR trained != verdict",
+        [],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        },
+    )?;
+
+    let mut report = SweepReport {
+        ran_at: Utc::now().to_rfc3339(),
+        spam_trained: 0,
+        ham_trained: 0,
+        expired: 0,
+    };
+    for (account_id, message_id, verdict, trained) in pending {
+        let Some(verdict) = Verdict::parse(&verdict) else { continue };
+        // Spam must be in Junk; rescued mail anywhere but Junk and Trash
+        let folder_condition = match verdict {
+            Verdict::Spam => "f.folder_type = 'spam'",
+            Verdict::Ham => "f.folder_type NOT IN ('spam', 'trash')",
+        };
+        let found = db.query(
+            &format!(
+                "SELECT e.from_address, e.subject, e.body_text, e.body_html, e.preview
+                 FROM emails e
+                 JOIN folders f ON f.id = e.folder_id
+                 WHERE e.account_id = ?1 AND e.message_id = ?2 AND e.is_deleted = 0 AND {}
+                 LIMIT 1",
+                folder_condition
+            ),
+            params![account_id, message_id],
+            content_row,
+        )?;
+        let Some(content) = found.into_iter().next() else { continue };
+
+        let tokens = content.tokens();
+        let sender = content.from.trim().to_lowercase();
+        if let Some(previous) = trained.as_deref().and_then(Verdict::parse) {
+            count(db, &tokens, &sender, previous, -1)?;
+        }
+        count(db, &tokens, &sender, verdict, 1)?;
+        db.execute(
+            "UPDATE spam_training SET trained = ?3, trained_at = datetime('now')
+             WHERE account_id = ?1 AND message_id = ?2",
+            params![account_id, message_id, verdict.as_str()],
+        )?;
+        match verdict {
+            Verdict::Spam => report.spam_trained += 1,
+            Verdict::Ham => report.ham_trained += 1,
+        }
+    }
+
+    let cutoff = format!("-{} days", PENDING_EXPIRY_DAYS);
+    report.expired = db.execute(
+        "DELETE FROM spam_training WHERE trained IS NULL AND requested_at < datetime('now', ?1)",
+        [&cutoff],
+    )?;
+    // A change of mind that never arrived: keep what was trained
+    report.expired += db.execute(
+        "UPDATE spam_training SET verdict = trained
+         WHERE trained IS NOT NULL AND trained != verdict AND requested_at < datetime('now', ?1)",
+        [&cutoff],
+    )?;
+    Ok(report)
+}
+
+/// Run a sweep and keep its report
+pub fn run(db: &Database) -> DbResult<SweepReport> {
+    let report = sweep(db)?;
+    db.set_setting(LAST_REPORT_SETTING, &report)?;
+    if report.spam_trained + report.ham_trained > 0 {
+        log::info!("Spam training: {} spam, {} wanted", report.spam_trained, report.ham_trained);
+    }
+    Ok(report)
+}
+
+// ============================================================================
+// Scoring
+// ============================================================================
+
+/// Messages trained as spam and as wanted
+fn trained_totals(db: &Database) -> DbResult<(i64, i64)> {
+    db.query_row(
+        "SELECT COALESCE(SUM(trained = 'spam'), 0), COALESCE(SUM(trained = 'ham'), 0) FROM spam_training",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// Spam probability of each known token, furthest from neutral first
+fn token_probabilities(db: &Database, tokens: &BTreeSet<String>) -> DbResult<Option<Vec<(String, f64)>>> {
+    let (spam_total, ham_total) = trained_totals(db)?;
+    if spam_total < MIN_TRAINED || ham_total < MIN_TRAINED {
+        return Ok(None);
+    }
+
+    let mut counts: HashMap<&str, (i64, i64)> = HashMap::new();
+    {
+        let conn = db.get_conn()?;
+        let mut statement = conn.prepare("SELECT spam_count, ham_count FROM spam_tokens WHERE token = ?1")?;
+        for token in tokens {
+            let mut rows = statement.query([token])?;
+            if let Some(row) = rows.next()? {
+                counts.insert(token.as_str(), (row.get(0)?, row.get(1)?));
+            }
+        }
+    }
+
+    let mut probabilities: Vec<(String, f64)> = counts
+        .into_iter()
+        .filter(|(_, (spam, ham))| spam + ham > 0)
+        .map(|(token, (spam, ham))| {
+            let spam_rate = spam as f64 / spam_total as f64;
+            let ham_rate = ham as f64 / ham_total as f64;
+            let p = spam_rate / (spam_rate + ham_rate);
+            // Few sightings stay close to neutral
+            let seen = (spam + ham) as f64;
+            let p = (NEUTRAL_STRENGTH * 0.5 + seen * p) / (NEUTRAL_STRENGTH + seen);
+            (token.to_string(), p.clamp(0.01, 0.99))
+        })
+        .collect();
+    probabilities.sort_by(|a, b| (b.1 - 0.5).abs().total_cmp(&(a.1 - 0.5).abs()).then_with(|| a.0.cmp(&b.0)));
+    probabilities.truncate(INTERESTING_TOKENS);
+    Ok(Some(probabilities))
+}
+
+/// Naive Bayes combination of token probabilities
+fn combine(probabilities: &[(String, f64)]) -> f64 {
+    let (spam, ham) = probabilities
+        .iter()
+        .fold((0.0, 0.0), |(spam, ham), (_, p)| (spam + p.ln(), ham + (1.0 - p).ln()));
+    1.0 / (1.0 + (ham - spam).exp())
+}
+
+/// Reputation of a sender address, if any of their mail was trained
+pub fn sender_reputation(db: &Database, address: &str) -> DbResult<Option<SenderReputation>> {
+    let rows = db.query(
+        "SELECT address, spam_count, ham_count, score FROM sender_reputation WHERE address = ?1",
+        [address.trim().to_lowercase()],
+        |row| {
+            Ok(SenderReputation {
+                address: row.get(0)?,
+                spam_count: row.get(1)?,
+                ham_count: row.get(2)?,
+                score: row.get(3)?,
+            })
+        },
+    )?;
+    Ok(rows.into_iter().next())
+}
+
+/// Spam score of a stored message
+pub fn score(db: &Database, email_id: i64) -> DbResult<SpamScore> {
+    let content = db.query_row(
+        "SELECT from_address, subject, body_text, body_html, preview FROM emails WHERE id = ?1",
+        [email_id],
+        content_row,
+    )?;
+    let probability = token_probabilities(db, &content.tokens())?.map(|probabilities| combine(&probabilities));
+    Ok(SpamScore {
+        probability,
+        sender: sender_reputation(db, &content.from)?,
+    })
+}
+
+// ============================================================================
+// Explanation
+// ============================================================================
+
+/// Topmost field of that name, the one added by the receiving server
+fn header<'a>(headers: &'a [HeaderField], name: &str) -> Option<&'a str> {
+    headers.iter().find(|field| field.name.eq_ignore_ascii_case(name)).map(|field| field.value.as_str())
+}
+
+fn number(text: &str) -> Option<f64> {
+    text.trim().trim_end_matches(',').parse().ok()
+}
+
+/// `key=value` pairs of a whitespace-separated header value
+fn key_value<'a>(value: &'a str, key: &str) -> Option<&'a str> {
+    value.split_whitespace().find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        name.eq_ignore_ascii_case(key).then_some(value)
+    })
+}
+
+/// SpamAssassin: X-Spam-Status (verdict, score, tests), X-Spam-Report (rule scores),
+/// X-Spam-Flag and X-Spam-Score
+fn spamassassin(headers: &[HeaderField]) -> Option<ServerVerdict> {
+    let status = header(headers, "X-Spam-Status");
+    let flag = header(headers, "X-Spam-Flag");
+    let report = header(headers, "X-Spam-Report");
+    let score_header = header(headers, "X-Spam-Score");
+    if status.is_none() && flag.is_none() && report.is_none() && score_header.is_none() {
+        return None;
+    }
+
+    let is_spam = status
+        .and_then(|status| status.split([',', ' ']).next())
+        .or(flag)
+        .map(|verdict| verdict.trim().eq_ignore_ascii_case("yes"));
+    let score = status.and_then(|status| key_value(status, "score")).and_then(number).or_else(|| score_header.and_then(number));
+    let threshold = status.and_then(|status| key_value(status, "required")).and_then(number);
+
+    // Report lines are "* 3.5 BAYES_99 BODY: Bayes spam probability is 99 to 100%"
+    let mut rules: Vec<SpamRule> = report
+        .unwrap_or_default()
+        .split('*')
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let score = number(words.next()?)?;
+            let name = words.next()?;
+            if !name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_') {
+                return None;
+            }
+            let description = words.collect::<Vec<_>>().join(" ");
+            Some(SpamRule {
+                name: name.to_string(),
+                score: Some(score),
+                description: Some(description).filter(|description| !description.is_empty()),
+            })
+        })
+        .collect();
+    // Without a report only the names of the tests are known
+    if rules.is_empty() {
+        let tests = status.and_then(|status| {
+            let start = status.to_ascii_lowercase().find("tests=")? + "tests=".len();
+            Some(status[start..].split_whitespace().next().unwrap_or_default().to_string())
+        });
+        rules = tests
+            .unwrap_or_default()
+            .split(',')
+            .filter(|name| !name.trim().is_empty() && *name != "none")
+            .map(|name| SpamRule { name: name.trim().to_string(), score: None, description: None })
+            .collect();
+    }
+
+    Some(ServerVerdict { filter: "SpamAssassin".to_string(), is_spam, score, threshold, rules })
+}
+
+/// Rspamd: "default: True [12.40 / 15.00]; BAYES_SPAM(5.10)[99.99%]; R_SPF_FAIL(1.00)[-all]"
+fn rspamd(headers: &[HeaderField]) -> Option<ServerVerdict> {
+    let result = header(headers, "X-Spamd-Result");
+    let score_header = header(headers, "X-Rspamd-Score");
+    if result.is_none() && score_header.is_none() {
+        return None;
+    }
+
+    let mut is_spam = None;
+    let mut score = score_header.and_then(number);
+    let mut threshold = None;
+    let mut rules = Vec::new();
+    for (index, item) in result.unwrap_or_default().split(';').enumerate() {
+        let item = item.trim();
+        if index == 0 {
+            // "default: True [12.40 / 15.00]"
+            let verdict = item.split_once(':').map_or(item, |(_, verdict)| verdict).trim();
+            is_spam = verdict.split_whitespace().next().map(|word| word.eq_ignore_ascii_case("true"));
+            if let Some((scores, _)) = verdict.split_once('[').and_then(|(_, rest)| rest.split_once(']')) {
+                let (actual, required) = scores.split_once('/').unwrap_or((scores, ""));
+                score = number(actual).or(score);
+                threshold = number(required);
+            }
+            continue;
+        }
+        let Some((name, rest)) = item.split_once('(') else { continue };
+        let (value, rest) = rest.split_once(')').unwrap_or((rest, ""));
+        let description = rest.trim().strip_prefix('[').and_then(|rest| rest.strip_suffix(']'));
+        rules.push(SpamRule {
+            name: name.trim().to_string(),
+            score: number(value),
+            description: description.filter(|description| !description.is_empty()).map(str::to_string),
+        });
+    }
+
+    Some(ServerVerdict { filter: "Rspamd".to_string(), is_spam, score, threshold, rules })
+}
+
+/// Microsoft: spam confidence level (SCL) 5 and above is junk, -1 means the sender was trusted
+fn microsoft(headers: &[HeaderField]) -> Option<ServerVerdict> {
+    let scl = header(headers, "X-MS-Exchange-Organization-SCL").and_then(number).or_else(|| {
+        header(headers, "X-Forefront-Antispam-Report")?
+            .split(';')
+            .find_map(|item| item.trim().strip_prefix("SCL:").and_then(number))
+    })?;
+    Some(ServerVerdict {
+        filter: "Microsoft".to_string(),
+        is_spam: Some(scl >= 5.0),
+        score: Some(scl),
+        threshold: Some(5.0),
+        rules: Vec::new(),
+    })
+}
+
+/// Verdicts of the server-side filters that processed a message
+pub fn server_verdicts(headers: &[HeaderField]) -> Vec<ServerVerdict> {
+    [spamassassin(headers), rspamd(headers), microsoft(headers)]
+        .into_iter()
+        .flatten()
+        .map(|mut verdict| {
+            verdict.rules.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+            verdict
+        })
+        .collect()
+}
+
+/// Explain the classification of a stored message; `headers` are its header fields
+pub fn report(db: &Database, email_id: i64, headers: &[HeaderField]) -> DbResult<SpamReport> {
+    let (content, in_junk) = db.query_row(
+        "SELECT e.from_address, e.subject, e.body_text, e.body_html, e.preview,
+                e.is_spam OR f.folder_type = 'spam'
+         FROM emails e JOIN folders f ON f.id = e.folder_id WHERE e.id = ?1",
+        [email_id],
+        |row| Ok((content_row(row)?, row.get::<_, bool>(5)?)),
+    )?;
+    let probabilities = token_probabilities(db, &content.tokens())?;
+    let probability = probabilities.as_deref().map(combine);
+    let tokens = probabilities
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(token, p)| TokenSignal { token, probability: p, weight: (p / (1.0 - p)).ln() })
+        .collect();
+    Ok(SpamReport {
+        in_junk,
+        probability,
+        tokens,
+        sender: sender_reputation(db, &content.from)?,
+        server: server_verdicts(headers),
+    })
+}
+
+/// Whether a sweep is due at `now`
+fn is_due(config: &TrainingConfig, last: Option<&SweepReport>, now: DateTime<Utc>) -> bool {
+    if !config.enabled {
+        return false;
+    }
+    match last.and_then(|report| DateTime::parse_from_rfc3339(&report.ran_at).ok()) {
+        Some(ran_at) => now - ran_at.with_timezone(&Utc) >= chrono::Duration::minutes(config.interval_minutes.into()),
+        None => true,
+    }
+}
+
+/// Runs the training sweep at the configured interval
+pub struct SpamTrainer {
+    db: Arc<Database>,
+    started: AtomicBool,
+}
+
+impl SpamTrainer {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Start the scheduler (only the first call has an effect)
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            log::warn!("Spam trainer already started");
+            return;
+        }
+
+        let db = self.db.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                let (config, last) = match (load_config(&db), last_report(&db)) {
+                    (Ok(config), Ok(last)) => (config, last),
+                    (Err(e), _) | (_, Err(e)) => {
+                        log::error!("Failed to load spam training state: {}", e);
+                        continue;
+                    }
+                };
+                if !is_due(&config, last.as_ref(), Utc::now()) {
+                    continue;
+                }
+                if let Err(e) = run(&db) {
+                    log::error!("Spam training failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewEmail, NewFolder};
+
+    fn add_email(db: &Database, account_id: i64, folder_id: i64, uid: u32, from: &str, subject: &str, body: &str) -> i64 {
+        db.upsert_email(&NewEmail {
+            message_id: format!("<{}@test.com>", uid % 100),
+            from_address: from.to_string(),
+            subject: subject.to_string(),
+            body_text: Some(body.to_string()),
+            ..test_email(account_id, folder_id, uid)
+        })
+        .unwrap()
+    }
+
+    fn add_folder(db: &Database, account_id: i64, name: &str, folder_type: &str) -> i64 {
+        db.upsert_folder(&NewFolder {
+            account_id,
+            name: name.to_string(),
+            remote_name: name.to_string(),
+            folder_type: folder_type.to_string(),
+            is_subscribed: true,
+            is_selectable: true,
+            delimiter: "/".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_moves_train_classifier_and_reputation() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let inbox = add_folder(&db, account_id, "INBOX", "inbox");
+        let junk = add_folder(&db, account_id, "Junk", "spam");
+        add_folder(&db, account_id, "Archive", "archive");
+
+        // The user moves five offers to Junk and rescues five invoices.
+        // Until the sync brings them into their new folder nothing is trained.
+        for uid in 1..=5 {
+            let offer = add_email(&db, account_id, inbox, uid, "deals@offers.test", "Cheap pills", "Buy cheap pills now, casino bonus");
+            assert_eq!(record_move(&db, account_id, &[offer], "INBOX", "Junk").unwrap(), 1);
+            let invoice = add_email(&db, account_id, junk, uid + 10, "billing@vendor.test", "Invoice", "Your invoice for the project meeting");
+            assert_eq!(record_move(&db, account_id, &[invoice], "Junk", "INBOX").unwrap(), 1);
+        }
+        assert_eq!(record_move(&db, account_id, &[1], "INBOX", "Archive").unwrap(), 0);
+        let report = sweep(&db).unwrap();
+        assert_eq!((report.spam_trained, report.ham_trained), (0, 0));
+
+        db.execute("UPDATE emails SET is_deleted = 1", []).unwrap();
+        for uid in 1..=5 {
+            add_email(&db, account_id, junk, uid + 100, "deals@offers.test", "Cheap pills", "Buy cheap pills now, casino bonus");
+            add_email(&db, account_id, inbox, uid + 110, "billing@vendor.test", "Invoice", "Your invoice for the project meeting");
+        }
+        let report = sweep(&db).unwrap();
+        assert_eq!((report.spam_trained, report.ham_trained), (5, 5));
+        assert_eq!(sweep(&db).unwrap().spam_trained, 0);
+
+        let spam = add_email(&db, account_id, inbox, 50, "other@offers.test", "Casino pills", "cheap casino bonus");
+        let ham = add_email(&db, account_id, inbox, 51, "ana@vendor.test", "Meeting", "project invoice meeting");
+        assert!(score(&db, spam).unwrap().probability.unwrap() > 0.9);
+        assert!(score(&db, ham).unwrap().probability.unwrap() < 0.1);
+
+        let reputation = sender_reputation(&db, "Deals@Offers.test").unwrap().unwrap();
+        assert_eq!((reputation.spam_count, reputation.ham_count), (5, 0));
+        assert!(reputation.score < -0.8);
+
+        // Changing one's mind untrains the message first
+        let offer = db.query_row("SELECT id FROM emails WHERE uid = 101", [], |row| row.get(0)).unwrap();
+        record_move(&db, account_id, &[offer], "Junk", "INBOX").unwrap();
+        db.execute("UPDATE emails SET is_deleted = 1 WHERE id = ?1", [offer]).unwrap();
+        add_email(&db, account_id, inbox, 201, "deals@offers.test", "Cheap pills", "Buy cheap pills now, casino bonus");
+        assert_eq!(sweep(&db).unwrap().ham_trained, 1);
+        let reputation = sender_reputation(&db, "deals@offers.test").unwrap().unwrap();
+        assert_eq!((reputation.spam_count, reputation.ham_count), (4, 1));
+        assert_eq!(trained_totals(&db).unwrap(), (4, 6));
+    }
+
+    #[test]
+    fn test_server_verdicts() {
+        let headers = crate::mail::headers::parse(
+            b"X-Spam-Flag: YES\r\n\
+            X-Spam-Status: Yes, score=7.2 required=5.0 tests=BAYES_99,HTML_MESSAGE\r\n\
+            \tautolearn=no version=3.4.6\r\n\
+            X-Spam-Report:\r\n\
+            \t*  0.0 HTML_MESSAGE BODY: HTML included in message\r\n\
+            \t*  3.5 BAYES_99 BODY: Bayes spam probability is 99 to 100%\r\n\
+            \t*      [score: 1.0000]\r\n\
+            X-Spamd-Result: default: False [2.40 / 15.00]; R_SPF_FAIL(1.00)[-all]; MIME_GOOD(-0.10)[text/plain]\r\n\
+            X-MS-Exchange-Organization-SCL: 1\r\n\r\nBody",
+        );
+        let verdicts = server_verdicts(&headers);
+        assert_eq!(verdicts.len(), 3);
+
+        let spamassassin = &verdicts[0];
+        assert_eq!((spamassassin.is_spam, spamassassin.score, spamassassin.threshold), (Some(true), Some(7.2), Some(5.0)));
+        assert_eq!(spamassassin.rules[0].name, "BAYES_99");
+        assert_eq!(spamassassin.rules[0].score, Some(3.5));
+        assert_eq!(spamassassin.rules[0].description.as_deref(), Some("BODY: Bayes spam probability is 99 to 100%"));
+        assert_eq!(spamassassin.rules.len(), 2);
+
+        let rspamd = &verdicts[1];
+        assert_eq!((rspamd.is_spam, rspamd.score, rspamd.threshold), (Some(false), Some(2.4), Some(15.0)));
+        assert_eq!(rspamd.rules[0].name, "R_SPF_FAIL");
+        assert_eq!(rspamd.rules[1].description.as_deref(), Some("text/plain"));
+
+        assert_eq!(verdicts[2].is_spam, Some(false));
+
+        // Test names only, without a report
+        let headers = crate::mail::headers::parse(b"X-Spam-Status: No, score=-0.1 required=5.0 tests=DKIM_SIGNED,DKIM_VALID\r\n\r\n");
+        let verdicts = server_verdicts(&headers);
+        assert_eq!(verdicts[0].is_spam, Some(false));
+        assert_eq!(verdicts[0].rules.iter().map(|rule| rule.name.as_str()).collect::<Vec<_>>(), ["DKIM_SIGNED", "DKIM_VALID"]);
+        assert!(server_verdicts(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_due_by_interval() {
+        let now = Utc::now();
+        let report = |minutes_ago: i64| SweepReport {
+            ran_at: (now - chrono::Duration::minutes(minutes_ago)).to_rfc3339(),
+            spam_trained: 0,
+            ham_trained: 0,
+            expired: 0,
+        };
+        let config = TrainingConfig::default();
+        assert!(is_due(&config, None, now));
+        assert!(is_due(&config, Some(&report(31)), now));
+        assert!(!is_due(&config, Some(&report(5)), now));
+        assert!(!is_due(&TrainingConfig { enabled: false, ..config }, None, now));
+    }
+}
@@ -0,0 +1,266 @@
+//! Startup Sync
+//!
+//! Connecting every account at once on launch competes with the one inbox the
+//! user is waiting for. At startup the default account connects first and its
+//! Inbox is streamed to the frontend a page at a time; only then do the other
+//! accounts connect, at most [`MAX_PARALLEL_CONNECTS`] at a time. Every step is
+//! reported as a `startup://progress` event.
+
+use crate::mail::{EmailSummary, FetchResult};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Tauri event name for startup progress
+pub const PROGRESS_EVENT: &str = "startup://progress";
+
+/// Tauri event name for a page of the default account's Inbox
+pub const INBOX_EVENT: &str = "startup://inbox";
+
+/// Accounts connecting at once after the default one
+pub const MAX_PARALLEL_CONNECTS: usize = 2;
+
+/// Emails per streamed Inbox page
+pub const INBOX_PAGE_SIZE: u32 = 50;
+
+/// Inbox pages streamed at startup
+pub const INBOX_PAGES: u32 = 10;
+
+/// Order in which accounts are brought up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct StartupPlan {
+    /// Connected first, with its Inbox streamed
+    #[cfg_attr(test, ts(type = "number | null"))]
+    pub default_account_id: Option<i64>,
+    /// Connected afterwards
+    #[cfg_attr(test, ts(type = "Array<number>"))]
+    pub others: Vec<i64>,
+}
+
+/// Plan from `(account id, is default)` pairs; without a default the first account leads
+pub fn plan(accounts: impl IntoIterator<Item = (i64, bool)>) -> StartupPlan {
+    let mut accounts: Vec<(i64, bool)> = accounts.into_iter().collect();
+    let lead = accounts
+        .iter()
+        .position(|(_, is_default)| *is_default)
+        .or(if accounts.is_empty() { None } else { Some(0) });
+    let default_account_id = lead.map(|index| accounts.remove(index).0);
+    StartupPlan {
+        default_account_id,
+        others: accounts.into_iter().map(|(id, _)| id).collect(),
+    }
+}
+
+/// Step of the startup sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupStage {
+    Connecting,
+    Connected,
+    Failed,
+    /// The default account's Inbox was streamed (`error` set if it stopped early)
+    InboxLoaded,
+    Done,
+}
+
+/// Payload of `startup://progress`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupProgress {
+    pub stage: StartupStage,
+    pub account_id: Option<i64>,
+    pub error: Option<String>,
+    /// Accounts connected so far
+    pub connected: usize,
+    pub total: usize,
+}
+
+/// Payload of `startup://inbox`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupInbox {
+    pub account_id: i64,
+    pub page: u32,
+    pub emails: Vec<EmailSummary>,
+    pub total: u32,
+    /// Last page streamed at startup
+    pub done: bool,
+}
+
+/// What the startup sync does (implemented over the app state)
+#[async_trait]
+pub trait StartupSteps: Sync {
+    async fn connect(&self, account_id: i64) -> Result<(), String>;
+    async fn inbox_page(&self, account_id: i64, page: u32, page_size: u32) -> Result<FetchResult, String>;
+    fn progress(&self, progress: StartupProgress);
+    fn inbox(&self, inbox: StartupInbox);
+}
+
+/// Bring up the accounts of `plan`
+pub async fn run<S: StartupSteps>(plan: &StartupPlan, steps: &S) {
+    let total = usize::from(plan.default_account_id.is_some()) + plan.others.len();
+    let connected = AtomicUsize::new(0);
+    let report = |stage: StartupStage, account_id: Option<i64>, error: Option<String>| {
+        steps.progress(StartupProgress {
+            stage,
+            account_id,
+            error,
+            connected: connected.load(Ordering::SeqCst),
+            total,
+        })
+    };
+    let connect = |account_id: i64| {
+        let (report, connected) = (&report, &connected);
+        async move {
+            report(StartupStage::Connecting, Some(account_id), None);
+            match steps.connect(account_id).await {
+                Ok(()) => {
+                    connected.fetch_add(1, Ordering::SeqCst);
+                    report(StartupStage::Connected, Some(account_id), None);
+                    true
+                }
+                Err(e) => {
+                    log::warn!("Startup connect of account {} failed: {}", account_id, e);
+                    report(StartupStage::Failed, Some(account_id), Some(e));
+                    false
+                }
+            }
+        }
+    };
+
+    if let Some(account_id) = plan.default_account_id {
+        if connect(account_id).await {
+            let error = stream_inbox(steps, account_id).await.err();
+            report(StartupStage::InboxLoaded, Some(account_id), error);
+        }
+    }
+
+    futures::stream::iter(plan.others.iter().copied())
+        .for_each_concurrent(MAX_PARALLEL_CONNECTS, |account_id| async move {
+            connect(account_id).await;
+        })
+        .await;
+
+    report(StartupStage::Done, None, None);
+}
+
+/// Send the Inbox of `account_id` page by page
+async fn stream_inbox<S: StartupSteps>(steps: &S, account_id: i64) -> Result<(), String> {
+    for page in 0..INBOX_PAGES {
+        let result = steps.inbox_page(account_id, page, INBOX_PAGE_SIZE).await?;
+        let done = !result.has_more || page + 1 == INBOX_PAGES;
+        steps.inbox(StartupInbox {
+            account_id,
+            page,
+            emails: result.emails,
+            total: result.total,
+            done,
+        });
+        if done {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Runs the startup sync once per app run
+#[derive(Default)]
+pub struct StartupSync {
+    started: AtomicBool,
+}
+
+impl StartupSync {
+    /// True for the first caller only
+    pub fn begin(&self) -> bool {
+        !self.started.swap(true, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Records what happens; account 3 fails to connect
+    #[derive(Default)]
+    struct FakeSteps {
+        log: Mutex<Vec<String>>,
+        active: AtomicUsize,
+        most_active: AtomicUsize,
+        progress: Mutex<Vec<StartupProgress>>,
+    }
+
+    #[async_trait]
+    impl StartupSteps for FakeSteps {
+        async fn connect(&self, account_id: i64) -> Result<(), String> {
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most_active.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            self.log.lock().unwrap().push(format!("connect {}", account_id));
+            if account_id == 3 {
+                return Err("refused".to_string());
+            }
+            Ok(())
+        }
+
+        async fn inbox_page(&self, account_id: i64, page: u32, page_size: u32) -> Result<FetchResult, String> {
+            self.log.lock().unwrap().push(format!("inbox {} page {}", account_id, page));
+            Ok(FetchResult {
+                emails: Vec::new(),
+                total: 120,
+                has_more: (page + 1) * page_size < 120,
+            })
+        }
+
+        fn progress(&self, progress: StartupProgress) {
+            self.progress.lock().unwrap().push(progress);
+        }
+
+        fn inbox(&self, inbox: StartupInbox) {
+            self.log.lock().unwrap().push(format!("sent page {} done={}", inbox.page, inbox.done));
+        }
+    }
+
+    #[test]
+    fn test_plan_puts_default_first() {
+        assert_eq!(
+            plan(vec![(1, false), (2, true), (3, false)]),
+            StartupPlan { default_account_id: Some(2), others: vec![1, 3] }
+        );
+        assert_eq!(plan(vec![(4, false), (5, false)]).default_account_id, Some(4));
+        assert_eq!(plan(Vec::new()), StartupPlan { default_account_id: None, others: Vec::new() });
+    }
+
+    #[tokio::test]
+    async fn test_default_inbox_streams_before_others_connect() {
+        let steps = FakeSteps::default();
+        let plan = StartupPlan { default_account_id: Some(1), others: vec![2, 3, 4, 5] };
+        run(&plan, &steps).await;
+
+        let log = steps.log.lock().unwrap().clone();
+        assert_eq!(
+            log[..7],
+            [
+                "connect 1",
+                "inbox 1 page 0",
+                "sent page 0 done=false",
+                "inbox 1 page 1",
+                "sent page 1 done=false",
+                "inbox 1 page 2",
+                "sent page 2 done=true",
+            ]
+        );
+        assert_eq!(log.len(), 11);
+        assert_eq!(steps.most_active.load(Ordering::SeqCst), MAX_PARALLEL_CONNECTS);
+
+        let progress = steps.progress.lock().unwrap();
+        assert!(progress.iter().any(|p| p.stage == StartupStage::Failed && p.account_id == Some(3)));
+        let last = progress.last().unwrap();
+        assert_eq!((last.stage, last.connected, last.total), (StartupStage::Done, 4, 5));
+    }
+}
@@ -5,14 +5,25 @@
 //! - Device management
 //! - Data upload/download
 //! - Token refresh
+//!
+//! Data transfers are retried on transient failures (timeouts, connection
+//! errors, 5xx, rate limiting) with jittered exponential backoff, so a brief
+//! network hiccup does not fail a whole sync.
 
 use serde::{Deserialize, Serialize};
 use reqwest::{Client, StatusCode};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 const API_BASE_URL: &str = "https://owlivion.com/api/v1";
 
+const MAX_ATTEMPTS: u32 = 3;          // Attempts per data transfer
+const RETRY_BASE_MS: u64 = 500;       // Delay before the first retry
+const RETRY_MAX_MS: u64 = 8_000;      // Upper bound for a single delay
+
 /// API client for Owlivion Sync Server
 pub struct SyncApiClient {
     client: Client,
@@ -137,14 +148,16 @@ impl SyncApiClient {
         let token = self.get_token().await
             .ok_or(SyncApiError::Unauthorized)?;
 
-        let response = self.client
-            .post(format!("{}/sync/{}", API_BASE_URL, data_type))
-            .bearer_auth(token)
-            .json(&payload)
-            .send()
-            .await?;
+        with_retry(data_type, || async {
+            let response = self.client
+                .post(format!("{}/sync/{}", API_BASE_URL, data_type))
+                .bearer_auth(&token)
+                .json(&payload)
+                .send()
+                .await?;
 
-        handle_response(response).await
+            handle_response(response).await
+        }).await
     }
 
     /// Download encrypted sync data
@@ -155,22 +168,24 @@ impl SyncApiClient {
         let token = self.get_token().await
             .ok_or(SyncApiError::Unauthorized)?;
 
-        let response = self.client
-            .get(format!("{}/sync/{}", API_BASE_URL, data_type))
-            .bearer_auth(token)
-            .send()
-            .await?;
-
-        // Handle 404 as empty data (first sync)
-        if response.status() == StatusCode::NOT_FOUND {
-            return Ok(DownloadResponse {
-                encrypted_data: String::new(),
-                version: 0,
-                updated_at: chrono::Utc::now().to_rfc3339(),
-            });
-        }
-
-        handle_response(response).await
+        with_retry(data_type, || async {
+            let response = self.client
+                .get(format!("{}/sync/{}", API_BASE_URL, data_type))
+                .bearer_auth(&token)
+                .send()
+                .await?;
+
+            // Handle 404 as empty data (first sync)
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(DownloadResponse {
+                    encrypted_data: String::new(),
+                    version: 0,
+                    updated_at: chrono::Utc::now().to_rfc3339(),
+                });
+            }
+
+            handle_response(response).await
+        }).await
     }
 
     /// Get current sync status for all data types
@@ -383,6 +398,61 @@ pub enum SyncApiError {
     InvalidResponse,
 }
 
+impl SyncApiError {
+    /// Worth retrying: the same request may succeed a little later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            SyncApiError::Request(e) => e.is_timeout() || e.is_connect(),
+            SyncApiError::ServerError(_) | SyncApiError::RateLimitExceeded => true,
+            _ => false,
+        }
+    }
+
+    /// The server could not be reached at all
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, SyncApiError::Request(e) if e.is_connect())
+    }
+}
+
+/// Delay before retry number `attempt` (0-based)
+///
+/// Exponential backoff with "equal jitter": half of the capped delay is fixed,
+/// the other half random, so devices that failed together do not retry in lockstep.
+pub fn retry_delay(attempt: u32) -> Duration {
+    let cap = RETRY_BASE_MS
+        .saturating_mul(1u64 << attempt.min(16))
+        .min(RETRY_MAX_MS);
+    let half = cap / 2;
+
+    let mut bytes = [0u8; 8];
+    let jitter = match SystemRandom::new().fill(&mut bytes) {
+        Ok(()) => u64::from_le_bytes(bytes) % (half + 1),
+        Err(_) => half / 2,
+    };
+
+    Duration::from_millis(half + jitter)
+}
+
+/// Run a request, retrying transient failures
+async fn with_retry<T, F, Fut>(label: &str, mut request: F) -> Result<T, SyncApiError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, SyncApiError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Err(e) if e.is_transient() && attempt + 1 < MAX_ATTEMPTS => {
+                let delay = retry_delay(attempt);
+                log::warn!("Sync request for {} failed ({}), retrying in {:?}", label, e, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
 /// Handle successful JSON response
 async fn handle_response<T: serde::de::DeserializeOwned>(
     response: reqwest::Response,
@@ -406,7 +476,7 @@ async fn handle_error(response: reqwest::Response) -> SyncApiError {
         StatusCode::FORBIDDEN => SyncApiError::InvalidCredentials,
         StatusCode::CONFLICT => SyncApiError::UserExists,
         StatusCode::TOO_MANY_REQUESTS => SyncApiError::RateLimitExceeded,
-        StatusCode::INTERNAL_SERVER_ERROR => {
+        status if status.is_server_error() => {
             let msg = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
             SyncApiError::ServerError(msg)
         }
@@ -446,4 +516,19 @@ mod tests {
         client.clear_token().await;
         assert!(client.get_token().await.is_none());
     }
+
+    #[test]
+    fn test_retry_delay_is_jittered_and_capped() {
+        for attempt in 0..8 {
+            let cap = (RETRY_BASE_MS << attempt).min(RETRY_MAX_MS);
+            let delay = retry_delay(attempt).as_millis() as u64;
+            assert!(delay >= cap / 2 && delay <= cap, "attempt {}: {}ms", attempt, delay);
+        }
+        assert!(retry_delay(40).as_millis() as u64 <= RETRY_MAX_MS);
+
+        assert!(SyncApiError::ServerError("busy".to_string()).is_transient());
+        assert!(SyncApiError::RateLimitExceeded.is_transient());
+        assert!(!SyncApiError::Unauthorized.is_transient());
+        assert!(!SyncApiError::NetworkError("400 Bad Request".to_string()).is_transient());
+    }
 }
@@ -35,15 +35,30 @@ const DATA_KEY_LEN: usize = 32;
 
 /// Sync data categories
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
 #[serde(rename_all = "lowercase")]
 pub enum SyncDataType {
     Accounts,
     Contacts,
     Preferences,
     Signatures,
+    Templates,
+    Filters,
+    Notes,
 }
 
 impl SyncDataType {
+    /// Every data type, in sync order
+    pub const ALL: [SyncDataType; 7] = [
+        SyncDataType::Accounts,
+        SyncDataType::Contacts,
+        SyncDataType::Preferences,
+        SyncDataType::Signatures,
+        SyncDataType::Templates,
+        SyncDataType::Filters,
+        SyncDataType::Notes,
+    ];
+
     /// Get context string for key derivation
     fn key_context(&self) -> &'static [u8] {
         match self {
@@ -51,6 +66,9 @@ impl SyncDataType {
             SyncDataType::Contacts => b"contacts-v1",
             SyncDataType::Preferences => b"preferences-v1",
             SyncDataType::Signatures => b"signatures-v1",
+            SyncDataType::Templates => b"templates-v1",
+            SyncDataType::Filters => b"filters-v1",
+            SyncDataType::Notes => b"notes-v1",
         }
     }
 
@@ -61,6 +79,9 @@ impl SyncDataType {
             SyncDataType::Contacts => "contacts",
             SyncDataType::Preferences => "preferences",
             SyncDataType::Signatures => "signatures",
+            SyncDataType::Templates => "templates",
+            SyncDataType::Filters => "filters",
+            SyncDataType::Notes => "notes",
         }
     }
 }
@@ -680,6 +701,9 @@ mod tests {
         assert_eq!(SyncDataType::Contacts.as_str(), "contacts");
         assert_eq!(SyncDataType::Preferences.as_str(), "preferences");
         assert_eq!(SyncDataType::Signatures.as_str(), "signatures");
+        assert_eq!(SyncDataType::Templates.as_str(), "templates");
+        assert_eq!(SyncDataType::Filters.as_str(), "filters");
+        assert_eq!(SyncDataType::Notes.as_str(), "notes");
     }
 
     #[test]
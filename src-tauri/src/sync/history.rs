@@ -257,6 +257,15 @@ impl HistoryManager {
         Ok(deleted)
     }
 
+    /// Delete every snapshot of a data type
+    /// Used after key rotation: snapshots under the old key can no longer be restored.
+    pub fn clear_snapshots(&self, data_type: SyncDataType) -> Result<usize, HistoryError> {
+        self.db.execute(
+            "DELETE FROM sync_history WHERE data_type = ?1",
+            rusqlite::params![data_type.as_str()],
+        ).map_err(|e| HistoryError::DatabaseError(e.to_string()))
+    }
+
     /// Get statistics about sync history
     pub fn get_stats(&self) -> Result<HistoryStats, HistoryError> {
         self.db.query_row(
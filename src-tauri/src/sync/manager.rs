@@ -24,12 +24,18 @@ use super::models::{
     ContactSyncData, ContactItem,
     PreferencesSyncData,
     SignatureSyncData,
+    TemplateSyncData,
+    FilterSyncData,
+    NoteSyncData,
     SyncStatus, SyncState,
     ConflictStrategy,
 };
 use super::queue::{QueueManager, QueueItem, QueueStats};
 use super::history::{HistoryManager, SyncOperation};
+use super::rotation::{self, KeyRotationResult, KeyRotationState};
 use crate::db::Database;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -41,6 +47,8 @@ pub struct SyncManager {
     db: Arc<Database>,
     queue_manager: Arc<QueueManager>,
     history_manager: Arc<HistoryManager>,
+    /// OS network state, fed from the frontend's online/offline events
+    online: Arc<AtomicBool>,
 }
 
 impl SyncManager {
@@ -57,6 +65,7 @@ impl SyncManager {
             db,
             queue_manager: Arc::new(queue_manager),
             history_manager: Arc::new(history_manager),
+            online: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -73,6 +82,7 @@ impl SyncManager {
             db,
             queue_manager: Arc::new(queue_manager),
             history_manager: Arc::new(history_manager),
+            online: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -164,69 +174,98 @@ impl SyncManager {
     // ========================================================================
 
     /// Perform full sync for all enabled data types (bidirectional)
+    ///
+    /// Each data type is synced independently: a failure is recorded in the
+    /// result and the remaining types still run. Types that failed for a
+    /// transient reason are remembered and can be retried with `retry_deferred`.
     pub async fn sync_all(
         &self,
         master_password: &str,
     ) -> Result<SyncResult, SyncManagerError> {
         let config = self.config.read().await.clone();
+        let types: Vec<SyncDataType> = SyncDataType::ALL
+            .into_iter()
+            .filter(|data_type| config.syncs(*data_type))
+            .collect();
+
+        self.sync_types(&types, master_password).await
+    }
+
+    /// Sync only the data types whose last attempt failed transiently
+    pub async fn retry_deferred(
+        &self,
+        master_password: &str,
+    ) -> Result<SyncResult, SyncManagerError> {
+        let types = self.queue_manager.deferred_types()
+            .map_err(|e| SyncManagerError::QueueError(e.to_string()))?;
+
+        self.sync_types(&types, master_password).await
+    }
+
+    /// Whether the network is believed to be available
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::SeqCst)
+    }
+
+    /// Record the OS network state; while offline no request is attempted
+    pub fn set_online(&self, online: bool) {
+        self.online.store(online, Ordering::SeqCst);
+    }
 
-        if !config.enabled {
+    async fn sync_types(
+        &self,
+        types: &[SyncDataType],
+        master_password: &str,
+    ) -> Result<SyncResult, SyncManagerError> {
+        if !self.config.read().await.enabled {
             return Err(SyncManagerError::SyncDisabled);
         }
 
-        let mut result = SyncResult::default();
+        // Server data may be split between two keys until the rotation finishes
+        if rotation::load_state(&self.db)
+            .map_err(|e| SyncManagerError::DatabaseError(e.to_string()))?
+            .is_some()
+        {
+            return Err(SyncManagerError::KeyRotationPending);
+        }
+
+        let mut result = SyncResult {
+            offline: !self.is_online(),
+            ..Default::default()
+        };
         let mut all_conflicts = Vec::new();
 
-        // Sync each data type with bidirectional conflict detection
-        if config.sync_accounts {
-            match self.sync_accounts_bidirectional(master_password).await {
-                Ok(conflicts) => {
-                    if let Some(mut conflicts) = conflicts {
-                        all_conflicts.append(&mut conflicts);
-                    } else {
-                        result.accounts_synced = true;
-                    }
-                }
-                Err(e) => result.errors.push(format!("Accounts: {}", e)),
+        for &data_type in types {
+            // Once the server is unreachable, don't spend retries on the remaining types
+            if result.offline {
+                self.defer(data_type);
+                result.record(data_type, TypeSyncStatus::Deferred, Some("Offline".to_string()));
+                continue;
             }
-        }
 
-        if config.sync_contacts {
-            match self.sync_contacts_bidirectional(master_password).await {
-                Ok(conflicts) => {
-                    if let Some(mut conflicts) = conflicts {
-                        all_conflicts.append(&mut conflicts);
-                    } else {
-                        result.contacts_synced = true;
-                    }
+            match self.sync_data_type(data_type, master_password).await {
+                Ok(Some(mut conflicts)) => {
+                    all_conflicts.append(&mut conflicts);
+                    result.record(data_type, TypeSyncStatus::Conflict, None);
                 }
-                Err(e) => result.errors.push(format!("Contacts: {}", e)),
-            }
-        }
-
-        if config.sync_preferences {
-            match self.sync_preferences_bidirectional(master_password).await {
-                Ok(conflicts) => {
-                    if let Some(mut conflicts) = conflicts {
-                        all_conflicts.append(&mut conflicts);
-                    } else {
-                        result.preferences_synced = true;
+                Ok(None) => {
+                    if let Err(e) = self.queue_manager.clear_deferred(data_type) {
+                        log::warn!("Failed to clear deferred {}: {}", data_type.as_str(), e);
                     }
+                    result.record(data_type, TypeSyncStatus::Synced, None);
                 }
-                Err(e) => result.errors.push(format!("Preferences: {}", e)),
-            }
-        }
+                Err(e) => {
+                    log::warn!("Sync of {} failed: {}", data_type.as_str(), e);
+                    result.errors.push(format!("{}: {}", data_type.as_str(), e));
 
-        if config.sync_signatures {
-            match self.sync_signatures_bidirectional(master_password).await {
-                Ok(conflicts) => {
-                    if let Some(mut conflicts) = conflicts {
-                        all_conflicts.append(&mut conflicts);
+                    if e.is_transient() {
+                        self.defer(data_type);
+                        result.offline = e.is_unreachable();
+                        result.record(data_type, TypeSyncStatus::Deferred, Some(e.to_string()));
                     } else {
-                        result.signatures_synced = true;
+                        result.record(data_type, TypeSyncStatus::Failed, Some(e.to_string()));
                     }
                 }
-                Err(e) => result.errors.push(format!("Signatures: {}", e)),
             }
         }
 
@@ -236,12 +275,38 @@ impl SyncManager {
         }
 
         // Update last sync timestamp
-        let mut config = self.config.write().await;
-        config.last_sync_at = Some(chrono::Utc::now());
+        if result.has_any_success() {
+            let mut config = self.config.write().await;
+            config.last_sync_at = Some(chrono::Utc::now());
+        }
 
         Ok(result)
     }
 
+    /// Bidirectional sync of a single data type
+    async fn sync_data_type(
+        &self,
+        data_type: SyncDataType,
+        master_password: &str,
+    ) -> Result<Option<Vec<super::models::ConflictInfo>>, SyncManagerError> {
+        match data_type {
+            SyncDataType::Accounts => self.sync_accounts_bidirectional(master_password).await,
+            SyncDataType::Contacts => self.sync_contacts_bidirectional(master_password).await,
+            SyncDataType::Preferences => self.sync_preferences_bidirectional(master_password).await,
+            SyncDataType::Signatures => self.sync_signatures_bidirectional(master_password).await,
+            SyncDataType::Templates => self.sync_templates_bidirectional(master_password).await,
+            SyncDataType::Filters => self.sync_filters_bidirectional(master_password).await,
+            SyncDataType::Notes => self.sync_notes_bidirectional(master_password).await,
+        }
+    }
+
+    /// Remember a data type for the next retry
+    fn defer(&self, data_type: SyncDataType) {
+        if let Err(e) = self.queue_manager.defer_type(data_type) {
+            log::error!("Failed to defer {}: {}", data_type.as_str(), e);
+        }
+    }
+
     /// Sync accounts data (DELTA SYNC - only changed data)
     async fn sync_accounts(
         &self,
@@ -927,6 +992,125 @@ impl SyncManager {
         Ok(None) // No conflicts (all resolved)
     }
 
+    /// Bidirectional sync for templates with per-template conflict detection
+    async fn sync_templates_bidirectional(
+        &self,
+        master_password: &str,
+    ) -> Result<Option<Vec<super::models::ConflictInfo>>, SyncManagerError> {
+        log::info!("Starting bidirectional templates sync");
+
+        // 1. Load local templates
+        let items = crate::templates::collect_sync_items(&self.db)
+            .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to load templates: {}", e)))?;
+        let local_data = TemplateSyncData::new(items);
+
+        // 2. Download server data
+        let server_data: Option<TemplateSyncData> = self.download(SyncDataType::Templates, master_password).await?;
+
+        let Some(server_data) = server_data else {
+            log::info!("Server has no template data, uploading local");
+            let version = self.upload(SyncDataType::Templates, &local_data, master_password).await?;
+            log::info!("Templates synced successfully (version: {})", version);
+            return Ok(None);
+        };
+
+        // 3. Detect conflicts before merging
+        let conflicts = self.detect_templates_conflicts(&local_data, &server_data);
+        if !conflicts.is_empty() {
+            log::warn!("Template conflicts detected: {}", conflicts.len());
+            return Ok(Some(conflicts));
+        }
+
+        // 4. Merge per template and store the ones the server won locally
+        let (merged, server_wins) = self.merge_templates(local_data, server_data);
+        if !server_wins.is_empty() {
+            self.apply_templates_to_db(&TemplateSyncData::new(server_wins)).await?;
+        }
+
+        // 5. Upload merged data
+        let version = self.upload(SyncDataType::Templates, &merged, master_password).await?;
+        log::info!("Templates synced successfully (version: {})", version);
+
+        Ok(None)
+    }
+
+    /// Bidirectional sync for filters with per-rule conflict detection
+    async fn sync_filters_bidirectional(
+        &self,
+        master_password: &str,
+    ) -> Result<Option<Vec<super::models::ConflictInfo>>, SyncManagerError> {
+        log::info!("Starting bidirectional filters sync");
+
+        // 1. Load local filters
+        let items = crate::filters::portable::collect_sync_items(&self.db)
+            .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to load filters: {}", e)))?;
+        let local_data = FilterSyncData::new(items);
+
+        // 2. Download server data
+        let server_data: Option<FilterSyncData> = self.download(SyncDataType::Filters, master_password).await?;
+
+        let Some(server_data) = server_data else {
+            log::info!("Server has no filter data, uploading local");
+            let version = self.upload(SyncDataType::Filters, &local_data, master_password).await?;
+            log::info!("Filters synced successfully (version: {})", version);
+            return Ok(None);
+        };
+
+        // 3. Detect conflicts before merging
+        let conflicts = self.detect_filters_conflicts(&local_data, &server_data);
+        if !conflicts.is_empty() {
+            log::warn!("Filter conflicts detected: {}", conflicts.len());
+            return Ok(Some(conflicts));
+        }
+
+        // 4. Merge per rule and store the ones the server won locally
+        let (merged, server_wins) = self.merge_filters(local_data, server_data);
+        if !server_wins.is_empty() {
+            self.apply_filters_to_db(&FilterSyncData::new(server_wins)).await?;
+        }
+
+        // 5. Upload merged data
+        let version = self.upload(SyncDataType::Filters, &merged, master_password).await?;
+        log::info!("Filters synced successfully (version: {})", version);
+
+        Ok(None)
+    }
+
+    /// Bidirectional sync for email notes (per-note Last-Write-Wins, no prompts)
+    async fn sync_notes_bidirectional(
+        &self,
+        master_password: &str,
+    ) -> Result<Option<Vec<super::models::ConflictInfo>>, SyncManagerError> {
+        log::info!("Starting bidirectional notes sync");
+
+        // 1. Load local notes
+        let items = crate::notes::collect_sync_items(&self.db)
+            .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to load notes: {}", e)))?;
+        let local_data = NoteSyncData::new(items);
+
+        // 2. Download server data
+        let server_data: Option<NoteSyncData> = self.download(SyncDataType::Notes, master_password).await?;
+
+        let Some(server_data) = server_data else {
+            log::info!("Server has no note data, uploading local");
+            let version = self.upload(SyncDataType::Notes, &local_data, master_password).await?;
+            log::info!("Notes synced successfully (version: {})", version);
+            return Ok(None);
+        };
+
+        // 3. Merge per note and store the ones the server won locally
+        let (merged, server_wins) = self.merge_notes(local_data, server_data);
+        if !server_wins.is_empty() {
+            self.apply_notes_to_db(&NoteSyncData::new(server_wins)).await?;
+        }
+
+        // 4. Upload merged data
+        let version = self.upload(SyncDataType::Notes, &merged, master_password).await?;
+        log::info!("Notes synced successfully (version: {})", version);
+
+        Ok(None)
+    }
+
     /// Download and decrypt data from server
     async fn download<T: for<'de> serde::Deserialize<'de>>(
         &self,
@@ -938,15 +1122,25 @@ impl SyncManager {
         let salt = config.master_key_salt.as_ref()
             .ok_or(SyncManagerError::NoMasterKeySalt)?;
 
-        let salt_bytes: [u8; 32] = hex::decode(salt)
-            .map_err(|_| SyncManagerError::InvalidSalt)?
-            .try_into()
-            .map_err(|_| SyncManagerError::InvalidSalt)?;
-
-        let _device_id = config.device_id.clone();
+        let salt_bytes = decode_salt(salt)?;
 
         drop(config);
 
+        // Derive master key
+        let master_key = derive_sync_master_key(master_password, &salt_bytes)
+            .map_err(|_| SyncManagerError::DecryptionFailed)?;
+
+        self.download_with_key(data_type, &master_key).await
+    }
+
+    /// Download and decrypt data from server with an already derived master key
+    async fn download_with_key<T: for<'de> serde::Deserialize<'de>>(
+        &self,
+        data_type: SyncDataType,
+        master_key: &[u8; 32],
+    ) -> Result<Option<T>, SyncManagerError> {
+        let _device_id = self.config.read().await.device_id.clone();
+
         // Download
         let response = self.api_client.download_data(
             data_type.as_str(),
@@ -982,10 +1176,6 @@ impl SyncManager {
         let mut nonce_array = [0u8; 12];
         nonce_array.copy_from_slice(nonce_slice);
 
-        // Derive master key
-        let master_key = derive_sync_master_key(master_password, &salt_bytes)
-            .map_err(|_| SyncManagerError::DecryptionFailed)?;
-
         // Reconstruct payload for decryption
         use super::crypto::{SyncPayload, decrypt_sync_data, compute_sha256};
         let payload = SyncPayload {
@@ -999,12 +1189,142 @@ impl SyncManager {
         };
 
         // Decrypt
-        let decrypted = decrypt_sync_data(&payload, &master_key)
+        let decrypted = decrypt_sync_data(&payload, master_key)
             .map_err(|_| SyncManagerError::DecryptionFailed)?;
 
         Ok(Some(decrypted))
     }
 
+    // ========================================================================
+    // Master Key Rotation
+    // ========================================================================
+
+    /// Change the master password, re-encrypting every server payload
+    ///
+    /// Data types are rotated one at a time and progress is persisted, so an
+    /// interrupted rotation resumes when called again with the same passwords.
+    /// Local history snapshots and queued uploads of each type are dropped,
+    /// since they are encrypted with the old key. Regular sync is refused
+    /// until the rotation has finished.
+    pub async fn rotate_master_password(
+        &self,
+        old_master_password: &str,
+        new_master_password: &str,
+    ) -> Result<KeyRotationResult, SyncManagerError> {
+        if new_master_password.is_empty() || new_master_password == old_master_password {
+            return Err(SyncManagerError::KeyRotation(
+                "New master password must be set and differ from the current one".to_string(),
+            ));
+        }
+
+        let db_error = |e: crate::db::DbError| SyncManagerError::DatabaseError(e.to_string());
+
+        let (mut state, resumed) = match rotation::load_state(&self.db).map_err(db_error)? {
+            Some(state) => (state, true),
+            None => {
+                let old_salt = self.config.read().await.master_key_salt.clone()
+                    .ok_or(SyncManagerError::NoMasterKeySalt)?;
+                let new_salt = generate_random_salt()
+                    .map_err(SyncManagerError::CryptoError)?;
+                (KeyRotationState::new(old_salt, hex::encode(new_salt)), false)
+            }
+        };
+
+        let old_key = derive_sync_master_key(old_master_password, &decode_salt(&state.old_salt)?)
+            .map_err(SyncManagerError::EncryptionFailed)?;
+        let new_key = derive_sync_master_key(new_master_password, &decode_salt(&state.new_salt)?)
+            .map_err(SyncManagerError::EncryptionFailed)?;
+
+        // A resumed rotation must continue with the same new password
+        if let Some(&done) = state.completed.first() {
+            if let Err(e) = self.download_with_key::<serde_json::Value>(done, &new_key).await {
+                return Err(match e {
+                    SyncManagerError::DecryptionFailed => SyncManagerError::KeyRotation(
+                        "New master password does not match the interrupted rotation".to_string(),
+                    ),
+                    other => other,
+                });
+            }
+        }
+
+        rotation::save_state(&self.db, &state).map_err(db_error)?;
+        log::info!(
+            "{} master key rotation ({} data types pending)",
+            if resumed { "Resuming" } else { "Starting" },
+            state.pending().len()
+        );
+
+        let mut result = KeyRotationResult { resumed, ..Default::default() };
+        let device_id = self.config.read().await.device_id.clone();
+
+        for data_type in state.pending() {
+            let data = match self.download_with_key::<serde_json::Value>(data_type, &old_key).await {
+                Ok(data) => data,
+                Err(SyncManagerError::DecryptionFailed) if resumed => {
+                    // Uploaded just before the interruption, progress not yet saved
+                    match self.download_with_key::<serde_json::Value>(data_type, &new_key).await {
+                        Ok(_) => {
+                            log::info!("{} was already re-encrypted", data_type.as_str());
+                            state.mark_completed(data_type);
+                            rotation::save_state(&self.db, &state).map_err(db_error)?;
+                            continue;
+                        }
+                        Err(SyncManagerError::DecryptionFailed) => return Err(SyncManagerError::InvalidMasterPassword),
+                        Err(e) => return Err(e),
+                    }
+                }
+                Err(SyncManagerError::DecryptionFailed) => return Err(SyncManagerError::InvalidMasterPassword),
+                Err(e) => return Err(e),
+            };
+
+            // Queued uploads and snapshots use the old key
+            self.queue_manager.clear_data_type(data_type)
+                .map_err(|e| SyncManagerError::QueueError(e.to_string()))?;
+            self.history_manager.clear_snapshots(data_type)
+                .map_err(|e| SyncManagerError::HistoryError(e.to_string()))?;
+
+            match data {
+                Some(data) => {
+                    let payload = encrypt_sync_data(&data, &new_key, data_type, &device_id)
+                        .map_err(SyncManagerError::EncryptionFailed)?;
+                    let compressed = gzip_compress(&payload.encrypted_data)
+                        .map_err(|e| SyncManagerError::EncryptionFailed(format!("Compression failed: {}", e)))?;
+
+                    // No offline queue here: a failed upload stops the rotation for a later resume
+                    let response = self.api_client.upload_data(data_type.as_str(), UploadRequest {
+                        encrypted_data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &compressed),
+                        version: payload.version as i64,
+                    }).await?;
+
+                    if let Err(e) = self.history_manager.record_snapshot(
+                        data_type,
+                        response.version,
+                        &payload.encrypted_data,
+                        &device_id,
+                        SyncOperation::Push,
+                        extract_item_count(&data),
+                    ) {
+                        log::warn!("Failed to record snapshot in history: {}", e);
+                    }
+
+                    log::info!("Re-encrypted {} (version: {})", data_type.as_str(), response.version);
+                    result.rotated.push(data_type.as_str().to_string());
+                }
+                None => result.empty.push(data_type.as_str().to_string()),
+            }
+
+            state.mark_completed(data_type);
+            rotation::save_state(&self.db, &state).map_err(db_error)?;
+        }
+
+        // Switch to the new key only once everything is re-encrypted
+        self.config.write().await.master_key_salt = Some(state.new_salt.clone());
+        rotation::clear_state(&self.db).map_err(db_error)?;
+
+        log::info!("Master key rotation completed ({} data types re-encrypted)", result.rotated.len());
+        Ok(result)
+    }
+
     // ========================================================================
     // Device Management
     // ========================================================================
@@ -1041,7 +1361,7 @@ impl SyncManager {
         // Placeholder - would fetch from server
         let config = self.config.read().await;
 
-        let statuses = vec![
+        let mut statuses = vec![
             SyncStatus {
                 data_type: "accounts".to_string(),
                 version: 1,
@@ -1070,8 +1390,38 @@ impl SyncManager {
                 device_id: config.device_id.clone(),
                 status: SyncState::Idle,
             },
+            SyncStatus {
+                data_type: "templates".to_string(),
+                version: 1,
+                last_sync_at: config.last_sync_at,
+                device_id: config.device_id.clone(),
+                status: SyncState::Idle,
+            },
+            SyncStatus {
+                data_type: "filters".to_string(),
+                version: 1,
+                last_sync_at: config.last_sync_at,
+                device_id: config.device_id.clone(),
+                status: SyncState::Idle,
+            },
+            SyncStatus {
+                data_type: "notes".to_string(),
+                version: 1,
+                last_sync_at: config.last_sync_at,
+                device_id: config.device_id.clone(),
+                status: SyncState::Idle,
+            },
         ];
 
+        // Types waiting for a retry after a failed sync
+        let deferred = self.queue_manager.deferred_types()
+            .map_err(|e| SyncManagerError::QueueError(e.to_string()))?;
+        for status in &mut statuses {
+            if deferred.iter().any(|t| t.as_str() == status.data_type) {
+                status.status = SyncState::Error;
+            }
+        }
+
         Ok(statuses)
     }
 
@@ -1113,6 +1463,9 @@ impl SyncManager {
                 "contacts" => SyncDataType::Contacts,
                 "preferences" => SyncDataType::Preferences,
                 "signatures" => SyncDataType::Signatures,
+                "templates" => SyncDataType::Templates,
+                "filters" => SyncDataType::Filters,
+                "notes" => SyncDataType::Notes,
                 _ => {
                     log::warn!("Unknown data type in queue: {}", item.data_type);
                     continue;
@@ -1267,6 +1620,24 @@ impl SyncManager {
                     .map_err(|_| SyncManagerError::DecryptionFailed)?;
                 self.apply_signatures_rollback(signatures).await?;
             }
+            SyncDataType::Templates => {
+                let templates: TemplateSyncData = decrypt_sync_data(&payload, &master_key)
+                    .map_err(|_| SyncManagerError::DecryptionFailed)?;
+                log::info!("Applying templates rollback with {} templates", templates.templates.len());
+                self.apply_templates_to_db(&templates).await?;
+            }
+            SyncDataType::Filters => {
+                let filters: FilterSyncData = decrypt_sync_data(&payload, &master_key)
+                    .map_err(|_| SyncManagerError::DecryptionFailed)?;
+                log::info!("Applying filters rollback with {} filters", filters.filters.len());
+                self.apply_filters_to_db(&filters).await?;
+            }
+            SyncDataType::Notes => {
+                let notes: NoteSyncData = decrypt_sync_data(&payload, &master_key)
+                    .map_err(|_| SyncManagerError::DecryptionFailed)?;
+                log::info!("Applying notes rollback with {} notes", notes.notes.len());
+                self.apply_notes_to_db(&notes).await?;
+            }
         }
 
         log::info!("Rollback completed successfully for {}", data_type.as_str());
@@ -1719,6 +2090,231 @@ impl SyncManager {
         conflicts
     }
 
+    /// Detect conflicts between local and server template data
+    ///
+    /// A template edited on both sides is only a conflict when the edit times
+    /// cannot order the changes; otherwise LWW picks the newer one.
+    fn detect_templates_conflicts(
+        &self,
+        local: &TemplateSyncData,
+        server: &TemplateSyncData,
+    ) -> Vec<super::models::ConflictInfo> {
+        let mut conflicts = Vec::new();
+
+        for local_item in &local.templates {
+            let Some(server_item) = server.templates.iter().find(|t| {
+                t.name == local_item.name && t.account_email == local_item.account_email
+            }) else {
+                continue;
+            };
+
+            if local_item.same_content(server_item) {
+                continue;
+            }
+
+            let reason = match (local_item.updated_at, server_item.updated_at) {
+                (Some(local_time), Some(server_time)) if local_time == server_time => {
+                    "was changed on two devices at the same time"
+                }
+                (Some(_), Some(_)) => continue,
+                _ => "has no timestamp information for conflict resolution",
+            };
+
+            log::warn!("Template conflict detected for '{}'", local_item.name);
+
+            let field_changes = [
+                ("subject_template", local_item.subject_template != server_item.subject_template),
+                ("body_html_template", local_item.body_html_template != server_item.body_html_template),
+                ("body_text_template", local_item.body_text_template != server_item.body_text_template),
+                ("description", local_item.description != server_item.description),
+                ("category", local_item.category != server_item.category),
+                ("tags", local_item.tags != server_item.tags),
+                ("is_enabled", local_item.is_enabled != server_item.is_enabled),
+                ("is_favorite", local_item.is_favorite != server_item.is_favorite),
+            ]
+            .iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(field, _)| field.to_string())
+            .collect();
+
+            conflicts.push(super::models::ConflictInfo {
+                data_type: "templates".to_string(),
+                local_version: 0,
+                server_version: 0,
+                local_updated_at: local_item.updated_at,
+                server_updated_at: server_item.updated_at,
+                strategy: super::models::ConflictStrategy::Manual,
+                conflict_details: format!("Template '{}' {}", local_item.name, reason),
+                local_data: serde_json::to_value(local_item).unwrap_or_default(),
+                server_data: serde_json::to_value(server_item).unwrap_or_default(),
+                field_changes: Some(field_changes),
+            });
+        }
+
+        conflicts
+    }
+
+    /// Merge templates per item using Last-Write-Wins
+    /// Returns the merged set and the server templates that won over local ones.
+    fn merge_templates(
+        &self,
+        local: TemplateSyncData,
+        server: TemplateSyncData,
+    ) -> (TemplateSyncData, Vec<crate::templates::TemplateItem>) {
+        let mut merged = local.templates;
+        let mut server_wins = Vec::new();
+
+        for server_item in server.templates {
+            let existing = merged.iter_mut().find(|t| {
+                t.name == server_item.name && t.account_email == server_item.account_email
+            });
+
+            match existing {
+                Some(local_item) => {
+                    if !local_item.same_content(&server_item) && server_item.updated_at > local_item.updated_at {
+                        *local_item = server_item.clone();
+                        server_wins.push(server_item);
+                    }
+                }
+                None => {
+                    merged.push(server_item.clone());
+                    server_wins.push(server_item);
+                }
+            }
+        }
+
+        (TemplateSyncData::new(merged), server_wins)
+    }
+
+    /// Detect conflicts between local and server filter data
+    ///
+    /// Like templates, a rule changed on both sides only conflicts when the
+    /// edit times cannot order the changes.
+    fn detect_filters_conflicts(
+        &self,
+        local: &FilterSyncData,
+        server: &FilterSyncData,
+    ) -> Vec<super::models::ConflictInfo> {
+        let mut conflicts = Vec::new();
+
+        for local_item in &local.filters {
+            let Some(server_item) = server.filters.iter().find(|f| {
+                f.name == local_item.name && f.account_email == local_item.account_email
+            }) else {
+                continue;
+            };
+
+            if local_item.same_content(server_item) {
+                continue;
+            }
+
+            let reason = match (local_item.updated_at, server_item.updated_at) {
+                (Some(local_time), Some(server_time)) if local_time == server_time => {
+                    "was changed on two devices at the same time"
+                }
+                (Some(_), Some(_)) => continue,
+                _ => "has no timestamp information for conflict resolution",
+            };
+
+            log::warn!("Filter conflict detected for '{}' ({})", local_item.name, local_item.account_email);
+
+            let field_changes = [
+                ("description", local_item.description != server_item.description),
+                ("is_enabled", local_item.is_enabled != server_item.is_enabled),
+                ("priority", local_item.priority != server_item.priority),
+                ("match_logic", local_item.match_logic != server_item.match_logic),
+                ("conditions", local_item.conditions != server_item.conditions),
+                ("actions", local_item.actions != server_item.actions),
+            ]
+            .iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(field, _)| field.to_string())
+            .collect();
+
+            conflicts.push(super::models::ConflictInfo {
+                data_type: "filters".to_string(),
+                local_version: 0,
+                server_version: 0,
+                local_updated_at: local_item.updated_at,
+                server_updated_at: server_item.updated_at,
+                strategy: super::models::ConflictStrategy::Manual,
+                conflict_details: format!(
+                    "Filter '{}' for {} {}",
+                    local_item.name, local_item.account_email, reason
+                ),
+                local_data: serde_json::to_value(local_item).unwrap_or_default(),
+                server_data: serde_json::to_value(server_item).unwrap_or_default(),
+                field_changes: Some(field_changes),
+            });
+        }
+
+        conflicts
+    }
+
+    /// Merge filters per rule using Last-Write-Wins
+    /// Returns the merged set and the server rules that won over local ones.
+    fn merge_filters(
+        &self,
+        local: FilterSyncData,
+        server: FilterSyncData,
+    ) -> (FilterSyncData, Vec<crate::filters::portable::FilterItem>) {
+        let mut merged = local.filters;
+        let mut server_wins = Vec::new();
+
+        for server_item in server.filters {
+            let existing = merged.iter_mut().find(|f| {
+                f.name == server_item.name && f.account_email == server_item.account_email
+            });
+
+            match existing {
+                Some(local_item) => {
+                    if !local_item.same_content(&server_item) && server_item.updated_at > local_item.updated_at {
+                        *local_item = server_item.clone();
+                        server_wins.push(server_item);
+                    }
+                }
+                None => {
+                    merged.push(server_item.clone());
+                    server_wins.push(server_item);
+                }
+            }
+        }
+
+        (FilterSyncData::new(merged), server_wins)
+    }
+
+    /// Merge notes per message using Last-Write-Wins
+    /// Returns the merged set and the server notes that won over local ones.
+    fn merge_notes(
+        &self,
+        local: NoteSyncData,
+        server: NoteSyncData,
+    ) -> (NoteSyncData, Vec<crate::notes::NoteItem>) {
+        let mut merged = local.notes;
+        let mut server_wins = Vec::new();
+
+        for server_item in server.notes {
+            let existing = merged.iter_mut().find(|n| {
+                n.message_id == server_item.message_id && n.account_email == server_item.account_email
+            });
+
+            match existing {
+                Some(local_item) => {
+                    if server_item.updated_at > local_item.updated_at {
+                        *local_item = server_item.clone();
+                        server_wins.push(server_item);
+                    }
+                }
+                None => {
+                    merged.push(server_item.clone());
+                    server_wins.push(server_item);
+                }
+            }
+        }
+
+        (NoteSyncData::new(merged), server_wins)
+    }
+
     /// Merge contacts using Last-Write-Wins (LWW) strategy
     fn merge_contacts(
         &self,
@@ -1944,6 +2540,27 @@ impl SyncManager {
                 self.upload(SyncDataType::Signatures, &local_data, master_password).await?;
                 log::info!("Signatures uploaded successfully");
             }
+            SyncDataType::Templates => {
+                let items = crate::templates::collect_sync_items(&self.db)
+                    .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to load templates: {}", e)))?;
+                let local_data = TemplateSyncData::new(items);
+                self.upload(SyncDataType::Templates, &local_data, master_password).await?;
+                log::info!("Templates uploaded successfully");
+            }
+            SyncDataType::Filters => {
+                let items = crate::filters::portable::collect_sync_items(&self.db)
+                    .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to load filters: {}", e)))?;
+                let local_data = FilterSyncData::new(items);
+                self.upload(SyncDataType::Filters, &local_data, master_password).await?;
+                log::info!("Filters uploaded successfully");
+            }
+            SyncDataType::Notes => {
+                let items = crate::notes::collect_sync_items(&self.db)
+                    .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to load notes: {}", e)))?;
+                let local_data = NoteSyncData::new(items);
+                self.upload(SyncDataType::Notes, &local_data, master_password).await?;
+                log::info!("Notes uploaded successfully");
+            }
         }
 
         Ok(())
@@ -1998,6 +2615,36 @@ impl SyncManager {
                     log::warn!("No server data for signatures");
                 }
             }
+            SyncDataType::Templates => {
+                let server_data: Option<TemplateSyncData> = self.download(data_type, master_password).await?;
+
+                if let Some(data) = server_data {
+                    self.apply_templates_to_db(&data).await?;
+                    log::info!("Templates applied to database successfully");
+                } else {
+                    log::warn!("No server data for templates");
+                }
+            }
+            SyncDataType::Filters => {
+                let server_data: Option<FilterSyncData> = self.download(data_type, master_password).await?;
+
+                if let Some(data) = server_data {
+                    self.apply_filters_to_db(&data).await?;
+                    log::info!("Filters applied to database successfully");
+                } else {
+                    log::warn!("No server data for filters");
+                }
+            }
+            SyncDataType::Notes => {
+                let server_data: Option<NoteSyncData> = self.download(data_type, master_password).await?;
+
+                if let Some(data) = server_data {
+                    self.apply_notes_to_db(&data).await?;
+                    log::info!("Notes applied to database successfully");
+                } else {
+                    log::warn!("No server data for notes");
+                }
+            }
         }
 
         Ok(())
@@ -2203,6 +2850,50 @@ impl SyncManager {
                    success_count, skip_count);
         Ok(())
     }
+
+    /// Apply templates from server to local database
+    /// Existing templates with the same name are overwritten; local-only ones are kept.
+    async fn apply_templates_to_db(
+        &self,
+        data: &TemplateSyncData,
+    ) -> Result<(), SyncManagerError> {
+        log::info!("Applying {} templates from server to local DB", data.templates.len());
+
+        let changed = crate::templates::apply_sync_items(&self.db, &data.templates)
+            .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to apply templates: {}", e)))?;
+
+        log::info!("✓ Templates applied: {} added or updated", changed);
+        Ok(())
+    }
+
+    /// Apply filters from server to local database
+    /// Rules whose target folder does not exist on this machine are skipped.
+    async fn apply_filters_to_db(
+        &self,
+        data: &FilterSyncData,
+    ) -> Result<(), SyncManagerError> {
+        log::info!("Applying {} filters from server to local DB", data.filters.len());
+
+        let changed = crate::filters::portable::apply_sync_items(&self.db, &data.filters)
+            .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to apply filters: {}", e)))?;
+
+        log::info!("✓ Filters applied: {} added or updated", changed);
+        Ok(())
+    }
+
+    /// Apply email notes from server to local database
+    async fn apply_notes_to_db(
+        &self,
+        data: &NoteSyncData,
+    ) -> Result<(), SyncManagerError> {
+        log::info!("Applying {} notes from server to local DB", data.notes.len());
+
+        let changed = crate::notes::apply_sync_items(&self.db, &data.notes)
+            .map_err(|e| SyncManagerError::DatabaseError(format!("Failed to apply notes: {}", e)))?;
+
+        log::info!("✓ Notes applied: {} added, updated or deleted", changed);
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -2254,6 +2945,14 @@ enum ConflictResolution<T> {
     RequiresManualResolution(super::models::ConflictInfo),
 }
 
+/// Decode a hex master key salt
+fn decode_salt(salt: &str) -> Result<[u8; 32], SyncManagerError> {
+    hex::decode(salt)
+        .map_err(|_| SyncManagerError::InvalidSalt)?
+        .try_into()
+        .map_err(|_| SyncManagerError::InvalidSalt)
+}
+
 /// Extract item count from sync data
 fn extract_item_count<T: serde::Serialize>(data: &T) -> i32 {
     // Serialize to JSON and try to count items
@@ -2263,7 +2962,7 @@ fn extract_item_count<T: serde::Serialize>(data: &T) -> i32 {
             if let Some(obj) = json_val.as_object() {
                 for (key, value) in obj {
                     if let Some(arr) = value.as_array() {
-                        if key == "accounts" || key == "contacts" || key == "signatures" {
+                        if key == "accounts" || key == "contacts" || key == "signatures" || key == "templates" || key == "filters" || key == "notes" {
                             return arr.len() as i32;
                         }
                     }
@@ -2285,19 +2984,74 @@ pub struct ProcessQueueResult {
     pub failed: i32,
 }
 
+/// Outcome of syncing one data type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "lowercase")]
+pub enum TypeSyncStatus {
+    Synced,
+    /// Needs user resolution
+    Conflict,
+    /// Permanent error (bad master password, rejected data, ...)
+    Failed,
+    /// Transient error or offline; will be retried
+    Deferred,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+pub struct TypeSyncResult {
+    pub data_type: SyncDataType,
+    pub status: TypeSyncStatus,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SyncResult {
     pub accounts_synced: bool,
     pub contacts_synced: bool,
     pub preferences_synced: bool,
     pub signatures_synced: bool,
+    pub templates_synced: bool,
+    pub filters_synced: bool,
+    pub notes_synced: bool,
     pub errors: Vec<String>,
 
+    /// Per data type outcome, in sync order
+    pub types: Vec<TypeSyncResult>,
+
+    /// The network was (or became) unavailable during this sync
+    pub offline: bool,
+
     /// Detected conflicts requiring user resolution
     pub conflicts: Option<Vec<super::models::ConflictInfo>>,
 }
 
 impl SyncResult {
+    fn record(&mut self, data_type: SyncDataType, status: TypeSyncStatus, error: Option<String>) {
+        if status == TypeSyncStatus::Synced {
+            match data_type {
+                SyncDataType::Accounts => self.accounts_synced = true,
+                SyncDataType::Contacts => self.contacts_synced = true,
+                SyncDataType::Preferences => self.preferences_synced = true,
+                SyncDataType::Signatures => self.signatures_synced = true,
+                SyncDataType::Templates => self.templates_synced = true,
+                SyncDataType::Filters => self.filters_synced = true,
+                SyncDataType::Notes => self.notes_synced = true,
+            }
+        }
+        self.types.push(TypeSyncResult { data_type, status, error });
+    }
+
+    /// Data types left for a later retry
+    pub fn deferred(&self) -> Vec<SyncDataType> {
+        self.types
+            .iter()
+            .filter(|t| t.status == TypeSyncStatus::Deferred)
+            .map(|t| t.data_type)
+            .collect()
+    }
+
     pub fn is_success(&self) -> bool {
         self.errors.is_empty()
     }
@@ -2307,6 +3061,9 @@ impl SyncResult {
             || self.contacts_synced
             || self.preferences_synced
             || self.signatures_synced
+            || self.templates_synced
+            || self.filters_synced
+            || self.notes_synced
     }
 
     /// Check if there are any unresolved conflicts
@@ -2353,6 +3110,27 @@ pub enum SyncManagerError {
 
     #[error("Invalid conflict resolution strategy")]
     InvalidConflictStrategy,
+
+    #[error("Current master password is incorrect")]
+    InvalidMasterPassword,
+
+    #[error("Key rotation failed: {0}")]
+    KeyRotation(String),
+
+    #[error("A master password change is in progress; finish it before syncing")]
+    KeyRotationPending,
+}
+
+impl SyncManagerError {
+    /// Retrying later may succeed
+    pub fn is_transient(&self) -> bool {
+        matches!(self, SyncManagerError::ApiError(e) if e.is_transient())
+    }
+
+    /// The sync server could not be reached
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, SyncManagerError::ApiError(e) if e.is_unreachable())
+    }
 }
 
 // ============================================================================
@@ -2404,6 +3182,54 @@ mod tests {
         assert!(config.user_id.is_none());
     }
 
+    #[tokio::test]
+    async fn test_sync_refused_while_key_rotation_pending() {
+        let db = Arc::new(Database::in_memory().expect("Failed to create test database"));
+        let manager = SyncManager::new(db.clone());
+
+        let mut config = manager.get_config().await;
+        config.enabled = true;
+        config.master_key_salt = Some("aa".repeat(32));
+        manager.update_config(config).await;
+
+        // Same password is rejected before anything is touched
+        let result = manager.rotate_master_password("secret", "secret").await;
+        assert!(matches!(result, Err(SyncManagerError::KeyRotation(_))));
+        assert!(rotation::load_state(&db).unwrap().is_none());
+
+        rotation::save_state(&db, &KeyRotationState::new("aa".repeat(32), "bb".repeat(32))).unwrap();
+        let result = manager.sync_all("secret").await;
+        assert!(matches!(result, Err(SyncManagerError::KeyRotationPending)));
+    }
+
+    #[tokio::test]
+    async fn test_offline_sync_defers_every_type() {
+        let db = Arc::new(Database::in_memory().expect("Failed to create test database"));
+        let manager = SyncManager::new(db);
+
+        let mut config = manager.get_config().await;
+        config.enabled = true;
+        config.sync_filters = false;
+        config.master_key_salt = Some("aa".repeat(32));
+        manager.update_config(config).await;
+        manager.set_online(false);
+
+        let result = manager.sync_all("secret").await.unwrap();
+        assert!(result.offline);
+        assert!(!result.has_any_success());
+        assert_eq!(result.types.len(), 5);
+        assert!(result.types.iter().all(|t| t.status == TypeSyncStatus::Deferred));
+        assert!(manager.get_config().await.last_sync_at.is_none());
+
+        // Remembered for the retry, and reported in the status
+        assert_eq!(manager.queue_manager.deferred_types().unwrap(), result.deferred());
+        let statuses = manager.get_status().await.unwrap();
+        assert_eq!(statuses.iter().filter(|s| s.status == SyncState::Error).count(), 5);
+
+        let retry = manager.retry_deferred("secret").await.unwrap();
+        assert_eq!(retry.deferred(), result.deferred());
+    }
+
     #[test]
     fn test_queue_manager_integration() {
         let db = Arc::new(Database::in_memory().expect("Failed to create test database"));
@@ -2622,6 +3448,136 @@ mod tests {
         assert!(emails.contains(&"charlie@example.com".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_templates_merge_per_item_lww() {
+        use crate::templates::TemplateItem;
+
+        let db = Arc::new(Database::in_memory().expect("Failed to create test database"));
+        let manager = SyncManager::new(db);
+
+        let now = chrono::Utc::now();
+        let past = now - chrono::Duration::hours(1);
+
+        let template = |name: &str, subject: &str, updated_at| TemplateItem {
+            name: name.to_string(),
+            description: None,
+            category: "business".to_string(),
+            subject_template: subject.to_string(),
+            body_html_template: "<p>Hi</p>".to_string(),
+            body_text_template: None,
+            tags: vec![],
+            is_enabled: true,
+            is_favorite: false,
+            account_email: None,
+            updated_at,
+        };
+
+        // Each side edited a different template more recently
+        let local_data = TemplateSyncData::new(vec![
+            template("Quote", "Local quote", Some(now)),
+            template("Follow-up", "Local follow-up", Some(past)),
+        ]);
+        let server_data = TemplateSyncData::new(vec![
+            template("Quote", "Server quote", Some(past)),
+            template("Follow-up", "Server follow-up", Some(now)),
+            template("Welcome", "Welcome aboard", Some(past)),
+        ]);
+
+        assert!(manager.detect_templates_conflicts(&local_data, &server_data).is_empty());
+
+        let (merged, server_wins) = manager.merge_templates(local_data, server_data);
+        let subjects: Vec<&str> = merged.templates.iter().map(|t| t.subject_template.as_str()).collect();
+        assert_eq!(subjects, vec!["Local quote", "Server follow-up", "Welcome aboard"]);
+        assert_eq!(server_wins.len(), 2);
+
+        // Same edit time but different content cannot be ordered
+        let local_data = TemplateSyncData::new(vec![template("Quote", "A", Some(now))]);
+        let server_data = TemplateSyncData::new(vec![template("Quote", "B", Some(now))]);
+        let conflicts = manager.detect_templates_conflicts(&local_data, &server_data);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].data_type, "templates");
+        assert_eq!(conflicts[0].field_changes, Some(vec!["subject_template".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_filters_conflict_and_merge() {
+        use crate::filters::portable::{ActionItem, FilterItem};
+        use crate::filters::{FilterActionType, MatchLogic};
+
+        let db = Arc::new(Database::in_memory().expect("Failed to create test database"));
+        let manager = SyncManager::new(db);
+
+        let now = chrono::Utc::now();
+        let past = now - chrono::Duration::hours(1);
+
+        let filter = |folder: &str, updated_at| FilterItem {
+            account_email: "test@example.com".to_string(),
+            name: "Invoices".to_string(),
+            description: None,
+            is_enabled: true,
+            priority: 1,
+            match_logic: MatchLogic::All,
+            conditions: vec![],
+            actions: vec![ActionItem {
+                action: FilterActionType::MoveToFolder,
+                folder_path: Some(folder.to_string()),
+                label: None,
+                webhook: None,
+            }],
+            updated_at,
+        };
+
+        // Newer server rule wins without a conflict
+        let local_data = FilterSyncData::new(vec![filter("Finance", Some(past))]);
+        let server_data = FilterSyncData::new(vec![filter("Finance/Invoices", Some(now))]);
+        assert!(manager.detect_filters_conflicts(&local_data, &server_data).is_empty());
+
+        let (merged, server_wins) = manager.merge_filters(local_data, server_data);
+        assert_eq!(merged.filters[0].actions[0].folder_path.as_deref(), Some("Finance/Invoices"));
+        assert_eq!(server_wins.len(), 1);
+
+        // Unordered edits need the user
+        let local_data = FilterSyncData::new(vec![filter("Finance", None)]);
+        let server_data = FilterSyncData::new(vec![filter("Archive", Some(now))]);
+        let conflicts = manager.detect_filters_conflicts(&local_data, &server_data);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field_changes, Some(vec!["actions".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_notes_merge_last_write_wins() {
+        use crate::notes::NoteItem;
+
+        let db = Arc::new(Database::in_memory().expect("Failed to create test database"));
+        let manager = SyncManager::new(db);
+
+        let now = chrono::Utc::now();
+        let past = now - chrono::Duration::hours(1);
+
+        let note = |message_id: &str, body: &str, deleted, updated_at| NoteItem {
+            account_email: "test@example.com".to_string(),
+            message_id: message_id.to_string(),
+            body: body.to_string(),
+            color: None,
+            deleted,
+            updated_at: Some(updated_at),
+        };
+
+        let local_data = NoteSyncData::new(vec![note("<1@x>", "local", false, now), note("<2@x>", "kept", false, past)]);
+        let server_data = NoteSyncData::new(vec![
+            note("<1@x>", "older", false, past),
+            note("<2@x>", "", true, now),
+            note("<3@x>", "new", false, past),
+        ]);
+
+        let (merged, server_wins) = manager.merge_notes(local_data, server_data);
+        assert_eq!(merged.notes.len(), 3);
+        assert_eq!(merged.notes[0].body, "local");
+        assert!(merged.notes[1].deleted);
+        let won: Vec<&str> = server_wins.iter().map(|n| n.message_id.as_str()).collect();
+        assert_eq!(won, ["<2@x>", "<3@x>"]);
+    }
+
     #[test]
     fn test_sync_result_has_conflicts() {
         use crate::sync::{ConflictInfo, ConflictStrategy};
@@ -5,6 +5,9 @@
 //! - Contacts (address book)
 //! - Preferences (theme, language, settings)
 //! - Email signatures
+//! - Email templates
+//! - Mail filter rules
+//! - Private notes on emails (opt-in)
 //!
 //! Architecture:
 //! - Zero-Knowledge: Server never sees plaintext
@@ -18,6 +21,7 @@ pub mod api;
 pub mod queue;
 pub mod history;
 pub mod scheduler;
+pub mod rotation;
 // pub mod conflict;
 // pub mod adapters;
 
@@ -40,12 +44,16 @@ pub use models::{
     ContactSyncData, ContactItem,
     PreferencesSyncData,
     SignatureSyncData,
+    TemplateSyncData,
+    FilterSyncData,
+    NoteSyncData,
     SyncStatus, SyncState,
     ConflictStrategy, ConflictInfo,
 };
 
-pub use manager::{SyncManager, SyncResult, SyncManagerError};
+pub use manager::{SyncManager, SyncResult, SyncManagerError, TypeSyncResult, TypeSyncStatus};
 pub use api::{SyncApiClient, SyncApiError, DeviceResponse};
 pub use queue::{QueueManager, QueueItem, QueueStatus, QueueStats, QueueError};
 pub use history::{HistoryManager, SyncSnapshot, SyncOperation, HistoryStats, HistoryError};
 pub use scheduler::{BackgroundScheduler, SchedulerConfig, SchedulerError};
+pub use rotation::{KeyRotationState, KeyRotationResult};
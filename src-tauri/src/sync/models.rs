@@ -9,7 +9,11 @@
 //! - ContactSyncData: Address book contacts
 //! - PreferencesSyncData: App preferences and settings
 //! - SignatureSyncData: Email signatures per account
+//! - TemplateSyncData: Email templates
+//! - FilterSyncData: Mail filter rules
+//! - NoteSyncData: Private notes on emails
 
+use super::crypto::SyncDataType;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -53,6 +57,13 @@ pub struct SyncConfig {
     pub sync_contacts: bool,
     pub sync_preferences: bool,
     pub sync_signatures: bool,
+    #[serde(default = "default_sync_templates")]
+    pub sync_templates: bool,
+    #[serde(default = "default_sync_filters")]
+    pub sync_filters: bool,
+    /// Notes are private, so they are only synced when asked for
+    #[serde(default)]
+    pub sync_notes: bool,
 
     /// Sync master key salt (32 bytes as hex)
     /// Generated once per user and persisted
@@ -74,11 +85,38 @@ impl Default for SyncConfig {
             sync_contacts: true,
             sync_preferences: true,
             sync_signatures: true,
+            sync_templates: true,
+            sync_filters: true,
+            sync_notes: false,
             master_key_salt: None,
         }
     }
 }
 
+impl SyncConfig {
+    /// Whether the user enabled syncing this data type
+    pub fn syncs(&self, data_type: SyncDataType) -> bool {
+        match data_type {
+            SyncDataType::Accounts => self.sync_accounts,
+            SyncDataType::Contacts => self.sync_contacts,
+            SyncDataType::Preferences => self.sync_preferences,
+            SyncDataType::Signatures => self.sync_signatures,
+            SyncDataType::Templates => self.sync_templates,
+            SyncDataType::Filters => self.sync_filters,
+            SyncDataType::Notes => self.sync_notes,
+        }
+    }
+}
+
+/// Templates were added later; configs saved before that opt in
+fn default_sync_templates() -> bool {
+    true
+}
+
+fn default_sync_filters() -> bool {
+    true
+}
+
 /// Platform identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -413,6 +451,84 @@ impl Default for SignatureSyncData {
     }
 }
 
+// ============================================================================
+// Template Sync Data
+// ============================================================================
+
+/// Email templates sync data
+///
+/// Templates are identified by (account email, name); each carries its own
+/// modification time so concurrent edits on different templates both survive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateSyncData {
+    pub templates: Vec<crate::templates::TemplateItem>,
+
+    /// Sync metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+impl TemplateSyncData {
+    pub fn new(templates: Vec<crate::templates::TemplateItem>) -> Self {
+        Self {
+            templates,
+            synced_at: Some(Utc::now()),
+        }
+    }
+}
+
+// ============================================================================
+// Filter Sync Data
+// ============================================================================
+
+/// Mail filter rules sync data
+///
+/// Filters are identified by (account email, name). Move-to-folder actions
+/// carry the folder's remote path, resolved to a local folder on apply.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterSyncData {
+    pub filters: Vec<crate::filters::portable::FilterItem>,
+
+    /// Sync metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+impl FilterSyncData {
+    pub fn new(filters: Vec<crate::filters::portable::FilterItem>) -> Self {
+        Self {
+            filters,
+            synced_at: Some(Utc::now()),
+        }
+    }
+}
+
+// ============================================================================
+// Note Sync Data
+// ============================================================================
+
+/// Private email notes sync data
+///
+/// Notes are identified by (account email, Message-ID). Deleted notes are
+/// included as tombstones so deletions propagate.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteSyncData {
+    pub notes: Vec<crate::notes::NoteItem>,
+
+    /// Sync metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+impl NoteSyncData {
+    pub fn new(notes: Vec<crate::notes::NoteItem>) -> Self {
+        Self {
+            notes,
+            synced_at: Some(Utc::now()),
+        }
+    }
+}
+
 // ============================================================================
 // Sync Status & Metadata
 // ============================================================================
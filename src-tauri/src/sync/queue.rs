@@ -25,6 +25,9 @@ const BASE_DELAY_SECS: i64 = 30;      // Initial retry delay: 30 seconds
 const MAX_DELAY_SECS: i64 = 3600;     // Max retry delay: 1 hour
 const DEFAULT_MAX_RETRIES: i32 = 5;   // Max retry attempts
 
+/// Settings key listing data types whose last sync failed transiently
+const DEFERRED_SETTING: &str = "sync_deferred_types";
+
 // ============================================================================
 // Data Types
 // ============================================================================
@@ -444,6 +447,21 @@ impl QueueManager {
         Ok(updated as i32)
     }
 
+    /// Drop all unfinished items of a data type
+    /// Their payloads are encrypted with the key that was current when they were queued.
+    pub fn clear_data_type(&self, data_type: SyncDataType) -> Result<i32, QueueError> {
+        let deleted = self.db.execute(
+            r#"
+            DELETE FROM sync_queue
+            WHERE data_type = ?1 AND status != 'completed'
+            "#,
+            params![data_type.as_str()],
+        ).map_err(|e| QueueError::DatabaseError(e.to_string()))?;
+
+        log::info!("Dropped {} queued {} uploads", deleted, data_type.as_str());
+        Ok(deleted as i32)
+    }
+
     /// Delete specific queue item
     pub fn delete_item(&self, id: i64) -> Result<(), QueueError> {
         self.db.execute(
@@ -454,6 +472,39 @@ impl QueueManager {
         log::info!("Deleted queue item {}", id);
         Ok(())
     }
+
+    /// Data types waiting for a full sync retry
+    /// A failed download cannot be queued as a payload, so the type itself is remembered.
+    pub fn deferred_types(&self) -> Result<Vec<SyncDataType>, QueueError> {
+        self.db.get_setting::<Vec<SyncDataType>>(DEFERRED_SETTING)
+            .map(Option::unwrap_or_default)
+            .map_err(|e| QueueError::DatabaseError(e.to_string()))
+    }
+
+    /// Remember a data type for retry
+    pub fn defer_type(&self, data_type: SyncDataType) -> Result<(), QueueError> {
+        let mut types = self.deferred_types()?;
+        if !types.contains(&data_type) {
+            types.push(data_type);
+            self.save_deferred(&types)?;
+        }
+        Ok(())
+    }
+
+    /// Forget a data type after it synced
+    pub fn clear_deferred(&self, data_type: SyncDataType) -> Result<(), QueueError> {
+        let mut types = self.deferred_types()?;
+        if types.contains(&data_type) {
+            types.retain(|t| *t != data_type);
+            self.save_deferred(&types)?;
+        }
+        Ok(())
+    }
+
+    fn save_deferred(&self, types: &[SyncDataType]) -> Result<(), QueueError> {
+        self.db.set_setting(DEFERRED_SETTING, &types)
+            .map_err(|e| QueueError::DatabaseError(e.to_string()))
+    }
 }
 
 // ============================================================================
@@ -620,4 +671,18 @@ mod tests {
         assert!(retry2 > retry1);
         assert!(retry3 > retry2);
     }
+
+    #[test]
+    fn test_deferred_types() {
+        let manager = create_test_manager();
+        assert!(manager.deferred_types().unwrap().is_empty());
+
+        manager.defer_type(SyncDataType::Contacts).unwrap();
+        manager.defer_type(SyncDataType::Filters).unwrap();
+        manager.defer_type(SyncDataType::Contacts).unwrap();
+        assert_eq!(manager.deferred_types().unwrap(), vec![SyncDataType::Contacts, SyncDataType::Filters]);
+
+        manager.clear_deferred(SyncDataType::Contacts).unwrap();
+        assert_eq!(manager.deferred_types().unwrap(), vec![SyncDataType::Filters]);
+    }
 }
@@ -0,0 +1,107 @@
+//! Master Key Rotation - Resumable re-encryption state
+//!
+//! Changing the master password re-derives every data key, so each server
+//! payload has to be downloaded with the old key and uploaded with the new
+//! one. Progress is persisted after each data type: if the app quits or the
+//! network drops halfway, the next attempt continues with the remaining types
+//! instead of leaving the account split between two keys.
+
+use super::crypto::SyncDataType;
+use crate::db::{Database, DbResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Settings key holding an unfinished rotation
+const ROTATION_SETTING: &str = "sync_key_rotation";
+
+/// Unfinished key rotation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotationState {
+    /// Salt the current server payloads were encrypted with (hex)
+    pub old_salt: String,
+    /// Salt for the new master key (hex)
+    pub new_salt: String,
+    /// Data types already re-encrypted with the new key
+    pub completed: Vec<SyncDataType>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl KeyRotationState {
+    pub fn new(old_salt: String, new_salt: String) -> Self {
+        Self {
+            old_salt,
+            new_salt,
+            completed: Vec::new(),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// Data types still encrypted with the old key
+    pub fn pending(&self) -> Vec<SyncDataType> {
+        SyncDataType::ALL
+            .into_iter()
+            .filter(|data_type| !self.completed.contains(data_type))
+            .collect()
+    }
+
+    pub fn mark_completed(&mut self, data_type: SyncDataType) {
+        if !self.completed.contains(&data_type) {
+            self.completed.push(data_type);
+        }
+    }
+}
+
+/// Outcome of `SyncManager::rotate_master_password`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+pub struct KeyRotationResult {
+    /// Data types re-encrypted in this run
+    pub rotated: Vec<String>,
+    /// Data types with nothing stored on the server
+    pub empty: Vec<String>,
+    /// Whether an interrupted rotation was continued
+    pub resumed: bool,
+}
+
+/// Load an unfinished rotation, if any
+pub fn load_state(db: &Database) -> DbResult<Option<KeyRotationState>> {
+    db.get_setting(ROTATION_SETTING)
+}
+
+/// Persist rotation progress
+pub fn save_state(db: &Database, state: &KeyRotationState) -> DbResult<()> {
+    db.set_setting(ROTATION_SETTING, state)
+}
+
+/// Forget a finished rotation
+pub fn clear_state(db: &Database) -> DbResult<()> {
+    db.execute("DELETE FROM settings WHERE key = ?1", [ROTATION_SETTING])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_state_persists_progress() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        assert_eq!(load_state(&db).unwrap(), None);
+
+        let mut state = KeyRotationState::new("aa".repeat(32), "bb".repeat(32));
+        assert_eq!(state.pending(), SyncDataType::ALL.to_vec());
+
+        state.mark_completed(SyncDataType::Accounts);
+        state.mark_completed(SyncDataType::Contacts);
+        state.mark_completed(SyncDataType::Accounts);
+        save_state(&db, &state).unwrap();
+
+        let loaded = load_state(&db).unwrap().expect("state should be stored");
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.completed.len(), 2);
+        assert_eq!(loaded.pending()[0], SyncDataType::Preferences);
+
+        clear_state(&db).unwrap();
+        assert_eq!(load_state(&db).unwrap(), None);
+    }
+}
@@ -243,11 +243,14 @@ impl BackgroundScheduler {
             match sync_manager.sync_all("").await {
                 Ok(result) => {
                     log::info!(
-                        "Background sync completed successfully: accounts={}, contacts={}, preferences={}, signatures={}, errors={}",
+                        "Background sync completed successfully: accounts={}, contacts={}, preferences={}, signatures={}, templates={}, filters={}, notes={}, errors={}",
                         result.accounts_synced,
                         result.contacts_synced,
                         result.preferences_synced,
                         result.signatures_synced,
+                        result.templates_synced,
+                        result.filters_synced,
+                        result.notes_synced,
                         result.errors.len()
                     );
 
@@ -255,6 +258,15 @@ impl BackgroundScheduler {
                         log::warn!("Background sync had {} errors: {:?}", result.errors.len(), result.errors);
                     }
 
+                    let deferred = result.deferred();
+                    if !deferred.is_empty() {
+                        log::info!(
+                            "Deferred {:?} until the next run{}",
+                            deferred,
+                            if result.offline { " (offline)" } else { "" }
+                        );
+                    }
+
                     // Update last_run timestamp
                     let mut cfg = config.write().await;
                     cfg.last_run = Some(Utc::now().to_rfc3339());
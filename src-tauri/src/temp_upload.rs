@@ -0,0 +1,197 @@
+//! Chunked Attachment Uploads
+//!
+//! Files picked in the composer reach the backend in chunks instead of one
+//! `Vec<u8>` argument: `begin` declares the name and size and returns a
+//! handle, each `append` writes one chunk straight to the temp file, and
+//! `finish` checks that exactly the declared number of bytes arrived. An
+//! upload that overruns its declared size, comes up short or is abandoned is
+//! deleted, so a failed upload never leaves a partial file to be sent.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Largest attachment accepted
+pub const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Largest chunk accepted by one `append`
+pub const MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Uploads in progress at once
+const MAX_OPEN_UPLOADS: usize = 16;
+
+/// Uploads without a chunk for this long are discarded
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// A completed upload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinishedUpload {
+    pub path: PathBuf,
+    pub filename: String,
+    pub content_type: String,
+}
+
+struct Upload {
+    file: tokio::fs::File,
+    path: PathBuf,
+    filename: String,
+    content_type: String,
+    declared_size: u64,
+    written: u64,
+    last_activity: Instant,
+}
+
+/// Uploads in progress, keyed by handle
+pub struct TempUploads {
+    dir: PathBuf,
+    uploads: Mutex<HashMap<String, Upload>>,
+}
+
+async fn discard(upload: Upload) {
+    drop(upload.file);
+    if let Err(e) = tokio::fs::remove_file(&upload.path).await {
+        log::warn!("Failed to remove partial upload {}: {}", upload.path.display(), e);
+    }
+}
+
+impl TempUploads {
+    /// Uploads are written to `dir`
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, uploads: Mutex::new(HashMap::new()) }
+    }
+
+    /// Start an upload of `size` bytes; returns its handle
+    pub async fn begin(&self, filename: &str, content_type: &str, size: u64) -> Result<String, String> {
+        if filename.is_empty() || filename.contains("..") || filename.contains('/') || filename.contains('\\') {
+            return Err("Invalid filename".to_string());
+        }
+        if size > MAX_FILE_SIZE {
+            return Err("File too large (max 50MB)".to_string());
+        }
+
+        let mut uploads = self.uploads.lock().await;
+        let now = Instant::now();
+        let stale: Vec<String> = uploads
+            .iter()
+            .filter(|(_, upload)| now.duration_since(upload.last_activity) >= UPLOAD_TIMEOUT)
+            .map(|(handle, _)| handle.clone())
+            .collect();
+        for handle in stale {
+            if let Some(upload) = uploads.remove(&handle) {
+                discard(upload).await;
+            }
+        }
+        if uploads.len() >= MAX_OPEN_UPLOADS {
+            return Err("Too many uploads in progress".to_string());
+        }
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+        let handle = uuid::Uuid::new_v4().to_string();
+        let path = self.dir.join(format!("{}_{}", handle, filename));
+        let file = tokio::fs::File::create(&path)
+            .await
+            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+        uploads.insert(
+            handle.clone(),
+            Upload {
+                file,
+                path,
+                filename: filename.to_string(),
+                content_type: content_type.to_string(),
+                declared_size: size,
+                written: 0,
+                last_activity: now,
+            },
+        );
+        Ok(handle)
+    }
+
+    /// Write the next chunk; returns the bytes received so far
+    pub async fn append(&self, handle: &str, chunk: &[u8]) -> Result<u64, String> {
+        if chunk.len() > MAX_CHUNK_SIZE {
+            return Err("Chunk too large".to_string());
+        }
+        let mut uploads = self.uploads.lock().await;
+        let upload = uploads.get_mut(handle).ok_or("Unknown upload")?;
+
+        if upload.written + chunk.len() as u64 > upload.declared_size {
+            if let Some(upload) = uploads.remove(handle) {
+                discard(upload).await;
+            }
+            return Err("Upload exceeds its declared size".to_string());
+        }
+        if let Err(e) = upload.file.write_all(chunk).await {
+            if let Some(upload) = uploads.remove(handle) {
+                discard(upload).await;
+            }
+            return Err(format!("Failed to write temp file: {}", e));
+        }
+        upload.written += chunk.len() as u64;
+        upload.last_activity = Instant::now();
+        Ok(upload.written)
+    }
+
+    /// Complete an upload; fails (and deletes the file) unless all declared bytes arrived
+    pub async fn finish(&self, handle: &str) -> Result<FinishedUpload, String> {
+        let mut upload = self.uploads.lock().await.remove(handle).ok_or("Unknown upload")?;
+
+        if upload.written != upload.declared_size {
+            let error = format!("Upload incomplete: {} of {} bytes", upload.written, upload.declared_size);
+            discard(upload).await;
+            return Err(error);
+        }
+        if let Err(e) = upload.file.flush().await {
+            discard(upload).await;
+            return Err(format!("Failed to write temp file: {}", e));
+        }
+
+        Ok(FinishedUpload { path: upload.path, filename: upload.filename, content_type: upload.content_type })
+    }
+
+    /// Cancel an upload and delete what was written
+    pub async fn abort(&self, handle: &str) {
+        if let Some(upload) = self.uploads.lock().await.remove(handle) {
+            discard(upload).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chunked_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = TempUploads::new(dir.path().to_path_buf());
+
+        assert!(uploads.begin("../evil", "text/plain", 1).await.is_err());
+        assert!(uploads.begin("big.bin", "application/octet-stream", MAX_FILE_SIZE + 1).await.is_err());
+
+        let handle = uploads.begin("notes.txt", "text/plain", 11).await.unwrap();
+        assert_eq!(uploads.append(&handle, b"hello ").await.unwrap(), 6);
+        assert_eq!(uploads.append(&handle, b"world").await.unwrap(), 11);
+        let done = uploads.finish(&handle).await.unwrap();
+        assert_eq!(done.filename, "notes.txt");
+        assert_eq!(std::fs::read(&done.path).unwrap(), b"hello world");
+        assert!(uploads.finish(&handle).await.is_err());
+
+        // Overrunning the declared size drops the upload and its file
+        let handle = uploads.begin("short.txt", "text/plain", 3).await.unwrap();
+        assert!(uploads.append(&handle, b"toolong").await.is_err());
+        assert!(uploads.append(&handle, b"a").await.is_err());
+
+        // So does finishing early
+        let handle = uploads.begin("partial.txt", "text/plain", 4).await.unwrap();
+        uploads.append(&handle, b"ab").await.unwrap();
+        assert!(uploads.finish(&handle).await.is_err());
+
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+    }
+}
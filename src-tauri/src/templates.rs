@@ -0,0 +1,387 @@
+//! Template Sharing
+//!
+//! Portable form of email templates, used by file import/export and by
+//! Owlivion sync. Templates are matched by name within their scope (a single
+//! account, or global); local ids and usage statistics never leave the machine.
+
+use crate::db::{parse_db_timestamp, Database, DbResult, EmailTemplate, NewEmailTemplate};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Marker written into export files
+pub const EXPORT_FORMAT: &str = "owlivion-templates";
+
+/// Current export file version
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Allowed template categories (mirrors the `email_templates` CHECK constraint)
+pub const CATEGORIES: [&str; 7] = [
+    "business", "personal", "customer_support",
+    "sales", "marketing", "internal", "custom",
+];
+
+/// Validate a template before it is stored
+pub fn validate(template: &NewEmailTemplate) -> Result<(), String> {
+    if template.name.trim().is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    if template.name.len() > 200 {
+        return Err("Template name cannot exceed 200 characters".to_string());
+    }
+    if template.subject_template.len() > 1000 {
+        return Err("Subject template cannot exceed 1000 characters".to_string());
+    }
+    if template.body_html_template.len() > 50_000 {
+        return Err("Body template cannot exceed 50KB".to_string());
+    }
+    if !CATEGORIES.contains(&template.category.as_str()) {
+        return Err("Invalid category".to_string());
+    }
+    Ok(())
+}
+
+/// Template without local ids or statistics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateItem {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub category: String,
+    #[serde(default)]
+    pub subject_template: String,
+    #[serde(default)]
+    pub body_html_template: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_text_template: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub is_enabled: bool,
+    #[serde(default)]
+    pub is_favorite: bool,
+
+    /// Owning account's address (sync only; None for global templates)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_email: Option<String>,
+
+    /// Last local modification (for conflict resolution)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl TemplateItem {
+    pub fn from_template(template: &EmailTemplate, account_email: Option<String>) -> Self {
+        Self {
+            name: template.name.clone(),
+            description: template.description.clone(),
+            category: template.category.clone(),
+            subject_template: template.subject_template.clone(),
+            body_html_template: template.body_html_template.clone(),
+            body_text_template: template.body_text_template.clone(),
+            tags: template.tags.clone(),
+            is_enabled: template.is_enabled,
+            is_favorite: template.is_favorite,
+            account_email,
+            updated_at: parse_db_timestamp(&template.updated_at),
+        }
+    }
+
+    pub fn to_new_template(&self, account_id: Option<i64>) -> NewEmailTemplate {
+        NewEmailTemplate {
+            account_id,
+            name: self.name.trim().to_string(),
+            description: self.description.clone(),
+            category: self.category.clone(),
+            subject_template: self.subject_template.clone(),
+            body_html_template: self.body_html_template.clone(),
+            body_text_template: self.body_text_template.clone(),
+            tags: self.tags.clone(),
+            is_enabled: self.is_enabled,
+            is_favorite: self.is_favorite,
+        }
+    }
+
+    /// Same user-visible content (ignores owner and timestamps)
+    pub fn same_content(&self, other: &TemplateItem) -> bool {
+        self.name == other.name
+            && self.description == other.description
+            && self.category == other.category
+            && self.subject_template == other.subject_template
+            && self.body_html_template == other.body_html_template
+            && self.body_text_template == other.body_text_template
+            && self.tags == other.tags
+            && self.is_enabled == other.is_enabled
+            && self.is_favorite == other.is_favorite
+    }
+}
+
+/// Contents of a template export file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateExportFile {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub templates: Vec<TemplateItem>,
+}
+
+impl TemplateExportFile {
+    pub fn new(templates: Vec<TemplateItem>) -> Self {
+        Self {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            templates,
+        }
+    }
+
+    /// Parse and check an export file
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let file: Self = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid template file: {}", e))?;
+
+        if file.format != EXPORT_FORMAT {
+            return Err("Not an Owlivion template export".to_string());
+        }
+        if file.version > EXPORT_VERSION {
+            return Err(format!(
+                "Template file version {} is newer than supported ({})",
+                file.version, EXPORT_VERSION
+            ));
+        }
+        Ok(file)
+    }
+}
+
+/// What to do when an imported template has the same name as an existing one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "TemplateConflictPolicy"))]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Keep the existing template
+    #[default]
+    Skip,
+    /// Replace the existing template's content
+    Overwrite,
+    /// Import under a new name ("Name (2)")
+    KeepBoth,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "TemplateImportSummary"))]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub updated: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+    /// Identical templates that already existed
+    pub unchanged: usize,
+    /// Templates rejected by validation
+    pub invalid: usize,
+}
+
+/// Build an export file from the given template ids
+pub fn export_templates(db: &Database, ids: &[i64]) -> DbResult<TemplateExportFile> {
+    let templates = ids
+        .iter()
+        .map(|id| db.get_template(*id).map(|t| TemplateItem::from_template(&t, None)))
+        .collect::<DbResult<Vec<_>>>()?;
+
+    Ok(TemplateExportFile::new(templates))
+}
+
+/// Import templates into an account (or as global templates)
+pub fn import_templates(
+    db: &Database,
+    account_id: Option<i64>,
+    items: &[TemplateItem],
+    policy: ConflictPolicy,
+) -> DbResult<ImportSummary> {
+    let mut existing: HashMap<String, EmailTemplate> = db
+        .get_all_templates()?
+        .into_iter()
+        .filter(|t| t.account_id == account_id)
+        .map(|t| (t.name.clone(), t))
+        .collect();
+
+    let mut summary = ImportSummary::default();
+
+    for item in items {
+        let mut template = item.to_new_template(account_id);
+        if let Err(e) = validate(&template) {
+            log::warn!("Skipping template '{}': {}", template.name, e);
+            summary.invalid += 1;
+            continue;
+        }
+
+        match existing.get(&template.name) {
+            None => {}
+            Some(current) if TemplateItem::from_template(current, None).same_content(item) => {
+                summary.unchanged += 1;
+                continue;
+            }
+            Some(current) => match policy {
+                ConflictPolicy::Skip => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                ConflictPolicy::Overwrite => {
+                    db.update_template(current.id, &template)?;
+                    summary.updated += 1;
+                    continue;
+                }
+                ConflictPolicy::KeepBoth => {
+                    template.name = unique_name(&template.name, &existing);
+                    summary.renamed += 1;
+                }
+            },
+        }
+
+        let id = db.add_template(&template)?;
+        existing.insert(template.name.clone(), db.get_template(id)?);
+        summary.imported += 1;
+    }
+
+    Ok(summary)
+}
+
+/// "Name (2)", "Name (3)", ... whichever is free
+fn unique_name(name: &str, existing: &HashMap<String, EmailTemplate>) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !existing.contains_key(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// All local templates in sync form, tagged with their account's address
+pub fn collect_sync_items(db: &Database) -> DbResult<Vec<TemplateItem>> {
+    let emails: HashMap<i64, String> = db
+        .get_accounts()?
+        .into_iter()
+        .map(|account| (account.id, account.email))
+        .collect();
+
+    Ok(db
+        .get_all_templates()?
+        .iter()
+        // Templates of accounts that no longer exist cannot be mapped elsewhere
+        .filter(|t| t.account_id.is_none_or(|id| emails.contains_key(&id)))
+        .map(|t| TemplateItem::from_template(t, t.account_id.and_then(|id| emails.get(&id).cloned())))
+        .collect())
+}
+
+/// Store synced templates, matching accounts by address
+/// Returns the number of templates added or updated.
+pub fn apply_sync_items(db: &Database, items: &[TemplateItem]) -> DbResult<usize> {
+    let mut changed = 0;
+
+    for item in items {
+        let account_id = match &item.account_email {
+            None => None,
+            Some(email) => match db.get_account_by_email(email)? {
+                Some(account) => Some(account.id),
+                None => {
+                    log::warn!("Account not found locally, skipping template '{}' for {}", item.name, email);
+                    continue;
+                }
+            },
+        };
+
+        let summary = import_templates(db, account_id, std::slice::from_ref(item), ConflictPolicy::Overwrite)?;
+        changed += summary.imported + summary.updated;
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_account;
+
+    fn template(name: &str, subject: &str) -> NewEmailTemplate {
+        NewEmailTemplate {
+            account_id: None,
+            name: name.to_string(),
+            description: None,
+            category: "business".to_string(),
+            subject_template: subject.to_string(),
+            body_html_template: "<p>Hello {{name}}</p>".to_string(),
+            body_text_template: None,
+            tags: vec!["sales".to_string()],
+            is_enabled: true,
+            is_favorite: false,
+        }
+    }
+
+    #[test]
+    fn test_export_import_round_trip_with_conflicts() {
+        let db = Database::in_memory().expect("Failed to create database");
+        let account_id = test_account(&db);
+
+        let quote = db.add_template(&template("Quote", "Your quote")).unwrap();
+        let follow_up = db.add_template(&template("Follow-up", "Checking in")).unwrap();
+
+        let json = serde_json::to_string(&export_templates(&db, &[quote, follow_up]).unwrap()).unwrap();
+        let file = TemplateExportFile::parse(&json).unwrap();
+        assert_eq!(file.templates.len(), 2);
+
+        // Fresh account scope: everything is new
+        let summary = import_templates(&db, Some(account_id), &file.templates, ConflictPolicy::Skip).unwrap();
+        assert_eq!(summary.imported, 2);
+
+        // Same scope again: identical templates are recognised
+        let summary = import_templates(&db, Some(account_id), &file.templates, ConflictPolicy::Skip).unwrap();
+        assert_eq!(summary.unchanged, 2);
+
+        let mut changed = file.templates.clone();
+        changed[0].subject_template = "Updated quote".to_string();
+        changed[1].subject_template = "Still there?".to_string();
+
+        let summary = import_templates(&db, Some(account_id), &changed[..1], ConflictPolicy::Skip).unwrap();
+        assert_eq!(summary.skipped, 1);
+        let summary = import_templates(&db, Some(account_id), &changed[..1], ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(summary.updated, 1);
+        let summary = import_templates(&db, Some(account_id), &changed[1..], ConflictPolicy::KeepBoth).unwrap();
+        assert_eq!(summary.renamed, 1);
+
+        let names: Vec<String> = db
+            .get_all_templates()
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.account_id == Some(account_id))
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Quote", "Follow-up", "Follow-up (2)"]);
+
+        assert!(TemplateExportFile::parse(r#"{"format":"other","version":1,"exportedAt":"2024-01-01T00:00:00Z","templates":[]}"#).is_err());
+    }
+
+    #[test]
+    fn test_sync_items_map_accounts_by_email() {
+        let db = Database::in_memory().expect("Failed to create database");
+        db.add_template(&template("Global", "Hi")).unwrap();
+
+        let items = collect_sync_items(&db).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].account_email, None);
+        assert!(items[0].updated_at.is_some());
+
+        let mut remote = items[0].clone();
+        remote.name = "Remote".to_string();
+        let mut orphan = items[0].clone();
+        orphan.account_email = Some("missing@test.com".to_string());
+
+        assert_eq!(apply_sync_items(&db, &[remote.clone(), orphan]).unwrap(), 1);
+        assert_eq!(apply_sync_items(&db, &[remote]).unwrap(), 0);
+        assert_eq!(db.get_all_templates().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,938 @@
+//! Mock IMAP Server
+//!
+//! Plain-TCP IMAP4rev1 server on 127.0.0.1 implementing the subset used by
+//! `AsyncImapClient`: LOGIN, CAPABILITY, LIST, SELECT/EXAMINE, (UID) FETCH,
+//! (UID) SEARCH, (UID) STORE, (UID) COPY/MOVE, (UID) EXPUNGE, CREATE, DELETE,
+//! RENAME, STATUS, NOOP and LOGOUT. Mailboxes live in memory; tests seed and inspect
+//! them and can script failures for individual commands.
+
+use crate::mail::{ImapConfig, SecurityType};
+use chrono::{DateTime, NaiveDate, Utc};
+use mail_parser::{Address, MessageParser};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Hierarchy delimiter announced in LIST responses
+pub const DELIMITER: &str = "/";
+
+const SYSTEM_FLAGS: &str = "\\Answered \\Flagged \\Deleted \\Seen \\Draft";
+
+/// Capabilities announced unless a test replaces them
+const DEFAULT_CAPABILITIES: &str = "IMAP4rev1 UIDPLUS MOVE SPECIAL-USE";
+
+/// Message stored in a mock mailbox
+#[derive(Debug, Clone)]
+pub struct MockMessage {
+    pub uid: u32,
+    pub flags: Vec<String>,
+    pub internal_date: DateTime<Utc>,
+    pub raw: Vec<u8>,
+}
+
+impl MockMessage {
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f.eq_ignore_ascii_case(flag))
+    }
+
+    fn set_flag(&mut self, flag: &str, on: bool) {
+        if on && !self.has_flag(flag) {
+            self.flags.push(flag.to_string());
+        } else if !on {
+            self.flags.retain(|f| !f.eq_ignore_ascii_case(flag));
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Mailbox {
+    name: String,
+    attributes: Vec<String>,
+    uid_validity: u32,
+    uid_next: u32,
+    messages: Vec<MockMessage>,
+}
+
+impl Mailbox {
+    fn new(name: &str, attributes: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: attributes.iter().map(|a| a.to_string()).collect(),
+            uid_validity: 1,
+            uid_next: 1,
+            messages: Vec::new(),
+        }
+    }
+
+    fn append(&mut self, mut message: MockMessage) -> u32 {
+        message.uid = self.uid_next;
+        self.uid_next += 1;
+        self.messages.push(message);
+        self.uid_next - 1
+    }
+}
+
+#[derive(Default)]
+struct State {
+    username: String,
+    password: String,
+    capabilities: String,
+    mailboxes: Vec<Mailbox>,
+    /// Scripted responses: (command prefix, tagged response)
+    failures: Vec<(String, String)>,
+    commands: Vec<String>,
+}
+
+impl State {
+    fn position(&self, name: &str) -> Option<usize> {
+        self.mailboxes.iter().position(|mb| {
+            mb.name == name || (name.eq_ignore_ascii_case("INBOX") && mb.name.eq_ignore_ascii_case("INBOX"))
+        })
+    }
+
+    fn mailbox_mut(&mut self, name: &str) -> Option<&mut Mailbox> {
+        let index = self.position(name)?;
+        Some(&mut self.mailboxes[index])
+    }
+
+    fn take_failure(&mut self, command: &str) -> Option<String> {
+        let index = self.failures.iter().position(|(prefix, _)| command.starts_with(prefix.as_str()))?;
+        Some(self.failures.remove(index).1)
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// In-memory IMAP server for integration tests
+pub struct MockImapServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl MockImapServer {
+    /// Start a server accepting `username`/`password`, with an empty INBOX
+    pub async fn start(username: &str, password: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            username: username.to_string(),
+            password: password.to_string(),
+            capabilities: DEFAULT_CAPABILITIES.to_string(),
+            mailboxes: vec![Mailbox::new("INBOX", &[])],
+            ..Default::default()
+        }));
+
+        let shared = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = shared.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, state).await {
+                        log::debug!("Mock IMAP connection closed: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// Client configuration pointing at this server
+    pub fn config(&self) -> ImapConfig {
+        let state = lock(&self.state);
+        ImapConfig {
+            host: self.addr.ip().to_string(),
+            port: self.addr.port(),
+            security: SecurityType::NONE,
+            username: state.username.clone(),
+            password: state.password.clone(),
+            accept_invalid_certs: false,
+            oauth_provider: None,
+        }
+    }
+
+    /// Add a mailbox with LIST attributes such as `\Trash` or `\Noselect`
+    pub fn add_mailbox(&self, name: &str, attributes: &[&str]) {
+        let mut state = lock(&self.state);
+        if state.position(name).is_none() {
+            state.mailboxes.push(Mailbox::new(name, attributes));
+        }
+    }
+
+    /// Store a message received now; returns its UID
+    pub fn append(&self, mailbox: &str, raw: &[u8], flags: &[&str]) -> u32 {
+        self.append_dated(mailbox, raw, flags, Utc::now())
+    }
+
+    /// Store a message with a given internal date; returns its UID
+    pub fn append_dated(&self, mailbox: &str, raw: &[u8], flags: &[&str], internal_date: DateTime<Utc>) -> u32 {
+        let mut state = lock(&self.state);
+        let mailbox = state
+            .mailbox_mut(mailbox)
+            .unwrap_or_else(|| panic!("Mock mailbox {} does not exist", mailbox));
+        mailbox.append(MockMessage {
+            uid: 0,
+            flags: flags.iter().map(|f| f.to_string()).collect(),
+            internal_date,
+            raw: raw.to_vec(),
+        })
+    }
+
+    /// Current contents of a mailbox (empty if it does not exist)
+    pub fn messages(&self, mailbox: &str) -> Vec<MockMessage> {
+        let state = lock(&self.state);
+        state
+            .position(mailbox)
+            .map(|index| state.mailboxes[index].messages.clone())
+            .unwrap_or_default()
+    }
+
+    /// Names of all mailboxes
+    pub fn mailboxes(&self) -> Vec<String> {
+        lock(&self.state).mailboxes.iter().map(|mb| mb.name.clone()).collect()
+    }
+
+    /// Replace the announced capabilities (e.g. "IMAP4rev1" for a server without MOVE)
+    /// The mock still runs every command it implements.
+    pub fn set_capabilities(&self, capabilities: &str) {
+        lock(&self.state).capabilities = capabilities.to_string();
+    }
+
+    /// Answer the next command starting with `command` (e.g. "UID STORE")
+    /// with `response` (e.g. "NO [UNAVAILABLE] Try again later") instead of running it
+    pub fn fail_next(&self, command: &str, response: &str) {
+        lock(&self.state).failures.push((command.to_uppercase(), response.to_string()));
+    }
+
+    /// Commands received so far, without tags (LOGIN arguments are redacted)
+    pub fn commands(&self) -> Vec<String> {
+        lock(&self.state).commands.clone()
+    }
+}
+
+impl Drop for MockImapServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// ============================================================================
+// Connection handling
+// ============================================================================
+
+async fn serve(stream: TcpStream, state: Arc<Mutex<State>>) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut session = Session::default();
+
+    let greeting = format!("* OK [CAPABILITY {}] Mock IMAP ready\r\n", lock(&state).capabilities);
+    writer.write_all(greeting.as_bytes()).await?;
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(());
+        }
+
+        let reply = session.handle(&mut lock(&state), line.trim_end());
+        writer.write_all(&reply).await?;
+        writer.flush().await?;
+
+        if session.logged_out {
+            return Ok(());
+        }
+    }
+}
+
+#[derive(Default)]
+struct Session {
+    authenticated: bool,
+    selected: Option<String>,
+    logged_out: bool,
+}
+
+impl Session {
+    /// Run one command line and return everything to send back
+    fn handle(&mut self, state: &mut State, line: &str) -> Vec<u8> {
+        let Some((tag, command)) = line.split_once(' ') else {
+            return b"* BAD Missing command\r\n".to_vec();
+        };
+
+        let tokens = tokenize(command);
+        let Some(first) = tokens.first() else {
+            return format!("{} BAD Missing command\r\n", tag).into_bytes();
+        };
+
+        let mut name = first.to_uppercase();
+        let mut args = &tokens[1..];
+        let by_uid = name == "UID";
+        if by_uid {
+            let Some(sub) = args.first() else {
+                return format!("{} BAD Missing UID command\r\n", tag).into_bytes();
+            };
+            name = format!("UID {}", sub.to_uppercase());
+            args = &args[1..];
+        }
+
+        state.commands.push(if name == "LOGIN" { "LOGIN ***".to_string() } else { command.to_string() });
+
+        if let Some(response) = state.take_failure(&name) {
+            return format!("{} {}\r\n", tag, response).into_bytes();
+        }
+
+        let mut out = Vec::new();
+        let status = self.run(state, &name, args, by_uid, &mut out);
+        out.extend_from_slice(format!("{} {}\r\n", tag, status).as_bytes());
+        out
+    }
+
+    fn run(&mut self, state: &mut State, name: &str, args: &[String], by_uid: bool, out: &mut Vec<u8>) -> String {
+        match name {
+            "CAPABILITY" => {
+                line(out, &format!("* CAPABILITY {}", state.capabilities));
+                "OK CAPABILITY completed".to_string()
+            }
+            "NOOP" => "OK NOOP completed".to_string(),
+            "LOGOUT" => {
+                line(out, "* BYE Mock IMAP signing off");
+                self.logged_out = true;
+                "OK LOGOUT completed".to_string()
+            }
+            "LOGIN" => match args {
+                [user, pass] if *user == state.username && *pass == state.password => {
+                    self.authenticated = true;
+                    "OK LOGIN completed".to_string()
+                }
+                [_, _] => "NO [AUTHENTICATIONFAILED] Invalid credentials".to_string(),
+                _ => "BAD LOGIN expects user and password".to_string(),
+            },
+            _ if !self.authenticated => "BAD Command requires authentication".to_string(),
+            "LIST" => self.list(state, args, out),
+            "SELECT" | "EXAMINE" => self.select(state, name, args, out),
+            "CREATE" => match args.first() {
+                Some(mailbox) if state.position(mailbox).is_some() => "NO [ALREADYEXISTS] Mailbox exists".to_string(),
+                Some(mailbox) => {
+                    state.mailboxes.push(Mailbox::new(mailbox, &[]));
+                    "OK CREATE completed".to_string()
+                }
+                None => "BAD CREATE expects a mailbox".to_string(),
+            },
+            "DELETE" => match args.first().and_then(|mailbox| state.position(mailbox)) {
+                Some(index) if state.mailboxes[index].name.eq_ignore_ascii_case("INBOX") => {
+                    "NO Cannot delete INBOX".to_string()
+                }
+                Some(index) => {
+                    state.mailboxes.remove(index);
+                    "OK DELETE completed".to_string()
+                }
+                None => "NO [NONEXISTENT] Mailbox does not exist".to_string(),
+            },
+            "RENAME" => self.rename(state, args),
+            "STATUS" => status(state, args, out),
+            _ => match self.selected_index(state) {
+                Err(status) => status,
+                Ok(index) => self.run_selected(&mut state.mailboxes, index, name, args, by_uid, out),
+            },
+        }
+    }
+
+    fn selected_index(&self, state: &State) -> Result<usize, String> {
+        let selected = self.selected.as_deref().ok_or("BAD No mailbox selected")?;
+        state.position(selected).ok_or_else(|| "NO Selected mailbox no longer exists".to_string())
+    }
+
+    /// Commands that operate on the selected mailbox
+    fn run_selected(
+        &mut self,
+        mailboxes: &mut [Mailbox],
+        index: usize,
+        name: &str,
+        args: &[String],
+        by_uid: bool,
+        out: &mut Vec<u8>,
+    ) -> String {
+        match name.trim_start_matches("UID ") {
+            "FETCH" => fetch(&mut mailboxes[index], args, by_uid, out),
+            "SEARCH" => match search(&mailboxes[index], args, by_uid) {
+                Ok(ids) => {
+                    let ids: Vec<String> = ids.iter().map(u32::to_string).collect();
+                    line(out, format!("* SEARCH {}", ids.join(" ")).trim_end());
+                    "OK SEARCH completed".to_string()
+                }
+                Err(status) => status,
+            },
+            "STORE" => store(&mut mailboxes[index], args, by_uid, out),
+            "COPY" | "MOVE" => {
+                let moving = name.ends_with("MOVE");
+                copy(mailboxes, index, args, by_uid, moving, out)
+            }
+            "EXPUNGE" => {
+                let set = if by_uid { args.first().map(|set| parse_set(set, max_uid(&mailboxes[index]))) } else { None };
+                expunge(&mut mailboxes[index], |message| {
+                    message.has_flag("\\Deleted") && set.as_ref().is_none_or(|ranges| in_set(ranges, message.uid))
+                }, out);
+                "OK EXPUNGE completed".to_string()
+            }
+            _ => "BAD Unknown command".to_string(),
+        }
+    }
+
+    fn list(&self, state: &State, args: &[String], out: &mut Vec<u8>) -> String {
+        let [reference, pattern] = args else {
+            return "BAD LIST expects reference and pattern".to_string();
+        };
+        let pattern = format!("{}{}", reference, pattern);
+
+        for mailbox in state.mailboxes.iter().filter(|mb| wildcard_match(&pattern, &mb.name)) {
+            let has_children = state
+                .mailboxes
+                .iter()
+                .any(|other| other.name.starts_with(&format!("{}{}", mailbox.name, DELIMITER)));
+            let mut attributes = mailbox.attributes.clone();
+            attributes.push(if has_children { "\\HasChildren" } else { "\\HasNoChildren" }.to_string());
+
+            line(out, &format!("* LIST ({}) \"{}\" {}", attributes.join(" "), DELIMITER, quoted(&mailbox.name)));
+        }
+        "OK LIST completed".to_string()
+    }
+
+    fn select(&mut self, state: &State, name: &str, args: &[String], out: &mut Vec<u8>) -> String {
+        let Some(index) = args.first().and_then(|mailbox| state.position(mailbox)) else {
+            self.selected = None;
+            return "NO [NONEXISTENT] Mailbox does not exist".to_string();
+        };
+        let mailbox = &state.mailboxes[index];
+
+        line(out, &format!("* FLAGS ({})", SYSTEM_FLAGS));
+        line(out, &format!("* {} EXISTS", mailbox.messages.len()));
+        line(out, "* 0 RECENT");
+        line(out, &format!("* OK [PERMANENTFLAGS ({} \\*)] Flags permitted", SYSTEM_FLAGS));
+        line(out, &format!("* OK [UIDVALIDITY {}] UIDs valid", mailbox.uid_validity));
+        line(out, &format!("* OK [UIDNEXT {}] Predicted next UID", mailbox.uid_next));
+
+        self.selected = Some(mailbox.name.clone());
+        if name == "SELECT" {
+            "OK [READ-WRITE] SELECT completed".to_string()
+        } else {
+            "OK [READ-ONLY] EXAMINE completed".to_string()
+        }
+    }
+
+    /// Rename a mailbox together with everything below it
+    fn rename(&mut self, state: &mut State, args: &[String]) -> String {
+        let [from, to] = args else {
+            return "BAD RENAME expects two mailboxes".to_string();
+        };
+        let Some(index) = state.position(from) else {
+            return "NO [NONEXISTENT] Mailbox does not exist".to_string();
+        };
+        if state.position(to).is_some() {
+            return "NO [ALREADYEXISTS] Target mailbox exists".to_string();
+        }
+
+        let old = state.mailboxes[index].name.clone();
+        let prefix = format!("{}{}", old, DELIMITER);
+        for mailbox in &mut state.mailboxes {
+            if mailbox.name == old {
+                mailbox.name = to.clone();
+            } else if let Some(rest) = mailbox.name.strip_prefix(&prefix) {
+                mailbox.name = format!("{}{}{}", to, DELIMITER, rest);
+            }
+        }
+        if self.selected.as_deref() == Some(old.as_str()) {
+            self.selected = Some(to.clone());
+        }
+        "OK RENAME completed".to_string()
+    }
+}
+
+// ============================================================================
+// Mailbox commands
+// ============================================================================
+
+fn fetch(mailbox: &mut Mailbox, args: &[String], by_uid: bool, out: &mut Vec<u8>) -> String {
+    let [set, items] = args else {
+        return "BAD FETCH expects a set and items".to_string();
+    };
+    let mut items = list_items(items);
+    if by_uid && !items.iter().any(|item| item.eq_ignore_ascii_case("UID")) {
+        items.insert(0, "UID".to_string());
+    }
+
+    let ranges = parse_set(set, if by_uid { max_uid(mailbox) } else { mailbox.messages.len() as u32 });
+
+    for (position, message) in mailbox.messages.iter_mut().enumerate() {
+        let id = if by_uid { message.uid } else { position as u32 + 1 };
+        if !in_set(&ranges, id) {
+            continue;
+        }
+
+        let mut response = format!("* {} FETCH (", position + 1).into_bytes();
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                response.push(b' ');
+            }
+            match item.to_uppercase().as_str() {
+                "UID" => response.extend_from_slice(format!("UID {}", message.uid).as_bytes()),
+                "FLAGS" => response.extend_from_slice(format!("FLAGS ({})", message.flags.join(" ")).as_bytes()),
+                "INTERNALDATE" => response.extend_from_slice(
+                    format!("INTERNALDATE \"{}\"", message.internal_date.format("%d-%b-%Y %H:%M:%S +0000")).as_bytes(),
+                ),
+                "RFC822.SIZE" => response.extend_from_slice(format!("RFC822.SIZE {}", message.raw.len()).as_bytes()),
+                "ENVELOPE" => {
+                    response.extend_from_slice(b"ENVELOPE ");
+                    response.extend_from_slice(&envelope(&message.raw));
+                }
+                "RFC822" | "BODY[]" | "BODY.PEEK[]" => {
+                    let label = if item.eq_ignore_ascii_case("RFC822") { "RFC822" } else { "BODY[]" };
+                    response.extend_from_slice(format!("{} {{{}}}\r\n", label, message.raw.len()).as_bytes());
+                    response.extend_from_slice(&message.raw);
+                    if !item.to_uppercase().contains("PEEK") {
+                        message.set_flag("\\Seen", true);
+                    }
+                }
+                other => return format!("BAD Unsupported fetch item {}", other),
+            }
+        }
+        response.extend_from_slice(b")\r\n");
+        out.extend_from_slice(&response);
+    }
+
+    "OK FETCH completed".to_string()
+}
+
+fn store(mailbox: &mut Mailbox, args: &[String], by_uid: bool, out: &mut Vec<u8>) -> String {
+    let [set, operation, flags @ ..] = args else {
+        return "BAD STORE expects a set, an operation and flags".to_string();
+    };
+    let flags: Vec<String> = flags.iter().flat_map(|token| list_items(token)).collect();
+    let operation = operation.to_uppercase();
+    let silent = operation.ends_with(".SILENT");
+
+    let ranges = parse_set(set, if by_uid { max_uid(mailbox) } else { mailbox.messages.len() as u32 });
+
+    for (position, message) in mailbox.messages.iter_mut().enumerate() {
+        let id = if by_uid { message.uid } else { position as u32 + 1 };
+        if !in_set(&ranges, id) {
+            continue;
+        }
+
+        match operation.trim_end_matches(".SILENT") {
+            "+FLAGS" => flags.iter().for_each(|flag| message.set_flag(flag, true)),
+            "-FLAGS" => flags.iter().for_each(|flag| message.set_flag(flag, false)),
+            "FLAGS" => message.flags = flags.clone(),
+            _ => return "BAD Unknown STORE operation".to_string(),
+        }
+
+        if !silent {
+            let uid = if by_uid { format!(" UID {}", message.uid) } else { String::new() };
+            line(out, &format!("* {} FETCH (FLAGS ({}){})", position + 1, message.flags.join(" "), uid));
+        }
+    }
+
+    "OK STORE completed".to_string()
+}
+
+fn copy(mailboxes: &mut [Mailbox], source: usize, args: &[String], by_uid: bool, moving: bool, out: &mut Vec<u8>) -> String {
+    let [set, target @ ..] = args else {
+        return "BAD COPY expects a set and a mailbox".to_string();
+    };
+    // The client does not always quote the target, so "Deleted Items" arrives as two tokens
+    let target_name = target.join(" ");
+    let Some(target) = mailboxes.iter().position(|mb| {
+        mb.name == target_name || (target_name.eq_ignore_ascii_case("INBOX") && mb.name.eq_ignore_ascii_case("INBOX"))
+    }) else {
+        return "NO [TRYCREATE] Mailbox does not exist".to_string();
+    };
+
+    let ranges = parse_set(set, if by_uid { max_uid(&mailboxes[source]) } else { mailboxes[source].messages.len() as u32 });
+    let selected: Vec<MockMessage> = mailboxes[source]
+        .messages
+        .iter()
+        .enumerate()
+        .filter(|(position, message)| in_set(&ranges, if by_uid { message.uid } else { *position as u32 + 1 }))
+        .map(|(_, message)| message.clone())
+        .collect();
+
+    let source_uids: Vec<String> = selected.iter().map(|message| message.uid.to_string()).collect();
+    let target_uids: Vec<String> = selected
+        .into_iter()
+        .map(|mut message| {
+            message.set_flag("\\Deleted", false);
+            mailboxes[target].append(message).to_string()
+        })
+        .collect();
+
+    if moving {
+        let moved: Vec<u32> = source_uids.iter().filter_map(|uid| uid.parse().ok()).collect();
+        expunge(&mut mailboxes[source], |message| moved.contains(&message.uid), out);
+    }
+
+    format!(
+        "OK [COPYUID {} {} {}] {} completed",
+        mailboxes[target].uid_validity,
+        source_uids.join(","),
+        target_uids.join(","),
+        if moving { "MOVE" } else { "COPY" }
+    )
+}
+
+/// Remove matching messages, reporting each sequence number as it goes
+fn expunge(mailbox: &mut Mailbox, remove: impl Fn(&MockMessage) -> bool, out: &mut Vec<u8>) {
+    let mut position = 0;
+    while position < mailbox.messages.len() {
+        if remove(&mailbox.messages[position]) {
+            line(out, &format!("* {} EXPUNGE", position + 1));
+            mailbox.messages.remove(position);
+        } else {
+            position += 1;
+        }
+    }
+}
+
+// ============================================================================
+// SEARCH
+// ============================================================================
+
+enum Criterion {
+    All,
+    Flag(&'static str, bool),
+    Since(NaiveDate),
+    Before(NaiveDate),
+    On(NaiveDate),
+    Header(String, String),
+    Body(String),
+    Text(String),
+    Uid(String),
+    Sequence(String),
+    Not(Box<Criterion>),
+    Or(Box<Criterion>, Box<Criterion>),
+    And(Vec<Criterion>),
+}
+
+fn search(mailbox: &Mailbox, args: &[String], by_uid: bool) -> Result<Vec<u32>, String> {
+    let mut args = args;
+    if args.first().is_some_and(|arg| arg.eq_ignore_ascii_case("CHARSET")) {
+        args = args.get(2..).unwrap_or_default();
+    }
+    let criterion = parse_criteria(args)?;
+
+    Ok(mailbox
+        .messages
+        .iter()
+        .enumerate()
+        .filter(|(position, message)| matches(&criterion, mailbox, *position as u32 + 1, message))
+        .map(|(position, message)| if by_uid { message.uid } else { position as u32 + 1 })
+        .collect())
+}
+
+fn parse_criteria(tokens: &[String]) -> Result<Criterion, String> {
+    let mut position = 0;
+    let mut all = Vec::new();
+    while position < tokens.len() {
+        all.push(parse_key(tokens, &mut position)?);
+    }
+    Ok(Criterion::And(all))
+}
+
+fn parse_key(tokens: &[String], position: &mut usize) -> Result<Criterion, String> {
+    let mut next = || {
+        let token = tokens.get(*position).cloned().ok_or("BAD Incomplete search criteria")?;
+        *position += 1;
+        Ok::<String, String>(token)
+    };
+
+    let key = next()?;
+    let criterion = match key.to_uppercase().as_str() {
+        "ALL" => Criterion::All,
+        "SEEN" => Criterion::Flag("\\Seen", true),
+        "UNSEEN" => Criterion::Flag("\\Seen", false),
+        "FLAGGED" => Criterion::Flag("\\Flagged", true),
+        "UNFLAGGED" => Criterion::Flag("\\Flagged", false),
+        "DELETED" => Criterion::Flag("\\Deleted", true),
+        "UNDELETED" => Criterion::Flag("\\Deleted", false),
+        "ANSWERED" => Criterion::Flag("\\Answered", true),
+        "UNANSWERED" => Criterion::Flag("\\Answered", false),
+        "DRAFT" => Criterion::Flag("\\Draft", true),
+        "UNDRAFT" => Criterion::Flag("\\Draft", false),
+        "SINCE" => Criterion::Since(parse_date(&next()?)?),
+        "BEFORE" => Criterion::Before(parse_date(&next()?)?),
+        "ON" => Criterion::On(parse_date(&next()?)?),
+        field @ ("SUBJECT" | "FROM" | "TO" | "CC" | "BCC") => Criterion::Header(field.to_string(), next()?),
+        "HEADER" => {
+            let field = next()?.to_uppercase();
+            Criterion::Header(field, next()?)
+        }
+        "BODY" => Criterion::Body(next()?),
+        "TEXT" => Criterion::Text(next()?),
+        "UID" => Criterion::Uid(next()?),
+        "NOT" => Criterion::Not(Box::new(parse_key(tokens, position)?)),
+        "OR" => {
+            let left = parse_key(tokens, position)?;
+            let right = parse_key(tokens, position)?;
+            Criterion::Or(Box::new(left), Box::new(right))
+        }
+        _ if key.starts_with('(') => parse_criteria(&list_items(&key))?,
+        _ if key.chars().all(|c| c.is_ascii_digit() || matches!(c, ':' | ',' | '*')) => Criterion::Sequence(key),
+        other => return Err(format!("BAD Unsupported search key {}", other)),
+    };
+    Ok(criterion)
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%d-%b-%Y").map_err(|_| format!("BAD Invalid date {}", value))
+}
+
+fn matches(criterion: &Criterion, mailbox: &Mailbox, sequence: u32, message: &MockMessage) -> bool {
+    let date = message.internal_date.date_naive();
+    let contains = |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+
+    match criterion {
+        Criterion::All => true,
+        Criterion::Flag(flag, set) => message.has_flag(flag) == *set,
+        Criterion::Since(since) => date >= *since,
+        Criterion::Before(before) => date < *before,
+        Criterion::On(on) => date == *on,
+        Criterion::Header(field, value) => MessageParser::default()
+            .parse(&message.raw)
+            .and_then(|parsed| parsed.header_raw(field.as_str()).map(|raw| contains(raw, value)))
+            .unwrap_or(false),
+        Criterion::Body(value) => MessageParser::default()
+            .parse(&message.raw)
+            .and_then(|parsed| parsed.body_text(0).map(|body| contains(&body, value)))
+            .unwrap_or(false),
+        Criterion::Text(value) => contains(&String::from_utf8_lossy(&message.raw), value),
+        Criterion::Uid(set) => in_set(&parse_set(set, max_uid(mailbox)), message.uid),
+        Criterion::Sequence(set) => in_set(&parse_set(set, mailbox.messages.len() as u32), sequence),
+        Criterion::Not(inner) => !matches(inner, mailbox, sequence, message),
+        Criterion::Or(left, right) => {
+            matches(left, mailbox, sequence, message) || matches(right, mailbox, sequence, message)
+        }
+        Criterion::And(all) => all.iter().all(|inner| matches(inner, mailbox, sequence, message)),
+    }
+}
+
+// ============================================================================
+// Protocol helpers
+// ============================================================================
+
+/// STATUS with MESSAGES, UIDNEXT, UIDVALIDITY and UNSEEN
+fn status(state: &State, args: &[String], out: &mut Vec<u8>) -> String {
+    let [mailbox, items] = args else {
+        return "BAD STATUS expects a mailbox and items".to_string();
+    };
+    let Some(index) = state.position(mailbox) else {
+        return "NO [NONEXISTENT] Mailbox does not exist".to_string();
+    };
+    let mailbox = &state.mailboxes[index];
+
+    let mut values = Vec::new();
+    for item in list_items(items) {
+        let value = match item.to_uppercase().as_str() {
+            "MESSAGES" => mailbox.messages.len() as u32,
+            "UIDNEXT" => mailbox.uid_next,
+            "UIDVALIDITY" => mailbox.uid_validity,
+            "UNSEEN" => mailbox.messages.iter().filter(|m| !m.has_flag("\\Seen")).count() as u32,
+            other => return format!("BAD Unsupported status item {}", other),
+        };
+        values.push(format!("{} {}", item.to_uppercase(), value));
+    }
+    line(out, &format!("* STATUS {} ({})", quoted(&mailbox.name), values.join(" ")));
+    "OK STATUS completed".to_string()
+}
+
+fn line(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(text.as_bytes());
+    out.extend_from_slice(b"\r\n");
+}
+
+/// Split a command into atoms, quoted strings and parenthesized lists
+/// Lists are kept whole (with their parentheses); quotes are removed.
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' => {
+                chars.next();
+            }
+            '"' => {
+                chars.next();
+                let mut token = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => token.extend(chars.next()),
+                        '"' => break,
+                        _ => token.push(c),
+                    }
+                }
+                tokens.push(token);
+            }
+            '(' => {
+                let mut token = String::new();
+                let mut depth = 0;
+                let mut in_quote = false;
+                for c in chars.by_ref() {
+                    token.push(c);
+                    match c {
+                        '"' => in_quote = !in_quote,
+                        '(' if !in_quote => depth += 1,
+                        ')' if !in_quote => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                tokens.push(token);
+            }
+            _ => {
+                let mut token = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == ' ' {
+                        break;
+                    }
+                    chars.next();
+                    token.push(c);
+                    // Section specs like BODY.PEEK[HEADER.FIELDS (FROM)] may contain spaces
+                    if c == '[' {
+                        for c in chars.by_ref() {
+                            token.push(c);
+                            if c == ']' {
+                                break;
+                            }
+                        }
+                    }
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    tokens
+}
+
+/// Items of a parenthesized list, or the token itself
+fn list_items(token: &str) -> Vec<String> {
+    match token.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(inner) => tokenize(inner),
+        None => vec![token.to_string()],
+    }
+}
+
+fn max_uid(mailbox: &Mailbox) -> u32 {
+    mailbox.messages.iter().map(|message| message.uid).max().unwrap_or(0)
+}
+
+/// Parse a sequence set ("1:3,7,9:*") into inclusive ranges
+fn parse_set(set: &str, max: u32) -> Vec<(u32, u32)> {
+    let value = |part: &str| if part == "*" { Some(max) } else { part.parse().ok() };
+
+    set.split(',')
+        .filter_map(|part| match part.split_once(':') {
+            Some((a, b)) => {
+                let (a, b) = (value(a)?, value(b)?);
+                Some((a.min(b), a.max(b)))
+            }
+            None => value(part).map(|n| (n, n)),
+        })
+        .collect()
+}
+
+fn in_set(ranges: &[(u32, u32)], id: u32) -> bool {
+    ranges.iter().any(|(low, high)| (*low..=*high).contains(&id))
+}
+
+/// LIST pattern match: `*` matches anything, `%` anything but the delimiter
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.chars().next() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len())
+            .filter(|i| name.is_char_boundary(*i))
+            .any(|i| wildcard_match(&pattern[1..], &name[i..])),
+        Some('%') => (0..=name.len())
+            .filter(|i| name.is_char_boundary(*i) && !name[..*i].contains(DELIMITER))
+            .any(|i| wildcard_match(&pattern[1..], &name[i..])),
+        Some(c) => name.starts_with(c) && wildcard_match(&pattern[c.len_utf8()..], &name[c.len_utf8()..]),
+    }
+}
+
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// NIL, a quoted string, or a literal for values that cannot be quoted
+fn nstring(value: Option<&str>) -> String {
+    match value {
+        None => "NIL".to_string(),
+        Some(value) if value.is_ascii() && !value.contains(['\r', '\n']) => quoted(value),
+        Some(value) => format!("{{{}}}\r\n{}", value.len(), value),
+    }
+}
+
+/// ENVELOPE structure built from the message headers (RFC 3501 7.4.2)
+fn envelope(raw: &[u8]) -> Vec<u8> {
+    let Some(message) = MessageParser::default().parse(raw) else {
+        return b"(NIL NIL NIL NIL NIL NIL NIL NIL NIL NIL)".to_vec();
+    };
+
+    // Raw header values, unfolded
+    let header = |name: &str| {
+        message
+            .header_raw(name)
+            .map(|value| value.replace("\r\n", "").replace('\n', "").trim().to_string())
+    };
+    let from = addresses(message.from());
+
+    let fields = [
+        nstring(header("Date").as_deref()),
+        nstring(header("Subject").as_deref()),
+        from.clone(),
+        message.sender().map(|a| addresses(Some(a))).unwrap_or_else(|| from.clone()),
+        message.reply_to().map(|a| addresses(Some(a))).unwrap_or_else(|| from.clone()),
+        addresses(message.to()),
+        addresses(message.cc()),
+        addresses(message.bcc()),
+        nstring(header("In-Reply-To").as_deref()),
+        nstring(header("Message-ID").as_deref()),
+    ];
+    format!("({})", fields.join(" ")).into_bytes()
+}
+
+fn addresses(address: Option<&Address>) -> String {
+    let Some(address) = address else {
+        return "NIL".to_string();
+    };
+
+    let list: Vec<String> = address
+        .iter()
+        .map(|addr| {
+            let email = addr.address().unwrap_or_default();
+            let (mailbox, host) = email.rsplit_once('@').unwrap_or((email, ""));
+            format!(
+                "({} NIL {} {})",
+                nstring(addr.name()),
+                nstring(Some(mailbox)),
+                nstring(Some(host).filter(|h| !h.is_empty()))
+            )
+        })
+        .collect();
+
+    if list.is_empty() {
+        "NIL".to_string()
+    } else {
+        format!("({})", list.join(""))
+    }
+}
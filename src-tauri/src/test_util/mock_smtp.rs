@@ -0,0 +1,251 @@
+//! Mock SMTP Server
+//!
+//! Plain-TCP SMTP server on 127.0.0.1 that checks AUTH PLAIN/LOGIN
+//! credentials and records every accepted message. Failures can be scripted
+//! per command to exercise the send queue's error handling.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Message accepted by the server
+#[derive(Debug, Clone)]
+pub struct ReceivedMail {
+    /// MAIL FROM address
+    pub from: String,
+    /// RCPT TO addresses (includes Bcc recipients)
+    pub recipients: Vec<String>,
+    /// Message as transmitted, dot-unstuffed
+    pub data: Vec<u8>,
+}
+
+impl ReceivedMail {
+    /// Message as text (lossy)
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.data).into_owned()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    username: String,
+    password: String,
+    received: Vec<ReceivedMail>,
+    /// Scripted replies: (command verb, full reply line)
+    failures: Vec<(String, String)>,
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// In-memory SMTP server for integration tests
+pub struct MockSmtpServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl MockSmtpServer {
+    /// Start a server accepting `username`/`password`
+    pub async fn start(username: &str, password: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            username: username.to_string(),
+            password: password.to_string(),
+            ..Default::default()
+        }));
+
+        let shared = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = shared.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, state).await {
+                        log::debug!("Mock SMTP connection closed: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    pub fn host(&self) -> String {
+        self.addr.ip().to_string()
+    }
+
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// Reply to the next `command` (e.g. "RCPT") with `reply` (e.g. "550 5.1.1 No such user")
+    pub fn fail_next(&self, command: &str, reply: &str) {
+        lock(&self.state).failures.push((command.to_uppercase(), reply.to_string()));
+    }
+
+    /// Messages accepted so far
+    pub fn received(&self) -> Vec<ReceivedMail> {
+        lock(&self.state).received.clone()
+    }
+}
+
+impl Drop for MockSmtpServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[derive(Default)]
+struct Envelope {
+    from: Option<String>,
+    recipients: Vec<String>,
+}
+
+async fn serve(stream: TcpStream, state: Arc<Mutex<State>>) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut authenticated = false;
+    let mut envelope = Envelope::default();
+
+    writer.write_all(b"220 localhost Mock SMTP ready\r\n").await?;
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(());
+        }
+        let command = line.trim_end();
+        let (verb, argument) = command.split_once(' ').unwrap_or((command, ""));
+        let verb = verb.to_uppercase();
+
+        let scripted = {
+            let mut state = lock(&state);
+            let index = state.failures.iter().position(|(failing, _)| *failing == verb);
+            index.map(|index| state.failures.remove(index).1)
+        };
+        if let Some(reply) = scripted {
+            writer.write_all(format!("{}\r\n", reply).as_bytes()).await?;
+            continue;
+        }
+
+        let reply = match verb.as_str() {
+            "EHLO" => "250-localhost\r\n250-AUTH PLAIN LOGIN\r\n250 8BITMIME".to_string(),
+            "HELO" => "250 localhost".to_string(),
+            "NOOP" => "250 OK".to_string(),
+            "RSET" => {
+                envelope = Envelope::default();
+                "250 OK".to_string()
+            }
+            "QUIT" => {
+                writer.write_all(b"221 Bye\r\n").await?;
+                return Ok(());
+            }
+            "AUTH" => {
+                let (mechanism, initial) = argument.split_once(' ').unwrap_or((argument, ""));
+                let credentials = match mechanism.to_uppercase().as_str() {
+                    "PLAIN" => {
+                        let response = if initial.is_empty() {
+                            writer.write_all(b"334 \r\n").await?;
+                            read_line(&mut reader).await?
+                        } else {
+                            initial.to_string()
+                        };
+                        decode(&response).and_then(|plain| {
+                            let mut parts = plain.split('\0').skip(1);
+                            Some((parts.next()?.to_string(), parts.next()?.to_string()))
+                        })
+                    }
+                    "LOGIN" => {
+                        writer.write_all(b"334 VXNlcm5hbWU6\r\n").await?;
+                        let username = decode(&read_line(&mut reader).await?);
+                        writer.write_all(b"334 UGFzc3dvcmQ6\r\n").await?;
+                        let password = decode(&read_line(&mut reader).await?);
+                        username.zip(password)
+                    }
+                    _ => None,
+                };
+
+                let state = lock(&state);
+                if credentials == Some((state.username.clone(), state.password.clone())) {
+                    authenticated = true;
+                    "235 2.7.0 Authentication successful".to_string()
+                } else {
+                    "535 5.7.8 Authentication credentials invalid".to_string()
+                }
+            }
+            _ if !authenticated => "530 5.7.0 Authentication required".to_string(),
+            "MAIL" => match path(argument, "FROM:") {
+                Some(from) => {
+                    envelope = Envelope { from: Some(from), recipients: Vec::new() };
+                    "250 OK".to_string()
+                }
+                None => "501 Syntax: MAIL FROM:<address>".to_string(),
+            },
+            "RCPT" => match (&envelope.from, path(argument, "TO:")) {
+                (None, _) => "503 Need MAIL before RCPT".to_string(),
+                (Some(_), Some(to)) => {
+                    envelope.recipients.push(to);
+                    "250 OK".to_string()
+                }
+                (Some(_), None) => "501 Syntax: RCPT TO:<address>".to_string(),
+            },
+            "DATA" if envelope.recipients.is_empty() => "503 Need RCPT before DATA".to_string(),
+            "DATA" => {
+                writer.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n").await?;
+                let data = read_data(&mut reader).await?;
+
+                lock(&state).received.push(ReceivedMail {
+                    from: envelope.from.take().unwrap_or_default(),
+                    recipients: std::mem::take(&mut envelope.recipients),
+                    data,
+                });
+                "250 OK queued".to_string()
+            }
+            _ => "502 Command not implemented".to_string(),
+        };
+
+        writer.write_all(format!("{}\r\n", reply).as_bytes()).await?;
+        writer.flush().await?;
+    }
+}
+
+async fn read_line<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> std::io::Result<String> {
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    Ok(line.trim_end().to_string())
+}
+
+/// Read a message body up to the terminating "." line, undoing dot-stuffing
+async fn read_data<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line).await? == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Connection closed in DATA"));
+        }
+        if line == b".\r\n" || line == b".\n" {
+            return Ok(data);
+        }
+        let unstuffed = if line.starts_with(b"..") { &line[1..] } else { &line[..] };
+        data.extend_from_slice(unstuffed);
+    }
+}
+
+fn decode(value: &str) -> Option<String> {
+    STANDARD.decode(value.trim()).ok().and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+/// Address from "FROM:<a@b> SIZE=..." style arguments
+fn path(argument: &str, prefix: &str) -> Option<String> {
+    let rest = argument.get(..prefix.len())?.eq_ignore_ascii_case(prefix).then(|| &argument[prefix.len()..])?;
+    let start = rest.find('<')?;
+    let end = rest[start..].find('>')? + start;
+    Some(rest[start + 1..end].to_string())
+}
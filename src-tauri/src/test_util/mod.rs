@@ -0,0 +1,224 @@
+//! Test Utilities - Embedded mail servers
+//!
+//! Scriptable IMAP and SMTP servers listening on 127.0.0.1, so the fetch,
+//! flag, move and send paths and the sync pipeline can be exercised end to
+//! end without real accounts. Compiled for this crate's tests and, for other
+//! crates, behind the `test-util` feature.
+
+pub mod mock_imap;
+pub mod mock_smtp;
+
+pub use mock_imap::{MockImapServer, MockMessage};
+pub use mock_smtp::{MockSmtpServer, ReceivedMail};
+
+/// Minimal RFC 822 text message
+pub fn sample_message(from: &str, to: &str, subject: &str, body: &str) -> Vec<u8> {
+    format!(
+        "From: {from}\r\nTo: {to}\r\nSubject: {subject}\r\nDate: Wed, 01 Jan 2025 10:00:00 +0000\r\n\
+         Message-ID: <{id}@mock.test>\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{body}\r\n",
+        id = uuid::Uuid::new_v4(),
+    )
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_new_account, Database, NewAccount};
+    use crate::mail::{AsyncImapClient, MailError, MoveMethod, MoveOutcome, MoveReport, MoveStep};
+    use crate::pending_ops::QueuedSend;
+
+    async fn connected_client(server: &MockImapServer) -> AsyncImapClient {
+        let mut client = AsyncImapClient::new(server.config());
+        client.connect().await.expect("Failed to connect to mock IMAP");
+        client
+    }
+
+    #[tokio::test]
+    async fn test_fetch_and_flag_against_mock_server() {
+        let server = MockImapServer::start("alice@mock.test", "secret").await.unwrap();
+        server.add_mailbox("Archive", &[]);
+        server.add_mailbox("Trash", &["\\Trash"]);
+        server.append("INBOX", &sample_message("bob@mock.test", "alice@mock.test", "Hello", "First"), &[]);
+        let second = server.append(
+            "INBOX",
+            &sample_message("carol@mock.test", "alice@mock.test", "Invoice 42", "Second"),
+            &["\\Seen"],
+        );
+
+        let mut client = connected_client(&server).await;
+
+        let folders = client.list_folders().await.unwrap();
+        assert!(folders.iter().any(|f| f.path == "Archive"));
+        assert!(folders.iter().any(|f| f.path == "Trash"));
+
+        let page = client.fetch_emails("INBOX", 0, 50).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert!(page.emails.iter().any(|e| e.subject == "Hello" && !e.is_read));
+        assert!(page.emails.iter().any(|e| e.subject == "Invoice 42" && e.from == "carol@mock.test"));
+
+        let email = client.fetch_email("INBOX", second).await.unwrap();
+        assert_eq!(email.subject, "Invoice 42");
+        assert!(email.body_text.unwrap_or_default().contains("Second"));
+
+        assert_eq!(client.search("INBOX", "invoice").await.unwrap(), vec![second]);
+
+        client.set_read("INBOX", 1, true).await.unwrap();
+        client.set_starred("INBOX", 1, true).await.unwrap();
+        let first = &server.messages("INBOX")[0];
+        assert!(first.has_flag("\\Seen") && first.has_flag("\\Flagged"));
+
+        // The mock announces MOVE, so no COPY + EXPUNGE round trip is needed
+        assert!(client.capabilities().is_some_and(|caps| caps.move_ && caps.uidplus));
+        assert_eq!(client.move_email("INBOX", 1, "Archive").await.unwrap(), MoveReport::moved(MoveMethod::Move));
+        client.delete_email("INBOX", second, false).await.unwrap();
+        assert!(server.commands().iter().any(|command| command.contains("UID MOVE")));
+        assert!(!server.commands().iter().any(|command| command.contains("COPY")));
+        assert!(server.messages("INBOX").is_empty());
+        assert_eq!(server.messages("Archive").len(), 1);
+        assert_eq!(server.messages("Trash").len(), 1);
+        assert!(!server.messages("Trash")[0].has_flag("\\Deleted"));
+    }
+
+    #[tokio::test]
+    async fn test_move_without_move_capability_rolls_back() {
+        let server = MockImapServer::start("alice@mock.test", "secret").await.unwrap();
+        server.set_capabilities("IMAP4rev1 UIDPLUS");
+        server.add_mailbox("Archive", &[]);
+        server.append("INBOX", &sample_message("bob@mock.test", "alice@mock.test", "First", "One"), &[]);
+        let second = server.append("INBOX", &sample_message("bob@mock.test", "alice@mock.test", "Second", "Two"), &[]);
+
+        let mut client = connected_client(&server).await;
+        assert!(client.capabilities().is_some_and(|caps| !caps.move_));
+
+        let report = client.move_email("INBOX", 1, "Archive").await.unwrap();
+        assert_eq!(report, MoveReport::moved(MoveMethod::CopyDelete));
+        assert!(!server.commands().iter().any(|command| command.contains("MOVE")));
+        assert_eq!(server.messages("INBOX").len(), 1);
+        assert_eq!(server.messages("Archive").len(), 1);
+
+        // EXPUNGE fails after the copy: the copy goes away, the original stays unflagged
+        server.fail_next("UID EXPUNGE", "NO [INUSE] Mailbox busy");
+        let report = client.move_email("INBOX", second, "Archive").await.unwrap();
+        assert_eq!(report.method, MoveMethod::CopyDelete);
+        assert!(matches!(report.outcome, MoveOutcome::RolledBack { failed_step: MoveStep::Expunge, .. }));
+        assert!(report.into_result().is_err());
+
+        let inbox = server.messages("INBOX");
+        assert_eq!(inbox.len(), 1);
+        assert!(!inbox[0].has_flag("\\Deleted"));
+        assert_eq!(server.messages("Archive").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_move_without_uidplus_leaves_original_flagged() {
+        let server = MockImapServer::start("alice@mock.test", "secret").await.unwrap();
+        server.set_capabilities("IMAP4rev1");
+        server.add_mailbox("Archive", &[]);
+        let moved = server.append("INBOX", &sample_message("bob@mock.test", "alice@mock.test", "First", "One"), &[]);
+        // Deleted by another client but not yet expunged
+        server.append("INBOX", &sample_message("bob@mock.test", "alice@mock.test", "Second", "Two"), &["\\Deleted"]);
+
+        let mut client = connected_client(&server).await;
+        assert!(client.capabilities().is_some_and(|caps| !caps.move_ && !caps.uidplus));
+
+        let report = client.move_email("INBOX", moved, "Archive").await.unwrap();
+        assert_eq!(report, MoveReport { method: MoveMethod::CopyDelete, outcome: MoveOutcome::LeftFlagged });
+        assert!(report.into_result().is_ok());
+        assert!(!server.commands().iter().any(|command| command.contains("EXPUNGE")));
+
+        // Neither message was expunged; the other client's deletion is untouched
+        let inbox = server.messages("INBOX");
+        assert_eq!(inbox.len(), 2);
+        assert!(inbox.iter().all(|message| message.has_flag("\\Deleted")));
+        assert_eq!(server.messages("Archive").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_failures_surface_as_errors() {
+        let server = MockImapServer::start("alice@mock.test", "secret").await.unwrap();
+
+        let mut wrong = server.config();
+        wrong.password = "nope".to_string();
+        assert!(AsyncImapClient::new(wrong).connect().await.is_err());
+
+        let mut client = connected_client(&server).await;
+        server.fail_next("SELECT", "NO [UNAVAILABLE] Mailbox busy");
+        assert!(client.search("INBOX", "anything").await.is_err());
+        assert!(client.search("INBOX", "anything").await.is_ok());
+        assert!(server.commands().contains(&"LOGIN ***".to_string()));
+    }
+
+    #[test]
+    fn test_plain_connections_are_limited_to_loopback() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut config = runtime.block_on(MockImapServer::start("a", "b")).unwrap().config();
+        config.host = "imap.example.com".to_string();
+
+        let result = runtime.block_on(AsyncImapClient::new(config).connect());
+        assert!(matches!(result, Err(MailError::Connection(_)) | Err(MailError::Config(_))));
+    }
+
+    fn add_account(db: &Database, smtp: &MockSmtpServer) -> i64 {
+        db.add_account(&NewAccount {
+            imap_host: "127.0.0.1".to_string(),
+            imap_port: 143,
+            imap_security: "NONE".to_string(),
+            smtp_host: smtp.host(),
+            smtp_port: i32::from(smtp.port()),
+            smtp_security: "NONE".to_string(),
+            password_encrypted: Some(crate::crypto::encrypt_password("secret").unwrap()),
+            sync_days: 0,
+            ..test_new_account("alice@mock.test")
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send_and_backfill_pipeline() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let smtp = MockSmtpServer::start("alice@mock.test", "secret").await.unwrap();
+        let account_id = add_account(&db, &smtp);
+
+        let message = QueuedSend {
+            to: vec!["bob@mock.test".to_string()],
+            cc: Vec::new(),
+            bcc: vec!["audit@mock.test".to_string()],
+            subject: "Quarterly report".to_string(),
+            text_body: Some("See attached numbers".to_string()),
+            html_body: None,
+            attachment_paths: Vec::new(),
+            on_behalf_of: None,
+        };
+        let credentials = crate::credentials::CredentialCache::new();
+        crate::deliver_email(&db, &credentials, account_id, &message, None).await.unwrap();
+
+        let received = smtp.received();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].from, "alice@mock.test");
+        assert_eq!(received[0].recipients, vec!["bob@mock.test", "audit@mock.test"]);
+        assert!(received[0].text().contains("Subject: Quarterly report"));
+        assert!(!received[0].text().contains("audit@mock.test"));
+
+        smtp.fail_next("RCPT", "550 5.1.1 No such user");
+        assert!(crate::deliver_email(&db, &credentials, account_id, &message, None).await.is_err());
+        assert_eq!(smtp.received().len(), 1);
+
+        // Backfill the sent copy and a reply into the local cache
+        let imap = MockImapServer::start("alice@mock.test", "secret").await.unwrap();
+        imap.append("INBOX", &received[0].data, &[]);
+        imap.append("INBOX", &sample_message("bob@mock.test", "alice@mock.test", "Re: Quarterly report", "Thanks"), &["\\Seen"]);
+
+        let mut client = connected_client(&imap).await;
+        let state = crate::backfill::run(&db, &mut client, account_id, "INBOX", None, || true, |_| {})
+            .await
+            .unwrap();
+        assert!(state.done);
+        assert_eq!((state.total, state.fetched), (2, 2));
+
+        let cached: Vec<String> = db
+            .query("SELECT subject FROM emails WHERE account_id = ?1 ORDER BY uid", [account_id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cached, vec!["Quarterly report", "Re: Quarterly report"]);
+    }
+}
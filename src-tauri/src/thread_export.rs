@@ -0,0 +1,356 @@
+//! Conversation Export
+//!
+//! Renders a whole thread into one HTML file for printing or archiving, such
+//! as keeping legal correspondence outside the mailbox. Each message shows
+//! its headers, body and the names of its attachments. Bodies are sanitized:
+//! scripts, event handlers, styles and forms are removed. The file is
+//! self-contained and opens without network access: inline images stored
+//! locally are embedded as data URIs, remote images are dropped.
+//!
+//! The Markdown form is meant for pasting into note-taking tools: plain text
+//! bodies with quoted replies and signatures trimmed, a participant list, and
+//! attachments linked to copies saved next to the note when a folder is given.
+
+use crate::attachment_store::AttachmentStore;
+use crate::db::{Database, DbResult, Email};
+use crate::mail::compose::{escape_html, normalize_content_id};
+use crate::mail::{html_text, quotes};
+use base64::Engine;
+use rusqlite::params;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Largest inline image embedded (bigger ones are left out)
+const MAX_INLINE_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Print-friendly styles of the exported document
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Roboto, Helvetica, Arial, sans-serif; color: #111; max-width: 860px; margin: 24px auto; padding: 0 16px; }
+h1 { font-size: 20px; margin-bottom: 4px; }
+.meta { color: #555; font-size: 12px; margin-bottom: 24px; }
+.message { border-top: 1px solid #ccc; padding: 16px 0; page-break-inside: avoid; }
+.headers { font-size: 13px; margin-bottom: 12px; }
+.headers th { text-align: left; color: #555; font-weight: normal; padding-right: 12px; vertical-align: top; }
+.body img { max-width: 100%; height: auto; }
+.body pre { white-space: pre-wrap; word-wrap: break-word; font-family: inherit; }
+.attachments { font-size: 12px; color: #555; margin-top: 12px; }
+@media print { body { margin: 0; max-width: none; } }
+";
+
+/// Messages of a thread, oldest first (one copy per Message-ID)
+fn thread_emails(db: &Database, account_id: i64, thread_id: &str) -> DbResult<Vec<Email>> {
+    let ids: Vec<i64> = db.query(
+        "SELECT MAX(id) FROM emails
+         WHERE account_id = ?1 AND is_deleted = 0
+           AND (thread_id = ?2 OR message_id = ?2 OR in_reply_to = ?2
+                OR instr(COALESCE(references_header, ''), ?2) > 0)
+         GROUP BY message_id
+         ORDER BY date ASC",
+        params![account_id, thread_id],
+        |row| row.get(0),
+    )?;
+    ids.into_iter().map(|id| db.get_email(id)).collect()
+}
+
+/// Display form of a JSON address list (`["a@b"]` or `[{"name", "email"}]`)
+fn format_addresses(json: &str) -> String {
+    serde_json::from_str::<Vec<serde_json::Value>>(json)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|value| {
+            if let Some(address) = value.as_str() {
+                return Some(address.to_string());
+            }
+            let email = value.get("email")?.as_str()?;
+            match value.get("name").and_then(|name| name.as_str()).filter(|name| !name.is_empty()) {
+                Some(name) => Some(format!("{} <{}>", name, email)),
+                None => Some(email.to_string()),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_date(date: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(date)
+        .map(|date| date.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+/// Sanitize an HTML body, replacing `cid:` images with the data URIs in `images`
+fn sanitize(html: &str, images: HashMap<String, String>) -> String {
+    ammonia::Builder::default()
+        .add_url_schemes(["cid", "data"])
+        .attribute_filter(move |element, attribute, value| {
+            let lower = value.trim_start().to_ascii_lowercase();
+            match (element, attribute) {
+                ("img", "src") if lower.starts_with("cid:") => {
+                    images.get(&normalize_content_id(&value.trim_start()[4..])).cloned().map(Cow::Owned)
+                }
+                ("img", "src") if lower.starts_with("data:image/") => Some(value.into()),
+                // Remote images would be fetched when the file is opened
+                ("img", "src") => None,
+                _ if lower.starts_with("data:") || lower.starts_with("cid:") => None,
+                _ => Some(value.into()),
+            }
+        })
+        .clean(html)
+        .to_string()
+}
+
+/// Inline images of a message as data URIs, by Content-ID
+fn inline_images(db: &Database, store: &AttachmentStore, attachments: &[crate::db::Attachment]) -> HashMap<String, String> {
+    attachments
+        .iter()
+        .filter(|attachment| attachment.content_type.to_ascii_lowercase().starts_with("image/"))
+        .filter_map(|attachment| {
+            let content_id = normalize_content_id(attachment.content_id.as_deref()?);
+            let bytes = store.load(db, attachment)?;
+            if bytes.len() > MAX_INLINE_IMAGE_BYTES {
+                return None;
+            }
+            let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+            Some((content_id, format!("data:{};base64,{}", attachment.content_type, data)))
+        })
+        .collect()
+}
+
+fn render_message(db: &Database, store: &AttachmentStore, email: &Email) -> DbResult<String> {
+    let attachments = db.get_attachments_for_email(email.id)?;
+
+    let from = match email.from_name.as_deref().filter(|name| !name.is_empty()) {
+        Some(name) => format!("{} <{}>", name, email.from_address),
+        None => email.from_address.clone(),
+    };
+    let mut headers = vec![("From", from), ("Date", format_date(&email.date))];
+    for (label, json) in [("To", &email.to_addresses), ("Cc", &email.cc_addresses)] {
+        let addresses = format_addresses(json);
+        if !addresses.is_empty() {
+            headers.push((label, addresses));
+        }
+    }
+    headers.push(("Subject", email.subject.clone()));
+
+    let body = match (&email.body_html, &email.body_text) {
+        (Some(html), _) if !html.trim().is_empty() => sanitize(html, inline_images(db, store, &attachments)),
+        (_, Some(text)) => format!("<pre>{}</pre>", escape_html(text)),
+        _ => format!("<pre>{}</pre>", escape_html(&email.preview)),
+    };
+
+    let mut html = String::from("<section class=\"message\">\n<table class=\"headers\">\n");
+    for (label, value) in headers {
+        html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, escape_html(&value)));
+    }
+    html.push_str(&format!("</table>\n<div class=\"body\">{}</div>\n", body));
+
+    let listed: Vec<String> = attachments
+        .iter()
+        .filter(|attachment| !attachment.is_inline)
+        .map(|attachment| format!("{} ({} KB)", escape_html(&attachment.filename), (attachment.size.max(0) as u64).div_ceil(1024)))
+        .collect();
+    if !listed.is_empty() {
+        html.push_str(&format!("<div class=\"attachments\">Attachments: {}</div>\n", listed.join(", ")));
+    }
+    html.push_str("</section>\n");
+    Ok(html)
+}
+
+/// Self-contained HTML document of a thread
+pub fn render(db: &Database, store: &AttachmentStore, account_id: i64, thread_id: &str) -> Result<String, String> {
+    let db_error = |e: crate::db::DbError| format!("Failed to read thread: {}", e);
+    let emails = thread_emails(db, account_id, thread_id).map_err(db_error)?;
+    let Some(first) = emails.first() else {
+        return Err("Thread not found".to_string());
+    };
+
+    let title = escape_html(if first.subject.is_empty() { "(no subject)" } else { &first.subject });
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta http-equiv=\"Content-Security-Policy\" content=\"default-src 'none'; img-src data:; style-src 'unsafe-inline'\">\n\
+         <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n\
+         <div class=\"meta\">{} messages, exported {}</div>\n",
+        emails.len(),
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+    );
+    for email in &emails {
+        html.push_str(&render_message(db, store, email).map_err(db_error)?);
+    }
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+fn sender(email: &Email) -> String {
+    match email.from_name.as_deref().filter(|name| !name.is_empty()) {
+        Some(name) => format!("{} <{}>", name, email.from_address),
+        None => email.from_address.clone(),
+    }
+}
+
+/// What the sender wrote, as plain text without quotes and signature
+fn own_text(email: &Email) -> String {
+    let text = match (&email.body_text, &email.body_html) {
+        (Some(text), _) if !text.trim().is_empty() => text.clone(),
+        (_, Some(html)) => html_text::html_to_text(html),
+        _ => email.preview.clone(),
+    };
+    quotes::own_text(&text)
+}
+
+/// Markdown link target for a path (angle brackets allow spaces)
+fn link_target(path: &Path) -> String {
+    format!("<{}>", path.display().to_string().replace('>', "%3E"))
+}
+
+/// Attachment lines of a message, saving downloaded ones into `attachments_dir`
+fn markdown_attachments(
+    db: &Database,
+    store: &AttachmentStore,
+    attachments: &[crate::db::Attachment],
+    attachments_dir: Option<&Path>,
+) -> Result<Vec<String>, String> {
+    let mut lines = Vec::new();
+    for attachment in attachments.iter().filter(|attachment| !attachment.is_inline) {
+        let label = format!("{} ({} KB)", attachment.filename, (attachment.size.max(0) as u64).div_ceil(1024));
+        let saved = match (attachments_dir, store.load(db, attachment)) {
+            (Some(dir), Some(bytes)) => {
+                let path = dir.join(format!("{}_{}", attachment.id, crate::sanitize_filename(&attachment.filename)));
+                std::fs::write(&path, bytes).map_err(|e| format!("Failed to save {}: {}", attachment.filename, e))?;
+                Some(path)
+            }
+            _ => None,
+        };
+        lines.push(match saved {
+            Some(path) => format!("- [{}]({})", label, link_target(&path)),
+            None => format!("- {}", label),
+        });
+    }
+    Ok(lines)
+}
+
+/// Markdown of a thread for note-taking tools
+/// Downloaded attachments are copied into `attachments_dir` (when given) and linked.
+pub fn render_markdown(
+    db: &Database,
+    store: &AttachmentStore,
+    account_id: i64,
+    thread_id: &str,
+    attachments_dir: Option<&Path>,
+) -> Result<String, String> {
+    let db_error = |e: crate::db::DbError| format!("Failed to read thread: {}", e);
+    let emails = thread_emails(db, account_id, thread_id).map_err(db_error)?;
+    let Some(first) = emails.first() else {
+        return Err("Thread not found".to_string());
+    };
+    if let Some(dir) = attachments_dir {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create attachments folder: {}", e))?;
+    }
+
+    let mut participants: Vec<String> = Vec::new();
+    for email in &emails {
+        let recipients = [email.to_addresses.as_str(), email.cc_addresses.as_str()].map(format_addresses);
+        for participant in std::iter::once(sender(email))
+            .chain(recipients.iter().flat_map(|list| list.split(", ").map(str::to_string)))
+        {
+            if !participant.is_empty() && !participants.contains(&participant) {
+                participants.push(participant);
+            }
+        }
+    }
+
+    let title = if first.subject.is_empty() { "(no subject)" } else { &first.subject };
+    let mut markdown = format!("# {}\n\n", title);
+    markdown.push_str(&format!("**Participants:** {}  \n", participants.join(", ")));
+    markdown.push_str(&format!("**Messages:** {}\n", emails.len()));
+
+    for email in &emails {
+        markdown.push_str(&format!("\n---\n\n## {} — {}\n\n", sender(email), format_date(&email.date)));
+        for (label, json) in [("To", &email.to_addresses), ("Cc", &email.cc_addresses)] {
+            let addresses = format_addresses(json);
+            if !addresses.is_empty() {
+                markdown.push_str(&format!("**{}:** {}  \n", label, addresses));
+            }
+        }
+        markdown.push('\n');
+        let text = own_text(email);
+        if !text.is_empty() {
+            markdown.push_str(&text);
+            markdown.push('\n');
+        }
+
+        let attachments = db.get_attachments_for_email(email.id).map_err(db_error)?;
+        let lines = markdown_attachments(db, store, &attachments, attachments_dir)?;
+        if !lines.is_empty() {
+            markdown.push_str(&format!("\n**Attachments:**\n\n{}\n", lines.join("\n")));
+        }
+    }
+    Ok(markdown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_and_addresses() {
+        let images = HashMap::from([("logo@example.com".to_string(), "data:image/png;base64,AAAA".to_string())]);
+        let html = sanitize(
+            r#"<p onclick="steal()">Hi<script>alert(1)</script></p>
+               <img src="cid:logo@example.com" alt="Logo"><img src="https://tracker.test/p.gif">
+               <img src="cid:missing@example.com"><a href="data:text/html,x">x</a><a href="https://example.com">ok</a>"#,
+            images,
+        );
+        assert!(!html.contains("script") && !html.contains("onclick"));
+        assert!(html.contains(r#"src="data:image/png;base64,AAAA""#));
+        assert!(!html.contains("tracker.test") && !html.contains("cid:") && !html.contains("data:text"));
+        assert!(html.contains(r#"href="https://example.com""#));
+
+        assert_eq!(
+            format_addresses(r#"[{"name": "Ana", "email": "ana@example.com"}, "bob@example.com", {"email": "c@example.com"}]"#),
+            "Ana <ana@example.com>, bob@example.com, c@example.com"
+        );
+        assert_eq!(format_addresses("not json"), "");
+    }
+
+    #[test]
+    fn test_render_markdown_trims_quotes() {
+        use crate::db::{test_account, test_email, NewEmail, NewFolder};
+
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+        for (uid, from, body, date) in [
+            (1, "ana@test.com", "Can we meet on Friday?", "2024-03-01T09:00:00Z"),
+            (2, "me@test.com", "Friday works.\n\nOn Fri, Ana wrote:\n> Can we meet on Friday?", "2024-03-01T10:00:00Z"),
+        ] {
+            db.upsert_email(&NewEmail {
+                from_address: from.to_string(),
+                to_addresses: r#"["bob@test.com"]"#.to_string(),
+                subject: "Meeting".to_string(),
+                body_text: Some(body.to_string()),
+                date: date.to_string(),
+                is_read: true,
+                thread_id: Some("<1@test.com>".to_string()),
+                ..test_email(account_id, folder_id, uid)
+            })
+            .unwrap();
+        }
+
+        let store = AttachmentStore::new(std::env::temp_dir().join("owlivion-markdown-test"));
+        let markdown = render_markdown(&db, &store, account_id, "<1@test.com>", None).unwrap();
+        assert!(markdown.starts_with("# Meeting\n"));
+        assert!(markdown.contains("**Participants:** ana@test.com, bob@test.com, me@test.com"));
+        assert!(markdown.contains("Friday works."));
+        assert_eq!(markdown.matches("Can we meet on Friday?").count(), 1);
+    }
+}
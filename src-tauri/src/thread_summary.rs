@@ -0,0 +1,256 @@
+//! Thread Summaries
+//!
+//! The whole conversation is sent to the configured AI provider, which
+//! returns a short summary, the decisions taken and the action items with
+//! their owners and due dates. Results are cached per thread together with a
+//! fingerprint of the messages they cover; when a new message joins the
+//! thread the fingerprint changes and the next request regenerates it.
+//!
+//! A thread is identified by its `thread_id`, or by the Message-ID of its
+//! first message when the server provides no thread ids.
+
+use crate::ai::{self, ProviderConfig};
+use crate::db::{Database, DbResult};
+use crate::mail::quotes;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Messages of a thread sent to the provider (the most recent ones)
+const MAX_MESSAGES: usize = 50;
+
+/// Characters of each message body in the prompt
+const MAX_BODY_CHARS: usize = 3000;
+
+const MAX_OUTPUT_TOKENS: u32 = 1024;
+
+/// Action item extracted from a thread
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export, rename = "ThreadActionItem"))]
+#[serde(rename_all = "camelCase")]
+pub struct ActionItem {
+    pub task: String,
+    /// Person responsible, as named in the thread
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Due date as written or resolved (YYYY-MM-DD when possible)
+    #[serde(default)]
+    pub due: Option<String>,
+}
+
+/// Result of `thread_summarize`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadSummary {
+    pub summary: String,
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    #[serde(default)]
+    pub action_items: Vec<ActionItem>,
+    /// Messages the summary covers
+    #[serde(default)]
+    pub message_count: usize,
+    /// Served from the cache
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// Message of a thread, oldest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadMessage {
+    pub id: i64,
+    pub from: String,
+    pub from_name: Option<String>,
+    pub date: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Messages of a thread (one copy per Message-ID)
+pub fn thread_messages(db: &Database, account_id: i64, thread_id: &str) -> DbResult<Vec<ThreadMessage>> {
+    let mut messages = db.query(
+        "SELECT MAX(id), from_address, from_name, date, subject, COALESCE(body_text, preview)
+         FROM emails
+         WHERE account_id = ?1 AND is_deleted = 0
+           AND (thread_id = ?2 OR message_id = ?2 OR in_reply_to = ?2
+                OR instr(COALESCE(references_header, ''), ?2) > 0)
+         GROUP BY message_id
+         ORDER BY date DESC
+         LIMIT ?3",
+        params![account_id, thread_id, MAX_MESSAGES as i64],
+        |row| {
+            Ok(ThreadMessage {
+                id: row.get(0)?,
+                from: row.get(1)?,
+                from_name: row.get(2)?,
+                date: row.get(3)?,
+                subject: row.get(4)?,
+                body: row.get(5)?,
+            })
+        },
+    )?;
+    messages.reverse();
+    Ok(messages)
+}
+
+/// Identifies the set of messages a summary was generated from
+pub fn fingerprint(messages: &[ThreadMessage]) -> String {
+    let mut ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+    ids.sort_unstable();
+    format!("{}:{}", ids.len(), ids.iter().map(i64::to_string).collect::<Vec<_>>().join(","))
+}
+
+fn cached(db: &Database, account_id: i64, thread_id: &str, fingerprint: &str) -> DbResult<Option<ThreadSummary>> {
+    let rows = db.query(
+        "SELECT summary_json FROM thread_summaries WHERE account_id = ?1 AND thread_id = ?2 AND fingerprint = ?3",
+        params![account_id, thread_id, fingerprint],
+        |row| row.get::<_, String>(0),
+    )?;
+    Ok(rows.first().and_then(|json| serde_json::from_str(json).ok()))
+}
+
+fn store(db: &Database, account_id: i64, thread_id: &str, fingerprint: &str, summary: &ThreadSummary) -> DbResult<()> {
+    let json = serde_json::to_string(summary).map_err(|e| crate::db::DbError::Serialization(e.to_string()))?;
+    db.execute(
+        "INSERT OR REPLACE INTO thread_summaries (account_id, thread_id, fingerprint, summary_json, created_at)
+         VALUES (?1, ?2, ?3, ?4, datetime('now'))",
+        params![account_id, thread_id, fingerprint, json],
+    )?;
+    Ok(())
+}
+
+/// First `max` characters of `text`
+fn head(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+/// Prompt asking for a JSON summary of the conversation
+pub fn build_prompt(messages: &[ThreadMessage]) -> String {
+    let mut prompt = String::from(
+        "Summarize this email conversation. Reply with JSON only, in the language of the conversation:\n\
+         {\"summary\": \"2-4 sentences\", \"decisions\": [\"decision taken\"], \
+         \"actionItems\": [{\"task\": \"what\", \"owner\": \"who or null\", \"due\": \"YYYY-MM-DD, as written, or null\"}]}\n\
+         Use empty lists when there are no decisions or action items.\n",
+    );
+    for message in messages {
+        let sender = match &message.from_name {
+            Some(name) if !name.trim().is_empty() => format!("{} <{}>", name.trim(), message.from),
+            _ => message.from.clone(),
+        };
+        prompt.push_str(&format!(
+            "\n--- From: {}\nDate: {}\nSubject: {}\n\n{}\n",
+            sender,
+            message.date,
+            message.subject,
+            head(&own_text(&message.body), MAX_BODY_CHARS)
+        ));
+    }
+    prompt
+}
+
+/// Body without the quoted earlier messages, which the prompt already has
+fn own_text(body: &str) -> String {
+    let text = quotes::own_text(body);
+    if text.is_empty() {
+        body.trim().to_string()
+    } else {
+        text
+    }
+}
+
+/// Parse the provider's reply, tolerating Markdown code fences around the JSON
+pub fn parse_response(text: &str) -> Result<ThreadSummary, String> {
+    let start = text.find('{').ok_or("AI response contains no summary")?;
+    let end = text.rfind('}').filter(|end| *end > start).ok_or("AI response contains no summary")?;
+    let mut summary: ThreadSummary =
+        serde_json::from_str(&text[start..=end]).map_err(|e| format!("Invalid summary from AI provider: {}", e))?;
+
+    summary.summary = summary.summary.trim().to_string();
+    summary.decisions.retain(|decision| !decision.trim().is_empty());
+    summary.action_items.retain(|item| !item.task.trim().is_empty());
+    for item in &mut summary.action_items {
+        for field in [&mut item.owner, &mut item.due] {
+            if field.as_deref().is_some_and(|value| value.trim().is_empty() || value.eq_ignore_ascii_case("null")) {
+                *field = None;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Summarize a thread, from the cache when no message joined it since
+/// `share_context`: whether the account allows mail content to go to remote providers.
+pub async fn summarize(
+    db: &Database,
+    config: &ProviderConfig,
+    share_context: bool,
+    account_id: i64,
+    thread_id: &str,
+) -> Result<ThreadSummary, String> {
+    let messages = thread_messages(db, account_id, thread_id).map_err(|e| format!("Database error: {}", e))?;
+    if messages.is_empty() {
+        return Err("Thread not found".to_string());
+    }
+
+    let fingerprint = fingerprint(&messages);
+    if let Some(summary) = cached(db, account_id, thread_id, &fingerprint).map_err(|e| format!("Database error: {}", e))? {
+        return Ok(ThreadSummary { cached: true, ..summary });
+    }
+
+    if config.is_remote() && !share_context {
+        return Err("Sharing mail content with the AI provider is disabled for this account".to_string());
+    }
+
+    let response = ai::generate(config, &build_prompt(&messages), MAX_OUTPUT_TOKENS).await?;
+    let summary = ThreadSummary { message_count: messages.len(), cached: false, ..parse_response(&response)? };
+    store(db, account_id, thread_id, &fingerprint, &summary).map_err(|e| format!("Database error: {}", e))?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let text = "```json\n{\"summary\": \" Launch moves to May. \", \"decisions\": [\"Ship on 2025-05-02\", \"\"],\n\
+                    \"actionItems\": [{\"task\": \"Update the plan\", \"owner\": \"Ana\", \"due\": \"null\"}, {\"task\": \" \"}]}\n```";
+        let summary = parse_response(text).unwrap();
+        assert_eq!(summary.summary, "Launch moves to May.");
+        assert_eq!(summary.decisions, vec!["Ship on 2025-05-02".to_string()]);
+        assert_eq!(
+            summary.action_items,
+            vec![ActionItem { task: "Update the plan".to_string(), owner: Some("Ana".to_string()), due: None }]
+        );
+        assert!(parse_response("Sorry, I can't help with that.").is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_new_message() {
+        let message = |id: i64| ThreadMessage {
+            id,
+            from: "ana@example.com".to_string(),
+            from_name: Some("Ana".to_string()),
+            date: "2025-01-01T10:00:00Z".to_string(),
+            subject: "Launch".to_string(),
+            body: "x".repeat(MAX_BODY_CHARS + 10),
+        };
+        let thread = vec![message(3), message(1)];
+        assert_eq!(fingerprint(&thread), "2:1,3");
+        assert_ne!(fingerprint(&thread), fingerprint(&[message(1), message(3), message(7)]));
+
+        let prompt = build_prompt(&thread);
+        assert!(prompt.contains("From: Ana <ana@example.com>"));
+        assert!(!prompt.contains(&"x".repeat(MAX_BODY_CHARS + 1)));
+
+        let reply = ThreadMessage {
+            body: "Friday works.\n\nOn Mon, Ana wrote:\n> Is Friday ok?\n\n-- \nBob".to_string(),
+            ..message(4)
+        };
+        let prompt = build_prompt(&[reply]);
+        assert!(prompt.contains("\n\nFriday works.\n") && !prompt.contains("Is Friday ok?") && !prompt.contains("Bob"));
+    }
+}
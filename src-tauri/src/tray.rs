@@ -6,7 +6,7 @@ use tauri::{
     image::Image,
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Emitter, Manager, Runtime,
+    AppHandle, Manager, Runtime,
 };
 
 /// Get tray icon - use white icon for better visibility on dark panels
@@ -72,7 +72,7 @@ pub fn setup_tray<R: Runtime>(app: &AppHandle<R>) -> Result<(), Box<dyn std::err
                         let _ = window.set_focus();
                         let _ = window.unminimize();
                         // Emit event to frontend to open compose modal (matches existing listener)
-                        let _ = window.emit("tray:new-email", ());
+                        crate::events::emit(&window, &crate::hotkeys::ComposeRequested);
                     }
                 }
                 "quit" => {
@@ -0,0 +1,183 @@
+//! UIDVALIDITY Resets
+//!
+//! A server that changes a folder's UIDVALIDITY (mailbox rebuilt, restored
+//! from backup, migrated) invalidates every cached UID: upserting the fresh
+//! listing would attach new flags to the wrong cached rows or duplicate
+//! messages. When a change is detected the folder's cached rows are
+//! quarantined (hidden, with their old UID kept in `email_quarantine`) and the
+//! folder is synced again. Messages that come back with the same Message-ID
+//! take their new UID and keep their bodies, labels and other local state;
+//! the rest are deleted after `QUARANTINE_DAYS`. The outcome is reported to
+//! the UI.
+
+use crate::db::{Database, DbResult};
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// Tauri event name for the report of a finished re-sync
+pub const RESYNC_REPORT_EVENT: &str = "sync://uidvalidity-reset";
+
+/// Outcome of re-syncing a folder after its UIDVALIDITY changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncReport {
+    pub account_id: i64,
+    pub folder: String,
+    pub previous_uid_validity: u32,
+    pub uid_validity: u32,
+    /// Cached messages moved out of the folder
+    pub quarantined: usize,
+    /// Of those, found again on the server and refreshed
+    pub refreshed: usize,
+    /// Still in quarantine (not found again by Message-ID)
+    pub unmatched: usize,
+}
+
+/// Record the folder's current UIDVALIDITY; returns the previous one when it changed
+/// The first value seen for a folder is only stored.
+pub fn check(db: &Database, account_id: i64, folder_id: i64, current: u32) -> DbResult<Option<u32>> {
+    let previous: Option<u32> = db
+        .query(
+            "SELECT uid_validity FROM sync_state WHERE account_id = ?1 AND folder_id = ?2",
+            params![account_id, folder_id],
+            |row| row.get::<_, Option<u32>>(0),
+        )?
+        .pop()
+        .flatten();
+    if previous == Some(current) {
+        return Ok(None);
+    }
+
+    db.execute(
+        r#"
+        INSERT INTO sync_state (account_id, folder_id, uid_validity)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT(account_id, folder_id) DO UPDATE SET
+            uid_validity = excluded.uid_validity,
+            last_uid = CASE WHEN uid_validity IS NULL THEN last_uid ELSE 0 END
+        "#,
+        params![account_id, folder_id, current],
+    )?;
+    Ok(previous)
+}
+
+/// Quarantined messages not found again within this many days are dropped
+const QUARANTINE_DAYS: i64 = 30;
+
+/// Placeholder UIDs of quarantined rows start here (servers count up from 1)
+const PLACEHOLDER_UID_BASE: i64 = 1 << 31;
+
+/// Quarantine the folder's cached messages; returns how many were quarantined
+/// The rows stay in `emails` (with everything attached to them) but are
+/// hidden as deleted under a placeholder UID, so the re-synced listing cannot
+/// land on them. Storing a message with the same Message-ID again gives the
+/// row its new UID back (see `Database::upsert_email`).
+pub fn quarantine(db: &Database, account_id: i64, folder_id: i64, previous: u32) -> DbResult<usize> {
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    let moved = tx.execute(
+        "INSERT INTO email_quarantine (email_id, account_id, folder_id, uid_validity, uid, message_id)
+         SELECT id, account_id, folder_id, ?3, uid, message_id
+         FROM emails WHERE account_id = ?1 AND folder_id = ?2
+           AND id NOT IN (SELECT email_id FROM email_quarantine)",
+        params![account_id, folder_id, previous],
+    )?;
+    tx.execute(
+        "UPDATE emails SET uid = ?3 + id, is_deleted = 1
+         WHERE account_id = ?1 AND folder_id = ?2 AND id IN (SELECT email_id FROM email_quarantine)",
+        params![account_id, folder_id, PLACEHOLDER_UID_BASE],
+    )?;
+    tx.commit()?;
+    Ok(moved)
+}
+
+/// Messages quarantined at `uid_validity` that were not found again
+pub fn unmatched(db: &Database, account_id: i64, folder_id: i64, uid_validity: u32) -> DbResult<usize> {
+    let count: i64 = db.query_row(
+        "SELECT COUNT(*) FROM email_quarantine WHERE account_id = ?1 AND folder_id = ?2 AND uid_validity = ?3",
+        params![account_id, folder_id, uid_validity],
+        |row| row.get(0),
+    )?;
+    Ok(count as usize)
+}
+
+/// Delete quarantined messages older than `QUARANTINE_DAYS`; returns how many
+pub fn purge_expired(db: &Database, now: DateTime<Utc>) -> DbResult<usize> {
+    let cutoff = (now - chrono::Duration::days(QUARANTINE_DAYS)).format("%Y-%m-%d %H:%M:%S").to_string();
+    db.execute(
+        "DELETE FROM emails WHERE id IN (SELECT email_id FROM email_quarantine WHERE quarantined_at < ?1)",
+        params![cutoff],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewEmail};
+
+    fn email(account_id: i64, folder_id: i64, uid: u32, message_id: &str, body: Option<&str>) -> NewEmail {
+        NewEmail {
+            message_id: message_id.to_string(),
+            body_text: body.map(str::to_string),
+            ..test_email(account_id, folder_id, uid)
+        }
+    }
+
+    #[test]
+    fn test_quarantine_and_rekey() {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = crate::sync_folder_to_db(&db, account_id, "INBOX").unwrap();
+
+        assert_eq!(check(&db, account_id, folder_id, 7).unwrap(), None);
+        assert_eq!(check(&db, account_id, folder_id, 7).unwrap(), None);
+
+        let cached = db.upsert_email(&email(account_id, folder_id, 1, "<a@test>", Some("cached body"))).unwrap();
+        db.upsert_email(&email(account_id, folder_id, 2, "<b@test>", None)).unwrap();
+        db.upsert_email(&email(account_id, folder_id, 3, "uid-3", None)).unwrap();
+        db.execute("UPDATE emails SET labels = '[\"work\"]' WHERE id = ?1", params![cached]).unwrap();
+        db.execute("INSERT INTO email_ai_metadata (email_id, needs_reply) VALUES (?1, 1)", params![cached]).unwrap();
+
+        assert_eq!(check(&db, account_id, folder_id, 9).unwrap(), Some(7));
+        assert_eq!(quarantine(&db, account_id, folder_id, 7).unwrap(), 3);
+        let visible = |db: &Database| -> i64 {
+            db.query_row("SELECT COUNT(*) FROM emails WHERE folder_id = ?1 AND is_deleted = 0", [folder_id], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(visible(&db), 0);
+
+        // The server renumbered the folder; UID 1 is now a different message
+        db.batch_upsert_emails(&[
+            email(account_id, folder_id, 1, "<b@test>", None),
+            email(account_id, folder_id, 2, "<a@test>", None),
+            email(account_id, folder_id, 3, "uid-3", None),
+        ])
+        .unwrap();
+        assert_eq!(unmatched(&db, account_id, folder_id, 7).unwrap(), 1);
+        assert_eq!(visible(&db), 3);
+
+        // The same row took the new UID, with its body, labels and metadata
+        let (id, body, labels): (i64, Option<String>, String) = db
+            .query_row(
+                "SELECT id, body_text, labels FROM emails WHERE folder_id = ?1 AND uid = 2",
+                [folder_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((id, body.as_deref(), labels.as_str()), (cached, Some("cached body"), "[\"work\"]"));
+        let needs_reply: bool = db
+            .query_row("SELECT needs_reply FROM email_ai_metadata WHERE email_id = ?1", [cached], |row| row.get(0))
+            .unwrap();
+        assert!(needs_reply);
+
+        // Unmatched messages are dropped once expired
+        assert_eq!(purge_expired(&db, Utc::now()).unwrap(), 0);
+        assert_eq!(purge_expired(&db, Utc::now() + chrono::Duration::days(QUARANTINE_DAYS + 1)).unwrap(), 1);
+        assert_eq!(unmatched(&db, account_id, folder_id, 7).unwrap(), 0);
+        let count: i64 = db
+            .query_row("SELECT COUNT(*) FROM emails WHERE folder_id = ?1", [folder_id], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 3);
+    }
+}
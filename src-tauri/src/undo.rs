@@ -0,0 +1,256 @@
+//! Undo
+//!
+//! Mailbox actions (read, star, move, delete) return an undo token that stays
+//! valid for a configurable window, so the UI can offer an undo toast. If the
+//! action's server command is still queued, undoing cancels it and restores
+//! the local state. Once it has run, a compensating command is queued
+//! instead: the inverse flag change, or a move back out of the target (or
+//! Trash) folder, where the message is found again by its Message-ID. A
+//! permanent delete that already ran cannot be undone.
+
+use crate::db::{Database, DbResult};
+use crate::events::StoreEvents;
+use crate::pending_ops::{self, PendingOpKind, PendingOperation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Settings key: seconds an undo token stays valid
+const WINDOW_SETTING: &str = "undo_window_secs";
+
+const DEFAULT_WINDOW_SECS: u64 = 10;
+const MAX_WINDOW_SECS: u64 = 300;
+
+/// Token returned by a mutating command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "camelCase")]
+pub struct UndoHandle {
+    pub token: String,
+    /// Seconds the token stays valid
+    #[cfg_attr(test, ts(type = "number"))]
+    pub window_secs: u64,
+}
+
+/// Server command that reverses an action that already ran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compensation {
+    /// Queue this flag change for the message
+    Flag {
+        account_id: String,
+        folder: String,
+        uid: u32,
+        kind: PendingOpKind,
+    },
+    /// Find the message in `folder` by Message-ID and move it to `back_to`
+    MoveBack {
+        account_id: String,
+        folder: String,
+        message_id: String,
+        back_to: String,
+    },
+}
+
+pub fn window_secs(db: &Database) -> DbResult<u64> {
+    Ok(db.get_setting(WINDOW_SETTING)?.unwrap_or(DEFAULT_WINDOW_SECS))
+}
+
+/// Save the undo window; returns it as stored (1 to 300 seconds)
+pub fn set_window_secs(db: &Database, secs: u64) -> DbResult<u64> {
+    let secs = secs.clamp(1, MAX_WINDOW_SECS);
+    db.set_setting(WINDOW_SETTING, &secs)?;
+    Ok(secs)
+}
+
+struct Entry {
+    op: PendingOperation,
+    message_id: Option<String>,
+    expires: Instant,
+}
+
+/// Undo tokens issued in this session
+#[derive(Default)]
+pub struct UndoRegistry {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl UndoRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Issue a token for a queued operation
+    pub fn issue(&self, db: &Database, op: PendingOperation) -> UndoHandle {
+        let window_secs = window_secs(db).unwrap_or(DEFAULT_WINDOW_SECS);
+        // Needed to find the message again once it was moved on the server
+        let message_id = op.email_ids.first().and_then(|id| db.get_email(*id).ok()).map(|email| email.message_id);
+        let token = uuid::Uuid::new_v4().to_string();
+
+        let now = Instant::now();
+        let mut entries = self.lock();
+        entries.retain(|_, entry| entry.expires > now);
+        entries.insert(
+            token.clone(),
+            Entry { op, message_id, expires: now + Duration::from_secs(window_secs) },
+        );
+        UndoHandle { token, window_secs }
+    }
+
+    /// Undo an action: reverted locally when its server command is still
+    /// queued, otherwise the compensating command to run is returned
+    /// A command already being executed cannot be stopped; the next sync
+    /// brings the local state in line with the server again.
+    pub fn undo(&self, db: &Database, events: &StoreEvents, token: &str) -> Result<Option<Compensation>, String> {
+        let entry = self.lock().remove(token).ok_or("Nothing to undo")?;
+        if entry.expires <= Instant::now() {
+            return Err("The undo window has expired".to_string());
+        }
+
+        let op = entry.op;
+        if pending_ops::cancel(db, &op.id).map_err(|e| format!("Database error: {}", e))? {
+            pending_ops::revert_local(db, events, &op);
+            return Ok(None);
+        }
+
+        let flag = |kind| Compensation::Flag { account_id: op.account_id.clone(), folder: op.folder.clone(), uid: op.uid, kind };
+        let move_back = |folder: String| -> Result<Compensation, String> {
+            let message_id = entry.message_id.clone().ok_or("The message cannot be found again on the server")?;
+            Ok(Compensation::MoveBack { account_id: op.account_id.clone(), folder, message_id, back_to: op.folder.clone() })
+        };
+
+        match &op.kind {
+            PendingOpKind::SetRead { value } => {
+                Ok(Some(flag(PendingOpKind::SetRead { value: op.previous_value.unwrap_or(!value) })))
+            }
+            PendingOpKind::SetStarred { value } => {
+                Ok(Some(flag(PendingOpKind::SetStarred { value: op.previous_value.unwrap_or(!value) })))
+            }
+            PendingOpKind::SetKeyword { keyword, value } => Ok(Some(flag(PendingOpKind::SetKeyword {
+                keyword: keyword.clone(),
+                value: op.previous_value.unwrap_or(!value),
+            }))),
+            PendingOpKind::Move { target } => move_back(target.clone()).map(Some),
+            PendingOpKind::Delete { permanent: false } => {
+                let account_id: i64 = op.account_id.parse().map_err(|_| "Invalid account ID")?;
+                let trash = db
+                    .get_special_folder(account_id, "trash")
+                    .map_err(|e| format!("Database error: {}", e))?
+                    .ok_or("The account has no Trash folder to restore from")?;
+                move_back(trash).map(Some)
+            }
+            PendingOpKind::Delete { permanent: true } => Err("Permanently deleted messages cannot be restored".to_string()),
+            PendingOpKind::Send { .. } => Err("Sent messages cannot be undone".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_account, test_email, NewEmail, NewFolder};
+    use crate::pending_ops::PendingOps;
+    use std::sync::Arc;
+
+    fn setup() -> (Arc<Database>, String, i64) {
+        let db = Database::in_memory().expect("Failed to create test DB");
+        let account_id = test_account(&db);
+        let folder_id = db
+            .upsert_folder(&NewFolder {
+                account_id,
+                name: "INBOX".to_string(),
+                remote_name: "INBOX".to_string(),
+                folder_type: "inbox".to_string(),
+                is_subscribed: true,
+                is_selectable: true,
+                delimiter: "/".to_string(),
+            })
+            .unwrap();
+        let email_id = db
+            .upsert_email(&NewEmail {
+                message_id: "<undo@test.com>".to_string(),
+                ..test_email(account_id, folder_id, 7)
+            })
+            .unwrap();
+        (Arc::new(db), account_id.to_string(), email_id)
+    }
+
+    fn queue(db: &Arc<Database>, events: &StoreEvents, account_id: &str, kind: PendingOpKind) -> PendingOperation {
+        let op = pending_ops::apply_local(db, events, account_id, "INBOX", 7, kind).unwrap();
+        PendingOps::new(db.clone()).enqueue(&op).unwrap();
+        op
+    }
+
+    #[test]
+    fn test_undo_queued_action_reverts_locally() {
+        let (db, account_id, email_id) = setup();
+        let events = StoreEvents::new();
+        let undo = UndoRegistry::new();
+
+        let op = queue(&db, &events, &account_id, PendingOpKind::Delete { permanent: false });
+        assert!(db.get_email(email_id).unwrap().is_deleted);
+        let handle = undo.issue(&db, op);
+        assert_eq!(handle.window_secs, DEFAULT_WINDOW_SECS);
+
+        assert_eq!(undo.undo(&db, &events, &handle.token).unwrap(), None);
+        assert!(!db.get_email(email_id).unwrap().is_deleted);
+        assert!(pending_ops::load_queue(&db).unwrap().is_empty());
+        // Tokens are single-use
+        assert!(undo.undo(&db, &events, &handle.token).is_err());
+    }
+
+    #[test]
+    fn test_undo_after_server_ran_compensates() {
+        let (db, account_id, _) = setup();
+        let events = StoreEvents::new();
+        let undo = UndoRegistry::new();
+
+        let read = queue(&db, &events, &account_id, PendingOpKind::SetRead { value: true });
+        pending_ops::cancel(&db, &read.id).unwrap();
+        let handle = undo.issue(&db, read);
+        assert_eq!(
+            undo.undo(&db, &events, &handle.token).unwrap(),
+            Some(Compensation::Flag {
+                account_id: account_id.clone(),
+                folder: "INBOX".to_string(),
+                uid: 7,
+                kind: PendingOpKind::SetRead { value: false },
+            })
+        );
+
+        let moved = queue(&db, &events, &account_id, PendingOpKind::Move { target: "Archive".to_string() });
+        pending_ops::cancel(&db, &moved.id).unwrap();
+        let handle = undo.issue(&db, moved);
+        assert_eq!(
+            undo.undo(&db, &events, &handle.token).unwrap(),
+            Some(Compensation::MoveBack {
+                account_id: account_id.clone(),
+                folder: "Archive".to_string(),
+                message_id: "<undo@test.com>".to_string(),
+                back_to: "INBOX".to_string(),
+            })
+        );
+
+        let purged = queue(&db, &events, &account_id, PendingOpKind::Delete { permanent: true });
+        pending_ops::cancel(&db, &purged.id).unwrap();
+        let handle = undo.issue(&db, purged);
+        assert!(undo.undo(&db, &events, &handle.token).is_err());
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let (db, account_id, _) = setup();
+        let events = StoreEvents::new();
+        let undo = UndoRegistry::new();
+        db.set_setting(WINDOW_SETTING, &0u64).unwrap();
+
+        let op = queue(&db, &events, &account_id, PendingOpKind::SetStarred { value: true });
+        let handle = undo.issue(&db, op);
+        assert_eq!(undo.undo(&db, &events, &handle.token), Err("The undo window has expired".to_string()));
+        assert_eq!(set_window_secs(&db, 0).unwrap(), 1);
+    }
+}
@@ -13,7 +13,7 @@ import SearchFiltersComponent from "./components/SearchFilters";
 import { summarizeEmail, analyzePhishing, detectEmailTracking, type PhishingAnalysis, type TrackingAnalysis } from "./services/geminiService";
 import { requestNotificationPermission, showNewEmailNotification, playNotificationSound } from "./services/notificationService";
 import { listDrafts, getDraft, deleteDraft } from "./services/draftService";
-import type { DraftEmail, EmailAddress, Account, ImapFolder, DraftListItem, SearchFilters } from "./types";
+import type { DraftEmail, EmailAddress, Account, ImapFolder, DraftListItem, SearchFilters, ListedEmail } from "./types";
 
 // Configure DOMPurify to remove dangerous content
 // SECURITY: 'style' attribute removed to prevent CSS injection attacks (e.g., expression(), url(javascript:))
@@ -1674,8 +1674,8 @@ function App() {
 
   // Settings state for API keys and auto-sync
   const [geminiApiKey, setGeminiApiKey] = useState<string | undefined>(undefined);
-  const [autoSyncEnabled, setAutoSyncEnabled] = useState(false);
-  const [autoSyncInterval, setAutoSyncInterval] = useState(5); // minutes
+  // Inbox UIDs and state token of accounts refreshed while not shown
+  const backgroundRefresh = useRef<Map<number, { known: Set<number>; stateToken?: string }>>(new Map());
   const [autoPhishingDetection, setAutoPhishingDetection] = useState(true); // Auto phishing detection enabled by default
 
   // Load settings from localStorage on mount
@@ -1685,8 +1685,13 @@ function App() {
       if (saved) {
         const settings = JSON.parse(saved);
         setGeminiApiKey(settings.geminiApiKey);
-        setAutoSyncEnabled(settings.autoSyncEnabled ?? false);
-        setAutoSyncInterval(settings.autoSyncInterval ?? 5);
+        // The backend runs the refresh timers
+        import('./services/mailService')
+          .then(({ saveSettings }) => saveSettings({
+            autoSyncEnabled: settings.autoSyncEnabled ?? false,
+            autoSyncInterval: settings.autoSyncInterval ?? 5,
+          }))
+          .catch(err => console.error('Failed to save auto-sync settings:', err));
         setAutoPhishingDetection(settings.autoPhishingDetection ?? true); // Default to true for security
       }
     } catch (err) {
@@ -1854,7 +1859,7 @@ function App() {
   useEffect(() => {
     const loadAccounts = async () => {
       try {
-        const { listAccounts, connectAccount, listEmails, listFolders } = await import('./services/mailService');
+        const { listAccounts, connectAccount, listEmails, listFolders, startupSync } = await import('./services/mailService');
         const dbAccounts = await listAccounts();
         console.log('Loaded accounts from DB:', dbAccounts);
 
@@ -1880,96 +1885,75 @@ function App() {
 
           setAccounts(frontendAccounts);
 
-          // Set selected account to the default one or first one
-          const defaultAccount = frontendAccounts.find(a => a.isDefault) || frontendAccounts[0];
+          // Set selected account to the default one or first one, preferring enabled accounts
+          const defaultAccount = frontendAccounts.find(a => a.isDefault && a.isActive)
+            || frontendAccounts.find(a => a.isActive)
+            || frontendAccounts[0];
           setSelectedAccountId(defaultAccount.id);
 
-          // Connect to first account and load emails
+          // Connect the default account first and show its Inbox page by page as it
+          // arrives; the backend then brings up the other accounts a few at a time
           const firstAccount = defaultAccount;
+          const toEmail = (e: ListedEmail): Email => ({
+            id: e.uid.toString(),
+            from: { name: e.fromName || e.from || '', email: e.from || '' },
+            to: [{ name: '', email: '' }],
+            subject: e.subject || '(Konu yok)',
+            preview: e.preview || '',
+            body: e.preview || '',
+            date: new Date(e.date || Date.now()),
+            read: e.isRead,
+            starred: e.isStarred,
+            hasAttachments: e.hasAttachments,
+            hasImages: false,
+          });
+          const showInbox = (loadedEmails: Email[]) => {
+            loadedEmails.forEach(e => knownEmailIds.current.add(e.id));
+            emailCache.current.set(firstAccount.id, loadedEmails);
+            setEmails(loadedEmails);
+          };
+
           try {
-            await connectAccount(firstAccount.id.toString());
-            console.log('Connected to account:', firstAccount.email);
-
-            // Fetch IMAP folders
-            try {
-              const folders = await listFolders(firstAccount.id.toString());
-              console.log('Fetched IMAP folders:', folders);
-              setImapFolders(folders);
-            } catch (folderErr) {
-              console.error('Failed to fetch folders:', folderErr);
-            }
+            const streamed: Email[] = [];
+            const failure: { error: string | null } = { error: null };
+            const plan = await startupSync(
+              (page) => {
+                if (page.accountId !== firstAccount.id) return;
+                streamed.push(...page.emails.map(toEmail));
+                showInbox([...streamed]);
+                setIsLoading(false);
+                if (page.done) isInitialLoad.current = false;
+              },
+              (progress) => {
+                if (progress.accountId !== firstAccount.id) return;
+                if (progress.stage === 'connected') {
+                  listFolders(firstAccount.id.toString())
+                    .then(setImapFolders)
+                    .catch(folderErr => console.error('Failed to fetch folders:', folderErr));
+                } else if (progress.error) {
+                  failure.error = progress.error;
+                }
+              }
+            );
 
-            // Load emails (page is 0-indexed)
-            try {
+            if (!plan) {
+              // Startup sync already ran (the window was reloaded): load the Inbox directly
+              await connectAccount(firstAccount.id.toString());
+              listFolders(firstAccount.id.toString())
+                .then(setImapFolders)
+                .catch(folderErr => console.error('Failed to fetch folders:', folderErr));
               const result = await listEmails(firstAccount.id.toString(), 0, 500, 'INBOX');
-              console.log('listEmails result:', result);
-              console.log('Result keys:', result ? Object.keys(result) : 'null');
-
-              if (result && result.emails && result.emails.length > 0) {
-                console.log('Raw emails from backend, count:', result.emails.length);
-                console.log('First email keys:', Object.keys(result.emails[0]));
-                console.log('First email:', JSON.stringify(result.emails[0], null, 2));
-
-                const loadedEmails: Email[] = result.emails.map((e: any, idx: number) => {
-                  const emailId = e.uid?.toString() || e.id?.toString() || idx.toString();
-                  // Track known emails on initial load
-                  knownEmailIds.current.add(emailId);
-                  console.log(`Mapping email ${idx}: uid=${e.uid}, subject=${e.subject}`);
-                  return {
-                    id: emailId,
-                    from: { name: e.fromName || e.from || '', email: e.from || '' },
-                    to: [{ name: '', email: '' }],
-                    subject: e.subject || '(Konu yok)',
-                    preview: e.preview || '',
-                    body: e.bodyText || e.preview || '',
-                    bodyHtml: e.bodyHtml,
-                    bodyText: e.bodyText,
-                    date: new Date(e.date || Date.now()),
-                    read: e.isRead ?? false,
-                    starred: e.isStarred ?? false,
-                    hasAttachments: e.hasAttachments ?? false,
-                    hasImages: false,
-                  };
-                });
-                console.log('Mapped emails count:', loadedEmails.length);
-                // Save to cache
-                emailCache.current.set(firstAccount.id, loadedEmails);
-                setEmails(loadedEmails);
-                isInitialLoad.current = false; // Mark initial load as complete
-                console.log('State updated with emails, cached for account:', firstAccount.id);
-              } else {
-                console.log('No emails in result or result is empty');
-                console.log('result:', result);
-              }
-            } catch (emailErr) {
-              console.error('Error loading emails:', emailErr);
-              // Show error to user
-              const errorMessage = emailErr instanceof Error ? emailErr.message : String(emailErr);
-              if (window.confirm(`E-postalar yüklenemedi: ${errorMessage}\n\nHesabı yeniden bağlamayı denemek ister misiniz?`)) {
+              showInbox(result.emails.map(toEmail));
+              isInitialLoad.current = false;
+            } else if (failure.error && streamed.length === 0) {
+              console.error('Error loading emails:', failure.error);
+              if (window.confirm(`E-postalar yüklenemedi: ${failure.error}\n\nHesabı yeniden bağlamayı denemek ister misiniz?`)) {
                 // Reconnect attempt
                 try {
                   await connectAccount(firstAccount.id.toString());
                   // Retry loading emails
                   const retryResult = await listEmails(firstAccount.id.toString(), 0, 500, 'INBOX');
-                  if (retryResult && retryResult.emails) {
-                    const loadedEmails: Email[] = retryResult.emails.map((e: any, idx: number) => ({
-                      id: e.uid?.toString() || e.id?.toString() || idx.toString(),
-                      from: { name: e.fromName || e.from || '', email: e.from || '' },
-                      to: [{ name: '', email: '' }],
-                      subject: e.subject || '(Konu yok)',
-                      preview: e.preview || '',
-                      body: e.bodyText || e.preview || '',
-                      bodyHtml: e.bodyHtml,
-                      bodyText: e.bodyText,
-                      date: new Date(e.date || Date.now()),
-                      read: e.isRead ?? false,
-                      starred: e.isStarred ?? false,
-                      hasAttachments: e.hasAttachments ?? false,
-                      hasImages: false,
-                    }));
-                    emailCache.current.set(firstAccount.id, loadedEmails);
-                    setEmails(loadedEmails);
-                  }
+                  showInbox(retryResult.emails.map(toEmail));
                 } catch (retryErr) {
                   console.error('Reconnect failed:', retryErr);
                   alert('Yeniden bağlanma başarısız oldu. Lütfen hesabı silin ve OAuth ile tekrar ekleyin.');
@@ -2050,15 +2034,14 @@ function App() {
 
       if (result && result.emails) {
         const newEmails: Email[] = [];
+        const toNotify: any[] = [];
 
         result.emails.forEach((e: any) => {
           const emailId = e.uid?.toString() || e.id?.toString();
           if (emailId && !knownEmailIds.current.has(emailId)) {
             // This is a new email
             if (!isInitialLoad.current && notificationsEnabled) {
-              const senderName = e.fromName || e.from || 'Bilinmeyen';
-              const subject = e.subject || '(Konu yok)';
-              showNewEmailNotification(senderName, subject, e.preview);
+              toNotify.push(e);
             }
             knownEmailIds.current.add(emailId);
             newEmails.push({
@@ -2079,6 +2062,19 @@ function App() {
           }
         });
 
+        // DND and newsletters held back for the digest do not notify; VIP mail always does
+        if (toNotify.length > 0) {
+          const { notificationRoute } = await import('./services/notificationService');
+          const routes = await notificationRoute(
+            toNotify.map((e: any) => ({ from: e.from || '', subject: e.subject || '' }))
+          ).catch(() => toNotify.map(() => 'normal' as const));
+          toNotify.filter((_, i) => routes[i] !== 'silent').forEach((e: any) => {
+            const senderName = e.fromName || e.from || 'Bilinmeyen';
+            const subject = e.subject || '(Konu yok)';
+            showNewEmailNotification(senderName, subject, e.preview);
+          });
+        }
+
         if (newEmails.length > 0 && !isInitialLoad.current) {
           console.log('Found', newEmails.length, 'new emails');
           setEmails(prev => {
@@ -2101,22 +2097,89 @@ function App() {
     }
   }, [selectedAccountId, accounts, notificationsEnabled]);
 
-  // Poll for new emails based on auto-sync settings
-  useEffect(() => {
-    if (!selectedAccountId || accounts.length === 0 || !autoSyncEnabled) return;
+  // Refresh an account whose timer fired
+  // The shown account goes through checkForNewEmails. Other accounts sync their
+  // Inbox to the database and notify about new mail, which the unified inbox
+  // also adds to its list; their first refresh only records what is there.
+  const refreshAccount = useCallback(async (accountId: number) => {
+    if (accountId === selectedAccountId) {
+      await checkForNewEmails();
+      return;
+    }
 
-    // Initialize known email IDs from current emails
-    emails.forEach(e => knownEmailIds.current.add(e.id));
+    try {
+      const { listEmails } = await import('./services/mailService');
+      const previous = backgroundRefresh.current.get(accountId);
+      const result = await listEmails(accountId.toString(), 0, 50, 'INBOX', previous?.stateToken);
+      if (result.notModified) return;
+
+      const known = new Set(previous?.known);
+      const fresh = result.emails.filter(e => !known.has(e.uid));
+      fresh.forEach(e => known.add(e.uid));
+      backgroundRefresh.current.set(accountId, { known, stateToken: result.stateToken });
+      if (!previous || fresh.length === 0) return;
+
+      if (notificationsEnabled) {
+        const { notificationRoute } = await import('./services/notificationService');
+        const routes = await notificationRoute(
+          fresh.map(e => ({ from: e.from, subject: e.subject }))
+        ).catch(() => fresh.map(() => 'normal' as const));
+        fresh.filter((_, i) => routes[i] !== 'silent').forEach(e => {
+          showNewEmailNotification(e.fromName || e.from || 'Bilinmeyen', e.subject || '(Konu yok)', e.preview);
+        });
+      }
+
+      if (selectedAccountId === 'all') {
+        const newEmails: Email[] = fresh.map(e => ({
+          id: `${accountId}-${e.uid}`,
+          from: { name: e.fromName || e.from, email: e.from },
+          to: [{ name: '', email: '' }],
+          subject: e.subject || '(Konu yok)',
+          preview: e.preview || '',
+          body: '',
+          date: new Date(e.date || Date.now()),
+          read: e.isRead,
+          starred: e.isStarred,
+          hasAttachments: e.hasAttachments,
+          hasImages: false,
+          accountId: accountId.toString(),
+        }));
+        setEmails(prev => {
+          const existingIds = new Set(prev.map(e => e.id));
+          return [...newEmails.filter(e => !existingIds.has(e.id)), ...prev];
+        });
+      }
+    } catch (err) {
+      console.error('Error refreshing account', accountId, err);
+    }
+  }, [selectedAccountId, checkForNewEmails, notificationsEnabled]);
 
-    // Convert interval from minutes to milliseconds
-    const intervalMs = autoSyncInterval * 60 * 1000;
+  const refreshAccountRef = useRef(refreshAccount);
+  useEffect(() => {
+    refreshAccountRef.current = refreshAccount;
+  }, [refreshAccount]);
 
-    const pollInterval = setInterval(() => {
-      checkForNewEmails();
-    }, intervalMs);
+  // Initialize known email IDs from current emails
+  useEffect(() => {
+    emails.forEach(e => knownEmailIds.current.add(e.id));
+  }, [emails]);
 
-    return () => clearInterval(pollInterval);
-  }, [selectedAccountId, accounts, checkForNewEmails, emails, autoSyncEnabled, autoSyncInterval]);
+  // Refresh an account whenever its backend timer fires (refresh_schedule)
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+    import('./services/mailService')
+      .then(({ onRefreshDue }) => onRefreshDue(accountId => refreshAccountRef.current(accountId)))
+      .then(stop => {
+        if (cancelled) stop();
+        else unlisten = stop;
+      })
+      .catch(err => console.error('Failed to listen for refresh timers:', err));
+    return () => {
+      cancelled = true;
+      if (unlisten) unlisten();
+    };
+  }, []);
 
   // Sync emails handler
   const handleSync = useCallback(async () => {
@@ -2135,24 +2198,14 @@ function App() {
       console.log('Sync result for folder', folderToSync, ':', result);
 
       if (result && result.emails) {
-        let newEmailCount = 0;
+        const newRaw: any[] = [];
         const loadedEmails: Email[] = result.emails.map((e: any) => {
           const emailId = e.uid?.toString() || e.id?.toString();
 
           // Check if this is a new email
           if (emailId && !knownEmailIds.current.has(emailId)) {
-            newEmailCount++;
             knownEmailIds.current.add(emailId);
-
-            // Show notification for the first new email (to avoid spam)
-            if (newEmailCount === 1 && notificationsEnabled) {
-              const senderName = e.fromName || e.from || 'Bilinmeyen';
-              const subject = e.subject || '(Konu yok)';
-              showNewEmailNotification(senderName, subject, e.preview);
-            } else if (newEmailCount > 1 && notificationsEnabled) {
-              // Play sound for additional new emails
-              playNotificationSound();
-            }
+            newRaw.push(e);
           }
 
           return {
@@ -2176,7 +2229,28 @@ function App() {
           emailCache.current.set(selectedAccountId, loadedEmails);
         }
         setEmails(loadedEmails);
-        console.log('Synced emails:', loadedEmails.length, 'New:', newEmailCount);
+        console.log('Synced emails:', loadedEmails.length, 'New:', newRaw.length);
+
+        // Notify for the first new email (to avoid spam) and every VIP email, sound for the rest;
+        // DND and newsletters held back for the digest stay silent
+        if (notificationsEnabled && newRaw.length > 0) {
+          const { notificationRoute } = await import('./services/notificationService');
+          const routes = await notificationRoute(
+            newRaw.map((e: any) => ({ from: e.from || '', subject: e.subject || '' }))
+          ).catch(() => newRaw.map(() => 'normal' as const));
+          const audible = newRaw
+            .map((e: any, i: number) => ({ e, route: routes[i] }))
+            .filter(({ route }) => route !== 'silent');
+          audible.forEach(({ e, route }, i: number) => {
+            if (i === 0 || route === 'priority') {
+              const senderName = e.fromName || e.from || 'Bilinmeyen';
+              const subject = e.subject || '(Konu yok)';
+              showNewEmailNotification(senderName, subject, e.preview);
+            } else {
+              playNotificationSound();
+            }
+          });
+        }
       }
     } catch (err) {
       console.error('Sync failed:', err);
@@ -2724,8 +2798,8 @@ function App() {
           localPath: att.localPath,
           isInline: false,
         })),
-        replyToEmailId: draftDetail.replyToEmailId,
-        forwardEmailId: draftDetail.forwardEmailId,
+        replyToEmailId: draftDetail.replyToEmailId ?? undefined,
+        forwardEmailId: draftDetail.forwardEmailId ?? undefined,
         composeType: draftDetail.composeType as 'new' | 'reply' | 'replyAll' | 'forward',
       });
     } catch (err) {
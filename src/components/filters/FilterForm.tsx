@@ -37,6 +37,7 @@ const Icons = {
 // Field labels in Turkish
 const FIELD_LABELS: Record<ConditionField, string> = {
   from: 'Gönderen',
+  from_address: 'Gönderen adresi',
   to: 'Alıcı',
   subject: 'Konu',
   body: 'İçerik',
@@ -62,6 +63,7 @@ const ACTION_LABELS: Record<FilterActionType, string> = {
   mark_as_spam: 'Spam olarak işaretle',
   delete: 'Sil',
   archive: 'Arşivle',
+  webhook: 'Webhook gönder',
 };
 
 interface FilterFormProps {
@@ -100,9 +102,9 @@ export function FilterForm({
     if (filter) {
       setName(filter.name);
       setDescription(filter.description || '');
-      setIsEnabled(filter.isEnabled);
+      setIsEnabled(filter.is_enabled);
       setPriority(filter.priority);
-      setMatchLogic(filter.matchLogic);
+      setMatchLogic(filter.match_logic);
       setConditions(filter.conditions.length > 0 ? filter.conditions : [
         { field: 'from', operator: 'contains', value: '' },
       ]);
@@ -215,7 +217,7 @@ export function FilterForm({
 
     // Check action-specific requirements
     for (const action of actions) {
-      if (action.action === 'move_to_folder' && !action.folderId) {
+      if (action.action === 'move_to_folder' && !action.folder_id) {
         setError('Klasöre taşı eylemi için klasör seçilmelidir');
         return;
       }
@@ -223,6 +225,10 @@ export function FilterForm({
         setError('Etiket ekle eylemi için etiket girilmelidir');
         return;
       }
+      if (action.action === 'webhook' && !action.webhook?.url.trim()) {
+        setError('Webhook eylemi için URL girilmelidir');
+        return;
+      }
     }
 
     setIsSaving(true);
@@ -230,12 +236,12 @@ export function FilterForm({
 
     try {
       const newFilter: NewEmailFilter = {
-        accountId,
+        account_id: accountId,
         name: name.trim(),
-        description: description.trim() || undefined,
-        isEnabled,
+        description: description.trim() || null,
+        is_enabled: isEnabled,
         priority,
-        matchLogic,
+        match_logic: matchLogic,
         conditions,
         actions,
       };
@@ -547,9 +553,9 @@ export function FilterForm({
                     {action.action === 'move_to_folder' && (
                       <input
                         type="number"
-                        value={action.folderId || ''}
+                        value={action.folder_id || ''}
                         onChange={(e) =>
-                          updateAction(index, { folderId: Number(e.target.value) })
+                          updateAction(index, { folder_id: Number(e.target.value) })
                         }
                         className="px-2 py-1.5 bg-gray-700 border border-gray-600 rounded text-sm text-gray-100 focus:outline-none focus:border-blue-500"
                         placeholder="Klasör ID"
@@ -567,6 +573,34 @@ export function FilterForm({
                         placeholder="Etiket adı"
                       />
                     )}
+
+                    {action.action === 'webhook' && (
+                      <div className="flex flex-col gap-1">
+                        <input
+                          type="url"
+                          value={action.webhook?.url || ''}
+                          onChange={(e) =>
+                            updateAction(index, {
+                              webhook: { url: e.target.value, includeBody: action.webhook?.includeBody ?? false },
+                            })
+                          }
+                          className="px-2 py-1.5 bg-gray-700 border border-gray-600 rounded text-sm text-gray-100 focus:outline-none focus:border-blue-500"
+                          placeholder="https://..."
+                        />
+                        <label className="flex items-center gap-2 text-xs text-gray-400">
+                          <input
+                            type="checkbox"
+                            checked={action.webhook?.includeBody ?? false}
+                            onChange={(e) =>
+                              updateAction(index, {
+                                webhook: { url: action.webhook?.url || '', includeBody: e.target.checked },
+                              })
+                            }
+                          />
+                          E-posta içeriğini dahil et
+                        </label>
+                      </div>
+                    )}
                   </div>
 
                   {actions.length > 1 && (
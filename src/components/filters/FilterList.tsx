@@ -98,7 +98,7 @@ export function FilterList({
   }
 
   // Format date
-  const formatDate = (dateStr?: string) => {
+  const formatDate = (dateStr: string | null) => {
     if (!dateStr) return 'Hiç';
     const date = new Date(dateStr);
     return new Intl.DateTimeFormat('tr-TR', {
@@ -116,7 +116,7 @@ export function FilterList({
         <div
           key={filter.id}
           className={`border rounded-lg p-4 transition-all ${
-            filter.isEnabled
+            filter.is_enabled
               ? 'bg-gray-800 border-gray-700 hover:border-gray-600'
               : 'bg-gray-800/50 border-gray-700/50 opacity-60'
           }`}
@@ -128,7 +128,7 @@ export function FilterList({
                 <h3 className="text-base font-semibold text-gray-100 truncate">
                   {filter.name}
                 </h3>
-                {!filter.isEnabled && (
+                {!filter.is_enabled && (
                   <span className="px-2 py-0.5 text-xs font-medium bg-gray-700 text-gray-400 rounded">
                     Pasif
                   </span>
@@ -153,7 +153,7 @@ export function FilterList({
               <button
                 onClick={() => onToggle(filter.id)}
                 className="p-2 hover:bg-gray-700 rounded transition-colors"
-                title={filter.isEnabled ? 'Pasif yap' : 'Aktif yap'}
+                title={filter.is_enabled ? 'Pasif yap' : 'Aktif yap'}
               >
                 <Icons.Toggle />
               </button>
@@ -192,13 +192,13 @@ export function FilterList({
             <div>
               <div className="text-xs text-gray-500">Eşleşme</div>
               <div className="text-sm font-medium text-gray-300">
-                {filter.matchedCount} email
+                {filter.matched_count} email
               </div>
             </div>
             <div>
               <div className="text-xs text-gray-500">Son eşleşme</div>
               <div className="text-sm font-medium text-gray-300">
-                {formatDate(filter.lastMatchedAt)}
+                {formatDate(filter.last_matched_at)}
               </div>
             </div>
           </div>
@@ -210,7 +210,7 @@ export function FilterList({
                 Koşullar:
               </span>
               <span className="text-xs text-gray-400">
-                {filter.conditions.length} koşul ({filter.matchLogic === 'all' ? 'Tümü' : 'Herhangi biri'})
+                {filter.conditions.length} koşul ({filter.match_logic === 'all' ? 'Tümü' : 'Herhangi biri'})
               </span>
             </div>
             <div className="flex items-start gap-2">
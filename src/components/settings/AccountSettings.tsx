@@ -3,9 +3,14 @@
 // ============================================================================
 
 import { useState, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
 import { AddAccountModal } from './AddAccountModal';
-import { getAccountPriorityFetch, setAccountPriorityFetch } from '../../services/mailService';
+import {
+  deleteAccount,
+  getAccountPriorityFetch,
+  requestConfirmation,
+  setAccountActive,
+  setAccountPriorityFetch,
+} from '../../services/mailService';
 import type { Account } from '../../types';
 
 interface AccountSettingsProps {
@@ -50,16 +55,16 @@ export function AccountSettings({ accounts, onAccountsChange }: AccountSettingsP
   };
 
   const handleDeleteAccount = async (accountId: number) => {
-    if (confirm('Bu hesabı silmek istediğinizden emin misiniz?')) {
-      try {
-        // Call backend to delete from database
-        await invoke('account_delete', { accountId: accountId.toString() });
-        // Update local state after successful deletion
-        onAccountsChange(accounts.filter((a) => a.id !== accountId));
-      } catch (error) {
-        console.error('Failed to delete account:', error);
-        alert('Hesap silinirken bir hata oluştu: ' + error);
-      }
+    try {
+      // The backend asks for confirmation in a native dialog
+      const token = await requestConfirmation('account_delete', accountId.toString());
+      if (!token) return;
+      await deleteAccount(accountId, token);
+      // Update local state after successful deletion
+      onAccountsChange(accounts.filter((a) => a.id !== accountId));
+    } catch (error) {
+      console.error('Failed to delete account:', error);
+      alert('Hesap silinirken bir hata oluştu: ' + error);
     }
   };
 
@@ -72,12 +77,19 @@ export function AccountSettings({ accounts, onAccountsChange }: AccountSettingsP
     );
   };
 
-  const handleToggleActive = (accountId: number) => {
-    onAccountsChange(
-      accounts.map((a) =>
-        a.id === accountId ? { ...a, isActive: !a.isActive } : a
-      )
-    );
+  const handleToggleActive = async (account: Account) => {
+    try {
+      // Disabling disconnects the account and pauses its sync; its mail is kept
+      await setAccountActive(account.id, !account.isActive);
+      onAccountsChange(
+        accounts.map((a) =>
+          a.id === account.id ? { ...a, isActive: !account.isActive } : a
+        )
+      );
+    } catch (error) {
+      console.error('Failed to update account state:', error);
+      alert('Hesap durumu güncellenirken bir hata oluştu: ' + error);
+    }
   };
 
   return (
@@ -191,7 +203,7 @@ export function AccountSettings({ accounts, onAccountsChange }: AccountSettingsP
                     </button>
                   )}
                   <button
-                    onClick={() => handleToggleActive(account.id)}
+                    onClick={() => handleToggleActive(account)}
                     className="px-3 py-1.5 text-sm text-owl-text-secondary hover:text-owl-text hover:bg-owl-surface-2 rounded-lg transition-colors"
                     title={account.isActive ? 'Devre dışı bırak' : 'Etkinleştir'}
                   >
@@ -5,28 +5,9 @@
 
 import React, { useState, useEffect } from 'react';
 import { useShortcut } from '../../hooks/useKeyboardShortcuts';
-import type { Account, AutoConfig, SecurityType } from '../../types';
+import type { Account, AutoConfig, AutoConfigDebug, OAuthCompleteResult, SecurityType } from '../../types';
 import { invoke } from '@tauri-apps/api/core';
 
-interface AutoConfigDebug {
-  email: string;
-  domain: string;
-  presetTried: boolean;
-  presetResult: string | null;
-  ispAutoconfigTried: boolean;
-  ispAutoconfigResult: string | null;
-  wellknownTried: boolean;
-  wellknownResult: string | null;
-  ispdbTried: boolean;
-  ispdbResult: string | null;
-  mxLookupTried: boolean;
-  mxLookupResult: string | null;
-  guessingTried: boolean;
-  guessingResult: string | null;
-  finalConfig: AutoConfig | null;
-  totalDurationMs: number;
-}
-
 // SECURITY: Logger wrapper - only log in development, never log sensitive data
 const log = {
   info: (message: string) => {
@@ -103,16 +84,7 @@ export function AddAccountModal({
       setTestProgress('Tarayıcı açılıyor...');
 
       // Start OAuth flow - this will open browser and wait for callback automatically
-      const result = await invoke<{
-        email: string;
-        display_name: string | null;
-        access_token: string;
-        refresh_token: string | null;
-        imap_host: string;
-        imap_port: number;
-        smtp_host: string;
-        smtp_port: number;
-      }>('oauth_start_gmail');
+      const result = await invoke<OAuthCompleteResult>('oauth_start_gmail');
 
       // OAuth completed successfully! Fill in the form
       setEmail(result.email);
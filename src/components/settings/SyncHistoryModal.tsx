@@ -1,6 +1,7 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { SyncSnapshot } from '../../types';
+import type { SyncSnapshot } from '../../types';
+import { requestConfirmation } from '../../services/mailService';
 
 interface Props {
   isOpen: boolean;
@@ -49,10 +50,13 @@ export function SyncHistoryModal({ isOpen, onClose, dataType }: Props) {
     if (!rollbackVersion || !masterPassword) return;
 
     try {
+      const confirmToken = await requestConfirmation('sync_rollback', `${dataType}@${rollbackVersion}`);
+      if (!confirmToken) return;
       await invoke('rollback_sync', {
         dataType,
         version: rollbackVersion,
         masterPassword,
+        confirmToken,
       });
       alert('Geri alma başarılı! Verileriniz seçili versiyona döndürüldü.');
       setShowPasswordPrompt(false);
@@ -7,7 +7,7 @@ import { FilterList } from '../components/filters/FilterList';
 import { FilterForm } from '../components/filters/FilterForm';
 import { FilterTestModal } from '../components/filters/FilterTestModal';
 import { filterList as fetchFilters, filterAdd, filterUpdate, filterDelete, filterToggle, filterApplyBatch, filterExport, filterImport } from '../services/filterService';
-import { listAccounts, syncEmailsWithFilters } from '../services/mailService';
+import { listAccounts, requestConfirmation, syncEmailsWithFilters } from '../services/mailService';
 import type { EmailFilter, NewEmailFilter, Account } from '../types';
 
 interface FiltersProps {
@@ -255,7 +255,9 @@ export function Filters({ onBack, defaultAccountId }: FiltersProps) {
 
       try {
         const text = await file.text();
-        const count = await filterImport(selectedAccountId, text);
+        const token = await requestConfirmation('filter_import', String(selectedAccountId));
+        if (!token) return;
+        const count = await filterImport(selectedAccountId, text, token);
         showToast('success', `${count} filtre içe aktarıldı`);
         await loadFilters();
       } catch (err) {
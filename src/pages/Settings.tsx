@@ -15,7 +15,7 @@ import { ActiveSessions } from '../components/settings/ActiveSessions';
 import { AuditLogViewer } from '../components/settings/AuditLogViewer';
 import { AuditStatsComponent } from '../components/settings/AuditStats';
 import TemplateSettings from '../components/settings/TemplateSettings';
-import { listAccounts } from '../services/mailService';
+import { listAccounts, saveSettings } from '../services/mailService';
 import type { SettingsTab, Settings as SettingsType, Account } from '../types';
 
 interface SettingsProps {
@@ -130,6 +130,7 @@ const defaultSettings: SettingsType = {
   signaturePosition: 'bottom',
   replyPosition: 'top',
   closeToTray: true,
+  startMinimized: false,
 
   // Auto-Sync
   autoSyncEnabled: true,
@@ -204,6 +205,11 @@ export function Settings({ onBack }: SettingsProps) {
     } catch (err) {
       console.error('Failed to save settings:', err);
     }
+    // The backend's refresh timers follow the auto-sync settings
+    saveSettings({
+      autoSyncEnabled: newSettings.autoSyncEnabled,
+      autoSyncInterval: newSettings.autoSyncInterval,
+    }).catch((err) => console.error('Failed to save auto-sync settings:', err));
   }, []);
 
   // Close on Escape
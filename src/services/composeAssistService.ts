@@ -0,0 +1,68 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { AICompletionChunk, AIProviderInfo, AIProviderKind } from '../types';
+
+/** Event carrying streamed completion chunks */
+export const AI_COMPLETION_EVENT = 'ai://completion';
+
+/**
+ * Complete the sentence before the cursor
+ * `onChunk` receives the completion while it streams in; the full text is returned.
+ * `context` (quoted thread, rest of the draft) only reaches remote providers
+ * when the account allows it.
+ */
+export async function aiComplete(
+  accountId: number,
+  context: string,
+  cursorText: string,
+  onChunk?: (text: string) => void
+): Promise<string> {
+  const requestId = crypto.randomUUID();
+  const unlisten = onChunk
+    ? await listen<AICompletionChunk>(AI_COMPLETION_EVENT, (event) => {
+        if (event.payload.requestId === requestId && !event.payload.done) {
+          onChunk(event.payload.text);
+        }
+      })
+    : undefined;
+
+  try {
+    return await invoke<string>('ai_complete', { accountId, context, cursorText, requestId });
+  } finally {
+    unlisten?.();
+  }
+}
+
+/**
+ * Get the completion provider (without the API key)
+ */
+export async function aiGetProvider(): Promise<AIProviderInfo> {
+  return await invoke<AIProviderInfo>('ai_get_provider');
+}
+
+/**
+ * Configure the completion provider
+ * Omit `apiKey` to keep the stored key; an empty string removes it.
+ */
+export async function aiSetProvider(
+  kind: AIProviderKind,
+  baseUrl?: string,
+  model?: string,
+  apiKey?: string
+): Promise<AIProviderInfo> {
+  return await invoke<AIProviderInfo>('ai_set_provider', { kind, baseUrl, model, apiKey });
+}
+
+/**
+ * Whether message context may be sent to remote AI providers for an account
+ */
+export async function aiGetContextSharing(accountId: number): Promise<boolean> {
+  return await invoke<boolean>('ai_get_context_sharing', { accountId });
+}
+
+/**
+ * Allow or forbid sending message context to remote AI providers for an account
+ */
+export async function aiSetContextSharing(accountId: number, enabled: boolean): Promise<void> {
+  await invoke('ai_set_context_sharing', { accountId, enabled });
+}
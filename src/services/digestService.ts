@@ -0,0 +1,37 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { DigestConfig, DigestResult } from '../types';
+
+/** Event emitted when scheduled digests were generated */
+export const DIGEST_CREATED_EVENT = 'digest://created';
+
+/**
+ * Get the newsletter digest configuration
+ */
+export async function digestGetConfig(): Promise<DigestConfig> {
+  return await invoke<DigestConfig>('digest_get_config');
+}
+
+/**
+ * Update schedule and categories of the newsletter digest
+ */
+export async function digestSetConfig(config: DigestConfig): Promise<DigestConfig> {
+  return await invoke<DigestConfig>('digest_set_config', { config });
+}
+
+/**
+ * Generate digests for all accounts now
+ */
+export async function digestRunNow(): Promise<DigestResult[]> {
+  return await invoke<DigestResult[]>('digest_run_now');
+}
+
+/**
+ * Which of these new messages should raise a desktop notification
+ * Messages held back for the digest return false.
+ */
+export async function digestFilterNotifications(
+  emails: { from: string; subject: string }[]
+): Promise<boolean[]> {
+  if (emails.length === 0) return [];
+  return await invoke<boolean[]>('digest_filter_notifications', { emails });
+}
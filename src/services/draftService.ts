@@ -3,30 +3,38 @@
 // ============================================================================
 
 import { invoke } from '@tauri-apps/api/core';
-import type { DraftEmail, Attachment, DraftListItem, DraftDetail } from '../types';
+import type {
+  DraftEmail,
+  Attachment,
+  DraftAttachmentData,
+  DraftEmailData,
+  DraftListItem,
+  DraftDetail,
+} from '../types';
 
 export async function saveDraft(draft: DraftEmail, attachments: Attachment[]): Promise<number> {
-  const draftData = {
-    id: draft.id,
-    account_id: draft.accountId,
-    to_addresses: JSON.stringify(draft.to),
-    cc_addresses: JSON.stringify(draft.cc),
-    bcc_addresses: JSON.stringify(draft.bcc),
+  const draftData: DraftEmailData = {
+    id: draft.id ?? null,
+    accountId: draft.accountId,
+    toAddresses: JSON.stringify(draft.to),
+    ccAddresses: JSON.stringify(draft.cc),
+    bccAddresses: JSON.stringify(draft.bcc),
     subject: draft.subject,
-    body_text: draft.bodyText,
-    body_html: draft.bodyHtml,
-    reply_to_email_id: draft.replyToEmailId,
-    forward_email_id: draft.forwardEmailId,
-    compose_type: draft.composeType,
+    bodyText: draft.bodyText,
+    bodyHtml: draft.bodyHtml,
+    bodyMarkdown: draft.bodyMarkdown ?? null,
+    replyToEmailId: draft.replyToEmailId ?? null,
+    forwardEmailId: draft.forwardEmailId ?? null,
+    composeType: draft.composeType,
   };
 
-  const attachmentData = attachments
+  const attachmentData: DraftAttachmentData[] = attachments
     .filter(att => att._file)
     .map(att => ({
       filename: att.filename,
-      content_type: att.contentType,
+      contentType: att.contentType,
       size: att.size,
-      local_path: att.localPath || '',
+      localPath: att.localPath || '',
     }));
 
   return await invoke<number>('draft_save', {
@@ -0,0 +1,46 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { Feed, FeedPollResult } from '../types';
+
+/** Event emitted when a background poll stored new items or failed */
+export const FEEDS_UPDATED_EVENT = 'feeds://updated';
+
+/**
+ * Subscribe an account to an RSS/Atom feed and fetch it right away
+ * Entries are stored in the local folder `folderName` (default "Feeds").
+ */
+export async function feedAdd(
+  accountId: number,
+  url: string,
+  folderName?: string,
+  intervalMinutes?: number
+): Promise<FeedPollResult> {
+  return await invoke<FeedPollResult>('feed_add', { accountId, url, folderName, intervalMinutes });
+}
+
+/**
+ * List feed subscriptions of an account (all accounts when omitted)
+ */
+export async function feedList(accountId?: number): Promise<Feed[]> {
+  return await invoke<Feed[]>('feed_list', { accountId });
+}
+
+/**
+ * Change a feed's poll interval or pause it
+ */
+export async function feedUpdate(feedId: number, intervalMinutes: number, enabled: boolean): Promise<Feed> {
+  return await invoke<Feed>('feed_update', { feedId, intervalMinutes, enabled });
+}
+
+/**
+ * Unsubscribe from a feed (stored items are kept)
+ */
+export async function feedRemove(feedId: number): Promise<void> {
+  await invoke('feed_remove', { feedId });
+}
+
+/**
+ * Poll a feed now, regardless of its interval
+ */
+export async function feedRefresh(feedId: number): Promise<FeedPollResult> {
+  return await invoke<FeedPollResult>('feed_refresh', { feedId });
+}
@@ -3,7 +3,7 @@
 // ============================================================================
 
 import { invoke } from '@tauri-apps/api/core';
-import type { EmailFilter, NewEmailFilter } from '../types';
+import type { EmailFilter, FilterBatchResult, NewEmailFilter } from '../types';
 
 // ============================================================================
 // Filter Management
@@ -71,12 +71,8 @@ export async function filterApplyBatch(
   accountId: number,
   filterId?: number,
   folderId?: number
-): Promise<{
-  emailsProcessed: number;
-  filtersMatched: number;
-  actionsExecuted: number;
-}> {
-  return invoke('filter_apply_batch', { accountId, filterId, folderId });
+): Promise<FilterBatchResult> {
+  return invoke<FilterBatchResult>('filter_apply_batch', { accountId, filterId, folderId });
 }
 
 /**
@@ -87,11 +83,33 @@ export async function filterExport(accountId: number): Promise<string> {
 }
 
 /**
- * Import filters from JSON
+ * Import filters from JSON (confirmToken from requestConfirmation('filter_import', ...))
  */
 export async function filterImport(
   accountId: number,
-  jsonData: string
+  jsonData: string,
+  confirmToken: string
 ): Promise<number> {
-  return invoke<number>('filter_import', { accountId, jsonData });
+  return invoke<number>('filter_import', { accountId, jsonData, confirmToken });
+}
+
+/**
+ * Secret used to sign webhook requests (X-Owlivion-Signature)
+ */
+export async function webhookGetSecret(): Promise<string> {
+  return invoke<string>('webhook_get_secret');
+}
+
+/**
+ * Generate a new webhook signing secret
+ */
+export async function webhookRotateSecret(): Promise<string> {
+  return invoke<string>('webhook_rotate_secret');
+}
+
+/**
+ * Send a sample payload to a webhook URL
+ */
+export async function webhookTest(url: string, includeBody: boolean): Promise<void> {
+  return invoke<void>('webhook_test', { url, includeBody });
 }
@@ -3,3 +3,7 @@ export * from './mailService';
 export * from './geminiService';
 export * from './filterService';
 export * from './templateService';
+export * from './feedService';
+export * from './digestService';
+export * from './composeAssistService';
+export * from './threadSummaryService';
@@ -3,17 +3,84 @@
 // ============================================================================
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import type {
+  AccountAuthState,
+  AccountRefresh,
+  RefreshDue,
+  StartupInbox,
+  StartupPlan,
+  StartupProgress,
+  AliasStats,
+  AttachmentFilters,
+  AttachmentPage,
+  ActivityEntry,
+  AccountCapabilities,
   Account,
+  AutostartStatus,
+  CleanupSuggestion,
+  MailboxStats,
+  ProposedEvent,
+  Invite,
+  DeepLink,
+  EmailNote,
+  FolderKeywords,
+  HotkeyConfig,
+  HotkeyStatus,
   NewAccount,
+  NoteColor,
+  PlusAlias,
+  PrefetchSettings,
+  BodyBackfillSettings,
+  SharedFolderTree,
   AutoConfig,
   ImapFolder,
-  EmailSummary,
+  LocalFolder,
+  LocalEmailSummary,
+  FolderNode,
+  EmailSyncResult,
+  EmailWindow,
+  EmailListChanges,
+  EmailListPage,
+  ConfirmScope,
   Email,
+  DbMaintenanceReport,
+  DbRecoveryReport,
+  DomainPolicy,
   DraftEmail,
+  RekeyReport,
+  SendWarning,
+  PrecheckSettings,
+  BouncingContact,
+  FolderMapping,
+  MigrationState,
+  SpamScore,
+  SpamReport,
+  BlockAction,
+  BlockedSender,
+  SenderBlockResult,
+  SendQuotaLimits,
+  SendQuotaStatus,
+  FolderWatchConfig,
+  FolderWatchStatus,
+  OnboardingState,
+  Profile,
+  ProfileList,
+  OnboardingStep,
+  SpamSweepReport,
+  SpamTrainingConfig,
+  SentCopyWarning,
+  SenderAuthReport,
+  SendTimeSuggestion,
+  FetchBenchmark,
   Settings,
   SearchFilters,
+  SearchIndexStatus,
   SearchResult,
+  LinkCheck,
+  LinkPreview,
+  NewDomainPolicy,
+  UndoHandle,
   MultiAccountFetchResult,
 } from '../types';
 
@@ -136,10 +203,28 @@ export async function fetchUrlContent(url: string): Promise<string> {
 }
 
 /**
- * Delete an account
+ * Ask the user to confirm a destructive action in a native dialog
+ * Resolves to the token the action's command requires, or null when declined.
+ * `target` is what the command acts on (see each command's wrapper), e.g. the
+ * account id, or `<dataType>@<version>` for sync rollbacks.
  */
-export async function deleteAccount(accountId: number): Promise<void> {
-  return invoke('account_delete', { accountId });
+export async function requestConfirmation(scope: ConfirmScope, target: string): Promise<string | null> {
+  return invoke<string | null>('confirm_request', { scope, target });
+}
+
+/**
+ * Delete an account (confirmToken from requestConfirmation('account_delete', ...))
+ */
+export async function deleteAccount(accountId: number, confirmToken: string): Promise<void> {
+  return invoke('account_delete', { accountId: accountId.toString(), confirmToken });
+}
+
+/**
+ * Disable or re-enable an account; a disabled account is disconnected and
+ * skipped by the unified inbox and background sync, but its mail is kept
+ */
+export async function setAccountActive(accountId: number, active: boolean): Promise<void> {
+  return invoke('account_set_active', { accountId, active });
 }
 
 /**
@@ -154,10 +239,54 @@ export async function setDefaultAccount(accountId: number): Promise<void> {
 // ============================================================================
 
 /**
- * List folders for an account (from IMAP server)
+ * List folders for an account as a tree (from IMAP server)
+ */
+export async function listFolderTree(accountId: string): Promise<FolderNode[]> {
+  return invoke<FolderNode[]>('folder_list', { accountId });
+}
+
+/**
+ * List shared and other users' folders, one tree per IMAP namespace
+ */
+export async function listSharedFolderTrees(accountId: string): Promise<SharedFolderTree[]> {
+  return invoke<SharedFolderTree[]>('folder_list_shared', { accountId });
+}
+
+/**
+ * Addresses an account may send on behalf of
+ */
+export async function getSendOnBehalf(accountId: number): Promise<string[]> {
+  return invoke<string[]>('account_get_send_on_behalf', { accountId });
+}
+
+/**
+ * Set the addresses an account may send on behalf of (returns them normalized)
+ */
+export async function setSendOnBehalf(accountId: number, addresses: string[]): Promise<string[]> {
+  return invoke<string[]>('account_set_send_on_behalf', { accountId, addresses });
+}
+
+/**
+ * List folders for an account (from IMAP server), parents before children
  */
 export async function listFolders(accountId: string): Promise<ImapFolder[]> {
-  return invoke<ImapFolder[]>('folder_list', { accountId });
+  const flatten = (nodes: FolderNode[]): ImapFolder[] =>
+    nodes.flatMap((node) => [node, ...flatten(node.children)]);
+  return flatten(await listFolderTree(accountId));
+}
+
+/**
+ * Rename a folder, keeping its children; returns the new path
+ */
+export async function renameFolder(accountId: string, path: string, newName: string): Promise<string> {
+  return invoke<string>('folder_rename', { accountId, path, newName });
+}
+
+/**
+ * Move a folder (with its children) under another folder, or to the top level
+ */
+export async function moveFolder(accountId: string, path: string, newParent: string | null): Promise<string> {
+  return invoke<string>('folder_move', { accountId, path, newParent });
 }
 
 /**
@@ -170,25 +299,444 @@ export async function selectFolder(
   return invoke<number>('folder_select', { accountId, folderPath });
 }
 
+// ============================================================================
+// Local Folders
+// ============================================================================
+
+/**
+ * Create a local-only folder (stored in the database, never synced to IMAP)
+ */
+export async function createLocalFolder(accountId: string, name: string): Promise<LocalFolder> {
+  return invoke<LocalFolder>('local_folder_create', { accountId, name });
+}
+
+/**
+ * List local-only folders of an account
+ */
+export async function listLocalFolders(accountId: string): Promise<LocalFolder[]> {
+  return invoke<LocalFolder[]>('local_folder_list', { accountId });
+}
+
+/**
+ * Rename a local-only folder
+ */
+export async function renameLocalFolder(folderId: number, name: string): Promise<void> {
+  return invoke('local_folder_rename', { folderId, name });
+}
+
+/**
+ * Delete a local-only folder and all messages stored in it
+ * (confirmToken from requestConfirmation('local_folder_delete', String(folderId)))
+ */
+export async function deleteLocalFolder(folderId: number, confirmToken: string): Promise<void> {
+  return invoke('local_folder_delete', { folderId, confirmToken });
+}
+
+/**
+ * List messages stored in a local folder
+ */
+export async function listLocalFolderEmails(folderId: number, page: number, pageSize: number): Promise<LocalEmailSummary[]> {
+  return invoke<LocalEmailSummary[]>('local_folder_emails', { folderId, page, pageSize });
+}
+
+/**
+ * Copy or move a server message into a local folder; returns the id of the local copy
+ */
+export async function copyToLocalFolder(
+  accountId: string,
+  folder: string,
+  uid: number,
+  localFolderId: number,
+  removeOriginal: boolean
+): Promise<number> {
+  return invoke<number>('email_copy_to_local_folder', { accountId, folder, uid, localFolderId, removeOriginal });
+}
+
+/**
+ * Export a local folder as an mbox file; returns the number of exported messages
+ */
+export async function exportLocalFolder(folderId: number, savePath: string): Promise<number> {
+  return invoke<number>('local_folder_export', { folderId, savePath });
+}
+
 // ============================================================================
 // Email Operations
 // ============================================================================
 
 /**
  * Fetch emails with pagination
+ * Pass the stateToken of the previous result to get notModified instead of an unchanged page.
  */
 export async function listEmails(
   accountId: string,
   page: number,
   pageSize: number,
-  folder?: string
-): Promise<{ emails: EmailSummary[]; total: number; hasMore: boolean }> {
-  return invoke('email_list', { accountId, folder, page, pageSize });
+  folder?: string,
+  stateToken?: string
+): Promise<EmailListPage> {
+  return invoke<EmailListPage>('email_list', { accountId, folder, page, pageSize, stateToken });
 }
 
 // Alias for backwards compatibility
 export const emailList = listEmails;
 
+/**
+ * Fetch a page and get only what changed since the snapshot the list shows
+ * Pass the previous result as `since`; its stateToken skips the fetch while
+ * the folder is unchanged.
+ */
+export async function listEmailChanges(
+  accountId: string,
+  page: number,
+  pageSize: number,
+  folder?: string,
+  since?: Pick<EmailListChanges, 'snapshotId' | 'stateToken'>
+): Promise<EmailListChanges> {
+  return invoke<EmailListChanges>('email_list_changes', {
+    accountId,
+    folder,
+    page,
+    pageSize,
+    since: since && { snapshotId: since.snapshotId, stateToken: since.stateToken },
+  });
+}
+
+/**
+ * Fetch a window of a folder for virtual scrolling (newest first)
+ * Pass `window.nextCursor` of the previous call to continue.
+ */
+export async function listEmailWindow(
+  folderId: number,
+  count: number,
+  before?: { date: string; uid: number }
+): Promise<EmailWindow> {
+  return invoke<EmailWindow>('email_list_window', {
+    folderId,
+    beforeDate: before?.date,
+    beforeUid: before?.uid,
+    count,
+  });
+}
+
+/**
+ * Compare the search indexes with the stored mail and templates
+ */
+export async function checkSearchIndex(accountId?: number): Promise<SearchIndexStatus[]> {
+  return invoke<SearchIndexStatus[]>('search_index_check', { accountId });
+}
+
+/**
+ * Rebuild the search indexes (e.g. when results look stale after a crash)
+ */
+export async function rebuildSearchIndex(accountId?: number): Promise<SearchIndexStatus[]> {
+  return invoke<SearchIndexStatus[]>('search_index_rebuild', { accountId });
+}
+
+/** Event emitted when the background check had to rebuild an index */
+export const SEARCH_INDEX_REPAIRED_EVENT = 'search-index://repaired';
+
+/**
+ * Outcome of the startup database integrity check
+ */
+export async function getDbRecoveryReport(): Promise<DbRecoveryReport> {
+  return invoke<DbRecoveryReport>('db_recovery_report');
+}
+
+/** Event emitted at startup when a corrupt database was recovered or reset */
+export const DB_RECOVERED_EVENT = 'db://recovered';
+
+/**
+ * Run database maintenance now (ANALYZE, vacuum, WAL checkpoint)
+ */
+export async function runDbMaintenance(): Promise<DbMaintenanceReport> {
+  return invoke<DbMaintenanceReport>('db_maintenance_run');
+}
+
+/**
+ * Report of the last database maintenance run
+ */
+export async function getLastDbMaintenance(): Promise<DbMaintenanceReport | null> {
+  return invoke<DbMaintenanceReport | null>('db_maintenance_last');
+}
+
+/**
+ * Schedule of the spam training sweep
+ */
+export async function getSpamTrainingConfig(): Promise<SpamTrainingConfig> {
+  return invoke<SpamTrainingConfig>('spam_training_get_config');
+}
+
+/**
+ * Turn the spam training sweep on or off and set its interval
+ */
+export async function setSpamTrainingConfig(config: SpamTrainingConfig): Promise<SpamTrainingConfig> {
+  return invoke<SpamTrainingConfig>('spam_training_set_config', { config });
+}
+
+/**
+ * Train on messages moved into or out of Junk now
+ */
+export async function runSpamTraining(): Promise<SpamSweepReport> {
+  return invoke<SpamSweepReport>('spam_training_run');
+}
+
+/**
+ * Report of the last spam training sweep
+ */
+export async function getLastSpamTraining(): Promise<SpamSweepReport | null> {
+  return invoke<SpamSweepReport | null>('spam_training_last');
+}
+
+/**
+ * Spam probability of a message and its sender's reputation
+ */
+export async function getSpamScore(emailId: number): Promise<SpamScore> {
+  return invoke<SpamScore>('spam_score', { emailId });
+}
+
+/**
+ * Why a message is considered spam: deciding tokens, sender reputation and
+ * the verdicts of server-side filters
+ */
+export async function getSpamReport(emailId: number): Promise<SpamReport> {
+  return invoke<SpamReport>('email_spam_report', { emailId });
+}
+
+/**
+ * Block a sender address or domain with a high-priority filter
+ */
+export async function blockSender(
+  accountId: number,
+  addressOrDomain: string,
+  action: BlockAction,
+  applyToExisting: boolean
+): Promise<SenderBlockResult> {
+  return invoke<SenderBlockResult>('sender_block', { accountId, addressOrDomain, action, applyToExisting });
+}
+
+/**
+ * Blocked senders of an account
+ */
+export async function listBlockedSenders(accountId: number): Promise<BlockedSender[]> {
+  return invoke<BlockedSender[]>('sender_block_list', { accountId });
+}
+
+/**
+ * Unblock a sender and delete its filter
+ */
+export async function unblockSender(blockId: number): Promise<void> {
+  return invoke('sender_unblock', { blockId });
+}
+
+/**
+ * Apply a block to the sender's existing mail; returns the number of messages
+ * (confirmToken from requestConfirmation('sender_block_apply', String(blockId)))
+ */
+export async function applySenderBlock(blockId: number, confirmToken: string): Promise<number> {
+  return invoke<number>('sender_block_apply', { blockId, confirmToken });
+}
+
+/**
+ * Sending quota of an account: limits in effect and recipients sent recently
+ */
+export async function getSendQuotaStatus(accountId: number): Promise<SendQuotaStatus> {
+  return invoke<SendQuotaStatus>('send_quota_status', { accountId });
+}
+
+/**
+ * Set an account's own sending limits; null returns to the provider's limits
+ */
+export async function setSendQuotaLimits(accountId: number, limits: SendQuotaLimits | null): Promise<void> {
+  return invoke('send_quota_set_limits', { accountId, limits });
+}
+
+/**
+ * Folders watched for new mail and changes
+ */
+export async function getFolderWatchConfig(accountId: number): Promise<FolderWatchConfig> {
+  return invoke<FolderWatchConfig>('folder_watch_get_config', { accountId });
+}
+
+/**
+ * Change the watched folders; resolves to the saved (cleaned up) list
+ */
+export async function setFolderWatchConfig(accountId: number, config: FolderWatchConfig): Promise<FolderWatchConfig> {
+  return invoke<FolderWatchConfig>('folder_watch_set_config', { accountId, config });
+}
+
+/**
+ * Folder watcher state of an account; null while not watching
+ */
+export async function getFolderWatchStatus(accountId: number): Promise<FolderWatchStatus | null> {
+  return invoke<FolderWatchStatus | null>('folder_watch_status', { accountId });
+}
+
+/**
+ * Accounts refreshed on their own interval instead of the auto-sync interval
+ */
+export async function getRefreshIntervals(): Promise<AccountRefresh[]> {
+  return invoke<AccountRefresh[]>('refresh_intervals_get');
+}
+
+/**
+ * Set an account's refresh interval in minutes; null follows the auto-sync interval
+ */
+export async function setRefreshInterval(accountId: number, intervalMinutes: number | null): Promise<void> {
+  return invoke('refresh_interval_set', { accountId, intervalMinutes });
+}
+
+/** Event sent when an account's refresh timer fires */
+export const REFRESH_DUE_EVENT = 'refresh://due';
+
+/**
+ * Call `onDue` whenever the backend's timer for an account fires (each enabled
+ * account on its own interval while auto-sync is on); resolves to an unlisten function
+ */
+export async function onRefreshDue(onDue: (accountId: number) => void): Promise<() => void> {
+  return listen<RefreshDue>(REFRESH_DUE_EVENT, (event) => onDue(event.payload.accountId));
+}
+
+/**
+ * Whether attachments are encrypted at rest
+ */
+export async function getAttachmentEncryption(): Promise<boolean> {
+  return invoke<boolean>('attachment_encryption_get');
+}
+
+/**
+ * Turn attachment encryption at rest on or off; enabling encrypts the files
+ * already stored and resolves to their number
+ */
+export async function setAttachmentEncryption(enabled: boolean): Promise<number> {
+  return invoke<number>('attachment_encryption_set', { enabled });
+}
+
+/**
+ * Read-ahead settings for unread message bodies
+ */
+export async function getPrefetchSettings(): Promise<PrefetchSettings> {
+  return invoke<PrefetchSettings>('prefetch_settings_get');
+}
+
+/**
+ * Save read-ahead settings; resolves to them as stored (clamped)
+ */
+export async function setPrefetchSettings(settings: PrefetchSettings): Promise<PrefetchSettings> {
+  return invoke<PrefetchSettings>('prefetch_settings_set', { settings });
+}
+
+/**
+ * Settings of the parallel body download after a folder backfill
+ */
+export async function getBodyBackfillSettings(): Promise<BodyBackfillSettings> {
+  return invoke<BodyBackfillSettings>('body_backfill_settings_get');
+}
+
+/**
+ * Save the body download settings; returns them as stored (clamped)
+ */
+export async function setBodyBackfillSettings(settings: BodyBackfillSettings): Promise<BodyBackfillSettings> {
+  return invoke<BodyBackfillSettings>('body_backfill_settings_set', { settings });
+}
+
+/**
+ * Report whether the network is metered; optional traffic such as
+ * prefetching is skipped while it is
+ */
+export async function setNetworkMetered(metered: boolean): Promise<void> {
+  return invoke<void>('network_set_metered', { metered });
+}
+
+/**
+ * Whether sent messages are uploaded to the Sent folder
+ */
+export async function getSentCopyUpload(): Promise<boolean> {
+  return invoke<boolean>('sent_copy_upload_get');
+}
+
+/**
+ * Turn uploading sent messages to the Sent folder on or off
+ */
+export async function setSentCopyUpload(enabled: boolean): Promise<void> {
+  return invoke<void>('sent_copy_upload_set', { enabled });
+}
+
+/**
+ * Sent copies skipped because they exceeded the server's APPENDLIMIT
+ */
+export async function getSentCopyWarnings(accountId?: number): Promise<SentCopyWarning[]> {
+  return invoke<SentCopyWarning[]>('sent_copy_warnings', { accountId: accountId ?? null });
+}
+
+/**
+ * Re-encrypt all stored secrets under a newly generated encryption key
+ * (confirmToken from requestConfirmation('crypto_rekey', ''))
+ */
+export async function rekeyCrypto(confirmToken: string): Promise<RekeyReport> {
+  return invoke<RekeyReport>('crypto_rekey', { confirmToken });
+}
+
+/**
+ * Drop decrypted account credentials cached in memory (e.g. on app lock)
+ */
+export async function lockCredentials(): Promise<void> {
+  return invoke('credentials_lock');
+}
+
+/**
+ * Sign-in failure state of an account; `needsAttention` means sign-ins have
+ * stopped until a new password is entered
+ */
+export async function getAccountAuthState(accountId: number): Promise<AccountAuthState> {
+  return invoke<AccountAuthState>('account_get_auth_state', { accountId });
+}
+
+/** Event emitted when repeated sign-in failures stop an account's sign-ins */
+export const ACCOUNT_NEEDS_ATTENTION_EVENT = 'account://needs-attention';
+
+/**
+ * Server extensions of an account (IDLE, MOVE, SMTP size limit, ...);
+ * probes the servers when nothing is cached or `refresh` is set
+ */
+export async function getAccountCapabilities(accountId: number, refresh = false): Promise<AccountCapabilities> {
+  return invoke<AccountCapabilities>('account_get_capabilities', { accountId, refresh });
+}
+
+/** Event carrying the steps of the startup sync */
+export const STARTUP_PROGRESS_EVENT = 'startup://progress';
+
+/** Event carrying pages of the default account's Inbox during the startup sync */
+export const STARTUP_INBOX_EVENT = 'startup://inbox';
+
+/**
+ * Connect the accounts at startup: the default one first, its Inbox passed to
+ * `onInbox` page by page, then the others a few at a time
+ * Resolves once every account was tried; null when the startup sync already ran.
+ */
+export async function startupSync(
+  onInbox: (page: StartupInbox) => void,
+  onProgress?: (progress: StartupProgress) => void
+): Promise<StartupPlan | null> {
+  let finish = () => {};
+  const finished = new Promise<void>((resolve) => {
+    finish = resolve;
+  });
+  const unlistenInbox = await listen<StartupInbox>(STARTUP_INBOX_EVENT, (event) => onInbox(event.payload));
+  const unlistenProgress = await listen<StartupProgress>(STARTUP_PROGRESS_EVENT, (event) => {
+    onProgress?.(event.payload);
+    if (event.payload.stage === 'done') finish();
+  });
+
+  try {
+    const plan = await invoke<StartupPlan | null>('startup_sync');
+    if (plan) await finished;
+    return plan;
+  } finally {
+    unlistenInbox();
+    unlistenProgress();
+  }
+}
+
 /**
  * Fetch emails from all active accounts (unified inbox)
  */
@@ -214,12 +762,8 @@ export async function syncEmailsWithFilters(
   page: number,
   pageSize: number,
   folder?: string
-): Promise<{
-  fetchResult: { emails: EmailSummary[]; total: number; hasMore: boolean };
-  newEmailsCount: number;
-  filtersAppliedCount: number;
-}> {
-  return invoke('email_sync_with_filters', { accountId, folder, page, pageSize });
+): Promise<EmailSyncResult> {
+  return invoke<EmailSyncResult>('email_sync_with_filters', { accountId, folder, page, pageSize });
 }
 
 /**
@@ -229,6 +773,28 @@ export async function getEmail(accountId: string, uid: number, folder?: string):
   return invoke<Email>('email_get', { accountId, uid, folder });
 }
 
+/**
+ * Recent destructive actions (deletes, filter moves, removals), newest first
+ */
+export async function listActivity(limit?: number): Promise<ActivityEntry[]> {
+  return invoke<ActivityEntry[]>('activity_list', { limit: limit ?? null });
+}
+
+/**
+ * Revert a logged action (soft-deleted mail and filter moves)
+ */
+export async function undoActivity(id: number): Promise<void> {
+  return invoke<void>('activity_undo', { id });
+}
+
+/**
+ * All contents seen for a Message-ID, oldest first (more than one when the
+ * message was re-delivered with altered content)
+ */
+export async function getEmailVersions(accountId: number, messageId: string): Promise<MessageVersion[]> {
+  return invoke<MessageVersion[]>('email_versions', { accountId, messageId });
+}
+
 /**
  * Search emails using local FTS5
  */
@@ -236,8 +802,8 @@ export async function searchEmails(
   accountId: string,
   query: string,
   folder?: string
-): Promise<EmailSummary[]> {
-  return invoke<EmailSummary[]>('email_search', { accountId, query, folder });
+): Promise<LocalEmailSummary[]> {
+  return invoke<LocalEmailSummary[]>('email_search', { accountId, query, folder });
 }
 
 /**
@@ -249,7 +815,40 @@ export async function searchEmailsAdvanced(
   limit: number = 100,
   offset: number = 0
 ): Promise<SearchResult> {
-  return invoke('email_search_advanced', { accountId, filters, limit, offset });
+  return invoke<SearchResult>('email_search_advanced', { accountId, filters, limit, offset });
+}
+
+/**
+ * List an account's attachments, one page (0-based) at a time
+ */
+export async function listAttachments(
+  accountId: number,
+  filters: Partial<AttachmentFilters> = {},
+  page: number = 0
+): Promise<AttachmentPage> {
+  return invoke<AttachmentPage>('attachments_list', { accountId, filters, page });
+}
+
+/**
+ * Largest messages, biggest senders, counts per year and unread aging of an account
+ */
+export async function getMailboxStats(accountId: number): Promise<MailboxStats> {
+  return invoke<MailboxStats>('mailbox_stats', { accountId });
+}
+
+/**
+ * Cleanup proposals for an account; each can be carried out with executeCleanup
+ */
+export async function getCleanupSuggestions(accountId: number): Promise<CleanupSuggestion[]> {
+  return invoke<CleanupSuggestion[]>('cleanup_suggestions', { accountId });
+}
+
+/**
+ * Carry out a cleanup suggestion, returning the number of messages queued
+ * (confirmToken from requestConfirmation('cleanup_execute', `${accountId}@${actionId}`))
+ */
+export async function executeCleanup(accountId: number, actionId: string, confirmToken: string): Promise<number> {
+  return invoke<number>('cleanup_execute', { accountId, actionId, confirmToken });
 }
 
 /**
@@ -260,8 +859,8 @@ export async function markEmailRead(
   uid: number,
   read: boolean,
   folder?: string
-): Promise<void> {
-  return invoke('email_mark_read', { accountId, uid, read, folder });
+): Promise<UndoHandle> {
+  return invoke<UndoHandle>('email_mark_read', { accountId, uid, read, folder });
 }
 
 /**
@@ -272,8 +871,28 @@ export async function markEmailStarred(
   uid: number,
   starred: boolean,
   folder?: string
-): Promise<void> {
-  return invoke('email_mark_starred', { accountId, uid, starred, folder });
+): Promise<UndoHandle> {
+  return invoke<UndoHandle>('email_mark_starred', { accountId, uid, starred, folder });
+}
+
+/**
+ * Add or remove a shared tag (IMAP keyword) on an email
+ */
+export async function setEmailTag(
+  accountId: string,
+  uid: number,
+  tag: string,
+  value: boolean,
+  folder?: string
+): Promise<UndoHandle> {
+  return invoke<UndoHandle>('email_set_tag', { accountId, uid, tag, value, folder });
+}
+
+/**
+ * Shared tags defined in a folder
+ */
+export async function getFolderTags(accountId: string, folder: string): Promise<FolderKeywords> {
+  return invoke<FolderKeywords>('imap_folder_tags', { accountId, folder });
 }
 
 /**
@@ -284,8 +903,8 @@ export async function moveEmail(
   uid: number,
   targetFolder: string,
   folder?: string
-): Promise<void> {
-  return invoke('email_move', { accountId, uid, targetFolder, folder });
+): Promise<UndoHandle> {
+  return invoke<UndoHandle>('email_move', { accountId, uid, targetFolder, folder });
 }
 
 /**
@@ -296,8 +915,133 @@ export async function deleteEmail(
   uid: number,
   permanent: boolean = false,
   folder?: string
-): Promise<void> {
-  return invoke('email_delete', { accountId, uid, permanent, folder });
+): Promise<UndoHandle> {
+  return invoke<UndoHandle>('email_delete', { accountId, uid, permanent, folder });
+}
+
+/**
+ * Reverse a mailbox action by the token it returned (valid for the undo window)
+ */
+export async function undoAction(token: string): Promise<void> {
+  return invoke<void>('action_undo', { token });
+}
+
+/**
+ * Seconds an undo token stays valid
+ */
+export async function getUndoWindow(): Promise<number> {
+  return invoke<number>('undo_window_get');
+}
+
+/**
+ * Set the undo window; returns it as stored (1 to 300 seconds)
+ */
+export async function setUndoWindow(seconds: number): Promise<number> {
+  return invoke<number>('undo_window_set', { seconds });
+}
+
+/** Event emitted when reply reminders are due (payload: ReplyReminder[]) */
+export const REPLY_REMINDER_EVENT = 'reply://reminder';
+
+/**
+ * Override whether a message needs a reply (null = automatic detection)
+ */
+export async function setNeedsReply(emailId: number, needsReply: boolean | null): Promise<void> {
+  return invoke('reply_needed_set', { emailId, needsReply });
+}
+
+/**
+ * Remind about an unanswered message (null clears the reminder)
+ */
+export async function setReplyReminder(emailId: number, remindAt: Date | null): Promise<void> {
+  return invoke('reply_needed_remind', { emailId, remindAt: remindAt?.toISOString() ?? null });
+}
+
+/**
+ * Events proposed from the dates and times a message mentions, for the user to confirm
+ */
+export async function extractEmailEvents(emailId: number): Promise<ProposedEvent[]> {
+  return invoke<ProposedEvent[]>('email_extract_events', { emailId });
+}
+
+/**
+ * Meeting invites sent from an account with each attendee's answer
+ */
+export async function listInvites(accountId: number): Promise<Invite[]> {
+  return invoke<Invite[]>('invite_list', { accountId });
+}
+
+/**
+ * Move a message between Focused and Other; the choice trains the classifier.
+ * Returns how many other messages moved along with it.
+ */
+export async function setEmailFocus(emailId: number, focused: boolean): Promise<number> {
+  return invoke<number>('email_set_focus', { emailId, focused });
+}
+
+/**
+ * UIDs of a folder's messages sorted into Other (default INBOX)
+ */
+export async function getOtherUids(accountId: number, folder?: string): Promise<number[]> {
+  return invoke<number[]>('email_focus_other', { accountId, folder: folder ?? null });
+}
+
+/**
+ * All per-domain security policies
+ */
+export async function listDomainPolicies(): Promise<DomainPolicy[]> {
+  return invoke<DomainPolicy[]>('domain_policy_list');
+}
+
+/**
+ * Create a domain policy, or replace the rules of an existing pattern
+ */
+export async function saveDomainPolicy(policy: NewDomainPolicy): Promise<DomainPolicy> {
+  return invoke<DomainPolicy>('domain_policy_save', { policy });
+}
+
+export async function deleteDomainPolicy(id: number): Promise<void> {
+  return invoke<void>('domain_policy_delete', { id });
+}
+
+/**
+ * Whether opening a link from a sender's message needs an interstitial warning
+ */
+export async function checkPolicyLink(sender: string, url: string): Promise<LinkCheck> {
+  return invoke<LinkCheck>('domain_policy_check_link', { sender, url });
+}
+
+/**
+ * Final destination of a link with reputation hints; wrappers are decoded
+ * and shorteners resolved without loading the target
+ */
+export async function previewLink(url: string): Promise<LinkPreview> {
+  return invoke<LinkPreview>('link_preview', { url });
+}
+
+/** Bytes sent per temp_attachment_append call (the backend accepts up to 4 MiB) */
+const UPLOAD_CHUNK_SIZE = 1024 * 1024;
+
+/**
+ * Copy a picked file to a temp file for sending, in chunks
+ * The upload is cancelled (and its partial file deleted) on any failure.
+ */
+export async function uploadTempAttachment(
+  file: Blob,
+  filename: string,
+  contentType: string
+): Promise<{ path: string; filename: string; contentType: string }> {
+  const handle = await invoke<string>('temp_attachment_begin', { filename, contentType, size: file.size });
+  try {
+    for (let offset = 0; offset < file.size; offset += UPLOAD_CHUNK_SIZE) {
+      const chunk = new Uint8Array(await file.slice(offset, offset + UPLOAD_CHUNK_SIZE).arrayBuffer());
+      await invoke<number>('temp_attachment_append', chunk, { headers: { 'Upload-Handle': handle } });
+    }
+    return await invoke('temp_attachment_finish', { handle });
+  } catch (err) {
+    await invoke('temp_attachment_abort', { handle }).catch(() => undefined);
+    throw err;
+  }
 }
 
 /**
@@ -321,23 +1065,9 @@ export async function sendEmail(draft: DraftEmail): Promise<void> {
     attachmentPaths = await Promise.all(
       attachmentsWithFiles.map(async (att) => {
         if (att._file) {
-          // Read File as ArrayBuffer
-          const buffer = await att._file.arrayBuffer();
-          const uint8Array = new Uint8Array(buffer);
-
-          // Write to temp file using Tauri
+          // Stream the file to a temp file in chunks
           try {
-            // Use Tauri command to write temp file
-            const result = await invoke<{ path: string; filename: string; contentType: string }>(
-              'write_temp_attachment',
-              {
-                filename: att.filename,
-                contentType: att.contentType,
-                data: Array.from(uint8Array),
-              }
-            );
-
-            return result;
+            return await uploadTempAttachment(att._file, att.filename, att.contentType);
           } catch (err) {
             console.error('Failed to write temp file:', err);
             throw new Error(`Failed to prepare attachment: ${att.filename}`);
@@ -362,12 +1092,121 @@ export async function sendEmail(draft: DraftEmail): Promise<void> {
     cc: draft.cc.map((r) => r.email),
     bcc: draft.bcc.map((r) => r.email),
     subject: draft.subject,
-    textBody: draft.bodyText,
-    htmlBody: draft.bodyHtml,
-    attachmentPaths,
+    options: {
+      // Markdown drafts are rendered to both bodies by the backend
+      textBody: draft.bodyMarkdown ? null : draft.bodyText,
+      htmlBody: draft.bodyMarkdown ? null : draft.bodyHtml,
+      markdownBody: draft.bodyMarkdown ?? null,
+      attachmentPaths,
+      onBehalfOf: draft.onBehalfOf ?? null,
+      invite: draft.invite ?? null,
+    },
+  });
+}
+
+/**
+ * Redirect a message unchanged to new recipients; they see the original sender
+ */
+export async function redirectEmail(emailId: number, to: string[]): Promise<void> {
+  return invoke('email_redirect', { emailId, to });
+}
+
+/**
+ * Check a draft before sending (forgotten attachment, empty subject, recipients)
+ * Pass the folder and UID of the message being replied to so confidential threads are detected.
+ */
+export async function precheckSend(
+  draft: DraftEmail,
+  replyTo?: { folder: string; uid: number }
+): Promise<SendWarning[]> {
+  return invoke('email_precheck_send', {
+    accountId: draft.accountId.toString(),
+    draft: {
+      to: draft.to.map((r) => r.email),
+      cc: draft.cc.map((r) => r.email),
+      bcc: draft.bcc.map((r) => r.email),
+      subject: draft.subject,
+      textBody: draft.bodyText,
+      htmlBody: draft.bodyHtml,
+      attachmentCount: draft.attachments.length,
+      replyFolder: replyTo?.folder,
+      replyUid: replyTo?.uid,
+      replyAll: draft.composeType === 'replyAll',
+    },
   });
 }
 
+/**
+ * Limits of the pre-send checks (reply-all threshold)
+ */
+export async function getPrecheckSettings(): Promise<PrecheckSettings> {
+  return invoke<PrecheckSettings>('precheck_settings_get');
+}
+
+/**
+ * Save the limits of the pre-send checks
+ */
+export async function setPrecheckSettings(settings: PrecheckSettings): Promise<void> {
+  return invoke('precheck_settings_set', { settings });
+}
+
+/**
+ * Contacts whose address bounced, with when and why
+ */
+export async function listBouncingContacts(accountId: number): Promise<BouncingContact[]> {
+  return invoke<BouncingContact[]>('contact_list_bouncing', { accountId });
+}
+
+/**
+ * Remove dead addresses from the address book; returns how many were removed
+ */
+export async function removeBouncingContacts(accountId: number, contactIds: number[]): Promise<number> {
+  return invoke<number>('contact_remove_bouncing', { accountId, contactIds });
+}
+
+/**
+ * Copy folders from one account to another in the background (progress via
+ * the "migration://progress" event); false when one is already running
+ * (confirmToken from requestConfirmation('account_migrate', `${sourceId}@${targetId}`))
+ */
+export async function migrateAccount(
+  sourceId: number,
+  targetId: number,
+  folderMapping: FolderMapping[],
+  confirmToken: string
+): Promise<boolean> {
+  return invoke<boolean>('account_migrate', { sourceId, targetId, folderMapping, confirmToken });
+}
+
+/**
+ * Migration progress for each folder pair between two accounts
+ */
+export async function getAccountMigrationStatus(sourceId: number, targetId: number): Promise<MigrationState[]> {
+  return invoke<MigrationState[]>('account_migrate_status', { sourceId, targetId });
+}
+
+/**
+ * Check SPF/DKIM/DMARC of the account's sending domain against its SMTP host
+ */
+export async function checkSenderAuth(accountId: number): Promise<SenderAuthReport> {
+  return invoke<SenderAuthReport>('account_check_sender_auth', { accountId: accountId.toString() });
+}
+
+/**
+ * Suggest when to send to these recipients, from their past activity
+ * `sendAt` feeds the scheduled send; undefined means no usable pattern.
+ */
+export async function suggestSendTime(recipients: string[]): Promise<SendTimeSuggestion> {
+  return invoke<SendTimeSuggestion>('suggest_send_time', { recipients });
+}
+
+/**
+ * Attachment download totals since start: BINARY fetches vs whole messages
+ */
+export async function getFetchBenchmarks(): Promise<FetchBenchmark[]> {
+  return invoke<FetchBenchmark[]>('metrics_fetch_benchmarks');
+}
+
 /**
  * Download attachment from email
  */
@@ -424,17 +1263,213 @@ export async function setSetting<T>(key: string, value: T): Promise<void> {
 }
 
 /**
- * Get all settings
+ * Write a diagnostics zip for support to `path`
+ * Secrets are always masked; email addresses only with `redactAddresses`
+ * (confirmToken from requestConfirmation('diagnostics_export', path))
+ */
+export async function exportDiagnostics(path: string, confirmToken: string, redactAddresses = false): Promise<void> {
+  return invoke('diagnostics_export', { path, redactAddresses, confirmToken });
+}
+
+/**
+ * Get the global hotkey bindings
+ */
+export async function getHotkeys(): Promise<HotkeyConfig> {
+  return invoke<HotkeyConfig>('hotkeys_get_config');
+}
+
+/**
+ * Save the global hotkey bindings and register them again
+ * Shortcuts held by another application come back as conflicts
+ */
+export async function setHotkeys(config: HotkeyConfig): Promise<HotkeyStatus> {
+  return invoke<HotkeyStatus>('hotkeys_set_config', { config });
+}
+
+/**
+ * Registered global hotkeys and conflicts
+ */
+export async function getHotkeyStatus(): Promise<HotkeyStatus> {
+  return invoke<HotkeyStatus>('hotkeys_status');
+}
+
+/**
+ * Parse a `mailto:` or `owlivion://` link
+ */
+export async function parseDeepLink(link: string): Promise<DeepLink> {
+  return invoke<DeepLink>('deep_link_parse', { link });
+}
+
+/**
+ * Links opened from outside the app since the last call
+ * Call on startup and on each `deep-link://open` event
+ */
+export async function takePendingDeepLinks(): Promise<DeepLink[]> {
+  return invoke<DeepLink[]>('deep_link_take_pending');
+}
+
+/**
+ * Whether `mailto:` links open in Owlivion Mail
+ */
+export async function isDefaultMailer(): Promise<boolean> {
+  return invoke<boolean>('system_is_default_mailer');
+}
+
+/**
+ * Make Owlivion Mail the default mail client
+ * Resolves false when the user still has to confirm in the system settings (Windows)
+ */
+export async function setDefaultMailer(): Promise<boolean> {
+  return invoke<boolean>('system_set_default_mailer');
+}
+
+/**
+ * Start Owlivion Mail on login, optionally minimized to the tray
+ */
+export async function enableAutostart(minimized: boolean): Promise<AutostartStatus> {
+  return invoke<AutostartStatus>('autostart_enable', { minimized });
+}
+
+/**
+ * Stop starting Owlivion Mail on login
+ */
+export async function disableAutostart(): Promise<AutostartStatus> {
+  return invoke<AutostartStatus>('autostart_disable');
+}
+
+/**
+ * Whether Owlivion Mail starts on login
+ */
+export async function getAutostartStatus(): Promise<AutostartStatus> {
+  return invoke<AutostartStatus>('autostart_status');
+}
+
+/**
+ * Private note on an email, null if it has none
+ */
+export async function getNote(emailId: number): Promise<EmailNote | null> {
+  return invoke<EmailNote | null>('note_get', { emailId });
+}
+
+/**
+ * Save the note of an email; an empty note without a color removes it
+ */
+export async function setNote(emailId: number, body: string, color: NoteColor | null): Promise<EmailNote | null> {
+  return invoke<EmailNote | null>('note_set', { emailId, body, color });
+}
+
+/**
+ * Remove the note of an email
+ */
+export async function deleteNote(emailId: number): Promise<boolean> {
+  return invoke<boolean>('note_delete', { emailId });
+}
+
+/**
+ * Notes of an account (all accounts if omitted), most recently edited first
+ */
+export async function listNotes(accountId?: number): Promise<EmailNote[]> {
+  return invoke<EmailNote[]>('note_list', { accountId: accountId ?? null });
+}
+
+/**
+ * Generate a plus-address (user+label@domain); the same label returns the same alias
+ */
+export async function generateAlias(accountId: number, label: string): Promise<PlusAlias> {
+  return invoke<PlusAlias>('alias_generate', { accountId, label });
+}
+
+/**
+ * Plus-addresses generated for an account
+ */
+export async function listAliases(accountId: number): Promise<PlusAlias[]> {
+  return invoke<PlusAlias[]>('alias_list', { accountId });
+}
+
+/**
+ * Forget a generated plus-address
+ */
+export async function deleteAlias(accountId: number, tag: string): Promise<boolean> {
+  return invoke<boolean>('alias_delete', { accountId, tag });
+}
+
+/**
+ * Tag of the plus-address an email was received through
+ */
+export async function getEmailAlias(emailId: number): Promise<string | null> {
+  return invoke<string | null>('alias_of_email', { emailId });
+}
+
+/**
+ * Received and junk counts per plus-address
+ */
+export async function getAliasStats(accountId: number): Promise<AliasStats[]> {
+  return invoke<AliasStats[]>('alias_stats', { accountId });
+}
+
+/**
+ * Data directory when running in portable mode, null otherwise
+ */
+export async function getPortableDir(): Promise<string | null> {
+  return invoke<string | null>('portable_dir_get');
+}
+
+/**
+ * Profiles, and the one the app is running in
+ */
+export async function listProfiles(): Promise<ProfileList> {
+  return invoke<ProfileList>('profile_list');
+}
+
+/**
+ * Create an empty profile
+ */
+export async function createProfile(name: string): Promise<Profile> {
+  return invoke<Profile>('profile_create', { name });
+}
+
+/**
+ * Restart the app into another profile (also the one opened at the next launch)
+ * (confirmToken from requestConfirmation('profile_switch', profileId))
+ */
+export async function switchProfile(profileId: string, confirmToken: string): Promise<void> {
+  return invoke('profile_switch', { profileId, confirmToken });
+}
+
+/**
+ * Progress of the guided first-run setup
+ */
+export async function getOnboardingState(): Promise<OnboardingState> {
+  return invoke<OnboardingState>('onboarding_get_state');
+}
+
+/**
+ * Mark a setup step completed, or skipped
+ */
+export async function completeOnboardingStep(step: OnboardingStep, skipped = false): Promise<OnboardingState> {
+  return invoke<OnboardingState>('onboarding_complete_step', { step, skipped });
+}
+
+/**
+ * Start the guided setup over
+ */
+export async function resetOnboarding(): Promise<OnboardingState> {
+  return invoke<OnboardingState>('onboarding_reset');
+}
+
+/**
+ * Get all settings, with defaults for those never saved
  */
 export async function getSettings(): Promise<Settings> {
   return invoke<Settings>('settings_get_all');
 }
 
 /**
- * Save all settings
+ * Save several settings at once; rejects (saving nothing) if any value is invalid.
+ * Resolves to the values that changed, also broadcast as `settings://changed`.
  */
-export async function saveSettings(settings: Settings): Promise<void> {
-  return invoke('settings_save_all', { settings });
+export async function saveSettings(settings: Partial<Settings>): Promise<Partial<Settings>> {
+  return invoke<Partial<Settings>>('settings_set_many', { values: settings });
 }
 
 // ============================================================================
@@ -2,7 +2,9 @@
 // Owlivion Mail - Notification Service
 // ============================================================================
 
+import { invoke } from '@tauri-apps/api/core';
 import { isPermissionGranted, requestPermission, sendNotification } from '@tauri-apps/plugin-notification';
+import type { Contact, DndConfig, NotificationRoute, NotificationSound, NotificationSoundConfig } from '../types';
 
 // Audio context for notification sound
 let audioContext: AudioContext | null = null;
@@ -137,3 +139,75 @@ export async function showNotification(title: string, body: string): Promise<voi
     console.error('Failed to show notification:', err);
   }
 }
+
+/**
+ * How each new message should be announced
+ * VIP senders are always 'priority'; DND and the digest make the rest 'silent'.
+ */
+export async function notificationRoute(
+  emails: { from: string; subject: string }[]
+): Promise<NotificationRoute[]> {
+  if (emails.length === 0) return [];
+  return await invoke<NotificationRoute[]>('notification_route', { emails });
+}
+
+/**
+ * Get the Do Not Disturb configuration
+ */
+export async function getDndConfig(): Promise<DndConfig> {
+  return await invoke<DndConfig>('notification_get_dnd');
+}
+
+/**
+ * Update Do Not Disturb (always on or quiet hours)
+ */
+export async function setDndConfig(config: DndConfig): Promise<DndConfig> {
+  return await invoke<DndConfig>('notification_set_dnd', { config });
+}
+
+/**
+ * Get the notification volume and per-account/per-folder sounds
+ */
+export async function getNotificationSounds(): Promise<NotificationSoundConfig> {
+  return await invoke<NotificationSoundConfig>('notification_get_sounds');
+}
+
+/**
+ * Update the notification volume and per-account/per-folder sounds
+ */
+export async function setNotificationSounds(config: NotificationSoundConfig): Promise<NotificationSoundConfig> {
+  return await invoke<NotificationSoundConfig>('notification_set_sounds', { config });
+}
+
+/**
+ * Play the sound for a new message (played by the backend, respecting DND)
+ * Returns whether a sound played.
+ */
+export async function playNewMailSound(
+  accountId: number,
+  folder: string | null,
+  email: { from: string; subject: string }
+): Promise<boolean> {
+  return await invoke<boolean>('notification_play_sound', { accountId, folder, email });
+}
+
+/**
+ * Play a sound once to try it (ignores DND)
+ */
+export async function previewNotificationSound(sound: NotificationSound, volume?: number): Promise<void> {
+  await invoke('notification_preview_sound', { sound, volume });
+}
+
+/**
+ * Mark or unmark a sender as VIP
+ */
+export async function setContactVip(accountId: number, email: string, vip: boolean): Promise<void> {
+  await invoke('contact_set_vip', { accountId, email, vip });
+}
+
+/**
+ * VIP contacts of all accounts
+ */
+export async function listVipContacts(): Promise<Contact[]> {
+  return await invoke<Contact[]>('contact_list_vip');
+}
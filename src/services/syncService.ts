@@ -9,7 +9,15 @@ import type {
   DeviceInfo,
   SyncResult,
   ConflictInfo,
-  SchedulerStatus
+  SchedulerStatus,
+  SyncConfigDto,
+  SyncStatusDto,
+  DeviceInfoDto,
+  SyncResultDto,
+  QueueStatsDto,
+  ProcessQueueResultDto,
+  SchedulerStatusDto,
+  KeyRotationResult
 } from '../types';
 
 // ============================================================================
@@ -45,42 +53,28 @@ export async function logoutAccount(): Promise<void> {
 // Sync Operations
 // ============================================================================
 
-/**
- * Start manual sync (requires master password)
- * Now supports bidirectional sync with conflict detection
- */
-export async function startSync(masterPassword: string): Promise<SyncResult> {
-  const result = await invoke<{
-    accounts_synced: boolean;
-    contacts_synced: boolean;
-    preferences_synced: boolean;
-    signatures_synced: boolean;
-    errors: string[];
-    conflicts?: {
-      data_type: string;
-      local_version: number;
-      server_version: number;
-      local_updated_at?: string;
-      server_updated_at?: string;
-      strategy: string;
-      conflict_details: string;
-      local_data: any;
-      server_data: any;
-    }[];
-  }>('sync_start', { masterPassword });
-
+function toSyncResult(result: SyncResultDto): SyncResult {
   return {
     accountsSynced: result.accounts_synced,
     contactsSynced: result.contacts_synced,
     preferencesSynced: result.preferences_synced,
     signaturesSynced: result.signatures_synced,
+    templatesSynced: result.templates_synced,
+    filtersSynced: result.filters_synced,
+    notesSynced: result.notes_synced,
     errors: result.errors,
+    types: result.types.map(t => ({
+      dataType: t.data_type,
+      status: t.status,
+      error: t.error ?? undefined,
+    })),
+    offline: result.offline,
     conflicts: result.conflicts?.map(c => ({
       dataType: c.data_type,
       localVersion: c.local_version,
       serverVersion: c.server_version,
-      localUpdatedAt: c.local_updated_at,
-      serverUpdatedAt: c.server_updated_at,
+      localUpdatedAt: c.local_updated_at ?? undefined,
+      serverUpdatedAt: c.server_updated_at ?? undefined,
       strategy: c.strategy as ConflictInfo['strategy'],
       conflictDetails: c.conflict_details,
       localData: c.local_data,
@@ -89,6 +83,22 @@ export async function startSync(masterPassword: string): Promise<SyncResult> {
   };
 }
 
+/**
+ * Start manual sync (requires master password)
+ * Now supports bidirectional sync with conflict detection
+ * Each data type syncs independently; see `types` for per-type outcomes
+ */
+export async function startSync(masterPassword: string): Promise<SyncResult> {
+  return toSyncResult(await invoke<SyncResultDto>('sync_start', { masterPassword }));
+}
+
+/**
+ * Retry only the data types deferred by an earlier sync (offline, server errors)
+ */
+export async function retryDeferredSync(masterPassword: string): Promise<SyncResult> {
+  return toSyncResult(await invoke<SyncResultDto>('sync_retry_deferred', { masterPassword }));
+}
+
 /**
  * Resolve a sync conflict manually
  */
@@ -108,30 +118,22 @@ export async function resolveConflict(
  * Get current sync configuration
  */
 export async function getSyncConfig(): Promise<SyncConfig> {
-  const config = await invoke<{
-    enabled: boolean;
-    user_id?: string;
-    device_id: string;
-    device_name: string;
-    platform: string;
-    last_sync_at?: string;
-    sync_accounts: boolean;
-    sync_contacts: boolean;
-    sync_preferences: boolean;
-    sync_signatures: boolean;
-  }>('sync_get_config');
+  const config = await invoke<SyncConfigDto>('sync_get_config');
 
   return {
     enabled: config.enabled,
-    userId: config.user_id,
+    userId: config.user_id ?? undefined,
     deviceId: config.device_id,
     deviceName: config.device_name,
     platform: config.platform as 'windows' | 'macos' | 'linux',
-    lastSyncAt: config.last_sync_at,
+    lastSyncAt: config.last_sync_at ?? undefined,
     syncAccounts: config.sync_accounts,
     syncContacts: config.sync_contacts,
     syncPreferences: config.sync_preferences,
     syncSignatures: config.sync_signatures,
+    syncTemplates: config.sync_templates,
+    syncFilters: config.sync_filters,
+    syncNotes: config.sync_notes,
   };
 }
 
@@ -139,20 +141,35 @@ export async function getSyncConfig(): Promise<SyncConfig> {
  * Update sync configuration
  */
 export async function updateSyncConfig(config: SyncConfig): Promise<void> {
-  return invoke('sync_update_config', {
-    config: {
-      enabled: config.enabled,
-      user_id: config.userId,
-      device_id: config.deviceId,
-      device_name: config.deviceName,
-      platform: config.platform,
-      last_sync_at: config.lastSyncAt,
-      sync_accounts: config.syncAccounts,
-      sync_contacts: config.syncContacts,
-      sync_preferences: config.syncPreferences,
-      sync_signatures: config.syncSignatures,
-    },
-  });
+  const dto: SyncConfigDto = {
+    enabled: config.enabled,
+    user_id: config.userId ?? null,
+    device_id: config.deviceId,
+    device_name: config.deviceName,
+    platform: config.platform,
+    last_sync_at: config.lastSyncAt ?? null,
+    sync_accounts: config.syncAccounts,
+    sync_contacts: config.syncContacts,
+    sync_preferences: config.syncPreferences,
+    sync_signatures: config.syncSignatures,
+    sync_templates: config.syncTemplates,
+    sync_filters: config.syncFilters,
+    sync_notes: config.syncNotes,
+  };
+  return invoke('sync_update_config', { config: dto });
+}
+
+/**
+ * Change the master password and re-encrypt all synced data.
+ * Calling again with the same passwords resumes an interrupted change.
+ * (confirmToken from requestConfirmation('sync_rotate_master_password', ''))
+ */
+export async function rotateMasterPassword(
+  oldMasterPassword: string,
+  newMasterPassword: string,
+  confirmToken: string
+): Promise<KeyRotationResult> {
+  return invoke<KeyRotationResult>('sync_rotate_master_password', { oldMasterPassword, newMasterPassword, confirmToken });
 }
 
 // ============================================================================
@@ -163,19 +180,12 @@ export async function updateSyncConfig(config: SyncConfig): Promise<void> {
  * Get sync status for all data types
  */
 export async function getSyncStatus(): Promise<SyncStatusItem[]> {
-  const statuses = await invoke<
-    {
-      data_type: string;
-      version: number;
-      last_sync_at?: string;
-      status: string;
-    }[]
-  >('sync_get_status');
+  const statuses = await invoke<SyncStatusDto[]>('sync_get_status');
 
   return statuses.map((s) => ({
-    dataType: s.data_type as 'accounts' | 'contacts' | 'preferences' | 'signatures',
+    dataType: s.data_type as 'accounts' | 'contacts' | 'preferences' | 'signatures' | 'templates' | 'filters' | 'notes',
     version: s.version,
-    lastSyncAt: s.last_sync_at,
+    lastSyncAt: s.last_sync_at ?? undefined,
     status: s.status as 'idle' | 'syncing' | 'error',
   }));
 }
@@ -188,14 +198,7 @@ export async function getSyncStatus(): Promise<SyncStatusItem[]> {
  * List all devices for this account
  */
 export async function listDevices(): Promise<DeviceInfo[]> {
-  const devices = await invoke<
-    {
-      device_id: string;
-      device_name: string;
-      platform: string;
-      last_seen_at: string;
-    }[]
-  >('sync_list_devices');
+  const devices = await invoke<DeviceInfoDto[]>('sync_list_devices');
 
   return devices.map((d) => ({
     deviceId: d.device_id,
@@ -224,23 +227,11 @@ export interface QueueStats {
   totalCount: number;
 }
 
-export interface ProcessQueueResult {
-  processed: number;
-  succeeded: number;
-  failed: number;
-}
-
 /**
  * Get queue statistics
  */
 export async function getQueueStats(): Promise<QueueStats> {
-  const result = await invoke<{
-    pending_count: number;
-    in_progress_count: number;
-    failed_count: number;
-    completed_count: number;
-    total_count: number;
-  }>('sync_get_queue_stats');
+  const result = await invoke<QueueStatsDto>('sync_get_queue_stats');
 
   return {
     pendingCount: result.pending_count,
@@ -254,14 +245,8 @@ export async function getQueueStats(): Promise<QueueStats> {
 /**
  * Process pending queue items (retry failed syncs)
  */
-export async function processQueue(masterPassword: string): Promise<ProcessQueueResult> {
-  const result = await invoke<{
-    processed: number;
-    succeeded: number;
-    failed: number;
-  }>('sync_process_queue', { masterPassword });
-
-  return result;
+export async function processQueue(masterPassword: string): Promise<ProcessQueueResultDto> {
+  return invoke<ProcessQueueResultDto>('sync_process_queue', { masterPassword });
 }
 
 /**
@@ -349,20 +334,14 @@ export function getPlatformIcon(platform: string): string {
  * Get scheduler status
  */
 export async function getSchedulerStatus(): Promise<SchedulerStatus> {
-  const status = await invoke<{
-    enabled: boolean;
-    running: boolean;
-    interval_minutes: number;
-    last_run?: string;
-    next_run?: string;
-  }>('scheduler_get_status');
+  const status = await invoke<SchedulerStatusDto>('scheduler_get_status');
 
   return {
     enabled: status.enabled,
     running: status.running,
     intervalMinutes: status.interval_minutes,
-    lastRun: status.last_run,
-    nextRun: status.next_run,
+    lastRun: status.last_run ?? undefined,
+    nextRun: status.next_run ?? undefined,
   };
 }
 
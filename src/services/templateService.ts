@@ -1,5 +1,11 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { EmailTemplate, NewEmailTemplate } from '../types';
+import type {
+  EmailTemplate,
+  MailMergeStatus,
+  NewEmailTemplate,
+  TemplateConflictPolicy,
+  TemplateImportSummary,
+} from '../types';
 
 /**
  * Add a new email template
@@ -94,3 +100,59 @@ export async function templateGetFavorites(accountId: number): Promise<EmailTemp
 export async function templateGetCategories(): Promise<string[]> {
   return await invoke<string[]>('template_get_categories');
 }
+
+/**
+ * Export templates to a JSON file, returns the number written
+ */
+export async function templateExport(ids: number[], path: string): Promise<number> {
+  return await invoke<number>('template_export', { ids, path });
+}
+
+/**
+ * Import templates from an export file into an account (or as global templates)
+ * (confirmToken from requestConfirmation('template_import', path))
+ */
+export async function templateImport(
+  path: string,
+  confirmToken: string,
+  accountId?: number,
+  onConflict?: TemplateConflictPolicy
+): Promise<TemplateImportSummary> {
+  return await invoke<TemplateImportSummary>('template_import', { path, accountId, onConflict, confirmToken });
+}
+
+/**
+ * Send a template to every row of a CSV file ({{column}} variables), returns the merge id
+ * Progress is reported with `mailmerge://progress` events.
+ * (confirmToken from requestConfirmation('mailmerge_start', `${accountId}@${templateId}`))
+ */
+export async function mailMergeStart(
+  accountId: number,
+  templateId: number,
+  csv: string,
+  confirmToken: string,
+  perMinute?: number
+): Promise<number> {
+  return await invoke<number>('mailmerge_start', { accountId, templateId, csv, perMinute, confirmToken });
+}
+
+/**
+ * Progress of a mail merge with the status of every recipient
+ */
+export async function mailMergeStatus(mergeId: number): Promise<MailMergeStatus> {
+  return await invoke<MailMergeStatus>('mailmerge_status', { mergeId });
+}
+
+/**
+ * Stop a mail merge; unsent recipients stay pending
+ */
+export async function mailMergeCancel(mergeId: number): Promise<void> {
+  await invoke('mailmerge_cancel', { mergeId });
+}
+
+/**
+ * Resume a paused or cancelled mail merge, false when it is already running
+ */
+export async function mailMergeResume(mergeId: number): Promise<boolean> {
+  return await invoke<boolean>('mailmerge_resume', { mergeId });
+}
@@ -0,0 +1,31 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { ThreadSummary } from '../types';
+
+/**
+ * Summarize a thread: summary, decisions and action items
+ * Cached until a new message joins the thread. `threadId` is the thread id,
+ * or the Message-ID of the first message when the server has no thread ids.
+ */
+export async function threadSummarize(accountId: number, threadId: string): Promise<ThreadSummary> {
+  return await invoke<ThreadSummary>('thread_summarize', { accountId, threadId });
+}
+
+/**
+ * Write a whole conversation to a self-contained HTML file for printing or archiving
+ */
+export async function threadExportHtml(accountId: number, threadId: string, path: string): Promise<void> {
+  return await invoke('thread_export_html', { accountId, threadId, path });
+}
+
+/**
+ * A whole conversation as Markdown for note-taking tools (Obsidian, Notion)
+ * Quoted replies and signatures are trimmed. With `attachmentsDir`, downloaded
+ * attachments are saved there and linked from the Markdown.
+ */
+export async function threadExportMarkdown(
+  accountId: number,
+  threadId: string,
+  attachmentsDir?: string
+): Promise<string> {
+  return await invoke<string>('thread_export_markdown', { accountId, threadId, attachmentsDir });
+}
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AIProviderKind } from "./AIProviderKind";
+
+/**
+ * Provider configuration as shown to the frontend
+ */
+export type AIProviderInfo = { kind: AIProviderKind, baseUrl: string, model: string, hasApiKey: boolean, 
+/**
+ * False when the provider runs on this machine
+ */
+isRemote: boolean, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Account = { id: number, email: string, displayName: string, imapHost: string, imapPort: number, imapSecurity: string, imapUsername: string | null, smtpHost: string, smtpPort: number, smtpSecurity: string, smtpUsername: string | null, oauthProvider: string | null, oauthRefreshToken: string | null, oauthExpiresAt: number | null, isActive: boolean, isDefault: boolean, signature: string, syncDays: number, acceptInvalidCerts: boolean, enablePriorityFetch: boolean, createdAt: string, updatedAt: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Sign-in failure state of an account
+ */
+export type AccountAuthState = { accountId: number, consecutiveFailures: number, lastError: string | null, lastFailureAt: string | null, 
+/**
+ * Sign-ins stopped until a new password is entered
+ */
+needsAttention: boolean, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImapCapabilities } from "./ImapCapabilities";
+import type { SmtpCapabilities } from "./SmtpCapabilities";
+
+/**
+ * Cached server extensions of an account
+ */
+export type AccountCapabilities = { accountId: number, 
+/**
+ * `None` until the account has connected once
+ */
+imap: ImapCapabilities | null, imapProbedAt: string | null, 
+/**
+ * `None` until the SMTP server has been probed
+ */
+smtp: SmtpCapabilities | null, smtpProbedAt: string | null, };
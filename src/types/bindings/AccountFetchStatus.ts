@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Per-account fetch status
+ */
+export type AccountFetchStatus = { accountId: string, accountEmail: string, accountName: string | null, emailCount: number, success: boolean, error: string | null, fetchTimeMs: number, };
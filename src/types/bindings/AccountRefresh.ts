@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Refresh interval of an account that has its own
+ */
+export type AccountRefresh = { accountId: number, intervalMinutes: number, };
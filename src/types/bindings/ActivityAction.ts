@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kind of destructive action
+ */
+export type ActivityAction = "email_delete" | "filter_delete" | "filter_move" | "local_folder_delete" | "account_remove";
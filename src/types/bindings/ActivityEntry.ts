@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActivityAction } from "./ActivityAction";
+
+/**
+ * Logged action
+ */
+export type ActivityEntry = { id: number, accountId: number | null, action: ActivityAction, summary: string, 
+/**
+ * Has an undo payload that was not applied yet
+ */
+canUndo: boolean, undoneAt: string | null, createdAt: string, };
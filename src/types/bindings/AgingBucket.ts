@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Unread messages received within a span of days
+ */
+export type AgingBucket = { 
+/**
+ * `week`, `month`, `quarter`, `year` or `older`
+ */
+label: string, 
+/**
+ * Upper bound in days (`None` for the last bucket)
+ */
+maxDays: number | null, messages: number, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Mail received through one alias
+ */
+export type AliasStats = { tag: string, address: string, 
+/**
+ * Label given when the alias was generated (`None` if made up elsewhere)
+ */
+label: string | null, received: number, 
+/**
+ * Messages in the Junk folder
+ */
+junk: number, lastReceived: string | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Attachment = { id: number, emailId: number, filename: string, contentType: string, size: number, contentId: string | null, isInline: boolean, localPath: string | null, isDownloaded: boolean, createdAt: string, };
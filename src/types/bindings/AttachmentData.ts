@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Email attachment with data
+ */
+export type AttachmentData = { filename: string, contentType: string, size: number, data: string, };
@@ -0,0 +1,32 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttachmentSort } from "./AttachmentSort";
+
+export type AttachmentFilters = { 
+/**
+ * Part of the file name
+ */
+name?: string, 
+/**
+ * Part of the sender's address or name
+ */
+sender?: string, 
+/**
+ * MIME type, or a type prefix such as `image/`
+ */
+contentType?: string, minSize?: number, maxSize?: number, 
+/**
+ * Messages dated at or after this (RFC 3339 or a date prefix like `2024-01-31`)
+ */
+since?: string, 
+/**
+ * Messages dated at or before this
+ */
+until?: string, folderId?: number, 
+/**
+ * Only attachments already downloaded (or only those not yet)
+ */
+downloaded?: boolean, includeInline: boolean, sort: AttachmentSort, 
+/**
+ * Ascending instead of the default descending order
+ */
+ascending: boolean, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AttachmentItem = { id: number, emailId: number, filename: string, contentType: string, size: number, isInline: boolean, isDownloaded: boolean, fromAddress: string, fromName: string | null, subject: string, date: string, folderId: number, };
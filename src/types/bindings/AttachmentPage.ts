@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttachmentItem } from "./AttachmentItem";
+
+export type AttachmentPage = { items: Array<AttachmentItem>, 
+/**
+ * Matching attachments on all pages
+ */
+total: number, page: number, pageSize: number, hasMore: boolean, };
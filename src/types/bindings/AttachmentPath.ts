@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Attachment file path for sending
+ */
+export type AttachmentPath = { path: string, filename: string, content_type: string, 
+/**
+ * Content-ID for inline images referenced as cid: from the HTML body
+ */
+content_id: string | null, 
+/**
+ * Type the caller declared, when detection found the file to be something else
+ */
+declared_content_type?: string, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result of a pre-send size check
+ */
+export type AttachmentSizeReport = { provider: string, maxMessageSize: number, totalRawSize: number, totalEncodedSize: number, exceedsLimit: boolean, 
+/**
+ * Attachments that would exceed the limit on their own
+ */
+oversized: Array<string>, 
+/**
+ * True when a large-attachment strategy is configured and should be offered
+ */
+suggestLink: boolean, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AttendeeStatus = { 
+/**
+ * Lowercase address
+ */
+email: string, 
+/**
+ * `ACCEPTED`, `DECLINED`, `TENTATIVE`, `NEEDS-ACTION` or `DELEGATED`
+ */
+partstat: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SecurityType } from "./SecurityType";
+
+/**
+ * Auto-detected email configuration
+ */
+export type AutoConfig = { provider: string | null, displayName: string | null, imapHost: string, imapPort: number, imapSecurity: SecurityType, smtpHost: string, smtpPort: number, smtpSecurity: SecurityType, detectionMethod?: string, };
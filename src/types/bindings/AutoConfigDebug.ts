@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AutoConfig } from "./AutoConfig";
+
+/**
+ * Detailed auto-detection debug info
+ */
+export type AutoConfigDebug = { email: string, domain: string, presetTried: boolean, presetResult: string | null, ispAutoconfigTried: boolean, ispAutoconfigResult: string | null, wellknownTried: boolean, wellknownResult: string | null, ispdbTried: boolean, ispdbResult: string | null, mxLookupTried: boolean, mxLookupResult: string | null, guessingTried: boolean, guessingResult: string | null, finalConfig: AutoConfig | null, totalDurationMs: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Backfill progress of one account folder (payload of `backfill://progress`)
+ */
+export type BackfillState = { accountId: number, folder: string, 
+/**
+ * Oldest date included; None means the full history
+ */
+since: string | null, 
+/**
+ * Messages in the window on the server
+ */
+total: number, 
+/**
+ * Of those, already cached locally
+ */
+fetched: number, done: boolean, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BlockAction } from "./BlockAction";
+
+/**
+ * Entry of the blocklist
+ */
+export type BlockedSender = { id: number, accountId: number, 
+/**
+ * Lowercase address, or domain without '@'
+ */
+pattern: string, action: BlockAction, 
+/**
+ * Filter enforcing the block; `None` once the user deleted it
+ */
+filterId: number | null, createdAt: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Body backfill configuration
+ */
+export type BodyBackfillSettings = { enabled: boolean, 
+/**
+ * Concurrent FETCHes (one IMAP connection each)
+ */
+concurrency: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BodySectionKind } from "./BodySectionKind";
+
+/**
+ * Lines `start..end` (0-based) of the plain-text body
+ */
+export type BodySection = { kind: BodySectionKind, start: number, end: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Role of a run of lines
+ */
+export type BodySectionKind = "body" | "quote" | "signature";
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BodySection } from "./BodySection";
+
+/**
+ * Structural markers of a message body
+ */
+export type BodyStructure = { 
+/**
+ * Sections of the plain-text body, in order
+ */
+textSections: Array<BodySection>, 
+/**
+ * Byte offset in the HTML body where the quoted part starts
+ */
+htmlQuoteStart: number | null, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Recipient a DSN reports as permanently undeliverable
+ */
+export type BouncedRecipient = { 
+/**
+ * Lowercase address
+ */
+address: string, 
+/**
+ * Enhanced status code (`5.1.1`)
+ */
+status: string, 
+/**
+ * Diagnostic from the remote server, or the status code when there is none
+ */
+reason: string, };
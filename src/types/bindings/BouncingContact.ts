@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type BouncingContact = { id: number, email: string, name: string | null, bouncedAt: string, bounceReason: string | null, };
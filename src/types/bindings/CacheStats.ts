@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Cache statistics
+ */
+export type CacheStats = { 
+/**
+ * Number of cache hits
+ */
+hits: number, 
+/**
+ * Number of cache misses
+ */
+misses: number, 
+/**
+ * Total cache requests
+ */
+total_requests: number, 
+/**
+ * Cache hit rate (percentage)
+ */
+hit_rate: number, 
+/**
+ * Number of entries in cache
+ */
+entry_count: number, 
+/**
+ * Weighted size of cache
+ */
+weighted_size: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttendeeStatus } from "./AttendeeStatus";
+
+/**
+ * Calendar part of a received message
+ */
+export type CalendarPart = { 
+/**
+ * iTIP method in uppercase (`REQUEST`, `REPLY`, `CANCEL`, ...)
+ */
+method: string, uid: string, summary: string | null, 
+/**
+ * Attendees with their participation status (a REPLY lists the responder)
+ */
+attendees: Array<AttendeeStatus>, };
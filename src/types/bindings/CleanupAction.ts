@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What executing a suggestion does with its messages
+ */
+export type CleanupAction = "archive" | "delete";
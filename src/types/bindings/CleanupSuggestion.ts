@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CleanupAction } from "./CleanupAction";
+import type { CleanupKind } from "./CleanupKind";
+
+export type CleanupSuggestion = { 
+/**
+ * Pass to `cleanup_execute` to carry out the suggestion
+ */
+actionId: string, kind: CleanupKind, action: CleanupAction, title: string, messages: number, totalSize: number, };
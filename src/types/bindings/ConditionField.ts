@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Email fields that can be filtered
+ */
+export type ConditionField = "from" | "from_address" | "to" | "subject" | "body" | "has_attachment";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Action a confirmation token authorizes
+ */
+export type ConfirmScope = "account_delete" | "filter_import" | "sync_rollback" | "crypto_rekey" | "cleanup_execute" | "sender_block_apply" | "local_folder_delete" | "account_migrate" | "mailmerge_start" | "sync_rotate_master_password" | "template_import" | "diagnostics_export" | "profile_switch";
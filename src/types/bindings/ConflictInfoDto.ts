@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+export type ConflictInfoDto = { data_type: string, local_version: number, server_version: number, local_updated_at: string | null, server_updated_at: string | null, strategy: string, conflict_details: string, local_data: JsonValue, server_data: JsonValue, };
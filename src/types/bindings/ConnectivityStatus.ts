@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of the `connectivity://changed` event
+ */
+export type ConnectivityStatus = { online: boolean, metered: boolean, pausedAccounts: Array<number>, disabledAccounts: Array<number>, };
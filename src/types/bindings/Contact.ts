@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Contact = { id: number, accountId: number | null, email: string, name: string | null, avatarUrl: string | null, company: string | null, phone: string | null, notes: string | null, isFavorite: boolean, emailCount: number, lastEmailedAt: string | null, 
+/**
+ * Always notifies, listed in the VIP view
+ */
+isVip: boolean, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of one maintenance run
+ */
+export type DbMaintenanceReport = { ranAt: string, durationMs: number, dbBytesBefore: number, dbBytesAfter: number, walBytesBefore: number, walBytesAfter: number, 
+/**
+ * Free pages returned to the file system
+ */
+pagesFreed: number, 
+/**
+ * Switched to incremental auto-vacuum with a full VACUUM
+ */
+fullVacuum: boolean, 
+/**
+ * Readers kept the checkpoint from copying the whole WAL
+ */
+checkpointBusy: boolean, };
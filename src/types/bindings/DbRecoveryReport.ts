@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of the startup check
+ */
+export type DbRecoveryReport = { "status": "healthy" } | { "status": "recovered", backupPath: string, rowsRecovered: number, 
+/**
+ * Tables where rows were lost
+ */
+incompleteTables: Array<string>, } | { "status": "reset", backupPath: string, error: string, };
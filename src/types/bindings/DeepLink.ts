@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MailtoDraft } from "./MailtoDraft";
+
+/**
+ * Where a link leads
+ */
+export type DeepLink = { "type": "compose", draft: MailtoDraft, } | { "type": "email", emailId: number, } | { "type": "folder", accountId: number, folder: string, };
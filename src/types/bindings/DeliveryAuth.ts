@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Transport and signature checks of a received message
+ */
+export type DeliveryAuth = { 
+/**
+ * DKIM signature verified by the receiving server
+ */
+dkim: boolean | null, 
+/**
+ * Last hop delivered over TLS
+ */
+tls: boolean | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DeviceInfoDto = { device_id: string, device_name: string, platform: string, last_seen_at: string, created_at: string, };
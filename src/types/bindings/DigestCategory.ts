@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Categories that can be rolled up into the digest
+ */
+export type DigestCategory = "newsletter" | "promotions" | "social" | "notifications";
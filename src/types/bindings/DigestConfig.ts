@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DigestCategory } from "./DigestCategory";
+import type { DigestSchedule } from "./DigestSchedule";
+
+/**
+ * Digest configuration stored in settings table
+ */
+export type DigestConfig = { enabled: boolean, schedule: DigestSchedule, 
+/**
+ * Local hour the digest is generated (0-23)
+ */
+hour: number, 
+/**
+ * Day of the weekly digest (0 = Monday)
+ */
+weekday: number, categories: Array<DigestCategory>, 
+/**
+ * Last generation (RFC 3339)
+ */
+lastRun: string | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Digest generated for an account
+ */
+export type DigestResult = { accountId: number, 
+/**
+ * Id of the digest message (None when nothing arrived in the period)
+ */
+emailId: number | null, items: number, };
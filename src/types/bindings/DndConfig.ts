@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Do Not Disturb configuration stored in settings table
+ */
+export type DndConfig = { 
+/**
+ * DND active all day
+ */
+enabled: boolean, 
+/**
+ * Daily quiet hours, local time (0-23); the window may wrap past midnight
+ */
+quietHours: [number, number] | null, };
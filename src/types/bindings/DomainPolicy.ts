@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Stored policy
+ */
+export type DomainPolicy = { id: number, 
+/**
+ * Sender address or domain (covers subdomains)
+ */
+pattern: string, requireDkim: boolean, requireTls: boolean, blockAttachments: boolean, blockRemoteContent: boolean, warnLinks: boolean, createdAt: string, updatedAt: string, };
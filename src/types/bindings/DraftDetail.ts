@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DraftAttachmentData } from "./DraftAttachmentData";
+
+/**
+ * Draft detail (full data, for editing)
+ */
+export type DraftDetail = { id: number, accountId: number, toAddresses: string, ccAddresses: string, bccAddresses: string, subject: string, bodyText: string, bodyHtml: string, bodyMarkdown: string | null, replyToEmailId: number | null, forwardEmailId: number | null, composeType: string, createdAt: string, updatedAt: string, attachments: Array<DraftAttachmentData>, };
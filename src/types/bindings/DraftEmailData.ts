@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DraftEmailData = { id: number | null, accountId: number, toAddresses: string, ccAddresses: string, bccAddresses: string, subject: string, bodyText: string, bodyHtml: string, 
+/**
+ * Markdown source; body_html and body_text are rendered from it
+ */
+bodyMarkdown: string | null, replyToEmailId: number | null, forwardEmailId: number | null, composeType: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Draft list item (lightweight, for listing)
+ */
+export type DraftListItem = { id: number, accountId: number, subject: string, toAddresses: string, createdAt: string, updatedAt: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Position in a folder listing: the last message of the previous window
+ */
+export type EmailCursor = { date: string, uid: number, };
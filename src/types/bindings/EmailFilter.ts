@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FilterAction } from "./FilterAction";
+import type { FilterCondition } from "./FilterCondition";
+import type { MatchLogic } from "./MatchLogic";
+
+/**
+ * Email filter rule stored in database
+ */
+export type EmailFilter = { id: number, account_id: number, name: string, description: string | null, is_enabled: boolean, priority: number, match_logic: MatchLogic, conditions: Array<FilterCondition>, actions: Array<FilterAction>, matched_count: number, last_matched_at: string | null, created_at: string, updated_at: string, };
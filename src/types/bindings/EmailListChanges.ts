@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EmailSummary } from "./EmailSummary";
+
+/**
+ * A page of the list relative to an earlier snapshot
+ */
+export type EmailListChanges = { 
+/**
+ * Pass this as `since_snapshot` on the next refresh
+ */
+snapshotId: number, 
+/**
+ * True when `added` holds the whole page (no usable base snapshot)
+ */
+full: boolean, total: number, hasMore: boolean, 
+/**
+ * UIDs of the page in display order
+ */
+order: Array<number>, added: Array<EmailSummary>, 
+/**
+ * Summaries of messages whose flags, subject or other fields changed
+ */
+changed: Array<EmailSummary>, removed: Array<number>, 
+/**
+ * Pass this as `state_token` on the next refresh (see `email_list`)
+ */
+stateToken?: string, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EmailSummary } from "./EmailSummary";
+
+/**
+ * `email_list` page tagged with the folder state it was read in
+ * `not_modified` means the state still matches the token the caller sent:
+ * `emails` is empty and the caller keeps the page it has.
+ */
+export type EmailListPage = { stateToken?: string, notModified: boolean, emails: Array<EmailSummary>, total: number, hasMore: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type EmailNote = { accountId: number, messageId: string, 
+/**
+ * A local copy of the message, if any
+ */
+emailId: number | null, body: string, color: string | null, createdAt: string, updatedAt: string, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Email summary for list view
+ */
+export type EmailSummary = { uid: number, messageId: string | null, from: string, fromName: string | null, subject: string, preview: string, date: string, isRead: boolean, isStarred: boolean, hasAttachments: boolean, 
+/**
+ * Message size in bytes (RFC822.SIZE), 0 if unknown
+ */
+size: number, 
+/**
+ * Tag keywords (custom IMAP flags) on the message
+ */
+keywords?: Array<string>, accountId?: string, accountEmail?: string, accountName?: string, accountColor?: string, };
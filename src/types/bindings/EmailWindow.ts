@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EmailCursor } from "./EmailCursor";
+import type { LocalEmailSummary } from "./LocalEmailSummary";
+
+/**
+ * Window of a folder listing (see `get_emails_window`)
+ */
+export type EmailWindow = { emails: Array<LocalEmailSummary>, 
+/**
+ * Cursor of the next window, None at the end of the folder
+ */
+nextCursor: EmailCursor | null, hasMore: boolean, 
+/**
+ * Messages in the folder (first window only)
+ */
+total: number | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Feed subscription
+ */
+export type Feed = { id: number, accountId: number, 
+/**
+ * Local folder receiving the entries
+ */
+folderId: number, url: string, 
+/**
+ * Feed title (taken from the document on the first successful poll)
+ */
+title: string, intervalMinutes: number, enabled: boolean, lastPolledAt: string | null, lastError: string | null, };
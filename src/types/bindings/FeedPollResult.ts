@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of polling one feed
+ */
+export type FeedPollResult = { feedId: number, accountId: number, 
+/**
+ * Ids of the messages created for new entries
+ */
+newEmailIds: Array<number>, error: string | null, };
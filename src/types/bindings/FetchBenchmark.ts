@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FetchMethod } from "./FetchMethod";
+
+/**
+ * Attachment download totals of one fetch method since start
+ */
+export type FetchBenchmark = { method: FetchMethod, fetches: number, 
+/**
+ * Attachment bytes delivered (decoded)
+ */
+bytes: number, millis: number, bytesPerSecond: number | null, };
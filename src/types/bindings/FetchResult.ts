@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EmailSummary } from "./EmailSummary";
+
+/**
+ * Fetch result with pagination
+ */
+export type FetchResult = { emails: Array<EmailSummary>, total: number, hasMore: boolean, };
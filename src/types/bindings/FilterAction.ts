@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FilterActionType } from "./FilterActionType";
+import type { WebhookTarget } from "./WebhookTarget";
+
+/**
+ * Action to perform when filter matches
+ */
+export type FilterAction = { action: FilterActionType, folder_id?: number, label?: string, webhook?: WebhookTarget, };
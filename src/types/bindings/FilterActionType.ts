@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Types of actions that can be performed
+ */
+export type FilterActionType = "move_to_folder" | "add_label" | "mark_as_read" | "mark_as_starred" | "mark_as_spam" | "delete" | "archive" | "webhook";
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConditionField } from "./ConditionField";
+import type { ConditionOperator } from "./ConditionOperator";
+
+/**
+ * Filter condition to match against emails
+ */
+export type FilterCondition = { field: ConditionField, operator: ConditionOperator, value: string, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Tags a folder offers
+ */
+export type FolderKeywords = { 
+/**
+ * New keywords can be created (PERMANENTFLAGS has `\*` or is absent)
+ */
+allowsNew: boolean, 
+/**
+ * Keywords already defined in the folder
+ */
+keywords: Array<string>, 
+/**
+ * Their labels, in the same order
+ */
+labels: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Source folder and the target folder its messages are copied to
+ */
+export type FolderMapping = { source: string, target: string, };
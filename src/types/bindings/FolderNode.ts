@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FolderType } from "./FolderType";
+
+/**
+ * Folder with its nested children, as returned by `folder_list`
+ */
+export type FolderNode = { children: Array<FolderNode>, name: string, path: string, folder_type: FolderType, delimiter: string, is_subscribed: boolean, is_selectable: boolean, unread_count: number, total_count: number, 
+/**
+ * LIST attributes as sent by the server (e.g. `\Sent`, `\Noselect`)
+ */
+attributes: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Folder types
+ */
+export type FolderType = "Inbox" | "Sent" | "Drafts" | "Trash" | "Junk" | "Archive" | "Starred" | "Custom";
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Watched folders of an account
+ */
+export type FolderWatchConfig = { enabled: boolean, 
+/**
+ * Remote folder names
+ */
+folders: Array<string>, };
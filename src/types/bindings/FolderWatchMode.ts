@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How an account's folders are watched
+ */
+export type FolderWatchMode = "notify" | "rotating_idle" | "off";
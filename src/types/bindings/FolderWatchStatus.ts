@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FolderWatchMode } from "./FolderWatchMode";
+
+/**
+ * Watcher state of an account
+ */
+export type FolderWatchStatus = { accountId: number, mode: FolderWatchMode, folders: Array<string>, connected: boolean, lastChangeAt: string | null, 
+/**
+ * Why the last connection ended
+ */
+lastError: string | null, };
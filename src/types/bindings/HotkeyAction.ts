@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Action bound to a hotkey
+ */
+export type HotkeyAction = "compose" | "toggle_window";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Hotkey configuration stored in settings table
+ * Bindings use the `CommandOrControl+Shift+M` form; None leaves an action unbound.
+ */
+export type HotkeyConfig = { enabled: boolean, compose: string | null, toggleWindow: string | null, };
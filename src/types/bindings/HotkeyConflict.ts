@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HotkeyAction } from "./HotkeyAction";
+
+/**
+ * Binding that could not be registered
+ */
+export type HotkeyConflict = { action: HotkeyAction, shortcut: string, error: string, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HotkeyAction } from "./HotkeyAction";
+import type { HotkeyConflict } from "./HotkeyConflict";
+
+/**
+ * Outcome of registering the hotkeys
+ */
+export type HotkeyStatus = { registered: Array<HotkeyAction>, conflicts: Array<HotkeyConflict>, };
@@ -0,0 +1,52 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Extensions of an IMAP server that change how the client works
+ */
+export type ImapCapabilities = { 
+/**
+ * IDLE (RFC 2177): push notifications for a selected mailbox
+ */
+idle: boolean, 
+/**
+ * MOVE (RFC 6851): atomic UID MOVE
+ */
+move: boolean, 
+/**
+ * CONDSTORE (RFC 7162): mod-sequences for flag changes
+ */
+condstore: boolean, 
+/**
+ * UIDPLUS (RFC 4315): UID EXPUNGE and APPENDUID/COPYUID
+ */
+uidplus: boolean, 
+/**
+ * SPECIAL-USE (RFC 6154): mailbox roles in LIST
+ */
+specialUse: boolean, 
+/**
+ * NOTIFY (RFC 5465): change events for several mailboxes on one connection
+ */
+notify: boolean, 
+/**
+ * NAMESPACE (RFC 2342): personal, other users' and shared folder prefixes
+ */
+namespace: boolean, 
+/**
+ * Largest message APPEND accepts (APPENDLIMIT=n, RFC 7889); `None` if
+ * not announced or only announced per mailbox
+ */
+appendLimit: number | null, 
+/**
+ * BINARY (RFC 3516): FETCH BINARY[] returns parts already decoded
+ */
+binary: boolean, 
+/**
+ * LITERAL+ (RFC 7888): literals of any size may be sent without waiting
+ * for a continuation request
+ */
+literalPlus: boolean, 
+/**
+ * LITERAL- (RFC 7888): the same for literals up to 4096 bytes
+ */
+literalMinus: boolean, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InviteAttendee } from "./InviteAttendee";
+
+export type Invite = { id: number, accountId: number, uid: string, title: string, start: string, end: string, allDay: boolean, location: string | null, createdAt: string, attendees: Array<InviteAttendee>, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of `SyncManager::rotate_master_password`
+ */
+export type KeyRotationResult = { 
+/**
+ * Data types re-encrypted in this run
+ */
+rotated: Array<string>, 
+/**
+ * Data types with nothing stored on the server
+ */
+empty: Array<string>, 
+/**
+ * Whether an interrupted rotation was continued
+ */
+resumed: boolean, };
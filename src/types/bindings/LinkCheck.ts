@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Whether a link needs a warning before it is opened
+ */
+export type LinkCheck = { host: string | null, warn: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What stands out about a link's destination
+ */
+export type LinkHint = "insecure" | "ip_address" | "punycode" | "credentials" | "unusual_port" | "shortened" | "tracked" | "unresolved" | "not_web";
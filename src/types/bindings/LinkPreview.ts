@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LinkHint } from "./LinkHint";
+
+/**
+ * Destination of a link
+ */
+export type LinkPreview = { original: string, finalUrl: string, domain: string | null, 
+/**
+ * Addresses passed through, in order, excluding the original
+ */
+hops: Array<string>, hints: Array<LinkHint>, };
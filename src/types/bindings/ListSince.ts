@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Listing the list view shows, sent back by `email_list_changes`
+ */
+export type ListSince = { snapshotId: number, 
+/**
+ * `state_token` returned with the snapshot
+ */
+stateToken: string | null, };
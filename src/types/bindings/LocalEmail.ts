@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type LocalEmail = { id: number, account_id: number, folder_id: number, message_id: string, uid: number, from_address: string, from_name: string | null, to_addresses: string, cc_addresses: string, bcc_addresses: string, reply_to: string | null, subject: string, preview: string, body_text: string | null, body_html: string | null, date: string, is_read: boolean, is_starred: boolean, is_deleted: boolean, is_spam: boolean, is_draft: boolean, is_answered: boolean, is_forwarded: boolean, has_attachments: boolean, has_inline_images: boolean, thread_id: string | null, in_reply_to: string | null, references_header: string | null, priority: number, labels: string, };
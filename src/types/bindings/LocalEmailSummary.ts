@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type LocalEmailSummary = { id: number, messageId: string, uid: number, fromAddress: string, fromName: string | null, subject: string, preview: string, date: string, isRead: boolean, isStarred: boolean, hasAttachments: boolean, hasInlineImages: boolean, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type LocalFolder = { id: number, account_id: number, name: string, remote_name: string, folder_type: string, unread_count: number, total_count: number, is_subscribed: boolean, is_selectable: boolean, delimiter: string, 
+/**
+ * Local-only folder stored in SQLite, never synced to IMAP
+ */
+is_local: boolean, 
+/**
+ * Parent folder in the server hierarchy (None for top-level folders)
+ */
+parent_id: number | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MailMergeState } from "./MailMergeState";
+
+/**
+ * Counts of a merge (payload of `mailmerge://progress`)
+ */
+export type MailMergeProgress = { mergeId: number, accountId: number, state: MailMergeState, total: number, sent: number, failed: number, pending: number, 
+/**
+ * Why the merge was paused
+ */
+lastError: string | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Delivery of one recipient
+ */
+export type MailMergeRecipient = { email: string, 
+/**
+ * "pending", "sent" or "failed"
+ */
+status: string, error: string | null, sentAt: string | null, };
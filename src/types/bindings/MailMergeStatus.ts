@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MailMergeRecipient } from "./MailMergeRecipient";
+import type { MailMergeState } from "./MailMergeState";
+
+/**
+ * A merge with the status of every recipient
+ */
+export type MailMergeStatus = { perMinute: number, createdAt: string, recipients: Array<MailMergeRecipient>, mergeId: number, accountId: number, state: MailMergeState, total: number, sent: number, failed: number, pending: number, 
+/**
+ * Why the merge was paused
+ */
+lastError: string | null, };
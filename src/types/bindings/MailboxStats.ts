@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AgingBucket } from "./AgingBucket";
+import type { LargeMessage } from "./LargeMessage";
+import type { SenderVolume } from "./SenderVolume";
+import type { YearCount } from "./YearCount";
+
+export type MailboxStats = { totalMessages: number, totalSize: number, largestMessages: Array<LargeMessage>, topSenders: Array<SenderVolume>, 
+/**
+ * Oldest year first; messages with an unreadable date are left out
+ */
+perYear: Array<YearCount>, unreadAging: Array<AgingBucket>, };
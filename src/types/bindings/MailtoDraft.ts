@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Draft from a `mailto:` link
+ */
+export type MailtoDraft = { to: Array<string>, cc: Array<string>, bcc: Array<string>, subject: string | null, body: string | null, };
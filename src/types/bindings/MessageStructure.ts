@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MimePart } from "./MimePart";
+import type { ParseWarning } from "./ParseWarning";
+
+/**
+ * MIME tree of a message and the problems found while parsing it
+ */
+export type MessageStructure = { root: MimePart, warnings: Array<ParseWarning>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One content seen under a Message-ID
+ */
+export type MessageVersion = { id: number, messageId: string, contentHash: string, subject: string, bodyText: string | null, bodyHtml: string | null, seenAt: string, };
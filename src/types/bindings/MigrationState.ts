@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Progress of one folder pair (payload of `migration://progress`)
+ */
+export type MigrationState = { sourceAccountId: number, targetAccountId: number, sourceFolder: string, targetFolder: string, 
+/**
+ * Messages in the source folder
+ */
+total: number, 
+/**
+ * Appended to the target folder
+ */
+copied: number, 
+/**
+ * Already in the target folder (same Message-ID)
+ */
+skipped: number, 
+/**
+ * Refused by the target server (over its APPENDLIMIT)
+ */
+failed: number, 
+/**
+ * Highest source UID processed; the copy resumes after it
+ */
+lastUid: number, done: boolean, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One part of the MIME tree
+ */
+export type MimePart = { 
+/**
+ * IMAP section number ("" for the message itself, "1.2", ...)
+ */
+path: string, contentType: string, charset: string | null, 
+/**
+ * Content-Transfer-Encoding as declared
+ */
+encoding: string | null, 
+/**
+ * "inline" or "attachment"
+ */
+disposition: string | null, filename: string | null, contentId: string | null, 
+/**
+ * Bytes of the part body as transmitted
+ */
+size: number, parts: Array<MimePart>, };
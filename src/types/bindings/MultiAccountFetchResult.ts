@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccountFetchStatus } from "./AccountFetchStatus";
+import type { EmailSummary } from "./EmailSummary";
+
+/**
+ * Multi-account fetch result with per-account status
+ */
+export type MultiAccountFetchResult = { emails: Array<EmailSummary>, total: number, hasMore: boolean, accountResults: Array<AccountFetchStatus>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Policy as entered by the user
+ */
+export type NewDomainPolicy = { pattern: string, requireDkim: boolean, requireTls: boolean, blockAttachments: boolean, blockRemoteContent: boolean, warnLinks: boolean, };
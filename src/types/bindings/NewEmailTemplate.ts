@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NewEmailTemplate = { accountId: number | null, name: string, description: string | null, category: string, subjectTemplate: string, bodyHtmlTemplate: string, bodyTextTemplate: string | null, tags: Array<string>, isEnabled: boolean, isFavorite: boolean, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Meeting to invite the recipients to
+ */
+export type NewInvite = { title: string, 
+/**
+ * RFC 3339, or `YYYY-MM-DD` for all-day events
+ */
+start: string, 
+/**
+ * Exclusive end in the same form as `start`
+ */
+end: string, allDay: boolean, location: string | null, description: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * New-mail notification candidate
+ */
+export type NotificationCandidate = { from: string, subject: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How a new message is announced
+ */
+export type NotificationRoute = "silent" | "normal" | "priority";
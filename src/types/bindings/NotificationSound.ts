@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Sound to play
+ */
+export type NotificationSound = { "type": "builtIn", "value": string } | { "type": "file", "value": string } | { "type": "silent" };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationSoundOverride } from "./NotificationSoundOverride";
+
+/**
+ * Sound configuration stored in settings table
+ * Whether sounds play at all and the default sound are the
+ * `notification_sound` and `notification_sound_type` settings.
+ */
+export type NotificationSoundConfig = { 
+/**
+ * Volume in percent (0-100)
+ */
+volume: number, overrides: Array<NotificationSoundOverride>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationSound } from "./NotificationSound";
+
+/**
+ * Sound for an account, or one folder of it
+ */
+export type NotificationSoundOverride = { accountId: number, 
+/**
+ * Remote folder name; the whole account if None
+ */
+folder: string | null, sound: NotificationSound, };
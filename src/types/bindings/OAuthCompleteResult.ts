@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type OAuthCompleteResult = { email: string, display_name: string | null, access_token: string, refresh_token: string | null, imap_host: string, imap_port: number, smtp_host: string, smtp_port: number, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OnboardingStep } from "./OnboardingStep";
+import type { OnboardingTip } from "./OnboardingTip";
+
+/**
+ * Onboarding state for the frontend
+ */
+export type OnboardingState = { completed: Array<OnboardingStep>, skipped: Array<OnboardingStep>, 
+/**
+ * First step neither completed nor skipped; None once setup is finished
+ */
+currentStep: OnboardingStep | null, finishedAt: string | null, tips: Array<OnboardingTip>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Steps of the guided setup, in order
+ */
+export type OnboardingStep = "account_added" | "sync_configured" | "notifications_granted";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Hint about a feature the user has not tried yet
+ */
+export type OnboardingTip = "add_signature" | "create_filter" | "save_template";
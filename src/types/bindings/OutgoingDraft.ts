@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Draft as passed to `email_precheck_send`
+ */
+export type OutgoingDraft = { to: Array<string>, cc: Array<string>, bcc: Array<string>, subject: string, textBody: string | null, htmlBody: string | null, attachmentCount: number, 
+/**
+ * Folder and UID of the message being replied to or forwarded
+ */
+replyFolder: string | null, replyUid: number | null, 
+/**
+ * The draft was started with Reply All
+ */
+replyAll: boolean, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ParseWarningKind } from "./ParseWarningKind";
+
+/**
+ * Malformed input the parser worked around
+ */
+export type ParseWarning = { kind: ParseWarningKind, 
+/**
+ * Path of the part it concerns
+ */
+part: string, detail: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kind of malformed input
+ */
+export type ParseWarningKind = "missing_boundary" | "unclosed_multipart" | "invalid_header" | "unknown_charset" | "unknown_encoding" | "encoding_problem" | "too_deep";
@@ -0,0 +1,46 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BodyStructure } from "./BodyStructure";
+import type { BouncedRecipient } from "./BouncedRecipient";
+import type { CalendarPart } from "./CalendarPart";
+import type { DeliveryAuth } from "./DeliveryAuth";
+import type { EmailAttachment } from "./EmailAttachment";
+import type { HeaderField } from "./HeaderField";
+import type { MessageStructure } from "./MessageStructure";
+import type { PolicyVerdict } from "./PolicyVerdict";
+
+/**
+ * Parsed email
+ */
+export type ParsedEmail = { uid: number, messageId: string | null, from: string, fromName: string | null, to: Array<string>, cc: Array<string>, subject: string, date: string, bodyText: string | null, bodyHtml: string | null, isRead: boolean, isStarred: boolean, attachments: Array<EmailAttachment>, 
+/**
+ * DKIM and TLS checks recorded by the receiving server
+ */
+delivery: DeliveryAuth, 
+/**
+ * Quoted text and signature markers, for collapsing them
+ */
+quotes: BodyStructure, 
+/**
+ * MIME tree and parse warnings
+ */
+structure?: MessageStructure, 
+/**
+ * Domain policy of the sender, when one applies
+ */
+policy?: PolicyVerdict, 
+/**
+ * Size of the raw message in bytes
+ */
+size: number, 
+/**
+ * Header fields as sent, for showing the full headers
+ */
+headers: Array<HeaderField>, 
+/**
+ * iCalendar invite or reply carried by the message
+ */
+calendar?: CalendarPart, 
+/**
+ * Recipients a delivery status notification reports as undeliverable
+ */
+bounced?: Array<BouncedRecipient>, };
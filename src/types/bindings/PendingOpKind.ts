@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QueuedSend } from "./QueuedSend";
+
+/**
+ * Server-side command behind a pending operation
+ */
+export type PendingOpKind = { "type": "setRead", value: boolean, } | { "type": "setStarred", value: boolean, } | { "type": "setKeyword", keyword: string, value: boolean, } | { "type": "move", target: string, } | { "type": "delete", permanent: boolean, } | { "type": "send", message: QueuedSend, };
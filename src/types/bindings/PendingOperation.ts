@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PendingOpKind } from "./PendingOpKind";
+
+/**
+ * Mutation applied locally and waiting for the server
+ */
+export type PendingOperation = { id: string, accountId: string, folder: string, uid: number, kind: PendingOpKind, 
+/**
+ * Local rows touched by the optimistic change
+ */
+emailIds: Array<number>, 
+/**
+ * Flag value before the change (rollback of read/star)
+ */
+previousValue: boolean | null, attempts: number, lastError: string | null, createdAt: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type PlusAlias = { id: number, accountId: number, 
+/**
+ * Part after the '+', lowercase
+ */
+tag: string, label: string, address: string, createdAt: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PolicyViolation } from "./PolicyViolation";
+
+/**
+ * Policy applied to one message
+ */
+export type PolicyVerdict = { policyId: number, pattern: string, violations: Array<PolicyViolation>, blockAttachments: boolean, blockRemoteContent: boolean, warnLinks: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Requirement a message did not meet
+ */
+export type PolicyViolation = "dkim_not_verified" | "not_delivered_over_tls";
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Configurable limits of the pre-send checks
+ */
+export type PrecheckSettings = { 
+/**
+ * Reply-all to more recipients than this asks for confirmation (0 turns the check off)
+ */
+replyAllThreshold: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Read-ahead configuration
+ */
+export type PrefetchSettings = { enabled: boolean, 
+/**
+ * Unread messages fetched when a folder is opened
+ */
+maxMessages: number, 
+/**
+ * Total size of the bodies kept in memory
+ */
+budgetBytes: number, 
+/**
+ * IMAP connections used at once
+ */
+concurrency: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Profile = { 
+/**
+ * Directory name under `profiles/` (`default` for the data directory)
+ */
+id: string, name: string, createdAt: string | null, };
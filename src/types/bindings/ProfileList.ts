@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Profile } from "./Profile";
+
+/**
+ * Profiles and the one the running app uses
+ */
+export type ProfileList = { current: string, profiles: Array<Profile>, };
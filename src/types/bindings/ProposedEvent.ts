@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Event proposed from a message, for the user to confirm
+ */
+export type ProposedEvent = { title: string, 
+/**
+ * RFC 3339 in the sender's offset, or `YYYY-MM-DD` for all-day events
+ */
+start: string, 
+/**
+ * Exclusive end: RFC 3339, or the day after the last day for all-day events
+ */
+end: string, allDay: boolean, 
+/**
+ * Sentence the event was found in
+ */
+source: string, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type QueueStatsDto = { pending_count: number, in_progress_count: number, failed_count: number, completed_count: number, total_count: number, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttachmentPath } from "./AttachmentPath";
+
+/**
+ * Outgoing message waiting for delivery
+ */
+export type QueuedSend = { to: Array<string>, cc: Array<string>, bcc: Array<string>, subject: string, textBody: string | null, htmlBody: string | null, attachmentPaths: Array<AttachmentPath>, 
+/**
+ * Mailbox the account sends for (From), with the account as Sender
+ */
+onBehalfOf?: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttachmentPath } from "./AttachmentPath";
+
+/**
+ * Quoted HTML with inline images re-embedded for a reply/forward
+ */
+export type QuotedInlineHtml = { html: string, inlineAttachments: Array<AttachmentPath>, };
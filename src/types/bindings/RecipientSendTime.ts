@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Send-time analysis for one recipient
+ */
+export type RecipientSendTime = { address: string, 
+/**
+ * Messages received from this address that were analysed
+ */
+samples: number, replies: number, 
+/**
+ * Median time between our message and their reply
+ */
+medianReplyMinutes: number | null, 
+/**
+ * Most active hour of the week (local time), when there is enough data
+ */
+bestWeekday: number | null, bestHour: number | null, };
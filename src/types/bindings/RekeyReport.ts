@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a rotation re-encrypted
+ */
+export type RekeyReport = { 
+/**
+ * Account passwords and OAuth tokens
+ */
+accountSecrets: number, 
+/**
+ * AI provider API key and WebDAV upload password
+ */
+otherSecrets: number, 
+/**
+ * Wrapped keys of encrypted attachment files
+ */
+fileKeys: number, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SchedulerStatusDto = { enabled: boolean, running: boolean, interval_minutes: number, last_run: string | null, next_run: string | null, };
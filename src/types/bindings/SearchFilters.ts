@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DateRange } from "./DateRange";
+
+export type SearchFilters = { query?: string, dateRange?: DateRange, fromEmail?: string, fromDomain?: string, folderId?: number, hasAttachments?: boolean, isRead?: boolean, isStarred?: boolean, hasInlineImages?: boolean, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchIndex } from "./SearchIndex";
+
+/**
+ * State of one index
+ */
+export type SearchIndexStatus = { index: SearchIndex, 
+/**
+ * Content rows (of the account, when the check is scoped to one)
+ */
+rows: number, 
+/**
+ * Content rows with no index entry
+ */
+missing: number, 
+/**
+ * Index entries whose row no longer exists
+ */
+orphaned: number, 
+/**
+ * Indexed terms match the content (FTS5 integrity-check)
+ */
+termsMatch: boolean, 
+/**
+ * Rebuilt by this run
+ */
+rebuilt: boolean, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LocalEmailSummary } from "./LocalEmailSummary";
+
+export type SearchResult = { emails: Array<LocalEmailSummary>, totalCount: number, hasMore: boolean, searchTime: number, };
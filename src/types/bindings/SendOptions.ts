@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AttachmentPath } from "./AttachmentPath";
+import type { NewInvite } from "./NewInvite";
+
+/**
+ * Optional parts of a message sent with `email_send`
+ */
+export type SendOptions = { textBody: string | null, htmlBody: string | null, 
+/**
+ * Rendered to both bodies when set
+ */
+markdownBody: string | null, attachmentPaths: Array<AttachmentPath> | null, 
+/**
+ * Delegated mailbox to send as (see `delegation`)
+ */
+onBehalfOf: string | null, invite: NewInvite | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Sending limits of an account
+ */
+export type SendQuotaLimits = { perMinute: number, 
+/**
+ * None when the provider has no known daily limit
+ */
+perDay: number | null, };
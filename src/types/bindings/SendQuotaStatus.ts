@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SendQuotaLimits } from "./SendQuotaLimits";
+
+/**
+ * Sending quota of an account
+ */
+export type SendQuotaStatus = { accountId: number, provider: string, limits: SendQuotaLimits, 
+/**
+ * Whether the limits were set for the account
+ */
+custom: boolean, sentLastMinute: number, 
+/**
+ * Recipients in the last 24 hours
+ */
+sentLastDay: number, remainingToday: number | null, 
+/**
+ * When the oldest send of the day window drops out (while any were sent)
+ */
+nextReleaseAt: string | null, };
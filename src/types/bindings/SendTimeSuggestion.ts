@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RecipientSendTime } from "./RecipientSendTime";
+
+/**
+ * Result of `suggest_send_time`
+ */
+export type SendTimeSuggestion = { 
+/**
+ * Suggested send time (RFC 3339) for the scheduled send; None = no pattern, send now
+ */
+sendAt: string | null, 
+/**
+ * True when now is already the best time
+ */
+sendNow: boolean, recipients: Array<RecipientSendTime>, };
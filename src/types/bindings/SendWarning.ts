@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SendWarningKind } from "./SendWarningKind";
+
+/**
+ * Warning returned by `email_precheck_send`
+ */
+export type SendWarning = { kind: SendWarningKind, message: string, 
+/**
+ * Matched phrase, offending address or suggested correction
+ */
+detail?: string, 
+/**
+ * Number of recipients the warning is about
+ */
+count?: number, };
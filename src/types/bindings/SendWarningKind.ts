@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kind of pre-send warning
+ */
+export type SendWarningKind = "missing_attachment" | "empty_subject" | "no_recipients" | "invalid_recipient" | "domain_typo" | "bouncing_recipient" | "new_recipient_on_confidential_thread" | "large_reply_all" | "external_recipients";
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SenderAuthWarning } from "./SenderAuthWarning";
+
+/**
+ * Result of `account_check_sender_auth`
+ */
+export type SenderAuthReport = { domain: string, smtpHost: string, 
+/**
+ * False for mailbox providers, which are not checked
+ */
+customDomain: boolean, spfRecord: string | null, 
+/**
+ * SPF authorises the SMTP host
+ */
+spfCoversSmtpHost: boolean, 
+/**
+ * Selectors with a published DKIM key
+ */
+dkimSelectors: Array<string>, dmarcRecord: string | null, warnings: Array<SenderAuthWarning>, 
+/**
+ * Mail from this account will likely be filtered as spam
+ */
+likelySpam: boolean, };
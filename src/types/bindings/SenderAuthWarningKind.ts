@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Kind of sender authentication warning
+ */
+export type SenderAuthWarningKind = "no_spf" | "multiple_spf" | "spf_excludes_smtp_host" | "spf_permissive" | "spf_too_many_lookups" | "no_dkim" | "no_dmarc" | "dns_error";
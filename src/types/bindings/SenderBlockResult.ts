@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BlockedSender } from "./BlockedSender";
+
+export type SenderBlockResult = { blocked: BlockedSender, 
+/**
+ * Existing messages the block was applied to
+ */
+applied: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How a sender's mail was judged so far
+ */
+export type SenderReputation = { address: string, spamCount: number, hamCount: number, 
+/**
+ * -1 (only spam) to 1 (only wanted), closer to 0 with few messages
+ */
+score: number, };
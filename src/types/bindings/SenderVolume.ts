@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SenderVolume = { fromAddress: string, fromName: string | null, messages: number, totalSize: number, };
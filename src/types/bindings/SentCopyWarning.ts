@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Sent copy that was not uploaded because the server would refuse it
+ */
+export type SentCopyWarning = { accountId: number, folder: string, subject: string, size: number, limit: number, recordedAt: string, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SpamRule } from "./SpamRule";
+
+/**
+ * What a server-side filter decided, read from its headers
+ */
+export type ServerSpamVerdict = { 
+/**
+ * `SpamAssassin`, `Rspamd` or `Microsoft`
+ */
+filter: string, isSpam: boolean | null, score: number | null, 
+/**
+ * Score from which the filter treats mail as spam
+ */
+threshold: number | null, 
+/**
+ * Matched rules, highest score first
+ */
+rules: Array<SpamRule>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FolderNode } from "./FolderNode";
+import type { NamespaceKind } from "./NamespaceKind";
+
+/**
+ * Folders of one shared or other users' namespace, as returned by `folder_list_shared`
+ */
+export type SharedFolderTree = { kind: NamespaceKind, prefix: string, delimiter: string | null, folders: Array<FolderNode>, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Extensions of an SMTP server from its EHLO reply
+ */
+export type SmtpCapabilities = { 
+/**
+ * Largest message the server accepts (SIZE, RFC 1870); `None` if not
+ * announced or announced without a limit
+ */
+sizeLimit: number | null, 
+/**
+ * PIPELINING (RFC 2920)
+ */
+pipelining: boolean, 
+/**
+ * 8BITMIME (RFC 6152)
+ */
+eightBitMime: boolean, 
+/**
+ * SMTPUTF8 (RFC 6531)
+ */
+smtpUtf8: boolean, 
+/**
+ * AUTH mechanisms offered (after STARTTLS when the server uses it)
+ */
+authMechanisms: Array<string>, };
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SenderReputation } from "./SenderReputation";
+import type { ServerSpamVerdict } from "./ServerSpamVerdict";
+import type { TokenSignal } from "./TokenSignal";
+
+/**
+ * Why a message is (or is not) considered spam
+ */
+export type SpamReport = { 
+/**
+ * The message is in Junk or flagged as spam
+ */
+inJunk: boolean, 
+/**
+ * Local classifier probability; `None` until enough messages were trained
+ */
+probability: number | null, 
+/**
+ * Tokens that decided the local score, most decisive first
+ */
+tokens: Array<TokenSignal>, sender: SenderReputation | null, server: Array<ServerSpamVerdict>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Rule a server-side filter matched
+ */
+export type SpamRule = { name: string, score: number | null, description: string | null, };
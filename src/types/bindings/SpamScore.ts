@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SenderReputation } from "./SenderReputation";
+
+/**
+ * Spam score of a message
+ */
+export type SpamScore = { 
+/**
+ * Probability of spam; `None` until enough messages were trained
+ */
+probability: number | null, sender: SenderReputation | null, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of one training sweep
+ */
+export type SpamSweepReport = { ranAt: string, 
+/**
+ * Messages the user moved to Junk
+ */
+spamTrained: number, 
+/**
+ * Messages the user rescued from Junk
+ */
+hamTrained: number, 
+/**
+ * Queued verdicts dropped because the message never arrived
+ */
+expired: number, };
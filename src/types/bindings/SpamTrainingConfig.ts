@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * When the training sweep runs
+ */
+export type SpamTrainingConfig = { enabled: boolean, 
+/**
+ * Minutes between sweeps (5 to 1440)
+ */
+intervalMinutes: number, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Order in which accounts are brought up
+ */
+export type StartupPlan = { 
+/**
+ * Connected first, with its Inbox streamed
+ */
+defaultAccountId: number | null, 
+/**
+ * Connected afterwards
+ */
+others: Array<number>, };
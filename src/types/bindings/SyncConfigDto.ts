@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SyncConfigDto = { enabled: boolean, user_id: string | null, device_id: string, device_name: string, platform: string, last_sync_at: string | null, sync_accounts: boolean, sync_contacts: boolean, sync_preferences: boolean, sync_signatures: boolean, sync_templates: boolean, sync_filters: boolean, sync_notes: boolean, };
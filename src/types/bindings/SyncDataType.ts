@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Sync data categories
+ */
+export type SyncDataType = "accounts" | "contacts" | "preferences" | "signatures" | "templates" | "filters" | "notes";
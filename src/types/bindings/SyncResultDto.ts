@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConflictInfoDto } from "./ConflictInfoDto";
+import type { TypeSyncResult } from "./TypeSyncResult";
+
+export type SyncResultDto = { accounts_synced: boolean, contacts_synced: boolean, preferences_synced: boolean, signatures_synced: boolean, templates_synced: boolean, filters_synced: boolean, notes_synced: boolean, errors: Array<string>, types: Array<TypeSyncResult>, offline: boolean, conflicts: Array<ConflictInfoDto> | null, };
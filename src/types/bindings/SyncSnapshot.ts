@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SyncSnapshot = { id: number, dataType: string, version: number, snapshotHash: string, deviceId: string, operation: string, itemsCount: number, syncStatus: string, errorMessage: string | null, createdAt: string, };
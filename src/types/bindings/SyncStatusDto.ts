@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SyncStatusDto = { data_type: string, version: number, last_sync_at: string | null, status: string, };
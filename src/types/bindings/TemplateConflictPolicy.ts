@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What to do when an imported template has the same name as an existing one
+ */
+export type TemplateConflictPolicy = "skip" | "overwrite" | "keep_both";
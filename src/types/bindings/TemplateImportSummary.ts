@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of an import
+ */
+export type TemplateImportSummary = { imported: number, updated: number, renamed: number, skipped: number, 
+/**
+ * Identical templates that already existed
+ */
+unchanged: number, 
+/**
+ * Templates rejected by validation
+ */
+invalid: number, };
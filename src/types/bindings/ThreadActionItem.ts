@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Action item extracted from a thread
+ */
+export type ThreadActionItem = { task: string, 
+/**
+ * Person responsible, as named in the thread
+ */
+owner: string | null, 
+/**
+ * Due date as written or resolved (YYYY-MM-DD when possible)
+ */
+due: string | null, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ThreadActionItem } from "./ThreadActionItem";
+
+/**
+ * Result of `thread_summarize`
+ */
+export type ThreadSummary = { summary: string, decisions: Array<string>, actionItems: Array<ThreadActionItem>, 
+/**
+ * Messages the summary covers
+ */
+messageCount: number, 
+/**
+ * Served from the cache
+ */
+cached: boolean, };
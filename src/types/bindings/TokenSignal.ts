@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Contribution of one token to the local score
+ */
+export type TokenSignal = { 
+/**
+ * Word, `subject:` word or `from:` domain
+ */
+token: string, 
+/**
+ * Spam probability of messages containing the token
+ */
+probability: number, 
+/**
+ * Log-odds the token adds to the score; positive leans towards spam
+ */
+weight: number, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SyncDataType } from "./SyncDataType";
+import type { TypeSyncStatus } from "./TypeSyncStatus";
+
+export type TypeSyncResult = { data_type: SyncDataType, status: TypeSyncStatus, error: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of syncing one data type
+ */
+export type TypeSyncStatus = "synced" | "conflict" | "failed" | "deferred";
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Token returned by a mutating command
+ */
+export type UndoHandle = { token: string, 
+/**
+ * Seconds the token stays valid
+ */
+windowSecs: number, };
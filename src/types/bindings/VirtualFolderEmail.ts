@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Email in a virtual folder, with the account/folder it lives in
+ */
+export type VirtualFolderEmail = { accountId: number, accountEmail: string, folderId: number, folderName: string, folderRemoteName: string, id: number, messageId: string, uid: number, fromAddress: string, fromName: string | null, subject: string, preview: string, date: string, isRead: boolean, isStarred: boolean, hasAttachments: boolean, hasInlineImages: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Cross-account virtual folders built from the local cache
+ */
+export type VirtualFolderKind = "starred" | "unread" | "vip" | "needs_reply";
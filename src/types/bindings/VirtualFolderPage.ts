@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VirtualFolderEmail } from "./VirtualFolderEmail";
+import type { VirtualFolderKind } from "./VirtualFolderKind";
+
+export type VirtualFolderPage = { kind: VirtualFolderKind, emails: Array<VirtualFolderEmail>, totalCount: number, hasMore: boolean, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Webhook destination of a filter action
+ */
+export type WebhookTarget = { url: string, 
+/**
+ * Include the message body (text and HTML) in the payload
+ */
+includeBody: boolean, };
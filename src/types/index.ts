@@ -2,6 +2,27 @@
 // Owlivion Mail - TypeScript Type Definitions
 // ============================================================================
 
+// Types generated from the Rust command DTOs (`npm run bindings`)
+import type { AccountFetchStatus } from './bindings/AccountFetchStatus';
+import type { EmailSummary as ListedEmail } from './bindings/EmailSummary';
+import type { MailtoDraft } from './bindings/MailtoDraft';
+import type { BodyStructure } from './bindings/BodyStructure';
+import type { BouncedRecipient } from './bindings/BouncedRecipient';
+import type { CalendarPart } from './bindings/CalendarPart';
+import type { ConditionField } from './bindings/ConditionField';
+import type { ConditionOperator } from './bindings/ConditionOperator';
+import type { DeliveryAuth } from './bindings/DeliveryAuth';
+import type { FilterAction } from './bindings/FilterAction';
+import type { FilterCondition } from './bindings/FilterCondition';
+import type { HeaderField } from './bindings/HeaderField';
+import type { MessageStructure } from './bindings/MessageStructure';
+import type { NewInvite } from './bindings/NewInvite';
+import type { PolicyVerdict } from './bindings/PolicyVerdict';
+import type { SecurityType } from './bindings/SecurityType';
+import type { TypeSyncStatus } from './bindings/TypeSyncStatus';
+import type { DateRange as DateRangeFields } from './bindings/DateRange';
+import type { SearchFilters as SearchFilterFields } from './bindings/SearchFilters';
+
 // Email address with optional display name
 export interface EmailAddress {
   email: string;
@@ -39,8 +60,62 @@ export interface Email {
   inReplyTo?: string;
   priority: number;
   labels: string[];
+  delivery?: DeliveryAuth;
+  quotes?: BodyStructure;
+  structure?: MessageStructure;
+  policy?: PolicyVerdict;
+  /** Raw message size in bytes */
+  size?: number;
+  /** Header fields as sent, for "Show headers" */
+  headers?: HeaderField[];
+  // iCalendar invite or reply carried by the message
+  calendar?: CalendarPart;
+  // Recipients a delivery failure report lists as undeliverable
+  bounced?: BouncedRecipient[];
 }
 
+export type { BouncedRecipient } from './bindings/BouncedRecipient';
+
+export type { HeaderField } from './bindings/HeaderField';
+
+// Role of a run of body lines, for collapsing quotes and signatures
+export type { BodySectionKind } from './bindings/BodySectionKind';
+
+// Lines start..end (0-based, end exclusive) of the plain-text body
+export type { BodySection } from './bindings/BodySection';
+
+export type { BodyStructure } from './bindings/BodyStructure';
+
+// Part of the MIME tree; path is the IMAP section number ("" for the message)
+export type { MimePart } from './bindings/MimePart';
+
+export type { ParseWarningKind } from './bindings/ParseWarningKind';
+
+// Malformed input the parser worked around
+export type { ParseWarning } from './bindings/ParseWarning';
+
+export type { MessageStructure } from './bindings/MessageStructure';
+
+// DKIM and TLS checks recorded by the receiving server (null = not recorded)
+export type { DeliveryAuth } from './bindings/DeliveryAuth';
+
+// Per-sender security rules; pattern is an address or a domain (with subdomains)
+export type { DomainPolicy } from './bindings/DomainPolicy';
+
+export type { NewDomainPolicy } from './bindings/NewDomainPolicy';
+
+export type { PolicyViolation } from './bindings/PolicyViolation';
+
+// Domain policy applied to an opened message
+export type { PolicyVerdict } from './bindings/PolicyVerdict';
+
+export type { LinkHint } from './bindings/LinkHint';
+
+// Final destination of a link (wrappers decoded, shorteners resolved)
+export type { LinkPreview } from './bindings/LinkPreview';
+
+export type { LinkCheck } from './bindings/LinkCheck';
+
 // Email summary for list view
 export interface EmailSummary {
   id: number;
@@ -55,12 +130,162 @@ export interface EmailSummary {
   isStarred: boolean;
   hasAttachments: boolean;
   hasInlineImages: boolean;
+  size?: number; // Message size in bytes (RFC822.SIZE)
+  keywords?: string[]; // IMAP keywords (shared tags) set on the server
   accountId?: string; // Account ID for unified inbox
   accountEmail?: string; // Account email for display
   accountName?: string; // Account name/label
   accountColor?: string; // Account color badge (hex)
 }
 
+// Window of a folder listing (email_list_window)
+export type { EmailWindow } from './bindings/EmailWindow';
+
+// Page of email_list; notModified means the page passed as stateToken is still current
+export type { EmailListPage } from './bindings/EmailListPage';
+
+// Page of email_list_changes relative to the snapshot the list shows
+export type { EmailListChanges } from './bindings/EmailListChanges';
+
+// State of a full-text search index (search_index_check / search_index_rebuild)
+export type { SearchIndexStatus } from './bindings/SearchIndexStatus';
+
+// Outcome of the startup database integrity check (db_recovery_report)
+export type { DbRecoveryReport } from './bindings/DbRecoveryReport';
+
+// Result of a database maintenance run (db_maintenance_run)
+export type { DbMaintenanceReport } from './bindings/DbMaintenanceReport';
+
+// Schedule of the spam training sweep (intervalMinutes: 5-1440)
+export type { SpamTrainingConfig } from './bindings/SpamTrainingConfig';
+
+// Result of a spam training sweep (spam_training_run)
+export type { SpamSweepReport } from './bindings/SpamSweepReport';
+
+// How a sender's mail was judged; score from -1 (spam) to 1 (wanted)
+export type { SenderReputation } from './bindings/SenderReputation';
+
+// probability is null until enough messages were trained
+export type { SpamScore } from './bindings/SpamScore';
+
+// Contribution of one token to the local spam score
+export type { TokenSignal } from './bindings/TokenSignal';
+
+// Rule a server-side filter matched
+export type { SpamRule } from './bindings/SpamRule';
+
+// Verdict a server-side filter left in the headers
+export type { ServerSpamVerdict } from './bindings/ServerSpamVerdict';
+
+// Why a message is (or is not) considered spam
+export type { SpamReport } from './bindings/SpamReport';
+
+// What happens to mail from a blocked sender ('spam' moves it to Junk)
+export type { BlockAction } from './bindings/BlockAction';
+
+// Blocklist entry; pattern is a lowercase address or a domain
+export type { BlockedSender } from './bindings/BlockedSender';
+
+// applied = existing messages the block was applied to
+export type { SenderBlockResult } from './bindings/SenderBlockResult';
+
+// Sending limits in recipients; perDay null when the provider has none known
+export type { SendQuotaLimits } from './bindings/SendQuotaLimits';
+
+// Sending quota of an account (send_quota_status)
+export type { SendQuotaStatus } from './bindings/SendQuotaStatus';
+
+// Folders watched for changes over NOTIFY or rotating IDLE
+export type { FolderWatchConfig } from './bindings/FolderWatchConfig';
+
+export type { FolderWatchMode } from './bindings/FolderWatchMode';
+
+// Watcher state of an account (folder_watch_status)
+export type { FolderWatchStatus } from './bindings/FolderWatchStatus';
+
+// Payload of the folder_watch://changed event
+export interface FolderChangedEvent {
+  accountId: number;
+  folder: string;
+}
+
+// Account with its own refresh interval (refresh_intervals_get)
+export type { AccountRefresh } from './bindings/AccountRefresh';
+
+// Payload of the refresh://due event (an account's refresh timer fired)
+export interface RefreshDue {
+  accountId: number;
+}
+
+// Order in which accounts connect at startup (startup_sync)
+export type { StartupPlan } from './bindings/StartupPlan';
+
+export type StartupStage = 'connecting' | 'connected' | 'failed' | 'inbox_loaded' | 'done';
+
+// Payload of the startup://progress event
+export interface StartupProgress {
+  stage: StartupStage;
+  accountId: number | null;
+  error: string | null;
+  connected: number; // accounts connected so far
+  total: number;
+}
+
+// Payload of the startup://inbox event: one page of the default account's Inbox
+export interface StartupInbox {
+  accountId: number;
+  page: number;
+  emails: ListedEmail[];
+  total: number;
+  done: boolean; // last page streamed at startup
+}
+
+// Sign-in failure state of an account (account_get_auth_state)
+export type { AccountAuthState } from './bindings/AccountAuthState';
+
+// Extensions announced by an account's IMAP server
+export type { ImapCapabilities } from './bindings/ImapCapabilities';
+
+// Extensions announced by an account's SMTP server (EHLO)
+export type { SmtpCapabilities } from './bindings/SmtpCapabilities';
+
+// Cached server extensions of an account (account_get_capabilities)
+export type { AccountCapabilities } from './bindings/AccountCapabilities';
+
+// Destructive action recorded in the local activity log
+export type { ActivityAction } from './bindings/ActivityAction';
+
+export type { ActivityEntry } from './bindings/ActivityEntry';
+
+// Returned by mailbox actions; pass the token to undoAction within windowSecs
+export type { UndoHandle } from './bindings/UndoHandle';
+
+// One content seen under a Message-ID (re-delivered messages can change)
+export type { MessageVersion } from './bindings/MessageVersion';
+
+// Read-ahead of unread message bodies when a folder is opened
+export type { PrefetchSettings } from './bindings/PrefetchSettings';
+
+// Download of cached message bodies after a folder backfill
+export type { BodyBackfillSettings } from './bindings/BodyBackfillSettings';
+
+// Body backfill progress of one folder ("backfill://bodies" event)
+export interface BodyBackfillProgress {
+  accountId: number;
+  folder: string;
+  total: number;
+  fetched: number;
+  failed: number;
+  connections: number;
+  done: boolean;
+}
+
+// Sent copy not uploaded because it exceeded the server's APPENDLIMIT
+export type { SentCopyWarning } from './bindings/SentCopyWarning';
+
+// Secrets re-encrypted by a key rotation (crypto_rekey)
+export type { RekeyReport } from './bindings/RekeyReport';
+
 // Draft email for composing
 export interface DraftEmail {
   id?: number;
@@ -71,44 +296,52 @@ export interface DraftEmail {
   subject: string;
   bodyText: string;
   bodyHtml: string;
+  // Markdown source; when set the backend renders the HTML and text bodies
+  bodyMarkdown?: string;
+  // Delegated mailbox to send on behalf of (From), with the account as Sender
+  onBehalfOf?: string;
+  // Meeting invite sent along; To and Cc are the attendees
+  invite?: NewInvite;
   attachments: Attachment[];
   replyToEmailId?: number;
   forwardEmailId?: number;
   composeType: 'new' | 'reply' | 'replyAll' | 'forward';
 }
 
-// Draft list item (lightweight)
-export interface DraftListItem {
-  id: number;
+// Pre-send warning (email_precheck_send)
+export type { SendWarningKind } from './bindings/SendWarningKind';
+
+export type { SendWarning } from './bindings/SendWarning';
+
+// Limits of the pre-send checks
+export type { PrecheckSettings } from './bindings/PrecheckSettings';
+
+// Sender domain authentication check (account_check_sender_auth)
+export type { SenderAuthWarningKind } from './bindings/SenderAuthWarningKind';
+
+export type { SenderAuthReport } from './bindings/SenderAuthReport';
+
+// Due reply reminder (payload item of `reply://reminder`)
+export interface ReplyReminder {
+  emailId: number;
   accountId: number;
+  from: string;
   subject: string;
-  toAddresses: string; // JSON string
-  createdAt: string;
-  updatedAt: string;
 }
 
+// Send time suggestion (suggest_send_time)
+export type { RecipientSendTime } from './bindings/RecipientSendTime';
+
+export type { SendTimeSuggestion } from './bindings/SendTimeSuggestion';
+
+// Attachment download totals per fetch method (metrics_fetch_benchmarks)
+export type { FetchBenchmark } from './bindings/FetchBenchmark';
+
+// Draft list item (lightweight)
+export type { DraftListItem } from './bindings/DraftListItem';
+
 // Draft detail (full data)
-export interface DraftDetail {
-  id: number;
-  accountId: number;
-  toAddresses: string;
-  ccAddresses: string;
-  bccAddresses: string;
-  subject: string;
-  bodyText: string;
-  bodyHtml: string;
-  replyToEmailId?: number;
-  forwardEmailId?: number;
-  composeType: string;
-  createdAt: string;
-  updatedAt: string;
-  attachments: {
-    filename: string;
-    contentType: string;
-    size: number;
-    localPath: string;
-  }[];
-}
+export type { DraftDetail } from './bindings/DraftDetail';
 
 // Attachment
 export interface Attachment {
@@ -163,32 +396,18 @@ export interface NewAccount {
 }
 
 // Security type for connections
-export type SecurityType = 'SSL' | 'STARTTLS' | 'NONE';
+export type { SecurityType } from './bindings/SecurityType';
 
 // Auto-detected email configuration (flat structure from Rust backend)
-export interface AutoConfig {
-  provider?: string;
-  displayName?: string;
-  imapHost: string;
-  imapPort: number;
-  imapSecurity: SecurityType;
-  smtpHost: string;
-  smtpPort: number;
-  smtpSecurity: SecurityType;
-  detectionMethod?: string;
-}
+export type { AutoConfig } from './bindings/AutoConfig';
 
 // Folder from IMAP server
-export interface ImapFolder {
-  name: string;
-  path: string;
-  folder_type: FolderType;
-  delimiter: string;
-  is_subscribed: boolean;
-  is_selectable: boolean;
-  unread_count: number;
-  total_count: number;
-}
+export type { ImapFolder } from './bindings/ImapFolder';
+
+// Folders of a shared or other users' IMAP namespace (folder_list_shared)
+export type { NamespaceKind } from './bindings/NamespaceKind';
+
+export type { SharedFolderTree } from './bindings/SharedFolderTree';
 
 // Folder (legacy/local)
 export interface Folder {
@@ -203,6 +422,9 @@ export interface Folder {
   isSelectable: boolean;
 }
 
+// Local-only folder (stored in the database, never synced to IMAP)
+export type { LocalFolder } from './bindings/LocalFolder';
+
 export type FolderType = 'Inbox' | 'Sent' | 'Drafts' | 'Trash' | 'Spam' | 'Archive' | 'Starred' | 'Custom' | 'All' | 'Junk';
 
 // Trusted sender
@@ -214,19 +436,7 @@ export interface TrustedSender {
 }
 
 // Contact
-export interface Contact {
-  id: number;
-  accountId?: number;
-  email: string;
-  name?: string;
-  avatarUrl?: string;
-  company?: string;
-  phone?: string;
-  notes?: string;
-  isFavorite: boolean;
-  emailCount: number;
-  lastEmailedAt?: string;
-}
+export type { Contact } from './bindings/Contact';
 
 // App settings
 export interface Settings {
@@ -251,6 +461,7 @@ export interface Settings {
   signaturePosition: 'top' | 'bottom';
   replyPosition: 'top' | 'bottom';
   closeToTray: boolean;
+  startMinimized: boolean;
 
   // AI
   geminiApiKey?: string;
@@ -288,6 +499,7 @@ export const DEFAULT_SETTINGS: Settings = {
   signaturePosition: 'bottom',
   replyPosition: 'top',
   closeToTray: true,
+  startMinimized: false,
   geminiApiKey: undefined,
   aiAutoSummarize: false,
   aiReplyTone: 'professional',
@@ -299,6 +511,25 @@ export const DEFAULT_SETTINGS: Settings = {
   unifiedInboxSortBy: 'priority',
 };
 
+// A separate set of accounts and data (own database and caches)
+export type { Profile } from './bindings/Profile';
+
+// Profiles, and the one the app is running in
+export type { ProfileList } from './bindings/ProfileList';
+
+// First-run setup steps, in order
+export type { OnboardingStep } from './bindings/OnboardingStep';
+
+export type { OnboardingTip } from './bindings/OnboardingTip';
+
+// Guided setup progress (onboarding_get_state); tips appear once it is finished
+export type { OnboardingState } from './bindings/OnboardingState';
+
+// Payload of the settings://changed event
+export interface SettingsChangedEvent {
+  changes: Partial<Settings>;
+}
+
 // Sync status
 export type SyncStatus = 'idle' | 'syncing' | 'error';
 
@@ -339,6 +570,23 @@ export interface AIReplyResponse {
   actionItems?: string[];
 }
 
+// Smart compose completion provider
+export type { AIProviderKind } from './bindings/AIProviderKind';
+
+export type { AIProviderInfo } from './bindings/AIProviderInfo';
+
+// Streamed smart compose chunk (payload of `ai://completion`)
+export interface AICompletionChunk {
+  requestId: string;
+  text: string;
+  done: boolean;
+}
+
+// AI thread summary (thread_summarize)
+export type { ThreadActionItem } from './bindings/ThreadActionItem';
+
+export type { ThreadSummary } from './bindings/ThreadSummary';
+
 // Keyboard shortcut definition
 export interface ShortcutDefinition {
   key: string;
@@ -390,11 +638,14 @@ export interface SyncConfig {
   syncContacts: boolean;
   syncPreferences: boolean;
   syncSignatures: boolean;
+  syncTemplates: boolean;
+  syncFilters: boolean;
+  syncNotes: boolean;
 }
 
 /// Sync status for a data type
 export interface SyncStatusItem {
-  dataType: 'accounts' | 'contacts' | 'preferences' | 'signatures';
+  dataType: 'accounts' | 'contacts' | 'preferences' | 'signatures' | 'templates' | 'filters' | 'notes';
   version: number;
   lastSyncAt?: string; // ISO 8601
   status: 'idle' | 'syncing' | 'error';
@@ -414,10 +665,24 @@ export interface SyncResult {
   contactsSynced: boolean;
   preferencesSynced: boolean;
   signaturesSynced: boolean;
+  templatesSynced: boolean;
+  filtersSynced: boolean;
+  notesSynced: boolean;
   errors: string[];
+  types: TypeSyncResult[]; // Per data type outcome
+  offline: boolean;
   conflicts?: ConflictInfo[]; // NEW: Detected conflicts
 }
 
+/// Outcome of one data type; "deferred" types are retried later
+export type { TypeSyncStatus } from './bindings/TypeSyncStatus';
+
+export interface TypeSyncResult {
+  dataType: string;
+  status: TypeSyncStatus;
+  error?: string;
+}
+
 /// Conflict information for user resolution
 export interface ConflictInfo {
   dataType: string;
@@ -449,18 +714,7 @@ export interface SchedulerStatus {
 }
 
 /// Sync snapshot (history entry)
-export interface SyncSnapshot {
-  id: number;
-  dataType: string;
-  version: number;
-  snapshotHash: string;
-  deviceId: string;
-  operation: 'push' | 'pull' | 'merge';
-  itemsCount: number;
-  syncStatus: 'success' | 'failed' | 'conflict';
-  errorMessage?: string;
-  createdAt: string; // ISO 8601
-}
+export type { SyncSnapshot } from './bindings/SyncSnapshot';
 
 /// Conflict information
 export interface ConflictInfo {
@@ -499,74 +753,28 @@ export interface QueueStats {
 // ============================================================================
 
 /// Email filter rule
-export interface EmailFilter {
-  id: number;
-  accountId: number;
-  name: string;
-  description?: string;
-  isEnabled: boolean;
-  priority: number;
-  matchLogic: 'all' | 'any';
-  conditions: FilterCondition[];
-  actions: FilterAction[];
-  matchedCount: number;
-  lastMatchedAt?: string;
-  createdAt: string;
-  updatedAt: string;
-}
+export type { EmailFilter } from './bindings/EmailFilter';
 
 /// New filter for creation
-export interface NewEmailFilter {
-  accountId: number;
-  name: string;
-  description?: string;
-  isEnabled: boolean;
-  priority: number;
-  matchLogic: 'all' | 'any';
-  conditions: FilterCondition[];
-  actions: FilterAction[];
-}
+export type { NewEmailFilter } from './bindings/NewEmailFilter';
 
 /// Filter condition
-export interface FilterCondition {
-  field: ConditionField;
-  operator: ConditionOperator;
-  value: string;
-}
+export type { FilterCondition } from './bindings/FilterCondition';
 
 /// Email fields that can be filtered
-export type ConditionField =
-  | 'from'
-  | 'to'
-  | 'subject'
-  | 'body'
-  | 'has_attachment';
+export type { ConditionField } from './bindings/ConditionField';
 
 /// Comparison operators
-export type ConditionOperator =
-  | 'contains'
-  | 'not_contains'
-  | 'equals'
-  | 'not_equals'
-  | 'starts_with'
-  | 'ends_with';
+export type { ConditionOperator } from './bindings/ConditionOperator';
 
 /// Filter action
-export interface FilterAction {
-  action: FilterActionType;
-  folderId?: number;
-  label?: string;
-}
+export type { FilterAction } from './bindings/FilterAction';
+
+/// Webhook destination of a filter action
+export type { WebhookTarget } from './bindings/WebhookTarget';
 
 /// Types of filter actions
-export type FilterActionType =
-  | 'move_to_folder'
-  | 'add_label'
-  | 'mark_as_read'
-  | 'mark_as_starred'
-  | 'mark_as_spam'
-  | 'delete'
-  | 'archive';
+export type { FilterActionType } from './bindings/FilterActionType';
 
 /// Helper to create filter conditions
 export const createCondition = (
@@ -612,6 +820,10 @@ export const createAction = {
   archive: (): FilterAction => ({
     action: 'archive',
   }),
+  webhook: (url: string, includeBody = false): FilterAction => ({
+    action: 'webhook',
+    webhook: { url, includeBody },
+  }),
 };
 
 // ============================================================================
@@ -621,48 +833,14 @@ export const createAction = {
 // Date range preset types
 export type DateRangePreset = 'last_7_days' | 'last_30_days' | 'last_3_months' | 'last_year' | 'custom';
 
-// Date range filter
-export interface DateRange {
+// Date range filter; dates are ISO 8601 (YYYY-MM-DD), the preset only drives the picker
+export interface DateRange extends DateRangeFields {
   preset?: DateRangePreset;
-  startDate?: string; // ISO 8601 format (YYYY-MM-DD)
-  endDate?: string; // ISO 8601 format (YYYY-MM-DD)
 }
 
 // Advanced search filters
-export interface SearchFilters {
-  // Text search (FTS5)
-  query?: string;
-
-  // Date range
+export interface SearchFilters extends Omit<SearchFilterFields, 'dateRange'> {
   dateRange?: DateRange;
-
-  // Sender filter
-  fromEmail?: string;
-  fromDomain?: string;
-
-  // Folder filter
-  folderId?: number;
-  folderType?: FolderType;
-
-  // Attachment filter
-  hasAttachments?: boolean;
-  attachmentType?: string; // e.g., 'pdf', 'image', 'document'
-
-  // Read/unread filter
-  isRead?: boolean;
-
-  // Starred filter
-  isStarred?: boolean;
-
-  // Size filter (bytes)
-  minSize?: number;
-  maxSize?: number;
-
-  // Has inline images
-  hasInlineImages?: boolean;
-
-  // Labels
-  labels?: string[];
 }
 
 // Saved search
@@ -686,35 +864,20 @@ export interface SearchHistory {
 }
 
 // Search result with metadata
-export interface SearchResult {
-  emails: EmailSummary[];
-  totalCount: number;
-  hasMore: boolean;
-  searchTime: number; // milliseconds
-}
+export type { SearchResult } from './bindings/SearchResult';
 
 // ============================================================================
 // Multi-Account Inbox Types
 // ============================================================================
 
 // Multi-account fetch result with per-account status
-export interface MultiAccountFetchResult {
-  emails: EmailSummary[];
-  total: number;
-  hasMore: boolean;
-  accountResults: AccountFetchStatus[];
-}
+export type { MultiAccountFetchResult } from './bindings/MultiAccountFetchResult';
 
-// Per-account fetch status
-export interface AccountFetchStatus {
-  accountId: string;
-  accountEmail: string;
-  accountName?: string;
-  emailCount: number;
-  success: boolean;
-  error?: string;
-  fetchTimeMs: number;
-}
+// Per-account fetch status (generated from the Rust type)
+export type { AccountFetchStatus } from './bindings/AccountFetchStatus';
+
+// Email of a list page as the server listed it (email_list)
+export type { ListedEmail };
 
 // ============================================================================
 // EMAIL TEMPLATES
@@ -760,6 +923,21 @@ export interface NewEmailTemplate {
   isFavorite: boolean;
 }
 
+/// How template_import treats a template whose name already exists
+export type { TemplateConflictPolicy } from './bindings/TemplateConflictPolicy';
+
+export type { TemplateImportSummary } from './bindings/TemplateImportSummary';
+
+/// State of a mail merge; 'paused' after a connection or login failure
+export type { MailMergeState } from './bindings/MailMergeState';
+
+/// Payload of the `mailmerge://progress` event
+export type { MailMergeProgress } from './bindings/MailMergeProgress';
+
+export type { MailMergeRecipient } from './bindings/MailMergeRecipient';
+
+export type { MailMergeStatus } from './bindings/MailMergeStatus';
+
 export interface TemplateContext {
   sender_name?: string;
   sender_email?: string;
@@ -784,6 +962,143 @@ export interface TemplateVariable {
   category: 'sender' | 'recipient' | 'datetime' | 'custom';
 }
 
+// ============================================================================
+// RSS/ATOM FEEDS
+// ============================================================================
+
+export type { Feed } from './bindings/Feed';
+
+/** Result of polling a feed (also the payload of `feeds://updated`) */
+export type { FeedPollResult } from './bindings/FeedPollResult';
+
+// ============================================================================
+// NEWSLETTER DIGEST
+// ============================================================================
+
+export type { DigestCategory } from './bindings/DigestCategory';
+
+export type { DigestSchedule } from './bindings/DigestSchedule';
+
+export type { DigestConfig } from './bindings/DigestConfig';
+
+/** Digest generated for an account (also the payload of `digest://created`) */
+export type { DigestResult } from './bindings/DigestResult';
+
+// ============================================================================
+// NOTIFICATION ROUTING
+// ============================================================================
+
+/** Do Not Disturb: always on, or daily quiet hours (local, may wrap past midnight) */
+export type { DndConfig } from './bindings/DndConfig';
+
+/** `priority` = VIP sender (shown even in DND), `silent` = DND or digest */
+export type { NotificationRoute } from './bindings/NotificationRoute';
+
+/** Built-in sound, audio file (WAV, MP3, Ogg, FLAC) or no sound */
+export type { NotificationSound } from './bindings/NotificationSound';
+
+/** Sound for an account, or one folder of it (`folder` unset = whole account) */
+export type { NotificationSoundOverride } from './bindings/NotificationSoundOverride';
+
+/** Volume (0-100) and per-account/per-folder sounds; the default sound is `notificationSoundType` */
+export type { NotificationSoundConfig } from './bindings/NotificationSoundConfig';
+
+/** Global shortcuts in `CommandOrControl+Shift+M` form; null leaves the action unbound */
+export type { HotkeyConfig } from './bindings/HotkeyConfig';
+
+export type { HotkeyAction } from './bindings/HotkeyAction';
+
+/** Registered hotkeys, and those another application already holds */
+export type { HotkeyStatus } from './bindings/HotkeyStatus';
+
+/** Destructive action that needs a confirmation token (confirm_request) */
+export type { ConfirmScope } from './bindings/ConfirmScope';
+
+/** Prefilled draft from a `mailto:` link (generated from the Rust type) */
+export type { MailtoDraft } from './bindings/MailtoDraft';
+
+/** `mailto:`, `owlivion://email/<id>` or `owlivion://folder/<accountId>/<folder>` link */
+export type { DeepLink } from './bindings/DeepLink';
+
+/** Start on login; `minimized` keeps login launches in the tray */
+export type { AutostartStatus } from './bindings/AutostartStatus';
+
+export type NoteColor = 'red' | 'orange' | 'yellow' | 'green' | 'blue' | 'purple';
+
+/** Private note on a message, kept locally (synced only if enabled) */
+export type { EmailNote } from './bindings/EmailNote';
+
+// Shared tags (IMAP keywords) of a folder
+export type { FolderKeywords } from './bindings/FolderKeywords';
+
+// Plus-address alias (user+label@domain)
+export type { PlusAlias } from './bindings/PlusAlias';
+
+// Mail received through one plus-address
+export type { AliasStats } from './bindings/AliasStats';
+
+// Attachments view (attachments_list)
+export type { AttachmentSort } from './bindings/AttachmentSort';
+
+export type { AttachmentFilters } from './bindings/AttachmentFilters';
+
+export type { AttachmentItem } from './bindings/AttachmentItem';
+
+export type { AttachmentPage } from './bindings/AttachmentPage';
+
+// Mailbox statistics (mailbox_stats)
+export type { LargeMessage } from './bindings/LargeMessage';
+
+export type { SenderVolume } from './bindings/SenderVolume';
+
+export type { YearCount } from './bindings/YearCount';
+
+export type { AgingBucket } from './bindings/AgingBucket';
+
+export type { MailboxStats } from './bindings/MailboxStats';
+
+// Cleanup suggestions (cleanup_suggestions / cleanup_execute)
+export type { CleanupKind } from './bindings/CleanupKind';
+export type { CleanupAction } from './bindings/CleanupAction';
+
+export type { CleanupSuggestion } from './bindings/CleanupSuggestion';
+
+// Events proposed from a message (email_extract_events)
+export type { ProposedEvent } from './bindings/ProposedEvent';
+
+// Meeting invite created from the compose window
+export type { NewInvite } from './bindings/NewInvite';
+
+export type { AttendeeStatus } from './bindings/AttendeeStatus';
+
+// text/calendar part of a received message (METHOD:REQUEST, REPLY, ...)
+export type { CalendarPart } from './bindings/CalendarPart';
+
+export type { InviteAttendee } from './bindings/InviteAttendee';
+
+// Invite sent from an account with the answers received so far
+export type { Invite } from './bindings/Invite';
+
+// Contact whose address bounced (contact_list_bouncing)
+export type { BouncingContact } from './bindings/BouncingContact';
+
+// Source folder and the target folder its messages are copied to (account_migrate)
+export type { FolderMapping } from './bindings/FolderMapping';
+
+// Progress of one folder pair (account_migrate_status, "migration://progress" event)
+export type { MigrationState } from './bindings/MigrationState';
+
+// Folder re-synced after its UIDVALIDITY changed ("sync://uidvalidity-reset" event)
+export interface ResyncReport {
+  accountId: number;
+  folder: string;
+  previousUidValidity: number;
+  uidValidity: number;
+  quarantined: number;
+  refreshed: number;
+  unmatched: number;
+}
+
 // ============================================================================
 // Session Management & Security
 // ============================================================================
@@ -864,3 +1179,54 @@ export interface AuditLogFilters {
   success?: boolean;
   device_id?: string;
 }
+
+// ============================================================================
+// Command DTOs
+// ============================================================================
+
+// Wire types of the remaining commands, as generated from Rust
+export type { AttachmentData } from './bindings/AttachmentData';
+export type { AttachmentPath } from './bindings/AttachmentPath';
+export type { AttachmentSizeReport } from './bindings/AttachmentSizeReport';
+export type { AutoConfigDebug } from './bindings/AutoConfigDebug';
+export type { BackfillState } from './bindings/BackfillState';
+export type { CacheStats } from './bindings/CacheStats';
+export type { ConflictInfoDto } from './bindings/ConflictInfoDto';
+export type { ConnectivityStatus } from './bindings/ConnectivityStatus';
+export type { DeviceInfoDto } from './bindings/DeviceInfoDto';
+export type { DraftAttachmentData } from './bindings/DraftAttachmentData';
+export type { DraftEmailData } from './bindings/DraftEmailData';
+export type { EmailAttachment } from './bindings/EmailAttachment';
+export type { EmailCursor } from './bindings/EmailCursor';
+export type { EmailSyncResult } from './bindings/EmailSyncResult';
+export type { FetchMethod } from './bindings/FetchMethod';
+export type { FetchResult } from './bindings/FetchResult';
+export type { FilterBatchResult } from './bindings/FilterBatchResult';
+export type { FolderNode } from './bindings/FolderNode';
+export type { HotkeyConflict } from './bindings/HotkeyConflict';
+export type { KeyRotationResult } from './bindings/KeyRotationResult';
+export type { ListSince } from './bindings/ListSince';
+export type { LocalEmail } from './bindings/LocalEmail';
+export type { LocalEmailSummary } from './bindings/LocalEmailSummary';
+export type { MatchLogic } from './bindings/MatchLogic';
+export type { NotificationCandidate } from './bindings/NotificationCandidate';
+export type { OAuthCompleteResult } from './bindings/OAuthCompleteResult';
+export type { OutgoingDraft } from './bindings/OutgoingDraft';
+export type { ParsedEmail } from './bindings/ParsedEmail';
+export type { PendingOpKind } from './bindings/PendingOpKind';
+export type { PendingOperation } from './bindings/PendingOperation';
+export type { ProcessQueueResultDto } from './bindings/ProcessQueueResultDto';
+export type { QueueStatsDto } from './bindings/QueueStatsDto';
+export type { QueuedSend } from './bindings/QueuedSend';
+export type { QuotedInlineHtml } from './bindings/QuotedInlineHtml';
+export type { SchedulerStatusDto } from './bindings/SchedulerStatusDto';
+export type { SearchIndex } from './bindings/SearchIndex';
+export type { SendOptions } from './bindings/SendOptions';
+export type { SenderAuthWarning } from './bindings/SenderAuthWarning';
+export type { SyncConfigDto } from './bindings/SyncConfigDto';
+export type { SyncDataType } from './bindings/SyncDataType';
+export type { SyncResultDto } from './bindings/SyncResultDto';
+export type { SyncStatusDto } from './bindings/SyncStatusDto';
+export type { VirtualFolderEmail } from './bindings/VirtualFolderEmail';
+export type { VirtualFolderKind } from './bindings/VirtualFolderKind';
+export type { VirtualFolderPage } from './bindings/VirtualFolderPage';